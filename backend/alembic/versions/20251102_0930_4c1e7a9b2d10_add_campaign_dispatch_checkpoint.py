@@ -0,0 +1,43 @@
+"""add_campaign_dispatch_checkpoint
+
+Revision ID: 4c1e7a9b2d10
+Revises: 3ad3630fd913
+Create Date: 2025-11-02 09:30:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects.postgresql import JSONB
+from sqlalchemy.sql import text
+
+
+# revision identifiers, used by Alembic.
+revision: str = '4c1e7a9b2d10'
+down_revision: Union[str, None] = '3ad3630fd913'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    """
+    Add dispatch_checkpoint to campaigns
+
+    Stores the contact cursor of every dispatch batch so a crashed or
+    redeployed worker resumes exactly where it stopped.
+    """
+    op.add_column(
+        'campaigns',
+        sa.Column(
+            'dispatch_checkpoint',
+            JSONB,
+            nullable=False,
+            server_default=text("'{}'::jsonb"),
+            comment='Per-batch contact cursors used to resume dispatching'
+        )
+    )
+
+
+def downgrade() -> None:
+    op.drop_column('campaigns', 'dispatch_checkpoint')
@@ -0,0 +1,35 @@
+"""add_message_encryption_to_organizations
+
+Revision ID: 8e2f4b6a1c37
+Revises: 4c1e7a9b2d10
+Create Date: 2025-11-03 10:15:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = '8e2f4b6a1c37'
+down_revision: Union[str, None] = '4c1e7a9b2d10'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Per-tenant message encryption flag and wrapped data key
+    op.add_column(
+        'organizations',
+        sa.Column('message_encryption_enabled', sa.Boolean(), server_default='false', nullable=False)
+    )
+    op.add_column(
+        'organizations',
+        sa.Column('message_data_key', sa.Text(), nullable=True)
+    )
+
+
+def downgrade() -> None:
+    op.drop_column('organizations', 'message_data_key')
+    op.drop_column('organizations', 'message_encryption_enabled')
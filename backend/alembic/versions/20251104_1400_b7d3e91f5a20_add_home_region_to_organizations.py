@@ -0,0 +1,27 @@
+"""add_home_region_to_organizations
+
+Revision ID: b7d3e91f5a20
+Revises: 8e2f4b6a1c37
+Create Date: 2025-11-04 14:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'b7d3e91f5a20'
+down_revision: Union[str, None] = '8e2f4b6a1c37'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Home region used to pin webhook processing and outbound sends
+    op.add_column('organizations', sa.Column('home_region', sa.String(length=50), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('organizations', 'home_region')
@@ -0,0 +1,112 @@
+"""add_dunning_tables
+
+Revision ID: c4a8e2d61f93
+Revises: b7d3e91f5a20
+Create Date: 2025-11-05 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c4a8e2d61f93'
+down_revision: Union[str, None] = 'b7d3e91f5a20'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Invoices synced from the tenant's ERP
+    op.create_table('invoices',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('contact_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('external_id', sa.String(length=255), nullable=False),
+        sa.Column('number', sa.String(length=100), nullable=True),
+        sa.Column('amount', sa.Numeric(12, 2), nullable=False),
+        sa.Column('currency', sa.String(length=3), server_default='BRL', nullable=False),
+        sa.Column('due_date', sa.Date(), nullable=False),
+        sa.Column('status', sa.String(length=20), server_default='open', nullable=False),
+        sa.Column('paid_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('pix_code', sa.Text(), nullable=True),
+        sa.Column('payment_url', sa.Text(), nullable=True),
+        sa.Column('extra_data', postgresql.JSONB(astext_type=sa.Text()), server_default='{}', nullable=False),
+        sa.Column('last_synced_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['contact_id'], ['contacts.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id'),
+        sa.UniqueConstraint('organization_id', 'external_id', name='uq_invoices_org_external_id')
+    )
+    op.create_index('ix_invoices_organization_id', 'invoices', ['organization_id'])
+    op.create_index('ix_invoices_contact_id', 'invoices', ['contact_id'])
+    op.create_index('ix_invoices_due_date', 'invoices', ['due_date'])
+    op.create_index('ix_invoices_status', 'invoices', ['status'])
+
+    # Reminder rules relative to the due date
+    op.create_table('dunning_rules',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('whatsapp_number_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('template_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('name', sa.String(length=255), nullable=False),
+        sa.Column('description', sa.Text(), nullable=True),
+        sa.Column('offset_days', sa.Integer(), nullable=False),
+        sa.Column('send_time', sa.Time(), nullable=False),
+        sa.Column('timezone', sa.String(length=50), server_default='America/Sao_Paulo', nullable=False),
+        sa.Column('template_variables', postgresql.JSONB(astext_type=sa.Text()), server_default='[]', nullable=False),
+        sa.Column('offer_pix', sa.Boolean(), server_default='false', nullable=False),
+        sa.Column('min_amount', sa.Numeric(12, 2), nullable=True),
+        sa.Column('is_active', sa.Boolean(), server_default='true', nullable=False),
+        sa.Column('total_scheduled', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('total_sent', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('total_failed', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('total_skipped', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('total_paid', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('recovered_amount', sa.Numeric(14, 2), server_default='0', nullable=False),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('deleted_at', sa.DateTime(timezone=True), nullable=True),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['whatsapp_number_id'], ['whatsapp_numbers.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['template_id'], ['whatsapp_templates.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_dunning_rules_organization_id', 'dunning_rules', ['organization_id'])
+    op.create_index('ix_dunning_rules_deleted_at', 'dunning_rules', ['deleted_at'])
+
+    # Planned reminders (one per rule and invoice)
+    op.create_table('dunning_reminders',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('rule_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('invoice_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('status', sa.String(length=20), server_default='scheduled', nullable=False),
+        sa.Column('scheduled_for', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('sent_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('whatsapp_message_id', sa.String(length=255), nullable=True),
+        sa.Column('skip_reason', sa.String(length=100), nullable=True),
+        sa.Column('error_message', sa.Text(), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['rule_id'], ['dunning_rules.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['invoice_id'], ['invoices.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id'),
+        sa.UniqueConstraint('rule_id', 'invoice_id', name='uq_dunning_reminders_rule_invoice')
+    )
+    op.create_index('ix_dunning_reminders_organization_id', 'dunning_reminders', ['organization_id'])
+    op.create_index('ix_dunning_reminders_rule_id', 'dunning_reminders', ['rule_id'])
+    op.create_index('ix_dunning_reminders_invoice_id', 'dunning_reminders', ['invoice_id'])
+    op.create_index('ix_dunning_reminders_status', 'dunning_reminders', ['status'])
+
+
+def downgrade() -> None:
+    op.drop_table('dunning_reminders')
+    op.drop_table('dunning_rules')
+    op.drop_table('invoices')
@@ -0,0 +1,46 @@
+"""add_user_workspaces
+
+Revision ID: d9f1b3c7a248
+Revises: c4a8e2d61f93
+Create Date: 2025-11-06 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd9f1b3c7a248'
+down_revision: Union[str, None] = 'c4a8e2d61f93'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Extra workspaces of a user (the home organization stays in users.organization_id)
+    op.create_table('user_workspaces',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('user_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('invited_by_user_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('role', sa.String(length=50), server_default='agent', nullable=False),
+        sa.Column('department_ids', postgresql.ARRAY(postgresql.UUID(as_uuid=True)), server_default=sa.text('ARRAY[]::uuid[]'), nullable=False),
+        sa.Column('is_active', sa.Boolean(), server_default='true', nullable=False),
+        sa.Column('last_accessed_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['user_id'], ['users.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['invited_by_user_id'], ['users.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id'),
+        sa.UniqueConstraint('user_id', 'organization_id', name='uq_user_workspaces_user_org')
+    )
+    op.create_index('ix_user_workspaces_user_id', 'user_workspaces', ['user_id'])
+    op.create_index('ix_user_workspaces_organization_id', 'user_workspaces', ['organization_id'])
+
+
+def downgrade() -> None:
+    op.drop_table('user_workspaces')
@@ -0,0 +1,49 @@
+"""add_dashboard_annotations
+
+Revision ID: e7a2c5d9b1f4
+Revises: d9f1b3c7a248
+Create Date: 2025-11-07 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e7a2c5d9b1f4'
+down_revision: Union[str, None] = 'd9f1b3c7a248'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Events overlaid on dashboard charts (organization_id NULL = platform-wide)
+    op.create_table('dashboard_annotations',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('created_by_user_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('kind', sa.String(length=30), nullable=False),
+        sa.Column('source', sa.String(length=20), server_default='manual', nullable=False),
+        sa.Column('title', sa.String(length=255), nullable=False),
+        sa.Column('description', sa.Text(), nullable=True),
+        sa.Column('occurred_at', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('ends_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('resource_type', sa.String(length=50), nullable=True),
+        sa.Column('resource_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('extra_data', postgresql.JSONB(astext_type=sa.Text()), server_default='{}', nullable=False),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['created_by_user_id'], ['users.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_dashboard_annotations_organization_id', 'dashboard_annotations', ['organization_id'])
+    op.create_index('ix_dashboard_annotations_kind', 'dashboard_annotations', ['kind'])
+    op.create_index('ix_dashboard_annotations_org_occurred_at', 'dashboard_annotations', ['organization_id', 'occurred_at'])
+
+
+def downgrade() -> None:
+    op.drop_table('dashboard_annotations')
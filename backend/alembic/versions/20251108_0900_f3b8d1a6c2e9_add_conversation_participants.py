@@ -0,0 +1,60 @@
+"""add_conversation_participants
+
+Revision ID: f3b8d1a6c2e9
+Revises: e7a2c5d9b1f4
+Create Date: 2025-11-08 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'f3b8d1a6c2e9'
+down_revision: Union[str, None] = 'e7a2c5d9b1f4'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Agents collaborating on a conversation (primary + collaborators)
+    op.create_table('conversation_participants',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('conversation_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('user_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('added_by_user_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('role', sa.String(length=20), server_default='collaborator', nullable=False),
+        sa.Column('notification_level', sa.String(length=20), server_default='all', nullable=False),
+        sa.Column('joined_at', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('left_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('messages_sent', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('last_message_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['conversation_id'], ['conversations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['user_id'], ['users.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['added_by_user_id'], ['users.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id'),
+        sa.UniqueConstraint('conversation_id', 'user_id', name='uq_conversation_participant')
+    )
+    op.create_index('ix_conversation_participants_organization_id', 'conversation_participants', ['organization_id'])
+    op.create_index('ix_conversation_participants_conversation_id', 'conversation_participants', ['conversation_id'])
+    op.create_index('ix_conversation_participants_user_id', 'conversation_participants', ['user_id'])
+
+    # Existing assignments become primary participants
+    op.execute("""
+        INSERT INTO conversation_participants
+            (organization_id, conversation_id, user_id, role, joined_at)
+        SELECT organization_id, id, current_agent_id, 'primary', COALESCE(assigned_at, updated_at)
+        FROM conversations
+        WHERE current_agent_id IS NOT NULL AND deleted_at IS NULL
+    """)
+
+
+def downgrade() -> None:
+    op.drop_table('conversation_participants')
@@ -0,0 +1,51 @@
+"""add_webhook_events
+
+Revision ID: a4c9e2f7b135
+Revises: f3b8d1a6c2e9
+Create Date: 2025-11-08 14:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'a4c9e2f7b135'
+down_revision: Union[str, None] = 'f3b8d1a6c2e9'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Raw webhook payloads kept for replay after failures
+    op.create_table('webhook_events',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('whatsapp_number_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('source', sa.String(length=20), server_default='whatsapp', nullable=False),
+        sa.Column('phone_number_id', sa.String(length=50), nullable=True),
+        sa.Column('payload', postgresql.JSONB(astext_type=sa.Text()), nullable=False),
+        sa.Column('status', sa.String(length=20), server_default='received', nullable=False),
+        sa.Column('error', sa.Text(), nullable=True),
+        sa.Column('attempts', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('received_at', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('last_attempt_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('processed_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['whatsapp_number_id'], ['whatsapp_numbers.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_webhook_events_organization_id', 'webhook_events', ['organization_id'])
+    op.create_index('ix_webhook_events_phone_number_id', 'webhook_events', ['phone_number_id'])
+    op.create_index('ix_webhook_events_status', 'webhook_events', ['status'])
+    op.create_index('ix_webhook_events_received_at', 'webhook_events', ['received_at'])
+    op.create_index('ix_webhook_events_org_status_received', 'webhook_events', ['organization_id', 'status', 'received_at'])
+
+
+def downgrade() -> None:
+    op.drop_table('webhook_events')
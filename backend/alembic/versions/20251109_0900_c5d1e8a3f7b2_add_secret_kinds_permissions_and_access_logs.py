@@ -0,0 +1,61 @@
+"""add_secret_kinds_permissions_and_access_logs
+
+Revision ID: c5d1e8a3f7b2
+Revises: a4c9e2f7b135
+Create Date: 2025-11-09 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c5d1e8a3f7b2'
+down_revision: Union[str, None] = 'a4c9e2f7b135'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    secret_kind_enum = postgresql.ENUM('secret', 'variable', name='secretkind', create_type=False)
+    secret_kind_enum.create(op.get_bind(), checkfirst=True)
+
+    # Plain variables next to secrets, plus per-key permissions
+    op.add_column('secrets', sa.Column('kind', secret_kind_enum, server_default='secret', nullable=False))
+    op.add_column('secrets', sa.Column('allowed_roles', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+    op.add_column('secrets', sa.Column('allowed_usages', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+
+    # Access audit
+    op.create_table('secret_access_logs',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('secret_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('secret_name', sa.String(length=255), nullable=False),
+        sa.Column('action', sa.String(length=20), nullable=False),
+        sa.Column('usage', sa.String(length=20), nullable=False),
+        sa.Column('user_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('resource_type', sa.String(length=50), nullable=True),
+        sa.Column('resource_id', sa.String(length=255), nullable=True),
+        sa.Column('allowed', sa.Boolean(), server_default=sa.text('true'), nullable=False),
+        sa.Column('reason', sa.Text(), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['secret_id'], ['secrets.id'], ondelete='SET NULL'),
+        sa.ForeignKeyConstraint(['user_id'], ['users.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_secret_access_logs_organization_id', 'secret_access_logs', ['organization_id'])
+    op.create_index('ix_secret_access_logs_secret_id', 'secret_access_logs', ['secret_id'])
+    op.create_index('ix_secret_access_logs_action', 'secret_access_logs', ['action'])
+
+
+def downgrade() -> None:
+    op.drop_table('secret_access_logs')
+    op.drop_column('secrets', 'allowed_usages')
+    op.drop_column('secrets', 'allowed_roles')
+    op.drop_column('secrets', 'kind')
+    postgresql.ENUM(name='secretkind').drop(op.get_bind(), checkfirst=True)
@@ -0,0 +1,29 @@
+"""add_contact_language
+
+Revision ID: d8e2f4a6b1c3
+Revises: c5d1e8a3f7b2
+Create Date: 2025-11-10 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd8e2f4a6b1c3'
+down_revision: Union[str, None] = 'c5d1e8a3f7b2'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Contact language (detected from early messages or set manually)
+    op.add_column('contacts', sa.Column('language', sa.String(length=10), nullable=True))
+    op.create_index(op.f('ix_contacts_language'), 'contacts', ['language'], unique=False)
+
+
+def downgrade() -> None:
+    op.drop_index(op.f('ix_contacts_language'), table_name='contacts')
+    op.drop_column('contacts', 'language')
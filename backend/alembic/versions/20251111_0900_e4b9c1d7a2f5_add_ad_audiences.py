@@ -0,0 +1,70 @@
+"""add_ad_audiences
+
+Revision ID: e4b9c1d7a2f5
+Revises: d8e2f4a6b1c3
+Create Date: 2025-11-11 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e4b9c1d7a2f5'
+down_revision: Union[str, None] = 'd8e2f4a6b1c3'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Contact segments mirrored to Meta Custom Audiences
+    op.create_table('ad_audiences',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('name', sa.String(length=255), nullable=False),
+        sa.Column('description', sa.Text(), nullable=True),
+        sa.Column('ad_account_id', sa.String(length=100), nullable=True),
+        sa.Column('meta_audience_id', sa.String(length=100), nullable=True),
+        sa.Column('audience_type', sa.String(length=50), server_default='tags', nullable=False),
+        sa.Column('target_tag_ids', postgresql.ARRAY(postgresql.UUID(as_uuid=True)), server_default=sa.text('ARRAY[]::uuid[]'), nullable=False),
+        sa.Column('target_contact_ids', postgresql.ARRAY(postgresql.UUID(as_uuid=True)), server_default=sa.text('ARRAY[]::uuid[]'), nullable=False),
+        sa.Column('segment_filters', postgresql.JSONB(astext_type=sa.Text()), server_default='{}', nullable=False),
+        sa.Column('is_active', sa.Boolean(), server_default='true', nullable=False),
+        sa.Column('last_synced_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('last_sync_status', sa.String(length=20), nullable=True),
+        sa.Column('last_error', sa.Text(), nullable=True),
+        sa.Column('member_count', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('deleted_at', sa.DateTime(timezone=True), nullable=True),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_ad_audiences_organization_id', 'ad_audiences', ['organization_id'])
+    op.create_index('ix_ad_audiences_meta_audience_id', 'ad_audiences', ['meta_audience_id'])
+    op.create_index('ix_ad_audiences_deleted_at', 'ad_audiences', ['deleted_at'])
+
+    # Contacts pushed to each audience (hashes kept for removal)
+    op.create_table('ad_audience_members',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('audience_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('contact_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('phone_hash', sa.String(length=64), nullable=True),
+        sa.Column('email_hash', sa.String(length=64), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['audience_id'], ['ad_audiences.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['contact_id'], ['contacts.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id'),
+        sa.UniqueConstraint('audience_id', 'contact_id', name='uq_ad_audience_members_audience_contact')
+    )
+    op.create_index('ix_ad_audience_members_audience_id', 'ad_audience_members', ['audience_id'])
+    op.create_index('ix_ad_audience_members_contact_id', 'ad_audience_members', ['contact_id'])
+
+
+def downgrade() -> None:
+    op.drop_table('ad_audience_members')
+    op.drop_table('ad_audiences')
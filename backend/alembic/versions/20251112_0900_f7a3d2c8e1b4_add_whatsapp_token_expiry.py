@@ -0,0 +1,33 @@
+"""add_whatsapp_token_expiry
+
+Revision ID: f7a3d2c8e1b4
+Revises: e4b9c1d7a2f5
+Create Date: 2025-11-12 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'f7a3d2c8e1b4'
+down_revision: Union[str, None] = 'e4b9c1d7a2f5'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Access token validity, refreshed by debug_token checks
+    op.add_column('whatsapp_numbers', sa.Column('token_status', sa.String(length=20), nullable=True))
+    op.add_column('whatsapp_numbers', sa.Column('token_expires_at', sa.DateTime(timezone=True), nullable=True))
+    op.add_column('whatsapp_numbers', sa.Column('token_checked_at', sa.DateTime(timezone=True), nullable=True))
+    op.add_column('whatsapp_numbers', sa.Column('token_expiry_notified_at', sa.DateTime(timezone=True), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('whatsapp_numbers', 'token_expiry_notified_at')
+    op.drop_column('whatsapp_numbers', 'token_checked_at')
+    op.drop_column('whatsapp_numbers', 'token_expires_at')
+    op.drop_column('whatsapp_numbers', 'token_status')
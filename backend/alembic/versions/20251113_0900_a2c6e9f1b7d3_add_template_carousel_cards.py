@@ -0,0 +1,28 @@
+"""add_template_carousel_cards
+
+Revision ID: a2c6e9f1b7d3
+Revises: f7a3d2c8e1b4
+Create Date: 2025-11-13 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'a2c6e9f1b7d3'
+down_revision: Union[str, None] = 'f7a3d2c8e1b4'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Card layout of carousel templates (header format, body variables, buttons per card)
+    op.add_column('whatsapp_templates', sa.Column('carousel_cards', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('whatsapp_templates', 'carousel_cards')
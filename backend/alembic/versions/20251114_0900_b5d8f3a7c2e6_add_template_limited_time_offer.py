@@ -0,0 +1,28 @@
+"""add_template_limited_time_offer
+
+Revision ID: b5d8f3a7c2e6
+Revises: a2c6e9f1b7d3
+Create Date: 2025-11-14 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'b5d8f3a7c2e6'
+down_revision: Union[str, None] = 'a2c6e9f1b7d3'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # LIMITED_TIME_OFFER component of promotional templates ({"text", "has_expiration"})
+    op.add_column('whatsapp_templates', sa.Column('limited_time_offer', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('whatsapp_templates', 'limited_time_offer')
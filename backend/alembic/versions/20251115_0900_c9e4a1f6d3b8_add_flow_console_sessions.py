@@ -0,0 +1,51 @@
+"""add_flow_console_sessions
+
+Revision ID: c9e4a1f6d3b8
+Revises: b5d8f3a7c2e6
+Create Date: 2025-11-15 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c9e4a1f6d3b8'
+down_revision: Union[str, None] = 'b5d8f3a7c2e6'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Flow console sessions (Redis is only a cache of these)
+    op.create_table('flow_console_sessions',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('session_id', sa.String(length=32), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('user_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('state', postgresql.JSONB(astext_type=sa.Text()), server_default='{}', nullable=False),
+        sa.Column('finished', sa.Boolean(), server_default='false', nullable=False),
+        sa.Column('expires_at', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['user_id'], ['users.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_flow_console_sessions_session_id', 'flow_console_sessions', ['session_id'], unique=True)
+    op.create_index('ix_flow_console_sessions_organization_id', 'flow_console_sessions', ['organization_id'])
+    op.create_index('ix_flow_console_sessions_flow_id', 'flow_console_sessions', ['flow_id'])
+    op.create_index('ix_flow_console_sessions_expires_at', 'flow_console_sessions', ['expires_at'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_flow_console_sessions_expires_at', table_name='flow_console_sessions')
+    op.drop_index('ix_flow_console_sessions_flow_id', table_name='flow_console_sessions')
+    op.drop_index('ix_flow_console_sessions_organization_id', table_name='flow_console_sessions')
+    op.drop_index('ix_flow_console_sessions_session_id', table_name='flow_console_sessions')
+    op.drop_table('flow_console_sessions')
@@ -0,0 +1,52 @@
+"""add_flow_waits
+
+Revision ID: d4f8b2e7a9c1
+Revises: c9e4a1f6d3b8
+Create Date: 2025-11-16 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd4f8b2e7a9c1'
+down_revision: Union[str, None] = 'c9e4a1f6d3b8'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Flows paused by wait nodes, resumed by the resume_flow_waits task
+    op.create_table('flow_waits',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('conversation_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('node_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('resume_at', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('status', sa.String(length=20), server_default='pending', nullable=False),
+        sa.Column('resumed_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['conversation_id'], ['conversations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['node_id'], ['nodes.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_flow_waits_organization_id', 'flow_waits', ['organization_id'])
+    op.create_index('ix_flow_waits_conversation_id', 'flow_waits', ['conversation_id'])
+    op.create_index('ix_flow_waits_resume_at', 'flow_waits', ['resume_at'])
+    op.create_index('ix_flow_waits_status', 'flow_waits', ['status'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_flow_waits_status', table_name='flow_waits')
+    op.drop_index('ix_flow_waits_resume_at', table_name='flow_waits')
+    op.drop_index('ix_flow_waits_conversation_id', table_name='flow_waits')
+    op.drop_index('ix_flow_waits_organization_id', table_name='flow_waits')
+    op.drop_table('flow_waits')
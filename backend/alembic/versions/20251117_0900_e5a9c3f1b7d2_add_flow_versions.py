@@ -0,0 +1,73 @@
+"""add_flow_versions
+
+Revision ID: e5a9c3f1b7d2
+Revises: d4f8b2e7a9c1
+Create Date: 2025-11-17 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e5a9c3f1b7d2'
+down_revision: Union[str, None] = 'd4f8b2e7a9c1'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Immutable snapshots published from a flow's draft
+    op.create_table('flow_versions',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('published_by_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('version', sa.Integer(), nullable=False),
+        sa.Column('canvas_data', postgresql.JSONB(astext_type=sa.Text()), nullable=False),
+        sa.Column('variables', postgresql.JSONB(astext_type=sa.Text()), server_default=sa.text("'{}'::jsonb"), nullable=False),
+        sa.Column('notes', sa.Text(), nullable=True),
+        sa.Column('status', sa.String(length=20), server_default='published', nullable=False),
+        sa.Column('published_at', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('sessions_started', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('sessions_completed', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.func.now(), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['published_by_id'], ['users.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id'),
+        sa.UniqueConstraint('flow_id', 'version', name='uq_flow_versions_flow_version')
+    )
+    op.create_index('ix_flow_versions_organization_id', 'flow_versions', ['organization_id'])
+    op.create_index('ix_flow_versions_flow_id', 'flow_versions', ['flow_id'])
+
+    # Live version of each flow (NULL = never published, the draft keeps running)
+    op.add_column('flows', sa.Column('published_version_id', postgresql.UUID(as_uuid=True), nullable=True))
+    op.create_foreign_key(
+        'fk_flows_published_version_id', 'flows', 'flow_versions',
+        ['published_version_id'], ['id'], ondelete='SET NULL'
+    )
+
+    # Node copies of published versions (NULL = draft node)
+    op.add_column('nodes', sa.Column('flow_version_id', postgresql.UUID(as_uuid=True), nullable=True))
+    op.create_foreign_key(
+        'fk_nodes_flow_version_id', 'nodes', 'flow_versions',
+        ['flow_version_id'], ['id'], ondelete='CASCADE'
+    )
+    op.create_index('ix_nodes_flow_version_id', 'nodes', ['flow_version_id'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_nodes_flow_version_id', table_name='nodes')
+    op.execute("DELETE FROM nodes WHERE flow_version_id IS NOT NULL")
+    op.drop_constraint('fk_nodes_flow_version_id', 'nodes', type_='foreignkey')
+    op.drop_column('nodes', 'flow_version_id')
+    op.drop_constraint('fk_flows_published_version_id', 'flows', type_='foreignkey')
+    op.drop_column('flows', 'published_version_id')
+    op.drop_index('ix_flow_versions_flow_id', table_name='flow_versions')
+    op.drop_index('ix_flow_versions_organization_id', table_name='flow_versions')
+    op.drop_table('flow_versions')
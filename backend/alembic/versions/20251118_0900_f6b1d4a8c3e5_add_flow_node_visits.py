@@ -0,0 +1,53 @@
+"""add_flow_node_visits
+
+Revision ID: f6b1d4a8c3e5
+Revises: e5a9c3f1b7d2
+Create Date: 2025-11-18 09:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'f6b1d4a8c3e5'
+down_revision: Union[str, None] = 'e5a9c3f1b7d2'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Node entry/exit of each flow session, for the drop-off funnel
+    op.create_table('flow_node_visits',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_version_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('conversation_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('session_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('node_id', sa.String(length=255), nullable=False),
+        sa.Column('node_type', sa.String(length=50), nullable=False),
+        sa.Column('entered_at', sa.DateTime(timezone=True), nullable=False),
+        sa.Column('exited_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('duration_ms', sa.Integer(), nullable=True),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_version_id'], ['flow_versions.id'], ondelete='SET NULL'),
+        sa.ForeignKeyConstraint(['conversation_id'], ['conversations.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_flow_node_visits_organization_id', 'flow_node_visits', ['organization_id'])
+    op.create_index('ix_flow_node_visits_flow_id', 'flow_node_visits', ['flow_id'])
+    op.create_index('ix_flow_node_visits_session_id', 'flow_node_visits', ['session_id'])
+    op.create_index('ix_flow_node_visits_entered_at', 'flow_node_visits', ['entered_at'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_flow_node_visits_entered_at', table_name='flow_node_visits')
+    op.drop_index('ix_flow_node_visits_session_id', table_name='flow_node_visits')
+    op.drop_index('ix_flow_node_visits_flow_id', table_name='flow_node_visits')
+    op.drop_index('ix_flow_node_visits_organization_id', table_name='flow_node_visits')
+    op.drop_table('flow_node_visits')
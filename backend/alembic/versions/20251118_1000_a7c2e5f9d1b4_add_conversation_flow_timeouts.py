@@ -0,0 +1,33 @@
+"""add_conversation_flow_timeouts
+
+Revision ID: a7c2e5f9d1b4
+Revises: f6b1d4a8c3e5
+Create Date: 2025-11-18 10:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'a7c2e5f9d1b4'
+down_revision: Union[str, None] = 'f6b1d4a8c3e5'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Flow session deadlines, enforced by the expire_flow_sessions task
+    op.add_column('conversations', sa.Column('flow_timeout_at', sa.DateTime(timezone=True), nullable=True))
+    op.add_column('conversations', sa.Column('flow_expires_at', sa.DateTime(timezone=True), nullable=True))
+    op.create_index('ix_conversations_flow_timeout_at', 'conversations', ['flow_timeout_at'])
+    op.create_index('ix_conversations_flow_expires_at', 'conversations', ['flow_expires_at'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_conversations_flow_expires_at', table_name='conversations')
+    op.drop_index('ix_conversations_flow_timeout_at', table_name='conversations')
+    op.drop_column('conversations', 'flow_expires_at')
+    op.drop_column('conversations', 'flow_timeout_at')
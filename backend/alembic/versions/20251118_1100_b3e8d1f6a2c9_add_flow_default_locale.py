@@ -0,0 +1,27 @@
+"""add_flow_default_locale
+
+Revision ID: b3e8d1f6a2c9
+Revises: a7c2e5f9d1b4
+Create Date: 2025-11-18 11:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'b3e8d1f6a2c9'
+down_revision: Union[str, None] = 'a7c2e5f9d1b4'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Language of the nodes' own content, last step of the translations fallback chain
+    op.add_column('flows', sa.Column('default_locale', sa.String(length=10), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('flows', 'default_locale')
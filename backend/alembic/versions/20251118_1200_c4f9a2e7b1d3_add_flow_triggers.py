@@ -0,0 +1,48 @@
+"""add_flow_triggers
+
+Revision ID: c4f9a2e7b1d3
+Revises: b3e8d1f6a2c9
+Create Date: 2025-11-18 12:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c4f9a2e7b1d3'
+down_revision: Union[str, None] = 'b3e8d1f6a2c9'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Keywords, exact phrases and regexes that start flows from inbound messages
+    op.create_table('flow_triggers',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('match_type', sa.String(length=20), server_default='keyword', nullable=False),
+        sa.Column('pattern', sa.String(length=500), nullable=False),
+        sa.Column('priority', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('interrupts_flow', sa.Boolean(), server_default='false', nullable=False),
+        sa.Column('is_active', sa.Boolean(), server_default='true', nullable=False),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_flow_triggers_organization_id', 'flow_triggers', ['organization_id'])
+    op.create_index('ix_flow_triggers_flow_id', 'flow_triggers', ['flow_id'])
+    op.create_index('ix_flow_triggers_created_at', 'flow_triggers', ['created_at'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_flow_triggers_created_at', table_name='flow_triggers')
+    op.drop_index('ix_flow_triggers_flow_id', table_name='flow_triggers')
+    op.drop_index('ix_flow_triggers_organization_id', table_name='flow_triggers')
+    op.drop_table('flow_triggers')
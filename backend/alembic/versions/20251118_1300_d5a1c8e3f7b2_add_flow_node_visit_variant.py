@@ -0,0 +1,27 @@
+"""add_flow_node_visit_variant
+
+Revision ID: d5a1c8e3f7b2
+Revises: c4f9a2e7b1d3
+Create Date: 2025-11-18 13:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd5a1c8e3f7b2'
+down_revision: Union[str, None] = 'c4f9a2e7b1d3'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Variant drawn by split (A/B test) nodes, compared in the flow funnel
+    op.add_column('flow_node_visits', sa.Column('variant', sa.String(length=100), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('flow_node_visits', 'variant')
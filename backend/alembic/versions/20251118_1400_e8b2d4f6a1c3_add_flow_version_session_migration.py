@@ -0,0 +1,28 @@
+"""add_flow_version_session_migration
+
+Revision ID: e8b2d4f6a1c3
+Revises: d5a1c8e3f7b2
+Create Date: 2025-11-18 14:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e8b2d4f6a1c3'
+down_revision: Union[str, None] = 'd5a1c8e3f7b2'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Running sessions moved to the version when it was published
+    op.add_column('flow_versions', sa.Column('session_migration', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('flow_versions', 'session_migration')
@@ -0,0 +1,61 @@
+"""add_flow_event_triggers
+
+Revision ID: f3c7a9e2b5d8
+Revises: e8b2d4f6a1c3
+Create Date: 2025-11-18 15:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'f3c7a9e2b5d8'
+down_revision: Union[str, None] = 'e8b2d4f6a1c3'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Internal and external events that start flows without an inbound message
+    op.create_table('flow_event_triggers',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('whatsapp_number_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('event_type', sa.String(length=100), nullable=False),
+        sa.Column('conditions', postgresql.JSONB(astext_type=sa.Text()), server_default=sa.text("'{}'::jsonb"), nullable=False),
+        sa.Column('variable_mapping', postgresql.JSONB(astext_type=sa.Text()), server_default=sa.text("'{}'::jsonb"), nullable=False),
+        sa.Column('template_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('template_variables', postgresql.JSONB(astext_type=sa.Text()), server_default=sa.text("'[]'::jsonb"), nullable=False),
+        sa.Column('interrupts_flow', sa.Boolean(), server_default='false', nullable=False),
+        sa.Column('is_active', sa.Boolean(), server_default='true', nullable=False),
+        sa.Column('total_started', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('total_skipped', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('total_failed', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('last_fired_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['whatsapp_number_id'], ['whatsapp_numbers.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['template_id'], ['whatsapp_templates.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id')
+    )
+    op.create_index('ix_flow_event_triggers_organization_id', 'flow_event_triggers', ['organization_id'])
+    op.create_index('ix_flow_event_triggers_flow_id', 'flow_event_triggers', ['flow_id'])
+    op.create_index('ix_flow_event_triggers_event_type', 'flow_event_triggers', ['event_type'])
+
+    # Overdue invoices are announced to event triggers once
+    op.add_column('invoices', sa.Column('overdue_event_at', sa.DateTime(timezone=True), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('invoices', 'overdue_event_at')
+    op.drop_index('ix_flow_event_triggers_event_type', table_name='flow_event_triggers')
+    op.drop_index('ix_flow_event_triggers_flow_id', table_name='flow_event_triggers')
+    op.drop_index('ix_flow_event_triggers_organization_id', table_name='flow_event_triggers')
+    op.drop_table('flow_event_triggers')
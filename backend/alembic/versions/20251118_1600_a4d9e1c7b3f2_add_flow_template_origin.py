@@ -0,0 +1,28 @@
+"""add_flow_template_origin
+
+Revision ID: a4d9e1c7b3f2
+Revises: f3c7a9e2b5d8
+Create Date: 2025-11-18 16:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'a4d9e1c7b3f2'
+down_revision: Union[str, None] = 'f3c7a9e2b5d8'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Gallery template (version and parameters) flows were instantiated from
+    op.add_column('flows', sa.Column('template_origin', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('flows', 'template_origin')
@@ -0,0 +1,58 @@
+"""add_survey_responses
+
+Revision ID: c8e2f5a1d9b4
+Revises: a4d9e1c7b3f2
+Create Date: 2025-11-18 17:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c8e2f5a1d9b4'
+down_revision: Union[str, None] = 'a4d9e1c7b3f2'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Answers to survey nodes (NPS / CSAT)
+    op.create_table(
+        'survey_responses',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('conversation_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('contact_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('agent_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('node_id', sa.String(length=255), nullable=False),
+        sa.Column('survey_type', sa.String(length=10), nullable=False),
+        sa.Column('score', sa.Integer(), nullable=False),
+        sa.Column('comment', sa.Text(), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='SET NULL'),
+        sa.ForeignKeyConstraint(['conversation_id'], ['conversations.id'], ondelete='SET NULL'),
+        sa.ForeignKeyConstraint(['contact_id'], ['contacts.id'], ondelete='SET NULL'),
+        sa.ForeignKeyConstraint(['agent_id'], ['users.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id'),
+    )
+    op.create_index('ix_survey_responses_organization_id', 'survey_responses', ['organization_id'])
+    op.create_index('ix_survey_responses_flow_id', 'survey_responses', ['flow_id'])
+    op.create_index('ix_survey_responses_agent_id', 'survey_responses', ['agent_id'])
+    op.create_index('ix_survey_responses_created_at', 'survey_responses', ['created_at'])
+    op.create_index('ix_survey_responses_org_created_at', 'survey_responses', ['organization_id', 'created_at'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_survey_responses_org_created_at', table_name='survey_responses')
+    op.drop_index('ix_survey_responses_created_at', table_name='survey_responses')
+    op.drop_index('ix_survey_responses_agent_id', table_name='survey_responses')
+    op.drop_index('ix_survey_responses_flow_id', table_name='survey_responses')
+    op.drop_index('ix_survey_responses_organization_id', table_name='survey_responses')
+    op.drop_table('survey_responses')
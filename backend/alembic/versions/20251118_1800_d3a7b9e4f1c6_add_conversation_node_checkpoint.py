@@ -0,0 +1,32 @@
+"""add_conversation_node_checkpoint
+
+Revision ID: d3a7b9e4f1c6
+Revises: c8e2f5a1d9b4
+Create Date: 2025-11-18 18:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd3a7b9e4f1c6'
+down_revision: Union[str, None] = 'c8e2f5a1d9b4'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Node execution checkpoints, resumed by the resume_interrupted_flows task
+    op.add_column('conversations', sa.Column('node_checkpoint', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+    op.add_column('conversations', sa.Column('node_checkpoint_at', sa.DateTime(timezone=True), nullable=True))
+    op.create_index('ix_conversations_node_checkpoint_at', 'conversations', ['node_checkpoint_at'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_conversations_node_checkpoint_at', table_name='conversations')
+    op.drop_column('conversations', 'node_checkpoint_at')
+    op.drop_column('conversations', 'node_checkpoint')
@@ -0,0 +1,27 @@
+"""add_conversation_session_fence
+
+Revision ID: e5b1c8d2a7f3
+Revises: d3a7b9e4f1c6
+Create Date: 2025-11-18 19:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e5b1c8d2a7f3'
+down_revision: Union[str, None] = 'd3a7b9e4f1c6'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Fencing token of the flow session lock holder
+    op.add_column('conversations', sa.Column('flow_session_fence', sa.BigInteger(), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('conversations', 'flow_session_fence')
@@ -0,0 +1,66 @@
+"""add_flow_trace_events
+
+Revision ID: f2c6a9d4b8e1
+Revises: e5b1c8d2a7f3
+Create Date: 2025-11-18 20:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'f2c6a9d4b8e1'
+down_revision: Union[str, None] = 'e5b1c8d2a7f3'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Execution trace of flow sessions (append-only)
+    op.create_table(
+        'flow_trace_events',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('flow_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('conversation_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('session_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('event_type', sa.String(length=30), nullable=False),
+        sa.Column('node_id', sa.String(length=255), nullable=True),
+        sa.Column('node_type', sa.String(length=50), nullable=True),
+        sa.Column('data', postgresql.JSONB(astext_type=sa.Text()), server_default=sa.text("'{}'::jsonb"), nullable=False),
+        sa.Column('occurred_at', sa.DateTime(timezone=True), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['flow_id'], ['flows.id'], ondelete='SET NULL'),
+        sa.ForeignKeyConstraint(['conversation_id'], ['conversations.id'], ondelete='CASCADE'),
+        sa.PrimaryKeyConstraint('id'),
+    )
+    op.create_index('ix_flow_trace_events_organization_id', 'flow_trace_events', ['organization_id'])
+    op.create_index(
+        'ix_flow_trace_events_session_occurred_at', 'flow_trace_events', ['session_id', 'occurred_at']
+    )
+
+    # Entries are never edited (deletes still cascade from organizations/conversations)
+    op.execute("""
+        CREATE FUNCTION flow_trace_events_append_only() RETURNS trigger AS $$
+        BEGIN
+            RAISE EXCEPTION 'flow_trace_events is append-only';
+        END;
+        $$ LANGUAGE plpgsql;
+    """)
+    op.execute("""
+        CREATE TRIGGER flow_trace_events_no_update
+        BEFORE UPDATE ON flow_trace_events
+        FOR EACH ROW EXECUTE FUNCTION flow_trace_events_append_only();
+    """)
+
+
+def downgrade() -> None:
+    op.execute("DROP TRIGGER IF EXISTS flow_trace_events_no_update ON flow_trace_events")
+    op.execute("DROP FUNCTION IF EXISTS flow_trace_events_append_only()")
+    op.drop_index('ix_flow_trace_events_session_occurred_at', table_name='flow_trace_events')
+    op.drop_index('ix_flow_trace_events_organization_id', table_name='flow_trace_events')
+    op.drop_table('flow_trace_events')
@@ -0,0 +1,28 @@
+"""add_flow_version_complexity
+
+Revision ID: a7d3e8f1c2b5
+Revises: f2c6a9d4b8e1
+Create Date: 2025-11-18 21:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'a7d3e8f1c2b5'
+down_revision: Union[str, None] = 'f2c6a9d4b8e1'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Graph metrics computed when a version is published
+    op.add_column('flow_versions', sa.Column('complexity', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('flow_versions', 'complexity')
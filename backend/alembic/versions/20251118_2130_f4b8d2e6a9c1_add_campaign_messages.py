@@ -0,0 +1,64 @@
+"""add_campaign_messages
+
+Revision ID: f4b8d2e6a9c1
+Revises: a7d3e8f1c2b5
+Create Date: 2025-11-18 21:30:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'f4b8d2e6a9c1'
+down_revision: Union[str, None] = 'a7d3e8f1c2b5'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Delivery of a campaign to each contact
+    op.create_table(
+        'campaign_messages',
+        sa.Column('id', postgresql.UUID(as_uuid=True), server_default=sa.text('gen_random_uuid()'), nullable=False),
+        sa.Column('organization_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('campaign_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('contact_id', postgresql.UUID(as_uuid=True), nullable=False),
+        sa.Column('message_id', postgresql.UUID(as_uuid=True), nullable=True),
+        sa.Column('whatsapp_message_id', sa.String(length=255), nullable=True),
+        sa.Column('status', sa.String(length=20), server_default='pending', nullable=False),
+        sa.Column('attempts', sa.Integer(), server_default='0', nullable=False),
+        sa.Column('error_code', sa.String(length=50), nullable=True),
+        sa.Column('error_message', sa.Text(), nullable=True),
+        sa.Column('sent_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('delivered_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('read_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('failed_at', sa.DateTime(timezone=True), nullable=True),
+        sa.Column('created_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.Column('updated_at', sa.DateTime(timezone=True), server_default=sa.text('now()'), nullable=False),
+        sa.ForeignKeyConstraint(['organization_id'], ['organizations.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['campaign_id'], ['campaigns.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['contact_id'], ['contacts.id'], ondelete='CASCADE'),
+        sa.ForeignKeyConstraint(['message_id'], ['messages.id'], ondelete='SET NULL'),
+        sa.PrimaryKeyConstraint('id'),
+        sa.UniqueConstraint('campaign_id', 'contact_id', name='uq_campaign_messages_campaign_contact'),
+    )
+    op.create_index('ix_campaign_messages_organization_id', 'campaign_messages', ['organization_id'])
+    op.create_index('ix_campaign_messages_campaign_id', 'campaign_messages', ['campaign_id'])
+    op.create_index('ix_campaign_messages_contact_id', 'campaign_messages', ['contact_id'])
+    op.create_index('ix_campaign_messages_message_id', 'campaign_messages', ['message_id'])
+    op.create_index('ix_campaign_messages_whatsapp_message_id', 'campaign_messages', ['whatsapp_message_id'])
+    op.create_index('ix_campaign_messages_status', 'campaign_messages', ['status'])
+
+
+def downgrade() -> None:
+    op.drop_index('ix_campaign_messages_status', table_name='campaign_messages')
+    op.drop_index('ix_campaign_messages_whatsapp_message_id', table_name='campaign_messages')
+    op.drop_index('ix_campaign_messages_message_id', table_name='campaign_messages')
+    op.drop_index('ix_campaign_messages_contact_id', table_name='campaign_messages')
+    op.drop_index('ix_campaign_messages_campaign_id', table_name='campaign_messages')
+    op.drop_index('ix_campaign_messages_organization_id', table_name='campaign_messages')
+    op.drop_table('campaign_messages')
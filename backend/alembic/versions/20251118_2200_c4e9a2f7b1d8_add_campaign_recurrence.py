@@ -0,0 +1,49 @@
+"""add_campaign_recurrence
+
+Revision ID: c4e9a2f7b1d8
+Revises: f4b8d2e6a9c1
+Create Date: 2025-11-18 22:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'c4e9a2f7b1d8'
+down_revision: Union[str, None] = 'f4b8d2e6a9c1'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Recurring parents and the occurrences created from them
+    op.add_column('campaigns', sa.Column('parent_campaign_id', postgresql.UUID(as_uuid=True), nullable=True))
+    op.add_column('campaigns', sa.Column('recurrence', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+    op.add_column('campaigns', sa.Column('next_occurrence_at', sa.DateTime(timezone=True), nullable=True))
+    op.add_column('campaigns', sa.Column('occurrences_created', sa.Integer(), server_default='0', nullable=False))
+    op.add_column('campaigns', sa.Column('occurrence_number', sa.Integer(), nullable=True))
+    op.create_foreign_key(
+        'fk_campaigns_parent_campaign_id', 'campaigns', 'campaigns',
+        ['parent_campaign_id'], ['id'], ondelete='SET NULL',
+    )
+    op.create_index(op.f('ix_campaigns_parent_campaign_id'), 'campaigns', ['parent_campaign_id'], unique=False)
+    op.create_index(op.f('ix_campaigns_next_occurrence_at'), 'campaigns', ['next_occurrence_at'], unique=False)
+    op.create_unique_constraint(
+        'uq_campaigns_parent_occurrence', 'campaigns', ['parent_campaign_id', 'occurrence_number']
+    )
+
+
+def downgrade() -> None:
+    op.drop_constraint('uq_campaigns_parent_occurrence', 'campaigns', type_='unique')
+    op.drop_index(op.f('ix_campaigns_next_occurrence_at'), table_name='campaigns')
+    op.drop_index(op.f('ix_campaigns_parent_campaign_id'), table_name='campaigns')
+    op.drop_constraint('fk_campaigns_parent_campaign_id', 'campaigns', type_='foreignkey')
+    op.drop_column('campaigns', 'occurrence_number')
+    op.drop_column('campaigns', 'occurrences_created')
+    op.drop_column('campaigns', 'next_occurrence_at')
+    op.drop_column('campaigns', 'recurrence')
+    op.drop_column('campaigns', 'parent_campaign_id')
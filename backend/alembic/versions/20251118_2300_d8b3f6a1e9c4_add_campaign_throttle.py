@@ -0,0 +1,36 @@
+"""add_campaign_throttle
+
+Revision ID: d8b3f6a1e9c4
+Revises: c4e9a2f7b1d8
+Create Date: 2025-11-18 23:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'd8b3f6a1e9c4'
+down_revision: Union[str, None] = 'c4e9a2f7b1d8'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Per-minute/day limits and business hours enforced by the dispatcher
+    op.add_column(
+        'campaigns',
+        sa.Column(
+            'throttle',
+            postgresql.JSONB(astext_type=sa.Text()),
+            server_default=sa.text("'{}'::jsonb"),
+            nullable=False,
+        ),
+    )
+
+
+def downgrade() -> None:
+    op.drop_column('campaigns', 'throttle')
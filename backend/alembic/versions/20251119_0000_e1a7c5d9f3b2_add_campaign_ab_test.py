@@ -0,0 +1,31 @@
+"""add_campaign_ab_test
+
+Revision ID: e1a7c5d9f3b2
+Revises: d8b3f6a1e9c4
+Create Date: 2025-11-19 00:00:00.000000
+
+"""
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+# revision identifiers, used by Alembic.
+revision: str = 'e1a7c5d9f3b2'
+down_revision: Union[str, None] = 'd8b3f6a1e9c4'
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    # Variants, test settings and the declared winner
+    op.add_column('campaigns', sa.Column('ab_test', postgresql.JSONB(astext_type=sa.Text()), nullable=True))
+    # Variant sent to each contact
+    op.add_column('campaign_messages', sa.Column('ab_variant', sa.String(length=50), nullable=True))
+
+
+def downgrade() -> None:
+    op.drop_column('campaign_messages', 'ab_variant')
+    op.drop_column('campaigns', 'ab_test')
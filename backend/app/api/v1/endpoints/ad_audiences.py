@@ -0,0 +1,219 @@
+"""
+Ad audience endpoints - Contact segments synced to Meta Custom Audiences
+"""
+
+from typing import List
+from uuid import UUID
+
+from fastapi import APIRouter, Depends, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.api.deps import get_current_user, get_db, require_role
+from app.models.user import User
+from app.schemas.ad_audience import (
+    AdAudienceCreate,
+    AdAudienceResponse,
+    AdAudienceSyncResult,
+    AdAudienceUpdate,
+    MetaAdsSettings,
+)
+from app.services.ad_audience_service import AdAudienceService
+
+router = APIRouter()
+
+
+# ============================================
+# SETTINGS
+# ============================================
+
+
+@router.get(
+    "/settings",
+    response_model=MetaAdsSettings,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Get Meta Ads settings",
+    description="Ad account and access token secret used to sync the organization's ad audiences.",
+    responses={
+        200: {"description": "Meta Ads settings"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+    },
+)
+async def get_settings(
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get Meta Ads settings."""
+    service = AdAudienceService(db)
+    return await service.get_settings(current_user.organization_id)
+
+
+@router.put(
+    "/settings",
+    response_model=MetaAdsSettings,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Update Meta Ads settings",
+    description=(
+        "Set the ad account and the secret holding a token with ads_management permission. "
+        "Audiences are only synced while enabled."
+    ),
+    responses={
+        200: {"description": "Settings updated"},
+        400: {"description": "Missing ad account or token to enable"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+        404: {"description": "Secret not found"},
+    },
+)
+async def update_settings(
+    data: MetaAdsSettings,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Update Meta Ads settings."""
+    service = AdAudienceService(db)
+    return await service.update_settings(current_user.organization_id, data)
+
+
+# ============================================
+# AUDIENCES
+# ============================================
+
+
+@router.get(
+    "",
+    response_model=List[AdAudienceResponse],
+    summary="List ad audiences",
+    description="List contact segments synced to Meta Custom Audiences, with their last sync.",
+    responses={
+        200: {"description": "Ad audiences"},
+        401: {"description": "Not authenticated"},
+    },
+)
+async def list_audiences(
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """List ad audiences."""
+    service = AdAudienceService(db)
+    return await service.list_audiences(current_user.organization_id)
+
+
+@router.post(
+    "",
+    response_model=AdAudienceResponse,
+    status_code=status.HTTP_201_CREATED,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Create ad audience",
+    description=(
+        "Create an audience with the same definition as campaigns (all_contacts, tags, "
+        "custom_list or segment). The Custom Audience is created on Meta on the first sync."
+    ),
+    responses={
+        201: {"description": "Audience created"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+    },
+)
+async def create_audience(
+    data: AdAudienceCreate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Create an ad audience."""
+    service = AdAudienceService(db)
+    return await service.create_audience(current_user.organization_id, data)
+
+
+@router.get(
+    "/{audience_id}",
+    response_model=AdAudienceResponse,
+    summary="Get ad audience",
+    responses={
+        200: {"description": "Ad audience"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Audience not found"},
+    },
+)
+async def get_audience(
+    audience_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get an ad audience."""
+    service = AdAudienceService(db)
+    return await service.get_audience(audience_id, current_user.organization_id)
+
+
+@router.put(
+    "/{audience_id}",
+    response_model=AdAudienceResponse,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Update ad audience",
+    description="Update an audience. Membership changes are pushed on the next sync.",
+    responses={
+        200: {"description": "Audience updated"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+        404: {"description": "Audience not found"},
+    },
+)
+async def update_audience(
+    audience_id: UUID,
+    data: AdAudienceUpdate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Update an ad audience."""
+    service = AdAudienceService(db)
+    return await service.update_audience(audience_id, current_user.organization_id, data)
+
+
+@router.delete(
+    "/{audience_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Delete ad audience",
+    description="Stop syncing the audience. The Custom Audience on Meta is kept.",
+    responses={
+        204: {"description": "Audience deleted"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+        404: {"description": "Audience not found"},
+    },
+)
+async def delete_audience(
+    audience_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Delete an ad audience."""
+    service = AdAudienceService(db)
+    await service.delete_audience(audience_id, current_user.organization_id)
+
+
+@router.post(
+    "/{audience_id}/sync",
+    response_model=AdAudienceSyncResult,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Sync ad audience now",
+    description=(
+        "Push the segment changes since the last sync: contacts that accept marketing are added "
+        "(SHA-256 hashed phone and email), contacts that left the segment or opted out are removed."
+    ),
+    responses={
+        200: {"description": "Sync result"},
+        400: {"description": "Meta Ads not enabled or rejected by Meta"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+        404: {"description": "Audience not found"},
+    },
+)
+async def sync_audience(
+    audience_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Sync an ad audience now."""
+    service = AdAudienceService(db)
+    return await service.sync_audience(audience_id, current_user.organization_id)
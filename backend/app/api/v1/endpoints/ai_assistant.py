@@ -29,11 +29,14 @@ from app.schemas.ai_assistant import (
     FlowTemplateDetail,
     TemplateListResponse,
     ImportTemplateRequest,
+    FlowTemplateUpgradeRequest,
+    FlowTemplateUpgradeStatus,
 )
 from app.schemas.chatbot import FlowInDB
 from app.services.flow_generator_service import FlowGeneratorService
+from app.services.flow_template_service import FlowTemplateService
 from app.repositories.flow_template_repository import FlowTemplateRepository
-from app.repositories.chatbot import ChatbotRepository, FlowRepository
+from app.repositories.chatbot import FlowRepository
 from app.repositories.organization import OrganizationRepository
 from app.models.chatbot import Chatbot, Flow
 from app.models.organization import Organization
@@ -798,9 +801,10 @@ async def search_templates(
     "/templates/{template_id}/import",
     response_model=FlowInDB,
     summary="Importar template",
-    description="Importa um template como um novo flow em um chatbot existente.",
+    description="Importa um template como um novo flow em um chatbot existente, preenchendo os parâmetros do template.",
     responses={
         200: {"description": "Flow criado a partir do template"},
+        400: {"description": "Parâmetros do template inválidos"},
         401: {"description": "Não autenticado"},
         404: {"description": "Template ou chatbot não encontrado"}
     }
@@ -812,57 +816,75 @@ async def import_template(
     db: AsyncSession = Depends(get_db),
 ):
     """
-    Import a template into a chatbot as a new flow.
+    Import a template into a chatbot as a new flow, filling in its parameters.
     """
-    # Get template
-    template = await FlowTemplateRepository.get_template(template_id)
-
-    if not template:
-        raise HTTPException(
-            status_code=status.HTTP_404_NOT_FOUND,
-            detail="Template not found"
-        )
-
-    # Verify chatbot ownership
-    chatbot_id = UUID(request.chatbot_id)
-    chatbot_repo = ChatbotRepository(db)
-    chatbot = await chatbot_repo.get(chatbot_id)
-
-    if not chatbot or chatbot.organization_id != current_user.organization_id:
-        raise HTTPException(
-            status_code=status.HTTP_404_NOT_FOUND,
-            detail="Chatbot not found"
-        )
+    service = FlowTemplateService(db)
+    new_flow = await service.instantiate(
+        template_id,
+        UUID(request.chatbot_id),
+        current_user.organization_id,
+        flow_name=request.flow_name,
+        parameters=request.parameters,
+    )
 
-    # Create flow from template
-    flow_repo = FlowRepository(db)
+    logger.info(
+        f"Imported template {template_id} as flow {new_flow.id} "
+        f"into chatbot {request.chatbot_id}"
+    )
 
-    flow_name = request.flow_name or template.name
-    flow_data = template.flow_data
+    return new_flow
 
-    # Apply variable customizations if provided
-    if request.customize_variables:
-        # TODO: Implement variable name mapping
-        pass
 
-    # Create flow
-    new_flow = await flow_repo.create({
-        "organization_id": current_user.organization_id,
-        "chatbot_id": chatbot_id,
-        "name": flow_name,
-        "description": template.description,
-        "canvas_data": flow_data.get("canvas_data", {}),
-        "variables": flow_data.get("variables", {}),
-        "is_main": False,
-        "is_active": True
-    })
+@router.get(
+    "/templates/flows/{flow_id}/upgrade",
+    response_model=FlowTemplateUpgradeStatus,
+    summary="Verificar atualização do template",
+    description="Informa se o template de origem do flow tem uma versão mais nova, com o changelog e os novos parâmetros.",
+    responses={
+        200: {"description": "Situação da atualização"},
+        400: {"description": "Flow não foi criado a partir de um template"},
+        401: {"description": "Não autenticado"},
+        404: {"description": "Flow ou template não encontrado"}
+    }
+)
+async def get_template_upgrade(
+    flow_id: UUID,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Check whether the flow's template has a newer version.
+    """
+    service = FlowTemplateService(db)
+    return await service.upgrade_status(flow_id, current_user.organization_id)
 
-    # Increment template use count
-    await FlowTemplateRepository.increment_use_count(template_id)
 
-    logger.info(
-        f"Imported template {template_id} as flow {new_flow.id} "
-        f"into chatbot {chatbot_id}"
+@router.post(
+    "/templates/flows/{flow_id}/upgrade",
+    response_model=FlowInDB,
+    summary="Atualizar flow para a versão mais nova do template",
+    description=(
+        "Regenera o rascunho do flow a partir da última versão do template, com os mesmos parâmetros. "
+        "A versão publicada só muda ao publicar o flow novamente."
+    ),
+    responses={
+        200: {"description": "Flow atualizado"},
+        400: {"description": "Sem versão nova ou parâmetros inválidos"},
+        401: {"description": "Não autenticado"},
+        404: {"description": "Flow ou template não encontrado"},
+        409: {"description": "Rascunho editado desde a geração (use force)"}
+    }
+)
+async def upgrade_template_flow(
+    flow_id: UUID,
+    request: FlowTemplateUpgradeRequest,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Regenerate the flow's draft from the latest template version.
+    """
+    service = FlowTemplateService(db)
+    return await service.upgrade(
+        flow_id, current_user.organization_id, parameters=request.parameters, force=request.force
     )
-
-    return new_flow
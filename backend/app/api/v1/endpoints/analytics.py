@@ -3,6 +3,8 @@ Analytics endpoints - Metrics, reports, and business intelligence
 """
 
 from datetime import datetime, timedelta
+from typing import Optional
+from uuid import UUID
 
 from fastapi import APIRouter, Depends, Query
 from sqlalchemy.ext.asyncio import AsyncSession
@@ -15,6 +17,7 @@ from app.schemas.analytics import (
     ChatbotMetrics,
     ContactMetrics,
     ConversationMetrics,
+    FlowFunnelMetrics,
     FullReport,
     MessageMetrics,
     OverviewMetrics,
@@ -215,6 +218,53 @@ async def get_chatbot_metrics(
     return metrics
 
 
+@router.get(
+    "/flows/{flow_id}/funnel",
+    response_model=FlowFunnelMetrics,
+    summary="Funil do flow",
+    description=(
+        "Retorna a taxa de conclusão, o abandono por node, os pontos de saída mais comuns "
+        "e a conversão por variante dos nodes de split (teste A/B) das sessões do flow iniciadas no período."
+    ),
+    responses={
+        200: {"description": "Funil do flow"},
+        401: {"description": "Não autenticado"},
+        404: {"description": "Flow não encontrado"}
+    }
+)
+async def get_flow_funnel(
+    flow_id: UUID,
+    start_date: datetime = Query(None, description="Start date (defaults to 30 days ago)"),
+    end_date: datetime = Query(None, description="End date (defaults to now)"),
+    abandon_after_minutes: int = Query(
+        30, ge=1, le=10080, description="Idle time after which an unfinished session counts as abandoned"
+    ),
+    goal_node_id: Optional[str] = Query(
+        None, description="Node that counts as a conversion for split variants (defaults to reaching an end node)"
+    ),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    Get the drop-off funnel of a flow
+
+    Returns completion rate, sessions and time spent per node, drop-offs
+    per node, the most common exit points and the conversion of each
+    split variant.
+    """
+    if not end_date:
+        end_date = datetime.utcnow()
+    if not start_date:
+        start_date = end_date - timedelta(days=30)
+
+    service = AnalyticsService(db)
+    metrics = await service.get_flow_funnel(
+        flow_id, current_user.organization_id, start_date, end_date, abandon_after_minutes,
+        goal_node_id=goal_node_id,
+    )
+    return metrics
+
+
 @router.get(
     "/messages",
     response_model=MessageMetrics,
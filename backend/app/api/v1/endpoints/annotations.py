@@ -0,0 +1,105 @@
+"""
+Annotation endpoints - Events overlaid on dashboard and analytics charts
+"""
+
+from datetime import datetime, timedelta
+from typing import List, Optional
+from uuid import UUID
+
+from fastapi import APIRouter, Depends, Query, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.api.deps import get_current_admin, get_current_user, get_db
+from app.models.user import User
+from app.schemas.annotation import AnnotationCreate, AnnotationList, AnnotationResponse
+from app.services.annotation_service import AnnotationService
+
+router = APIRouter()
+
+
+@router.get(
+    "/",
+    response_model=AnnotationList,
+    summary="Listar anotações",
+    description=(
+        "Lista as anotações do período (deploys, mudanças de templates, lançamentos de campanhas "
+        "e anotações manuais) para sobrepor nos gráficos. Inclui deploys da plataforma."
+    ),
+    responses={
+        200: {"description": "Anotações do período"},
+        401: {"description": "Não autenticado"}
+    }
+)
+async def list_annotations(
+    start_date: datetime = Query(None, description="Start date (defaults to 30 days ago)"),
+    end_date: datetime = Query(None, description="End date (defaults to now)"),
+    kind: Optional[List[str]] = Query(
+        None, description="Filter by kind: deploy, template_change, campaign_launch, custom"
+    ),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    List annotations of a period
+    """
+    if not end_date:
+        end_date = datetime.utcnow()
+    if not start_date:
+        start_date = end_date - timedelta(days=30)
+
+    items = await AnnotationService(db).list_annotations(
+        current_user.organization_id, start_date, end_date, kind
+    )
+    return AnnotationList(items=items, total=len(items))
+
+
+@router.post(
+    "/",
+    response_model=AnnotationResponse,
+    status_code=status.HTTP_201_CREATED,
+    summary="Criar anotação",
+    description=(
+        "Cria uma anotação manual (ex.: mudança de preço, anúncio na TV). "
+        "Dashboards abertos recebem o evento annotation:new em tempo real."
+    ),
+    responses={
+        201: {"description": "Anotação criada"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Requer admin"},
+        422: {"description": "Dados inválidos"}
+    }
+)
+async def create_annotation(
+    data: AnnotationCreate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_admin),
+):
+    """
+    Create a manual annotation
+    """
+    return await AnnotationService(db).create_annotation(
+        current_user.organization_id, data, current_user.id
+    )
+
+
+@router.delete(
+    "/{annotation_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    summary="Remover anotação",
+    description="Remove uma anotação da organização. Deploys da plataforma não podem ser removidos.",
+    responses={
+        204: {"description": "Anotação removida"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Requer admin ou anotação da plataforma"},
+        404: {"description": "Anotação não encontrada"}
+    }
+)
+async def delete_annotation(
+    annotation_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_admin),
+):
+    """
+    Delete an annotation
+    """
+    await AnnotationService(db).delete_annotation(annotation_id, current_user.organization_id)
@@ -6,7 +6,7 @@ from datetime import datetime
 from typing import Optional
 from uuid import UUID
 
-from fastapi import APIRouter, Depends, Query, status
+from fastapi import APIRouter, Depends, Query, Response, status
 from pydantic import BaseModel
 from sqlalchemy.ext.asyncio import AsyncSession
 
@@ -15,16 +15,23 @@ from app.core.exceptions import NotFoundException
 from app.models.user import User
 from app.schemas.campaign import (
     AudiencePreview,
+    CampaignABTestResults,
+    BulkTemplateSendRequest,
     CampaignCreate,
+    CampaignDryRunReport,
     CampaignInDB,
     CampaignListResponse,
+    CampaignPreflightReport,
     CampaignProgress,
     CampaignScheduleResponse,
     CampaignStartResponse,
     CampaignStats,
     CampaignUpdate,
 )
+from app.services.bulk_template_service import BulkTemplateSendService, results_csv
+from app.schemas.message import DeliveryStats
 from app.services.campaign_service import CampaignService
+from app.services.message_status_tracker import MessageStatusTracker
 
 router = APIRouter()
 
@@ -40,6 +47,52 @@ class ScheduleCampaignRequest(BaseModel):
     scheduled_at: datetime
 
 
+# ============================================
+# BULK TEMPLATE SEND
+# ============================================
+
+
+@router.post(
+    "/bulk-send",
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Bulk send template from CSV",
+    description=(
+        "Send a template to every row of a CSV (phone, var1..varN) without creating a campaign. "
+        "Rate limits, messaging tier and contact opt-outs are respected. Returns a CSV with the "
+        "outcome of each row (sent, failed, skipped, invalid)."
+    ),
+    responses={
+        200: {"description": "Results file", "content": {"text/csv": {}}},
+        400: {"description": "Invalid CSV, too many rows, non-official number or unusable template"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "WhatsApp number or template not found"},
+    }
+)
+async def bulk_send_template(
+    data: BulkTemplateSendRequest,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    One-off personalized template blast
+
+    Required role: org_admin or agent
+
+    Counts per status are also returned in X-Bulk-Send-* headers.
+    """
+    service = BulkTemplateSendService(db)
+    rows = await service.send(current_user.organization_id, data)
+
+    headers = {
+        "Content-Disposition": f'attachment; filename="bulk-send-{data.template_name}-{datetime.utcnow():%Y%m%d%H%M%S}.csv"',
+    }
+    for row_status in ("sent", "failed", "skipped", "invalid"):
+        headers[f"X-Bulk-Send-{row_status.capitalize()}"] = str(sum(1 for row in rows if row.status == row_status))
+
+    return Response(content=results_csv(rows), media_type="text/csv", headers=headers)
+
+
 # ============================================
 # CAMPAIGN ENDPOINTS
 # ============================================
@@ -91,7 +144,7 @@ async def create_campaign(
 async def list_campaigns(
     skip: int = Query(0, ge=0, description="Number of records to skip"),
     limit: int = Query(100, ge=1, le=500, description="Maximum records to return"),
-    status: Optional[str] = Query(None, description="Filter by status (draft, scheduled, running, paused, completed, cancelled)"),
+    status: Optional[str] = Query(None, description="Filter by status (draft, scheduled, recurring, running, testing, paused, completed, cancelled)"),
     db: AsyncSession = Depends(get_db),
     current_user: User = Depends(get_current_user),
 ):
@@ -133,6 +186,58 @@ async def get_campaign(
     return campaign
 
 
+@router.get(
+    "/{campaign_id}/ab-test",
+    response_model=CampaignABTestResults,
+    summary="Get A/B test results",
+    description="Sends, reads, replies and clicks per variant of the campaign's A/B test, with the rate of the test metric and its confidence interval. The winner appears once declared; the rest of the audience then receives it.",
+    responses={
+        200: {"description": "A/B test results returned successfully"},
+        400: {"description": "Campaign has no A/B test"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Campaign not found"},
+    }
+)
+async def get_campaign_ab_test(
+    campaign_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    Get A/B test results per variant
+    """
+    service = CampaignService(db)
+    return await service.get_ab_test_results(campaign_id, current_user.organization_id)
+
+
+@router.get(
+    "/{campaign_id}/occurrences",
+    response_model=CampaignListResponse,
+    summary="List campaign occurrences",
+    description="List the campaigns created from a recurring campaign, one per occurrence, latest first. Each occurrence has its own status and statistics.",
+    responses={
+        200: {"description": "Occurrences returned successfully"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Campaign not found"},
+    }
+)
+async def list_campaign_occurrences(
+    campaign_id: UUID,
+    skip: int = Query(0, ge=0, description="Number of records to skip"),
+    limit: int = Query(100, ge=1, le=500, description="Maximum records to return"),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    List occurrences of a recurring campaign
+    """
+    service = CampaignService(db)
+    occurrences, total = await service.list_occurrences(
+        campaign_id, current_user.organization_id, skip, limit
+    )
+    return CampaignListResponse(total=total, items=occurrences)
+
+
 @router.get(
     "/{campaign_id}/stats",
     response_model=CampaignStats,
@@ -174,6 +279,31 @@ async def get_campaign_stats(
     return stats
 
 
+@router.get(
+    "/{campaign_id}/delivery-stats",
+    response_model=DeliveryStats,
+    summary="Get campaign delivery receipts",
+    description="Message counts per delivery status (pending, sent, delivered, read, failed) aggregated from the campaign's messages.",
+    responses={
+        200: {"description": "Delivery stats returned successfully"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Campaign not found"},
+    }
+)
+async def get_campaign_delivery_stats(
+    campaign_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    Get delivery receipts of the campaign messages
+    """
+    campaign = await CampaignService(db).get_campaign(campaign_id, current_user.organization_id)
+    if not campaign:
+        raise NotFoundException("Campaign not found")
+    return await MessageStatusTracker(db).campaign_stats(campaign.id)
+
+
 @router.get(
     "/{campaign_id}/progress",
     response_model=CampaignProgress,
@@ -325,6 +455,116 @@ async def preview_campaign_audience(
     return preview
 
 
+@router.get(
+    "/{campaign_id}/preflight",
+    response_model=CampaignPreflightReport,
+    summary="Validate campaign templates",
+    description="Validate every template attached to the campaign against the synced WABA definitions: approval status, language availability, parameter count/type, media header and category vs audience consent. The same checks run when the campaign is scheduled or started.",
+    responses={
+        200: {
+            "description": "Pre-flight report returned successfully",
+            "content": {
+                "application/json": {
+                    "example": {
+                        "campaign_id": "uuid",
+                        "is_valid": False,
+                        "templates": [
+                            {
+                                "template_id": "uuid",
+                                "name": "black_friday",
+                                "language": "pt_BR",
+                                "category": "MARKETING",
+                                "is_valid": False,
+                                "errors": [
+                                    {
+                                        "code": "parameter_count_mismatch",
+                                        "field": "template_variables.body",
+                                        "message": "Template body expects 2 parameter(s) but 1 were provided"
+                                    }
+                                ],
+                                "warnings": []
+                            }
+                        ]
+                    }
+                }
+            }
+        },
+        401: {"description": "Not authenticated"},
+        404: {"description": "Campaign not found"},
+    }
+)
+async def preflight_campaign(
+    campaign_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    Validate campaign templates before scheduling
+
+    Returns actionable errors and warnings per template.
+    """
+    service = CampaignService(db)
+    return await service.preflight_campaign(campaign_id, current_user.organization_id)
+
+
+@router.get(
+    "/{campaign_id}/dry-run",
+    response_model=CampaignDryRunReport,
+    summary="Dry-run campaign",
+    description="Render the campaign for a sample of its audience without sending anything: personalized messages, recipients missing contact variables, estimated cost and sending time under the campaign limits and business hours, plus the pre-flight report.",
+    responses={
+        200: {
+            "description": "Dry-run report returned successfully",
+            "content": {
+                "application/json": {
+                    "example": {
+                        "campaign_id": "uuid",
+                        "total_recipients": 1200,
+                        "samples": [
+                            {
+                                "contact_id": "uuid",
+                                "name": "Maria Silva",
+                                "whatsapp_id": "5511999999999",
+                                "variant": None,
+                                "message_type": "text",
+                                "text": "Olá Maria, sua fatura vence amanhã!",
+                                "missing_variables": [],
+                            }
+                        ],
+                        "variables": [
+                            {"variable": "first_name", "known": True, "contacts_missing": 35}
+                        ],
+                        "contacts_with_missing_variables": 35,
+                        "pricing_category": "marketing",
+                        "estimated_cost": 75.0,
+                        "currency": "USD",
+                        "estimated_duration_seconds": 4320.0,
+                        "estimated_completion_at": "2025-11-20T11:12:00Z",
+                        "warnings": [],
+                    }
+                }
+            },
+        },
+        400: {"description": "Invalid segment filters"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Campaign not found"},
+    }
+)
+async def dry_run_campaign(
+    campaign_id: UUID,
+    sample_size: int = Query(5, ge=1, le=20, description="Number of sample messages"),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    Preview what the campaign would send
+
+    Nothing is sent and the campaign is not changed.
+    """
+    service = CampaignService(db)
+    return await service.dry_run_campaign(campaign_id, current_user.organization_id, sample_size)
+
+
 @router.patch(
     "/{campaign_id}",
     response_model=CampaignInDB,
@@ -399,10 +639,10 @@ async def delete_campaign(
     response_model=CampaignScheduleResponse,
     dependencies=[Depends(require_role(["org_admin", "agent"]))],
     summary="Schedule campaign",
-    description="Schedule a draft campaign for future execution. The campaign will automatically start at the specified time.",
+    description="Schedule a draft campaign for future execution. The campaign will automatically start at the specified time. A campaign with a recurrence becomes recurring instead: from that time on, one occurrence is created and sent per recurrence date.",
     responses={
         200: {"description": "Campaign scheduled successfully"},
-        400: {"description": "Invalid schedule time or campaign not in draft status"},
+        400: {"description": "Invalid schedule time, campaign not in draft status or template pre-flight failed"},
         401: {"description": "Not authenticated"},
         403: {"description": "Insufficient permissions"},
         404: {"description": "Campaign not found"},
@@ -436,7 +676,7 @@ async def schedule_campaign(
     description="Start a draft or scheduled campaign immediately. Messages will begin sending to the target audience.",
     responses={
         200: {"description": "Campaign started successfully"},
-        400: {"description": "Campaign cannot be started (invalid status or no audience)"},
+        400: {"description": "Campaign cannot be started (invalid status, no audience or template pre-flight failed)"},
         401: {"description": "Not authenticated"},
         403: {"description": "Insufficient permissions"},
         404: {"description": "Campaign not found"},
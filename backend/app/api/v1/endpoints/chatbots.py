@@ -3,13 +3,15 @@ Chatbot endpoints - Bot builder and flow management
 
 Provides complete CRUD operations for:
 - Chatbots: Create, list, update, activate/deactivate, delete
-- Flows: Create, list, update, delete, export/import
+- Flows: Create, list, update, delete, validate, simulate, export/import
+- Flow versions: Publish the draft, list versions with stats, rollback
+- Flow sessions: Execution trace (nodes, inputs, messages, variables)
 - Nodes: Create, list, update, delete
 
 All endpoints require authentication and respect organization multi-tenancy.
 """
 
-from typing import List
+from typing import List, Optional
 from uuid import UUID
 
 from fastapi import APIRouter, Depends, Query, status
@@ -26,16 +28,32 @@ from app.schemas.chatbot import (
     ChatbotUpdate,
     ChatbotWithFlows,
     FlowCreate,
+    FlowImport,
     FlowInDB,
     FlowListResponse,
+    FlowPublish,
+    FlowSaved,
+    FlowSessionTrace,
     FlowUpdate,
+    FlowValidationReport,
+    FlowVersionDetail,
+    FlowVersionInDB,
+    FlowVersionListResponse,
     FlowWithNodes,
     NodeCreate,
     NodeInDB,
     NodeListResponse,
     NodeUpdate,
 )
+from app.schemas.flow_console import (
+    FlowConsoleInput,
+    FlowConsoleSession,
+    FlowConsoleStart,
+    FlowSimulationRequest,
+    FlowSimulationResult,
+)
 from app.services.chatbot_service import ChatbotService
+from app.services.flow_console_service import FlowConsoleService
 
 router = APIRouter(tags=["Chatbots"])
 
@@ -378,10 +396,13 @@ async def get_flow_with_nodes(
 
 @router.patch(
     "/flows/{flow_id}",
-    response_model=FlowInDB,
+    response_model=FlowSaved,
     dependencies=[Depends(require_role(["org_admin", "agent"]))],
     summary="Update flow",
-    description="Update flow properties. Only provided fields will be updated.",
+    description=(
+        "Update flow properties. Only provided fields will be updated. "
+        "The response includes the static validation diagnostics of the canvas."
+    ),
     responses={
         200: {"description": "Flow updated"},
         401: {"description": "Not authenticated"},
@@ -398,7 +419,36 @@ async def update_flow(
     """Update flow properties."""
     service = ChatbotService(db)
     flow = await service.update_flow(flow_id, current_user.organization_id, data)
-    return flow
+    report = await service.validate_flow(flow_id, current_user.organization_id)
+    return FlowSaved.model_validate(flow).model_copy(update={"diagnostics": report.diagnostics})
+
+
+@router.get(
+    "/flows/{flow_id}/validate",
+    response_model=FlowValidationReport,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Validate flow",
+    description=(
+        "Statically validate the flow's draft: unreachable nodes, edges to missing nodes, "
+        "missing start/end, unconnected failure outputs and interactive nodes over WhatsApp limits, "
+        "plus complexity metrics (depth, cycles, estimated messages per run) checked against the "
+        "configured limits. Errors block publishing; warnings do not."
+    ),
+    responses={
+        200: {"description": "Validation report"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow not found"},
+    },
+)
+async def validate_flow(
+    flow_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Statically validate the flow's draft."""
+    service = ChatbotService(db)
+    return await service.validate_flow(flow_id, current_user.organization_id)
 
 
 @router.delete(
@@ -531,12 +581,32 @@ async def delete_node(
 # ============================================
 
 
+@router.get(
+    "/flows/export/schema",
+    response_model=dict,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Flow export JSON Schema",
+    description="JSON Schema of the flow export document accepted by the import endpoint.",
+    responses={
+        200: {"description": "JSON Schema"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+    },
+)
+async def get_flow_export_schema():
+    """JSON Schema of flow export documents."""
+    return FlowImport.model_json_schema()
+
+
 @router.get(
     "/flows/{flow_id}/export",
     response_model=dict,
     dependencies=[Depends(require_role(["org_admin"]))],
     summary="Export flow",
-    description="Export flow as JSON for backup or as a template. Includes canvas_data, variables, and metadata.",
+    description=(
+        "Export flow as a versioned JSON document for backup or for another organization. "
+        "Includes canvas_data, variables, the referenced flows/departments/queues/agents and metadata."
+    ),
     responses={
         200: {"description": "Exported flow JSON"},
         401: {"description": "Not authenticated"},
@@ -561,19 +631,25 @@ async def export_flow(
     status_code=status.HTTP_201_CREATED,
     dependencies=[Depends(require_role(["org_admin"]))],
     summary="Import flow",
-    description="Import a flow from previously exported JSON. The imported flow will not be set as main.",
+    description=(
+        "Import a flow from an export document (validated against GET /flows/export/schema). "
+        "Referenced records are remapped through id_map, then by flow name, department/queue slug "
+        "or agent email. The imported flow will not be set as main."
+    ),
     responses={
         201: {"description": "Flow imported"},
-        400: {"description": "Invalid import data"},
+        400: {"description": "Unresolved references"},
         401: {"description": "Not authenticated"},
         403: {"description": "Insufficient permissions"},
         404: {"description": "Chatbot not found"},
+        422: {"description": "Document does not match the export schema"},
     },
 )
 async def import_flow(
     chatbot_id: UUID,
-    import_data: dict,
+    import_data: FlowImport,
     override_name: str = Query(None, description="Optional name override for imported flow"),
+    drop_unresolved: bool = Query(False, description="Clear references that cannot be remapped instead of failing"),
     db: AsyncSession = Depends(get_db),
     current_user: User = Depends(get_current_user),
 ):
@@ -584,5 +660,290 @@ async def import_flow(
         chatbot_id=chatbot_id,
         organization_id=current_user.organization_id,
         override_name=override_name,
+        drop_unresolved=drop_unresolved,
     )
     return flow
+
+
+# ============================================
+# FLOW VERSION ENDPOINTS
+# ============================================
+
+
+@router.post(
+    "/flows/{flow_id}/publish",
+    response_model=FlowVersionInDB,
+    status_code=status.HTTP_201_CREATED,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Publish flow",
+    description=(
+        "Publish the flow's draft as a new immutable version. New conversations start on it; "
+        "conversations already running keep the version they started on, move to the same node "
+        "ID (map_node_ids) or restart at a checkpoint node (restart_at_checkpoint) according to "
+        "migration_strategy. The draft stays editable."
+    ),
+    responses={
+        201: {"description": "Version published, with the session migration summary"},
+        400: {"description": "Draft has validation errors (diagnostics in the detail) or invalid migration"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow not found"},
+    },
+)
+async def publish_flow(
+    flow_id: UUID,
+    data: Optional[FlowPublish] = None,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Publish the flow's draft."""
+    data = data or FlowPublish()
+    service = ChatbotService(db)
+    return await service.publish_flow(
+        flow_id,
+        current_user.organization_id,
+        current_user.id,
+        data.notes,
+        migration_strategy=data.migration_strategy,
+        node_map=data.node_map,
+        checkpoint_node_id=data.checkpoint_node_id,
+    )
+
+
+@router.get(
+    "/flows/{flow_id}/versions",
+    response_model=FlowVersionListResponse,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="List flow versions",
+    description="Published versions of a flow, newest first, with sessions started/completed per version.",
+    responses={
+        200: {"description": "Flow versions"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow not found"},
+    },
+)
+async def list_flow_versions(
+    flow_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """List the versions of a flow."""
+    service = ChatbotService(db)
+    versions = await service.list_flow_versions(flow_id, current_user.organization_id)
+    return FlowVersionListResponse(total=len(versions), items=versions)
+
+
+@router.get(
+    "/flows/{flow_id}/versions/{version}",
+    response_model=FlowVersionDetail,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Get flow version",
+    description="A published version with its canvas_data and variables snapshot.",
+    responses={
+        200: {"description": "Flow version"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow or version not found"},
+    },
+)
+async def get_flow_version(
+    flow_id: UUID,
+    version: int,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get a version of a flow."""
+    service = ChatbotService(db)
+    return await service.get_flow_version(flow_id, current_user.organization_id, version)
+
+
+@router.get(
+    "/flows/sessions/{session_id}/trace",
+    response_model=FlowSessionTrace,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Get flow session trace",
+    description=(
+        "Append-only log of a flow session: nodes executed, inputs received, messages sent and "
+        "variables changed, oldest first. The session ID is in the conversation's context "
+        "variables (_flow_session)."
+    ),
+    responses={
+        200: {"description": "Session trace"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Session not found"},
+    },
+)
+async def get_flow_session_trace(
+    session_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get the execution trace of a flow session."""
+    service = ChatbotService(db)
+    return await service.get_session_trace(session_id, current_user.organization_id)
+
+
+@router.post(
+    "/flows/{flow_id}/versions/{version}/rollback",
+    response_model=FlowVersionInDB,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Rollback flow",
+    description="Make an earlier version live again for new conversations. The draft is not changed.",
+    responses={
+        200: {"description": "Version is live"},
+        400: {"description": "Version is already live"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow or version not found"},
+    },
+)
+async def rollback_flow(
+    flow_id: UUID,
+    version: int,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Make an earlier version of a flow live."""
+    service = ChatbotService(db)
+    return await service.rollback_flow(flow_id, current_user.organization_id, version)
+
+
+# ============================================
+# FLOW TEST CONSOLE
+# ============================================
+
+
+@router.post(
+    "/flows/{flow_id}/console",
+    response_model=FlowConsoleSession,
+    status_code=status.HTTP_201_CREATED,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Start flow console session",
+    description=(
+        "Run a flow (draft or active) from its start node without WhatsApp. Returns what the flow "
+        "would send, the visited nodes and the session variables. Nodes with external side effects "
+        "are skipped; mock their outputs with `variables`."
+    ),
+    responses={
+        201: {"description": "Session started"},
+        400: {"description": "Flow has no start node"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow not found"},
+    },
+)
+async def start_flow_console(
+    flow_id: UUID,
+    data: Optional[FlowConsoleStart] = None,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Start a console session on a flow."""
+    service = FlowConsoleService(db)
+    return await service.start_session(
+        flow_id, current_user.organization_id, current_user.id, data or FlowConsoleStart()
+    )
+
+
+@router.post(
+    "/flows/{flow_id}/simulate",
+    response_model=FlowSimulationResult,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Simulate flow",
+    description=(
+        "Run the flow (draft or a published version) against scripted contact messages without "
+        "sending WhatsApp messages. Returns the transcript with the variables after every turn; "
+        "with expect, passed/failures tell whether the run ended as expected (for CI tests)."
+    ),
+    responses={
+        200: {"description": "Simulation transcript"},
+        400: {"description": "Flow has no start node"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow or version not found"},
+    },
+)
+async def simulate_flow(
+    flow_id: UUID,
+    data: FlowSimulationRequest,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Run a flow against scripted inputs."""
+    service = FlowConsoleService(db)
+    return await service.simulate(flow_id, current_user.organization_id, current_user.id, data)
+
+
+@router.post(
+    "/flows/{flow_id}/console/{session_id}/messages",
+    response_model=FlowConsoleSession,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Send console message",
+    description="Answer the node the flow is waiting on, as the contact, and run until it waits again.",
+    responses={
+        200: {"description": "Turn processed"},
+        400: {"description": "Session finished"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Flow or session not found"},
+    },
+)
+async def send_flow_console_message(
+    flow_id: UUID,
+    session_id: str,
+    data: FlowConsoleInput,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Send a message to a console session."""
+    service = FlowConsoleService(db)
+    return await service.send_message(flow_id, session_id, current_user.organization_id, data)
+
+
+@router.get(
+    "/flows/{flow_id}/console/{session_id}",
+    response_model=FlowConsoleSession,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="Get console session",
+    description="Current node, variables and the outputs of the last turn.",
+    responses={
+        200: {"description": "Session state"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Session not found or expired"},
+    },
+)
+async def get_flow_console(
+    flow_id: UUID,
+    session_id: str,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get a console session."""
+    service = FlowConsoleService(db)
+    return await service.get_session(flow_id, session_id, current_user.organization_id)
+
+
+@router.delete(
+    "/flows/{flow_id}/console/{session_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    dependencies=[Depends(require_role(["org_admin", "agent"]))],
+    summary="End console session",
+    responses={
+        204: {"description": "Session ended"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions"},
+        404: {"description": "Session not found or expired"},
+    },
+)
+async def end_flow_console(
+    flow_id: UUID,
+    session_id: str,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """End a console session."""
+    service = FlowConsoleService(db)
+    await service.end_session(flow_id, session_id, current_user.organization_id)
@@ -5,18 +5,21 @@ Contact Endpoints
 from typing import List, Optional
 from uuid import UUID
 
-from fastapi import APIRouter, Depends, Query, status
+from fastapi import APIRouter, Depends, File, Form, Query, UploadFile, status
 
 from app.api.deps import get_current_user, get_db
 from app.models.user import User
+from app.core.config import settings
 from app.schemas.contact import (
     Contact,
     ContactCreate,
+    ContactImportProgress,
     ContactUpdate,
     Tag,
     TagCreate,
     TagUpdate,
 )
+from app.services.contact_import_service import ContactImportService
 from app.services.contact_service import ContactService, TagService
 from app.core.swagger_examples import CONTACT_EXAMPLES, ERROR_EXAMPLES
 from sqlalchemy.ext.asyncio import AsyncSession
@@ -124,6 +127,70 @@ async def create_contact(
     )
 
 
+@router.post(
+    "/import",
+    response_model=ContactImportProgress,
+    status_code=status.HTTP_202_ACCEPTED,
+    summary="Import contacts from CSV/XLSX",
+    description=(
+        "Upload a CSV or XLSX file (multipart) with a header row and a phone column. The file is "
+        "processed row by row by a background worker; poll GET /contacts/import/{import_id} for progress. "
+        "Phones are normalized to WhatsApp IDs, other unknown columns become contact attributes."
+    ),
+    responses={
+        202: {"description": "Import queued"},
+        400: {"description": "Unsupported format, empty or too large file"},
+        401: {"description": "Not authenticated"},
+    }
+)
+async def import_contacts(
+    file: UploadFile = File(..., description="CSV (comma, semicolon or tab) or XLSX file"),
+    update_existing: bool = Form(False, description="Update contacts that already exist instead of skipping them"),
+    tag_ids: Optional[List[UUID]] = Form(None, description="Tags added to every imported contact"),
+    default_country_code: Optional[str] = Form(
+        None, pattern=r"^\d{0,4}$", description="Added to national numbers (default: CONTACT_IMPORT_DEFAULT_COUNTRY_CODE)"
+    ),
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Import contacts from a CSV/XLSX upload
+    """
+    service = ContactImportService(db)
+    return await service.start(
+        current_user.organization_id,
+        file,
+        update_existing=update_existing,
+        tag_ids=tag_ids,
+        default_country_code=(
+            settings.CONTACT_IMPORT_DEFAULT_COUNTRY_CODE if default_country_code is None else default_country_code
+        ),
+    )
+
+
+@router.get(
+    "/import/{import_id}",
+    response_model=ContactImportProgress,
+    summary="Get contact import progress",
+    description="Progress and outcome of a contact import: rows processed, contacts created, updated, skipped and the first invalid rows.",
+    responses={
+        200: {"description": "Import progress returned successfully"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Import not found or expired"},
+    }
+)
+async def get_contact_import(
+    import_id: str,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Get contact import progress
+    """
+    service = ContactImportService(db)
+    return await service.get_progress(current_user.organization_id, import_id)
+
+
 @router.get(
     "/{contact_id}",
     response_model=Contact,
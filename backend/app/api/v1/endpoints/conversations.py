@@ -20,9 +20,18 @@ from app.schemas.conversation import (
     Message,
     MessageCreate,
 )
-from app.schemas.message import MessageSendRequest, MessageResponse
+from app.schemas.conversation_participant import (
+    ParticipantAdd,
+    ParticipantList,
+    ParticipantResponse,
+    ParticipantUpdate,
+)
+from app.schemas.message import DeliveryStats, MessageSendRequest, MessageResponse
 from app.schemas.sla import SlaAlert
 from app.services.conversation_service import ConversationService
+from app.services.identity_service import IdentityVerificationService
+from app.services.message_status_tracker import MessageStatusTracker
+from app.services.participant_service import ConversationParticipantService
 from sqlalchemy.ext.asyncio import AsyncSession
 
 router = APIRouter()
@@ -165,6 +174,37 @@ async def get_conversations_metrics(
     )
 
 
+@router.get(
+    "/messages/search",
+    response_model=List[MessageResponse],
+    summary="Search messages",
+    description="Search messages across conversations. Organizations with message encryption enabled only match metadata (media filename, WhatsApp message ID, error message).",
+    responses={
+        200: {"description": "Matching messages returned successfully"},
+        401: {"description": "Not authenticated"},
+    }
+)
+async def search_messages(
+    q: str = Query(..., min_length=1, description="Search term"),
+    conversation_id: Optional[UUID] = Query(None, description="Restrict to a conversation"),
+    message_type: Optional[str] = Query(None, description="Filter by message type"),
+    skip: int = Query(0, ge=0, description="Number of records to skip"),
+    limit: int = Query(50, ge=1, le=100, description="Maximum records to return"),
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Search messages"""
+    service = ConversationService(db)
+    return await service.search_messages(
+        organization_id=current_user.organization_id,
+        query=q,
+        conversation_id=conversation_id,
+        message_type=message_type,
+        skip=skip,
+        limit=limit,
+    )
+
+
 @router.get(
     "/{conversation_id}",
     response_model=Conversation,
@@ -244,6 +284,30 @@ async def get_conversation_messages(
     )
 
 
+@router.get(
+    "/{conversation_id}/delivery-stats",
+    response_model=DeliveryStats,
+    summary="Get conversation delivery receipts",
+    description="Counts of outbound messages per delivery status (pending, sent, delivered, read, failed) with delivery, read and failure rates.",
+    responses={
+        200: {"description": "Delivery stats returned successfully"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Conversation not found"},
+    }
+)
+async def get_conversation_delivery_stats(
+    conversation_id: UUID,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Get delivery stats of a conversation"""
+    conversation = await ConversationService(db).get_by_id(
+        conversation_id=conversation_id,
+        organization_id=current_user.organization_id,
+    )
+    return await MessageStatusTracker(db).conversation_stats(conversation.id)
+
+
 @router.post(
     "/{conversation_id}/messages",
     response_model=MessageResponse,
@@ -278,6 +342,7 @@ async def send_message(
         message_type=data.message_type,
         content=data.content,
         sender_user_id=current_user.id,
+        reply_to_message_id=data.reply_to_message_id,
     )
 
     return message
@@ -287,7 +352,7 @@ async def send_message(
     "/{conversation_id}/read",
     response_model=Conversation,
     summary="Mark as read",
-    description="Mark a conversation as read by the current user. Updates unread count and last read timestamp.",
+    description="Mark a conversation as read by the current user. Updates unread count and last read timestamp, and sends read receipts (blue ticks) to the contact.",
     responses={
         200: {"description": "Conversation marked as read"},
         401: {"description": "Not authenticated"},
@@ -307,6 +372,31 @@ async def mark_conversation_as_read(
     )
 
 
+@router.post(
+    "/{conversation_id}/verify-identity",
+    response_model=Conversation,
+    summary="Confirm contact identity",
+    description="Clear the re-verification flag set when WhatsApp reported that the contact changed phone number or identity. Call after confirming who the contact is.",
+    responses={
+        200: {"description": "Identity confirmed"},
+        400: {"description": "Conversation does not require identity verification"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Conversation not found"},
+    }
+)
+async def verify_conversation_identity(
+    conversation_id: UUID,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Confirm the contact's identity after a security notification"""
+    return await IdentityVerificationService(db).mark_verified(
+        conversation_id=conversation_id,
+        organization_id=current_user.organization_id,
+        user_id=current_user.id,
+    )
+
+
 # ============================================
 # ACTION ENDPOINTS
 # ============================================
@@ -409,6 +499,107 @@ async def close_conversation(
         resolved=data.resolved,
     )
 
+# ============================================
+# PARTICIPANTS
+# ============================================
+
+
+@router.get(
+    "/{conversation_id}/participants",
+    response_model=ParticipantList,
+    summary="List participants",
+    description="List the agents working on a conversation: the primary (assigned) agent first, then collaborators.",
+    responses={
+        200: {"description": "Active participants"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Conversation not found"},
+    }
+)
+async def list_participants(
+    conversation_id: UUID,
+    include_left: bool = Query(False, description="Include agents who left (attribution history)"),
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """List conversation participants"""
+    items = await ConversationParticipantService(db).list_participants(
+        conversation_id, current_user.organization_id, include_left
+    )
+    return ParticipantList(items=items, total=len(items))
+
+
+@router.post(
+    "/{conversation_id}/participants",
+    response_model=ParticipantResponse,
+    status_code=status.HTTP_201_CREATED,
+    summary="Add participant",
+    description="Add an agent to a conversation as collaborator (or as primary, which reassigns it). Emits participant:joined to the conversation room.",
+    responses={
+        201: {"description": "Participant added"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Conversation or agent not found"},
+    }
+)
+async def add_participant(
+    conversation_id: UUID,
+    data: ParticipantAdd,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Add an agent to a conversation"""
+    return await ConversationParticipantService(db).add_participant(
+        conversation_id, current_user.organization_id, data, current_user.id
+    )
+
+
+@router.patch(
+    "/{conversation_id}/participants/{user_id}",
+    response_model=ParticipantResponse,
+    summary="Update participant",
+    description="Promote a participant to primary or change their notification preference (all, mentions, none). Emits participant:updated.",
+    responses={
+        200: {"description": "Participant updated"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Conversation or participant not found"},
+    }
+)
+async def update_participant(
+    conversation_id: UUID,
+    user_id: UUID,
+    data: ParticipantUpdate,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Update a conversation participant"""
+    return await ConversationParticipantService(db).update_participant(
+        conversation_id, current_user.organization_id, user_id, data
+    )
+
+
+@router.delete(
+    "/{conversation_id}/participants/{user_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    summary="Remove participant",
+    description="Remove a collaborator from a conversation. The primary agent must be reassigned or transferred instead. Emits participant:left.",
+    responses={
+        204: {"description": "Participant removed"},
+        400: {"description": "Primary agent cannot be removed"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Conversation or participant not found"},
+    }
+)
+async def remove_participant(
+    conversation_id: UUID,
+    user_id: UUID,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Remove a conversation participant"""
+    await ConversationParticipantService(db).remove_participant(
+        conversation_id, current_user.organization_id, user_id
+    )
+
+
 # ============================================
 # SLA ALERTS
 # ============================================
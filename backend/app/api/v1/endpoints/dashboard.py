@@ -2,12 +2,16 @@
 Dashboard endpoints - Summary and statistics for main dashboard
 """
 
-from fastapi import APIRouter, Depends
+from datetime import datetime, timedelta
+from typing import Optional
+from uuid import UUID
+
+from fastapi import APIRouter, Depends, Query
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.api.deps import get_current_user, get_db
 from app.models.user import User
-from app.schemas.analytics import OverviewMetrics
+from app.schemas.analytics import OverviewMetrics, SurveyMetrics
 from app.services.analytics_service import AnalyticsService
 
 router = APIRouter()
@@ -42,3 +46,39 @@ async def get_dashboard_summary(
     service = AnalyticsService(db)
     metrics = await service.get_overview_metrics(current_user.organization_id)
     return metrics
+
+
+@router.get(
+    "/surveys",
+    response_model=SurveyMetrics,
+    summary="NPS e CSAT",
+    description=(
+        "Retorna o NPS e o CSAT coletados pelos nodes de pesquisa no período: "
+        "geral, por flow e por agente."
+    ),
+    responses={
+        200: {"description": "Métricas de pesquisas"},
+        401: {"description": "Não autenticado"}
+    }
+)
+async def get_dashboard_surveys(
+    start_date: datetime = Query(None, description="Start date (defaults to 30 days ago)"),
+    end_date: datetime = Query(None, description="End date (defaults to now)"),
+    flow_id: Optional[UUID] = Query(None, description="Only answers given in this flow"),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """
+    Get survey (NPS / CSAT) metrics
+
+    Returns the scores of the survey node answers overall, per flow and
+    per agent.
+    """
+    if not end_date:
+        end_date = datetime.utcnow()
+    if not start_date:
+        start_date = end_date - timedelta(days=30)
+
+    service = AnalyticsService(db)
+    metrics = await service.get_survey_metrics(current_user.organization_id, start_date, end_date, flow_id)
+    return metrics
@@ -0,0 +1,142 @@
+"""
+Debug endpoints - Recent webhook deliveries of the organization, webhook routing
+and WhatsApp API client metrics
+"""
+
+from typing import Dict, Optional
+
+from fastapi import APIRouter, Depends, Query, status
+
+from app.api.deps import get_current_admin, get_current_super_admin
+from app.core.webhook_inbox import webhook_inbox
+from app.core.webhook_routing import number_router, unroutable_counter
+from app.core.whatsapp_metrics import whatsapp_metrics
+from app.models.user import User
+from app.schemas.webhook_event import WebhookInboxList, WebhookRoutingStats
+from app.schemas.whatsapp import WhatsAppNumberMetrics
+
+router = APIRouter()
+
+
+@router.get(
+    "/webhooks",
+    response_model=WebhookInboxList,
+    summary="Recent webhook deliveries",
+    description=(
+        "Last inbound WhatsApp webhooks (with their processing outcome) and outbound "
+        "webhook calls made by flows, newest first. Credentials are redacted and phone "
+        "numbers masked. Keeps the last WEBHOOK_INBOX_SIZE deliveries per organization."
+    ),
+    responses={
+        200: {"description": "Recent webhook deliveries"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Admin required"},
+    }
+)
+async def list_recent_webhooks(
+    type: Optional[str] = Query(None, pattern="^(inbound|outbound)$"),
+    status_filter: Optional[str] = Query(
+        None, alias="status", pattern="^(processed|failed|skipped|delivered)$"
+    ),
+    limit: int = Query(50, ge=1, le=200),
+    current_user: User = Depends(get_current_admin),
+):
+    """List recent webhook deliveries"""
+    items = await webhook_inbox.list(current_user.organization_id, type, status_filter, limit)
+    return WebhookInboxList(items=items, size=webhook_inbox.size)
+
+
+@router.delete(
+    "/webhooks",
+    status_code=status.HTTP_204_NO_CONTENT,
+    summary="Clear recent webhook deliveries",
+    responses={
+        204: {"description": "Inbox cleared"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Admin required"},
+    }
+)
+async def clear_recent_webhooks(
+    current_user: User = Depends(get_current_admin),
+):
+    """Clear the webhook inbox of the organization"""
+    await webhook_inbox.clear(current_user.organization_id)
+
+
+@router.get(
+    "/webhooks/routing",
+    response_model=WebhookRoutingStats,
+    summary="Webhook routing metrics",
+    description=(
+        "Inbound webhook changes routed per phone_number_id (this instance) and unroutable "
+        "events (missing or unknown phone_number_id) counted across all instances and workers."
+    ),
+    responses={
+        200: {"description": "Routing metrics"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Super admin required"},
+    }
+)
+async def get_webhook_routing(
+    current_user: User = Depends(get_current_super_admin),
+):
+    """Webhook routing metrics"""
+    stats = await unroutable_counter.stats()
+    return WebhookRoutingStats(**stats, routed=number_router.metrics.routed)
+
+
+@router.delete(
+    "/webhooks/routing",
+    status_code=status.HTTP_204_NO_CONTENT,
+    summary="Reset unroutable webhook counters",
+    responses={
+        204: {"description": "Counters reset"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Super admin required"},
+    }
+)
+async def reset_webhook_routing(
+    current_user: User = Depends(get_current_super_admin),
+):
+    """Reset the unroutable counters (e.g. after unsubscribing a removed number)"""
+    await unroutable_counter.reset()
+    number_router.invalidate()
+
+
+@router.get(
+    "/whatsapp/metrics",
+    response_model=Dict[str, WhatsAppNumberMetrics],
+    summary="WhatsApp API client metrics",
+    description=(
+        "Meta Cloud API calls per phone_number_id since this instance started: request and "
+        "error counts, status codes, calls per endpoint, latency and the last rate-limit "
+        "usage headers returned by Meta."
+    ),
+    responses={
+        200: {"description": "Metrics per phone_number_id"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Super admin required"},
+    }
+)
+async def get_whatsapp_metrics(
+    current_user: User = Depends(get_current_super_admin),
+):
+    """WhatsApp API client metrics"""
+    return whatsapp_metrics.snapshot()
+
+
+@router.delete(
+    "/whatsapp/metrics",
+    status_code=status.HTTP_204_NO_CONTENT,
+    summary="Reset WhatsApp API client metrics",
+    responses={
+        204: {"description": "Metrics reset"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Super admin required"},
+    }
+)
+async def reset_whatsapp_metrics(
+    current_user: User = Depends(get_current_super_admin),
+):
+    """Reset the WhatsApp API client metrics of this instance"""
+    whatsapp_metrics.reset()
@@ -0,0 +1,177 @@
+"""
+Dunning endpoints - ERP invoice sync and reminder rules
+"""
+
+from typing import List
+from uuid import UUID
+
+from fastapi import APIRouter, Depends, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.api.deps import get_current_user, get_db, require_role
+from app.models.user import User
+from app.schemas.dunning import (
+    DunningRuleCreate,
+    DunningRuleMetrics,
+    DunningRuleResponse,
+    DunningRuleUpdate,
+    InvoiceSyncRequest,
+    InvoiceSyncResponse,
+)
+from app.services.dunning_service import DunningService
+
+router = APIRouter()
+
+
+# ============================================
+# ERP SYNC
+# ============================================
+
+
+@router.post(
+    "/invoices/sync",
+    response_model=InvoiceSyncResponse,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Sync invoices from ERP",
+    description=(
+        "Upsert up to 1000 invoices by external_id. Contacts are created by phone when missing. "
+        "Invoices marked as paid or cancelled stop their pending reminders; payments after a "
+        "reminder are credited to that rule's metrics."
+    ),
+    responses={
+        200: {"description": "Sync result"},
+        400: {"description": "Invalid invoice data"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+    },
+)
+async def sync_invoices(
+    data: InvoiceSyncRequest,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Sync invoices pushed by the ERP."""
+    service = DunningService(db)
+    return await service.sync_invoices(current_user.organization_id, data.invoices)
+
+
+# ============================================
+# RULES
+# ============================================
+
+
+@router.get(
+    "/rules",
+    response_model=List[DunningRuleResponse],
+    summary="List dunning rules",
+    description="List reminder rules ordered by offset relative to the due date.",
+    responses={
+        200: {"description": "Dunning rules"},
+        401: {"description": "Not authenticated"},
+    },
+)
+async def list_rules(
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """List dunning rules."""
+    service = DunningService(db)
+    return await service.list_rules(current_user.organization_id)
+
+
+@router.post(
+    "/rules",
+    response_model=DunningRuleResponse,
+    status_code=status.HTTP_201_CREATED,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Create dunning rule",
+    description=(
+        "Create a reminder rule, e.g. offset_days=-3 sends 3 days before the due date and "
+        "offset_days=5 sends 5 days overdue. The template must be approved for the number and "
+        "template_variables must match its body variables."
+    ),
+    responses={
+        201: {"description": "Rule created"},
+        400: {"description": "Invalid number, template or variables"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+        404: {"description": "Number or template not found"},
+    },
+)
+async def create_rule(
+    data: DunningRuleCreate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Create a dunning rule."""
+    service = DunningService(db)
+    return await service.create_rule(current_user.organization_id, data)
+
+
+@router.put(
+    "/rules/{rule_id}",
+    response_model=DunningRuleResponse,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Update dunning rule",
+    description="Update a reminder rule. Reminders already planned keep their send time.",
+    responses={
+        200: {"description": "Rule updated"},
+        400: {"description": "Invalid number, template or variables"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+        404: {"description": "Rule not found"},
+    },
+)
+async def update_rule(
+    rule_id: UUID,
+    data: DunningRuleUpdate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Update a dunning rule."""
+    service = DunningService(db)
+    return await service.update_rule(rule_id, current_user.organization_id, data)
+
+
+@router.delete(
+    "/rules/{rule_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Delete dunning rule",
+    description="Delete a reminder rule. Pending reminders of the rule are skipped.",
+    responses={
+        204: {"description": "Rule deleted"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires org_admin role"},
+        404: {"description": "Rule not found"},
+    },
+)
+async def delete_rule(
+    rule_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Delete a dunning rule."""
+    service = DunningService(db)
+    await service.delete_rule(rule_id, current_user.organization_id)
+
+
+@router.get(
+    "/rules/{rule_id}/metrics",
+    response_model=DunningRuleMetrics,
+    summary="Dunning rule metrics",
+    description="Scheduled, sent, failed and skipped reminders, invoices paid after the reminder and recovered amount.",
+    responses={
+        200: {"description": "Rule metrics"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Rule not found"},
+    },
+)
+async def get_rule_metrics(
+    rule_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get dunning rule metrics."""
+    service = DunningService(db)
+    return await service.get_rule_metrics(rule_id, current_user.organization_id)
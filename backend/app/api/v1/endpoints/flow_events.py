@@ -0,0 +1,180 @@
+"""
+Flow Event endpoints - Internal and external events that start flows
+"""
+
+from typing import Optional
+from uuid import UUID
+
+from fastapi import APIRouter, Depends, Query, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.api.deps import get_current_user, get_db, require_role
+from app.models.user import User
+from app.schemas.flow_event import (
+    FlowEventEmit,
+    FlowEventEmitResult,
+    FlowEventTriggerCreate,
+    FlowEventTriggerInDB,
+    FlowEventTriggerListResponse,
+    FlowEventTriggerUpdate,
+)
+from app.services.flow_event_service import FlowEventService
+
+router = APIRouter()
+
+
+# ============================================
+# EVENT TRIGGER ENDPOINTS
+# ============================================
+
+
+@router.post(
+    "/triggers",
+    response_model=FlowEventTriggerInDB,
+    status_code=status.HTTP_201_CREATED,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Create event trigger",
+    description=(
+        "Start a flow when an event happens (campaign.reply_received, invoice.overdue, "
+        "ticket.resolved or a custom event posted to /flow-events/emit). With template_id the "
+        "flow is outbound-initiated: the approved template opens the conversation."
+    ),
+    responses={
+        201: {"description": "Trigger created"},
+        400: {"description": "Template cannot be sent from the number"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions (requires org_admin)"},
+        404: {"description": "Flow, number or template not found"},
+        422: {"description": "Validation error (e.g. invalid event type)"},
+    },
+)
+async def create_trigger(
+    data: FlowEventTriggerCreate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Create an event trigger."""
+    service = FlowEventService(db)
+    return await service.create_trigger(data, current_user.organization_id)
+
+
+@router.get(
+    "/triggers",
+    response_model=FlowEventTriggerListResponse,
+    summary="List event triggers",
+    responses={
+        200: {"description": "List of event triggers"},
+        401: {"description": "Not authenticated"},
+    },
+)
+async def list_triggers(
+    event_type: Optional[str] = Query(None, description="Only the triggers of this event"),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """List event triggers."""
+    service = FlowEventService(db)
+    triggers = await service.list_triggers(current_user.organization_id, event_type)
+    return FlowEventTriggerListResponse(total=len(triggers), items=triggers)
+
+
+@router.get(
+    "/triggers/{trigger_id}",
+    response_model=FlowEventTriggerInDB,
+    summary="Get event trigger",
+    responses={
+        200: {"description": "Trigger details"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Trigger not found"},
+    },
+)
+async def get_trigger(
+    trigger_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get an event trigger."""
+    service = FlowEventService(db)
+    return await service.get_trigger(trigger_id, current_user.organization_id)
+
+
+@router.patch(
+    "/triggers/{trigger_id}",
+    response_model=FlowEventTriggerInDB,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Update event trigger",
+    description="Set template_id to null to stop opening the conversation with a template.",
+    responses={
+        200: {"description": "Trigger updated"},
+        400: {"description": "Template cannot be sent from the number"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions (requires org_admin)"},
+        404: {"description": "Trigger, flow, number or template not found"},
+    },
+)
+async def update_trigger(
+    trigger_id: UUID,
+    data: FlowEventTriggerUpdate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Update an event trigger."""
+    service = FlowEventService(db)
+    return await service.update_trigger(trigger_id, data, current_user.organization_id)
+
+
+@router.delete(
+    "/triggers/{trigger_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Delete event trigger",
+    responses={
+        204: {"description": "Trigger deleted"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions (requires org_admin)"},
+        404: {"description": "Trigger not found"},
+    },
+)
+async def delete_trigger(
+    trigger_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Delete an event trigger."""
+    service = FlowEventService(db)
+    await service.delete_trigger(trigger_id, current_user.organization_id)
+
+
+# ============================================
+# EVENTS
+# ============================================
+
+
+@router.post(
+    "/emit",
+    response_model=FlowEventEmitResult,
+    status_code=status.HTTP_202_ACCEPTED,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Emit event",
+    description=(
+        "Event from an external system (ERP, e-commerce, helpdesk). Every active trigger of the "
+        "event whose conditions match the payload starts its flow for the contact, in the background. "
+        "Contacts given by phone are created when missing."
+    ),
+    responses={
+        202: {"description": "Matching triggers queued"},
+        400: {"description": "Invalid phone"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions (requires org_admin)"},
+        404: {"description": "Contact not found"},
+        422: {"description": "Validation error (e.g. missing contact_id and phone)"},
+    },
+)
+async def emit_event(
+    data: FlowEventEmit,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Emit an event."""
+    service = FlowEventService(db)
+    return await service.emit_external(current_user.organization_id, data)
@@ -0,0 +1,197 @@
+"""
+Flow Trigger endpoints - Keywords, exact phrases and regexes that start flows
+"""
+
+from typing import List, Optional
+from uuid import UUID
+
+from fastapi import APIRouter, Depends, Query, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.api.deps import get_current_user, get_db, require_role
+from app.models.user import User
+from app.schemas.flow_trigger import (
+    FlowTriggerConflict,
+    FlowTriggerCreate,
+    FlowTriggerInDB,
+    FlowTriggerListResponse,
+    FlowTriggerSaved,
+    FlowTriggerTest,
+    FlowTriggerTestResult,
+    FlowTriggerUpdate,
+)
+from app.services.flow_trigger_service import FlowTriggerService
+
+router = APIRouter()
+
+
+def _saved(trigger, conflicts) -> FlowTriggerSaved:
+    return FlowTriggerSaved(
+        **FlowTriggerInDB.model_validate(trigger).model_dump(),
+        conflicts=[FlowTriggerConflict.model_validate(conflict) for conflict in conflicts],
+    )
+
+
+# ============================================
+# FLOW TRIGGER ENDPOINTS
+# ============================================
+
+
+@router.post(
+    "/",
+    response_model=FlowTriggerSaved,
+    status_code=status.HTTP_201_CREATED,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Create flow trigger",
+    description=(
+        "Start a flow when an inbound message matches an exact phrase, keyword or regex. "
+        "Overlaps with other triggers are returned in conflicts."
+    ),
+    responses={
+        201: {"description": "Trigger created"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions (requires org_admin)"},
+        404: {"description": "Flow not found"},
+        409: {"description": "Another flow has the same trigger"},
+        422: {"description": "Validation error (e.g. invalid regex)"},
+    },
+)
+async def create_trigger(
+    data: FlowTriggerCreate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Create a trigger."""
+    service = FlowTriggerService(db)
+    trigger, conflicts = await service.create_trigger(data, current_user.organization_id)
+    return _saved(trigger, conflicts)
+
+
+@router.get(
+    "/",
+    response_model=FlowTriggerListResponse,
+    summary="List flow triggers",
+    description="Triggers of the organization in evaluation order (highest priority first)",
+    responses={
+        200: {"description": "List of triggers"},
+        401: {"description": "Not authenticated"},
+    },
+)
+async def list_triggers(
+    flow_id: Optional[UUID] = Query(None, description="Only the triggers of this flow"),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """List triggers."""
+    service = FlowTriggerService(db)
+    triggers = await service.list_triggers(current_user.organization_id, flow_id)
+    return FlowTriggerListResponse(total=len(triggers), items=triggers)
+
+
+@router.get(
+    "/conflicts",
+    response_model=List[FlowTriggerConflict],
+    summary="List trigger conflicts",
+    description="Active triggers of different flows that compete for the same messages, and which one wins",
+    responses={
+        200: {"description": "Conflicts"},
+        401: {"description": "Not authenticated"},
+    },
+)
+async def list_trigger_conflicts(
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """List conflicts between triggers."""
+    service = FlowTriggerService(db)
+    return await service.list_conflicts(current_user.organization_id)
+
+
+@router.post(
+    "/test",
+    response_model=FlowTriggerTestResult,
+    summary="Test flow triggers",
+    description="Which trigger a message would fire, without starting any flow",
+    responses={
+        200: {"description": "Matched trigger, if any"},
+        401: {"description": "Not authenticated"},
+    },
+)
+async def test_triggers(
+    data: FlowTriggerTest,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Check a message against the triggers."""
+    service = FlowTriggerService(db)
+    trigger = await service.test_message(current_user.organization_id, data.text, data.in_flow)
+    return FlowTriggerTestResult(matched=trigger is not None, trigger=trigger)
+
+
+@router.get(
+    "/{trigger_id}",
+    response_model=FlowTriggerInDB,
+    summary="Get flow trigger",
+    responses={
+        200: {"description": "Trigger details"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Trigger not found"},
+    },
+)
+async def get_trigger(
+    trigger_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Get a trigger."""
+    service = FlowTriggerService(db)
+    return await service.get_trigger(trigger_id, current_user.organization_id)
+
+
+@router.patch(
+    "/{trigger_id}",
+    response_model=FlowTriggerSaved,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Update flow trigger",
+    description="Overlaps with other triggers are returned in conflicts.",
+    responses={
+        200: {"description": "Trigger updated"},
+        400: {"description": "Invalid pattern"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions (requires org_admin)"},
+        404: {"description": "Trigger or flow not found"},
+        409: {"description": "Another flow has the same trigger"},
+    },
+)
+async def update_trigger(
+    trigger_id: UUID,
+    data: FlowTriggerUpdate,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Update a trigger."""
+    service = FlowTriggerService(db)
+    trigger, conflicts = await service.update_trigger(trigger_id, data, current_user.organization_id)
+    return _saved(trigger, conflicts)
+
+
+@router.delete(
+    "/{trigger_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    dependencies=[Depends(require_role(["org_admin"]))],
+    summary="Delete flow trigger",
+    responses={
+        204: {"description": "Trigger deleted"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Insufficient permissions (requires org_admin)"},
+        404: {"description": "Trigger not found"},
+    },
+)
+async def delete_trigger(
+    trigger_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_user),
+):
+    """Delete a trigger."""
+    service = FlowTriggerService(db)
+    await service.delete_trigger(trigger_id, current_user.organization_id)
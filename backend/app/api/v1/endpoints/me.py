@@ -0,0 +1,63 @@
+"""
+Current account endpoints - workspaces (multi-tenant accounts)
+"""
+
+from typing import List
+from uuid import UUID
+
+from fastapi import APIRouter, Depends
+
+from app.api.deps import get_auth_service, get_current_active_user
+from app.models.user import User
+from app.schemas.auth import Token, WorkspaceResponse
+from app.services.auth_service import AuthService
+
+router = APIRouter()
+
+
+@router.get(
+    "/workspaces",
+    response_model=List[WorkspaceResponse],
+    summary="Listar workspaces",
+    description=(
+        "Lista as organizações que a conta pode acessar (organização de origem primeiro), "
+        "com o role em cada uma. is_current indica a organização do token usado."
+    ),
+    responses={
+        200: {"description": "Workspaces da conta"},
+        401: {"description": "Não autenticado"}
+    }
+)
+async def list_workspaces(
+    current_user: User = Depends(get_current_active_user),
+    auth_service: AuthService = Depends(get_auth_service),
+):
+    """
+    List workspaces of the current account
+    """
+    return await auth_service.list_workspaces(current_user)
+
+
+@router.post(
+    "/workspaces/{organization_id}/switch",
+    response_model=Token,
+    summary="Alternar workspace",
+    description=(
+        "Emite novos tokens (access e refresh) com organization_id e role da organização escolhida. "
+        "Todas as chamadas com o novo token passam a operar nessa organização."
+    ),
+    responses={
+        200: {"description": "Tokens do workspace"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Conta não é membro da organização"}
+    }
+)
+async def switch_workspace(
+    organization_id: UUID,
+    current_user: User = Depends(get_current_active_user),
+    auth_service: AuthService = Depends(get_auth_service),
+):
+    """
+    Switch to another workspace of the current account
+    """
+    return await auth_service.switch_workspace(current_user, organization_id)
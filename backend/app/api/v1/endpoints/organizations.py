@@ -173,6 +173,35 @@ async def update_my_organization_settings(
     return await service.update_settings(current_user.organization_id, settings)
 
 
+@router.put(
+    "/me/message-encryption",
+    response_model=Organization,
+    summary="Configurar criptografia de mensagens",
+    description="Ativa ou desativa a criptografia em repouso do conteúdo das mensagens. Com a criptografia ativa, a busca de mensagens usa apenas metadados. Requer org_admin.",
+    responses={
+        200: {"description": "Configuração de criptografia atualizada"},
+        400: {"description": "Chave de criptografia não configurada no servidor"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Sem permissão (apenas org_admin)"}
+    }
+)
+async def update_message_encryption(
+    enabled: bool,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Enable/disable message encryption at rest for current organization
+    Requires: org_admin role
+    """
+    if current_user.role not in ["org_admin", "super_admin"]:
+        from app.core.exceptions import ForbiddenException
+        raise ForbiddenException("Only organization admins can change message encryption")
+
+    service = OrganizationService(db)
+    return await service.set_message_encryption(current_user.organization_id, enabled)
+
+
 @router.put(
     "/{org_id}",
     response_model=Organization,
@@ -18,6 +18,7 @@ from app.schemas.secret import (
     SecretUpdate,
     SecretInDB,
     SecretWithValue,
+    SecretAccessLogList,
 )
 from app.services.secret_service import SecretService
 
@@ -107,6 +108,10 @@ async def create_secret(
             metadata=data.secret_metadata,
             encryption_provider=data.encryption_provider,
             encryption_key_id=data.encryption_key_id,
+            kind=data.kind,
+            allowed_roles=data.allowed_roles,
+            allowed_usages=data.allowed_usages,
+            user_id=current_user.id,
         )
     except ValueError as e:
         raise HTTPException(
@@ -161,7 +166,7 @@ async def get_secret(
     "/{secret_id}/value",
     response_model=SecretWithValue,
     summary="Obter valor do secret",
-    description="Retorna o secret com valor descriptografado. Requer org_admin ou super_admin. ⚠️ USE COM CUIDADO.",
+    description="Retorna o secret com valor descriptografado. Admins sempre; demais papéis conforme allowed_roles (variáveis: todos por padrão). Acesso auditado. ⚠️ USE COM CUIDADO.",
     responses={
         200: {"description": "Secret com valor"},
         401: {"description": "Não autenticado"},
@@ -171,13 +176,15 @@ async def get_secret(
 )
 async def get_secret_value(
     secret_id: UUID,
-    current_user: User = Depends(get_current_admin),
+    current_user: User = Depends(get_current_user),
     db: AsyncSession = Depends(get_db),
 ):
     """
     Get secret with decrypted value
     
-    Requires: org_admin or super_admin role
+    Requires: org_admin or super_admin role, or a role listed in the
+    secret's allowed_roles (variables are readable by everyone by default).
+    Every read is recorded in the access audit.
     
     ⚠️ SECURITY WARNING: This endpoint returns the decrypted value.
     Use with caution and ensure proper access controls.
@@ -188,6 +195,8 @@ async def get_secret_value(
         secret_with_value = await service.get_secret_with_value(
             secret_id=secret_id,
             organization_id=current_user.organization_id,
+            user_id=current_user.id,
+            role=current_user.role,
         )
         
         if not secret_with_value:
@@ -198,6 +207,13 @@ async def get_secret_value(
         
         return secret_with_value
         
+    except HTTPException:
+        raise
+    except PermissionError as e:
+        raise HTTPException(
+            status_code=status.HTTP_403_FORBIDDEN,
+            detail=str(e),
+        )
     except Exception as e:
         raise HTTPException(
             status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
@@ -242,6 +258,9 @@ async def update_secret(
             value=data.value,
             is_active=data.is_active,
             metadata=data.secret_metadata,
+            allowed_roles=data.allowed_roles,
+            allowed_usages=data.allowed_usages,
+            user_id=current_user.id,
         )
         
         if not updated:
@@ -293,6 +312,7 @@ async def delete_secret(
     deleted = await service.delete_secret(
         secret_id=secret_id,
         organization_id=current_user.organization_id,
+        user_id=current_user.id,
     )
     
     if not deleted:
@@ -336,6 +356,7 @@ async def rotate_secret(
             secret_id=secret_id,
             organization_id=current_user.organization_id,
             new_value=new_value,
+            user_id=current_user.id,
         )
         
         if not rotated:
@@ -353,6 +374,51 @@ async def rotate_secret(
         )
 
 
+@router.get(
+    "/{secret_id}/access-logs",
+    response_model=SecretAccessLogList,
+    summary="Auditoria de acesso do secret",
+    description="Leituras do valor (API e interpolação em fluxos), acessos negados e alterações. Requer org_admin.",
+    responses={
+        200: {"description": "Registros de acesso"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Sem permissão"},
+        404: {"description": "Secret não encontrado"}
+    }
+)
+async def list_secret_access_logs(
+    secret_id: UUID,
+    skip: int = Query(0, ge=0),
+    limit: int = Query(50, ge=1, le=200),
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Access audit of a secret, newest first
+    
+    Requires: org_admin or super_admin role
+    """
+    service = SecretService(db)
+
+    secret = await service.get_secret(
+        secret_id=secret_id,
+        organization_id=current_user.organization_id,
+    )
+    if not secret:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND,
+            detail="Secret not found",
+        )
+
+    items, total = await service.list_access_logs(
+        secret_id=secret_id,
+        organization_id=current_user.organization_id,
+        skip=skip,
+        limit=limit,
+    )
+    return SecretAccessLogList(items=items, total=total, skip=skip, limit=limit)
+
+
 @router.get(
     "/stats",
     response_model=dict,
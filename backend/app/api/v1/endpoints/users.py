@@ -9,7 +9,13 @@ from fastapi import APIRouter, Depends, Query, status
 
 from app.api.deps import get_current_user, get_db, get_current_admin
 from app.models.user import User
-from app.schemas.user import User as UserSchema, UserCreate, UserUpdate
+from app.schemas.user import (
+    User as UserSchema,
+    UserCreate,
+    UserUpdate,
+    WorkspaceMember,
+    WorkspaceMemberAdd,
+)
 from app.services.user_service import UserService
 from sqlalchemy.ext.asyncio import AsyncSession
 
@@ -153,6 +159,92 @@ async def update_my_profile(
     )
 
 
+@router.get(
+    "/members",
+    response_model=List[WorkspaceMember],
+    summary="Listar membros convidados",
+    description="Lista contas de outras organizações (ex: agências) com acesso a esta organização.",
+    responses={
+        200: {"description": "Membros convidados"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Sem permissão"}
+    }
+)
+async def list_workspace_members(
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    List guest members of the organization
+    Requires: org_admin or super_admin role
+    """
+    service = UserService(db)
+    return await service.list_workspace_members(current_user.organization_id)
+
+
+@router.post(
+    "/members",
+    response_model=WorkspaceMember,
+    status_code=status.HTTP_201_CREATED,
+    summary="Adicionar conta existente",
+    description=(
+        "Dá acesso a esta organização a uma conta já existente, com role próprio. "
+        "O usuário alterna entre organizações via POST /me/workspaces/{organization_id}/switch."
+    ),
+    responses={
+        201: {"description": "Membro adicionado"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Sem permissão"},
+        404: {"description": "Conta não encontrada"},
+        409: {"description": "Usuário já é membro"}
+    }
+)
+async def add_workspace_member(
+    data: WorkspaceMemberAdd,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Add an existing account to the organization
+    Requires: org_admin or super_admin role
+    """
+    service = UserService(db)
+    return await service.add_workspace_member(
+        organization_id=current_user.organization_id,
+        data=data,
+        added_by=current_user,
+    )
+
+
+@router.delete(
+    "/members/{user_id}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    summary="Remover membro convidado",
+    description="Revoga o acesso de uma conta convidada. Os tokens dessa organização deixam de valer.",
+    responses={
+        204: {"description": "Membro removido"},
+        401: {"description": "Não autenticado"},
+        403: {"description": "Sem permissão"},
+        404: {"description": "Membro não encontrado"}
+    }
+)
+async def remove_workspace_member(
+    user_id: UUID,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Remove a guest member from the organization
+    Requires: org_admin or super_admin role
+    """
+    service = UserService(db)
+    await service.remove_workspace_member(
+        organization_id=current_user.organization_id,
+        user_id=user_id,
+        removed_by=current_user,
+    )
+
+
 @router.get(
     "/{user_id}",
     response_model=UserSchema,
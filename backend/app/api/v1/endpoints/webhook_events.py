@@ -0,0 +1,129 @@
+"""
+Webhook event endpoints - Stored Meta webhook payloads and replay
+"""
+
+from datetime import datetime
+from typing import Optional
+from uuid import UUID
+
+from fastapi import APIRouter, Depends, Query, status
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.api.deps import get_current_admin, get_db
+from app.models.user import User
+from app.schemas.webhook_event import (
+    WebhookEventDetail,
+    WebhookEventList,
+    WebhookReplayRequest,
+    WebhookReplayResponse,
+)
+from app.services.webhook_event_service import WebhookEventService
+
+router = APIRouter()
+
+
+@router.get(
+    "/",
+    response_model=WebhookEventList,
+    summary="List webhook events",
+    description=(
+        "List raw webhooks received from Meta with their processing status "
+        "(received, queued, processed, failed, skipped), newest first. Includes "
+        "events that arrived before one of your numbers was registered."
+    ),
+    responses={
+        200: {"description": "Stored webhook events"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Admin required"},
+    }
+)
+async def list_webhook_events(
+    status_filter: Optional[str] = Query(
+        None, alias="status", pattern="^(received|queued|processed|failed|skipped)$"
+    ),
+    since: Optional[datetime] = Query(None, description="Received at or after"),
+    until: Optional[datetime] = Query(None, description="Received at or before"),
+    skip: int = Query(0, ge=0),
+    limit: int = Query(50, ge=1, le=200),
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_admin),
+):
+    """List stored webhook events"""
+    items, total = await WebhookEventService(db).list_events(
+        current_user.organization_id, status_filter, since, until, skip, limit
+    )
+    return WebhookEventList(items=items, total=total)
+
+
+@router.get(
+    "/{event_id}",
+    response_model=WebhookEventDetail,
+    summary="Get webhook event",
+    description="Get a stored webhook event with its raw payload and last error.",
+    responses={
+        200: {"description": "Webhook event"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Admin required"},
+        404: {"description": "Event not found"},
+    }
+)
+async def get_webhook_event(
+    event_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_admin),
+):
+    """Get a stored webhook event"""
+    return await WebhookEventService(db).get_event(event_id, current_user.organization_id)
+
+
+@router.post(
+    "/replay",
+    response_model=WebhookReplayResponse,
+    status_code=status.HTTP_202_ACCEPTED,
+    summary="Replay webhook events",
+    description=(
+        "Queue failed or skipped webhook events for reprocessing, by id or by period "
+        "(e.g. after a bug fix or an outage). Events are replayed by a worker in "
+        "arrival order; messages already stored are not duplicated."
+    ),
+    responses={
+        202: {"description": "Events queued for replay"},
+        400: {"description": "No replayable event matches the selection"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Admin required"},
+        422: {"description": "Invalid selection"},
+    }
+)
+async def replay_webhook_events(
+    data: WebhookReplayRequest,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_admin),
+):
+    """Replay failed or skipped webhook events"""
+    event_ids = await WebhookEventService(db).request_replay(current_user.organization_id, data)
+    return WebhookReplayResponse(queued=len(event_ids), event_ids=event_ids)
+
+
+@router.post(
+    "/{event_id}/replay",
+    response_model=WebhookReplayResponse,
+    status_code=status.HTTP_202_ACCEPTED,
+    summary="Replay webhook event",
+    description="Queue one failed or skipped webhook event for reprocessing.",
+    responses={
+        202: {"description": "Event queued for replay"},
+        400: {"description": "Event is not failed or skipped"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Admin required"},
+    }
+)
+async def replay_webhook_event(
+    event_id: UUID,
+    db: AsyncSession = Depends(get_db),
+    current_user: User = Depends(get_current_admin),
+):
+    """Replay one webhook event"""
+    event_ids = await WebhookEventService(db).request_replay(
+        current_user.organization_id, WebhookReplayRequest(event_ids=[event_id])
+    )
+    return WebhookReplayResponse(queued=len(event_ids), event_ids=event_ids)
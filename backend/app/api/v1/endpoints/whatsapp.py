@@ -6,6 +6,7 @@ Provides complete management for WhatsApp Business integration:
 - Webhook handling for Meta Cloud API
 - QR Code connection for Evolution API
 - Template management and synchronization
+- Click-to-chat links and QR codes with prefilled messages
 - Message sending capabilities
 
 All endpoints require authentication except webhooks which are public.
@@ -16,7 +17,7 @@ from uuid import UUID
 
 from fastapi import APIRouter, Depends, status, Request, HTTPException, Query
 from fastapi.responses import PlainTextResponse
-from pydantic import BaseModel
+from pydantic import BaseModel, Field
 
 from app.api.deps import get_current_user, get_db, get_current_admin
 from app.models.user import User
@@ -27,6 +28,7 @@ from app.schemas.whatsapp import (
     ConnectionType,
 )
 from app.schemas.template import (
+    TemplateCatalogResponse,
     TemplateCreateRequest,
     TemplateUpdateRequest,
     TemplateResponse,
@@ -47,6 +49,53 @@ class QRCodeResponse(BaseModel):
     message: str
 
 
+class ClickToChatLinkResponse(BaseModel):
+    """wa.me click-to-chat link"""
+    link: str
+    phone_number: str
+    text: Optional[str] = None
+
+
+class QRLinkCreate(BaseModel):
+    """Create/update a Meta QR code with prefilled message"""
+    prefilled_message: str = Field(..., min_length=1, max_length=140)
+    image_format: str = Field("PNG", pattern="^(PNG|SVG)$")
+
+
+class AccessTokenRotate(BaseModel):
+    """New Meta access token of an official number"""
+
+    access_token: str = Field(..., min_length=1, description="System user or user access token")
+
+
+class TwoStepPinRequest(BaseModel):
+    """Two-step verification PIN (never stored by PyTake)"""
+    pin: str = Field(..., pattern=r"^\d{6}$", description="6-digit PIN")
+
+
+class ConversationalCommand(BaseModel):
+    """Command shown when the user types "/" in the chat"""
+    command_name: str = Field(..., min_length=1, max_length=33, description="Name, with or without leading /")
+    command_description: str = Field(..., min_length=1, max_length=256)
+
+
+class ConversationalAutomationUpdate(BaseModel):
+    """Conversational components (omitted fields are left unchanged)"""
+    enable_welcome_message: Optional[bool] = Field(
+        None, description="Send request_welcome webhooks when a user opens the chat for the first time"
+    )
+    prompts: Optional[List[str]] = Field(None, max_length=4, description="Ice breakers (max 4, 80 characters)")
+    commands: Optional[List[ConversationalCommand]] = Field(None, max_length=30)
+
+
+class QRLinkResponse(BaseModel):
+    """Meta QR code (message_qrdls)"""
+    code: str
+    prefilled_message: Optional[str] = None
+    deep_link_url: Optional[str] = None
+    qr_image_url: Optional[str] = None
+
+
 @router.get(
     "/",
     response_model=List[WhatsAppNumber],
@@ -116,45 +165,14 @@ async def verify_webhook(
     challenge: str = Query(None, alias="hub.challenge"),
 ):
     """Webhook verification endpoint for Meta Cloud API (PUBLIC)."""
-    if not mode or not token or not challenge:
-        raise HTTPException(
-            status_code=400,
-            detail="Missing required query parameters"
-        )
-
-    if mode != "subscribe":
-        raise HTTPException(
-            status_code=403,
-            detail="Invalid mode"
-        )
-
-    # Verify token against database manually (without dependency injection)
     from app.core.database import async_session
-    from sqlalchemy import select
-    from app.models.whatsapp_number import WhatsAppNumber
+    from app.schemas.webhook import WebhookChallenge
 
-    async with async_session() as db:
-        try:
-            stmt = select(WhatsAppNumber).where(
-                WhatsAppNumber.webhook_verify_token == token,
-                WhatsAppNumber.deleted_at.is_(None),
-            )
-            result = await db.execute(stmt)
-            number = result.scalar_one_or_none()
+    verification = WebhookChallenge(mode=mode, verify_token=token, challenge=challenge)
 
-            if not number:
-                raise HTTPException(
-                    status_code=403,
-                    detail="Invalid verify token"
-                )
-        except HTTPException:
-            raise
-        except Exception as e:
-            print(f"Error verifying webhook token: {e}")
-            raise HTTPException(
-                status_code=403,
-                detail="Invalid verify token"
-            )
+    # Resolve the number (tenant) by its own verify token
+    async with async_session() as db:
+        await WhatsAppService(db).resolve_webhook_challenge(verification)
 
     # Return challenge to complete verification
     return challenge
@@ -309,10 +327,38 @@ async def update_whatsapp_number(
     )
 
 
+def _phone_number_warnings(info: dict) -> List[str]:
+    """Explain why sends from an Official API number may be throttled or blocked"""
+    warnings = []
+
+    quality = info.get("quality_rating")
+    if quality == "YELLOW":
+        warnings.append("Quality rating is YELLOW: the messaging limit may be lowered if it keeps dropping")
+    elif quality in ("RED", "FLAGGED"):
+        warnings.append(f"Quality rating is {quality}: the messaging limit will not be upgraded and may be lowered")
+
+    limit = info.get("daily_conversation_limit")
+    if limit is not None:
+        warnings.append(
+            f"Messaging limit {info.get('messaging_limit_tier')}: up to {limit} unique customers per 24h "
+            "can receive business-initiated messages"
+        )
+
+    name_status = info.get("name_status")
+    if name_status and name_status not in ("APPROVED", "AVAILABLE_WITHOUT_REVIEW"):
+        warnings.append(f"Display name status is {name_status}: messaging may be limited until it is approved")
+
+    return warnings
+
+
 @router.post(
     "/{number_id}/test",
     summary="Test connection",
-    description="Test WhatsApp connection status for Official or Evolution API",
+    description=(
+        "Test WhatsApp connection status for Official or Evolution API. "
+        "Official API numbers also return quality rating, messaging limit tier and "
+        "display name status, with warnings explaining throttled sends."
+    ),
     responses={
         200: {"description": "Connection status"},
         401: {"description": "Not authenticated"},
@@ -356,26 +402,25 @@ async def test_whatsapp_connection(
                 access_token=number.access_token
             )
             
-            # Try to fetch phone number details as a test
-            import httpx
-            async with httpx.AsyncClient() as client:
-                response = await client.get(
-                    f"https://graph.facebook.com/v18.0/{number.phone_number_id}",
-                    params={"access_token": number.access_token}
-                )
-                
-                if response.status_code == 200:
-                    data = response.json()
-                    result["status"] = "connected"
-                    result["display_name"] = data.get("display_phone_number", number.phone_number)
-                    result["message"] = "✅ Official API connection successful"
-                elif response.status_code == 401:
+            # Fetch phone number details (quality rating, messaging limit) as a test
+            from app.integrations.meta_api import MetaAPIError
+
+            try:
+                info = await meta_api.get_phone_number_info()
+            except MetaAPIError as e:
+                if e.status_code == 401:
                     result["status"] = "disconnected"
                     result["message"] = "❌ Invalid access token - connection failed"
                 else:
                     result["status"] = "error"
-                    result["message"] = f"❌ API returned status {response.status_code}"
-                    
+                    result["message"] = f"❌ API returned status {e.status_code}: {e.message}"
+            else:
+                result["status"] = "connected"
+                result["display_name"] = info.get("display_phone_number") or number.phone_number
+                result["message"] = "✅ Official API connection successful"
+                result["phone_number_info"] = info
+                result["warnings"] = _phone_number_warnings(info)
+
         elif number.connection_type == "qrcode":
             # Test Evolution API connection
             from app.integrations.evolution_api import EvolutionAPIClient
@@ -414,7 +459,11 @@ async def test_whatsapp_connection(
             .values(
                 status=result["status"],
                 last_seen_at=datetime.now(datetime.now().astimezone().tzinfo),
-                connected_at=datetime.now(datetime.now().astimezone().tzinfo) if result["status"] == "connected" else number.connected_at
+                connected_at=datetime.now(datetime.now().astimezone().tzinfo) if result["status"] == "connected" else number.connected_at,
+                quality_rating=result.get("phone_number_info", {}).get("quality_rating", number.quality_rating),
+                messaging_limit_tier=result.get("phone_number_info", {}).get(
+                    "messaging_limit_tier", number.messaging_limit_tier
+                ),
             )
         )
         await db.execute(update_stmt)
@@ -571,7 +620,7 @@ async def disconnect_number(
     "/{number_id}/templates",
     response_model=List[Dict[str, Any]],
     summary="List templates (Meta)",
-    description="List message templates from Meta. Only for Official API connections.",
+    description="List all message templates from Meta (every page). Only for Official API connections.",
     responses={
         200: {"description": "List of templates"},
         400: {"description": "Invalid connection type"},
@@ -612,7 +661,7 @@ async def list_templates(
     )
 
     try:
-        templates = await meta_api.list_templates(
+        templates = await meta_api.list_all_templates(
             waba_id=number.whatsapp_business_account_id,
             status=status_filter
         )
@@ -735,6 +784,46 @@ async def list_local_templates(
     return templates
 
 
+@router.get(
+    "/{number_id}/templates/catalog",
+    response_model=TemplateCatalogResponse,
+    summary="Template catalog",
+    description=(
+        "Paginated catalog of templates usable by campaigns (approved and enabled by default). "
+        "Served from cache; refreshed by template sync and Meta template status webhooks."
+    ),
+    responses={
+        200: {"description": "Catalog page"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Number not found"},
+    },
+)
+async def get_template_catalog(
+    number_id: UUID,
+    status_filter: str = Query("APPROVED", alias="status", description="Filter: APPROVED, PENDING, REJECTED, PAUSED"),
+    skip: int = Query(0, ge=0),
+    limit: int = Query(50, ge=1, le=100),
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Get a page of the cached template catalog."""
+    from app.services.template_service import TemplateService
+
+    whatsapp_service = WhatsAppService(db)
+    template_service = TemplateService(db)
+
+    # Verify access
+    await whatsapp_service.get_by_id(number_id, current_user.organization_id)
+
+    return await template_service.get_catalog(
+        whatsapp_number_id=number_id,
+        organization_id=current_user.organization_id,
+        status=status_filter.upper(),
+        skip=skip,
+        limit=limit,
+    )
+
+
 @router.get(
     "/{number_id}/templates/{template_id}",
     response_model=TemplateResponse,
@@ -958,6 +1047,50 @@ async def get_rate_limit_usage(
     }
 
 
+@router.get(
+    "/{number_id}/rate-limit/tier",
+    summary="Get messaging tier utilization",
+    description=(
+        "Unique customers reached by business-initiated messages in the rolling 24h window "
+        "versus the Meta messaging limit tier, plus sends queued until a slot frees up"
+    ),
+    responses={
+        200: {"description": "Tier utilization"},
+        400: {"description": "Number is not an Official API connection"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Number not found"},
+    },
+)
+async def get_messaging_tier_utilization(
+    number_id: UUID,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Get messaging tier utilization."""
+    from app.core.whatsapp_rate_limit import get_tier_shaper
+
+    service = WhatsAppService(db)
+    whatsapp_number = await service.get_number(number_id, current_user.organization_id)
+
+    if not whatsapp_number:
+        raise HTTPException(status_code=404, detail="WhatsApp number not found")
+
+    shaper = get_tier_shaper(whatsapp_number)
+    if not shaper:
+        raise HTTPException(
+            status_code=400,
+            detail="Messaging tiers only apply to Official API connections"
+        )
+
+    return {
+        "whatsapp_number_id": str(number_id),
+        "phone_number": whatsapp_number.phone_number,
+        "quality_rating": whatsapp_number.quality_rating,
+        "tier_synced": whatsapp_number.messaging_limit_tier is not None,
+        **await shaper.get_utilization(),
+    }
+
+
 @router.post(
     "/{number_id}/rate-limit/reset",
     summary="Reset rate limit",
@@ -1002,3 +1135,396 @@ async def reset_rate_limit(
         "message": "Rate limit counters reset successfully",
         "whatsapp_number_id": str(number_id),
     }
+
+
+# ============= CLICK-TO-CHAT / QR CODE ENDPOINTS =============
+
+
+async def _get_official_meta_api(number_id: UUID, current_user: User, db: AsyncSession):
+    """MetaCloudAPI for an official number of the user's organization"""
+    from app.integrations.meta_api import MetaCloudAPI
+
+    number = await WhatsAppService(db).get_by_id(number_id, current_user.organization_id)
+
+    if number.connection_type != "official":
+        raise HTTPException(
+            status_code=400,
+            detail="Only available for Official API connections"
+        )
+
+    return MetaCloudAPI(
+        phone_number_id=number.phone_number_id,
+        access_token=number.access_token
+    )
+
+
+@router.get(
+    "/{number_id}/token",
+    summary="Check access token (Meta)",
+    description=(
+        "Inspect the number's access token with Meta (debug_token): status (valid, expiring, expired, "
+        "invalid), expiry date and scopes. Tokens that never expire (system users) have no expires_at."
+    ),
+    responses={
+        200: {"description": "Token status"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def get_access_token_status(
+    number_id: UUID,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Check access token."""
+    from app.integrations.meta_api import MetaAPIError
+    from app.services.whatsapp_token_service import WhatsAppTokenService
+
+    try:
+        return await WhatsAppTokenService(db).get_status(number_id, current_user.organization_id)
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 502, detail=f"Meta API error: {e.message}")
+
+
+@router.put(
+    "/{number_id}/token",
+    summary="Rotate access token (Meta)",
+    description=(
+        "Replace the number's access token without restarting. The new token is validated with Meta "
+        "first; an invalid or expired token is rejected and the current one is kept."
+    ),
+    responses={
+        200: {"description": "Token replaced"},
+        400: {"description": "Invalid connection type or token"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def rotate_access_token(
+    number_id: UUID,
+    data: AccessTokenRotate,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Rotate access token."""
+    from app.services.whatsapp_token_service import WhatsAppTokenService
+
+    return await WhatsAppTokenService(db).rotate_token(number_id, current_user.organization_id, data.access_token)
+
+
+@router.get(
+    "/{number_id}/click-to-chat",
+    response_model=ClickToChatLinkResponse,
+    summary="Build click-to-chat link",
+    description="Build a wa.me link that opens a chat with this number, optionally with prefilled text. Works for any connection type.",
+    responses={
+        200: {"description": "Link generated"},
+        400: {"description": "Invalid phone number"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Number not found"},
+    },
+)
+async def get_click_to_chat_link(
+    number_id: UUID,
+    text: Optional[str] = Query(None, max_length=1000, description="Prefilled message"),
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Build a wa.me click-to-chat link."""
+    from app.utils.click_to_chat import build_click_to_chat_link
+
+    number = await WhatsAppService(db).get_by_id(number_id, current_user.organization_id)
+
+    try:
+        link = build_click_to_chat_link(number.phone_number, text)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    return ClickToChatLinkResponse(link=link, phone_number=number.phone_number, text=text)
+
+
+@router.get(
+    "/{number_id}/qr-links",
+    response_model=List[QRLinkResponse],
+    summary="List QR codes (Meta)",
+    description="List QR codes with prefilled messages of this number. Only for Official API connections.",
+    responses={
+        200: {"description": "List of QR codes"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Number not found"},
+    },
+)
+async def list_qr_links(
+    number_id: UUID,
+    image_format: Optional[str] = Query(None, pattern="^(PNG|SVG)$", description="Include image URLs"),
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """List Meta QR codes."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        return await meta_api.list_qr_codes(image_format=image_format)
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+
+@router.post(
+    "/{number_id}/qr-links",
+    response_model=QRLinkResponse,
+    status_code=status.HTTP_201_CREATED,
+    summary="Create QR code (Meta)",
+    description="Create a scannable QR code and short link that open a chat with this number and a prefilled message. Only for Official API connections.",
+    responses={
+        201: {"description": "QR code created"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def create_qr_link(
+    number_id: UUID,
+    data: QRLinkCreate,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Create a Meta QR code."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        return await meta_api.create_qr_code(data.prefilled_message, image_format=data.image_format)
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+
+@router.put(
+    "/{number_id}/qr-links/{code}",
+    response_model=QRLinkResponse,
+    summary="Update QR code (Meta)",
+    description="Change the prefilled message of a QR code. The image and link stay the same.",
+    responses={
+        200: {"description": "QR code updated"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def update_qr_link(
+    number_id: UUID,
+    code: str,
+    data: QRLinkCreate,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Update a Meta QR code."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        return await meta_api.update_qr_code(code, data.prefilled_message)
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+
+@router.delete(
+    "/{number_id}/qr-links/{code}",
+    status_code=status.HTTP_204_NO_CONTENT,
+    summary="Delete QR code (Meta)",
+    description="Delete a QR code. Printed codes and links stop working (irreversible).",
+    responses={
+        204: {"description": "QR code deleted"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def delete_qr_link(
+    number_id: UUID,
+    code: str,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Delete a Meta QR code."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        await meta_api.delete_qr_code(code)
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+    return None
+
+
+# ============= TWO-STEP VERIFICATION / REGISTRATION ENDPOINTS =============
+
+
+@router.post(
+    "/{number_id}/two-step-pin",
+    summary="Set two-step verification PIN (Meta)",
+    description="Set or change the 6-digit two-step verification PIN of the number. Required to register the number again after migration. The PIN is not stored.",
+    responses={
+        200: {"description": "PIN set"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+        422: {"description": "PIN must have 6 digits"},
+    },
+)
+async def set_two_step_pin(
+    number_id: UUID,
+    data: TwoStepPinRequest,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Set the two-step verification PIN."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        success = await meta_api.set_two_step_pin(data.pin)
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+    return {"success": success, "whatsapp_number_id": str(number_id)}
+
+
+@router.post(
+    "/{number_id}/register",
+    summary="Register number (Meta)",
+    description="Register the number for Cloud API use with its two-step verification PIN (new numbers, migration or re-registration).",
+    responses={
+        200: {"description": "Number registered"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def register_number(
+    number_id: UUID,
+    data: TwoStepPinRequest,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Register the number on Cloud API."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        success = await meta_api.register_phone_number(data.pin)
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+    return {"success": success, "whatsapp_number_id": str(number_id)}
+
+
+@router.post(
+    "/{number_id}/deregister",
+    summary="Deregister number (Meta)",
+    description="Deregister the number from Cloud API before migrating it to another WABA or provider. Messages stop being delivered until it is registered again.",
+    responses={
+        200: {"description": "Number deregistered"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def deregister_number(
+    number_id: UUID,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Deregister the number from Cloud API."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        success = await meta_api.deregister_phone_number()
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+    return {"success": success, "whatsapp_number_id": str(number_id)}
+
+
+@router.get(
+    "/{number_id}/conversational-automation",
+    summary="Get conversational components (Meta)",
+    description="Get the ice breakers, commands and welcome message setting of the number.",
+    responses={
+        200: {"description": "Conversational components"},
+        400: {"description": "Invalid connection type"},
+        401: {"description": "Not authenticated"},
+        404: {"description": "Number not found"},
+    },
+)
+async def get_conversational_automation(
+    number_id: UUID,
+    current_user: User = Depends(get_current_user),
+    db: AsyncSession = Depends(get_db),
+):
+    """Get conversational components."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        return await meta_api.get_conversational_automation()
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 500, detail=f"Meta API error: {e.message}")
+
+
+@router.put(
+    "/{number_id}/conversational-automation",
+    summary="Configure conversational components (Meta)",
+    description=(
+        "Configure ice breakers, commands and the welcome message. With the welcome message enabled, "
+        "the chatbot's request_welcome event flow greets first-time users."
+    ),
+    responses={
+        200: {"description": "Conversational components updated"},
+        400: {"description": "Invalid connection type or components"},
+        401: {"description": "Not authenticated"},
+        403: {"description": "Requires admin role"},
+        404: {"description": "Number not found"},
+    },
+)
+async def update_conversational_automation(
+    number_id: UUID,
+    data: ConversationalAutomationUpdate,
+    current_user: User = Depends(get_current_admin),
+    db: AsyncSession = Depends(get_db),
+):
+    """Configure conversational components."""
+    from app.integrations.meta_api import MetaAPIError
+
+    meta_api = await _get_official_meta_api(number_id, current_user, db)
+
+    try:
+        success = await meta_api.update_conversational_automation(
+            enable_welcome_message=data.enable_welcome_message,
+            prompts=data.prompts,
+            commands=[command.model_dump() for command in data.commands] if data.commands is not None else None,
+        )
+    except MetaAPIError as e:
+        raise HTTPException(status_code=e.status_code or 400, detail=f"Meta API error: {e.message}")
+
+    return {"success": success, "whatsapp_number_id": str(number_id)}
@@ -3,6 +3,9 @@ API v1 Router
 Combines all v1 endpoints
 """
 
+from typing import Any, Dict, Optional
+from uuid import UUID
+
 from fastapi import APIRouter, Request, Query, HTTPException
 from fastapi.responses import PlainTextResponse
 
@@ -44,36 +47,50 @@ async def health_check():
     """
     return {"status": "ok"}
 
+
+async def _verify_whatsapp_webhook(request: Request, number_id: Optional[UUID] = None) -> str:
+    """Resolve the tenant of a verification request and echo hub.challenge"""
+    from app.core.database import async_session
+    from app.schemas.webhook import WebhookChallenge
+    from app.services.whatsapp_service import WhatsAppService
+
+    challenge = WebhookChallenge.from_query(request.query_params)
+
+    async with async_session() as db:
+        await WhatsAppService(db).resolve_webhook_challenge(challenge, number_id=number_id)
+
+    return challenge.challenge
+
+
 @api_router.get("/whatsapp/webhook", response_class=PlainTextResponse, include_in_schema=True)
 async def whatsapp_webhook_verify(
+    request: Request,
     mode: str = Query(None, alias="hub.mode"),
     token: str = Query(None, alias="hub.verify_token"),
     challenge: str = Query(None, alias="hub.challenge"),
 ):
     """
     PUBLIC WEBHOOK: Meta WhatsApp verification endpoint
+
+    The tenant is resolved by the verify token value (one token per number).
     """
-    if not mode or not token or not challenge:
-        raise HTTPException(status_code=400, detail="Missing parameters")
+    return await _verify_whatsapp_webhook(request)
 
-    if mode != "subscribe":
-        raise HTTPException(status_code=403, detail="Invalid mode")
 
-    # Verify token
-    from app.core.database import async_session
-    from sqlalchemy import select
-    from app.models.whatsapp_number import WhatsAppNumber
-
-    async with async_session() as db:
-        stmt = select(WhatsAppNumber).where(
-            WhatsAppNumber.webhook_verify_token == token,
-            WhatsAppNumber.deleted_at.is_(None),
-        )
-        result = await db.execute(stmt)
-        if not result.scalar_one_or_none():
-            raise HTTPException(status_code=403, detail="Invalid token")
+@api_router.get("/whatsapp/webhook/{number_id}", response_class=PlainTextResponse, include_in_schema=True)
+async def whatsapp_number_webhook_verify(
+    number_id: UUID,
+    request: Request,
+    mode: str = Query(None, alias="hub.mode"),
+    token: str = Query(None, alias="hub.verify_token"),
+    challenge: str = Query(None, alias="hub.challenge"),
+):
+    """
+    PUBLIC WEBHOOK: Meta WhatsApp verification endpoint for one number
 
-    return challenge
+    Callback URL per number; the token is checked only against that number.
+    """
+    return await _verify_whatsapp_webhook(request, number_id=number_id)
 
 
 @api_router.post("/whatsapp/webhook", include_in_schema=True)
@@ -83,8 +100,23 @@ async def whatsapp_webhook_receive(request: Request):
 
     Security: Verifies X-Hub-Signature-256 header to ensure request is from Meta
     """
+    return await _receive_whatsapp_webhook(request)
+
+
+@api_router.post("/whatsapp/webhook/{number_id}", include_in_schema=True)
+async def whatsapp_number_webhook_receive(number_id: UUID, request: Request):
+    """
+    PUBLIC WEBHOOK: Meta WhatsApp message endpoint for one number
+
+    Rejects payloads whose phone_number_id does not belong to the number in the path.
+    """
+    return await _receive_whatsapp_webhook(request, number_id=number_id)
+
+
+async def _receive_whatsapp_webhook(request: Request, number_id: Optional[UUID] = None):
+    """Verify signature, resolve the number and process (or forward) the payload"""
     from app.core.database import async_session
-    from app.services.whatsapp_service import WhatsAppService
+    from app.core.fault_injection import InjectedFault, fault_injector
     from app.core.security import verify_whatsapp_signature
     from app.models.whatsapp_number import WhatsAppNumber
     import logging
@@ -114,76 +146,160 @@ async def whatsapp_webhook_receive(request: Request):
             detail="Invalid JSON payload"
         )
 
-    # Extract phone_number_id to find which WhatsApp number this webhook is for
-    phone_number_id = None
+    # Fault injection (staging): latency, 5xx so Meta redelivers, or a lost delivery
     try:
-        entries = body.get("entry", [])
-        for entry in entries:
-            changes = entry.get("changes", [])
-            for change in changes:
-                value = change.get("value", {})
-                metadata = value.get("metadata", {})
-                phone_number_id = metadata.get("phone_number_id")
-                if phone_number_id:
-                    break
-            if phone_number_id:
-                break
-    except Exception as e:
-        logger.error(f"Failed to extract phone_number_id: {e}")
+        if await fault_injector.inbound("webhook"):
+            return {"status": "ok"}
+    except InjectedFault as e:
+        raise HTTPException(status_code=e.status_code, detail=str(e))
 
-    if not phone_number_id:
+    # One part per phone_number_id: a batch may carry events of several numbers (tenants)
+    from pytake_client.processor import UNROUTABLE_MISSING, split_by_number
+    from app.core.webhook_routing import number_router
+
+    parts = split_by_number(body)
+    account_part = parts.pop(None, None)  # Template/quality updates ride with the first number
+
+    if not parts:
+        await number_router.record_unroutable(UNROUTABLE_MISSING)
         logger.warning("No phone_number_id found in webhook payload")
         raise HTTPException(
             status_code=400,
             detail="Invalid webhook payload: missing phone_number_id"
         )
 
-    # Get WhatsApp number and app_secret from database
     async with async_session() as db:
-        from sqlalchemy import select
-
-        stmt = select(WhatsAppNumber).where(
-            WhatsAppNumber.phone_number_id == phone_number_id
-        )
-        result = await db.execute(stmt)
-        whatsapp_number = result.scalar_one_or_none()
+        # Resolve each number (unknown ones are counted as unroutable and dropped)
+        routed = []
+        for phone_number_id, part in parts.items():
+            route = await number_router.route_number(phone_number_id)
+            whatsapp_number = (
+                await db.get(WhatsAppNumber, route.config["whatsapp_number_id"]) if route else None
+            )
+            if route and (not whatsapp_number or whatsapp_number.deleted_at):
+                number_router.invalidate(phone_number_id)
+                whatsapp_number = None
+            if not whatsapp_number:
+                logger.warning(f"WhatsApp number not found for phone_number_id: {phone_number_id}")
+                continue
+
+            if number_id and whatsapp_number.id != number_id:
+                logger.warning(f"Webhook for {phone_number_id} received on callback of number {number_id}")
+                raise HTTPException(
+                    status_code=403,
+                    detail="Payload does not belong to this number"
+                )
+            routed.append((whatsapp_number, part))
 
-        if not whatsapp_number:
-            logger.warning(f"WhatsApp number not found for phone_number_id: {phone_number_id}")
+        if not routed:
             raise HTTPException(
                 status_code=404,
                 detail="WhatsApp number not found"
             )
 
-        # Verify signature if app_secret is configured
-        if whatsapp_number.app_secret:
-            is_valid = verify_whatsapp_signature(
-                payload=raw_body,
-                signature=signature,
-                app_secret=whatsapp_number.app_secret
-            )
+        # Verify signature with the app_secret of every number in the batch
+        for whatsapp_number, _ in routed:
+            if whatsapp_number.app_secret:
+                is_valid = verify_whatsapp_signature(
+                    payload=raw_body,
+                    signature=signature,
+                    app_secret=whatsapp_number.app_secret
+                )
 
-            if not is_valid:
-                logger.error(f"Invalid webhook signature for phone_number_id: {phone_number_id}")
-                raise HTTPException(
-                    status_code=403,
-                    detail="Invalid webhook signature"
+                if not is_valid:
+                    logger.error(
+                        f"Invalid webhook signature for phone_number_id: {whatsapp_number.phone_number_id}"
+                    )
+                    raise HTTPException(
+                        status_code=403,
+                        detail="Invalid webhook signature"
+                    )
+
+                logger.info(f"✅ Webhook signature verified for {whatsapp_number.phone_number}")
+            else:
+                logger.warning(
+                    f"⚠️ Webhook signature verification skipped - "
+                    f"no app_secret configured for {whatsapp_number.phone_number}"
                 )
 
-            logger.info(f"✅ Webhook signature verified for {whatsapp_number.phone_number}")
-        else:
-            logger.warning(
-                f"⚠️ Webhook signature verification skipped - "
-                f"no app_secret configured for {whatsapp_number.phone_number}"
-            )
+        if account_part:
+            routed[0][1]["entry"].extend(account_part["entry"])
 
-        # Process webhook
-        service = WhatsAppService(db)
-        await service.process_webhook(body)
+        # Replies sent while processing are child spans (traceparent from the caller, if any)
+        from app.core.tracing import tracer
+
+        for whatsapp_number, part in routed:
+            with tracer.start_span(
+                "whatsapp.webhook",
+                parent=request.headers.get("traceparent"),
+                **{"whatsapp.phone_number_id": whatsapp_number.phone_number_id},
+            ):
+                await _process_number_webhook(db, whatsapp_number, part)
 
     return {"status": "ok"}
 
 
+async def _process_number_webhook(db, whatsapp_number, body: Dict[str, Any]) -> None:
+    """Store and process (or forward to its region) the part of a webhook for one number"""
+    import logging
+
+    from sqlalchemy import select
+
+    logger = logging.getLogger(__name__)
+
+    # Keep the raw payload so failed or skipped events can be replayed
+    from app.services.webhook_event_service import WebhookEventService
+
+    webhook_events = WebhookEventService(db)
+    stored = await webhook_events.record(
+        body,
+        source="whatsapp",
+        organization_id=whatsapp_number.organization_id,
+        whatsapp_number_id=whatsapp_number.id,
+    )
+    event_id = stored.id if stored else None
+
+    # Multi-region: forward to the region serving this tenant
+    from app.core.config import settings
+    from app.core.region_router import is_multi_region, region_router, regional_queue
+
+    if is_multi_region():
+        from app.models.organization import Organization
+
+        home_region = await db.scalar(
+            select(Organization.home_region).where(
+                Organization.id == whatsapp_number.organization_id
+            )
+        )
+        region = await region_router.resolve_region(
+            whatsapp_number.organization_id, home_region
+        )
+
+        if region != settings.DEPLOYMENT_REGION:
+            from app.core.sla_queue import sla_scheduler
+            from app.core.tracing import current_traceparent
+            from app.tasks.webhook_tasks import process_webhook
+
+            # Promoted ahead of the backlog if the region's workers fall behind
+            await sla_scheduler.submit(
+                process_webhook,
+                args=[body],
+                kwargs={
+                    "event_id": str(event_id) if event_id else None,
+                    "traceparent": current_traceparent(),
+                },
+                queue=regional_queue("webhooks", region),
+                sla_seconds=settings.SLA_WEBHOOK_SECONDS,
+                organization_id=whatsapp_number.organization_id,
+            )
+            await webhook_events.mark(event_id, "queued")
+            logger.info(f"🌎 Webhook forwarded to region {region}")
+            return
+
+    # Process webhook (outcome recorded on the stored event)
+    await webhook_events.process(event_id, body)
+
+
 # Include all endpoint routers using lazy loading
 auth = _load_endpoint_module("auth")
 api_router.include_router(auth.router, prefix="/auth", tags=["Authentication"])
@@ -194,6 +310,9 @@ api_router.include_router(organizations.router, prefix="/organizations", tags=["
 users = _load_endpoint_module("users")
 api_router.include_router(users.router, prefix="/users", tags=["Users"])
 
+me = _load_endpoint_module("me")
+api_router.include_router(me.router, prefix="/me", tags=["Authentication"])
+
 contacts = _load_endpoint_module("contacts")
 api_router.include_router(contacts.router, prefix="/contacts", tags=["Contacts"])
 
@@ -224,9 +343,18 @@ api_router.include_router(analytics.router, prefix="/analytics", tags=["Analytic
 dashboard = _load_endpoint_module("dashboard")
 api_router.include_router(dashboard.router, prefix="/dashboard", tags=["Dashboard"])
 
+annotations = _load_endpoint_module("annotations")
+api_router.include_router(annotations.router, prefix="/annotations", tags=["Dashboard"])
+
 flow_automations = _load_endpoint_module("flow_automations")
 api_router.include_router(flow_automations.router, prefix="/flow-automations", tags=["Flow Automations"])
 
+flow_triggers = _load_endpoint_module("flow_triggers")
+api_router.include_router(flow_triggers.router, prefix="/flow-triggers", tags=["Flow Triggers"])
+
+flow_events = _load_endpoint_module("flow_events")
+api_router.include_router(flow_events.router, prefix="/flow-events", tags=["Flow Events"])
+
 secrets = _load_endpoint_module("secrets")
 api_router.include_router(secrets.router, prefix="/secrets", tags=["Secrets"])
 
@@ -245,6 +373,18 @@ api_router.include_router(websocket.router, tags=["WebSocket"])
 notifications = _load_endpoint_module("notifications")
 api_router.include_router(notifications.router, tags=["Notifications"])
 
+dunning = _load_endpoint_module("dunning")
+api_router.include_router(dunning.router, prefix="/dunning", tags=["Dunning"])
+
+ad_audiences = _load_endpoint_module("ad_audiences")
+api_router.include_router(ad_audiences.router, prefix="/ad-audiences", tags=["Ad Audiences"])
+
+webhook_events = _load_endpoint_module("webhook_events")
+api_router.include_router(webhook_events.router, prefix="/webhook-events", tags=["Webhooks"])
+
+debug = _load_endpoint_module("debug")
+api_router.include_router(debug.router, prefix="/debug", tags=["Webhooks"])
+
 # ============================================
 # FLOWS ENDPOINTS (Mock for now)
 # ============================================
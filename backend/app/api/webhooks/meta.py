@@ -5,7 +5,7 @@ Receives and processes webhooks from Meta Cloud API for WhatsApp Business.
 Handles message status updates (sent, delivered, read, failed).
 
 Webhook Flow:
-1. Verify signature (HMAC SHA256)
+1. Verify signature (HMAC SHA256, WebhookSignatureMiddleware)
 2. Parse event type
 3. Process message_status events
 4. Update campaign stats
@@ -15,20 +15,31 @@ Meta Webhook Events:
 - messages: New incoming messages
 - message_status: Status updates (sent, delivered, read, failed)
 - message_template_status_update: Template approval status
+- phone_number_quality_update: Quality flag and messaging limit tier changes
 """
 
 import logging
-import hmac
-import hashlib
-from typing import Dict, Any, Optional
+from typing import Dict, Any
 from datetime import datetime
 
-from fastapi import APIRouter, Request, Response, HTTPException, Query, Depends, Header
+from fastapi import APIRouter, Request, Response, HTTPException, Query, Depends
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.core.database import async_session
 from app.core.config import settings
+from app.core.fault_injection import InjectedFault, fault_injector
+from app.core.webhook_dedup import get_webhook_processor
+from app.core.webhook_routing import number_router
+from app.services.webhook_event_service import WebhookEventService
 from app.services.webhook_service import WebhookService
+from pytake_client.processor import message_dedup_key, status_dedup_key
+from pytake_client.webhooks import (
+    ECHO_FIELDS,
+    PhoneNumberQualityUpdate,
+    TemplateStatusUpdate,
+    WebhookChallenge,
+    verify_signature,
+)
 
 router = APIRouter()
 logger = logging.getLogger(__name__)
@@ -46,21 +57,7 @@ def verify_webhook_signature(payload: bytes, signature: str, secret: str) -> boo
     Returns:
         True if signature is valid
     """
-    if not signature or not signature.startswith("sha256="):
-        return False
-    
-    expected_signature = signature.split("sha256=")[1]
-    
-    # Calculate HMAC
-    hmac_obj = hmac.new(
-        secret.encode("utf-8"),
-        payload,
-        hashlib.sha256
-    )
-    calculated_signature = hmac_obj.hexdigest()
-    
-    # Constant-time comparison
-    return hmac.compare_digest(calculated_signature, expected_signature)
+    return verify_signature(payload, signature, secret)
 
 
 @router.get("/verify")
@@ -81,15 +78,21 @@ async def verify_webhook(
     """
     logger.info(f"📥 Webhook verification request: mode={hub_mode}")
     
-    # Verify token matches
-    if hub_verify_token != settings.META_WEBHOOK_VERIFY_TOKEN:
-        logger.error("❌ Invalid verify token")
-        raise HTTPException(status_code=403, detail="Invalid verify token")
-    
+    challenge = WebhookChallenge(mode=hub_mode, verify_token=hub_verify_token, challenge=hub_challenge)
+
     # Verify mode is subscribe
     if hub_mode != "subscribe":
         logger.error(f"❌ Invalid mode: {hub_mode}")
         raise HTTPException(status_code=400, detail="Invalid mode")
+
+    # Deployment-wide token, or the verify token of any registered number
+    if not challenge.matches(settings.META_WEBHOOK_VERIFY_TOKEN):
+        from app.services.whatsapp_service import WhatsAppService
+
+        async with async_session() as db:
+            if not await WhatsAppService(db).verify_webhook_token(hub_verify_token):
+                logger.error("❌ Invalid verify token")
+                raise HTTPException(status_code=403, detail="Invalid verify token")
     
     # Return challenge
     logger.info(f"✅ Webhook verified successfully")
@@ -97,10 +100,7 @@ async def verify_webhook(
 
 
 @router.post("/")
-async def receive_webhook(
-    request: Request,
-    x_hub_signature_256: Optional[str] = Header(None, alias="X-Hub-Signature-256"),
-):
+async def receive_webhook(request: Request):
     """
     Receive webhook events from Meta Cloud API
     
@@ -109,26 +109,14 @@ async def receive_webhook(
     - messages: incoming messages (future)
     
     Security:
-    - Verifies HMAC SHA256 signature
+    - HMAC SHA256 signature verified by WebhookSignatureMiddleware (app.main)
+      before this handler runs, when META_WEBHOOK_SECRET is configured
     - Validates request structure
+
+    Redelivered messages and statuses (same id) are skipped, see
+    app.core.webhook_dedup. The raw payload is stored with its outcome so
+    failed or skipped events can be replayed (WebhookEventService).
     """
-    # Read raw body for signature verification
-    body = await request.body()
-    
-    # Verify signature if configured
-    if settings.META_WEBHOOK_SECRET:
-        if not x_hub_signature_256:
-            logger.error("❌ Missing X-Hub-Signature-256 header")
-            raise HTTPException(status_code=401, detail="Missing signature")
-        
-        if not verify_webhook_signature(
-            body,
-            x_hub_signature_256,
-            settings.META_WEBHOOK_SECRET
-        ):
-            logger.error("❌ Invalid webhook signature")
-            raise HTTPException(status_code=403, detail="Invalid signature")
-    
     # Parse JSON
     try:
         data = await request.json()
@@ -137,6 +125,13 @@ async def receive_webhook(
         raise HTTPException(status_code=400, detail="Invalid JSON")
     
     logger.info(f"📥 Webhook received: {data.get('object', 'unknown')}")
+
+    # Fault injection (staging): latency, 5xx so Meta redelivers, or a lost delivery
+    try:
+        if await fault_injector.inbound("webhook"):
+            return {"status": "ok"}
+    except InjectedFault as e:
+        raise HTTPException(status_code=e.status_code, detail=str(e))
     
     # Validate object type
     if data.get("object") != "whatsapp_business_account":
@@ -144,13 +139,27 @@ async def receive_webhook(
         return {"status": "ignored"}
     
     # Process entries
+    processor = get_webhook_processor()
     async with async_session() as db:
         webhook_service = WebhookService(db)
+        webhook_events = WebhookEventService(db)
+        stored = await webhook_events.record(data, source="meta")
+        errors = []
+        handled = 0
         
         for entry in data.get("entry", []):
             for change in entry.get("changes", []):
                 field = change.get("field")
                 value = change.get("value", {})
+
+                # Events of numbers no organization owns are counted and dropped
+                if field == "messages" or field in ECHO_FIELDS:
+                    phone_number_id = value.get("metadata", {}).get("phone_number_id")
+                    if not await number_router.route_number(phone_number_id):
+                        continue
+
+                if field in ("messages", "message_template_status_update", "phone_number_quality_update", *ECHO_FIELDS):
+                    handled += 1
                 
                 if field == "messages":
                     # Get metadata for identifying WhatsApp number
@@ -160,15 +169,24 @@ async def receive_webhook(
                     # Process message status updates
                     statuses = value.get("statuses", [])
                     for status in statuses:
+                        key = status_dedup_key(status)
+                        if not await processor.claim(key):
+                            logger.info(f"⏭️ Duplicate status skipped: {key}")
+                            continue
                         try:
                             await webhook_service.process_message_status(status)
                         except Exception as e:
                             logger.error(f"❌ Error processing status: {e}")
+                            errors.append(f"status {status.get('id')}: {e}")
                             # Continue processing other statuses
                     
                     # Process incoming messages
                     messages = value.get("messages", [])
                     for message in messages:
+                        key = message_dedup_key(message)
+                        if not await processor.claim(key):
+                            logger.info(f"⏭️ Duplicate message skipped: {key}")
+                            continue
                         try:
                             await webhook_service.process_incoming_message(
                                 message=message,
@@ -177,13 +195,54 @@ async def receive_webhook(
                             )
                         except Exception as e:
                             logger.error(f"❌ Error processing incoming message: {e}")
+                            errors.append(f"message {message.get('id')}: {e}")
                 
                 elif field == "message_template_status_update":
-                    # Template status update (future)
-                    logger.info(f"📋 Template status update: {value}")
+                    # Template approval status: refresh local templates and catalog cache
+                    from app.services.template_service import TemplateService
+
+                    try:
+                        await TemplateService(db).apply_status_update(
+                            entry.get("id"), TemplateStatusUpdate.from_webhook(value)
+                        )
+                    except Exception as e:
+                        logger.error(f"❌ Error processing template status update: {e}")
+                        errors.append(f"template status update: {e}")
                 
+                elif field == "phone_number_quality_update":
+                    # Messaging limit tier changes feed the outbound tier shaper
+                    from app.services.whatsapp_service import WhatsAppService
+
+                    try:
+                        await WhatsAppService(db).apply_quality_update(
+                            entry.get("id"), PhoneNumberQualityUpdate.from_webhook(value)
+                        )
+                    except Exception as e:
+                        logger.error(f"❌ Error processing phone number quality update: {e}")
+                        errors.append(f"phone number quality update: {e}")
+                
+                elif field in ECHO_FIELDS:
+                    # Messages sent from the WhatsApp Business app (coexistence numbers)
+                    from app.services.whatsapp_service import WhatsAppService
+
+                    try:
+                        await WhatsAppService(db).process_webhook(
+                            {"entry": [{"id": entry.get("id"), "changes": [change]}]}
+                        )
+                    except Exception as e:
+                        logger.error(f"❌ Error processing message echoes: {e}")
+                        errors.append(f"message echoes: {e}")
+
                 else:
                     logger.warning(f"⚠️ Unknown field: {field}")
+
+        event_id = stored.id if stored else None
+        if errors:
+            await webhook_events.mark(event_id, "failed", "\n".join(errors))
+        elif handled:
+            await webhook_events.mark(event_id, "processed")
+        else:
+            await webhook_events.mark(event_id, "skipped", "No supported field in the payload")
     
     # Return 200 OK to acknowledge receipt
     return {"status": "ok"}
@@ -0,0 +1,267 @@
+"""
+Campaign throttling
+
+Enforces the sending limits of a campaign in the dispatcher (see
+process_batch), across every worker sending its batches:
+
+- messages_per_minute / messages_per_hour / messages_per_day: one counter
+  per window in Redis (fixed UTC windows: minute, hour, day), checked and
+  incremented atomically, so parallel batches share the budget. The hour
+  limit is the campaign's messages_per_hour column, the others come from
+  campaign.throttle
+- business_hours: sends only while open, in the organization business hours
+  format (see app.utils.business_hours). With use_contact_timezone the hours
+  are read in the contact's timezone (contact.attributes["timezone"], IANA
+  name), falling back to the business hours timezone
+
+A send over a limit gets the moment it may go out instead; the dispatcher
+waits for short holds and defers the batch for longer ones.
+estimate_send_duration forecasts how long an audience takes under the same
+limits (used by the campaign dry run).
+"""
+
+import logging
+import math
+import time
+from dataclasses import dataclass
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, List, Optional, Tuple
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+from app.core.redis import RedisClient, rate_limit_redis
+from app.utils.business_hours import business_hours_status
+
+logger = logging.getLogger(__name__)
+
+KEY_PREFIX = "campaign:throttle:"
+
+WINDOW_MINUTE = "minute"
+WINDOW_HOUR = "hour"
+WINDOW_DAY = "day"
+WINDOW_SECONDS = {WINDOW_MINUTE: 60, WINDOW_HOUR: 3600, WINDOW_DAY: 86400}
+
+REASON_BUSINESS_HOURS = "business_hours"
+
+# Longest sending time estimate_send_duration forecasts
+MAX_ESTIMATE_HOURS = 366 * 24
+
+# Takes one send from every window, or none if a window is full.
+# KEYS: window counters; ARGV: limit and ttl of each window
+RESERVE_SCRIPT = """
+for i, key in ipairs(KEYS) do
+    if tonumber(redis.call("get", key) or "0") >= tonumber(ARGV[i * 2 - 1]) then
+        return i
+    end
+end
+for i, key in ipairs(KEYS) do
+    redis.call("incr", key)
+    redis.call("expire", key, ARGV[i * 2])
+end
+return 0
+"""
+
+
+@dataclass
+class ThrottleDecision:
+    """Whether a campaign send may go out now"""
+
+    allowed: bool
+    send_at: datetime  # aware UTC
+    reason: Optional[str] = None  # full window or business_hours
+
+    @property
+    def wait_seconds(self) -> float:
+        return max(0.0, (self.send_at - datetime.now(timezone.utc)).total_seconds())
+
+
+class RedisThrottleStore:
+    """Window counters in Redis"""
+
+    def __init__(self, client: RedisClient):
+        self.client = client
+
+    async def reserve(self, keys: List[str], limits: List[int], ttls: List[int]) -> int:
+        args = [value for pair in zip(limits, ttls) for value in pair]
+        return int(await self.client.eval(RESERVE_SCRIPT, len(keys), *keys, *args))
+
+    async def release(self, keys: List[str]) -> None:
+        for key in keys:
+            await self.client.decr(key)
+
+
+class InMemoryThrottleStore:
+    """Window counters of a single process"""
+
+    def __init__(self):
+        self.counters: Dict[str, int] = {}
+
+    async def reserve(self, keys: List[str], limits: List[int], ttls: List[int]) -> int:
+        for index, (key, limit) in enumerate(zip(keys, limits), start=1):
+            if self.counters.get(key, 0) >= limit:
+                return index
+        for key in keys:
+            self.counters[key] = self.counters.get(key, 0) + 1
+        return 0
+
+    async def release(self, keys: List[str]) -> None:
+        for key in keys:
+            self.counters[key] = self.counters.get(key, 0) - 1
+
+
+def throttle_limits(campaign: Any) -> Dict[str, int]:
+    """Limit per window of a campaign (windows without a limit are left out)"""
+    throttle = campaign.throttle or {}
+    limits = {
+        WINDOW_MINUTE: throttle.get("messages_per_minute"),
+        WINDOW_HOUR: campaign.messages_per_hour,
+        WINDOW_DAY: throttle.get("messages_per_day"),
+    }
+    return {window: int(limit) for window, limit in limits.items() if limit}
+
+
+def estimate_send_duration(campaign: Any, recipients: int, start: datetime) -> Optional[timedelta]:
+    """
+    Time to send to an audience under the campaign limits
+
+    Walks the UTC hours from start: each open hour (business_hours, checked at
+    the start of the hour, in the business hours timezone) sends up to the
+    hour limit (or 60x the minute limit), within the day limit.
+
+    Args:
+        start: Aware datetime the sending starts
+
+    Returns:
+        Duration, or None when it would take longer than MAX_ESTIMATE_HOURS
+    """
+    limits = throttle_limits(campaign)
+    hourly = float(min(limits.get(WINDOW_HOUR, math.inf), limits.get(WINDOW_MINUTE, math.inf) * 60))
+    daily = float(limits.get(WINDOW_DAY, math.inf))
+    business_hours = (campaign.throttle or {}).get("business_hours") or None
+
+    remaining = float(recipients)
+    moment = start.astimezone(timezone.utc)
+    day, sent_today = moment.date(), 0.0
+    for _ in range(MAX_ESTIMATE_HOURS):
+        if remaining <= 0:
+            return moment - start
+        if moment.date() != day:
+            day, sent_today = moment.date(), 0.0
+        hour_end = moment.replace(minute=0, second=0, microsecond=0) + timedelta(hours=1)
+
+        if not business_hours or business_hours_status(business_hours, moment).is_open:
+            capacity = min(hourly * (hour_end - moment).total_seconds() / 3600, daily - sent_today)
+            if remaining <= capacity:
+                return moment + timedelta(hours=remaining / hourly) - start
+            remaining -= capacity
+            sent_today += capacity
+        moment = hour_end
+    return None
+
+
+def contact_timezone(contact: Any) -> Optional[str]:
+    """IANA timezone of a contact, if known and valid"""
+    name = (getattr(contact, "attributes", None) or {}).get("timezone")
+    if not isinstance(name, str) or not name:
+        return None
+    try:
+        ZoneInfo(name)
+    except (ZoneInfoNotFoundError, ValueError):
+        return None
+    return name
+
+
+class CampaignThrottle:
+    """Sending limits of one campaign"""
+
+    def __init__(self, campaign: Any, store: Any):
+        self.campaign_id = str(campaign.id)
+        self.store = store
+        self.limits = throttle_limits(campaign)
+        throttle = campaign.throttle or {}
+        self.business_hours = throttle.get("business_hours") or None
+        self.use_contact_timezone = bool(throttle.get("use_contact_timezone"))
+
+    def check_hours(self, contact: Any, now: Optional[datetime] = None) -> ThrottleDecision:
+        """Whether the contact may be messaged now under the business hours"""
+        now = now or datetime.now(timezone.utc)
+        if not self.business_hours:
+            return ThrottleDecision(allowed=True, send_at=now)
+
+        config = self.business_hours
+        zone = contact_timezone(contact) if self.use_contact_timezone else None
+        if zone:
+            config = {**config, "timezone": zone}
+
+        status = business_hours_status(config, now)
+        if status.is_open:
+            return ThrottleDecision(allowed=True, send_at=now)
+        # No opening found ahead: retry once the search horizon has passed
+        send_at = status.next_open.astimezone(timezone.utc) if status.next_open else None
+        return ThrottleDecision(
+            allowed=False,
+            send_at=send_at or datetime.fromtimestamp(now.timestamp() + 86400, tz=timezone.utc),
+            reason=REASON_BUSINESS_HOURS,
+        )
+
+    async def reserve(self, now: Optional[float] = None) -> ThrottleDecision:
+        """
+        Take one send from every window of the campaign
+
+        Args:
+            now: Current timestamp (defaults to time.time())
+
+        Returns:
+            ThrottleDecision (allowed, or the start of the next window of the full one)
+        """
+        now = now if now is not None else time.time()
+        if not self.limits:
+            return ThrottleDecision(allowed=True, send_at=_utc(now))
+
+        windows, keys, limits, ttls = self._windows(now)
+        try:
+            full = await self.store.reserve(keys, limits, ttls)
+        except Exception as e:
+            logger.error(f"Error reserving throttle slot for campaign {self.campaign_id}: {e}")
+            # On error, allow sending (fail open)
+            return ThrottleDecision(allowed=True, send_at=_utc(now))
+
+        if not full:
+            return ThrottleDecision(allowed=True, send_at=_utc(now))
+
+        window = windows[full - 1]
+        seconds = WINDOW_SECONDS[window]
+        send_at = _utc((int(now) // seconds + 1) * seconds)
+        logger.info(
+            f"⏳ Campaign {self.campaign_id} reached its per-{window} limit "
+            f"({self.limits[window]}), next send at {send_at.isoformat()}"
+        )
+        return ThrottleDecision(allowed=False, send_at=send_at, reason=window)
+
+    async def release(self, now: Optional[float] = None) -> None:
+        """Give back a send reserved but not made"""
+        if not self.limits:
+            return
+        _, keys, _, _ = self._windows(now if now is not None else time.time())
+        try:
+            await self.store.release(keys)
+        except Exception as e:
+            logger.error(f"Error releasing throttle slot for campaign {self.campaign_id}: {e}")
+
+    def _windows(self, now: float) -> Tuple[List[str], List[str], List[int], List[int]]:
+        windows = list(self.limits)
+        keys = [
+            f"{KEY_PREFIX}{self.campaign_id}:{window}:{int(now) // WINDOW_SECONDS[window]}"
+            for window in windows
+        ]
+        limits = [self.limits[window] for window in windows]
+        ttls = [WINDOW_SECONDS[window] * 2 for window in windows]
+        return windows, keys, limits, ttls
+
+
+def _utc(timestamp: float) -> datetime:
+    return datetime.fromtimestamp(timestamp, tz=timezone.utc)
+
+
+def get_campaign_throttle(campaign: Any) -> CampaignThrottle:
+    """Throttle of a campaign with counters shared by every worker"""
+    return CampaignThrottle(campaign, RedisThrottleStore(rate_limit_redis))
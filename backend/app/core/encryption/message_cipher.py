@@ -0,0 +1,129 @@
+"""
+Message Content Cipher - Per-tenant encryption of message bodies at rest
+
+Envelope encryption:
+- Each organization gets its own Fernet data key
+- The data key is stored wrapped (encrypted) by the default encryption service
+- Message content is stored as an encrypted envelope instead of plain JSON
+
+Envelope format stored in messages.content:
+    {"encrypted": true, "v": 1, "ciphertext": "<fernet token>"}
+
+Usage:
+    from app.core.encryption.message_cipher import message_cipher
+
+    wrapped_key = message_cipher.generate_data_key()
+    envelope = message_cipher.encrypt_content({"text": "hi"}, wrapped_key)
+    content = message_cipher.decrypt_content(envelope, wrapped_key)
+"""
+
+import json
+import logging
+from functools import lru_cache
+from typing import Any, Dict
+
+from cryptography.fernet import Fernet, InvalidToken
+
+from app.core.encryption.base import EncryptionError, DecryptionError
+
+logger = logging.getLogger(__name__)
+
+ENVELOPE_VERSION = 1
+
+
+@lru_cache(maxsize=256)
+def _unwrap_data_key(wrapped_key: str) -> Fernet:
+    """Unwrap a tenant data key with the default encryption service (cached)"""
+    from app.core.encryption.factory import encryption_service
+
+    if encryption_service is None:
+        raise DecryptionError("Default encryption service is not configured")
+
+    return Fernet(encryption_service.decrypt(wrapped_key).encode("utf-8"))
+
+
+class MessageContentCipher:
+    """Encrypts and decrypts message content with a tenant data key"""
+
+    def generate_data_key(self) -> str:
+        """
+        Generate a new tenant data key
+
+        Returns:
+            Data key wrapped by the default encryption service
+
+        Raises:
+            EncryptionError: If the default encryption service is not configured
+        """
+        from app.core.encryption.factory import encryption_service
+
+        if encryption_service is None:
+            raise EncryptionError("Default encryption service is not configured")
+
+        return encryption_service.encrypt(Fernet.generate_key().decode("utf-8"))
+
+    @staticmethod
+    def is_encrypted(content: Any) -> bool:
+        """Check if a stored content value is an encrypted envelope"""
+        return (
+            isinstance(content, dict)
+            and content.get("encrypted") is True
+            and "ciphertext" in content
+        )
+
+    def encrypt_content(self, content: Dict[str, Any], wrapped_key: str) -> Dict[str, Any]:
+        """
+        Encrypt message content
+
+        Args:
+            content: Plain message content dict
+            wrapped_key: Tenant data key (wrapped)
+
+        Returns:
+            Encrypted envelope dict
+        """
+        if self.is_encrypted(content):
+            return content
+
+        try:
+            payload = json.dumps(content or {}, ensure_ascii=False).encode("utf-8")
+            token = _unwrap_data_key(wrapped_key).encrypt(payload)
+        except Exception as e:
+            logger.error(f"Message encryption failed: {str(e)}")
+            raise EncryptionError(f"Failed to encrypt message content: {str(e)}")
+
+        return {
+            "encrypted": True,
+            "v": ENVELOPE_VERSION,
+            "ciphertext": token.decode("utf-8"),
+        }
+
+    def decrypt_content(self, content: Dict[str, Any], wrapped_key: str) -> Dict[str, Any]:
+        """
+        Decrypt an encrypted envelope (plain content is returned unchanged)
+
+        Args:
+            content: Stored content (envelope or plain dict)
+            wrapped_key: Tenant data key (wrapped)
+
+        Returns:
+            Plain message content dict
+
+        Raises:
+            DecryptionError: If the envelope cannot be decrypted
+        """
+        if not self.is_encrypted(content):
+            return content
+
+        try:
+            payload = _unwrap_data_key(wrapped_key).decrypt(
+                content["ciphertext"].encode("utf-8")
+            )
+            return json.loads(payload.decode("utf-8"))
+        except InvalidToken:
+            raise DecryptionError("Invalid message envelope - wrong tenant key or corrupted data")
+        except Exception as e:
+            raise DecryptionError(f"Failed to decrypt message content: {str(e)}")
+
+
+message_cipher = MessageContentCipher()
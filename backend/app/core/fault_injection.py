@@ -0,0 +1,279 @@
+"""
+Fault injection for resilience testing (development/staging only)
+
+Injects latency, errors and dropped deliveries into outbound integrations,
+the task queue and inbound webhooks so retries, circuit breakers and
+dead-letter handling can be exercised in integration tests:
+
+    FAULT_INJECTION_ENABLED=true
+    FAULT_INJECTION_RULES="meta_api.latency=0.5:800,meta_api.error=0.1:503,webhook.drop=0.2"
+
+Each rule is ``target.fault=rate[:param]`` where rate is the fraction of
+calls affected (0-1) and param is the latency in ms or the HTTP status.
+
+Targets:
+    meta_api        Meta Cloud API client
+    meta_ads_api    Meta Custom Audiences (ad audience sync)
+    evolution_api   Evolution API client
+    http_connector  Flow API Call nodes and webhook actions (ERP, CRM...)
+    queue           Celery task execution
+    webhook         Inbound Meta webhooks
+
+Faults:
+    latency  Delay before the call
+    error    HTTP targets and webhooks answer with the status (default 503);
+             queue tasks raise InjectedFault (and follow their retry policy)
+    drop     HTTP targets fail with a connection error; queue tasks are
+             acknowledged without running; webhooks are acknowledged and discarded
+
+Never active when ENVIRONMENT=production. Tests can override rules at runtime:
+
+    with fault_injector.injected("meta_api", FaultRule(error_rate=1.0)):
+        ...
+"""
+
+import asyncio
+import logging
+import random
+import time
+from contextlib import contextmanager
+from dataclasses import dataclass
+from typing import Dict, Iterator, Optional
+
+import httpx
+
+from app.core.config import settings
+from app.core.tracing import Parent, TracingTransport
+from app.core.whatsapp_config import WHATSAPP_TARGETS, whatsapp_config
+from app.core.whatsapp_metrics import MetricsSink, MetricsTransport
+
+logger = logging.getLogger(__name__)
+
+FAULT_TARGETS = ("meta_api", "meta_ads_api", "evolution_api", "http_connector", "queue", "webhook")
+
+
+class InjectedFault(Exception):
+    """Failure raised (or simulated) by the fault injector"""
+
+    def __init__(self, target: str, status_code: int = 500):
+        self.target = target
+        self.status_code = status_code
+        super().__init__(f"Injected fault on {target} (status {status_code})")
+
+
+@dataclass
+class FaultRule:
+    """Faults applied to one target; rates are fractions of calls (0-1)"""
+
+    latency_rate: float = 0.0
+    latency_ms: int = 0
+    error_rate: float = 0.0
+    error_status: int = 503
+    drop_rate: float = 0.0
+
+
+def parse_rules(value: str) -> Dict[str, FaultRule]:
+    """
+    Parse 'target.fault=rate[:param],...' into rules per target
+
+    Raises:
+        ValueError: On an unknown target/fault or a malformed rule
+    """
+    rules: Dict[str, FaultRule] = {}
+    for item in (value or "").split(","):
+        item = item.strip()
+        if not item:
+            continue
+
+        key, _, spec = item.partition("=")
+        target, _, fault = key.strip().partition(".")
+        if target not in FAULT_TARGETS:
+            raise ValueError(f"Unknown fault injection target '{target}'")
+
+        rate_str, _, param = spec.partition(":")
+        rate = float(rate_str)
+        if not 0 <= rate <= 1:
+            raise ValueError(f"Fault rate must be between 0 and 1: '{item}'")
+
+        rule = rules.setdefault(target, FaultRule())
+        if fault == "latency":
+            rule.latency_rate, rule.latency_ms = rate, int(param or 1000)
+        elif fault == "error":
+            rule.error_rate, rule.error_status = rate, int(param or 503)
+        elif fault == "drop":
+            rule.drop_rate = rate
+        else:
+            raise ValueError(f"Unknown fault '{fault}' (latency, error or drop)")
+    return rules
+
+
+class FaultInjector:
+    """Decides, per call, which fault (if any) applies to a target"""
+
+    def __init__(
+        self,
+        rules: Optional[Dict[str, FaultRule]] = None,
+        enabled: bool = False,
+        seed: Optional[int] = None,
+    ):
+        self.rules: Dict[str, FaultRule] = dict(rules or {})
+        self._enabled = enabled
+        self._random = random.Random(seed)
+
+    @classmethod
+    def from_settings(cls) -> "FaultInjector":
+        enabled = settings.FAULT_INJECTION_ENABLED and not settings.is_production
+        if settings.FAULT_INJECTION_ENABLED and settings.is_production:
+            logger.warning("⚠️ FAULT_INJECTION_ENABLED ignored in production")
+
+        rules: Dict[str, FaultRule] = {}
+        if enabled:
+            rules = parse_rules(settings.FAULT_INJECTION_RULES)
+            logger.warning(f"🧪 Fault injection enabled: {settings.FAULT_INJECTION_RULES}")
+        return cls(rules, enabled=enabled, seed=settings.FAULT_INJECTION_SEED)
+
+    @property
+    def enabled(self) -> bool:
+        return self._enabled and bool(self.rules)
+
+    def rule(self, target: str) -> Optional[FaultRule]:
+        return self.rules.get(target) if self._enabled else None
+
+    def _hit(self, rate: float) -> bool:
+        return rate > 0 and self._random.random() < rate
+
+    def latency(self, target: str) -> float:
+        """Seconds to wait before the call (0 when no latency is injected)"""
+        rule = self.rule(target)
+        if rule and self._hit(rule.latency_rate):
+            return rule.latency_ms / 1000
+        return 0.0
+
+    def error(self, target: str) -> Optional[int]:
+        """Status of an injected error, if this call fails"""
+        rule = self.rule(target)
+        if rule and self._hit(rule.error_rate):
+            logger.warning(f"🧪 Injected error {rule.error_status} on {target}")
+            return rule.error_status
+        return None
+
+    def should_drop(self, target: str) -> bool:
+        rule = self.rule(target)
+        if rule and self._hit(rule.drop_rate):
+            logger.warning(f"🧪 Injected drop on {target}")
+            return True
+        return False
+
+    async def inbound(self, target: str) -> bool:
+        """
+        Apply latency and raise InjectedFault for an injected error
+
+        Returns:
+            True when the delivery must be acknowledged and discarded
+        """
+        delay = self.latency(target)
+        if delay:
+            await asyncio.sleep(delay)
+        status_code = self.error(target)
+        if status_code:
+            raise InjectedFault(target, status_code)
+        return self.should_drop(target)
+
+    def before_task(self, target: str = "queue") -> bool:
+        """
+        Blocking variant of inbound() for worker tasks
+
+        Returns:
+            True when the task must be acknowledged without running
+        """
+        delay = self.latency(target)
+        if delay:
+            time.sleep(delay)
+        status_code = self.error(target)
+        if status_code:
+            raise InjectedFault(target, status_code)
+        return self.should_drop(target)
+
+    @contextmanager
+    def injected(self, target: str, rule: FaultRule) -> Iterator[FaultRule]:
+        """Temporarily apply a rule (integration tests), even if disabled by config"""
+        previous_rule = self.rules.get(target)
+        previous_enabled = self._enabled
+        self.rules[target] = rule
+        self._enabled = True
+        try:
+            yield rule
+        finally:
+            self._enabled = previous_enabled
+            if previous_rule is None:
+                self.rules.pop(target, None)
+            else:
+                self.rules[target] = previous_rule
+
+
+class FaultInjectionTransport(httpx.AsyncBaseTransport):
+    """httpx transport answering with injected latency, 5xx or connection errors"""
+
+    def __init__(self, target: str, injector: FaultInjector, wrapped: Optional[httpx.AsyncBaseTransport] = None):
+        self.target = target
+        self.injector = injector
+        self.wrapped = wrapped or httpx.AsyncHTTPTransport()
+
+    async def handle_async_request(self, request: httpx.Request) -> httpx.Response:
+        delay = self.injector.latency(self.target)
+        if delay:
+            await asyncio.sleep(delay)
+
+        if self.injector.should_drop(self.target):
+            raise httpx.ConnectError(f"Injected drop on {self.target}", request=request)
+
+        status_code = self.injector.error(self.target)
+        if status_code:
+            return httpx.Response(
+                status_code,
+                json={"error": {"message": f"Injected fault on {self.target}", "code": status_code}},
+                request=request,
+            )
+
+        return await self.wrapped.handle_async_request(request)
+
+    async def aclose(self) -> None:
+        await self.wrapped.aclose()
+
+
+def http_client(
+    target: str,
+    metrics: Optional[MetricsSink] = None,
+    phone_number_id: Optional[str] = None,
+    trace_parent: Parent = None,
+    **kwargs,
+) -> httpx.AsyncClient:
+    """
+    httpx.AsyncClient for an outbound integration, with faults when enabled
+
+    WhatsApp targets go through the configured proxy, CA bundle and timeouts
+    (see app.core.whatsapp_config) and open a span per request (child of the
+    active span, else of trace_parent). With a metrics sink, every request is
+    recorded under phone_number_id (injected faults included).
+    """
+    transport = None
+    if target in WHATSAPP_TARGETS:
+        transport = whatsapp_config.transport()
+        kwargs["timeout"] = whatsapp_config.timeout(kwargs.get("timeout"))
+
+    if fault_injector.rule(target):
+        transport = FaultInjectionTransport(target, fault_injector, wrapped=transport)
+
+    if metrics is not None:
+        transport = MetricsTransport(target, metrics, phone_number_id, wrapped=transport)
+
+    if target in WHATSAPP_TARGETS:
+        transport = TracingTransport(target, phone_number_id, trace_parent, wrapped=transport)
+
+    if transport:
+        kwargs["transport"] = transport
+    return httpx.AsyncClient(**kwargs)
+
+
+# Global instance (rules from settings)
+fault_injector = FaultInjector.from_settings()
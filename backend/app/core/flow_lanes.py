@@ -0,0 +1,132 @@
+"""
+Priority lanes of flow execution
+
+Each process runs at most FLOW_ENGINE_MAX_CONCURRENCY flow steps (a
+contact's message going through the bot) at once. While the engine is
+saturated, waiting steps are queued in two lanes:
+
+- priority: VIP contacts (is_vip or a FLOW_PRIORITY_TAGS tag) and tenants
+  on a FLOW_PRIORITY_PLANS plan
+- bulk: everything else
+
+A freed slot goes to the oldest priority step, except that after
+FLOW_PRIORITY_STARVATION_LIMIT priority steps in a row while bulk steps are
+waiting, the oldest bulk step runs next, so bulk traffic slows down but is
+never starved. Without saturation steps start right away in arrival order.
+"""
+
+import asyncio
+import logging
+from collections import deque
+from contextlib import asynccontextmanager
+from typing import AsyncIterator, Deque, Dict, Iterable, Optional
+
+from app.core.config import settings
+
+logger = logging.getLogger(__name__)
+
+LANE_PRIORITY = "priority"
+LANE_BULK = "bulk"
+LANES = (LANE_PRIORITY, LANE_BULK)
+
+
+def flow_lane(
+    is_vip: bool,
+    contact_tags: Iterable[str],
+    plan_type: Optional[str],
+    plan_active: bool = True,
+    priority_tags: Optional[Iterable[str]] = None,
+    priority_plans: Optional[Iterable[str]] = None,
+) -> str:
+    """Lane of a contact's flow steps"""
+    tags = {tag.strip().lower() for tag in (settings.FLOW_PRIORITY_TAGS if priority_tags is None else priority_tags)}
+    plans = {plan.strip().lower() for plan in (settings.FLOW_PRIORITY_PLANS if priority_plans is None else priority_plans)}
+    if is_vip or any((tag or "").strip().lower() in tags for tag in contact_tags):
+        return LANE_PRIORITY
+    if plan_active and (plan_type or "").lower() in plans:
+        return LANE_PRIORITY
+    return LANE_BULK
+
+
+class FlowLaneScheduler:
+    """Two-lane admission of flow steps with starvation protection"""
+
+    def __init__(self, max_concurrency: int = 50, starvation_limit: int = 5):
+        self.max_concurrency = max(1, max_concurrency)
+        self.starvation_limit = max(1, starvation_limit)
+        self.active = 0
+        self._waiting: Dict[str, Deque[asyncio.Future]] = {lane: deque() for lane in LANES}
+        # Priority steps started in a row while bulk steps were waiting
+        self._priority_streak = 0
+
+    def waiting(self, lane: str) -> int:
+        """Steps of a lane waiting for a slot"""
+        return sum(1 for future in self._waiting[lane] if not future.done())
+
+    async def acquire(self, lane: str) -> None:
+        """Wait for a slot to run a step of the lane"""
+        if lane not in self._waiting:
+            raise ValueError(f"Unknown flow lane: {lane}")
+        if self.active < self.max_concurrency and not any(self.waiting(name) for name in LANES):
+            self.active += 1
+            return
+
+        future = asyncio.get_running_loop().create_future()
+        self._waiting[lane].append(future)
+        logger.debug(f"🚦 Flow engine saturated ({self.active} steps running), {lane} step waiting")
+        try:
+            await future
+        except asyncio.CancelledError:
+            if future.done() and not future.cancelled():
+                # Slot granted while the caller was cancelled: hand it on
+                self.release()
+            raise
+
+    def release(self) -> None:
+        """Free a slot and start the next waiting step"""
+        self.active = max(0, self.active - 1)
+        while self.active < self.max_concurrency:
+            future = self._next_waiter()
+            if future is None:
+                return
+            self.active += 1
+            future.set_result(None)
+
+    def _next_waiter(self) -> Optional[asyncio.Future]:
+        for lane in LANES:
+            queue = self._waiting[lane]
+            while queue and queue[0].done():
+                queue.popleft()
+
+        priority, bulk = self._waiting[LANE_PRIORITY], self._waiting[LANE_BULK]
+        if not bulk:
+            self._priority_streak = 0
+            return priority.popleft() if priority else None
+        if priority and self._priority_streak < self.starvation_limit:
+            self._priority_streak += 1
+            return priority.popleft()
+        self._priority_streak = 0
+        return bulk.popleft()
+
+    @asynccontextmanager
+    async def slot(self, lane: str) -> AsyncIterator[None]:
+        """Hold a slot while the block runs"""
+        await self.acquire(lane)
+        try:
+            yield
+        finally:
+            self.release()
+
+
+_scheduler: Optional[FlowLaneScheduler] = None
+
+
+def get_flow_lane_scheduler() -> FlowLaneScheduler:
+    """Scheduler of this process configured from settings"""
+    global _scheduler
+    if _scheduler is None:
+        _scheduler = FlowLaneScheduler(
+            max_concurrency=settings.FLOW_ENGINE_MAX_CONCURRENCY,
+            starvation_limit=settings.FLOW_PRIORITY_STARVATION_LIMIT,
+        )
+    return _scheduler
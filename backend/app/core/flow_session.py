@@ -0,0 +1,226 @@
+"""
+Distributed locks of flow sessions
+
+With several API replicas, two webhooks for the same contact can run the
+same flow session at once and overwrite each other's state (current node,
+variables). FlowSessionManager serializes them with one lock per
+conversation:
+
+- The lock is a key holding a random owner id, set only if missing and with
+  a timeout (FLOW_SESSION_LOCK_TTL_SECONDS), so a replica that dies while
+  holding it blocks the session for at most that long
+- While held, the lock is renewed every third of the timeout; when renewal
+  fails (the key expired or has another owner) the lock is marked lost and
+  the holder stops running the session
+- Waiters poll until FLOW_SESSION_LOCK_WAIT_SECONDS, then give up with
+  FlowSessionLockError
+- Each acquisition gets a fencing token from a counter that only grows. The
+  holder stamps it on the conversation (flow_session_fence) and its session
+  writes only apply while the stamp is still its own, so a holder whose lock
+  timed out cannot overwrite the state written by the next one
+- Release deletes the key only while it is still owned
+
+FLOW_SESSION_LOCK_BACKEND selects where locks live: redis (shared by every
+replica, default) or memory (single process, tests).
+"""
+
+import asyncio
+import logging
+import time
+import uuid
+from contextlib import asynccontextmanager
+from dataclasses import dataclass
+from typing import Any, AsyncIterator, Dict, Optional, Tuple
+
+from app.core.config import settings
+from app.core.redis import RedisClient, session_redis
+
+logger = logging.getLogger(__name__)
+
+LOCK_KEY_PREFIX = "flow:session:lock:"
+FENCE_KEY = "flow:session:fence"
+
+# Deletes the lock only if the caller still owns it
+RELEASE_SCRIPT = """
+if redis.call("get", KEYS[1]) == ARGV[1] then
+    return redis.call("del", KEYS[1])
+end
+return 0
+"""
+
+# Extends the lock timeout only if the caller still owns it
+RENEW_SCRIPT = """
+if redis.call("get", KEYS[1]) == ARGV[1] then
+    return redis.call("expire", KEYS[1], ARGV[2])
+end
+return 0
+"""
+
+
+class FlowSessionLockError(Exception):
+    """The session is locked by another process (or the lock was lost)"""
+
+
+@dataclass
+class FlowSessionLock:
+    """Lock held on the flow session of a conversation"""
+
+    conversation_id: str
+    owner: str
+    token: int  # fencing token, higher for every acquisition
+    lost: bool = False  # renewal failed: another process may hold the session
+
+
+class RedisLockStore:
+    """Locks and fencing counter in Redis"""
+
+    def __init__(self, client: RedisClient):
+        self.client = client
+
+    async def acquire(self, key: str, owner: str, ttl_seconds: int) -> bool:
+        return bool(await self.client.set(key, owner, expire=ttl_seconds, nx=True))
+
+    async def release(self, key: str, owner: str) -> bool:
+        return bool(await self.client.eval(RELEASE_SCRIPT, 1, key, owner))
+
+    async def renew(self, key: str, owner: str, ttl_seconds: int) -> bool:
+        return bool(await self.client.eval(RENEW_SCRIPT, 1, key, owner, ttl_seconds))
+
+    async def next_token(self) -> int:
+        return int(await self.client.incr(FENCE_KEY))
+
+
+class InMemoryLockStore:
+    """Locks of a single process"""
+
+    def __init__(self):
+        self._locks: Dict[str, Tuple[str, float]] = {}
+        self._token = 0
+
+    async def acquire(self, key: str, owner: str, ttl_seconds: int) -> bool:
+        now = time.monotonic()
+        current = self._locks.get(key)
+        if current and current[1] > now:
+            return False
+        self._locks[key] = (owner, now + ttl_seconds)
+        return True
+
+    async def release(self, key: str, owner: str) -> bool:
+        current = self._locks.get(key)
+        if not current or current[0] != owner:
+            return False
+        del self._locks[key]
+        return True
+
+    async def renew(self, key: str, owner: str, ttl_seconds: int) -> bool:
+        now = time.monotonic()
+        current = self._locks.get(key)
+        if not current or current[0] != owner or current[1] <= now:
+            return False
+        self._locks[key] = (owner, now + ttl_seconds)
+        return True
+
+    async def next_token(self) -> int:
+        self._token += 1
+        return self._token
+
+
+class FlowSessionManager:
+    """Per-conversation locks of flow sessions"""
+
+    def __init__(
+        self,
+        store: Any,
+        ttl_seconds: int = 120,
+        wait_seconds: float = 15,
+        poll_seconds: float = 0.05,
+    ):
+        self.store = store
+        self.ttl_seconds = ttl_seconds
+        self.wait_seconds = wait_seconds
+        self.poll_seconds = poll_seconds
+
+    async def acquire(self, conversation_id: Any) -> FlowSessionLock:
+        """
+        Lock the flow session of a conversation, waiting up to wait_seconds
+
+        Raises:
+            FlowSessionLockError: If another process keeps the session locked
+        """
+        key = f"{LOCK_KEY_PREFIX}{conversation_id}"
+        owner = uuid.uuid4().hex
+        deadline = time.monotonic() + self.wait_seconds
+        while not await self.store.acquire(key, owner, self.ttl_seconds):
+            if time.monotonic() >= deadline:
+                raise FlowSessionLockError(f"Flow session of conversation {conversation_id} is locked")
+            await asyncio.sleep(self.poll_seconds)
+        return FlowSessionLock(str(conversation_id), owner, await self.store.next_token())
+
+    async def renew(self, lock: FlowSessionLock) -> bool:
+        """
+        Extend the lock timeout
+
+        Returns:
+            False (and the lock is marked lost) if it already timed out
+        """
+        if not lock.lost and not await self.store.renew(
+            f"{LOCK_KEY_PREFIX}{lock.conversation_id}", lock.owner, self.ttl_seconds
+        ):
+            lock.lost = True
+            logger.warning(f"⚠️ Flow session lock of conversation {lock.conversation_id} was lost")
+        return not lock.lost
+
+    async def _keep_alive(self, lock: FlowSessionLock) -> None:
+        """Renew the lock every third of its timeout until it is released or lost"""
+        while True:
+            await asyncio.sleep(self.ttl_seconds / 3)
+            if not await self.renew(lock):
+                return
+
+    async def release(self, lock: FlowSessionLock) -> None:
+        """Unlock the session (no-op when the lock already timed out)"""
+        if not await self.store.release(f"{LOCK_KEY_PREFIX}{lock.conversation_id}", lock.owner):
+            logger.warning(
+                f"⚠️ Flow session lock of conversation {lock.conversation_id} timed out before release"
+            )
+
+    @asynccontextmanager
+    async def lock(self, conversation_id: Any) -> AsyncIterator[FlowSessionLock]:
+        """Hold the session lock (renewed in the background) while the block runs"""
+        lock = await self.acquire(conversation_id)
+        keep_alive = asyncio.create_task(self._keep_alive(lock))
+        try:
+            yield lock
+        finally:
+            keep_alive.cancel()
+            await asyncio.gather(keep_alive, return_exceptions=True)
+            await self.release(lock)
+
+
+_manager: Optional[FlowSessionManager] = None
+
+
+def build_lock_store(backend: str) -> Any:
+    """
+    Lock store from its setting
+
+    Raises:
+        ValueError: If the backend is not redis or memory
+    """
+    if backend == "redis":
+        return RedisLockStore(session_redis)
+    if backend == "memory":
+        return InMemoryLockStore()
+    raise ValueError(f'Unknown flow session lock backend "{backend}" (use redis or memory)')
+
+
+def get_flow_session_manager() -> FlowSessionManager:
+    """Shared session manager configured from settings"""
+    global _manager
+    if _manager is None:
+        _manager = FlowSessionManager(
+            build_lock_store(settings.FLOW_SESSION_LOCK_BACKEND),
+            ttl_seconds=settings.FLOW_SESSION_LOCK_TTL_SECONDS,
+            wait_seconds=settings.FLOW_SESSION_LOCK_WAIT_SECONDS,
+        )
+    return _manager
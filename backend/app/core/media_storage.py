@@ -0,0 +1,59 @@
+"""
+Storage of media collected by flows
+
+MEDIA_STORAGE_BACKEND selects where media_collect nodes put the files the
+contacts send:
+
+    local    files under MEDIA_STORAGE_DIR (default)
+    memory   kept in the process (tests)
+    package.module:Class
+             any pytake_client.media.MediaStorage subclass (S3, GCS...),
+             built without arguments
+
+The backend is created once per process.
+"""
+
+import importlib
+import logging
+from typing import Optional
+
+from pytake_client.media import InMemoryMediaStorage, LocalMediaStorage, MediaStorage
+
+from app.core.config import settings
+
+logger = logging.getLogger(__name__)
+
+_storage: Optional[MediaStorage] = None
+
+
+def build_media_storage(backend: str, base_dir: str) -> MediaStorage:
+    """
+    Storage backend from its setting
+
+    Raises:
+        ValueError: If the backend cannot be loaded or is not a MediaStorage
+    """
+    if backend == "local":
+        return LocalMediaStorage(base_dir)
+    if backend == "memory":
+        return InMemoryMediaStorage()
+
+    module_name, _, class_name = backend.partition(":")
+    if not module_name or not class_name:
+        raise ValueError(f'Unknown media storage backend "{backend}" (use local, memory or "module:Class")')
+    try:
+        storage_class = getattr(importlib.import_module(module_name), class_name)
+    except (ImportError, AttributeError) as e:
+        raise ValueError(f'Media storage backend "{backend}" could not be loaded: {e}') from e
+    if not (isinstance(storage_class, type) and issubclass(storage_class, MediaStorage)):
+        raise ValueError(f'Media storage backend "{backend}" is not a MediaStorage')
+    return storage_class()
+
+
+def get_media_storage() -> MediaStorage:
+    """Configured storage backend (created on first use)"""
+    global _storage
+    if _storage is None:
+        _storage = build_media_storage(settings.MEDIA_STORAGE_BACKEND, settings.MEDIA_STORAGE_DIR)
+        logger.info(f"🗄️ Media storage: {settings.MEDIA_STORAGE_BACKEND}")
+    return _storage
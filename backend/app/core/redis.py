@@ -1,32 +1,132 @@
 """
 Redis configuration for caching and queue management
+
+Supports three deployment modes (settings.REDIS_MODE):
+- standalone: single node from REDIS_URL
+- sentinel: master discovered through REDIS_SENTINELS; after a failover the
+  pool reconnects to the new master on the next command
+- cluster: RedisCluster from REDIS_CLUSTER_NODES (MOVED/ASK handled by redis-py)
+
+Each component (cache, sessions, rate_limit, queue) gets its own client and
+connection pool, sized by REDIS_POOL_SIZES, so a burst in one layer cannot
+starve the others. Component clients connect lazily on first use.
 """
 
-from typing import Optional
+import asyncio
+import logging
+import time
+from typing import Any, Dict, List, Optional, Tuple
 
 import redis.asyncio as aioredis
 from redis.asyncio import Redis
 from redis.asyncio.connection import ConnectionPool
+from redis.backoff import ExponentialBackoff
+from redis.exceptions import ConnectionError as RedisConnectionError
+from redis.exceptions import TimeoutError as RedisTimeoutError
+from redis.retry import Retry
 
 from app.core.config import settings
 
+logger = logging.getLogger(__name__)
+
+RETRY_ON_ERRORS = [RedisConnectionError, RedisTimeoutError]
+
+
+def parse_node(node: str, default_port: int) -> Tuple[str, int]:
+    """'host:port' -> (host, port)"""
+    host, _, port = node.rpartition(":")
+    if not host:
+        return node, default_port
+    return host, int(port)
+
 
 class RedisClient:
     """Redis async client wrapper"""
 
-    def __init__(self):
+    def __init__(self, component: str = "default", lazy: bool = False):
+        """
+        Args:
+            component: Name used for the pool size (REDIS_POOL_SIZES) and metrics
+            lazy: Connect automatically on the first command
+        """
+        self.component = component
+        self.lazy = lazy
         self.pool: Optional[ConnectionPool] = None
         self.client: Optional[Redis] = None
+        self.sentinel = None
+        self._loop: Optional[asyncio.AbstractEventLoop] = None
+        self.master_address: Optional[Tuple[str, int]] = None
+        self.stats: Dict[str, Any] = {
+            "commands": 0,
+            "errors": 0,
+            "failovers": 0,
+            "last_error": None,
+        }
+
+    @property
+    def max_connections(self) -> int:
+        return settings.REDIS_POOL_SIZES.get(self.component, settings.REDIS_MAX_CONNECTIONS)
+
+    def _connection_kwargs(self) -> Dict[str, Any]:
+        return {
+            "encoding": "utf-8",
+            "decode_responses": True,
+            "max_connections": self.max_connections,
+            "socket_timeout": settings.REDIS_SOCKET_TIMEOUT_SECONDS,
+            "socket_connect_timeout": settings.REDIS_SOCKET_TIMEOUT_SECONDS,
+            "retry": Retry(ExponentialBackoff(cap=2, base=0.1), settings.REDIS_RETRY_ATTEMPTS),
+            "retry_on_error": RETRY_ON_ERRORS,
+        }
 
     async def connect(self):
         """Initialize Redis connection pool"""
-        self.pool = ConnectionPool.from_url(
-            str(settings.REDIS_URL),
-            encoding="utf-8",
-            decode_responses=True,
-            max_connections=50,
-        )
-        self.client = Redis(connection_pool=self.pool)
+        mode = settings.REDIS_MODE
+
+        if mode == "sentinel":
+            from redis.asyncio.sentinel import Sentinel
+
+            if not settings.REDIS_SENTINELS:
+                raise RuntimeError("REDIS_MODE=sentinel requires REDIS_SENTINELS")
+
+            self.sentinel = Sentinel(
+                [parse_node(node, 26379) for node in settings.REDIS_SENTINELS],
+                sentinel_kwargs={
+                    "password": settings.REDIS_SENTINEL_PASSWORD,
+                    "socket_timeout": settings.REDIS_SOCKET_TIMEOUT_SECONDS,
+                },
+            )
+            self.client = self.sentinel.master_for(
+                settings.REDIS_SENTINEL_MASTER,
+                password=settings.REDIS_PASSWORD,
+                **self._connection_kwargs(),
+            )
+            self.pool = self.client.connection_pool
+            self.master_address = await self.sentinel.discover_master(settings.REDIS_SENTINEL_MASTER)
+
+        elif mode == "cluster":
+            from redis.asyncio.cluster import ClusterNode, RedisCluster
+
+            if not settings.REDIS_CLUSTER_NODES:
+                raise RuntimeError("REDIS_MODE=cluster requires REDIS_CLUSTER_NODES")
+
+            self.client = RedisCluster(
+                startup_nodes=[
+                    ClusterNode(*parse_node(node, 6379)) for node in settings.REDIS_CLUSTER_NODES
+                ],
+                password=settings.REDIS_PASSWORD,
+                **self._connection_kwargs(),
+            )
+            await self.client.initialize()
+
+        else:
+            self.pool = ConnectionPool.from_url(
+                str(settings.REDIS_URL),
+                **self._connection_kwargs(),
+            )
+            self.client = Redis(connection_pool=self.pool)
+
+        self._loop = asyncio.get_running_loop()
+        logger.info(f"Redis [{self.component}] connected ({mode}, pool={self.max_connections})")
 
     async def disconnect(self):
         """Close Redis connections"""
@@ -34,142 +134,248 @@ class RedisClient:
             await self.client.close()
         if self.pool:
             await self.pool.disconnect()
+        self.client = None
+        self.pool = None
+
+    async def _get_client(self) -> Redis:
+        if self.lazy and self.client and self._loop is not asyncio.get_running_loop():
+            # Celery tasks run each job in a new event loop; connections are loop-bound
+            self.client = None
+            self.pool = None
+
+        if not self.client:
+            if not self.lazy:
+                raise RuntimeError("Redis client not initialized")
+            await self.connect()
+        return self.client
+
+    async def _execute(self, command: str, *args, **kwargs) -> Any:
+        """Run a command, recording metrics (retries happen inside redis-py)"""
+        client = await self._get_client()
+        self.stats["commands"] += 1
+        try:
+            return await getattr(client, command)(*args, **kwargs)
+        except (RedisConnectionError, RedisTimeoutError) as e:
+            self.stats["errors"] += 1
+            self.stats["last_error"] = str(e)
+            logger.warning(f"⚠️ Redis [{self.component}] {command} failed: {e}")
+            raise
+
+    async def _check_failover(self) -> None:
+        """Detect master changes reported by Sentinel"""
+        if not self.sentinel:
+            return
+
+        address = await self.sentinel.discover_master(settings.REDIS_SENTINEL_MASTER)
+        if self.master_address and address != self.master_address:
+            self.stats["failovers"] += 1
+            logger.warning(
+                f"⚠️ Redis [{self.component}] failover: master moved "
+                f"{self.master_address} -> {address}"
+            )
+        self.master_address = address
+
+    def _pool_stats(self) -> Dict[str, Any]:
+        pool = self.pool
+        if pool is None:
+            return {"max": self.max_connections}
+        return {
+            "max": self.max_connections,
+            "in_use": len(getattr(pool, "_in_use_connections", ())),
+            "available": len(getattr(pool, "_available_connections", ())),
+        }
+
+    async def health(self) -> Dict[str, Any]:
+        """
+        Health and metrics of this component
+
+        Returns:
+            {"component", "mode", "status", "latency_ms", "master", "pool", "commands", "errors", "failovers", "last_error"}
+        """
+        result: Dict[str, Any] = {
+            "component": self.component,
+            "mode": settings.REDIS_MODE,
+        }
+
+        if not self.client:
+            result["status"] = "not_connected"
+            return {**result, **self.stats}
+
+        try:
+            started = time.perf_counter()
+            await self.client.ping()
+            result["latency_ms"] = round((time.perf_counter() - started) * 1000, 2)
+            await self._check_failover()
+            result["status"] = "healthy"
+        except Exception as e:
+            result["status"] = f"unhealthy: {e}"
+
+        if self.master_address:
+            result["master"] = f"{self.master_address[0]}:{self.master_address[1]}"
+        result["pool"] = self._pool_stats()
+        return {**result, **self.stats}
 
     async def get(self, key: str) -> Optional[str]:
         """Get value by key"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.get(key)
+        return await self._execute("get", key)
 
     async def set(
         self,
         key: str,
         value: str,
         expire: Optional[int] = None,
+        nx: bool = False,
     ) -> bool:
-        """Set key-value with optional expiration (seconds)"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.set(key, value, ex=expire)
+        """Set key-value with optional expiration (seconds); nx only sets if missing"""
+        return await self._execute("set", key, value, ex=expire, nx=nx)
 
-    async def delete(self, key: str) -> int:
-        """Delete key"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.delete(key)
+    async def delete(self, *keys: str) -> int:
+        """Delete keys"""
+        return await self._execute("delete", *keys)
 
     async def exists(self, key: str) -> bool:
         """Check if key exists"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.exists(key) > 0
+        return await self._execute("exists", key) > 0
 
     async def expire(self, key: str, seconds: int) -> bool:
         """Set expiration on key"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.expire(key, seconds)
+        return await self._execute("expire", key, seconds)
+
+    async def eval(self, script: str, numkeys: int, *keys_and_args: Any) -> Any:
+        """Run a Lua script (keys first, then arguments)"""
+        return await self._execute("eval", script, numkeys, *keys_and_args)
 
     async def incr(self, key: str) -> int:
         """Increment key value"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.incr(key)
+        return await self._execute("incr", key)
 
     async def decr(self, key: str) -> int:
         """Decrement key value"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.decr(key)
+        return await self._execute("decr", key)
 
     async def hget(self, name: str, key: str) -> Optional[str]:
         """Get value from hash"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.hget(name, key)
+        return await self._execute("hget", name, key)
 
     async def hset(self, name: str, key: str, value: str) -> int:
         """Set value in hash"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.hset(name, key, value)
+        return await self._execute("hset", name, key, value)
+
+    async def hincrby(self, name: str, key: str, amount: int = 1) -> int:
+        """Increment a hash field"""
+        return await self._execute("hincrby", name, key, amount)
 
     async def hgetall(self, name: str) -> dict:
         """Get all values from hash"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.hgetall(name)
+        return await self._execute("hgetall", name)
 
     async def hdel(self, name: str, *keys: str) -> int:
         """Delete keys from hash"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.hdel(name, *keys)
+        return await self._execute("hdel", name, *keys)
 
     async def lpush(self, name: str, *values: str) -> int:
         """Push values to list (left)"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.lpush(name, *values)
+        return await self._execute("lpush", name, *values)
 
     async def rpush(self, name: str, *values: str) -> int:
         """Push values to list (right)"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.rpush(name, *values)
+        return await self._execute("rpush", name, *values)
 
     async def lpop(self, name: str) -> Optional[str]:
         """Pop value from list (left)"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.lpop(name)
+        return await self._execute("lpop", name)
 
     async def rpop(self, name: str) -> Optional[str]:
         """Pop value from list (right)"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.rpop(name)
+        return await self._execute("rpop", name)
 
     async def lrange(self, name: str, start: int, end: int) -> list:
         """Get range from list"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.lrange(name, start, end)
+        return await self._execute("lrange", name, start, end)
+
+    async def ltrim(self, name: str, start: int, end: int) -> bool:
+        """Keep only the given range of the list"""
+        return await self._execute("ltrim", name, start, end)
+
+    async def lrem(self, name: str, count: int, value: str) -> int:
+        """Remove occurrences of value from list (count=0 removes all)"""
+        return await self._execute("lrem", name, count, value)
 
     async def llen(self, name: str) -> int:
         """Get list length"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.llen(name)
+        return await self._execute("llen", name)
 
     async def sadd(self, name: str, *values: str) -> int:
         """Add values to set"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.sadd(name, *values)
+        return await self._execute("sadd", name, *values)
 
     async def srem(self, name: str, *values: str) -> int:
         """Remove values from set"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.srem(name, *values)
+        return await self._execute("srem", name, *values)
 
     async def smembers(self, name: str) -> set:
         """Get all members of set"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.smembers(name)
+        return await self._execute("smembers", name)
 
     async def sismember(self, name: str, value: str) -> bool:
         """Check if value is member of set"""
-        if not self.client:
-            raise RuntimeError("Redis client not initialized")
-        return await self.client.sismember(name, value)
+        return await self._execute("sismember", name, value)
+
+    async def zadd(self, name: str, mapping: dict) -> int:
+        """Add members with scores to sorted set"""
+        return await self._execute("zadd", name, mapping)
+
+    async def zrem(self, name: str, *values: str) -> int:
+        """Remove members from sorted set"""
+        return await self._execute("zrem", name, *values)
+
+    async def zscore(self, name: str, value: str) -> Optional[float]:
+        """Get score of a sorted set member"""
+        return await self._execute("zscore", name, value)
+
+    async def zcard(self, name: str) -> int:
+        """Get sorted set size"""
+        return await self._execute("zcard", name)
+
+    async def zcount(self, name: str, min_score: float, max_score: float) -> int:
+        """Count members with score between min and max (inclusive)"""
+        return await self._execute("zcount", name, min_score, max_score)
+
+    async def zrange(self, name: str, start: int, end: int, withscores: bool = False) -> List:
+        """Get members by rank (ascending score)"""
+        return await self._execute("zrange", name, start, end, withscores=withscores)
+
+    async def zrangebyscore(self, name: str, min_score: Any, max_score: Any) -> List:
+        """Get members with score between min and max (inclusive, ascending)"""
+        return await self._execute("zrangebyscore", name, min_score, max_score)
+
+    async def zremrangebyscore(self, name: str, min_score: float, max_score: float) -> int:
+        """Remove members with score between min and max (inclusive)"""
+        return await self._execute("zremrangebyscore", name, min_score, max_score)
 
 
 # Global Redis client instance
 redis_client = RedisClient()
 
+# Per-component clients (own pools, lazy connection)
+cache_redis = RedisClient("cache", lazy=True)
+session_redis = RedisClient("sessions", lazy=True)
+rate_limit_redis = RedisClient("rate_limit", lazy=True)
+queue_redis = RedisClient("queue", lazy=True)
+
+REDIS_CLIENTS: List[RedisClient] = [redis_client, cache_redis, session_redis, rate_limit_redis, queue_redis]
+
+
+async def disconnect_all():
+    """Close every component client"""
+    for client in REDIS_CLIENTS:
+        await client.disconnect()
+
+
+async def redis_health() -> Dict[str, Any]:
+    """Health and metrics of all Redis components"""
+    return {client.component: await client.health() for client in REDIS_CLIENTS}
+
 
 async def get_redis() -> Redis:
     """
@@ -187,30 +393,29 @@ async def get_redis() -> Redis:
 # Cache helper functions
 async def cache_set(key: str, value: str, expire: int = 3600) -> bool:
     """Set cache with default 1 hour expiration"""
-    return await redis_client.set(key, value, expire=expire)
+    return await cache_redis.set(key, value, expire=expire)
 
 
 async def cache_get(key: str) -> Optional[str]:
     """Get cached value"""
-    return await redis_client.get(key)
+    return await cache_redis.get(key)
 
 
 async def cache_delete(key: str) -> int:
     """Delete cached value"""
-    return await redis_client.delete(key)
+    return await cache_redis.delete(key)
 
 
 async def cache_invalidate_pattern(pattern: str) -> int:
     """Delete all keys matching pattern"""
-    if not redis_client.client:
-        raise RuntimeError("Redis client not initialized")
+    client = await cache_redis._get_client()
 
     keys = []
-    async for key in redis_client.client.scan_iter(match=pattern):
+    async for key in client.scan_iter(match=pattern):
         keys.append(key)
 
     if keys:
-        return await redis_client.client.delete(*keys)
+        return await cache_redis.delete(*keys)
     return 0
 
 
@@ -220,11 +425,11 @@ async def rate_limit_check(key: str, max_requests: int, window_seconds: int) ->
     Check if rate limit is exceeded
     Returns True if allowed, False if exceeded
     """
-    current = await redis_client.incr(key)
+    current = await rate_limit_redis.incr(key)
 
     if current == 1:
         # First request, set expiration
-        await redis_client.expire(key, window_seconds)
+        await rate_limit_redis.expire(key, window_seconds)
 
     return current <= max_requests
 
@@ -233,7 +438,7 @@ async def rate_limit_check(key: str, max_requests: int, window_seconds: int) ->
 async def create_session(session_id: str, user_data: dict, expire: int = 3600) -> bool:
     """Create user session"""
     import json
-    return await redis_client.set(
+    return await session_redis.set(
         f"session:{session_id}",
         json.dumps(user_data),
         expire=expire,
@@ -243,7 +448,7 @@ async def create_session(session_id: str, user_data: dict, expire: int = 3600) -
 async def get_session(session_id: str) -> Optional[dict]:
     """Get user session data"""
     import json
-    data = await redis_client.get(f"session:{session_id}")
+    data = await session_redis.get(f"session:{session_id}")
     if data:
         return json.loads(data)
     return None
@@ -251,13 +456,13 @@ async def get_session(session_id: str) -> Optional[dict]:
 
 async def delete_session(session_id: str) -> int:
     """Delete user session"""
-    return await redis_client.delete(f"session:{session_id}")
+    return await session_redis.delete(f"session:{session_id}")
 
 
 # Queue management helpers for conversation queue
 async def enqueue_conversation(department_id: str, conversation_id: str) -> int:
     """Add conversation to department queue"""
-    return await redis_client.rpush(
+    return await queue_redis.rpush(
         f"queue:department:{department_id}",
         conversation_id,
     )
@@ -265,12 +470,12 @@ async def enqueue_conversation(department_id: str, conversation_id: str) -> int:
 
 async def dequeue_conversation(department_id: str) -> Optional[str]:
     """Remove and return next conversation from department queue"""
-    return await redis_client.lpop(f"queue:department:{department_id}")
+    return await queue_redis.lpop(f"queue:department:{department_id}")
 
 
 async def get_queue_length(department_id: str) -> int:
     """Get current queue length for department"""
-    return await redis_client.llen(f"queue:department:{department_id}")
+    return await queue_redis.llen(f"queue:department:{department_id}")
 
 
 async def get_queue_position(department_id: str, conversation_id: str) -> int:
@@ -278,7 +483,7 @@ async def get_queue_position(department_id: str, conversation_id: str) -> int:
     Get position of conversation in queue (0-indexed)
     Returns -1 if not found
     """
-    queue = await redis_client.lrange(
+    queue = await queue_redis.lrange(
         f"queue:department:{department_id}",
         0,
         -1,
@@ -291,11 +496,8 @@ async def get_queue_position(department_id: str, conversation_id: str) -> int:
 
 async def remove_from_queue(department_id: str, conversation_id: str) -> bool:
     """Remove specific conversation from queue"""
-    if not redis_client.client:
-        return False
-
     # lrem: remove all occurrences of value from list
-    removed = await redis_client.client.lrem(
+    removed = await queue_redis.lrem(
         f"queue:department:{department_id}",
         0,
         conversation_id,
@@ -0,0 +1,147 @@
+"""
+Regional Routing for Multi-Region Deployments
+
+Keeps a tenant's webhook processing and outbound sends pinned to a single
+region so ordering guarantees and latency budgets hold when instances run
+in several regions.
+
+- Each region publishes a heartbeat in Redis (region:heartbeat:{region})
+- A tenant is served by its home region (Organization.home_region) or, if
+  none is set, by a stable hash of its id over REGIONS
+- When the serving region stops sending heartbeats, the tenant fails over
+  to the next healthy region (REGIONS order) and stays pinned there
+  (region:pin:{org_id}) until its home region is healthy again
+- Celery queues are region-tagged: "<queue>.<region>" (e.g. campaigns.sa-east-1)
+
+With a single region configured everything stays on the plain queue names,
+so single-region deployments are unaffected.
+"""
+
+import hashlib
+import logging
+import time
+from typing import List, Optional
+from uuid import UUID
+
+from app.core.config import settings
+from app.core.redis import redis_client
+
+logger = logging.getLogger(__name__)
+
+# Base queues that get a per-region variant
+REGIONAL_QUEUES = ["campaigns", "webhooks", "templates"]
+
+
+def is_multi_region() -> bool:
+    """True when more than one region is configured"""
+    return len(settings.REGIONS) > 1
+
+
+def regional_queue(base_queue: str, region: Optional[str]) -> str:
+    """Region-tagged queue name (plain name in single-region deployments)"""
+    if not is_multi_region() or not region:
+        return base_queue
+    return f"{base_queue}.{region}"
+
+
+def get_worker_queues(region: Optional[str] = None) -> List[str]:
+    """Queues a worker of the given region should consume (celery -Q)"""
+    region = region or settings.DEPLOYMENT_REGION
+    queues = [regional_queue(base, region) for base in REGIONAL_QUEUES]
+    if is_multi_region():
+        queues.append(f"regional.{region}")
+    return queues
+
+
+class RegionRouter:
+    """Resolves the region that serves a tenant, with failover"""
+
+    HEARTBEAT_KEY = "region:heartbeat:{region}"
+    PIN_KEY = "region:pin:{organization_id}"
+
+    def __init__(self, regions: Optional[List[str]] = None):
+        self.regions = regions or list(settings.REGIONS)
+        self.heartbeat_ttl = settings.REGION_HEARTBEAT_TTL_SECONDS
+
+    def default_region(self, organization_id: UUID) -> str:
+        """Stable region for a tenant without a configured home region"""
+        digest = hashlib.sha256(str(organization_id).encode("utf-8")).hexdigest()
+        return self.regions[int(digest, 16) % len(self.regions)]
+
+    async def record_heartbeat(self, region: Optional[str] = None) -> None:
+        """Publish a heartbeat for a region"""
+        region = region or settings.DEPLOYMENT_REGION
+        await redis_client.set(
+            self.HEARTBEAT_KEY.format(region=region),
+            str(int(time.time())),
+            expire=self.heartbeat_ttl,
+        )
+
+    async def is_healthy(self, region: str) -> bool:
+        """A region is healthy while its heartbeat key exists"""
+        return await redis_client.exists(self.HEARTBEAT_KEY.format(region=region))
+
+    def _failover_order(self, home_region: str) -> List[str]:
+        """Regions after home_region in REGIONS order (ring)"""
+        if home_region not in self.regions:
+            return list(self.regions)
+        index = self.regions.index(home_region)
+        return self.regions[index + 1:] + self.regions[:index]
+
+    async def resolve_region(
+        self, organization_id: UUID, home_region: Optional[str] = None
+    ) -> str:
+        """
+        Region that must process this tenant's webhooks and sends
+
+        Args:
+            organization_id: Tenant UUID
+            home_region: Organization.home_region (optional)
+
+        Returns:
+            Region name
+        """
+        home = home_region if home_region in self.regions else self.default_region(organization_id)
+
+        if not is_multi_region():
+            return home
+
+        pin_key = self.PIN_KEY.format(organization_id=organization_id)
+
+        try:
+            if await self.is_healthy(home):
+                # Home is back: drop any failover pin
+                await redis_client.delete(pin_key)
+                return home
+
+            pinned = await redis_client.get(pin_key)
+            if pinned and pinned != home and await self.is_healthy(pinned):
+                return pinned
+
+            for region in self._failover_order(home):
+                if await self.is_healthy(region):
+                    await redis_client.set(pin_key, region)
+                    logger.warning(
+                        f"⚠️ Region {home} unhealthy, organization {organization_id} "
+                        f"pinned to {region}"
+                    )
+                    return region
+
+        except Exception as e:
+            logger.error(f"❌ Region resolution failed for {organization_id}: {e}")
+
+        # No health information: stay on home region
+        return home
+
+    async def queue_for(
+        self,
+        base_queue: str,
+        organization_id: UUID,
+        home_region: Optional[str] = None,
+    ) -> str:
+        """Region-tagged queue for a tenant"""
+        region = await self.resolve_region(organization_id, home_region)
+        return regional_queue(base_queue, region)
+
+
+region_router = RegionRouter()
@@ -115,6 +115,7 @@ def create_access_token(
 def create_refresh_token(
     subject: Union[str, Any],
     expires_delta: Optional[timedelta] = None,
+    additional_claims: Optional[dict] = None,
 ) -> str:
     """
     Create JWT refresh token
@@ -122,6 +123,7 @@ def create_refresh_token(
     Args:
         subject: User ID or identifier
         expires_delta: Optional custom expiration time
+        additional_claims: Additional data to include in token (e.g. workspace)
 
     Returns:
         Encoded JWT token string
@@ -139,6 +141,9 @@ def create_refresh_token(
         "type": "refresh",
     }
 
+    if additional_claims:
+        to_encode.update(additional_claims)
+
     encoded_jwt = jwt.encode(
         to_encode,
         settings.JWT_SECRET_KEY,
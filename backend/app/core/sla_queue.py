@@ -0,0 +1,269 @@
+"""
+SLA-aware task scheduling
+
+Jobs may carry an SLA deadline: the time by which a worker must have
+started them. SlaScheduler sends them to Celery ahead of jobs without an SLA
+(Redis broker priorities: 0 runs first) and tracks them in a Redis sorted set
+scored by the time they become at risk (less than SLA_AT_RISK_RATIO of the
+SLA left).
+
+During a backlog, escalate() (beat, every SLA_ESCALATION_INTERVAL_SECONDS)
+promotes at-risk jobs still waiting: each one is re-published at the top
+priority, ahead of newer work, and an SlaAtRisk event is emitted to the
+registered listeners. Both copies carry the job id in the ``sla_job_id``
+header; the first copy a worker starts claims the job and the other one is
+skipped (see FaultInjectingTask).
+
+Tracking fails open: without Redis jobs are sent at their initial priority.
+"""
+
+import json
+import logging
+import math
+import time
+import uuid
+from dataclasses import asdict, dataclass
+from datetime import datetime, timezone
+from typing import Any, Awaitable, Callable, Dict, List, Optional, Sequence
+
+from app.core.config import settings
+from app.core.redis import RedisClient, queue_redis
+
+logger = logging.getLogger(__name__)
+
+SLA_HEADER = "sla_job_id"
+PENDING_KEY = "sla:pending"
+JOB_KEY = "sla:job:{job_id}"
+CLAIM_KEY = "sla:claimed:{job_id}"
+
+# Celery priorities (Redis broker: lower runs first)
+PRIORITY_URGENT = 0
+PRIORITY_SLA = 4
+PRIORITY_DEFAULT = 5
+MAX_PRIORITY = 9
+
+# Jobs and claims outlive their deadline by this long
+JOB_TTL_SECONDS = 3600
+
+
+@dataclass
+class SlaAtRisk:
+    """A queued job close to (or past) its SLA deadline, promoted by the scheduler"""
+
+    job_id: str
+    task: str
+    queue: Optional[str]
+    deadline: datetime
+    remaining_seconds: float
+    sla_seconds: float
+    organization_id: Optional[str] = None
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data["deadline"] = self.deadline.isoformat()
+        return data
+
+
+SlaListener = Callable[[SlaAtRisk], Awaitable[None]]
+
+_listeners: List[SlaListener] = []
+
+
+def on_sla_at_risk(listener: SlaListener) -> SlaListener:
+    """
+    Call ``listener(event)`` for every SlaAtRisk event
+
+    Usable as a decorator. Listener failures are logged and never stop the
+    escalation.
+    """
+    if listener not in _listeners:
+        _listeners.append(listener)
+    return listener
+
+
+def remove_sla_listener(listener: SlaListener) -> None:
+    if listener in _listeners:
+        _listeners.remove(listener)
+
+
+def sla_priority(remaining_seconds: float, sla_seconds: float, at_risk_ratio: Optional[float] = None) -> int:
+    """
+    Celery priority of a job from the share of its SLA left
+
+    Jobs with an SLA never rank behind jobs without one (PRIORITY_DEFAULT);
+    jobs at risk or late get PRIORITY_URGENT.
+    """
+    ratio = settings.SLA_AT_RISK_RATIO if at_risk_ratio is None else at_risk_ratio
+    if sla_seconds <= 0 or remaining_seconds <= 0:
+        return PRIORITY_URGENT
+    share = min(1.0, remaining_seconds / sla_seconds)
+    if share <= ratio:
+        return PRIORITY_URGENT
+    return max(1, min(PRIORITY_SLA, math.ceil(share * PRIORITY_SLA)))
+
+
+def _send_task(name: str, **options) -> None:
+    from app.tasks.celery_app import celery_app
+
+    celery_app.send_task(name, **options)
+
+
+class SlaScheduler:
+    """Sends SLA jobs to Celery and promotes the ones about to breach"""
+
+    def __init__(
+        self,
+        redis: RedisClient,
+        at_risk_ratio: Optional[float] = None,
+        send: Callable[..., Any] = _send_task,
+    ):
+        self.redis = redis
+        self.at_risk_ratio = settings.SLA_AT_RISK_RATIO if at_risk_ratio is None else at_risk_ratio
+        self.send = send
+
+    async def submit(
+        self,
+        task: Any,
+        args: Sequence[Any] = (),
+        kwargs: Optional[Dict[str, Any]] = None,
+        sla_seconds: float = 60,
+        queue: Optional[str] = None,
+        eta: Optional[datetime] = None,
+        organization_id: Optional[Any] = None,
+        now: Optional[float] = None,
+    ) -> str:
+        """
+        Queue a Celery task that must start within ``sla_seconds``
+
+        Args:
+            task: Celery task (or task name)
+            sla_seconds: Deadline, counted from ``eta`` for delayed tasks
+            queue: Celery queue (promoted copies go to the same queue)
+            eta: Earliest start (e.g. a reminder's send time)
+            organization_id: Tenant, reported in SlaAtRisk events
+
+        Returns:
+            Job id (also sent in the ``sla_job_id`` header)
+        """
+        now = time.time() if now is None else now
+        name = getattr(task, "name", task)
+        job_id = uuid.uuid4().hex
+        start = now
+        if eta is not None:
+            start = max(now, (eta if eta.tzinfo else eta.replace(tzinfo=timezone.utc)).timestamp())
+        deadline = start + sla_seconds
+
+        job = {
+            "task": name,
+            "args": list(args),
+            "kwargs": kwargs or {},
+            "queue": queue,
+            "deadline": deadline,
+            "sla_seconds": sla_seconds,
+            "organization_id": str(organization_id) if organization_id else None,
+        }
+        at_risk_at = deadline - sla_seconds * self.at_risk_ratio
+        try:
+            await self.redis.set(
+                JOB_KEY.format(job_id=job_id),
+                json.dumps(job),
+                expire=int(deadline - now) + JOB_TTL_SECONDS,
+            )
+            await self.redis.zadd(PENDING_KEY, {job_id: at_risk_at})
+        except Exception as e:
+            logger.warning(f"⚠️ SLA tracking unavailable, sending {name} without escalation: {e}")
+
+        options: Dict[str, Any] = {
+            "args": list(args),
+            "kwargs": kwargs or {},
+            "priority": sla_priority(deadline - start, sla_seconds, self.at_risk_ratio),
+            "headers": {SLA_HEADER: job_id},
+        }
+        if queue:
+            options["queue"] = queue
+        if eta is not None:
+            options["eta"] = eta
+        self.send(name, **options)
+        return job_id
+
+    async def claim(self, job_id: str, task_id: Optional[str] = None, now: Optional[float] = None) -> bool:
+        """
+        Claim a job when a worker starts it
+
+        Returns False if another copy (original or promoted) already started.
+        Retries of the claiming copy keep the same task id and still run.
+        """
+        now = time.time() if now is None else now
+        key = CLAIM_KEY.format(job_id=job_id)
+        try:
+            if not await self.redis.set(key, task_id or "claimed", expire=JOB_TTL_SECONDS, nx=True):
+                return task_id is not None and await self.redis.get(key) == task_id
+
+            raw = await self.redis.get(JOB_KEY.format(job_id=job_id))
+            await self.redis.zrem(PENDING_KEY, job_id)
+            await self.redis.delete(JOB_KEY.format(job_id=job_id))
+        except Exception as e:
+            logger.warning(f"⚠️ SLA claim unavailable for job {job_id}, running it: {e}")
+            return True
+
+        if raw:
+            job = json.loads(raw)
+            late = now - job["deadline"]
+            if late > 0:
+                logger.warning(f"⏰ SLA breached: {job['task']} (job {job_id}) started {late:.1f}s late")
+        return True
+
+    async def escalate(self, now: Optional[float] = None) -> List[SlaAtRisk]:
+        """Promote waiting jobs that became at risk; returns the emitted events"""
+        now = time.time() if now is None else now
+        events: List[SlaAtRisk] = []
+
+        try:
+            due = await self.redis.zrangebyscore(PENDING_KEY, "-inf", now)
+        except Exception as e:
+            logger.warning(f"⚠️ SLA escalation skipped, tracking unavailable: {e}")
+            return events
+
+        for job_id in due:
+            # Only the caller that removes the job promotes it (several beats may overlap)
+            if not await self.redis.zrem(PENDING_KEY, job_id):
+                continue
+            raw = await self.redis.get(JOB_KEY.format(job_id=job_id))
+            if not raw:
+                continue
+            job = json.loads(raw)
+
+            options: Dict[str, Any] = {
+                "args": job["args"],
+                "kwargs": job["kwargs"],
+                "priority": PRIORITY_URGENT,
+                "headers": {SLA_HEADER: job_id},
+            }
+            if job.get("queue"):
+                options["queue"] = job["queue"]
+            self.send(job["task"], **options)
+
+            event = SlaAtRisk(
+                job_id=job_id,
+                task=job["task"],
+                queue=job.get("queue"),
+                deadline=datetime.fromtimestamp(job["deadline"], tz=timezone.utc),
+                remaining_seconds=round(job["deadline"] - now, 3),
+                sla_seconds=job["sla_seconds"],
+                organization_id=job.get("organization_id"),
+            )
+            logger.warning(
+                f"🚨 SlaAtRisk: {event.task} (job {job_id}) promoted, {event.remaining_seconds}s left"
+            )
+            events.append(event)
+
+            for listener in list(_listeners):
+                try:
+                    await listener(event)
+                except Exception as e:
+                    logger.error(f"❌ SlaAtRisk listener {getattr(listener, '__name__', listener)} failed: {e}")
+
+        return events
+
+
+sla_scheduler = SlaScheduler(queue_redis)
@@ -0,0 +1,243 @@
+"""
+Lightweight tracing for WhatsApp traffic (W3C trace context)
+
+Spans nest through a context variable, so a webhook span opened by the
+receiving endpoint becomes the parent of every Meta call made while handling
+it (auto-replies, flow messages...):
+
+    with tracer.start_span("whatsapp.webhook", parent=request.headers.get("traceparent")):
+        await service.process_webhook(...)
+
+Work handed to another process keeps the trace by passing
+current_traceparent() along and using it as the parent there, e.g.
+MetaCloudAPI(..., trace_parent=traceparent).
+
+Finished spans go to tracer.exporter (debug logs by default); other backends
+(OpenTelemetry collector, Jaeger...) subclass SpanExporter and implement export().
+"""
+
+import hashlib
+import json
+import logging
+import re
+import secrets
+import time
+from contextlib import contextmanager
+from contextvars import ContextVar
+from dataclasses import dataclass, field
+from typing import Any, Dict, Iterator, Optional, Union
+
+import httpx
+
+from app.core.whatsapp_metrics import endpoint_label
+
+logger = logging.getLogger(__name__)
+
+_TRACEPARENT = re.compile(r"^00-([0-9a-f]{32})-([0-9a-f]{16})-([0-9a-f]{2})$")
+
+
+@dataclass(frozen=True)
+class TraceContext:
+    """Trace and span ids, as carried by a traceparent header"""
+
+    trace_id: str
+    span_id: str
+    sampled: bool = True
+
+    @classmethod
+    def from_traceparent(cls, value: Optional[str]) -> Optional["TraceContext"]:
+        """Parse "00-<trace_id>-<span_id>-<flags>"; invalid headers are ignored"""
+        match = _TRACEPARENT.match((value or "").strip().lower())
+        if not match or set(match.group(1)) == {"0"} or set(match.group(2)) == {"0"}:
+            return None
+        return cls(match.group(1), match.group(2), bool(int(match.group(3), 16) & 1))
+
+    @property
+    def traceparent(self) -> str:
+        return f"00-{self.trace_id}-{self.span_id}-{'01' if self.sampled else '00'}"
+
+
+@dataclass
+class Span:
+    """Timed operation of a trace"""
+
+    name: str
+    context: TraceContext
+    parent_span_id: Optional[str] = None
+    attributes: Dict[str, Any] = field(default_factory=dict)
+    started_at: float = field(default_factory=time.time)
+    duration_ms: Optional[float] = None
+    error: Optional[str] = None
+
+    def set_attribute(self, key: str, value: Any) -> None:
+        if value is not None:
+            self.attributes[key] = value
+
+
+class SpanExporter:
+    """Receives finished spans"""
+
+    def export(self, span: Span) -> None:
+        raise NotImplementedError
+
+
+class LoggingSpanExporter(SpanExporter):
+    """Writes finished spans to the debug log"""
+
+    def export(self, span: Span) -> None:
+        logger.debug(
+            f"🔭 {span.name} trace={span.context.trace_id} span={span.context.span_id} "
+            f"parent={span.parent_span_id} {span.duration_ms}ms "
+            f"{'error=' + span.error if span.error else 'ok'} {span.attributes}"
+        )
+
+
+_current_span: ContextVar[Optional[Span]] = ContextVar("current_span", default=None)
+
+Parent = Union[TraceContext, str, None]
+
+
+class Tracer:
+    """Creates spans and hands them to the exporter when they end"""
+
+    def __init__(self, exporter: Optional[SpanExporter] = None):
+        self.exporter = exporter or LoggingSpanExporter()
+
+    @contextmanager
+    def start_span(self, name: str, parent: Parent = None, **attributes: Any) -> Iterator[Span]:
+        """
+        Run a block inside a span
+
+        Args:
+            name: Span name
+            parent: Parent context or traceparent header, used when no span is active
+            **attributes: Initial attributes (None values are skipped)
+        """
+        active = _current_span.get()
+        parent_context = active.context if active else _as_context(parent)
+
+        span = Span(
+            name=name,
+            context=TraceContext(
+                trace_id=parent_context.trace_id if parent_context else secrets.token_hex(16),
+                span_id=secrets.token_hex(8),
+                sampled=parent_context.sampled if parent_context else True,
+            ),
+            parent_span_id=parent_context.span_id if parent_context else None,
+        )
+        for key, value in attributes.items():
+            span.set_attribute(key, value)
+
+        token = _current_span.set(span)
+        started = time.perf_counter()
+        try:
+            yield span
+        except BaseException as e:
+            span.error = f"{type(e).__name__}: {e}"
+            raise
+        finally:
+            span.duration_ms = round((time.perf_counter() - started) * 1000, 2)
+            _current_span.reset(token)
+            if span.context.sampled:
+                try:
+                    self.exporter.export(span)
+                except Exception as e:
+                    # Tracing must never fail the traced operation
+                    logger.warning(f"⚠️ Span exporter failed for {name}: {e}")
+
+
+def _as_context(parent: Parent) -> Optional[TraceContext]:
+    if isinstance(parent, TraceContext):
+        return parent
+    return TraceContext.from_traceparent(parent)
+
+
+def current_span() -> Optional[Span]:
+    return _current_span.get()
+
+
+def current_traceparent() -> Optional[str]:
+    """traceparent of the active span, to continue the trace in another process"""
+    span = _current_span.get()
+    return span.context.traceparent if span else None
+
+
+def hash_recipient(phone: Optional[str]) -> Optional[str]:
+    """Stable, non-reversible recipient label (phone numbers never go to traces)"""
+    digits = re.sub(r"\D", "", phone or "")
+    if not digits:
+        return None
+    return hashlib.sha256(digits.encode("utf-8")).hexdigest()[:16]
+
+
+# ============================================
+# HTTP
+# ============================================
+
+
+def _message_attributes(request: httpx.Request) -> Dict[str, Any]:
+    """Recipient (hashed) and type of a message send"""
+    if "json" not in request.headers.get("Content-Type", ""):
+        return {}
+    try:
+        body = json.loads(request.content or b"{}")
+    except ValueError:
+        return {}
+    if not isinstance(body, dict):
+        return {}
+    return {"whatsapp.recipient_hash": hash_recipient(body.get("to")), "whatsapp.message_type": body.get("type")}
+
+
+async def _fbtrace_id(response: httpx.Response) -> Optional[str]:
+    """Graph request id: the x-fb-trace-id header, or error.fbtrace_id of an error body"""
+    if response.headers.get("x-fb-trace-id"):
+        return response.headers["x-fb-trace-id"]
+    if response.status_code < 400:
+        return None
+    try:
+        await response.aread()
+        return response.json().get("error", {}).get("fbtrace_id")
+    except Exception:
+        return None
+
+
+class TracingTransport(httpx.AsyncBaseTransport):
+    """httpx transport opening one span per WhatsApp API request"""
+
+    def __init__(
+        self,
+        target: str,
+        phone_number_id: Optional[str] = None,
+        parent: Parent = None,
+        wrapped: Optional[httpx.AsyncBaseTransport] = None,
+    ):
+        self.target = target
+        self.phone_number_id = phone_number_id
+        self.parent = parent
+        self.wrapped = wrapped or httpx.AsyncHTTPTransport()
+
+    async def handle_async_request(self, request: httpx.Request) -> httpx.Response:
+        endpoint = endpoint_label(request.url.path)
+        with tracer.start_span(
+            f"{self.target} {request.method} {endpoint}",
+            parent=self.parent,
+            **{"http.method": request.method, "whatsapp.endpoint": endpoint,
+               "whatsapp.phone_number_id": self.phone_number_id},
+        ) as span:
+            for key, value in _message_attributes(request).items():
+                span.set_attribute(key, value)
+
+            response = await self.wrapped.handle_async_request(request)
+
+            span.set_attribute("http.status_code", response.status_code)
+            span.set_attribute("meta.fbtrace_id", await _fbtrace_id(response))
+            if response.status_code >= 400:
+                span.error = f"HTTP {response.status_code}"
+            return response
+
+    async def aclose(self) -> None:
+        await self.wrapped.aclose()
+
+
+# Global instance
+tracer = Tracer()
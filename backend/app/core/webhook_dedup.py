@@ -0,0 +1,36 @@
+"""
+Deduplication of inbound Meta webhooks
+
+Meta redelivers webhooks when we answer slowly; without deduplication the
+same message would run its flow twice. Both receivers (the /webhooks/meta
+route and the Celery webhook task) share one WebhookProcessor whose store is
+chosen by WEBHOOK_DEDUP_BACKEND.
+"""
+
+from typing import Optional
+
+from app.core.config import settings
+from app.core.redis import queue_redis
+from pytake_client.processor import InMemoryDedupStore, RedisDedupStore, WebhookProcessor
+
+
+class AppRedisDedupStore(RedisDedupStore):
+    """RedisDedupStore over the app's RedisClient wrapper"""
+
+    async def _set_nx(self, key: str, ttl_seconds: int) -> bool:
+        return bool(await self.client.set(key, "1", expire=ttl_seconds, nx=True))
+
+
+_processor: Optional[WebhookProcessor] = None
+
+
+def get_webhook_processor() -> WebhookProcessor:
+    """Shared webhook processor configured from settings"""
+    global _processor
+    if _processor is None:
+        if settings.WEBHOOK_DEDUP_BACKEND == "memory":
+            store = InMemoryDedupStore()
+        else:
+            store = AppRedisDedupStore(queue_redis, prefix="webhook:seen:")
+        _processor = WebhookProcessor(store, ttl_seconds=settings.WEBHOOK_DEDUP_TTL_SECONDS)
+    return _processor
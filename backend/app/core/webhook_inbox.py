@@ -0,0 +1,186 @@
+"""
+Webhook inbox - recent webhook deliveries per organization (debugging)
+
+Keeps the last WEBHOOK_INBOX_SIZE inbound WhatsApp webhooks (with their
+processing outcome) and outbound webhook calls made by flows, so integrators
+can answer "did my webhook fire?" from GET /api/v1/debug/webhooks instead of
+asking for server logs.
+
+Entries live in a capped Redis list per organization. Credentials (auth
+headers, tokens, secrets...) are redacted, phone numbers are masked and long
+values truncated before anything is stored. Recording never raises.
+"""
+
+import json
+import logging
+import re
+import uuid
+from datetime import datetime, timezone
+from typing import Any, Dict, List, Optional
+from urllib.parse import parse_qsl, urlencode, urlsplit, urlunsplit
+from uuid import UUID
+
+from app.core.config import settings
+from app.core.redis import RedisClient, queue_redis
+
+logger = logging.getLogger(__name__)
+
+INBOX_KEY = "webhook:inbox:{organization_id}"
+REDACTED = "[REDACTED]"
+MAX_VALUE_LENGTH = 2000
+
+SENSITIVE_KEY_PATTERN = re.compile(
+    r"authorization|token|secret|password|passwd|api[-_]?key|signature|cookie|credential",
+    re.IGNORECASE,
+)
+PHONE_KEYS = {"from", "to", "wa_id", "recipient_id", "phone", "display_phone_number"}
+
+
+def mask_phone(value: str) -> str:
+    """Keep only the last 4 digits of a phone number"""
+    return "*" * max(len(value) - 4, 0) + value[-4:]
+
+
+def redact(value: Any, key: Optional[str] = None) -> Any:
+    """Copy of a payload safe to show in the inbox"""
+    if key and SENSITIVE_KEY_PATTERN.search(key):
+        return REDACTED
+    if isinstance(value, dict):
+        return {k: redact(v, str(k)) for k, v in value.items()}
+    if isinstance(value, list):
+        return [redact(item) for item in value]
+    if isinstance(value, str):
+        if key in PHONE_KEYS and value.isdigit():
+            return mask_phone(value)
+        if len(value) > MAX_VALUE_LENGTH:
+            return value[:MAX_VALUE_LENGTH] + f"... ({len(value)} chars)"
+    return value
+
+
+def redact_url(url: str) -> str:
+    """URL with credentials in userinfo or query string redacted"""
+    try:
+        parts = urlsplit(url)
+    except ValueError:
+        return url
+    netloc = parts.netloc
+    if "@" in netloc:
+        netloc = f"{REDACTED}@{netloc.rsplit('@', 1)[1]}"
+    query = urlencode([
+        (name, REDACTED if SENSITIVE_KEY_PATTERN.search(name) else value)
+        for name, value in parse_qsl(parts.query, keep_blank_values=True)
+    ], safe="[]")
+    return urlunsplit((parts.scheme, netloc, parts.path, query, parts.fragment))
+
+
+class WebhookInbox:
+    """Capped list of recent webhook deliveries per organization"""
+
+    def __init__(self, redis: RedisClient, size: Optional[int] = None, ttl_days: Optional[int] = None):
+        self.redis = redis
+        self.size = settings.WEBHOOK_INBOX_SIZE if size is None else size
+        self.ttl_seconds = (settings.WEBHOOK_INBOX_TTL_DAYS if ttl_days is None else ttl_days) * 86400
+
+    async def record_inbound(
+        self,
+        organization_id: Optional[UUID],
+        payload: Dict[str, Any],
+        status: str,
+        source: str = "meta",
+        error: Optional[str] = None,
+        event_id: Optional[UUID] = None,
+    ) -> None:
+        """Inbound WhatsApp webhook and its processing outcome (processed, failed, skipped)"""
+        await self._push(organization_id, {
+            "type": "inbound",
+            "status": status,
+            "source": source,
+            "event_id": str(event_id) if event_id else None,
+            "fields": sorted({
+                change.get("field") for entry in payload.get("entry", [])
+                for change in entry.get("changes", []) if change.get("field")
+            }),
+            "error": error,
+            "payload": redact(payload),
+        })
+
+    async def record_outbound(
+        self,
+        organization_id: Optional[UUID],
+        method: str,
+        url: str,
+        headers: Optional[Dict[str, Any]] = None,
+        body: Any = None,
+        status_code: Optional[int] = None,
+        response_body: Optional[str] = None,
+        error: Optional[str] = None,
+        duration_ms: Optional[int] = None,
+        source: Optional[str] = None,
+    ) -> None:
+        """
+        Outbound webhook attempt
+
+        Status is delivered for 2xx/3xx responses, failed for error responses
+        and connection errors/timeouts.
+        """
+        delivered = error is None and status_code is not None and status_code < 400
+        await self._push(organization_id, {
+            "type": "outbound",
+            "status": "delivered" if delivered else "failed",
+            "source": source,
+            "method": method,
+            "url": redact_url(url),
+            "status_code": status_code,
+            "duration_ms": duration_ms,
+            "error": error or (f"HTTP {status_code}" if not delivered else None),
+            "request": {"headers": redact(headers or {}), "body": redact(body)},
+            "response": redact(response_body) if response_body is not None else None,
+        })
+
+    async def list(
+        self,
+        organization_id: UUID,
+        type: Optional[str] = None,
+        status: Optional[str] = None,
+        limit: int = 50,
+    ) -> List[Dict[str, Any]]:
+        """Recent deliveries, newest first"""
+        try:
+            raw = await self.redis.lrange(self._key(organization_id), 0, -1)
+        except Exception as e:
+            logger.warning(f"⚠️ Webhook inbox unavailable: {e}")
+            return []
+
+        entries = []
+        for item in raw or []:
+            entry = json.loads(item)
+            if (type and entry.get("type") != type) or (status and entry.get("status") != status):
+                continue
+            entries.append(entry)
+            if len(entries) >= limit:
+                break
+        return entries
+
+    async def clear(self, organization_id: UUID) -> None:
+        await self.redis.delete(self._key(organization_id))
+
+    @staticmethod
+    def _key(organization_id: UUID) -> str:
+        return INBOX_KEY.format(organization_id=organization_id)
+
+    async def _push(self, organization_id: Optional[UUID], entry: Dict[str, Any]) -> None:
+        if not organization_id or self.size <= 0:
+            return
+
+        entry = {"id": str(uuid.uuid4()), "at": datetime.now(timezone.utc).isoformat(), **entry}
+        key = self._key(organization_id)
+        try:
+            await self.redis.lpush(key, json.dumps(entry, default=str))
+            await self.redis.ltrim(key, 0, self.size - 1)
+            await self.redis.expire(key, self.ttl_seconds)
+        except Exception as e:
+            logger.warning(f"⚠️ Could not record webhook in inbox: {e}")
+
+
+# Global instance
+webhook_inbox = WebhookInbox(queue_redis)
@@ -0,0 +1,95 @@
+"""
+Routing of inbound Meta webhooks to the number that received them
+
+Meta sends the events of every number of the app to one webhook URL. The
+shared PhoneNumberRouter maps ``metadata.phone_number_id`` to the registered
+WhatsAppNumber and its organization (cached per process for
+WEBHOOK_ROUTE_CACHE_SECONDS); receivers process a change only when its
+number routes.
+
+Unroutable events (no phone_number_id, or a number no organization owns) are
+counted in Redis so the totals cover the API and every worker, and listed in
+GET /api/v1/debug/webhooks/routing. A steady flow of unknown numbers usually
+means a number was removed here but is still subscribed on Meta's side.
+"""
+
+import logging
+from datetime import datetime, timezone
+from typing import Any, Dict, Optional
+
+from sqlalchemy import select
+
+from app.core.config import settings
+from app.core.redis import RedisClient, queue_redis
+from app.models.whatsapp_number import WhatsAppNumber
+from pytake_client.processor import NumberRoute, PhoneNumberRouter
+from pytake_client.webhooks import WebhookEvent
+
+logger = logging.getLogger(__name__)
+
+UNROUTABLE_KEY = "webhook:routing:unroutable"
+UNKNOWN_NUMBERS_KEY = "webhook:routing:unknown_numbers"
+LAST_UNROUTABLE_KEY = "webhook:routing:last_unroutable_at"
+
+
+async def resolve_whatsapp_number(phone_number_id: str) -> Optional[NumberRoute]:
+    """Registered number for a phone_number_id (tenant = its organization)"""
+    from app.core.database import async_session
+
+    async with async_session() as db:
+        number = await db.scalar(
+            select(WhatsAppNumber).where(
+                WhatsAppNumber.phone_number_id == phone_number_id,
+                WhatsAppNumber.deleted_at.is_(None),
+            ).limit(1)
+        )
+
+    if not number:
+        return None
+
+    return NumberRoute(
+        phone_number_id=phone_number_id,
+        tenant=number.organization_id,
+        config={
+            "whatsapp_number_id": number.id,
+            "organization_id": number.organization_id,
+            "connection_type": str(getattr(number.connection_type, "value", number.connection_type)),
+        },
+    )
+
+
+class UnroutableCounter:
+    """Cluster-wide counters of unroutable webhook events"""
+
+    def __init__(self, redis: RedisClient):
+        self.redis = redis
+
+    async def __call__(
+        self, reason: str, phone_number_id: Optional[str], event: Optional[WebhookEvent] = None
+    ) -> None:
+        await self.redis.hincrby(UNROUTABLE_KEY, reason, 1)
+        if phone_number_id:
+            await self.redis.hincrby(UNKNOWN_NUMBERS_KEY, phone_number_id, 1)
+        await self.redis.set(LAST_UNROUTABLE_KEY, datetime.now(timezone.utc).isoformat())
+
+    async def stats(self) -> Dict[str, Any]:
+        unroutable = {key: int(value) for key, value in (await self.redis.hgetall(UNROUTABLE_KEY) or {}).items()}
+        unknown = await self.redis.hgetall(UNKNOWN_NUMBERS_KEY) or {}
+        return {
+            "unroutable": unroutable,
+            "unroutable_total": sum(unroutable.values()),
+            "unknown_numbers": {key: int(value) for key, value in unknown.items()},
+            "last_unroutable_at": await self.redis.get(LAST_UNROUTABLE_KEY),
+        }
+
+    async def reset(self) -> None:
+        await self.redis.delete(UNROUTABLE_KEY, UNKNOWN_NUMBERS_KEY, LAST_UNROUTABLE_KEY)
+
+
+unroutable_counter = UnroutableCounter(queue_redis)
+
+number_router = PhoneNumberRouter(
+    resolver=resolve_whatsapp_number,
+    cache_ttl_seconds=settings.WEBHOOK_ROUTE_CACHE_SECONDS,
+    on_unroutable=unroutable_counter,
+)
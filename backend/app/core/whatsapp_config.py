@@ -0,0 +1,94 @@
+"""
+Egress settings of the WhatsApp API clients
+
+Deployments that must reach Meta/Evolution through a corporate proxy set
+WHATSAPP_PROXY_URL (http://, https://, socks5:// or socks5h:// - SOCKS needs
+the httpx[socks] extra) and, when the proxy inspects TLS, WHATSAPP_CA_BUNDLE
+with the proxy's CA. http_client() applies them to the WhatsApp targets only;
+flow API calls and other integrations keep connecting directly.
+
+WhatsApp clients of every number share one connection pool per event loop
+(Celery tasks run each job in a new loop), so sends reuse open connections.
+"""
+
+import asyncio
+import ssl
+import weakref
+from dataclasses import dataclass
+from functools import cached_property
+from typing import Optional, Union
+
+import httpx
+
+from app.core.config import settings
+
+# http_client() targets that talk to WhatsApp providers
+WHATSAPP_TARGETS = ("meta_api", "meta_ads_api", "evolution_api")
+
+
+@dataclass(frozen=True)
+class WhatsAppConfig:
+    """Proxy, TLS and timeouts used when building WhatsApp API clients"""
+
+    proxy_url: Optional[str] = None
+    ca_bundle: Optional[str] = None
+    timeout_seconds: float = 30.0
+    connect_timeout_seconds: float = 10.0
+
+    @classmethod
+    def from_settings(cls) -> "WhatsAppConfig":
+        return cls(
+            proxy_url=settings.WHATSAPP_PROXY_URL or None,
+            ca_bundle=settings.WHATSAPP_CA_BUNDLE or None,
+            timeout_seconds=settings.WHATSAPP_TIMEOUT_SECONDS,
+            connect_timeout_seconds=settings.WHATSAPP_CONNECT_TIMEOUT_SECONDS,
+        )
+
+    def timeout(self, seconds: Optional[float] = None) -> httpx.Timeout:
+        """Timeout of a call (default timeout_seconds); connecting never waits longer than connect_timeout_seconds"""
+        seconds = seconds or self.timeout_seconds
+        return httpx.Timeout(seconds, connect=min(seconds, self.connect_timeout_seconds))
+
+    @cached_property
+    def verify(self) -> Union[bool, ssl.SSLContext]:
+        """System CAs, or the custom bundle (loaded once)"""
+        if not self.ca_bundle:
+            return True
+        return ssl.create_default_context(cafile=self.ca_bundle)
+
+    def new_transport(self) -> httpx.AsyncHTTPTransport:
+        return httpx.AsyncHTTPTransport(verify=self.verify, proxy=self.proxy_url)
+
+    @cached_property
+    def _pools(self) -> "weakref.WeakKeyDictionary[asyncio.AbstractEventLoop, SharedTransport]":
+        return weakref.WeakKeyDictionary()
+
+    def transport(self) -> httpx.AsyncBaseTransport:
+        """Connection pool of the running event loop (a new one outside a loop)"""
+        try:
+            loop = asyncio.get_running_loop()
+        except RuntimeError:
+            return self.new_transport()
+
+        pool = self._pools.get(loop)
+        if pool is None:
+            pool = self._pools[loop] = SharedTransport(self.new_transport())
+        return pool
+
+
+class SharedTransport(httpx.AsyncBaseTransport):
+    """Transport kept open when the client using it is closed"""
+
+    def __init__(self, wrapped: httpx.AsyncBaseTransport):
+        self.wrapped = wrapped
+
+    async def handle_async_request(self, request: httpx.Request) -> httpx.Response:
+        return await self.wrapped.handle_async_request(request)
+
+    async def aclose(self) -> None:
+        # Owned by WhatsAppConfig; clients come and go, the pool stays
+        pass
+
+
+# Global instance (from settings)
+whatsapp_config = WhatsAppConfig.from_settings()
@@ -0,0 +1,172 @@
+"""
+Request metrics of the WhatsApp API clients
+
+MetaCloudAPI passes a MetricsSink to http_client(), which wraps the transport
+so every call is recorded once (count, latency, status and Meta's rate-limit
+usage headers) labelled with the phone number, without touching call sites:
+
+    api = MetaCloudAPI(phone_number_id, token, metrics=my_sink)
+
+The default sink (whatsapp_metrics) keeps per-number aggregates of this
+process, exported by GET /debug/whatsapp/metrics. Other backends
+(Prometheus, StatsD...) subclass MetricsSink and implement record().
+"""
+
+import json
+import logging
+import time
+from collections import Counter
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from typing import Any, Dict, Mapping, Optional
+
+import httpx
+
+logger = logging.getLogger(__name__)
+
+# Usage headers Meta returns on Graph API calls (JSON, % of the quota used)
+RATE_LIMIT_HEADERS = ("x-business-use-case-usage", "x-app-usage", "x-ad-account-usage")
+
+
+@dataclass
+class RequestMetric:
+    """One call to a WhatsApp API"""
+
+    target: str
+    phone_number_id: Optional[str]
+    method: str
+    endpoint: str
+    status_code: Optional[int]  # None when no response was received
+    latency_ms: float
+    rate_limit: Dict[str, Any] = field(default_factory=dict)
+
+    @property
+    def failed(self) -> bool:
+        return self.status_code is None or self.status_code >= 400
+
+
+class MetricsSink:
+    """Receives one RequestMetric per WhatsApp API call"""
+
+    def record(self, metric: RequestMetric) -> None:
+        raise NotImplementedError
+
+
+class InMemoryMetricsSink(MetricsSink):
+    """Per-number aggregates kept in this process"""
+
+    def __init__(self):
+        self._numbers: Dict[str, Dict[str, Any]] = {}
+
+    def record(self, metric: RequestMetric) -> None:
+        stats = self._numbers.setdefault(metric.phone_number_id or "unknown", {
+            "requests": 0,
+            "errors": 0,
+            "status_codes": Counter(),
+            "endpoints": Counter(),
+            "latency_ms_total": 0.0,
+            "latency_ms_max": 0.0,
+            "rate_limit": {},
+            "last_request_at": None,
+        })
+        stats["requests"] += 1
+        stats["errors"] += int(metric.failed)
+        stats["status_codes"][str(metric.status_code or "network_error")] += 1
+        stats["endpoints"][f"{metric.method} {metric.endpoint}"] += 1
+        stats["latency_ms_total"] += metric.latency_ms
+        stats["latency_ms_max"] = max(stats["latency_ms_max"], metric.latency_ms)
+        if metric.rate_limit:
+            stats["rate_limit"] = metric.rate_limit
+        stats["last_request_at"] = datetime.now(timezone.utc)
+
+    def snapshot(self) -> Dict[str, Dict[str, Any]]:
+        """Aggregates per phone_number_id, with the average latency"""
+        result = {}
+        for phone_number_id, stats in self._numbers.items():
+            result[phone_number_id] = {
+                "requests": stats["requests"],
+                "errors": stats["errors"],
+                "status_codes": dict(stats["status_codes"]),
+                "endpoints": dict(stats["endpoints"]),
+                "latency_ms_avg": round(stats["latency_ms_total"] / stats["requests"], 2),
+                "latency_ms_max": round(stats["latency_ms_max"], 2),
+                "rate_limit": stats["rate_limit"],
+                "last_request_at": stats["last_request_at"],
+            }
+        return result
+
+    def reset(self) -> None:
+        self._numbers.clear()
+
+
+def endpoint_label(path: str) -> str:
+    """
+    Low-cardinality name of a Graph API path: the edge after the node id
+    ("/v21.0/123/messages" -> "messages"), or "node" for the node itself
+    """
+    parts = [part for part in path.strip("/").split("/") if part]
+    if parts and parts[0].startswith("v") and "." in parts[0]:
+        parts = parts[1:]
+    return "/".join(parts[1:]) or "node"
+
+
+def rate_limit_usage(headers: Mapping[str, str]) -> Dict[str, Any]:
+    """Meta usage headers present on a response, JSON-decoded when possible"""
+    usage = {}
+    for name in RATE_LIMIT_HEADERS:
+        value = headers.get(name)
+        if value is None:
+            continue
+        try:
+            usage[name] = json.loads(value)
+        except ValueError:
+            usage[name] = value
+    return usage
+
+
+class MetricsTransport(httpx.AsyncBaseTransport):
+    """httpx transport recording each request in a MetricsSink"""
+
+    def __init__(
+        self,
+        target: str,
+        sink: MetricsSink,
+        phone_number_id: Optional[str] = None,
+        wrapped: Optional[httpx.AsyncBaseTransport] = None,
+    ):
+        self.target = target
+        self.sink = sink
+        self.phone_number_id = phone_number_id
+        self.wrapped = wrapped or httpx.AsyncHTTPTransport()
+
+    async def handle_async_request(self, request: httpx.Request) -> httpx.Response:
+        started = time.perf_counter()
+        response = None
+        try:
+            response = await self.wrapped.handle_async_request(request)
+            return response
+        finally:
+            self._record(request, response, (time.perf_counter() - started) * 1000)
+
+    def _record(self, request: httpx.Request, response: Optional[httpx.Response], latency_ms: float) -> None:
+        metric = RequestMetric(
+            target=self.target,
+            phone_number_id=self.phone_number_id,
+            method=request.method,
+            endpoint=endpoint_label(request.url.path),
+            status_code=response.status_code if response is not None else None,
+            latency_ms=latency_ms,
+            rate_limit=rate_limit_usage(response.headers) if response is not None else {},
+        )
+        try:
+            self.sink.record(metric)
+        except Exception as e:
+            # Metrics must never fail a WhatsApp call
+            logger.warning(f"⚠️ Metrics sink failed for {self.target}: {e}")
+
+    async def aclose(self) -> None:
+        await self.wrapped.aclose()
+
+
+# Global instance (per-process aggregates)
+whatsapp_metrics = InMemoryMetricsSink()
@@ -6,14 +6,25 @@ Handles rate limiting per WhatsApp number with different strategies:
 - Evolution API (QR Code): Unlimited with 500ms delay between messages
 
 Tracks usage in Redis for distributed rate limiting.
+
+MessagingTierShaper enforces the Meta messaging limit tier of each number
+(unique customers reached by business-initiated messages in a rolling 24h)
+across every sender of that number: overflow gets a reserved slot with an
+ETA instead of an error.
 """
 
+import asyncio
 import logging
-from datetime import datetime, timedelta
-from typing import Optional, Tuple
+import time
+import uuid
+from contextlib import asynccontextmanager
+from dataclasses import dataclass
+from datetime import datetime, timedelta, timezone
+from typing import AsyncIterator, List, Optional, Tuple
 from uuid import UUID
 
-from app.core.redis import redis_client
+from app.core.redis import rate_limit_redis
+from app.integrations.meta_api import MetaCloudAPI
 
 logger = logging.getLogger(__name__)
 
@@ -96,7 +107,7 @@ class WhatsAppRateLimiter:
             return False, f"Hourly soft limit reached ({self.EVOLUTION_HOURLY_LIMIT}/hour)"
         
         # Check minimum delay between messages
-        last_send_time = await redis_client.get(self.last_message_key)
+        last_send_time = await rate_limit_redis.get(self.last_message_key)
         if last_send_time:
             last_send = float(last_send_time)
             time_since_last = datetime.now().timestamp() - last_send
@@ -114,10 +125,10 @@ class WhatsAppRateLimiter:
             await self._increment_counter(self.minute_key, ttl=60)    # 1 minute
             
             # Record timestamp of last message
-            await redis_client.set(
+            await rate_limit_redis.set(
                 self.last_message_key,
                 str(datetime.now().timestamp()),
-                ex=60  # Expire after 1 minute
+                expire=60  # Expire after 1 minute
             )
             
             logger.debug(f"Recorded message sent for {self.whatsapp_number_id}")
@@ -169,10 +180,10 @@ class WhatsAppRateLimiter:
     async def reset_counters(self):
         """Reset all counters (for testing or manual reset)"""
         try:
-            await redis_client.delete(self.daily_key)
-            await redis_client.delete(self.hourly_key)
-            await redis_client.delete(self.minute_key)
-            await redis_client.delete(self.last_message_key)
+            await rate_limit_redis.delete(self.daily_key)
+            await rate_limit_redis.delete(self.hourly_key)
+            await rate_limit_redis.delete(self.minute_key)
+            await rate_limit_redis.delete(self.last_message_key)
             logger.info(f"Reset counters for {self.whatsapp_number_id}")
         except Exception as e:
             logger.error(f"Error resetting counters: {e}")
@@ -180,7 +191,7 @@ class WhatsAppRateLimiter:
     async def _get_counter(self, key: str) -> int:
         """Get counter value from Redis"""
         try:
-            value = await redis_client.get(key)
+            value = await rate_limit_redis.get(key)
             return int(value) if value else 0
         except Exception as e:
             logger.error(f"Error getting counter {key}: {e}")
@@ -190,9 +201,9 @@ class WhatsAppRateLimiter:
         """Increment counter in Redis with TTL"""
         try:
             # Increment counter
-            await redis_client.incr(key)
+            await rate_limit_redis.incr(key)
             # Set TTL only if key is new (NX = Not eXists)
-            await redis_client.expire(key, ttl)
+            await rate_limit_redis.expire(key, ttl)
         except Exception as e:
             logger.error(f"Error incrementing counter {key}: {e}")
     
@@ -217,7 +228,7 @@ class WhatsAppRateLimiter:
             return 86400.0  # Wait 24 hours
         elif "Min delay" in reason:
             # Wait for minimum delay
-            last_send_time = await redis_client.get(self.last_message_key)
+            last_send_time = await rate_limit_redis.get(self.last_message_key)
             if last_send_time:
                 last_send = float(last_send_time)
                 elapsed = datetime.now().timestamp() - last_send
@@ -278,3 +289,209 @@ async def check_and_wait_for_rate_limit(
             return True, None
     
     return True, None
+
+
+
+# ============================================
+# MESSAGING TIER SHAPING
+# ============================================
+
+TIER_WINDOW_SECONDS = 24 * 3600
+
+# New numbers start at TIER_250 until the real tier is synced from Meta
+DEFAULT_MESSAGING_TIER = "TIER_250"
+
+TIER_LOCK_TTL = 5  # seconds
+TIER_LOCK_WAIT = 2.0  # seconds
+
+
+def tier_limit(tier: Optional[str]) -> Optional[int]:
+    """Unique recipients per 24h allowed by a tier (None = unlimited)"""
+    limits = MetaCloudAPI.MESSAGING_LIMITS
+    if tier not in limits:
+        tier = DEFAULT_MESSAGING_TIER
+    return limits[tier]
+
+
+def next_slot_at(scores: List[float], limit: int, now: float) -> float:
+    """
+    Timestamp at which one more recipient fits in the tier window
+
+    Args:
+        scores: Ascending slot timestamps still in the window (past = sent,
+            future = already queued)
+        limit: Tier limit
+        now: Current timestamp
+
+    Returns:
+        ``now`` if there is room, else when the slot it takes over expires
+    """
+    if len(scores) < limit:
+        return now
+    return max(now, scores[len(scores) - limit] + TIER_WINDOW_SECONDS)
+
+
+@dataclass
+class TierDecision:
+    """Result of reserving a tier slot for a recipient"""
+
+    allowed: bool
+    send_at: datetime
+    tier: str
+    limit: Optional[int]
+    reserved: bool = False  # True if this call took a new slot (release it if the send fails)
+
+    @property
+    def wait_seconds(self) -> float:
+        return max(0.0, (self.send_at - datetime.now(timezone.utc)).total_seconds())
+
+
+class MessagingTierShaper:
+    """
+    Shapes business-initiated sends of a number to its messaging limit tier
+
+    Every recipient takes a slot in a Redis sorted set scored by the time
+    it counts against the tier. Recipients already counted in the window are
+    free; when the tier is full the recipient is queued at the time the
+    oldest slot expires (shifted by the sends queued before it).
+    """
+
+    def __init__(self, whatsapp_number_id: str, tier: Optional[str]):
+        self.whatsapp_number_id = whatsapp_number_id
+        self.tier = tier if tier in MetaCloudAPI.MESSAGING_LIMITS else DEFAULT_MESSAGING_TIER
+        self.limit = tier_limit(self.tier)
+
+        self.recipients_key = f"whatsapp:tier:{whatsapp_number_id}:recipients"
+        self.lock_key = f"whatsapp:tier:{whatsapp_number_id}:lock"
+
+    async def reserve(self, recipient: str, now: Optional[float] = None) -> TierDecision:
+        """
+        Reserve a tier slot for a recipient
+
+        Args:
+            recipient: WhatsApp id of the customer
+            now: Current timestamp (defaults to time.time())
+
+        Returns:
+            TierDecision (allowed now, or queued until send_at)
+        """
+        now = now if now is not None else time.time()
+        try:
+            async with self._lock():
+                await rate_limit_redis.zremrangebyscore(
+                    self.recipients_key, "-inf", now - TIER_WINDOW_SECONDS
+                )
+
+                # Already counted (or queued) in the window: no extra slot
+                score = await rate_limit_redis.zscore(self.recipients_key, recipient)
+                if score is not None:
+                    return self._decision(float(score), now, reserved=False)
+
+                score = now
+                if self.limit is not None:
+                    entries = await rate_limit_redis.zrange(self.recipients_key, 0, -1, withscores=True)
+                    score = next_slot_at([s for _, s in entries], self.limit, now)
+
+                await rate_limit_redis.zadd(self.recipients_key, {recipient: score})
+                await rate_limit_redis.expire(
+                    self.recipients_key, int(score - now) + TIER_WINDOW_SECONDS
+                )
+        except Exception as e:
+            logger.error(f"Error reserving tier slot for {self.whatsapp_number_id}: {e}")
+            # On error, allow sending (fail open)
+            return self._decision(now, now, reserved=False)
+
+        decision = self._decision(score, now, reserved=True)
+        if not decision.allowed:
+            logger.info(
+                f"⏳ Tier {self.tier} full for {self.whatsapp_number_id}: "
+                f"{recipient} queued until {decision.send_at.isoformat()}"
+            )
+        return decision
+
+    async def record(self, recipient: str, now: Optional[float] = None) -> None:
+        """Count a send that could not be deferred (e.g. agent template)"""
+        now = now if now is not None else time.time()
+        try:
+            if await rate_limit_redis.zscore(self.recipients_key, recipient) is None:
+                await rate_limit_redis.zadd(self.recipients_key, {recipient: now})
+                await rate_limit_redis.expire(self.recipients_key, TIER_WINDOW_SECONDS)
+        except Exception as e:
+            logger.error(f"Error recording tier slot for {self.whatsapp_number_id}: {e}")
+
+    async def release(self, recipient: str) -> None:
+        """Free the slot of a send that failed (the customer was not reached)"""
+        try:
+            await rate_limit_redis.zrem(self.recipients_key, recipient)
+        except Exception as e:
+            logger.error(f"Error releasing tier slot for {self.whatsapp_number_id}: {e}")
+
+    async def get_utilization(self, now: Optional[float] = None) -> dict:
+        """Tier utilization: recipients counted in the window and queued sends"""
+        now = now if now is not None else time.time()
+        try:
+            entries = await rate_limit_redis.zrange(self.recipients_key, 0, -1, withscores=True)
+        except Exception as e:
+            logger.error(f"Error getting tier utilization: {e}")
+            entries = []
+
+        scores = [s for _, s in entries if s > now - TIER_WINDOW_SECONDS]
+        used = sum(1 for s in scores if s <= now)
+        queued = len(scores) - used
+
+        next_slot = None
+        if self.limit is not None:
+            next_slot = datetime.fromtimestamp(next_slot_at(scores, self.limit, now), tz=timezone.utc)
+
+        return {
+            "tier": self.tier,
+            "limit": self.limit,
+            "window_hours": TIER_WINDOW_SECONDS // 3600,
+            "used": used,
+            "queued": queued,
+            "remaining": max(0, self.limit - len(scores)) if self.limit is not None else None,
+            "percentage": round(used / self.limit * 100, 1) if self.limit else 0.0,
+            "next_slot_at": next_slot.isoformat() if next_slot else None,
+        }
+
+    def _decision(self, score: float, now: float, reserved: bool) -> TierDecision:
+        return TierDecision(
+            allowed=score <= now,
+            send_at=datetime.fromtimestamp(max(score, now), tz=timezone.utc),
+            tier=self.tier,
+            limit=self.limit,
+            reserved=reserved,
+        )
+
+    @asynccontextmanager
+    async def _lock(self) -> AsyncIterator[None]:
+        """Serialize reservations of a number across workers (best effort)"""
+        token = uuid.uuid4().hex
+        deadline = time.monotonic() + TIER_LOCK_WAIT
+        acquired = False
+        while not acquired:
+            acquired = await rate_limit_redis.set(self.lock_key, token, expire=TIER_LOCK_TTL, nx=True)
+            if acquired or time.monotonic() >= deadline:
+                break
+            await asyncio.sleep(0.05)
+
+        if not acquired:
+            logger.warning(f"⚠️ Tier lock busy for {self.whatsapp_number_id}, reserving without it")
+
+        try:
+            yield
+        finally:
+            if acquired and await rate_limit_redis.get(self.lock_key) == token:
+                await rate_limit_redis.delete(self.lock_key)
+
+
+def get_tier_shaper(whatsapp_number) -> Optional[MessagingTierShaper]:
+    """
+    Tier shaper of a WhatsApp number
+
+    Returns:
+        MessagingTierShaper, or None for QR code numbers (no Meta tier)
+    """
+    if whatsapp_number.connection_type != "official":
+        return None
+    return MessagingTierShaper(str(whatsapp_number.id), whatsapp_number.messaging_limit_tier)
@@ -6,10 +6,13 @@ for WhatsApp connection via QR Code (Baileys library)
 """
 
 import logging
-from typing import Dict, Any, Optional
+from typing import Dict, Any, List, Optional
 import httpx
 from datetime import datetime, timedelta
 
+from app.core.fault_injection import http_client
+from app.core.whatsapp_config import whatsapp_config
+
 logger = logging.getLogger(__name__)
 
 
@@ -97,7 +100,7 @@ class EvolutionAPIClient:
         }
 
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.post(
                     f"{self.api_url}/instance/create",
                     json=payload,
@@ -124,7 +127,7 @@ class EvolutionAPIClient:
             Connection data with QR Code (base64)
         """
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.get(
                     f"{self.api_url}/instance/connect/{instance_name}",
                     headers=self.headers
@@ -150,7 +153,7 @@ class EvolutionAPIClient:
             QR Code as base64 string or None if not available
         """
         try:
-            async with httpx.AsyncClient(timeout=10.0) as client:
+            async with http_client("evolution_api", timeout=10.0) as client:
                 response = await client.get(
                     f"{self.api_url}/instance/qrcode/{instance_name}",
                     headers=self.headers
@@ -181,7 +184,7 @@ class EvolutionAPIClient:
             Status data (connected, disconnected, etc)
         """
         try:
-            async with httpx.AsyncClient(timeout=10.0) as client:
+            async with http_client("evolution_api", timeout=10.0) as client:
                 response = await client.get(
                     f"{self.api_url}/instance/connectionState/{instance_name}",
                     headers=self.headers
@@ -206,7 +209,7 @@ class EvolutionAPIClient:
             True if deleted successfully
         """
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.delete(
                     f"{self.api_url}/instance/delete/{instance_name}",
                     headers=self.headers
@@ -224,7 +227,8 @@ class EvolutionAPIClient:
         self,
         instance_name: str,
         phone_number: str,
-        message: str
+        message: str,
+        reply_to_message_id: Optional[str] = None
     ) -> Dict[str, Any]:
         """
         Send text message via Evolution API
@@ -233,6 +237,7 @@ class EvolutionAPIClient:
             instance_name: Instance identifier
             phone_number: Recipient phone number (with country code, no +)
             message: Text message to send
+            reply_to_message_id: Message key ID to quote (optional)
 
         Returns:
             Message send result with message ID
@@ -242,8 +247,11 @@ class EvolutionAPIClient:
             "text": message,
         }
 
+        if reply_to_message_id:
+            payload["quoted"] = {"key": {"id": reply_to_message_id}}
+
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.post(
                     f"{self.api_url}/message/sendText/{instance_name}",
                     json=payload,
@@ -259,6 +267,96 @@ class EvolutionAPIClient:
             logger.error(f"Error sending message: {e}")
             raise EvolutionAPIError(f"Failed to send message: {str(e)}")
 
+    async def send_presence(
+        self,
+        instance_name: str,
+        phone_number: str,
+        presence: str = "composing",
+        delay_ms: int = 3000,
+    ) -> Dict[str, Any]:
+        """
+        Send chat presence (typing/recording/paused) via Evolution API
+
+        Args:
+            instance_name: Instance identifier
+            phone_number: Recipient phone number (with country code, no +)
+            presence: composing, recording, paused, available, unavailable
+            delay_ms: How long the presence is shown (milliseconds)
+
+        Returns:
+            API response
+        """
+        payload = {
+            "number": phone_number,
+            "presence": presence,
+            "delay": delay_ms,
+        }
+
+        try:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
+                response = await client.post(
+                    f"{self.api_url}/chat/sendPresence/{instance_name}",
+                    json=payload,
+                    headers=self.headers
+                )
+                response.raise_for_status()
+                return response.json()
+
+        except httpx.HTTPError as e:
+            logger.error(f"Error sending presence: {e}")
+            raise EvolutionAPIError(f"Failed to send presence: {str(e)}")
+
+    async def send_typing_on(self, instance_name: str, phone_number: str, delay_ms: int = 3000) -> Dict[str, Any]:
+        """Show "typing..." to the contact"""
+        return await self.send_presence(instance_name, phone_number, "composing", delay_ms)
+
+    async def send_typing_off(self, instance_name: str, phone_number: str) -> Dict[str, Any]:
+        """Clear the typing presence"""
+        return await self.send_presence(instance_name, phone_number, "paused", 0)
+
+    async def mark_messages_as_read(
+        self,
+        instance_name: str,
+        phone_number: str,
+        message_ids: List[str],
+    ) -> Dict[str, Any]:
+        """
+        Mark messages received from a contact as read (blue ticks)
+
+        Args:
+            instance_name: Instance identifier
+            phone_number: Contact phone number (with country code, no +)
+            message_ids: Message key IDs received from the contact
+
+        Returns:
+            API response
+        """
+        remote_jid = phone_number if "@" in phone_number else f"{phone_number}@s.whatsapp.net"
+        payload = {
+            "readMessages": [
+                {"remoteJid": remote_jid, "fromMe": False, "id": message_id}
+                for message_id in dict.fromkeys(message_ids)
+            ]
+        }
+
+        try:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
+                response = await client.post(
+                    f"{self.api_url}/chat/markMessageAsRead/{instance_name}",
+                    json=payload,
+                    headers=self.headers
+                )
+                response.raise_for_status()
+                return response.json()
+
+        except httpx.HTTPError as e:
+            logger.error(f"Error marking messages as read: {e}")
+            raise EvolutionAPIError(f"Failed to mark messages as read: {str(e)}")
+
+    async def mark_message_as_read(self, instance_name: str, phone_number: str, message_id: str) -> Dict[str, Any]:
+        """Mark a single message received from a contact as read"""
+        return await self.mark_messages_as_read(instance_name, phone_number, [message_id])
+
     async def logout_instance(self, instance_name: str) -> bool:
         """
         Logout from WhatsApp (disconnect but keep instance)
@@ -270,7 +368,7 @@ class EvolutionAPIClient:
             True if logged out successfully
         """
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.delete(
                     f"{self.api_url}/instance/logout/{instance_name}",
                     headers=self.headers
@@ -295,7 +393,7 @@ class EvolutionAPIClient:
             True if restarted successfully
         """
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.put(
                     f"{self.api_url}/instance/restart/{instance_name}",
                     headers=self.headers
@@ -343,7 +441,7 @@ class EvolutionAPIClient:
             payload["footer"] = footer
 
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.post(
                     f"{self.api_url}/message/sendButtons/{instance_name}",
                     json=payload,
@@ -396,7 +494,7 @@ class EvolutionAPIClient:
             payload["footer"] = footer
 
         try:
-            async with httpx.AsyncClient(timeout=30.0) as client:
+            async with http_client("evolution_api", timeout=whatsapp_config.timeout_seconds) as client:
                 response = await client.post(
                     f"{self.api_url}/message/sendList/{instance_name}",
                     json=payload,
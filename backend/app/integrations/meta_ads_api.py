@@ -0,0 +1,151 @@
+"""
+Meta Marketing API Integration for Custom Audiences
+Official API: https://developers.facebook.com/docs/marketing-api/audiences/guides/custom-audiences
+
+Contacts are sent as SHA-256 hashes of normalized identifiers (never in clear
+text), using the multi-key schema ["PHONE", "EMAIL"].
+"""
+
+import hashlib
+import logging
+import re
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+import httpx
+
+from app.core.fault_injection import http_client
+from app.integrations.meta_api import MetaAPIError, graph_base_url
+
+logger = logging.getLogger(__name__)
+
+AUDIENCE_SCHEMA = ["PHONE", "EMAIL"]
+
+# Maximum rows per /users request accepted by Meta
+MAX_USERS_PER_REQUEST = 10000
+
+
+def normalize_phone(phone: Optional[str]) -> Optional[str]:
+    """Digits only, with country code and no leading zeros (Meta's PHONE rule)"""
+    digits = re.sub(r"\D", "", phone or "").lstrip("0")
+    return digits or None
+
+
+def normalize_email(email: Optional[str]) -> Optional[str]:
+    """Trimmed and lowercase (Meta's EMAIL rule)"""
+    value = (email or "").strip().lower()
+    return value or None
+
+
+def hash_identifier(value: Optional[str]) -> Optional[str]:
+    """SHA-256 hex digest of an already normalized identifier"""
+    if not value:
+        return None
+    return hashlib.sha256(value.encode("utf-8")).hexdigest()
+
+
+def hash_contact(phone: Optional[str], email: Optional[str]) -> Tuple[Optional[str], Optional[str]]:
+    """(phone_hash, email_hash) of a contact, in AUDIENCE_SCHEMA order"""
+    return hash_identifier(normalize_phone(phone)), hash_identifier(normalize_email(email))
+
+
+class MetaAdsAPI:
+    """Client for Meta Custom Audiences of an ad account"""
+
+    def __init__(self, ad_account_id: str, access_token: str):
+        """
+        Initialize Meta Ads API client
+
+        Args:
+            ad_account_id: Ad account ID (with or without the "act_" prefix)
+            access_token: Token with ads_management permission on the account
+        """
+        self.ad_account_id = ad_account_id.removeprefix("act_")
+        self.access_token = access_token
+        self.base_url = graph_base_url()
+        self.timeout = 60.0
+
+    async def _request(self, method: str, path: str, payload: Dict[str, Any]) -> Dict[str, Any]:
+        url = f"{self.base_url}/{path}"
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        async with http_client("meta_ads_api", timeout=self.timeout) as client:
+            try:
+                response = await client.request(method, url, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    logger.error(f"Meta Ads API error on {path}: {error_message} (code: {error_code})")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code
+                    )
+
+                return response_data
+
+            except httpx.RequestError as e:
+                logger.error(f"HTTP request failed: {e}")
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    async def create_custom_audience(self, name: str, description: Optional[str] = None) -> str:
+        """
+        Create an empty customer-list Custom Audience
+
+        Returns:
+            ID of the new audience
+        """
+        payload = {
+            "name": name,
+            "subtype": "CUSTOM",
+            "customer_file_source": "USER_PROVIDED_ONLY",
+        }
+        if description:
+            payload["description"] = description
+
+        response_data = await self._request("POST", f"act_{self.ad_account_id}/customaudiences", payload)
+        logger.info(f"✅ Custom Audience created on act_{self.ad_account_id}: {response_data.get('id')}")
+        return response_data["id"]
+
+    @staticmethod
+    def users_payload(rows: Sequence[Tuple[Optional[str], Optional[str]]]) -> Dict[str, Any]:
+        """Multi-key payload; a missing identifier is sent as an empty string"""
+        return {
+            "payload": {
+                "schema": AUDIENCE_SCHEMA,
+                "data": [[value or "" for value in row] for row in rows],
+            }
+        }
+
+    async def _users(self, method: str, audience_id: str, rows: List[Tuple[Optional[str], Optional[str]]]) -> int:
+        received = 0
+        for start in range(0, len(rows), MAX_USERS_PER_REQUEST):
+            batch = rows[start:start + MAX_USERS_PER_REQUEST]
+            response_data = await self._request(method, f"{audience_id}/users", self.users_payload(batch))
+            received += response_data.get("num_received", len(batch))
+        return received
+
+    async def add_users(self, audience_id: str, rows: List[Tuple[Optional[str], Optional[str]]]) -> int:
+        """
+        Add hashed contacts to an audience
+
+        Args:
+            audience_id: Custom Audience ID
+            rows: (phone_hash, email_hash) per contact
+
+        Returns:
+            Number of rows received by Meta
+        """
+        if not rows:
+            return 0
+        return await self._users("POST", audience_id, rows)
+
+    async def remove_users(self, audience_id: str, rows: List[Tuple[Optional[str], Optional[str]]]) -> int:
+        """Remove hashed contacts from an audience (same rows that were added)"""
+        if not rows:
+            return 0
+        return await self._users("DELETE", audience_id, rows)
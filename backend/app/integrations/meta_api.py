@@ -3,44 +3,406 @@ Meta Cloud API Integration for WhatsApp Business
 Official API: https://developers.facebook.com/docs/whatsapp/cloud-api
 """
 
+import asyncio
 import logging
-from typing import Dict, Any, Optional, List
+import re
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from typing import Dict, Any, Generic, Iterable, Optional, List, Set, Tuple, Type, TypeVar
 import httpx
+from pydantic import BaseModel, ValidationError
+from pytake_client.media import DEFAULT_MAX_MEDIA_BYTES, MediaFetcher, MediaStorage
+from pytake_client.webhooks import StoredMedia
+
+from app.core.config import settings
+from app.core.fault_injection import http_client
+from app.core.tracing import Parent
+from app.core.whatsapp_config import whatsapp_config
+from app.core.whatsapp_metrics import MetricsSink, rate_limit_usage, whatsapp_metrics
+from app.utils.message_limits import (
+    BUTTON_TITLE_MAX,
+    CTA_DISPLAY_TEXT_MAX,
+    LIST_BUTTON_MAX,
+    LIST_ROW_DESCRIPTION_MAX,
+    LIST_ROW_TITLE_MAX,
+    LIST_SECTION_TITLE_MAX,
+    grapheme_len,
+    truncate_graphemes,
+)
+from app.utils.template_builder import TemplateBuilder
+from app.utils.urls import extract_urls, is_valid_preview_url
 
 logger = logging.getLogger(__name__)
 
 
 class MetaAPIError(Exception):
     """Exception raised for Meta API errors"""
-    def __init__(self, message: str, error_code: Optional[str] = None, status_code: Optional[int] = None):
+    def __init__(
+        self,
+        message: str,
+        error_code: Optional[str] = None,
+        status_code: Optional[int] = None,
+        response: Optional["ResponseInfo"] = None,
+    ):
         self.message = message
         self.error_code = error_code
         self.status_code = status_code
+        self.response = response  # Headers of the failed call (None for local/network errors)
         super().__init__(self.message)
 
+    @property
+    def fbtrace_id(self) -> Optional[str]:
+        """Meta's request id, to quote in support tickets"""
+        return self.response.fbtrace_id if self.response else None
+
+    @property
+    def retry_after(self) -> Optional[float]:
+        """Seconds to wait before calling again, when Meta says so"""
+        return self.response.retry_after if self.response else None
+
+
+# ============================================
+# RESPONSE METADATA
+# ============================================
+
+
+@dataclass
+class ResponseInfo:
+    """
+    Request id and rate-limit headers of a Graph API response
+
+    usage_percent and retry_after let callers slow down before Meta starts
+    rejecting calls; fbtrace_id identifies the call for Meta support.
+    """
+
+    status_code: int
+    fbtrace_id: Optional[str] = None
+    request_id: Optional[str] = None
+    retry_after: Optional[float] = None
+    business_use_case_usage: Dict[str, Any] = field(default_factory=dict)
+    app_usage: Dict[str, Any] = field(default_factory=dict)
+
+    @classmethod
+    def from_response(cls, response: httpx.Response, response_data: Any = None) -> "ResponseInfo":
+        headers = getattr(response, "headers", None) or {}
+        usage = rate_limit_usage(headers)
+        business_use_case_usage = _json_dict(usage.get("x-business-use-case-usage"))
+
+        error = response_data.get("error") if isinstance(response_data, dict) else None
+        fbtrace_id = headers.get("x-fb-trace-id") or (error.get("fbtrace_id") if isinstance(error, dict) else None)
+
+        return cls(
+            status_code=response.status_code,
+            fbtrace_id=fbtrace_id,
+            request_id=headers.get("x-fb-request-id"),
+            retry_after=_retry_after(headers.get("retry-after"), business_use_case_usage),
+            business_use_case_usage=business_use_case_usage,
+            app_usage=_json_dict(usage.get("x-app-usage")),
+        )
+
+    @property
+    def usage_percent(self) -> Optional[float]:
+        """Highest quota share used (call count, CPU or total time) across app and business usage"""
+        entries = [self.app_usage] + [
+            entry for entries in self.business_use_case_usage.values() if isinstance(entries, list)
+            for entry in entries if isinstance(entry, dict)
+        ]
+        values = [
+            float(entry[key]) for entry in entries
+            for key in ("call_count", "total_cputime", "total_time")
+            if isinstance(entry.get(key), (int, float))
+        ]
+        return max(values) if values else None
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "status_code": self.status_code,
+            "fbtrace_id": self.fbtrace_id,
+            "request_id": self.request_id,
+            "retry_after": self.retry_after,
+            "usage_percent": self.usage_percent,
+        }
+
+
+class MetaMessageResponse(dict):
+    """
+    Body of a successful send (same keys as Meta's JSON), plus the response headers
+
+        result = await api.send_text_message(to, "Olá")
+        wamid = result["messages"][0]["id"]
+        if (result.response.usage_percent or 0) > 80:
+            ...
+    """
+
+    def __init__(self, data: Dict[str, Any], response: ResponseInfo):
+        super().__init__(data)
+        self.response = response
+
+
+def _json_dict(value: Any) -> Dict[str, Any]:
+    return value if isinstance(value, dict) else {}
+
+
+def _retry_after(header: Optional[str], business_use_case_usage: Dict[str, Any]) -> Optional[float]:
+    """Retry-After header (seconds), else the longest estimated_time_to_regain_access (minutes)"""
+    if header:
+        try:
+            return max(float(header), 0.0)
+        except ValueError:
+            pass
+    minutes = [
+        entry.get("estimated_time_to_regain_access") for entries in business_use_case_usage.values()
+        if isinstance(entries, list) for entry in entries if isinstance(entry, dict)
+    ]
+    minutes = [m for m in minutes if isinstance(m, (int, float)) and m > 0]
+    return max(minutes) * 60.0 if minutes else None
+
+
+# ============================================
+# GRAPH API VERSION
+# ============================================
+
+# Features that need a newer Graph API version than the oldest one we support
+# capability -> (minimum version, feature name shown in errors)
+GRAPH_API_CAPABILITIES: Dict[str, Tuple[str, str]] = {
+    "flows": ("v18.0", "WhatsApp Flows"),
+    "carousel_templates": ("v19.0", "Carousel templates"),
+}
+
+
+def parse_graph_version(version: str) -> Tuple[int, int]:
+    """("v21.0") -> (21, 0)"""
+    match = re.fullmatch(r"v(\d+)\.(\d+)", (version or "").strip())
+    if not match:
+        raise ValueError(f"Invalid Graph API version '{version}' (expected e.g. v21.0)")
+    return int(match.group(1)), int(match.group(2))
+
+
+def graph_base_url(version: Optional[str] = None) -> str:
+    """Graph API root of the configured (or given) version, e.g. https://graph.facebook.com/v21.0"""
+    return f"{settings.WHATSAPP_API_URL.rstrip('/')}/{version or settings.WHATSAPP_API_VERSION}"
+
+
+def graph_url(*path: Any, version: Optional[str] = None) -> str:
+    """Graph API URL of a node/edge, e.g. graph_url(phone_number_id, "messages")"""
+    return "/".join([graph_base_url(version), *(str(part).strip("/") for part in path)])
+
+
+def supports_capability(capability: str, version: Optional[str] = None) -> bool:
+    required, _ = GRAPH_API_CAPABILITIES[capability]
+    return parse_graph_version(version or settings.WHATSAPP_API_VERSION) >= parse_graph_version(required)
+
+
+def template_capabilities(components: Optional[Iterable[Dict[str, Any]]]) -> Set[str]:
+    """
+    Version-gated features used by template components
+
+    Works for both the definition sent on creation (CAROUSEL component, FLOW
+    buttons) and the parameters sent with a message (carousel component,
+    button with sub_type flow).
+    """
+    capabilities: Set[str] = set()
+    for component in components or []:
+        component_type = str(component.get("type", "")).upper()
+        if component_type == "CAROUSEL":
+            capabilities.add("carousel_templates")
+        if component_type == "BUTTONS" and any(
+            str(button.get("type", "")).upper() == "FLOW" for button in component.get("buttons", [])
+        ):
+            capabilities.add("flows")
+        if component_type == "BUTTON" and str(component.get("sub_type", "")).lower() == "flow":
+            capabilities.add("flows")
+    return capabilities
+
+
+def _timestamp(value: Any) -> Optional[datetime]:
+    """Unix timestamp of a Graph API field (0 or missing means "never")"""
+    if not value:
+        return None
+    return datetime.fromtimestamp(int(value), tz=timezone.utc)
+
+
+class GraphAPIVersionError(MetaAPIError):
+    """Feature not available in the configured Graph API version (raised before calling Meta)"""
+
+    def __init__(self, capability: str, version: str):
+        required, feature = GRAPH_API_CAPABILITIES[capability]
+        self.capability = capability
+        self.required_version = required
+        super().__init__(
+            message=(
+                f"{feature} require Graph API {required} or newer, but WHATSAPP_API_VERSION is {version}. "
+                f"Raise WHATSAPP_API_VERSION to use this feature."
+            ),
+            error_code="graph_api_version",
+            status_code=400,
+        )
+
+
+ResponseT = TypeVar("ResponseT")
+
+
+@dataclass
+class GraphResponse(Generic[ResponseT]):
+    """Result of a raw Graph API call (see MetaCloudAPI.graph_request)"""
+
+    status_code: int
+    data: ResponseT
+    headers: Dict[str, str] = field(default_factory=dict)
+
+    @property
+    def info(self) -> ResponseInfo:
+        return ResponseInfo.from_response(self, self.data)
+
 
 class MetaCloudAPI:
     """Client for Meta Cloud API (WhatsApp Business)"""
 
-    def __init__(self, phone_number_id: str, access_token: str):
+    def __init__(
+        self,
+        phone_number_id: str,
+        access_token: str,
+        api_version: Optional[str] = None,
+        metrics: Optional[MetricsSink] = None,
+        trace_parent: Parent = None,
+    ):
         """
         Initialize Meta Cloud API client
 
         Args:
             phone_number_id: WhatsApp phone number ID from Meta
             access_token: Access token for Meta Graph API
+            api_version: Graph API version (default: WHATSAPP_API_VERSION)
+            metrics: Sink receiving per-request metrics (default: process-wide whatsapp_metrics)
+            trace_parent: traceparent (or TraceContext) parenting request spans when no span is active
         """
         self.phone_number_id = phone_number_id
         self.access_token = access_token
-        self.base_url = "https://graph.facebook.com/v18.0"
-        self.timeout = 30.0
+        self.api_version = api_version or settings.WHATSAPP_API_VERSION
+        self.metrics = metrics if metrics is not None else whatsapp_metrics
+        self.trace_parent = trace_parent
+        self.base_url = graph_base_url(self.api_version)
+        self.timeout = whatsapp_config.timeout_seconds
+
+    def _http_client(self) -> httpx.AsyncClient:
+        """Client for one call: proxy/timeouts, fault injection, metrics and tracing of this number"""
+        return http_client(
+            "meta_api",
+            timeout=self.timeout,
+            metrics=self.metrics,
+            phone_number_id=self.phone_number_id,
+            trace_parent=self.trace_parent,
+        )
+
+    def require_capability(self, capability: str) -> None:
+        """
+        Fail fast when the client's Graph API version lacks a feature
+
+        Raises:
+            GraphAPIVersionError: If the version is older than the feature's minimum
+        """
+        if not supports_capability(capability, self.api_version):
+            raise GraphAPIVersionError(capability, self.api_version)
+
+    @staticmethod
+    def _error_from_response(response: httpx.Response, response_data: Dict[str, Any]) -> MetaAPIError:
+        error = response_data.get("error", {}) if isinstance(response_data, dict) else {}
+        error_code = error.get("code")
+        return MetaAPIError(
+            message=error.get("message", "Unknown error"),
+            error_code=str(error_code) if error_code else None,
+            status_code=response.status_code,
+            response=ResponseInfo.from_response(response, response_data),
+        )
+
+    async def graph_request(
+        self,
+        method: str,
+        path: str,
+        body: Optional[Dict[str, Any]] = None,
+        params: Optional[Dict[str, Any]] = None,
+        response_model: Optional[Type[BaseModel]] = None,
+    ) -> GraphResponse:
+        """
+        Call a Graph API endpoint this client does not wrap yet
+
+        Uses the client's token, Graph API version, proxy/timeouts and error
+        parsing, so a missing endpoint does not need a second HTTP client:
+
+            flows = await api.graph_request("GET", f"{waba_id}/flows", params={"fields": "id,name,status"})
+            number = await api.graph_request("GET", phone_number_id, response_model=PhoneNumber)
+
+        Args:
+            method: HTTP method (GET, POST, DELETE...)
+            path: Path relative to the versioned Graph API root (e.g. "{waba_id}/flows")
+            body: JSON body
+            params: Query parameters
+            response_model: Pydantic model to validate the response with (default: raw dict)
+
+        Returns:
+            GraphResponse with the status, the parsed body (dict or response_model) and headers
+
+        Raises:
+            MetaAPIError: Absolute URL, Graph API error, network error or unexpected response shape
+        """
+        if "://" in path:
+            raise MetaAPIError("graph_request path must be relative to the Graph API root")
+
+        url = graph_url(path, version=self.api_version)
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        async with self._http_client() as client:
+            try:
+                response = await client.request(method.upper(), url, params=params, json=body, headers=headers)
+            except httpx.RequestError as e:
+                logger.error(f"HTTP request failed: {e}")
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+        try:
+            response_data = response.json()
+        except ValueError:
+            raise MetaAPIError(
+                f"Graph API returned a non-JSON response for {method.upper()} {path}",
+                status_code=response.status_code
+            )
+
+        if response.status_code >= 400:
+            error = self._error_from_response(response, response_data)
+            logger.error(f"Meta API error on {method.upper()} {path}: {error.message} (code: {error.error_code})")
+            raise error
+
+        data: Any = response_data
+        if response_model is not None:
+            try:
+                data = response_model.model_validate(response_data)
+            except ValidationError as e:
+                raise MetaAPIError(
+                    f"Unexpected response for {method.upper()} {path}: {e.error_count()} validation error(s)",
+                    status_code=response.status_code
+                )
+
+        return GraphResponse(
+            status_code=response.status_code,
+            data=data,
+            headers=dict(getattr(response, "headers", {}) or {}),
+        )
+
+    @staticmethod
+    def _apply_reply_context(payload: Dict[str, Any], reply_to_message_id: Optional[str]) -> None:
+        """Quote a previous message so the reply appears threaded in WhatsApp"""
+        if reply_to_message_id:
+            payload["context"] = {"message_id": reply_to_message_id}
 
     async def send_text_message(
         self,
         to: str,
         text: str,
-        preview_url: bool = False
-    ) -> Dict[str, Any]:
+        preview_url: bool = False,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
         """
         Send a text message
 
@@ -48,9 +410,10 @@ class MetaCloudAPI:
             to: Recipient WhatsApp ID (phone number with country code, no +)
             text: Message text
             preview_url: Enable URL preview
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
 
         Returns:
-            Response from Meta API with message ID
+            Response from Meta API with message ID (headers in .response)
 
         Raises:
             MetaAPIError: If API request fails
@@ -68,6 +431,8 @@ class MetaCloudAPI:
             }
         }
 
+        self._apply_reply_context(payload, reply_to_message_id)
+
         headers = {
             "Authorization": f"Bearer {self.access_token}",
             "Content-Type": "application/json",
@@ -75,7 +440,7 @@ class MetaCloudAPI:
 
         logger.info(f"Sending text message to {to}")
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.post(url, json=payload, headers=headers)
                 response_data = response.json()
@@ -87,22 +452,72 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
                 logger.info(f"✅ Message sent successfully: {response_data.get('messages', [{}])[0].get('id')}")
-                return response_data
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
 
             except httpx.RequestError as e:
                 logger.error(f"HTTP request failed: {e}")
                 raise MetaAPIError(f"Network error: {str(e)}")
 
+    async def send_text_with_preview(
+        self,
+        to: str,
+        text: str,
+        url: Optional[str] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
+        """
+        Send a text message with link preview
+
+        WhatsApp previews the first URL of the body. When the body has no
+        detectable URL the preview silently does not render, so this method
+        warns and sends without preview instead.
+
+        Args:
+            to: Recipient WhatsApp ID
+            text: Message text
+            url: URL to preview (appended to the text if missing)
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
+
+        Returns:
+            Response from Meta API
+
+        Raises:
+            MetaAPIError: If the URL is invalid or the API request fails
+        """
+        if url:
+            if not is_valid_preview_url(url):
+                raise MetaAPIError(f"Invalid preview URL: {url}")
+            if url not in text:
+                text = f"{text}\n{url}" if text else url
+
+        urls = extract_urls(text)
+        if not urls:
+            logger.warning(f"⚠️ No URL detected in message to {to}, sending without link preview")
+            return await self.send_text_message(
+                to=to, text=text, preview_url=False, reply_to_message_id=reply_to_message_id
+            )
+
+        if url and urls[0] != url:
+            logger.warning(
+                f"⚠️ Preview will show {urls[0]} (first URL in the text), not {url}"
+            )
+
+        return await self.send_text_message(
+            to=to, text=text, preview_url=True, reply_to_message_id=reply_to_message_id
+        )
+
     async def send_image_message(
         self,
         to: str,
         image_url: str,
-        caption: Optional[str] = None
-    ) -> Dict[str, Any]:
+        caption: Optional[str] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
         """
         Send an image message
 
@@ -110,6 +525,7 @@ class MetaCloudAPI:
             to: Recipient WhatsApp ID
             image_url: URL of the image (must be HTTPS)
             caption: Optional caption text
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
 
         Returns:
             Response from Meta API
@@ -129,6 +545,8 @@ class MetaCloudAPI:
         if caption:
             payload["image"]["caption"] = caption
 
+        self._apply_reply_context(payload, reply_to_message_id)
+
         headers = {
             "Authorization": f"Bearer {self.access_token}",
             "Content-Type": "application/json",
@@ -136,7 +554,7 @@ class MetaCloudAPI:
 
         logger.info(f"Sending image message to {to}")
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.post(url, json=payload, headers=headers)
                 response_data = response.json()
@@ -147,21 +565,60 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
-                return response_data
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
 
             except httpx.RequestError as e:
                 raise MetaAPIError(f"Network error: {str(e)}")
 
+    async def download_media(
+        self,
+        media_id: str,
+        storage: MediaStorage,
+        mime_type: Optional[str] = None,
+        filename: Optional[str] = None,
+        key_prefix: str = "",
+        max_bytes: int = DEFAULT_MAX_MEDIA_BYTES,
+    ) -> StoredMedia:
+        """
+        Download inbound media into a storage backend
+
+        Args:
+            media_id: Media id of the inbound message
+            storage: Backend receiving the bytes
+            mime_type: MIME type announced in the message
+            filename: File name sent by the contact (documents)
+            key_prefix: Prefix of the storage key
+            max_bytes: Downloads larger than this are aborted
+
+        Returns:
+            The stored media (reference, size, SHA-256...)
+
+        Raises:
+            MediaFetchError: On lookup, download, size or checksum failures
+        """
+        async with self._http_client() as client:
+            fetcher = MediaFetcher(
+                self.access_token,
+                storage,
+                graph_url=settings.WHATSAPP_API_URL,
+                api_version=self.api_version,
+                max_bytes=max_bytes,
+                http_client=client,
+            )
+            return await fetcher.fetch(media_id, mime_type=mime_type, filename=filename, key_prefix=key_prefix)
+
     async def send_template_message(
         self,
         to: str,
         template_name: str,
         language_code: str = "pt_BR",
-        components: Optional[List[Dict]] = None
-    ) -> Dict[str, Any]:
+        components: Optional[List[Dict]] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
         """
         Send a template message
 
@@ -169,11 +626,19 @@ class MetaCloudAPI:
             to: Recipient WhatsApp ID
             template_name: Template name (slug)
             language_code: Language code (e.g., pt_BR, en_US)
-            components: Template components with variable values
+            components: Template components with variable values (send_template builds
+                and checks them from a TemplateBuilder)
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
 
         Returns:
             Response from Meta API
+
+        Raises:
+            GraphAPIVersionError: Carousel or Flow components on a too old Graph API version
         """
+        for capability in sorted(template_capabilities(components)):
+            self.require_capability(capability)
+
         url = f"{self.base_url}/{self.phone_number_id}/messages"
 
         payload = {
@@ -192,6 +657,8 @@ class MetaCloudAPI:
         if components:
             payload["template"]["components"] = components
 
+        self._apply_reply_context(payload, reply_to_message_id)
+
         headers = {
             "Authorization": f"Bearer {self.access_token}",
             "Content-Type": "application/json",
@@ -199,7 +666,7 @@ class MetaCloudAPI:
 
         logger.info(f"Sending template '{template_name}' to {to}")
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.post(url, json=payload, headers=headers)
                 response_data = response.json()
@@ -210,21 +677,49 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
-                return response_data
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
 
             except httpx.RequestError as e:
                 raise MetaAPIError(f"Network error: {str(e)}")
 
+    async def send_template(
+        self,
+        to: str,
+        template: TemplateBuilder,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
+        """
+        Send a template message from a TemplateBuilder
+
+        Args:
+            to: Recipient WhatsApp ID
+            template: Template name, language and typed parameters
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
+
+        Raises:
+            TemplateBuildError: Parameters do not match the template placeholders (nothing is sent)
+            GraphAPIVersionError: Carousel or Flow components on a too old Graph API version
+        """
+        return await self.send_template_message(
+            to,
+            template.name,
+            template.language,
+            components=template.build() or None,
+            reply_to_message_id=reply_to_message_id,
+        )
+
     async def send_document_message(
         self,
         to: str,
         document_url: str,
         filename: Optional[str] = None,
-        caption: Optional[str] = None
-    ) -> Dict[str, Any]:
+        caption: Optional[str] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
         """
         Send a document message
 
@@ -233,6 +728,7 @@ class MetaCloudAPI:
             document_url: URL of the document (must be HTTPS)
             filename: Display filename
             caption: Optional caption
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
 
         Returns:
             Response from Meta API
@@ -255,12 +751,14 @@ class MetaCloudAPI:
         if caption:
             payload["document"]["caption"] = caption
 
+        self._apply_reply_context(payload, reply_to_message_id)
+
         headers = {
             "Authorization": f"Bearer {self.access_token}",
             "Content-Type": "application/json",
         }
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.post(url, json=payload, headers=headers)
                 response_data = response.json()
@@ -271,20 +769,22 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
-                return response_data
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
 
             except httpx.RequestError as e:
                 raise MetaAPIError(f"Network error: {str(e)}")
 
-    async def mark_message_as_read(self, message_id: str) -> Dict[str, Any]:
+    async def mark_message_as_read(self, message_id: str, typing: bool = False) -> Dict[str, Any]:
         """
         Mark a message as read
 
         Args:
             message_id: WhatsApp message ID
+            typing: Also show the typing indicator to the contact
 
         Returns:
             Response from Meta API
@@ -297,12 +797,15 @@ class MetaCloudAPI:
             "message_id": message_id
         }
 
+        if typing:
+            payload["typing_indicator"] = {"type": "text"}
+
         headers = {
             "Authorization": f"Bearer {self.access_token}",
             "Content-Type": "application/json",
         }
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.post(url, json=payload, headers=headers)
                 response_data = response.json()
@@ -316,9 +819,78 @@ class MetaCloudAPI:
             except httpx.RequestError as e:
                 raise MetaAPIError(f"Network error: {str(e)}")
 
+    async def mark_messages_as_read(
+        self,
+        message_ids: List[str],
+        max_concurrency: int = 5,
+    ) -> Dict[str, Any]:
+        """
+        Mark several messages as read
+
+        Cloud API has no batch endpoint, so one request is sent per message
+        (with limited concurrency). Failures do not stop the batch.
+
+        Args:
+            message_ids: WhatsApp message IDs (duplicates are ignored)
+            max_concurrency: Maximum simultaneous requests
+
+        Returns:
+            {"read": [ids marked], "failed": {id: error message}}
+        """
+        semaphore = asyncio.Semaphore(max_concurrency)
+        read: List[str] = []
+        failed: Dict[str, str] = {}
+
+        async def _mark(message_id: str):
+            async with semaphore:
+                try:
+                    await self.mark_message_as_read(message_id)
+                    read.append(message_id)
+                except MetaAPIError as e:
+                    failed[message_id] = e.message
+
+        await asyncio.gather(*(_mark(message_id) for message_id in dict.fromkeys(message_ids)))
+
+        if failed:
+            logger.warning(f"⚠️ Failed to mark {len(failed)} message(s) as read: {failed}")
+
+        return {"read": read, "failed": failed}
+
+    async def send_typing_on(self, message_id: str) -> Dict[str, Any]:
+        """
+        Show the typing indicator to the contact
+
+        Cloud API ties the indicator to the inbound message being answered,
+        so this also marks that message as read. The indicator is dismissed
+        when the next message is sent or after 25 seconds.
+
+        Args:
+            message_id: WhatsApp ID of the inbound message being answered
+
+        Returns:
+            Response from Meta API
+        """
+        return await self.mark_message_as_read(message_id, typing=True)
+
+    async def send_typing_off(self, message_id: str) -> Dict[str, Any]:
+        """
+        Hide the typing indicator
+
+        Cloud API has no explicit "typing off": the indicator disappears when
+        the reply is sent or after 25 seconds. Kept for parity with the
+        Evolution API client so callers can always pair on/off.
+
+        Args:
+            message_id: WhatsApp ID of the inbound message being answered
+
+        Returns:
+            {"success": True}
+        """
+        return {"success": True}
+
     async def list_templates(self, waba_id: str, status: str = "APPROVED", limit: int = 100) -> List[Dict[str, Any]]:
         """
-        List message templates from WhatsApp Business Account
+        List message templates from WhatsApp Business Account (first page)
 
         Args:
             waba_id: WhatsApp Business Account ID
@@ -328,25 +900,52 @@ class MetaCloudAPI:
         Returns:
             List of template objects
 
+        Raises:
+            MetaAPIError: If API request fails
+        """
+        templates, _ = await self.list_templates_page(waba_id, status=status, limit=limit)
+        return templates
+
+    async def list_templates_page(
+        self,
+        waba_id: str,
+        status: Optional[str] = None,
+        limit: int = 100,
+        after: Optional[str] = None,
+    ) -> Tuple[List[Dict[str, Any]], Optional[str]]:
+        """
+        Fetch one page of message templates
+
+        Args:
+            waba_id: WhatsApp Business Account ID
+            status: Filter by status (empty for all)
+            limit: Page size
+            after: Cursor returned by the previous page
+
+        Returns:
+            (templates, next cursor or None on the last page)
+
         Raises:
             MetaAPIError: If API request fails
         """
         url = f"{self.base_url}/{waba_id}/message_templates"
 
-        params = {
+        params: Dict[str, Any] = {
             "limit": limit
         }
 
         if status:
             params["status"] = status
+        if after:
+            params["after"] = after
 
         headers = {
             "Authorization": f"Bearer {self.access_token}",
         }
 
-        logger.info(f"Fetching templates for WABA {waba_id} with status {status}")
+        logger.info(f"Fetching templates for WABA {waba_id} with status {status or 'ALL'}")
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.get(url, params=params, headers=headers)
                 response_data = response.json()
@@ -358,17 +957,54 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
                 templates = response_data.get("data", [])
+                paging = response_data.get("paging", {})
+                # Meta only includes "next" when there is another page
+                next_cursor = paging.get("cursors", {}).get("after") if paging.get("next") else None
+
                 logger.info(f"✅ Fetched {len(templates)} templates")
-                return templates
+                return templates, next_cursor
 
             except httpx.RequestError as e:
                 logger.error(f"HTTP request failed: {e}")
                 raise MetaAPIError(f"Network error: {str(e)}")
 
+    async def list_all_templates(
+        self,
+        waba_id: str,
+        status: Optional[str] = None,
+        page_size: int = 100,
+        max_pages: int = 50,
+    ) -> List[Dict[str, Any]]:
+        """
+        Fetch every message template of the WABA following the paging cursors
+
+        Args:
+            waba_id: WhatsApp Business Account ID
+            status: Filter by status (empty for all)
+            page_size: Templates per request
+            max_pages: Safety limit on the number of requests
+
+        Returns:
+            List of template objects
+        """
+        templates: List[Dict[str, Any]] = []
+        after = None
+
+        for _ in range(max_pages):
+            page, after = await self.list_templates_page(waba_id, status=status, limit=page_size, after=after)
+            templates.extend(page)
+            if not after:
+                break
+        else:
+            logger.warning(f"⚠️ Stopped fetching templates for WABA {waba_id} after {max_pages} pages")
+
+        return templates
+
     async def create_template(
         self,
         waba_id: str,
@@ -416,8 +1052,12 @@ class MetaCloudAPI:
             Response from Meta API with template ID and status
 
         Raises:
+            GraphAPIVersionError: CAROUSEL component or FLOW buttons on a too old Graph API version
             MetaAPIError: If API request fails
         """
+        for capability in sorted(template_capabilities(components)):
+            self.require_capability(capability)
+
         url = f"{self.base_url}/{waba_id}/message_templates"
 
         payload = {
@@ -434,7 +1074,7 @@ class MetaCloudAPI:
 
         logger.info(f"Creating template '{name}' ({language}) for WABA {waba_id}")
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.post(url, json=payload, headers=headers)
                 response_data = response.json()
@@ -446,7 +1086,8 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
                 logger.info(f"✅ Template '{name}' created successfully. ID: {response_data.get('id')}")
@@ -486,7 +1127,7 @@ class MetaCloudAPI:
 
         logger.info(f"Deleting template '{template_name}' from WABA {waba_id}")
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.delete(url, params=params, headers=headers)
                 response_data = response.json()
@@ -498,7 +1139,8 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
                 logger.info(f"✅ Template '{template_name}' deleted successfully")
@@ -508,111 +1150,572 @@ class MetaCloudAPI:
                 logger.error(f"HTTP request failed: {e}")
                 raise MetaAPIError(f"Network error: {str(e)}")
 
-    async def send_interactive_buttons(
-        self,
-        to: str,
-        body_text: str,
-        buttons: List[Dict[str, str]],
-        header_text: Optional[str] = None,
-        footer_text: Optional[str] = None
-    ) -> Dict[str, Any]:
-        """
-        Send an interactive message with buttons
-
-        Args:
-            to: Recipient WhatsApp ID
-            body_text: Main message body
-            buttons: List of buttons (max 3) with format: [{"id": "btn1", "title": "Button 1"}]
-            header_text: Optional header text
-            footer_text: Optional footer text
-
-        Returns:
-            Response from Meta API
-
-        Raises:
-            MetaAPIError: If API request fails
-        """
-        if len(buttons) > 3:
-            raise MetaAPIError("Maximum of 3 buttons allowed")
-
-        url = f"{self.base_url}/{self.phone_number_id}/messages"
-
-        # Formatar botões para o padrão da Meta API
-        formatted_buttons = []
-        for btn in buttons:
-            formatted_buttons.append({
-                "type": "reply",
-                "reply": {
-                    "id": btn.get("id", f"btn_{len(formatted_buttons)}"),
-                    "title": btn.get("title", "")[:20]  # Max 20 chars
-                }
-            })
-
-        payload = {
-            "messaging_product": "whatsapp",
-            "recipient_type": "individual",
-            "to": to,
-            "type": "interactive",
-            "interactive": {
-                "type": "button",
-                "body": {
-                    "text": body_text
-                },
-                "action": {
-                    "buttons": formatted_buttons
-                }
-            }
-        }
-
-        # Adicionar header se fornecido
-        if header_text:
-            payload["interactive"]["header"] = {
-                "type": "text",
-                "text": header_text[:60]  # Max 60 chars
-            }
-
-        # Adicionar footer se fornecido
-        if footer_text:
-            payload["interactive"]["footer"] = {
-                "text": footer_text[:60]  # Max 60 chars
-            }
-
+    # ============================================
+    # PHONE NUMBER INFO
+    # ============================================
+
+    PHONE_NUMBER_FIELDS = (
+        "display_phone_number",
+        "verified_name",
+        "quality_rating",
+        "messaging_limit_tier",
+        "name_status",
+        "code_verification_status",
+        "throughput",
+    )
+
+    # Unique customers that can receive business-initiated messages per 24h
+    MESSAGING_LIMITS = {
+        "TIER_50": 50,
+        "TIER_250": 250,
+        "TIER_1K": 1000,
+        "TIER_2K": 2000,
+        "TIER_10K": 10000,
+        "TIER_100K": 100000,
+        "TIER_UNLIMITED": None,
+    }
+
+    async def _phone_number_get(self, fields: List[str]) -> Dict[str, Any]:
+        url = f"{self.base_url}/{self.phone_number_id}"
+        params = {"fields": ",".join(fields)}
         headers = {
             "Authorization": f"Bearer {self.access_token}",
-            "Content-Type": "application/json",
         }
 
-        logger.info(f"Sending interactive buttons to {to} ({len(buttons)} buttons)")
-
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
-                response = await client.post(url, json=payload, headers=headers)
+                response = await client.get(url, params=params, headers=headers)
                 response_data = response.json()
 
                 if response.status_code != 200:
                     error_message = response_data.get("error", {}).get("message", "Unknown error")
                     error_code = response_data.get("error", {}).get("code")
+                    logger.error(f"Meta API error fetching phone number {self.phone_number_id}: {error_message}")
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
-                logger.info(f"✅ Interactive buttons sent successfully")
                 return response_data
 
             except httpx.RequestError as e:
+                logger.error(f"HTTP request failed: {e}")
                 raise MetaAPIError(f"Network error: {str(e)}")
 
-    async def send_interactive_list(
-        self,
-        to: str,
-        body_text: str,
-        button_text: str,
-        sections: List[Dict[str, Any]],
-        header_text: Optional[str] = None,
-        footer_text: Optional[str] = None
+    async def get_phone_number_info(self) -> Dict[str, Any]:
+        """
+        Get quality rating, messaging limit and display name status of the number
+
+        Returns:
+            Dict with display_phone_number, verified_name, quality_rating,
+            messaging_limit_tier, daily_conversation_limit (None if unlimited),
+            name_status, code_verification_status and throughput_level
+        """
+        response_data = await self._phone_number_get(list(self.PHONE_NUMBER_FIELDS))
+
+        tier = response_data.get("messaging_limit_tier")
+        return {
+            "display_phone_number": response_data.get("display_phone_number"),
+            "verified_name": response_data.get("verified_name"),
+            "quality_rating": response_data.get("quality_rating"),
+            "messaging_limit_tier": tier,
+            "daily_conversation_limit": self.MESSAGING_LIMITS.get(tier),
+            "name_status": response_data.get("name_status"),
+            "code_verification_status": response_data.get("code_verification_status"),
+            "throughput_level": (response_data.get("throughput") or {}).get("level"),
+        }
+
+    async def debug_token(self) -> Dict[str, Any]:
+        """
+        Inspect the client's access token (Graph API debug_token)
+
+        Returns:
+            Dict with is_valid, expires_at (None for tokens that never expire,
+            e.g. system users), data_access_expires_at, scopes, app_id, type
+            and error (Meta's reason when the token is invalid)
+        """
+        response = await self.graph_request("GET", "debug_token", params={"input_token": self.access_token})
+        data = (response.data or {}).get("data") or {}
+
+        return {
+            "is_valid": bool(data.get("is_valid")),
+            "expires_at": _timestamp(data.get("expires_at")),
+            "data_access_expires_at": _timestamp(data.get("data_access_expires_at")),
+            "scopes": data.get("scopes") or [],
+            "app_id": data.get("app_id"),
+            "type": data.get("type"),
+            "error": (data.get("error") or {}).get("message"),
+        }
+
+    # ============================================
+    # CONVERSATIONAL COMPONENTS
+    # ============================================
+
+    MAX_ICE_BREAKERS = 4
+    ICE_BREAKER_MAX = 80
+    MAX_COMMANDS = 30
+    COMMAND_NAME_MAX = 32
+    COMMAND_DESCRIPTION_MAX = 256
+
+    async def get_conversational_automation(self) -> Dict[str, Any]:
+        """
+        Get ice breakers, commands and welcome message setting of the number
+
+        Returns:
+            {"enable_welcome_message": bool, "prompts": [...], "commands": [{command_name, command_description}]}
+        """
+        response_data = await self._phone_number_get(["conversational_automation"])
+        automation = response_data.get("conversational_automation") or {}
+        return {
+            "enable_welcome_message": bool(automation.get("enable_welcome_message", False)),
+            "prompts": automation.get("prompts", []),
+            "commands": automation.get("commands", []),
+        }
+
+    def _validate_conversational_automation(
+        self,
+        prompts: Optional[List[str]],
+        commands: Optional[List[Dict[str, str]]],
+    ) -> None:
+        if prompts is not None:
+            if len(prompts) > self.MAX_ICE_BREAKERS:
+                raise MetaAPIError(f"At most {self.MAX_ICE_BREAKERS} ice breakers are allowed")
+            for prompt in prompts:
+                if not prompt or grapheme_len(prompt) > self.ICE_BREAKER_MAX:
+                    raise MetaAPIError(f"Ice breakers must have 1-{self.ICE_BREAKER_MAX} characters: {prompt!r}")
+
+        if commands is not None:
+            if len(commands) > self.MAX_COMMANDS:
+                raise MetaAPIError(f"At most {self.MAX_COMMANDS} commands are allowed")
+            names = [command.get("command_name", "") for command in commands]
+            if len(set(names)) != len(names):
+                raise MetaAPIError("Command names must be unique")
+            for command in commands:
+                name = command.get("command_name", "")
+                description = command.get("command_description", "")
+                if not name or len(name) > self.COMMAND_NAME_MAX or " " in name:
+                    raise MetaAPIError(
+                        f"Command names must have 1-{self.COMMAND_NAME_MAX} characters without spaces: {name!r}"
+                    )
+                if not description or grapheme_len(description) > self.COMMAND_DESCRIPTION_MAX:
+                    raise MetaAPIError(
+                        f"Command descriptions must have 1-{self.COMMAND_DESCRIPTION_MAX} characters: {name!r}"
+                    )
+
+    async def update_conversational_automation(
+        self,
+        enable_welcome_message: Optional[bool] = None,
+        prompts: Optional[List[str]] = None,
+        commands: Optional[List[Dict[str, str]]] = None,
+    ) -> bool:
+        """
+        Configure conversational components of the number
+
+        Only the given settings are changed; pass an empty list to remove all
+        ice breakers or commands. With the welcome message enabled, Meta sends
+        a ``request_welcome`` message when a user opens the chat for the first time.
+
+        Args:
+            enable_welcome_message: Enable request_welcome webhooks
+            prompts: Ice breakers (max 4, 80 characters each)
+            commands: [{"command_name": "pedidos", "command_description": "..."}] (max 30)
+
+        Returns:
+            True if updated
+        """
+        if commands is not None:
+            # Commands are typed as "/name" in WhatsApp; Meta expects the name only
+            commands = [
+                {**command, "command_name": command.get("command_name", "").lstrip("/")}
+                for command in commands
+            ]
+        self._validate_conversational_automation(prompts, commands)
+
+        payload: Dict[str, Any] = {}
+        if enable_welcome_message is not None:
+            payload["enable_welcome_message"] = enable_welcome_message
+        if prompts is not None:
+            payload["prompts"] = prompts
+        if commands is not None:
+            payload["commands"] = commands
+
+        logger.info(f"Updating conversational automation for phone number {self.phone_number_id}")
+        response_data = await self._phone_number_request("/conversational_automation", payload)
+        return response_data.get("success", False)
+
+    # ============================================
+    # PHONE NUMBER REGISTRATION
+    # ============================================
+
+    @staticmethod
+    def _validate_pin(pin: str) -> None:
+        if not (isinstance(pin, str) and len(pin) == 6 and pin.isdigit()):
+            raise MetaAPIError("Two-step verification PIN must have exactly 6 digits")
+
+    async def _phone_number_request(self, path: str, payload: Dict[str, Any]) -> Dict[str, Any]:
+        url = f"{self.base_url}/{self.phone_number_id}{path}"
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        async with self._http_client() as client:
+            try:
+                response = await client.post(url, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    logger.error(f"Meta API error on phone number {self.phone_number_id}: {error_message}")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
+                    )
+
+                return response_data
+
+            except httpx.RequestError as e:
+                logger.error(f"HTTP request failed: {e}")
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    async def set_two_step_pin(self, pin: str) -> bool:
+        """
+        Set or change the two-step verification PIN of the number
+
+        Meta does not require the current PIN to change it; the new PIN is
+        needed to register the number again (migration, re-registration).
+
+        Args:
+            pin: 6-digit PIN
+
+        Returns:
+            True if the PIN was set
+        """
+        self._validate_pin(pin)
+        logger.info(f"Setting two-step verification PIN for phone number {self.phone_number_id}")
+        response_data = await self._phone_number_request("", {"pin": pin})
+        return response_data.get("success", False)
+
+    async def register_phone_number(self, pin: str) -> bool:
+        """
+        Register the number for Cloud API use (also sets the two-step PIN)
+
+        Args:
+            pin: 6-digit two-step verification PIN (the current one if already set)
+
+        Returns:
+            True if registered
+        """
+        self._validate_pin(pin)
+        logger.info(f"Registering phone number {self.phone_number_id}")
+        response_data = await self._phone_number_request("/register", {
+            "messaging_product": "whatsapp",
+            "pin": pin,
+        })
+        return response_data.get("success", False)
+
+    async def deregister_phone_number(self) -> bool:
+        """Deregister the number from Cloud API (before migrating it elsewhere)"""
+        logger.info(f"Deregistering phone number {self.phone_number_id}")
+        response_data = await self._phone_number_request("/deregister", {})
+        return response_data.get("success", False)
+
+    # ============================================
+    # QR CODES (message_qrdls)
+    # ============================================
+
+    async def _qr_request(
+        self,
+        method: str,
+        path: str = "",
+        params: Optional[Dict[str, Any]] = None,
+        payload: Optional[Dict[str, Any]] = None,
     ) -> Dict[str, Any]:
+        url = f"{self.base_url}/{self.phone_number_id}/message_qrdls{path}"
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+        }
+
+        async with self._http_client() as client:
+            try:
+                response = await client.request(method, url, params=params, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    logger.error(f"Meta API error on QR codes: {error_message}")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
+                    )
+
+                return response_data
+
+            except httpx.RequestError as e:
+                logger.error(f"HTTP request failed: {e}")
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    async def create_qr_code(self, prefilled_message: str, image_format: str = "PNG") -> Dict[str, Any]:
+        """
+        Create a QR code that opens a chat with this number and a prefilled message
+
+        Args:
+            prefilled_message: Text placed in the contact's composer (max 140 chars)
+            image_format: PNG or SVG
+
+        Returns:
+            {"code": "...", "prefilled_message": "...", "deep_link_url": "https://wa.me/message/...",
+             "qr_image_url": "https://..."}
+        """
+        logger.info(f"Creating QR code for phone number {self.phone_number_id}")
+        return await self._qr_request("POST", payload={
+            "prefilled_message": prefilled_message,
+            "generate_qr_image": image_format.upper(),
+        })
+
+    async def update_qr_code(self, code: str, prefilled_message: str) -> Dict[str, Any]:
+        """Change the prefilled message of an existing QR code"""
+        return await self._qr_request("POST", payload={
+            "code": code,
+            "prefilled_message": prefilled_message,
+        })
+
+    async def list_qr_codes(self, image_format: Optional[str] = None) -> List[Dict[str, Any]]:
+        """
+        List QR codes of this number
+
+        Args:
+            image_format: Include image URLs in this format (PNG or SVG)
+
+        Returns:
+            List of QR code objects
+        """
+        params = {"fields": "code,prefilled_message,deep_link_url"}
+        if image_format:
+            params["fields"] += f",qr_image_url.format({image_format.upper()})"
+
+        response_data = await self._qr_request("GET", params=params)
+        return response_data.get("data", [])
+
+    async def get_qr_code(self, code: str) -> Optional[Dict[str, Any]]:
+        """Get a QR code by its code (None if it does not exist)"""
+        response_data = await self._qr_request("GET", f"/{code}")
+        data = response_data.get("data", [])
+        return data[0] if data else None
+
+    async def delete_qr_code(self, code: str) -> bool:
+        """Delete a QR code; its deep link stops working"""
+        logger.info(f"Deleting QR code {code} from phone number {self.phone_number_id}")
+        response_data = await self._qr_request("DELETE", f"/{code}")
+        return response_data.get("success", False)
+
+    async def send_interactive_buttons(
+        self,
+        to: str,
+        body_text: str,
+        buttons: List[Dict[str, str]],
+        header_text: Optional[str] = None,
+        footer_text: Optional[str] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
+        """
+        Send an interactive message with buttons
+
+        Args:
+            to: Recipient WhatsApp ID
+            body_text: Main message body
+            buttons: List of buttons (max 3) with format: [{"id": "btn1", "title": "Button 1"}]
+            header_text: Optional header text
+            footer_text: Optional footer text
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
+
+        Returns:
+            Response from Meta API
+
+        Raises:
+            MetaAPIError: If API request fails
+        """
+        if len(buttons) > 3:
+            raise MetaAPIError("Maximum of 3 buttons allowed")
+
+        url = f"{self.base_url}/{self.phone_number_id}/messages"
+
+        # Formatar botões para o padrão da Meta API
+        formatted_buttons = []
+        for btn in buttons:
+            formatted_buttons.append({
+                "type": "reply",
+                "reply": {
+                    "id": btn.get("id", f"btn_{len(formatted_buttons)}"),
+                    "title": truncate_graphemes(btn.get("title", ""), BUTTON_TITLE_MAX)
+                }
+            })
+
+        payload = {
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+            "type": "interactive",
+            "interactive": {
+                "type": "button",
+                "body": {
+                    "text": body_text
+                },
+                "action": {
+                    "buttons": formatted_buttons
+                }
+            }
+        }
+
+        # Adicionar header se fornecido
+        if header_text:
+            payload["interactive"]["header"] = {
+                "type": "text",
+                "text": header_text[:60]  # Max 60 chars
+            }
+
+        # Adicionar footer se fornecido
+        if footer_text:
+            payload["interactive"]["footer"] = {
+                "text": footer_text[:60]  # Max 60 chars
+            }
+
+        self._apply_reply_context(payload, reply_to_message_id)
+
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        logger.info(f"Sending interactive buttons to {to} ({len(buttons)} buttons)")
+
+        async with self._http_client() as client:
+            try:
+                response = await client.post(url, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
+                    )
+
+                logger.info(f"✅ Interactive buttons sent successfully")
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
+
+            except httpx.RequestError as e:
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    async def send_cta_url_message(
+        self,
+        to: str,
+        body_text: str,
+        display_text: str,
+        url: str,
+        header_text: Optional[str] = None,
+        footer_text: Optional[str] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
+        """
+        Send an interactive message with a call-to-action URL button
+
+        Unlike a link pasted in the text, the URL stays behind a button
+        labelled display_text.
+
+        Args:
+            to: Recipient WhatsApp ID
+            body_text: Main message body
+            display_text: Button label (max 20 characters)
+            url: Absolute http(s) URL opened by the button
+            header_text: Optional header text
+            footer_text: Optional footer text
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
+
+        Returns:
+            Response from Meta API
+
+        Raises:
+            MetaAPIError: If the URL is invalid or the API request fails
+        """
+        if not is_valid_preview_url(url):
+            raise MetaAPIError(f"Invalid call-to-action URL: {url}")
+
+        api_url = f"{self.base_url}/{self.phone_number_id}/messages"
+
+        payload = {
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+            "type": "interactive",
+            "interactive": {
+                "type": "cta_url",
+                "body": {"text": body_text},
+                "action": {
+                    "name": "cta_url",
+                    "parameters": {
+                        "display_text": truncate_graphemes(display_text, CTA_DISPLAY_TEXT_MAX),
+                        "url": url,
+                    },
+                },
+            }
+        }
+        if header_text:
+            payload["interactive"]["header"] = {"type": "text", "text": header_text[:60]}
+        if footer_text:
+            payload["interactive"]["footer"] = {"text": footer_text[:60]}
+
+        self._apply_reply_context(payload, reply_to_message_id)
+
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        logger.info(f"Sending call-to-action URL message to {to}")
+
+        async with self._http_client() as client:
+            try:
+                response = await client.post(api_url, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
+                    )
+
+                logger.info(f"✅ Call-to-action URL message sent successfully")
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
+
+            except httpx.RequestError as e:
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    async def send_interactive_list(
+        self,
+        to: str,
+        body_text: str,
+        button_text: str,
+        sections: List[Dict[str, Any]],
+        header_text: Optional[str] = None,
+        footer_text: Optional[str] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
         """
         Send an interactive message with list/menu
 
@@ -631,6 +1734,7 @@ class MetaCloudAPI:
                 ]
             header_text: Optional header text
             footer_text: Optional footer text
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
 
         Returns:
             Response from Meta API
@@ -655,12 +1759,12 @@ class MetaCloudAPI:
             for row in section.get("rows", []):
                 formatted_rows.append({
                     "id": row.get("id", f"row_{len(formatted_rows)}"),
-                    "title": row.get("title", "")[:24],  # Max 24 chars
-                    "description": row.get("description", "")[:72]  # Max 72 chars
+                    "title": truncate_graphemes(row.get("title", ""), LIST_ROW_TITLE_MAX),
+                    "description": truncate_graphemes(row.get("description", ""), LIST_ROW_DESCRIPTION_MAX)
                 })
 
             formatted_sections.append({
-                "title": section.get("title", "")[:24],  # Max 24 chars
+                "title": truncate_graphemes(section.get("title", ""), LIST_SECTION_TITLE_MAX),
                 "rows": formatted_rows
             })
 
@@ -675,7 +1779,7 @@ class MetaCloudAPI:
                     "text": body_text
                 },
                 "action": {
-                    "button": button_text[:20],  # Max 20 chars
+                    "button": truncate_graphemes(button_text, LIST_BUTTON_MAX),
                     "sections": formatted_sections
                 }
             }
@@ -694,6 +1798,8 @@ class MetaCloudAPI:
                 "text": footer_text[:60]  # Max 60 chars
             }
 
+        self._apply_reply_context(payload, reply_to_message_id)
+
         headers = {
             "Authorization": f"Bearer {self.access_token}",
             "Content-Type": "application/json",
@@ -701,7 +1807,7 @@ class MetaCloudAPI:
 
         logger.info(f"Sending interactive list to {to} ({len(sections)} sections, {total_rows} total rows)")
 
-        async with httpx.AsyncClient(timeout=self.timeout) as client:
+        async with self._http_client() as client:
             try:
                 response = await client.post(url, json=payload, headers=headers)
                 response_data = response.json()
@@ -712,11 +1818,267 @@ class MetaCloudAPI:
                     raise MetaAPIError(
                         message=error_message,
                         error_code=str(error_code) if error_code else None,
-                        status_code=response.status_code
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
                     )
 
                 logger.info(f"✅ Interactive list sent successfully")
-                return response_data
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
+
+            except httpx.RequestError as e:
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    async def send_flow_message(
+        self,
+        to: str,
+        flow_id: str,
+        flow_cta: str,
+        body_text: str,
+        flow_token: Optional[str] = None,
+        screen: Optional[str] = None,
+        data: Optional[Dict[str, Any]] = None,
+        header_text: Optional[str] = None,
+        footer_text: Optional[str] = None,
+        draft: bool = False,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
+        """
+        Send a WhatsApp Flow (interactive message of type flow)
+
+        Args:
+            to: Recipient WhatsApp ID
+            flow_id: Published (or draft) Flow ID
+            flow_cta: Text of the button that opens the Flow
+            body_text: Message body
+            flow_token: Token echoed back in the Flow response (identifies the session)
+            screen: First screen (navigate action); without it the Flow starts on its data_exchange
+            data: Initial data of the first screen
+            header_text: Optional header
+            footer_text: Optional footer
+            draft: Send the draft version (testing)
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
+
+        Returns:
+            Response from Meta API
+
+        Raises:
+            GraphAPIVersionError: If the configured Graph API version has no Flows
+            MetaAPIError: If API request fails
+        """
+        self.require_capability("flows")
+
+        url = f"{self.base_url}/{self.phone_number_id}/messages"
+
+        parameters: Dict[str, Any] = {
+            "flow_message_version": "3",
+            "flow_id": flow_id,
+            "flow_cta": truncate_graphemes(flow_cta, BUTTON_TITLE_MAX),
+            "flow_action": "navigate" if screen else "data_exchange",
+        }
+        if flow_token:
+            parameters["flow_token"] = flow_token
+        if screen:
+            parameters["flow_action_payload"] = {"screen": screen, **({"data": data} if data else {})}
+        if draft:
+            parameters["mode"] = "draft"
+
+        payload = {
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+            "type": "interactive",
+            "interactive": {
+                "type": "flow",
+                "body": {"text": body_text},
+                "action": {"name": "flow", "parameters": parameters},
+            }
+        }
+        if header_text:
+            payload["interactive"]["header"] = {"type": "text", "text": header_text[:60]}
+        if footer_text:
+            payload["interactive"]["footer"] = {"text": footer_text[:60]}
+
+        self._apply_reply_context(payload, reply_to_message_id)
+
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        logger.info(f"Sending flow {flow_id} to {to}")
+
+        async with self._http_client() as client:
+            try:
+                response = await client.post(url, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
+                    )
+
+                logger.info(f"✅ Flow message sent successfully")
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
+
+            except httpx.RequestError as e:
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    # Countries where WhatsApp offers the native address form
+    ADDRESS_MESSAGE_COUNTRIES = ("IN", "SG")
+
+    async def send_address_message(
+        self,
+        to: str,
+        body_text: str,
+        country: str,
+        values: Optional[Dict[str, Any]] = None,
+        saved_addresses: Optional[List[Dict[str, Any]]] = None,
+        validation_errors: Optional[Dict[str, str]] = None,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
+        """
+        Ask for a shipping address with the native WhatsApp address form (interactive address_message)
+
+        The contact's answer arrives as an nfm_reply named "address_message"
+        (InboundMessageType.ADDRESS_REPLY, parsed into ShippingAddress).
+
+        Args:
+            to: Recipient WhatsApp ID
+            body_text: Message body
+            country: ISO country code of the form (see ADDRESS_MESSAGE_COUNTRIES)
+            values: Fields to prefill (name, phone_number, in_pin_code, city...)
+            saved_addresses: Addresses to choose from: [{"id": "address1", "value": {...fields}}]
+            validation_errors: Field -> error shown when asking again for a rejected address
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
+
+        Returns:
+            Response from Meta API
+
+        Raises:
+            MetaAPIError: If the country has no address form or the API request fails
+        """
+        country = country.upper()
+        if country not in self.ADDRESS_MESSAGE_COUNTRIES:
+            raise MetaAPIError(
+                f"Address messages are only available in {', '.join(self.ADDRESS_MESSAGE_COUNTRIES)} (got {country})"
+            )
+
+        url = f"{self.base_url}/{self.phone_number_id}/messages"
+
+        parameters: Dict[str, Any] = {"country": country}
+        if values:
+            parameters["values"] = values
+        if saved_addresses:
+            parameters["saved_addresses"] = saved_addresses
+        if validation_errors:
+            parameters["validation_errors"] = validation_errors
+
+        payload = {
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+            "type": "interactive",
+            "interactive": {
+                "type": "address_message",
+                "body": {"text": body_text},
+                "action": {"name": "address_message", "parameters": parameters},
+            }
+        }
+
+        self._apply_reply_context(payload, reply_to_message_id)
+
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        logger.info(f"Sending address request to {to}")
+
+        async with self._http_client() as client:
+            try:
+                response = await client.post(url, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
+                    )
+
+                logger.info(f"✅ Address message sent successfully")
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
+
+            except httpx.RequestError as e:
+                raise MetaAPIError(f"Network error: {str(e)}")
+
+    async def send_location_request(
+        self,
+        to: str,
+        body_text: str,
+        reply_to_message_id: Optional[str] = None
+    ) -> MetaMessageResponse:
+        """
+        Ask the contact to share a location (interactive location_request_message)
+
+        The message carries WhatsApp's native "Send location" button; the
+        answer arrives as a regular location message.
+
+        Args:
+            to: Recipient WhatsApp ID
+            body_text: Message body
+            reply_to_message_id: WhatsApp message ID (wamid) to quote
+
+        Returns:
+            Response from Meta API
+        """
+        url = f"{self.base_url}/{self.phone_number_id}/messages"
+
+        payload = {
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+            "type": "interactive",
+            "interactive": {
+                "type": "location_request_message",
+                "body": {"text": body_text},
+                "action": {"name": "send_location"},
+            }
+        }
+
+        self._apply_reply_context(payload, reply_to_message_id)
+
+        headers = {
+            "Authorization": f"Bearer {self.access_token}",
+            "Content-Type": "application/json",
+        }
+
+        logger.info(f"Sending location request to {to}")
+
+        async with self._http_client() as client:
+            try:
+                response = await client.post(url, json=payload, headers=headers)
+                response_data = response.json()
+
+                if response.status_code != 200:
+                    error_message = response_data.get("error", {}).get("message", "Unknown error")
+                    error_code = response_data.get("error", {}).get("code")
+                    raise MetaAPIError(
+                        message=error_message,
+                        error_code=str(error_code) if error_code else None,
+                        status_code=response.status_code,
+                        response=ResponseInfo.from_response(response, response_data),
+                    )
+
+                return MetaMessageResponse(response_data, ResponseInfo.from_response(response))
 
             except httpx.RequestError as e:
                 raise MetaAPIError(f"Network error: {str(e)}")
@@ -0,0 +1,127 @@
+"""
+Meta Cloud API clients of many phone numbers
+
+One MetaCloudAPI per number, reused across calls (connections are shared by
+every number, see app.core.whatsapp_config), and sender selection when an
+organization has several official numbers:
+
+    async with whatsapp_clients.sender(numbers, SenderPolicy.STICKY, contact_key=contact.whatsapp_id) as (number, api):
+        await api.send_text_message(contact.whatsapp_id, "Olá!")
+
+Policies:
+    sticky        Same number for the same contact while the set of numbers is
+                  unchanged (rendezvous hashing: removing a number only moves
+                  its own contacts)
+    round_robin   Numbers in turn
+    least_loaded  Number with the fewest sends in flight in this process
+"""
+
+import hashlib
+from collections import Counter
+from contextlib import asynccontextmanager
+from enum import Enum
+from typing import TYPE_CHECKING, AsyncIterator, Dict, Optional, Sequence, Tuple
+
+from app.integrations.meta_api import MetaCloudAPI
+
+if TYPE_CHECKING:
+    from app.models.whatsapp_number import WhatsAppNumber
+
+
+class SenderPolicy(str, Enum):
+    """How a sender is picked among several numbers"""
+
+    STICKY = "sticky"
+    ROUND_ROBIN = "round_robin"
+    LEAST_LOADED = "least_loaded"
+
+
+class WhatsAppClientPool:
+    """MetaCloudAPI clients by phone_number_id, and sender selection between them"""
+
+    def __init__(self):
+        self._clients: Dict[str, MetaCloudAPI] = {}
+        self._in_flight: Counter = Counter()
+        self._turns: Counter = Counter()
+
+    def client(self, number: "WhatsAppNumber") -> MetaCloudAPI:
+        """Client of an official number (recreated when its access token changes)"""
+        api = self._clients.get(number.phone_number_id)
+        if api is None or api.access_token != number.access_token:
+            api = MetaCloudAPI(phone_number_id=number.phone_number_id, access_token=number.access_token)
+            self._clients[number.phone_number_id] = api
+        return api
+
+    def forget(self, phone_number_id: str) -> None:
+        """Drop the client of a removed or disconnected number"""
+        self._clients.pop(phone_number_id, None)
+
+    def in_flight(self, phone_number_id: str) -> int:
+        return self._in_flight[phone_number_id]
+
+    def pick(
+        self,
+        numbers: Sequence["WhatsAppNumber"],
+        policy: SenderPolicy = SenderPolicy.STICKY,
+        contact_key: Optional[str] = None,
+    ) -> Optional["WhatsAppNumber"]:
+        """
+        Choose the sender among the active official numbers
+
+        Args:
+            numbers: Candidate numbers (others are ignored)
+            policy: Selection policy
+            contact_key: Recipient (required for sticky; without it sticky falls back to round_robin)
+
+        Returns:
+            The chosen number, or None if no number can send
+        """
+        candidates = sorted(
+            (n for n in numbers if n.connection_type == "official" and n.is_active and n.access_token),
+            key=lambda n: n.phone_number_id,
+        )
+        if not candidates:
+            return None
+
+        if policy == SenderPolicy.STICKY and contact_key:
+            return max(candidates, key=lambda n: _rendezvous_score(contact_key, n.phone_number_id))
+
+        if policy == SenderPolicy.LEAST_LOADED:
+            return min(candidates, key=lambda n: self._in_flight[n.phone_number_id])
+
+        group = tuple(n.phone_number_id for n in candidates)
+        turn = self._turns[group]
+        self._turns[group] += 1
+        return candidates[turn % len(candidates)]
+
+    @asynccontextmanager
+    async def sender(
+        self,
+        numbers: Sequence["WhatsAppNumber"],
+        policy: SenderPolicy = SenderPolicy.STICKY,
+        contact_key: Optional[str] = None,
+    ) -> AsyncIterator[Tuple["WhatsAppNumber", MetaCloudAPI]]:
+        """
+        Pick a sender and count the send as in flight until the block ends
+
+        Raises:
+            ValueError: If no active official number can send
+        """
+        number = self.pick(numbers, policy, contact_key)
+        if number is None:
+            raise ValueError("No active official WhatsApp number to send from")
+
+        self._in_flight[number.phone_number_id] += 1
+        try:
+            yield number, self.client(number)
+        finally:
+            self._in_flight[number.phone_number_id] -= 1
+
+
+def _rendezvous_score(contact_key: str, phone_number_id: str) -> int:
+    digest = hashlib.sha256(f"{contact_key}:{phone_number_id}".encode("utf-8")).digest()
+    return int.from_bytes(digest[:8], "big")
+
+
+# Global instance
+whatsapp_clients = WhatsAppClientPool()
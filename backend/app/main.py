@@ -11,13 +11,14 @@ from fastapi.middleware.gzip import GZipMiddleware
 from fastapi.middleware.trustedhost import TrustedHostMiddleware
 
 from app.core.config import settings
-from app.core.database import close_db, init_db
+from app.core.database import async_session, close_db, init_db
 from app.core.mongodb import mongodb_client
-from app.core.redis import redis_client
+from app.core.redis import disconnect_all as redis_disconnect_all, redis_client, redis_health
 from app.core.rate_limit import limiter, rate_limit_exceeded_handler
 
 # Import routers
 from app.api.v1.router import api_router
+from app.services.annotation_service import AnnotationService
 
 
 def run_migrations():
@@ -82,6 +83,13 @@ async def lifespan(app: FastAPI):
         await mongodb_client.connect()
         print("✅ MongoDB connected")
 
+        # Deploy annotation on dashboards (recorded once per release)
+        try:
+            async with async_session() as db:
+                await AnnotationService(db).record_deploy(settings.APP_VERSION, settings.DEPLOY_REVISION)
+        except Exception as e:
+            print(f"⚠️ Could not record deploy annotation: {e}")
+
         print(f"🎉 PyTake v{settings.APP_VERSION} started successfully!")
         print(f"📍 Environment: {settings.ENVIRONMENT}")
         print(f"🔧 Debug mode: {settings.DEBUG}")
@@ -99,7 +107,7 @@ async def lifespan(app: FastAPI):
         await close_db()
         print("✅ PostgreSQL disconnected")
 
-        await redis_client.disconnect()
+        await redis_disconnect_all()
         print("✅ Redis disconnected")
 
         await mongodb_client.disconnect()
@@ -217,6 +225,16 @@ async def cors_headers_middleware(request: Request, call_next):
     
     return response
 
+# Meta webhook signature (X-Hub-Signature-256) checked before the handler runs
+if settings.META_WEBHOOK_SECRET:
+    from pytake_client.middleware import WebhookSignatureMiddleware
+
+    app.add_middleware(
+        WebhookSignatureMiddleware,
+        secret=settings.META_WEBHOOK_SECRET,
+        paths=[f"{settings.API_V1_PREFIX}/webhooks/meta"],
+    )
+
 # Trusted Host (only in production)
 if settings.is_production:
     app.add_middleware(
@@ -323,6 +341,12 @@ async def health_check():
         health_status["services"]["redis"] = f"unhealthy: {str(e)}"
         health_status["status"] = "degraded"
 
+    # Redis pools per component (mode, latency, pool usage, errors, failovers)
+    try:
+        health_status["redis"] = await redis_health()
+    except Exception as e:
+        health_status["redis"] = {"error": str(e)}
+
     # Check MongoDB
     try:
         await mongodb_client.client.admin.command("ping")
@@ -355,6 +379,7 @@ app.include_router(api_router, prefix=settings.API_V1_PREFIX)
 # Mount Socket.IO app
 from app.websocket.manager import get_sio_app
 import app.websocket.events as _  # Import to register event handlers (side effect only)
+import app.websocket.commands as _commands  # noqa: F401 - registers the "command" event
 
 sio_asgi_app = get_sio_app()
 app.mount("/socket.io", sio_asgi_app)
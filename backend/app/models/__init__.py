@@ -5,18 +5,35 @@ Import all models here for Alembic to detect them
 
 from app.models.base import Base, SoftDeleteMixin, TimestampMixin
 from app.models.organization import Organization
-from app.models.user import RefreshToken, User
+from app.models.user import RefreshToken, User, UserWorkspace
 from app.models.whatsapp_number import WhatsAppNumber, WhatsAppTemplate
-from app.models.chatbot import Chatbot, Flow, Node
+from app.models.chatbot import (
+    Chatbot,
+    Flow,
+    FlowConsoleSession,
+    FlowEventTrigger,
+    FlowNodeVisit,
+    FlowTraceEvent,
+    FlowTrigger,
+    FlowVersion,
+    FlowWait,
+    Node,
+    SurveyResponse,
+)
 from app.models.contact import Contact, Tag
 from app.models.conversation import Conversation, Message
+from app.models.conversation_participant import ConversationParticipant
 from app.models.department import Department
 from app.models.queue import Queue
-from app.models.campaign import Campaign
+from app.models.campaign import Campaign, CampaignMessage
+from app.models.dunning import DunningReminder, DunningRule, Invoice
+from app.models.ad_audience import AdAudience, AdAudienceMember
+from app.models.annotation import DashboardAnnotation
+from app.models.webhook_event import WebhookEvent
 from app.models.ai_custom_model import AICustomModel
 from app.models.notification import NotificationPreference, NotificationLog
 from app.models.agent_skill import AgentSkill
-from app.models.secret import Secret
+from app.models.secret import Secret, SecretAccessLog
 from app.models.flow_automation import (
     FlowAutomation,
     FlowAutomationExecution,
@@ -32,23 +49,42 @@ __all__ = [
     "Organization",
     "User",
     "RefreshToken",
+    "UserWorkspace",
     "WhatsAppNumber",
     "WhatsAppTemplate",
     "Chatbot",
     "Flow",
+    "FlowConsoleSession",
+    "FlowEventTrigger",
+    "FlowNodeVisit",
+    "FlowTraceEvent",
+    "FlowTrigger",
+    "FlowVersion",
+    "FlowWait",
     "Node",
+    "SurveyResponse",
     "Contact",
     "Tag",
     "Conversation",
     "Message",
+    "ConversationParticipant",
     "Department",
     "Queue",
     "Campaign",
+    "CampaignMessage",
+    "Invoice",
+    "DunningRule",
+    "DunningReminder",
+    "AdAudience",
+    "AdAudienceMember",
+    "DashboardAnnotation",
+    "WebhookEvent",
     "AICustomModel",
     "NotificationPreference",
     "NotificationLog",
     "AgentSkill",
     "Secret",
+    "SecretAccessLog",
     "FlowAutomation",
     "FlowAutomationExecution",
     "FlowAutomationRecipient",
@@ -0,0 +1,136 @@
+"""
+Ad audience models - Contact segments synced to Meta Custom Audiences
+
+An AdAudience uses the same audience definition as campaigns (all contacts,
+tags, custom list or segment). The sync pushes only contacts that accept
+marketing, hashed (SHA-256) as Meta requires, and AdAudienceMember keeps what
+was pushed so each run only adds new contacts and removes the ones that left
+the segment or opted out.
+
+Ad account and access token are configured per organization in
+organization.settings['meta_ads'] (see GET/PUT /ad-audiences/settings).
+"""
+
+from sqlalchemy import (
+    Boolean,
+    Column,
+    DateTime,
+    ForeignKey,
+    Integer,
+    String,
+    Text,
+    UniqueConstraint,
+)
+from sqlalchemy.dialects.postgresql import ARRAY, UUID
+from sqlalchemy.orm import relationship
+from sqlalchemy.sql import text
+
+from app.models.base import Base, SoftDeleteMixin, TimestampMixin, JSONBCompatible
+
+
+class AdAudience(Base, TimestampMixin, SoftDeleteMixin):
+    """Contact segment mirrored to a Meta Custom Audience"""
+
+    __tablename__ = "ad_audiences"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    name = Column(String(255), nullable=False)
+    description = Column(Text, nullable=True)
+
+    # Custom Audience on Meta (created on the first sync)
+    ad_account_id = Column(String(100), nullable=True)
+    meta_audience_id = Column(String(100), nullable=True, index=True)
+
+    # Audience definition (same as campaigns): all_contacts, tags, custom_list, segment
+    audience_type = Column(
+        String(50),
+        nullable=False,
+        default="tags",
+        server_default="tags",
+    )
+    target_tag_ids = Column(
+        ARRAY(UUID(as_uuid=True)),
+        nullable=False,
+        default=[],
+        server_default=text("ARRAY[]::uuid[]"),
+    )
+    target_contact_ids = Column(
+        ARRAY(UUID(as_uuid=True)),
+        nullable=False,
+        default=[],
+        server_default=text("ARRAY[]::uuid[]"),
+    )
+    segment_filters = Column(
+        JSONBCompatible,
+        nullable=False,
+        default={},
+        server_default=text("'{}'::jsonb"),
+    )
+
+    is_active = Column(Boolean, default=True, server_default="true", nullable=False)
+
+    # Last sync: success, failed
+    last_synced_at = Column(DateTime(timezone=True), nullable=True)
+    last_sync_status = Column(String(20), nullable=True)
+    last_error = Column(Text, nullable=True)
+    member_count = Column(Integer, default=0, server_default="0", nullable=False)
+
+    # Relationships
+    members = relationship("AdAudienceMember", back_populates="audience", cascade="all, delete-orphan")
+
+    def __repr__(self):
+        return f"<AdAudience(id={self.id}, name='{self.name}', meta_audience_id='{self.meta_audience_id}')>"
+
+
+class AdAudienceMember(Base, TimestampMixin):
+    """Contact pushed to a Custom Audience (with the hashes that were sent)"""
+
+    __tablename__ = "ad_audience_members"
+    __table_args__ = (
+        UniqueConstraint("audience_id", "contact_id", name="uq_ad_audience_members_audience_contact"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    audience_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("ad_audiences.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    contact_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("contacts.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    # Hashes sent to Meta, needed to remove the contact even after its phone/email change
+    phone_hash = Column(String(64), nullable=True)
+    email_hash = Column(String(64), nullable=True)
+
+    # Relationships
+    audience = relationship("AdAudience", back_populates="members")
+
+    def __repr__(self):
+        return f"<AdAudienceMember(audience_id={self.audience_id}, contact_id={self.contact_id})>"
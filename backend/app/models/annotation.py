@@ -0,0 +1,76 @@
+"""
+Dashboard annotation model - Platform and tenant events overlaid on charts
+
+Annotations mark moments that may explain a metric change (a deploy, a
+template approved or paused by Meta, a campaign launch, or a note written by
+the team). Realtime dashboards and analytics time series overlay them.
+"""
+
+from sqlalchemy import Column, DateTime, ForeignKey, Index, String, Text
+from sqlalchemy.dialects.postgresql import UUID
+from sqlalchemy.sql import text
+
+from app.models.base import Base, TimestampMixin, JSONBCompatible
+
+# Kinds of annotations (custom = written by a user or an API client)
+ANNOTATION_KINDS = ("deploy", "template_change", "campaign_launch", "custom")
+
+
+class DashboardAnnotation(Base, TimestampMixin):
+    """Event annotation shown on dashboard time series"""
+
+    __tablename__ = "dashboard_annotations"
+    __table_args__ = (
+        Index("ix_dashboard_annotations_org_occurred_at", "organization_id", "occurred_at"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys (organization_id NULL = platform-wide, e.g. deploys)
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=True,
+        index=True,
+    )
+    created_by_user_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    # deploy, template_change, campaign_launch, custom
+    kind = Column(String(30), nullable=False, index=True)
+    # auto (recorded by the platform) or manual
+    source = Column(String(20), nullable=False, default="manual", server_default="manual")
+
+    title = Column(String(255), nullable=False)
+    description = Column(Text, nullable=True)
+
+    # Point in time, or a range when ends_at is set
+    occurred_at = Column(DateTime(timezone=True), nullable=False)
+    ends_at = Column(DateTime(timezone=True), nullable=True)
+
+    # Object the event refers to (template, campaign...)
+    resource_type = Column(String(50), nullable=True)
+    resource_id = Column(UUID(as_uuid=True), nullable=True)
+
+    extra_data = Column(
+        JSONBCompatible,
+        nullable=False,
+        default={},
+        server_default=text("'{}'::jsonb"),
+    )
+
+    @property
+    def is_platform(self) -> bool:
+        """True for annotations shared by every organization"""
+        return self.organization_id is None
+
+    def __repr__(self):
+        return f"<DashboardAnnotation(id={self.id}, kind='{self.kind}', title='{self.title}')>"
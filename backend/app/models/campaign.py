@@ -11,6 +11,7 @@ from sqlalchemy import (
     Integer,
     String,
     Text,
+    UniqueConstraint,
 )
 from sqlalchemy.dialects.postgresql import ARRAY, JSONB, UUID
 from sqlalchemy.orm import relationship
@@ -25,6 +26,11 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
     """
 
     __tablename__ = "campaigns"
+    __table_args__ = (
+        UniqueConstraint(
+            "parent_campaign_id", "occurrence_number", name="uq_campaigns_parent_occurrence"
+        ),
+    )
 
     # Primary Key
     id = Column(
@@ -63,6 +69,14 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
         index=True,
     )
 
+    # Occurrence of a recurring campaign (see app/utils/campaign_recurrence.py)
+    parent_campaign_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("campaigns.id", ondelete="SET NULL"),
+        nullable=True,
+        index=True,
+    )
+
     # Campaign Info
     name = Column(String(255), nullable=False, index=True)
     description = Column(Text, nullable=True)
@@ -76,7 +90,7 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
         index=True,
     )
 
-    # Status: draft, scheduled, running, paused, completed, failed, cancelled
+    # Status: draft, scheduled, recurring, running, testing, paused, completed, failed, cancelled
     status = Column(
         String(50),
         nullable=False,
@@ -92,6 +106,13 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
     paused_at = Column(DateTime(timezone=True), nullable=True)
     cancelled_at = Column(DateTime(timezone=True), nullable=True)
 
+    # Recurrence (parents only): config, next occurrence to create and how many were created
+    recurrence = Column(JSONB, nullable=True)
+    next_occurrence_at = Column(DateTime(timezone=True), nullable=True, index=True)
+    occurrences_created = Column(Integer, nullable=False, default=0, server_default="0")
+    # Ordinal of a child among its parent's occurrences
+    occurrence_number = Column(Integer, nullable=True)
+
     # Message Content
     # If not using template
     message_type = Column(
@@ -104,6 +125,9 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
         server_default=text("'{}'::jsonb"),
     )
 
+    # A/B test: variants sent to a test share, winner to the rest (see app/utils/campaign_ab_test.py)
+    ab_test = Column(JSONB, nullable=True)
+
     # Template Variables (if using template)
     # Global variables applied to all recipients
     template_variables = Column(
@@ -153,6 +177,15 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
     # Delay between messages (seconds)
     delay_between_messages_seconds = Column(Integer, default=2, server_default="2")
 
+    # Other sending limits: messages_per_minute, messages_per_day, business_hours,
+    # use_contact_timezone (see app/core/campaign_throttle.py)
+    throttle = Column(
+        JSONB,
+        nullable=False,
+        default={},
+        server_default=text("'{}'::jsonb"),
+    )
+
     # Respect opt-out
     respect_opt_out = Column(
         Boolean, default=True, server_default="true", nullable=False
@@ -231,6 +264,15 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
         comment='Maximum delay in seconds for exponential backoff'
     )
 
+    # Dispatch checkpoint (per-batch contact cursors for crash-safe resume)
+    dispatch_checkpoint = Column(
+        JSONB,
+        nullable=False,
+        default={},
+        server_default=text("'{}'::jsonb"),
+        comment='Per-batch contact cursors used to resume dispatching'
+    )
+
     # Settings
     settings = Column(
         JSONB,
@@ -244,7 +286,8 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
     created_by_user = relationship("User")
     whatsapp_number = relationship("WhatsAppNumber")
     template = relationship("WhatsAppTemplate")
-    # messages = relationship("CampaignMessage", back_populates="campaign", cascade="all, delete-orphan")
+    parent_campaign = relationship("Campaign", remote_side=[id])
+    messages = relationship("CampaignMessage", back_populates="campaign", passive_deletes=True)
 
     def __repr__(self):
         return f"<Campaign(id={self.id}, name='{self.name}', status='{self.status}')>"
@@ -269,6 +312,11 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
         """Check if campaign is completed"""
         return self.status == "completed"
 
+    @property
+    def is_recurring(self) -> bool:
+        """Check if campaign creates recurring occurrences"""
+        return self.status == "recurring"
+
     @property
     def is_paused(self) -> bool:
         """Check if campaign is paused"""
@@ -329,5 +377,70 @@ class Campaign(Base, TimestampMixin, SoftDeleteMixin):
         self.cancelled_at = datetime.utcnow()
 
 
-# Note: CampaignMessage could be a separate model to track individual messages
-# For now, we'll track them in Message model with campaign_id reference
+class CampaignMessage(Base, TimestampMixin):
+    """
+    Delivery of a campaign to one contact (one row per contact)
+
+    Written by the dispatcher (app/tasks/campaign_retry.py) on every send
+    attempt and moved forward by delivery receipts (message_status_tracker).
+    """
+
+    __tablename__ = "campaign_messages"
+    __table_args__ = (
+        UniqueConstraint("campaign_id", "contact_id", name="uq_campaign_messages_campaign_contact"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    campaign_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("campaigns.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    contact_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("contacts.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    # Message stored in the contact's conversation once WhatsApp accepted it
+    message_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("messages.id", ondelete="SET NULL"),
+        nullable=True,
+        index=True,
+    )
+
+    whatsapp_message_id = Column(String(255), nullable=True, index=True)
+    # A/B test variant sent to the contact
+    ab_variant = Column(String(50), nullable=True)
+
+    # Status: pending, retrying, sent, delivered, read, failed
+    status = Column(String(20), nullable=False, default="pending", server_default="pending", index=True)
+    attempts = Column(Integer, nullable=False, default=0, server_default="0")
+    error_code = Column(String(50), nullable=True)
+    error_message = Column(Text, nullable=True)
+
+    sent_at = Column(DateTime(timezone=True), nullable=True)
+    delivered_at = Column(DateTime(timezone=True), nullable=True)
+    read_at = Column(DateTime(timezone=True), nullable=True)
+    failed_at = Column(DateTime(timezone=True), nullable=True)
+
+    # Relationships
+    campaign = relationship("Campaign", back_populates="messages")
+
+    def __repr__(self):
+        return f"<CampaignMessage(campaign_id={self.campaign_id}, contact_id={self.contact_id}, status='{self.status}')>"
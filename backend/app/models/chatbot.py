@@ -1,8 +1,8 @@
 """
-Chatbot, Flow, and Node models for the bot builder
+Chatbot, Flow, FlowVersion, Node, FlowNodeVisit, FlowTraceEvent, SurveyResponse and FlowTrigger models for the bot builder
 """
 
-from sqlalchemy import Boolean, Column, ForeignKey, Integer, String, Text
+from sqlalchemy import Boolean, Column, DateTime, ForeignKey, Index, Integer, String, Text, UniqueConstraint
 from sqlalchemy.dialects.postgresql import JSONB, UUID
 from sqlalchemy.orm import relationship
 from sqlalchemy.sql import text
@@ -140,6 +140,10 @@ class Flow(Base, TimestampMixin, SoftDeleteMixin):
         Boolean, default=False, server_default="false", nullable=False
     )  # Fallback flow for errors
 
+    # Language of the nodes' own content, last step of the translations
+    # fallback chain (see app.utils.flow_locale)
+    default_locale = Column(String(10), nullable=True)
+
     # Canvas Data (React Flow format)
     # Stores nodes, edges, and viewport state
     canvas_data = Column(
@@ -161,25 +165,134 @@ class Flow(Base, TimestampMixin, SoftDeleteMixin):
     is_active = Column(Boolean, default=True, server_default="true", nullable=False)
 
     # Versioning
+    # canvas_data is the draft; version is the number it gets when published
     version = Column(Integer, default=1, server_default="1", nullable=False)
 
+    # Version new conversations run (None = never published, the draft runs)
+    published_version_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flow_versions.id", ondelete="SET NULL", use_alter=True, name="fk_flows_published_version_id"),
+        nullable=True,
+    )
+
+    # Gallery template the flow was created from (see app.utils.flow_template_params):
+    # {"template_id", "version", "parameters", "fingerprint"}; None for other flows
+    template_origin = Column(JSONBCompatible, nullable=True)
+
     # Relationships
     organization = relationship("Organization")
     chatbot = relationship("Chatbot", back_populates="flows")
     nodes = relationship("Node", back_populates="flow", cascade="all, delete-orphan")
+    versions = relationship(
+        "FlowVersion",
+        back_populates="flow",
+        cascade="all, delete-orphan",
+        foreign_keys="FlowVersion.flow_id",
+    )
 
     def __repr__(self):
         return f"<Flow(id={self.id}, name='{self.name}', chatbot_id={self.chatbot_id})>"
 
+    @property
+    def draft_nodes(self):
+        """Nodes of the draft (published versions keep their own copies)"""
+        return [n for n in self.nodes if n.flow_version_id is None]
+
     @property
     def start_node(self):
-        """Get the start node of the flow"""
-        return next((n for n in self.nodes if n.node_type == "start"), None)
+        """Get the start node of the draft"""
+        return next((n for n in self.draft_nodes if n.node_type == "start"), None)
 
     @property
     def total_nodes(self) -> int:
-        """Total number of nodes in flow"""
-        return len(self.nodes)
+        """Total number of nodes in the draft"""
+        return len(self.draft_nodes)
+
+
+class FlowVersion(Base, TimestampMixin):
+    """
+    Immutable snapshot of a flow published from its draft
+
+    Publishing copies canvas_data, variables and the draft nodes (tagged with
+    flow_version_id). Conversations keep running the version of the node they
+    are on unless the publish migrates them (see app.utils.flow_migration);
+    rolling back only affects new conversations.
+    """
+
+    __tablename__ = "flow_versions"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    published_by_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    # Sequential per flow (1, 2, 3...)
+    version = Column(Integer, nullable=False)
+
+    # Snapshot
+    canvas_data = Column(JSONBCompatible, nullable=False)
+    variables = Column(
+        JSONBCompatible,
+        nullable=False,
+        default={},
+        server_default=text("'{}'::jsonb"),
+    )
+
+    notes = Column(Text, nullable=True)
+
+    # published (live) or archived
+    status = Column(String(20), nullable=False, default="published", server_default="published")
+    published_at = Column(DateTime(timezone=True), nullable=False)
+
+    # Execution stats
+    sessions_started = Column(Integer, default=0, server_default="0", nullable=False)
+    sessions_completed = Column(Integer, default=0, server_default="0", nullable=False)
+
+    # Running sessions moved here on publish (see app.utils.flow_migration)
+    session_migration = Column(JSONBCompatible, nullable=True)
+
+    # Graph metrics computed on publish (see app.utils.flow_complexity)
+    complexity = Column(JSONBCompatible, nullable=True)
+
+    # Relationships
+    flow = relationship("Flow", back_populates="versions", foreign_keys=[flow_id])
+
+    __table_args__ = (
+        UniqueConstraint("flow_id", "version", name="uq_flow_versions_flow_version"),
+    )
+
+    def __repr__(self):
+        return f"<FlowVersion(flow_id={self.flow_id}, version={self.version}, status='{self.status}')>"
+
+    @property
+    def completion_rate(self):
+        """Percentage of started sessions that reached an end node"""
+        if not self.sessions_started:
+            return None
+        return round(self.sessions_completed / self.sessions_started * 100, 2)
 
 
 class Node(Base, TimestampMixin):
@@ -212,6 +325,14 @@ class Node(Base, TimestampMixin):
         index=True,
     )
 
+    # Published version this copy belongs to (None = draft node)
+    flow_version_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flow_versions.id", ondelete="CASCADE"),
+        nullable=True,
+        index=True,
+    )
+
     # Node Info
     # React Flow node ID (for frontend sync)
     node_id = Column(String(255), nullable=False, index=True)
@@ -263,3 +384,430 @@ class Node(Base, TimestampMixin):
     def is_decision_node(self) -> bool:
         """Check if this node makes decisions (condition, question)"""
         return self.node_type in ["condition", "question"]
+
+
+class FlowConsoleSession(Base, TimestampMixin):
+    """
+    Flow console session - a developer chatting with a flow over REST
+
+    The whole session (variables, attempts, last outputs) is kept in state;
+    Redis caches it, this table keeps it across Redis flushes and restarts.
+    """
+
+    __tablename__ = "flow_console_sessions"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Public session id (hex) used by the console endpoints
+    session_id = Column(String(32), nullable=False, unique=True, index=True)
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    user_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    # Session as returned by the console endpoints
+    state = Column(
+        JSONB,
+        nullable=False,
+        default={},
+        server_default=text("'{}'::jsonb"),
+    )
+
+    finished = Column(Boolean, default=False, server_default="false", nullable=False)
+
+    # Idle expiry (FLOW_CONSOLE_SESSION_TTL_SECONDS after the last turn)
+    expires_at = Column(DateTime(timezone=True), nullable=False, index=True)
+
+    def __repr__(self):
+        return f"<FlowConsoleSession(session_id='{self.session_id}', flow_id={self.flow_id})>"
+
+
+class FlowWait(Base, TimestampMixin):
+    """
+    Flow paused by a wait node until resume_at
+
+    Picked up by the resume_flow_waits task, which follows the wait node's
+    outgoing edge. Waits whose conversation has left the node meanwhile
+    (handoff, reply with resumeOnReply, new flow) are cancelled instead.
+    """
+
+    __tablename__ = "flow_waits"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    conversation_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("conversations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="CASCADE"),
+        nullable=False,
+    )
+
+    # Wait node the flow resumes from
+    node_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("nodes.id", ondelete="CASCADE"),
+        nullable=False,
+    )
+
+    resume_at = Column(DateTime(timezone=True), nullable=False, index=True)
+
+    # pending, resumed, cancelled
+    status = Column(String(20), nullable=False, default="pending", server_default="pending", index=True)
+
+    resumed_at = Column(DateTime(timezone=True), nullable=True)
+
+    def __repr__(self):
+        return f"<FlowWait(conversation_id={self.conversation_id}, resume_at={self.resume_at}, status='{self.status}')>"
+
+
+class FlowNodeVisit(Base):
+    """
+    One node of a flow session, from entering it until the next node
+
+    A session is one run of a flow for a conversation (from its start node
+    until end, handoff or the bot being switched off). The visit is closed
+    when the session enters the next node or finishes; visits left open
+    are where contacts abandoned the flow.
+    """
+
+    __tablename__ = "flow_node_visits"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_version_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flow_versions.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    conversation_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("conversations.id", ondelete="CASCADE"),
+        nullable=False,
+    )
+
+    # Flow session (kept in the conversation's context variables while the flow runs)
+    session_id = Column(UUID(as_uuid=True), nullable=False, index=True)
+
+    # React Flow node ID, the same across versions
+    node_id = Column(String(255), nullable=False)
+    node_type = Column(String(50), nullable=False)
+
+    entered_at = Column(DateTime(timezone=True), nullable=False, index=True)
+    exited_at = Column(DateTime(timezone=True), nullable=True)
+    duration_ms = Column(Integer, nullable=True)
+
+    # Branch drawn by a split node (A/B test), set on the split node's visit
+    variant = Column(String(100), nullable=True)
+
+    def __repr__(self):
+        return f"<FlowNodeVisit(session_id={self.session_id}, node_id='{self.node_id}')>"
+
+
+class FlowTraceEvent(Base):
+    """
+    Entry of a flow session's execution trace (append-only)
+
+    Nodes executed, inputs received, messages sent and variables changed,
+    in order, to explain what the bot did in a session. Event types and
+    their data live in app.utils.flow_trace.
+    """
+
+    __tablename__ = "flow_trace_events"
+    __table_args__ = (
+        Index("ix_flow_trace_events_session_occurred_at", "session_id", "occurred_at"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    conversation_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("conversations.id", ondelete="CASCADE"),
+        nullable=False,
+    )
+
+    # Flow session (same as FlowNodeVisit.session_id)
+    session_id = Column(UUID(as_uuid=True), nullable=False)
+
+    # node_executed, input_received, message_sent or variable_set
+    event_type = Column(String(30), nullable=False)
+
+    # React Flow node ID and type of the node involved, if any
+    node_id = Column(String(255), nullable=True)
+    node_type = Column(String(50), nullable=True)
+
+    data = Column(JSONBCompatible, nullable=False, default=dict, server_default=text("'{}'::jsonb"))
+    occurred_at = Column(DateTime(timezone=True), nullable=False)
+
+    def __repr__(self):
+        return f"<FlowTraceEvent(session_id={self.session_id}, event_type='{self.event_type}', node_id='{self.node_id}')>"
+
+
+class SurveyResponse(Base, TimestampMixin):
+    """
+    Answer to a survey node (NPS or CSAT score plus optional comment)
+
+    The agent is the one assigned to the conversation when the contact
+    answered, so scores can be compared per agent as well as per flow.
+    Aggregation lives in app.utils.survey.
+    """
+
+    __tablename__ = "survey_responses"
+    __table_args__ = (
+        Index("ix_survey_responses_org_created_at", "organization_id", "created_at"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys (answers outlive deleted flows, conversations and agents)
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="SET NULL"),
+        nullable=True,
+        index=True,
+    )
+
+    conversation_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("conversations.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    contact_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("contacts.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    agent_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="SET NULL"),
+        nullable=True,
+        index=True,
+    )
+
+    # React Flow node ID of the survey node
+    node_id = Column(String(255), nullable=False)
+
+    # nps (0-10) or csat (1-5)
+    survey_type = Column(String(10), nullable=False)
+    score = Column(Integer, nullable=False)
+    comment = Column(Text, nullable=True)
+
+    def __repr__(self):
+        return f"<SurveyResponse(survey_type='{self.survey_type}', score={self.score}, flow_id={self.flow_id})>"
+
+
+class FlowTrigger(Base, TimestampMixin):
+    """
+    Keyword trigger that starts a flow when an inbound message matches
+
+    Evaluated before the chatbot's main flow; triggers with interrupts_flow
+    also fire while another flow is running (global commands like "menu").
+    Matching rules and ordering live in app.utils.flow_triggers.
+    """
+
+    __tablename__ = "flow_triggers"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    # exact, keyword, regex
+    match_type = Column(String(20), nullable=False, default="keyword", server_default="keyword")
+    pattern = Column(String(500), nullable=False)
+
+    # Higher priority wins when several triggers match
+    priority = Column(Integer, nullable=False, default=0, server_default="0")
+
+    # Also fires while a flow is running, replacing it
+    interrupts_flow = Column(Boolean, nullable=False, default=False, server_default="false")
+
+    is_active = Column(Boolean, nullable=False, default=True, server_default="true")
+
+    def __repr__(self):
+        return f"<FlowTrigger(match_type='{self.match_type}', pattern='{self.pattern}', flow_id={self.flow_id})>"
+
+
+class FlowEventTrigger(Base, TimestampMixin):
+    """
+    Event that starts a flow for a contact without an inbound message
+
+    Fired by internal events (campaign reply, overdue invoice, resolved
+    ticket) or by external systems; payload conditions and variable mapping
+    live in app.utils.flow_events. With a template the flow is
+    outbound-initiated: the template opens the conversation and the flow
+    starts when the contact replies.
+    """
+
+    __tablename__ = "flow_event_triggers"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    flow_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("flows.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    # Number the conversation runs on
+    whatsapp_number_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("whatsapp_numbers.id", ondelete="CASCADE"),
+        nullable=False,
+    )
+
+    # campaign.reply_received, invoice.overdue, ticket.resolved or a custom name
+    event_type = Column(String(100), nullable=False, index=True)
+
+    # {"invoice.currency": "BRL"}: payload values the event must have
+    conditions = Column(JSONBCompatible, nullable=False, default=dict, server_default=text("'{}'::jsonb"))
+
+    # {"valor": "invoice.amount"}: flow variable -> payload path
+    variable_mapping = Column(JSONBCompatible, nullable=False, default=dict, server_default=text("'{}'::jsonb"))
+
+    # Outbound-initiated: approved template sent first, with body parameters from these paths
+    template_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("whatsapp_templates.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+    template_variables = Column(JSONBCompatible, nullable=False, default=list, server_default=text("'[]'::jsonb"))
+
+    # Also fires while a flow is running, replacing it
+    interrupts_flow = Column(Boolean, nullable=False, default=False, server_default="false")
+
+    is_active = Column(Boolean, nullable=False, default=True, server_default="true")
+
+    # Stats
+    total_started = Column(Integer, nullable=False, default=0, server_default="0")
+    total_skipped = Column(Integer, nullable=False, default=0, server_default="0")
+    total_failed = Column(Integer, nullable=False, default=0, server_default="0")
+    last_fired_at = Column(DateTime(timezone=True), nullable=True)
+
+    def __repr__(self):
+        return f"<FlowEventTrigger(event_type='{self.event_type}', flow_id={self.flow_id})>"
@@ -112,6 +112,9 @@ class Contact(Base, TimestampMixin, SoftDeleteMixin):
     # VIP Status
     is_vip = Column(Boolean, default=False, server_default="false", nullable=False, index=True)
 
+    # Language (ISO 639-1: pt, es, en...) - detected from early messages or set manually
+    language = Column(String(10), nullable=True, index=True)
+
     # Activity Tracking
     last_message_at = Column(DateTime(timezone=True), nullable=True, index=True)
     last_message_received_at = Column(DateTime(timezone=True), nullable=True)
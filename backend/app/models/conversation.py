@@ -2,7 +2,10 @@
 Conversation and Message models for chat/inbox
 """
 
+from typing import Optional
+
 from sqlalchemy import (
+    BigInteger,
     Boolean,
     Column,
     DateTime,
@@ -100,6 +103,18 @@ class Conversation(Base, TimestampMixin, SoftDeleteMixin):
         nullable=True,
     )
 
+    # Flow session deadlines: no input until / session ends at (see app.utils.flow_timeout)
+    flow_timeout_at = Column(DateTime(timezone=True), nullable=True, index=True)
+    flow_expires_at = Column(DateTime(timezone=True), nullable=True, index=True)
+
+    # Execution checkpoint of the current node; the timestamp is set while a node runs
+    # or the next one is about to, and cleared once it stops (see app.utils.flow_checkpoint)
+    node_checkpoint = Column(JSONBCompatible, nullable=True)
+    node_checkpoint_at = Column(DateTime(timezone=True), nullable=True, index=True)
+
+    # Fencing token of the flow session lock holder (see app.core.flow_session)
+    flow_session_fence = Column(BigInteger, nullable=True)
+
     # Status
     # open, active, queued, closed, archived
     status = Column(
@@ -421,6 +436,11 @@ class Message(Base, TimestampMixin, SoftDeleteMixin):
     def __repr__(self):
         return f"<Message(id={self.id}, type='{self.message_type}', direction='{self.direction}')>"
 
+    @property
+    def meta_response(self) -> Optional[dict]:
+        """Meta request id (fbtrace_id) and rate-limit usage of the send"""
+        return (self.extra_data or {}).get("meta_response")
+
     @property
     def is_inbound(self) -> bool:
         """Check if message is inbound (from contact)"""
@@ -0,0 +1,86 @@
+"""
+Conversation participant model - Agents collaborating on a conversation
+
+A conversation has at most one primary agent (mirrors
+Conversation.current_agent_id) and any number of collaborators. Each
+participant chooses which conversation events notify them.
+"""
+
+from sqlalchemy import Column, DateTime, ForeignKey, Integer, String, UniqueConstraint
+from sqlalchemy.dialects.postgresql import UUID
+from sqlalchemy.orm import relationship
+from sqlalchemy.sql import text
+
+from app.models.base import Base, TimestampMixin
+
+PARTICIPANT_ROLES = ("primary", "collaborator")
+# all = every inbound message, mentions = only when mentioned, none = muted
+NOTIFICATION_LEVELS = ("all", "mentions", "none")
+
+
+class ConversationParticipant(Base, TimestampMixin):
+    """Agent taking part in a conversation"""
+
+    __tablename__ = "conversation_participants"
+    __table_args__ = (
+        UniqueConstraint("conversation_id", "user_id", name="uq_conversation_participant"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    conversation_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("conversations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    user_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    added_by_user_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    # primary, collaborator
+    role = Column(String(20), nullable=False, default="collaborator", server_default="collaborator")
+    # all, mentions, none
+    notification_level = Column(String(20), nullable=False, default="all", server_default="all")
+
+    joined_at = Column(DateTime(timezone=True), nullable=False)
+    # Set when the agent leaves; the row is kept for attribution history
+    left_at = Column(DateTime(timezone=True), nullable=True)
+
+    # Outbound attribution
+    messages_sent = Column(Integer, nullable=False, default=0, server_default="0")
+    last_message_at = Column(DateTime(timezone=True), nullable=True)
+
+    # Relationships
+    user = relationship("User", foreign_keys=[user_id])
+
+    @property
+    def is_active(self) -> bool:
+        return self.left_at is None
+
+    @property
+    def is_primary(self) -> bool:
+        return self.role == "primary"
+
+    def __repr__(self):
+        return f"<ConversationParticipant(conversation_id={self.conversation_id}, user_id={self.user_id}, role='{self.role}')>"
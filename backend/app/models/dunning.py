@@ -0,0 +1,225 @@
+"""
+Dunning models - Invoice reminders driven by ERP data
+
+Invoices are pushed by the tenant's ERP (see POST /dunning/invoices/sync).
+Dunning rules decide which WhatsApp template is sent relative to the due
+date ("3 days before", "5 days overdue"...), and each planned send is a
+DunningReminder dispatched through the delayed Celery queue.
+"""
+
+from sqlalchemy import (
+    Boolean,
+    Column,
+    Date,
+    DateTime,
+    ForeignKey,
+    Integer,
+    Numeric,
+    String,
+    Text,
+    Time,
+    UniqueConstraint,
+)
+from sqlalchemy.dialects.postgresql import UUID
+from sqlalchemy.orm import relationship
+from sqlalchemy.sql import text
+
+from app.models.base import Base, SoftDeleteMixin, TimestampMixin, JSONBCompatible
+
+
+class Invoice(Base, TimestampMixin):
+    """Invoice (receivable) synced from the tenant's ERP"""
+
+    __tablename__ = "invoices"
+    __table_args__ = (
+        UniqueConstraint("organization_id", "external_id", name="uq_invoices_org_external_id"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    contact_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("contacts.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    # ERP identification
+    external_id = Column(String(255), nullable=False)  # Invoice ID in the ERP
+    number = Column(String(100), nullable=True)  # Human-readable invoice number
+
+    # Amount and due date
+    amount = Column(Numeric(12, 2), nullable=False)
+    currency = Column(String(3), nullable=False, default="BRL", server_default="BRL")
+    due_date = Column(Date, nullable=False, index=True)
+
+    # Status: open, paid, cancelled
+    status = Column(String(20), nullable=False, default="open", server_default="open", index=True)
+    paid_at = Column(DateTime(timezone=True), nullable=True)
+
+    # Payment options offered in reminders
+    pix_code = Column(Text, nullable=True)  # Pix "copia e cola"
+    payment_url = Column(Text, nullable=True)  # Boleto / payment link
+
+    # Extra ERP fields (exposed to templates as invoice.extra.<key>)
+    extra_data = Column(
+        JSONBCompatible,
+        nullable=False,
+        default={},
+        server_default=text("'{}'::jsonb"),
+    )
+
+    last_synced_at = Column(DateTime(timezone=True), nullable=True)
+
+    # invoice.overdue flow event emitted (see app.utils.flow_events)
+    overdue_event_at = Column(DateTime(timezone=True), nullable=True)
+
+    # Relationships
+    contact = relationship("Contact")
+    reminders = relationship("DunningReminder", back_populates="invoice", cascade="all, delete-orphan")
+
+    def __repr__(self):
+        return f"<Invoice(id={self.id}, external_id='{self.external_id}', status='{self.status}')>"
+
+    @property
+    def is_open(self) -> bool:
+        return self.status == "open"
+
+
+class DunningRule(Base, TimestampMixin, SoftDeleteMixin):
+    """
+    Reminder rule relative to the invoice due date
+
+    offset_days < 0: before the due date (-3 = 3 days before)
+    offset_days = 0: on the due date
+    offset_days > 0: overdue (5 = 5 days after the due date)
+    """
+
+    __tablename__ = "dunning_rules"
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    whatsapp_number_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("whatsapp_numbers.id", ondelete="CASCADE"),
+        nullable=False,
+    )
+    template_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("whatsapp_templates.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    # Configuration
+    name = Column(String(255), nullable=False)
+    description = Column(Text, nullable=True)
+    offset_days = Column(Integer, nullable=False)
+    send_time = Column(Time, nullable=False)  # Local time of the send (Ex: 09:00)
+    timezone = Column(String(50), nullable=False, default="America/Sao_Paulo", server_default="America/Sao_Paulo")
+
+    # Template body parameters, in order, as invoice fields
+    # Ex: ["contact.name", "invoice.amount", "invoice.due_date", "invoice.pix_code"]
+    template_variables = Column(
+        JSONBCompatible,
+        nullable=False,
+        default=[],
+        server_default=text("'[]'::jsonb"),
+    )
+
+    # Require a Pix code on the invoice (skip invoices without one)
+    offer_pix = Column(Boolean, default=False, server_default="false", nullable=False)
+
+    # Minimum amount to remind (None = any amount)
+    min_amount = Column(Numeric(12, 2), nullable=True)
+
+    is_active = Column(Boolean, default=True, server_default="true", nullable=False)
+
+    # Performance metrics
+    total_scheduled = Column(Integer, default=0, server_default="0", nullable=False)
+    total_sent = Column(Integer, default=0, server_default="0", nullable=False)
+    total_failed = Column(Integer, default=0, server_default="0", nullable=False)
+    total_skipped = Column(Integer, default=0, server_default="0", nullable=False)
+    total_paid = Column(Integer, default=0, server_default="0", nullable=False)  # Paid after this reminder
+    recovered_amount = Column(Numeric(14, 2), default=0, server_default="0", nullable=False)
+
+    # Relationships
+    whatsapp_number = relationship("WhatsAppNumber")
+    template = relationship("WhatsAppTemplate")
+
+    def __repr__(self):
+        return f"<DunningRule(id={self.id}, name='{self.name}', offset_days={self.offset_days})>"
+
+
+class DunningReminder(Base, TimestampMixin):
+    """One planned reminder of an invoice (unique per rule and invoice)"""
+
+    __tablename__ = "dunning_reminders"
+    __table_args__ = (
+        UniqueConstraint("rule_id", "invoice_id", name="uq_dunning_reminders_rule_invoice"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    rule_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("dunning_rules.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    invoice_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("invoices.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+
+    # Status: scheduled, sent, failed, skipped
+    status = Column(String(20), nullable=False, default="scheduled", server_default="scheduled", index=True)
+    scheduled_for = Column(DateTime(timezone=True), nullable=False)
+    sent_at = Column(DateTime(timezone=True), nullable=True)
+    whatsapp_message_id = Column(String(255), nullable=True)
+    skip_reason = Column(String(100), nullable=True)
+    error_message = Column(Text, nullable=True)
+
+    # Relationships
+    rule = relationship("DunningRule")
+    invoice = relationship("Invoice", back_populates="reminders")
+
+    def __repr__(self):
+        return f"<DunningReminder(id={self.id}, status='{self.status}')>"
@@ -75,6 +75,15 @@ class Organization(Base, TimestampMixin, SoftDeleteMixin):
     is_trial = Column(Boolean, default=True, server_default="true", nullable=False)
     trial_ends_at = Column(DateTime(timezone=True), nullable=True)
 
+    # Home region for multi-region deployments (webhooks and sends are pinned to it)
+    home_region = Column(String(50), nullable=True)
+
+    # Message Encryption at Rest (per-tenant data key, wrapped by ENCRYPTION_KEY)
+    message_encryption_enabled = Column(
+        Boolean, default=False, server_default="false", nullable=False
+    )
+    message_data_key = Column(Text, nullable=True)
+
     # Billing
     stripe_customer_id = Column(String(255), nullable=True, unique=True)
     stripe_subscription_id = Column(String(255), nullable=True)
@@ -18,6 +18,19 @@ class SecretScope(str, Enum):
     CHATBOT = "chatbot"            # Only for specific chatbot
 
 
+class SecretKind(str, Enum):
+    """Kind of entry - both are encrypted at rest, variables are readable by any member"""
+    SECRET = "secret"      # Referenced as {{org.secrets.NAME}}
+    VARIABLE = "variable"  # Referenced as {{org.vars.NAME}}
+
+
+class SecretUsage(str, Enum):
+    """Places where an entry can be interpolated"""
+    FLOW_HTTP = "flow_http"    # API Call nodes
+    WEBHOOK = "webhook"        # Webhook actions of Action nodes
+    AI_PROMPT = "ai_prompt"    # AI Prompt nodes
+
+
 class EncryptionProvider(str, Enum):
     """Encryption provider used to encrypt the secret"""
     FERNET = "fernet"      # Internal symmetric encryption (default)
@@ -97,6 +110,15 @@ class Secret(Base, TimestampMixin):
         server_default=SecretScope.CHATBOT.value
     )
     is_active = Column(Boolean, nullable=False, server_default=text("true"), index=True)
+    kind = Column(
+        SQLEnum(SecretKind, values_callable=lambda x: [e.value for e in x]),
+        nullable=False,
+        server_default=SecretKind.SECRET.value
+    )
+
+    # Per-key permissions (NULL = defaults)
+    allowed_roles = Column(JSONB, nullable=True)   # Roles that may read the value besides admins
+    allowed_usages = Column(JSONB, nullable=True)  # SecretUsage values where it may be interpolated
 
     # Metadata (note: using 'secret_metadata' to avoid conflict with SQLAlchemy's metadata attribute)
     secret_metadata = Column("metadata", JSONB, nullable=True)  # Tags, categories, etc.
@@ -108,7 +130,65 @@ class Secret(Base, TimestampMixin):
     def __repr__(self):
         return f"<Secret(id={self.id}, name={self.name}, provider={self.encryption_provider})>"
 
+    @property
+    def reference(self) -> str:
+        """Placeholder used in flows, webhook headers and prompts"""
+        namespace = "vars" if self.kind == SecretKind.VARIABLE else "secrets"
+        return f"{{{{org.{namespace}.{self.name}}}}}"
+
+    def can_be_read_by(self, role: str) -> bool:
+        """Whether a user with this role may read the plaintext value through the API"""
+        if role in ("super_admin", "org_admin"):
+            return True
+        if self.allowed_roles is not None:
+            return role in self.allowed_roles
+        return self.kind == SecretKind.VARIABLE
+
+    def can_be_used_in(self, usage: str) -> bool:
+        return self.allowed_usages is None or usage in self.allowed_usages
+
     def increment_usage(self):
         """Increment usage counter and update last used timestamp"""
         self.usage_count += 1
         self.last_used_at = datetime.utcnow()
+
+
+class SecretAccessLog(Base, TimestampMixin):
+    """
+    Audit trail of a secret: reads of its value (API or interpolation),
+    denied accesses and changes.
+    """
+
+    __tablename__ = "secret_access_logs"
+
+    id = Column(UUID, primary_key=True, server_default=text("gen_random_uuid()"))
+    organization_id = Column(
+        UUID,
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True
+    )
+    # Kept after a hard delete (name is copied for that reason)
+    secret_id = Column(
+        UUID,
+        ForeignKey("secrets.id", ondelete="SET NULL"),
+        nullable=True,
+        index=True
+    )
+    secret_name = Column(String(255), nullable=False)
+
+    # create, update, rotate, delete, reveal (API read), resolve (interpolation)
+    action = Column(String(20), nullable=False, index=True)
+    # api or a SecretUsage value
+    usage = Column(String(20), nullable=False)
+    user_id = Column(UUID, ForeignKey("users.id", ondelete="SET NULL"), nullable=True)
+
+    # What used the value (e.g. resource_type="node", resource_id=<node id>)
+    resource_type = Column(String(50), nullable=True)
+    resource_id = Column(String(255), nullable=True)
+
+    allowed = Column(Boolean, nullable=False, server_default=text("true"))
+    reason = Column(Text, nullable=True)  # Why the access was denied
+
+    def __repr__(self):
+        return f"<SecretAccessLog(secret={self.secret_name}, action={self.action}, allowed={self.allowed})>"
@@ -4,9 +4,10 @@ User and authentication models
 
 from datetime import datetime
 
-from sqlalchemy import Boolean, Column, DateTime, ForeignKey, Integer, String, Text
+from sqlalchemy import Boolean, Column, DateTime, ForeignKey, Integer, String, Text, UniqueConstraint
 from sqlalchemy.dialects.postgresql import ARRAY, INET, JSONB, UUID
 from sqlalchemy.orm import relationship
+from sqlalchemy.orm.attributes import set_committed_value
 from sqlalchemy.sql import text
 
 from app.models.base import Base, SoftDeleteMixin, TimestampMixin
@@ -118,6 +119,12 @@ class User(Base, TimestampMixin, SoftDeleteMixin):
     refresh_tokens = relationship(
         "RefreshToken", back_populates="user", cascade="all, delete-orphan"
     )
+    workspaces = relationship(
+        "UserWorkspace",
+        back_populates="user",
+        cascade="all, delete-orphan",
+        foreign_keys="UserWorkspace.user_id",
+    )
     # conversations_as_agent = relationship("Conversation", foreign_keys="Conversation.current_agent_id", back_populates="current_agent")
     # messages_sent = relationship("Message", back_populates="sender_user")
 
@@ -161,6 +168,23 @@ class User(Base, TimestampMixin, SoftDeleteMixin):
         """Check if user can access conversations"""
         return self.role in ["super_admin", "org_admin", "agent"]
 
+    @property
+    def home_organization_id(self):
+        """Organization the account was created in (organization_id may be a switched workspace)"""
+        return getattr(self, "_home_organization_id", None) or self.organization_id
+
+    def apply_workspace(self, workspace: "UserWorkspace") -> None:
+        """
+        Scope this instance to another workspace for the current request
+
+        Values are set as committed state, so a later flush never writes the
+        guest organization/role over the user's home organization.
+        """
+        self._home_organization_id = self.home_organization_id
+        set_committed_value(self, "organization_id", workspace.organization_id)
+        set_committed_value(self, "role", workspace.role)
+        set_committed_value(self, "department_ids", list(workspace.department_ids or []))
+
     def has_permission(self, permission: str) -> bool:
         """Check if user has specific permission"""
         return permission in self.permissions or self.is_super_admin
@@ -243,3 +267,63 @@ class RefreshToken(Base, TimestampMixin):
         self.revoked = True
         self.revoked_at = datetime.utcnow()
         self.revoked_reason = reason
+
+
+class UserWorkspace(Base, TimestampMixin):
+    """
+    Membership of a user in an organization other than their home one
+
+    Lets one account (e.g. an agency) work in several tenants with a
+    different role in each. The home organization stays in
+    users.organization_id / users.role; tokens carry the active workspace.
+    """
+
+    __tablename__ = "user_workspaces"
+    __table_args__ = (
+        UniqueConstraint("user_id", "organization_id", name="uq_user_workspaces_user_org"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys
+    user_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=False,
+        index=True,
+    )
+    invited_by_user_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("users.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    # Role in this workspace: org_admin, agent, viewer
+    role = Column(String(50), nullable=False, default="agent", server_default="agent")
+    department_ids = Column(
+        ARRAY(UUID(as_uuid=True)),
+        nullable=False,
+        default=[],
+        server_default=text("ARRAY[]::uuid[]"),
+    )
+
+    is_active = Column(Boolean, default=True, server_default="true", nullable=False)
+    last_accessed_at = Column(DateTime(timezone=True), nullable=True)
+
+    # Relationships
+    user = relationship("User", back_populates="workspaces", foreign_keys=[user_id])
+    organization = relationship("Organization")
+
+    def __repr__(self):
+        return f"<UserWorkspace(user_id={self.user_id}, organization_id={self.organization_id}, role='{self.role}')>"
@@ -0,0 +1,69 @@
+"""
+Webhook event model - Raw Meta webhook payloads and their processing status
+
+Every payload accepted by a webhook receiver is stored before it is
+processed, so events that failed (bug, outage) or were skipped (e.g. the
+number was not configured yet) can be replayed instead of being lost.
+"""
+
+from sqlalchemy import Column, DateTime, ForeignKey, Index, Integer, String, Text
+from sqlalchemy.dialects.postgresql import UUID
+from sqlalchemy.sql import text
+
+from app.models.base import Base, TimestampMixin, JSONBCompatible
+
+# received -> processed | failed | skipped; queued = waiting for a worker (forward or replay)
+WEBHOOK_EVENT_STATUSES = ("received", "queued", "processed", "failed", "skipped")
+REPLAYABLE_STATUSES = ("failed", "skipped")
+
+
+class WebhookEvent(Base, TimestampMixin):
+    """Raw webhook payload received from Meta"""
+
+    __tablename__ = "webhook_events"
+    __table_args__ = (
+        Index("ix_webhook_events_org_status_received", "organization_id", "status", "received_at"),
+    )
+
+    # Primary Key
+    id = Column(
+        UUID(as_uuid=True),
+        primary_key=True,
+        server_default=text("gen_random_uuid()"),
+    )
+
+    # Foreign Keys (NULL when the number was unknown on arrival)
+    organization_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("organizations.id", ondelete="CASCADE"),
+        nullable=True,
+        index=True,
+    )
+    whatsapp_number_id = Column(
+        UUID(as_uuid=True),
+        ForeignKey("whatsapp_numbers.id", ondelete="SET NULL"),
+        nullable=True,
+    )
+
+    # Receiver that accepted the payload: whatsapp (per-number callback) or meta (app-level)
+    source = Column(String(20), nullable=False, default="whatsapp", server_default="whatsapp")
+    phone_number_id = Column(String(50), nullable=True, index=True)
+
+    payload = Column(JSONBCompatible, nullable=False)
+
+    # received, queued, processed, failed, skipped
+    status = Column(String(20), nullable=False, default="received", server_default="received", index=True)
+    error = Column(Text, nullable=True)
+
+    # Processing attempts, replays included
+    attempts = Column(Integer, nullable=False, default=0, server_default="0")
+    received_at = Column(DateTime(timezone=True), nullable=False, index=True)
+    last_attempt_at = Column(DateTime(timezone=True), nullable=True)
+    processed_at = Column(DateTime(timezone=True), nullable=True)
+
+    @property
+    def is_replayable(self) -> bool:
+        return self.status in REPLAYABLE_STATUSES
+
+    def __repr__(self):
+        return f"<WebhookEvent(id={self.id}, source='{self.source}', status='{self.status}')>"
@@ -58,6 +58,10 @@ class WhatsAppNumber(Base, TimestampMixin, SoftDeleteMixin):
         String(255), nullable=True
     )  # Meta's WABA ID
     access_token = Column(Text, nullable=True)  # Meta API access token
+    token_status = Column(String(20), nullable=True)  # valid, expiring, expired, invalid (see debug_token)
+    token_expires_at = Column(DateTime(timezone=True), nullable=True)  # None: never expires (system user) or unknown
+    token_checked_at = Column(DateTime(timezone=True), nullable=True)
+    token_expiry_notified_at = Column(DateTime(timezone=True), nullable=True)
     app_secret = Column(Text, nullable=True)  # Meta App Secret for webhook signature verification
     webhook_verify_token = Column(String(255), nullable=True)
 
@@ -86,7 +90,7 @@ class WhatsAppNumber(Base, TimestampMixin, SoftDeleteMixin):
     )  # GREEN, YELLOW, RED, FLAGGED
     messaging_limit_tier = Column(
         String(50), nullable=True
-    )  # TIER_50, TIER_250, TIER_1K, TIER_10K, TIER_100K, TIER_UNLIMITED
+    )  # TIER_50, TIER_250, TIER_1K, TIER_2K, TIER_10K, TIER_100K, TIER_UNLIMITED
 
     # Configuration
     # Default chatbot to activate for new conversations
@@ -191,7 +195,7 @@ class WhatsAppTemplate(Base, TimestampMixin, SoftDeleteMixin):
     meta_template_id = Column(String(255), nullable=True)
 
     # Status
-    # DRAFT, PENDING, APPROVED, REJECTED, DISABLED, DELETED
+    # DRAFT, PENDING, APPROVED, REJECTED, PAUSED, FLAGGED, DISABLED, DELETED
     status = Column(
         String(50),
         nullable=False,
@@ -219,7 +223,7 @@ class WhatsAppTemplate(Base, TimestampMixin, SoftDeleteMixin):
     footer_text = Column(Text, nullable=True)
 
     # Buttons (optional)
-    # Array of buttons: QUICK_REPLY, CALL_TO_ACTION (URL or PHONE)
+    # Array of buttons: QUICK_REPLY, CALL_TO_ACTION (URL or PHONE), COPY_CODE
     buttons = Column(
         JSONB,
         nullable=False,
@@ -227,6 +231,13 @@ class WhatsAppTemplate(Base, TimestampMixin, SoftDeleteMixin):
         server_default=text("'[]'::jsonb"),
     )
 
+    # Carousel (optional): card layout synced from the CAROUSEL component
+    # [{"header_format": "IMAGE", "body_variables_count": 1, "buttons": [...]}]
+    carousel_cards = Column(JSONB, nullable=True)
+
+    # Limited-time offer (optional): {"text": "...", "has_expiration": true}
+    limited_time_offer = Column(JSONB, nullable=True)
+
     # Variables placeholders (for reference)
     # Example: ["{{1}}", "{{2}}"] for body variables
     variables = Column(
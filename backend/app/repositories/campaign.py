@@ -80,6 +80,42 @@ class CampaignRepository(BaseRepository[Campaign]):
         result = await self.db.execute(query)
         return result.scalar() or 0
 
+    async def get_occurrences(
+        self, parent_campaign_id: UUID, skip: int = 0, limit: int = 100
+    ) -> List[Campaign]:
+        """
+        Get the occurrences of a recurring campaign
+
+        Args:
+            parent_campaign_id: Parent campaign UUID
+            skip: Number of records to skip
+            limit: Maximum number of records
+
+        Returns:
+            List of campaigns, latest occurrence first
+        """
+        query = (
+            select(Campaign)
+            .where(
+                Campaign.parent_campaign_id == parent_campaign_id,
+                Campaign.deleted_at.is_(None),
+            )
+            .order_by(Campaign.occurrence_number.desc())
+            .offset(skip)
+            .limit(limit)
+        )
+        result = await self.db.execute(query)
+        return list(result.scalars().all())
+
+    async def count_occurrences(self, parent_campaign_id: UUID) -> int:
+        """Count the occurrences of a recurring campaign"""
+        query = select(func.count(Campaign.id)).where(
+            Campaign.parent_campaign_id == parent_campaign_id,
+            Campaign.deleted_at.is_(None),
+        )
+        result = await self.db.execute(query)
+        return result.scalar() or 0
+
     async def get_scheduled_campaigns(
         self, organization_id: UUID, before_time: Optional[datetime] = None
     ) -> List[Campaign]:
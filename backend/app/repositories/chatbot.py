@@ -1,15 +1,29 @@
 """
-Chatbot, Flow, and Node repositories
+Chatbot, Flow, FlowVersion, Node, flow console session, flow wait, node visit, survey response,
+flow trigger and flow event trigger repositories
 """
 
-from typing import List, Optional
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, List, Optional, Set
 from uuid import UUID
 
-from sqlalchemy import delete, func, select
+from sqlalchemy import case, delete, func, or_, select, update
 from sqlalchemy.ext.asyncio import AsyncSession
 from sqlalchemy.orm import selectinload
 
-from app.models.chatbot import Chatbot, Flow, Node
+from app.models.chatbot import (
+    Chatbot,
+    Flow,
+    FlowConsoleSession,
+    FlowEventTrigger,
+    FlowNodeVisit,
+    FlowTraceEvent,
+    FlowTrigger,
+    FlowVersion,
+    FlowWait,
+    Node,
+    SurveyResponse,
+)
 from app.repositories.base import BaseRepository
 
 
@@ -163,6 +177,27 @@ class FlowRepository(BaseRepository[Flow]):
         )
         return list(result.scalars().all())
 
+    async def get_by_name(self, name: str, organization_id: UUID) -> Optional[Flow]:
+        """
+        Get a flow of the organization by name (oldest first when repeated)
+
+        Args:
+            name: Flow name
+            organization_id: Organization UUID
+
+        Returns:
+            Flow or None
+        """
+        result = await self.db.execute(
+            select(Flow)
+            .where(Flow.name == name)
+            .where(Flow.organization_id == organization_id)
+            .where(Flow.deleted_at.is_(None))
+            .order_by(Flow.created_at)
+            .limit(1)
+        )
+        return result.scalar_one_or_none()
+
     async def get_with_nodes(
         self, flow_id: UUID, organization_id: UUID
     ) -> Optional[Flow]:
@@ -228,13 +263,16 @@ class NodeRepository(BaseRepository[Node]):
     def __init__(self, db: AsyncSession):
         super().__init__(Node, db)
 
-    async def get_by_flow(self, flow_id: UUID, organization_id: UUID) -> List[Node]:
+    async def get_by_flow(
+        self, flow_id: UUID, organization_id: UUID, flow_version_id: Optional[UUID] = None
+    ) -> List[Node]:
         """
         Get all nodes for a flow
 
         Args:
             flow_id: Flow UUID
             organization_id: Organization UUID
+            flow_version_id: Published version (None = draft nodes)
 
         Returns:
             List of nodes
@@ -243,12 +281,13 @@ class NodeRepository(BaseRepository[Node]):
             select(Node)
             .where(Node.flow_id == flow_id)
             .where(Node.organization_id == organization_id)
+            .where(Node.flow_version_id == flow_version_id)
             .order_by(Node.order, Node.created_at)
         )
         return list(result.scalars().all())
 
     async def get_by_node_id(
-        self, node_id: str, flow_id: UUID, organization_id: UUID
+        self, node_id: str, flow_id: UUID, organization_id: UUID, flow_version_id: Optional[UUID] = None
     ) -> Optional[Node]:
         """
         Get node by node_id (React Flow ID)
@@ -257,6 +296,7 @@ class NodeRepository(BaseRepository[Node]):
             node_id: React Flow node ID
             flow_id: Flow UUID
             organization_id: Organization UUID
+            flow_version_id: Published version (None = draft node)
 
         Returns:
             Node or None
@@ -266,11 +306,12 @@ class NodeRepository(BaseRepository[Node]):
             .where(Node.node_id == node_id)
             .where(Node.flow_id == flow_id)
             .where(Node.organization_id == organization_id)
+            .where(Node.flow_version_id == flow_version_id)
         )
         return result.scalar_one_or_none()
 
     async def get_start_node(
-        self, flow_id: UUID, organization_id: UUID
+        self, flow_id: UUID, organization_id: UUID, flow_version_id: Optional[UUID] = None
     ) -> Optional[Node]:
         """
         Get start node for a flow
@@ -278,6 +319,7 @@ class NodeRepository(BaseRepository[Node]):
         Args:
             flow_id: Flow UUID
             organization_id: Organization UUID
+            flow_version_id: Published version (None = draft)
 
         Returns:
             Start node or None
@@ -286,13 +328,14 @@ class NodeRepository(BaseRepository[Node]):
             select(Node)
             .where(Node.flow_id == flow_id)
             .where(Node.organization_id == organization_id)
+            .where(Node.flow_version_id == flow_version_id)
             .where(Node.node_type == "start")
         )
         return result.scalar_one_or_none()
 
     async def delete_by_flow(self, flow_id: UUID, organization_id: UUID):
         """
-        Delete all nodes for a flow
+        Delete the draft nodes of a flow (published versions keep theirs)
 
         Args:
             flow_id: Flow UUID
@@ -302,6 +345,7 @@ class NodeRepository(BaseRepository[Node]):
             delete(Node)
             .where(Node.flow_id == flow_id)
             .where(Node.organization_id == organization_id)
+            .where(Node.flow_version_id.is_(None))
         )
         await self.db.commit()
 
@@ -320,3 +364,529 @@ class NodeRepository(BaseRepository[Node]):
         for node in nodes:
             await self.db.refresh(node)
         return nodes
+
+
+class FlowVersionRepository(BaseRepository[FlowVersion]):
+    """Repository for FlowVersion model"""
+
+    def __init__(self, db: AsyncSession):
+        super().__init__(FlowVersion, db)
+
+    async def get_by_flow(self, flow_id: UUID, organization_id: UUID) -> List[FlowVersion]:
+        """Versions of a flow, newest first"""
+        result = await self.db.execute(
+            select(FlowVersion)
+            .where(FlowVersion.flow_id == flow_id)
+            .where(FlowVersion.organization_id == organization_id)
+            .order_by(FlowVersion.version.desc())
+        )
+        return list(result.scalars().all())
+
+    async def get_by_number(
+        self, flow_id: UUID, organization_id: UUID, version: int
+    ) -> Optional[FlowVersion]:
+        """Version of a flow by its number"""
+        result = await self.db.execute(
+            select(FlowVersion)
+            .where(FlowVersion.flow_id == flow_id)
+            .where(FlowVersion.organization_id == organization_id)
+            .where(FlowVersion.version == version)
+        )
+        return result.scalar_one_or_none()
+
+    async def increment_stats(
+        self, version_id: UUID, sessions_started: int = 0, sessions_completed: int = 0
+    ) -> None:
+        """Atomically bump the execution counters of a version"""
+        await self.db.execute(
+            update(FlowVersion)
+            .where(FlowVersion.id == version_id)
+            .values(
+                sessions_started=FlowVersion.sessions_started + sessions_started,
+                sessions_completed=FlowVersion.sessions_completed + sessions_completed,
+            )
+        )
+        await self.db.commit()
+
+
+class FlowNodeVisitRepository(BaseRepository[FlowNodeVisit]):
+    """Repository for FlowNodeVisit model (per-node flow analytics)"""
+
+    def __init__(self, db: AsyncSession):
+        super().__init__(FlowNodeVisit, db)
+
+    async def enter(
+        self,
+        session_id: UUID,
+        organization_id: UUID,
+        conversation_id: UUID,
+        node: Node,
+        now: datetime,
+    ) -> FlowNodeVisit:
+        """Close the session's current visit and open one for the node"""
+        await self.close_open(session_id, now)
+        visit = FlowNodeVisit(
+            organization_id=organization_id,
+            flow_id=node.flow_id,
+            flow_version_id=node.flow_version_id,
+            conversation_id=conversation_id,
+            session_id=session_id,
+            node_id=node.node_id,
+            node_type=node.node_type,
+            entered_at=now,
+        )
+        self.db.add(visit)
+        await self.db.commit()
+        return visit
+
+    async def close_open(self, session_id: UUID, now: datetime) -> None:
+        """Set exit time and time spent of the session's open visit (not committed)"""
+        result = await self.db.execute(
+            select(FlowNodeVisit)
+            .where(FlowNodeVisit.session_id == session_id)
+            .where(FlowNodeVisit.exited_at.is_(None))
+        )
+        for visit in result.scalars().all():
+            entered_at = visit.entered_at
+            if entered_at.tzinfo is None:
+                entered_at = entered_at.replace(tzinfo=timezone.utc)
+            visit.exited_at = now
+            visit.duration_ms = max(int((now - entered_at).total_seconds() * 1000), 0)
+
+    async def set_variant(self, session_id: UUID, node_id: str, variant: str) -> None:
+        """Tag the session's open visit to a split node with the drawn variant"""
+        await self.db.execute(
+            update(FlowNodeVisit)
+            .where(FlowNodeVisit.session_id == session_id)
+            .where(FlowNodeVisit.node_id == node_id)
+            .where(FlowNodeVisit.exited_at.is_(None))
+            .values(variant=variant)
+        )
+        await self.db.commit()
+
+    def _sessions(self, flow_id: UUID, organization_id: UUID, start_date: datetime, end_date: datetime):
+        """Sessions of the flow started in the period, with their last entry and completion"""
+        return (
+            select(
+                FlowNodeVisit.session_id,
+                func.max(FlowNodeVisit.entered_at).label("last_entered_at"),
+                func.max(case((FlowNodeVisit.node_type == "end", 1), else_=0)).label("completed"),
+            )
+            .where(FlowNodeVisit.flow_id == flow_id)
+            .where(FlowNodeVisit.organization_id == organization_id)
+            .group_by(FlowNodeVisit.session_id)
+            .having(func.min(FlowNodeVisit.entered_at) >= start_date)
+            .having(func.min(FlowNodeVisit.entered_at) <= end_date)
+            .subquery()
+        )
+
+    async def get_session_summaries(
+        self, flow_id: UUID, organization_id: UUID, start_date: datetime, end_date: datetime
+    ) -> List[Any]:
+        """(session_id, last_entered_at, completed) of the sessions started in the period"""
+        sessions = self._sessions(flow_id, organization_id, start_date, end_date)
+        result = await self.db.execute(select(sessions))
+        return list(result.all())
+
+    async def get_node_stats(
+        self, flow_id: UUID, organization_id: UUID, start_date: datetime, end_date: datetime
+    ) -> List[Any]:
+        """(node_id, node_type, visits, sessions, avg_duration_ms) over the period's sessions"""
+        sessions = self._sessions(flow_id, organization_id, start_date, end_date)
+        result = await self.db.execute(
+            select(
+                FlowNodeVisit.node_id,
+                FlowNodeVisit.node_type,
+                func.count(FlowNodeVisit.id).label("visits"),
+                func.count(func.distinct(FlowNodeVisit.session_id)).label("sessions"),
+                func.avg(FlowNodeVisit.duration_ms).label("avg_duration_ms"),
+            )
+            .where(FlowNodeVisit.flow_id == flow_id)
+            .where(FlowNodeVisit.session_id.in_(select(sessions.c.session_id)))
+            .group_by(FlowNodeVisit.node_id, FlowNodeVisit.node_type)
+        )
+        return list(result.all())
+
+    async def get_split_assignments(
+        self, flow_id: UUID, organization_id: UUID, start_date: datetime, end_date: datetime
+    ) -> List[Any]:
+        """(node_id, session_id, variant) of the split nodes the period's sessions went through"""
+        sessions = self._sessions(flow_id, organization_id, start_date, end_date)
+        result = await self.db.execute(
+            select(
+                FlowNodeVisit.node_id,
+                FlowNodeVisit.session_id,
+                func.min(FlowNodeVisit.variant).label("variant"),
+            )
+            .where(FlowNodeVisit.flow_id == flow_id)
+            .where(FlowNodeVisit.variant.is_not(None))
+            .where(FlowNodeVisit.session_id.in_(select(sessions.c.session_id)))
+            .group_by(FlowNodeVisit.node_id, FlowNodeVisit.session_id)
+        )
+        return list(result.all())
+
+    async def get_sessions_reaching(
+        self, flow_id: UUID, node_id: str, organization_id: UUID, start_date: datetime, end_date: datetime
+    ) -> Set[Any]:
+        """IDs of the period's sessions that entered a node"""
+        sessions = self._sessions(flow_id, organization_id, start_date, end_date)
+        result = await self.db.execute(
+            select(FlowNodeVisit.session_id)
+            .where(FlowNodeVisit.flow_id == flow_id)
+            .where(FlowNodeVisit.node_id == node_id)
+            .where(FlowNodeVisit.session_id.in_(select(sessions.c.session_id)))
+            .distinct()
+        )
+        return set(result.scalars().all())
+
+    async def get_last_nodes(
+        self, flow_id: UUID, organization_id: UUID, start_date: datetime, end_date: datetime
+    ) -> Dict[Any, str]:
+        """Node of the flow each of the period's sessions was last in"""
+        sessions = self._sessions(flow_id, organization_id, start_date, end_date)
+        ranked = (
+            select(
+                FlowNodeVisit.session_id,
+                FlowNodeVisit.node_id,
+                func.row_number().over(
+                    partition_by=FlowNodeVisit.session_id,
+                    order_by=FlowNodeVisit.entered_at.desc(),
+                ).label("position"),
+            )
+            .where(FlowNodeVisit.flow_id == flow_id)
+            .where(FlowNodeVisit.session_id.in_(select(sessions.c.session_id)))
+            .subquery()
+        )
+        result = await self.db.execute(
+            select(ranked.c.session_id, ranked.c.node_id).where(ranked.c.position == 1)
+        )
+        return {row.session_id: row.node_id for row in result.all()}
+
+
+class FlowTraceRepository:
+    """
+    Repository for FlowTraceEvent model (flow session traces)
+
+    Append-only: events are added and read, never updated.
+    """
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def append(
+        self,
+        session_id: UUID,
+        organization_id: UUID,
+        conversation_id: UUID,
+        flow_id: Optional[UUID],
+        events: List[Dict[str, Any]],
+        now: datetime,
+    ) -> None:
+        """
+        Add events to the session's trace, in order
+
+        Args:
+            events: {"event_type", "node_id", "node_type", "data"} per event
+        """
+        for offset, event in enumerate(events):
+            self.db.add(FlowTraceEvent(
+                organization_id=organization_id,
+                flow_id=flow_id,
+                conversation_id=conversation_id,
+                session_id=session_id,
+                event_type=event["event_type"],
+                node_id=event.get("node_id"),
+                node_type=event.get("node_type"),
+                data=event.get("data") or {},
+                # Keeps the order of events added together
+                occurred_at=now + timedelta(microseconds=offset),
+            ))
+        await self.db.commit()
+
+    async def list_session(self, session_id: UUID, organization_id: UUID) -> List[FlowTraceEvent]:
+        """Events of a session, oldest first"""
+        result = await self.db.execute(
+            select(FlowTraceEvent)
+            .where(FlowTraceEvent.session_id == session_id)
+            .where(FlowTraceEvent.organization_id == organization_id)
+            .order_by(FlowTraceEvent.occurred_at, FlowTraceEvent.id)
+        )
+        return list(result.scalars().all())
+
+
+class SurveyResponseRepository(BaseRepository[SurveyResponse]):
+    """Repository for SurveyResponse model (NPS / CSAT answers)"""
+
+    def __init__(self, db: AsyncSession):
+        super().__init__(SurveyResponse, db)
+
+    async def get_score_counts(
+        self,
+        organization_id: UUID,
+        start_date: datetime,
+        end_date: datetime,
+        group_by: str = "flow",
+        flow_id: Optional[UUID] = None,
+    ) -> List[Any]:
+        """
+        (key, survey_type, score, responses) of the period's answers
+
+        Args:
+            group_by: "flow" (key = flow_id) or "agent" (key = agent_id)
+            flow_id: Only answers given in this flow
+        """
+        key = SurveyResponse.agent_id if group_by == "agent" else SurveyResponse.flow_id
+        query = (
+            select(
+                key.label("key"),
+                SurveyResponse.survey_type,
+                SurveyResponse.score,
+                func.count(SurveyResponse.id).label("responses"),
+            )
+            .where(SurveyResponse.organization_id == organization_id)
+            .where(SurveyResponse.created_at >= start_date)
+            .where(SurveyResponse.created_at <= end_date)
+            .group_by(key, SurveyResponse.survey_type, SurveyResponse.score)
+        )
+        if flow_id:
+            query = query.where(SurveyResponse.flow_id == flow_id)
+        result = await self.db.execute(query)
+        return list(result.all())
+
+
+class FlowConsoleSessionRepository(BaseRepository[FlowConsoleSession]):
+    """Repository for FlowConsoleSession model"""
+
+    def __init__(self, db: AsyncSession):
+        super().__init__(FlowConsoleSession, db)
+
+    async def get_active(self, session_id: str, now: datetime) -> Optional[FlowConsoleSession]:
+        """
+        Get a session that has not expired
+
+        Args:
+            session_id: Public session id
+            now: Current time
+
+        Returns:
+            Session or None
+        """
+        result = await self.db.execute(
+            select(FlowConsoleSession)
+            .where(FlowConsoleSession.session_id == session_id)
+            .where(FlowConsoleSession.expires_at > now)
+        )
+        return result.scalar_one_or_none()
+
+    async def save(self, state: Dict[str, Any], expires_at: datetime) -> FlowConsoleSession:
+        """
+        Create or update a session from its state (does not commit)
+
+        Args:
+            state: Session as returned by the console
+            expires_at: New idle expiry
+
+        Returns:
+            Saved session
+        """
+        session = await self.get_by_field("session_id", state["session_id"])
+        if session is None:
+            session = FlowConsoleSession(
+                session_id=state["session_id"],
+                organization_id=UUID(state["organization_id"]),
+                flow_id=UUID(state["flow_id"]),
+                user_id=UUID(state["user_id"]) if state.get("user_id") else None,
+            )
+            self.db.add(session)
+
+        session.state = state
+        session.finished = bool(state.get("finished"))
+        session.expires_at = expires_at
+        await self.db.flush()
+        return session
+
+    async def delete_by_session_id(self, session_id: str) -> None:
+        """Delete a session (does not commit)"""
+        await self.db.execute(
+            delete(FlowConsoleSession).where(FlowConsoleSession.session_id == session_id)
+        )
+
+    async def delete_expired(self, now: datetime) -> int:
+        """
+        Delete sessions idle past their expiry
+
+        Returns:
+            Number of deleted sessions
+        """
+        result = await self.db.execute(
+            delete(FlowConsoleSession).where(FlowConsoleSession.expires_at <= now)
+        )
+        await self.db.commit()
+        return result.rowcount or 0
+
+
+class FlowWaitRepository(BaseRepository[FlowWait]):
+    """Repository for FlowWait model"""
+
+    def __init__(self, db: AsyncSession):
+        super().__init__(FlowWait, db)
+
+    async def claim_due(self, now: datetime, limit: int = 100) -> List[FlowWait]:
+        """
+        Mark due pending waits as resumed (does not commit)
+
+        Rows are locked with SKIP LOCKED so concurrent workers never claim the
+        same wait.
+
+        Returns:
+            Claimed waits, oldest first
+        """
+        result = await self.db.execute(
+            select(FlowWait)
+            .where(FlowWait.status == "pending")
+            .where(FlowWait.resume_at <= now)
+            .order_by(FlowWait.resume_at)
+            .limit(limit)
+            .with_for_update(skip_locked=True)
+        )
+        waits = list(result.scalars().all())
+        for wait in waits:
+            wait.status = "resumed"
+            wait.resumed_at = now
+        await self.db.flush()
+        return waits
+
+    async def cancel_pending(self, conversation_id: UUID) -> int:
+        """
+        Cancel the pending waits of a conversation (does not commit)
+
+        Returns:
+            Number of cancelled waits
+        """
+        result = await self.db.execute(
+            update(FlowWait)
+            .where(FlowWait.conversation_id == conversation_id)
+            .where(FlowWait.status == "pending")
+            .values(status="cancelled")
+        )
+        return result.rowcount or 0
+
+    async def move_pending(self, conversation_id: UUID, node_id: UUID) -> int:
+        """
+        Point the pending waits of a conversation at another node (does not commit)
+
+        Returns:
+            Number of moved waits
+        """
+        result = await self.db.execute(
+            update(FlowWait)
+            .where(FlowWait.conversation_id == conversation_id)
+            .where(FlowWait.status == "pending")
+            .values(node_id=node_id)
+        )
+        return result.rowcount or 0
+
+
+class FlowTriggerRepository(BaseRepository[FlowTrigger]):
+    """Repository for FlowTrigger model"""
+
+    def __init__(self, db: AsyncSession):
+        super().__init__(FlowTrigger, db)
+
+    async def list_by_organization(
+        self, organization_id: UUID, flow_id: Optional[UUID] = None
+    ) -> List[FlowTrigger]:
+        """Triggers of the organization (optionally of one flow), highest priority first"""
+        query = (
+            select(FlowTrigger)
+            .where(FlowTrigger.organization_id == organization_id)
+            .order_by(FlowTrigger.priority.desc(), FlowTrigger.created_at)
+        )
+        if flow_id:
+            query = query.where(FlowTrigger.flow_id == flow_id)
+        result = await self.db.execute(query)
+        return list(result.scalars().all())
+
+    async def list_runnable(
+        self,
+        organization_id: UUID,
+        whatsapp_number_id: Optional[UUID] = None,
+        chatbot_id: Optional[UUID] = None,
+    ) -> List[FlowTrigger]:
+        """
+        Active triggers whose flow can run on a conversation
+
+        The flow must be active and not deleted, and its chatbot must be the
+        conversation's chatbot or one that is not bound to another number.
+        """
+        query = (
+            select(FlowTrigger)
+            .join(Flow, Flow.id == FlowTrigger.flow_id)
+            .join(Chatbot, Chatbot.id == Flow.chatbot_id)
+            .where(FlowTrigger.organization_id == organization_id)
+            .where(FlowTrigger.is_active == True)
+            .where(Flow.is_active == True)
+            .where(Flow.deleted_at.is_(None))
+            .where(Chatbot.deleted_at.is_(None))
+        )
+        scopes = [Chatbot.whatsapp_number_id.is_(None)]
+        if whatsapp_number_id:
+            scopes.append(Chatbot.whatsapp_number_id == whatsapp_number_id)
+        if chatbot_id:
+            scopes.append(Chatbot.id == chatbot_id)
+        result = await self.db.execute(query.where(or_(*scopes)))
+        return list(result.scalars().all())
+
+
+class FlowEventTriggerRepository(BaseRepository[FlowEventTrigger]):
+    """Repository for FlowEventTrigger model"""
+
+    def __init__(self, db: AsyncSession):
+        super().__init__(FlowEventTrigger, db)
+
+    async def list_by_organization(
+        self, organization_id: UUID, event_type: Optional[str] = None
+    ) -> List[FlowEventTrigger]:
+        """Event triggers of the organization (optionally of one event), oldest first"""
+        query = (
+            select(FlowEventTrigger)
+            .where(FlowEventTrigger.organization_id == organization_id)
+            .order_by(FlowEventTrigger.created_at)
+        )
+        if event_type:
+            query = query.where(FlowEventTrigger.event_type == event_type)
+        result = await self.db.execute(query)
+        return list(result.scalars().all())
+
+    async def list_runnable(self, organization_id: UUID, event_type: str) -> List[FlowEventTrigger]:
+        """Active triggers of an event whose flow is active and not deleted"""
+        result = await self.db.execute(
+            select(FlowEventTrigger)
+            .join(Flow, Flow.id == FlowEventTrigger.flow_id)
+            .where(FlowEventTrigger.organization_id == organization_id)
+            .where(FlowEventTrigger.event_type == event_type)
+            .where(FlowEventTrigger.is_active == True)
+            .where(Flow.is_active == True)
+            .where(Flow.deleted_at.is_(None))
+            .order_by(FlowEventTrigger.created_at)
+        )
+        return list(result.scalars().all())
+
+    async def list_organizations_with(self, event_type: str) -> List[UUID]:
+        """Organizations with an active trigger for the event"""
+        result = await self.db.execute(
+            select(FlowEventTrigger.organization_id)
+            .where(FlowEventTrigger.event_type == event_type)
+            .where(FlowEventTrigger.is_active == True)
+            .distinct()
+        )
+        return list(result.scalars().all())
+
+    async def record_run(self, trigger_id: UUID, outcome: str) -> None:
+        """Count a run as started, skipped or failed (does not commit)"""
+        column = getattr(FlowEventTrigger, f"total_{outcome}")
+        await self.db.execute(
+            update(FlowEventTrigger)
+            .where(FlowEventTrigger.id == trigger_id)
+            .values({f"total_{outcome}": column + 1, "last_fired_at": datetime.now(timezone.utc)})
+        )
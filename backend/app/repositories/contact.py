@@ -8,6 +8,7 @@ from uuid import UUID
 from sqlalchemy import select, func, and_, or_
 from sqlalchemy.ext.asyncio import AsyncSession
 from sqlalchemy.orm import selectinload
+from sqlalchemy.sql.elements import ColumnElement
 
 from app.models.contact import Contact, Tag, contact_tags
 from app.repositories.base import BaseRepository
@@ -257,6 +258,35 @@ class ContactRepository(BaseRepository[Contact]):
             "recent_contacts": recent_contacts or 0,
         }
 
+    async def count_audience(self, conditions: List[ColumnElement]) -> int:
+        """Number of contacts matching audience clauses (see app.repositories.contact_segment)"""
+        result = await self.db.execute(
+            select(func.count()).select_from(Contact).where(*conditions)
+        )
+        return result.scalar_one()
+
+    async def get_audience_page(
+        self,
+        conditions: List[ColumnElement],
+        after_id: Optional[UUID] = None,
+        skip: int = 0,
+        limit: Optional[int] = 100,
+    ) -> List[Contact]:
+        """
+        Contacts matching audience clauses, ordered by id
+
+        Pages either by offset (skip) or by keyset (after_id: the last id of
+        the previous page), which stays fast deep into large audiences.
+        """
+        stmt = select(Contact).where(*conditions)
+        if after_id:
+            stmt = stmt.where(Contact.id > after_id)
+        stmt = stmt.order_by(Contact.id).offset(skip)
+        if limit:
+            stmt = stmt.limit(limit)
+        result = await self.db.execute(stmt)
+        return list(result.scalars().all())
+
 
 class TagRepository(BaseRepository[Tag]):
     """Repository for Tag model"""
@@ -0,0 +1,207 @@
+"""
+Segmentation compiler - contact audiences as SQL
+
+Turns an audience definition (see Campaign.audience_type) into WHERE
+clauses on contacts. Segments (app.utils.contact_segment) compile to
+parameterized SQL: every value is a bound parameter, custom fields are read
+with JSONB operators and tags through contact_tags subqueries, so the
+clauses never join and counts and pages see each contact once.
+
+    all_contacts    every contact of the organization
+    tags            contacts with any of target_tag_ids
+    custom_list     target_contact_ids
+    segment         contacts matching segment_filters
+"""
+
+from datetime import datetime, timedelta, timezone
+from typing import Any, List, Optional
+from uuid import UUID
+
+from sqlalchemy import Numeric, and_, case, false, func, not_, or_, select, true
+from sqlalchemy.sql.elements import ColumnElement
+
+from app.models.contact import Contact, contact_tags
+from app.utils.contact_segment import (
+    MATCH_ALL,
+    TAGS_FIELD,
+    TEXT_FIELDS,
+    SegmentCondition,
+    SegmentGroup,
+    parse_segment,
+)
+
+# Text of a custom field that can be compared as a number
+NUMERIC_PATTERN = r"^\s*-?\d+(\.\d+)?\s*$"
+TRUE_VALUES = ("true", "1", "yes", "sim")
+
+# Segment fields read from another column
+FIELD_COLUMNS = {"last_interaction": "last_message_at"}
+
+
+def audience_conditions(
+    organization_id: UUID,
+    audience_type: str,
+    target_tag_ids: Optional[List[UUID]] = None,
+    target_contact_ids: Optional[List[UUID]] = None,
+    segment_filters: Optional[dict] = None,
+    respect_opt_out: bool = False,
+    now: Optional[datetime] = None,
+) -> List[ColumnElement]:
+    """
+    WHERE clauses of the contacts in an audience
+
+    Raises:
+        SegmentError: If segment_filters is invalid
+    """
+    conditions = [
+        Contact.organization_id == organization_id,
+        Contact.deleted_at.is_(None),
+        Contact.whatsapp_id.isnot(None),
+    ]
+    if respect_opt_out:
+        conditions += [Contact.opt_in.is_(True), Contact.is_blocked.is_(False)]
+
+    if audience_type == "all_contacts":
+        pass
+    elif audience_type == "tags":
+        conditions.append(_tagged(target_tag_ids or []))
+    elif audience_type == "custom_list":
+        conditions.append(Contact.id.in_(target_contact_ids) if target_contact_ids else false())
+    elif audience_type == "segment":
+        conditions.append(compile_segment(parse_segment(segment_filters), now))
+    else:
+        conditions.append(false())
+    return conditions
+
+
+def compile_segment(group: SegmentGroup, now: Optional[datetime] = None) -> ColumnElement:
+    """WHERE clause of a parsed segment"""
+    now = now or datetime.now(timezone.utc)
+    clauses = [
+        compile_segment(item, now) if isinstance(item, SegmentGroup) else compile_condition(item, now)
+        for item in group.conditions
+    ]
+    if group.match == MATCH_ALL:
+        return and_(true(), *clauses)
+    return or_(false(), *clauses)
+
+
+def compile_condition(condition: SegmentCondition, now: datetime) -> ColumnElement:
+    """WHERE clause of one condition"""
+    if condition.field == TAGS_FIELD:
+        return _tag_condition(condition)
+    if condition.attribute is not None:
+        return _attribute_condition(condition)
+
+    column = getattr(Contact, FIELD_COLUMNS.get(condition.field, condition.field))
+    operator, value = condition.operator, condition.value
+
+    if operator == "is_true":
+        return column.is_(True)
+    if operator == "is_false":
+        return column.is_(False)
+    if operator == "within_days":
+        return column >= now - timedelta(days=value)
+    if operator == "older_than_days":
+        return column < now - timedelta(days=value)
+    if operator == "before":
+        return column < value
+    if operator == "after":
+        return column > value
+    if operator == "between":
+        return column.between(*value)
+    if operator in ("gt", "gte", "lt", "lte"):
+        return _compare(column, operator, value)
+    text = condition.field in TEXT_FIELDS
+    if operator == "is_empty":
+        return or_(column.is_(None), column == "") if text else column.is_(None)
+    if operator == "is_not_empty":
+        return and_(column.isnot(None), column != "") if text else column.isnot(None)
+    if text:
+        return _text_condition(column, operator, value)
+    if operator == "eq":
+        return column == value
+    return or_(column.is_(None), column != value)
+
+
+def _text_condition(column: Any, operator: str, value: Any) -> ColumnElement:
+    """Case-insensitive text comparison (missing values never match positively)"""
+    lowered = func.lower(column)
+    if operator == "eq":
+        return lowered == value.lower()
+    if operator == "neq":
+        return or_(column.is_(None), lowered != value.lower())
+    if operator == "in":
+        return lowered.in_([item.lower() for item in value])
+    if operator == "not_in":
+        return or_(column.is_(None), lowered.notin_([item.lower() for item in value]))
+    if operator == "contains":
+        return column.ilike(f"%{_escape(value)}%", escape="\\")
+    if operator == "not_contains":
+        return or_(column.is_(None), not_(column.ilike(f"%{_escape(value)}%", escape="\\")))
+    if operator == "starts_with":
+        return column.ilike(f"{_escape(value)}%", escape="\\")
+    raise ValueError(f"Unsupported text operator: {operator}")
+
+
+def _attribute_condition(condition: SegmentCondition) -> ColumnElement:
+    """Custom field (contact.attributes) condition"""
+    key, operator, value = condition.attribute, condition.operator, condition.value
+    text = Contact.attributes[key].astext
+
+    if operator == "exists":
+        return Contact.attributes.has_key(key)
+    if operator == "not_exists":
+        return not_(Contact.attributes.has_key(key))
+    if operator == "is_true":
+        return func.lower(text).in_(TRUE_VALUES)
+    if operator == "is_false":
+        return or_(text.is_(None), func.lower(text).notin_(TRUE_VALUES))
+    if operator == "is_empty":
+        return or_(text.is_(None), text == "")
+    if operator == "is_not_empty":
+        return and_(text.isnot(None), text != "")
+    if operator in ("gt", "gte", "lt", "lte", "between"):
+        # CASE keeps the cast away from values that are not numbers
+        number = case((text.op("~")(NUMERIC_PATTERN), text.cast(Numeric)), else_=None)
+        if operator == "between":
+            return number.between(*value)
+        return _compare(number, operator, value)
+    return _text_condition(text, operator, value)
+
+
+def _tag_condition(condition: SegmentCondition) -> ColumnElement:
+    tag_ids = condition.value
+    if condition.operator == "has_any":
+        return _tagged(tag_ids)
+    if condition.operator == "has_none":
+        return not_(_tagged(tag_ids))
+    # has_all: every tag of the list
+    return Contact.id.in_(
+        select(contact_tags.c.contact_id)
+        .where(contact_tags.c.tag_id.in_(tag_ids))
+        .group_by(contact_tags.c.contact_id)
+        .having(func.count(func.distinct(contact_tags.c.tag_id)) == len(set(tag_ids)))
+    )
+
+
+def _tagged(tag_ids: List[UUID]) -> ColumnElement:
+    """Contacts with any of the tags"""
+    if not tag_ids:
+        return false()
+    return Contact.id.in_(select(contact_tags.c.contact_id).where(contact_tags.c.tag_id.in_(tag_ids)))
+
+
+def _compare(column: Any, operator: str, value: Any) -> ColumnElement:
+    if operator == "gt":
+        return column > value
+    if operator == "gte":
+        return column >= value
+    if operator == "lt":
+        return column < value
+    return column <= value
+
+
+def _escape(value: str) -> str:
+    """LIKE pattern matching the text literally"""
+    return value.replace("\\", "\\\\").replace("%", "\\%").replace("_", "\\_")
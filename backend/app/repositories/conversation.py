@@ -2,19 +2,27 @@
 Conversation and Message Repositories
 """
 
+import logging
 from datetime import datetime
-from typing import List, Optional, Tuple
+from typing import Any, Dict, List, Optional, Tuple, Union
 from uuid import UUID
 
-from sqlalchemy import select, func, desc, and_, text
+from sqlalchemy import select, update, func, desc, and_, or_, text, cast, String
 from sqlalchemy.ext.asyncio import AsyncSession
 from sqlalchemy.orm import selectinload, joinedload
+from sqlalchemy.orm.attributes import set_committed_value
 
+from app.core.encryption.base import DecryptionError
+from app.core.encryption.message_cipher import message_cipher
+from app.models.chatbot import Node
 from app.models.conversation import Conversation, Message
 from app.models.contact import Contact
+from app.models.organization import Organization
 from app.models.queue import Queue
 from app.repositories.base import BaseRepository
 
+logger = logging.getLogger(__name__)
+
 
 class ConversationRepository(BaseRepository[Conversation]):
     """Repository for Conversation model"""
@@ -138,6 +146,130 @@ class ConversationRepository(BaseRepository[Conversation]):
             conversation.unread_count += 1
             await self.db.commit()
 
+    async def claim_flow_timeouts(self, now: datetime, limit: int = 100) -> List[Tuple[Conversation, str]]:
+        """
+        Claim bot conversations whose flow session timed out (does not commit)
+
+        Both deadlines are cleared so the session is handled once; rows are
+        locked with SKIP LOCKED so concurrent workers never claim the same one.
+
+        Returns:
+            (conversation, reason) pairs, reason "expired" or "timeout"
+        """
+        result = await self.db.execute(
+            select(Conversation)
+            .where(
+                Conversation.is_bot_active.is_(True),
+                Conversation.active_flow_id.is_not(None),
+                Conversation.deleted_at.is_(None),
+                or_(Conversation.flow_expires_at <= now, Conversation.flow_timeout_at <= now),
+            )
+            .order_by(func.coalesce(Conversation.flow_expires_at, Conversation.flow_timeout_at))
+            .limit(limit)
+            .with_for_update(skip_locked=True)
+        )
+        claimed = []
+        for conversation in result.scalars().all():
+            expired = conversation.flow_expires_at is not None and conversation.flow_expires_at <= now
+            claimed.append((conversation, "expired" if expired else "timeout"))
+            conversation.flow_expires_at = None
+            conversation.flow_timeout_at = None
+        await self.db.flush()
+        return claimed
+
+    async def claim_interrupted_nodes(
+        self, stale_before: datetime, now: datetime, limit: int = 50
+    ) -> List[Conversation]:
+        """
+        Claim bot conversations whose node checkpoint is older than stale_before (does not commit)
+
+        The checkpoint time is moved to now so the conversation is not claimed
+        again while it resumes; rows are locked with SKIP LOCKED.
+        """
+        result = await self.db.execute(
+            select(Conversation)
+            .where(
+                Conversation.is_bot_active.is_(True),
+                Conversation.active_flow_id.is_not(None),
+                Conversation.deleted_at.is_(None),
+                Conversation.node_checkpoint_at <= stale_before,
+            )
+            .order_by(Conversation.node_checkpoint_at)
+            .limit(limit)
+            .with_for_update(skip_locked=True)
+        )
+        claimed = list(result.scalars().all())
+        for conversation in claimed:
+            conversation.node_checkpoint_at = now
+        await self.db.flush()
+        return claimed
+
+    async def stamp_session_fence(self, conversation_id: UUID, token: int) -> bool:
+        """
+        Record the fencing token of a new flow session lock holder (does not commit)
+
+        Returns:
+            False when a newer holder already stamped the conversation
+        """
+        result = await self.db.execute(
+            update(Conversation)
+            .where(
+                Conversation.id == conversation_id,
+                or_(
+                    Conversation.flow_session_fence.is_(None),
+                    Conversation.flow_session_fence < token,
+                ),
+            )
+            .values(flow_session_fence=token)
+        )
+        return result.rowcount > 0
+
+    async def update_fenced(
+        self, conversation_id: UUID, token: int, data: Dict[str, Any]
+    ) -> bool:
+        """
+        Update session columns only while token is the conversation's fencing token
+
+        Returns:
+            False (nothing written) when the lock was taken over by another holder
+        """
+        result = await self.db.execute(
+            update(Conversation)
+            .where(
+                Conversation.id == conversation_id,
+                Conversation.flow_session_fence == token,
+            )
+            .values(**data)
+        )
+        await self.db.commit()
+        return result.rowcount > 0
+
+    async def list_flow_sessions(
+        self, flow_id: UUID, organization_id: UUID, exclude_version_id: UUID
+    ) -> List[Tuple[Conversation, Node]]:
+        """
+        Conversations running a flow on a version other than exclude_version_id
+
+        Includes sessions on draft nodes; rows are locked until the caller
+        commits so incoming messages do not move them meanwhile.
+
+        Returns:
+            (conversation, current node) pairs
+        """
+        result = await self.db.execute(
+            select(Conversation, Node)
+            .join(Node, Node.id == Conversation.current_node_id)
+            .where(
+                Conversation.organization_id == organization_id,
+                Conversation.active_flow_id == flow_id,
+                Conversation.is_bot_active.is_(True),
+                Conversation.deleted_at.is_(None),
+                Node.flow_version_id.is_distinct_from(exclude_version_id),
+            )
+            .with_for_update(of=Conversation)
+        )
+        return [(conversation, node) for conversation, node in result.all()]
+
     async def list_sla_alerts(
         self,
         organization_id: UUID,
@@ -217,10 +349,198 @@ class ConversationRepository(BaseRepository[Conversation]):
 
 
 class MessageRepository(BaseRepository[Message]):
-    """Repository for Message model"""
+    """
+    Repository for Message model
+
+    For organizations with message encryption enabled, content (and the
+    previous contents kept in extra_data["edit_history"]) is encrypted with
+    the tenant data key before it is written (create and update) and
+    decrypted transparently on reads. Decrypted values are set as committed
+    state, so they are never flushed back to the database in plain text.
+    Messages with content are written and read through this repository.
+    """
 
     def __init__(self, db: AsyncSession):
         super().__init__(Message, db)
+        self._key_cache: Dict[UUID, Tuple[bool, Optional[str]]] = {}
+
+    async def _get_encryption(self, organization_id: Optional[UUID]) -> Tuple[bool, Optional[str]]:
+        """Get (encryption enabled, wrapped data key) for an organization"""
+        if organization_id is None:
+            return False, None
+
+        if organization_id not in self._key_cache:
+            result = await self.db.execute(
+                select(
+                    Organization.message_encryption_enabled,
+                    Organization.message_data_key,
+                ).where(Organization.id == organization_id)
+            )
+            row = result.first()
+            self._key_cache[organization_id] = (
+                (bool(row.message_encryption_enabled), row.message_data_key)
+                if row
+                else (False, None)
+            )
+
+        return self._key_cache[organization_id]
+
+    async def encrypt_for_storage(
+        self, organization_id: UUID, content: Dict[str, Any]
+    ) -> Dict[str, Any]:
+        """Encrypt content if the organization has message encryption enabled"""
+        enabled, data_key = await self._get_encryption(organization_id)
+        if enabled and data_key and not message_cipher.is_encrypted(content):
+            return message_cipher.encrypt_content(content or {}, data_key)
+        return content
+
+    async def _encrypt_values(
+        self, organization_id: Optional[UUID], obj_in: Dict[str, Any]
+    ) -> Dict[str, Any]:
+        """Encrypt the content and edit history of values about to be written"""
+        values = dict(obj_in)
+        if "content" in values:
+            values["content"] = await self.encrypt_for_storage(organization_id, values["content"])
+
+        history = (values.get("extra_data") or {}).get("edit_history")
+        if history:
+            values["extra_data"] = {
+                **values["extra_data"],
+                "edit_history": [
+                    {**entry, "content": await self.encrypt_for_storage(organization_id, entry.get("content"))}
+                    for entry in history
+                ],
+            }
+        return values
+
+    async def decrypt_messages(self, messages: List[Message]) -> List[Message]:
+        """Decrypt content and edit history of loaded messages in place (without marking them dirty)"""
+        for message in messages:
+            if message is None:
+                continue
+
+            history = (message.extra_data or {}).get("edit_history") or []
+            encrypted_history = any(message_cipher.is_encrypted(entry.get("content")) for entry in history)
+            if not message_cipher.is_encrypted(message.content) and not encrypted_history:
+                continue
+
+            _, data_key = await self._get_encryption(message.organization_id)
+            if not data_key:
+                continue
+
+            try:
+                set_committed_value(
+                    message,
+                    "content",
+                    message_cipher.decrypt_content(message.content, data_key),
+                )
+                if encrypted_history:
+                    set_committed_value(message, "extra_data", {
+                        **message.extra_data,
+                        "edit_history": [
+                            {**entry, "content": message_cipher.decrypt_content(entry.get("content"), data_key)}
+                            for entry in history
+                        ],
+                    })
+            except DecryptionError as e:
+                logger.error(f"❌ Could not decrypt message {message.id}: {e}")
+
+        return messages
+
+    async def get(self, id: UUID) -> Optional[Message]:
+        """Get message by ID (decrypted)"""
+        message = await super().get(id)
+        if message:
+            await self.decrypt_messages([message])
+        return message
+
+    async def create(self, obj_in: Dict[str, Any]) -> Message:
+        """Create message, encrypting content for encrypted tenants"""
+        obj_in = await self._encrypt_values(obj_in.get("organization_id"), obj_in)
+
+        message = await super().create(obj_in)
+        await self.decrypt_messages([message])
+        return message
+
+    async def update(
+        self, id: UUID, obj_in: Union[Dict[str, Any], Message]
+    ) -> Optional[Message]:
+        """Update message, encrypting new content and edit history for encrypted tenants"""
+        if not isinstance(obj_in, dict):
+            obj_in = {
+                key: value
+                for key, value in obj_in.__dict__.items()
+                if not key.startswith("_")
+            }
+
+        if "content" in obj_in or "extra_data" in obj_in:
+            organization_id = await self.db.scalar(
+                select(Message.organization_id).where(Message.id == id)
+            )
+            obj_in = await self._encrypt_values(organization_id, obj_in)
+
+        return await super().update(id, obj_in)
+
+    async def get_by_whatsapp_id(
+        self, whatsapp_message_id: str, organization_id: UUID
+    ) -> Optional[Message]:
+        """Get message by WhatsApp message ID (decrypted)"""
+        result = await self.db.execute(
+            select(Message).where(
+                Message.whatsapp_message_id == whatsapp_message_id,
+                Message.organization_id == organization_id,
+            )
+        )
+        message = result.scalar_one_or_none()
+        if message:
+            await self.decrypt_messages([message])
+        return message
+
+    async def search_messages(
+        self,
+        organization_id: UUID,
+        query: str,
+        conversation_id: Optional[UUID] = None,
+        message_type: Optional[str] = None,
+        skip: int = 0,
+        limit: int = 50,
+    ) -> List[Message]:
+        """
+        Search messages
+
+        Encrypted tenants only get metadata search (media filename, WhatsApp
+        message ID, error message), since bodies cannot be matched in SQL.
+        """
+        stmt = select(Message).where(
+            Message.organization_id == organization_id,
+            Message.deleted_at.is_(None),
+        )
+
+        if conversation_id:
+            stmt = stmt.where(Message.conversation_id == conversation_id)
+
+        if message_type:
+            stmt = stmt.where(Message.message_type == message_type)
+
+        _, data_key = await self._get_encryption(organization_id)
+        pattern = f"%{query}%"
+        metadata_filter = or_(
+            Message.media_filename.ilike(pattern),
+            Message.whatsapp_message_id == query,
+            Message.error_message.ilike(pattern),
+        )
+
+        if data_key:
+            stmt = stmt.where(metadata_filter)
+        else:
+            stmt = stmt.where(
+                or_(cast(Message.content, String).ilike(pattern), metadata_filter)
+            )
+
+        stmt = stmt.order_by(desc(Message.created_at)).offset(skip).limit(limit)
+
+        result = await self.db.execute(stmt)
+        return await self.decrypt_messages(list(result.scalars().all()))
 
     async def get_conversation_messages(
         self,
@@ -241,7 +561,7 @@ class MessageRepository(BaseRepository[Message]):
             .offset(skip)
             .limit(limit)
         )
-        return list(result.scalars().all())
+        return await self.decrypt_messages(list(result.scalars().all()))
 
     async def get_last_message(
         self, conversation_id: UUID, organization_id: UUID
@@ -257,7 +577,10 @@ class MessageRepository(BaseRepository[Message]):
             .order_by(desc(Message.created_at))
             .limit(1)
         )
-        return result.scalar_one_or_none()
+        message = result.scalar_one_or_none()
+        if message:
+            await self.decrypt_messages([message])
+        return message
 
     async def mark_as_delivered(
         self, message_id: UUID, whatsapp_message_id: str
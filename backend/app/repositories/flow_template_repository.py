@@ -168,7 +168,9 @@ class FlowTemplateRepository:
                     requires_integrations=template_data.get("requires_integrations", []),
                     use_count=template_data.get("metadata", {}).get("use_count", 0),
                     rating=template_data.get("metadata", {}).get("rating", 0.0),
-                    language=template_data.get("metadata", {}).get("language", "pt-BR")
+                    language=template_data.get("metadata", {}).get("language", "pt-BR"),
+                    version=str(template_data.get("metadata", {}).get("version", "1.0")),
+                    parameters=template_data.get("parameters", [])
                 )
 
                 templates.append(template)
@@ -226,7 +228,10 @@ class FlowTemplateRepository:
                 use_count=template_data.get("metadata", {}).get("use_count", 0),
                 rating=template_data.get("metadata", {}).get("rating", 0.0),
                 language=template_data.get("metadata", {}).get("language", "pt-BR"),
-                flow_data=template_data.get("flow_data", {})
+                version=str(template_data.get("metadata", {}).get("version", "1.0")),
+                parameters=template_data.get("parameters", []),
+                flow_data=template_data.get("flow_data", {}),
+                changelog=template_data.get("metadata", {}).get("changelog", [])
             )
 
             return template
@@ -339,7 +344,9 @@ class FlowTemplateRepository:
                         requires_integrations=template_data.get("requires_integrations", []),
                         use_count=template_data.get("metadata", {}).get("use_count", 0),
                         rating=template_data.get("metadata", {}).get("rating", 0.0),
-                        language=template_data.get("metadata", {}).get("language", "pt-BR")
+                        language=template_data.get("metadata", {}).get("language", "pt-BR"),
+                        version=str(template_data.get("metadata", {}).get("version", "1.0")),
+                        parameters=template_data.get("parameters", [])
                     )
                     matches.append(template)
 
@@ -0,0 +1,106 @@
+"""
+Ad audience schemas (Meta Custom Audiences sync)
+"""
+
+from datetime import datetime
+from typing import List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field, field_validator
+
+from app.utils.contact_segment import validate_segment
+
+
+# ============================================
+# ORGANIZATION SETTINGS
+# ============================================
+
+class MetaAdsSettings(BaseModel):
+    """Meta Ads settings stored in organization.settings['meta_ads']"""
+
+    enabled: bool = Field(default=False, description="Sync ad audiences of the organization")
+    ad_account_id: Optional[str] = Field(
+        None, pattern=r"^(act_)?\d+$", description="Ad account ID (e.g. act_1234567890)"
+    )
+    access_token_secret_id: Optional[UUID] = Field(
+        None, description="Secret holding a token with ads_management permission on the ad account"
+    )
+
+
+# ============================================
+# AUDIENCES
+# ============================================
+
+class AdAudienceBase(BaseModel):
+    """Base schema for AdAudience"""
+
+    name: str = Field(..., min_length=1, max_length=255)
+    description: Optional[str] = None
+    audience_type: str = Field(
+        default="tags", pattern="^(all_contacts|tags|custom_list|segment)$",
+        description="Same audience definition as campaigns",
+    )
+    target_tag_ids: List[UUID] = Field(default_factory=list)
+    target_contact_ids: List[UUID] = Field(default_factory=list)
+    segment_filters: dict = Field(default_factory=dict)
+    is_active: bool = True
+
+
+class AdAudienceCreate(AdAudienceBase):
+    """Schema for creating an ad audience"""
+
+    @field_validator("segment_filters")
+    @classmethod
+    def check_segment_filters(cls, value: Optional[dict]) -> Optional[dict]:
+        errors = validate_segment(value)
+        if errors:
+            raise ValueError("; ".join(errors))
+        return value
+
+
+class AdAudienceUpdate(BaseModel):
+    """Schema for updating an ad audience"""
+
+    name: Optional[str] = Field(None, min_length=1, max_length=255)
+    description: Optional[str] = None
+    audience_type: Optional[str] = Field(None, pattern="^(all_contacts|tags|custom_list|segment)$")
+    target_tag_ids: Optional[List[UUID]] = None
+    target_contact_ids: Optional[List[UUID]] = None
+    segment_filters: Optional[dict] = None
+    is_active: Optional[bool] = None
+
+    @field_validator("segment_filters")
+    @classmethod
+    def check_segment_filters(cls, value: Optional[dict]) -> Optional[dict]:
+        errors = validate_segment(value)
+        if errors:
+            raise ValueError("; ".join(errors))
+        return value
+
+
+class AdAudienceResponse(AdAudienceBase):
+    """Ad audience with its sync state"""
+
+    id: UUID
+    organization_id: UUID
+    ad_account_id: Optional[str] = None
+    meta_audience_id: Optional[str] = None
+    last_synced_at: Optional[datetime] = None
+    last_sync_status: Optional[str] = None
+    last_error: Optional[str] = None
+    member_count: int = 0
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class AdAudienceSyncResult(BaseModel):
+    """Result of an audience sync"""
+
+    audience_id: UUID
+    meta_audience_id: Optional[str] = None
+    added: int = 0
+    removed: int = 0
+    member_count: int = 0
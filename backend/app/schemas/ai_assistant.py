@@ -3,6 +3,7 @@ AI Flow Assistant Schemas - Configuration and request/response models
 """
 
 from typing import Optional, List, Dict, Any
+from uuid import UUID
 from enum import Enum
 from pydantic import BaseModel, Field, field_validator
 
@@ -299,6 +300,18 @@ class TemplateCategory(BaseModel):
     template_count: int = Field(default=0, description="Number of templates in category")
 
 
+class FlowTemplateParameter(BaseModel):
+    """Value a tenant fills in when instantiating a template"""
+    name: str = Field(..., description="Used as [[name]] in the template's flow data")
+    label: str = Field(..., description="Display name")
+    type: str = Field(default="text", description="text, number, boolean, choice or list")
+    description: Optional[str] = None
+    required: bool = False
+    default: Optional[Any] = None
+    options: List[Any] = Field(default_factory=list, description="Allowed values of choice parameters")
+    max_length: Optional[int] = None
+
+
 class FlowTemplate(BaseModel):
     """Flow template metadata"""
     id: str = Field(..., description="Template ID")
@@ -321,11 +334,16 @@ class FlowTemplate(BaseModel):
     use_count: int = Field(default=0, description="How many times used")
     rating: float = Field(default=0.0, ge=0.0, le=5.0)
     language: str = Field(default="pt-BR")
+    version: str = Field(default="1.0", description="Template version (metadata.version)")
+    parameters: List[FlowTemplateParameter] = Field(default_factory=list)
 
 
 class FlowTemplateDetail(FlowTemplate):
     """Detailed template with flow data"""
     flow_data: Dict[str, Any] = Field(..., description="Complete flow data (nodes + edges)")
+    changelog: List[Dict[str, Any]] = Field(
+        default_factory=list, description="Changes per version: [{version, notes}]"
+    )
 
 
 class TemplateListResponse(BaseModel):
@@ -347,6 +365,40 @@ class ImportTemplateRequest(BaseModel):
         None,
         description="Variable name mappings for customization"
     )
+    parameters: Dict[str, Any] = Field(
+        default_factory=dict,
+        description="Template parameter values (defaults fill the missing ones)"
+    )
+
+
+class FlowTemplateUpgradeStatus(BaseModel):
+    """Whether a flow created from a template can move to the template's latest version"""
+    flow_id: UUID
+    template_id: str
+    current_version: str
+    latest_version: str
+    upgrade_available: bool
+    draft_modified: bool = Field(
+        ..., description="Draft edited since it was generated (upgrading discards the edits)"
+    )
+    new_parameters: List[FlowTemplateParameter] = Field(
+        default_factory=list, description="Parameters added by the latest version"
+    )
+    changelog: List[Dict[str, Any]] = Field(
+        default_factory=list, description="Changes after the current version"
+    )
+
+
+class FlowTemplateUpgradeRequest(BaseModel):
+    """Request to regenerate a flow from the template's latest version"""
+    parameters: Dict[str, Any] = Field(
+        default_factory=dict,
+        description="Values overriding the flow's parameters (required for new parameters without default)"
+    )
+    force: bool = Field(
+        default=False,
+        description="Upgrade even if the draft was edited after it was generated"
+    )
 
 
 # Export all schemas
@@ -367,8 +419,11 @@ __all__ = [
     'SuggestImprovementsResponse',
     'ApplyImprovementRequest',
     'TemplateCategory',
+    'FlowTemplateParameter',
     'FlowTemplate',
     'FlowTemplateDetail',
     'TemplateListResponse',
     'ImportTemplateRequest',
+    'FlowTemplateUpgradeStatus',
+    'FlowTemplateUpgradeRequest',
 ]
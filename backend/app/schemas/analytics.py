@@ -4,9 +4,12 @@ Analytics and Reports schemas
 
 from datetime import datetime
 from typing import Dict, List, Optional
+from uuid import UUID
 
 from pydantic import BaseModel, Field
 
+from app.schemas.annotation import AnnotationResponse
+
 
 # ============================================
 # OVERVIEW METRICS
@@ -66,6 +69,7 @@ class TimeSeriesData(BaseModel):
     data_points: List[TimeSeriesDataPoint] = Field(default_factory=list)
     total: Optional[float] = None
     average: Optional[float] = None
+    annotations: List[AnnotationResponse] = Field(default_factory=list)  # Events to overlay
 
 
 # ============================================
@@ -177,6 +181,113 @@ class ChatbotMetrics(BaseModel):
     avg_conversation_steps: Optional[float] = None
 
 
+# ============================================
+# FLOW FUNNEL
+# ============================================
+
+class FlowNodeFunnelStep(BaseModel):
+    """Traffic and drop-off of one node of a flow"""
+
+    node_id: str
+    node_type: str
+    label: Optional[str] = None
+    sessions: int = 0  # sessions that reached the node
+    visits: int = 0  # includes repeated entries (loops, invalid answers)
+    avg_time_spent_seconds: Optional[float] = None
+    drop_offs: int = 0  # abandoned sessions whose last node was this one
+    drop_off_rate: float = 0.0  # percentage of the node's sessions
+
+
+class FlowExitPoint(BaseModel):
+    """Node where abandoned sessions stopped"""
+
+    node_id: str
+    node_type: str
+    label: Optional[str] = None
+    sessions: int = 0
+    share: float = 0.0  # percentage of all abandoned sessions
+
+
+class FlowSplitVariantMetrics(BaseModel):
+    """Sessions and conversion of one branch of a split node"""
+
+    variant: str
+    label: Optional[str] = None
+    sessions: int = 0
+    conversions: int = 0  # reached the goal node (an end node without goal)
+    conversion_rate: float = 0.0
+
+
+class FlowSplitComparison(BaseModel):
+    """Branches of a split (A/B test) node side by side"""
+
+    node_id: str
+    label: Optional[str] = None
+    variants: List[FlowSplitVariantMetrics] = Field(default_factory=list)
+
+
+class FlowFunnelMetrics(BaseModel):
+    """Per-node analytics of a flow"""
+
+    flow_id: UUID
+    start_date: datetime
+    end_date: datetime
+    total_sessions: int = 0
+    completed_sessions: int = 0  # reached an end node
+    in_progress_sessions: int = 0  # still active within the abandon window
+    abandoned_sessions: int = 0
+    completion_rate: float = 0.0
+    nodes: List[FlowNodeFunnelStep] = Field(default_factory=list)
+    top_exit_points: List[FlowExitPoint] = Field(default_factory=list)
+    goal_node_id: Optional[str] = None  # conversion of split variants; None = completion
+    splits: List[FlowSplitComparison] = Field(default_factory=list)
+
+
+# ============================================
+# SURVEYS (NPS / CSAT)
+# ============================================
+
+class SurveyScore(BaseModel):
+    """NPS or CSAT of a set of survey answers"""
+
+    survey_type: str  # nps, csat
+    responses: int = 0
+    average_score: Optional[float] = None
+    promoters: int = 0  # satisfied answers (4-5) for CSAT
+    passives: int = 0  # neutral answers (3) for CSAT
+    detractors: int = 0  # dissatisfied answers (1-2) for CSAT
+    score: Optional[float] = None  # NPS (-100 to 100) or CSAT (0 to 100)
+
+
+class SurveyFlowScores(BaseModel):
+    """Survey scores of one flow"""
+
+    flow_id: Optional[UUID] = None  # None = flow deleted
+    flow_name: Optional[str] = None
+    nps: Optional[SurveyScore] = None
+    csat: Optional[SurveyScore] = None
+
+
+class SurveyAgentScores(BaseModel):
+    """Survey scores of the conversations of one agent"""
+
+    agent_id: Optional[UUID] = None  # None = answered without an assigned agent
+    agent_name: Optional[str] = None
+    nps: Optional[SurveyScore] = None
+    csat: Optional[SurveyScore] = None
+
+
+class SurveyMetrics(BaseModel):
+    """NPS / CSAT collected by survey nodes, overall, per flow and per agent"""
+
+    start_date: datetime
+    end_date: datetime
+    nps: Optional[SurveyScore] = None
+    csat: Optional[SurveyScore] = None
+    flows: List[SurveyFlowScores] = Field(default_factory=list)
+    agents: List[SurveyAgentScores] = Field(default_factory=list)
+
+
 # ============================================
 # MESSAGE METRICS
 # ============================================
@@ -248,6 +359,7 @@ class FullReport(BaseModel):
     contacts: ContactMetrics
     chatbots: ChatbotMetrics
     messages: MessageMetrics
+    annotations: List[AnnotationResponse] = Field(default_factory=list)
     generated_at: datetime = Field(default_factory=datetime.utcnow)
 
 
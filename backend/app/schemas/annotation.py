@@ -0,0 +1,55 @@
+"""
+Dashboard annotation schemas
+"""
+
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field, model_validator
+
+ANNOTATION_KIND_PATTERN = "^(deploy|template_change|campaign_launch|custom)$"
+
+
+class AnnotationCreate(BaseModel):
+    """Manual annotation (e.g. "price change", "TV ad aired")"""
+
+    title: str = Field(..., min_length=1, max_length=255)
+    description: Optional[str] = None
+    kind: str = Field(default="custom", pattern=ANNOTATION_KIND_PATTERN)
+    occurred_at: Optional[datetime] = Field(None, description="Defaults to now")
+    ends_at: Optional[datetime] = Field(None, description="End of a ranged event")
+    extra_data: Dict[str, Any] = Field(default_factory=dict)
+
+    @model_validator(mode="after")
+    def check_range(self) -> "AnnotationCreate":
+        if self.ends_at and self.occurred_at and self.ends_at < self.occurred_at:
+            raise ValueError("ends_at must be after occurred_at")
+        return self
+
+
+class AnnotationResponse(BaseModel):
+    """Annotation overlaid on time series"""
+
+    id: UUID
+    organization_id: Optional[UUID] = None  # None = platform-wide
+    kind: str
+    source: str
+    title: str
+    description: Optional[str] = None
+    occurred_at: datetime
+    ends_at: Optional[datetime] = None
+    resource_type: Optional[str] = None
+    resource_id: Optional[UUID] = None
+    created_by_user_id: Optional[UUID] = None
+    extra_data: Dict[str, Any] = Field(default_factory=dict)
+
+    class Config:
+        from_attributes = True
+
+
+class AnnotationList(BaseModel):
+    """Annotations of a period"""
+
+    items: List[AnnotationResponse] = Field(default_factory=list)
+    total: int = 0
@@ -2,6 +2,7 @@
 Authentication schemas
 """
 
+from datetime import datetime
 from typing import Optional
 from uuid import UUID
 
@@ -90,6 +91,18 @@ class TokenPayload(BaseSchema):
     iat: Optional[int] = None
 
 
+class WorkspaceResponse(BaseSchema):
+    """Organization the user can work in, with the user's role there"""
+
+    organization_id: UUID
+    name: str
+    slug: str
+    role: str
+    is_home: bool = False  # Organization the account belongs to
+    is_current: bool = False  # Workspace of the token used in the request
+    last_accessed_at: Optional[datetime] = None
+
+
 class RefreshTokenRequest(BaseSchema):
     """Request to refresh access token"""
 
@@ -2,17 +2,106 @@
 Campaign schemas
 """
 
-from datetime import datetime
-from typing import List, Optional
+from datetime import date, datetime
+from typing import List, Literal, Optional
 from uuid import UUID
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
 
-from pydantic import BaseModel, Field
+from pydantic import BaseModel, Field, field_validator, model_validator
+
+from app.utils.business_hours import validate_business_hours
+from app.utils.campaign_recurrence import WEEKDAYS
+from app.utils.contact_segment import validate_segment
 
 
 # ============================================
 # CAMPAIGN SCHEMAS
 # ============================================
 
+class CampaignRecurrence(BaseModel):
+    """When a recurring campaign creates its occurrences"""
+
+    frequency: Literal["daily", "weekly", "monthly"]
+    interval: int = Field(default=1, ge=1, le=52, description="Every N days, weeks or months")
+    days_of_week: List[str] = Field(default_factory=list, description="Weekly: MON, TUE, ... SUN")
+    day_of_month: Optional[int] = Field(None, ge=1, le=31, description="Monthly: last day of shorter months")
+    hour: int = Field(default=9, ge=0, le=23)
+    minute: int = Field(default=0, ge=0, le=59)
+    timezone: str = Field(default="America/Sao_Paulo")
+    end_after_occurrences: Optional[int] = Field(None, ge=1, description="Stop after N occurrences")
+    end_date: Optional[date] = Field(None, description="Stop after this date (local)")
+
+    @model_validator(mode="after")
+    def check_rule(self) -> "CampaignRecurrence":
+        self.days_of_week = [day.upper() for day in self.days_of_week]
+        unknown = [day for day in self.days_of_week if day not in WEEKDAYS]
+        if unknown:
+            raise ValueError(f"Unknown days_of_week: {', '.join(unknown)}")
+        if self.frequency == "weekly" and not self.days_of_week:
+            raise ValueError("Weekly recurrence requires days_of_week")
+        if self.frequency == "monthly" and not self.day_of_month:
+            raise ValueError("Monthly recurrence requires day_of_month")
+        try:
+            ZoneInfo(self.timezone)
+        except (ZoneInfoNotFoundError, ValueError):
+            raise ValueError(f"Unknown timezone: {self.timezone}")
+        return self
+
+
+class CampaignThrottle(BaseModel):
+    """Sending limits enforced while the campaign is dispatched (see app.core.campaign_throttle)"""
+
+    messages_per_minute: Optional[int] = Field(None, ge=1, le=1000)
+    messages_per_day: Optional[int] = Field(None, ge=1, le=100000)
+    business_hours: Optional[dict] = Field(
+        None, description="Only send while open (organization business hours format)"
+    )
+    use_contact_timezone: bool = Field(
+        default=False, description="Read business hours in the contact's timezone attribute"
+    )
+
+    @field_validator("business_hours")
+    @classmethod
+    def check_business_hours(cls, value: Optional[dict]) -> Optional[dict]:
+        errors = validate_business_hours(value)
+        if errors:
+            raise ValueError("; ".join(errors))
+        return value
+
+
+class CampaignABTestVariant(BaseModel):
+    """One variant of a campaign A/B test"""
+
+    id: str = Field(..., min_length=1, max_length=50)
+    label: Optional[str] = None
+    percentage: Optional[float] = Field(None, ge=0, le=100, description="Share of the test group")
+    template_id: Optional[UUID] = Field(None, description="Template campaigns: template of the variant")
+    template_variables: Optional[dict] = Field(None, description="Defaults to the campaign's variables")
+    text: Optional[str] = Field(None, description="Text campaigns: message of the variant")
+
+
+class CampaignABTest(BaseModel):
+    """A/B test of a campaign (see app.utils.campaign_ab_test)"""
+
+    variants: List[CampaignABTestVariant] = Field(..., min_length=2, max_length=5)
+    test_percentage: float = Field(default=20, gt=0, le=100, description="Share of the audience in the test")
+    metric: Literal["read", "reply", "click"] = "read"
+    confidence_level: float = Field(default=0.95, ge=0.8, le=0.999)
+    min_sample_size: int = Field(default=100, ge=1, description="Sends per variant before deciding")
+    max_test_hours: Optional[float] = Field(
+        None, gt=0, description="Roll out the best variant after this long without a significant result"
+    )
+    winner: Optional[dict] = Field(None, description="Set when the winner is declared")
+    test_completed_at: Optional[datetime] = None
+
+    @model_validator(mode="after")
+    def check_variants(self) -> "CampaignABTest":
+        ids = [variant.id for variant in self.variants]
+        if len(set(ids)) != len(ids):
+            raise ValueError("Variant ids must be unique")
+        return self
+
+
 class CampaignBase(BaseModel):
     """Base schema for Campaign"""
 
@@ -30,9 +119,16 @@ class CampaignBase(BaseModel):
     segment_filters: dict = Field(default_factory=dict)
     messages_per_hour: int = Field(default=100, ge=1, le=1000)
     delay_between_messages_seconds: int = Field(default=2, ge=0, le=60)
+    throttle: Optional[CampaignThrottle] = None
     respect_opt_out: bool = True
     skip_active_conversations: bool = False
     scheduled_at: Optional[datetime] = None
+    recurrence: Optional[CampaignRecurrence] = Field(
+        None, description="Repeat the campaign: scheduling creates one occurrence per date"
+    )
+    ab_test: Optional[CampaignABTest] = Field(
+        None, description="Test variants on part of the audience, then send the winner to the rest"
+    )
     settings: dict = Field(default_factory=dict)
     # Retry configuration
     retry_max_attempts: int = Field(default=3, ge=1, le=10, description="Maximum retry attempts per message")
@@ -42,7 +138,14 @@ class CampaignBase(BaseModel):
 
 class CampaignCreate(CampaignBase):
     """Schema for creating a campaign"""
-    pass
+
+    @field_validator("segment_filters")
+    @classmethod
+    def check_segment_filters(cls, value: Optional[dict]) -> Optional[dict]:
+        errors = validate_segment(value)
+        if errors:
+            raise ValueError("; ".join(errors))
+        return value
 
 
 class CampaignUpdate(BaseModel):
@@ -62,15 +165,26 @@ class CampaignUpdate(BaseModel):
     segment_filters: Optional[dict] = None
     messages_per_hour: Optional[int] = Field(None, ge=1, le=1000)
     delay_between_messages_seconds: Optional[int] = Field(None, ge=0, le=60)
+    throttle: Optional[CampaignThrottle] = None
     respect_opt_out: Optional[bool] = None
     skip_active_conversations: Optional[bool] = None
     scheduled_at: Optional[datetime] = None
+    recurrence: Optional[CampaignRecurrence] = None
+    ab_test: Optional[CampaignABTest] = None
     settings: Optional[dict] = None
     # Retry configuration
     retry_max_attempts: Optional[int] = Field(None, ge=1, le=10)
     retry_base_delay: Optional[int] = Field(None, ge=10, le=600)
     retry_max_delay: Optional[int] = Field(None, ge=60, le=7200)
 
+    @field_validator("segment_filters")
+    @classmethod
+    def check_segment_filters(cls, value: Optional[dict]) -> Optional[dict]:
+        errors = validate_segment(value)
+        if errors:
+            raise ValueError("; ".join(errors))
+        return value
+
 
 class CampaignInDB(CampaignBase):
     """Schema for campaign in database"""
@@ -83,6 +197,10 @@ class CampaignInDB(CampaignBase):
     completed_at: Optional[datetime] = None
     paused_at: Optional[datetime] = None
     cancelled_at: Optional[datetime] = None
+    parent_campaign_id: Optional[UUID] = None
+    occurrence_number: Optional[int] = None
+    next_occurrence_at: Optional[datetime] = None
+    occurrences_created: int = 0
     total_recipients: int = 0
     messages_sent: int = 0
     messages_delivered: int = 0
@@ -139,6 +257,35 @@ class CampaignProgress(BaseModel):
     messages_failed: int
     progress_percentage: float
     estimated_completion_time: Optional[datetime] = None
+    deferred_until: Optional[datetime] = None  # Held by the messaging tier until then (UTC)
+
+
+class CampaignABVariantResult(BaseModel):
+    """Sends and engagement of one A/B test variant"""
+
+    variant: str
+    label: Optional[str] = None
+    sent: int = 0
+    delivered: int = 0
+    read: int = 0
+    replied: int = 0
+    clicked: int = 0
+    conversions: int = 0  # contacts counted by the test metric
+    rate: float = 0.0  # percentage 0-100
+    ci_low: float = 0.0  # confidence interval of the rate, percentage
+    ci_high: float = 0.0
+
+
+class CampaignABTestResults(BaseModel):
+    """Results of a campaign A/B test"""
+
+    campaign_id: UUID
+    status: str
+    metric: str
+    confidence_level: float
+    min_sample_size: int
+    winner: Optional[dict] = None
+    variants: List[CampaignABVariantResult] = Field(default_factory=list)
 
 
 # ============================================
@@ -153,6 +300,101 @@ class AudiencePreview(BaseModel):
     filters_applied: dict = Field(default_factory=dict)
 
 
+# ============================================
+# TEMPLATE PRE-FLIGHT
+# ============================================
+
+class TemplateValidationIssue(BaseModel):
+    """Problem found while validating a campaign template"""
+
+    code: str = Field(..., description="Machine-readable code, e.g. parameter_count_mismatch")
+    field: Optional[str] = Field(None, description="Campaign field to fix")
+    message: str = Field(..., description="Actionable description")
+
+
+class TemplateValidationResult(BaseModel):
+    """Validation result of one template attached to a campaign"""
+
+    template_id: Optional[UUID] = None
+    name: Optional[str] = None
+    language: Optional[str] = None
+    category: Optional[str] = None
+    is_valid: bool
+    errors: List[TemplateValidationIssue] = Field(default_factory=list)
+    warnings: List[TemplateValidationIssue] = Field(default_factory=list)
+
+
+class CampaignPreflightReport(BaseModel):
+    """Pre-flight validation of a campaign's templates"""
+
+    campaign_id: UUID
+    is_valid: bool
+    templates: List[TemplateValidationResult] = Field(default_factory=list)
+    message_errors: List[TemplateValidationIssue] = Field(
+        default_factory=list, description="Non-template message content over WhatsApp limits"
+    )
+
+
+class CampaignDryRunMessage(BaseModel):
+    """Message a sample contact would receive"""
+
+    contact_id: UUID
+    name: Optional[str] = None
+    whatsapp_id: str
+    variant: Optional[str] = Field(None, description="A/B test variant (None: waits for the winner)")
+    message_type: str
+    template_name: Optional[str] = None
+    language: Optional[str] = None
+    components: Optional[List[dict]] = None
+    text: Optional[str] = Field(None, description="Rendered text (template header and body for templates)")
+    missing_variables: List[str] = Field(default_factory=list)
+    error: Optional[str] = None
+
+
+class CampaignDryRunVariable(BaseModel):
+    """Contact variable used by the campaign and how many recipients lack it"""
+
+    variable: str
+    known: bool = Field(..., description="False for variables that never resolve")
+    contacts_missing: int
+
+
+class CampaignDryRunReport(BaseModel):
+    """What a campaign would send, without sending anything"""
+
+    campaign_id: UUID
+    total_recipients: int
+    samples: List[CampaignDryRunMessage] = Field(default_factory=list)
+    variables: List[CampaignDryRunVariable] = Field(default_factory=list)
+    contacts_with_missing_variables: int = 0
+    pricing_category: Optional[str] = None
+    estimated_cost: Optional[float] = None
+    currency: str
+    estimated_duration_seconds: Optional[float] = Field(
+        None, description="Under the campaign limits and business hours (None: over a year)"
+    )
+    estimated_completion_at: Optional[datetime] = None
+    preflight: Optional[CampaignPreflightReport] = None
+    warnings: List[str] = Field(default_factory=list)
+
+
+# ============================================
+# BULK TEMPLATE SEND
+# ============================================
+
+class BulkTemplateSendRequest(BaseModel):
+    """One-off personalized template blast from a CSV"""
+
+    whatsapp_number_id: UUID
+    template_name: str = Field(..., min_length=1, max_length=255)
+    language: Optional[str] = Field(None, description="Template language (default: any approved translation)")
+    recipients_csv: str = Field(
+        ...,
+        min_length=1,
+        description="CSV with a header row: phone, then the template variables in order (header* columns fill the header)",
+    )
+
+
 # ============================================
 # RESPONSE SCHEMAS
 # ============================================
@@ -3,10 +3,13 @@ Chatbot, Flow, and Node schemas
 """
 
 from datetime import datetime
-from typing import List, Optional
+from typing import Dict, List, Literal, Optional
 from uuid import UUID
 
-from pydantic import BaseModel, Field
+from pydantic import BaseModel, ConfigDict, Field, field_validator, model_validator
+
+from app.utils.flow_locale import normalize_locale
+from app.utils.flow_transfer import FLOW_EXPORT_FORMAT_VERSION
 
 
 # ============================================
@@ -48,6 +51,7 @@ class NodeInDB(NodeBase):
     id: UUID
     organization_id: UUID
     flow_id: UUID
+    flow_version_id: Optional[UUID] = Field(None, description="Published version of this copy (null = draft)")
     created_at: datetime
     updated_at: datetime
 
@@ -66,10 +70,18 @@ class FlowBase(BaseModel):
     description: Optional[str] = None
     is_main: bool = Field(default=False, description="Is this the main entry flow?")
     is_fallback: bool = Field(default=False, description="Is this a fallback flow?")
+    default_locale: Optional[str] = Field(
+        None, max_length=10, description="Language of the nodes' own content (e.g. pt_BR), used when no translation matches"
+    )
     canvas_data: dict = Field(default_factory=lambda: {"nodes": [], "edges": []})
     variables: dict = Field(default_factory=dict)
     is_active: bool = True
 
+    @field_validator("default_locale")
+    @classmethod
+    def normalize_default_locale(cls, value: Optional[str]) -> Optional[str]:
+        return normalize_locale(value)
+
 
 class FlowCreate(FlowBase):
     """Schema for creating a flow"""
@@ -83,10 +95,16 @@ class FlowUpdate(BaseModel):
     description: Optional[str] = None
     is_main: Optional[bool] = None
     is_fallback: Optional[bool] = None
+    default_locale: Optional[str] = Field(None, max_length=10)
     canvas_data: Optional[dict] = None
     variables: Optional[dict] = None
     is_active: Optional[bool] = None
 
+    @field_validator("default_locale")
+    @classmethod
+    def normalize_default_locale(cls, value: Optional[str]) -> Optional[str]:
+        return normalize_locale(value)
+
 
 class FlowInDB(FlowBase):
     """Schema for flow in database"""
@@ -94,7 +112,11 @@ class FlowInDB(FlowBase):
     id: UUID
     organization_id: UUID
     chatbot_id: UUID
-    version: int
+    version: int = Field(..., description="Number the draft gets when published")
+    published_version_id: Optional[UUID] = Field(None, description="Live version (null = the draft runs)")
+    template_origin: Optional[dict] = Field(
+        None, description="Gallery template the flow was created from (template_id, version, parameters)"
+    )
     created_at: datetime
     updated_at: datetime
     deleted_at: Optional[datetime] = None
@@ -103,10 +125,224 @@ class FlowInDB(FlowBase):
         from_attributes = True
 
 
+class FlowValidationIssue(BaseModel):
+    """Problem found by the static flow validator"""
+
+    code: str = Field(..., description="Machine-readable code, e.g. unreachable_node")
+    severity: str = Field(..., description="error (blocks publishing) or warning")
+    message: str
+    node_id: Optional[str] = Field(None, description="React Flow node ID")
+    edge_id: Optional[str] = None
+    field: Optional[str] = Field(None, description="Node data field over WhatsApp limits")
+
+    class Config:
+        from_attributes = True
+
+
+class FlowComplexityMetrics(BaseModel):
+    """Graph metrics of a flow (see app.utils.flow_complexity)"""
+
+    node_count: int = 0
+    max_depth: int = Field(0, description="Nodes on the longest path from the start node, each cycle counted once")
+    cycles: int = 0
+    unguarded_cycles: int = Field(0, description="Cycles that never wait for the contact nor bound their iterations")
+    estimated_messages: int = Field(0, description="Messages sent on the longest path of a run")
+    cycle_nodes: List[List[str]] = Field(default_factory=list, description="React Flow node IDs of each cycle")
+
+
+class FlowValidationReport(BaseModel):
+    """Static validation of a flow's draft"""
+
+    flow_id: UUID
+    is_valid: bool = Field(..., description="No errors (warnings do not block publishing)")
+    diagnostics: List[FlowValidationIssue] = Field(default_factory=list)
+    complexity: Optional[FlowComplexityMetrics] = None
+
+
+class FlowSaved(FlowInDB):
+    """Saved flow with the diagnostics of its canvas"""
+
+    diagnostics: List[FlowValidationIssue] = Field(default_factory=list)
+
+
 class FlowWithNodes(FlowInDB):
-    """Flow with nodes included"""
+    """Flow with its draft nodes included"""
+
+    nodes: List[NodeInDB] = Field(default_factory=list, validation_alias="draft_nodes")
+
+
+# ============================================
+# FLOW VERSION SCHEMAS
+# ============================================
+
+class FlowPublish(BaseModel):
+    """Schema for publishing a flow's draft"""
+
+    notes: Optional[str] = Field(None, max_length=2000, description="Release notes")
+    migration_strategy: Literal["continue", "map_node_ids", "restart_at_checkpoint"] = Field(
+        "continue",
+        description=(
+            "What happens to conversations running an earlier version: continue on it, move to the "
+            "node with the same ID in the new version, or restart at a checkpoint node"
+        ),
+    )
+    node_map: Dict[str, str] = Field(
+        default_factory=dict, description="map_node_ids: old node ID -> node ID in the new version"
+    )
+    checkpoint_node_id: Optional[str] = Field(
+        None, description="restart_at_checkpoint: node to restart at (start node when empty)"
+    )
+
+
+class FlowVersionInDB(BaseModel):
+    """Published version of a flow with its execution stats"""
+
+    id: UUID
+    flow_id: UUID
+    version: int
+    status: str = Field(..., description="published (live) or archived")
+    notes: Optional[str] = None
+    published_by_id: Optional[UUID] = None
+    published_at: datetime
+    sessions_started: int = 0
+    sessions_completed: int = 0
+    completion_rate: Optional[float] = Field(None, description="Percentage of sessions that reached an end node")
+    session_migration: Optional[dict] = Field(
+        None, description="Running sessions moved on publish: strategy, migrated, restarted, kept"
+    )
+    complexity: Optional[FlowComplexityMetrics] = Field(None, description="Graph metrics computed on publish")
+
+    class Config:
+        from_attributes = True
+
+
+class FlowVersionDetail(FlowVersionInDB):
+    """Version with its snapshot"""
+
+    canvas_data: dict
+    variables: dict
+
+
+class FlowVersionListResponse(BaseModel):
+    """Response for flow version list"""
+
+    total: int
+    items: List[FlowVersionInDB]
+
+
+# ============================================
+# FLOW SESSION TRACE SCHEMAS
+# ============================================
+
+class FlowTraceEventInDB(BaseModel):
+    """Entry of a flow session's execution trace"""
+
+    id: UUID
+    event_type: str = Field(..., description="node_executed, input_received, message_sent or variable_set")
+    node_id: Optional[str] = Field(None, description="React Flow node ID")
+    node_type: Optional[str] = None
+    data: dict = Field(default_factory=dict)
+    occurred_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class FlowSessionTrace(BaseModel):
+    """Execution trace of a flow session, oldest event first"""
+
+    session_id: UUID
+    conversation_id: UUID
+    flow_id: Optional[UUID] = None
+    total: int
+    events: List[FlowTraceEventInDB]
+
+
+# ============================================
+# FLOW EXPORT/IMPORT SCHEMAS
+# ============================================
+
+class FlowExportNode(BaseModel):
+    """React Flow node of an exported canvas"""
+
+    model_config = ConfigDict(extra="allow")
+
+    id: str = Field(..., min_length=1)
+    type: Optional[str] = None
+    position: dict = Field(default_factory=dict)
+    data: dict = Field(default_factory=dict)
+
+
+class FlowExportEdge(BaseModel):
+    """React Flow edge of an exported canvas"""
+
+    model_config = ConfigDict(extra="allow")
+
+    id: Optional[str] = None
+    source: str = Field(..., min_length=1)
+    target: str = Field(..., min_length=1)
+    sourceHandle: Optional[str] = None
+
+
+class FlowExportCanvas(BaseModel):
+    """Exported canvas; edges must connect nodes of the canvas"""
+
+    model_config = ConfigDict(extra="allow")
+
+    nodes: List[FlowExportNode] = Field(default_factory=list)
+    edges: List[FlowExportEdge] = Field(default_factory=list)
+
+    @model_validator(mode="after")
+    def check_graph(self):
+        node_ids = [node.id for node in self.nodes]
+        duplicated = sorted({node_id for node_id in node_ids if node_ids.count(node_id) > 1})
+        if duplicated:
+            raise ValueError(f"Duplicated node ids: {', '.join(duplicated)}")
+
+        for edge in self.edges:
+            for end in (edge.source, edge.target):
+                if end not in node_ids:
+                    raise ValueError(f"Edge {edge.source} -> {edge.target} points to unknown node {end}")
+        return self
+
+
+class FlowExportFlow(BaseModel):
+    """Flow part of an export document"""
+
+    name: str = Field(..., min_length=1, max_length=255)
+    description: Optional[str] = None
+    is_fallback: bool = False
+    is_active: bool = True
+    default_locale: Optional[str] = Field(None, max_length=10)
+    canvas_data: FlowExportCanvas = Field(default_factory=FlowExportCanvas)
+    variables: dict = Field(default_factory=dict)
+
+
+class FlowExportReference(BaseModel):
+    """Record of the exporting organization referenced by node data"""
+
+    type: Literal["flow", "department", "queue", "agent"]
+    id: str
+    key: Optional[str] = Field(None, description="Flow name, department/queue slug or agent email")
+
+
+class FlowExportDocument(BaseModel):
+    """Versioned flow export document"""
+
+    format_version: Literal["1.0", "2.0"] = FLOW_EXPORT_FORMAT_VERSION
+    exported_at: Optional[datetime] = None
+    flow: FlowExportFlow
+    references: List[FlowExportReference] = Field(default_factory=list)
+    metadata: dict = Field(default_factory=dict)
+
+
+class FlowImport(FlowExportDocument):
+    """Export document being imported, with optional explicit id remapping"""
 
-    nodes: List[NodeInDB] = []
+    id_map: Dict[str, str] = Field(
+        default_factory=dict,
+        description="Referenced id of the exporting organization -> id in this organization",
+    )
 
 
 # ============================================
@@ -8,6 +8,8 @@ from uuid import UUID
 
 from pydantic import BaseModel, Field, field_validator
 
+from app.utils.language_detection import base_language
+
 
 # Base Contact Schema
 class ContactBase(BaseModel):
@@ -42,9 +44,16 @@ class ContactUpdate(ContactBase):
     is_blocked: Optional[bool] = None
     blocked_reason: Optional[str] = None
     is_vip: Optional[bool] = None
+    language: Optional[str] = Field(None, max_length=10, description="ISO 639-1 code (pt, es, en...)")
     assigned_agent_id: Optional[UUID] = None
     assigned_department_id: Optional[UUID] = None
 
+    @field_validator("language")
+    @classmethod
+    def normalize_language(cls, v: Optional[str]) -> Optional[str]:
+        """Keep the language part of a locale (pt_BR -> pt)"""
+        return base_language(v)
+
 
 # Contact in DB
 class ContactInDB(ContactBase):
@@ -76,6 +85,9 @@ class ContactInDB(ContactBase):
     # VIP Status
     is_vip: bool = False
 
+    # Language
+    language: Optional[str] = None
+
     # Activity
     last_message_at: Optional[datetime] = None
     last_message_received_at: Optional[datetime] = None
@@ -160,3 +172,26 @@ class ContactBulkTag(BaseModel):
 class ContactBulkUpdate(BaseModel):
     contact_ids: List[UUID] = Field(..., min_items=1)
     update_data: ContactUpdate
+
+
+# Contact Import
+class ContactImportError(BaseModel):
+    line: int
+    error: str
+
+
+class ContactImportProgress(BaseModel):
+    import_id: str
+    filename: str
+    file_format: str = Field(..., description="csv or xlsx")
+    status: str = Field(..., description="queued, running, completed or failed")
+    progress_percentage: float = 0.0
+    processed_rows: int = 0
+    created: int = 0
+    updated: int = 0
+    skipped: int = Field(0, description="Repeated rows, or existing contacts without update_existing")
+    invalid: int = 0
+    errors: List[ContactImportError] = Field(default_factory=list, description="First invalid rows")
+    error_message: Optional[str] = None
+    started_at: Optional[datetime] = None
+    completed_at: Optional[datetime] = None
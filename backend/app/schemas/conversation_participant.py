@@ -0,0 +1,56 @@
+"""
+Conversation participant schemas
+"""
+
+from datetime import datetime
+from typing import List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field
+
+PARTICIPANT_ROLE_PATTERN = "^(primary|collaborator)$"
+NOTIFICATION_LEVEL_PATTERN = "^(all|mentions|none)$"
+
+
+class ParticipantAdd(BaseModel):
+    """Add an agent to a conversation"""
+
+    user_id: UUID
+    role: str = Field(
+        default="collaborator",
+        pattern=PARTICIPANT_ROLE_PATTERN,
+        description="primary replaces the current primary agent (assignment)",
+    )
+    notification_level: str = Field(default="all", pattern=NOTIFICATION_LEVEL_PATTERN)
+
+
+class ParticipantUpdate(BaseModel):
+    """Change a participant's role or notification preference"""
+
+    role: Optional[str] = Field(None, pattern=PARTICIPANT_ROLE_PATTERN)
+    notification_level: Optional[str] = Field(None, pattern=NOTIFICATION_LEVEL_PATTERN)
+
+
+class ParticipantResponse(BaseModel):
+    """Agent taking part in a conversation"""
+
+    id: UUID
+    conversation_id: UUID
+    user_id: UUID
+    role: str
+    notification_level: str
+    added_by_user_id: Optional[UUID] = None
+    joined_at: datetime
+    left_at: Optional[datetime] = None
+    messages_sent: int = 0
+    last_message_at: Optional[datetime] = None
+
+    class Config:
+        from_attributes = True
+
+
+class ParticipantList(BaseModel):
+    """Active participants of a conversation"""
+
+    items: List[ParticipantResponse] = Field(default_factory=list)
+    total: int = 0
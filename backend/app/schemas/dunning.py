@@ -0,0 +1,142 @@
+"""
+Dunning schemas (ERP invoices and reminder rules)
+"""
+
+from datetime import date, datetime, time
+from decimal import Decimal
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field
+
+
+# ============================================
+# INVOICE SYNC (ERP)
+# ============================================
+
+class InvoiceSyncItem(BaseModel):
+    """Invoice as sent by the ERP (upserted by external_id)"""
+
+    external_id: str = Field(..., min_length=1, max_length=255)
+    customer_phone: str = Field(..., description="WhatsApp number of the customer (E.164)")
+    customer_name: Optional[str] = None
+    number: Optional[str] = Field(None, max_length=100)
+    amount: Decimal = Field(..., gt=0)
+    currency: str = Field(default="BRL", min_length=3, max_length=3)
+    due_date: date
+    status: str = Field(default="open", pattern="^(open|paid|cancelled)$")
+    paid_at: Optional[datetime] = None
+    pix_code: Optional[str] = None
+    payment_url: Optional[str] = None
+    extra_data: Dict[str, Any] = Field(default_factory=dict)
+
+
+class InvoiceSyncRequest(BaseModel):
+    """Batch of invoices from the ERP"""
+
+    invoices: List[InvoiceSyncItem] = Field(..., min_length=1, max_length=1000)
+
+
+class InvoiceSyncResponse(BaseModel):
+    """Result of an ERP sync"""
+
+    created: int = 0
+    updated: int = 0
+    paid: int = 0
+    cancelled_reminders: int = 0
+
+
+class InvoiceResponse(BaseModel):
+    """Invoice stored for dunning"""
+
+    id: UUID
+    contact_id: UUID
+    external_id: str
+    number: Optional[str] = None
+    amount: Decimal
+    currency: str
+    due_date: date
+    status: str
+    paid_at: Optional[datetime] = None
+    last_synced_at: Optional[datetime] = None
+
+    class Config:
+        from_attributes = True
+
+
+# ============================================
+# RULES
+# ============================================
+
+class DunningRuleBase(BaseModel):
+    """Base schema for DunningRule"""
+
+    name: str = Field(..., min_length=1, max_length=255)
+    description: Optional[str] = None
+    whatsapp_number_id: UUID
+    template_id: UUID
+    offset_days: int = Field(
+        ..., ge=-60, le=180, description="Days relative to the due date (-3 = 3 days before, 5 = 5 days overdue)"
+    )
+    send_time: time = Field(default=time(9, 0), description="Local time of the send")
+    timezone: str = Field(default="America/Sao_Paulo", max_length=50)
+    template_variables: List[str] = Field(
+        default_factory=list,
+        description='Template body parameters in order, e.g. ["contact.name", "invoice.amount", "invoice.pix_code"]',
+    )
+    offer_pix: bool = Field(default=False, description="Only remind invoices that have a Pix code")
+    min_amount: Optional[Decimal] = Field(None, ge=0)
+    is_active: bool = True
+
+
+class DunningRuleCreate(DunningRuleBase):
+    """Schema for creating a dunning rule"""
+    pass
+
+
+class DunningRuleUpdate(BaseModel):
+    """Schema for updating a dunning rule"""
+
+    name: Optional[str] = Field(None, min_length=1, max_length=255)
+    description: Optional[str] = None
+    whatsapp_number_id: Optional[UUID] = None
+    template_id: Optional[UUID] = None
+    offset_days: Optional[int] = Field(None, ge=-60, le=180)
+    send_time: Optional[time] = None
+    timezone: Optional[str] = Field(None, max_length=50)
+    template_variables: Optional[List[str]] = None
+    offer_pix: Optional[bool] = None
+    min_amount: Optional[Decimal] = Field(None, ge=0)
+    is_active: Optional[bool] = None
+
+
+class DunningRuleResponse(DunningRuleBase):
+    """Dunning rule with its counters"""
+
+    id: UUID
+    organization_id: UUID
+    total_scheduled: int = 0
+    total_sent: int = 0
+    total_failed: int = 0
+    total_skipped: int = 0
+    total_paid: int = 0
+    recovered_amount: Decimal = Decimal("0")
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class DunningRuleMetrics(BaseModel):
+    """Performance of a dunning rule"""
+
+    rule_id: UUID
+    scheduled: int
+    sent: int
+    failed: int
+    skipped: int
+    paid: int
+    recovered_amount: Decimal
+    delivery_rate: float = Field(..., description="sent / (sent + failed)")
+    conversion_rate: float = Field(..., description="Invoices paid after the reminder / sent")
@@ -0,0 +1,116 @@
+"""
+Flow test console schemas
+"""
+
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field
+
+
+class FlowConsoleStart(BaseModel):
+    """Start a console session"""
+
+    variables: Dict[str, Any] = Field(
+        default_factory=dict,
+        description="Initial variables (e.g. contact_name, or mocked outputs of nodes the console skips)",
+    )
+
+
+class FlowConsoleInput(BaseModel):
+    """Message sent to the flow as if it came from the contact"""
+
+    text: str = Field(..., min_length=1, max_length=4096)
+    variables: Dict[str, Any] = Field(
+        default_factory=dict,
+        description="Variables merged into the session before the message is processed",
+    )
+
+
+class FlowConsoleOutput(BaseModel):
+    """Something the flow would have sent to the contact"""
+
+    type: str = Field(..., description="text, media, interactive, template, error or handoff")
+    node_id: Optional[str] = None
+    text: Optional[str] = None
+    content: Dict[str, Any] = Field(default_factory=dict)
+
+
+class FlowConsoleStep(BaseModel):
+    """Node visited during a turn"""
+
+    node_id: str
+    node_type: str
+    status: str = Field(..., description="executed, waiting, skipped or error")
+    detail: Optional[str] = None
+
+
+class FlowConsoleSession(BaseModel):
+    """Session state after the last turn"""
+
+    session_id: str
+    flow_id: UUID
+    current_node_id: Optional[str] = None
+    waiting_for_input: bool = False
+    finished: bool = False
+    variables: Dict[str, Any] = Field(default_factory=dict)
+    outputs: List[FlowConsoleOutput] = Field(
+        default_factory=list, description="What the flow sent in the last turn"
+    )
+    steps: List[FlowConsoleStep] = Field(
+        default_factory=list, description="Nodes visited in the last turn"
+    )
+    turns: int = 0
+    created_at: datetime
+    updated_at: datetime
+
+
+class FlowSimulationExpectation(BaseModel):
+    """What a simulation must end with to pass (for CI tests of flows)"""
+
+    finished: Optional[bool] = Field(None, description="Whether the flow must have finished")
+    node_id: Optional[str] = Field(None, description="Node the simulation must stop on")
+    variables: Dict[str, Any] = Field(
+        default_factory=dict, description="Variables that must have these values at the end"
+    )
+
+
+class FlowSimulationRequest(BaseModel):
+    """Run a flow against scripted contact messages"""
+
+    variables: Dict[str, Any] = Field(
+        default_factory=dict,
+        description="Initial variables (e.g. contact_name, or mocked outputs of nodes the console skips)",
+    )
+    inputs: List[FlowConsoleInput] = Field(
+        default_factory=list, max_length=100, description="Contact messages, answered in order"
+    )
+    version: Optional[int] = Field(None, ge=1, description="Published version to run (default: the draft)")
+    expect: Optional[FlowSimulationExpectation] = None
+
+
+class FlowSimulationTurn(BaseModel):
+    """One turn of the transcript: the contact's message and what the flow did"""
+
+    input: Optional[str] = Field(None, description="Contact message (null for the opening turn)")
+    outputs: List[FlowConsoleOutput] = Field(default_factory=list)
+    steps: List[FlowConsoleStep] = Field(default_factory=list)
+    variables: Dict[str, Any] = Field(default_factory=dict, description="Variables after the turn")
+    current_node_id: Optional[str] = None
+    waiting_for_input: bool = False
+    finished: bool = False
+
+
+class FlowSimulationResult(BaseModel):
+    """Transcript of a simulation"""
+
+    flow_id: UUID
+    version: Optional[int] = None
+    turns: List[FlowSimulationTurn] = Field(default_factory=list)
+    finished: bool = False
+    current_node_id: Optional[str] = None
+    variables: Dict[str, Any] = Field(default_factory=dict)
+    unused_inputs: int = Field(0, description="Inputs left over when the flow stopped waiting")
+    passed: bool = Field(True, description="All expectations met (always true without expect)")
+    failures: List[str] = Field(default_factory=list)
@@ -0,0 +1,127 @@
+"""
+Flow event trigger schemas
+"""
+
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field, field_validator, model_validator
+
+from app.utils.flow_events import validate_event_type
+
+
+class FlowEventTriggerBase(BaseModel):
+    """Base schema for FlowEventTrigger"""
+
+    flow_id: UUID
+    whatsapp_number_id: UUID = Field(..., description="Number the conversation runs on")
+    event_type: str = Field(
+        ...,
+        min_length=1,
+        max_length=100,
+        description="campaign.reply_received, invoice.overdue, ticket.resolved or a custom event name",
+        examples=["invoice.overdue"],
+    )
+    conditions: Dict[str, Any] = Field(
+        default_factory=dict,
+        description="Payload values the event must have (dotted path -> value or list of values)",
+        examples=[{"invoice.currency": "BRL"}],
+    )
+    variable_mapping: Dict[str, str] = Field(
+        default_factory=dict,
+        description="Flow variable -> payload path",
+        examples=[{"valor": "invoice.amount"}],
+    )
+    template_id: Optional[UUID] = Field(
+        None, description="Approved template that opens the conversation (outbound-initiated)"
+    )
+    template_variables: List[str] = Field(
+        default_factory=list, description="Payload paths of the template's body parameters, in order"
+    )
+    interrupts_flow: bool = Field(
+        default=False, description="Also fires while a flow is running for the contact, replacing it"
+    )
+    is_active: bool = True
+
+    @field_validator("event_type")
+    @classmethod
+    def check_event_type(cls, value: str) -> str:
+        return validate_event_type(value)
+
+
+class FlowEventTriggerCreate(FlowEventTriggerBase):
+    """Schema for creating an event trigger"""
+    pass
+
+
+class FlowEventTriggerUpdate(BaseModel):
+    """Schema for updating an event trigger"""
+
+    flow_id: Optional[UUID] = None
+    whatsapp_number_id: Optional[UUID] = None
+    event_type: Optional[str] = Field(None, min_length=1, max_length=100)
+    conditions: Optional[Dict[str, Any]] = None
+    variable_mapping: Optional[Dict[str, str]] = None
+    template_id: Optional[UUID] = None
+    template_variables: Optional[List[str]] = None
+    interrupts_flow: Optional[bool] = None
+    is_active: Optional[bool] = None
+
+    @field_validator("event_type")
+    @classmethod
+    def check_event_type(cls, value: Optional[str]) -> Optional[str]:
+        return validate_event_type(value) if value is not None else value
+
+
+class FlowEventTriggerInDB(FlowEventTriggerBase):
+    """Schema for event trigger in database"""
+
+    id: UUID
+    organization_id: UUID
+    total_started: int = 0
+    total_skipped: int = 0
+    total_failed: int = 0
+    last_fired_at: Optional[datetime] = None
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class FlowEventTriggerListResponse(BaseModel):
+    """Response for event trigger list"""
+
+    total: int
+    items: List[FlowEventTriggerInDB]
+
+
+class FlowEventEmit(BaseModel):
+    """Event posted by an external system (or to test triggers)"""
+
+    event_type: str = Field(..., min_length=1, max_length=100, examples=["order.shipped"])
+    contact_id: Optional[UUID] = None
+    phone: Optional[str] = Field(
+        None, max_length=30, description="Contact phone with country code; the contact is created when missing"
+    )
+    name: Optional[str] = Field(None, max_length=255, description="Name of a contact created by phone")
+    payload: Dict[str, Any] = Field(default_factory=dict)
+
+    @field_validator("event_type")
+    @classmethod
+    def check_event_type(cls, value: str) -> str:
+        return validate_event_type(value)
+
+    @model_validator(mode="after")
+    def check_contact(self):
+        if not self.contact_id and not self.phone:
+            raise ValueError("contact_id or phone is required")
+        return self
+
+
+class FlowEventEmitResult(BaseModel):
+    """Triggers an event fired"""
+
+    contact_id: UUID
+    trigger_ids: List[UUID] = Field(default_factory=list, description="Triggers queued to start their flow")
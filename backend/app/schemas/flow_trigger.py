@@ -0,0 +1,101 @@
+"""
+Flow keyword trigger schemas
+"""
+
+from datetime import datetime
+from typing import List, Literal, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field, model_validator
+
+from app.utils.flow_triggers import validate_pattern
+
+MatchType = Literal["exact", "keyword", "regex"]
+
+
+class FlowTriggerBase(BaseModel):
+    """Base schema for FlowTrigger"""
+
+    flow_id: UUID
+    match_type: MatchType = Field(
+        default="keyword",
+        description="exact (whole message), keyword (whole words in the message) or regex",
+    )
+    pattern: str = Field(..., min_length=1, max_length=500, examples=["2ª via"])
+    priority: int = Field(default=0, ge=-1000, le=1000, description="Higher priority wins when several triggers match")
+    interrupts_flow: bool = Field(
+        default=False, description="Also fires while a flow is running, replacing it (e.g. \"menu\")"
+    )
+    is_active: bool = True
+
+    @model_validator(mode="after")
+    def check_pattern(self):
+        validate_pattern(self.match_type, self.pattern)
+        return self
+
+
+class FlowTriggerCreate(FlowTriggerBase):
+    """Schema for creating a trigger"""
+    pass
+
+
+class FlowTriggerUpdate(BaseModel):
+    """Schema for updating a trigger"""
+
+    flow_id: Optional[UUID] = None
+    match_type: Optional[MatchType] = None
+    pattern: Optional[str] = Field(None, min_length=1, max_length=500)
+    priority: Optional[int] = Field(None, ge=-1000, le=1000)
+    interrupts_flow: Optional[bool] = None
+    is_active: Optional[bool] = None
+
+
+class FlowTriggerInDB(FlowTriggerBase):
+    """Schema for trigger in database"""
+
+    id: UUID
+    organization_id: UUID
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class FlowTriggerConflict(BaseModel):
+    """Two triggers of different flows that compete for the same messages"""
+
+    kind: str = Field(..., description="duplicate or overlap")
+    trigger_id: UUID = Field(..., description="Trigger that wins")
+    other_trigger_id: UUID
+    message: str
+
+    class Config:
+        from_attributes = True
+
+
+class FlowTriggerSaved(FlowTriggerInDB):
+    """Saved trigger with the overlaps it has with other triggers"""
+
+    conflicts: List[FlowTriggerConflict] = Field(default_factory=list)
+
+
+class FlowTriggerListResponse(BaseModel):
+    """Response for trigger list"""
+
+    total: int
+    items: List[FlowTriggerInDB]
+
+
+class FlowTriggerTest(BaseModel):
+    """Message to check against the organization's triggers"""
+
+    text: str = Field(..., min_length=1, max_length=4096)
+    in_flow: bool = Field(default=False, description="A flow is running (only interrupting triggers apply)")
+
+
+class FlowTriggerTestResult(BaseModel):
+    """Trigger a message would fire"""
+
+    matched: bool
+    trigger: Optional[FlowTriggerInDB] = None
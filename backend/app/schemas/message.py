@@ -11,14 +11,20 @@ from pydantic import BaseModel, Field
 
 class MessageSendRequest(BaseModel):
     """Schema for sending a message"""
-    message_type: str = Field(..., pattern="^(text|image|document|template|audio|video)$")
+    message_type: str = Field(..., pattern="^(text|image|document|template|audio|video|cta_url|address)$")
     content: Dict[str, Any] = Field(..., description="Message content based on type")
+    reply_to_message_id: Optional[UUID] = Field(
+        None, description="ID of a message in this conversation to quote (threaded reply)"
+    )
 
     # Examples:
     # text: {"text": "Hello!", "preview_url": false}
+    # text with preview: {"text": "See", "preview_url": true, "preview_link": "https://..."}
     # image: {"url": "https://...", "caption": "Caption"}
     # document: {"url": "https://...", "filename": "file.pdf", "caption": "Caption"}
     # template: {"name": "hello_world", "language": "pt_BR", "components": [...]}
+    # cta_url: {"body": "Acompanhe seu pedido", "display_text": "Rastrear", "url": "https://...", "header": "...", "footer": "..."}
+    # address: {"text": "Where should we deliver?", "country": "IN", "values": {"name": "Priya"}, "saved_addresses": [...]}
 
 
 class MessageResponse(BaseModel):
@@ -27,10 +33,12 @@ class MessageResponse(BaseModel):
     conversation_id: UUID
     direction: str  # inbound, outbound
     sender_type: str  # contact, agent, bot, system
+    sender_user_id: Optional[UUID] = None  # Agent who sent it (outbound)
     message_type: str
     content: Dict[str, Any]
     status: str  # pending, sent, delivered, read, failed, received
     whatsapp_message_id: Optional[str] = None
+    reply_to_message_id: Optional[UUID] = None
 
     # Timestamps
     created_at: datetime
@@ -42,5 +50,35 @@ class MessageResponse(BaseModel):
     # Error info
     error_code: Optional[str] = None
     error_message: Optional[str] = None
+    meta_response: Optional[Dict[str, Any]] = Field(
+        None, description="Meta request id (fbtrace_id), retry_after and quota usage of the send, for support tickets"
+    )
 
     model_config = {"from_attributes": True}
+
+
+class DeliveryStats(BaseModel):
+    """Delivery receipts of outbound messages (conversation or campaign)"""
+    total: int = 0
+    pending: int = 0
+    sent: int = 0  # Accepted by WhatsApp, not yet delivered
+    delivered: int = 0  # Delivered, not read
+    read: int = 0
+    failed: int = 0
+    delivery_rate: float = Field(0.0, description="% of non-pending messages delivered (read included)")
+    read_rate: float = Field(0.0, description="% of delivered messages read")
+    failure_rate: float = Field(0.0, description="% of non-pending messages failed")
+
+    @classmethod
+    def from_counts(cls, counts: Dict[str, int]) -> "DeliveryStats":
+        """Build from message counts per status"""
+        stats = cls(**{status: counts.get(status, 0) for status in ("pending", "sent", "delivered", "read", "failed")})
+        stats.total = sum(counts.values())
+        reached = stats.delivered + stats.read
+        processed = stats.sent + reached + stats.failed
+        if processed:
+            stats.delivery_rate = round(reached / processed * 100, 2)
+            stats.failure_rate = round(stats.failed / processed * 100, 2)
+        if reached:
+            stats.read_rate = round(stats.read / reached * 100, 2)
+        return stats
@@ -8,6 +8,8 @@ from uuid import UUID
 
 from pydantic import BaseModel, Field, field_validator
 
+from app.utils.business_hours import validate_business_hours
+
 
 # Base Organization Schema
 class OrganizationBase(BaseModel):
@@ -29,6 +31,7 @@ class OrganizationUpdate(BaseModel):
     description: Optional[str] = None
     website: Optional[str] = None
     logo_url: Optional[str] = None
+    home_region: Optional[str] = Field(None, max_length=50, description="Region pinned for webhooks and sends")
 
 
 # Organization in DB
@@ -41,6 +44,8 @@ class OrganizationInDB(OrganizationBase):
     trial_ends_at: Optional[datetime] = None
     subscription_starts_at: Optional[datetime] = None
     subscription_ends_at: Optional[datetime] = None
+    message_encryption_enabled: bool = False
+    home_region: Optional[str] = None
     created_at: datetime
     updated_at: datetime
 
@@ -73,13 +78,23 @@ class OrganizationWithStats(Organization):
 
 # Organization Settings Update
 class OrganizationSettingsUpdate(BaseModel):
-    business_hours: Optional[dict] = None
+    business_hours: Optional[dict] = Field(
+        None, description="Timezone, weekly schedule and holiday calendar (see app.utils.business_hours)"
+    )
     timezone: Optional[str] = None
     language: Optional[str] = None
     currency: Optional[str] = None
     notification_settings: Optional[dict] = None
     security_settings: Optional[dict] = None
 
+    @field_validator("business_hours")
+    @classmethod
+    def check_business_hours(cls, value: Optional[dict]) -> Optional[dict]:
+        errors = validate_business_hours(value)
+        if errors:
+            raise ValueError("; ".join(errors))
+        return value
+
 
 # Organization Plan Update
 class OrganizationPlanUpdate(BaseModel):
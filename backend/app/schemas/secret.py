@@ -3,12 +3,15 @@ Secret Schemas - Pydantic models for API requests/responses
 """
 
 from datetime import datetime
-from typing import Optional, Dict, Any
+from typing import Optional, Dict, Any, List, Literal
 from uuid import UUID
 
 from pydantic import BaseModel, Field, field_validator
 
-from app.models.secret import SecretScope, EncryptionProvider
+from app.models.secret import SecretScope, SecretKind, SecretUsage, EncryptionProvider
+
+# Roles that can be granted read access (admins always have it)
+ReaderRole = Literal["agent", "viewer"]
 
 
 class SecretBase(BaseModel):
@@ -38,6 +41,18 @@ class SecretBase(BaseModel):
         default=None,
         description="Optional metadata (tags, categories, etc.)"
     )
+    kind: SecretKind = Field(
+        default=SecretKind.SECRET,
+        description="secret ({{org.secrets.NAME}}) or variable ({{org.vars.NAME}})"
+    )
+    allowed_roles: Optional[List[ReaderRole]] = Field(
+        default=None,
+        description="Roles that may read the value besides admins (default: admins only for secrets, everyone for variables)"
+    )
+    allowed_usages: Optional[List[SecretUsage]] = Field(
+        default=None,
+        description="Where the value may be interpolated (default: everywhere)"
+    )
 
     @field_validator('name')
     @classmethod
@@ -103,6 +118,14 @@ class SecretUpdate(BaseModel):
         None,
         description="New metadata"
     )
+    allowed_roles: Optional[List[ReaderRole]] = Field(
+        None,
+        description="New reader roles ([] = admins only)"
+    )
+    allowed_usages: Optional[List[SecretUsage]] = Field(
+        None,
+        description="New allowed usages"
+    )
 
 
 class SecretRotateKey(BaseModel):
@@ -131,6 +154,9 @@ class SecretInDB(SecretBase):
     # Status
     is_active: bool
 
+    # Placeholder for flows, webhook headers and prompts
+    reference: Optional[str] = None
+
     # Audit
     last_used_at: Optional[datetime] = None
     usage_count: int
@@ -204,6 +230,31 @@ class SecretUsageStats(BaseModel):
     is_active: bool
 
 
+class SecretAccessLogResponse(BaseModel):
+    """Entry of a secret's access audit"""
+    id: UUID
+    secret_id: Optional[UUID] = None
+    secret_name: str
+    action: str
+    usage: str
+    user_id: Optional[UUID] = None
+    resource_type: Optional[str] = None
+    resource_id: Optional[str] = None
+    allowed: bool
+    reason: Optional[str] = None
+    created_at: datetime
+
+    model_config = {"from_attributes": True}
+
+
+class SecretAccessLogList(BaseModel):
+    """Paginated access audit"""
+    items: List[SecretAccessLogResponse]
+    total: int
+    skip: int
+    limit: int
+
+
 # Export all schemas
 __all__ = [
     'SecretBase',
@@ -218,4 +269,6 @@ __all__ = [
     'SecretListResponse',
     'SecretValidationResponse',
     'SecretUsageStats',
+    'SecretAccessLogResponse',
+    'SecretAccessLogList',
 ]
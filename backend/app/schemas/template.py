@@ -5,30 +5,49 @@ WhatsApp Template Schemas
 from typing import List, Optional, Dict, Any
 from datetime import datetime
 from uuid import UUID
-from pydantic import BaseModel, Field, field_validator
+from pydantic import BaseModel, Field, field_validator, model_validator
 
 
 # ============= Component Schemas =============
 
 class TemplateButtonSchema(BaseModel):
     """Template button schema"""
-    type: str = Field(..., description="Button type: QUICK_REPLY, PHONE_NUMBER, URL")
-    text: str = Field(..., max_length=25, description="Button text")
+    type: str = Field(..., description="Button type: QUICK_REPLY, PHONE_NUMBER, URL, COPY_CODE")
+    text: Optional[str] = Field(None, max_length=25, description="Button text (not used by COPY_CODE)")
     phone_number: Optional[str] = Field(None, description="Phone number for PHONE_NUMBER type")
     url: Optional[str] = Field(None, description="URL for URL type")
+    example: Optional[str] = Field(None, max_length=15, description="Sample coupon code for COPY_CODE type")
+
+    @model_validator(mode="after")
+    def validate_button(self):
+        if self.type == "COPY_CODE":
+            if not self.example:
+                raise ValueError("COPY_CODE buttons need an example coupon code")
+        elif not self.text:
+            raise ValueError(f"{self.type} buttons need a text")
+        return self
+
+
+class TemplateLimitedTimeOfferSchema(BaseModel):
+    """Limited-time offer settings (LIMITED_TIME_OFFER component)"""
+    text: str = Field(..., max_length=16, description="Offer headline, e.g. Oferta relâmpago!")
+    has_expiration: bool = Field(True, description="Show a countdown to the expiration sent with each message")
 
 
 class TemplateComponentSchema(BaseModel):
-    """Template component schema (Header, Body, Footer, Buttons)"""
-    type: str = Field(..., description="Component type: HEADER, BODY, FOOTER, BUTTONS")
+    """Template component schema (Header, Body, Footer, Buttons, Limited-time offer)"""
+    type: str = Field(..., description="Component type: HEADER, BODY, FOOTER, BUTTONS, LIMITED_TIME_OFFER")
     format: Optional[str] = Field(None, description="Format for HEADER: TEXT, IMAGE, VIDEO, DOCUMENT")
     text: Optional[str] = Field(None, description="Text content")
     buttons: Optional[List[TemplateButtonSchema]] = Field(None, description="Buttons for BUTTONS component")
+    limited_time_offer: Optional[TemplateLimitedTimeOfferSchema] = Field(
+        None, description="Settings for LIMITED_TIME_OFFER component"
+    )
 
     @field_validator("type")
     @classmethod
     def validate_type(cls, v):
-        allowed = ["HEADER", "BODY", "FOOTER", "BUTTONS"]
+        allowed = ["HEADER", "BODY", "FOOTER", "BUTTONS", "LIMITED_TIME_OFFER"]
         if v not in allowed:
             raise ValueError(f"type must be one of {allowed}")
         return v
@@ -98,6 +117,8 @@ class TemplateResponse(BaseModel):
     body_variables_count: int = 0
     footer_text: Optional[str] = None
     buttons: List[Dict[str, Any]] = []
+    carousel_cards: Optional[List[Dict[str, Any]]] = None
+    limited_time_offer: Optional[Dict[str, Any]] = None
 
     # Stats
     sent_count: int = 0
@@ -125,6 +146,15 @@ class TemplateListResponse(BaseModel):
     total: int
 
 
+class TemplateCatalogResponse(BaseModel):
+    """Page of the cached template catalog"""
+    templates: List[TemplateResponse]
+    total: int
+    skip: int
+    limit: int
+    cached: bool = Field(False, description="Served from the Redis cache")
+
+
 # ============= Sync Schema =============
 
 class TemplateSyncRequest(BaseModel):
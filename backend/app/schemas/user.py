@@ -122,3 +122,24 @@ class UserListItem(BaseSchema):
     is_active: bool
     is_online: bool
     last_seen_at: Optional[datetime] = None
+
+
+class WorkspaceMemberAdd(BaseSchema):
+    """Add an existing account (e.g. an agency user) to this organization"""
+
+    email: EmailStr
+    role: str = Field(default="agent", pattern="^(org_admin|agent|viewer)$")
+    department_ids: List[UUID] = Field(default_factory=list)
+
+
+class WorkspaceMember(BaseSchema):
+    """Guest member of the organization (home organization is another one)"""
+
+    user_id: UUID
+    email: EmailStr
+    full_name: str
+    role: str
+    department_ids: List[UUID] = []
+    is_active: bool
+    last_accessed_at: Optional[datetime] = None
+    created_at: datetime
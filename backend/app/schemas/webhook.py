@@ -0,0 +1,49 @@
+"""
+Webhook Schemas
+
+Typed representations of Meta Cloud API webhook payloads (every inbound
+message type, business events such as orders and payments, and changes to
+earlier messages such as reactions and edits, security notifications and
+echoes of messages sent from the WhatsApp Business app) so services and the flow
+engine can work with validated data instead of raw dicts.
+
+The models live in the pytake-client SDK so tenant integrations and the
+backend share a single definition.
+"""
+
+from pytake_client.webhooks import (  # noqa: F401
+    ECHO_FIELDS,
+    DeliveryError,
+    DeliveryErrorCode,
+    FlowReply,
+    IdentityChange,
+    IdentityChangeKind,
+    InboundMessage,
+    InboundMessageType,
+    InteractiveReply,
+    LocationShare,
+    MediaAttachment,
+    MessageContext,
+    MessageEcho,
+    MessageEdit,
+    MessageReaction,
+    OrderMessage,
+    OrderProductItem,
+    PaymentAmount,
+    PaymentStatus,
+    PaymentTransaction,
+    PhoneNumberQualityUpdate,
+    QuickReplyButton,
+    SharedContact,
+    ShippingAddress,
+    SystemNotice,
+    TemplateStatusUpdate,
+    WebhookChallenge,
+    WebhookEvent,
+    WebhookEventType,
+    classify_message,
+    classify_status,
+    inbound_message_type,
+    iter_events,
+    verify_signature,
+)
@@ -0,0 +1,111 @@
+"""
+Stored webhook event schemas (persistence and replay)
+"""
+
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field, model_validator
+
+REPLAY_STATUS_PATTERN = "^(failed|skipped)$"
+
+
+class WebhookEventSummary(BaseModel):
+    """Stored webhook payload without its body"""
+
+    id: UUID
+    organization_id: Optional[UUID] = None
+    whatsapp_number_id: Optional[UUID] = None
+    source: str
+    phone_number_id: Optional[str] = None
+    status: str
+    error: Optional[str] = None
+    attempts: int = 0
+    received_at: datetime
+    last_attempt_at: Optional[datetime] = None
+    processed_at: Optional[datetime] = None
+
+    class Config:
+        from_attributes = True
+
+
+class WebhookEventDetail(WebhookEventSummary):
+    """Stored webhook payload"""
+
+    payload: Dict[str, Any] = Field(default_factory=dict)
+
+
+class WebhookEventList(BaseModel):
+    """Page of stored webhook events"""
+
+    items: List[WebhookEventSummary] = Field(default_factory=list)
+    total: int = 0
+
+
+class WebhookReplayRequest(BaseModel):
+    """Events to replay: explicit ids, or every failed/skipped event of a period"""
+
+    event_ids: Optional[List[UUID]] = Field(None, max_length=1000)
+    status: Optional[str] = Field(None, pattern=REPLAY_STATUS_PATTERN)
+    since: Optional[datetime] = None
+    until: Optional[datetime] = None
+    limit: int = Field(default=500, ge=1, le=1000)
+
+    @model_validator(mode="after")
+    def check_selection(self) -> "WebhookReplayRequest":
+        if not self.event_ids and not self.since:
+            raise ValueError("Provide event_ids or a since date")
+        return self
+
+
+class WebhookReplayResponse(BaseModel):
+    """Events queued for replay"""
+
+    queued: int = 0
+    event_ids: List[UUID] = Field(default_factory=list)
+
+
+class WebhookInboxEntry(BaseModel):
+    """Recent webhook delivery (redacted) kept for debugging"""
+
+    id: str
+    at: datetime
+    type: str  # inbound, outbound
+    status: str  # inbound: processed, failed, skipped / outbound: delivered, failed
+    source: Optional[str] = None
+    error: Optional[str] = None
+    # Inbound
+    event_id: Optional[str] = None
+    fields: List[str] = Field(default_factory=list)
+    payload: Optional[Dict[str, Any]] = None
+    # Outbound
+    method: Optional[str] = None
+    url: Optional[str] = None
+    status_code: Optional[int] = None
+    duration_ms: Optional[int] = None
+    request: Optional[Dict[str, Any]] = None
+    response: Optional[Any] = None
+
+
+class WebhookInboxList(BaseModel):
+    """Recent webhook deliveries, newest first"""
+
+    items: List[WebhookInboxEntry] = Field(default_factory=list)
+    size: int = 0
+
+
+class WebhookRoutingStats(BaseModel):
+    """Routing of inbound webhooks to numbers by phone_number_id"""
+
+    unroutable: Dict[str, int] = Field(
+        default_factory=dict, description="Unroutable events by reason, all instances and workers"
+    )
+    unroutable_total: int = 0
+    unknown_numbers: Dict[str, int] = Field(
+        default_factory=dict, description="Events per phone_number_id no organization owns"
+    )
+    last_unroutable_at: Optional[datetime] = None
+    routed: Dict[str, int] = Field(
+        default_factory=dict, description="Routed changes per phone_number_id since this instance started"
+    )
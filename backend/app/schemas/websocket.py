@@ -0,0 +1,68 @@
+"""
+WebSocket command schemas
+
+Commands sent by agents over Socket.IO (event "command") and the
+acknowledgement returned for each of them.
+"""
+
+from enum import Enum
+from typing import Any, Dict, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, Field
+
+
+class WSCommandType(str, Enum):
+    SEND_MESSAGE = "send_message"
+    MARK_READ = "mark_read"
+    TYPING = "typing"
+    ASSIGN = "assign"
+
+
+class WSCommand(BaseModel):
+    """Command envelope"""
+
+    id: str = Field(..., min_length=1, max_length=100, description="Client-generated (optimistic) id")
+    command: WSCommandType
+    payload: Dict[str, Any] = Field(default_factory=dict)
+
+
+class WSCommandError(BaseModel):
+    code: int
+    message: str
+
+
+class WSCommandAck(BaseModel):
+    """Acknowledgement returned to the client"""
+
+    id: Optional[str] = None
+    command: Optional[str] = None
+    ok: bool
+    data: Optional[Dict[str, Any]] = None
+    error: Optional[WSCommandError] = None
+
+
+# ============================================
+# PAYLOADS
+# ============================================
+
+
+class SendMessageCommand(BaseModel):
+    conversation_id: UUID
+    message_type: str = Field("text", pattern="^(text|image|document|template|audio|video)$")
+    content: Dict[str, Any]
+    reply_to_message_id: Optional[UUID] = None
+
+
+class MarkReadCommand(BaseModel):
+    conversation_id: UUID
+
+
+class TypingCommand(BaseModel):
+    conversation_id: UUID
+    typing: bool = True
+
+
+class AssignCommand(BaseModel):
+    conversation_id: UUID
+    agent_id: UUID
@@ -66,6 +66,8 @@ class WhatsAppNumberInDB(WhatsAppNumberBase):
     verified: bool = False
     quality_rating: Optional[str] = None
     messaging_limit: Optional[str] = None
+    token_status: Optional[str] = None  # valid, expiring, expired, invalid
+    token_expires_at: Optional[datetime] = None
 
     # Evolution API fields (QR Code)
     evolution_instance_name: Optional[str] = None
@@ -157,3 +159,20 @@ class QRCodeResponse(BaseModel):
     """QR Code for WhatsApp connection"""
     qr_code: str
     expires_at: datetime
+
+
+# ============= Client Metrics Schema =============
+
+class WhatsAppNumberMetrics(BaseModel):
+    """Meta Cloud API calls of one phone number (this instance)"""
+
+    requests: int = 0
+    errors: int = Field(0, description="4xx/5xx responses and network errors")
+    status_codes: Dict[str, int] = Field(default_factory=dict)
+    endpoints: Dict[str, int] = Field(default_factory=dict, description='Calls per "METHOD edge"')
+    latency_ms_avg: float = 0.0
+    latency_ms_max: float = 0.0
+    rate_limit: Dict = Field(
+        default_factory=dict, description="Last usage headers returned by Meta (X-Business-Use-Case-Usage...)"
+    )
+    last_request_at: Optional[datetime] = None
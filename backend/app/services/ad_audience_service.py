@@ -0,0 +1,302 @@
+"""
+Ad Audience Service - Sync contact segments to Meta Custom Audiences
+
+Each sync resolves the audience with the campaign audience rules, keeps only
+contacts that accept marketing and diffs the result against the members
+already pushed: new contacts are added, contacts that left the segment or
+opted out are removed, and contacts whose phone/email changed are replaced.
+Only SHA-256 hashes ever leave the platform.
+"""
+
+import logging
+from datetime import datetime, timezone
+from typing import Dict, List, Optional, Tuple
+from uuid import UUID
+
+from sqlalchemy import delete, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.integrations.meta_ads_api import MetaAdsAPI, hash_contact
+from app.integrations.meta_api import MetaAPIError
+from app.models.ad_audience import AdAudience, AdAudienceMember
+from app.models.organization import Organization
+from app.models.secret import Secret
+from app.schemas.ad_audience import (
+    AdAudienceCreate,
+    AdAudienceSyncResult,
+    AdAudienceUpdate,
+    MetaAdsSettings,
+)
+from app.services.campaign_service import CampaignService
+from app.services.secret_service import SecretService
+
+logger = logging.getLogger(__name__)
+
+HashRow = Tuple[Optional[str], Optional[str]]
+
+
+def diff_members(
+    desired: Dict[UUID, HashRow], members: List[AdAudienceMember]
+) -> Tuple[Dict[UUID, HashRow], List[AdAudienceMember]]:
+    """
+    Contacts to add and members to remove
+
+    A member whose hashes differ from the contact's current ones is removed
+    and added again, so Meta never keeps an outdated phone or email.
+    """
+    current = {member.contact_id: member for member in members}
+
+    to_remove = [
+        member for contact_id, member in current.items()
+        if desired.get(contact_id) != (member.phone_hash, member.email_hash)
+    ]
+    to_add = {
+        contact_id: row for contact_id, row in desired.items()
+        if contact_id not in current or current[contact_id] in to_remove
+    }
+    return to_add, to_remove
+
+
+class AdAudienceService:
+    """Service for Meta Custom Audience sync"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    # ============= Settings =============
+
+    async def _get_organization(self, organization_id: UUID) -> Organization:
+        organization = await self.db.get(Organization, organization_id)
+        if not organization:
+            raise NotFoundException("Organization not found")
+        return organization
+
+    async def get_settings(self, organization_id: UUID) -> MetaAdsSettings:
+        organization = await self._get_organization(organization_id)
+        return MetaAdsSettings(**(organization.settings or {}).get("meta_ads", {}))
+
+    async def update_settings(self, organization_id: UUID, data: MetaAdsSettings) -> MetaAdsSettings:
+        organization = await self._get_organization(organization_id)
+
+        if data.access_token_secret_id:
+            secret = await self.db.get(Secret, data.access_token_secret_id)
+            if not secret or secret.organization_id != organization_id:
+                raise NotFoundException("Secret not found")
+        if data.enabled and not (data.ad_account_id and data.access_token_secret_id):
+            raise BadRequestException("ad_account_id and access_token_secret_id are required to enable Meta Ads")
+
+        new_settings = dict(organization.settings or {})
+        new_settings["meta_ads"] = data.model_dump(mode="json")
+        organization.settings = new_settings
+        await self.db.commit()
+
+        logger.info(f"📣 Meta Ads settings updated for organization {organization_id}")
+        return data
+
+    async def _client(self, organization_id: UUID) -> MetaAdsAPI:
+        """Ads client of the organization (requires Meta Ads to be enabled)"""
+        meta_ads = await self.get_settings(organization_id)
+        if not (meta_ads.enabled and meta_ads.ad_account_id and meta_ads.access_token_secret_id):
+            raise BadRequestException("Meta Ads is not enabled for this organization")
+
+        try:
+            access_token = await SecretService(self.db).get_decrypted_value(
+                meta_ads.access_token_secret_id, organization_id
+            )
+        except ValueError as e:
+            raise BadRequestException(f"Meta Ads access token unavailable: {e}")
+
+        return MetaAdsAPI(meta_ads.ad_account_id, access_token)
+
+    # ============= Audiences =============
+
+    async def list_audiences(self, organization_id: UUID) -> List[AdAudience]:
+        result = await self.db.execute(
+            select(AdAudience)
+            .where(
+                AdAudience.organization_id == organization_id,
+                AdAudience.deleted_at.is_(None),
+            )
+            .order_by(AdAudience.name)
+        )
+        return list(result.scalars().all())
+
+    async def get_audience(self, audience_id: UUID, organization_id: UUID) -> AdAudience:
+        audience = (await self.db.execute(
+            select(AdAudience).where(
+                AdAudience.id == audience_id,
+                AdAudience.organization_id == organization_id,
+                AdAudience.deleted_at.is_(None),
+            )
+        )).scalar_one_or_none()
+
+        if not audience:
+            raise NotFoundException("Ad audience not found")
+        return audience
+
+    async def create_audience(self, organization_id: UUID, data: AdAudienceCreate) -> AdAudience:
+        audience = AdAudience(organization_id=organization_id, **data.model_dump())
+        self.db.add(audience)
+        await self.db.commit()
+        await self.db.refresh(audience)
+
+        logger.info(f"📣 Ad audience '{audience.name}' created ({audience.audience_type})")
+        return audience
+
+    async def update_audience(
+        self, audience_id: UUID, organization_id: UUID, data: AdAudienceUpdate
+    ) -> AdAudience:
+        audience = await self.get_audience(audience_id, organization_id)
+
+        for field, value in data.model_dump(exclude_unset=True).items():
+            setattr(audience, field, value)
+
+        await self.db.commit()
+        await self.db.refresh(audience)
+        return audience
+
+    async def delete_audience(self, audience_id: UUID, organization_id: UUID) -> None:
+        """Stop syncing the audience (the Custom Audience on Meta is kept)"""
+        audience = await self.get_audience(audience_id, organization_id)
+        audience.deleted_at = datetime.now(timezone.utc)
+        audience.is_active = False
+        await self.db.commit()
+
+    # ============= Sync =============
+
+    async def _desired_members(self, audience: AdAudience) -> Dict[UUID, HashRow]:
+        """Hashes of the contacts currently in the segment that accept marketing"""
+        contacts = await CampaignService(self.db).get_audience_contacts(
+            audience.organization_id,
+            audience.audience_type,
+            audience.target_tag_ids,
+            audience.target_contact_ids,
+            audience.segment_filters,
+        )
+
+        desired = {}
+        for contact in contacts:
+            if not contact.can_receive_marketing:
+                continue
+            row = hash_contact(contact.whatsapp_id, contact.email)
+            if any(row):
+                desired[contact.id] = row
+        return desired
+
+    async def sync_audience(self, audience_id: UUID, organization_id: UUID) -> AdAudienceSyncResult:
+        """
+        Push the changes of the segment since the last sync
+
+        Raises:
+            BadRequestException: Meta Ads not configured or Meta rejected the update
+        """
+        audience = await self.get_audience(audience_id, organization_id)
+        client = await self._client(organization_id)
+
+        desired = await self._desired_members(audience)
+        members = list((await self.db.execute(
+            select(AdAudienceMember).where(AdAudienceMember.audience_id == audience.id)
+        )).scalars().all())
+
+        try:
+            if not audience.meta_audience_id or audience.ad_account_id != client.ad_account_id:
+                # First sync or another ad account: the new Custom Audience starts empty
+                audience.meta_audience_id = await client.create_custom_audience(
+                    audience.name, audience.description
+                )
+                audience.ad_account_id = client.ad_account_id
+                await self.db.execute(
+                    delete(AdAudienceMember).where(AdAudienceMember.audience_id == audience.id)
+                )
+                members = []
+
+            to_add, to_remove = diff_members(desired, members)
+            await client.remove_users(
+                audience.meta_audience_id, [(m.phone_hash, m.email_hash) for m in to_remove]
+            )
+            await client.add_users(audience.meta_audience_id, list(to_add.values()))
+
+        except MetaAPIError as e:
+            audience.last_synced_at = datetime.now(timezone.utc)
+            audience.last_sync_status = "failed"
+            audience.last_error = e.message
+            await self.db.commit()
+            logger.error(f"❌ Ad audience {audience.id} sync failed: {e.message}")
+            raise BadRequestException(f"Meta Ads sync failed: {e.message}")
+
+        for member in to_remove:
+            await self.db.delete(member)
+        for contact_id, (phone_hash, email_hash) in to_add.items():
+            self.db.add(AdAudienceMember(
+                audience_id=audience.id,
+                contact_id=contact_id,
+                phone_hash=phone_hash,
+                email_hash=email_hash,
+            ))
+
+        audience.member_count = len(members) - len(to_remove) + len(to_add)
+        audience.last_synced_at = datetime.now(timezone.utc)
+        audience.last_sync_status = "success"
+        audience.last_error = None
+        await self.db.commit()
+
+        logger.info(
+            f"📣 Ad audience '{audience.name}' synced: +{len(to_add)} -{len(to_remove)} "
+            f"({audience.member_count} members)"
+        )
+        return AdAudienceSyncResult(
+            audience_id=audience.id,
+            meta_audience_id=audience.meta_audience_id,
+            added=len(to_add),
+            removed=len(to_remove),
+            member_count=audience.member_count,
+        )
+
+    async def remove_contact(self, contact_id: UUID) -> int:
+        """
+        Remove a contact from every audience it was pushed to (opt-out, block, deletion)
+
+        Returns:
+            Number of audiences the contact was removed from
+        """
+        rows = (await self.db.execute(
+            select(AdAudienceMember, AdAudience)
+            .join(AdAudience, AdAudience.id == AdAudienceMember.audience_id)
+            .where(AdAudienceMember.contact_id == contact_id)
+        )).all()
+
+        removed = 0
+        for member, audience in rows:
+            if audience.meta_audience_id:
+                try:
+                    client = await self._client(audience.organization_id)
+                    await client.remove_users(
+                        audience.meta_audience_id, [(member.phone_hash, member.email_hash)]
+                    )
+                except (BadRequestException, MetaAPIError) as e:
+                    # Member kept so the next sync retries the removal
+                    logger.error(f"❌ Could not remove contact {contact_id} from ad audience {audience.id}: {e}")
+                    continue
+
+            await self.db.delete(member)
+            audience.member_count = max((audience.member_count or 0) - 1, 0)
+            removed += 1
+
+        await self.db.commit()
+        if removed:
+            logger.info(f"📣 Contact {contact_id} removed from {removed} ad audience(s)")
+        return removed
+
+    async def list_syncable_audiences(self) -> List[AdAudience]:
+        """Active audiences of organizations with Meta Ads enabled"""
+        result = await self.db.execute(
+            select(AdAudience)
+            .join(Organization, Organization.id == AdAudience.organization_id)
+            .where(
+                AdAudience.is_active.is_(True),
+                AdAudience.deleted_at.is_(None),
+                Organization.settings["meta_ads"]["enabled"].as_boolean().is_(True),
+            )
+        )
+        return list(result.scalars().all())
@@ -2,15 +2,17 @@
 Analytics service - Business intelligence and reporting
 """
 
-from datetime import datetime, timedelta
-from typing import Dict, List, Optional
+from dataclasses import asdict
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, List, Optional, Set
 from uuid import UUID
 
 from sqlalchemy import func, select, and_
 from sqlalchemy.ext.asyncio import AsyncSession
 
+from app.core.exceptions import NotFoundException
 from app.models.campaign import Campaign
-from app.models.chatbot import Chatbot
+from app.models.chatbot import Chatbot, Flow
 from app.models.contact import Contact
 from app.models.conversation import Conversation, Message
 from app.models.user import User
@@ -21,13 +23,26 @@ from app.schemas.analytics import (
     ChatbotMetrics,
     ContactMetrics,
     ConversationMetrics,
+    FlowExitPoint,
+    FlowFunnelMetrics,
+    FlowNodeFunnelStep,
+    FlowSplitComparison,
+    FlowSplitVariantMetrics,
     FullReport,
     MessageMetrics,
     OverviewMetrics,
     ReportPeriod,
+    SurveyAgentScores,
+    SurveyFlowScores,
+    SurveyMetrics,
+    SurveyScore,
     TimeSeriesData,
     TimeSeriesDataPoint,
 )
+from app.repositories.chatbot import FlowNodeVisitRepository, SurveyResponseRepository
+from app.services.annotation_service import AnnotationService
+from app.utils.flow_split import split_variants
+from app.utils.survey import CSAT, NPS, summarize
 
 
 class AnalyticsService:
@@ -340,6 +355,214 @@ class AnalyticsService:
             data_points=[],
             total=0.0,
             average=0.0,
+            annotations=await AnnotationService(self.db).list_annotations(
+                organization_id, start_date, end_date
+            ),
+        )
+
+    # ============================================
+    # FLOW FUNNEL
+    # ============================================
+
+    async def get_flow_funnel(
+        self,
+        flow_id: UUID,
+        organization_id: UUID,
+        start_date: datetime,
+        end_date: datetime,
+        abandon_after_minutes: int = 30,
+        top_exit_points: int = 5,
+        goal_node_id: Optional[str] = None,
+    ) -> FlowFunnelMetrics:
+        """
+        Get completion rate, drop-off per node and exit points of a flow
+
+        Covers the flow sessions started in the period. A session is completed
+        when it reaches an end node; otherwise it is abandoned at its last node
+        once idle for abandon_after_minutes, and in progress before that.
+
+        Split (A/B test) nodes are compared per variant: a session converts when
+        it reaches goal_node_id, or completes the flow when no goal is given.
+
+        Raises:
+            NotFoundException: If the flow does not exist
+        """
+        flow = await self.db.get(Flow, flow_id)
+        if not flow or flow.organization_id != organization_id or flow.deleted_at is not None:
+            raise NotFoundException("Flow not found")
+
+        visits = FlowNodeVisitRepository(self.db)
+        sessions = await visits.get_session_summaries(flow_id, organization_id, start_date, end_date)
+        node_stats = await visits.get_node_stats(flow_id, organization_id, start_date, end_date)
+        last_nodes = await visits.get_last_nodes(flow_id, organization_id, start_date, end_date)
+
+        abandoned_after = datetime.now(timezone.utc) - timedelta(minutes=abandon_after_minutes)
+        completed = in_progress = 0
+        drop_offs: Dict[str, int] = {}
+        for session in sessions:
+            if session.completed:
+                completed += 1
+            elif _as_utc(session.last_entered_at) > abandoned_after:
+                in_progress += 1
+            elif session.session_id in last_nodes:
+                node_id = last_nodes[session.session_id]
+                drop_offs[node_id] = drop_offs.get(node_id, 0) + 1
+
+        labels = {
+            node.get("id"): (node.get("data") or {}).get("label")
+            for node in (flow.canvas_data or {}).get("nodes", [])
+        }
+        nodes = [
+            FlowNodeFunnelStep(
+                node_id=row.node_id,
+                node_type=row.node_type,
+                label=labels.get(row.node_id),
+                sessions=row.sessions,
+                visits=row.visits,
+                avg_time_spent_seconds=(
+                    round(float(row.avg_duration_ms) / 1000, 2) if row.avg_duration_ms is not None else None
+                ),
+                drop_offs=drop_offs.get(row.node_id, 0),
+                drop_off_rate=_percentage(drop_offs.get(row.node_id, 0), row.sessions),
+            )
+            for row in node_stats
+        ]
+        nodes.sort(key=lambda step: (-step.sessions, step.node_id))
+
+        abandoned = sum(drop_offs.values())
+        exit_points = [
+            FlowExitPoint(
+                node_id=step.node_id,
+                node_type=step.node_type,
+                label=step.label,
+                sessions=step.drop_offs,
+                share=_percentage(step.drop_offs, abandoned),
+            )
+            for step in sorted(nodes, key=lambda step: -step.drop_offs)
+            if step.drop_offs
+        ]
+
+        converted = (
+            await visits.get_sessions_reaching(flow_id, goal_node_id, organization_id, start_date, end_date)
+            if goal_node_id
+            else {session.session_id for session in sessions if session.completed}
+        )
+        splits = self._compare_splits(
+            flow, await visits.get_split_assignments(flow_id, organization_id, start_date, end_date), converted
+        )
+
+        return FlowFunnelMetrics(
+            flow_id=flow_id,
+            start_date=start_date,
+            end_date=end_date,
+            total_sessions=len(sessions),
+            completed_sessions=completed,
+            in_progress_sessions=in_progress,
+            abandoned_sessions=abandoned,
+            completion_rate=_percentage(completed, len(sessions)),
+            nodes=nodes,
+            top_exit_points=exit_points[:top_exit_points],
+            goal_node_id=goal_node_id,
+            splits=splits,
+        )
+
+    @staticmethod
+    def _compare_splits(flow: Flow, assignments: List[Any], converted: Set[Any]) -> List[FlowSplitComparison]:
+        """Sessions and conversions per variant of each split node"""
+        canvas_nodes = {
+            node.get("id"): node.get("data") or {} for node in (flow.canvas_data or {}).get("nodes", [])
+        }
+
+        counts: Dict[str, Dict[str, List[int]]] = {}
+        for row in assignments:
+            variant_counts = counts.setdefault(row.node_id, {}).setdefault(row.variant, [0, 0])
+            variant_counts[0] += 1
+            if row.session_id in converted:
+                variant_counts[1] += 1
+
+        splits = []
+        for node_id in sorted(counts):
+            data = canvas_nodes.get(node_id, {})
+            labels = {variant["id"]: variant["label"] for variant in split_variants(data)}
+            # Variants of the canvas first (also those without sessions yet), then removed ones
+            order = list(labels) + sorted(variant for variant in counts[node_id] if variant not in labels)
+            variants = []
+            for variant in order:
+                sessions, conversions = counts[node_id].get(variant, (0, 0))
+                variants.append(FlowSplitVariantMetrics(
+                    variant=variant,
+                    label=labels.get(variant),
+                    sessions=sessions,
+                    conversions=conversions,
+                    conversion_rate=_percentage(conversions, sessions),
+                ))
+            splits.append(FlowSplitComparison(node_id=node_id, label=data.get("label"), variants=variants))
+        return splits
+
+    # ============================================
+    # SURVEYS (NPS / CSAT)
+    # ============================================
+
+    async def get_survey_metrics(
+        self,
+        organization_id: UUID,
+        start_date: datetime,
+        end_date: datetime,
+        flow_id: Optional[UUID] = None,
+    ) -> SurveyMetrics:
+        """
+        Get NPS and CSAT collected by survey nodes in the period
+
+        Scores are given overall, per flow and per agent (the agent assigned
+        to the conversation when the contact answered).
+        """
+        responses = SurveyResponseRepository(self.db)
+        by_flow = await responses.get_score_counts(organization_id, start_date, end_date, "flow", flow_id)
+        by_agent = await responses.get_score_counts(organization_id, start_date, end_date, "agent", flow_id)
+
+        flow_counts = _survey_counts(by_flow)
+        agent_counts = _survey_counts(by_agent)
+
+        flow_names: Dict[Any, str] = {}
+        flow_ids = [key for key in flow_counts if key is not None]
+        if flow_ids:
+            result = await self.db.execute(select(Flow.id, Flow.name).where(Flow.id.in_(flow_ids)))
+            flow_names = {row.id: row.name for row in result.all()}
+
+        agent_names: Dict[Any, str] = {}
+        agent_ids = [key for key in agent_counts if key is not None]
+        if agent_ids:
+            result = await self.db.execute(select(User.id, User.full_name).where(User.id.in_(agent_ids)))
+            agent_names = {row.id: row.full_name for row in result.all()}
+
+        overall: Dict[str, Dict[int, int]] = {}
+        for counts in flow_counts.values():
+            for kind, scores in counts.items():
+                kind_counts = overall.setdefault(kind, {})
+                for score, count in scores.items():
+                    kind_counts[score] = kind_counts.get(score, 0) + count
+
+        flows = [
+            SurveyFlowScores(
+                flow_id=key, flow_name=flow_names.get(key), **_survey_scores(counts)
+            )
+            for key, counts in flow_counts.items()
+        ]
+        agents = [
+            SurveyAgentScores(
+                agent_id=key, agent_name=agent_names.get(key), **_survey_scores(counts)
+            )
+            for key, counts in agent_counts.items()
+        ]
+        flows.sort(key=_survey_responses, reverse=True)
+        agents.sort(key=_survey_responses, reverse=True)
+
+        return SurveyMetrics(
+            start_date=start_date,
+            end_date=end_date,
+            flows=flows,
+            agents=agents,
+            **_survey_scores(overall),
         )
 
     # ============================================
@@ -364,6 +587,9 @@ class AnalyticsService:
         contacts = await self.get_contact_metrics(organization_id, start_date, end_date)
         chatbots = await self.get_chatbot_metrics(organization_id)
         messages = await self.get_message_metrics(organization_id, start_date, end_date)
+        annotations = await AnnotationService(self.db).list_annotations(
+            organization_id, start_date, end_date
+        )
 
         return FullReport(
             period=period,
@@ -374,6 +600,7 @@ class AnalyticsService:
             contacts=contacts,
             chatbots=chatbots,
             messages=messages,
+            annotations=annotations,
         )
 
     # ============================================
@@ -564,3 +791,33 @@ class AnalyticsService:
             )
 
         return top_agents
+
+
+def _as_utc(value: datetime) -> datetime:
+    """Treat naive datetimes (SQLite) as UTC"""
+    return value.replace(tzinfo=timezone.utc) if value.tzinfo is None else value
+
+
+def _survey_counts(rows: List[Any]) -> Dict[Any, Dict[str, Dict[int, int]]]:
+    """Answers per score and survey type of each key (flow or agent)"""
+    counts: Dict[Any, Dict[str, Dict[int, int]]] = {}
+    for row in rows:
+        counts.setdefault(row.key, {}).setdefault(row.survey_type, {})[row.score] = row.responses
+    return counts
+
+
+def _survey_scores(counts: Dict[str, Dict[int, int]]) -> Dict[str, Optional[SurveyScore]]:
+    """nps and csat fields of a survey schema"""
+    scores: Dict[str, Optional[SurveyScore]] = {NPS: None, CSAT: None}
+    for kind in scores:
+        if counts.get(kind):
+            scores[kind] = SurveyScore(survey_type=kind, **asdict(summarize(kind, counts[kind])))
+    return scores
+
+
+def _survey_responses(item: Any) -> int:
+    return sum(score.responses for score in (item.nps, item.csat) if score)
+
+
+def _percentage(part: int, total: int) -> float:
+    return round(part / total * 100, 2) if total else 0.0
@@ -0,0 +1,186 @@
+"""
+Annotation Service - Events overlaid on dashboard and analytics charts
+
+Manual annotations are created through the API. The platform records the
+others automatically:
+- deploy: once per released version, at API startup (platform-wide)
+- template_change: template created, reviewed by Meta or deleted
+- campaign_launch: first dispatch of a campaign (manual or scheduled start)
+
+Automatic recording never breaks the operation that triggered it.
+"""
+
+import logging
+from datetime import datetime, timezone
+from typing import List, Optional, Sequence
+from uuid import UUID
+
+from sqlalchemy import and_, or_, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import ForbiddenException, NotFoundException
+from app.models.annotation import DashboardAnnotation
+from app.schemas.annotation import AnnotationCreate, AnnotationResponse
+
+logger = logging.getLogger(__name__)
+
+
+class AnnotationService:
+    """Service for dashboard annotations"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def list_annotations(
+        self,
+        organization_id: UUID,
+        start_date: datetime,
+        end_date: datetime,
+        kinds: Optional[Sequence[str]] = None,
+    ) -> List[DashboardAnnotation]:
+        """
+        Annotations of the organization (and platform-wide ones) in a period
+
+        Ranged annotations are included when they overlap the period.
+        """
+        query = (
+            select(DashboardAnnotation)
+            .where(
+                or_(
+                    DashboardAnnotation.organization_id == organization_id,
+                    DashboardAnnotation.organization_id.is_(None),
+                ),
+                DashboardAnnotation.occurred_at <= end_date,
+                or_(
+                    DashboardAnnotation.occurred_at >= start_date,
+                    and_(
+                        DashboardAnnotation.ends_at.is_not(None),
+                        DashboardAnnotation.ends_at >= start_date,
+                    ),
+                ),
+            )
+            .order_by(DashboardAnnotation.occurred_at)
+        )
+        if kinds:
+            query = query.where(DashboardAnnotation.kind.in_(list(kinds)))
+
+        result = await self.db.execute(query)
+        return list(result.scalars().all())
+
+    async def create_annotation(
+        self, organization_id: UUID, data: AnnotationCreate, user_id: Optional[UUID] = None
+    ) -> DashboardAnnotation:
+        """Create a manual annotation and push it to open dashboards"""
+        annotation = DashboardAnnotation(
+            organization_id=organization_id,
+            created_by_user_id=user_id,
+            kind=data.kind,
+            source="manual",
+            title=data.title,
+            description=data.description,
+            occurred_at=data.occurred_at or datetime.now(timezone.utc),
+            ends_at=data.ends_at,
+            extra_data=data.extra_data,
+        )
+        self.db.add(annotation)
+        await self.db.commit()
+        await self.db.refresh(annotation)
+
+        await self._broadcast(annotation)
+        return annotation
+
+    async def delete_annotation(self, annotation_id: UUID, organization_id: UUID) -> None:
+        """
+        Delete an annotation of the organization
+
+        Raises:
+            NotFoundException: If the annotation does not exist
+            ForbiddenException: For platform-wide annotations
+        """
+        annotation = await self.db.get(DashboardAnnotation, annotation_id)
+        if not annotation or annotation.organization_id not in (organization_id, None):
+            raise NotFoundException("Annotation not found")
+        if annotation.is_platform:
+            raise ForbiddenException("Platform annotations cannot be deleted")
+
+        await self.db.delete(annotation)
+        await self.db.commit()
+
+    # ============= Automatic annotations =============
+
+    async def record(
+        self,
+        kind: str,
+        title: str,
+        organization_id: Optional[UUID] = None,
+        description: Optional[str] = None,
+        resource_type: Optional[str] = None,
+        resource_id: Optional[UUID] = None,
+        occurred_at: Optional[datetime] = None,
+        extra_data: Optional[dict] = None,
+    ) -> Optional[DashboardAnnotation]:
+        """
+        Record an automatic annotation (commits; call after the caller's commit)
+
+        Returns:
+            The annotation, or None if it could not be stored
+        """
+        annotation = DashboardAnnotation(
+            organization_id=organization_id,
+            kind=kind,
+            source="auto",
+            title=title[:255],
+            description=description,
+            occurred_at=occurred_at or datetime.now(timezone.utc),
+            resource_type=resource_type,
+            resource_id=resource_id,
+            extra_data=extra_data or {},
+        )
+        try:
+            self.db.add(annotation)
+            await self.db.commit()
+        except Exception as e:
+            await self.db.rollback()
+            logger.warning(f"⚠️ Could not record {kind} annotation '{title}': {e}")
+            return None
+
+        await self._broadcast(annotation)
+        return annotation
+
+    async def record_deploy(self, version: str, revision: Optional[str] = None) -> Optional[DashboardAnnotation]:
+        """
+        Annotate a deploy once per version/revision (every API replica calls this at startup)
+        """
+        release = f"{version}+{revision}" if revision else version
+
+        existing = await self.db.execute(
+            select(DashboardAnnotation.id).where(
+                DashboardAnnotation.kind == "deploy",
+                DashboardAnnotation.organization_id.is_(None),
+                DashboardAnnotation.extra_data["release"].as_string() == release,
+            ).limit(1)
+        )
+        if existing.scalar_one_or_none():
+            return None
+
+        return await self.record(
+            "deploy",
+            f"Deploy {release}",
+            extra_data={"release": release, "version": version, "revision": revision},
+        )
+
+    async def _broadcast(self, annotation: DashboardAnnotation) -> None:
+        """Push the annotation to the organization's realtime dashboards"""
+        if annotation.is_platform:
+            return
+
+        from app.websocket.manager import emit_to_organization
+
+        try:
+            await emit_to_organization(
+                organization_id=str(annotation.organization_id),
+                event="annotation:new",
+                data=AnnotationResponse.model_validate(annotation).model_dump(mode="json"),
+            )
+        except Exception as e:
+            logger.warning(f"⚠️ Could not broadcast annotation {annotation.id}: {e}")
@@ -4,10 +4,11 @@ Handles user registration, login, token management
 """
 
 from datetime import datetime, timedelta
-from typing import Optional
+from typing import List, Optional
 from uuid import UUID
 
 from fastapi import HTTPException, status
+from sqlalchemy import select
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.core.config import settings
@@ -19,10 +20,10 @@ from app.core.security import (
     verify_password,
 )
 from app.models.organization import Organization
-from app.models.user import RefreshToken, User
+from app.models.user import RefreshToken, User, UserWorkspace
 from app.repositories.organization import OrganizationRepository
 from app.repositories.user import UserRepository
-from app.schemas.auth import Token, UserLogin, UserRegister
+from app.schemas.auth import Token, UserLogin, UserRegister, WorkspaceResponse
 from app.schemas.user import User as UserSchema
 
 
@@ -221,8 +222,11 @@ class AuthService:
                     detail="User is not active",
                 )
 
+            # Keep the workspace of the refresh token (membership re-checked)
+            workspace = await self._resolve_workspace(user, payload)
+
             # Generate new tokens
-            return await self._generate_tokens(user)
+            return await self._generate_tokens(user, workspace)
 
         except Exception as e:
             raise HTTPException(
@@ -243,20 +247,23 @@ class AuthService:
         # For now, we'll just let the token expire naturally
         pass
 
-    async def _generate_tokens(self, user: User) -> Token:
+    async def _generate_tokens(
+        self, user: User, workspace: Optional[UserWorkspace] = None
+    ) -> Token:
         """
         Generate access and refresh tokens for user
 
         Args:
             user: User model instance
+            workspace: Workspace the tokens are scoped to (None = home organization)
 
         Returns:
             Token response
         """
         # Additional claims for access token
         additional_claims = {
-            "organization_id": str(user.organization_id),
-            "role": user.role,
+            "organization_id": str(workspace.organization_id if workspace else user.organization_id),
+            "role": workspace.role if workspace else user.role,
         }
 
         # Generate tokens
@@ -264,7 +271,10 @@ class AuthService:
             subject=str(user.id),
             additional_claims=additional_claims,
         )
-        refresh_token = create_refresh_token(subject=str(user.id))
+        refresh_token = create_refresh_token(
+            subject=str(user.id),
+            additional_claims={"organization_id": additional_claims["organization_id"]},
+        )
 
         # Calculate expiration
         expires_in = settings.ACCESS_TOKEN_EXPIRE_MINUTES * 60  # Convert to seconds
@@ -316,6 +326,11 @@ class AuthService:
                     detail="User is not active",
                 )
 
+            # Tokens of another workspace scope organization_id/role to it
+            workspace = await self._resolve_workspace(user, payload)
+            if workspace:
+                user.apply_workspace(workspace)
+
             return user
 
         except Exception as e:
@@ -323,3 +338,126 @@ class AuthService:
                 status_code=status.HTTP_401_UNAUTHORIZED,
                 detail="Could not validate credentials",
             )
+
+    # ============================================
+    # WORKSPACES
+    # ============================================
+
+    async def _get_workspace(
+        self, user_id: UUID, organization_id: UUID
+    ) -> Optional[UserWorkspace]:
+        """Active membership of a user in a non-home organization"""
+        result = await self.db.execute(
+            select(UserWorkspace).where(
+                UserWorkspace.user_id == user_id,
+                UserWorkspace.organization_id == organization_id,
+                UserWorkspace.is_active.is_(True),
+            )
+        )
+        return result.scalar_one_or_none()
+
+    async def _resolve_workspace(
+        self, user: User, payload: dict
+    ) -> Optional[UserWorkspace]:
+        """
+        Workspace carried by a token (None = home organization)
+
+        Raises:
+            HTTPException: If the membership was removed or deactivated
+        """
+        claim = payload.get("organization_id")
+        if not claim or UUID(claim) == user.home_organization_id:
+            return None
+
+        workspace = await self._get_workspace(user.id, UUID(claim))
+        if not workspace:
+            raise HTTPException(
+                status_code=status.HTTP_403_FORBIDDEN,
+                detail="Workspace access revoked",
+            )
+        return workspace
+
+    async def list_workspaces(self, user: User) -> List[WorkspaceResponse]:
+        """
+        Organizations the user can switch to (home first)
+
+        Args:
+            user: Current user (organization_id is the active workspace)
+
+        Returns:
+            Workspaces with the user's role in each
+        """
+        home = await self.org_repo.get(user.home_organization_id)
+        home_role = user.role
+        if user.organization_id != user.home_organization_id:
+            # The instance carries the workspace role; the home role is still in the DB
+            home_role = (await self.db.execute(select(User.role).where(User.id == user.id))).scalar_one()
+
+        workspaces = [
+            WorkspaceResponse(
+                organization_id=home.id,
+                name=home.name,
+                slug=home.slug,
+                role=home_role,
+                is_home=True,
+                is_current=user.organization_id == home.id,
+            )
+        ]
+
+        result = await self.db.execute(
+            select(UserWorkspace, Organization)
+            .join(Organization, Organization.id == UserWorkspace.organization_id)
+            .where(
+                UserWorkspace.user_id == user.id,
+                UserWorkspace.is_active.is_(True),
+                Organization.is_active.is_(True),
+                Organization.deleted_at.is_(None),
+            )
+            .order_by(Organization.name)
+        )
+        for workspace, organization in result.all():
+            workspaces.append(
+                WorkspaceResponse(
+                    organization_id=organization.id,
+                    name=organization.name,
+                    slug=organization.slug,
+                    role=workspace.role,
+                    is_home=False,
+                    is_current=user.organization_id == organization.id,
+                    last_accessed_at=workspace.last_accessed_at,
+                )
+            )
+
+        return workspaces
+
+    async def switch_workspace(self, user: User, organization_id: UUID) -> Token:
+        """
+        Issue tokens scoped to another workspace of the user
+
+        Args:
+            user: Current user
+            organization_id: Target organization (home or a membership)
+
+        Returns:
+            New token pair (organization_id/role claims of the target)
+
+        Raises:
+            HTTPException: If the user is not a member of the organization
+        """
+        if organization_id == user.home_organization_id:
+            # Drop the per-request workspace scope before signing home tokens
+            await self.db.refresh(user)
+            return await self._generate_tokens(user)
+
+        workspace = await self._get_workspace(user.id, organization_id)
+        organization = await self.org_repo.get(organization_id) if workspace else None
+        if not workspace or not organization or not organization.is_active:
+            raise HTTPException(
+                status_code=status.HTTP_403_FORBIDDEN,
+                detail="You are not a member of this workspace",
+            )
+
+        workspace.last_accessed_at = datetime.utcnow()
+        await self.db.commit()
+
+        return await self._generate_tokens(user, workspace)
@@ -0,0 +1,299 @@
+"""
+Bulk template sender - one-off personalized template blasts from a CSV
+
+Sends an approved template to every row of a CSV without building a campaign.
+The first column is the recipient phone, the remaining columns are the
+template variables in order:
+
+    phone,var1,var2
+    5511999999999,Maria,20%
+    5511988888888,João,15%
+
+Columns whose name starts with ``header`` fill the header variables instead
+of the body. Sending reuses the campaign guards: per-number rate limiter,
+messaging tier shaper and contact opt-out/blocked checks. Every row ends as
+sent, failed, skipped or invalid in the results CSV returned to the caller.
+"""
+
+import asyncio
+import csv
+import io
+import logging
+from dataclasses import dataclass, field
+from typing import Any, Dict, List, Optional, Set
+from uuid import UUID
+
+from sqlalchemy import and_, or_, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.config import settings
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.core.whatsapp_rate_limit import get_tier_shaper, get_whatsapp_rate_limiter
+from app.integrations.meta_api import MetaAPIError, MetaCloudAPI
+from app.models.contact import Contact
+from app.models.whatsapp_number import WhatsAppNumber, WhatsAppTemplate
+from app.schemas.campaign import BulkTemplateSendRequest, TemplateValidationIssue
+from app.services.campaign_template_validator import CampaignTemplateValidator
+from app.utils.click_to_chat import normalize_wa_phone
+
+logger = logging.getLogger(__name__)
+
+RESULT_COLUMNS = ["line", "phone", "status", "message_id", "error"]
+
+
+@dataclass
+class BulkSendRow:
+    """One CSV recipient and its send outcome"""
+
+    line: int
+    phone: str
+    header: List[str] = field(default_factory=list)
+    body: List[str] = field(default_factory=list)
+    status: str = "pending"  # pending, sent, failed, skipped, invalid
+    message_id: Optional[str] = None
+    error: Optional[str] = None
+
+    def finish(self, status: str, error: Optional[str] = None, message_id: Optional[str] = None) -> None:
+        self.status = status
+        self.error = error
+        self.message_id = message_id
+
+
+def parse_recipients_csv(content: str, max_rows: Optional[int] = None) -> List[BulkSendRow]:
+    """
+    Parse the recipients CSV
+
+    Rows with an invalid or repeated phone are returned already finished
+    (invalid/skipped) so they show up in the results file.
+
+    Raises:
+        ValueError: If the CSV has no header, no rows or more than max_rows rows
+    """
+    reader = csv.reader(io.StringIO((content or "").lstrip("\ufeff")))
+    header = next(reader, None)
+    if not header or not header[0].strip():
+        raise ValueError("CSV must start with a header row (phone, var1..varN)")
+
+    header_columns = [
+        index for index, name in enumerate(header[1:], start=1)
+        if name.strip().lower().startswith("header")
+    ]
+
+    rows: List[BulkSendRow] = []
+    seen: Set[str] = set()
+    for line, values in enumerate(reader, start=2):
+        if not any(value.strip() for value in values):
+            continue
+
+        values = [value.strip() for value in values] + [""] * (len(header) - len(values))
+        row = BulkSendRow(
+            line=line,
+            phone=values[0],
+            header=[values[index] for index in header_columns],
+            body=[value for index, value in enumerate(values[1:len(header)], start=1) if index not in header_columns],
+        )
+        rows.append(row)
+
+        try:
+            row.phone = normalize_wa_phone(row.phone)
+        except ValueError:
+            row.finish("invalid", "invalid phone number")
+            continue
+        if row.phone in seen:
+            row.finish("skipped", "duplicate phone")
+        seen.add(row.phone)
+
+    if not rows:
+        raise ValueError("CSV has no recipients")
+    if max_rows and len(rows) > max_rows:
+        raise ValueError(f"CSV has {len(rows)} recipients (max {max_rows}) - use a campaign instead")
+    return rows
+
+
+def build_components(row: BulkSendRow) -> Optional[List[Dict[str, Any]]]:
+    """Template components with the row's variables"""
+    components = []
+    for section, values in (("header", row.header), ("body", row.body)):
+        if values:
+            components.append({
+                "type": section,
+                "parameters": [{"type": "text", "text": value} for value in values],
+            })
+    return components or None
+
+
+def results_csv(rows: List[BulkSendRow]) -> str:
+    """Results file: one line per CSV row"""
+    output = io.StringIO()
+    writer = csv.writer(output)
+    writer.writerow(RESULT_COLUMNS)
+    for row in rows:
+        writer.writerow([row.line, row.phone, row.status, row.message_id or "", row.error or ""])
+    return output.getvalue()
+
+
+class BulkTemplateSendService:
+    """Service for one-off template blasts"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def send(self, organization_id: UUID, data: BulkTemplateSendRequest) -> List[BulkSendRow]:
+        """
+        Send the template to every CSV row
+
+        Raises:
+            BadRequestException: Invalid CSV, non-official number or unusable template
+            NotFoundException: Number or template not found
+        """
+        try:
+            rows = parse_recipients_csv(data.recipients_csv, settings.BULK_SEND_MAX_ROWS)
+        except ValueError as e:
+            raise BadRequestException(str(e))
+
+        number = (await self.db.execute(
+            select(WhatsAppNumber).where(
+                WhatsAppNumber.id == data.whatsapp_number_id,
+                WhatsAppNumber.organization_id == organization_id,
+                WhatsAppNumber.deleted_at.is_(None),
+            )
+        )).scalar_one_or_none()
+        if not number:
+            raise NotFoundException("WhatsApp number not found")
+        if number.connection_type != "official":
+            raise BadRequestException("Template messages require an official (Cloud API) number")
+
+        template = await self._get_template(organization_id, number.id, data.template_name, data.language)
+        opted_out = await self._opted_out_phones(
+            organization_id, [row.phone for row in rows if row.status == "pending"]
+        )
+
+        logger.info(
+            f"📤 Bulk send of template '{template.name}' to {len(rows)} recipient(s) "
+            f"from number {number.id}"
+        )
+        await self._dispatch(
+            rows,
+            template,
+            opted_out,
+            MetaCloudAPI(phone_number_id=number.phone_number_id, access_token=number.access_token),
+            await get_whatsapp_rate_limiter(number.id, number.connection_type),
+            get_tier_shaper(number),
+        )
+
+        summary = {status: sum(1 for row in rows if row.status == status) for status in ("sent", "failed", "skipped", "invalid")}
+        logger.info(f"✅ Bulk send of template '{template.name}' finished: {summary}")
+        return rows
+
+    async def _get_template(
+        self,
+        organization_id: UUID,
+        whatsapp_number_id: UUID,
+        name: str,
+        language: Optional[str],
+    ) -> WhatsAppTemplate:
+        stmt = select(WhatsAppTemplate).where(
+            WhatsAppTemplate.organization_id == organization_id,
+            WhatsAppTemplate.whatsapp_number_id == whatsapp_number_id,
+            WhatsAppTemplate.name == name,
+            WhatsAppTemplate.deleted_at.is_(None),
+        )
+        if language:
+            stmt = stmt.where(WhatsAppTemplate.language == language)
+
+        template = (await self.db.execute(stmt.limit(1))).scalar_one_or_none()
+        if not template:
+            raise NotFoundException(f"Template '{name}' not found for this WhatsApp number")
+        if template.status != "APPROVED" or not template.is_enabled:
+            raise BadRequestException(f"Template '{name}' is not approved and enabled")
+        if (template.category or "").upper() == "AUTHENTICATION":
+            raise BadRequestException("Authentication templates cannot be bulk sent")
+        if template.carousel_cards:
+            raise BadRequestException("Carousel templates need values per card - send them with a campaign")
+        return template
+
+    async def _opted_out_phones(self, organization_id: UUID, phones: List[str]) -> Set[str]:
+        """Phones of contacts that opted out or are blocked"""
+        if not phones:
+            return set()
+        result = await self.db.execute(
+            select(Contact.whatsapp_id).where(
+                and_(
+                    Contact.organization_id == organization_id,
+                    Contact.whatsapp_id.in_(phones),
+                    Contact.deleted_at.is_(None),
+                    or_(Contact.opt_in.is_(False), Contact.is_blocked.is_(True)),
+                )
+            )
+        )
+        return set(result.scalars().all())
+
+    async def _dispatch(
+        self,
+        rows: List[BulkSendRow],
+        template: WhatsAppTemplate,
+        opted_out: Set[str],
+        api: MetaCloudAPI,
+        rate_limiter,
+        tier_shaper,
+    ) -> None:
+        """Send pending rows in order; stops (skipping the rest) when limits are exhausted"""
+        expected_header = (
+            (template.header_variables_count or 0) if (template.header_type or "").upper() == "TEXT" else 0
+        )
+        stop_reason: Optional[str] = None
+
+        for row in rows:
+            if row.status != "pending":
+                continue
+            if stop_reason:
+                row.finish("skipped", stop_reason)
+                continue
+
+            issues: List[TemplateValidationIssue] = []
+            CampaignTemplateValidator._check_parameters("header", expected_header, row.header, issues)
+            CampaignTemplateValidator._check_parameters("body", template.body_variables_count or 0, row.body, issues)
+            if issues:
+                row.finish("invalid", "; ".join(issue.message for issue in issues))
+                continue
+
+            if row.phone in opted_out:
+                row.finish("skipped", "opted out")
+                continue
+
+            tier = await tier_shaper.reserve(row.phone) if tier_shaper else None
+            if tier and not tier.allowed:
+                # One-off blasts are not deferred: give the queued slot back
+                if tier.reserved:
+                    await tier_shaper.release(row.phone)
+                stop_reason = f"messaging tier {tier.tier} full until {tier.send_at.isoformat()}"
+                row.finish("skipped", stop_reason)
+                continue
+
+            wait_time = await rate_limiter.wait_if_needed()
+            if wait_time > settings.BULK_SEND_MAX_WAIT_SECONDS:
+                stop_reason = f"rate limit reached, retry in {wait_time / 60:.0f} min"
+                if tier and tier.reserved:
+                    await tier_shaper.release(row.phone)
+                row.finish("skipped", stop_reason)
+                continue
+            if wait_time:
+                logger.info(f"⏳ Bulk send waiting {wait_time}s for rate limit...")
+                await asyncio.sleep(wait_time)
+
+            try:
+                response = await api.send_template_message(
+                    to=row.phone,
+                    template_name=template.name,
+                    language_code=template.language,
+                    components=build_components(row),
+                )
+            except MetaAPIError as e:
+                if tier and tier.reserved:
+                    await tier_shaper.release(row.phone)
+                row.finish("failed", e.message)
+                logger.warning(f"⚠️ Bulk send to {row.phone} failed: {e.message}")
+                continue
+
+            await rate_limiter.record_message_sent()
+            row.finish("sent", message_id=(response.get("messages") or [{}])[0].get("id"))
@@ -2,27 +2,46 @@
 Campaign service - Business logic for bulk messaging campaigns
 """
 
-from datetime import datetime
+from datetime import datetime, timezone
 from typing import List, Optional, Tuple
 from uuid import UUID
 
-from sqlalchemy import select
+from sqlalchemy import or_, select, true
 from sqlalchemy.ext.asyncio import AsyncSession
 
+from app.core.config import settings
 from app.core.exceptions import BadRequestException, NotFoundException
 from app.models.campaign import Campaign
 from app.models.contact import Contact
+from app.models.whatsapp_number import WhatsAppNumber, WhatsAppTemplate
 from app.repositories.campaign import CampaignRepository
 from app.repositories.contact import ContactRepository
+from app.repositories.contact_segment import audience_conditions, compile_condition
 from app.schemas.campaign import (
     AudiencePreview,
+    CampaignABTestResults,
+    CampaignABVariantResult,
     CampaignCreate,
+    CampaignDryRunMessage,
+    CampaignDryRunReport,
+    CampaignDryRunVariable,
+    CampaignPreflightReport,
     CampaignProgress,
     CampaignScheduleResponse,
     CampaignStartResponse,
     CampaignStats,
     CampaignUpdate,
 )
+from app.utils.campaign_ab_test import contact_variant, variant_results
+from app.utils.campaign_personalization import (
+    component_parameters,
+    find_variables,
+    personalize,
+    render_template_text,
+    variable_field,
+)
+from app.utils.campaign_recurrence import next_occurrence
+from app.utils.contact_segment import SegmentCondition, SegmentError
 
 
 class CampaignService:
@@ -56,6 +75,9 @@ class CampaignService:
             "organization_id": organization_id,
             "created_by_user_id": user_id,
             "status": "draft",
+            "recurrence": data.recurrence.model_dump(mode="json") if data.recurrence else None,
+            "throttle": data.throttle.model_dump(exclude_none=True) if data.throttle else {},
+            "ab_test": self._ab_test_data(data.ab_test),
         }
 
         # Calculate total recipients
@@ -65,6 +87,7 @@ class CampaignService:
             data.target_tag_ids,
             data.target_contact_ids,
             data.segment_filters,
+            respect_opt_out=data.respect_opt_out,
         )
         campaign_data["total_recipients"] = total_recipients
         campaign_data["messages_pending"] = total_recipients
@@ -117,6 +140,36 @@ class CampaignService:
         total = await self.campaign_repo.count_by_organization(organization_id, status)
         return campaigns, total
 
+    async def list_occurrences(
+        self,
+        campaign_id: UUID,
+        organization_id: UUID,
+        skip: int = 0,
+        limit: int = 100,
+    ) -> Tuple[List[Campaign], int]:
+        """
+        List the occurrences created from a recurring campaign
+
+        Args:
+            campaign_id: Parent campaign UUID
+            organization_id: Organization UUID
+            skip: Records to skip
+            limit: Max records
+
+        Returns:
+            Tuple of (occurrences, total_count), latest first
+
+        Raises:
+            NotFoundException: If campaign not found
+        """
+        campaign = await self.get_campaign(campaign_id, organization_id)
+        if not campaign:
+            raise NotFoundException("Campaign not found")
+
+        occurrences = await self.campaign_repo.get_occurrences(campaign_id, skip, limit)
+        total = await self.campaign_repo.count_occurrences(campaign_id)
+        return occurrences, total
+
     async def update_campaign(
         self, campaign_id: UUID, organization_id: UUID, data: CampaignUpdate
     ) -> Campaign:
@@ -139,14 +192,39 @@ class CampaignService:
         if not campaign:
             raise NotFoundException("Campaign not found")
 
-        # Can only edit draft or scheduled campaigns
-        if campaign.status not in ["draft", "scheduled"]:
+        # Can only edit draft or scheduled campaigns (and recurring ones, for future occurrences)
+        if campaign.status not in ["draft", "scheduled", "recurring"]:
             raise BadRequestException(
                 f"Cannot edit campaign with status '{campaign.status}'"
             )
 
         update_data = data.model_dump(exclude_unset=True)
 
+        if "throttle" in update_data:
+            update_data["throttle"] = (
+                data.throttle.model_dump(exclude_none=True) if data.throttle else {}
+            )
+
+        if "ab_test" in update_data:
+            update_data["ab_test"] = self._ab_test_data(data.ab_test)
+
+        if "recurrence" in update_data:
+            update_data["recurrence"] = (
+                data.recurrence.model_dump(mode="json") if data.recurrence else None
+            )
+            if campaign.status == "recurring":
+                if not data.recurrence:
+                    raise BadRequestException(
+                        "Cancel the recurring campaign to stop its occurrences"
+                    )
+                next_at = next_occurrence(
+                    update_data["recurrence"], campaign.scheduled_at, datetime.utcnow()
+                )
+                update_data["next_occurrence_at"] = next_at
+                if next_at is None:
+                    update_data["status"] = "completed"
+                    update_data["completed_at"] = datetime.utcnow()
+
         # Recalculate recipients if audience changed
         if any(
             key in update_data
@@ -155,6 +233,7 @@ class CampaignService:
                 "target_tag_ids",
                 "target_contact_ids",
                 "segment_filters",
+                "respect_opt_out",
             ]
         ):
             total_recipients = await self._calculate_recipients(
@@ -163,6 +242,7 @@ class CampaignService:
                 update_data.get("target_tag_ids", campaign.target_tag_ids),
                 update_data.get("target_contact_ids", campaign.target_contact_ids),
                 update_data.get("segment_filters", campaign.segment_filters),
+                respect_opt_out=update_data.get("respect_opt_out", campaign.respect_opt_out),
             )
             update_data["total_recipients"] = total_recipients
             update_data["messages_pending"] = total_recipients
@@ -170,6 +250,13 @@ class CampaignService:
         updated_campaign = await self.campaign_repo.update(campaign_id, update_data)
         return updated_campaign
 
+    @staticmethod
+    def _ab_test_data(ab_test) -> Optional[dict]:
+        """A/B test config as stored (the winner is only set by the evaluation)"""
+        if not ab_test:
+            return None
+        return ab_test.model_dump(mode="json", exclude_none=True, exclude={"winner", "test_completed_at"})
+
     async def delete_campaign(self, campaign_id: UUID, organization_id: UUID):
         """
         Soft delete campaign
@@ -228,6 +315,30 @@ class CampaignService:
         if campaign.total_recipients == 0:
             raise BadRequestException("Campaign has no recipients")
 
+        await self._ensure_templates_valid(campaign)
+
+        if campaign.recurrence:
+            # Parent of the occurrences: materialize_recurring_campaigns creates them
+            next_at = next_occurrence(campaign.recurrence, scheduled_at, datetime.utcnow())
+            if next_at is None:
+                raise BadRequestException("Recurrence ends before its first occurrence")
+
+            await self.campaign_repo.update(
+                campaign_id,
+                {
+                    "status": "recurring",
+                    "scheduled_at": scheduled_at,
+                    "next_occurrence_at": next_at,
+                },
+            )
+            return CampaignScheduleResponse(
+                campaign_id=campaign_id,
+                scheduled_at=next_at,
+                total_recipients=campaign.total_recipients,
+                estimated_cost=campaign.estimated_cost,
+                message=f"Recurring campaign scheduled, first occurrence at {next_at.isoformat()}",
+            )
+
         await self.campaign_repo.update(
             campaign_id, {"status": "scheduled", "scheduled_at": scheduled_at}
         )
@@ -269,14 +380,17 @@ class CampaignService:
         if campaign.total_recipients == 0:
             raise BadRequestException("Campaign has no recipients")
 
+        await self._ensure_templates_valid(campaign)
+
         # Import here to avoid circular imports
         from app.tasks.campaign_tasks import execute_campaign
         
         # Update campaign status to running
         updated_campaign = await self.campaign_repo.start_campaign(campaign_id)
         
-        # Trigger Celery task for campaign execution
-        task = execute_campaign.delay(str(campaign_id))
+        # Trigger Celery task for campaign execution (on the tenant's region queue)
+        queue = await self._campaign_queue(organization_id)
+        task = execute_campaign.apply_async(args=[str(campaign_id)], queue=queue)
 
         return CampaignStartResponse(
             campaign_id=campaign_id,
@@ -286,6 +400,58 @@ class CampaignService:
             message=f"Campaign started successfully. Task ID: {task.id}",
         )
 
+    async def preflight_campaign(
+        self, campaign_id: UUID, organization_id: UUID
+    ) -> CampaignPreflightReport:
+        """
+        Validate campaign templates against the synced WABA definitions
+
+        Args:
+            campaign_id: Campaign UUID
+            organization_id: Organization UUID
+
+        Returns:
+            Pre-flight report with errors and warnings per template
+
+        Raises:
+            NotFoundException: If campaign not found
+        """
+        from app.services.campaign_template_validator import CampaignTemplateValidator
+
+        campaign = await self.get_campaign(campaign_id, organization_id)
+        if not campaign:
+            raise NotFoundException("Campaign not found")
+
+        return await CampaignTemplateValidator(self.db).validate(campaign)
+
+    async def _ensure_templates_valid(self, campaign: Campaign) -> None:
+        """Raise BadRequestException listing every template error of the campaign"""
+        from app.services.campaign_template_validator import CampaignTemplateValidator
+
+        report = await CampaignTemplateValidator(self.db).validate(campaign)
+        if report.is_valid:
+            return
+
+        problems = [
+            f"{result.name or result.template_id}: {issue.message}"
+            for result in report.templates
+            for issue in result.errors
+        ]
+        problems.extend(issue.message for issue in report.message_errors)
+        raise BadRequestException(
+            "Campaign templates failed pre-flight validation: " + "; ".join(problems)
+        )
+
+    async def _campaign_queue(self, organization_id: UUID) -> str:
+        """Region-tagged campaigns queue for the organization"""
+        from app.core.region_router import region_router
+        from app.models.organization import Organization
+
+        home_region = await self.db.scalar(
+            select(Organization.home_region).where(Organization.id == organization_id)
+        )
+        return await region_router.queue_for("campaigns", organization_id, home_region)
+
     async def pause_campaign(self, campaign_id: UUID, organization_id: UUID) -> Campaign:
         """
         Pause running campaign
@@ -332,7 +498,15 @@ class CampaignService:
         if campaign.status != "paused":
             raise BadRequestException("Can only resume paused campaigns")
 
-        return await self.campaign_repo.resume_campaign(campaign_id)
+        from app.tasks.campaign_tasks import execute_campaign
+
+        updated_campaign = await self.campaign_repo.resume_campaign(campaign_id)
+
+        # Dispatcher picks up from the persisted checkpoint
+        queue = await self._campaign_queue(organization_id)
+        execute_campaign.apply_async(args=[str(campaign_id)], queue=queue)
+
+        return updated_campaign
 
     async def cancel_campaign(self, campaign_id: UUID, organization_id: UUID) -> Campaign:
         """
@@ -363,6 +537,49 @@ class CampaignService:
     # STATS & PROGRESS
     # ============================================
 
+    async def get_ab_test_results(
+        self, campaign_id: UUID, organization_id: UUID
+    ) -> CampaignABTestResults:
+        """
+        Get sends, engagement and confidence intervals per A/B test variant
+
+        Args:
+            campaign_id: Campaign UUID
+            organization_id: Organization UUID
+
+        Returns:
+            Results per variant and the winner, once declared
+
+        Raises:
+            NotFoundException: If campaign not found
+            BadRequestException: If the campaign has no A/B test
+        """
+        campaign = await self.get_campaign(campaign_id, organization_id)
+        if not campaign:
+            raise NotFoundException("Campaign not found")
+        if not campaign.ab_test:
+            raise BadRequestException("Campaign has no A/B test")
+
+        config = campaign.ab_test
+        variants = [
+            CampaignABVariantResult(
+                **{key: row[key] for key in ("variant", "label", "sent", "delivered", "read", "replied", "clicked", "conversions")},
+                rate=round(row["rate"] * 100, 2),
+                ci_low=round(row["ci_low"] * 100, 2),
+                ci_high=round(row["ci_high"] * 100, 2),
+            )
+            for row in variant_results(config, campaign.message_statuses or {})
+        ]
+        return CampaignABTestResults(
+            campaign_id=campaign.id,
+            status=campaign.status,
+            metric=config.get("metric") or "read",
+            confidence_level=float(config.get("confidence_level") or 0.95),
+            min_sample_size=int(config.get("min_sample_size") or 0),
+            winner=config.get("winner"),
+            variants=variants,
+        )
+
     async def get_campaign_stats(
         self, campaign_id: UUID, organization_id: UUID
     ) -> CampaignStats:
@@ -457,6 +674,7 @@ class CampaignService:
             messages_failed=campaign.messages_failed,
             progress_percentage=progress_percentage,
             estimated_completion_time=estimated_completion_time,
+            deferred_until=(campaign.dispatch_checkpoint or {}).get("deferred_until"),
         )
 
     async def preview_audience(
@@ -479,15 +697,17 @@ class CampaignService:
         if not campaign:
             raise NotFoundException("Campaign not found")
 
-        # Get sample contacts (first 10)
-        contacts = await self._get_target_contacts(
+        # Audience size and a sample of its contacts (first 10)
+        conditions = self._audience_conditions(
             organization_id,
             campaign.audience_type,
             campaign.target_tag_ids,
             campaign.target_contact_ids,
             campaign.segment_filters,
-            limit=10,
+            respect_opt_out=campaign.respect_opt_out,
         )
+        total_contacts = await self.contact_repo.count_audience(conditions)
+        contacts = await self.contact_repo.get_audience_page(conditions, limit=10)
 
         sample_contacts = [
             {
@@ -500,7 +720,7 @@ class CampaignService:
         ]
 
         return AudiencePreview(
-            total_contacts=campaign.total_recipients,
+            total_contacts=total_contacts,
             sample_contacts=sample_contacts,
             filters_applied={
                 "audience_type": campaign.audience_type,
@@ -509,10 +729,217 @@ class CampaignService:
             },
         )
 
+    async def dry_run_campaign(
+        self, campaign_id: UUID, organization_id: UUID, sample_size: int = 5
+    ) -> CampaignDryRunReport:
+        """
+        Render the campaign for a sample of its audience without sending
+
+        Reports the personalized messages of the sample, the recipients
+        missing a contact variable, the estimated cost and how long the
+        sending takes under the campaign limits.
+
+        Args:
+            campaign_id: Campaign UUID
+            organization_id: Organization UUID
+            sample_size: Number of sample messages (capped by CAMPAIGN_DRY_RUN_MAX_SAMPLES)
+
+        Returns:
+            Dry-run report
+
+        Raises:
+            NotFoundException: If campaign not found
+        """
+        from app.core.campaign_throttle import estimate_send_duration
+        from app.services.campaign_template_validator import CampaignTemplateValidator
+
+        campaign = await self.get_campaign(campaign_id, organization_id)
+        if not campaign:
+            raise NotFoundException("Campaign not found")
+
+        now = datetime.now(timezone.utc)
+        warnings: List[str] = []
+        conditions = self._audience_conditions(
+            organization_id,
+            campaign.audience_type,
+            campaign.target_tag_ids,
+            campaign.target_contact_ids,
+            campaign.segment_filters,
+            respect_opt_out=campaign.respect_opt_out,
+        )
+        total = await self.contact_repo.count_audience(conditions)
+        contacts = await self.contact_repo.get_audience_page(
+            conditions, limit=min(sample_size, settings.CAMPAIGN_DRY_RUN_MAX_SAMPLES)
+        )
+        if not total:
+            warnings.append("The campaign audience is empty")
+
+        whatsapp_number = None
+        if campaign.whatsapp_number_id:
+            whatsapp_number = await self.db.get(WhatsAppNumber, campaign.whatsapp_number_id)
+        if not whatsapp_number:
+            warnings.append("The campaign has no WhatsApp number")
+
+        samples = [
+            await self._dry_run_message(campaign, contact, whatsapp_number)
+            for contact in contacts
+        ]
+
+        # Recipients without a value for each contact variable
+        variables = []
+        missing_clauses = []
+        for variable in sorted(self._campaign_variables(campaign)):
+            field = variable_field(variable)
+            clause = true() if field is None else compile_condition(SegmentCondition(field, "is_empty"), now)
+            missing_clauses.append(clause)
+            variables.append(CampaignDryRunVariable(
+                variable=variable,
+                known=field is not None,
+                contacts_missing=total if field is None else await self.contact_repo.count_audience(conditions + [clause]),
+            ))
+            if field is None:
+                warnings.append(f"Unknown variable {{{{contact.{variable}}}}} renders empty")
+        contacts_with_missing = 0
+        if missing_clauses and total:
+            contacts_with_missing = await self.contact_repo.count_audience(conditions + [or_(*missing_clauses)])
+
+        # Cost: conversations are billed per template category on the official API
+        pricing_category = None
+        estimated_cost: Optional[float] = 0.0
+        if campaign.message_type != "template":
+            pricing_category = "service"
+            warnings.append("Text messages only reach contacts inside the 24h customer service window")
+        elif whatsapp_number and whatsapp_number.connection_type != "official":
+            warnings.append("QR code numbers are not billed per message")
+        else:
+            template = await self._campaign_template(campaign)
+            pricing_category = (template.category or "").lower() if template else None
+            price = settings.CAMPAIGN_MESSAGE_PRICES.get(pricing_category) if pricing_category else None
+            if price is None:
+                estimated_cost = None
+                warnings.append("No price for the template category, cost not estimated")
+            else:
+                estimated_cost = round(total * price, 4)
+
+        # Duration under the throttle limits and business hours
+        start = max(campaign.scheduled_at or now, now)
+        duration = estimate_send_duration(campaign, total, start)
+        if duration is None:
+            warnings.append("The campaign limits would take over a year to reach the audience")
+
+        preflight = await CampaignTemplateValidator(self.db).validate(campaign)
+        if not preflight.is_valid:
+            warnings.append("The campaign fails pre-flight validation")
+
+        return CampaignDryRunReport(
+            campaign_id=campaign.id,
+            total_recipients=total,
+            samples=samples,
+            variables=variables,
+            contacts_with_missing_variables=contacts_with_missing,
+            pricing_category=pricing_category,
+            estimated_cost=estimated_cost,
+            currency=settings.CAMPAIGN_PRICE_CURRENCY,
+            estimated_duration_seconds=duration.total_seconds() if duration is not None else None,
+            estimated_completion_at=start + duration if duration is not None else None,
+            preflight=preflight,
+            warnings=warnings,
+        )
+
     # ============================================
     # HELPER METHODS
     # ============================================
 
+    async def _dry_run_message(
+        self,
+        campaign: Campaign,
+        contact: Contact,
+        whatsapp_number: Optional[WhatsAppNumber],
+    ) -> CampaignDryRunMessage:
+        """Message a contact would receive (rendered as send_campaign_message builds it)"""
+        from app.tasks.campaign_retry import resolve_campaign_template
+
+        message = CampaignDryRunMessage(
+            contact_id=contact.id,
+            name=contact.name,
+            whatsapp_id=contact.whatsapp_id,
+            message_type=campaign.message_type,
+        )
+        variant = None
+        if campaign.ab_test:
+            variant = contact_variant(campaign.ab_test, campaign.id, contact.id)
+            if variant is None:
+                message.error = "Waits for the A/B test winner"
+                return message
+            message.variant = variant["id"]
+
+        if campaign.message_type == "template":
+            if not whatsapp_number:
+                message.error = "No WhatsApp number to resolve the template"
+                return message
+            name, language, components = await resolve_campaign_template(
+                self.db, campaign, contact, whatsapp_number, variant
+            )
+            if not name:
+                message.error = "Template not found"
+                return message
+            variables = (variant or {}).get("template_variables") or campaign.template_variables or {}
+            _, message.missing_variables = personalize(variables, contact)
+            message.template_name, message.language, message.components = name, language, components
+
+            template = await self.db.scalar(
+                select(WhatsAppTemplate).where(
+                    WhatsAppTemplate.organization_id == campaign.organization_id,
+                    WhatsAppTemplate.whatsapp_number_id == whatsapp_number.id,
+                    WhatsAppTemplate.name == name,
+                    WhatsAppTemplate.language == language,
+                    WhatsAppTemplate.deleted_at.is_(None),
+                ).limit(1)
+            )
+            if template:
+                parts = [
+                    render_template_text(template.header_text, component_parameters(components, "header")),
+                    render_template_text(template.body_text, component_parameters(components, "body")),
+                    template.footer_text,
+                ]
+                message.text = "\n\n".join(part for part in parts if part)
+            return message
+
+        text = (variant or {}).get("text") or (campaign.message_content or {}).get("text")
+        if not text:
+            message.error = "Campaign has no message text"
+            return message
+        message.text, message.missing_variables = personalize(text, contact)
+        return message
+
+    async def _campaign_template(self, campaign: Campaign) -> Optional[WhatsAppTemplate]:
+        """Template of a template campaign (by id, or by name and language)"""
+        content = campaign.message_content or {}
+        stmt = select(WhatsAppTemplate).where(
+            WhatsAppTemplate.organization_id == campaign.organization_id,
+            WhatsAppTemplate.deleted_at.is_(None),
+        )
+        if campaign.template_id:
+            stmt = stmt.where(WhatsAppTemplate.id == campaign.template_id)
+        elif content.get("name"):
+            stmt = stmt.where(WhatsAppTemplate.name == content["name"])
+            if content.get("language"):
+                stmt = stmt.where(WhatsAppTemplate.language == content["language"])
+        else:
+            return None
+        return await self.db.scalar(stmt.limit(1))
+
+    @staticmethod
+    def _campaign_variables(campaign: Campaign) -> set:
+        """Contact variables referenced by the campaign and its A/B variants"""
+        variants = (campaign.ab_test or {}).get("variants") or []
+        return find_variables([
+            (campaign.message_content or {}).get("text"),
+            campaign.template_variables,
+            [variant.get("text") for variant in variants],
+            [variant.get("template_variables") for variant in variants],
+        ])
+
     async def _calculate_recipients(
         self,
         organization_id: UUID,
@@ -520,63 +947,60 @@ class CampaignService:
         target_tag_ids: List[UUID],
         target_contact_ids: List[UUID],
         segment_filters: dict,
+        respect_opt_out: bool = True,
     ) -> int:
         """Calculate total number of recipients"""
-        if audience_type == "custom_list":
-            return len(target_contact_ids)
-
-        contacts = await self._get_target_contacts(
-            organization_id,
-            audience_type,
-            target_tag_ids,
-            target_contact_ids,
-            segment_filters,
+        return await self.contact_repo.count_audience(
+            self._audience_conditions(
+                organization_id,
+                audience_type,
+                target_tag_ids,
+                target_contact_ids,
+                segment_filters,
+                respect_opt_out=respect_opt_out,
+            )
         )
-        return len(contacts)
 
-    async def _get_target_contacts(
+    async def get_audience_contacts(
         self,
         organization_id: UUID,
         audience_type: str,
         target_tag_ids: List[UUID],
         target_contact_ids: List[UUID],
         segment_filters: dict,
-        limit: Optional[int] = None,
     ) -> List[Contact]:
-        """Get target contacts based on audience configuration"""
-        if audience_type == "all_contacts":
-            return await self.contact_repo.get_by_organization(
-                organization_id, limit=limit or 10000
-            )
+        """Contacts of an audience definition (shared with ad audience sync)"""
+        return await self.contact_repo.get_audience_page(
+            self._audience_conditions(
+                organization_id,
+                audience_type,
+                target_tag_ids,
+                target_contact_ids,
+                segment_filters,
+            ),
+            limit=None,
+        )
 
-        elif audience_type == "tags":
-            # Get contacts with any of the target tags
-            contacts = []
-            for tag_id in target_tag_ids:
-                tag_contacts = await self.contact_repo.get_by_tag(
-                    tag_id, organization_id
-                )
-                contacts.extend(tag_contacts)
-            # Remove duplicates
-            unique_contacts = {c.id: c for c in contacts}.values()
-            result = list(unique_contacts)
-            if limit:
-                result = result[:limit]
-            return result
-
-        elif audience_type == "custom_list":
-            contacts = []
-            for contact_id in target_contact_ids:
-                contact = await self.contact_repo.get(contact_id)
-                if contact and contact.organization_id == organization_id:
-                    contacts.append(contact)
-            if limit:
-                contacts = contacts[:limit]
-            return contacts
-
-        elif audience_type == "segment":
-            # TODO: Implement segment filtering based on segment_filters
-            # This would require complex query building
-            return []
+    @staticmethod
+    def _audience_conditions(
+        organization_id: UUID,
+        audience_type: str,
+        target_tag_ids: List[UUID],
+        target_contact_ids: List[UUID],
+        segment_filters: dict,
+        respect_opt_out: bool = False,
+    ) -> list:
+        """SQL clauses of an audience (see app.repositories.contact_segment)"""
+        try:
+            return audience_conditions(
+                organization_id,
+                audience_type,
+                target_tag_ids,
+                target_contact_ids,
+                segment_filters,
+                respect_opt_out=respect_opt_out,
+            )
+        except SegmentError as e:
+            raise BadRequestException(f"Invalid segment filters: {e}")
 
         return []
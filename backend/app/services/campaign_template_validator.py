@@ -0,0 +1,435 @@
+"""
+Campaign template pre-flight validation
+
+Validates every template attached to a campaign against the WABA template
+definitions synced from Meta before the campaign can be scheduled or started,
+so problems surface as actionable errors instead of thousands of failed sends:
+
+- Template synced, approved, enabled and owned by the campaign's number
+- Requested language available (approved) for the template name
+- Header/body parameter count and type match the template placeholders
+- Media headers (IMAGE, VIDEO, DOCUMENT) have an HTTPS media URL
+- Carousel templates: one entry per card, each with its header media, body
+  parameters and button values
+- Coupon code of COPY_CODE buttons and expiration of limited-time offers
+- Category appropriate for the audience consent (marketing vs utility)
+- Non-template content (text, captions) within WhatsApp length limits
+
+Template parameters are read from ``Campaign.template_variables``:
+    {"header": ["..."], "body": ["...", "..."], "header_media_url": "https://..."}
+or the flat form used by template previews (body only):
+    {"1": "...", "2": "..."}
+Carousel templates add the values of each card (see app.utils.template_carousel):
+    {"body": [...], "cards": [{"header_url": "https://...", "body": [...], "buttons": [{"payload": "..."}]}]}
+and promotional templates their coupon code and offer expiration (see app.utils.template_components):
+    {"body": [...], "coupon_code": "BLACK20", "offer_expires_at": "2025-11-30T23:59:00-03:00"}
+"""
+
+from datetime import datetime, timezone
+from typing import Any, Dict, List, Optional, Tuple
+from uuid import UUID
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.models.campaign import Campaign
+from app.models.whatsapp_number import WhatsAppTemplate
+from app.schemas.campaign import (
+    CampaignPreflightReport,
+    TemplateValidationIssue,
+    TemplateValidationResult,
+)
+from app.utils.message_limits import validate_outbound_message
+from app.utils.template_builder import INVALID_PARAMETER_PATTERN
+from app.utils.template_components import CopyCodeButton, LimitedTimeOffer, copy_code_button_index
+
+MEDIA_HEADER_TYPES = {"IMAGE", "VIDEO", "DOCUMENT"}
+
+
+class CampaignTemplateValidator:
+    """Pre-flight validator for campaign templates"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def validate(self, campaign: Campaign) -> CampaignPreflightReport:
+        """
+        Validate all templates attached to a campaign
+
+        Args:
+            campaign: Campaign to validate
+
+        Returns:
+            Report with errors and warnings per template
+        """
+        results = [
+            await self._validate_template(campaign, template_id, name, language)
+            for template_id, name, language in self._attached_templates(campaign)
+        ]
+        message_errors = self._validate_message_content(campaign)
+
+        return CampaignPreflightReport(
+            campaign_id=campaign.id,
+            is_valid=all(result.is_valid for result in results) and not message_errors,
+            templates=results,
+            message_errors=message_errors,
+        )
+
+    # ============================================
+    # HELPERS
+    # ============================================
+
+    @staticmethod
+    def _validate_message_content(campaign: Campaign) -> List[TemplateValidationIssue]:
+        """Length limits of non-template content (grapheme-aware)"""
+        if campaign.message_type == "template":
+            return []
+
+        return [
+            TemplateValidationIssue(
+                code="message_limit_exceeded",
+                field=f"message_content.{issue.field}",
+                message=str(issue),
+            )
+            for issue in validate_outbound_message(campaign.message_type, campaign.message_content or {})
+        ]
+
+    @staticmethod
+    def _attached_templates(
+        campaign: Campaign,
+    ) -> List[Tuple[Optional[UUID], Optional[str], Optional[str]]]:
+        """(template_id, name, language) of templates used by the campaign"""
+        content = campaign.message_content or {}
+        language = content.get("language")
+
+        if campaign.template_id:
+            return [(campaign.template_id, None, language)]
+
+        if campaign.message_type == "template":
+            return [(None, content.get("name"), language)]
+
+        return []
+
+    async def _find_template(
+        self,
+        organization_id: UUID,
+        template_id: Optional[UUID],
+        name: Optional[str],
+        language: Optional[str],
+    ) -> Optional[WhatsAppTemplate]:
+        stmt = select(WhatsAppTemplate).where(
+            WhatsAppTemplate.organization_id == organization_id,
+            WhatsAppTemplate.deleted_at.is_(None),
+        )
+        if template_id:
+            stmt = stmt.where(WhatsAppTemplate.id == template_id)
+        else:
+            stmt = stmt.where(WhatsAppTemplate.name == name)
+            if language:
+                stmt = stmt.where(WhatsAppTemplate.language == language)
+
+        result = await self.db.execute(stmt.limit(1))
+        return result.scalar_one_or_none()
+
+    async def _approved_languages(self, organization_id: UUID, name: str) -> List[str]:
+        stmt = select(WhatsAppTemplate.language).where(
+            WhatsAppTemplate.organization_id == organization_id,
+            WhatsAppTemplate.name == name,
+            WhatsAppTemplate.status == "APPROVED",
+            WhatsAppTemplate.deleted_at.is_(None),
+        )
+        result = await self.db.execute(stmt)
+        return sorted(set(result.scalars().all()))
+
+    @staticmethod
+    def _split_parameters(variables: Dict[str, Any]) -> Tuple[List[Any], List[Any], Optional[str]]:
+        """Header parameters, body parameters and header media URL"""
+        variables = variables or {}
+        media_url = variables.get("header_media_url")
+
+        if "body" in variables or "header" in variables:
+            return list(variables.get("header") or []), list(variables.get("body") or []), media_url
+
+        positional = sorted(
+            (key for key in variables if str(key).isdigit()), key=lambda key: int(key)
+        )
+        return [], [variables[key] for key in positional], media_url
+
+    @staticmethod
+    def _check_parameters(
+        section: str, expected: int, values: List[Any], errors: List[TemplateValidationIssue]
+    ) -> None:
+        if len(values) != expected:
+            errors.append(TemplateValidationIssue(
+                code="parameter_count_mismatch",
+                field=f"template_variables.{section}",
+                message=(
+                    f"Template {section} expects {expected} parameter(s) but "
+                    f"{len(values)} were provided"
+                ),
+            ))
+
+        for index, value in enumerate(values, start=1):
+            if not isinstance(value, (str, int, float)) or isinstance(value, bool):
+                errors.append(TemplateValidationIssue(
+                    code="parameter_type_invalid",
+                    field=f"template_variables.{section}[{index}]",
+                    message=f"{section} parameter {{{{{index}}}}} must be text or a number",
+                ))
+            elif str(value).strip() == "":
+                errors.append(TemplateValidationIssue(
+                    code="parameter_empty",
+                    field=f"template_variables.{section}[{index}]",
+                    message=f"{section} parameter {{{{{index}}}}} is empty",
+                ))
+            elif isinstance(value, str) and INVALID_PARAMETER_PATTERN.search(value):
+                errors.append(TemplateValidationIssue(
+                    code="parameter_format_invalid",
+                    field=f"template_variables.{section}[{index}]",
+                    message=(
+                        f"{section} parameter {{{{{index}}}}} cannot contain new lines, "
+                        f"tabs or more than 4 consecutive spaces"
+                    ),
+                ))
+
+    @classmethod
+    def _check_carousel(
+        cls, definition: List[Dict[str, Any]], cards: Any, errors: List[TemplateValidationIssue]
+    ) -> None:
+        """Values of every card of a carousel template against its card layout"""
+        if not isinstance(cards, list) or len(cards) != len(definition):
+            provided = len(cards) if isinstance(cards, list) else 0
+            errors.append(TemplateValidationIssue(
+                code="carousel_card_count_mismatch",
+                field="template_variables.cards",
+                message=(
+                    f"Carousel template has {len(definition)} card(s) but "
+                    f"{provided} were provided"
+                ),
+            ))
+            return
+
+        for index, (layout, card) in enumerate(zip(definition, cards)):
+            section = f"cards[{index}]"
+            card = card if isinstance(card, dict) else {}
+
+            header_url, header_id = card.get("header_url"), card.get("header_id")
+            if not header_url and not header_id:
+                errors.append(TemplateValidationIssue(
+                    code="media_header_missing",
+                    field=f"template_variables.{section}.header_url",
+                    message=(
+                        f"Card {index + 1} has a {layout.get('header_format') or 'media'} header - "
+                        f"provide header_url or header_id"
+                    ),
+                ))
+            elif header_url and not str(header_url).startswith("https://"):
+                errors.append(TemplateValidationIssue(
+                    code="media_header_invalid",
+                    field=f"template_variables.{section}.header_url",
+                    message="header_url must be a public HTTPS URL",
+                ))
+
+            cls._check_parameters(
+                f"{section}.body", layout.get("body_variables_count") or 0, list(card.get("body") or []), errors
+            )
+
+            buttons = list(card.get("buttons") or [])
+            for button_index, button_layout in enumerate(layout.get("buttons") or []):
+                button = buttons[button_index] if button_index < len(buttons) else {}
+                button = button if isinstance(button, dict) else {}
+                if button_layout.get("type") == "QUICK_REPLY":
+                    key = "payload"
+                elif button_layout.get("type") == "URL" and button_layout.get("has_variable"):
+                    key = "text"
+                else:
+                    continue
+                if str(button.get(key) or "").strip() == "":
+                    errors.append(TemplateValidationIssue(
+                        code="carousel_button_missing",
+                        field=f"template_variables.{section}.buttons[{button_index}].{key}",
+                        message=f"Card {index + 1} button {button_index + 1} needs a {key}",
+                    ))
+
+    @staticmethod
+    def _check_promotional(
+        template: WhatsAppTemplate, variables: Dict[str, Any], errors: List[TemplateValidationIssue]
+    ) -> None:
+        """Coupon code of a COPY_CODE button and expiration of a limited-time offer"""
+        variables = variables or {}
+
+        if copy_code_button_index(template.buttons) is not None:
+            problem = CopyCodeButton(code=str(variables.get("coupon_code") or "")).validate()
+            if problem:
+                errors.append(TemplateValidationIssue(
+                    code="coupon_code_invalid",
+                    field="template_variables.coupon_code",
+                    message=f"Template '{template.name}' has a copy code button - {problem}",
+                ))
+
+        if (template.limited_time_offer or {}).get("has_expiration"):
+            value = variables.get("offer_expires_at")
+            try:
+                offer = LimitedTimeOffer.parse(value)
+            except (TypeError, ValueError):
+                errors.append(TemplateValidationIssue(
+                    code="offer_expiration_missing" if value in (None, "") else "offer_expiration_invalid",
+                    field="template_variables.offer_expires_at",
+                    message=(
+                        f"Template '{template.name}' is a limited-time offer - provide offer_expires_at "
+                        f"as an ISO 8601 date or a timestamp in milliseconds"
+                    ),
+                ))
+                return
+            if offer.expires_at <= datetime.now(timezone.utc):
+                errors.append(TemplateValidationIssue(
+                    code="offer_expired",
+                    field="template_variables.offer_expires_at",
+                    message=f"Offer expiration {offer.expires_at.isoformat()} is in the past",
+                ))
+
+    # ============================================
+    # VALIDATION
+    # ============================================
+
+    async def _validate_template(
+        self,
+        campaign: Campaign,
+        template_id: Optional[UUID],
+        name: Optional[str],
+        language: Optional[str],
+    ) -> TemplateValidationResult:
+        errors: List[TemplateValidationIssue] = []
+        warnings: List[TemplateValidationIssue] = []
+
+        if not template_id and not name:
+            errors.append(TemplateValidationIssue(
+                code="template_missing",
+                field="template_id",
+                message="Template campaign has no template_id or message_content.name",
+            ))
+            return TemplateValidationResult(is_valid=False, errors=errors)
+
+        template = await self._find_template(campaign.organization_id, template_id, name, language)
+        if not template:
+            errors.append(TemplateValidationIssue(
+                code="template_not_found",
+                field="template_id",
+                message=(
+                    f"Template {template_id or name} was not found - sync templates "
+                    f"from Meta or pick another template"
+                ),
+            ))
+            return TemplateValidationResult(
+                template_id=template_id, name=name, language=language, is_valid=False, errors=errors
+            )
+
+        # 1. Synced and approved
+        if not template.meta_template_id:
+            errors.append(TemplateValidationIssue(
+                code="template_not_synced",
+                field="template_id",
+                message=f"Template '{template.name}' was never submitted to Meta",
+            ))
+        if template.status != "APPROVED":
+            errors.append(TemplateValidationIssue(
+                code="template_not_approved",
+                field="template_id",
+                message=(
+                    f"Template '{template.name}' is {template.status} - only APPROVED "
+                    f"templates can be sent"
+                    + (f" (rejected: {template.rejected_reason})" if template.rejected_reason else "")
+                ),
+            ))
+        if not template.is_enabled:
+            errors.append(TemplateValidationIssue(
+                code="template_disabled",
+                field="template_id",
+                message=f"Template '{template.name}' is disabled - enable it before sending",
+            ))
+        if campaign.whatsapp_number_id and template.whatsapp_number_id != campaign.whatsapp_number_id:
+            errors.append(TemplateValidationIssue(
+                code="template_number_mismatch",
+                field="whatsapp_number_id",
+                message=(
+                    f"Template '{template.name}' belongs to another WhatsApp number - "
+                    f"use a template from the campaign's number"
+                ),
+            ))
+
+        # 2. Language
+        requested_language = language or template.language
+        if requested_language != template.language or template.status != "APPROVED":
+            available = await self._approved_languages(campaign.organization_id, template.name)
+            if requested_language not in available:
+                errors.append(TemplateValidationIssue(
+                    code="language_unavailable",
+                    field="message_content.language",
+                    message=(
+                        f"Template '{template.name}' has no approved '{requested_language}' "
+                        f"translation (available: {', '.join(available) or 'none'})"
+                    ),
+                ))
+
+        # 3. Parameters
+        header_values, body_values, media_url = self._split_parameters(campaign.template_variables)
+        header_type = (template.header_type or "").upper()
+
+        if header_type == "TEXT":
+            self._check_parameters("header", template.header_variables_count or 0, header_values, errors)
+        self._check_parameters("body", template.body_variables_count or 0, body_values, errors)
+        if template.carousel_cards:
+            self._check_carousel(template.carousel_cards, (campaign.template_variables or {}).get("cards"), errors)
+        self._check_promotional(template, campaign.template_variables, errors)
+
+        # 4. Media header
+        if header_type in MEDIA_HEADER_TYPES:
+            if not media_url:
+                errors.append(TemplateValidationIssue(
+                    code="media_header_missing",
+                    field="template_variables.header_media_url",
+                    message=f"Template '{template.name}' has a {header_type} header - provide header_media_url",
+                ))
+            elif not str(media_url).startswith("https://"):
+                errors.append(TemplateValidationIssue(
+                    code="media_header_invalid",
+                    field="template_variables.header_media_url",
+                    message="header_media_url must be a public HTTPS URL",
+                ))
+
+        # 5. Category vs audience consent
+        category = (template.category or "").upper()
+        if category == "AUTHENTICATION":
+            errors.append(TemplateValidationIssue(
+                code="category_not_allowed",
+                field="template_id",
+                message="Authentication templates cannot be used in campaigns",
+            ))
+        elif category == "MARKETING" and not campaign.respect_opt_out:
+            errors.append(TemplateValidationIssue(
+                code="marketing_requires_opt_in",
+                field="respect_opt_out",
+                message=(
+                    "Marketing templates can only be sent to opted-in contacts - "
+                    "enable respect_opt_out"
+                ),
+            ))
+        elif category == "UTILITY" and campaign.campaign_type == "broadcast":
+            warnings.append(TemplateValidationIssue(
+                code="utility_broadcast",
+                field="template_id",
+                message=(
+                    f"Utility template '{template.name}' in a broadcast - Meta may "
+                    f"recategorize it as marketing if it is not tied to a transaction"
+                ),
+            ))
+
+        return TemplateValidationResult(
+            template_id=template.id,
+            name=template.name,
+            language=requested_language,
+            category=template.category,
+            is_valid=not errors,
+            errors=errors,
+            warnings=warnings,
+        )
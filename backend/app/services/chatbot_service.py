@@ -2,25 +2,52 @@
 Chatbot service - Business logic for chatbots, flows, and nodes
 """
 
-from typing import List, Optional
-from uuid import UUID
+from copy import deepcopy
+from datetime import datetime, timezone
+from typing import Any, Dict, List, Optional
+from uuid import UUID, uuid4
 
 from fastapi import HTTPException, status
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.core.exceptions import BadRequestException, ConflictException, NotFoundException
-from app.models.chatbot import Chatbot, Flow, Node
-from app.repositories.chatbot import ChatbotRepository, FlowRepository, NodeRepository
+from app.models.chatbot import Chatbot, Flow, FlowVersion, Node
+from app.repositories.chatbot import (
+    ChatbotRepository,
+    FlowRepository,
+    FlowTraceRepository,
+    FlowVersionRepository,
+    FlowWaitRepository,
+    NodeRepository,
+)
+from app.repositories.conversation import ConversationRepository
+from app.repositories.department import DepartmentRepository
+from app.repositories.queue import QueueRepository
+from app.repositories.user import UserRepository
 from app.schemas.chatbot import (
     ChatbotCreate,
     ChatbotInDB,
     ChatbotStats,
     ChatbotUpdate,
     FlowCreate,
+    FlowImport,
+    FlowSessionTrace,
     FlowUpdate,
+    FlowValidationIssue,
+    FlowValidationReport,
     NodeCreate,
     NodeUpdate,
 )
+from app.utils.flow_migration import (
+    FLOW_RESTART_KEY,
+    MIGRATION_CONTINUE,
+    MIGRATION_RESTART_AT_CHECKPOINT,
+    migration_target,
+    validate_migration,
+)
+from app.utils.flow_complexity import analyze_flow
+from app.utils.flow_transfer import FLOW_EXPORT_FORMAT_VERSION, collect_references, remap_references
+from app.utils.flow_validation import has_errors, validate_flow as validate_canvas
 
 
 class ChatbotService:
@@ -31,6 +58,7 @@ class ChatbotService:
         self.chatbot_repo = ChatbotRepository(db)
         self.flow_repo = FlowRepository(db)
         self.node_repo = NodeRepository(db)
+        self.version_repo = FlowVersionRepository(db)
 
     # ============================================
     # CHATBOT OPERATIONS
@@ -366,6 +394,39 @@ class ChatbotService:
 
         return updated_flow
 
+    async def validate_flow(self, flow_id: UUID, organization_id: UUID) -> FlowValidationReport:
+        """
+        Statically validate the flow's draft (see app.utils.flow_validation)
+
+        Includes the complexity analysis (cycles and limits, see
+        app.utils.flow_complexity).
+
+        Args:
+            flow_id: Flow UUID
+            organization_id: Organization UUID
+
+        Returns:
+            Report with the diagnostics; errors block publishing
+
+        Raises:
+            NotFoundException: If flow not found
+        """
+        flow = await self.get_flow(flow_id, organization_id)
+        if not flow:
+            raise NotFoundException("Flow not found")
+
+        complexity, complexity_diagnostics = analyze_flow(flow.canvas_data)
+        diagnostics = sorted(
+            validate_canvas(flow.canvas_data) + complexity_diagnostics,
+            key=lambda diagnostic: diagnostic.severity != "error",
+        )
+        return FlowValidationReport(
+            flow_id=flow.id,
+            is_valid=not has_errors(diagnostics),
+            diagnostics=[FlowValidationIssue.model_validate(diagnostic) for diagnostic in diagnostics],
+            complexity=complexity.to_dict(),
+        )
+
     async def delete_flow(self, flow_id: UUID, organization_id: UUID):
         """
         Soft delete flow
@@ -387,6 +448,250 @@ class ChatbotService:
 
         await self.flow_repo.soft_delete(flow_id)
 
+    # ============================================
+    # FLOW VERSION OPERATIONS
+    # ============================================
+
+    async def publish_flow(
+        self,
+        flow_id: UUID,
+        organization_id: UUID,
+        published_by_id: Optional[UUID] = None,
+        notes: Optional[str] = None,
+        migration_strategy: str = MIGRATION_CONTINUE,
+        node_map: Optional[Dict[str, str]] = None,
+        checkpoint_node_id: Optional[str] = None,
+    ) -> FlowVersion:
+        """
+        Publish the flow's draft as a new immutable version
+
+        New conversations start on the published version; conversations
+        already running keep the version they started on unless the
+        migration strategy moves them (see app.utils.flow_migration). The
+        draft stays editable and gets the next version number.
+
+        Args:
+            flow_id: Flow UUID
+            organization_id: Organization UUID
+            published_by_id: User publishing the version
+            notes: Optional release notes
+            migration_strategy: continue, map_node_ids or restart_at_checkpoint
+            node_map: map_node_ids: old node ID -> node ID in the new version
+            checkpoint_node_id: restart_at_checkpoint: node to restart at
+
+        Returns:
+            Published version
+
+        Raises:
+            NotFoundException: If flow not found
+            HTTPException: 400 with the diagnostics if the draft has validation errors (complexity limits included)
+            BadRequestException: If the draft has no start node or the migration is invalid
+        """
+        report = await self.validate_flow(flow_id, organization_id)
+        if not report.is_valid:
+            raise HTTPException(
+                status_code=status.HTTP_400_BAD_REQUEST,
+                detail={
+                    "message": "Flow has validation errors",
+                    "diagnostics": [issue.model_dump() for issue in report.diagnostics if issue.severity == "error"],
+                },
+            )
+
+        flow = await self.get_flow(flow_id, organization_id)
+
+        draft_nodes = await self.node_repo.get_by_flow(flow_id, organization_id)
+        if not any(node.node_type == "start" for node in draft_nodes):
+            raise BadRequestException("Flow needs a start node to be published")
+
+        migration_errors = validate_migration(
+            migration_strategy, node_map or {}, checkpoint_node_id, [node.node_id for node in draft_nodes]
+        )
+        if migration_errors:
+            raise BadRequestException("; ".join(migration_errors))
+
+        version = FlowVersion(
+            organization_id=organization_id,
+            flow_id=flow_id,
+            published_by_id=published_by_id,
+            version=flow.version,
+            canvas_data=deepcopy(flow.canvas_data or {"nodes": [], "edges": []}),
+            variables=deepcopy(flow.variables or {}),
+            notes=notes,
+            status="published",
+            published_at=datetime.now(timezone.utc),
+            complexity=report.complexity.model_dump() if report.complexity else None,
+        )
+        self.db.add(version)
+        await self.db.flush()
+
+        version_nodes = [
+            Node(
+                flow_id=flow_id,
+                organization_id=organization_id,
+                flow_version_id=version.id,
+                node_id=node.node_id,
+                node_type=node.node_type,
+                label=node.label,
+                data=deepcopy(node.data),
+                position_x=node.position_x,
+                position_y=node.position_y,
+                order=node.order,
+            )
+            for node in draft_nodes
+        ]
+        self.db.add_all(version_nodes)
+        await self.db.flush()
+
+        version.session_migration = await self._migrate_sessions(
+            flow, version, version_nodes, migration_strategy, node_map or {}, checkpoint_node_id
+        )
+
+        await self._archive_live_version(flow)
+        flow.published_version_id = version.id
+        flow.version = version.version + 1
+
+        await self.db.commit()
+        await self.db.refresh(version)
+        return version
+
+    async def list_flow_versions(self, flow_id: UUID, organization_id: UUID) -> List[FlowVersion]:
+        """
+        List the published versions of a flow, newest first, with their execution stats
+
+        Raises:
+            NotFoundException: If flow not found
+        """
+        flow = await self.get_flow(flow_id, organization_id)
+        if not flow:
+            raise NotFoundException("Flow not found")
+
+        return await self.version_repo.get_by_flow(flow_id, organization_id)
+
+    async def get_flow_version(self, flow_id: UUID, organization_id: UUID, version: int) -> FlowVersion:
+        """
+        Get a version of a flow by number
+
+        Raises:
+            NotFoundException: If flow or version not found
+        """
+        flow = await self.get_flow(flow_id, organization_id)
+        if not flow:
+            raise NotFoundException("Flow not found")
+
+        flow_version = await self.version_repo.get_by_number(flow_id, organization_id, version)
+        if not flow_version:
+            raise NotFoundException(f"Version {version} not found")
+
+        return flow_version
+
+    async def rollback_flow(self, flow_id: UUID, organization_id: UUID, version: int) -> FlowVersion:
+        """
+        Make an earlier version live again
+
+        Only new conversations are affected; the draft is left untouched.
+
+        Raises:
+            NotFoundException: If flow or version not found
+            BadRequestException: If the version is already live
+        """
+        flow = await self.get_flow(flow_id, organization_id)
+        if not flow:
+            raise NotFoundException("Flow not found")
+
+        flow_version = await self.version_repo.get_by_number(flow_id, organization_id, version)
+        if not flow_version:
+            raise NotFoundException(f"Version {version} not found")
+        if flow_version.id == flow.published_version_id:
+            raise BadRequestException(f"Version {version} is already published")
+
+        await self._archive_live_version(flow)
+        flow_version.status = "published"
+        flow.published_version_id = flow_version.id
+
+        await self.db.commit()
+        await self.db.refresh(flow_version)
+        return flow_version
+
+    async def get_session_trace(self, session_id: UUID, organization_id: UUID) -> FlowSessionTrace:
+        """
+        Execution trace of a flow session (nodes, inputs, messages, variables)
+
+        Raises:
+            NotFoundException: If the session has no trace in the organization
+        """
+        events = await FlowTraceRepository(self.db).list_session(session_id, organization_id)
+        if not events:
+            raise NotFoundException("Flow session not found")
+
+        return FlowSessionTrace(
+            session_id=session_id,
+            conversation_id=events[0].conversation_id,
+            flow_id=events[-1].flow_id,
+            total=len(events),
+            events=events,
+        )
+
+    async def _migrate_sessions(
+        self,
+        flow: Flow,
+        version: FlowVersion,
+        version_nodes: List[Node],
+        strategy: str,
+        node_map: Dict[str, str],
+        checkpoint_node_id: Optional[str],
+    ) -> Dict[str, Any]:
+        """
+        Move the conversations running other versions of the flow to the new one (not committed)
+
+        Pending wait nodes follow a mapped session to its new node and are
+        cancelled for restarted sessions.
+
+        Returns:
+            Summary stored on the version
+        """
+        summary = {"strategy": strategy, "migrated": 0, "restarted": 0, "kept": 0}
+        sessions = await ConversationRepository(self.db).list_flow_sessions(
+            flow.id, flow.organization_id, version.id
+        )
+
+        nodes_by_id = {node.node_id: node for node in version_nodes}
+        start_node_id = next(node.node_id for node in version_nodes if node.node_type == "start")
+        wait_repo = FlowWaitRepository(self.db)
+        for conversation, current_node in sessions:
+            target_id = migration_target(
+                strategy,
+                current_node.node_id,
+                list(nodes_by_id),
+                start_node_id,
+                node_map=node_map,
+                checkpoint_node_id=checkpoint_node_id,
+            )
+            if target_id is None:
+                summary["kept"] += 1
+                continue
+
+            target = nodes_by_id[target_id]
+            conversation.current_node_id = target.id
+            if strategy == MIGRATION_RESTART_AT_CHECKPOINT:
+                context_vars = dict(conversation.context_variables or {})
+                context_vars[FLOW_RESTART_KEY] = True
+                conversation.context_variables = context_vars
+                await wait_repo.cancel_pending(conversation.id)
+                summary["restarted"] += 1
+            else:
+                await wait_repo.move_pending(conversation.id, target.id)
+                summary["migrated"] += 1
+
+        return summary
+
+    async def _archive_live_version(self, flow: Flow) -> None:
+        """Mark the flow's live version as archived (not committed)"""
+        if not flow.published_version_id:
+            return
+        live = await self.version_repo.get(flow.published_version_id)
+        if live:
+            live.status = "archived"
+
     # ============================================
     # NODE OPERATIONS
     # ============================================
@@ -449,10 +754,13 @@ class ChatbotService:
 
         Raises:
             NotFoundException: If node not found
+            BadRequestException: If the node belongs to a published version
         """
         node = await self.node_repo.get(node_id)
         if not node or node.organization_id != organization_id:
             raise NotFoundException("Node not found")
+        if node.flow_version_id:
+            raise BadRequestException("Nodes of published versions cannot be changed, edit the draft")
 
         update_data = data.model_dump(exclude_unset=True)
         updated_node = await self.node_repo.update(node_id, update_data)
@@ -469,10 +777,13 @@ class ChatbotService:
 
         Raises:
             NotFoundException: If node not found
+            BadRequestException: If the node belongs to a published version
         """
         node = await self.node_repo.get(node_id)
         if not node or node.organization_id != organization_id:
             raise NotFoundException("Node not found")
+        if node.flow_version_id:
+            raise BadRequestException("Nodes of published versions cannot be deleted, edit the draft")
 
         await self.node_repo.delete(node_id)
 
@@ -588,7 +899,11 @@ class ChatbotService:
 
     async def export_flow(self, flow_id: UUID, organization_id: UUID) -> dict:
         """
-        Export flow as JSON for backup/template
+        Export flow as a versioned JSON document for backup or another organization
+
+        Records of this organization referenced by node data (flows,
+        departments, queues, agents) are listed with a portable key so
+        import_flow can remap them.
 
         Args:
             flow_id: Flow UUID
@@ -601,7 +916,6 @@ class ChatbotService:
             NotFoundException: If flow not found
         """
         import logging
-        from datetime import datetime
 
         logger = logging.getLogger(__name__)
 
@@ -611,104 +925,199 @@ class ChatbotService:
 
         logger.info(f"📤 Exportando flow '{flow.name}' (ID: {flow_id})")
 
+        canvas_data = flow.canvas_data or {"nodes": [], "edges": []}
+        references = [
+            {"type": reference_type, "id": reference_id,
+             "key": await self._reference_key(reference_type, reference_id, organization_id)}
+            for reference_type, reference_id in collect_references(canvas_data)
+        ]
+
         # Build export data
         export_data = {
-            "format_version": "1.0",
-            "exported_at": datetime.utcnow().isoformat(),
+            "format_version": FLOW_EXPORT_FORMAT_VERSION,
+            "exported_at": datetime.now(timezone.utc).isoformat(),
             "flow": {
                 "name": flow.name,
                 "description": flow.description,
-                "is_main": flow.is_main,
-                "canvas_data": flow.canvas_data,
-                "variables": flow.variables,
-                "settings": flow.settings,
+                "is_fallback": flow.is_fallback,
+                "is_active": flow.is_active,
+                "default_locale": flow.default_locale,
+                "canvas_data": canvas_data,
+                "variables": flow.variables or {},
             },
+            "references": references,
             "metadata": {
                 "organization_id": str(organization_id),
                 "chatbot_id": str(flow.chatbot_id),
                 "original_flow_id": str(flow_id),
-                "total_nodes": len(flow.canvas_data.get("nodes", [])) if flow.canvas_data else 0,
-                "total_edges": len(flow.canvas_data.get("edges", [])) if flow.canvas_data else 0,
+                "total_nodes": len(canvas_data.get("nodes", [])),
+                "total_edges": len(canvas_data.get("edges", [])),
             }
         }
 
-        logger.info(f"✅ Flow exportado com {export_data['metadata']['total_nodes']} nodes")
+        logger.info(
+            f"✅ Flow exportado com {export_data['metadata']['total_nodes']} nodes "
+            f"e {len(references)} referências"
+        )
 
         return export_data
 
     async def import_flow(
         self,
-        import_data: dict,
+        import_data: FlowImport,
         chatbot_id: UUID,
         organization_id: UUID,
-        override_name: Optional[str] = None
+        override_name: Optional[str] = None,
+        drop_unresolved: bool = False,
     ) -> Flow:
         """
-        Import flow from exported JSON
+        Import flow from an export document (already schema-validated)
+
+        Referenced records are remapped to this organization: first through
+        import_data.id_map, then by their portable key (flow name,
+        department/queue slug, agent email), and finally kept as they are
+        when the id exists here (import into the same organization).
+        References to the exported flow itself point to the new flow.
 
         Args:
-            import_data: Exported flow data
+            import_data: Export document with optional id_map
             chatbot_id: Target chatbot ID (can be different from original)
             organization_id: Organization UUID
             override_name: Optional name override (if None, uses original + " (Imported)")
+            drop_unresolved: Clear references that cannot be resolved instead of failing
 
         Returns:
             Created flow
 
         Raises:
-            BadRequestException: If import data is invalid
+            BadRequestException: If references cannot be resolved
             NotFoundException: If chatbot not found
         """
         import logging
 
         logger = logging.getLogger(__name__)
 
-        # Validate import data format
-        if "format_version" not in import_data or "flow" not in import_data:
-            raise BadRequestException("Invalid import data format")
-
-        format_version = import_data.get("format_version")
-        if format_version != "1.0":
-            raise BadRequestException(f"Unsupported format version: {format_version}")
-
         # Verify chatbot exists
         chatbot = await self.get_chatbot(chatbot_id, organization_id)
         if not chatbot:
             raise NotFoundException("Target chatbot not found")
 
-        flow_data = import_data["flow"]
+        flow_data = import_data.flow
+        canvas_data = flow_data.canvas_data.model_dump(exclude_unset=True)
+        canvas_data.setdefault("nodes", [])
+        canvas_data.setdefault("edges", [])
 
         # Prepare flow name
-        flow_name = override_name or f"{flow_data.get('name', 'Imported Flow')} (Imported)"
+        flow_name = override_name or f"{flow_data.name} (Imported)"
 
         logger.info(f"📥 Importando flow '{flow_name}' para chatbot {chatbot_id}")
 
+        new_flow_id = uuid4()
+        original_flow_id = str(import_data.metadata.get("original_flow_id") or "")
+        keys = {(reference.type, reference.id): reference.key for reference in import_data.references}
+
+        id_map = {}
+        for reference_type, reference_id in collect_references(canvas_data):
+            if reference_id in import_data.id_map:
+                id_map[reference_id] = import_data.id_map[reference_id]
+            elif reference_type == "flow" and reference_id == original_flow_id:
+                id_map[reference_id] = str(new_flow_id)
+            else:
+                id_map[reference_id] = await self._resolve_reference(
+                    reference_type, reference_id, keys.get((reference_type, reference_id)), organization_id
+                )
+
+        canvas_data, unresolved = remap_references(canvas_data, id_map)
+        if unresolved and not drop_unresolved:
+            details = ", ".join(f"{item['type']} {item['id']} (node {item['node_id']})" for item in unresolved)
+            raise BadRequestException(
+                f"Unresolved references: {details}. Map them with id_map or import with drop_unresolved"
+            )
+        for item in unresolved:
+            logger.warning(f"⚠️ Referência removida no import: {item['type']} {item['id']} (node {item['node_id']})")
+
         # Create new flow
         new_flow_data = {
+            "id": new_flow_id,
             "name": flow_name,
-            "description": flow_data.get("description", ""),
+            "description": flow_data.description or "",
             "chatbot_id": chatbot_id,
             "organization_id": organization_id,
             "is_main": False,  # Never import as main (user must set manually)
-            "canvas_data": flow_data.get("canvas_data", {"nodes": [], "edges": []}),
-            "variables": flow_data.get("variables", {}),
-            "settings": flow_data.get("settings"),
+            "is_fallback": False,
+            "is_active": flow_data.is_active,
+            "default_locale": flow_data.default_locale,
+            "canvas_data": canvas_data,
+            "variables": flow_data.variables,
         }
 
         new_flow = await self.flow_repo.create(new_flow_data)
 
         # Sync nodes to database
-        if new_flow.canvas_data:
-            await self._sync_nodes_from_canvas(
-                flow_id=new_flow.id,
-                organization_id=organization_id,
-                canvas_data=new_flow.canvas_data
-            )
+        await self._sync_nodes_from_canvas(
+            flow_id=new_flow.id,
+            organization_id=organization_id,
+            canvas_data=new_flow.canvas_data
+        )
 
-        metadata = import_data.get("metadata", {})
         logger.info(
-            f"✅ Flow importado com {metadata.get('total_nodes', 0)} nodes "
-            f"(Original: {metadata.get('original_flow_id', 'unknown')})"
+            f"✅ Flow importado com {len(canvas_data['nodes'])} nodes "
+            f"(Original: {original_flow_id or 'unknown'}, formato {import_data.format_version})"
         )
 
         return new_flow
+
+    async def _reference_key(
+        self, reference_type: str, reference_id: str, organization_id: UUID
+    ) -> Optional[str]:
+        """Portable key of a referenced record of the organization"""
+        record = await self._get_reference(reference_type, reference_id, organization_id)
+        if not record:
+            return None
+        if reference_type == "flow":
+            return record.name
+        if reference_type == "agent":
+            return record.email
+        return record.slug
+
+    async def _resolve_reference(
+        self, reference_type: str, reference_id: str, key: Optional[str], organization_id: UUID
+    ) -> Optional[str]:
+        """Id of the organization's record matching an exported reference"""
+        record = None
+        if key:
+            if reference_type == "flow":
+                record = await self.flow_repo.get_by_name(key, organization_id)
+            elif reference_type == "department":
+                record = await DepartmentRepository(self.db).get_by_slug(key, organization_id)
+            elif reference_type == "queue":
+                record = await QueueRepository(self.db).get_by_slug(key, organization_id)
+            elif reference_type == "agent":
+                record = await UserRepository(self.db).get_by_email(key)
+                if record and record.organization_id != organization_id:
+                    record = None
+        if not record:
+            record = await self._get_reference(reference_type, reference_id, organization_id)
+        return str(record.id) if record else None
+
+    async def _get_reference(self, reference_type: str, reference_id: str, organization_id: UUID):
+        """Referenced record by id, only when it belongs to the organization"""
+        try:
+            record_id = UUID(str(reference_id))
+        except ValueError:
+            return None
+
+        if reference_type == "flow":
+            record = await self.flow_repo.get(record_id)
+        elif reference_type == "department":
+            record = await DepartmentRepository(self.db).get(record_id)
+        elif reference_type == "queue":
+            record = await QueueRepository(self.db).get_by_id(record_id, organization_id)
+        else:
+            record = await UserRepository(self.db).get(record_id)
+
+        if not record or record.organization_id != organization_id:
+            return None
+        if getattr(record, "deleted_at", None):
+            return None
+        return record
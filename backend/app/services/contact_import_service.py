@@ -0,0 +1,318 @@
+"""
+Contact import service - streaming CSV/XLSX upload
+
+The upload is streamed to the shared media storage (app.core.media_storage)
+and imported by the import_contacts Celery task on any worker (see
+run_contact_import): the file is downloaded to CONTACT_IMPORT_DIR, rows are
+parsed one at a time (app.utils.contact_import) and written
+CONTACT_IMPORT_BATCH_SIZE at a time, one transaction per batch, so memory
+stays flat whatever the file size. Progress is kept in Redis and polled with
+GET /contacts/import/{import_id}.
+
+Existing contacts (same WhatsApp ID) are updated with the non-empty columns
+of the row when update_existing is set, and skipped otherwise.
+"""
+
+import json
+import logging
+import os
+import uuid
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from fastapi import UploadFile
+from sqlalchemy import select
+from sqlalchemy.dialects.postgresql import insert
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.config import settings
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.core.media_storage import get_media_storage
+from app.core.redis import redis_client
+from app.models.contact import Contact, Tag, contact_tags
+from app.utils.contact_import import (
+    ContactRow,
+    ImportProgress,
+    RowReader,
+    column_mapping,
+    file_format,
+    parse_row,
+)
+
+logger = logging.getLogger(__name__)
+
+KEY_PREFIX = "contact_import:"
+# Uploads wait in the shared storage under this prefix until a worker imports them
+STORAGE_PREFIX = "contact-imports"
+# Progress of finished imports stays available for a week
+PROGRESS_TTL_SECONDS = 7 * 86400
+UPLOAD_CHUNK_BYTES = 1024 * 1024
+
+
+def _progress_key(organization_id: UUID, import_id: str) -> str:
+    return f"{KEY_PREFIX}{organization_id}:{import_id}"
+
+
+class ContactImportService:
+    """Service for streaming contact imports"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def start(
+        self,
+        organization_id: UUID,
+        upload: UploadFile,
+        update_existing: bool = False,
+        tag_ids: Optional[List[UUID]] = None,
+        default_country_code: Optional[str] = None,
+    ) -> Dict[str, Any]:
+        """
+        Store an uploaded file in the shared storage and queue its import (import_contacts task)
+
+        Returns:
+            Progress of the queued import
+
+        Raises:
+            BadRequestException: Unsupported format, empty or too large file
+        """
+        fmt = file_format(upload.filename)
+        if not fmt:
+            raise BadRequestException("Import file must be .csv or .xlsx")
+
+        import_id = uuid.uuid4().hex
+        storage_key = f"{STORAGE_PREFIX}/{organization_id}/{import_id}.{fmt}"
+        max_bytes = settings.CONTACT_IMPORT_MAX_FILE_MB * 1024 * 1024
+        size = 0
+
+        async def chunks():
+            nonlocal size
+            while chunk := await upload.read(UPLOAD_CHUNK_BYTES):
+                size += len(chunk)
+                if size > max_bytes:
+                    raise BadRequestException(
+                        f"Import file is larger than {settings.CONTACT_IMPORT_MAX_FILE_MB} MB"
+                    )
+                yield chunk
+
+        storage = get_media_storage()
+        try:
+            await storage.save(storage_key, chunks(), upload.content_type)
+            if not size:
+                raise BadRequestException("Import file is empty")
+        except BaseException:
+            await storage.delete(storage_key)
+            raise
+
+        progress = ImportProgress(import_id=import_id, filename=upload.filename or "", file_format=fmt)
+        await self._save(organization_id, progress)
+        job = {**progress.to_dict(), "storage_key": storage_key}
+        _queue_import(organization_id, job, update_existing, tag_ids or [], default_country_code)
+        logger.info(f"📥 Contact import {import_id} queued ({size} bytes, {fmt})")
+        return job
+
+    async def get_progress(self, organization_id: UUID, import_id: str) -> Dict[str, Any]:
+        """Progress of an import of the organization"""
+        value = await redis_client.get(_progress_key(organization_id, import_id))
+        if not value:
+            raise NotFoundException("Contact import not found")
+        return json.loads(value)
+
+    async def run(
+        self,
+        organization_id: UUID,
+        import_id: str,
+        storage_key: str,
+        filename: str,
+        fmt: str,
+        update_existing: bool = False,
+        tag_ids: Optional[List[UUID]] = None,
+        default_country_code: Optional[str] = None,
+    ) -> ImportProgress:
+        """Import every row of a stored file (the file is removed from the storage at the end)"""
+        progress = ImportProgress(
+            import_id=import_id,
+            filename=filename,
+            file_format=fmt,
+            status="running",
+            started_at=datetime.utcnow().isoformat(),
+        )
+        await self._save(organization_id, progress)
+        path = os.path.join(settings.CONTACT_IMPORT_DIR, os.path.basename(storage_key))
+
+        try:
+            await _download(storage_key, path)
+            tag_ids = await self._organization_tags(organization_id, tag_ids or [])
+            reader = RowReader(path, fmt)
+            mapping = None
+            batch: List[ContactRow] = []
+
+            for line, values in reader:
+                if mapping is None:
+                    mapping = column_mapping(values)
+                    continue
+
+                progress.processed_rows += 1
+                try:
+                    row = parse_row(line, values, mapping, default_country_code)
+                except ValueError as e:
+                    progress.add_error(line, str(e))
+                    continue
+                if row:
+                    batch.append(row)
+
+                if len(batch) >= settings.CONTACT_IMPORT_BATCH_SIZE:
+                    await self._write_batch(organization_id, batch, update_existing, tag_ids, progress)
+                    batch = []
+                    progress.progress_percentage = round(reader.progress * 100, 1)
+                    await self._save(organization_id, progress)
+
+            if mapping is None:
+                raise ValueError("Import file has no header row")
+            if batch:
+                await self._write_batch(organization_id, batch, update_existing, tag_ids, progress)
+            progress.finish("completed")
+            logger.info(
+                f"✅ Contact import {import_id} completed: {progress.created} created, "
+                f"{progress.updated} updated, {progress.skipped} skipped, {progress.invalid} invalid"
+            )
+
+        except Exception as e:
+            await self.db.rollback()
+            progress.finish("failed", str(e))
+            logger.error(f"❌ Contact import {import_id} failed: {e}")
+
+        finally:
+            _remove(path)
+            await get_media_storage().delete(storage_key)
+
+        await self._save(organization_id, progress)
+        return progress
+
+    async def _write_batch(
+        self,
+        organization_id: UUID,
+        rows: List[ContactRow],
+        update_existing: bool,
+        tag_ids: List[UUID],
+        progress: ImportProgress,
+    ) -> None:
+        """Create or update the contacts of a batch in one transaction"""
+        result = await self.db.execute(
+            select(Contact).where(
+                Contact.organization_id == organization_id,
+                Contact.whatsapp_id.in_({row.phone for row in rows}),
+                Contact.deleted_at.is_(None),
+            )
+        )
+        contacts = {contact.whatsapp_id: contact for contact in result.scalars().all()}
+        created = set()
+        touched: List[Contact] = []
+
+        for row in rows:
+            contact = contacts.get(row.phone)
+            if contact is None:
+                contact = Contact(
+                    organization_id=organization_id,
+                    whatsapp_id=row.phone,
+                    source="import",
+                    opt_in=True,
+                    is_blocked=False,
+                    attributes=row.attributes,
+                    **row.fields,
+                )
+                self.db.add(contact)
+                contacts[row.phone] = contact
+                created.add(row.phone)
+                progress.created += 1
+            elif row.phone in created or not update_existing:
+                progress.skipped += 1
+                continue
+            else:
+                for name, value in row.fields.items():
+                    setattr(contact, name, value)
+                if row.attributes:
+                    contact.attributes = {**(contact.attributes or {}), **row.attributes}
+                progress.updated += 1
+            touched.append(contact)
+
+        await self.db.flush()
+        if tag_ids and touched:
+            await self.db.execute(
+                insert(contact_tags)
+                .values([
+                    {"contact_id": contact.id, "tag_id": tag_id}
+                    for contact in {contact.id: contact for contact in touched}.values()
+                    for tag_id in tag_ids
+                ])
+                .on_conflict_do_nothing()
+            )
+        await self.db.commit()
+        # Nothing of the batch is needed anymore: keep the session from growing
+        self.db.expunge_all()
+
+    async def _organization_tags(self, organization_id: UUID, tag_ids: List[UUID]) -> List[UUID]:
+        """Tags of the organization among the requested ones"""
+        if not tag_ids:
+            return []
+        result = await self.db.execute(
+            select(Tag.id).where(Tag.id.in_(tag_ids), Tag.organization_id == organization_id)
+        )
+        return list(result.scalars().all())
+
+    async def _save(self, organization_id: UUID, progress: ImportProgress) -> None:
+        await redis_client.set(
+            _progress_key(organization_id, progress.import_id),
+            json.dumps(progress.to_dict()),
+            expire=PROGRESS_TTL_SECONDS,
+        )
+
+
+def _queue_import(
+    organization_id: UUID,
+    job: Dict[str, Any],
+    update_existing: bool,
+    tag_ids: List[UUID],
+    default_country_code: Optional[str],
+) -> None:
+    from app.tasks.contact_import_tasks import import_contacts
+
+    import_contacts.apply_async(
+        args=[str(organization_id), job],
+        kwargs={
+            "update_existing": update_existing,
+            "tag_ids": [str(tag_id) for tag_id in tag_ids],
+            "default_country_code": default_country_code,
+        },
+    )
+
+
+async def _download(storage_key: str, path: str) -> None:
+    """Copy a stored file to the worker's disk (rows are read from a local file)"""
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    with open(path, "wb") as target:
+        async for chunk in get_media_storage().read(storage_key):
+            target.write(chunk)
+
+
+def _remove(path: str) -> None:
+    try:
+        os.remove(path)
+    except FileNotFoundError:
+        pass
+
+
+async def run_contact_import(organization_id: UUID, job: Dict[str, Any], **options: Any) -> ImportProgress:
+    """Worker entry point (import_contacts task): import a stored file with its own database session"""
+    from app.core.database import async_session
+
+    async with async_session() as db:
+        return await ContactImportService(db).run(
+            organization_id,
+            job["import_id"],
+            job["storage_key"],
+            job["filename"],
+            job["file_format"],
+            **options,
+        )
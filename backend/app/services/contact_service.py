@@ -21,6 +21,13 @@ class ContactService:
         self.db = db
         self.repo = ContactRepository(db)
 
+    @staticmethod
+    def _remove_from_ad_audiences(contact_id: UUID) -> None:
+        """Contacts that no longer accept marketing leave Meta Custom Audiences right away"""
+        from app.tasks.ad_audience_tasks import remove_contact_from_ad_audiences
+
+        remove_contact_from_ad_audiences.apply_async(args=[str(contact_id)])
+
     async def get_by_id(self, contact_id: UUID, organization_id: UUID) -> Contact:
         """Get contact by ID"""
         contact = await self.repo.get(contact_id)
@@ -87,8 +94,12 @@ class ContactService:
         contact = await self.get_by_id(contact_id, organization_id)
 
         update_data = data.model_dump(exclude_unset=True)
+        opted_out = contact.opt_in and update_data.get("opt_in") is False
         updated_contact = await self.repo.update(contact_id, update_data)
 
+        if opted_out:
+            self._remove_from_ad_audiences(contact_id)
+
         return updated_contact
 
     async def delete_contact(
@@ -96,7 +107,9 @@ class ContactService:
     ) -> bool:
         """Soft delete contact"""
         contact = await self.get_by_id(contact_id, organization_id)
-        return await self.repo.delete(contact_id)
+        deleted = await self.repo.delete(contact_id)
+        self._remove_from_ad_audiences(contact_id)
+        return deleted
 
     async def block_contact(
         self, contact_id: UUID, organization_id: UUID, reason: Optional[str] = None
@@ -112,7 +125,9 @@ class ContactService:
             "blocked_reason": reason,
         }
 
-        return await self.repo.update(contact_id, update_data)
+        blocked = await self.repo.update(contact_id, update_data)
+        self._remove_from_ad_audiences(contact_id)
+        return blocked
 
     async def unblock_contact(
         self, contact_id: UUID, organization_id: UUID
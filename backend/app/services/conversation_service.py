@@ -3,6 +3,7 @@ Conversation Service
 Business logic for conversation and message management
 """
 
+import logging
 from datetime import datetime
 from typing import List, Optional
 from uuid import UUID
@@ -20,8 +21,11 @@ from app.schemas.conversation import (
     MessageCreate,
 )
 from app.schemas.sla import SlaAlert
+from app.core.config import settings
 from app.core.exceptions import NotFoundException
 
+logger = logging.getLogger(__name__)
+
 
 class ConversationService:
     """Service for conversation management"""
@@ -171,12 +175,35 @@ class ConversationService:
             limit=limit,
         )
 
+    async def search_messages(
+        self,
+        organization_id: UUID,
+        query: str,
+        conversation_id: Optional[UUID] = None,
+        message_type: Optional[str] = None,
+        skip: int = 0,
+        limit: int = 50,
+    ) -> List[Message]:
+        """Search messages (metadata-only for encrypted organizations)"""
+        return await self.message_repo.search_messages(
+            organization_id=organization_id,
+            query=query,
+            conversation_id=conversation_id,
+            message_type=message_type,
+            skip=skip,
+            limit=limit,
+        )
+
     async def mark_as_read(
         self, conversation_id: UUID, organization_id: UUID
     ) -> Conversation:
-        """Mark conversation as read"""
-        conversation = await self.get_by_id(conversation_id, organization_id)
-        return await self.repo.mark_as_read(conversation_id, organization_id)
+        """Mark conversation as read and send read receipts to the contact"""
+        from app.services.whatsapp_service import WhatsAppService
+
+        await self.get_by_id(conversation_id, organization_id)
+        conversation = await self.repo.mark_as_read(conversation_id, organization_id)
+        await WhatsAppService(self.db).send_read_receipts(conversation_id, organization_id)
+        return conversation
 
     async def get_sla_alerts(
         self,
@@ -279,8 +306,20 @@ class ConversationService:
         result = await self.db.execute(query)
         conversations = result.scalars().all()
 
+        # Language routing: conversations in a language some agent speaks are left to them
+        contact_languages = {}
+        agent_languages, covered_languages = set(), set()
+        if settings.LANGUAGE_ROUTING_ENABLED and conversations:
+            contact_languages = await self._contact_languages(conversations)
+            if contact_languages:
+                agent_languages, covered_languages = await self._agent_languages(organization_id, agent_id)
+
         # Filter conversations by agent restrictions and required skills
         for conversation in conversations:
+            language = contact_languages.get(conversation.contact_id)
+            if language in covered_languages and language not in agent_languages:
+                continue  # Skip: another agent speaks the contact's language
+
             if conversation.queue_id:
                 # Get queue to check agent restrictions
                 queue = await self.queue_repo.get(conversation.queue_id)
@@ -326,6 +365,37 @@ class ConversationService:
         # No conversation found that agent can take
         return None
 
+    async def _contact_languages(self, conversations) -> dict:
+        """Known language of the contact of each conversation (contact_id -> code)"""
+        from app.models.contact import Contact
+
+        result = await self.db.execute(
+            select(Contact.id, Contact.language).where(
+                Contact.id.in_({conversation.contact_id for conversation in conversations}),
+                Contact.language.isnot(None),
+            )
+        )
+        return {contact_id: language for contact_id, language in result.all()}
+
+    async def _agent_languages(self, organization_id: UUID, agent_id: UUID):
+        """
+        Languages spoken by the agent and by any agent of the organization,
+        from language skills ("es", "lang:es", "spanish")
+        """
+        from app.models.agent_skill import AgentSkill as AgentSkillModel
+        from app.utils.language_detection import skill_languages
+
+        result = await self.db.execute(
+            select(AgentSkillModel.user_id, AgentSkillModel.skill_name).where(
+                AgentSkillModel.organization_id == organization_id,
+                AgentSkillModel.deleted_at.is_(None),
+            )
+        )
+        rows = result.all()
+        agent = skill_languages(name for user_id, name in rows if user_id == agent_id)
+        covered = skill_languages(name for _, name in rows)
+        return agent, covered
+
     def _is_within_business_hours(self, queue) -> bool:
         """
         Check if current time is within queue's business hours.
@@ -399,7 +469,7 @@ class ConversationService:
 
         # Update status to active and assign agent
         update_data = {
-            "assigned_agent_id": agent_id,
+            "current_agent_id": agent_id,
             "status": "active",
             "assigned_at": datetime.utcnow(),
         }
@@ -409,6 +479,7 @@ class ConversationService:
             update_data["queued_at"] = None
 
         updated = await self.repo.update(conversation_id, update_data)
+        await self._sync_primary_participant(updated, agent_id)
         return updated
 
     async def transfer_to_department(
@@ -437,7 +508,7 @@ class ConversationService:
 
         # Update department and put back in queue
         update_data = {
-            "assigned_department_id": department_id,
+            "department_id": department_id,
             "current_agent_id": None,  # Unassign current agent (use current_agent_id column)
             "status": "queued",
             "queued_at": datetime.utcnow(),
@@ -459,8 +530,26 @@ class ConversationService:
             update_data["extra_data"] = extra_data
 
         updated = await self.repo.update(conversation_id, update_data)
+        await self._sync_primary_participant(updated, None)
         return updated
 
+    async def _sync_primary_participant(
+        self, conversation: Conversation, agent_id: Optional[UUID]
+    ) -> None:
+        """Keep the primary participant in line with the assigned agent"""
+        from app.services.participant_service import ConversationParticipantService
+
+        participants = ConversationParticipantService(self.db)
+        previous = await participants.get_primary(conversation.id)
+        primary = await participants.set_primary(conversation, agent_id)
+        await self.db.commit()
+
+        if previous and previous is not primary:
+            event = "participant:left" if previous.left_at else "participant:updated"
+            await participants.broadcast(event, previous)
+        if primary and primary is not previous:
+            await participants.broadcast("participant:updated", primary)
+
     async def close_conversation(
         self,
         conversation_id: UUID,
@@ -471,6 +560,9 @@ class ConversationService:
         """
         Close a conversation
 
+        Conversations handed off by a flow with returnToFlow go back to the
+        bot, which continues from the handoff node.
+
         Args:
             conversation_id: Conversation ID
             organization_id: Organization ID
@@ -503,8 +595,41 @@ class ConversationService:
             update_data["extra_data"] = extra_data
 
         updated = await self.repo.update(conversation_id, update_data)
+
+        from app.services.whatsapp_service import HANDOFF_RETURN_KEY, WhatsAppService
+
+        if (updated.extra_data or {}).get(HANDOFF_RETURN_KEY):
+            try:
+                if await WhatsAppService(self.db).return_from_handoff(conversation_id, organization_id):
+                    updated = await self.get_by_id(conversation_id, organization_id)
+            except Exception as e:
+                await self.db.rollback()
+                logger.error(f"❌ Could not return conversation {conversation_id} to its flow: {e}")
+
+        if resolved:
+            await self._emit_ticket_resolved(updated, reason)
+
         return updated
 
+    async def _emit_ticket_resolved(self, conversation: Conversation, reason: Optional[str]) -> None:
+        """Start the flows of ticket.resolved event triggers (e.g. a satisfaction survey)"""
+        from app.services.flow_event_service import FlowEventService
+        from app.utils.flow_events import EVENT_TICKET_RESOLVED
+
+        try:
+            await FlowEventService(self.db).emit(
+                conversation.organization_id,
+                EVENT_TICKET_RESOLVED,
+                conversation.contact_id,
+                {
+                    "conversation_id": str(conversation.id),
+                    "agent_id": str(conversation.current_agent_id) if conversation.current_agent_id else None,
+                    "reason": reason,
+                },
+            )
+        except Exception as e:
+            logger.error(f"❌ Could not emit ticket.resolved for conversation {conversation.id}: {e}")
+
     async def check_and_apply_overflow(
         self, queue_id: UUID, organization_id: UUID
     ) -> Optional[UUID]:
@@ -0,0 +1,584 @@
+"""
+Dunning Service - Invoice reminder automation
+
+Rules such as "3 days before the due date send utility template A",
+"5 days overdue send template B with the Pix code" or "15 days overdue warn
+about suspension" are evaluated daily against invoices synced from the ERP:
+
+1. plan_reminders() creates one DunningReminder per (rule, invoice) whose
+   due date matches the rule offset today, scheduled at the rule's local time
+2. The reminder is enqueued on the delayed queue (Celery eta)
+3. send_reminder() re-checks the invoice (it may have been paid meanwhile)
+   and sends the template; when the number's messaging tier is full the
+   reminder is deferred to its reserved slot instead of failing
+
+Invoices paid after a reminder are credited to the last rule that reminded
+them (total_paid / recovered_amount), giving per-rule performance metrics.
+"""
+
+import logging
+import re
+from datetime import date, datetime, time, timedelta, timezone
+from decimal import Decimal
+from typing import List, Optional
+from uuid import UUID
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+from sqlalchemy import and_, select, update
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.models.contact import Contact
+from app.models.dunning import DunningReminder, DunningRule, Invoice
+from app.models.whatsapp_number import WhatsAppNumber, WhatsAppTemplate
+from app.schemas.dunning import (
+    DunningRuleCreate,
+    DunningRuleMetrics,
+    DunningRuleUpdate,
+    InvoiceSyncItem,
+    InvoiceSyncResponse,
+)
+
+logger = logging.getLogger(__name__)
+
+
+# ============================================
+# RULE EVALUATION (pure helpers)
+# ============================================
+
+# Template parameters available to rules (plus invoice.extra.<key>)
+KNOWN_VARIABLES = {
+    "contact.name",
+    "contact.phone",
+    "invoice.number",
+    "invoice.amount",
+    "invoice.due_date",
+    "invoice.days_overdue",
+    "invoice.pix_code",
+    "invoice.payment_url",
+}
+
+
+def _zone(tz_name: str) -> ZoneInfo:
+    try:
+        return ZoneInfo(tz_name)
+    except ZoneInfoNotFoundError:
+        return ZoneInfo("America/Sao_Paulo")
+
+
+def target_due_date(today: date, offset_days: int) -> date:
+    """Due date of the invoices a rule reminds on ``today``"""
+    return today - timedelta(days=offset_days)
+
+
+def scheduled_datetime(day: date, send_time: time, tz_name: str) -> datetime:
+    """Send moment (UTC) of a rule on a local day"""
+    return datetime.combine(day, send_time, tzinfo=_zone(tz_name)).astimezone(timezone.utc)
+
+
+def local_today(tz_name: str, now: Optional[datetime] = None) -> date:
+    """Current date in the rule's timezone"""
+    return (now or datetime.now(timezone.utc)).astimezone(_zone(tz_name)).date()
+
+
+def format_amount(amount: Decimal, currency: str) -> str:
+    """R$ 1.234,56 for BRL, "1234.56 USD" otherwise"""
+    if currency == "BRL":
+        formatted = f"{Decimal(amount):,.2f}".replace(",", "X").replace(".", ",").replace("X", ".")
+        return f"R$ {formatted}"
+    return f"{Decimal(amount):.2f} {currency}"
+
+
+def resolve_variable(path: str, invoice: Invoice, contact: Contact, today: Optional[date] = None) -> str:
+    """
+    Value of a template parameter
+
+    Supported: contact.name, contact.phone, invoice.number, invoice.amount,
+    invoice.due_date, invoice.days_overdue, invoice.pix_code,
+    invoice.payment_url and invoice.extra.<key>
+    """
+    today = today or date.today()
+
+    values = {
+        "contact.name": contact.display_name,
+        "contact.phone": contact.whatsapp_id,
+        "invoice.number": invoice.number or invoice.external_id,
+        "invoice.amount": format_amount(invoice.amount, invoice.currency),
+        "invoice.due_date": invoice.due_date.strftime("%d/%m/%Y"),
+        "invoice.days_overdue": str(max((today - invoice.due_date).days, 0)),
+        "invoice.pix_code": invoice.pix_code or "",
+        "invoice.payment_url": invoice.payment_url or "",
+    }
+    if path in values:
+        return values[path]
+
+    if path.startswith("invoice.extra."):
+        return str((invoice.extra_data or {}).get(path[len("invoice.extra."):], ""))
+
+    raise ValueError(f"Unknown dunning variable: {path}")
+
+
+def invoice_event_payload(invoice: Invoice, today: date) -> dict:
+    """Payload of the invoice.overdue flow event (JSON serializable)"""
+    return {
+        "invoice": {
+            "external_id": invoice.external_id,
+            "number": invoice.number or invoice.external_id,
+            "amount": format_amount(invoice.amount, invoice.currency),
+            "currency": invoice.currency,
+            "due_date": invoice.due_date.strftime("%d/%m/%Y"),
+            "days_overdue": max((today - invoice.due_date).days, 0),
+            "payment_url": invoice.payment_url or "",
+            "pix_code": invoice.pix_code or "",
+            "extra": invoice.extra_data or {},
+        },
+    }
+
+
+def skip_reason(rule: DunningRule, invoice: Invoice, contact: Optional[Contact]) -> Optional[str]:
+    """Why a reminder must not be sent (None when it can be sent)"""
+    if not invoice.is_open:
+        return f"invoice_{invoice.status}"
+    if not contact or contact.is_blocked or contact.deleted_at is not None:
+        return "contact_unavailable"
+    if rule.offer_pix and not invoice.pix_code:
+        return "missing_pix_code"
+    if rule.min_amount is not None and invoice.amount < rule.min_amount:
+        return "below_min_amount"
+    return None
+
+
+class DunningService:
+    """Service for ERP invoice sync and dunning rules"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    # ============= ERP Sync =============
+
+    async def sync_invoices(
+        self, organization_id: UUID, items: List[InvoiceSyncItem]
+    ) -> InvoiceSyncResponse:
+        """
+        Upsert invoices pushed by the ERP
+
+        Contacts are created by phone number when missing. Invoices that
+        become paid credit the last reminder's rule and cancel pending reminders.
+        """
+        from app.repositories.contact import ContactRepository
+
+        contact_repo = ContactRepository(self.db)
+        result = InvoiceSyncResponse()
+        now = datetime.now(timezone.utc)
+
+        for item in items:
+            whatsapp_id = re.sub(r"\D", "", item.customer_phone)
+            if not whatsapp_id:
+                raise BadRequestException(f"Invoice {item.external_id}: invalid customer_phone")
+
+            contact = await contact_repo.get_by_whatsapp_id(whatsapp_id, organization_id)
+            if not contact:
+                contact = await contact_repo.create({
+                    "organization_id": organization_id,
+                    "whatsapp_id": whatsapp_id,
+                    "name": item.customer_name,
+                    "source": "erp",
+                })
+
+            invoice = (await self.db.execute(
+                select(Invoice).where(
+                    Invoice.organization_id == organization_id,
+                    Invoice.external_id == item.external_id,
+                )
+            )).scalar_one_or_none()
+
+            data = item.model_dump(exclude={"customer_phone", "customer_name"})
+            data["contact_id"] = contact.id
+            data["last_synced_at"] = now
+
+            if invoice is None:
+                invoice = Invoice(organization_id=organization_id, **data)
+                self.db.add(invoice)
+                result.created += 1
+                continue
+
+            previous_status = invoice.status
+            previous_due_date = invoice.due_date
+            for field, value in data.items():
+                setattr(invoice, field, value)
+            result.updated += 1
+
+            # Renegotiated: the new due date can become overdue again
+            if invoice.due_date != previous_due_date:
+                invoice.overdue_event_at = None
+
+            if previous_status == "open" and invoice.status != "open":
+                if invoice.status == "paid":
+                    invoice.paid_at = invoice.paid_at or now
+                    await self._credit_payment(invoice)
+                    result.paid += 1
+                result.cancelled_reminders += await self._cancel_pending_reminders(
+                    invoice, f"invoice_{invoice.status}"
+                )
+
+        await self.db.commit()
+
+        logger.info(
+            f"📥 ERP sync: {result.created} created, {result.updated} updated, {result.paid} paid"
+        )
+        return result
+
+    async def _credit_payment(self, invoice: Invoice) -> None:
+        """Attribute a payment to the rule of the last reminder sent before it"""
+        last_sent = (await self.db.execute(
+            select(DunningReminder)
+            .where(
+                DunningReminder.invoice_id == invoice.id,
+                DunningReminder.status == "sent",
+            )
+            .order_by(DunningReminder.sent_at.desc())
+            .limit(1)
+        )).scalar_one_or_none()
+
+        if not last_sent:
+            return
+
+        await self.db.execute(
+            update(DunningRule)
+            .where(DunningRule.id == last_sent.rule_id)
+            .values(
+                total_paid=DunningRule.total_paid + 1,
+                recovered_amount=DunningRule.recovered_amount + invoice.amount,
+            )
+        )
+
+    async def _cancel_pending_reminders(self, invoice: Invoice, reason: str) -> int:
+        reminders = (await self.db.execute(
+            select(DunningReminder).where(
+                DunningReminder.invoice_id == invoice.id,
+                DunningReminder.status == "scheduled",
+            )
+        )).scalars().all()
+
+        for reminder in reminders:
+            await self._mark_skipped(reminder, reason)
+        return len(reminders)
+
+    # ============= Rules =============
+
+    async def list_rules(self, organization_id: UUID) -> List[DunningRule]:
+        result = await self.db.execute(
+            select(DunningRule)
+            .where(
+                DunningRule.organization_id == organization_id,
+                DunningRule.deleted_at.is_(None),
+            )
+            .order_by(DunningRule.offset_days)
+        )
+        return list(result.scalars().all())
+
+    async def get_rule(self, rule_id: UUID, organization_id: UUID) -> DunningRule:
+        rule = (await self.db.execute(
+            select(DunningRule).where(
+                DunningRule.id == rule_id,
+                DunningRule.organization_id == organization_id,
+                DunningRule.deleted_at.is_(None),
+            )
+        )).scalar_one_or_none()
+
+        if not rule:
+            raise NotFoundException("Dunning rule not found")
+        return rule
+
+    async def _validate_rule_target(
+        self, organization_id: UUID, whatsapp_number_id: UUID, template_id: UUID, variables: List[str]
+    ) -> None:
+        """Official number of the organization and an approved template of that number"""
+        number = (await self.db.execute(
+            select(WhatsAppNumber).where(
+                WhatsAppNumber.id == whatsapp_number_id,
+                WhatsAppNumber.organization_id == organization_id,
+            )
+        )).scalar_one_or_none()
+        if not number:
+            raise NotFoundException("WhatsApp number not found")
+        if number.connection_type != "official":
+            raise BadRequestException("Dunning reminders require an Official API number (templates)")
+
+        template = (await self.db.execute(
+            select(WhatsAppTemplate).where(
+                WhatsAppTemplate.id == template_id,
+                WhatsAppTemplate.organization_id == organization_id,
+                WhatsAppTemplate.deleted_at.is_(None),
+            )
+        )).scalar_one_or_none()
+        if not template:
+            raise NotFoundException("Template not found")
+        if template.whatsapp_number_id != whatsapp_number_id:
+            raise BadRequestException("Template belongs to another WhatsApp number")
+        if template.status != "APPROVED":
+            raise BadRequestException(f"Template '{template.name}' is not approved ({template.status})")
+        if len(variables) != (template.body_variables_count or 0):
+            raise BadRequestException(
+                f"Template '{template.name}' expects {template.body_variables_count} variables, "
+                f"got {len(variables)}"
+            )
+
+        for path in variables:
+            if not (path in KNOWN_VARIABLES or path.startswith("invoice.extra.")):
+                raise BadRequestException(f"Unknown dunning variable: {path}")
+
+    async def create_rule(self, organization_id: UUID, data: DunningRuleCreate) -> DunningRule:
+        await self._validate_rule_target(
+            organization_id, data.whatsapp_number_id, data.template_id, data.template_variables
+        )
+
+        rule = DunningRule(organization_id=organization_id, **data.model_dump())
+        self.db.add(rule)
+        await self.db.commit()
+        await self.db.refresh(rule)
+
+        logger.info(f"📅 Dunning rule '{rule.name}' created (offset {rule.offset_days} days)")
+        return rule
+
+    async def update_rule(
+        self, rule_id: UUID, organization_id: UUID, data: DunningRuleUpdate
+    ) -> DunningRule:
+        rule = await self.get_rule(rule_id, organization_id)
+        changes = data.model_dump(exclude_unset=True)
+
+        if {"whatsapp_number_id", "template_id", "template_variables"} & changes.keys():
+            await self._validate_rule_target(
+                organization_id,
+                changes.get("whatsapp_number_id", rule.whatsapp_number_id),
+                changes.get("template_id", rule.template_id),
+                changes.get("template_variables", rule.template_variables),
+            )
+
+        for field, value in changes.items():
+            setattr(rule, field, value)
+
+        await self.db.commit()
+        await self.db.refresh(rule)
+        return rule
+
+    async def delete_rule(self, rule_id: UUID, organization_id: UUID) -> None:
+        rule = await self.get_rule(rule_id, organization_id)
+        rule.deleted_at = datetime.now(timezone.utc)
+        rule.is_active = False
+        await self.db.commit()
+
+    async def get_rule_metrics(self, rule_id: UUID, organization_id: UUID) -> DunningRuleMetrics:
+        rule = await self.get_rule(rule_id, organization_id)
+        attempted = rule.total_sent + rule.total_failed
+
+        return DunningRuleMetrics(
+            rule_id=rule.id,
+            scheduled=rule.total_scheduled,
+            sent=rule.total_sent,
+            failed=rule.total_failed,
+            skipped=rule.total_skipped,
+            paid=rule.total_paid,
+            recovered_amount=rule.recovered_amount or Decimal("0"),
+            delivery_rate=round(rule.total_sent / attempted, 4) if attempted else 0.0,
+            conversion_rate=round(rule.total_paid / rule.total_sent, 4) if rule.total_sent else 0.0,
+        )
+
+    # ============= Planning & Sending =============
+
+    async def plan_reminders(self, now: Optional[datetime] = None) -> List[DunningReminder]:
+        """
+        Create today's reminders for every active rule
+
+        Idempotent: a (rule, invoice) pair is planned only once.
+
+        Returns:
+            New reminders, to be enqueued at their scheduled_for
+        """
+        now = now or datetime.now(timezone.utc)
+        planned: List[DunningReminder] = []
+
+        rules = (await self.db.execute(
+            select(DunningRule).where(
+                DunningRule.is_active.is_(True),
+                DunningRule.deleted_at.is_(None),
+            )
+        )).scalars().all()
+
+        for rule in rules:
+            today = local_today(rule.timezone, now)
+            due_date = target_due_date(today, rule.offset_days)
+
+            already_planned = select(DunningReminder.invoice_id).where(DunningReminder.rule_id == rule.id)
+            invoices = (await self.db.execute(
+                select(Invoice).where(
+                    and_(
+                        Invoice.organization_id == rule.organization_id,
+                        Invoice.status == "open",
+                        Invoice.due_date == due_date,
+                        Invoice.id.not_in(already_planned),
+                    )
+                )
+            )).scalars().all()
+
+            if not invoices:
+                continue
+
+            # Past send time (late beat run): send as soon as possible
+            scheduled_for = max(scheduled_datetime(today, rule.send_time, rule.timezone), now)
+
+            for invoice in invoices:
+                reminder = DunningReminder(
+                    organization_id=rule.organization_id,
+                    rule_id=rule.id,
+                    invoice_id=invoice.id,
+                    status="scheduled",
+                    scheduled_for=scheduled_for,
+                )
+                self.db.add(reminder)
+                planned.append(reminder)
+
+            await self.db.execute(
+                update(DunningRule)
+                .where(DunningRule.id == rule.id)
+                .values(total_scheduled=DunningRule.total_scheduled + len(invoices))
+            )
+            logger.info(f"📅 Rule '{rule.name}': {len(invoices)} reminder(s) planned for {due_date}")
+
+        await self.db.commit()
+        return planned
+
+    async def emit_overdue_events(self, now: Optional[datetime] = None) -> int:
+        """
+        Emit invoice.overdue for open invoices past their due date
+
+        Only organizations with an active invoice.overdue trigger are
+        checked; each invoice is announced once.
+
+        Returns:
+            Number of invoices announced
+        """
+        from app.repositories.chatbot import FlowEventTriggerRepository
+        from app.services.flow_event_service import FlowEventService
+        from app.utils.flow_events import EVENT_INVOICE_OVERDUE
+
+        now = now or datetime.now(timezone.utc)
+        today = local_today("America/Sao_Paulo", now)
+        events = FlowEventService(self.db)
+        emitted = 0
+
+        for organization_id in await FlowEventTriggerRepository(self.db).list_organizations_with(EVENT_INVOICE_OVERDUE):
+            invoices = (await self.db.execute(
+                select(Invoice).where(
+                    Invoice.organization_id == organization_id,
+                    Invoice.status == "open",
+                    Invoice.due_date < today,
+                    Invoice.overdue_event_at.is_(None),
+                )
+            )).scalars().all()
+
+            for invoice in invoices:
+                invoice.overdue_event_at = now
+                await events.emit(
+                    organization_id, EVENT_INVOICE_OVERDUE, invoice.contact_id, invoice_event_payload(invoice, today)
+                )
+                emitted += 1
+
+        await self.db.commit()
+        return emitted
+
+    async def send_reminder(self, reminder_id: UUID) -> str:
+        """
+        Send a planned reminder
+
+        Returns:
+            Final reminder status (sent, failed, skipped), or deferred when
+            the messaging tier is full (scheduled_for moves to the tier slot)
+        """
+        from app.core.whatsapp_rate_limit import get_tier_shaper
+        from app.integrations.meta_api import MetaAPIError, MetaCloudAPI
+
+        reminder = (await self.db.execute(
+            select(DunningReminder).where(DunningReminder.id == reminder_id)
+        )).scalar_one_or_none()
+
+        if not reminder or reminder.status != "scheduled":
+            return reminder.status if reminder else "missing"
+
+        rule = await self.db.get(DunningRule, reminder.rule_id)
+        invoice = await self.db.get(Invoice, reminder.invoice_id)
+        contact = await self.db.get(Contact, invoice.contact_id)
+
+        if not rule.is_active or rule.deleted_at is not None:
+            await self._mark_skipped(reminder, "rule_inactive")
+            await self.db.commit()
+            return "skipped"
+
+        reason = skip_reason(rule, invoice, contact)
+        if reason:
+            await self._mark_skipped(reminder, reason)
+            await self.db.commit()
+            return "skipped"
+
+        template = await self.db.get(WhatsAppTemplate, rule.template_id) if rule.template_id else None
+        number = await self.db.get(WhatsAppNumber, rule.whatsapp_number_id)
+        shaper = get_tier_shaper(number) if number else None
+        tier = None
+
+        try:
+            if not template or template.status != "APPROVED":
+                raise MetaAPIError("Template missing or not approved")
+
+            tier = await shaper.reserve(contact.whatsapp_id) if shaper else None
+            if tier and not tier.allowed:
+                reminder.scheduled_for = tier.send_at
+                await self.db.commit()
+                logger.info(
+                    f"⏳ Dunning reminder {reminder.id} deferred by {tier.tier} "
+                    f"until {tier.send_at.isoformat()}"
+                )
+                return "deferred"
+
+            today = local_today(rule.timezone)
+            parameters = [
+                {"type": "text", "text": resolve_variable(path, invoice, contact, today)}
+                for path in rule.template_variables or []
+            ]
+            components = [{"type": "body", "parameters": parameters}] if parameters else None
+
+            meta_api = MetaCloudAPI(phone_number_id=number.phone_number_id, access_token=number.access_token)
+            response = await meta_api.send_template_message(
+                to=contact.whatsapp_id,
+                template_name=template.name,
+                language_code=template.language,
+                components=components,
+            )
+        except (MetaAPIError, ValueError) as e:
+            if tier and tier.reserved:
+                await shaper.release(contact.whatsapp_id)
+            reminder.status = "failed"
+            reminder.error_message = str(e)
+            await self._increment(rule.id, "total_failed")
+            await self.db.commit()
+            logger.error(f"❌ Dunning reminder {reminder.id} failed: {e}")
+            return "failed"
+
+        reminder.status = "sent"
+        reminder.sent_at = datetime.now(timezone.utc)
+        reminder.whatsapp_message_id = (response.get("messages") or [{}])[0].get("id")
+        await self._increment(rule.id, "total_sent")
+        await self.db.commit()
+
+        logger.info(f"✅ Dunning reminder sent: rule '{rule.name}', invoice {invoice.external_id}")
+        return "sent"
+
+    async def _mark_skipped(self, reminder: DunningReminder, reason: str) -> None:
+        reminder.status = "skipped"
+        reminder.skip_reason = reason
+        await self._increment(reminder.rule_id, "total_skipped")
+
+    async def _increment(self, rule_id: UUID, counter: str) -> None:
+        column = getattr(DunningRule, counter)
+        await self.db.execute(
+            update(DunningRule).where(DunningRule.id == rule_id).values({counter: column + 1})
+        )
@@ -0,0 +1,666 @@
+"""
+Flow Test Console - chat with a flow over REST, without WhatsApp
+
+A developer opens a session on a (draft) flow and sends messages as if they
+were the contact. The console walks the flow's canvas_data with the same
+rules as the WhatsApp engine (question validation and attempts, condition
+branches, set_variable, loops, sandboxed scripts, first edge otherwise) and
+returns what the flow would have sent plus the session variables after every turn.
+
+Nothing leaves the platform: nodes with external side effects (api_call,
+ai_prompt, database_query, script, action, delay, ...) are reported as
+skipped and the flow follows their first edge. Their outputs can be mocked
+by passing variables when starting the session or with each message; the
+locale variable previews the nodes' translations. Split nodes draw their
+variant from the contact_id variable (the session otherwise), or follow the
+variant mocked in their saveToVariable; business hours nodes check the
+organization's hours now, or follow the output mocked in their
+saveToVariable ("open", "closed" or "holiday").
+
+Sessions are stored in PostgreSQL (flow_console_sessions) and cached in
+Redis, so a Redis flush or restart does not end them; both expire
+FLOW_CONSOLE_SESSION_TTL_SECONDS after the last turn. The flow is read again
+on every turn, so edits to the draft apply to open sessions.
+
+Simulations (POST /flows/{id}/simulate) run the same interpreter over a
+list of scripted messages in one request, without storing a session, and
+return the transcript with the variables after every turn; optional
+expectations make the result usable as a CI test of the flow.
+"""
+
+import json
+import logging
+import uuid
+from copy import deepcopy
+from datetime import datetime, timedelta, timezone
+from types import SimpleNamespace
+from typing import Any, Dict, List, Optional, Tuple
+from uuid import UUID
+
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.config import settings
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.core.redis import RedisClient, session_redis
+from app.models.organization import Organization
+from app.repositories.chatbot import FlowConsoleSessionRepository
+from app.schemas.flow_console import (
+    FlowConsoleInput,
+    FlowConsoleStart,
+    FlowSimulationExpectation,
+    FlowSimulationRequest,
+)
+from app.services.chatbot_service import ChatbotService
+from app.utils.business_hours import (
+    BUSINESS_HOURS_NODE_TYPE,
+    CLOSED_HANDLE,
+    HOLIDAY_HANDLE,
+    OPEN_HANDLE,
+    BusinessHoursStatus,
+    business_hours_status,
+)
+from app.utils.flow_compute import apply_set_variables
+from app.utils.flow_template import render_text
+from app.utils.flow_locale import LOCALE_VARIABLE, localize_node_data
+from app.utils.flow_loop import LOOP_BODY_HANDLE, LOOP_DONE_HANDLE, next_iteration
+from app.utils.flow_split import SPLIT_NODE_TYPE, assign_variant, experiment_key, split_variants
+from app.utils.flow_intent import INTENT_FALLBACK_HANDLE
+from app.utils.input_validation import DEFAULT_MAX_ATTEMPTS_MESSAGE, INVALID_HANDLE, max_attempts, validate_answer
+from app.utils.script_sandbox import (
+    ERROR_HANDLE as SCRIPT_ERROR_HANDLE,
+    SANDBOX_SCRIPT_NODE_TYPE,
+    SUCCESS_HANDLE as SCRIPT_SUCCESS_HANDLE,
+    SandboxLimits,
+    ScriptError,
+    ScriptResult,
+    run_script,
+)
+
+logger = logging.getLogger(__name__)
+
+SESSION_KEY = "flow_console:{session_id}"
+
+MEDIA_TYPES = ("image", "video", "document", "audio")
+
+# Nodes that would call external systems; the console skips them
+SKIPPED_NODE_TYPES = {
+    "action": "actions are not run in the console",
+    "api_call": "HTTP calls are not made in the console, mock the response variable",
+    "ai_prompt": "AI providers are not called in the console, mock the response variable",
+    "intent": "intents are not classified in the console, mock the intent variable (fallback otherwise)",
+    "database_query": "queries are not run in the console, mock the result variable",
+    "script": "scripts are not run in the console, mock the output variable",
+    "delay": "delays are not waited in the console",
+    "wait": "waits are not scheduled in the console, the flow resumes right away",
+    "jump": "jumps to other flows are not followed in the console",
+    "subflow": "sub-flows are not called in the console, the first edge is followed",
+    "random": "random branches are not drawn in the console, the first edge is followed",
+    "datetime": "date/time nodes are not evaluated in the console",
+    "analytics": "analytics events are not recorded in the console",
+    "media_collect": "media cannot be sent in the console, mock the output variable",
+    "location_collect": "locations cannot be shared in the console, the first edge is followed",
+    "survey": "survey answers are not recorded in the console, the first edge is followed",
+}
+
+
+def substitute_variables(text: str, variables: Dict[str, Any]) -> str:
+    """Render {{ var | filter }} placeholders like the WhatsApp engine"""
+    return render_text(text or "", variables)
+
+
+class FlowGraph:
+    """Nodes and edges of a flow's canvas_data"""
+
+    def __init__(self, canvas_data: Optional[Dict[str, Any]], default_locale: Optional[str] = None):
+        canvas_data = canvas_data or {}
+        self.default_locale = default_locale
+        self.nodes: Dict[str, Dict[str, Any]] = {}
+        for node in canvas_data.get("nodes", []):
+            if node.get("id"):
+                data = node.get("data") or {}
+                self.nodes[node["id"]] = {"type": data.get("nodeType", "custom"), "data": data}
+        self.edges: List[Dict[str, Any]] = canvas_data.get("edges", [])
+
+    def start_node_id(self) -> Optional[str]:
+        for node_id, node in self.nodes.items():
+            if node["type"] == "start":
+                return node_id
+        return None
+
+    def next_node_id(
+        self, node_id: str, condition_result: Any = None, source_handle: Optional[str] = None
+    ) -> Optional[str]:
+        """Same edge rules as WhatsAppService._advance_to_next_node"""
+        outgoing = [edge for edge in self.edges if edge.get("source") == node_id]
+
+        if source_handle is not None:
+            return next((edge.get("target") for edge in outgoing if edge.get("sourceHandle") == source_handle), None)
+
+        if condition_result is None:
+            default = [edge for edge in outgoing if edge.get("sourceHandle") != INVALID_HANDLE]
+            return default[0].get("target") if default else None
+
+        # bool is checked first: True/False are ints too
+        if isinstance(condition_result, bool):
+            labels = ("true", "yes", "sim") if condition_result else ("false", "no", "não")
+            for edge in outgoing:
+                if (edge.get("label") or "").lower() in labels:
+                    return edge.get("target")
+            return None
+
+        handle = f"condition-{condition_result}"
+        for edge in outgoing:
+            if edge.get("sourceHandle") == handle:
+                return edge.get("target")
+        return None
+
+
+class FlowConsoleService:
+    """Service for flow console sessions"""
+
+    def __init__(self, db: AsyncSession, redis: RedisClient = session_redis):
+        self.db = db
+        self.redis = redis
+        self.chatbot_service = ChatbotService(db)
+        self.sessions = FlowConsoleSessionRepository(db)
+
+    # ============================================
+    # SESSIONS
+    # ============================================
+
+    async def start_session(
+        self, flow_id: UUID, organization_id: UUID, user_id: UUID, data: FlowConsoleStart
+    ) -> Dict[str, Any]:
+        """Open a session and run the flow from its start node until it waits for input"""
+        graph = await self._get_graph(flow_id, organization_id)
+        start_node_id = graph.start_node_id()
+        if not start_node_id:
+            raise BadRequestException("Flow has no start node")
+
+        session = self._new_session(flow_id, organization_id, user_id, start_node_id, data.variables)
+        await self._run(session, graph, start_node_id)
+        await self._save(session)
+
+        logger.info(f"🧪 Console session {session['session_id']} started on flow {flow_id}")
+        return session
+
+    async def send_message(
+        self, flow_id: UUID, session_id: str, organization_id: UUID, data: FlowConsoleInput
+    ) -> Dict[str, Any]:
+        """Answer the node the flow is waiting on and run until it waits again"""
+        session = await self.get_session(flow_id, session_id, organization_id)
+        if session["finished"] or not session["waiting_for_input"]:
+            raise BadRequestException("Console session has finished, start a new one")
+
+        graph = await self._get_graph(flow_id, organization_id)
+        await self._process_input(session, graph, data)
+
+        await self._save(session)
+        return session
+
+    async def simulate(
+        self, flow_id: UUID, organization_id: UUID, user_id: UUID, data: FlowSimulationRequest
+    ) -> Dict[str, Any]:
+        """
+        Run the flow against scripted contact messages and return the transcript
+
+        Same interpreter as the console sessions, but nothing is stored: the
+        run starts at the start node, answers each input in order while the
+        flow waits for one, and records outputs, steps and variables per turn.
+        Runs the draft, or a published version when data.version is given.
+        """
+        version = None
+        if data.version is not None:
+            version = await self.chatbot_service.get_flow_version(flow_id, organization_id, data.version)
+            flow = await self.chatbot_service.get_flow(flow_id, organization_id)
+            graph = FlowGraph(version.canvas_data, flow.default_locale)
+        else:
+            graph = await self._get_graph(flow_id, organization_id)
+
+        start_node_id = graph.start_node_id()
+        if not start_node_id:
+            raise BadRequestException("Flow has no start node")
+
+        session = self._new_session(flow_id, organization_id, user_id, start_node_id, data.variables)
+        session["session_id"] = f"simulation-{session['session_id']}"
+        await self._run(session, graph, start_node_id)
+        turns = [self._transcript_turn(session, None)]
+
+        unused_inputs = 0
+        for message in data.inputs:
+            if session["finished"] or not session["waiting_for_input"]:
+                unused_inputs += 1
+                continue
+            await self._process_input(session, graph, message)
+            turns.append(self._transcript_turn(session, message.text))
+
+        failures = self._check_expectations(session, data.expect) if data.expect else []
+
+        logger.info(
+            f"🧪 Simulated flow {flow_id}: {len(turns)} turns, "
+            f"{'passed' if not failures else f'{len(failures)} failed expectation(s)'}"
+        )
+        return {
+            "flow_id": flow_id,
+            "version": version.version if version else None,
+            "turns": turns,
+            "finished": session["finished"],
+            "current_node_id": session["current_node_id"],
+            "variables": session["variables"],
+            "unused_inputs": unused_inputs,
+            "passed": not failures,
+            "failures": failures,
+        }
+
+    async def get_session(self, flow_id: UUID, session_id: str, organization_id: UUID) -> Dict[str, Any]:
+        """Session from the Redis cache, or from PostgreSQL (re-cached) when Redis misses"""
+        try:
+            raw = await self.redis.get(SESSION_KEY.format(session_id=session_id))
+        except Exception as e:
+            logger.warning(f"⚠️ Could not load console session {session_id} from Redis: {e}")
+            raw = None
+
+        session = json.loads(raw) if raw else None
+        if session is None:
+            stored = await self.sessions.get_active(session_id, datetime.now(timezone.utc))
+            if stored:
+                session = stored.state
+                await self._cache(session)
+
+        if (
+            not session
+            or session["flow_id"] != str(flow_id)
+            or session["organization_id"] != str(organization_id)
+        ):
+            raise NotFoundException("Console session not found")
+        return session
+
+    async def end_session(self, flow_id: UUID, session_id: str, organization_id: UUID) -> None:
+        await self.get_session(flow_id, session_id, organization_id)
+        await self.sessions.delete_by_session_id(session_id)
+        await self.db.commit()
+        try:
+            await self.redis.delete(SESSION_KEY.format(session_id=session_id))
+        except Exception as e:
+            logger.warning(f"⚠️ Could not drop console session {session_id} from Redis: {e}")
+
+    async def purge_expired(self) -> int:
+        """Delete sessions idle for more than FLOW_CONSOLE_SESSION_TTL_SECONDS"""
+        return await self.sessions.delete_expired(datetime.now(timezone.utc))
+
+    # ============================================
+    # INTERPRETER
+    # ============================================
+
+    async def _process_input(self, session: Dict[str, Any], graph: FlowGraph, data: FlowConsoleInput) -> None:
+        """One contact turn: answer the waiting node and run until the flow waits again"""
+        text = data.text.strip()
+        if not text:
+            raise BadRequestException("Message text is empty")
+
+        session["variables"].update(data.variables)
+        session["outputs"] = []
+        session["steps"] = []
+        session["turns"] += 1
+
+        node_id = session["current_node_id"]
+        node = graph.nodes.get(node_id)
+        if not node:
+            self._fail(session, node_id, "Node no longer exists in the flow")
+        else:
+            next_node_id, proceed = await self._answer(session, graph, node_id, node, text)
+            if proceed:
+                await self._run(session, graph, next_node_id)
+
+        session["updated_at"] = datetime.now(timezone.utc).isoformat()
+
+    async def _run(self, session: Dict[str, Any], graph: FlowGraph, node_id: Optional[str]) -> None:
+        """Execute nodes from node_id until one waits for input or the flow ends"""
+        for _ in range(settings.FLOW_CONSOLE_MAX_STEPS):
+            if node_id is None:
+                # No edge out of the last node: the flow ends there, like in the engine
+                session["finished"] = True
+                return
+
+            node = graph.nodes.get(node_id)
+            if not node:
+                self._fail(session, node_id, "Edge points to a node that does not exist")
+                return
+
+            session["current_node_id"] = node_id
+            node_id, proceed = await self._execute(session, graph, node_id, node)
+            if not proceed:
+                return
+
+        self._fail(
+            session,
+            node_id,
+            f"Stopped after {settings.FLOW_CONSOLE_MAX_STEPS} nodes without waiting for input (loop?)",
+        )
+
+    async def _execute(
+        self, session: Dict[str, Any], graph: FlowGraph, node_id: str, node: Dict[str, Any]
+    ) -> Tuple[Optional[str], bool]:
+        """
+        Execute one node
+
+        Returns:
+            (next node id, whether to keep running)
+        """
+        variables = session["variables"]
+        node_type = node["type"]
+        data = localize_node_data(node["data"], variables.get(LOCALE_VARIABLE), graph.default_locale)
+
+        if node_type == "start":
+            self._step(session, node_id, node_type, "executed")
+
+        elif node_type == "question":
+            text = substitute_variables(data.get("questionText", ""), variables)
+            if text:
+                self._output(session, "text", node_id, text)
+                self._step(session, node_id, node_type, "waiting")
+                session["waiting_for_input"] = True
+                return None, False
+            self._step(session, node_id, node_type, "executed", "No question text, advancing")
+
+        elif node_type == "message":
+            media_type = data.get("mediaType")
+            if media_type in MEDIA_TYPES:
+                self._output(session, "media", node_id, substitute_variables(data.get("caption", ""), variables), {
+                    "media_type": media_type,
+                    "media_url": substitute_variables(data.get("mediaUrl", ""), variables),
+                })
+            elif data.get("messageText"):
+                self._output(session, "text", node_id, substitute_variables(data["messageText"], variables))
+            self._step(session, node_id, node_type, "executed")
+
+        elif node_type == "end":
+            if data.get("farewellMessage"):
+                self._output(session, "text", node_id, substitute_variables(data["farewellMessage"], variables))
+            self._step(session, node_id, node_type, "executed", "Flow finished")
+            session["finished"] = True
+            return None, False
+
+        elif node_type == "handoff":
+            text = None
+            if data.get("sendTransferMessage", True):
+                text = substitute_variables(
+                    data.get("transferMessage", "Transferindo para um agente humano..."), variables
+                )
+            self._output(session, "handoff", node_id, text, {
+                "handoff_type": data.get("handoffType", "queue"),
+                "priority": data.get("priority", "normal"),
+                "return_to_flow": bool(data.get("returnToFlow")),
+            })
+            self._step(session, node_id, node_type, "executed", "Conversation would be handed to an agent")
+            session["finished"] = True
+            return None, False
+
+        elif node_type == "condition":
+            result = await self._engine().evaluate_conditions(
+                SimpleNamespace(context_variables=variables), data
+            )
+            self._step(session, node_id, node_type, "executed", f"Condition result: {result}")
+            next_node_id = graph.next_node_id(node_id, result)
+            if result is not None and not next_node_id:
+                self._fail(session, node_id, f"No edge for condition result {result}")
+                return None, False
+            return next_node_id, True
+
+        elif node_type == "set_variable":
+            errors = apply_set_variables(data.get("variables", []), variables)
+            detail = "; ".join(f"{name}: {error}" for name, error in errors) or None
+            self._step(session, node_id, node_type, "executed", detail)
+
+        elif node_type == "loop":
+            condition_met = None
+            if data.get("loopType") == "condition":
+                condition_met = await self._engine().evaluate_conditions(
+                    SimpleNamespace(context_variables=variables), data
+                )
+            run, detail = next_iteration(node_id, data, variables, condition_met)
+            self._step(session, node_id, node_type, "executed", detail)
+            return graph.next_node_id(node_id, source_handle=LOOP_BODY_HANDLE if run else LOOP_DONE_HANDLE), True
+
+        elif node_type == SPLIT_NODE_TYPE:
+            variants = split_variants(data)
+            mocked = variables.get(data.get("saveToVariable") or "")
+            variant = next((variant for variant in variants if variant["id"] == str(mocked)), None)
+            if variant:
+                detail = f"Variant {variant['id']} (mocked)"
+            else:
+                subject = variables.get("contact_id") or session["session_id"]
+                variant = assign_variant(variants, experiment_key(data, session["flow_id"], node_id), subject)
+                detail = f"Variant {variant['id']}" if variant else "No variant with traffic"
+            self._step(session, node_id, node_type, "executed", detail)
+            if not variant:
+                return graph.next_node_id(node_id), True
+            if data.get("saveToVariable"):
+                variables[data["saveToVariable"]] = variant["id"]
+            return graph.next_node_id(node_id, source_handle=variant["id"]), True
+
+        elif node_type == BUSINESS_HOURS_NODE_TYPE:
+            mocked = variables.get(data.get("saveToVariable") or "")
+            if mocked in (OPEN_HANDLE, CLOSED_HANDLE, HOLIDAY_HANDLE):
+                handle, detail = mocked, f"{mocked.capitalize()} (mocked)"
+            else:
+                status = await self._business_hours_status(session)
+                handle = status.handle
+                detail = handle.capitalize() + (f" ({status.holiday})" if status.holiday else "")
+                if data.get("nextOpenVariable"):
+                    variables[data["nextOpenVariable"]] = (
+                        status.next_open.strftime("%d/%m/%Y %H:%M") if status.next_open else None
+                    )
+            if data.get("saveToVariable"):
+                variables[data["saveToVariable"]] = handle
+            self._step(session, node_id, node_type, "executed", detail)
+            next_node_id = graph.next_node_id(node_id, source_handle=handle)
+            if handle == HOLIDAY_HANDLE and not next_node_id:
+                next_node_id = graph.next_node_id(node_id, source_handle=CLOSED_HANDLE)
+            return next_node_id, True
+
+        elif node_type == SANDBOX_SCRIPT_NODE_TYPE:
+            error_handling = data.get("errorHandling") or {}
+            public = {name: value for name, value in variables.items() if not name.startswith("_")}
+            try:
+                outcome = await run_script(data.get("code", ""), public, SandboxLimits.from_node(data))
+            except ScriptError as e:
+                outcome = ScriptResult(ok=False, error=str(e))
+            if not outcome.ok and error_handling.get("onError") == "stop":
+                self._fail(session, node_id, f"Script failed: {outcome.error}")
+                return None, False
+            self._step(session, node_id, node_type, "executed" if outcome.ok else "error", outcome.error)
+
+            value = outcome.result if outcome.ok else error_handling.get("fallbackValue")
+            if data.get("outputVariable") and (outcome.ok or value is not None):
+                variables[data["outputVariable"]] = value
+            if error_handling.get("onError") == "branch":
+                return graph.next_node_id(
+                    node_id, source_handle=SCRIPT_SUCCESS_HANDLE if outcome.ok else SCRIPT_ERROR_HANDLE
+                ), True
+
+        elif node_type in ("interactive_buttons", "interactive_list"):
+            content = {
+                key: data[key]
+                for key in ("headerText", "footerText", "buttonText", "buttons", "sections")
+                if key in data
+            }
+            self._output(
+                session, "interactive", node_id, substitute_variables(data.get("bodyText", ""), variables), content
+            )
+            self._step(session, node_id, node_type, "executed")
+
+        elif node_type == "whatsapp_template":
+            self._output(session, "template", node_id, None, {
+                "template_name": data.get("templateName"),
+                "language_code": data.get("languageCode", "pt_BR"),
+            })
+            self._step(session, node_id, node_type, "executed")
+
+        else:
+            reason = SKIPPED_NODE_TYPES.get(node_type, f"Node type {node_type} is not supported by the console")
+            self._step(session, node_id, node_type, "skipped", reason)
+
+            # API calls with success/error outputs continue as if the call worked
+            if node_type == "api_call" and (data.get("errorHandling") or {}).get("onError") == "branch":
+                return graph.next_node_id(node_id, source_handle="success"), True
+
+            # Intent nodes follow the mocked intent variable, or their fallback output
+            if node_type == "intent":
+                intent = variables.get(data.get("outputVariable", "intent"))
+                return graph.next_node_id(node_id, source_handle=intent or INTENT_FALLBACK_HANDLE), True
+
+        return graph.next_node_id(node_id), True
+
+    async def _answer(
+        self, session: Dict[str, Any], graph: FlowGraph, node_id: str, node: Dict[str, Any], text: str
+    ) -> Tuple[Optional[str], bool]:
+        """Validate the answer to a question node, like the engine's retry system"""
+        data = localize_node_data(node["data"], session["variables"].get(LOCALE_VARIABLE), graph.default_locale)
+        session["waiting_for_input"] = False
+
+        answer = validate_answer(text, data)
+        if not answer.valid:
+            attempts = session["attempts"].get(node_id, 0) + 1
+            attempts_limit = max_attempts(data)
+
+            if attempts < attempts_limit:
+                session["attempts"][node_id] = attempts
+                self._output(session, "error", node_id, answer.error)
+                self._step(session, node_id, node["type"], "waiting", f"Invalid answer ({attempts}/{attempts_limit})")
+                session["waiting_for_input"] = True
+                return None, False
+
+            session["attempts"].pop(node_id, None)
+            validation = data.get("validation") or {}
+            self._output(session, "error", node_id, validation.get("maxAttemptsMessage") or DEFAULT_MAX_ATTEMPTS_MESSAGE)
+            invalid_node_id = graph.next_node_id(node_id, source_handle=INVALID_HANDLE)
+            if invalid_node_id:
+                self._step(session, node_id, node["type"], "executed", "Max attempts reached, following the invalid output")
+                return invalid_node_id, True
+            self._step(session, node_id, node["type"], "executed", "Max attempts reached, answer discarded")
+            return graph.next_node_id(node_id), True
+
+        variable_name = data.get("outputVariable") or f"user_response_{node_id.replace('node-', '')}"
+        session["variables"][variable_name] = text
+        if (data.get("validation") or {}).get("normalizedVariable"):
+            session["variables"][data["validation"]["normalizedVariable"]] = answer.value
+        session["attempts"].pop(node_id, None)
+        self._step(session, node_id, node["type"], "executed", f"Saved answer to {variable_name}")
+        return graph.next_node_id(node_id), True
+
+    async def _business_hours_status(self, session: Dict[str, Any]) -> BusinessHoursStatus:
+        """The organization's business hours right now"""
+        organization = await self.db.get(Organization, UUID(session["organization_id"]))
+        org_settings = (organization.settings if organization else None) or {}
+        return business_hours_status(
+            org_settings.get("business_hours"), datetime.now(timezone.utc), org_settings.get("timezone")
+        )
+
+    def _engine(self):
+        """Condition rules shared with the WhatsApp engine"""
+        from app.services.whatsapp_service import WhatsAppService
+
+        engine = WhatsAppService(self.db)
+        return SimpleNamespace(
+            evaluate_conditions=engine._evaluate_conditions,
+        )
+
+    # ============================================
+    # HELPERS
+    # ============================================
+
+    @staticmethod
+    def _new_session(
+        flow_id: UUID, organization_id: UUID, user_id: UUID, start_node_id: str, variables: Dict[str, Any]
+    ) -> Dict[str, Any]:
+        now = datetime.now(timezone.utc).isoformat()
+        return {
+            "session_id": uuid.uuid4().hex,
+            "flow_id": str(flow_id),
+            "organization_id": str(organization_id),
+            "user_id": str(user_id),
+            "current_node_id": start_node_id,
+            "waiting_for_input": False,
+            "finished": False,
+            "variables": dict(variables),
+            "attempts": {},
+            "outputs": [],
+            "steps": [],
+            "turns": 0,
+            "created_at": now,
+            "updated_at": now,
+        }
+
+    @staticmethod
+    def _transcript_turn(session: Dict[str, Any], text: Optional[str]) -> Dict[str, Any]:
+        return {
+            "input": text,
+            "outputs": list(session["outputs"]),
+            "steps": list(session["steps"]),
+            "variables": deepcopy(session["variables"]),
+            "current_node_id": session["current_node_id"],
+            "waiting_for_input": session["waiting_for_input"],
+            "finished": session["finished"],
+        }
+
+    @staticmethod
+    def _check_expectations(session: Dict[str, Any], expect: FlowSimulationExpectation) -> List[str]:
+        failures = []
+        if expect.finished is not None and session["finished"] != expect.finished:
+            failures.append(f"Expected finished={expect.finished}, got {session['finished']}")
+        if expect.node_id is not None and session["current_node_id"] != expect.node_id:
+            failures.append(f"Expected to stop on node {expect.node_id}, stopped on {session['current_node_id']}")
+        for name, expected in expect.variables.items():
+            if name not in session["variables"]:
+                failures.append(f"Expected variable {name}={expected!r}, it is not set")
+            elif session["variables"][name] != expected:
+                failures.append(f"Expected variable {name}={expected!r}, got {session['variables'][name]!r}")
+        return failures
+
+    @staticmethod
+    def _output(
+        session: Dict[str, Any],
+        output_type: str,
+        node_id: Optional[str],
+        text: Optional[str],
+        content: Optional[Dict[str, Any]] = None,
+    ) -> None:
+        session["outputs"].append({"type": output_type, "node_id": node_id, "text": text, "content": content or {}})
+
+    @staticmethod
+    def _step(
+        session: Dict[str, Any], node_id: str, node_type: str, status: str, detail: Optional[str] = None
+    ) -> None:
+        session["steps"].append({"node_id": node_id, "node_type": node_type, "status": status, "detail": detail})
+
+    def _fail(self, session: Dict[str, Any], node_id: Optional[str], detail: str) -> None:
+        logger.warning(f"⚠️ Console session {session['session_id']}: {detail}")
+        self._output(session, "error", node_id, detail)
+        self._step(session, node_id or "", "unknown", "error", detail)
+        session["finished"] = True
+        session["waiting_for_input"] = False
+
+    async def _get_graph(self, flow_id: UUID, organization_id: UUID) -> FlowGraph:
+        flow = await self.chatbot_service.get_flow(flow_id, organization_id)
+        if not flow:
+            raise NotFoundException("Flow not found")
+        return FlowGraph(flow.canvas_data, flow.default_locale)
+
+    async def _save(self, session: Dict[str, Any]) -> None:
+        """Store the session in PostgreSQL, then refresh the Redis cache"""
+        expires_at = datetime.now(timezone.utc) + timedelta(seconds=settings.FLOW_CONSOLE_SESSION_TTL_SECONDS)
+        await self.sessions.save(json.loads(json.dumps(session, default=str)), expires_at)
+        await self.db.commit()
+        await self._cache(session)
+
+    async def _cache(self, session: Dict[str, Any]) -> None:
+        # The cache is best effort: PostgreSQL has the session
+        try:
+            await self.redis.set(
+                SESSION_KEY.format(session_id=session["session_id"]),
+                json.dumps(session, default=str),
+                expire=settings.FLOW_CONSOLE_SESSION_TTL_SECONDS,
+            )
+        except Exception as e:
+            logger.warning(f"⚠️ Could not cache console session {session['session_id']}: {e}")
@@ -0,0 +1,285 @@
+"""
+Flow Events - internal and external events that start flows
+
+Event triggers start a flow for a contact without an inbound message:
+campaign replies, overdue ERP invoices and resolved tickets are emitted by
+the platform, and external systems post their own events to
+/flow-events/emit (see app.utils.flow_events for payloads and conditions).
+
+Emitting only queues a run per matching trigger; the worker starts the flow
+in the contact's conversation on the trigger's number. A trigger with an
+approved template is outbound-initiated: the template opens the
+conversation and the flow starts when the contact replies.
+"""
+
+import logging
+import re
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.models.chatbot import FlowEventTrigger
+from app.models.contact import Contact
+from app.models.whatsapp_number import WhatsAppNumber, WhatsAppTemplate
+from app.repositories.chatbot import FlowEventTriggerRepository, FlowRepository
+from app.schemas.flow_event import (
+    FlowEventEmit,
+    FlowEventEmitResult,
+    FlowEventTriggerCreate,
+    FlowEventTriggerUpdate,
+)
+from app.utils.flow_events import event_matches, event_variables
+
+logger = logging.getLogger(__name__)
+
+
+class FlowEventService:
+    """Service for flow event trigger operations"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+        self.repo = FlowEventTriggerRepository(db)
+        self.flow_repo = FlowRepository(db)
+
+    # ============= Triggers =============
+
+    async def list_triggers(self, organization_id: UUID, event_type: Optional[str] = None) -> List[FlowEventTrigger]:
+        """Event triggers of the organization, oldest first"""
+        return await self.repo.list_by_organization(organization_id, event_type)
+
+    async def get_trigger(self, trigger_id: UUID, organization_id: UUID) -> FlowEventTrigger:
+        """
+        Get event trigger by ID
+
+        Raises:
+            NotFoundException: If trigger not found
+        """
+        trigger = await self.repo.get(trigger_id)
+        if not trigger or trigger.organization_id != organization_id:
+            raise NotFoundException("Event trigger not found")
+        return trigger
+
+    async def create_trigger(self, data: FlowEventTriggerCreate, organization_id: UUID) -> FlowEventTrigger:
+        """
+        Create an event trigger
+
+        Raises:
+            NotFoundException: If flow, number or template not found
+            BadRequestException: If the template cannot be sent from the number
+        """
+        await self._validate_target(
+            organization_id, data.flow_id, data.whatsapp_number_id, data.template_id, data.template_variables
+        )
+        trigger = await self.repo.create({**data.model_dump(), "organization_id": organization_id})
+        logger.info(f"⚡ Event trigger '{trigger.event_type}' created for flow {trigger.flow_id}")
+        return trigger
+
+    async def update_trigger(
+        self, trigger_id: UUID, data: FlowEventTriggerUpdate, organization_id: UUID
+    ) -> FlowEventTrigger:
+        """
+        Update an event trigger (template_id null removes the template)
+
+        Raises:
+            NotFoundException: If trigger, flow, number or template not found
+            BadRequestException: If the template cannot be sent from the number
+        """
+        trigger = await self.get_trigger(trigger_id, organization_id)
+        update_data = data.model_dump(exclude_unset=True)
+        update_data = {
+            field: value for field, value in update_data.items() if value is not None or field == "template_id"
+        }
+
+        await self._validate_target(
+            organization_id,
+            update_data.get("flow_id", trigger.flow_id),
+            update_data.get("whatsapp_number_id", trigger.whatsapp_number_id),
+            update_data.get("template_id", trigger.template_id),
+            update_data.get("template_variables", trigger.template_variables),
+        )
+        return await self.repo.update(trigger_id, update_data)
+
+    async def delete_trigger(self, trigger_id: UUID, organization_id: UUID) -> None:
+        """
+        Delete an event trigger
+
+        Raises:
+            NotFoundException: If trigger not found
+        """
+        await self.get_trigger(trigger_id, organization_id)
+        await self.repo.delete(trigger_id)
+
+    async def _validate_target(
+        self,
+        organization_id: UUID,
+        flow_id: UUID,
+        whatsapp_number_id: UUID,
+        template_id: Optional[UUID],
+        template_variables: List[str],
+    ) -> None:
+        """Flow and number of the organization; an approved template of that number when set"""
+        flow = await self.flow_repo.get(flow_id)
+        if not flow or flow.organization_id != organization_id or flow.deleted_at:
+            raise NotFoundException("Flow not found")
+
+        number = (await self.db.execute(
+            select(WhatsAppNumber).where(
+                WhatsAppNumber.id == whatsapp_number_id,
+                WhatsAppNumber.organization_id == organization_id,
+            )
+        )).scalar_one_or_none()
+        if not number:
+            raise NotFoundException("WhatsApp number not found")
+
+        if not template_id:
+            return
+        if number.connection_type != "official":
+            raise BadRequestException("Outbound-initiated flows require an Official API number (templates)")
+
+        template = (await self.db.execute(
+            select(WhatsAppTemplate).where(
+                WhatsAppTemplate.id == template_id,
+                WhatsAppTemplate.organization_id == organization_id,
+                WhatsAppTemplate.deleted_at.is_(None),
+            )
+        )).scalar_one_or_none()
+        if not template:
+            raise NotFoundException("Template not found")
+        if template.whatsapp_number_id != whatsapp_number_id:
+            raise BadRequestException("Template belongs to another WhatsApp number")
+        if template.status != "APPROVED":
+            raise BadRequestException(f"Template '{template.name}' is not approved ({template.status})")
+        if len(template_variables or []) != (template.body_variables_count or 0):
+            raise BadRequestException(
+                f"Template '{template.name}' expects {template.body_variables_count} variables, "
+                f"got {len(template_variables or [])}"
+            )
+
+    # ============= Events =============
+
+    async def emit(
+        self, organization_id: UUID, event_type: str, contact_id: UUID, payload: Dict[str, Any]
+    ) -> List[FlowEventTrigger]:
+        """
+        Queue a run of every active trigger the event fires
+
+        The payload must be JSON serializable (it goes through the task queue).
+
+        Returns:
+            Triggers queued
+        """
+        triggers = [
+            trigger
+            for trigger in await self.repo.list_runnable(organization_id, event_type)
+            if event_matches(trigger.conditions, payload)
+        ]
+        for trigger in triggers:
+            self._enqueue_run(trigger.id, contact_id, event_type, payload)
+
+        if triggers:
+            logger.info(f"⚡ Event {event_type} for contact {contact_id}: {len(triggers)} trigger(s) queued")
+        return triggers
+
+    async def emit_external(self, organization_id: UUID, data: FlowEventEmit) -> FlowEventEmitResult:
+        """
+        Emit an event posted by an external system
+
+        Contacts given by phone are created when missing.
+
+        Raises:
+            NotFoundException: If contact_id is not a contact of the organization
+            BadRequestException: If the phone is invalid
+        """
+        from app.repositories.contact import ContactRepository
+
+        contact_repo = ContactRepository(self.db)
+        if data.contact_id:
+            contact = await self.db.get(Contact, data.contact_id)
+            if not contact or contact.organization_id != organization_id or contact.deleted_at:
+                raise NotFoundException("Contact not found")
+        else:
+            whatsapp_id = re.sub(r"\D", "", data.phone)
+            if not whatsapp_id:
+                raise BadRequestException("Invalid phone")
+            contact = await contact_repo.get_by_whatsapp_id(whatsapp_id, organization_id)
+            if not contact:
+                contact = await contact_repo.create({
+                    "organization_id": organization_id,
+                    "whatsapp_id": whatsapp_id,
+                    "name": data.name,
+                    "source": "api",
+                })
+
+        triggers = await self.emit(organization_id, data.event_type, contact.id, data.payload)
+        return FlowEventEmitResult(contact_id=contact.id, trigger_ids=[trigger.id for trigger in triggers])
+
+    def _enqueue_run(self, trigger_id: UUID, contact_id: UUID, event_type: str, payload: Dict[str, Any]) -> None:
+        from app.tasks.flow_event_tasks import run_flow_event_trigger
+
+        run_flow_event_trigger.apply_async(args=[str(trigger_id), str(contact_id), event_type, payload])
+
+    # ============= Worker =============
+
+    async def run_trigger(
+        self, trigger_id: UUID, contact_id: UUID, event_type: str, payload: Dict[str, Any]
+    ) -> str:
+        """
+        Start the trigger's flow for the contact
+
+        Returns:
+            started, opened (template sent, flow waits for the reply), skipped or failed
+        """
+        trigger = await self.repo.get(trigger_id)
+        contact = await self.db.get(Contact, contact_id)
+        if not trigger or not trigger.is_active or not contact or contact.deleted_at:
+            return "skipped"
+        return await self._run(trigger, contact, event_type, payload)
+
+    async def run_reply_event(
+        self, conversation, contact: Contact, event_type: str, payload: Dict[str, Any]
+    ) -> bool:
+        """
+        Run the first matching trigger of an event the contact's message caused
+
+        Used for campaign replies: runs inline (before the chatbot handles the
+        message) on the conversation's number, and without the trigger's
+        template since the contact just opened the window.
+
+        Returns:
+            True if a flow started
+        """
+        for trigger in await self.repo.list_runnable(conversation.organization_id, event_type):
+            if trigger.whatsapp_number_id != conversation.whatsapp_number_id:
+                continue
+            if not event_matches(trigger.conditions, payload):
+                continue
+            outcome = await self._run(trigger, contact, event_type, payload, use_template=False)
+            return outcome == "started"
+        return False
+
+    async def _run(
+        self,
+        trigger: FlowEventTrigger,
+        contact: Contact,
+        event_type: str,
+        payload: Dict[str, Any],
+        use_template: bool = True,
+    ) -> str:
+        from app.services.whatsapp_service import WhatsAppService
+
+        variables = event_variables(event_type, payload, trigger.variable_mapping)
+        try:
+            outcome = await WhatsAppService(self.db).start_flow_from_event(
+                trigger, contact, variables, use_template=use_template
+            )
+        except Exception as e:
+            await self.db.rollback()
+            logger.error(f"❌ Event trigger {trigger.id} failed for contact {contact.id}: {e}")
+            outcome = "failed"
+
+        await self.repo.record_run(trigger.id, "started" if outcome in ("started", "opened") else outcome)
+        await self.db.commit()
+        return outcome
@@ -0,0 +1,191 @@
+"""
+Flow Template Service - instantiate gallery templates and upgrade their flows
+
+Templates declare parameters (see app.utils.flow_template_params); the
+instantiated flow remembers the template version and parameter values, so a
+newer template version can regenerate its draft with the same values. The
+upgrade replaces the draft only: the live version changes when the flow is
+published again.
+"""
+
+import logging
+from typing import Any, Dict, Optional
+from uuid import UUID
+
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, ConflictException, NotFoundException
+from app.models.chatbot import Flow
+from app.repositories.flow_template_repository import FlowTemplateRepository
+from app.schemas.ai_assistant import FlowTemplateDetail, FlowTemplateUpgradeStatus
+from app.schemas.chatbot import FlowUpdate
+from app.services.chatbot_service import ChatbotService
+from app.utils.flow_template_params import (
+    canvas_fingerprint,
+    instantiate,
+    is_newer_version,
+    parse_version,
+    resolve_parameters,
+    validate_template,
+)
+
+logger = logging.getLogger(__name__)
+
+
+class FlowTemplateService:
+    """Service for template instantiation and upgrades"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+        self.chatbot_service = ChatbotService(db)
+
+    async def instantiate(
+        self,
+        template_id: str,
+        chatbot_id: UUID,
+        organization_id: UUID,
+        flow_name: Optional[str] = None,
+        parameters: Optional[Dict[str, Any]] = None,
+    ) -> Flow:
+        """
+        Create a flow from a template with the given parameter values
+
+        Raises:
+            NotFoundException: If template or chatbot not found
+            BadRequestException: If the parameter values are invalid
+        """
+        template = await self._get_template(template_id)
+        chatbot = await self.chatbot_service.get_chatbot(chatbot_id, organization_id)
+        if not chatbot:
+            raise NotFoundException("Chatbot not found")
+
+        values, flow_data = self._render(template, parameters)
+        canvas_data = flow_data.get("canvas_data", {})
+        flow = await self.chatbot_service.flow_repo.create({
+            "organization_id": organization_id,
+            "chatbot_id": chatbot_id,
+            "name": flow_name or template.name,
+            "description": template.description,
+            "canvas_data": canvas_data,
+            "variables": flow_data.get("variables", {}),
+            "is_main": False,
+            "is_active": True,
+            "template_origin": self._origin(template, values, canvas_data),
+        })
+        await self.chatbot_service._sync_nodes_from_canvas(
+            flow_id=flow.id, organization_id=organization_id, canvas_data=canvas_data
+        )
+        await FlowTemplateRepository.increment_use_count(template_id)
+
+        logger.info(f"🧩 Template {template_id} v{template.version} instantiated as flow {flow.id}")
+        return flow
+
+    async def upgrade_status(self, flow_id: UUID, organization_id: UUID) -> FlowTemplateUpgradeStatus:
+        """
+        Whether the flow's template has a newer version
+
+        Raises:
+            NotFoundException: If flow or template not found
+            BadRequestException: If the flow was not created from a template
+        """
+        flow, origin = await self._get_origin(flow_id, organization_id)
+        template = await self._get_template(origin["template_id"])
+
+        current = str(origin.get("version", "1.0"))
+        known = set((origin.get("parameters") or {}).keys())
+        return FlowTemplateUpgradeStatus(
+            flow_id=flow.id,
+            template_id=template.id,
+            current_version=current,
+            latest_version=template.version,
+            upgrade_available=is_newer_version(template.version, current),
+            draft_modified=canvas_fingerprint(flow.canvas_data or {}) != origin.get("fingerprint"),
+            new_parameters=[parameter for parameter in template.parameters if parameter.name not in known],
+            changelog=[
+                entry for entry in template.changelog
+                if parse_version(entry.get("version")) > parse_version(current)
+            ],
+        )
+
+    async def upgrade(
+        self,
+        flow_id: UUID,
+        organization_id: UUID,
+        parameters: Optional[Dict[str, Any]] = None,
+        force: bool = False,
+    ) -> Flow:
+        """
+        Regenerate the flow's draft from the template's latest version
+
+        The flow's parameter values are kept; parameters overrides them and
+        fills the ones the new version added.
+
+        Raises:
+            NotFoundException: If flow or template not found
+            BadRequestException: If there is no newer version or the parameters are invalid
+            ConflictException: If the draft was edited and force is not set
+        """
+        status = await self.upgrade_status(flow_id, organization_id)
+        if not status.upgrade_available and not parameters:
+            raise BadRequestException(f"Flow is already on the latest template version ({status.latest_version})")
+        if status.draft_modified and not force:
+            raise ConflictException(
+                "The flow was edited after it was generated from the template; upgrade with force to discard the edits"
+            )
+
+        flow, origin = await self._get_origin(flow_id, organization_id)
+        template = await self._get_template(status.template_id)
+        known = {parameter.name for parameter in template.parameters}
+        stored = {name: value for name, value in (origin.get("parameters") or {}).items() if name in known}
+        values, flow_data = self._render(template, {**stored, **(parameters or {})})
+
+        canvas_data = flow_data.get("canvas_data", {})
+        await self.chatbot_service.update_flow(flow_id, organization_id, FlowUpdate(
+            canvas_data=canvas_data,
+            variables={**(flow.variables or {}), **flow_data.get("variables", {})},
+        ))
+        flow = await self.chatbot_service.flow_repo.update(
+            flow_id, {"template_origin": self._origin(template, values, canvas_data)}
+        )
+
+        logger.info(f"🧩 Flow {flow_id} upgraded from template {template.id} v{status.current_version} to v{template.version}")
+        return flow
+
+    async def _get_template(self, template_id: str) -> FlowTemplateDetail:
+        template = await FlowTemplateRepository.get_template(template_id)
+        if not template:
+            raise NotFoundException("Template not found")
+        return template
+
+    async def _get_origin(self, flow_id: UUID, organization_id: UUID):
+        flow = await self.chatbot_service.get_flow(flow_id, organization_id)
+        if not flow:
+            raise NotFoundException("Flow not found")
+        if not flow.template_origin:
+            raise BadRequestException("Flow was not created from a template")
+        return flow, flow.template_origin
+
+    @staticmethod
+    def _render(template: FlowTemplateDetail, parameters: Optional[Dict[str, Any]]):
+        """Parameter values and flow data of an instantiation"""
+        errors = validate_template({
+            "parameters": [parameter.model_dump() for parameter in template.parameters],
+            "flow_data": template.flow_data,
+        })
+        if errors:
+            logger.error(f"❌ Template {template.id} is invalid: {'; '.join(errors)}")
+            raise BadRequestException(f"Template {template.id} is invalid: {'; '.join(errors)}")
+
+        values, errors = resolve_parameters([parameter.model_dump() for parameter in template.parameters], parameters)
+        if errors:
+            raise BadRequestException("; ".join(errors))
+        return values, instantiate(template.flow_data, values)
+
+    @staticmethod
+    def _origin(template: FlowTemplateDetail, values: Dict[str, Any], canvas_data: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            "template_id": template.id,
+            "version": template.version,
+            "parameters": values,
+            "fingerprint": canvas_fingerprint(canvas_data),
+        }
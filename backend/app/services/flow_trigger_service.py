@@ -0,0 +1,176 @@
+"""
+Flow Triggers - keywords, exact phrases and regexes that start flows
+
+Triggers belong to the organization and point at any of its flows. The
+WhatsApp engine checks them on every inbound text before falling back to
+the chatbot's main flow (see app.utils.flow_triggers for the matching
+rules and ordering).
+
+Saving a trigger that duplicates another one of a different flow (same
+match type and pattern) is rejected; overlaps, where a phrase fires both
+triggers, are allowed and returned as conflicts so the builder can show
+which one wins.
+"""
+
+import logging
+from typing import List, Optional, Tuple
+from uuid import UUID
+
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, ConflictException, NotFoundException
+from app.models.chatbot import FlowTrigger
+from app.repositories.chatbot import FlowRepository, FlowTriggerRepository
+from app.schemas.flow_trigger import FlowTriggerCreate, FlowTriggerUpdate
+from app.utils.flow_triggers import TriggerConflict, find_conflicts, pick_trigger, validate_pattern
+
+logger = logging.getLogger(__name__)
+
+
+class FlowTriggerService:
+    """Service for flow trigger operations"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+        self.repo = FlowTriggerRepository(db)
+        self.flow_repo = FlowRepository(db)
+
+    async def list_triggers(self, organization_id: UUID, flow_id: Optional[UUID] = None) -> List[FlowTrigger]:
+        """Triggers of the organization, highest priority first"""
+        return await self.repo.list_by_organization(organization_id, flow_id)
+
+    async def get_trigger(self, trigger_id: UUID, organization_id: UUID) -> FlowTrigger:
+        """
+        Get trigger by ID
+
+        Raises:
+            NotFoundException: If trigger not found
+        """
+        trigger = await self.repo.get(trigger_id)
+        if not trigger or trigger.organization_id != organization_id:
+            raise NotFoundException("Trigger not found")
+        return trigger
+
+    async def create_trigger(
+        self, data: FlowTriggerCreate, organization_id: UUID
+    ) -> Tuple[FlowTrigger, List[TriggerConflict]]:
+        """
+        Create a trigger
+
+        Returns:
+            Created trigger and its overlaps with other triggers
+
+        Raises:
+            NotFoundException: If flow not found
+            ConflictException: If another flow has the same trigger
+        """
+        await self._ensure_flow(data.flow_id, organization_id)
+        candidate = FlowTrigger(**data.model_dump(), organization_id=organization_id)
+        conflicts = await self._conflicts_of(candidate, organization_id)
+
+        trigger = await self.repo.create({**data.model_dump(), "organization_id": organization_id})
+        logger.info(f"🎯 Trigger {trigger.match_type} '{trigger.pattern}' created for flow {trigger.flow_id}")
+        # The candidate had no ID yet
+        for conflict in conflicts:
+            conflict.trigger_id = conflict.trigger_id or trigger.id
+            conflict.other_trigger_id = conflict.other_trigger_id or trigger.id
+        return trigger, conflicts
+
+    async def update_trigger(
+        self, trigger_id: UUID, data: FlowTriggerUpdate, organization_id: UUID
+    ) -> Tuple[FlowTrigger, List[TriggerConflict]]:
+        """
+        Update a trigger
+
+        Raises:
+            NotFoundException: If trigger or flow not found
+            BadRequestException: If the resulting pattern is invalid
+            ConflictException: If another flow has the same trigger
+        """
+        trigger = await self.get_trigger(trigger_id, organization_id)
+        update_data = data.model_dump(exclude_unset=True, exclude_none=True)
+
+        match_type = update_data.get("match_type", trigger.match_type)
+        pattern = update_data.get("pattern", trigger.pattern)
+        try:
+            validate_pattern(match_type, pattern)
+        except ValueError as e:
+            raise BadRequestException(str(e))
+
+        if "flow_id" in update_data:
+            await self._ensure_flow(update_data["flow_id"], organization_id)
+
+        candidate = FlowTrigger(
+            id=trigger.id,
+            organization_id=organization_id,
+            flow_id=update_data.get("flow_id", trigger.flow_id),
+            match_type=match_type,
+            pattern=pattern,
+            priority=update_data.get("priority", trigger.priority),
+            interrupts_flow=update_data.get("interrupts_flow", trigger.interrupts_flow),
+            is_active=update_data.get("is_active", trigger.is_active),
+            created_at=trigger.created_at,
+        )
+        conflicts = await self._conflicts_of(candidate, organization_id)
+
+        trigger = await self.repo.update(trigger_id, update_data)
+        return trigger, conflicts
+
+    async def delete_trigger(self, trigger_id: UUID, organization_id: UUID) -> None:
+        """
+        Delete a trigger
+
+        Raises:
+            NotFoundException: If trigger not found
+        """
+        await self.get_trigger(trigger_id, organization_id)
+        await self.repo.delete(trigger_id)
+
+    async def list_conflicts(self, organization_id: UUID) -> List[TriggerConflict]:
+        """Duplicates and overlaps between the organization's active triggers"""
+        return find_conflicts(await self.repo.list_by_organization(organization_id))
+
+    async def test_message(self, organization_id: UUID, text: str, in_flow: bool = False) -> Optional[FlowTrigger]:
+        """Trigger a message would fire, considering every active trigger of the organization"""
+        return pick_trigger(await self.repo.list_by_organization(organization_id), text, in_flow)
+
+    async def match(self, conversation, text: str) -> Optional[FlowTrigger]:
+        """
+        Trigger fired by an inbound message of a conversation
+
+        Only triggers whose flow can run on the conversation's number are
+        considered; while a flow is running, only those with interrupts_flow.
+        """
+        triggers = await self.repo.list_runnable(
+            conversation.organization_id,
+            conversation.whatsapp_number_id,
+            conversation.active_chatbot_id,
+        )
+        return pick_trigger(triggers, text, in_flow=bool(conversation.active_flow_id))
+
+    async def _ensure_flow(self, flow_id: UUID, organization_id: UUID) -> None:
+        flow = await self.flow_repo.get(flow_id)
+        if not flow or flow.organization_id != organization_id or flow.deleted_at:
+            raise NotFoundException("Flow not found")
+
+    async def _conflicts_of(self, candidate: FlowTrigger, organization_id: UUID) -> List[TriggerConflict]:
+        """
+        Conflicts the candidate would have with the other triggers
+
+        Raises:
+            ConflictException: If the candidate duplicates another flow's trigger
+        """
+        others = [
+            trigger
+            for trigger in await self.repo.list_by_organization(organization_id)
+            if trigger.id != candidate.id
+        ]
+        conflicts = [
+            conflict
+            for conflict in find_conflicts([*others, candidate])
+            if candidate.id in (conflict.trigger_id, conflict.other_trigger_id)
+        ]
+        for conflict in conflicts:
+            if conflict.kind == "duplicate":
+                raise ConflictException(conflict.message)
+        return conflicts
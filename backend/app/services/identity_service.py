@@ -0,0 +1,150 @@
+"""
+Identity Verification Service - security notifications about contacts
+
+WhatsApp reports when a contact changes phone number (user_changed_number)
+or identity key (customer_identity_changed, i.e. WhatsApp reinstalled or
+moved to another device). Following WhatsApp's security guidance, the open
+conversation is flagged so agents confirm who they are talking to before
+sharing sensitive data:
+- Conversation.extra_data["identity_verification"] keeps the change and,
+  once cleared, who verified the contact
+- conversation:identity_changed / conversation:identity_verified are
+  emitted to the conversation room
+- Hooks registered with register_identity_change_hook run on every change
+  (pause the bot, open a ticket, notify a supervisor...)
+"""
+
+import logging
+from datetime import datetime, timezone
+from typing import Any, Awaitable, Callable, Dict, List, Optional
+from uuid import UUID
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.models.conversation import Conversation
+from app.repositories.conversation import ConversationRepository
+from app.schemas.webhook import IdentityChange
+
+logger = logging.getLogger(__name__)
+
+VERIFICATION_KEY = "identity_verification"
+
+IdentityChangeHook = Callable[[AsyncSession, Conversation, IdentityChange], Awaitable[None]]
+
+_identity_change_hooks: List[IdentityChangeHook] = []
+
+
+def register_identity_change_hook(hook: IdentityChangeHook) -> IdentityChangeHook:
+    """
+    Run ``hook(db, conversation, change)`` whenever a conversation is flagged
+
+    Usable as a decorator. Hook failures are logged and never block the
+    webhook.
+    """
+    if hook not in _identity_change_hooks:
+        _identity_change_hooks.append(hook)
+    return hook
+
+
+def unregister_identity_change_hook(hook: IdentityChangeHook) -> None:
+    if hook in _identity_change_hooks:
+        _identity_change_hooks.remove(hook)
+
+
+def verification_state(conversation: Conversation) -> Optional[Dict[str, Any]]:
+    """Last identity change recorded on the conversation (None if never flagged)"""
+    return (conversation.extra_data or {}).get(VERIFICATION_KEY)
+
+
+def requires_reverification(conversation: Conversation) -> bool:
+    state = verification_state(conversation)
+    return bool(state and state.get("required"))
+
+
+class IdentityVerificationService:
+    """Service for flagging and clearing identity re-verification"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def flag(self, conversation: Conversation, change: IdentityChange) -> Dict[str, Any]:
+        """Mark the conversation for re-verification and run the registered hooks"""
+        state = {
+            "required": True,
+            "kind": change.kind.value,
+            "wa_id": change.wa_id,
+            "new_wa_id": change.new_wa_id,
+            "identity_hash": change.identity_hash,
+            "message_id": change.message_id,
+            "changed_at": datetime.now(timezone.utc).isoformat(),
+            "verified_at": None,
+            "verified_by": None,
+        }
+        extra_data = {**(conversation.extra_data or {}), VERIFICATION_KEY: state}
+        await ConversationRepository(self.db).update(conversation.id, {"extra_data": extra_data})
+        conversation.extra_data = extra_data
+
+        logger.warning(
+            f"🔐 Conversation {conversation.id} flagged for re-verification ({change.kind.value})"
+        )
+
+        for hook in list(_identity_change_hooks):
+            try:
+                await hook(self.db, conversation, change)
+            except Exception as e:
+                logger.error(f"❌ Identity change hook {getattr(hook, '__name__', hook)} failed: {e}")
+
+        await self._emit(conversation, "conversation:identity_changed", state)
+        return state
+
+    async def mark_verified(
+        self, conversation_id: UUID, organization_id: UUID, user_id: UUID
+    ) -> Conversation:
+        """An agent confirmed the contact's identity: clear the flag"""
+        conversation = await self._get_conversation(conversation_id, organization_id)
+        state = verification_state(conversation)
+        if not state or not state.get("required"):
+            raise BadRequestException("Conversation does not require identity verification")
+
+        state = {
+            **state,
+            "required": False,
+            "verified_at": datetime.now(timezone.utc).isoformat(),
+            "verified_by": str(user_id),
+        }
+        extra_data = {**(conversation.extra_data or {}), VERIFICATION_KEY: state}
+        conversation = await ConversationRepository(self.db).update(
+            conversation.id, {"extra_data": extra_data}
+        )
+
+        logger.info(f"✅ Identity verified on conversation {conversation_id} by {user_id}")
+
+        await self._emit(conversation, "conversation:identity_verified", state)
+        return conversation
+
+    async def _emit(self, conversation: Conversation, event: str, state: Dict[str, Any]) -> None:
+        from app.websocket.manager import emit_to_conversation
+
+        try:
+            await emit_to_conversation(
+                conversation_id=str(conversation.id),
+                event=event,
+                data={"conversation_id": str(conversation.id), **state},
+            )
+        except Exception as e:
+            logger.warning(f"⚠️ Could not emit {event} for {conversation.id}: {e}")
+
+    async def _get_conversation(self, conversation_id: UUID, organization_id: UUID) -> Conversation:
+        result = await self.db.execute(
+            select(Conversation).where(
+                Conversation.id == conversation_id,
+                Conversation.organization_id == organization_id,
+                Conversation.deleted_at.is_(None),
+            )
+        )
+        conversation = result.scalar_one_or_none()
+        if not conversation:
+            raise NotFoundException("Conversation not found")
+        return conversation
@@ -0,0 +1,301 @@
+"""
+Message Status Tracker - delivery receipts of outbound messages
+
+Consumes Meta status webhooks (sent, delivered, read, failed) and:
+- Moves Message.status forward only (receipts may arrive out of order or
+  twice: a late "delivered" never downgrades a "read" message)
+- Stamps sent_at/delivered_at/read_at/failed_at with the receipt time and
+  keeps the error code, detail and retry guidance (DeliveryError) of failed messages
+- Updates the counters and rates of the campaign that sent the message, and
+  its campaign_messages row for the contact
+- Emits message:status, and message:failed with the error detail, over WebSocket
+
+Per-conversation and per-campaign delivery stats are aggregated from the
+messages themselves.
+"""
+
+import logging
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from typing import Any, Dict, Optional
+from uuid import UUID
+
+from sqlalchemy import func, select, update
+from sqlalchemy.ext.asyncio import AsyncSession
+from sqlalchemy.orm.attributes import flag_modified
+
+from app.models.campaign import Campaign, CampaignMessage
+from app.models.conversation import Conversation, Message
+from app.schemas.message import DeliveryStats
+from app.schemas.webhook import DeliveryError
+
+logger = logging.getLogger(__name__)
+
+# Forward-only progression; failed is terminal
+STATUS_RANK = {"pending": 0, "sent": 1, "delivered": 2, "read": 3}
+RECEIPT_STATUSES = ("sent", "delivered", "read", "failed")
+
+
+def next_status(current: Optional[str], receipt: str) -> Optional[str]:
+    """
+    Status of a message after a receipt
+
+    Returns:
+        The new status, or None when the receipt is stale or unknown
+    """
+    if receipt not in RECEIPT_STATUSES or current in ("failed", receipt):
+        return None
+    if receipt == "failed":
+        # Delivered/read messages reached the customer, a late failure is noise
+        return None if current in ("delivered", "read") else "failed"
+    if STATUS_RANK[receipt] > STATUS_RANK.get(current or "pending", 0):
+        return receipt
+    return None
+
+
+def receipt_error(status: Dict[str, Any]) -> Dict[str, Any]:
+    """Error code, title, detail and retry guidance of a failed receipt"""
+    error = DeliveryError.from_status(status)
+    return {
+        "code": error.raw_code,
+        "title": error.title,
+        "detail": error.detail,
+        "kind": error.code.name.lower(),
+        "needs_template": error.needs_template,
+        "permanent_failure": error.permanent_failure,
+        "guidance": error.guidance,
+    }
+
+
+@dataclass
+class StatusChange:
+    """Transition applied to a message"""
+
+    message: Message
+    previous: Optional[str]
+    status: str
+    error: Optional[Dict[str, Any]] = None
+
+
+class MessageStatusTracker:
+    """Applies delivery receipts and aggregates delivery stats"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def handle(self, status: Dict[str, Any], organization_id: Optional[UUID] = None) -> Optional[StatusChange]:
+        """
+        Apply one receipt from a status webhook
+
+        Args:
+            status: Status object from the webhook ({"id", "status", "timestamp", "errors"})
+            organization_id: Restrict the lookup to the organization of the receiving number
+
+        Returns:
+            The applied change, or None (unknown message, stale or duplicate receipt)
+        """
+        whatsapp_message_id = status.get("id")
+        receipt = status.get("status")
+        if not whatsapp_message_id or not receipt:
+            logger.warning("Missing required fields in status update")
+            return None
+
+        stmt = select(Message).where(Message.whatsapp_message_id == whatsapp_message_id)
+        if organization_id:
+            stmt = stmt.where(Message.organization_id == organization_id)
+        message = (await self.db.execute(stmt)).scalar_one_or_none()
+        if not message:
+            logger.warning(f"⚠️ Message not found for WhatsApp ID: {whatsapp_message_id}")
+            return None
+
+        previous = message.status
+        new_status = next_status(previous, receipt)
+        if not new_status:
+            logger.info(f"⏭️ Stale receipt for message {message.id}: {previous} <- {receipt}")
+            return None
+
+        at = self._receipt_time(status.get("timestamp"))
+        change = StatusChange(message=message, previous=previous, status=new_status)
+        message.status = new_status
+
+        if new_status == "failed":
+            change.error = receipt_error(status)
+            message.failed_at = at
+            message.error_code = change.error["code"] or "unknown"
+            message.error_message = change.error["detail"] or "Unknown error"
+        else:
+            # A "read" arriving first implies the earlier steps
+            for step, column in (("sent", "sent_at"), ("delivered", "delivered_at"), ("read", "read_at")):
+                if STATUS_RANK[step] <= STATUS_RANK[new_status] and getattr(message, column) is None:
+                    setattr(message, column, at)
+
+        history = list((message.extra_data or {}).get("status_history", []))
+        history.append({"status": new_status, "at": at.isoformat()})
+        message.extra_data = {**(message.extra_data or {}), "status_history": history}
+        if change.error:
+            message.extra_data["delivery_error"] = change.error
+        flag_modified(message, "extra_data")
+
+        campaign = await self._update_campaign(change)
+        await self.db.commit()
+
+        if new_status == "failed":
+            logger.error(
+                f"❌ Message {message.id} failed: {message.error_code} - {message.error_message}"
+            )
+        else:
+            logger.info(f"✅ Message {message.id} marked as {new_status}")
+
+        await self._emit(change, at)
+        if campaign:
+            await self._broadcast_campaign_progress(campaign)
+        return change
+
+    # ============================================
+    # STATS
+    # ============================================
+
+    async def conversation_stats(self, conversation_id: UUID) -> DeliveryStats:
+        """Delivery stats of the outbound messages of a conversation"""
+        return await self._stats(Message.conversation_id == conversation_id)
+
+    async def campaign_stats(self, campaign_id: UUID) -> DeliveryStats:
+        """Delivery stats of the messages sent by a campaign"""
+        return await self._stats(Message.extra_data["campaign_id"].astext == str(campaign_id))
+
+    async def _stats(self, condition) -> DeliveryStats:
+        result = await self.db.execute(
+            select(Message.status, func.count(Message.id))
+            .where(condition, Message.direction == "outbound", Message.is_internal_note.is_(False))
+            .group_by(Message.status)
+        )
+        return DeliveryStats.from_counts(dict(result.all()))
+
+    # ============================================
+    # HELPERS
+    # ============================================
+
+    @staticmethod
+    def _receipt_time(timestamp: Optional[str]) -> datetime:
+        if timestamp:
+            try:
+                return datetime.fromtimestamp(int(timestamp), tz=timezone.utc)
+            except (TypeError, ValueError):
+                pass
+        return datetime.now(timezone.utc)
+
+    async def _update_campaign(self, change: StatusChange) -> Optional[Campaign]:
+        """Campaign counters: each message counts once per step it reaches"""
+        campaign_id = (change.message.extra_data or {}).get("campaign_id")
+        if not campaign_id:
+            return None
+
+        campaign = await self.db.get(Campaign, UUID(str(campaign_id)))
+        if not campaign:
+            logger.warning(f"⚠️ Campaign not found: {campaign_id}")
+            return None
+
+        if change.status == "failed":
+            campaign.messages_failed += 1
+            campaign.error_count += 1
+            campaign.last_error_message = f"Code: {change.error['code']}, Title: {change.error['title']}"
+        else:
+            previous_rank = STATUS_RANK.get(change.previous or "pending", 0)
+            if STATUS_RANK[change.status] >= STATUS_RANK["delivered"] > previous_rank:
+                campaign.messages_delivered += 1
+            if change.status == "read":
+                campaign.messages_read += 1
+
+        if campaign.messages_sent > 0:
+            campaign.delivery_rate = campaign.messages_delivered / campaign.messages_sent * 100
+            campaign.read_rate = campaign.messages_read / campaign.messages_sent * 100
+
+        # Per-contact status kept by the campaign dispatcher
+        contact_id = await self.db.scalar(
+            select(Conversation.contact_id).where(Conversation.id == change.message.conversation_id)
+        )
+        entry = (campaign.message_statuses or {}).get(str(contact_id))
+        if entry is not None:
+            entry["status"] = change.status
+            entry["last_update"] = datetime.utcnow().isoformat()
+            if change.error:
+                # Guidance for the next send to this contact (template fallback or drop)
+                entry["delivery_error"] = change.error
+                entry["dropped"] = change.error["permanent_failure"]
+            flag_modified(campaign, "message_statuses")
+
+        # Delivery row of the contact (timestamps were stamped on the message above)
+        message = change.message
+        values = {"status": change.status}
+        if change.status == "failed":
+            values.update(failed_at=message.failed_at, error_code=message.error_code, error_message=message.error_message)
+        else:
+            values.update(delivered_at=message.delivered_at, read_at=message.read_at)
+        await self.db.execute(
+            update(CampaignMessage)
+            .where(CampaignMessage.campaign_id == campaign.id, CampaignMessage.message_id == message.id)
+            .values(**values)
+        )
+        return campaign
+
+    async def _emit(self, change: StatusChange, at: datetime) -> None:
+        from app.websocket.manager import emit_to_conversation, emit_to_organization
+
+        message = change.message
+        await emit_to_conversation(
+            conversation_id=str(message.conversation_id),
+            event="message:status",
+            data={
+                "message_id": str(message.id),
+                "status": change.status,
+                "timestamp": at.isoformat(),
+            },
+        )
+
+        if change.status == "failed":
+            failure = {
+                "message_id": str(message.id),
+                "conversation_id": str(message.conversation_id),
+                "whatsapp_message_id": message.whatsapp_message_id,
+                "previous_status": change.previous,
+                "error_code": message.error_code,
+                "error_title": change.error.get("title"),
+                "error_message": message.error_message,
+                "error_kind": change.error.get("kind"),
+                "needs_template": change.error.get("needs_template"),
+                "permanent_failure": change.error.get("permanent_failure"),
+                "guidance": change.error.get("guidance"),
+                "campaign_id": (message.extra_data or {}).get("campaign_id"),
+                "failed_at": at.isoformat(),
+            }
+            await emit_to_conversation(str(message.conversation_id), "message:failed", failure)
+            await emit_to_organization(str(message.organization_id), "message:failed", failure)
+
+    async def _broadcast_campaign_progress(self, campaign: Campaign) -> None:
+        """Broadcast campaign progress to the campaign room"""
+        try:
+            from app.core.websocket_manager import websocket_manager
+
+            await websocket_manager.broadcast_to_room(
+                room=f"campaign:{campaign.id}",
+                message={
+                    "campaign_id": str(campaign.id),
+                    "campaign_name": campaign.name,
+                    "status": campaign.status,
+                    "progress": campaign.progress_percentage,
+                    "stats": {
+                        "total_recipients": campaign.total_recipients,
+                        "messages_sent": campaign.messages_sent,
+                        "messages_delivered": campaign.messages_delivered,
+                        "messages_read": campaign.messages_read,
+                        "messages_failed": campaign.messages_failed,
+                        "messages_pending": campaign.messages_pending,
+                        "delivery_rate": campaign.delivery_rate,
+                        "read_rate": campaign.read_rate,
+                    },
+                    "timestamp": datetime.utcnow().isoformat(),
+                },
+                event="campaign:progress",
+            )
+        except Exception as e:
+            logger.error(f"❌ Error broadcasting progress: {e}")
@@ -62,6 +62,16 @@ class OrganizationService:
         org = await self.get_by_id(org_id)
 
         update_data = data.model_dump(exclude_unset=True)
+
+        home_region = update_data.get("home_region")
+        if home_region:
+            from app.core.config import settings
+
+            if home_region not in settings.REGIONS:
+                raise BadRequestException(
+                    f"Unknown region '{home_region}'. Available: {', '.join(settings.REGIONS)}"
+                )
+
         updated_org = await self.repo.update(org_id, update_data)
 
         return updated_org
@@ -103,6 +113,27 @@ class OrganizationService:
         updated_org = await self.repo.update(org_id, update_data)
         return updated_org
 
+    async def set_message_encryption(self, org_id: UUID, enabled: bool) -> Organization:
+        """
+        Enable or disable message encryption at rest
+
+        A tenant data key is generated on first enable and kept when disabled,
+        so messages stored while encryption was on remain readable.
+        """
+        org = await self.get_by_id(org_id)
+
+        update_data = {"message_encryption_enabled": enabled}
+        if enabled and not org.message_data_key:
+            from app.core.encryption.base import EncryptionError
+            from app.core.encryption.message_cipher import message_cipher
+
+            try:
+                update_data["message_data_key"] = message_cipher.generate_data_key()
+            except EncryptionError as e:
+                raise BadRequestException(f"Message encryption unavailable: {str(e)}")
+
+        return await self.repo.update(org_id, update_data)
+
     async def deactivate(self, org_id: UUID) -> Organization:
         """Deactivate organization"""
         org = await self.get_by_id(org_id)
@@ -0,0 +1,340 @@
+"""
+Conversation Participant Service - Several agents working on one conversation
+
+The primary participant is the assigned agent (kept in sync with
+Conversation.current_agent_id); collaborators join explicitly or by replying.
+Participant changes are streamed to the conversation room:
+- participant:joined / participant:updated / participant:left
+"""
+
+import logging
+from datetime import datetime, timezone
+from typing import Iterable, List, Optional
+from uuid import UUID
+
+from sqlalchemy import or_, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.models.conversation import Conversation
+from app.models.conversation_participant import ConversationParticipant
+from app.models.user import User, UserWorkspace
+from app.schemas.conversation_participant import (
+    ParticipantAdd,
+    ParticipantResponse,
+    ParticipantUpdate,
+)
+
+logger = logging.getLogger(__name__)
+
+
+class ConversationParticipantService:
+    """Service for conversation participants"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def list_participants(
+        self, conversation_id: UUID, organization_id: UUID, include_left: bool = False
+    ) -> List[ConversationParticipant]:
+        """Participants of a conversation, primary first"""
+        await self._get_conversation(conversation_id, organization_id)
+
+        query = select(ConversationParticipant).where(
+            ConversationParticipant.conversation_id == conversation_id
+        )
+        if not include_left:
+            query = query.where(ConversationParticipant.left_at.is_(None))
+
+        result = await self.db.execute(query.order_by(ConversationParticipant.joined_at))
+        participants = list(result.scalars().all())
+        return sorted(participants, key=lambda p: not p.is_primary)
+
+    async def add_participant(
+        self,
+        conversation_id: UUID,
+        organization_id: UUID,
+        data: ParticipantAdd,
+        added_by_user_id: Optional[UUID] = None,
+    ) -> ConversationParticipant:
+        """
+        Add an agent to a conversation (re-joins if they had left)
+
+        Raises:
+            NotFoundException: If the conversation or agent does not exist
+        """
+        conversation = await self._get_conversation(conversation_id, organization_id)
+        await self._ensure_member(data.user_id, organization_id)
+
+        participant, _ = await self._join(
+            conversation, data.user_id, added_by_user_id, data.notification_level
+        )
+        if data.role == "primary":
+            await self._make_primary(conversation, participant)
+
+        await self.db.commit()
+        await self.broadcast("participant:joined", participant)
+        return participant
+
+    async def update_participant(
+        self,
+        conversation_id: UUID,
+        organization_id: UUID,
+        user_id: UUID,
+        data: ParticipantUpdate,
+    ) -> ConversationParticipant:
+        """Change role (promote to primary) or notification preference"""
+        conversation = await self._get_conversation(conversation_id, organization_id)
+        participant = await self._get_active(conversation_id, user_id)
+
+        if data.notification_level:
+            participant.notification_level = data.notification_level
+        if data.role == "primary":
+            await self._make_primary(conversation, participant)
+        elif data.role == "collaborator" and participant.is_primary:
+            participant.role = "collaborator"
+            conversation.current_agent_id = None
+
+        await self.db.commit()
+        await self.broadcast("participant:updated", participant)
+        return participant
+
+    async def remove_participant(
+        self, conversation_id: UUID, organization_id: UUID, user_id: UUID
+    ) -> None:
+        """
+        Remove an agent from a conversation
+
+        Raises:
+            BadRequestException: For the primary agent (transfer or reassign instead)
+        """
+        await self._get_conversation(conversation_id, organization_id)
+        participant = await self._get_active(conversation_id, user_id)
+        if participant.is_primary:
+            raise BadRequestException(
+                "The primary agent cannot leave; assign or transfer the conversation first"
+            )
+
+        participant.left_at = datetime.now(timezone.utc)
+        await self.db.commit()
+        await self.broadcast("participant:left", participant)
+
+    # ============= Hooks from assignment and messaging =============
+
+    async def set_primary(
+        self, conversation: Conversation, agent_id: Optional[UUID]
+    ) -> Optional[ConversationParticipant]:
+        """
+        Sync the primary participant with an assignment (does not commit)
+
+        With agent_id None (transfer to a queue) the primary leaves the
+        conversation; collaborators stay.
+        """
+        current = await self.get_primary(conversation.id)
+        if current and current.user_id == agent_id:
+            return current
+
+        if current:
+            current.role = "collaborator"
+            if agent_id is None:
+                current.left_at = datetime.now(timezone.utc)
+
+        if agent_id is None:
+            return None
+
+        participant, _ = await self._join(conversation, agent_id)
+        participant.role = "primary"
+        return participant
+
+    async def record_outbound(
+        self, conversation: Conversation, user_id: UUID
+    ) -> Optional[ConversationParticipant]:
+        """
+        Attribute an outbound message to its agent (does not commit)
+
+        Agents replying without being participants join as collaborators.
+
+        Returns:
+            The participant if they just joined, None otherwise
+        """
+        participant, joined = await self._join(conversation, user_id)
+        participant.messages_sent = (participant.messages_sent or 0) + 1
+        participant.last_message_at = datetime.now(timezone.utc)
+        return participant if joined else None
+
+    async def notification_recipients(
+        self,
+        conversation_id: UUID,
+        mentioned_user_ids: Iterable[UUID] = (),
+        exclude_user_id: Optional[UUID] = None,
+    ) -> List[UUID]:
+        """Participants to notify of a conversation event, per their preference"""
+        mentioned = set(mentioned_user_ids)
+        result = await self.db.execute(
+            select(ConversationParticipant).where(
+                ConversationParticipant.conversation_id == conversation_id,
+                ConversationParticipant.left_at.is_(None),
+            )
+        )
+
+        recipients = []
+        for participant in result.scalars().all():
+            if participant.user_id == exclude_user_id:
+                continue
+            if participant.notification_level == "all" or (
+                participant.notification_level == "mentions" and participant.user_id in mentioned
+            ):
+                recipients.append(participant.user_id)
+        return recipients
+
+    async def notify(
+        self,
+        conversation_id: UUID,
+        event: str,
+        data: dict,
+        mentioned_user_ids: Iterable[UUID] = (),
+        exclude_user_id: Optional[UUID] = None,
+    ) -> int:
+        """Emit an event to each participant who wants it; returns how many were notified"""
+        from app.websocket.manager import emit_to_user
+
+        recipients = await self.notification_recipients(
+            conversation_id, mentioned_user_ids, exclude_user_id
+        )
+        for user_id in recipients:
+            try:
+                await emit_to_user(str(user_id), event, data)
+            except Exception as e:
+                logger.warning(f"⚠️ Could not notify participant {user_id}: {e}")
+        return len(recipients)
+
+    async def broadcast(self, event: str, participant: ConversationParticipant) -> None:
+        """Stream a participant change to the conversation room"""
+        from app.websocket.manager import emit_to_conversation
+
+        try:
+            await emit_to_conversation(
+                conversation_id=str(participant.conversation_id),
+                event=event,
+                data=ParticipantResponse.model_validate(participant).model_dump(mode="json"),
+            )
+        except Exception as e:
+            logger.warning(f"⚠️ Could not broadcast {event} for {participant.user_id}: {e}")
+
+    # ============= Helpers =============
+
+    async def _get_conversation(self, conversation_id: UUID, organization_id: UUID) -> Conversation:
+        result = await self.db.execute(
+            select(Conversation).where(
+                Conversation.id == conversation_id,
+                Conversation.organization_id == organization_id,
+                Conversation.deleted_at.is_(None),
+            )
+        )
+        conversation = result.scalar_one_or_none()
+        if not conversation:
+            raise NotFoundException("Conversation not found")
+        return conversation
+
+    async def _ensure_member(self, user_id: UUID, organization_id: UUID) -> None:
+        """Agents of the organization, including members through a workspace"""
+        result = await self.db.execute(
+            select(User.id)
+            .outerjoin(
+                UserWorkspace,
+                (UserWorkspace.user_id == User.id)
+                & (UserWorkspace.organization_id == organization_id)
+                & UserWorkspace.is_active.is_(True),
+            )
+            .where(
+                User.id == user_id,
+                User.is_active.is_(True),
+                or_(User.organization_id == organization_id, UserWorkspace.id.is_not(None)),
+            )
+            .limit(1)
+        )
+        if not result.scalar_one_or_none():
+            raise NotFoundException("Agent not found")
+
+    async def _get_participant(
+        self, conversation_id: UUID, user_id: UUID
+    ) -> Optional[ConversationParticipant]:
+        result = await self.db.execute(
+            select(ConversationParticipant).where(
+                ConversationParticipant.conversation_id == conversation_id,
+                ConversationParticipant.user_id == user_id,
+            )
+        )
+        return result.scalar_one_or_none()
+
+    async def _get_active(self, conversation_id: UUID, user_id: UUID) -> ConversationParticipant:
+        participant = await self._get_participant(conversation_id, user_id)
+        if not participant or not participant.is_active:
+            raise NotFoundException("Participant not found")
+        return participant
+
+    async def get_primary(self, conversation_id: UUID) -> Optional[ConversationParticipant]:
+        """Active primary participant (the assigned agent)"""
+        result = await self.db.execute(
+            select(ConversationParticipant).where(
+                ConversationParticipant.conversation_id == conversation_id,
+                ConversationParticipant.role == "primary",
+                ConversationParticipant.left_at.is_(None),
+            )
+        )
+        return result.scalars().first()
+
+    async def _join(
+        self,
+        conversation: Conversation,
+        user_id: UUID,
+        added_by_user_id: Optional[UUID] = None,
+        notification_level: Optional[str] = None,
+    ) -> "tuple[ConversationParticipant, bool]":
+        """Get or create an active participant; True when they (re)joined now"""
+        participant = await self._get_participant(conversation.id, user_id)
+
+        if participant and participant.is_active:
+            if notification_level:
+                participant.notification_level = notification_level
+            return participant, False
+
+        now = datetime.now(timezone.utc)
+        if participant:
+            participant.left_at = None
+            participant.joined_at = now
+            participant.added_by_user_id = added_by_user_id
+        else:
+            participant = ConversationParticipant(
+                organization_id=conversation.organization_id,
+                conversation_id=conversation.id,
+                user_id=user_id,
+                added_by_user_id=added_by_user_id,
+                role="collaborator",
+                notification_level="all",
+                joined_at=now,
+                messages_sent=0,
+            )
+            self.db.add(participant)
+
+        participant.role = "collaborator"
+        if notification_level:
+            participant.notification_level = notification_level
+        await self.db.flush()
+        return participant, True
+
+    async def _make_primary(
+        self, conversation: Conversation, participant: ConversationParticipant
+    ) -> None:
+        """Promote a participant to primary and assign the conversation to them"""
+        current = await self.get_primary(conversation.id)
+        if current and current.id != participant.id:
+            current.role = "collaborator"
+
+        participant.role = "primary"
+        if conversation.current_agent_id != participant.user_id:
+            conversation.current_agent_id = participant.user_id
+            conversation.assigned_at = datetime.now(timezone.utc)
+        if conversation.status == "queued":
+            conversation.status = "active"
+            conversation.queued_at = None
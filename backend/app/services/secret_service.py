@@ -2,18 +2,27 @@
 Secret Service - Business logic for secrets management
 
 Handles encryption/decryption using the configured provider and
-manages the lifecycle of secrets.
+manages the lifecycle of secrets. OrgSecretResolver interpolates
+{{org.secrets.NAME}} / {{org.vars.NAME}} references in flow nodes.
 """
 
 import logging
-from typing import List, Optional, Dict, Any
+import re
+from typing import List, Optional, Dict, Any, Tuple
 from uuid import UUID
 from datetime import datetime
 
+from sqlalchemy import func, select
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.repositories.secret import SecretRepository
-from app.models.secret import Secret, SecretScope, EncryptionProvider
+from app.models.secret import (
+    Secret,
+    SecretAccessLog,
+    SecretKind,
+    SecretScope,
+    EncryptionProvider,
+)
 from app.core.encryption import get_encryption_provider
 from app.core.encryption.base import EncryptionError, DecryptionError
 
@@ -43,7 +52,11 @@ class SecretService:
         description: Optional[str] = None,
         metadata: Optional[Dict[str, Any]] = None,
         encryption_provider: EncryptionProvider = EncryptionProvider.FERNET,
-        encryption_key_id: Optional[str] = None
+        encryption_key_id: Optional[str] = None,
+        kind: SecretKind = SecretKind.SECRET,
+        allowed_roles: Optional[List[str]] = None,
+        allowed_usages: Optional[List[str]] = None,
+        user_id: Optional[UUID] = None
     ) -> Secret:
         """
         Create a new secret with encrypted value.
@@ -59,6 +72,10 @@ class SecretService:
             metadata: Optional metadata dict
             encryption_provider: Provider to use for encryption
             encryption_key_id: Optional key ID for external providers
+            kind: secret or variable (both encrypted at rest)
+            allowed_roles: Roles that may read the value besides admins
+            allowed_usages: Where the value may be interpolated (None = everywhere)
+            user_id: User creating the secret (audit)
 
         Returns:
             Created secret (without decrypted value)
@@ -104,12 +121,16 @@ class SecretService:
             "encryption_provider": encryption_provider,
             "encryption_key_id": encryption_key_id,
             "scope": scope,
+            "kind": kind,
+            "allowed_roles": allowed_roles,
+            "allowed_usages": allowed_usages,
             "secret_metadata": metadata or {},
             "is_active": True,
             "usage_count": 0
         }
 
         secret = await self.repository.create(secret_data)
+        await self.log_access(secret, "create", user_id=user_id)
         logger.info(f"Created secret: {secret.name} (id={secret.id})")
         return secret
 
@@ -225,7 +246,10 @@ class SecretService:
         description: Optional[str] = None,
         value: Optional[str] = None,
         is_active: Optional[bool] = None,
-        metadata: Optional[Dict[str, Any]] = None
+        metadata: Optional[Dict[str, Any]] = None,
+        allowed_roles: Optional[List[str]] = None,
+        allowed_usages: Optional[List[str]] = None,
+        user_id: Optional[UUID] = None
     ) -> Secret:
         """
         Update secret.
@@ -238,6 +262,9 @@ class SecretService:
             value: New plaintext value (will be re-encrypted)
             is_active: New active status
             metadata: New metadata
+            allowed_roles: New reader roles ([] = admins only)
+            allowed_usages: New allowed usages
+            user_id: User updating the secret (audit)
 
         Returns:
             Updated secret
@@ -270,6 +297,12 @@ class SecretService:
         if metadata is not None:
             update_data["secret_metadata"] = metadata
 
+        if allowed_roles is not None:
+            update_data["allowed_roles"] = allowed_roles
+
+        if allowed_usages is not None:
+            update_data["allowed_usages"] = allowed_usages
+
         # If new value provided, re-encrypt
         if value is not None:
             provider = get_encryption_provider(secret.encryption_provider)
@@ -285,6 +318,9 @@ class SecretService:
 
         # Update
         updated_secret = await self.repository.update(secret_id, update_data)
+        await self.log_access(
+            secret, "rotate" if value is not None else "update", user_id=user_id
+        )
         logger.info(f"Updated secret: {secret.name} (id={secret_id})")
         return updated_secret
 
@@ -292,7 +328,8 @@ class SecretService:
         self,
         secret_id: UUID,
         organization_id: UUID,
-        soft_delete: bool = True
+        soft_delete: bool = True,
+        user_id: Optional[UUID] = None
     ) -> bool:
         """
         Delete secret.
@@ -301,6 +338,7 @@ class SecretService:
             secret_id: Secret UUID
             organization_id: Organization UUID (for authorization)
             soft_delete: If True, deactivate. If False, hard delete.
+            user_id: User deleting the secret (audit)
 
         Returns:
             True if deleted, False if not found
@@ -317,6 +355,8 @@ class SecretService:
         if secret.organization_id != organization_id:
             raise ValueError("Unauthorized access to secret")
 
+        await self.log_access(secret, "delete", user_id=user_id)
+
         if soft_delete:
             result = await self.repository.deactivate(secret_id)
         else:
@@ -425,7 +465,9 @@ class SecretService:
     async def get_secret_with_value(
         self,
         secret_id: UUID,
-        organization_id: UUID
+        organization_id: UUID,
+        user_id: Optional[UUID] = None,
+        role: str = "org_admin"
     ) -> Optional[Dict[str, Any]]:
         """
         Get secret with decrypted value.
@@ -433,17 +475,27 @@ class SecretService:
         Args:
             secret_id: Secret UUID
             organization_id: Organization UUID (for authorization)
+            user_id: User reading the value (audit)
+            role: Role of that user, checked against the secret's allowed_roles
 
         Returns:
             Dict with secret data and decrypted value
 
         Raises:
+            PermissionError: If the role may not read this secret
             DecryptionError: If decryption fails
         """
         secret = await self.get_secret(secret_id, organization_id)
         
         if not secret:
             return None
+
+        if not secret.can_be_read_by(role):
+            await self.log_access(
+                secret, "reveal", user_id=user_id, allowed=False,
+                reason=f"Role '{role}' may not read this secret"
+            )
+            raise PermissionError("Not allowed to read this secret")
         
         # Decrypt value
         decrypted_value = await self.get_decrypted_value(
@@ -452,6 +504,8 @@ class SecretService:
             track_usage=False
         )
         
+        await self.log_access(secret, "reveal", user_id=user_id)
+
         # Return secret with value
         return {
             "id": secret.id,
@@ -462,6 +516,10 @@ class SecretService:
             "description": secret.description,
             "value": decrypted_value,  # Decrypted value
             "scope": secret.scope,
+            "kind": secret.kind,
+            "allowed_roles": secret.allowed_roles,
+            "allowed_usages": secret.allowed_usages,
+            "reference": secret.reference,
             "is_active": secret.is_active,
             "encryption_provider": secret.encryption_provider,
             "usage_count": secret.usage_count,
@@ -474,7 +532,8 @@ class SecretService:
         self,
         secret_id: UUID,
         organization_id: UUID,
-        new_value: str
+        new_value: str,
+        user_id: Optional[UUID] = None
     ) -> Optional[Secret]:
         """
         Rotate secret with new value (simplified version of rotate_secret_key).
@@ -483,6 +542,7 @@ class SecretService:
             secret_id: Secret UUID
             organization_id: Organization UUID (for authorization)
             new_value: New plaintext value
+            user_id: User rotating the secret (audit)
 
         Returns:
             Updated secret
@@ -494,8 +554,74 @@ class SecretService:
         return await self.update_secret(
             secret_id=secret_id,
             organization_id=organization_id,
-            value=new_value
+            value=new_value,
+            user_id=user_id
+        )
+
+    async def log_access(
+        self,
+        secret: Secret,
+        action: str,
+        usage: str = "api",
+        user_id: Optional[UUID] = None,
+        resource_type: Optional[str] = None,
+        resource_id: Optional[str] = None,
+        allowed: bool = True,
+        reason: Optional[str] = None
+    ) -> None:
+        """
+        Record an access to a secret in the audit trail.
+
+        Args:
+            secret: Secret accessed
+            action: create, update, rotate, delete, reveal or resolve
+            usage: api or the SecretUsage where the value was interpolated
+            user_id: User behind the access (None for flows)
+            resource_type: Kind of resource using the value (e.g. node)
+            resource_id: Id of that resource
+            allowed: False when the access was denied
+            reason: Why it was denied
+        """
+        self.db.add(SecretAccessLog(
+            organization_id=secret.organization_id,
+            secret_id=secret.id,
+            secret_name=secret.name,
+            action=action,
+            usage=usage,
+            user_id=user_id,
+            resource_type=resource_type,
+            resource_id=resource_id,
+            allowed=allowed,
+            reason=reason,
+        ))
+        await self.db.commit()
+
+    async def list_access_logs(
+        self,
+        secret_id: UUID,
+        organization_id: UUID,
+        skip: int = 0,
+        limit: int = 50
+    ) -> Tuple[List[SecretAccessLog], int]:
+        """
+        Audit trail of a secret, newest first.
+
+        Returns:
+            Tuple of (logs, total)
+        """
+        conditions = [
+            SecretAccessLog.secret_id == secret_id,
+            SecretAccessLog.organization_id == organization_id,
+        ]
+        total = await self.db.scalar(select(func.count(SecretAccessLog.id)).where(*conditions))
+        result = await self.db.execute(
+            select(SecretAccessLog)
+            .where(*conditions)
+            .order_by(SecretAccessLog.created_at.desc())
+            .offset(skip)
+            .limit(limit)
         )
+        return list(result.scalars().all()), total or 0
 
     async def get_stats(
         self,
@@ -537,3 +663,132 @@ class SecretService:
             "by_scope": by_scope,
             "by_provider": by_provider,
         }
+
+
+# {{org.secrets.NAME}} or {{org.vars.NAME}}
+ORG_REFERENCE_PATTERN = re.compile(r"\{\{\s*org\.(secrets|vars)\.([A-Za-z0-9_]+)\s*\}\}")
+
+
+class OrgSecretResolver:
+    """
+    Replaces organization secret/variable references in flow node configs.
+
+    Names are case-insensitive ({{org.secrets.API_KEY}} reads the secret
+    "api_key"); a chatbot-scoped entry wins over an organization-wide one
+    with the same name. References that are unknown, inactive, of the other
+    kind or not allowed for the usage are left untouched. Every resolution
+    (or denial) is recorded once per resolver in the access audit.
+
+    Usage:
+        resolver = OrgSecretResolver(db, org_id, chatbot_id=flow.chatbot_id)
+        headers = await resolver.resolve(headers, SecretUsage.FLOW_HTTP, "node", node.node_id)
+    """
+
+    def __init__(
+        self,
+        db: AsyncSession,
+        organization_id: UUID,
+        chatbot_id: Optional[UUID] = None
+    ):
+        self.service = SecretService(db)
+        self.organization_id = organization_id
+        self.chatbot_id = chatbot_id
+        # (namespace, name, usage) -> plaintext or None when not resolvable
+        self._values: Dict[Tuple[str, str, str], Optional[str]] = {}
+
+    @staticmethod
+    def references(value: Any) -> List[Tuple[str, str]]:
+        """(namespace, lowercased name) pairs referenced in a string, dict or list"""
+        if isinstance(value, str):
+            return [(ns, name.lower()) for ns, name in ORG_REFERENCE_PATTERN.findall(value)]
+        if isinstance(value, dict):
+            return [ref for item in value.values() for ref in OrgSecretResolver.references(item)]
+        if isinstance(value, list):
+            return [ref for item in value for ref in OrgSecretResolver.references(item)]
+        return []
+
+    async def resolve(
+        self,
+        value: Any,
+        usage: str,
+        resource_type: Optional[str] = None,
+        resource_id: Optional[str] = None
+    ) -> Any:
+        """
+        Resolve references in a string, or in the string values of a dict/list
+        (recursively). Other values are returned as is.
+        """
+        refs = self.references(value)
+        if not refs:
+            return value
+
+        for namespace, name in refs:
+            await self._load(namespace, name, usage, resource_type, resource_id)
+
+        return self._substitute(value, usage)
+
+    def _substitute(self, value: Any, usage: str) -> Any:
+        if isinstance(value, str):
+            def replace(match: re.Match) -> str:
+                resolved = self._values.get((match.group(1), match.group(2).lower(), usage))
+                return resolved if resolved is not None else match.group(0)
+            return ORG_REFERENCE_PATTERN.sub(replace, value)
+        if isinstance(value, dict):
+            return {key: self._substitute(item, usage) for key, item in value.items()}
+        if isinstance(value, list):
+            return [self._substitute(item, usage) for item in value]
+        return value
+
+    async def _load(
+        self,
+        namespace: str,
+        name: str,
+        usage: str,
+        resource_type: Optional[str],
+        resource_id: Optional[str]
+    ) -> None:
+        key = (namespace, name, usage)
+        if key in self._values:
+            return
+        self._values[key] = None
+
+        secret = await self._find(name)
+        if not secret:
+            logger.warning(f"⚠️ Org reference {{{{org.{namespace}.{name}}}}} not found")
+            return
+
+        expected = SecretKind.VARIABLE if namespace == "vars" else SecretKind.SECRET
+        reason = None
+        if secret.kind != expected:
+            reason = f"'{name}' is a {secret.kind.value}, use {secret.reference}"
+        elif not secret.can_be_used_in(usage):
+            reason = f"'{name}' may not be used in {usage}"
+
+        if reason:
+            logger.warning(f"⚠️ Org reference denied: {reason}")
+            await self.service.log_access(
+                secret, "resolve", usage=usage, resource_type=resource_type,
+                resource_id=resource_id, allowed=False, reason=reason
+            )
+            return
+
+        try:
+            self._values[key] = await self.service.get_decrypted_value(secret.id, self.organization_id)
+        except Exception as e:
+            logger.error(f"❌ Could not resolve org reference '{name}': {e}")
+            return
+
+        await self.service.log_access(
+            secret, "resolve", usage=usage, resource_type=resource_type, resource_id=resource_id
+        )
+
+    async def _find(self, name: str) -> Optional[Secret]:
+        repository = self.service.repository
+        if self.chatbot_id:
+            secret = await repository.get_by_name(self.organization_id, name, self.chatbot_id)
+            if secret:
+                return secret
+        secret = await repository.get_by_name(self.organization_id, name)
+        if secret and secret.scope == SecretScope.ORGANIZATION:
+            return secret
+        return None
@@ -5,6 +5,7 @@ WhatsApp Template Service
 from typing import List, Optional, Dict, Any
 from uuid import UUID
 from datetime import datetime
+import json
 import logging
 import re
 
@@ -13,16 +14,31 @@ from sqlalchemy import select, and_
 
 from app.models.whatsapp_number import WhatsAppTemplate
 from app.schemas.template import (
+    TemplateCatalogResponse,
     TemplateCreateRequest,
     TemplateUpdateRequest,
     TemplateResponse,
     TemplateComponentSchema
 )
+from app.schemas.webhook import TemplateStatusUpdate
 from app.integrations.meta_api import MetaCloudAPI, MetaAPIError
 from app.core.exceptions import NotFoundException, ConflictException
+from app.services.annotation_service import AnnotationService
+from app.utils.language_detection import pick_template_language
+from app.utils.template_carousel import carousel_definition
+from app.utils.template_components import limited_time_offer_definition
 
 logger = logging.getLogger(__name__)
 
+# Approved template catalog cached per number (invalidated on sync and status webhooks)
+CATALOG_CACHE_TTL = 600
+
+# Template status webhook events that map to a different local status
+TEMPLATE_EVENT_STATUS = {
+    "REINSTATED": "APPROVED",
+    "PENDING_DELETION": "DELETED",
+}
+
 
 class TemplateService:
     """Service for managing WhatsApp templates"""
@@ -149,6 +165,7 @@ class TemplateService:
         body_vars = 0
         footer_text = None
         buttons = []
+        limited_time_offer = None
 
         for component in data.components:
             if component.type == "HEADER":
@@ -165,7 +182,10 @@ class TemplateService:
                 footer_text = component.text
 
             elif component.type == "BUTTONS" and component.buttons:
-                buttons = [btn.model_dump() for btn in component.buttons]
+                buttons = [btn.model_dump(exclude_none=True) for btn in component.buttons]
+
+            elif component.type == "LIMITED_TIME_OFFER" and component.limited_time_offer:
+                limited_time_offer = component.limited_time_offer.model_dump()
 
         # Create template in database
         template = WhatsAppTemplate(
@@ -182,6 +202,7 @@ class TemplateService:
             body_variables_count=body_vars,
             footer_text=footer_text,
             buttons=buttons,
+            limited_time_offer=limited_time_offer,
         )
 
         self.db.add(template)
@@ -189,6 +210,7 @@ class TemplateService:
         await self.db.refresh(template)
 
         logger.info(f"Template '{data.name}' created locally with ID {template.id}")
+        await self._annotate(template, "created")
 
         # Submit to Meta if requested
         if submit_to_meta:
@@ -281,6 +303,7 @@ class TemplateService:
         await self.db.commit()
 
         logger.info(f"Template {template_id} deleted locally")
+        await self._annotate(template, "deleted")
         return True
 
     # ============= Meta API Operations =============
@@ -319,6 +342,13 @@ class TemplateService:
                 "text": template.header_text
             })
 
+        # Limited-time offer
+        if template.limited_time_offer:
+            components.append({
+                "type": "LIMITED_TIME_OFFER",
+                "limited_time_offer": template.limited_time_offer
+            })
+
         # Body (required)
         components.append({
             "type": "BODY",
@@ -394,8 +424,8 @@ class TemplateService:
             access_token=access_token
         )
 
-        # Fetch all templates from Meta (all statuses)
-        meta_templates = await meta_api.list_templates(waba_id, status="", limit=1000)
+        # Fetch all templates from Meta (all statuses, every page)
+        meta_templates = await meta_api.list_all_templates(waba_id)
 
         stats = {"created": 0, "updated": 0, "deleted": 0}
 
@@ -423,6 +453,16 @@ class TemplateService:
                     local_template.status = meta_template.get("status")
                     updated = True
 
+                carousel_cards = carousel_definition(meta_template.get("components")) or None
+                if local_template.carousel_cards != carousel_cards:
+                    local_template.carousel_cards = carousel_cards
+                    updated = True
+
+                limited_time_offer = limited_time_offer_definition(meta_template.get("components"))
+                if local_template.limited_time_offer != limited_time_offer:
+                    local_template.limited_time_offer = limited_time_offer
+                    updated = True
+
                 if meta_template.get("status") == "APPROVED" and not local_template.approved_at:
                     local_template.approved_at = datetime.utcnow()
                     updated = True
@@ -446,14 +486,221 @@ class TemplateService:
                 )
                 stats["created"] += 1
 
+        # Templates submitted to Meta that no longer exist there were deleted in Business Manager
+        meta_keys = {f"{t.get('name')}_{t.get('language')}" for t in meta_templates}
+        for key, local_template in local_by_name.items():
+            if key in meta_keys or not local_template.meta_template_id:
+                continue
+            local_template.status = "DELETED"
+            local_template.deleted_at = datetime.utcnow()
+            stats["deleted"] += 1
+
         await self.db.commit()
+        await self.invalidate_catalog(whatsapp_number_id)
 
-        logger.info(f"Sync completed: {stats['created']} created, {stats['updated']} updated")
+        logger.info(
+            f"Sync completed: {stats['created']} created, {stats['updated']} updated, "
+            f"{stats['deleted']} deleted"
+        )
 
         return stats
 
+    # ============= Catalog =============
+
+    @staticmethod
+    def _catalog_cache_key(whatsapp_number_id: UUID, status: str) -> str:
+        return f"templates:catalog:{whatsapp_number_id}:{status}"
+
+    async def get_catalog(
+        self,
+        whatsapp_number_id: UUID,
+        organization_id: UUID,
+        status: str = "APPROVED",
+        skip: int = 0,
+        limit: int = 50,
+    ) -> TemplateCatalogResponse:
+        """
+        Paginated template catalog of a number (approved and enabled by default)
+
+        The full list is cached in Redis and refreshed by sync_from_meta and
+        template status webhooks; the database is used when Redis is down.
+
+        Args:
+            whatsapp_number_id: WhatsApp number ID
+            organization_id: Organization ID
+            status: Template status to list
+            skip: Number of templates to skip
+            limit: Page size
+
+        Returns:
+            Catalog page
+        """
+        from app.core.redis import cache_get, cache_set
+
+        key = self._catalog_cache_key(whatsapp_number_id, status)
+        items = None
+        cached = False
+
+        try:
+            raw = await cache_get(key)
+            if raw:
+                items = json.loads(raw)
+                cached = True
+        except Exception as e:
+            logger.warning(f"⚠️ Template catalog cache unavailable: {e}")
+
+        if items is None:
+            query = select(WhatsAppTemplate).where(
+                and_(
+                    WhatsAppTemplate.whatsapp_number_id == whatsapp_number_id,
+                    WhatsAppTemplate.organization_id == organization_id,
+                    WhatsAppTemplate.status == status,
+                    WhatsAppTemplate.is_enabled.is_(True),
+                    WhatsAppTemplate.deleted_at.is_(None)
+                )
+            ).order_by(WhatsAppTemplate.name, WhatsAppTemplate.language)
+
+            result = await self.db.execute(query)
+            items = [
+                TemplateResponse.model_validate(template).model_dump(mode="json")
+                for template in result.scalars().all()
+            ]
+
+            try:
+                await cache_set(key, json.dumps(items), expire=CATALOG_CACHE_TTL)
+            except Exception as e:
+                logger.warning(f"⚠️ Could not cache template catalog: {e}")
+
+        return TemplateCatalogResponse(
+            templates=items[skip:skip + limit],
+            total=len(items),
+            skip=skip,
+            limit=limit,
+            cached=cached,
+        )
+
+    async def invalidate_catalog(self, whatsapp_number_id: UUID) -> None:
+        """Drop the cached catalog of a number (every status)"""
+        from app.core.redis import cache_invalidate_pattern
+
+        try:
+            await cache_invalidate_pattern(f"templates:catalog:{whatsapp_number_id}:*")
+        except Exception as e:
+            logger.warning(f"⚠️ Could not invalidate template catalog: {e}")
+
+    async def apply_status_update(self, waba_id: Optional[str], update: TemplateStatusUpdate) -> int:
+        """
+        Apply a message_template_status_update webhook to local templates
+
+        Args:
+            waba_id: WhatsApp Business Account ID (webhook entry id)
+            update: Parsed status update
+
+        Returns:
+            Number of local templates updated (one per number sharing the WABA)
+        """
+        from app.models.whatsapp_number import WhatsAppNumber
+
+        query = (
+            select(WhatsAppTemplate)
+            .join(WhatsAppNumber, WhatsAppNumber.id == WhatsAppTemplate.whatsapp_number_id)
+            .where(WhatsAppTemplate.deleted_at.is_(None))
+        )
+        if waba_id:
+            query = query.where(WhatsAppNumber.whatsapp_business_account_id == waba_id)
+
+        if update.template_id:
+            query = query.where(WhatsAppTemplate.meta_template_id == update.template_id)
+        else:
+            query = query.where(
+                and_(
+                    WhatsAppTemplate.name == update.name,
+                    WhatsAppTemplate.language == update.language
+                )
+            )
+
+        templates = list((await self.db.execute(query)).scalars().all())
+        if not templates:
+            logger.warning(
+                f"Template status update for unknown template {update.name} ({update.template_id})"
+            )
+            return 0
+
+        status = TEMPLATE_EVENT_STATUS.get(update.event, update.event)
+        now = datetime.utcnow()
+
+        for template in templates:
+            template.status = status
+            template.updated_at = now
+
+            if status == "APPROVED":
+                template.approved_at = template.approved_at or now
+                template.rejected_reason = None
+            elif status == "REJECTED":
+                template.rejected_at = now
+                template.rejected_reason = update.rejected_reason or "Unknown"
+            elif status == "DELETED":
+                template.deleted_at = now
+
+        await self.db.commit()
+
+        for number_id in {template.whatsapp_number_id for template in templates}:
+            await self.invalidate_catalog(number_id)
+
+        for template in templates:
+            await self._annotate(template, status.lower(), update.rejected_reason)
+
+        logger.info(f"📋 Template '{update.name}' ({update.language}) -> {status}")
+        return len(templates)
+
     # ============= Helper Methods =============
 
+    async def _annotate(
+        self, template: WhatsAppTemplate, action: str, reason: Optional[str] = None
+    ) -> None:
+        """Dashboard annotation for a template change (created, approved, deleted...)"""
+        await AnnotationService(self.db).record(
+            "template_change",
+            f"Template {template.name} ({template.language}) {action}",
+            organization_id=template.organization_id,
+            description=reason,
+            resource_type="whatsapp_template",
+            resource_id=template.id,
+            extra_data={"action": action, "whatsapp_number_id": str(template.whatsapp_number_id)},
+        )
+
+    async def resolve_language_variant(
+        self,
+        name: str,
+        language: Optional[str],
+        whatsapp_number_id: UUID,
+        organization_id: UUID,
+        contact_language: Optional[str],
+    ) -> Optional[str]:
+        """
+        Language variant of a template to send to a contact
+
+        Picks the approved variant in the contact's language (e.g. "es_MX" for a
+        Spanish-speaking contact), keeping the configured language when the
+        contact's language is unknown or has no approved variant.
+        """
+        if not contact_language:
+            return language
+
+        result = await self.db.execute(
+            select(WhatsAppTemplate.language).where(
+                and_(
+                    WhatsAppTemplate.name == name,
+                    WhatsAppTemplate.whatsapp_number_id == whatsapp_number_id,
+                    WhatsAppTemplate.organization_id == organization_id,
+                    WhatsAppTemplate.status == "APPROVED",
+                    WhatsAppTemplate.is_enabled.is_(True),
+                    WhatsAppTemplate.deleted_at.is_(None),
+                )
+            )
+        )
+        return pick_template_language(result.scalars().all(), contact_language, language)
+
     async def _get_by_name(
         self,
         name: str,
@@ -537,6 +784,8 @@ class TemplateService:
             body_variables_count=body_vars,
             footer_text=footer_text,
             buttons=buttons,
+            carousel_cards=carousel_definition(components) or None,
+            limited_time_offer=limited_time_offer_definition(components),
         )
 
         if meta_template.get("status") == "APPROVED":
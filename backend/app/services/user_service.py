@@ -9,9 +9,9 @@ from uuid import UUID
 from sqlalchemy import select, func
 from sqlalchemy.ext.asyncio import AsyncSession
 
-from app.models.user import User
+from app.models.user import User, UserWorkspace
 from app.repositories.user import UserRepository
-from app.schemas.user import UserCreate, UserUpdate
+from app.schemas.user import UserCreate, UserUpdate, WorkspaceMember, WorkspaceMemberAdd
 from app.core.security import hash_password
 from app.core.exceptions import (
     BadRequestException,
@@ -348,3 +348,88 @@ class UserService:
             }
             for skill in new_skills
         ]
+
+    # ============= Workspace members (multi-tenant accounts) =============
+
+    def _to_workspace_member(self, workspace: UserWorkspace, user: User) -> WorkspaceMember:
+        return WorkspaceMember(
+            user_id=user.id,
+            email=user.email,
+            full_name=user.full_name,
+            role=workspace.role,
+            department_ids=workspace.department_ids or [],
+            is_active=workspace.is_active,
+            last_accessed_at=workspace.last_accessed_at,
+            created_at=workspace.created_at,
+        )
+
+    async def list_workspace_members(self, organization_id: UUID) -> List[WorkspaceMember]:
+        """List guest members (accounts whose home is another organization)"""
+        result = await self.db.execute(
+            select(UserWorkspace, User)
+            .join(User, User.id == UserWorkspace.user_id)
+            .where(
+                UserWorkspace.organization_id == organization_id,
+                User.deleted_at.is_(None),
+            )
+            .order_by(User.full_name)
+        )
+        return [self._to_workspace_member(workspace, user) for workspace, user in result.all()]
+
+    async def add_workspace_member(
+        self, organization_id: UUID, data: WorkspaceMemberAdd, added_by: User
+    ) -> WorkspaceMember:
+        """Give an existing account access to this organization with its own role"""
+        if added_by.role not in ["super_admin", "org_admin"]:
+            raise ForbiddenException("Only admins can add workspace members")
+
+        user = await self.repo.get_by_email(data.email)
+        if not user or user.deleted_at or not user.is_active:
+            raise NotFoundException("No active account with this email")
+
+        if user.home_organization_id == organization_id:
+            raise ConflictException("User already belongs to this organization")
+
+        workspace = (await self.db.execute(
+            select(UserWorkspace).where(
+                UserWorkspace.user_id == user.id,
+                UserWorkspace.organization_id == organization_id,
+            )
+        )).scalar_one_or_none()
+
+        if workspace and workspace.is_active:
+            raise ConflictException("User is already a member of this organization")
+
+        if not workspace:
+            workspace = UserWorkspace(user_id=user.id, organization_id=organization_id)
+            self.db.add(workspace)
+
+        workspace.role = data.role
+        workspace.department_ids = data.department_ids
+        workspace.is_active = True
+        workspace.invited_by_user_id = added_by.id
+
+        await self.db.commit()
+        await self.db.refresh(workspace)
+        return self._to_workspace_member(workspace, user)
+
+    async def remove_workspace_member(
+        self, organization_id: UUID, user_id: UUID, removed_by: User
+    ) -> None:
+        """Revoke a guest member (their tokens for this workspace stop working)"""
+        if removed_by.role not in ["super_admin", "org_admin"]:
+            raise ForbiddenException("Only admins can remove workspace members")
+
+        workspace = (await self.db.execute(
+            select(UserWorkspace).where(
+                UserWorkspace.user_id == user_id,
+                UserWorkspace.organization_id == organization_id,
+                UserWorkspace.is_active.is_(True),
+            )
+        )).scalar_one_or_none()
+
+        if not workspace:
+            raise NotFoundException("Workspace member not found")
+
+        workspace.is_active = False
+        await self.db.commit()
@@ -0,0 +1,288 @@
+"""
+Webhook Event Service - Persistence and replay of raw Meta webhooks
+
+Receivers store each accepted payload before processing it and record the
+outcome (processed, failed, skipped), also pushed to the organization's
+webhook inbox (app.core.webhook_inbox). Failed and skipped events are
+replayed by the "replay_webhook_events" worker task, requested through the
+API after a bug fix or outage. Replays bypass webhook deduplication;
+messages already stored are still not duplicated.
+"""
+
+import logging
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, List, Optional, Sequence
+from uuid import UUID
+
+from sqlalchemy import and_, delete, func, or_, select, update
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.core.webhook_inbox import webhook_inbox
+from app.models.webhook_event import REPLAYABLE_STATUSES, WebhookEvent
+from app.models.whatsapp_number import WhatsAppNumber
+from app.schemas.webhook_event import WebhookReplayRequest
+
+logger = logging.getLogger(__name__)
+
+MAX_ERROR_LENGTH = 2000
+
+
+def first_phone_number_id(payload: Dict[str, Any]) -> Optional[str]:
+    """phone_number_id of the first change carrying metadata"""
+    for entry in payload.get("entry", []):
+        for change in entry.get("changes", []):
+            phone_number_id = change.get("value", {}).get("metadata", {}).get("phone_number_id")
+            if phone_number_id:
+                return phone_number_id
+    return None
+
+
+class WebhookEventService:
+    """Service for stored webhook events"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    # ============= Persistence (receivers) =============
+
+    async def record(
+        self,
+        payload: Dict[str, Any],
+        source: str = "whatsapp",
+        organization_id: Optional[UUID] = None,
+        whatsapp_number_id: Optional[UUID] = None,
+        status: str = "received",
+    ) -> Optional[WebhookEvent]:
+        """
+        Store a raw payload (commits)
+
+        Never raises: a storage problem must not stop the webhook from being
+        processed and acknowledged.
+        """
+        event = WebhookEvent(
+            organization_id=organization_id,
+            whatsapp_number_id=whatsapp_number_id,
+            source=source,
+            phone_number_id=first_phone_number_id(payload),
+            payload=payload,
+            status=status,
+            attempts=0,
+            received_at=datetime.now(timezone.utc),
+        )
+        try:
+            self.db.add(event)
+            await self.db.commit()
+        except Exception as e:
+            await self.db.rollback()
+            logger.warning(f"⚠️ Could not store webhook payload: {e}")
+            return None
+        return event
+
+    async def mark(
+        self, event_id: Optional[UUID], status: str, error: Optional[str] = None
+    ) -> None:
+        """Record the outcome of a processing attempt (commits, never raises)"""
+        if not event_id:
+            return
+
+        now = datetime.now(timezone.utc)
+        values: Dict[str, Any] = {"status": status, "error": error[:MAX_ERROR_LENGTH] if error else None}
+        if status in ("processed", "failed", "skipped"):
+            values["attempts"] = WebhookEvent.attempts + 1
+            values["last_attempt_at"] = now
+        if status == "processed":
+            values["processed_at"] = now
+
+        try:
+            result = await self.db.execute(
+                update(WebhookEvent)
+                .where(WebhookEvent.id == event_id)
+                .values(**values)
+                .returning(
+                    WebhookEvent.organization_id,
+                    WebhookEvent.phone_number_id,
+                    WebhookEvent.source,
+                    WebhookEvent.payload,
+                )
+            )
+            stored = result.first()
+            await self.db.commit()
+        except Exception as e:
+            await self.db.rollback()
+            logger.warning(f"⚠️ Could not update webhook event {event_id}: {e}")
+            return
+
+        if stored and status in ("processed", "failed", "skipped"):
+            await self._record_in_inbox(event_id, stored, status, error)
+
+    async def _record_in_inbox(self, event_id: UUID, stored: Any, status: str, error: Optional[str]) -> None:
+        """Processing outcome in the organization's webhook inbox (debugging)"""
+        organization_id = stored.organization_id
+        try:
+            if not organization_id and stored.phone_number_id:
+                organization_id = await self.db.scalar(
+                    select(WhatsAppNumber.organization_id).where(
+                        WhatsAppNumber.phone_number_id == stored.phone_number_id,
+                        WhatsAppNumber.deleted_at.is_(None),
+                    ).limit(1)
+                )
+        except Exception as e:
+            logger.warning(f"⚠️ Could not resolve organization of webhook event {event_id}: {e}")
+            return
+
+        await webhook_inbox.record_inbound(
+            organization_id, stored.payload or {}, status, source=stored.source, error=error, event_id=event_id
+        )
+
+    async def process(self, event_id: Optional[UUID], payload: Dict[str, Any], dedup: bool = True) -> str:
+        """
+        Process a payload with WhatsAppService and record the outcome
+
+        Raises:
+            Exception: Processing errors, after the event is marked failed
+        """
+        from app.services.whatsapp_service import WhatsAppService
+
+        try:
+            handled = await WhatsAppService(self.db).process_webhook(payload, dedup=dedup)
+        except Exception as e:
+            await self.mark(event_id, "failed", str(e) or e.__class__.__name__)
+            raise
+
+        if handled:
+            await self.mark(event_id, "processed")
+            return "processed"
+
+        await self.mark(event_id, "skipped", "No change of the payload was handled (unknown number?)")
+        return "skipped"
+
+    # ============= API =============
+
+    def _visible_to(self, organization_id: UUID):
+        """Events of the organization, plus unclaimed ones for its numbers"""
+        own_numbers = select(WhatsAppNumber.phone_number_id).where(
+            WhatsAppNumber.organization_id == organization_id,
+            WhatsAppNumber.deleted_at.is_(None),
+        )
+        return or_(
+            WebhookEvent.organization_id == organization_id,
+            and_(
+                WebhookEvent.organization_id.is_(None),
+                WebhookEvent.phone_number_id.in_(own_numbers),
+            ),
+        )
+
+    async def list_events(
+        self,
+        organization_id: UUID,
+        status: Optional[str] = None,
+        since: Optional[datetime] = None,
+        until: Optional[datetime] = None,
+        skip: int = 0,
+        limit: int = 50,
+    ) -> tuple[List[WebhookEvent], int]:
+        """Stored events of the organization, newest first"""
+        conditions = [self._visible_to(organization_id)]
+        if status:
+            conditions.append(WebhookEvent.status == status)
+        if since:
+            conditions.append(WebhookEvent.received_at >= since)
+        if until:
+            conditions.append(WebhookEvent.received_at <= until)
+
+        total = await self.db.scalar(select(func.count(WebhookEvent.id)).where(*conditions))
+        result = await self.db.execute(
+            select(WebhookEvent)
+            .where(*conditions)
+            .order_by(WebhookEvent.received_at.desc())
+            .offset(skip)
+            .limit(limit)
+        )
+        return list(result.scalars().all()), total or 0
+
+    async def get_event(self, event_id: UUID, organization_id: UUID) -> WebhookEvent:
+        """
+        Raises:
+            NotFoundException: If the event does not exist or is not visible
+        """
+        result = await self.db.execute(
+            select(WebhookEvent).where(WebhookEvent.id == event_id, self._visible_to(organization_id))
+        )
+        event = result.scalar_one_or_none()
+        if not event:
+            raise NotFoundException("Webhook event not found")
+        return event
+
+    async def request_replay(self, organization_id: UUID, data: WebhookReplayRequest) -> List[UUID]:
+        """
+        Queue failed/skipped events for the replay worker
+
+        Raises:
+            BadRequestException: If none of the selected events can be replayed
+        """
+        conditions = [
+            self._visible_to(organization_id),
+            WebhookEvent.status.in_([data.status] if data.status else list(REPLAYABLE_STATUSES)),
+        ]
+        if data.event_ids:
+            conditions.append(WebhookEvent.id.in_(data.event_ids))
+        if data.since:
+            conditions.append(WebhookEvent.received_at >= data.since)
+        if data.until:
+            conditions.append(WebhookEvent.received_at <= data.until)
+
+        result = await self.db.execute(
+            select(WebhookEvent.id)
+            .where(*conditions)
+            .order_by(WebhookEvent.received_at)
+            .limit(data.limit)
+        )
+        event_ids = list(result.scalars().all())
+        if not event_ids:
+            raise BadRequestException("No failed or skipped events match the selection")
+
+        # Unclaimed events (number registered later) now belong to the organization
+        await self.db.execute(
+            update(WebhookEvent)
+            .where(WebhookEvent.id.in_(event_ids))
+            .values(status="queued", organization_id=organization_id)
+        )
+        await self.db.commit()
+
+        self._enqueue_replay(event_ids)
+        logger.info(f"🔁 Queued {len(event_ids)} webhook events for replay (org {organization_id})")
+        return event_ids
+
+    def _enqueue_replay(self, event_ids: Sequence[UUID]) -> None:
+        from app.tasks.webhook_tasks import replay_webhook_events
+
+        replay_webhook_events.apply_async(args=[[str(event_id) for event_id in event_ids]])
+
+    # ============= Worker =============
+
+    async def replay(self, event_ids: Sequence[UUID]) -> Dict[str, int]:
+        """Re-process stored events in arrival order; one failure does not stop the others"""
+        result = await self.db.execute(
+            select(WebhookEvent.id, WebhookEvent.payload)
+            .where(WebhookEvent.id.in_(list(event_ids)))
+            .order_by(WebhookEvent.received_at)
+        )
+
+        summary = {"processed": 0, "failed": 0, "skipped": 0}
+        for event_id, payload in result.all():
+            try:
+                outcome = await self.process(event_id, payload, dedup=False)
+            except Exception as e:
+                logger.error(f"❌ Replay of webhook event {event_id} failed: {e}")
+                outcome = "failed"
+            summary[outcome] += 1
+
+        return summary
+
+    async def purge(self, retention_days: int) -> int:
+        """Delete events older than the retention period"""
+        cutoff = datetime.now(timezone.utc) - timedelta(days=retention_days)
+        result = await self.db.execute(delete(WebhookEvent).where(WebhookEvent.received_at < cutoff))
+        await self.db.commit()
+        return result.rowcount or 0
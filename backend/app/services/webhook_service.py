@@ -17,9 +17,10 @@ from sqlalchemy import select, and_
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.models.conversation import Message, Conversation
-from app.models.campaign import Campaign
 from app.models.contact import Contact
-from app.tasks.campaign_retry import CampaignRetryManager
+from app.repositories.conversation import MessageRepository
+from app.schemas.webhook import IdentityChange, InboundMessage, WebhookEventType, classify_message
+from app.services.message_status_tracker import MessageStatusTracker
 
 logger = logging.getLogger(__name__)
 
@@ -43,236 +44,13 @@ class WebhookService:
           "errors": [...]  # if status == failed
         }
         
+        Status, timestamps, campaign stats and WebSocket events are handled by
+        MessageStatusTracker.
+
         Args:
             status: Status object from webhook
         """
-        message_id = status.get("id")
-        status_value = status.get("status")
-        timestamp = status.get("timestamp")
-        recipient_id = status.get("recipient_id")
-        errors = status.get("errors", [])
-        
-        logger.info(
-            f"📝 Processing status update: "
-            f"message_id={message_id}, status={status_value}"
-        )
-        
-        # Find message by WhatsApp message ID
-        message = await self._find_message_by_whatsapp_id(message_id)
-        
-        if not message:
-            logger.warning(
-                f"⚠️ Message not found: {message_id}. "
-                f"Possibly not a campaign message or already deleted."
-            )
-            return
-        
-        # Update message status
-        old_status = message.status
-        message.status = status_value
-        message.metadata = message.metadata or {}
-        message.metadata["status_history"] = message.metadata.get("status_history", [])
-        message.metadata["status_history"].append({
-            "status": status_value,
-            "timestamp": timestamp,
-            "updated_at": datetime.utcnow().isoformat(),
-        })
-        
-        await self.db.commit()
-        
-        logger.info(
-            f"✅ Updated message {message_id}: {old_status} -> {status_value}"
-        )
-        
-        # If message is part of a campaign, update campaign stats
-        campaign_id = message.metadata.get("campaign_id")
-        
-        if campaign_id:
-            await self._update_campaign_stats(
-                campaign_id=UUID(campaign_id),
-                contact_id=message.contact_id,
-                message_id=message_id,
-                status=status_value,
-                errors=errors,
-            )
-    
-    async def _find_message_by_whatsapp_id(
-        self,
-        whatsapp_message_id: str
-    ) -> Optional[Message]:
-        """
-        Find message by WhatsApp message ID
-        
-        Args:
-            whatsapp_message_id: WhatsApp message ID (wamid.xxx)
-            
-        Returns:
-            Message or None if not found
-        """
-        stmt = select(Message).where(
-            Message.whatsapp_message_id == whatsapp_message_id
-        )
-        result = await self.db.execute(stmt)
-        return result.scalar_one_or_none()
-    
-    async def _update_campaign_stats(
-        self,
-        campaign_id: UUID,
-        contact_id: UUID,
-        message_id: str,
-        status: str,
-        errors: list,
-    ) -> None:
-        """
-        Update campaign statistics based on message status
-        
-        Status transitions:
-        - sent -> delivered: increment messages_delivered
-        - delivered -> read: increment messages_read
-        - any -> failed: increment messages_failed
-        
-        Args:
-            campaign_id: Campaign UUID
-            contact_id: Contact UUID
-            message_id: WhatsApp message ID
-            status: New status (sent, delivered, read, failed)
-            errors: List of error objects (if status == failed)
-        """
-        # Load campaign
-        stmt = select(Campaign).where(Campaign.id == campaign_id)
-        result = await self.db.execute(stmt)
-        campaign = result.scalar_one_or_none()
-        
-        if not campaign:
-            logger.warning(f"⚠️ Campaign not found: {campaign_id}")
-            return
-        
-        logger.info(
-            f"📊 Updating campaign {campaign_id} stats: "
-            f"status={status}, contact={contact_id}"
-        )
-        
-        # Initialize retry manager to update message_statuses
-        retry_manager = CampaignRetryManager(campaign, self.db)
-        
-        # Update message_statuses JSONB
-        await retry_manager.update_message_status(
-            contact_id=contact_id,
-            new_status=status,
-            message_id=message_id,
-        )
-        
-        # Update campaign counters based on status
-        if status == "delivered":
-            # Only increment if not already counted
-            # (prevents double counting if webhook sent twice)
-            contact_status = retry_manager.get_contact_status(contact_id)
-            
-            if contact_status and contact_status.get("status") != "delivered":
-                campaign.messages_delivered += 1
-                logger.info(
-                    f"✅ Campaign {campaign_id}: messages_delivered "
-                    f"now {campaign.messages_delivered}"
-                )
-        
-        elif status == "read":
-            # Increment read count
-            contact_status = retry_manager.get_contact_status(contact_id)
-            
-            if contact_status and contact_status.get("status") != "read":
-                campaign.messages_read += 1
-                
-                # Also ensure delivered is counted
-                if campaign.messages_delivered < campaign.messages_read:
-                    campaign.messages_delivered = campaign.messages_read
-                
-                logger.info(
-                    f"✅ Campaign {campaign_id}: messages_read "
-                    f"now {campaign.messages_read}"
-                )
-        
-        elif status == "failed":
-            # Increment failed count
-            campaign.messages_failed += 1
-            
-            # Record error details
-            if errors:
-                error_detail = errors[0] if errors else {}
-                error_message = (
-                    f"Code: {error_detail.get('code')}, "
-                    f"Title: {error_detail.get('title')}"
-                )
-                campaign.last_error_message = error_message
-            
-            logger.error(
-                f"❌ Campaign {campaign_id}: message failed. "
-                f"Total failed: {campaign.messages_failed}"
-            )
-        
-        # Recalculate rates
-        if campaign.messages_sent > 0:
-            campaign.delivery_rate = (
-                campaign.messages_delivered / campaign.messages_sent * 100
-            )
-            campaign.read_rate = (
-                campaign.messages_read / campaign.messages_sent * 100
-            )
-        
-        await self.db.commit()
-        
-        # Broadcast progress via WebSocket
-        await self._broadcast_campaign_progress(campaign)
-    
-    async def _broadcast_campaign_progress(self, campaign: Campaign) -> None:
-        """
-        Broadcast campaign progress via WebSocket
-        
-        Args:
-            campaign: Campaign model
-        """
-        try:
-            from app.core.websocket_manager import websocket_manager
-            
-            # Calculate progress
-            progress_percentage = campaign.progress_percentage
-            
-            # Prepare payload
-            payload = {
-                "campaign_id": str(campaign.id),
-                "campaign_name": campaign.name,
-                "status": campaign.status,
-                "progress": progress_percentage,
-                "stats": {
-                    "total_recipients": campaign.total_recipients,
-                    "messages_sent": campaign.messages_sent,
-                    "messages_delivered": campaign.messages_delivered,
-                    "messages_read": campaign.messages_read,
-                    "messages_failed": campaign.messages_failed,
-                    "messages_pending": campaign.messages_pending,
-                    "delivery_rate": campaign.delivery_rate,
-                    "read_rate": campaign.read_rate,
-                },
-                "timestamp": datetime.utcnow().isoformat(),
-            }
-            
-            # Broadcast to campaign room
-            await websocket_manager.broadcast_to_room(
-                room=f"campaign:{campaign.id}",
-                message=payload,
-                event="campaign:progress",
-            )
-            
-            logger.info(
-                f"📡 Broadcasted progress for campaign {campaign.id}: "
-                f"{progress_percentage:.1f}%"
-            )
-            
-        except ImportError:
-            logger.warning(
-                "⚠️ WebSocket manager not available, skipping broadcast"
-            )
-        except Exception as e:
-            logger.error(f"❌ Error broadcasting progress: {e}")
+        await MessageStatusTracker(self.db).handle(status)
 
     async def process_incoming_message(
         self,
@@ -364,6 +142,12 @@ class WebhookService:
             # 4. Extract content based on message type
             content = self._extract_message_content(message, message_type)
             
+            # Encrypted tenants store an envelope instead of the plain body
+            message_repo = MessageRepository(self.db)
+            stored_content = await message_repo.encrypt_for_storage(
+                organization_id, content
+            )
+            
             # 5. Create Message
             new_message = MessageModel(
                 organization_id=organization_id,
@@ -374,7 +158,7 @@ class WebhookService:
                 whatsapp_message_id=message_id,
                 whatsapp_timestamp=int(timestamp) if timestamp else None,
                 message_type=message_type,
-                content=content,
+                content=stored_content,
                 status="received",
             )
             
@@ -404,6 +188,7 @@ class WebhookService:
             
             await self.db.commit()
             await self.db.refresh(new_message)
+            await message_repo.decrypt_messages([new_message])
             
             logger.info(
                 f"✅ Created message {new_message.id} in conversation {conversation.id}"
@@ -416,6 +201,14 @@ class WebhookService:
                 contact=contact,
                 organization_id=organization_id,
             )
+
+            # Security notification: agents re-verify the contact before sharing sensitive data
+            if classify_message(message) == WebhookEventType.IDENTITY_CHANGE:
+                from app.services.identity_service import IdentityVerificationService
+
+                await IdentityVerificationService(self.db).flag(
+                    conversation, IdentityChange.from_webhook(message)
+                )
             
             return new_message
             
@@ -517,95 +310,8 @@ class WebhookService:
         message: Dict[str, Any],
         message_type: str,
     ) -> Dict[str, Any]:
-        """Extract content based on message type"""
-        if message_type == "text":
-            return {"text": message.get("text", {}).get("body", "")}
-        
-        elif message_type == "image":
-            img = message.get("image", {})
-            return {
-                "image": {
-                    "id": img.get("id"),
-                    "caption": img.get("caption"),
-                    "mime_type": img.get("mime_type"),
-                }
-            }
-        
-        elif message_type == "video":
-            vid = message.get("video", {})
-            return {
-                "video": {
-                    "id": vid.get("id"),
-                    "caption": vid.get("caption"),
-                    "mime_type": vid.get("mime_type"),
-                }
-            }
-        
-        elif message_type == "audio":
-            aud = message.get("audio", {})
-            return {
-                "audio": {
-                    "id": aud.get("id"),
-                    "mime_type": aud.get("mime_type"),
-                }
-            }
-        
-        elif message_type == "document":
-            doc = message.get("document", {})
-            return {
-                "document": {
-                    "id": doc.get("id"),
-                    "filename": doc.get("filename"),
-                    "caption": doc.get("caption"),
-                    "mime_type": doc.get("mime_type"),
-                }
-            }
-        
-        elif message_type == "location":
-            loc = message.get("location", {})
-            return {
-                "location": {
-                    "latitude": loc.get("latitude"),
-                    "longitude": loc.get("longitude"),
-                    "name": loc.get("name"),
-                    "address": loc.get("address"),
-                }
-            }
-        
-        elif message_type == "contacts":
-            return {"contacts": message.get("contacts", [])}
-        
-        elif message_type == "interactive":
-            inter = message.get("interactive", {})
-            return {
-                "interactive": {
-                    "type": inter.get("type"),
-                    "button_reply": inter.get("button_reply"),
-                    "list_reply": inter.get("list_reply"),
-                }
-            }
-        
-        elif message_type == "button":
-            btn = message.get("button", {})
-            return {
-                "button": {
-                    "text": btn.get("text"),
-                    "payload": btn.get("payload"),
-                }
-            }
-        
-        elif message_type == "sticker":
-            stk = message.get("sticker", {})
-            return {
-                "sticker": {
-                    "id": stk.get("id"),
-                    "animated": stk.get("animated"),
-                }
-            }
-        
-        else:
-            # Unknown type - store raw
-            return {"raw": message}
+        """Extract content based on message type (unknown types keep the raw payload)"""
+        return InboundMessage.from_webhook(message).to_content()
 
     async def _broadcast_new_message(
         self,
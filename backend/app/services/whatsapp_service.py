@@ -1,21 +1,222 @@
+from datetime import datetime, timedelta, timezone
 from typing import List, Dict, Any, Optional
-from uuid import UUID
+from uuid import UUID, uuid4
+import copy
 import logging
+import time
+from contextlib import asynccontextmanager
 from sqlalchemy.ext.asyncio import AsyncSession
 from sqlalchemy import select
 from app.models.whatsapp_number import WhatsAppNumber
 from app.models.conversation import Message
+from app.models.secret import SecretUsage
+from app.repositories.chatbot import (
+    FlowNodeVisitRepository,
+    FlowRepository,
+    FlowTraceRepository,
+    FlowVersionRepository,
+    FlowWaitRepository,
+)
 from app.repositories.whatsapp import WhatsAppNumberRepository
 from app.schemas.whatsapp import WhatsAppNumberCreate, WhatsAppNumberUpdate, ConnectionType
-from app.core.exceptions import ConflictException, NotFoundException
+from app.schemas.webhook import (
+    InboundMessage,
+    InboundMessageType,
+    ECHO_FIELDS,
+    IdentityChange,
+    MessageContext,
+    MessageEcho,
+    MessageEdit,
+    MessageReaction,
+    PaymentStatus,
+    PhoneNumberQualityUpdate,
+    TemplateStatusUpdate,
+    WebhookChallenge,
+    WebhookEventType,
+    classify_message,
+    classify_status,
+)
+from pytake_client.errors import MediaFetchError
+from pytake_client.processor import (
+    UNROUTABLE_MISSING,
+    UNROUTABLE_UNKNOWN,
+    echo_dedup_key,
+    message_dedup_key,
+    status_dedup_key,
+)
+from app.core.exceptions import BadRequestException, ConflictException, ForbiddenException, NotFoundException
+from app.core.config import settings
+from app.core.fault_injection import http_client
+from app.core.flow_lanes import flow_lane, get_flow_lane_scheduler
+from app.core.flow_session import FlowSessionLockError, get_flow_session_manager
+from app.core.media_storage import get_media_storage
+from app.core.redis import rate_limit_redis
+from app.core.webhook_inbox import webhook_inbox
 from app.integrations.evolution_api import EvolutionAPIClient, generate_instance_name, EvolutionAPIError
+from app.integrations.meta_api import graph_url
+from app.integrations.whatsapp_pool import SenderPolicy, whatsapp_clients
+from app.utils.message_limits import (
+    INTERACTIVE_BODY_MAX,
+    OutboundMessageError,
+    ensure_valid_outbound_message,
+    grapheme_len,
+    validate_buttons,
+    validate_list,
+)
+from app.utils.business_hours import BUSINESS_HOURS_NODE_TYPE, CLOSED_HANDLE, HOLIDAY_HANDLE, business_hours_status
+from app.utils.flow_compute import apply_set_variables
+from app.utils.flow_events import EVENT_CAMPAIGN_REPLY, EVENT_VARIABLE, payload_value
+from app.utils.flow_expression import ExpressionError, evaluate_expression
+from app.utils.flow_intent import (
+    DEFAULT_CONFIDENCE_THRESHOLD,
+    INTENT_FALLBACK_HANDLE,
+    build_intent_prompt,
+    intent_menu,
+    match_menu_choice,
+    normalize_intents,
+    parse_intent_response,
+)
+from app.utils.flow_guard import (
+    EXECUTION_PATH_KEY,
+    FALLBACK_END,
+    FLOW_GUARD_MESSAGE_NODE_TYPES,
+    GUARD_FLOW_STARTS,
+    GUARD_MESSAGES,
+    MAX_EXECUTION_PATH,
+    FlowGuardSettings,
+    detect_loop,
+    rate_windows,
+)
+from app.utils.flow_template import render_text
+from app.utils.flow_locale import LOCALE_VARIABLE, TRANSLATIONS_KEY, localize_node_data
+from app.utils.flow_loop import LOOP_BODY_HANDLE, LOOP_DONE_HANDLE, LOOP_STATE_KEY, next_iteration
+from app.utils.flow_split import SPLIT_NODE_TYPE, assign_variant, experiment_key, split_variants
+from app.utils.flow_migration import FLOW_RESTART_KEY
+from app.utils.flow_navigation import (
+    BACK_BUTTON_ID,
+    NAVIGATION_HISTORY_KEY,
+    NavigationSettings,
+    is_back_request,
+    pop_history,
+    push_history,
+)
+from app.utils.flow_checkpoint import (
+    STALE_AFTER_SECONDS as CHECKPOINT_STALE_AFTER_SECONDS,
+    CheckpointedSender,
+    begin as begin_checkpoint,
+    complete as complete_checkpoint,
+    is_executing,
+    should_give_up,
+)
+from app.utils.flow_timeout import FLOW_TIMEOUT_FIRED_KEY, FlowTimeoutSettings
+from app.utils.flow_trace import (
+    EVENT_INPUT_RECEIVED,
+    EVENT_MESSAGE_SENT,
+    EVENT_NODE_EXECUTED,
+    EVENT_VARIABLE_SET,
+    input_data,
+    sent_data,
+    trace_event,
+    variable_changes,
+)
+from app.utils.flow_wait import wait_resume_at
+from app.utils.http_request import is_retryable, map_response, render_template
+from app.utils.input_validation import DEFAULT_MAX_ATTEMPTS_MESSAGE, INVALID_HANDLE, max_attempts, validate_answer
+from app.utils.location_collection import (
+    DEFAULT_ERROR_MESSAGE as LOCATION_ERROR_MESSAGE,
+    DEFAULT_PROMPT as LOCATION_DEFAULT_PROMPT,
+    IN_AREA_HANDLE,
+    LOCATION_COLLECT_NODE_TYPE,
+    OUT_OF_AREA_HANDLE,
+    extract_location,
+    find_area,
+    parse_service_areas,
+)
+from app.utils.media_collection import (
+    DEFAULT_PROMPT as MEDIA_DEFAULT_PROMPT,
+    MEDIA_COLLECT_NODE_TYPE,
+    MediaRequirements,
+    check_media,
+    extract_media,
+    media_variable,
+)
+from app.utils.language_detection import detect_language
 from app.utils.node_availability import NodeAvailability
+from app.utils.script_sandbox import (
+    ERROR_HANDLE as SCRIPT_ERROR_HANDLE,
+    SANDBOX_SCRIPT_NODE_TYPE,
+    SUCCESS_HANDLE as SCRIPT_SUCCESS_HANDLE,
+    SandboxLimits,
+    ScriptError,
+    ScriptResult,
+    run_script,
+)
+from app.utils.survey import (
+    CATEGORY_HANDLES as SURVEY_CATEGORY_HANDLES,
+    DEFAULT_COMMENT_PROMPT as SURVEY_COMMENT_PROMPT,
+    DEFAULT_QUESTIONS as SURVEY_DEFAULT_QUESTIONS,
+    SCORE_RANGES as SURVEY_SCORE_RANGES,
+    SURVEY_NODE_TYPE,
+    classify as classify_survey_score,
+    clean_comment,
+    is_skip,
+    parse_score,
+    survey_type,
+)
+from app.services.secret_service import OrgSecretResolver
 
 logger = logging.getLogger(__name__)
 
+# Nodes que podem demorar (IA, HTTP, banco, scripts) - exibem "digitando..." antes
+LONG_RUNNING_NODE_TYPES = {"ai_prompt", "api_call", "database_query", "script", SANDBOX_SCRIPT_NODE_TYPE}
+
+# Sub-flows: pilha de retorno guardada em context_variables e profundidade máxima
+SUBFLOW_STACK_KEY = "_subflow_stack"
+MAX_SUBFLOW_DEPTH = 5
+
+# Handoff Node com returnToFlow: flow/node de retorno guardados em extra_data
+HANDOFF_RETURN_KEY = "handoff_return"
+
+# Sessão do flow em execução (context_variables), usada no funil por node (FlowNodeVisit)
+FLOW_SESSION_KEY = "_flow_session"
+
+# Saídas do API Call Node com errorHandling.onError "branch"
+API_CALL_SUCCESS_HANDLE = "success"
+API_CALL_ERROR_HANDLE = "error"
+
+# Colunas da sessão do fluxo relidas ao obter o lock da sessão
+FLOW_SESSION_COLUMNS = (
+    "status",
+    "current_agent_id",
+    "is_bot_active",
+    "active_chatbot_id",
+    "active_flow_id",
+    "current_node_id",
+    "context_variables",
+    "node_checkpoint",
+    "node_checkpoint_at",
+    "flow_timeout_at",
+    "flow_expires_at",
+)
+
+
+def _with_meta_response(message: Message, response) -> Dict[str, Any]:
+    """extra_data of a sent/failed message with Meta's request id and rate-limit usage (meta_response)"""
+    extra_data = dict(message.extra_data or {})
+    if response is not None:
+        extra_data["meta_response"] = response.to_dict()
+    return extra_data
+
 class WhatsAppService:
     """Service for WhatsApp number management"""
 
+    # (conversa, checkpoint) do node em execução, cujos envios passam pelo checkpoint
+    _checkpoint_run = None
+    # Lock da sessão do fluxo mantido por este processo (ver _flow_session)
+    _session_lock = None
+    # Variáveis de contexto já registradas no trace, por conversa (ver _trace_variables)
+    _trace_snapshots = None
+
     def __init__(self, db: AsyncSession):
         self.db = db
         self.repo = WhatsAppNumberRepository(db)
@@ -50,9 +251,6 @@ class WhatsAppService:
         Executa o fluxo do chatbot, processando node atual e avançando automaticamente.
         """
         from app.services.chatbot_service import ChatbotService
-        from app.repositories.conversation import ConversationRepository
-        from app.models.chatbot import Node
-        from sqlalchemy import select
 
         if not conversation.active_chatbot_id:
             logger.warning("Nenhum chatbot ativo para a conversa.")
@@ -60,8 +258,20 @@ class WhatsAppService:
 
         chatbot_service = ChatbotService(self.db)
         organization_id = conversation.organization_id
+
+        # Mensagem do contato: a detecção de loops conta só os nodes executados a partir daqui
+        if (conversation.context_variables or {}).get(EXECUTION_PATH_KEY):
+            context_vars = dict(conversation.context_variables)
+            context_vars.pop(EXECUTION_PATH_KEY)
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+
+        # Gatilhos por palavra-chave escolhem o fluxo antes do principal; com
+        # interrupts_flow também substituem o fluxo em andamento (ex: "menu")
+        if await self._start_triggered_flow(conversation, new_message):
+            return
+
         chatbot_id = conversation.active_chatbot_id
-        conv_repo = ConversationRepository(self.db)
 
         # Se não tem flow ativo, iniciar com main flow
         if not conversation.active_flow_id:
@@ -70,51 +280,7 @@ class WhatsAppService:
                 logger.warning(f"Nenhum fluxo principal encontrado para chatbot {chatbot_id}")
                 return
 
-            # Buscar start node
-            start_node = await chatbot_service.node_repo.get_start_node(main_flow.id, organization_id)
-            if not start_node:
-                logger.warning(f"Nenhum nó inicial encontrado para o fluxo principal {main_flow.id}")
-                return
-
-            # Encontrar primeiro node com conteúdo seguindo edge
-            canvas_data = main_flow.canvas_data or {}
-            edges = canvas_data.get("edges", [])
-            start_node_canvas_id = start_node.node_id
-            next_node_canvas_id = None
-
-            for edge in edges:
-                if edge.get("source") == start_node_canvas_id:
-                    next_node_canvas_id = edge.get("target")
-                    break
-
-            if not next_node_canvas_id:
-                logger.warning(f"Nenhuma edge encontrada saindo do start node")
-                return
-
-            # Buscar próximo node
-            stmt = select(Node).where(
-                Node.flow_id == main_flow.id,
-                Node.node_id == next_node_canvas_id,
-                Node.organization_id == organization_id
-            )
-            result = await self.db.execute(stmt)
-            first_node = result.scalar_one_or_none()
-
-            if not first_node:
-                logger.warning(f"Node {next_node_canvas_id} não encontrado no banco")
-                return
-
-            # Configurar flow e node inicial
-            await conv_repo.update(conversation.id, {
-                "active_flow_id": main_flow.id,
-                "current_node_id": first_node.id
-            })
-            await self.db.commit()
-
-            logger.info(f"🚀 Iniciando fluxo {main_flow.name} no node {first_node.node_type}")
-
-            # Executar primeiro node
-            await self._execute_node(conversation, first_node, main_flow, new_message)
+            await self._start_flow(conversation, main_flow, new_message)
         else:
             # Continuar fluxo - processar resposta do usuário e avançar
             if not conversation.current_node_id:
@@ -133,935 +299,2229 @@ class WhatsAppService:
                 logger.warning(f"Flow {conversation.active_flow_id} não encontrado")
                 return
 
+            # Contato respondeu: renova o timeout por inatividade da sessão
+            await self._refresh_flow_timeout(conversation)
+            await self._trace_input(conversation, current_node, new_message)
+
+            # Node atual aguardando a próxima mensagem para rodar: checkpoint de uma
+            # nova versão ou fluxo aberto pelo template de um gatilho de evento
+            if (conversation.context_variables or {}).get(FLOW_RESTART_KEY):
+                await self._restart_at_checkpoint(conversation, current_node, flow, new_message)
+                return
+
+            # Wait Node: a resposta só retoma o fluxo com resumeOnReply
+            if current_node.node_type == "wait":
+                await self._reply_during_wait(conversation, current_node, flow, new_message)
+                return
+
+            # Intent Node: a resposta ao menu de intenções é classificada de novo
+            if current_node.node_type == "intent":
+                node_data = await self._localized_node_data(conversation, current_node, flow)
+                await self._execute_intent(conversation, current_node, flow, new_message, node_data)
+                return
+
+            # Media Collect Node: a mensagem deve trazer o arquivo pedido
+            if current_node.node_type == MEDIA_COLLECT_NODE_TYPE:
+                await self._receive_media(conversation, current_node, flow, new_message)
+                return
+
+            # Location Collect Node: a mensagem deve trazer a localização
+            if current_node.node_type == LOCATION_COLLECT_NODE_TYPE:
+                await self._receive_location(conversation, current_node, flow, new_message)
+                return
+
+            # Survey Node: nota (e comentário opcional) da pesquisa NPS/CSAT
+            if current_node.node_type == SURVEY_NODE_TYPE:
+                await self._receive_survey(conversation, current_node, flow, new_message)
+                return
+
+            # Voltar: o contato pediu a pergunta anterior (palavra-chave ou botão "Voltar")
+            if current_node.node_type == "question" and await self._navigate_back(
+                conversation, current_node, flow, new_message
+            ):
+                return
+
             # Processar resposta do usuário e avançar
             await self._process_user_response_and_advance(conversation, current_node, flow, new_message)
 
-    async def _execute_node(self, conversation, node, flow, incoming_message):
+    async def _restart_at_checkpoint(self, conversation, checkpoint_node, flow, new_message):
         """
-        Executa um node do fluxo e envia mensagem via WhatsApp.
+        Retoma uma sessão reiniciada ao publicar uma versão (restart_at_checkpoint)
+        ou aberta pelo template de um gatilho de evento.
+
+        O node é executado em vez de receber a mensagem como resposta; no
+        start node o fluxo começa do início.
+        """
+        context_vars = dict(conversation.context_variables or {})
+        context_vars.pop(FLOW_RESTART_KEY, None)
+        await self._set_session(conversation, {"context_variables": context_vars})
+        conversation.context_variables = context_vars
+
+        logger.info(f"🔄 Conversa {conversation.id} retomada no node {checkpoint_node.node_id}")
+
+        if checkpoint_node.node_type == "start":
+            await self._start_flow(conversation, flow, new_message)
+        else:
+            await self._execute_node(conversation, checkpoint_node, flow, new_message)
+
+    async def _start_triggered_flow(self, conversation, new_message) -> bool:
+        """
+        Inicia o fluxo do gatilho (flow_triggers) que a mensagem dispara, se houver.
+
+        O fluxo pode ser de outro chatbot da organização; a conversa passa a
+        usar esse chatbot. Um fluxo interrompido não volta a sub-flows,
+        loops nem waits pendentes.
+
+        Returns:
+            True se um fluxo foi iniciado por gatilho
+        """
+        from app.services.flow_trigger_service import FlowTriggerService
+
+        text = ((new_message.content or {}).get("text") if new_message else None) or ""
+        if not text.strip():
+            return False
+
+        trigger = await FlowTriggerService(self.db).match(conversation, text)
+        if not trigger:
+            return False
+
+        flow = await FlowRepository(self.db).get(trigger.flow_id)
+        if not flow:
+            return False
+
+        logger.info(f"🎯 Gatilho {trigger.match_type} '{trigger.pattern}' iniciando fluxo {flow.name}")
+
+        update_data = {}
+        if flow.chatbot_id != conversation.active_chatbot_id:
+            update_data["active_chatbot_id"] = flow.chatbot_id
+        if conversation.active_flow_id:
+            context_vars = dict(conversation.context_variables or {})
+            for key in (SUBFLOW_STACK_KEY, LOOP_STATE_KEY, FLOW_TIMEOUT_FIRED_KEY):
+                context_vars.pop(key, None)
+            update_data["context_variables"] = context_vars
+            await FlowWaitRepository(self.db).cancel_pending(conversation.id)
+        if update_data:
+            await self._set_session(conversation, update_data)
+            for field, value in update_data.items():
+                setattr(conversation, field, value)
+
+        await self._start_flow(conversation, flow, new_message)
+        return True
+
+    async def start_flow_from_event(
+        self, trigger, contact, variables: Dict[str, Any], use_template: bool = True
+    ) -> str:
+        """
+        Inicia o fluxo de um gatilho de evento (flow_event_triggers) para um contato.
+
+        Usa a conversa aberta do contato no número do gatilho, ou cria uma.
+        Com template o fluxo é iniciado pela empresa: o template abre a
+        conversa e o fluxo começa do start node quando o contato responder.
+        Sem template o fluxo só roda dentro da janela de 24h.
 
         Args:
-            conversation: Instância da conversa
-            node: Node a ser executado
-            flow: Flow ativo
-            incoming_message: Mensagem que originou a execução
+            trigger: FlowEventTrigger disparado
+            contact: Contato do evento
+            variables: Variáveis do evento (ver app.utils.flow_events.event_variables)
+            use_template: False quando o contato acabou de escrever (resposta de campanha)
+
+        Returns:
+            "started", "opened" (template enviado, aguardando resposta) ou "skipped"
+
+        Raises:
+            ValueError: Se o template não estiver aprovado
         """
+        from app.models.whatsapp_number import WhatsAppTemplate
+        from app.repositories.chatbot import NodeRepository
         from app.repositories.conversation import ConversationRepository
-        import re
 
-        logger.info(f"🎬 Executando node {node.node_type}: {node.label}")
+        flow = await FlowRepository(self.db).get(trigger.flow_id)
+        whatsapp_number = await self.repo.get(trigger.whatsapp_number_id)
+        if not flow or not whatsapp_number or not whatsapp_number.is_active:
+            logger.warning(f"⚠️ Gatilho de evento {trigger.id}: fluxo ou número indisponível")
+            return "skipped"
 
-        # Validar compatibilidade do node com o tipo de conexão WhatsApp
-        whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
-        if whatsapp_number:
-            connection_type = whatsapp_number.connection_type.value if hasattr(whatsapp_number.connection_type, 'value') else str(whatsapp_number.connection_type)
-            is_available = NodeAvailability.is_node_available(node.node_type, connection_type)
+        conv_repo = ConversationRepository(self.db)
+        conversations = [
+            conv
+            for conv in await conv_repo.get_by_contact(contact.id, trigger.organization_id)
+            if conv.whatsapp_number_id == whatsapp_number.id
+        ]
+        conversation = next((conv for conv in conversations if conv.status == "open"), None)
+
+        if conversation and conversation.current_agent_id and not conversation.is_bot_active:
+            logger.info(f"⏭️ Evento {trigger.event_type} ignorado: conversa {conversation.id} com atendente")
+            return "skipped"
+        if conversation and conversation.active_flow_id and not trigger.interrupts_flow:
+            logger.info(f"⏭️ Evento {trigger.event_type} ignorado: conversa {conversation.id} já está em um fluxo")
+            return "skipped"
+
+        # A janela de 24h é do contato no número: vale a da última conversa (ex: ticket recém-fechado)
+        now = datetime.now(timezone.utc)
+        latest = conversation or (conversations[0] if conversations else None)
+        window_expires = latest.window_expires_at if latest else None
+        if window_expires and window_expires.tzinfo is None:
+            window_expires = window_expires.replace(tzinfo=timezone.utc)
+        template_id = trigger.template_id if use_template else None
+        if not template_id and not (window_expires and now < window_expires):
+            logger.info(f"⏭️ Evento {trigger.event_type} ignorado: janela de 24h fechada e gatilho sem template")
+            return "skipped"
 
-            if not is_available:
-                logger.error(
-                    f"❌ Node '{node.node_type}' não está disponível para conexão '{connection_type}'. "
-                    f"Este node requer Meta Cloud API (official)."
-                )
-                # Transferir para agente humano
-                await self._execute_handoff(
-                    conversation,
-                    {
-                        "transferMessage": (
-                            "Desculpe, esta funcionalidade não está disponível "
-                            "no momento. Vou transferir você para um agente humano."
-                        ),
-                        "sendTransferMessage": True,
-                        "priority": "high"
-                    }
-                )
-                return
+        if not conversation:
+            conversation = await conv_repo.create({
+                "organization_id": trigger.organization_id,
+                "contact_id": contact.id,
+                "whatsapp_number_id": whatsapp_number.id,
+                "status": "open",
+                "channel": "whatsapp",
+                "is_bot_active": True,
+                "active_chatbot_id": flow.chatbot_id,
+                "window_expires_at": window_expires,
+            })
 
-            # Log warning for experimental nodes
-            warning = NodeAvailability.get_node_warning(node.node_type, connection_type)
-            if warning:
-                logger.warning(f"⚠️ {warning}")
+        context_vars = dict(conversation.context_variables or {})
+        if conversation.active_flow_id:
+            # O evento substitui o fluxo em andamento
+            for key in (SUBFLOW_STACK_KEY, LOOP_STATE_KEY, FLOW_TIMEOUT_FIRED_KEY):
+                context_vars.pop(key, None)
+            await FlowWaitRepository(self.db).cancel_pending(conversation.id)
+        context_vars.update(variables)
+        update_data = {
+            "active_chatbot_id": flow.chatbot_id,
+            "is_bot_active": True,
+            "context_variables": context_vars,
+        }
 
-        # Extrair conteúdo baseado no tipo do node
-        node_data = node.data or {}
+        if template_id:
+            template = await self.db.get(WhatsAppTemplate, template_id)
+            if not template or template.status != "APPROVED":
+                raise ValueError("Template ausente ou não aprovado")
 
-        # CONDITION NODE: Avaliar condições e decidir próximo node
-        if node.node_type == "condition":
-            logger.info(f"🔀 Avaliando condições do Condition Node")
-            result = await self._evaluate_conditions(conversation, node_data)
-            # Avançar passando o resultado da condição (true/false)
-            await self._advance_to_next_node(conversation, node, flow, incoming_message, condition_result=result)
-            return
+            start_node = await NodeRepository(self.db).get_start_node(
+                flow.id, trigger.organization_id, flow.published_version_id
+            )
+            if not start_node:
+                logger.warning(f"Nenhum nó inicial encontrado para o fluxo {flow.id}")
+                return "skipped"
 
-        # HANDOFF NODE: Transferir para agente humano
-        if node.node_type == "handoff":
-            logger.info(f"👤 Transferindo conversa para agente humano")
-            await self._execute_handoff(conversation, node_data)
-            return
+            payload = variables.get(EVENT_VARIABLE) or {}
+            parameters = [
+                {"type": "text", "text": str(payload_value(payload, path) or "")}
+                for path in trigger.template_variables or []
+            ]
+            await self.send_message(
+                conversation.id,
+                trigger.organization_id,
+                "template",
+                {
+                    "name": template.name,
+                    "language": template.language,
+                    "components": [{"type": "body", "parameters": parameters}] if parameters else None,
+                },
+            )
 
-        # DELAY NODE: Aguardar X segundos antes de avançar
-        if node.node_type == "delay":
-            logger.info(f"⏰ Executando Delay Node")
-            await self._execute_delay(conversation, node, flow, incoming_message, node_data)
-            return
+            # O start node roda quando o contato responder (ver _restart_at_checkpoint)
+            context_vars[FLOW_RESTART_KEY] = True
+            update_data.update({"active_flow_id": flow.id, "current_node_id": start_node.id})
+            await self._set_session(conversation, update_data)
+            logger.info(
+                f"📨 Evento {trigger.event_type}: template {template.name} enviado, "
+                f"fluxo {flow.name} aguardando resposta"
+            )
+            return "opened"
 
-        # JUMP NODE: Pular para outro node/flow
-        if node.node_type == "jump":
-            logger.info(f"🔀 Executando Jump Node")
-            await self._execute_jump(conversation, node_data, incoming_message)
-            return
+        await self._set_session(conversation, update_data)
+        for field, value in update_data.items():
+            setattr(conversation, field, value)
 
-        # ACTION NODE: Executar ações (webhook, salvar contato, atualizar variável)
-        if node.node_type == "action":
-            logger.info(f"⚡ Executando Action Node")
-            await self._execute_action(conversation, node, flow, incoming_message, node_data)
-            return
+        logger.info(f"⚡ Evento {trigger.event_type} iniciando fluxo {flow.name} na conversa {conversation.id}")
+        await self._start_flow(conversation, flow, None)
+        return "started"
 
-        # API CALL NODE: Fazer chamada HTTP e salvar resposta
-        if node.node_type == "api_call":
-            logger.info(f"🌐 Executando API Call Node")
-            await self._execute_api_call(conversation, node, flow, incoming_message, node_data)
-            return
+    async def _start_flow(self, conversation, flow, new_message):
+        """
+        Inicia um fluxo a partir do start node, seguindo a primeira edge.
+        """
+        from app.services.chatbot_service import ChatbotService
 
-        # AI PROMPT NODE: Interagir com modelos de IA
-        if node.node_type == "ai_prompt":
-            logger.info(f"🤖 Executando AI Prompt Node")
-            await self._execute_ai_prompt(conversation, node, flow, incoming_message, node_data)
-            return
+        chatbot_service = ChatbotService(self.db)
+        organization_id = conversation.organization_id
 
-        # DATABASE QUERY NODE: Consultar bancos de dados
-        if node.node_type == "database_query":
-            logger.info(f"💾 Executando Database Query Node")
-            await self._execute_database_query(conversation, node, flow, incoming_message, node_data)
-            return
+        # Versão publicada do flow (flows nunca publicados rodam o rascunho)
+        version_id = flow.published_version_id
 
-        # SCRIPT NODE: Executar código Python customizado
-        if node.node_type == "script":
-            logger.info(f"📜 Executando Script Node")
-            await self._execute_script(conversation, node, flow, incoming_message, node_data)
+        # Buscar start node
+        start_node = await chatbot_service.node_repo.get_start_node(flow.id, organization_id, version_id)
+        if not start_node:
+            logger.warning(f"Nenhum nó inicial encontrado para o fluxo {flow.id}")
             return
 
-        # SET VARIABLE NODE: Definir/atualizar variáveis do contexto
-        if node.node_type == "set_variable":
-            logger.info(f"🔧 Executando Set Variable Node")
-            await self._execute_set_variable(conversation, node, flow, incoming_message, node_data)
-            return
+        # Encontrar primeiro node com conteúdo seguindo edge
+        edges = await self._flow_edges(flow, version_id)
+        start_node_canvas_id = start_node.node_id
+        next_node_canvas_id = None
 
-        # RANDOM NODE: Seleção aleatória de caminhos (A/B Testing)
-        if node.node_type == "random":
-            logger.info(f"🎲 Executando Random/A-B Testing Node")
-            await self._execute_random(conversation, node, flow, incoming_message, node_data)
-            return
+        for edge in edges:
+            if edge.get("source") == start_node_canvas_id:
+                next_node_canvas_id = edge.get("target")
+                break
 
-        # DATE/TIME NODE: Manipulação de datas e horários
-        if node.node_type == "datetime":
-            logger.info(f"📅 Executando Date/Time Node")
-            await self._execute_datetime(conversation, node, flow, incoming_message, node_data)
+        if not next_node_canvas_id:
+            logger.warning(f"Nenhuma edge encontrada saindo do start node")
             return
 
-        # ANALYTICS NODE: Tracking de métricas e eventos customizados
-        if node.node_type == "analytics":
-            logger.info(f"📊 Executando Analytics Node")
-            await self._execute_analytics(conversation, node, flow, incoming_message, node_data)
-            return
+        # Buscar próximo node
+        first_node = await self._find_node(flow.id, next_node_canvas_id, organization_id, version_id)
 
-        # WHATSAPP TEMPLATE NODE: Enviar template oficial do WhatsApp
-        if node.node_type == "whatsapp_template":
-            logger.info(f"📋 Executando WhatsApp Template Node")
-            await self._execute_whatsapp_template(conversation, node, flow, incoming_message, node_data)
+        if not first_node:
+            logger.warning(f"Node {next_node_canvas_id} não encontrado no banco")
             return
 
-        # INTERACTIVE BUTTONS NODE: Enviar botões interativos
-        if node.node_type == "interactive_buttons":
-            logger.info(f"🔘 Executando Interactive Buttons Node")
-            await self._execute_interactive_buttons(conversation, node, flow, incoming_message, node_data)
+        # 🛡️ PROTEÇÃO: Limite de fluxos iniciados por contato (hora/dia)
+        guard = await self._flow_guard_settings(conversation)
+        if not await self._flow_guard_allows(conversation, guard, GUARD_FLOW_STARTS):
+            await self._trip_flow_guard(conversation, guard, "limite de fluxos iniciados por contato")
             return
 
-        # INTERACTIVE LIST NODE: Enviar lista/menu interativo
-        if node.node_type == "interactive_list":
-            logger.info(f"📝 Executando Interactive List Node")
-            await self._execute_interactive_list(conversation, node, flow, incoming_message, node_data)
-            return
+        if version_id:
+            await FlowVersionRepository(self.db).increment_stats(version_id, sessions_started=1)
 
-        content_text = None
+        # Nova sessão do flow (a anterior, se houver, termina aqui)
+        now = datetime.now(timezone.utc)
+        context_vars = dict(conversation.context_variables or {})
+        previous_session = context_vars.get(FLOW_SESSION_KEY)
+        if previous_session:
+            await FlowNodeVisitRepository(self.db).close_open(UUID(previous_session), now)
+        context_vars[FLOW_SESSION_KEY] = str(uuid4())
+        context_vars.pop(NAVIGATION_HISTORY_KEY, None)
+
+        # Configurar flow e node inicial
+        update_data = {
+            "active_flow_id": flow.id,
+            "current_node_id": first_node.id,
+            "context_variables": context_vars,
+        }
 
-        if node.node_type == "question":
-            content_text = node_data.get("questionText", "")
-        elif node.node_type == "message":
-            # Verificar se é mensagem de mídia
-            media_type = node_data.get("mediaType")
-            if media_type in ["image", "video", "document", "audio"]:
-                logger.info(f"📎 Enviando mensagem de mídia: {media_type}")
-                await self._send_media_message(conversation, node_data, media_type)
-                # Avançar para próximo node após enviar mídia
-                await self._advance_to_next_node(conversation, node, flow, incoming_message)
-                return
-            else:
-                content_text = node_data.get("messageText", "")
-        elif node.node_type == "end":
-            content_text = node_data.get("farewellMessage", "")
-        else:
-            logger.warning(f"Node type {node.node_type} não suportado para envio de mensagem")
-            return
+        # Prazos da sessão; jumps e sub-flows mantêm a expiração em andamento e
+        # o flow de fallback de timeout usa o prazo definido por _run_flow_timeout
+        if not context_vars.get(FLOW_TIMEOUT_FIRED_KEY):
+            timeouts = await self._flow_timeout_settings(conversation)
+            current_expiry = conversation.flow_expires_at
+            if current_expiry and current_expiry.tzinfo is None:
+                current_expiry = current_expiry.replace(tzinfo=timezone.utc)
+            update_data["flow_timeout_at"] = timeouts.timeout_at(now)
+            update_data["flow_expires_at"] = (
+                current_expiry if current_expiry and current_expiry > now else timeouts.expires_at(now)
+            )
 
-        if not content_text:
-            logger.warning(f"Node {node.node_id} não tem conteúdo para enviar")
-            # Avançar para próximo node mesmo sem conteúdo
-            await self._advance_to_next_node(conversation, node, flow, incoming_message)
-            return
+        await self._set_session(conversation, update_data)
+        await self.db.commit()
+        await self._record_node_visit(conversation, start_node)
+        if new_message is not None:
+            await self._trace_input(conversation, start_node, new_message)
 
-        # Substituir variáveis no texto usando context_variables
-        context_vars = conversation.context_variables or {}
-        final_text = content_text
+        logger.info(f"🚀 Iniciando fluxo {flow.name} no node {first_node.node_type}")
 
-        # Encontrar todas as variáveis no formato {{variable_name}}
-        variables = re.findall(r'\{\{(\w+)\}\}', content_text)
-        for var_name in variables:
-            if var_name in context_vars:
-                var_value = context_vars[var_name]
-                final_text = final_text.replace(f"{{{{{var_name}}}}}", str(var_value))
-            else:
-                logger.warning(f"Variável {{{{var_name}}}} não encontrada em context_variables")
-                # Manter placeholder original se variável não existir
+        # Executar primeiro node
+        await self._execute_node(conversation, first_node, flow, new_message)
 
-        logger.info(f"📤 Enviando mensagem: {final_text[:50]}...")
+    async def _localized_node_data(self, conversation, node, flow) -> Dict[str, Any]:
+        """
+        Dados do node no idioma do contato (translations do node).
 
-        # Enviar mensagem via WhatsApp
-        whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+        O idioma vem da variável "locale" do contexto ou do idioma do contato
+        (detectado nas primeiras mensagens); sem tradução para ele, segue a
+        cadeia até o default_locale do flow e o conteúdo original do node.
+        """
+        from app.models.contact import Contact
 
-        if whatsapp_number.connection_type == "official":
-            # Meta Cloud API
-            from app.integrations.meta_api import MetaCloudAPI
+        node_data = node.data or {}
+        if not node_data.get(TRANSLATIONS_KEY):
+            return node_data
 
-            meta_api = MetaCloudAPI(
-                phone_number_id=whatsapp_number.phone_number_id,
-                access_token=whatsapp_number.access_token
-            )
+        locale = (conversation.context_variables or {}).get(LOCALE_VARIABLE)
+        if not locale:
+            locale = await self.db.scalar(select(Contact.language).where(Contact.id == conversation.contact_id))
+        return localize_node_data(node_data, locale, flow.default_locale if flow else None)
 
-            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+    async def _record_node_visit(self, conversation, node):
+        """
+        Registra a entrada no node na sessão do flow (funil por node).
 
-            # 🛡️ PROTEÇÃO: Retry automático de envio (até 3 tentativas)
-            max_retries = 3
-            retry_count = 0
-            last_error = None
+        Conversas sem sessão (flows iniciados antes do funil existir) não são
+        registradas; falhas aqui nunca interrompem o flow.
+        """
+        session_id = (conversation.context_variables or {}).get(FLOW_SESSION_KEY)
+        if not session_id:
+            return
+        try:
+            await FlowNodeVisitRepository(self.db).enter(
+                UUID(session_id), conversation.organization_id, conversation.id, node, datetime.now(timezone.utc)
+            )
+        except Exception as e:
+            logger.warning(f"⚠️ Não foi possível registrar a visita ao node {node.node_id}: {e}")
+            await self.db.rollback()
 
-            while retry_count < max_retries:
-                try:
-                    response = await meta_api.send_text_message(
-                        to=contact_whatsapp_id,
-                        text=final_text
-                    )
+    async def _flow_edges(self, flow, flow_version_id=None) -> List[Dict[str, Any]]:
+        """
+        Edges da versão do flow em execução.
 
-                    whatsapp_message_id = response.get("messages", [{}])[0].get("id")
-                    logger.info(f"✅ Mensagem enviada via Meta API. ID: {whatsapp_message_id}")
-                    break  # Sucesso - sair do loop
+        Conversas seguem a versão do node em que estão (flow_version_id do
+        node); None é o rascunho, usado por flows nunca publicados.
+        """
+        canvas_data = flow.canvas_data
+        if flow_version_id:
+            version = await FlowVersionRepository(self.db).get(flow_version_id)
+            if version:
+                canvas_data = version.canvas_data
+        return (canvas_data or {}).get("edges", [])
+
+    async def _find_node(self, flow_id, node_canvas_id, organization_id, flow_version_id=None):
+        """Node do canvas (ID do React Flow) na versão indicada (None = rascunho)"""
+        from app.models.chatbot import Node
 
-                except Exception as e:
-                    retry_count += 1
-                    last_error = e
-                    logger.warning(f"⚠️ Erro ao enviar mensagem (tentativa {retry_count}/{max_retries}): {e}")
+        result = await self.db.execute(select(Node).where(
+            Node.flow_id == flow_id,
+            Node.node_id == node_canvas_id,
+            Node.organization_id == organization_id,
+            Node.flow_version_id == flow_version_id,
+        ))
+        return result.scalar_one_or_none()
 
-                    if retry_count < max_retries:
-                        # Aguardar antes de tentar novamente (exponential backoff)
-                        import asyncio
-                        wait_time = 2 ** retry_count  # 2s, 4s, 8s
-                        logger.info(f"⏳ Aguardando {wait_time}s antes de tentar novamente...")
-                        await asyncio.sleep(wait_time)
-                    else:
-                        # Máximo de tentativas atingido
-                        logger.error(f"❌ Falha após {max_retries} tentativas: {last_error}")
-                        return
+    async def _trigger_campaign_reply_flow(self, conversation, contact, new_message) -> bool:
+        """
+        Resposta a uma campanha: inicia o fluxo do gatilho campaign.reply_received.
 
-        elif whatsapp_number.connection_type == "qrcode":
-            # Evolution API
-            from app.integrations.evolution_api import EvolutionAPIClient
+        A campanha vem da mensagem citada pelo contato ou, sem citação, da
+        última mensagem enviada na conversa.
 
-            evolution = EvolutionAPIClient(
-                api_url=whatsapp_number.evolution_api_url,
-                api_key=whatsapp_number.evolution_api_key
-            )
+        Returns:
+            True se um fluxo de evento foi iniciado
+        """
+        from sqlalchemy import desc
 
-            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+        from app.models.campaign import Campaign
+        from app.services.flow_event_service import FlowEventService
 
-            # 🛡️ PROTEÇÃO: Retry automático de envio (até 3 tentativas)
-            max_retries = 3
-            retry_count = 0
-            last_error = None
+        if new_message.reply_to_message_id:
+            campaign_message = await self.db.get(Message, new_message.reply_to_message_id)
+        else:
+            campaign_message = (await self.db.execute(
+                select(Message)
+                .where(
+                    Message.conversation_id == conversation.id,
+                    Message.direction == "outbound",
+                    Message.id != new_message.id,
+                )
+                .order_by(desc(Message.created_at))
+                .limit(1)
+            )).scalar_one_or_none()
 
-            while retry_count < max_retries:
-                try:
-                    response = await evolution.send_text_message(
-                        instance_name=whatsapp_number.evolution_instance_name,
-                        to=contact_whatsapp_id,
-                        text=final_text
-                    )
-                    logger.info(f"✅ Mensagem enviada via Evolution API")
-                    break  # Sucesso - sair do loop
+        campaign_id = ((campaign_message.extra_data or {}) if campaign_message else {}).get("campaign_id")
+        if not campaign_id:
+            return False
 
-                except Exception as e:
-                    retry_count += 1
-                    last_error = e
-                    logger.warning(f"⚠️ Erro ao enviar mensagem (tentativa {retry_count}/{max_retries}): {e}")
+        campaign = await self.db.get(Campaign, UUID(str(campaign_id)))
+        if campaign:
+            # Respostas e cliques da campanha (métricas do teste A/B)
+            from sqlalchemy.orm.attributes import flag_modified
 
-                    if retry_count < max_retries:
-                        # Aguardar antes de tentar novamente (exponential backoff)
-                        import asyncio
-                        wait_time = 2 ** retry_count  # 2s, 4s, 8s
-                        logger.info(f"⏳ Aguardando {wait_time}s antes de tentar novamente...")
-                        await asyncio.sleep(wait_time)
-                    else:
-                        # Máximo de tentativas atingido
-                        logger.error(f"❌ Falha após {max_retries} tentativas: {last_error}")
-                        return
+            from app.utils.campaign_ab_test import record_engagement
 
-        # Salvar mensagem no banco
-        from app.repositories.conversation import MessageRepository
-        from datetime import datetime
+            campaign.replies_count = (campaign.replies_count or 0) + 1
+            entry = (campaign.message_statuses or {}).get(str(contact.id))
+            if entry is not None:
+                if record_engagement(entry, new_message.message_type, datetime.utcnow()):
+                    campaign.unique_replies_count = (campaign.unique_replies_count or 0) + 1
+                flag_modified(campaign, "message_statuses")
+            await self.db.commit()
 
-        message_repo = MessageRepository(self.db)
-        message_data = {
-            "organization_id": conversation.organization_id,
-            "conversation_id": conversation.id,
-            "whatsapp_number_id": whatsapp_number.id,
-            "direction": "outbound",
-            "sender_type": "bot",
-            "message_type": "text",
-            "content": {"text": final_text},
-            "status": "sent",
-            "sent_at": datetime.utcnow(),
+        payload = {
+            "campaign_id": str(campaign_id),
+            "campaign_name": campaign.name if campaign else None,
+            "text": (new_message.content or {}).get("text"),
         }
-        await message_repo.create(message_data)
-        await self.db.commit()
-
-        # Se for node de pergunta, aguardar resposta do usuário
-        if node.node_type == "question":
-            logger.info(f"⏸️ Aguardando resposta do usuário para question node {node.node_id}")
-            # Não avançar - esperar próxima mensagem do usuário
-            return
-
-        # Para message e end nodes, avançar automaticamente
-        await self._advance_to_next_node(conversation, node, flow, incoming_message)
+        started = await FlowEventService(self.db).run_reply_event(
+            conversation, contact, EVENT_CAMPAIGN_REPLY, payload
+        )
+        if started:
+            logger.info(f"📣 Resposta à campanha {campaign_id}: fluxo de evento iniciado")
+        return started
 
-    async def _process_user_response_and_advance(self, conversation, current_node, flow, user_message):
+    async def _trigger_event_flow(self, conversation, event_type, variables, new_message=None):
         """
-        Processa resposta do usuário para um question node e avança para próximo node.
-        Inclui validação de responseType e sistema de retry com maxAttempts.
+        Expõe dados de um evento (order, payment, request_welcome) ao fluxo e, se o chatbot
+        tiver um fluxo mapeado em settings["event_flows"][event_type], inicia esse fluxo.
 
-        Args:
-            conversation: Instância da conversa
-            current_node: Node atual (deve ser tipo "question")
-            flow: Flow ativo
-            user_message: Mensagem do usuário
+        Returns:
+            True se um fluxo de evento foi iniciado
         """
-        from app.repositories.conversation import ConversationRepository
-        from datetime import datetime, timedelta
-
-        logger.info(f"💬 Processando resposta do usuário para node {current_node.node_id}")
+        from app.services.chatbot_service import ChatbotService
 
-        # 🛡️ PROTEÇÃO: Timeout de resposta (1 hora)
-        context_vars = conversation.context_variables or {}
-        timeout_key = f"_question_timestamp_{current_node.node_id}"
-        question_timestamp = context_vars.get(timeout_key)
+        event_key = event_type.value if hasattr(event_type, "value") else str(event_type)
 
-        if not question_timestamp:
-            # Primeira mensagem deste question node - salvar timestamp
-            context_vars[timeout_key] = datetime.utcnow().isoformat()
+        # Disponibilizar variáveis do evento (ex: {{order}}, {{payment}})
+        context_vars = dict(conversation.context_variables or {})
+        context_vars[event_key] = variables
+        await self._set_session(conversation, {"context_variables": context_vars})
+        conversation.context_variables = context_vars
 
-            conv_repo = ConversationRepository(self.db)
-            await conv_repo.update(conversation.id, {
-                "context_variables": context_vars
-            })
-            await self.db.commit()
-        else:
-            # Verificar se passou mais de 1 hora
-            question_time = datetime.fromisoformat(question_timestamp)
-            elapsed = datetime.utcnow() - question_time
+        if not conversation.active_chatbot_id:
+            return False
 
-            if elapsed > timedelta(hours=1):
-                logger.warning(f"⏰ Timeout de resposta! Passou {elapsed.total_seconds()//60:.0f} minutos")
+        chatbot_service = ChatbotService(self.db)
+        chatbot = await chatbot_service.chatbot_repo.get(conversation.active_chatbot_id)
+        event_flows = ((chatbot.settings or {}).get("event_flows") or {}) if chatbot else {}
+        flow_id = event_flows.get(event_key)
 
-                # Enviar mensagem de timeout
-                timeout_msg = (
-                    "O tempo para resposta expirou. "
-                    "Vou encaminhar você para um agente humano."
-                )
-                await self._send_error_message(conversation, timeout_msg)
+        if not flow_id:
+            return False
 
-                # Limpar timestamp
-                del context_vars[timeout_key]
+        flow = await chatbot_service.flow_repo.get(UUID(str(flow_id)))
+        if not flow or flow.organization_id != conversation.organization_id:
+            logger.warning(f"Fluxo de evento '{event_key}' não encontrado: {flow_id}")
+            return False
 
-                conv_repo = ConversationRepository(self.db)
-                await conv_repo.update(conversation.id, {
-                    "context_variables": context_vars
-                })
-                await self.db.commit()
+        logger.info(f"🛒 Evento '{event_key}' iniciando fluxo {flow.name}")
+        await self._start_flow(conversation, flow, new_message)
+        return True
 
-                # Transferir para agente humano
-                handoff_data = {
-                    "transferMessage": "Transferência automática devido a timeout de resposta.",
-                    "priority": "medium",
-                    "sendTransferMessage": False  # Já enviamos mensagem acima
-                }
-                await self._execute_handoff(conversation, handoff_data)
-                return
+    async def _execute_node(self, conversation, node, flow, incoming_message, resume: bool = False):
+        """
+        Executa um node do fluxo com checkpoint de execução.
 
-        # Extrair texto da resposta do usuário
-        user_text = user_message.content.get("text", "").strip()
+        Antes de rodar o node grava "executando node X" na conversa; as
+        mensagens enviadas pelo node passam pelo checkpoint. Se o processo
+        cair no meio do node, a task resume_interrupted_flows o executa de
+        novo sem repetir os envios já feitos (ver app.utils.flow_checkpoint).
 
-        if not user_text:
-            logger.warning("Mensagem do usuário sem texto")
+        Args:
+            conversation: Instância da conversa
+            node: Node a ser executado
+            flow: Flow ativo
+            incoming_message: Mensagem que originou a execução
+            resume: Retomada de uma execução interrompida do node
+        """
+        checkpoint = begin_checkpoint(
+            conversation.node_checkpoint,
+            node.node_id,
+            str(incoming_message.id) if getattr(incoming_message, "id", None) else None,
+            datetime.now(timezone.utc),
+            resume=resume,
+        )
+        if not await self._save_checkpoint(conversation, checkpoint):
             return
+        await self._trace(conversation, [trace_event(EVENT_NODE_EXECUTED, node, {"resume": True} if resume else None)])
 
-        node_data = current_node.data or {}
-
-        # VALIDAÇÃO: Verificar se resposta é válida baseado no responseType
-        is_valid, error_message = await self._validate_user_response(user_text, node_data)
-
-        if not is_valid:
-            logger.warning(f"❌ Resposta inválida: {user_text} (esperado: {node_data.get('responseType')})")
+        previous_run = self._checkpoint_run
+        self._checkpoint_run = (conversation, node, checkpoint)
+        try:
+            await self._run_node(conversation, node, flow, incoming_message)
+        finally:
+            self._checkpoint_run = previous_run
+        await self._trace_variables(conversation, node)
+
+        # Node parou sem avançar (aguardando resposta, fim do fluxo, handoff): nada a retomar
+        if is_executing(conversation.node_checkpoint, checkpoint["run_id"]):
+            await self._save_checkpoint(
+                conversation,
+                complete_checkpoint(checkpoint, node.node_id, {"next_node_id": None}, datetime.now(timezone.utc)),
+            )
 
-            # Sistema de retry: verificar número de tentativas
-            context_vars = conversation.context_variables or {}
-            attempt_key = f"_attempts_{current_node.node_id}"
-            attempts = context_vars.get(attempt_key, 0) + 1
+    async def _save_checkpoint(self, conversation, checkpoint) -> bool:
+        """
+        Grava o checkpoint de execução na conversa.
 
-            validation = node_data.get("validation", {})
-            max_attempts = validation.get("maxAttempts", 3)
+        node_checkpoint_at fica preenchido enquanto o node executa ou o
+        próximo node está para executar; a task de retomada usa essa data.
 
-            logger.info(f"  Tentativa {attempts}/{max_attempts}")
+        Returns:
+            False se o lock da sessão passou para outro processo (nada gravado)
+        """
+        output = checkpoint.get("output") or {}
+        pending = is_executing(checkpoint) or bool(output.get("next_node_id"))
+        checkpoint_at = datetime.now(timezone.utc) if pending else None
+        saved = await self._update_session(conversation, {
+            "node_checkpoint": dict(checkpoint),
+            "node_checkpoint_at": checkpoint_at,
+        })
+        if saved:
+            conversation.node_checkpoint = dict(checkpoint)
+            conversation.node_checkpoint_at = checkpoint_at
+        return saved
 
-            if attempts >= max_attempts:
-                # Máximo de tentativas atingido - enviar mensagem final e avançar
-                logger.warning(f"⚠️ Número máximo de tentativas ({max_attempts}) atingido")
+    def _guarded(self, client):
+        """
+        Cliente WhatsApp cujos envios passam pelo checkpoint do node em execução.
 
-                final_error_message = (
-                    "Número máximo de tentativas excedido. "
-                    "Continuando com o atendimento..."
-                )
+        Fora da execução de um node o cliente é retornado como está.
+        """
+        if self._checkpoint_run is None:
+            return client
 
-                await self._send_error_message(conversation, final_error_message)
+        conversation, node, checkpoint = self._checkpoint_run
 
-                # Limpar contador de tentativas
-                del context_vars[attempt_key]
+        async def persist(updated):
+            # Só o checkpoint desta execução (um node seguinte pode já ter começado)
+            if (conversation.node_checkpoint or {}).get("run_id") != updated.get("run_id"):
+                return
+            # Sessão assumida por outro processo: não enviar mais nada por este node
+            if not await self._update_session(conversation, {"node_checkpoint": dict(updated)}):
+                raise FlowSessionLockError(f"Flow session of conversation {conversation.id} was taken over")
+            conversation.node_checkpoint = dict(updated)
 
-                conv_repo = ConversationRepository(self.db)
-                await conv_repo.update(conversation.id, {
-                    "context_variables": context_vars
-                })
-                await self.db.commit()
+        async def on_sent(method, args, kwargs):
+            await self._trace(conversation, [trace_event(EVENT_MESSAGE_SENT, node, sent_data(method, args, kwargs))])
 
-                # Avançar para próximo node (sem salvar resposta inválida)
-                await self._advance_to_next_node(conversation, current_node, flow, user_message)
+        return CheckpointedSender(client, checkpoint, persist, on_sent)
 
-            else:
-                # Incrementar contador e enviar mensagem de erro
-                context_vars[attempt_key] = attempts
+    async def _trace(self, conversation, events: List[Dict[str, Any]]):
+        """
+        Adiciona eventos ao trace da sessão do fluxo (ver app.utils.flow_trace).
 
-                conv_repo = ConversationRepository(self.db)
-                await conv_repo.update(conversation.id, {
-                    "context_variables": context_vars
-                })
-                await self.db.commit()
+        Conversas sem sessão não são registradas; falhas aqui nunca
+        interrompem o flow.
+        """
+        session_id = (conversation.context_variables or {}).get(FLOW_SESSION_KEY)
+        if not session_id or not events:
+            return
+        try:
+            await FlowTraceRepository(self.db).append(
+                UUID(session_id),
+                conversation.organization_id,
+                conversation.id,
+                conversation.active_flow_id,
+                events,
+                datetime.now(timezone.utc),
+            )
+        except Exception as e:
+            logger.warning(f"⚠️ Não foi possível registrar o trace da sessão {session_id}: {e}")
+            await self.db.rollback()
 
-                # Enviar mensagem de erro personalizada
-                await self._send_error_message(conversation, error_message)
+    async def _trace_input(self, conversation, node, message):
+        """Registra a mensagem do contato processada pela sessão do fluxo"""
+        self._snapshot_variables(conversation)
+        await self._trace(conversation, [trace_event(EVENT_INPUT_RECEIVED, node, input_data(message))])
 
-                # NÃO avançar - aguardar nova resposta do usuário
+    async def _trace_variables(self, conversation, node):
+        """
+        Registra no trace as variáveis alteradas desde o último registro.
 
+        As alterações são atribuídas ao node informado (o node que acabou de
+        executar ou do qual o fluxo está avançando).
+        """
+        snapshots = self._trace_snapshots or {}
+        previous = snapshots.get(str(conversation.id))
+        current = self._snapshot_variables(conversation)
+        if previous is None:
             return
+        await self._trace(conversation, [
+            trace_event(EVENT_VARIABLE_SET, node, change)
+            for change in variable_changes(previous, current)
+        ])
+
+    def _snapshot_variables(self, conversation) -> Dict[str, Any]:
+        """Guarda uma cópia das variáveis de contexto da conversa para o próximo _trace_variables"""
+        if self._trace_snapshots is None:
+            self._trace_snapshots = {}
+        snapshot = copy.deepcopy(dict(conversation.context_variables or {}))
+        self._trace_snapshots[str(conversation.id)] = snapshot
+        return snapshot
+
+    @asynccontextmanager
+    async def _flow_session(self, conversation):
+        """
+        Executa a sessão do fluxo da conversa com o lock distribuído.
 
-        # Resposta válida - salvar e avançar
-        logger.info(f"✅ Resposta válida: {user_text}")
+        Com várias réplicas da API, dois webhooks do mesmo contato esperam um
+        pelo outro em vez de executar o fluxo ao mesmo tempo. Após obter o
+        lock, o estado da sessão é relido do banco (o outro processo pode tê-lo
+        alterado) e o fencing token é gravado na conversa: gravações de quem
+        perdeu o lock por timeout são descartadas (ver app.core.flow_session).
 
-        # Determinar nome da variável para salvar
-        variable_name = node_data.get("outputVariable")
+        Raises:
+            FlowSessionLockError: Se a sessão continuar com outro processo
+        """
+        from app.repositories.conversation import ConversationRepository
 
-        if not variable_name:
-            # Fallback: gerar nome baseado no node_id
-            node_canvas_id = current_node.node_id
-            variable_suffix = node_canvas_id.replace("node-", "")
-            variable_name = f"user_response_{variable_suffix}"
-            logger.warning(f"Node sem outputVariable, usando fallback: {variable_name}")
+        if self._session_lock is not None and self._session_lock.conversation_id == str(conversation.id):
+            # Lock já mantido por esta execução (ex: fallback iniciado dentro da sessão)
+            yield self._session_lock
+            return
 
-        logger.info(f"💾 Salvando resposta '{user_text}' na variável '{variable_name}'")
+        async with get_flow_session_manager().lock(conversation.id) as lock:
+            await self.db.refresh(conversation, attribute_names=list(FLOW_SESSION_COLUMNS))
+            if not await ConversationRepository(self.db).stamp_session_fence(conversation.id, lock.token):
+                await self.db.rollback()
+                raise FlowSessionLockError(f"Flow session of conversation {conversation.id} was taken over")
+            await self.db.commit()
 
-        # Atualizar context_variables
-        context_vars = conversation.context_variables or {}
-        context_vars[variable_name] = user_text
+            previous_lock = self._session_lock
+            self._session_lock = lock
+            try:
+                yield lock
+            finally:
+                self._session_lock = previous_lock
+
+    async def _flow_lane(self, contact, organization_id) -> str:
+        """Fila de execução do fluxo do contato: priority (VIP ou plano pago) ou bulk"""
+        from app.models.contact import Tag, contact_tags
+        from app.models.organization import Organization
+
+        tags = (await self.db.execute(
+            select(Tag.name)
+            .join(contact_tags, contact_tags.c.tag_id == Tag.id)
+            .where(contact_tags.c.contact_id == contact.id)
+        )).scalars().all()
+        organization = (await self.db.execute(
+            select(Organization.plan_type, Organization.plan_expires_at).where(Organization.id == organization_id)
+        )).first()
+
+        plan_type, plan_active = None, False
+        if organization:
+            plan_type, expires_at = organization
+            if expires_at and expires_at.tzinfo is None:
+                expires_at = expires_at.replace(tzinfo=timezone.utc)
+            plan_active = not expires_at or expires_at > datetime.now(timezone.utc)
+        return flow_lane(bool(contact.is_vip), tags, plan_type, plan_active)
+
+    async def _update_session(self, conversation, data: Dict[str, Any]) -> bool:
+        """
+        Grava colunas da sessão do fluxo (nó atual, variáveis, checkpoint).
 
-        # Limpar contador de tentativas (se existir)
-        attempt_key = f"_attempts_{current_node.node_id}"
-        if attempt_key in context_vars:
-            del context_vars[attempt_key]
+        Com o lock da sessão desta conversa, só grava se o fencing token ainda
+        for o deste processo; senão o lock é marcado como perdido.
+
+        Returns:
+            False se o lock passou para outro processo (nada gravado)
+        """
+        from app.repositories.conversation import ConversationRepository
 
         conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
-            "context_variables": context_vars
-        })
+        lock = self._session_lock
+        if lock is not None and lock.conversation_id == str(conversation.id):
+            if not lock.lost and not await conv_repo.update_fenced(conversation.id, lock.token, data):
+                # Outro processo assumiu a sessão: os próximos passos do fluxo são interrompidos
+                lock.lost = True
+            return not lock.lost
+
+        await conv_repo.update(conversation.id, data)
         await self.db.commit()
+        return True
 
-        # Avançar para próximo node
-        await self._advance_to_next_node(conversation, current_node, flow, user_message)
-
-    async def _advance_to_next_node(
-        self,
-        conversation,
-        current_node,
-        flow,
-        incoming_message,
-        condition_result: Optional[bool] = None
-    ):
+    async def _set_session(self, conversation, data: Dict[str, Any]) -> None:
         """
-        Avança para o próximo node seguindo as edges do canvas_data.
+        Grava colunas da conversa durante a sessão do fluxo (ver _update_session).
+
+        Raises:
+            FlowSessionLockError: Se o lock passou para outro processo; o passo
+                do fluxo é interrompido sem gravar nada
+        """
+        if not await self._update_session(conversation, data):
+            raise FlowSessionLockError(f"Flow session of conversation {conversation.id} was taken over")
+
+    def _check_session(self, conversation) -> None:
+        """
+        Interrompe o fluxo se o lock da sessão desta conversa foi perdido.
+
+        Raises:
+            FlowSessionLockError: Se a renovação do lock ou uma gravação da sessão falhou
+        """
+        lock = self._session_lock
+        if lock is not None and lock.conversation_id == str(conversation.id) and lock.lost:
+            raise FlowSessionLockError(f"Flow session of conversation {conversation.id} was lost")
+
+    async def _run_node(self, conversation, node, flow, incoming_message):
+        """
+        Executa um node do fluxo e envia mensagem via WhatsApp.
 
         Args:
             conversation: Instância da conversa
-            current_node: Node atual
+            node: Node a ser executado
             flow: Flow ativo
-            incoming_message: Mensagem que originou o avanço
-            condition_result: Resultado de condição (True/False) para Condition Nodes
+            incoming_message: Mensagem que originou a execução
         """
-        from app.repositories.conversation import ConversationRepository
-        from app.models.chatbot import Node
-        from sqlalchemy import select
+        logger.info(f"🎬 Executando node {node.node_type}: {node.label}")
 
-        logger.info(f"➡️ Avançando do node {current_node.node_id}")
+        # Lock perdido: outro processo pode estar executando a sessão
+        self._check_session(conversation)
+        await self._record_node_visit(conversation, node)
 
-        # 🛡️ PROTEÇÃO: Detecção de loops infinitos
-        context_vars = conversation.context_variables or {}
-        path_key = "_execution_path"
-        execution_path = context_vars.get(path_key, [])
+        # Validar compatibilidade do node com o tipo de conexão WhatsApp
+        whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+        if whatsapp_number:
+            connection_type = whatsapp_number.connection_type.value if hasattr(whatsapp_number.connection_type, 'value') else str(whatsapp_number.connection_type)
+            is_available = NodeAvailability.is_node_available(node.node_type, connection_type)
 
-        # Adicionar node atual ao caminho
-        execution_path.append(current_node.node_id)
+            if not is_available:
+                logger.error(
+                    f"❌ Node '{node.node_type}' não está disponível para conexão '{connection_type}'. "
+                    f"Este node requer Meta Cloud API (official)."
+                )
+                # Transferir para agente humano
+                await self._execute_handoff(
+                    conversation,
+                    {
+                        "transferMessage": (
+                            "Desculpe, esta funcionalidade não está disponível "
+                            "no momento. Vou transferir você para um agente humano."
+                        ),
+                        "sendTransferMessage": True,
+                        "priority": "high"
+                    }
+                )
+                return
 
-        # Verificar se node foi visitado mais de 10 vezes (loop infinito)
-        visit_count = execution_path.count(current_node.node_id)
-        if visit_count > 10:
-            logger.error(f"🚫 Loop infinito detectado! Node {current_node.node_id} visitado {visit_count} vezes")
+            # Log warning for experimental nodes
+            warning = NodeAvailability.get_node_warning(node.node_type, connection_type)
+            if warning:
+                logger.warning(f"⚠️ {warning}")
 
-            # Enviar mensagem de erro
-            error_msg = (
-                "Desculpe, detectamos um problema no fluxo de atendimento. "
-                "Um agente humano irá atendê-lo em breve."
-            )
-            await self._send_error_message(conversation, error_msg)
+        # 🛡️ PROTEÇÃO: Limite de mensagens do fluxo por contato (hora/dia)
+        if node.node_type in FLOW_GUARD_MESSAGE_NODE_TYPES:
+            guard = await self._flow_guard_settings(conversation)
+            if not await self._flow_guard_allows(conversation, guard, GUARD_MESSAGES):
+                await self._trip_flow_guard(conversation, guard, "limite de mensagens por contato")
+                return
 
-            # Transferir para agente humano
-            handoff_data = {
-                "transferMessage": "Transferência automática devido a loop infinito no fluxo.",
-                "priority": "high",
-                "sendTransferMessage": False  # Já enviamos mensagem acima
-            }
-            await self._execute_handoff(conversation, handoff_data)
-            return
+        # Extrair conteúdo baseado no tipo do node (no idioma do contato)
+        node_data = await self._localized_node_data(conversation, node, flow)
 
-        # Limitar tamanho do caminho (guardar apenas últimos 50 nodes)
-        if len(execution_path) > 50:
-            execution_path = execution_path[-50:]
+        # Nodes demorados: mostrar "digitando..." enquanto processa
+        if node.node_type in LONG_RUNNING_NODE_TYPES and node_data.get("showTyping", True):
+            await self._send_typing_indicator(conversation, incoming_message, whatsapp_number)
 
-        # Atualizar caminho
-        context_vars[path_key] = execution_path
+        # CONDITION NODE: Avaliar condições e decidir próximo node
+        if node.node_type == "condition":
+            logger.info(f"🔀 Avaliando condições do Condition Node")
+            result = await self._evaluate_conditions(conversation, node_data)
+            # Avançar passando o resultado da condição (true/false)
+            await self._advance_to_next_node(conversation, node, flow, incoming_message, condition_result=result)
+            return
 
-        conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
-            "context_variables": context_vars
-        })
-        await self.db.commit()
+        # HANDOFF NODE: Transferir para agente humano
+        if node.node_type == "handoff":
+            logger.info(f"👤 Transferindo conversa para agente humano")
+            await self._execute_handoff(conversation, node_data, node, flow)
+            return
 
-        # Buscar próximo node nas edges
-        canvas_data = flow.canvas_data or {}
-        edges = canvas_data.get("edges", [])
-        current_node_canvas_id = current_node.node_id
-        next_node_canvas_id = None
+        # DELAY NODE: Aguardar X segundos antes de avançar
+        if node.node_type == "delay":
+            logger.info(f"⏰ Executando Delay Node")
+            await self._execute_delay(conversation, node, flow, incoming_message, node_data)
+            return
 
-        # Se for Condition Node, buscar edge baseado no resultado
-        if condition_result is not None:
-            # condition_result pode ser:
-            # - True/False (para condition node simples com label true/false)
-            # - Número inteiro (índice da condição satisfeita em multi-condition nodes)
-            
-            if isinstance(condition_result, int):
-                # Multi-condition node: usar sourceHandle "condition-{index}"
-                target_handle = f"condition-{condition_result}"
-                logger.info(f"🔀 Buscando edge com sourceHandle '{target_handle}' para Condition Node")
+        # WAIT NODE: Pausar o fluxo por minutos/horas/dias ou até uma data
+        if node.node_type == "wait":
+            logger.info(f"⏸️ Executando Wait Node")
+            await self._execute_wait(conversation, node, flow, incoming_message, node_data)
+            return
 
-                for edge in edges:
-                    if edge.get("source") == current_node_canvas_id:
-                        source_handle = edge.get("sourceHandle", "")
-                        if source_handle == target_handle:
-                            next_node_canvas_id = edge.get("target")
-                            logger.info(f"  ✅ Edge encontrada: {source_handle} → {next_node_canvas_id}")
-                            break
+        # JUMP NODE: Pular para outro node/flow
+        if node.node_type == "jump":
+            logger.info(f"🔀 Executando Jump Node")
+            await self._execute_jump(conversation, node_data, incoming_message, node)
+            return
 
-                if not next_node_canvas_id:
-                    logger.error(
-                        f"❌ Nenhuma edge com sourceHandle '{target_handle}' encontrada "
-                        f"saindo do Condition Node {current_node_canvas_id}"
-                    )
-                    return
-            else:
-                # Boolean condition: usar label true/false
-                target_label = "true" if condition_result else "false"
-                logger.info(f"🔀 Buscando edge com label '{target_label}' para Condition Node")
+        # SUB-FLOW NODE: Chamar outro flow e retornar ao terminar
+        if node.node_type == "subflow":
+            logger.info(f"↪️ Executando Sub-flow Node")
+            await self._execute_subflow(conversation, node, flow, incoming_message, node_data)
+            return
 
-                for edge in edges:
-                    if edge.get("source") == current_node_canvas_id:
-                        edge_label = edge.get("label", "").lower()
-                        # Também aceita "yes"/"no" como sinônimos
-                        if (target_label == "true" and edge_label in ["true", "yes", "sim"]) or \
-                           (target_label == "false" and edge_label in ["false", "no", "não"]):
-                            next_node_canvas_id = edge.get("target")
-                            logger.info(f"  ✅ Edge encontrada: {edge_label} → {next_node_canvas_id}")
-                            break
+        # LOOP NODE: Repetir o corpo para cada item ou até as condições serem atendidas
+        if node.node_type == "loop":
+            logger.info(f"🔁 Executando Loop Node")
+            await self._execute_loop(conversation, node, flow, incoming_message, node_data)
+            return
 
-                if not next_node_canvas_id:
-                    logger.error(
-                        f"❌ Nenhuma edge com label '{target_label}' encontrada "
-                        f"saindo do Condition Node {current_node_canvas_id}"
-                    )
-                    return
+        # ACTION NODE: Executar ações (webhook, salvar contato, atualizar variável)
+        if node.node_type == "action":
+            logger.info(f"⚡ Executando Action Node")
+            await self._execute_action(conversation, node, flow, incoming_message, node_data)
+            return
 
-        else:
-            # Fluxo normal: primeira edge encontrada
-            for edge in edges:
-                if edge.get("source") == current_node_canvas_id:
-                    next_node_canvas_id = edge.get("target")
-                    break
+        # API CALL NODE: Fazer chamada HTTP e salvar resposta
+        if node.node_type == "api_call":
+            logger.info(f"🌐 Executando API Call Node")
+            await self._execute_api_call(conversation, node, flow, incoming_message, node_data)
+            return
 
-        if not next_node_canvas_id:
-            logger.warning(f"⚠️ Nenhuma edge encontrada saindo do node {current_node_canvas_id}")
+        # AI PROMPT NODE: Interagir com modelos de IA
+        if node.node_type == "ai_prompt":
+            logger.info(f"🤖 Executando AI Prompt Node")
+            await self._execute_ai_prompt(conversation, node, flow, incoming_message, node_data)
+            return
 
-            # Se for end node, finalizar fluxo
-            if current_node.node_type == "end":
-                await self._finalize_flow(conversation)
+        # INTENT NODE: Classificar a mensagem com IA e seguir a saída da intenção
+        if node.node_type == "intent":
+            logger.info(f"🧭 Executando Intent Node")
+            await self._execute_intent(conversation, node, flow, incoming_message, node_data)
+            return
 
+        # DATABASE QUERY NODE: Consultar bancos de dados
+        if node.node_type == "database_query":
+            logger.info(f"💾 Executando Database Query Node")
+            await self._execute_database_query(conversation, node, flow, incoming_message, node_data)
             return
 
-        # Buscar próximo node no banco
-        stmt = select(Node).where(
-            Node.flow_id == flow.id,
-            Node.node_id == next_node_canvas_id,
-            Node.organization_id == conversation.organization_id
-        )
-        result = await self.db.execute(stmt)
-        next_node = result.scalar_one_or_none()
+        # SCRIPT NODE: Executar código Python customizado
+        if node.node_type == "script":
+            logger.info(f"📜 Executando Script Node")
+            await self._execute_script(conversation, node, flow, incoming_message, node_data)
+            return
 
-        if not next_node:
-            logger.warning(f"❌ Node {next_node_canvas_id} não encontrado no banco")
+        # SET VARIABLE NODE: Definir/atualizar variáveis do contexto
+        if node.node_type == "set_variable":
+            logger.info(f"🔧 Executando Set Variable Node")
+            await self._execute_set_variable(conversation, node, flow, incoming_message, node_data)
             return
 
-        # Atualizar current_node_id
-        conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
-            "current_node_id": next_node.id
-        })
-        await self.db.commit()
+        # RANDOM NODE: Seleção aleatória de caminhos (A/B Testing)
+        if node.node_type == "random":
+            logger.info(f"🎲 Executando Random/A-B Testing Node")
+            await self._execute_random(conversation, node, flow, incoming_message, node_data)
+            return
 
-        logger.info(f"✅ Avançado para node {next_node.node_type}: {next_node.label}")
+        # SPLIT NODE: Teste A/B com variante estável por contato
+        if node.node_type == SPLIT_NODE_TYPE:
+            logger.info(f"🧪 Executando Split Node")
+            await self._execute_split(conversation, node, flow, incoming_message, node_data)
+            return
 
-        # Se for end node, finalizar após executar
-        if next_node.node_type == "end":
-            await self._execute_node(conversation, next_node, flow, incoming_message)
-            await self._finalize_flow(conversation)
-        else:
-            # Executar próximo node
-            await self._execute_node(conversation, next_node, flow, incoming_message)
+        # BUSINESS HOURS NODE: Expediente e feriados da organização
+        if node.node_type == BUSINESS_HOURS_NODE_TYPE:
+            logger.info(f"🕘 Executando Business Hours Node")
+            await self._execute_business_hours(conversation, node, flow, incoming_message, node_data)
+            return
 
-    async def _finalize_flow(self, conversation):
-        """
-        Finaliza o fluxo do chatbot.
+        # SANDBOX SCRIPT NODE: Snippet do tenant em processo isolado
+        if node.node_type == SANDBOX_SCRIPT_NODE_TYPE:
+            logger.info(f"🧱 Executando Sandbox Script Node")
+            await self._execute_sandbox_script(conversation, node, flow, incoming_message, node_data)
+            return
 
-        Args:
-            conversation: Instância da conversa
-        """
-        from app.repositories.conversation import ConversationRepository
+        # DATE/TIME NODE: Manipulação de datas e horários
+        if node.node_type == "datetime":
+            logger.info(f"📅 Executando Date/Time Node")
+            await self._execute_datetime(conversation, node, flow, incoming_message, node_data)
+            return
 
-        logger.info(f"🏁 Finalizando fluxo para conversa {conversation.id}")
+        # ANALYTICS NODE: Tracking de métricas e eventos customizados
+        if node.node_type == "analytics":
+            logger.info(f"📊 Executando Analytics Node")
+            await self._execute_analytics(conversation, node, flow, incoming_message, node_data)
+            return
 
-        conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
-            "is_bot_active": False,
-            "active_flow_id": None,
-            "current_node_id": None,
-        })
-        await self.db.commit()
+        # WHATSAPP TEMPLATE NODE: Enviar template oficial do WhatsApp
+        if node.node_type == "whatsapp_template":
+            logger.info(f"📋 Executando WhatsApp Template Node")
+            await self._execute_whatsapp_template(conversation, node, flow, incoming_message, node_data)
+            return
 
-        logger.info(f"✅ Fluxo finalizado com sucesso")
+        # INTERACTIVE BUTTONS NODE: Enviar botões interativos
+        if node.node_type == "interactive_buttons":
+            logger.info(f"🔘 Executando Interactive Buttons Node")
+            await self._execute_interactive_buttons(conversation, node, flow, incoming_message, node_data)
+            return
 
-    async def _evaluate_conditions(self, conversation, node_data):
-        """
-        Avalia as condições de um Condition Node.
+        # INTERACTIVE LIST NODE: Enviar lista/menu interativo
+        if node.node_type == "interactive_list":
+            logger.info(f"📝 Executando Interactive List Node")
+            await self._execute_interactive_list(conversation, node, flow, incoming_message, node_data)
+            return
 
-        Args:
-            conversation: Instância da conversa (com context_variables)
-            node_data: Dados do node (com campo "conditions")
+        content_text = None
 
-        Returns:
-            int|bool|None: 
-                - Para multi-condition nodes (várias conditions independentes): 
-                  retorna o índice (0, 1, 2...) da primeira condição satisfeita, ou None se nenhuma satisfeita
-                - Para single-condition node com lógica AND/OR: 
-                  retorna True se satisfeita, False caso contrário
+        if node.node_type == "question":
+            content_text = node_data.get("questionText", "")
+        elif node.node_type == MEDIA_COLLECT_NODE_TYPE:
+            content_text = node_data.get("promptText") or MEDIA_DEFAULT_PROMPT
+        elif node.node_type == LOCATION_COLLECT_NODE_TYPE:
+            content_text = node_data.get("promptText") or LOCATION_DEFAULT_PROMPT
+        elif node.node_type == SURVEY_NODE_TYPE:
+            content_text = node_data.get("questionText") or SURVEY_DEFAULT_QUESTIONS[survey_type(node_data)]
+        elif node.node_type == "message":
+            # Verificar se é mensagem de mídia
+            media_type = node_data.get("mediaType")
+            if media_type in ["image", "video", "document", "audio"]:
+                logger.info(f"📎 Enviando mensagem de mídia: {media_type}")
+                await self._send_media_message(conversation, node_data, media_type)
+                # Avançar para próximo node após enviar mídia
+                await self._advance_to_next_node(conversation, node, flow, incoming_message)
+                return
+            else:
+                content_text = node_data.get("messageText", "")
+        elif node.node_type == "end":
+            content_text = node_data.get("farewellMessage", "")
+        else:
+            logger.warning(f"Node type {node.node_type} não suportado para envio de mensagem")
+            return
+
+        if not content_text:
+            logger.warning(f"Node {node.node_id} não tem conteúdo para enviar")
+            # Avançar para próximo node mesmo sem conteúdo
+            await self._advance_to_next_node(conversation, node, flow, incoming_message)
+            return
+
+        # Substituir variáveis no texto usando context_variables ({{ var | filtro }})
+        final_text = render_text(content_text, conversation.context_variables or {})
+
+        logger.info(f"📤 Enviando mensagem: {final_text[:50]}...")
+
+        # Enviar mensagem via WhatsApp
+        whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+
+        if whatsapp_number.connection_type == "official":
+            # Meta Cloud API
+            meta_api = self._guarded(whatsapp_clients.client(whatsapp_number))
+
+            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+
+            # Pergunta com botão "Voltar" quando há pergunta anterior no fluxo
+            back_button = None
+            if node.node_type == "question" and grapheme_len(final_text) <= INTERACTIVE_BODY_MAX:
+                back_button = await self._back_button(conversation, flow, node_data)
+
+            # Pedido de localização com o botão nativo "Enviar localização"
+            location_request = (
+                node.node_type == LOCATION_COLLECT_NODE_TYPE
+                and node_data.get("requestButton", True)
+                and grapheme_len(final_text) <= INTERACTIVE_BODY_MAX
+            )
+
+            # 🛡️ PROTEÇÃO: Retry automático de envio (até 3 tentativas)
+            max_retries = 3
+            retry_count = 0
+            last_error = None
+
+            while retry_count < max_retries:
+                try:
+                    if back_button:
+                        response = await meta_api.send_interactive_buttons(
+                            to=contact_whatsapp_id,
+                            body_text=final_text,
+                            buttons=[back_button]
+                        )
+                    elif location_request:
+                        response = await meta_api.send_location_request(
+                            to=contact_whatsapp_id,
+                            body_text=final_text
+                        )
+                    else:
+                        response = await meta_api.send_text_message(
+                            to=contact_whatsapp_id,
+                            text=final_text
+                        )
+
+                    whatsapp_message_id = response.get("messages", [{}])[0].get("id")
+                    logger.info(f"✅ Mensagem enviada via Meta API. ID: {whatsapp_message_id}")
+                    break  # Sucesso - sair do loop
+
+                except Exception as e:
+                    retry_count += 1
+                    last_error = e
+                    logger.warning(f"⚠️ Erro ao enviar mensagem (tentativa {retry_count}/{max_retries}): {e}")
+
+                    if retry_count < max_retries:
+                        # Aguardar antes de tentar novamente (exponential backoff)
+                        import asyncio
+                        wait_time = 2 ** retry_count  # 2s, 4s, 8s
+                        logger.info(f"⏳ Aguardando {wait_time}s antes de tentar novamente...")
+                        await asyncio.sleep(wait_time)
+                    else:
+                        # Máximo de tentativas atingido
+                        logger.error(f"❌ Falha após {max_retries} tentativas: {last_error}")
+                        return
+
+        elif whatsapp_number.connection_type == "qrcode":
+            # Evolution API
+            from app.integrations.evolution_api import EvolutionAPIClient
+
+            evolution = self._guarded(EvolutionAPIClient(
+                api_url=whatsapp_number.evolution_api_url,
+                api_key=whatsapp_number.evolution_api_key
+            ))
+
+            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+
+            # 🛡️ PROTEÇÃO: Retry automático de envio (até 3 tentativas)
+            max_retries = 3
+            retry_count = 0
+            last_error = None
+
+            while retry_count < max_retries:
+                try:
+                    response = await evolution.send_text_message(
+                        instance_name=whatsapp_number.evolution_instance_name,
+                        to=contact_whatsapp_id,
+                        text=final_text
+                    )
+                    logger.info(f"✅ Mensagem enviada via Evolution API")
+                    break  # Sucesso - sair do loop
+
+                except Exception as e:
+                    retry_count += 1
+                    last_error = e
+                    logger.warning(f"⚠️ Erro ao enviar mensagem (tentativa {retry_count}/{max_retries}): {e}")
+
+                    if retry_count < max_retries:
+                        # Aguardar antes de tentar novamente (exponential backoff)
+                        import asyncio
+                        wait_time = 2 ** retry_count  # 2s, 4s, 8s
+                        logger.info(f"⏳ Aguardando {wait_time}s antes de tentar novamente...")
+                        await asyncio.sleep(wait_time)
+                    else:
+                        # Máximo de tentativas atingido
+                        logger.error(f"❌ Falha após {max_retries} tentativas: {last_error}")
+                        return
+
+        # Salvar mensagem no banco
+        from app.repositories.conversation import MessageRepository
+        from datetime import datetime
+
+        message_repo = MessageRepository(self.db)
+        message_data = {
+            "organization_id": conversation.organization_id,
+            "conversation_id": conversation.id,
+            "whatsapp_number_id": whatsapp_number.id,
+            "direction": "outbound",
+            "sender_type": "bot",
+            "message_type": "text",
+            "content": {"text": final_text},
+            "status": "sent",
+            "sent_at": datetime.utcnow(),
+        }
+        await message_repo.create(message_data)
+        await self.db.commit()
+
+        # Se for node de pergunta (ou de coleta de mídia/localização, ou pesquisa), aguardar resposta do usuário
+        if node.node_type in ("question", MEDIA_COLLECT_NODE_TYPE, LOCATION_COLLECT_NODE_TYPE, SURVEY_NODE_TYPE):
+            logger.info(f"⏸️ Aguardando resposta do usuário para {node.node_type} node {node.node_id}")
+            # Não avançar - esperar próxima mensagem do usuário
+            return
+
+        # Para message e end nodes, avançar automaticamente
+        await self._advance_to_next_node(conversation, node, flow, incoming_message)
+
+    async def _process_user_response_and_advance(self, conversation, current_node, flow, user_message):
+        """
+        Processa resposta do usuário para um question node e avança para próximo node.
+        Inclui validação de responseType e sistema de retry com maxAttempts.
+
+        Args:
+            conversation: Instância da conversa
+            current_node: Node atual (deve ser tipo "question")
+            flow: Flow ativo
+            user_message: Mensagem do usuário
+        """
+        from app.repositories.conversation import ConversationRepository
+        from datetime import datetime, timedelta
+
+        logger.info(f"💬 Processando resposta do usuário para node {current_node.node_id}")
+
+        # 🛡️ PROTEÇÃO: Timeout de resposta (1 hora)
+        context_vars = conversation.context_variables or {}
+        timeout_key = f"_question_timestamp_{current_node.node_id}"
+        question_timestamp = context_vars.get(timeout_key)
+
+        if not question_timestamp:
+            # Primeira mensagem deste question node - salvar timestamp
+            context_vars[timeout_key] = datetime.utcnow().isoformat()
+
+            conv_repo = ConversationRepository(self.db)
+            await self._set_session(conversation, {
+                "context_variables": context_vars
+            })
+            await self.db.commit()
+        else:
+            # Verificar se passou mais de 1 hora
+            question_time = datetime.fromisoformat(question_timestamp)
+            elapsed = datetime.utcnow() - question_time
+
+            if elapsed > timedelta(hours=1):
+                logger.warning(f"⏰ Timeout de resposta! Passou {elapsed.total_seconds()//60:.0f} minutos")
+
+                # Enviar mensagem de timeout
+                timeout_msg = (
+                    "O tempo para resposta expirou. "
+                    "Vou encaminhar você para um agente humano."
+                )
+                await self._send_error_message(conversation, timeout_msg)
+
+                # Limpar timestamp
+                del context_vars[timeout_key]
+
+                conv_repo = ConversationRepository(self.db)
+                await self._set_session(conversation, {
+                    "context_variables": context_vars
+                })
+                await self.db.commit()
+
+                # Transferir para agente humano
+                handoff_data = {
+                    "transferMessage": "Transferência automática devido a timeout de resposta.",
+                    "priority": "medium",
+                    "sendTransferMessage": False  # Já enviamos mensagem acima
+                }
+                await self._execute_handoff(conversation, handoff_data)
+                return
+
+        # Extrair texto da resposta do usuário
+        user_text = user_message.content.get("text", "").strip()
+
+        if not user_text:
+            logger.warning("Mensagem do usuário sem texto")
+            return
+
+        node_data = await self._localized_node_data(conversation, current_node, flow)
+
+        # VALIDAÇÃO: Verificar se resposta é válida baseado no responseType
+        answer = validate_answer(user_text, node_data)
+
+        if not answer.valid:
+            logger.warning(f"❌ Resposta inválida: {user_text} (esperado: {node_data.get('responseType')})")
+
+            # Sistema de retry: verificar número de tentativas
+            context_vars = conversation.context_variables or {}
+            attempt_key = f"_attempts_{current_node.node_id}"
+            attempts = context_vars.get(attempt_key, 0) + 1
+
+            validation = node_data.get("validation") or {}
+            attempts_limit = max_attempts(node_data)
+
+            logger.info(f"  Tentativa {attempts}/{attempts_limit}")
+
+            if attempts >= attempts_limit:
+                # Máximo de tentativas atingido - enviar mensagem final e seguir a saída "invalid" (se conectada)
+                logger.warning(f"⚠️ Número máximo de tentativas ({attempts_limit}) atingido")
+
+                final_error_message = validation.get("maxAttemptsMessage") or DEFAULT_MAX_ATTEMPTS_MESSAGE
+                await self._send_error_message(conversation, final_error_message)
+
+                # Limpar contador de tentativas
+                del context_vars[attempt_key]
+
+                conv_repo = ConversationRepository(self.db)
+                await self._set_session(conversation, {
+                    "context_variables": context_vars
+                })
+                await self.db.commit()
+
+                # Avançar para próximo node (sem salvar resposta inválida)
+                await self._advance_after_invalid_input(conversation, current_node, flow, user_message)
+
+            else:
+                # Incrementar contador e enviar mensagem de erro
+                context_vars[attempt_key] = attempts
+
+                conv_repo = ConversationRepository(self.db)
+                await self._set_session(conversation, {
+                    "context_variables": context_vars
+                })
+                await self.db.commit()
+
+                # Enviar mensagem de erro personalizada
+                await self._send_error_message(conversation, answer.error)
+
+                # NÃO avançar - aguardar nova resposta do usuário
+
+            return
+
+        # Resposta válida - salvar e avançar
+        logger.info(f"✅ Resposta válida: {user_text}")
+
+        # Determinar nome da variável para salvar
+        variable_name = node_data.get("outputVariable")
+
+        if not variable_name:
+            # Fallback: gerar nome baseado no node_id
+            node_canvas_id = current_node.node_id
+            variable_suffix = node_canvas_id.replace("node-", "")
+            variable_name = f"user_response_{variable_suffix}"
+            logger.warning(f"Node sem outputVariable, usando fallback: {variable_name}")
+
+        logger.info(f"💾 Salvando resposta '{user_text}' na variável '{variable_name}'")
+
+        # Atualizar context_variables (o histórico de navegação guarda as variáveis de antes da resposta)
+        context_vars = conversation.context_variables or {}
+        if (await self._navigation_settings(conversation)).enabled:
+            context_vars[NAVIGATION_HISTORY_KEY] = push_history(
+                context_vars, current_node.id, current_node.node_id, flow.id
+            )
+        context_vars[variable_name] = user_text
+
+        # Valor normalizado (dígitos do CPF, data ISO, número...) em variável própria
+        normalized_variable = (node_data.get("validation") or {}).get("normalizedVariable")
+        if normalized_variable:
+            context_vars[normalized_variable] = answer.value
+
+        # Limpar contador de tentativas (se existir)
+        attempt_key = f"_attempts_{current_node.node_id}"
+        if attempt_key in context_vars:
+            del context_vars[attempt_key]
+
+        conv_repo = ConversationRepository(self.db)
+        await self._set_session(conversation, {
+            "context_variables": context_vars
+        })
+        await self.db.commit()
+
+        # Avançar para próximo node
+        await self._advance_to_next_node(conversation, current_node, flow, user_message)
+
+    async def _advance_after_invalid_input(self, conversation, node, flow, incoming_message):
+        """
+        Avança após esgotar as tentativas de um node que aguarda entrada:
+        pela saída "invalid" quando conectada, senão pela saída normal.
+        """
+        edges = await self._flow_edges(flow, node.flow_version_id)
+        has_invalid_branch = any(
+            edge.get("source") == node.node_id and edge.get("sourceHandle") == INVALID_HANDLE
+            for edge in edges
+        )
+        await self._advance_to_next_node(
+            conversation, node, flow, incoming_message,
+            source_handle=INVALID_HANDLE if has_invalid_branch else None
+        )
+
+    async def _advance_to_next_node(
+        self,
+        conversation,
+        current_node,
+        flow,
+        incoming_message,
+        condition_result: Optional[bool] = None,
+        source_handle: Optional[str] = None
+    ):
+        """
+        Avança para o próximo node seguindo as edges do canvas_data.
+
+        Args:
+            conversation: Instância da conversa
+            current_node: Node atual
+            flow: Flow ativo
+            incoming_message: Mensagem que originou o avanço
+            condition_result: Resultado de condição (True/False) para Condition Nodes
+            source_handle: Saída do node a seguir (ex: "loop"/"done" do Loop Node)
+        """
+        logger.info(f"➡️ Avançando do node {current_node.node_id}")
+        await self._trace_variables(conversation, current_node)
+
+        # 🛡️ PROTEÇÃO: Detecção de loops (node repetido ou ping-pong entre nodes sem mensagem do contato)
+        context_vars = conversation.context_variables or {}
+        execution_path = context_vars.get(EXECUTION_PATH_KEY, [])
+        execution_path.append(current_node.node_id)
+
+        guard = await self._flow_guard_settings(conversation)
+        loop = detect_loop(execution_path, guard.max_node_visits, guard.max_cycle_repeats)
+        if loop:
+            await self._trip_flow_guard(conversation, guard, f"loop: {loop}", send_fallback_message=True)
+            return
+
+        # Limitar tamanho do caminho (guardar apenas os últimos nodes)
+        context_vars[EXECUTION_PATH_KEY] = execution_path[-MAX_EXECUTION_PATH:]
+
+        if not await self._update_session(conversation, {"context_variables": context_vars}):
+            logger.warning(f"🔒 Sessão da conversa {conversation.id} assumida por outro processo, avanço descartado")
+            return
+
+        # Buscar próximo node nas edges (da versão em que a conversa está)
+        edges = await self._flow_edges(flow, current_node.flow_version_id)
+        current_node_canvas_id = current_node.node_id
+        next_node_canvas_id = None
+
+        # Se for Condition Node, buscar edge baseado no resultado
+        if condition_result is not None:
+            # condition_result pode ser:
+            # - True/False (para condition node simples com label true/false)
+            # - Número inteiro (índice da condição satisfeita em multi-condition nodes)
+            
+            if isinstance(condition_result, int):
+                # Multi-condition node: usar sourceHandle "condition-{index}"
+                target_handle = f"condition-{condition_result}"
+                logger.info(f"🔀 Buscando edge com sourceHandle '{target_handle}' para Condition Node")
+
+                for edge in edges:
+                    if edge.get("source") == current_node_canvas_id:
+                        source_handle = edge.get("sourceHandle", "")
+                        if source_handle == target_handle:
+                            next_node_canvas_id = edge.get("target")
+                            logger.info(f"  ✅ Edge encontrada: {source_handle} → {next_node_canvas_id}")
+                            break
+
+                if not next_node_canvas_id:
+                    logger.error(
+                        f"❌ Nenhuma edge com sourceHandle '{target_handle}' encontrada "
+                        f"saindo do Condition Node {current_node_canvas_id}"
+                    )
+                    return
+            else:
+                # Boolean condition: usar label true/false
+                target_label = "true" if condition_result else "false"
+                logger.info(f"🔀 Buscando edge com label '{target_label}' para Condition Node")
+
+                for edge in edges:
+                    if edge.get("source") == current_node_canvas_id:
+                        edge_label = edge.get("label", "").lower()
+                        # Também aceita "yes"/"no" como sinônimos
+                        if (target_label == "true" and edge_label in ["true", "yes", "sim"]) or \
+                           (target_label == "false" and edge_label in ["false", "no", "não"]):
+                            next_node_canvas_id = edge.get("target")
+                            logger.info(f"  ✅ Edge encontrada: {edge_label} → {next_node_canvas_id}")
+                            break
+
+                if not next_node_canvas_id:
+                    logger.error(
+                        f"❌ Nenhuma edge com label '{target_label}' encontrada "
+                        f"saindo do Condition Node {current_node_canvas_id}"
+                    )
+                    return
+
+        elif source_handle is not None:
+            # Node com várias saídas nomeadas: usar a edge do sourceHandle
+            for edge in edges:
+                if edge.get("source") == current_node_canvas_id and edge.get("sourceHandle") == source_handle:
+                    next_node_canvas_id = edge.get("target")
+                    break
+
+        else:
+            # Fluxo normal: primeira edge encontrada (a saída "invalid" da pergunta só é seguida após as
+            # tentativas e as saídas por categoria da pesquisa só na sua categoria)
+            skipped_handles = {INVALID_HANDLE}
+            if current_node.node_type == SURVEY_NODE_TYPE:
+                skipped_handles.update(SURVEY_CATEGORY_HANDLES)
+            for edge in edges:
+                if edge.get("source") == current_node_canvas_id and edge.get("sourceHandle") not in skipped_handles:
+                    next_node_canvas_id = edge.get("target")
+                    break
+
+        if not next_node_canvas_id:
+            logger.warning(f"⚠️ Nenhuma edge encontrada saindo do node {current_node_canvas_id}")
+
+            # Se for end node, finalizar fluxo (ou voltar ao flow que chamou o sub-flow)
+            if current_node.node_type == "end":
+                await self._complete_flow(conversation, current_node, incoming_message)
+
+            return
+
+        # Buscar próximo node no banco
+        next_node = await self._find_node(
+            flow.id, next_node_canvas_id, conversation.organization_id, current_node.flow_version_id
+        )
+
+        if not next_node:
+            logger.warning(f"❌ Node {next_node_canvas_id} não encontrado no banco")
+            return
+
+        # Atualizar current_node_id e o checkpoint: node atual concluído com a saída seguida,
+        # próximo node a executar (retomado pela task se o processo cair antes)
+        now = datetime.now(timezone.utc)
+        checkpoint = complete_checkpoint(conversation.node_checkpoint, current_node.node_id, {
+            "next_node_id": next_node_canvas_id,
+            "source_handle": source_handle,
+            "condition_result": condition_result,
+        }, now)
+        advanced = await self._update_session(conversation, {
+            "current_node_id": next_node.id,
+            "node_checkpoint": checkpoint,
+            "node_checkpoint_at": now,
+        })
+        if not advanced:
+            logger.warning(f"🔒 Sessão da conversa {conversation.id} assumida por outro processo, avanço descartado")
+            return
+        conversation.node_checkpoint = checkpoint
+        conversation.node_checkpoint_at = now
+
+        logger.info(f"✅ Avançado para node {next_node.node_type}: {next_node.label}")
+
+        # Se for end node, finalizar após executar
+        if next_node.node_type == "end":
+            await self._execute_node(conversation, next_node, flow, incoming_message)
+            await self._complete_flow(conversation, next_node, incoming_message)
+        else:
+            # Executar próximo node
+            await self._execute_node(conversation, next_node, flow, incoming_message)
+
+    async def _finalize_flow(self, conversation):
+        """
+        Finaliza o fluxo do chatbot.
+
+        Args:
+            conversation: Instância da conversa
+        """
+        logger.info(f"🏁 Finalizando fluxo para conversa {conversation.id}")
+
+        update_data = {
+            "is_bot_active": False,
+            "active_flow_id": None,
+            "current_node_id": None,
+            "flow_timeout_at": None,
+            "flow_expires_at": None,
+        }
+
+        # Sub-flows pendentes não retornam mais, loops abertos recomeçam do zero
+        # e a sessão do flow termina (fecha a visita ao último node)
+        context_vars = dict(conversation.context_variables or {})
+        session_id = context_vars.get(FLOW_SESSION_KEY)
+        if session_id:
+            await FlowNodeVisitRepository(self.db).close_open(UUID(session_id), datetime.now(timezone.utc))
+        removed = [
+            context_vars.pop(key, None)
+            for key in (SUBFLOW_STACK_KEY, LOOP_STATE_KEY, FLOW_SESSION_KEY, FLOW_TIMEOUT_FIRED_KEY)
+        ]
+        if any(value is not None for value in removed):
+            update_data["context_variables"] = context_vars
+            conversation.context_variables = context_vars
+
+        await self._set_session(conversation, update_data)
+        await FlowWaitRepository(self.db).cancel_pending(conversation.id)
+        await self.db.commit()
+
+        logger.info(f"✅ Fluxo finalizado com sucesso")
+
+    async def _complete_flow(self, conversation, end_node, incoming_message):
+        """
+        Conclui o flow ao chegar em um end node.
+
+        Dentro de um sub-flow, volta ao flow que o chamou e segue a partir do
+        Sub-flow Node; caso contrário finaliza o fluxo. Só age uma vez por end
+        node (o end node pode ser concluído tanto ao executá-lo quanto ao
+        avançar a partir dele).
+        """
+        from app.models.conversation import Conversation
+
+        result = await self.db.execute(
+            select(Conversation.current_node_id).where(Conversation.id == conversation.id)
+        )
+        if result.scalar_one_or_none() != end_node.id:
+            return
+
+        if end_node.flow_version_id:
+            await FlowVersionRepository(self.db).increment_stats(end_node.flow_version_id, sessions_completed=1)
+
+        if (conversation.context_variables or {}).get(SUBFLOW_STACK_KEY):
+            await self._return_from_subflow(conversation, incoming_message)
+        else:
+            await self._finalize_flow(conversation)
+
+    async def _return_from_subflow(self, conversation, incoming_message):
+        """
+        Retorna ao flow que chamou o sub-flow e avança a partir do Sub-flow Node.
+
+        Args:
+            conversation: Instância da conversa
+            incoming_message: Mensagem que originou a execução
+        """
+        from app.services.chatbot_service import ChatbotService
+
+        context_vars = dict(conversation.context_variables or {})
+        stack = list(context_vars.get(SUBFLOW_STACK_KEY) or [])
+        frame = stack.pop()
+        if stack:
+            context_vars[SUBFLOW_STACK_KEY] = stack
+        else:
+            context_vars.pop(SUBFLOW_STACK_KEY, None)
+        conversation.context_variables = context_vars
+
+        chatbot_service = ChatbotService(self.db)
+        parent_flow = await chatbot_service.flow_repo.get(UUID(frame["flow_id"]))
+        parent_node = None
+        if parent_flow and parent_flow.organization_id == conversation.organization_id:
+            version_id = frame.get("version_id")
+            parent_node = await self._find_node(
+                parent_flow.id, frame["node_id"], conversation.organization_id, UUID(version_id) if version_id else None
+            )
+
+        if not parent_node:
+            logger.error(f"❌ Flow de retorno do sub-flow não encontrado: {frame}")
+            await self._finalize_flow(conversation)
+            return
+
+        await self._set_session(conversation, {
+            "active_flow_id": parent_flow.id,
+            "current_node_id": parent_node.id,
+            "context_variables": context_vars,
+        })
+        await self.db.commit()
+
+        logger.info(f"↩️ Sub-flow concluído, retornando ao flow {parent_flow.name}")
+        await self._advance_to_next_node(conversation, parent_node, parent_flow, incoming_message)
+
+    async def _send_typing_indicator(self, conversation, incoming_message, whatsapp_number=None):
+        """
+        Mostra o indicador "digitando..." ao contato (best-effort, nunca interrompe o fluxo).
+        """
+        try:
+            whatsapp_number = whatsapp_number or await self.repo.get(conversation.whatsapp_number_id)
+            if not whatsapp_number:
+                return
+
+            if whatsapp_number.connection_type == "official":
+                # Cloud API exige o ID da mensagem recebida
+                message_id = getattr(incoming_message, "whatsapp_message_id", None)
+                if not message_id:
+                    return
+
+                meta_api = whatsapp_clients.client(whatsapp_number)
+                await meta_api.send_typing_on(message_id)
+
+            elif whatsapp_number.connection_type == "qrcode":
+                evolution = EvolutionAPIClient(
+                    api_url=whatsapp_number.evolution_api_url,
+                    api_key=whatsapp_number.evolution_api_key
+                )
+                await evolution.send_typing_on(
+                    instance_name=whatsapp_number.evolution_instance_name,
+                    phone_number=conversation.contact.whatsapp_id.replace("+", ""),
+                )
+
+            logger.info(f"⌨️ Indicador de digitação enviado (conversa {conversation.id})")
+
+        except Exception as e:
+            logger.warning(f"⚠️ Não foi possível enviar indicador de digitação: {e}")
+
+    async def _evaluate_conditions(self, conversation, node_data):
+        """
+        Avalia as condições de um Condition Node.
+
+        Args:
+            conversation: Instância da conversa (com context_variables)
+            node_data: Dados do node (com campo "conditions")
+
+        Returns:
+            int|bool|None: 
+                - Para multi-condition nodes (várias conditions independentes): 
+                  retorna o índice (0, 1, 2...) da primeira condição satisfeita, ou None se nenhuma satisfeita
+                - Para single-condition node com lógica AND/OR: 
+                  retorna True se satisfeita, False caso contrário
+
+        Formato esperado do node_data:
+        {
+            "conditions": [
+                {
+                    "variable": "user_response_edad",
+                    "operator": ">=",
+                    "value": "18",
+                    "label": "Maior de idade"  # Opcional, para multi-condition
+                }
+            ],
+            "logicOperator": "AND",  # Opcional: AND (default) ou OR
+            "hasDefaultRoute": false  # Se true, é multi-condition (retorna índice)
+        }
+
+        Em vez da lista, o node (ou cada condição) pode ter uma expressão:
+        {"expression": "invoice.amount > 100 && customer.status == 'active'"}
+        (ver app.utils.flow_expression)
+        """
+        context_vars = conversation.context_variables or {}
+        conditions = node_data.get("conditions", [])
+        logic_operator = node_data.get("logicOperator", "AND").upper()
+        has_default_route = node_data.get("hasDefaultRoute", False)
+
+        # Expressão única no lugar da lista de condições
+        if node_data.get("expression"):
+            return self._evaluate_expression(node_data["expression"], context_vars)
+
+        if not conditions:
+            logger.warning("Condition Node sem condições definidas, retornando None")
+            return None
+
+        logger.info(f"🔍 Avaliando {len(conditions)} condição(ões) com lógica {logic_operator}")
+
+        results = []
+
+        for condition in conditions:
+            if condition.get("expression"):
+                results.append(self._evaluate_expression(condition["expression"], context_vars))
+                continue
+
+            var_name = condition.get("variable")
+            operator = condition.get("operator")
+            expected_value = condition.get("value")
+
+            if not var_name or not operator:
+                logger.warning(f"Condição inválida (sem variable ou operator): {condition}")
+                results.append(False)
+                continue
+
+            # Obter valor da variável
+            var_value = context_vars.get(var_name)
+
+            if var_value is None:
+                logger.warning(f"Variável '{var_name}' não encontrada em context_variables")
+                results.append(False)
+                continue
+
+            # Converter valores para comparação
+            # Tentar converter para número se possível
+            try:
+                var_value_num = float(var_value)
+                expected_value_num = float(expected_value)
+                use_numeric = True
+            except (ValueError, TypeError):
+                var_value_str = str(var_value).strip().lower()
+                expected_value_str = str(expected_value).strip().lower()
+                use_numeric = False
+
+            # Avaliar operador
+            condition_result = False
+
+            try:
+                if operator == "==":
+                    if use_numeric:
+                        condition_result = var_value_num == expected_value_num
+                    else:
+                        condition_result = var_value_str == expected_value_str
+
+                elif operator == "!=":
+                    if use_numeric:
+                        condition_result = var_value_num != expected_value_num
+                    else:
+                        condition_result = var_value_str != expected_value_str
+
+                elif operator == ">":
+                    if use_numeric:
+                        condition_result = var_value_num > expected_value_num
+                    else:
+                        logger.warning(f"Operador '>' requer valores numéricos")
+                        condition_result = False
+
+                elif operator == "<":
+                    if use_numeric:
+                        condition_result = var_value_num < expected_value_num
+                    else:
+                        logger.warning(f"Operador '<' requer valores numéricos")
+                        condition_result = False
+
+                elif operator == ">=":
+                    if use_numeric:
+                        condition_result = var_value_num >= expected_value_num
+                    else:
+                        logger.warning(f"Operador '>=' requer valores numéricos")
+                        condition_result = False
+
+                elif operator == "<=":
+                    if use_numeric:
+                        condition_result = var_value_num <= expected_value_num
+                    else:
+                        logger.warning(f"Operador '<=' requer valores numéricos")
+                        condition_result = False
+
+                elif operator == "contains":
+                    # Sempre string
+                    condition_result = expected_value_str in var_value_str
+
+                else:
+                    logger.warning(f"Operador desconhecido: {operator}")
+                    condition_result = False
+
+                logger.info(
+                    f"  Condição: {var_name} ({var_value}) {operator} {expected_value} "
+                    f"= {condition_result}"
+                )
+
+            except Exception as e:
+                logger.error(f"Erro ao avaliar condição: {e}")
+                condition_result = False
+
+            results.append(condition_result)
+
+        # Aplicar lógica AND/OR
+        if logic_operator == "AND":
+            final_result = all(results)
+        elif logic_operator == "OR":
+            final_result = any(results)
+        else:
+            logger.warning(f"Operador lógico desconhecido: {logic_operator}, usando AND")
+            final_result = all(results)
+
+        logger.info(f"✅ Resultado final das condições: {final_result}")
+
+        return final_result
+
+    @staticmethod
+    def _evaluate_expression(expression: str, context_vars: Dict[str, Any]) -> bool:
+        """
+        Avalia uma expressão de Condition Node (linguagem em app.utils.flow_expression).
+
+        Expressões inválidas são registradas e contam como falsas.
+        """
+        try:
+            result = bool(evaluate_expression(expression, context_vars))
+        except ExpressionError as e:
+            logger.error(f"❌ Expressão inválida '{expression}': {e}")
+            return False
+
+        logger.info(f"  Expressão: {expression} = {result}")
+        return result
+
+    async def _execute_handoff(self, conversation, node_data, node=None, flow=None):
+        """
+        Executa transferência de conversa para agente humano (Handoff Node).
+
+        Encerra a sessão do bot, atribui a conversa à fila/agente, guarda as
+        variáveis coletadas como contexto do atendimento e notifica os agentes
+        via WebSocket. Com returnToFlow, o contato volta ao flow (saída do
+        Handoff Node) quando o agente encerrar a conversa.
+
+        Args:
+            conversation: Instância da conversa
+            node_data: Dados do Handoff Node
+            node: Handoff Node (None em transferências automáticas)
+            flow: Flow em execução (None em transferências automáticas)
 
         Formato esperado do node_data:
         {
-            "conditions": [
-                {
-                    "variable": "user_response_edad",
-                    "operator": ">=",
-                    "value": "18",
-                    "label": "Maior de idade"  # Opcional, para multi-condition
-                }
-            ],
-            "logicOperator": "AND",  # Opcional: AND (default) ou OR
-            "hasDefaultRoute": false  # Se true, é multi-condition (retorna índice)
+            "handoffType": "queue|department|agent",  # Tipo de transferência
+            "queueId": "uuid-da-fila",               # Para handoffType = "queue"
+            "departmentId": "uuid-do-dept",          # Para handoffType = "department"
+            "agentId": "uuid-do-agente",             # Para handoffType = "agent"
+            "priority": "low|normal|high|urgent",    # Prioridade (default: normal)
+            "contextMessage": "Contexto...",         # Mensagem de contexto para o agente
+            "transferMessage": "Transferindo...",    # Mensagem enviada ao cliente
+            "sendTransferMessage": true,             # Se deve enviar mensagem ao cliente
+            "includeVariables": true,                # Passa as variáveis coletadas ao agente
+            "returnToFlow": false                    # Volta ao flow quando o agente encerrar
+        }
+        """
+        from app.repositories.queue import QueueRepository
+        from uuid import UUID
+
+        logger.info(f"👤 Executando handoff para conversa {conversation.id}")
+
+        # Extrair configurações
+        handoff_type = node_data.get("handoffType", "queue")  # queue, department, agent
+        queue_id = node_data.get("queueId")
+        department_id = node_data.get("departmentId")
+        agent_id = node_data.get("agentId")
+        priority = node_data.get("priority", "normal")
+        variables = conversation.context_variables or {}
+        context_message = render_text(node_data.get("contextMessage", ""), variables)
+        send_transfer_message = node_data.get("sendTransferMessage", True)
+        transfer_message = render_text(
+            node_data.get("transferMessage", "Transferindo para um agente humano..."), variables
+        )
+
+        logger.info(f"   Tipo de handoff: {handoff_type}")
+        logger.info(f"   Prioridade: {priority}")
+
+        # Determinar queue_id baseado no handoffType
+        final_queue_id = None
+        final_agent_id = None
+
+        if handoff_type == "queue" and queue_id:
+            # Transferir para fila específica
+            try:
+                final_queue_id = UUID(queue_id) if isinstance(queue_id, str) else queue_id
+                logger.info(f"   Transferindo para fila: {final_queue_id}")
+            except (ValueError, AttributeError) as e:
+                logger.error(f"   ❌ queueId inválido: {queue_id} - {e}")
+
+        elif handoff_type == "department" and department_id:
+            # Buscar fila principal do departamento
+            try:
+                dept_id_uuid = UUID(department_id) if isinstance(department_id, str) else department_id
+                queue_repo = QueueRepository(self.db)
+
+                # Buscar primeira fila ativa do departamento (ordenada por prioridade)
+                queues = await queue_repo.list_queues(
+                    organization_id=conversation.organization_id,
+                    department_id=dept_id_uuid,
+                    is_active=True,
+                    limit=1
+                )
+
+                if queues and len(queues) > 0:
+                    final_queue_id = queues[0].id
+                    logger.info(f"   Fila do departamento encontrada: {final_queue_id} ({queues[0].name})")
+                else:
+                    logger.warning(f"   ⚠️ Nenhuma fila ativa encontrada para departamento {dept_id_uuid}")
+                    logger.warning(f"   Transferindo para fila geral")
+
+            except (ValueError, AttributeError) as e:
+                logger.error(f"   ❌ departmentId inválido: {department_id} - {e}")
+
+        elif handoff_type == "agent" and agent_id:
+            # Transferir diretamente para agente específico
+            try:
+                final_agent_id = UUID(agent_id) if isinstance(agent_id, str) else agent_id
+                logger.info(f"   Transferindo para agente: {final_agent_id}")
+            except (ValueError, AttributeError) as e:
+                logger.error(f"   ❌ agentId inválido: {agent_id} - {e}")
+
+        # Enviar mensagem de transferência (se configurado)
+        if send_transfer_message and transfer_message:
+            whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+
+            if whatsapp_number.connection_type == "official":
+                # Meta Cloud API
+                meta_api = self._guarded(whatsapp_clients.client(whatsapp_number))
+
+                contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+
+                try:
+                    await meta_api.send_text_message(
+                        to=contact_whatsapp_id,
+                        text=transfer_message
+                    )
+                    logger.info(f"✅ Mensagem de transferência enviada via Meta API")
+                except Exception as e:
+                    logger.error(f"❌ Erro ao enviar mensagem de transferência: {e}")
+
+            elif whatsapp_number.connection_type == "qrcode":
+                # Evolution API
+                from app.integrations.evolution_api import EvolutionAPIClient
+
+                evolution = self._guarded(EvolutionAPIClient(
+                    api_url=whatsapp_number.evolution_api_url,
+                    api_key=whatsapp_number.evolution_api_key
+                ))
+
+                contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+
+                try:
+                    await evolution.send_text_message(
+                        instance_name=whatsapp_number.evolution_instance_name,
+                        to=contact_whatsapp_id,
+                        text=transfer_message
+                    )
+                    logger.info(f"✅ Mensagem de transferência enviada via Evolution API")
+                except Exception as e:
+                    logger.error(f"❌ Erro ao enviar mensagem de transferência: {e}")
+
+            # Salvar mensagem no banco
+            from app.repositories.conversation import MessageRepository
+            from datetime import datetime
+
+            message_repo = MessageRepository(self.db)
+            message_data = {
+                "organization_id": conversation.organization_id,
+                "conversation_id": conversation.id,
+                "whatsapp_number_id": whatsapp_number.id,
+                "direction": "outbound",
+                "sender_type": "bot",
+                "message_type": "text",
+                "content": {"text": transfer_message},
+                "status": "sent",
+                "sent_at": datetime.utcnow(),
+            }
+            await message_repo.create(message_data)
+            await self.db.commit()
+
+        # Atualizar conversa: desativar bot e atribuir à fila ou agente
+        from app.services.conversation_service import ConversationService
+        from datetime import datetime
+
+        # Mapear prioridade textual para prioridade numérica da fila
+        priority_map = {
+            "low": 10,
+            "normal": 50,
+            "medium": 50,  # Aceita "medium" como alias de "normal"
+            "high": 80,
+            "urgent": 100,
         }
+        queue_priority = priority_map.get(str(priority).lower(), 50)
+
+        # Contexto do atendimento: mensagem, variáveis coletadas e retorno ao flow
+        extra_data = dict(conversation.extra_data or {})
+        extra_data.pop(HANDOFF_RETURN_KEY, None)
+        if context_message:
+            extra_data["handoff_context"] = context_message
+        if node_data.get("includeVariables", True):
+            extra_data["handoff_variables"] = {
+                key: value for key, value in variables.items() if not key.startswith("_")
+            }
+        if node_data.get("returnToFlow") and node and flow:
+            extra_data[HANDOFF_RETURN_KEY] = {"flow_id": str(flow.id), "node_id": str(node.id)}
+
+        updated = None
+        try:
+            if final_agent_id:
+                # Transferência direta para agente
+                logger.info(f"   Atribuindo conversa diretamente ao agente {final_agent_id}")
+
+                await self._set_session(
+                    conversation,
+                    {
+                        "is_bot_active": False,
+                        "status": "active",
+                        "current_agent_id": final_agent_id,
+                        "queued_at": None,
+                        "queue_priority": queue_priority,
+                        "handoff_at": datetime.utcnow(),
+                        "extra_data": extra_data,
+                    },
+                )
+
+            elif final_queue_id:
+                # Transferência para fila específica (com overflow)
+                logger.info(f"   Atribuindo conversa à fila {final_queue_id} (com overflow)")
+
+                conv_service = ConversationService(self.db)
+                await conv_service.assign_to_queue_with_overflow(
+                    conversation_id=conversation.id,
+                    queue_id=final_queue_id,
+                    organization_id=conversation.organization_id,
+                )
+
+                # Atualizar prioridade e desativar bot
+                update_data = {
+                    "queue_priority": queue_priority,
+                    "is_bot_active": False,
+                    "handoff_at": datetime.utcnow(),
+                    "extra_data": extra_data,
+                }
+
+                await self._set_session(conversation, update_data)
+
+            else:
+                # Sem fila nem agente específico, marca como aguardando atendimento geral
+                logger.warning(f"   ⚠️ Handoff sem fila ou agente específico, colocando em fila geral")
+
+                update_data = {
+                    "is_bot_active": False,
+                    "status": "queued",
+                    "queued_at": datetime.utcnow(),
+                    "queue_priority": queue_priority,
+                    "handoff_at": datetime.utcnow(),
+                    "extra_data": extra_data,
+                }
+
+                await self._set_session(conversation, update_data)
+
+            await self.db.commit()
+            updated = conversation
+
+        except Exception as e:
+            logger.error(f"❌ Erro ao aplicar handoff: {e}")
+            import traceback
+            logger.error(traceback.format_exc())
+
+        logger.info(
+            f"✅ Handoff completo: conversa {conversation.id} "
+            f"transferida para fila (prioridade: {priority})"
+        )
+
+        # Finalizar fluxo do bot
+        await self._finalize_flow(conversation)
+
+        if updated:
+            await self._notify_handoff(updated, priority)
+
+    async def _notify_handoff(self, conversation, priority):
         """
-        context_vars = conversation.context_variables or {}
-        conditions = node_data.get("conditions", [])
-        logic_operator = node_data.get("logicOperator", "AND").upper()
-        has_default_route = node_data.get("hasDefaultRoute", False)
+        Avisa os agentes (organização e agente atribuído) de uma nova transferência.
 
-        if not conditions:
-            logger.warning("Condition Node sem condições definidas, retornando None")
-            return None
+        Args:
+            conversation: Conversa já atualizada pelo handoff
+            priority: Prioridade textual do Handoff Node
+        """
+        from app.websocket.manager import emit_to_organization, emit_to_user
 
-        logger.info(f"🔍 Avaliando {len(conditions)} condição(ões) com lógica {logic_operator}")
+        extra_data = conversation.extra_data or {}
+        payload = {
+            "conversation_id": str(conversation.id),
+            "contact_id": str(conversation.contact_id),
+            "queue_id": str(conversation.queue_id) if conversation.queue_id else None,
+            "agent_id": str(conversation.current_agent_id) if conversation.current_agent_id else None,
+            "priority": priority,
+            "context": extra_data.get("handoff_context"),
+            "variables": extra_data.get("handoff_variables", {}),
+            "returns_to_flow": HANDOFF_RETURN_KEY in extra_data,
+        }
 
-        results = []
+        try:
+            await emit_to_organization(str(conversation.organization_id), "conversation:handoff", payload)
+            if conversation.current_agent_id:
+                await emit_to_user(str(conversation.current_agent_id), "conversation:handoff", payload)
+        except Exception as e:
+            logger.warning(f"⚠️ Não foi possível notificar o handoff da conversa {conversation.id}: {e}")
 
-        for condition in conditions:
-            var_name = condition.get("variable")
-            operator = condition.get("operator")
-            expected_value = condition.get("value")
+    async def return_from_handoff(self, conversation_id: UUID, organization_id: UUID) -> bool:
+        """
+        Devolve o contato ao flow quando o agente encerra a conversa.
 
-            if not var_name or not operator:
-                logger.warning(f"Condição inválida (sem variable ou operator): {condition}")
-                results.append(False)
-                continue
+        Só age em conversas transferidas por um Handoff Node com returnToFlow:
+        reativa o bot e segue pela saída do Handoff Node.
 
-            # Obter valor da variável
-            var_value = context_vars.get(var_name)
+        Returns:
+            True se o fluxo foi retomado
+        """
+        from app.repositories.conversation import ConversationRepository
+        from app.services.chatbot_service import ChatbotService
 
-            if var_value is None:
-                logger.warning(f"Variável '{var_name}' não encontrada em context_variables")
-                results.append(False)
-                continue
+        conv_repo = ConversationRepository(self.db)
+        conversation = await conv_repo.get_with_contact(conversation_id, organization_id)
+        if not conversation:
+            return False
 
-            # Converter valores para comparação
-            # Tentar converter para número se possível
-            try:
-                var_value_num = float(var_value)
-                expected_value_num = float(expected_value)
-                use_numeric = True
-            except (ValueError, TypeError):
-                var_value_str = str(var_value).strip().lower()
-                expected_value_str = str(expected_value).strip().lower()
-                use_numeric = False
+        extra_data = dict(conversation.extra_data or {})
+        target = extra_data.pop(HANDOFF_RETURN_KEY, None)
+        if not target:
+            return False
 
-            # Avaliar operador
-            condition_result = False
+        chatbot_service = ChatbotService(self.db)
+        node = await chatbot_service.node_repo.get(UUID(target["node_id"]))
+        flow = await chatbot_service.flow_repo.get(UUID(target["flow_id"]))
 
-            try:
-                if operator == "==":
-                    if use_numeric:
-                        condition_result = var_value_num == expected_value_num
-                    else:
-                        condition_result = var_value_str == expected_value_str
+        if not node or not flow or flow.organization_id != organization_id:
+            logger.warning(f"⚠️ Flow de retorno do handoff não existe mais (conversa {conversation_id})")
+            await self._set_session(conversation, {"extra_data": extra_data})
+            await self.db.commit()
+            return False
 
-                elif operator == "!=":
-                    if use_numeric:
-                        condition_result = var_value_num != expected_value_num
-                    else:
-                        condition_result = var_value_str != expected_value_str
+        logger.info(f"↩️ Conversa {conversation_id} volta ao flow {flow.name} após o atendimento humano")
+        # O tempo com o agente não conta para o timeout da sessão
+        now = datetime.now(timezone.utc)
+        timeouts = await self._flow_timeout_settings(conversation)
+        await self._set_session(
+            conversation,
+            {
+                "extra_data": extra_data,
+                "status": "open",
+                "is_bot_active": True,
+                "active_flow_id": flow.id,
+                "current_node_id": node.id,
+                "flow_timeout_at": timeouts.timeout_at(now),
+                "flow_expires_at": timeouts.expires_at(now),
+            },
+        )
+        await self.db.commit()
 
-                elif operator == ">":
-                    if use_numeric:
-                        condition_result = var_value_num > expected_value_num
-                    else:
-                        logger.warning(f"Operador '>' requer valores numéricos")
-                        condition_result = False
+        await self._advance_to_next_node(conversation, node, flow, None)
+        return True
 
-                elif operator == "<":
-                    if use_numeric:
-                        condition_result = var_value_num < expected_value_num
-                    else:
-                        logger.warning(f"Operador '<' requer valores numéricos")
-                        condition_result = False
+    async def _send_error_message(self, conversation, error_text: str):
+        """
+        Envia mensagem de erro para o usuário via WhatsApp.
 
-                elif operator == ">=":
-                    if use_numeric:
-                        condition_result = var_value_num >= expected_value_num
-                    else:
-                        logger.warning(f"Operador '>=' requer valores numéricos")
-                        condition_result = False
+        Args:
+            conversation: Instância da conversa
+            error_text: Texto da mensagem de erro
+        """
+        logger.info(f"📮 Enviando mensagem de erro: {error_text}")
 
-                elif operator == "<=":
-                    if use_numeric:
-                        condition_result = var_value_num <= expected_value_num
-                    else:
-                        logger.warning(f"Operador '<=' requer valores numéricos")
-                        condition_result = False
+        whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
 
-                elif operator == "contains":
-                    # Sempre string
-                    condition_result = expected_value_str in var_value_str
+        if whatsapp_number.connection_type == "official":
+            # Meta Cloud API
+            meta_api = self._guarded(whatsapp_clients.client(whatsapp_number))
 
-                else:
-                    logger.warning(f"Operador desconhecido: {operator}")
-                    condition_result = False
+            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
 
-                logger.info(
-                    f"  Condição: {var_name} ({var_value}) {operator} {expected_value} "
-                    f"= {condition_result}"
+            try:
+                await meta_api.send_text_message(
+                    to=contact_whatsapp_id,
+                    text=error_text
                 )
-
+                logger.info(f"✅ Mensagem de erro enviada via Meta API")
             except Exception as e:
-                logger.error(f"Erro ao avaliar condição: {e}")
-                condition_result = False
+                logger.error(f"❌ Erro ao enviar mensagem de erro: {e}")
+                return
 
-            results.append(condition_result)
+        elif whatsapp_number.connection_type == "qrcode":
+            # Evolution API
+            from app.integrations.evolution_api import EvolutionAPIClient
 
-        # Aplicar lógica AND/OR
-        if logic_operator == "AND":
-            final_result = all(results)
-        elif logic_operator == "OR":
-            final_result = any(results)
-        else:
-            logger.warning(f"Operador lógico desconhecido: {logic_operator}, usando AND")
-            final_result = all(results)
+            evolution = self._guarded(EvolutionAPIClient(
+                api_url=whatsapp_number.evolution_api_url,
+                api_key=whatsapp_number.evolution_api_key
+            ))
 
-        logger.info(f"✅ Resultado final das condições: {final_result}")
+            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
 
-        return final_result
+            try:
+                await evolution.send_text_message(
+                    instance_name=whatsapp_number.evolution_instance_name,
+                    to=contact_whatsapp_id,
+                    text=error_text
+                )
+                logger.info(f"✅ Mensagem de erro enviada via Evolution API")
+            except Exception as e:
+                logger.error(f"❌ Erro ao enviar mensagem de erro: {e}")
+                return
+
+        # Salvar mensagem no banco
+        from app.repositories.conversation import MessageRepository
+        from datetime import datetime
+
+        message_repo = MessageRepository(self.db)
+        message_data = {
+            "organization_id": conversation.organization_id,
+            "conversation_id": conversation.id,
+            "whatsapp_number_id": whatsapp_number.id,
+            "direction": "outbound",
+            "sender_type": "bot",
+            "message_type": "text",
+            "content": {"text": error_text},
+            "status": "sent",
+            "sent_at": datetime.utcnow(),
+        }
+        await message_repo.create(message_data)
+        await self.db.commit()
 
-    async def _execute_handoff(self, conversation, node_data):
+    async def _execute_delay(self, conversation, node, flow, incoming_message, node_data):
         """
-        Executa transferência de conversa para agente humano (Handoff Node).
+        Executa um Delay Node - aguarda X segundos antes de avançar para o próximo node.
 
         Args:
             conversation: Instância da conversa
-            node_data: Dados do Handoff Node
+            node: Node atual (Delay)
+            flow: Flow ativo
+            incoming_message: Mensagem que originou a execução
+            node_data: Dados do Delay Node
 
         Formato esperado do node_data:
         {
-            "handoffType": "queue|department|agent",  # Tipo de transferência
-            "queueId": "uuid-da-fila",               # Para handoffType = "queue"
-            "departmentId": "uuid-do-dept",          # Para handoffType = "department"
-            "agentId": "uuid-do-agente",             # Para handoffType = "agent"
-            "priority": "low|normal|high|urgent",    # Prioridade (default: normal)
-            "contextMessage": "Contexto...",         # Mensagem de contexto para o agente
-            "transferMessage": "Transferindo...",    # Mensagem enviada ao cliente
-            "sendTransferMessage": true              # Se deve enviar mensagem ao cliente
+            "delaySeconds": 5,  # Tempo em segundos (padrão: 3)
+            "delayMessage": "Aguarde um momento..."  # Opcional
         }
         """
-        from app.repositories.conversation import ConversationRepository
-        from app.repositories.queue import QueueRepository
-        from uuid import UUID
+        import asyncio
 
-        logger.info(f"👤 Executando handoff para conversa {conversation.id}")
+        logger.info(f"⏰ Executando Delay Node")
 
         # Extrair configurações
-        handoff_type = node_data.get("handoffType", "queue")  # queue, department, agent
-        queue_id = node_data.get("queueId")
-        department_id = node_data.get("departmentId")
-        agent_id = node_data.get("agentId")
-        priority = node_data.get("priority", "normal")
-        context_message = node_data.get("contextMessage", "")
-        send_transfer_message = node_data.get("sendTransferMessage", True)
-        transfer_message = node_data.get("transferMessage", "Transferindo para um agente humano...")
-
-        logger.info(f"   Tipo de handoff: {handoff_type}")
-        logger.info(f"   Prioridade: {priority}")
-
-        # Determinar queue_id baseado no handoffType
-        final_queue_id = None
-        final_agent_id = None
-
-        if handoff_type == "queue" and queue_id:
-            # Transferir para fila específica
-            try:
-                final_queue_id = UUID(queue_id) if isinstance(queue_id, str) else queue_id
-                logger.info(f"   Transferindo para fila: {final_queue_id}")
-            except (ValueError, AttributeError) as e:
-                logger.error(f"   ❌ queueId inválido: {queue_id} - {e}")
-
-        elif handoff_type == "department" and department_id:
-            # Buscar fila principal do departamento
-            try:
-                dept_id_uuid = UUID(department_id) if isinstance(department_id, str) else department_id
-                queue_repo = QueueRepository(self.db)
-
-                # Buscar primeira fila ativa do departamento (ordenada por prioridade)
-                queues = await queue_repo.list_queues(
-                    organization_id=conversation.organization_id,
-                    department_id=dept_id_uuid,
-                    is_active=True,
-                    limit=1
-                )
-
-                if queues and len(queues) > 0:
-                    final_queue_id = queues[0].id
-                    logger.info(f"   Fila do departamento encontrada: {final_queue_id} ({queues[0].name})")
-                else:
-                    logger.warning(f"   ⚠️ Nenhuma fila ativa encontrada para departamento {dept_id_uuid}")
-                    logger.warning(f"   Transferindo para fila geral")
-
-            except (ValueError, AttributeError) as e:
-                logger.error(f"   ❌ departmentId inválido: {department_id} - {e}")
-
-        elif handoff_type == "agent" and agent_id:
-            # Transferir diretamente para agente específico
-            try:
-                final_agent_id = UUID(agent_id) if isinstance(agent_id, str) else agent_id
-                logger.info(f"   Transferindo para agente: {final_agent_id}")
-            except (ValueError, AttributeError) as e:
-                logger.error(f"   ❌ agentId inválido: {agent_id} - {e}")
+        delay_seconds = node_data.get("delaySeconds", 3)
+        delay_message = node_data.get("delayMessage")
 
-        # Enviar mensagem de transferência (se configurado)
-        if send_transfer_message and transfer_message:
+        # Validar delay (máximo 60 segundos para evitar bloqueios)
+        if delay_seconds > 60:
+            logger.warning(f"Delay de {delay_seconds}s reduzido para 60s (máximo permitido)")
+            delay_seconds = 60
+
+        # Enviar mensagem de espera (opcional)
+        if delay_message:
             whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
 
             if whatsapp_number.connection_type == "official":
-                # Meta Cloud API
-                from app.integrations.meta_api import MetaCloudAPI
-
-                meta_api = MetaCloudAPI(
-                    phone_number_id=whatsapp_number.phone_number_id,
-                    access_token=whatsapp_number.access_token
-                )
-
+                meta_api = self._guarded(whatsapp_clients.client(whatsapp_number))
                 contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
 
                 try:
-                    await meta_api.send_text_message(
-                        to=contact_whatsapp_id,
-                        text=transfer_message
-                    )
-                    logger.info(f"✅ Mensagem de transferência enviada via Meta API")
+                    await meta_api.send_text_message(to=contact_whatsapp_id, text=delay_message)
+                    logger.info(f"✅ Mensagem de delay enviada via Meta API")
                 except Exception as e:
-                    logger.error(f"❌ Erro ao enviar mensagem de transferência: {e}")
+                    logger.error(f"❌ Erro ao enviar mensagem de delay: {e}")
 
             elif whatsapp_number.connection_type == "qrcode":
-                # Evolution API
                 from app.integrations.evolution_api import EvolutionAPIClient
-
-                evolution = EvolutionAPIClient(
+                evolution = self._guarded(EvolutionAPIClient(
                     api_url=whatsapp_number.evolution_api_url,
                     api_key=whatsapp_number.evolution_api_key
-                )
-
+                ))
                 contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
 
                 try:
                     await evolution.send_text_message(
                         instance_name=whatsapp_number.evolution_instance_name,
                         to=contact_whatsapp_id,
-                        text=transfer_message
+                        text=delay_message
                     )
-                    logger.info(f"✅ Mensagem de transferência enviada via Evolution API")
+                    logger.info(f"✅ Mensagem de delay enviada via Evolution API")
                 except Exception as e:
-                    logger.error(f"❌ Erro ao enviar mensagem de transferência: {e}")
+                    logger.error(f"❌ Erro ao enviar mensagem de delay: {e}")
 
             # Salvar mensagem no banco
             from app.repositories.conversation import MessageRepository
@@ -1075,372 +2535,483 @@ class WhatsAppService:
                 "direction": "outbound",
                 "sender_type": "bot",
                 "message_type": "text",
-                "content": {"text": transfer_message},
+                "content": {"text": delay_message},
                 "status": "sent",
                 "sent_at": datetime.utcnow(),
             }
             await message_repo.create(message_data)
             await self.db.commit()
 
-        # Atualizar conversa: desativar bot e atribuir à fila ou agente
-        conv_repo = ConversationRepository(self.db)
-        from app.services.conversation_service import ConversationService
-        from datetime import datetime
+        # Aguardar o delay
+        logger.info(f"⏳ Aguardando {delay_seconds} segundos...")
+        await asyncio.sleep(delay_seconds)
+        logger.info(f"✅ Delay de {delay_seconds}s concluído")
 
-        # Mapear prioridade textual para prioridade numérica da fila
-        priority_map = {
-            "low": 10,
-            "normal": 50,
-            "medium": 50,  # Aceita "medium" como alias de "normal"
-            "high": 80,
-            "urgent": 100,
-        }
-        queue_priority = priority_map.get(str(priority).lower(), 50)
+        # Avançar para próximo node
+        await self._advance_to_next_node(conversation, node, flow, incoming_message)
 
+    async def _execute_wait(self, conversation, node, flow, incoming_message, node_data):
+        """
+        Executa um Wait Node - pausa o fluxo por minutos/horas/dias ou até uma
+        data (formato em app.utils.flow_wait).
+
+        A retomada fica gravada em flow_waits e é feita pela task
+        resume_flow_waits, então sobrevive a reinícios. Ao contrário do Delay
+        Node, nada fica bloqueado enquanto o fluxo espera.
+
+        Args:
+            conversation: Instância da conversa
+            node: Wait Node
+            flow: Flow atual
+            incoming_message: Mensagem que originou a execução
+            node_data: Dados do Wait Node
+        """
         try:
-            if final_agent_id:
-                # Transferência direta para agente
-                logger.info(f"   Atribuindo conversa diretamente ao agente {final_agent_id}")
+            resume_at = wait_resume_at(node_data, conversation.context_variables or {})
+        except ValueError as e:
+            # Espera inválida: o fluxo segue sem pausar
+            logger.error(f"❌ Wait Node {node.node_id}: {e}")
+            await self._advance_to_next_node(conversation, node, flow, incoming_message)
+            return
 
-                await conv_repo.update(
-                    conversation.id,
-                    {
-                        "is_bot_active": False,
-                        "status": "active",
-                        "current_agent_id": final_agent_id,
-                        "queued_at": None,
-                        "queue_priority": queue_priority,
-                    },
-                )
+        wait_repo = FlowWaitRepository(self.db)
+        await wait_repo.cancel_pending(conversation.id)
+        await wait_repo.create({
+            "organization_id": conversation.organization_id,
+            "conversation_id": conversation.id,
+            "flow_id": flow.id,
+            "node_id": node.id,
+            "resume_at": resume_at,
+        })
 
-                # Salvar contexto em extra_data
-                if context_message:
-                    extra_data = conversation.extra_data or {}
-                    extra_data["handoff_context"] = context_message
-                    await conv_repo.update(conversation.id, {"extra_data": extra_data})
+        logger.info(f"⏸️ Fluxo pausado até {resume_at.isoformat()} (conversa {conversation.id})")
 
-            elif final_queue_id:
-                # Transferência para fila específica (com overflow)
-                logger.info(f"   Atribuindo conversa à fila {final_queue_id} (com overflow)")
+    async def _reply_during_wait(self, conversation, node, flow, incoming_message):
+        """
+        Mensagem do contato enquanto o fluxo está em um Wait Node.
 
-                conv_service = ConversationService(self.db)
-                await conv_service.assign_to_queue_with_overflow(
-                    conversation_id=conversation.id,
-                    queue_id=final_queue_id,
-                    organization_id=conversation.organization_id,
-                )
+        Com resumeOnReply a espera é cancelada e o fluxo segue na hora; sem
+        ele a mensagem fica só no histórico e a espera continua.
+        """
+        if not (node.data or {}).get("resumeOnReply"):
+            logger.info(f"⏸️ Conversa {conversation.id} aguardando Wait Node, resposta não retoma o fluxo")
+            return
 
-                # Atualizar prioridade e desativar bot
-                update_data = {
-                    "queue_priority": queue_priority,
-                    "is_bot_active": False,
-                }
+        await FlowWaitRepository(self.db).cancel_pending(conversation.id)
+        await self.db.commit()
 
-                # Salvar contexto em extra_data
-                if context_message:
-                    extra_data = conversation.extra_data or {}
-                    extra_data["handoff_context"] = context_message
-                    update_data["extra_data"] = extra_data
+        logger.info(f"▶️ Contato respondeu, retomando fluxo do Wait Node {node.node_id}")
+        await self._advance_to_next_node(conversation, node, flow, incoming_message)
 
-                await conv_repo.update(conversation.id, update_data)
+    async def resume_due_waits(self, limit: int = 100) -> Dict[str, int]:
+        """
+        Retoma os fluxos cujos Wait Nodes venceram (task resume_flow_waits).
 
-            else:
-                # Sem fila nem agente específico, marca como aguardando atendimento geral
-                logger.warning(f"   ⚠️ Handoff sem fila ou agente específico, colocando em fila geral")
+        Cada espera é marcada como retomada antes de o fluxo seguir, então
+        nunca é executada duas vezes. Esperas de conversas que já saíram do
+        Wait Node (handoff, novo fluxo, bot desativado) são canceladas.
 
-                update_data = {
-                    "is_bot_active": False,
-                    "status": "queued",
-                    "queued_at": datetime.utcnow(),
-                    "queue_priority": queue_priority,
-                }
+        Returns:
+            Contagem de esperas retomadas, canceladas e com falha
+        """
+        from datetime import datetime, timezone
+        from app.repositories.conversation import ConversationRepository
+        from app.services.chatbot_service import ChatbotService
 
-                # Salvar contexto em extra_data
-                if context_message:
-                    extra_data = conversation.extra_data or {}
-                    extra_data["handoff_context"] = context_message
-                    update_data["extra_data"] = extra_data
+        wait_repo = FlowWaitRepository(self.db)
+        waits = await wait_repo.claim_due(datetime.now(timezone.utc), limit)
+        await self.db.commit()
 
-                await conv_repo.update(conversation.id, update_data)
+        chatbot_service = ChatbotService(self.db)
+        conv_repo = ConversationRepository(self.db)
+        counts = {"resumed": 0, "cancelled": 0, "failed": 0}
 
-            await self.db.commit()
+        for wait in waits:
+            conversation = await conv_repo.get_with_contact(wait.conversation_id, wait.organization_id)
+            node = await chatbot_service.node_repo.get(wait.node_id)
+            flow = await chatbot_service.flow_repo.get(wait.flow_id)
 
-        except Exception as e:
-            logger.error(f"❌ Erro ao aplicar handoff: {e}")
-            import traceback
-            logger.error(traceback.format_exc())
+            try:
+                if not conversation or not node or not flow:
+                    wait.status = "cancelled"
+                    await self.db.commit()
+                    counts["cancelled"] += 1
+                    continue
+
+                async with self._flow_session(conversation):
+                    if (
+                        not conversation.is_bot_active
+                        or conversation.active_flow_id != wait.flow_id
+                        or conversation.current_node_id != wait.node_id
+                    ):
+                        wait.status = "cancelled"
+                        await self.db.commit()
+                        counts["cancelled"] += 1
+                        continue
 
-        logger.info(
-            f"✅ Handoff completo: conversa {conversation.id} "
-            f"transferida para fila (prioridade: {priority})"
-        )
+                    logger.info(f"▶️ Retomando fluxo {flow.name} do Wait Node {node.node_id} (conversa {conversation.id})")
+                    await self._advance_to_next_node(conversation, node, flow, None)
+                    counts["resumed"] += 1
+            except Exception as e:
+                await self.db.rollback()
+                logger.error(f"❌ Erro ao retomar Wait Node da conversa {wait.conversation_id}: {e}")
+                counts["failed"] += 1
 
-        # Finalizar fluxo do bot
-        await self._finalize_flow(conversation)
+        return counts
 
-    async def _validate_user_response(self, user_text: str, node_data: Dict[str, Any]) -> tuple[bool, Optional[str]]:
-        """
-        Valida a resposta do usuário baseado no responseType do Question Node.
+    async def _flow_timeout_settings(self, conversation) -> FlowTimeoutSettings:
+        """Timeout da sessão configurado no chatbot ativo (settings["session_timeout"])"""
+        from app.services.chatbot_service import ChatbotService
 
-        Args:
-            user_text: Texto da resposta do usuário
-            node_data: Dados do Question Node (com responseType e validation)
+        if not conversation.active_chatbot_id:
+            return FlowTimeoutSettings()
+        chatbot = await ChatbotService(self.db).chatbot_repo.get(conversation.active_chatbot_id)
+        return FlowTimeoutSettings.from_settings(chatbot.settings if chatbot else None)
 
-        Returns:
-            tuple: (is_valid, error_message)
-                - is_valid: True se resposta é válida, False caso contrário
-                - error_message: Mensagem de erro (None se válido)
+    async def _flow_guard_settings(self, conversation) -> FlowGuardSettings:
+        """Proteções do fluxo configuradas no chatbot ativo (settings["flow_guard"])"""
+        from app.services.chatbot_service import ChatbotService
 
-        Tipos suportados:
-            - text: Qualquer texto (sempre válido)
-            - number: Apenas números (inteiros ou decimais)
-            - email: Formato de email válido
-            - phone: Formato de telefone válido (mínimo 10 dígitos)
-            - options: Escolha múltipla (deve estar na lista de opções)
-        """
-        import re
+        if not conversation.active_chatbot_id:
+            return FlowGuardSettings()
+        chatbot = await ChatbotService(self.db).chatbot_repo.get(conversation.active_chatbot_id)
+        return FlowGuardSettings.from_settings(chatbot.settings if chatbot else None)
 
-        response_type = node_data.get("responseType", "text")
-        validation = node_data.get("validation", {})
-        is_required = validation.get("required", True)
-        custom_error_message = validation.get("errorMessage")
+    async def _navigation_settings(self, conversation) -> NavigationSettings:
+        """Navegação "voltar" configurada no chatbot ativo (settings["navigation"])"""
+        from app.services.chatbot_service import ChatbotService
 
-        # Verificar se campo é obrigatório e está vazio
-        if is_required and not user_text.strip():
-            return False, custom_error_message or "Por favor, digite uma resposta."
+        if not conversation.active_chatbot_id:
+            return NavigationSettings()
+        chatbot = await ChatbotService(self.db).chatbot_repo.get(conversation.active_chatbot_id)
+        return NavigationSettings.from_settings(chatbot.settings if chatbot else None)
 
-        # Se não é obrigatório e está vazio, aceitar
-        if not is_required and not user_text.strip():
-            return True, None
+    async def _back_button(self, conversation, flow, node_data: Dict[str, Any]) -> Optional[Dict[str, str]]:
+        """Botão "Voltar" da pergunta, se a navegação usa botão e há pergunta anterior neste fluxo"""
+        if node_data.get("allowBack") is False:
+            return None
+        history = (conversation.context_variables or {}).get(NAVIGATION_HISTORY_KEY) or []
+        if not history or history[-1].get("flow_id") != str(flow.id):
+            return None
+        navigation = await self._navigation_settings(conversation)
+        if not navigation.enabled or not navigation.back_button:
+            return None
+        return {"id": BACK_BUTTON_ID, "title": navigation.back_button_title}
 
-        # Validar baseado no tipo
-        if response_type == "text":
-            # Texto sempre válido (se não vazio)
-            return True, None
+    async def _navigate_back(self, conversation, current_node, flow, new_message) -> bool:
+        """
+        Volta para a pergunta anterior se a mensagem pede (palavra-chave ou botão "Voltar").
 
-        elif response_type == "options":
-            # Validar se resposta está na lista de opções
-            options = node_data.get("options", [])
+        As variáveis voltam a ser as de quando a pergunta foi feita e ela é
+        enviada de novo. Sem pergunta anterior no fluxo, o contato é avisado e
+        continua na pergunta atual.
 
-            if not options:
-                logger.warning("Question Node com responseType 'options' mas sem opções definidas")
-                return True, None  # Aceitar qualquer resposta se não há opções
+        Returns:
+            True se a mensagem era um pedido de voltar (e não deve ser tratada como resposta)
+        """
+        navigation = await self._navigation_settings(conversation)
+        if not is_back_request(new_message.content if new_message else None, navigation):
+            return False
 
-            # Normalizar resposta do usuário (lowercase, sem espaços)
-            user_normalized = user_text.strip().lower()
+        node_data = await self._localized_node_data(conversation, current_node, flow)
+        if node_data.get("allowBack") is False:
+            return False
 
-            # Verificar se resposta corresponde a alguma opção (por valor ou label)
-            for option in options:
-                option_value = str(option.get("value", "")).strip().lower()
-                option_label = str(option.get("label", "")).strip().lower()
+        entry, context_vars = pop_history(dict(conversation.context_variables or {}), flow.id)
+        previous_node = None
+        if entry:
+            from app.services.chatbot_service import ChatbotService
 
-                if user_normalized == option_value or user_normalized == option_label:
-                    return True, None
+            previous_node = await ChatbotService(self.db).node_repo.get(UUID(entry["node_id"]))
 
-            # Resposta não encontrada nas opções
-            options_text = ", ".join([f"'{opt.get('label')}'" for opt in options if opt.get('label')])
-            default_error = f"Por favor, escolha uma das opções: {options_text}"
+        if not previous_node:
+            logger.info(f"↩️ Conversa {conversation.id} pediu para voltar sem pergunta anterior")
+            await self._send_error_message(conversation, navigation.no_history_message)
+            return True
 
-            return False, custom_error_message or default_error
+        await self._set_session(conversation, {
+            "current_node_id": previous_node.id,
+            "context_variables": context_vars,
+        })
+        await self.db.commit()
+        conversation.current_node_id = previous_node.id
+        conversation.context_variables = context_vars
 
-        elif response_type == "number":
-            # Verificar se é número
-            try:
-                float(user_text.strip().replace(",", "."))
-                return True, None
-            except ValueError:
-                return False, custom_error_message or "Por favor, digite um número válido."
+        logger.info(f"↩️ Conversa {conversation.id} voltou do node {current_node.node_id} para {previous_node.node_id}")
+        await self._execute_node(conversation, previous_node, flow, new_message)
+        return True
 
-        elif response_type == "email":
-            # Validação básica de email
-            email_pattern = r'^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$'
-            if re.match(email_pattern, user_text.strip()):
-                return True, None
-            else:
-                return False, custom_error_message or "Por favor, digite um e-mail válido."
+    async def _flow_guard_allows(self, conversation, guard: FlowGuardSettings, kind: str) -> bool:
+        """
+        Conta um fluxo iniciado ou uma mensagem do fluxo para o contato.
 
-        elif response_type == "phone":
-            # Remover caracteres especiais e validar telefone
-            phone_digits = re.sub(r'\D', '', user_text)
+        Returns:
+            False se o contato passou do limite da hora ou do dia (sem Redis, sempre True)
+        """
+        allowed = True
+        try:
+            for key, ttl, limit in rate_windows(kind, conversation.contact_id, datetime.now(timezone.utc), guard):
+                count = await rate_limit_redis.incr(key)
+                if count == 1:
+                    await rate_limit_redis.expire(key, ttl)
+                allowed = allowed and count <= limit
+        except Exception as e:
+            logger.warning(f"⚠️ Proteção de {kind} indisponível para a conversa {conversation.id}: {e}")
+            return True
+        return allowed
 
-            if len(phone_digits) >= 10:  # Mínimo 10 dígitos (DDD + número)
-                return True, None
-            else:
-                return False, custom_error_message or "Por favor, digite um telefone válido."
+    async def _trip_flow_guard(
+        self, conversation, guard: FlowGuardSettings, reason: str, send_fallback_message: bool = False
+    ):
+        """
+        Interrompe o fluxo que disparou uma proteção (loop ou limite por contato).
 
-        else:
-            # Tipo desconhecido - aceitar como text
-            logger.warning(f"Tipo de resposta desconhecido: {response_type}, aceitando como texto")
-            return True, None
+        Loops enviam a mensagem de fallback; limites não enviam mais nada ao
+        contato. A conversa vai para um atendente (fallback "handoff") ou o
+        fluxo apenas termina ("end").
+        """
+        logger.error(f"🚫 Fluxo interrompido na conversa {conversation.id}: {reason}")
 
-    async def _send_error_message(self, conversation, error_text: str):
+        if send_fallback_message:
+            await self._send_error_message(conversation, guard.fallback_message)
+
+        if guard.fallback == FALLBACK_END:
+            await self._finalize_flow(conversation)
+            return
+
+        await self._execute_handoff(conversation, {
+            "transferMessage": f"Transferência automática: {reason}.",
+            "priority": "high",
+            "sendTransferMessage": False,
+        })
+
+    async def _refresh_flow_timeout(self, conversation):
         """
-        Envia mensagem de erro para o usuário via WhatsApp.
+        Renova o timeout por inatividade quando o contato responde.
 
-        Args:
-            conversation: Instância da conversa
-            error_text: Texto da mensagem de erro
+        A resposta também rearma o fallback: se o contato voltar a ficar em
+        silêncio, o fallback roda de novo antes de o fluxo ser finalizado.
         """
-        logger.info(f"📮 Enviando mensagem de erro: {error_text}")
+        timeouts = await self._flow_timeout_settings(conversation)
+        update_data = {"flow_timeout_at": timeouts.timeout_at(datetime.now(timezone.utc))}
 
-        whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+        context_vars = dict(conversation.context_variables or {})
+        if context_vars.pop(FLOW_TIMEOUT_FIRED_KEY, None):
+            update_data["context_variables"] = context_vars
+            conversation.context_variables = context_vars
 
-        if whatsapp_number.connection_type == "official":
-            # Meta Cloud API
-            from app.integrations.meta_api import MetaCloudAPI
+        await self._set_session(conversation, update_data)
+        await self.db.commit()
 
-            meta_api = MetaCloudAPI(
-                phone_number_id=whatsapp_number.phone_number_id,
-                access_token=whatsapp_number.access_token
-            )
+    async def expire_flow_sessions(self, limit: int = 100) -> Dict[str, int]:
+        """
+        Aplica o timeout das sessões de flow (task expire_flow_sessions).
 
-            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+        Sessões sem resposta do contato há timeout_minutes, ou que passaram de
+        max_duration_minutes, executam o fallback do chatbot. Ver
+        app.utils.flow_timeout.
+
+        Returns:
+            Contagem de sessões com fallback, finalizadas, adiadas e com falha
+        """
+        from app.repositories.conversation import ConversationRepository
+
+        conv_repo = ConversationRepository(self.db)
+        claimed = await conv_repo.claim_flow_timeouts(datetime.now(timezone.utc), limit)
+        await self.db.commit()
 
+        counts = {"fallback": 0, "finished": 0, "postponed": 0, "failed": 0}
+        for claimed_conversation, reason in claimed:
             try:
-                await meta_api.send_text_message(
-                    to=contact_whatsapp_id,
-                    text=error_text
+                conversation = await conv_repo.get_with_contact(
+                    claimed_conversation.id, claimed_conversation.organization_id
                 )
-                logger.info(f"✅ Mensagem de erro enviada via Meta API")
+                async with self._flow_session(conversation):
+                    counts[await self._run_flow_timeout(conversation, reason)] += 1
             except Exception as e:
-                logger.error(f"❌ Erro ao enviar mensagem de erro: {e}")
-                return
+                await self.db.rollback()
+                logger.error(f"❌ Erro ao aplicar timeout do flow na conversa {claimed_conversation.id}: {e}")
+                counts["failed"] += 1
 
-        elif whatsapp_number.connection_type == "qrcode":
-            # Evolution API
-            from app.integrations.evolution_api import EvolutionAPIClient
+        return counts
 
-            evolution = EvolutionAPIClient(
-                api_url=whatsapp_number.evolution_api_url,
-                api_key=whatsapp_number.evolution_api_key
-            )
+    async def resume_interrupted_flows(self, limit: int = 50) -> Dict[str, int]:
+        """
+        Retoma nodes interrompidos no meio da execução (task resume_interrupted_flows).
 
-            contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+        Conversas cujo checkpoint ficou "executando" (ou com o próximo node
+        pendente) por mais de STALE_AFTER_SECONDS executam o node atual de
+        novo com a mesma mensagem de entrada; os envios já feitos são
+        pulados. Ver app.utils.flow_checkpoint.
+
+        Returns:
+            Contagem de nodes retomados, abandonados e com falha
+        """
+        from app.repositories.conversation import ConversationRepository
+
+        conv_repo = ConversationRepository(self.db)
+        now = datetime.now(timezone.utc)
+        claimed = await conv_repo.claim_interrupted_nodes(
+            now - timedelta(seconds=CHECKPOINT_STALE_AFTER_SECONDS), now, limit
+        )
+        await self.db.commit()
 
+        counts = {"resumed": 0, "given_up": 0, "failed": 0}
+        for claimed_conversation in claimed:
             try:
-                await evolution.send_text_message(
-                    instance_name=whatsapp_number.evolution_instance_name,
-                    to=contact_whatsapp_id,
-                    text=error_text
+                conversation = await conv_repo.get_with_contact(
+                    claimed_conversation.id, claimed_conversation.organization_id
                 )
-                logger.info(f"✅ Mensagem de erro enviada via Evolution API")
+                async with self._flow_session(conversation):
+                    # Outro processo concluiu o node enquanto o lock era aguardado
+                    if conversation.node_checkpoint_at is None:
+                        continue
+                    counts[await self._resume_interrupted_node(conversation)] += 1
             except Exception as e:
-                logger.error(f"❌ Erro ao enviar mensagem de erro: {e}")
-                return
-
-        # Salvar mensagem no banco
-        from app.repositories.conversation import MessageRepository
-        from datetime import datetime
+                await self.db.rollback()
+                logger.error(f"❌ Erro ao retomar node interrompido na conversa {claimed_conversation.id}: {e}")
+                counts["failed"] += 1
 
-        message_repo = MessageRepository(self.db)
-        message_data = {
-            "organization_id": conversation.organization_id,
-            "conversation_id": conversation.id,
-            "whatsapp_number_id": whatsapp_number.id,
-            "direction": "outbound",
-            "sender_type": "bot",
-            "message_type": "text",
-            "content": {"text": error_text},
-            "status": "sent",
-            "sent_at": datetime.utcnow(),
-        }
-        await message_repo.create(message_data)
-        await self.db.commit()
+        return counts
 
-    async def _execute_delay(self, conversation, node, flow, incoming_message, node_data):
+    async def _resume_interrupted_node(self, conversation) -> str:
         """
-        Executa um Delay Node - aguarda X segundos antes de avançar para o próximo node.
+        Executa de novo o node atual de uma conversa com checkpoint interrompido.
 
-        Args:
-            conversation: Instância da conversa
-            node: Node atual (Delay)
-            flow: Flow ativo
-            incoming_message: Mensagem que originou a execução
-            node_data: Dados do Delay Node
+        Um node interrompido MAX_RESUMES vezes não é executado de novo: a
+        conversa é transferida para um agente.
 
-        Formato esperado do node_data:
-        {
-            "delaySeconds": 5,  # Tempo em segundos (padrão: 3)
-            "delayMessage": "Aguarde um momento..."  # Opcional
-        }
+        Returns:
+            "resumed" ou "given_up"
         """
-        import asyncio
+        from app.repositories.conversation import MessageRepository
+        from app.services.chatbot_service import ChatbotService
+
+        chatbot_service = ChatbotService(self.db)
+        checkpoint = conversation.node_checkpoint or {}
+        now = datetime.now(timezone.utc)
+
+        node = await chatbot_service.node_repo.get(conversation.current_node_id) if conversation.current_node_id else None
+        flow = await chatbot_service.flow_repo.get(conversation.active_flow_id) if node else None
+        if not node or not flow:
+            logger.warning(f"⚠️ Node do checkpoint da conversa {conversation.id} não existe mais")
+            await self._save_checkpoint(
+                conversation, complete_checkpoint(checkpoint, checkpoint.get("node_id"), {"next_node_id": None}, now)
+            )
+            return "given_up"
 
-        logger.info(f"⏰ Executando Delay Node")
+        if is_executing(checkpoint) and checkpoint.get("node_id") == node.node_id and should_give_up(checkpoint):
+            logger.error(
+                f"❌ Node {node.node_id} interrompido {checkpoint.get('resumes', 0)} vezes na conversa "
+                f"{conversation.id}, transferindo para um agente"
+            )
+            await self._save_checkpoint(
+                conversation, complete_checkpoint(checkpoint, node.node_id, {"next_node_id": None, "given_up": True}, now)
+            )
+            await self._execute_handoff(conversation, {"sendTransferMessage": False, "priority": "high"})
+            return "given_up"
 
-        # Extrair configurações
-        delay_seconds = node_data.get("delaySeconds", 3)
-        delay_message = node_data.get("delayMessage")
+        incoming_message = None
+        if checkpoint.get("message_id"):
+            try:
+                incoming_message = await MessageRepository(self.db).get(UUID(checkpoint["message_id"]))
+            except ValueError:
+                incoming_message = None
 
-        # Validar delay (máximo 60 segundos para evitar bloqueios)
-        if delay_seconds > 60:
-            logger.warning(f"Delay de {delay_seconds}s reduzido para 60s (máximo permitido)")
-            delay_seconds = 60
+        logger.info(
+            f"♻️ Retomando node {node.node_id} na conversa {conversation.id} "
+            f"({checkpoint.get('sent', 0)} envio(s) já feito(s))"
+        )
+        await self._execute_node(conversation, node, flow, incoming_message, resume=True)
+        return "resumed"
 
-        # Enviar mensagem de espera (opcional)
-        if delay_message:
-            whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+    async def _run_flow_timeout(self, conversation, reason: str) -> str:
+        """
+        Executa o fallback de uma sessão expirada ou finaliza o fluxo.
 
-            if whatsapp_number.connection_type == "official":
-                from app.integrations.meta_api import MetaCloudAPI
-                meta_api = MetaCloudAPI(
-                    phone_number_id=whatsapp_number.phone_number_id,
-                    access_token=whatsapp_number.access_token
-                )
-                contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+        O primeiro timeout executa o fallback_node_id (node do flow em execução)
+        ou o fallback_flow_id (ex: "ainda está aí?"); um novo timeout antes de
+        o contato responder, ou a falta de fallback, envia a timeout_message e
+        finaliza o fluxo (fechando a conversa com close_conversation).
 
-                try:
-                    await meta_api.send_text_message(to=contact_whatsapp_id, text=delay_message)
-                    logger.info(f"✅ Mensagem de delay enviada via Meta API")
-                except Exception as e:
-                    logger.error(f"❌ Erro ao enviar mensagem de delay: {e}")
+        Args:
+            conversation: Conversa (com contato) reivindicada pela task
+            reason: "timeout" (sem resposta) ou "expired" (duração máxima)
 
-            elif whatsapp_number.connection_type == "qrcode":
-                from app.integrations.evolution_api import EvolutionAPIClient
-                evolution = EvolutionAPIClient(
-                    api_url=whatsapp_number.evolution_api_url,
-                    api_key=whatsapp_number.evolution_api_key
-                )
-                contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
+        Returns:
+            "fallback", "finished" ou "postponed" (Wait Node ainda aguardando)
+        """
+        from app.services.chatbot_service import ChatbotService
 
-                try:
-                    await evolution.send_text_message(
-                        instance_name=whatsapp_number.evolution_instance_name,
-                        to=contact_whatsapp_id,
-                        text=delay_message
-                    )
-                    logger.info(f"✅ Mensagem de delay enviada via Evolution API")
-                except Exception as e:
-                    logger.error(f"❌ Erro ao enviar mensagem de delay: {e}")
+        chatbot_service = ChatbotService(self.db)
+        timeouts = await self._flow_timeout_settings(conversation)
+        now = datetime.now(timezone.utc)
 
-            # Salvar mensagem no banco
-            from app.repositories.conversation import MessageRepository
-            from datetime import datetime
+        current_node = None
+        if conversation.current_node_id:
+            current_node = await chatbot_service.node_repo.get(conversation.current_node_id)
 
-            message_repo = MessageRepository(self.db)
-            message_data = {
-                "organization_id": conversation.organization_id,
-                "conversation_id": conversation.id,
-                "whatsapp_number_id": whatsapp_number.id,
-                "direction": "outbound",
-                "sender_type": "bot",
-                "message_type": "text",
-                "content": {"text": delay_message},
-                "status": "sent",
-                "sent_at": datetime.utcnow(),
+        # Wait Nodes não esperam resposta do contato: só a duração máxima vale
+        if reason == "timeout" and current_node and current_node.node_type == "wait":
+            await self._set_session(conversation, {"flow_timeout_at": timeouts.timeout_at(now)})
+            await self.db.commit()
+            return "postponed"
+
+        context_vars = dict(conversation.context_variables or {})
+        fallback_node = fallback_flow = None
+        if not context_vars.get(FLOW_TIMEOUT_FIRED_KEY):
+            if timeouts.fallback_node_id:
+                fallback_node = await self._find_node(
+                    conversation.active_flow_id,
+                    timeouts.fallback_node_id,
+                    conversation.organization_id,
+                    current_node.flow_version_id if current_node else None,
+                )
+            if not fallback_node and timeouts.fallback_flow_id:
+                try:
+                    fallback_flow = await chatbot_service.flow_repo.get(UUID(str(timeouts.fallback_flow_id)))
+                except ValueError:
+                    fallback_flow = None
+                if fallback_flow and fallback_flow.organization_id != conversation.organization_id:
+                    fallback_flow = None
+
+        if fallback_node or fallback_flow:
+            logger.info(f"⏰ Sessão do flow com {reason} na conversa {conversation.id}, executando fallback")
+            context_vars[FLOW_TIMEOUT_FIRED_KEY] = True
+            update_data = {
+                "context_variables": context_vars,
+                "flow_timeout_at": timeouts.fallback_timeout_at(now),
+                "flow_expires_at": None,
             }
-            await message_repo.create(message_data)
+            if fallback_node:
+                update_data["current_node_id"] = fallback_node.id
+            else:
+                # O flow de fallback substitui a sessão: não volta a sub-flows nem a loops
+                context_vars.pop(SUBFLOW_STACK_KEY, None)
+                context_vars.pop(LOOP_STATE_KEY, None)
+            await self._set_session(conversation, update_data)
+            conversation.context_variables = context_vars
+            await FlowWaitRepository(self.db).cancel_pending(conversation.id)
             await self.db.commit()
 
-        # Aguardar o delay
-        logger.info(f"⏳ Aguardando {delay_seconds} segundos...")
-        await asyncio.sleep(delay_seconds)
-        logger.info(f"✅ Delay de {delay_seconds}s concluído")
+            if fallback_node:
+                flow = await chatbot_service.flow_repo.get(conversation.active_flow_id)
+                await self._execute_node(conversation, fallback_node, flow, None)
+            else:
+                await self._start_flow(conversation, fallback_flow, None)
+            return "fallback"
 
-        # Avançar para próximo node
-        await self._advance_to_next_node(conversation, node, flow, incoming_message)
+        logger.info(f"⏰ Sessão do flow com {reason} na conversa {conversation.id}, finalizando fluxo")
+        if timeouts.timeout_message:
+            await self._send_error_message(conversation, timeouts.timeout_message)
+        await self._finalize_flow(conversation)
+
+        if timeouts.close_conversation:
+            await self._set_session(conversation, {"status": "closed", "closed_at": now})
+            await self.db.commit()
+        return "finished"
 
-    async def _execute_jump(self, conversation, node_data, incoming_message):
+    async def _execute_jump(self, conversation, node_data, incoming_message, node=None):
         """
         Executa um Jump Node - pula para outro node ou flow.
 
@@ -1448,6 +3019,7 @@ class WhatsAppService:
             conversation: Instância da conversa
             node_data: Dados do Jump Node
             incoming_message: Mensagem que originou a execução
+            node: Jump Node (jumps para node ficam na mesma versão do flow)
 
         Formato esperado do node_data:
         {
@@ -1457,15 +3029,11 @@ class WhatsAppService:
         }
         """
         from app.services.chatbot_service import ChatbotService
-        from app.repositories.conversation import ConversationRepository
-        from app.models.chatbot import Node
-        from sqlalchemy import select
 
         logger.info(f"🔀 Executando Jump Node")
 
         jump_type = node_data.get("jumpType", "node")
         chatbot_service = ChatbotService(self.db)
-        conv_repo = ConversationRepository(self.db)
 
         if jump_type == "node":
             # Pular para node específico no flow atual
@@ -1475,21 +3043,20 @@ class WhatsAppService:
                 logger.error("❌ Jump Node sem targetNodeId configurado")
                 return
 
-            # Buscar node no flow atual
-            stmt = select(Node).where(
-                Node.flow_id == conversation.active_flow_id,
-                Node.node_id == target_node_canvas_id,
-                Node.organization_id == conversation.organization_id
+            # Buscar node no flow atual (mesma versão do Jump Node)
+            target_node = await self._find_node(
+                conversation.active_flow_id,
+                target_node_canvas_id,
+                conversation.organization_id,
+                node.flow_version_id if node else None,
             )
-            result = await self.db.execute(stmt)
-            target_node = result.scalar_one_or_none()
 
             if not target_node:
                 logger.error(f"❌ Node {target_node_canvas_id} não encontrado no flow atual")
                 return
 
             # Atualizar current_node_id
-            await conv_repo.update(conversation.id, {
+            await self._set_session(conversation, {
                 "current_node_id": target_node.id
             })
             await self.db.commit()
@@ -1517,10 +3084,12 @@ class WhatsAppService:
                 logger.error(f"❌ Flow {target_flow_id} não encontrado")
                 return
 
-            # Buscar start node do novo flow
+            # Buscar start node da versão publicada do novo flow
+            version_id = target_flow.published_version_id
             start_node = await chatbot_service.node_repo.get_start_node(
                 target_flow.id,
-                conversation.organization_id
+                conversation.organization_id,
+                version_id
             )
 
             if not start_node:
@@ -1528,8 +3097,7 @@ class WhatsAppService:
                 return
 
             # Encontrar primeiro node real (seguindo edge do start)
-            canvas_data = target_flow.canvas_data or {}
-            edges = canvas_data.get("edges", [])
+            edges = await self._flow_edges(target_flow, version_id)
             next_node_canvas_id = None
 
             for edge in edges:
@@ -1542,20 +3110,16 @@ class WhatsAppService:
                 return
 
             # Buscar próximo node
-            stmt = select(Node).where(
-                Node.flow_id == target_flow.id,
-                Node.node_id == next_node_canvas_id,
-                Node.organization_id == conversation.organization_id
+            first_node = await self._find_node(
+                target_flow.id, next_node_canvas_id, conversation.organization_id, version_id
             )
-            result = await self.db.execute(stmt)
-            first_node = result.scalar_one_or_none()
 
             if not first_node:
                 logger.error(f"❌ Node {next_node_canvas_id} não encontrado")
                 return
 
             # Atualizar flow e node
-            await conv_repo.update(conversation.id, {
+            await self._set_session(conversation, {
                 "active_flow_id": target_flow.id,
                 "current_node_id": first_node.id
             })
@@ -1569,6 +3133,109 @@ class WhatsAppService:
         else:
             logger.error(f"❌ Tipo de jump desconhecido: {jump_type}")
 
+    async def _execute_subflow(self, conversation, node, flow, incoming_message, node_data):
+        """
+        Executa um Sub-flow Node - chama outro flow e, quando ele chega ao end
+        node, retorna e segue pela edge de saída do Sub-flow Node.
+
+        As variáveis de contexto são compartilhadas: o sub-flow lê e escreve
+        as mesmas variáveis do flow que o chamou.
+
+        Args:
+            conversation: Instância da conversa
+            node: Sub-flow Node
+            flow: Flow atual
+            incoming_message: Mensagem que originou a execução
+            node_data: Dados do Sub-flow Node
+
+        Formato esperado do node_data:
+        {
+            "targetFlowId": "uuid-do-flow"  # Flow chamado (ex: identificação, pesquisa de satisfação)
+        }
+        """
+        from app.services.chatbot_service import ChatbotService
+
+        context_vars = dict(conversation.context_variables or {})
+        stack = list(context_vars.get(SUBFLOW_STACK_KEY) or [])
+        target_flow_id = node_data.get("targetFlowId")
+
+        target_flow = None
+        if target_flow_id:
+            try:
+                target_flow = await ChatbotService(self.db).flow_repo.get(UUID(str(target_flow_id)))
+            except ValueError:
+                target_flow = None
+
+        error = None
+        if not target_flow or target_flow.organization_id != conversation.organization_id:
+            error = f"flow {target_flow_id} não encontrado"
+        elif len(stack) >= MAX_SUBFLOW_DEPTH:
+            error = f"limite de {MAX_SUBFLOW_DEPTH} sub-flows aninhados atingido"
+        elif str(target_flow.id) in {str(flow.id), *(frame["flow_id"] for frame in stack)}:
+            error = f"flow {target_flow.name} já está em execução (chamada recursiva)"
+
+        if error:
+            # Sub-flow ignorado: o flow atual segue pela edge de saída
+            logger.error(f"❌ Sub-flow Node {node.node_id}: {error}")
+            await self._advance_to_next_node(conversation, node, flow, incoming_message)
+            return
+
+        stack.append({
+            "flow_id": str(flow.id),
+            "node_id": node.node_id,
+            "version_id": str(node.flow_version_id) if node.flow_version_id else None,
+        })
+        context_vars[SUBFLOW_STACK_KEY] = stack
+        conversation.context_variables = context_vars
+
+        await self._set_session(conversation, {"context_variables": context_vars})
+        await self.db.commit()
+
+        logger.info(f"↪️ Chamando sub-flow {target_flow.name} (profundidade {len(stack)})")
+        await self._start_flow(conversation, target_flow, incoming_message)
+
+    async def _execute_loop(self, conversation, node, flow, incoming_message, node_data):
+        """
+        Executa um Loop Node - segue a saída "loop" a cada iteração e a saída
+        "done" quando os itens acabam, as condições são atendidas ou o limite
+        de iterações é atingido (formato em app.utils.flow_loop).
+
+        O último node do corpo deve ligar de volta ao Loop Node. A cada
+        iteração o caminho de execução volta ao ponto de entrada do loop, para
+        que a proteção contra loops infinitos conte só os nodes da iteração.
+
+        Args:
+            conversation: Instância da conversa
+            node: Loop Node
+            flow: Flow atual
+            incoming_message: Mensagem que originou a execução
+            node_data: Dados do Loop Node
+        """
+        condition_met = None
+        if node_data.get("loopType") == "condition":
+            condition_met = await self._evaluate_conditions(conversation, node_data)
+
+        context_vars = dict(conversation.context_variables or {})
+        execution_path = list(context_vars.get(EXECUTION_PATH_KEY) or [])
+        entry_path_length = ((context_vars.get(LOOP_STATE_KEY) or {}).get(node.node_id) or {}).get(
+            "path_length", len(execution_path)
+        )
+
+        run, detail = next_iteration(node.node_id, node_data, context_vars, condition_met)
+        if run:
+            context_vars[LOOP_STATE_KEY][node.node_id]["path_length"] = entry_path_length
+            context_vars[EXECUTION_PATH_KEY] = execution_path[:entry_path_length]
+        conversation.context_variables = context_vars
+
+        await self._set_session(conversation, {"context_variables": context_vars})
+        await self.db.commit()
+
+        logger.info(f"🔁 Loop Node {node.node_id}: {detail}")
+        await self._advance_to_next_node(
+            conversation, node, flow, incoming_message,
+            source_handle=LOOP_BODY_HANDLE if run else LOOP_DONE_HANDLE
+        )
+
     async def _send_media_message(self, conversation, node_data, media_type: str):
         """
         Envia mensagem de mídia (imagem, vídeo, documento, áudio) via WhatsApp.
@@ -1595,33 +3262,17 @@ class WhatsAppService:
             return
 
         # Substituir variáveis na URL e caption
-        import re
         context_vars = conversation.context_variables or {}
-
-        # Substituir variáveis no URL
-        variables = re.findall(r'\{\{(\w+)\}\}', media_url)
-        for var_name in variables:
-            if var_name in context_vars:
-                media_url = media_url.replace(f"{{{{{var_name}}}}}", str(context_vars[var_name]))
-
-        # Substituir variáveis no caption
+        media_url = render_text(media_url, context_vars)
         if caption:
-            variables = re.findall(r'\{\{(\w+)\}\}', caption)
-            for var_name in variables:
-                if var_name in context_vars:
-                    caption = caption.replace(f"{{{{{var_name}}}}}", str(context_vars[var_name]))
+            caption = render_text(caption, context_vars)
 
         # Enviar via WhatsApp
         whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
 
         if whatsapp_number.connection_type == "official":
             # Meta Cloud API
-            from app.integrations.meta_api import MetaCloudAPI
-
-            meta_api = MetaCloudAPI(
-                phone_number_id=whatsapp_number.phone_number_id,
-                access_token=whatsapp_number.access_token
-            )
+            meta_api = self._guarded(whatsapp_clients.client(whatsapp_number))
 
             contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
 
@@ -1665,10 +3316,10 @@ class WhatsAppService:
             # Evolution API
             from app.integrations.evolution_api import EvolutionAPIClient
 
-            evolution = EvolutionAPIClient(
+            evolution = self._guarded(EvolutionAPIClient(
                 api_url=whatsapp_number.evolution_api_url,
                 api_key=whatsapp_number.evolution_api_key
-            )
+            ))
 
             contact_whatsapp_id = conversation.contact.whatsapp_id.replace("+", "")
 
@@ -1738,6 +3389,14 @@ class WhatsAppService:
         await message_repo.create(message_data)
         await self.db.commit()
 
+    def _org_secret_resolver(self, conversation, flow) -> OrgSecretResolver:
+        """Resolver de {{org.secrets.X}} / {{org.vars.X}} para os nodes do flow"""
+        return OrgSecretResolver(
+            self.db,
+            conversation.organization_id,
+            chatbot_id=getattr(flow, "chatbot_id", None),
+        )
+
     async def _execute_action(self, conversation, node, flow, incoming_message, node_data):
         """
         Executa um Action Node - realiza ações automatizadas no fluxo.
@@ -1763,7 +3422,6 @@ class WhatsAppService:
         """
         import httpx
         import re
-        from app.repositories.conversation import ConversationRepository
         from app.repositories.contact import ContactRepository
 
         logger.info(f"⚡ Executando Action Node")
@@ -1777,6 +3435,7 @@ class WhatsAppService:
             return
 
         context_vars = conversation.context_variables or {}
+        resolver = self._org_secret_resolver(conversation, flow)
 
         # Executar cada ação sequencialmente
         for idx, action in enumerate(actions):
@@ -1824,19 +3483,41 @@ class WhatsAppService:
 
                     logger.info(f"  📡 Chamando webhook: {method} {url}")
 
-                    async with httpx.AsyncClient(timeout=timeout_seconds) as client:
-                        if method == "GET":
-                            response = await client.get(url, headers=headers)
-                        elif method == "POST":
-                            response = await client.post(url, headers=headers, json=body)
-                        elif method == "PUT":
-                            response = await client.put(url, headers=headers, json=body)
-                        elif method == "DELETE":
-                            response = await client.delete(url, headers=headers)
-                        else:
-                            logger.error(f"❌ Método HTTP não suportado: {method}")
-                            continue
+                    # Resolver {{org.secrets.X}} / {{org.vars.X}} (headers de autenticação, etc.)
+                    usage = SecretUsage.WEBHOOK.value
+                    url = await resolver.resolve(url, usage, "node", node.node_id)
+                    headers = await resolver.resolve(headers, usage, "node", node.node_id)
+                    body = await resolver.resolve(body, usage, "node", node.node_id)
+
+                    if method not in ("GET", "POST", "PUT", "DELETE"):
+                        logger.error(f"❌ Método HTTP não suportado: {method}")
+                        continue
 
+                    # Tentativa registrada no inbox de webhooks da organização (debug)
+                    inbox_source = f"flow:{flow.id}:node:{node.node_id}"
+                    started = time.monotonic()
+                    try:
+                        async with http_client("http_connector", timeout=timeout_seconds) as client:
+                            if method in ("POST", "PUT"):
+                                response = await client.request(method, url, headers=headers, json=body)
+                            else:
+                                response = await client.request(method, url, headers=headers)
+                    except Exception as e:
+                        await webhook_inbox.record_outbound(
+                            conversation.organization_id, method, url, headers, body,
+                            error=str(e) or e.__class__.__name__,
+                            duration_ms=int((time.monotonic() - started) * 1000),
+                            source=inbox_source,
+                        )
+                        raise
+
+                    await webhook_inbox.record_outbound(
+                        conversation.organization_id, method, url, headers, body,
+                        status_code=response.status_code,
+                        response_body=response.text,
+                        duration_ms=int((time.monotonic() - started) * 1000),
+                        source=inbox_source,
+                    )
                     logger.info(f"  ✅ Webhook respondeu: {response.status_code}")
 
                     # Salvar resposta em variável (se configurado)
@@ -1937,8 +3618,7 @@ class WhatsAppService:
                 # Continuar com próximas ações mesmo se uma falhar
 
         # Salvar context_variables atualizadas
-        conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
+        await self._set_session(conversation, {
             "context_variables": context_vars
         })
         await self.db.commit()
@@ -1977,16 +3657,24 @@ class WhatsAppService:
             },
             "timeout": 30,  # Segundos (padrão: 30)
             "responseVariable": "api_response",  # Nome da variável para salvar resposta
+            "responseMapping": {  # Opcional: campos da resposta (JSONPath) -> variáveis
+                "pedido_status": "$.order.status",
+                "primeiro_item": "$.order.items[0].name"
+            },
+            "statusVariable": "api_status",  # Opcional: status HTTP (null em timeout/erro de rede)
             "errorHandling": {
-                "onError": "continue",  # continue, stop, retry
-                "maxRetries": 3,
+                "onError": "continue",  # continue, stop (handoff) ou branch (saída "error")
+                "maxRetries": 3,  # Tentativas; 4xx (exceto 408/425/429) não são repetidos
                 "retryDelay": 2,
                 "fallbackValue": null
             }
         }
+
+        Com onError "branch" o node tem duas saídas: sourceHandle "success" e
+        "error". Os templates {{variavel}} valem também dentro de dicts e
+        listas do body (ver app.utils.http_request).
         """
         import httpx
-        import re
         import json
         from app.repositories.conversation import ConversationRepository
 
@@ -2001,6 +3689,8 @@ class WhatsAppService:
         timeout_seconds = node_data.get("timeout", 30)
         response_variable = node_data.get("responseVariable", "api_response")
         error_handling = node_data.get("errorHandling", {})
+        response_mapping = node_data.get("responseMapping") or {}
+        status_variable = node_data.get("statusVariable")
 
         if not url:
             logger.error("❌ API Call sem URL configurada")
@@ -2009,65 +3699,17 @@ class WhatsAppService:
 
         context_vars = conversation.context_variables or {}
 
-        # Substituir variáveis na URL
-        final_url = url
-        variables = re.findall(r'\{\{(\w+)\}\}', url)
-        for var_name in variables:
-            if var_name in context_vars:
-                final_url = final_url.replace(f"{{{{{var_name}}}}}", str(context_vars[var_name]))
-
-        # Substituir variáveis nos query params
-        final_query_params = {}
-        for key, value in query_params.items():
-            if isinstance(value, str):
-                variables = re.findall(r'\{\{(\w+)\}\}', value)
-                for var_name in variables:
-                    if var_name in context_vars:
-                        value = value.replace(f"{{{{{var_name}}}}}", str(context_vars[var_name]))
-            final_query_params[key] = value
-
-        # Substituir variáveis nos headers
-        final_headers = {}
-        for key, value in headers.items():
-            if isinstance(value, str):
-                variables = re.findall(r'\{\{(\w+)\}\}', value)
-                for var_name in variables:
-                    if var_name in context_vars:
-                        value = value.replace(f"{{{{{var_name}}}}}", str(context_vars[var_name]))
-            final_headers[key] = value
-
-        # Substituir variáveis no body
-        final_body = None
-        if body is not None:
-            if isinstance(body, str):
-                # Body como string (JSON ou texto)
-                variables = re.findall(r'\{\{(\w+)\}\}', body)
-                final_body = body
-                for var_name in variables:
-                    if var_name in context_vars:
-                        final_body = final_body.replace(
-                            f"{{{{{var_name}}}}}",
-                            str(context_vars[var_name])
-                        )
-                # Tentar parsear como JSON
-                try:
-                    final_body = json.loads(final_body)
-                except:
-                    pass  # Manter como string se não for JSON válido
-
-            elif isinstance(body, dict):
-                # Body como objeto - substituir variáveis nos valores
-                final_body = {}
-                for key, value in body.items():
-                    if isinstance(value, str):
-                        variables = re.findall(r'\{\{(\w+)\}\}', value)
-                        for var_name in variables:
-                            if var_name in context_vars:
-                                value = value.replace(
-                                    f"{{{{{var_name}}}}}",
-                                    str(context_vars[var_name])
-                                )
-                    final_body[key] = value
+        # Substituir variáveis na URL, query params, headers e body (inclusive aninhados)
+        final_url = render_template(url, context_vars)
+        final_query_params = render_template(query_params, context_vars)
+        final_headers = render_template(headers, context_vars)
+        final_body = render_template(body, context_vars)
+        if isinstance(final_body, str):
+            # Body como string: tentar parsear como JSON
+            try:
+                final_body = json.loads(final_body)
+            except ValueError:
+                pass  # Manter como string se não for JSON válido
 
         # Configurar retry
         on_error = error_handling.get("onError", "continue")
@@ -2077,17 +3719,28 @@ class WhatsAppService:
 
         retry_count = 0
         last_error = None
+        succeeded = False
+        status_code = None
 
         logger.info(f"  📡 {method} {final_url}")
         if final_query_params:
             logger.info(f"  🔍 Query Params: {final_query_params}")
         if final_body:
-            logger.info(f"  📦 Body: {json.dumps(final_body) if isinstance(final_body, dict) else final_body}")
+            logger.info(f"  📦 Body: {json.dumps(final_body) if isinstance(final_body, (dict, list)) else final_body}")
+
+        # Resolver {{org.secrets.X}} / {{org.vars.X}} depois dos logs, para não expor valores
+        resolver = self._org_secret_resolver(conversation, flow)
+        usage = SecretUsage.FLOW_HTTP.value
+        final_url = await resolver.resolve(final_url, usage, "node", node.node_id)
+        final_query_params = await resolver.resolve(final_query_params, usage, "node", node.node_id)
+        final_headers = await resolver.resolve(final_headers, usage, "node", node.node_id)
+        final_body = await resolver.resolve(final_body, usage, "node", node.node_id)
 
         # Tentar fazer a chamada (com retry se configurado)
         while retry_count < max_retries:
+            status_code = None
             try:
-                async with httpx.AsyncClient(timeout=timeout_seconds) as client:
+                async with http_client("http_connector", timeout=timeout_seconds) as client:
                     if method == "GET":
                         response = await client.get(
                             final_url,
@@ -2099,7 +3752,7 @@ class WhatsAppService:
                             final_url,
                             headers=final_headers,
                             params=final_query_params,
-                            json=final_body if isinstance(final_body, dict) else None,
+                            json=final_body if isinstance(final_body, (dict, list)) else None,
                             content=final_body if isinstance(final_body, str) else None
                         )
                     elif method == "PUT":
@@ -2107,7 +3760,7 @@ class WhatsAppService:
                             final_url,
                             headers=final_headers,
                             params=final_query_params,
-                            json=final_body if isinstance(final_body, dict) else None,
+                            json=final_body if isinstance(final_body, (dict, list)) else None,
                             content=final_body if isinstance(final_body, str) else None
                         )
                     elif method == "PATCH":
@@ -2115,7 +3768,7 @@ class WhatsAppService:
                             final_url,
                             headers=final_headers,
                             params=final_query_params,
-                            json=final_body if isinstance(final_body, dict) else None,
+                            json=final_body if isinstance(final_body, (dict, list)) else None,
                             content=final_body if isinstance(final_body, str) else None
                         )
                     elif method == "DELETE":
@@ -2130,6 +3783,7 @@ class WhatsAppService:
                         return
 
                 # Verificar status code
+                status_code = response.status_code
                 response.raise_for_status()
 
                 logger.info(f"  ✅ API respondeu: {response.status_code}")
@@ -2138,7 +3792,7 @@ class WhatsAppService:
                 try:
                     response_data = response.json()
                     logger.info(f"  📥 Resposta JSON recebida")
-                except:
+                except ValueError:
                     response_data = response.text
                     logger.info(f"  📥 Resposta em texto recebida")
 
@@ -2146,7 +3800,18 @@ class WhatsAppService:
                 context_vars[response_variable] = response_data
                 logger.info(f"  💾 Resposta salva em '{response_variable}'")
 
+                # Mapear campos da resposta (JSONPath) para variáveis
+                try:
+                    mapped = map_response(response_data, response_mapping)
+                except ValueError as e:
+                    logger.error(f"  ❌ responseMapping inválido: {e}")
+                    mapped = {}
+                for variable, value in mapped.items():
+                    context_vars[variable] = value
+                    logger.info(f"  💾 '{variable}' = {str(value)[:50]}")
+
                 # Sucesso - sair do loop de retry
+                succeeded = True
                 break
 
             except httpx.HTTPStatusError as e:
@@ -2154,6 +3819,9 @@ class WhatsAppService:
                 logger.warning(
                     f"  ⚠️ Erro HTTP {e.response.status_code}: {e.response.text[:100]}"
                 )
+                if not is_retryable(e.response.status_code):
+                    # 4xx não muda ao repetir
+                    break
 
             except httpx.TimeoutException as e:
                 last_error = e
@@ -2170,41 +3838,49 @@ class WhatsAppService:
                 import asyncio
                 logger.info(f"  🔄 Tentando novamente ({retry_count}/{max_retries})...")
                 await asyncio.sleep(retry_delay)
-            else:
-                # Esgotou tentativas
-                logger.error(f"  ❌ Falha após {max_retries} tentativas")
-
-                # Aplicar estratégia de erro
-                if on_error == "stop":
-                    logger.info(f"  🛑 Parando fluxo devido a erro")
-                    # Transferir para agente humano
-                    from app.repositories.conversation import ConversationRepository
-                    conv_repo = ConversationRepository(self.db)
-                    await conv_repo.update(conversation.id, {
-                        "is_bot_active": False,
-                        "status": "queued",
-                        "priority": "high"
-                    })
-                    await self.db.commit()
-                    return
 
-                elif on_error == "continue":
-                    logger.info(f"  ➡️ Continuando fluxo apesar do erro")
-                    if fallback_value is not None:
-                        context_vars[response_variable] = fallback_value
-                        logger.info(f"  💾 Valor fallback salvo em '{response_variable}'")
+        if status_variable:
+            context_vars[status_variable] = status_code
+
+        if not succeeded:
+            logger.error(f"  ❌ Falha na chamada da API: {last_error}")
+
+            # Aplicar estratégia de erro
+            if on_error == "stop":
+                logger.info(f"  🛑 Parando fluxo devido a erro")
+                # Transferir para agente humano
+                conv_repo = ConversationRepository(self.db)
+                await self._set_session(conversation, {
+                    "is_bot_active": False,
+                    "status": "queued",
+                    "priority": "high"
+                })
+                await self.db.commit()
+                return
+
+            if fallback_value is not None:
+                context_vars[response_variable] = fallback_value
+                logger.info(f"  💾 Valor fallback salvo em '{response_variable}'")
+
+            if on_error == "branch":
+                logger.info(f"  ↪️ Seguindo saída de erro do API Call Node")
+            else:
+                logger.info(f"  ➡️ Continuando fluxo apesar do erro")
 
         # Salvar context_variables atualizadas
         conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
+        await self._set_session(conversation, {
             "context_variables": context_vars
         })
         await self.db.commit()
 
         logger.info(f"✅ API Call Node concluído")
 
-        # Avançar para próximo node
-        await self._advance_to_next_node(conversation, node, flow, incoming_message)
+        # Avançar para próximo node (saídas "success"/"error" com onError "branch")
+        source_handle = None
+        if on_error == "branch":
+            source_handle = API_CALL_SUCCESS_HANDLE if succeeded else API_CALL_ERROR_HANDLE
+        await self._advance_to_next_node(conversation, node, flow, incoming_message, source_handle=source_handle)
 
     async def _execute_ai_prompt(self, conversation, node, flow, incoming_message, node_data):
         """
@@ -2304,50 +3980,25 @@ class WhatsAppService:
         logger.info(f"  🎯 Model: {model}")
         logger.info(f"  💬 Prompt: {final_prompt[:100]}...")
 
-        try:
-            # Chamar API baseado no provider
-            if provider == "openai":
-                ai_response = await self._call_openai(
-                    model=model,
-                    prompt=final_prompt,
-                    system_prompt=final_system_prompt,
-                    temperature=temperature,
-                    max_tokens=max_tokens,
-                    api_key=final_api_key,
-                    timeout=timeout_seconds
-                )
-
-            elif provider == "anthropic":
-                ai_response = await self._call_anthropic(
-                    model=model,
-                    prompt=final_prompt,
-                    system_prompt=final_system_prompt,
-                    temperature=temperature,
-                    max_tokens=max_tokens,
-                    api_key=final_api_key,
-                    timeout=timeout_seconds
-                )
+        # Resolver {{org.secrets.X}} / {{org.vars.X}} depois dos logs, para não expor valores
+        resolver = self._org_secret_resolver(conversation, flow)
+        usage = SecretUsage.AI_PROMPT.value
+        final_prompt = await resolver.resolve(final_prompt, usage, "node", node.node_id)
+        final_system_prompt = await resolver.resolve(final_system_prompt, usage, "node", node.node_id)
+        final_api_key = await resolver.resolve(final_api_key, usage, "node", node.node_id)
 
-            elif provider == "custom":
-                # Para APIs customizadas (compatíveis com formato OpenAI)
-                custom_url = node_data.get("customUrl")
-                if not custom_url:
-                    raise ValueError("Custom provider requer 'customUrl' configurado")
-
-                ai_response = await self._call_custom_ai(
-                    url=custom_url,
-                    model=model,
-                    prompt=final_prompt,
-                    system_prompt=final_system_prompt,
-                    temperature=temperature,
-                    max_tokens=max_tokens,
-                    api_key=final_api_key,
-                    timeout=timeout_seconds
-                )
-
-            else:
-                logger.error(f"❌ Provider não suportado: {provider}")
-                raise ValueError(f"Provider não suportado: {provider}")
+        try:
+            ai_response = await self._call_ai_provider(
+                provider=provider,
+                node_data=node_data,
+                model=model,
+                prompt=final_prompt,
+                system_prompt=final_system_prompt,
+                temperature=temperature,
+                max_tokens=max_tokens,
+                api_key=final_api_key,
+                timeout=timeout_seconds
+            )
 
             # Salvar resposta em variável
             context_vars[response_variable] = ai_response
@@ -2362,7 +4013,7 @@ class WhatsAppService:
                 logger.info(f"  🛑 Parando fluxo devido a erro")
                 # Transferir para agente humano
                 conv_repo = ConversationRepository(self.db)
-                await conv_repo.update(conversation.id, {
+                await self._set_session(conversation, {
                     "is_bot_active": False,
                     "status": "queued",
                     "priority": "high"
@@ -2370,23 +4021,197 @@ class WhatsAppService:
                 await self.db.commit()
                 return
 
-            elif on_error == "continue":
-                logger.info(f"  ➡️ Continuando fluxo apesar do erro")
-                if fallback_value is not None:
-                    context_vars[response_variable] = fallback_value
-                    logger.info(f"  💾 Valor fallback salvo em '{response_variable}'")
+            elif on_error == "continue":
+                logger.info(f"  ➡️ Continuando fluxo apesar do erro")
+                if fallback_value is not None:
+                    context_vars[response_variable] = fallback_value
+                    logger.info(f"  💾 Valor fallback salvo em '{response_variable}'")
+
+        # Salvar context_variables atualizadas
+        conv_repo = ConversationRepository(self.db)
+        await self._set_session(conversation, {
+            "context_variables": context_vars
+        })
+        await self.db.commit()
+
+        logger.info(f"✅ AI Prompt Node concluído")
+
+        # Avançar para próximo node
+        await self._advance_to_next_node(conversation, node, flow, incoming_message)
+
+    async def _call_ai_provider(
+        self, provider: str, node_data: Dict[str, Any], model: str, prompt: str, system_prompt: str,
+        temperature: float, max_tokens: int, api_key: str, timeout: int
+    ) -> str:
+        """Chama o provider de IA do node (openai, anthropic ou custom)"""
+        if provider == "openai":
+            return await self._call_openai(
+                model=model,
+                prompt=prompt,
+                system_prompt=system_prompt,
+                temperature=temperature,
+                max_tokens=max_tokens,
+                api_key=api_key,
+                timeout=timeout
+            )
+
+        if provider == "anthropic":
+            return await self._call_anthropic(
+                model=model,
+                prompt=prompt,
+                system_prompt=system_prompt,
+                temperature=temperature,
+                max_tokens=max_tokens,
+                api_key=api_key,
+                timeout=timeout
+            )
+
+        if provider == "custom":
+            # Para APIs customizadas (compatíveis com formato OpenAI)
+            custom_url = node_data.get("customUrl")
+            if not custom_url:
+                raise ValueError("Custom provider requer 'customUrl' configurado")
+
+            return await self._call_custom_ai(
+                url=custom_url,
+                model=model,
+                prompt=prompt,
+                system_prompt=system_prompt,
+                temperature=temperature,
+                max_tokens=max_tokens,
+                api_key=api_key,
+                timeout=timeout
+            )
+
+        logger.error(f"❌ Provider não suportado: {provider}")
+        raise ValueError(f"Provider não suportado: {provider}")
+
+    async def _execute_intent(self, conversation, node, flow, incoming_message, node_data):
+        """
+        Executa um Intent Node - classifica a mensagem do contato com IA e segue
+        a saída (sourceHandle) da intenção detectada.
+
+        Abaixo de confidenceThreshold segue a saída "fallback"; se ela não
+        estiver conectada, envia um menu numerado das intenções e aguarda a
+        escolha do contato neste node.
+
+        Args:
+            conversation: Instância da conversa
+            node: Node atual (Intent)
+            flow: Flow ativo
+            incoming_message: Mensagem que originou a execução
+            node_data: Dados do Intent Node
+
+        Formato esperado do node_data:
+        {
+            "intents": [
+                {"name": "segunda_via", "label": "2ª via do boleto", "examples": ["perdi meu boleto"]},
+                {"name": "cancelamento", "label": "Cancelar plano", "description": "Quer encerrar o contrato"}
+            ],
+            "confidenceThreshold": 0.6,
+            "inputVariable": "mensagem",  # Padrão: texto da mensagem recebida
+            "outputVariable": "intent",  # Também salva <outputVariable>_confidence
+            "provider": "openai",  # openai, anthropic, custom (como no AI Prompt Node)
+            "model": "gpt-4o-mini",
+            "apiKey": "{{org.secrets.OPENAI_API_KEY}}",
+            "timeout": 30,
+            "menuMessage": "Não entendi. Sobre qual destes assuntos você quer falar?"
+        }
+        """
+        logger.info(f"🧭 Executando Intent Node")
+
+        intents = normalize_intents(node_data.get("intents"))
+        output_variable = node_data.get("outputVariable", "intent")
+        menu_key = f"_intent_menu_{node.node_id}"
+        context_vars = conversation.context_variables or {}
+
+        input_variable = node_data.get("inputVariable")
+        if input_variable:
+            message_text = str(context_vars.get(input_variable) or "")
+        else:
+            message_text = (incoming_message.content or {}).get("text", "") if incoming_message else ""
+        message_text = message_text.strip()
+
+        # Resposta ao menu: número, label ou nome da intenção; senão classifica o texto
+        intent, confidence = None, 0.0
+        if context_vars.pop(menu_key, None):
+            intent = match_menu_choice(message_text, intents)
+            confidence = 1.0 if intent else 0.0
+        if not intent and intents and message_text:
+            intent, confidence = await self._classify_intent(conversation, node, flow, node_data, intents, message_text)
+
+        threshold = float(node_data.get("confidenceThreshold", DEFAULT_CONFIDENCE_THRESHOLD))
+        if intent and confidence < threshold:
+            logger.info(f"  🤔 Intenção '{intent}' abaixo do limite ({confidence:.2f} < {threshold})")
+            intent = None
+
+        context_vars[output_variable] = intent
+        context_vars[f"{output_variable}_confidence"] = confidence
+
+        if intent:
+            logger.info(f"  ✅ Intenção detectada: {intent} ({confidence:.2f})")
+            await self._set_session(conversation, {"context_variables": context_vars})
+            await self.db.commit()
+            await self._advance_to_next_node(conversation, node, flow, incoming_message, source_handle=intent)
+            return
+
+        edges = await self._flow_edges(flow, node.flow_version_id)
+        has_fallback = any(
+            edge.get("source") == node.node_id and edge.get("sourceHandle") == INTENT_FALLBACK_HANDLE
+            for edge in edges
+        )
+        if has_fallback or not intents:
+            logger.info(f"  ↪️ Nenhuma intenção confiável, seguindo saída '{INTENT_FALLBACK_HANDLE}'")
+            await self._set_session(conversation, {"context_variables": context_vars})
+            await self.db.commit()
+            await self._advance_to_next_node(
+                conversation, node, flow, incoming_message, source_handle=INTENT_FALLBACK_HANDLE
+            )
+            return
 
-        # Salvar context_variables atualizadas
-        conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
-            "context_variables": context_vars
-        })
+        # Sem saída fallback: menu das intenções, a escolha volta a este node
+        logger.info(f"  📋 Nenhuma intenção confiável, enviando menu de intenções")
+        context_vars[menu_key] = True
+        await self._set_session(conversation, {"context_variables": context_vars})
         await self.db.commit()
+        menu_text = render_text(
+            node_data.get("menuMessage", "Não entendi. Sobre qual destes assuntos você quer falar?"), context_vars
+        )
+        await self._send_error_message(conversation, intent_menu(intents, menu_text))
 
-        logger.info(f"✅ AI Prompt Node concluído")
+    async def _classify_intent(self, conversation, node, flow, node_data, intents, message_text):
+        """
+        Classifica o texto com o provider de IA do Intent Node.
 
-        # Avançar para próximo node
-        await self._advance_to_next_node(conversation, node, flow, incoming_message)
+        Returns:
+            (nome da intenção ou None, confiança); erros contam como confiança zero
+        """
+        api_key = render_text(node_data.get("apiKey") or "", conversation.context_variables or {})
+        if not api_key:
+            logger.error("❌ Intent Node sem API key configurada")
+            return None, 0.0
+
+        resolver = self._org_secret_resolver(conversation, flow)
+        api_key = await resolver.resolve(api_key, SecretUsage.AI_PROMPT.value, "node", node.node_id)
+        system_prompt, prompt = build_intent_prompt(intents, message_text)
+
+        try:
+            response = await self._call_ai_provider(
+                provider=node_data.get("provider", "openai"),
+                node_data=node_data,
+                model=node_data.get("model", "gpt-3.5-turbo"),
+                prompt=prompt,
+                system_prompt=system_prompt,
+                temperature=0,
+                max_tokens=100,
+                api_key=api_key,
+                timeout=node_data.get("timeout", 30)
+            )
+        except Exception as e:
+            logger.error(f"  ❌ Erro ao classificar intenção: {e}")
+            return None, 0.0
+
+        return parse_intent_response(response, intents)
 
     async def _call_openai(
         self, model: str, prompt: str, system_prompt: str, temperature: float,
@@ -2641,7 +4466,7 @@ class WhatsAppService:
                 logger.info(f"  🛑 Parando fluxo devido a erro")
                 # Transferir para agente humano
                 conv_repo = ConversationRepository(self.db)
-                await conv_repo.update(conversation.id, {
+                await self._set_session(conversation, {
                     "is_bot_active": False,
                     "status": "queued",
                     "priority": "high"
@@ -2656,7 +4481,7 @@ class WhatsAppService:
 
         # Salvar context_variables atualizadas
         conv_repo = ConversationRepository(self.db)
-        await conv_repo.update(conversation.id, {
+        await self._set_session(conversation, {
             "context_variables": context_vars
         })
         await self.db.commit()
@@ -2830,14 +4655,11 @@ class WhatsAppService:
             }
         }
         """
-        from app.repositories.conversation import ConversationRepository
         import asyncio
         import json
 
         logger.info(f"📜 Executando Script Node")
 
-        conv_repo = ConversationRepository(self.db)
-
         # Extrair configurações
         language = node_data.get("language", "python")
         code = node_data.get("code", "")
@@ -2871,7 +4693,7 @@ class WhatsAppService:
                 if output_variable and fallback_value is not None:
                     context_vars = conversation.context_variables or {}
                     context_vars[output_variable] = fallback_value
-                    await conv_repo.update(conversation.id, {"context_variables": context_vars})
+                    await self._set_session(conversation, {"context_variables": context_vars})
                 await self._advance_to_next_node(conversation, node, flow, incoming_message)
                 return
 
@@ -2934,7 +4756,7 @@ class WhatsAppService:
             # Salvar resultado na variável de output
             if output_variable:
                 context_vars[output_variable] = result
-                await conv_repo.update(conversation.id, {"context_variables": context_vars})
+                await self._set_session(conversation, {"context_variables": context_vars})
                 await self.db.commit()
                 logger.info(f"  💾 Resultado salvo em '{output_variable}'")
 
@@ -2955,7 +4777,7 @@ class WhatsAppService:
                 # Continue com fallback
                 if output_variable and fallback_value is not None:
                     context_vars[output_variable] = fallback_value
-                    await conv_repo.update(conversation.id, {"context_variables": context_vars})
+                    await self._set_session(conversation, {"context_variables": context_vars})
                     await self.db.commit()
 
         except Exception as e:
@@ -2975,7 +4797,7 @@ class WhatsAppService:
                 # Continue com fallback
                 if output_variable and fallback_value is not None:
                     context_vars[output_variable] = fallback_value
-                    await conv_repo.update(conversation.id, {"context_variables": context_vars})
+                    await self._set_session(conversation, {"context_variables": context_vars})
                     await self.db.commit()
 
         logger.info(f"✅ Script Node concluído")
@@ -2983,6 +4805,340 @@ class WhatsAppService:
         # Avançar para próximo node
         await self._advance_to_next_node(conversation, node, flow, incoming_message)
 
+    async def _receive_media(self, conversation, node, flow, incoming_message):
+        """
+        Recebe a mensagem do contato em um Media Collect Node.
+
+        A mídia aceita (tipo, MIME e tamanho) é baixada da Graph API para o
+        storage configurado e a referência é salva em outputVariable; outras
+        mensagens pedem o arquivo de novo até maxAttempts (ver
+        app.utils.media_collection).
+
+        Node Data Format:
+        {
+            "promptText": "Envie a foto ou o PDF do comprovante.",
+            "mediaTypes": ["image", "document"],
+            "mimeTypes": ["image/*", "application/pdf"],  # Opcional
+            "maxSizeMb": 10,
+            "outputVariable": "comprovante",
+            "errorMessage": "Envie uma imagem ou PDF de até 10 MB.",
+            "maxAttempts": 3,
+            "maxAttemptsMessage": "Não recebi o arquivo."  # Depois segue a saída "invalid"
+        }
+        """
+        node_data = await self._localized_node_data(conversation, node, flow)
+        requirements = MediaRequirements.from_node(node_data)
+        media = extract_media(incoming_message.message_type, incoming_message.content) if incoming_message else None
+
+        stored = None
+        if check_media(media, requirements):
+            whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+            try:
+                stored = await whatsapp_clients.client(whatsapp_number).download_media(
+                    media.media_id,
+                    get_media_storage(),
+                    mime_type=media.mime_type,
+                    filename=media.filename,
+                    key_prefix=f"{conversation.organization_id}/{conversation.id}/",
+                    max_bytes=requirements.max_bytes,
+                )
+            except MediaFetchError as e:
+                logger.warning(f"⚠️ Mídia {media.media_id} não foi baixada: {e.reason}")
+        else:
+            logger.warning(f"❌ Mensagem sem mídia aceita ({incoming_message.message_type if incoming_message else None})")
+
+        context_vars = dict(conversation.context_variables or {})
+        attempt_key = f"_attempts_{node.node_id}"
+
+        if stored is None:
+            attempts = context_vars.get(attempt_key, 0) + 1
+            logger.info(f"  Tentativa {attempts}/{requirements.max_attempts}")
+
+            if attempts < requirements.max_attempts:
+                # Pedir o arquivo de novo e continuar aguardando neste node
+                context_vars[attempt_key] = attempts
+                await self._set_session(conversation, {"context_variables": context_vars})
+                conversation.context_variables = context_vars
+                await self.db.commit()
+                await self._send_error_message(
+                    conversation, node_data.get("errorMessage") or requirements.error_message()
+                )
+                return
+
+            logger.warning(f"⚠️ Número máximo de tentativas ({requirements.max_attempts}) atingido")
+            context_vars.pop(attempt_key, None)
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+            await self.db.commit()
+            await self._send_error_message(
+                conversation, node_data.get("maxAttemptsMessage") or DEFAULT_MAX_ATTEMPTS_MESSAGE
+            )
+            await self._advance_after_invalid_input(conversation, node, flow, incoming_message)
+            return
+
+        output_variable = node_data.get("outputVariable") or "media"
+        logger.info(f"📎 Mídia {media.media_id} salva em '{stored.reference}' (variável '{output_variable}')")
+
+        context_vars.pop(attempt_key, None)
+        context_vars[output_variable] = media_variable(media, stored)
+        await self._set_session(conversation, {"context_variables": context_vars})
+        conversation.context_variables = context_vars
+        await self.db.commit()
+
+        await self._advance_to_next_node(conversation, node, flow, incoming_message)
+
+    async def _receive_location(self, conversation, node, flow, incoming_message):
+        """
+        Recebe a mensagem do contato em um Location Collect Node.
+
+        A localização é salva em outputVariable com o resultado da área de
+        atendimento; com serviceArea o fluxo segue a saída "in_area" ou
+        "out_of_area" (ver app.utils.location_collection). Outras mensagens
+        pedem a localização de novo até maxAttempts.
+
+        Node Data Format:
+        {
+            "promptText": "Compartilhe a localização do endereço de instalação.",
+            "requestButton": true,  # Botão nativo "Enviar localização" (API oficial)
+            "outputVariable": "localizacao",
+            "serviceArea": [[-23.55, -46.64], [-23.55, -46.60], [-23.52, -46.60]],  # Ou áreas nomeadas / GeoJSON
+            "errorMessage": "Não recebi sua localização.",
+            "maxAttempts": 3,
+            "maxAttemptsMessage": "Vou seguir sem a localização."  # Depois segue a saída "invalid"
+        }
+        """
+        node_data = await self._localized_node_data(conversation, node, flow)
+        location = extract_location(incoming_message.message_type, incoming_message.content) if incoming_message else None
+
+        context_vars = dict(conversation.context_variables or {})
+        attempt_key = f"_attempts_{node.node_id}"
+
+        if location is None:
+            attempts = context_vars.get(attempt_key, 0) + 1
+            attempts_limit = max_attempts(node_data)
+            logger.warning(f"❌ Mensagem sem localização (tentativa {attempts}/{attempts_limit})")
+
+            if attempts < attempts_limit:
+                # Pedir a localização de novo e continuar aguardando neste node
+                context_vars[attempt_key] = attempts
+                await self._set_session(conversation, {"context_variables": context_vars})
+                conversation.context_variables = context_vars
+                await self.db.commit()
+                await self._send_error_message(conversation, node_data.get("errorMessage") or LOCATION_ERROR_MESSAGE)
+                return
+
+            context_vars.pop(attempt_key, None)
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+            await self.db.commit()
+            await self._send_error_message(
+                conversation, node_data.get("maxAttemptsMessage") or DEFAULT_MAX_ATTEMPTS_MESSAGE
+            )
+            await self._advance_after_invalid_input(conversation, node, flow, incoming_message)
+            return
+
+        # Área de atendimento (polígonos inválidos são barrados na validação do fluxo)
+        source_handle = None
+        try:
+            areas = parse_service_areas(node_data.get("serviceArea"))
+        except ValueError as e:
+            logger.error(f"❌ serviceArea inválida no node {node.node_id}: {e}")
+            areas = []
+        if areas:
+            area = find_area(location["latitude"], location["longitude"], areas)
+            location["in_area"] = area is not None
+            location["area"] = area.name if area else None
+            source_handle = IN_AREA_HANDLE if area else OUT_OF_AREA_HANDLE
+            logger.info(f"📍 Localização {'dentro' if area else 'fora'} da área de atendimento")
+
+        output_variable = node_data.get("outputVariable") or "location"
+        context_vars.pop(attempt_key, None)
+        context_vars[output_variable] = location
+        await self._set_session(conversation, {"context_variables": context_vars})
+        conversation.context_variables = context_vars
+        await self.db.commit()
+
+        await self._advance_to_next_node(conversation, node, flow, incoming_message, source_handle=source_handle)
+
+    async def _receive_survey(self, conversation, node, flow, incoming_message):
+        """
+        Recebe a resposta do contato em um Survey Node (NPS ou CSAT).
+
+        A primeira resposta é a nota; com askComment o node pede um comentário
+        (que pode ser pulado) antes de gravar a resposta em survey_responses.
+        O fluxo segue a saída da categoria da nota quando conectada
+        ("promoter", "passive", "detractor" / "satisfied", "neutral",
+        "dissatisfied"), senão a saída normal (ver app.utils.survey).
+
+        Node Data Format:
+        {
+            "surveyType": "nps",  # nps (0-10) ou csat (1-5)
+            "questionText": "De 0 a 10, quanto você recomendaria a gente?",
+            "askComment": true,
+            "commentPrompt": "Quer contar o motivo da sua nota?",
+            "skipKeywords": ["pular", "não"],
+            "outputVariable": "nps",
+            "errorMessage": "Responda com um número de 0 a 10.",
+            "maxAttempts": 3,
+            "maxAttemptsMessage": "Tudo bem, seguimos sem a avaliação."  # Depois segue a saída "invalid"
+        }
+        """
+        node_data = await self._localized_node_data(conversation, node, flow)
+        kind = survey_type(node_data)
+        text = ((incoming_message.content or {}).get("text") or "").strip() if incoming_message else ""
+
+        context_vars = dict(conversation.context_variables or {})
+        attempt_key = f"_attempts_{node.node_id}"
+        score_key = f"_survey_score_{node.node_id}"
+
+        # Nota já recebida: esta mensagem é o comentário
+        if score_key in context_vars:
+            score = context_vars.pop(score_key)
+            comment = None if is_skip(text, node_data) else clean_comment(text)
+            await self._save_survey_response(
+                conversation, node, flow, incoming_message, node_data, kind, score, comment, context_vars
+            )
+            return
+
+        score = parse_score(text, kind)
+        if score is None:
+            attempts = context_vars.get(attempt_key, 0) + 1
+            attempts_limit = max_attempts(node_data)
+            logger.warning(f"❌ Nota de pesquisa inválida: {text!r} (tentativa {attempts}/{attempts_limit})")
+
+            if attempts < attempts_limit:
+                context_vars[attempt_key] = attempts
+                await self._set_session(conversation, {"context_variables": context_vars})
+                conversation.context_variables = context_vars
+                await self.db.commit()
+                low, high = SURVEY_SCORE_RANGES[kind]
+                await self._send_error_message(
+                    conversation, node_data.get("errorMessage") or f"Por favor, responda com um número de {low} a {high}."
+                )
+                return
+
+            context_vars.pop(attempt_key, None)
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+            await self.db.commit()
+            await self._send_error_message(
+                conversation, node_data.get("maxAttemptsMessage") or DEFAULT_MAX_ATTEMPTS_MESSAGE
+            )
+            await self._advance_after_invalid_input(conversation, node, flow, incoming_message)
+            return
+
+        context_vars.pop(attempt_key, None)
+        if node_data.get("askComment"):
+            # Guardar a nota e aguardar o comentário neste node
+            context_vars[score_key] = score
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+            await self.db.commit()
+            prompt = render_text(
+                node_data.get("commentPrompt") or SURVEY_COMMENT_PROMPT, conversation.context_variables or {}
+            )
+            await self._send_error_message(conversation, prompt)
+            return
+
+        await self._save_survey_response(
+            conversation, node, flow, incoming_message, node_data, kind, score, None, context_vars
+        )
+
+    async def _save_survey_response(
+        self, conversation, node, flow, incoming_message, node_data, kind, score, comment, context_vars
+    ):
+        """
+        Grava a resposta da pesquisa, salva a variável e segue a saída da categoria.
+        """
+        from app.repositories.chatbot import SurveyResponseRepository
+
+        category = classify_survey_score(score, kind)
+        await SurveyResponseRepository(self.db).create({
+            "organization_id": conversation.organization_id,
+            "flow_id": flow.id,
+            "conversation_id": conversation.id,
+            "contact_id": conversation.contact_id,
+            "agent_id": conversation.current_agent_id,
+            "node_id": node.node_id,
+            "survey_type": kind,
+            "score": score,
+            "comment": comment,
+        })
+        logger.info(f"⭐ Pesquisa {kind.upper()} respondida: nota {score} ({category})")
+
+        output_variable = node_data.get("outputVariable") or kind
+        context_vars[output_variable] = {"score": score, "category": category, "comment": comment}
+        await self._set_session(conversation, {"context_variables": context_vars})
+        conversation.context_variables = context_vars
+        await self.db.commit()
+
+        # Saída da categoria (promoter, detractor...) quando conectada, senão a saída normal
+        edges = await self._flow_edges(flow, node.flow_version_id)
+        has_category_branch = any(
+            edge.get("source") == node.node_id and edge.get("sourceHandle") == category for edge in edges
+        )
+        await self._advance_to_next_node(
+            conversation, node, flow, incoming_message, source_handle=category if has_category_branch else None
+        )
+
+    async def _execute_sandbox_script(self, conversation, node, flow, incoming_message, node_data):
+        """
+        Executa Sandbox Script Node - Snippet do tenant isolado da plataforma
+
+        Node Data Format:
+        {
+            "code": "return round(sum(item['preco'] for item in itens) * 0.9, 2)",
+            "outputVariable": "total",
+            "cpuSeconds": 1,       # Opcional (máx. 5)
+            "memoryMb": 64,        # Opcional (máx. 256)
+            "timeoutSeconds": 3,   # Opcional (máx. 10)
+            "errorHandling": {
+                "onError": "continue",  # continue, stop ou branch (saídas "success"/"error")
+                "fallbackValue": null
+            }
+        }
+
+        O snippet roda em outro processo, sem rede, com limites de CPU e
+        memória, e só enxerga as variáveis da sessão (ver app.utils.script_sandbox).
+        """
+        error_handling = node_data.get("errorHandling") or {}
+        on_error = error_handling.get("onError", "continue")
+        output_variable = node_data.get("outputVariable")
+        context_vars = dict(conversation.context_variables or {})
+        variables = {name: value for name, value in context_vars.items() if not name.startswith("_")}
+
+        try:
+            outcome = await run_script(node_data.get("code", ""), variables, SandboxLimits.from_node(node_data))
+        except ScriptError as e:
+            outcome = ScriptResult(ok=False, error=str(e))
+
+        if outcome.ok:
+            logger.info(f"  ✅ Script executado, resultado em '{output_variable}'")
+        else:
+            logger.error(f"  ❌ Sandbox Script {node.node_id} falhou: {outcome.error}")
+            if on_error == "stop":
+                await self._execute_handoff(
+                    conversation,
+                    {
+                        "transferMessage": "Erro ao processar dados. Transferindo para agente.",
+                        "sendTransferMessage": True,
+                        "priority": "high"
+                    }
+                )
+                return
+
+        value = outcome.result if outcome.ok else error_handling.get("fallbackValue")
+        if output_variable and (outcome.ok or value is not None):
+            context_vars[output_variable] = value
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+            await self.db.commit()
+
+        source_handle = None
+        if on_error == "branch":
+            source_handle = SCRIPT_SUCCESS_HANDLE if outcome.ok else SCRIPT_ERROR_HANDLE
+        await self._advance_to_next_node(conversation, node, flow, incoming_message, source_handle=source_handle)
+
     async def _run_python_script(self, code: str, namespace: dict) -> any:
         """
         Executa código Python em um namespace restrito.
@@ -3028,29 +5184,28 @@ __result__ = __script_func__()
 
     async def _execute_set_variable(self, conversation, node, flow, incoming_message, node_data):
         """
-        Executa Set Variable Node - Define/atualiza variáveis no contexto da conversa
+        Executa Set Variable Node - Define/calcula variáveis no contexto da conversa
 
         Node Data Format:
         {
             "variables": [
-                {
-                    "name": "user_name",
-                    "valueType": "static",     # "static", "variable", "expression"
-                    "value": "João Silva",
-                    "variableSource": null,    # Nome da variável para copiar
-                    "expression": null         # Expressão para avaliar
-                }
+                {"name": "user_name", "valueType": "static", "value": "João Silva"},
+                {"name": "tentativas", "valueType": "increment"},
+                {"name": "total", "valueType": "compute", "expression": "preco * quantidade"},
+                {"name": "vencimento", "valueType": "date", "addAmount": 7, "addUnit": "days", "format": "%d/%m/%Y"},
+                {"name": "brinde", "valueType": "random", "options": ["caneca", "camiseta"]}
             ]
         }
 
-        Value Types:
+        Value Types (ver app.utils.flow_compute):
         - static: Valor fixo/literal
         - variable: Copiar valor de outra variável
-        - expression: Avaliar expressão simples (ex: "{{first_name}} {{last_name}}")
+        - expression: Texto com {{variáveis}} substituídas (ex: "{{first_name}} {{last_name}}")
+        - compute: Expressão dos Condition Nodes (aritmética, concatenação com +)
+        - increment: Soma "step" (padrão 1) ao contador
+        - date: Data atual ou de uma variável, somando minutos/horas/dias/semanas/meses/anos
+        - random: Item sorteado de uma lista
         """
-        from app.repositories.conversation import ConversationRepository
-        import re
-
         logger.info(f"🔧 Set Variable Node - Configurando variáveis")
 
         # Obter variáveis configuradas
@@ -3061,68 +5216,16 @@ __result__ = __script_func__()
             await self._advance_to_next_node(conversation, node, flow, incoming_message)
             return
 
-        # Obter contexto atual
-        conv_repo = ConversationRepository(self.db)
-        context_vars = conversation.context_variables or {}
-
-        logger.info(f"📦 Contexto atual: {list(context_vars.keys())}")
-
-        # Processar cada variável
-        for var_config in variables_config:
-            var_name = var_config.get("name")
-            value_type = var_config.get("valueType", "static")
-
-            if not var_name:
-                logger.warning("⚠️ Nome de variável vazio, pulando")
-                continue
-
-            try:
-                # Determinar valor baseado no tipo
-                if value_type == "static":
-                    # Valor estático/literal
-                    value = var_config.get("value")
-                    logger.info(f"✏️ Definindo '{var_name}' = '{value}' (static)")
-
-                elif value_type == "variable":
-                    # Copiar de outra variável
-                    source_var = var_config.get("variableSource")
-                    if source_var and source_var in context_vars:
-                        value = context_vars[source_var]
-                        logger.info(f"📋 Copiando '{var_name}' <- '{source_var}' = '{value}'")
-                    else:
-                        logger.warning(f"⚠️ Variável source '{source_var}' não encontrada, usando null")
-                        value = None
-
-                elif value_type == "expression":
-                    # Avaliar expressão com substituição de variáveis
-                    expression = var_config.get("expression", "")
-
-                    # Substituir placeholders {{variable}} pelos valores
-                    def replace_placeholder(match):
-                        var = match.group(1)
-                        return str(context_vars.get(var, ""))
-
-                    value = re.sub(r'\{\{(\w+)\}\}', replace_placeholder, expression)
-                    logger.info(f"🔢 Avaliando expressão '{var_name}' = '{expression}' → '{value}'")
-
-                else:
-                    logger.warning(f"⚠️ Tipo de valor '{value_type}' desconhecido, usando null")
-                    value = None
-
-                # Salvar no contexto
-                context_vars[var_name] = value
-
-            except Exception as e:
-                logger.error(f"❌ Erro ao processar variável '{var_name}': {str(e)}")
-                context_vars[var_name] = None
+        context_vars = dict(conversation.context_variables or {})
+        errors = apply_set_variables(variables_config, context_vars)
+        for var_name, error in errors:
+            logger.error(f"❌ Erro ao calcular variável '{var_name}', usando null: {error}")
 
         # Atualizar contexto da conversa
         try:
-            await conv_repo.update(
-                conversation.id,
-                {"context_variables": context_vars}
-            )
-            logger.info(f"✅ Variáveis atualizadas: {list(context_vars.keys())}")
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+            logger.info(f"✅ Variáveis atualizadas: {[config.get('name') for config in variables_config]}")
         except Exception as e:
             logger.error(f"❌ Erro ao salvar contexto: {str(e)}")
 
@@ -3153,7 +5256,6 @@ __result__ = __script_func__()
             "seed": null  # Opcional: seed para randomização reproduzível
         }
         """
-        from app.repositories.conversation import ConversationRepository
         import random
 
         logger.info(f"🎲 Random Node - Selecionando caminho aleatório")
@@ -3204,13 +5306,12 @@ __result__ = __script_func__()
 
         # Salvar variante em variável se configurado
         if save_to_variable:
-            conv_repo = ConversationRepository(self.db)
             context_vars = conversation.context_variables or {}
             context_vars[save_to_variable] = selected_path.get("id")
 
             try:
-                await conv_repo.update(
-                    conversation.id,
+                await self._set_session(
+                    conversation,
                     {"context_variables": context_vars}
                 )
                 logger.info(f"💾 Variante salva em '{save_to_variable}' = '{selected_path.get('id')}'")
@@ -3221,17 +5322,14 @@ __result__ = __script_func__()
         target_node_id = selected_path.get("targetNodeId")
         if target_node_id:
             # Encontrar node de destino no flow
-            target_node = None
-            canvas_data = flow.canvas_data or {}
-            nodes = canvas_data.get("nodes", [])
-
-            for n in nodes:
-                if n.get("id") == target_node_id:
-                    target_node = n
-                    break
+            target_node = await self._find_node(
+                flow.id, target_node_id, conversation.organization_id, node.flow_version_id
+            )
 
             if target_node:
-                logger.info(f"➡️ Avançando para node de destino: {target_node.get('data', {}).get('label', target_node_id)}")
+                logger.info(f"➡️ Avançando para node de destino: {target_node.label or target_node_id}")
+                await self._set_session(conversation, {"current_node_id": target_node.id})
+                await self.db.commit()
                 await self._execute_node(conversation, target_node, flow, incoming_message)
             else:
                 logger.error(f"❌ Node de destino '{target_node_id}' não encontrado")
@@ -3240,6 +5338,95 @@ __result__ = __script_func__()
             logger.warning("⚠️ Caminho sem targetNodeId, avançando normalmente")
             await self._advance_to_next_node(conversation, node, flow, incoming_message)
 
+    async def _execute_split(self, conversation, node, flow, incoming_message, node_data):
+        """
+        Executa Split Node - Teste A/B com variante estável por contato
+
+        Node Data Format:
+        {
+            "experimentKey": "oferta-novembro",  # Opcional: padrão é flow + node
+            "variants": [
+                {"id": "a", "label": "Controle", "percentage": 50},
+                {"id": "b", "label": "Desconto", "percentage": 50}
+            ],
+            "saveToVariable": "oferta_variant"  # Opcional
+        }
+
+        Cada variante é uma saída do node (sourceHandle = id da variante). A
+        variante fica registrada na visita ao node para o funil comparar a
+        conversão dos caminhos (ver app.utils.flow_split).
+        """
+        key = experiment_key(node_data, flow.id, node.node_id)
+        variant = assign_variant(split_variants(node_data), key, conversation.contact_id)
+        if not variant:
+            logger.warning("⚠️ Split Node sem variantes com tráfego, seguindo a primeira edge")
+            await self._advance_to_next_node(conversation, node, flow, incoming_message)
+            return
+
+        logger.info(f"🧪 Split '{key}': variante '{variant['label']}' ({variant['percentage']:g}%)")
+
+        save_to_variable = node_data.get("saveToVariable")
+        if save_to_variable:
+            context_vars = dict(conversation.context_variables or {})
+            context_vars[save_to_variable] = variant["id"]
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+
+        session_id = (conversation.context_variables or {}).get(FLOW_SESSION_KEY)
+        if session_id:
+            try:
+                await FlowNodeVisitRepository(self.db).set_variant(UUID(session_id), node.node_id, variant["id"])
+            except Exception as e:
+                logger.warning(f"⚠️ Não foi possível registrar a variante do split {node.node_id}: {e}")
+                await self.db.rollback()
+
+        await self._advance_to_next_node(conversation, node, flow, incoming_message, source_handle=variant["id"])
+
+    async def _execute_business_hours(self, conversation, node, flow, incoming_message, node_data):
+        """
+        Executa Business Hours Node - Desvio pelo expediente da organização
+
+        Node Data Format:
+        {
+            "saveToVariable": "expediente",  # Opcional: open, closed ou holiday
+            "nextOpenVariable": "reabre_em"  # Opcional: próxima abertura (dd/mm/YYYY HH:MM)
+        }
+
+        Usa o horário, fuso e feriados de organization.settings["business_hours"]
+        (ver app.utils.business_hours). Saídas: "open", "closed" e "holiday";
+        sem a saída "holiday" conectada, feriados seguem "closed".
+        """
+        from app.models.organization import Organization
+
+        organization = await self.db.get(Organization, conversation.organization_id)
+        org_settings = (organization.settings if organization else None) or {}
+        status = business_hours_status(
+            org_settings.get("business_hours"), datetime.now(timezone.utc), org_settings.get("timezone")
+        )
+
+        handle = status.handle
+        if handle == HOLIDAY_HANDLE:
+            edges = await self._flow_edges(flow, node.flow_version_id)
+            if not any(
+                edge.get("source") == node.node_id and edge.get("sourceHandle") == HOLIDAY_HANDLE
+                for edge in edges
+            ):
+                handle = CLOSED_HANDLE
+        logger.info(f"🕘 Expediente: {handle}" + (f" (feriado: {status.holiday})" if status.holiday else ""))
+
+        context_vars = dict(conversation.context_variables or {})
+        if node_data.get("saveToVariable"):
+            context_vars[node_data["saveToVariable"]] = status.handle
+        if node_data.get("nextOpenVariable"):
+            context_vars[node_data["nextOpenVariable"]] = (
+                status.next_open.strftime("%d/%m/%Y %H:%M") if status.next_open else None
+            )
+        if context_vars != (conversation.context_variables or {}):
+            await self._set_session(conversation, {"context_variables": context_vars})
+            conversation.context_variables = context_vars
+
+        await self._advance_to_next_node(conversation, node, flow, incoming_message, source_handle=handle)
+
     async def _execute_datetime(self, conversation, node, flow, incoming_message, node_data):
         """
         Executa Date/Time Node - Manipulação de datas e horários
@@ -3265,7 +5452,6 @@ __result__ = __script_func__()
         - compare: Comparar datas
         - parse: Parse de string para data
         """
-        from app.repositories.conversation import ConversationRepository
         from datetime import datetime, timedelta
         from dateutil.relativedelta import relativedelta
         import pytz
@@ -3278,7 +5464,6 @@ __result__ = __script_func__()
         output_variable = node_data.get("outputVariable")
 
         # Obter contexto
-        conv_repo = ConversationRepository(self.db)
         context_vars = conversation.context_variables or {}
 
         try:
@@ -3385,8 +5570,8 @@ __result__ = __script_func__()
             # Salvar resultado em variável
             if output_variable:
                 context_vars[output_variable] = result
-                await conv_repo.update(
-                    conversation.id,
+                await self._set_session(
+                    conversation,
                     {"context_variables": context_vars}
                 )
                 logger.info(f"💾 Resultado salvo em '{output_variable}' = '{result}'")
@@ -3396,8 +5581,8 @@ __result__ = __script_func__()
             # Em caso de erro, salvar null
             if output_variable:
                 context_vars[output_variable] = None
-                await conv_repo.update(
-                    conversation.id,
+                await self._set_session(
+                    conversation,
                     {"context_variables": context_vars}
                 )
 
@@ -3420,7 +5605,6 @@ __result__ = __script_func__()
             "saveToVariable": "event_id"  # Salvar ID do evento em variável
         }
         """
-        from app.repositories.conversation import ConversationRepository
         from app.core.mongodb import get_mongodb_client
         from datetime import datetime
         import uuid
@@ -3437,7 +5621,6 @@ __result__ = __script_func__()
         save_to_variable = node_data.get("saveToVariable")
 
         # Obter contexto
-        conv_repo = ConversationRepository(self.db)
         context_vars = conversation.context_variables or {}
 
         try:
@@ -3485,8 +5668,8 @@ __result__ = __script_func__()
                         current_tags.append(tag)
 
                 try:
-                    await conv_repo.update(
-                        conversation.id,
+                    await self._set_session(
+                        conversation,
                         {"tags": current_tags}
                     )
                     logger.info(f"🏷️ Tags adicionadas: {tags}")
@@ -3501,8 +5684,8 @@ __result__ = __script_func__()
                 extra_data["counters"] = counters
 
                 try:
-                    await conv_repo.update(
-                        conversation.id,
+                    await self._set_session(
+                        conversation,
                         {"extra_data": extra_data}
                     )
                     logger.info(f"🔢 Contador '{increment_counter}' incrementado: {counters[increment_counter]}")
@@ -3513,8 +5696,8 @@ __result__ = __script_func__()
             if save_to_variable:
                 context_vars[save_to_variable] = event_id
                 try:
-                    await conv_repo.update(
-                        conversation.id,
+                    await self._set_session(
+                        conversation,
                         {"context_variables": context_vars}
                     )
                     logger.info(f"💾 Event ID salvo em '{save_to_variable}' = '{event_id}'")
@@ -3540,6 +5723,7 @@ __result__ = __script_func__()
         {
             "templateName": "welcome_message",
             "languageCode": "pt_BR",
+            "autoLanguage": true,  # variante no idioma do contato, se aprovada
             "components": [
                 {
                     "type": "body",
@@ -3551,7 +5735,6 @@ __result__ = __script_func__()
         }
         """
         from app.repositories.conversation import ConversationRepository
-        import re
 
         logger.info(f"📋 Executando WhatsApp Template Node")
 
@@ -3567,7 +5750,6 @@ __result__ = __script_func__()
 
         # Substituir variáveis nos componentes
         context_vars = conversation.context_variables or {}
-        variables_pattern = r'\{\{(\w+)\}\}'
 
         # Processar componentes e substituir variáveis
         processed_components = []
@@ -3578,11 +5760,8 @@ __result__ = __script_func__()
                 processed_params = []
                 for param in comp_copy["parameters"]:
                     if param.get("type") == "text":
-                        text = param.get("text", "")
                         # Substituir variáveis
-                        for var_name in re.findall(variables_pattern, text):
-                            value = str(context_vars.get(var_name, f"{{{{{var_name}}}}}"))
-                            text = text.replace(f"{{{{{var_name}}}}}", value)
+                        text = render_text(param.get("text", ""), context_vars)
                         processed_params.append({"type": "text", "text": text})
                     else:
                         processed_params.append(param)
@@ -3599,15 +5778,26 @@ __result__ = __script_func__()
 
         contact_phone = conversation.contact_whatsapp_id
 
+        # Variante do template no idioma do contato (desativar com "autoLanguage": false)
+        if node_data.get("autoLanguage", True) and whatsapp_number.connection_type == "official":
+            from app.models.contact import Contact
+            from app.services.template_service import TemplateService
+
+            contact_language = await self.db.scalar(
+                select(Contact.language).where(Contact.id == conversation.contact_id)
+            )
+            resolved_language = await TemplateService(self.db).resolve_language_variant(
+                template_name, language_code, whatsapp_number.id,
+                conversation.organization_id, contact_language,
+            )
+            if resolved_language != language_code:
+                logger.info(f"🌐 Template '{template_name}': {language_code} -> {resolved_language} (idioma do contato)")
+                language_code = resolved_language
+
         try:
             if whatsapp_number.connection_type == "official":
                 # Meta Cloud API
-                from app.integrations.meta_api import MetaCloudAPI
-
-                api = MetaCloudAPI(
-                    phone_number_id=whatsapp_number.phone_number_id,
-                    access_token=whatsapp_number.access_token
-                )
+                api = self._guarded(whatsapp_clients.client(whatsapp_number))
 
                 response = await api.send_template_message(
                     to=contact_phone,
@@ -3633,10 +5823,10 @@ __result__ = __script_func__()
 
                 from app.integrations.evolution_api import EvolutionAPIClient
 
-                evo_client = EvolutionAPIClient(
+                evo_client = self._guarded(EvolutionAPIClient(
                     api_url=whatsapp_number.evolution_api_url,
                     api_key=whatsapp_number.evolution_api_key
-                )
+                ))
 
                 await evo_client.send_text_message(
                     instance_name=whatsapp_number.evolution_instance_name,
@@ -3686,7 +5876,6 @@ __result__ = __script_func__()
         }
         """
         from app.repositories.conversation import ConversationRepository
-        import re
 
         logger.info(f"🔘 Executando Interactive Buttons Node")
 
@@ -3708,16 +5897,13 @@ __result__ = __script_func__()
 
         # Substituir variáveis
         context_vars = conversation.context_variables or {}
-        variables_pattern = r'\{\{(\w+)\}\}'
-
-        for var_name in re.findall(variables_pattern, body_text):
-            value = str(context_vars.get(var_name, f"{{{{{var_name}}}}}"))
-            body_text = body_text.replace(f"{{{{{var_name}}}}}", value)
-
+        body_text = render_text(body_text, context_vars)
         if header_text:
-            for var_name in re.findall(variables_pattern, header_text):
-                value = str(context_vars.get(var_name, f"{{{{{var_name}}}}}"))
-                header_text = header_text.replace(f"{{{{{var_name}}}}}", value)
+            header_text = render_text(header_text, context_vars)
+
+        # Validar limites do WhatsApp (contagem por grafemas); excedentes são truncados no envio
+        for issue in validate_buttons(body_text, buttons, header=header_text, footer=footer_text):
+            logger.warning(f"⚠️ Node {node.node_id} excede limites do WhatsApp - {issue}")
 
         # Buscar WhatsApp number
         whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
@@ -3731,12 +5917,7 @@ __result__ = __script_func__()
         try:
             if whatsapp_number.connection_type == "official":
                 # Meta Cloud API
-                from app.integrations.meta_api import MetaCloudAPI
-
-                api = MetaCloudAPI(
-                    phone_number_id=whatsapp_number.phone_number_id,
-                    access_token=whatsapp_number.access_token
-                )
+                api = self._guarded(whatsapp_clients.client(whatsapp_number))
 
                 await api.send_interactive_buttons(
                     to=contact_phone,
@@ -3752,10 +5933,10 @@ __result__ = __script_func__()
                 # Evolution API (QR Code)
                 from app.integrations.evolution_api import EvolutionAPIClient
 
-                evo_client = EvolutionAPIClient(
+                evo_client = self._guarded(EvolutionAPIClient(
                     api_url=whatsapp_number.evolution_api_url,
                     api_key=whatsapp_number.evolution_api_key
-                )
+                ))
 
                 # Formatar botões para Evolution API
                 evo_buttons = [{"displayText": btn["title"]} for btn in buttons]
@@ -3818,7 +5999,6 @@ __result__ = __script_func__()
         }
         """
         from app.repositories.conversation import ConversationRepository
-        import re
 
         logger.info(f"📝 Executando Interactive List Node")
 
@@ -3836,11 +6016,11 @@ __result__ = __script_func__()
 
         # Substituir variáveis
         context_vars = conversation.context_variables or {}
-        variables_pattern = r'\{\{(\w+)\}\}'
+        body_text = render_text(body_text, context_vars)
 
-        for var_name in re.findall(variables_pattern, body_text):
-            value = str(context_vars.get(var_name, f"{{{{{var_name}}}}}"))
-            body_text = body_text.replace(f"{{{{{var_name}}}}}", value)
+        # Validar limites do WhatsApp (contagem por grafemas); excedentes são truncados no envio
+        for issue in validate_list(body_text, button_text, sections, header=header_text, footer=footer_text):
+            logger.warning(f"⚠️ Node {node.node_id} excede limites do WhatsApp - {issue}")
 
         # Buscar WhatsApp number
         whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
@@ -3854,12 +6034,7 @@ __result__ = __script_func__()
         try:
             if whatsapp_number.connection_type == "official":
                 # Meta Cloud API
-                from app.integrations.meta_api import MetaCloudAPI
-
-                api = MetaCloudAPI(
-                    phone_number_id=whatsapp_number.phone_number_id,
-                    access_token=whatsapp_number.access_token
-                )
+                api = self._guarded(whatsapp_clients.client(whatsapp_number))
 
                 await api.send_interactive_list(
                     to=contact_phone,
@@ -3877,10 +6052,10 @@ __result__ = __script_func__()
                 # Evolution API (QR Code)
                 from app.integrations.evolution_api import EvolutionAPIClient
 
-                evo_client = EvolutionAPIClient(
+                evo_client = self._guarded(EvolutionAPIClient(
                     api_url=whatsapp_number.evolution_api_url,
                     api_key=whatsapp_number.evolution_api_key
-                )
+                ))
 
                 await evo_client.send_list(
                     instance_name=whatsapp_number.evolution_instance_name,
@@ -3947,10 +6122,9 @@ __result__ = __script_func__()
                 if number.connection_type == "official":
                     # Quick test for Official API
                     if number.access_token and number.phone_number_id:
-                        import httpx
-                        async with httpx.AsyncClient(timeout=5.0) as client:
+                        async with http_client("meta_api", timeout=5.0) as client:
                             response = await client.get(
-                                f"https://graph.facebook.com/v18.0/{number.phone_number_id}",
+                                graph_url(number.phone_number_id),
                                 params={"access_token": number.access_token}
                             )
                             new_status = "connected" if response.status_code == 200 else "disconnected"
@@ -4038,12 +6212,39 @@ __result__ = __script_func__()
         updated_number = await self.repo.update(number_id, update_data)
         return self._enrich_number_with_node_info(updated_number)
 
-    async def delete_number(
-        self, number_id: UUID, organization_id: UUID
-    ) -> bool:
-        """Delete WhatsApp number"""
-        number = await self.get_by_id(number_id, organization_id)
-        return await self.repo.delete(number_id)
+    async def delete_number(
+        self, number_id: UUID, organization_id: UUID
+    ) -> bool:
+        """Delete WhatsApp number"""
+        number = await self.get_by_id(number_id, organization_id)
+        if number.phone_number_id:
+            whatsapp_clients.forget(number.phone_number_id)
+        return await self.repo.delete(number_id)
+
+    async def pick_sender(
+        self,
+        organization_id: UUID,
+        contact_key: Optional[str] = None,
+        policy: Optional[SenderPolicy] = None,
+    ) -> WhatsAppNumber:
+        """
+        Choose the official number to send from when the organization has several
+
+        Args:
+            organization_id: Organization ID
+            contact_key: Recipient WhatsApp ID (keeps the same sender per contact with sticky)
+            policy: Selection policy (default: WHATSAPP_SENDER_POLICY)
+
+        Raises:
+            BadRequestException: If the organization has no active official number
+        """
+        numbers = await self.repo.get_active_numbers(organization_id)
+        number = whatsapp_clients.pick(
+            numbers, SenderPolicy(policy or settings.WHATSAPP_SENDER_POLICY), contact_key
+        )
+        if not number:
+            raise BadRequestException("No active official WhatsApp number to send from")
+        return number
 
     # ============= Webhook Methods =============
 
@@ -4053,18 +6254,111 @@ __result__ = __script_func__()
         Used during Meta webhook verification.
         """
         try:
-            stmt = select(WhatsAppNumber).where(
-                WhatsAppNumber.webhook_verify_token == token,
-                WhatsAppNumber.deleted_at.is_(None),
+            await self.resolve_webhook_challenge(
+                WebhookChallenge(mode="subscribe", verify_token=token, challenge="-")
             )
-            result = await self.db.execute(stmt)
-            number = result.scalar_one_or_none()
-            return number is not None
+            return True
+        except (BadRequestException, ForbiddenException):
+            return False
         except Exception as e:
             logger.error(f"Error verifying webhook token: {e}")
             return False
 
-    async def process_webhook(self, payload: Dict[str, Any]) -> None:
+    async def resolve_webhook_challenge(
+        self,
+        challenge: WebhookChallenge,
+        number_id: Optional[UUID] = None,
+    ) -> WhatsAppNumber:
+        """
+        Resolve the WhatsApp number (tenant) of a Meta webhook verification request.
+
+        Each number has its own verify token, so whitelabel deployments do not
+        share one token. The tenant is resolved by the callback path
+        (/whatsapp/webhook/{number_id}) when present, otherwise by the token value.
+
+        Args:
+            challenge: hub.mode / hub.verify_token / hub.challenge
+            number_id: Number from the callback URL path
+
+        Returns:
+            WhatsApp number the token belongs to
+
+        Raises:
+            BadRequestException: If parameters are missing
+            ForbiddenException: If mode or token is invalid
+        """
+        if not challenge.is_complete:
+            raise BadRequestException("Missing parameters")
+
+        if not challenge.is_subscribe:
+            raise ForbiddenException("Invalid mode")
+
+        if number_id:
+            number = await self.repo.get(number_id)
+            candidates = [number] if number and number.deleted_at is None else []
+        else:
+            result = await self.db.execute(
+                select(WhatsAppNumber).where(
+                    WhatsAppNumber.webhook_verify_token == challenge.verify_token,
+                    WhatsAppNumber.deleted_at.is_(None),
+                )
+            )
+            candidates = list(result.scalars().all())
+
+        matched_id = challenge.match({n.id: n.webhook_verify_token for n in candidates})
+        if matched_id is None:
+            logger.warning(f"⚠️ Webhook verification rejected (number_id={number_id})")
+            raise ForbiddenException("Invalid verify token")
+
+        number = next(n for n in candidates if n.id == matched_id)
+        logger.info(f"✅ Webhook verified for {number.phone_number} (org {number.organization_id})")
+        return number
+
+    async def apply_quality_update(
+        self, waba_id: Optional[str], update: PhoneNumberQualityUpdate
+    ) -> int:
+        """
+        Apply a phone_number_quality_update webhook (new messaging limit tier)
+
+        Args:
+            waba_id: WhatsApp Business Account ID (webhook entry id)
+            update: Parsed quality update
+
+        Returns:
+            Number of WhatsApp numbers updated
+        """
+        query = select(WhatsAppNumber).where(
+            WhatsAppNumber.connection_type == "official",
+            WhatsAppNumber.deleted_at.is_(None),
+        )
+        if waba_id:
+            query = query.where(WhatsAppNumber.whatsapp_business_account_id == waba_id)
+
+        numbers = [
+            number for number in (await self.db.execute(query)).scalars().all()
+            if "".join(ch for ch in number.phone_number if ch.isdigit()) == update.digits
+        ]
+        if not numbers:
+            logger.warning(f"Quality update for unknown number {update.display_phone_number}")
+            return 0
+
+        for number in numbers:
+            if update.current_limit:
+                number.messaging_limit_tier = update.current_limit
+            if update.event == "FLAGGED":
+                number.quality_rating = "FLAGGED"
+            elif update.event == "UNFLAGGED" and number.quality_rating == "FLAGGED":
+                number.quality_rating = None  # Refreshed on the next connection test
+
+            logger.info(
+                f"📶 {number.phone_number}: {update.event}, messaging limit "
+                f"{update.old_limit or '?'} -> {number.messaging_limit_tier}"
+            )
+
+        await self.db.commit()
+        return len(numbers)
+
+    async def process_webhook(self, payload: Dict[str, Any], dedup: bool = True) -> int:
         """
         Process incoming webhook from Meta Cloud API.
 
@@ -4087,7 +6381,22 @@ __result__ = __script_func__()
             }]
           }]
         }
+
+        Args:
+            payload: Raw webhook body
+            dedup: Skip message/status ids already received (replays pass False;
+                messages already stored are still not duplicated)
+
+        Returns:
+            Number of changes handled (0 = nothing in the payload was for us)
         """
+        from app.core.webhook_dedup import get_webhook_processor
+        from app.core.webhook_routing import number_router
+
+        processor = get_webhook_processor()
+        claimed: List[str] = []
+        handled = 0
+
         try:
             logger.info(f"Processing webhook payload: {payload}")
 
@@ -4101,41 +6410,88 @@ __result__ = __script_func__()
                     field = change.get("field")
                     value = change.get("value", {})
 
-                    # Get phone number ID to identify which number received the message
-                    metadata = value.get("metadata", {})
-                    phone_number_id = metadata.get("phone_number_id")
+                    # Template review results are per WABA (entry id), not per number
+                    if field == "message_template_status_update":
+                        from app.services.template_service import TemplateService
+
+                        await TemplateService(self.db).apply_status_update(
+                            entry.get("id"), TemplateStatusUpdate.from_webhook(value)
+                        )
+                        handled += 1
+                        continue
+
+                    # Tier changes identify the number by its display phone number
+                    if field == "phone_number_quality_update":
+                        await self.apply_quality_update(
+                            entry.get("id"), PhoneNumberQualityUpdate.from_webhook(value)
+                        )
+                        handled += 1
+                        continue
 
+                    # Número que recebeu o evento (o receiver já contou as rotas; aqui só replays/fila)
+                    phone_number_id = value.get("metadata", {}).get("phone_number_id")
                     if not phone_number_id:
-                        logger.warning("No phone_number_id in webhook payload")
+                        await number_router.record_unroutable(UNROUTABLE_MISSING)
                         continue
 
-                    # Get WhatsApp number from database
-                    stmt = select(WhatsAppNumber).where(
-                        WhatsAppNumber.phone_number_id == phone_number_id
-                    )
-                    result = await self.db.execute(stmt)
-                    whatsapp_number = result.scalar_one_or_none()
+                    route = await number_router.resolve(phone_number_id)
+                    if not route:
+                        await number_router.record_unroutable(UNROUTABLE_UNKNOWN, phone_number_id)
+                        continue
 
-                    if not whatsapp_number:
+                    whatsapp_number = await self.db.get(WhatsAppNumber, route.config["whatsapp_number_id"])
+                    if not whatsapp_number or whatsapp_number.deleted_at:
+                        # Removido desde que a rota foi cacheada
+                        number_router.invalidate(phone_number_id)
                         logger.warning(f"WhatsApp number not found for phone_number_id: {phone_number_id}")
                         continue
 
-                    # Process messages
+                    # Mensagens enviadas pelo app WhatsApp Business (coexistência)
+                    if field in ECHO_FIELDS:
+                        handled += 1
+                        for echo in value.get("message_echoes", []):
+                            key = echo_dedup_key(echo) if dedup else None
+                            if not await processor.claim(key):
+                                logger.info(f"Duplicate echo skipped: {key}")
+                                continue
+                            if key:
+                                claimed.append(key)
+                            await self._process_message_echo(MessageEcho.from_webhook(echo), whatsapp_number)
+                        continue
+
+                    # Process messages (redeliveries of the same id are skipped)
                     if field == "messages":
+                        handled += 1
                         messages = value.get("messages", [])
                         for message in messages:
+                            key = message_dedup_key(message) if dedup else None
+                            if not await processor.claim(key):
+                                logger.info(f"Duplicate message skipped: {key}")
+                                continue
+                            if key:
+                                claimed.append(key)
                             await self._process_incoming_message(message, whatsapp_number)
 
                         # Process statuses
                         statuses = value.get("statuses", [])
                         for status in statuses:
+                            key = status_dedup_key(status) if dedup else None
+                            if not await processor.claim(key):
+                                logger.info(f"Duplicate status skipped: {key}")
+                                continue
+                            if key:
+                                claimed.append(key)
                             await self._process_message_status(status, whatsapp_number)
 
             await self.db.commit()
+            return handled
 
         except Exception as e:
             logger.error(f"Error processing webhook: {e}")
             await self.db.rollback()
+            # Nothing was saved: let the task retry process these ids again
+            for key in claimed:
+                await processor.release(key)
             raise
 
     async def _process_incoming_message(
@@ -4170,6 +6526,15 @@ __result__ = __script_func__()
             logger.warning("Missing required fields in message")
             return
 
+        # Reactions and edits change an earlier message instead of creating a new one
+        event_type = classify_message(message)
+        if event_type == WebhookEventType.REACTION:
+            await self._process_reaction(MessageReaction.from_webhook(message), whatsapp_number)
+            return
+        if event_type == WebhookEventType.EDIT:
+            await self._process_message_edit(MessageEdit.from_webhook(message), whatsapp_number)
+            return
+
         # 1. Get or Create Contact
         contact_repo = ContactRepository(self.db)
         contact = await contact_repo.get_by_whatsapp_id(
@@ -4280,22 +6645,11 @@ __result__ = __script_func__()
             logger.info(f"Message {whatsapp_message_id} already processed. Skipping duplicate.")
             return  # Idempotent - just return without error
 
-        # Extract content based on message type
-        content = {}
-        if message_type == "text":
-            content = {"text": message.get("text", {}).get("body", "")}
-        elif message_type == "image":
-            content = {"image": message.get("image", {})}
-        elif message_type == "video":
-            content = {"video": message.get("video", {})}
-        elif message_type == "audio":
-            content = {"audio": message.get("audio", {})}
-        elif message_type == "document":
-            content = {"document": message.get("document", {})}
-        elif message_type == "location":
-            content = {"location": message.get("location", {})}
-        else:
-            content = message.get(message_type, {})
+        # Extract content based on message type (unknown types keep the raw payload)
+        inbound = InboundMessage.from_webhook(message)
+        content = inbound.to_content()
+        if inbound.type in (InboundMessageType.UNKNOWN, InboundMessageType.UNSUPPORTED):
+            logger.warning(f"⚠️ Unsupported message type stored raw: {message_type} ({whatsapp_message_id})")
 
         message_data = {
             "organization_id": whatsapp_number.organization_id,
@@ -4310,12 +6664,65 @@ __result__ = __script_func__()
             "status": "received",
         }
 
+        # Reply/forward context (quoted message is linked when we have it)
+        message_context = MessageContext.from_webhook(message)
+        if message_context:
+            message_data["extra_data"] = {"context": message_context.model_dump(exclude_none=True)}
+
+            if message_context.quoted_message_id:
+                quoted_stmt = select(Message.id).where(
+                    Message.whatsapp_message_id == message_context.quoted_message_id,
+                    Message.organization_id == whatsapp_number.organization_id,
+                )
+                message_data["reply_to_message_id"] = (
+                    await self.db.execute(quoted_stmt)
+                ).scalar_one_or_none()
+
         new_message = await message_repo.create(message_data)
         logger.info(f"Saved message: {new_message.id} (WhatsApp ID: {whatsapp_message_id})")
 
-        # 4. Trigger chatbot se configurado
-        if conversation.is_bot_active and conversation.active_chatbot_id:
-            await self._trigger_chatbot(conversation, new_message)
+        # Idioma do contato a partir das primeiras mensagens (antes do chatbot escolher templates)
+        self._detect_contact_language(contact, inbound)
+
+        # Aviso de segurança (troca de número/identidade): reverificar o contato antes de dados sensíveis
+        if event_type == WebhookEventType.IDENTITY_CHANGE:
+            from app.services.identity_service import IdentityVerificationService
+
+            await IdentityVerificationService(self.db).flag(
+                conversation, IdentityChange.from_webhook(message)
+            )
+
+        # 4. Trigger chatbot se configurado (avisos de sistema não disparam o bot)
+        # Um processo por vez executa a sessão do fluxo da conversa (várias réplicas da API); com o
+        # motor saturado, contatos VIP e planos pagos passam na frente (ver app.core.flow_lanes)
+        lane = await self._flow_lane(contact, whatsapp_number.organization_id)
+        try:
+            async with self._flow_session(conversation), get_flow_lane_scheduler().slot(lane):
+                event_flow_started = False
+                if event_type == WebhookEventType.ORDER:
+                    event_flow_started = await self._trigger_event_flow(
+                        conversation, WebhookEventType.ORDER, content["order"], new_message
+                    )
+                elif event_type == WebhookEventType.REQUEST_WELCOME:
+                    # Primeiro contato do usuário: fluxo de boas-vindas mapeado em event_flows["request_welcome"]
+                    event_flow_started = await self._trigger_event_flow(
+                        conversation,
+                        WebhookEventType.REQUEST_WELCOME,
+                        {"whatsapp_id": whatsapp_contact_id, "name": contact.whatsapp_name},
+                        new_message,
+                    )
+                elif event_type != WebhookEventType.IDENTITY_CHANGE:
+                    event_flow_started = await self._trigger_campaign_reply_flow(conversation, contact, new_message)
+
+                if (
+                    not event_flow_started
+                    and event_type != WebhookEventType.IDENTITY_CHANGE
+                    and conversation.is_bot_active
+                    and conversation.active_chatbot_id
+                ):
+                    await self._trigger_chatbot(conversation, new_message)
+        except FlowSessionLockError as e:
+            logger.warning(f"🔒 {e}: mensagem {new_message.id} salva sem executar o bot")
 
         # 5. TODO: Send to queue if needed
         # if not conversation.is_bot_active and not conversation.current_agent_id:
@@ -4335,6 +6742,8 @@ __result__ = __script_func__()
             "content": new_message.content,
             "status": new_message.status,
             "whatsapp_message_id": new_message.whatsapp_message_id,
+            "reply_to_message_id": str(new_message.reply_to_message_id) if new_message.reply_to_message_id else None,
+            "context": (new_message.extra_data or {}).get("context"),
             "created_at": new_message.created_at.isoformat() if new_message.created_at else None,
         }
 
@@ -4345,8 +6754,30 @@ __result__ = __script_func__()
         )
 
         logger.info(f"[WebSocket] Emitted message:new for incoming message {new_message.id}")
+
+        # Participants who are not looking at the conversation get notified per their preference
+        from app.services.participant_service import ConversationParticipantService
+
+        await ConversationParticipantService(self.db).notify(
+            conversation.id, "conversation:participant_message", message_dict
+        )
         logger.info(f"✅ Message processed successfully")
 
+    def _detect_contact_language(self, contact, inbound: InboundMessage) -> None:
+        """Store the contact's language when one of their first messages reveals it"""
+        if (
+            not settings.LANGUAGE_DETECTION_ENABLED
+            or contact.language
+            or (contact.total_messages_received or 0) > settings.LANGUAGE_DETECTION_MESSAGES
+        ):
+            return
+
+        text = inbound.text or (inbound.media.caption if inbound.media else None)
+        language = detect_language(text)
+        if language:
+            contact.language = language
+            logger.info(f"🌐 Contact {contact.id} language detected: {language}")
+
     async def _process_message_status(
         self, status: Dict[str, Any], whatsapp_number: WhatsAppNumber
     ) -> None:
@@ -4364,81 +6795,261 @@ __result__ = __script_func__()
                 "title": "Re-engagement message"
             }]
         }
+
+        Payment statuses go to _process_payment_status; delivery receipts
+        are applied by MessageStatusTracker (status, timestamps, error,
+        campaign stats and WebSocket events).
         """
+        from app.services.message_status_tracker import MessageStatusTracker
+
+        if classify_status(status) == WebhookEventType.PAYMENT:
+            await self._process_payment_status(status, whatsapp_number)
+            return
+
+        await MessageStatusTracker(self.db).handle(status, whatsapp_number.organization_id)
+
+    async def _get_message_by_whatsapp_id(
+        self, whatsapp_message_id: Optional[str], whatsapp_number: WhatsAppNumber
+    ) -> Optional[Message]:
+        """Mensagem pelo ID do WhatsApp (conteúdo descriptografado)"""
         from app.repositories.conversation import MessageRepository
-        from datetime import datetime
 
-        whatsapp_message_id = status.get("id")
-        message_status = status.get("status")
+        if not whatsapp_message_id:
+            return None
+        return await MessageRepository(self.db).get_by_whatsapp_id(
+            whatsapp_message_id, whatsapp_number.organization_id
+        )
+
+    @staticmethod
+    def _webhook_datetime(timestamp: Optional[int]) -> str:
+        from datetime import datetime, timezone
+
+        if timestamp:
+            return datetime.fromtimestamp(int(timestamp), tz=timezone.utc).isoformat()
+        return datetime.now(timezone.utc).isoformat()
+
+    async def _process_message_echo(
+        self, echo: MessageEcho, whatsapp_number: WhatsAppNumber
+    ) -> None:
+        """
+        Store a message the business sent from the WhatsApp Business app
+
+        The echo is added as an outbound agent message (without sender user)
+        to the contact's open conversation so the history matches what the
+        contact sees; echoes for contacts without an open conversation are
+        skipped. A message:new WebSocket event is emitted.
+        """
+        from app.repositories.contact import ContactRepository
+        from app.repositories.conversation import ConversationRepository, MessageRepository
 
-        if not whatsapp_message_id or not message_status:
-            logger.warning("Missing required fields in status update")
+        if not echo.id or not echo.to:
+            logger.warning("Missing required fields in message echo")
             return
 
-        logger.info(f"Processing status update: {whatsapp_message_id} -> {message_status}")
+        if await self._get_message_by_whatsapp_id(echo.id, whatsapp_number):
+            logger.info(f"Echo {echo.id} already stored. Skipping duplicate.")
+            return
 
-        # Find message by WhatsApp message ID
-        message_repo = MessageRepository(self.db)
-        stmt = select(Message).where(
-            Message.whatsapp_message_id == whatsapp_message_id,
-            Message.organization_id == whatsapp_number.organization_id,
+        contact = await ContactRepository(self.db).get_by_whatsapp_id(
+            whatsapp_id=echo.to, organization_id=whatsapp_number.organization_id
         )
-        result = await self.db.execute(stmt)
-        message = result.scalar_one_or_none()
+        if not contact:
+            logger.info(f"Echo {echo.id} skipped: no contact for WhatsApp ID {echo.to}")
+            return
+
+        conversations = await ConversationRepository(self.db).get_by_contact(
+            contact_id=contact.id,
+            organization_id=whatsapp_number.organization_id,
+            status="open",
+        )
+        if not conversations:
+            logger.info(f"Echo {echo.id} skipped: no open conversation for contact {contact.id}")
+            return
+        conversation = conversations[0]
+
+        new_message = await MessageRepository(self.db).create({
+            "organization_id": whatsapp_number.organization_id,
+            "conversation_id": conversation.id,
+            "whatsapp_number_id": whatsapp_number.id,
+            "direction": "outbound",
+            "sender_type": "agent",
+            "whatsapp_message_id": echo.id,
+            "whatsapp_timestamp": echo.timestamp,
+            "message_type": echo.message.raw_type or "text",
+            "content": echo.message.to_content(),
+            "status": "sent",
+            "extra_data": {"source": "business_app"},
+        })
+        logger.info(f"📱 Stored business app echo {echo.id} as message {new_message.id}")
+
+        from app.websocket.manager import emit_to_conversation
+
+        await emit_to_conversation(
+            conversation_id=str(conversation.id),
+            event="message:new",
+            data={
+                "id": str(new_message.id),
+                "conversation_id": str(conversation.id),
+                "direction": new_message.direction,
+                "sender_type": new_message.sender_type,
+                "message_type": new_message.message_type,
+                "content": new_message.content,
+                "status": new_message.status,
+                "whatsapp_message_id": new_message.whatsapp_message_id,
+                "source": "business_app",
+                "created_at": new_message.created_at.isoformat() if new_message.created_at else None,
+            },
+        )
+
+    async def _process_reaction(
+        self, reaction: MessageReaction, whatsapp_number: WhatsAppNumber
+    ) -> None:
+        """
+        Apply a contact reaction to the reacted message
+
+        WhatsApp keeps one reaction per person and message, so a new emoji
+        replaces the previous one and an empty emoji removes it. Reactions are
+        stored in Message.extra_data["reactions"] and a message:reaction
+        WebSocket patch is emitted.
+        """
+        from app.repositories.conversation import MessageRepository
 
+        message = await self._get_message_by_whatsapp_id(reaction.message_id, whatsapp_number)
         if not message:
-            logger.warning(f"Message not found for WhatsApp ID: {whatsapp_message_id}")
+            logger.warning(f"Reacted message not found for WhatsApp ID: {reaction.message_id}")
             return
 
-        # Update message status
-        now = datetime.utcnow()
-        update_data = {"status": message_status}
+        reactions = [
+            item for item in (message.extra_data or {}).get("reactions", [])
+            if item.get("from") != reaction.sender
+        ]
+        if not reaction.is_removal:
+            reactions.append({
+                "from": reaction.sender,
+                "emoji": reaction.emoji,
+                "reacted_at": self._webhook_datetime(reaction.timestamp),
+            })
+
+        extra_data = {**(message.extra_data or {}), "reactions": reactions}
+        await MessageRepository(self.db).update(message.id, {"extra_data": extra_data})
+
+        logger.info(
+            f"{'🗑️ Reaction removed from' if reaction.is_removal else f'{reaction.emoji} Reaction on'} "
+            f"message {message.id}"
+        )
+
+        from app.websocket.manager import emit_to_conversation
 
-        if message_status == "sent":
-            update_data["sent_at"] = now
-            logger.info(f"✅ Message {message.id} marked as sent")
+        await emit_to_conversation(
+            conversation_id=str(message.conversation_id),
+            event="message:reaction",
+            data={
+                "message_id": str(message.id),
+                "from": reaction.sender,
+                "emoji": reaction.emoji,
+                "removed": reaction.is_removal,
+                "reactions": reactions,
+            },
+        )
+
+    async def _process_message_edit(
+        self, edit: MessageEdit, whatsapp_number: WhatsAppNumber
+    ) -> None:
+        """
+        Replace the content of a message edited by the contact
 
-        elif message_status == "delivered":
-            update_data["delivered_at"] = now
-            logger.info(f"✅ Message {message.id} marked as delivered")
+        The previous content is kept in Message.extra_data["edit_history"]
+        and a message:edited WebSocket patch is emitted. The message is read
+        decrypted and written back through MessageRepository, so encrypted
+        tenants keep the new content and the history encrypted at rest.
+        """
+        from app.repositories.conversation import MessageRepository
 
-        elif message_status == "read":
-            update_data["read_at"] = now
-            logger.info(f"✅ Message {message.id} marked as read")
+        message = await self._get_message_by_whatsapp_id(edit.original_message_id, whatsapp_number)
+        if not message:
+            logger.warning(f"Edited message not found for WhatsApp ID: {edit.original_message_id}")
+            return
 
-        elif message_status == "failed":
-            update_data["failed_at"] = now
+        if not message.is_inbound:
+            logger.warning(f"Ignoring edit of outbound message {message.id}")
+            return
 
-            # Extract error information
-            errors = status.get("errors", [])
-            if errors:
-                error = errors[0]  # Get first error
-                update_data["error_code"] = str(error.get("code", "unknown"))
-                update_data["error_message"] = error.get("title") or error.get("message", "Unknown error")
+        edited_at = self._webhook_datetime(edit.timestamp)
+        history = list((message.extra_data or {}).get("edit_history", []))
+        history.append({"content": message.content, "replaced_at": edited_at})
 
-            logger.error(
-                f"❌ Message {message.id} failed: "
-                f"{update_data.get('error_code')} - {update_data.get('error_message')}"
-            )
+        extra_data = {**(message.extra_data or {}), "edit_history": history, "edited_at": edited_at}
+        await MessageRepository(self.db).update(
+            message.id, {"content": edit.content, "extra_data": extra_data}
+        )
 
-        # Update in database
-        await message_repo.update(message.id, update_data)
+        logger.info(f"✏️ Message {message.id} edited by contact ({len(history)} edit(s))")
 
-        # Emit WebSocket event for status update
         from app.websocket.manager import emit_to_conversation
-        from datetime import timezone
 
         await emit_to_conversation(
             conversation_id=str(message.conversation_id),
-            event="message:status",
+            event="message:edited",
             data={
                 "message_id": str(message.id),
-                "status": message_status,
-                "timestamp": datetime.now(timezone.utc).isoformat()
-            }
+                "content": edit.content,
+                "edited_at": edited_at,
+                "edit_count": len(history),
+            },
+        )
+
+    async def _process_payment_status(
+        self, status: Dict[str, Any], whatsapp_number: WhatsAppNumber
+    ) -> None:
+        """
+        Process a payment status notification (status.type == "payment").
+
+        Exposes the payment as {{payment}} in the contact's open conversation,
+        starts the chatbot's "payment" event flow if configured and emits a
+        payment:status WebSocket event.
+        """
+        from app.repositories.contact import ContactRepository
+        from app.repositories.conversation import ConversationRepository
+
+        payment = PaymentStatus.from_webhook(status)
+        logger.info(
+            f"💳 Payment {payment.reference_id} -> {payment.status} "
+            f"({payment.amount.decimal} {payment.currency})"
+        )
+
+        if not payment.recipient_id:
+            logger.warning("Payment status without recipient_id")
+            return
+
+        contact = await ContactRepository(self.db).get_by_whatsapp_id(
+            whatsapp_id=payment.recipient_id,
+            organization_id=whatsapp_number.organization_id,
+        )
+        if not contact:
+            logger.warning(f"Contact not found for payment recipient {payment.recipient_id}")
+            return
+
+        conversations = await ConversationRepository(self.db).get_by_contact(
+            contact_id=contact.id,
+            organization_id=whatsapp_number.organization_id,
+            status="open",
+        )
+        if not conversations:
+            logger.info(f"No open conversation for payment {payment.reference_id}")
+            return
+
+        conversation = conversations[0]
+        await self._trigger_event_flow(
+            conversation, WebhookEventType.PAYMENT, payment.to_variables()
         )
 
-        logger.info(f"[WebSocket] Emitted message:status update for message {message.id}")
+        from app.websocket.manager import emit_to_conversation
+
+        await emit_to_conversation(
+            conversation_id=str(conversation.id),
+            event="payment:status",
+            data=payment.to_variables(),
+        )
 
     # ============= Evolution API Methods =============
 
@@ -4672,7 +7283,9 @@ __result__ = __script_func__()
         organization_id: UUID,
         message_type: str,
         content: Dict[str, Any],
-        sender_user_id: Optional[UUID] = None
+        sender_user_id: Optional[UUID] = None,
+        reply_to_message_id: Optional[UUID] = None,
+        client_message_id: Optional[str] = None
     ) -> Message:
         """
         Send a message via WhatsApp
@@ -4683,22 +7296,31 @@ __result__ = __script_func__()
             message_type: Message type (text, image, document, template)
             content: Message content (depends on type)
             sender_user_id: User ID of sender (agent/bot)
+            reply_to_message_id: Message (in this conversation) to quote
+            client_message_id: Optimistic id from the sender's UI (echoed in events)
 
         Returns:
             Created message with whatsapp_message_id
 
         Raises:
             NotFoundException: If conversation not found
+            BadRequestException: If content exceeds WhatsApp limits
             ValueError: If 24h window expired and no template provided
             MetaAPIError: If API call fails
         """
         from app.repositories.conversation import ConversationRepository, MessageRepository
         from app.repositories.contact import ContactRepository
-        from app.integrations.meta_api import MetaCloudAPI, MetaAPIError
+        from app.integrations.meta_api import MetaAPIError
         from datetime import datetime
 
         logger.info(f"Sending {message_type} message to conversation {conversation_id}")
 
+        # 0. WhatsApp limits (length in graphemes, button/list counts)
+        try:
+            ensure_valid_outbound_message(message_type, content)
+        except OutboundMessageError as e:
+            raise BadRequestException(f"Message exceeds WhatsApp limits: {e}")
+
         # 1. Get conversation and validate
         conversation_repo = ConversationRepository(self.db)
         conversation = await conversation_repo.get_with_contact(conversation_id, organization_id)
@@ -4742,6 +7364,18 @@ __result__ = __script_func__()
         # 5. Create message record with pending status
         message_repo = MessageRepository(self.db)
 
+        # Quoted message must belong to this conversation and be known to WhatsApp
+        quoted_whatsapp_id = None
+        if reply_to_message_id:
+            quoted = await message_repo.get(reply_to_message_id)
+            if not quoted or quoted.conversation_id != conversation_id:
+                raise NotFoundException("Quoted message not found in this conversation")
+            quoted_whatsapp_id = quoted.whatsapp_message_id
+            if not quoted_whatsapp_id:
+                logger.warning(
+                    f"Quoted message {reply_to_message_id} has no WhatsApp ID, sending without context"
+                )
+
         # Determine sender type
         if sender_user_id:
             sender_type = "agent"
@@ -4758,18 +7392,31 @@ __result__ = __script_func__()
             "message_type": message_type,
             "content": content,
             "status": "pending",
+            "reply_to_message_id": reply_to_message_id,
         }
 
+        if client_message_id:
+            message_data["extra_data"] = {"client_message_id": client_message_id}
+
         message = await message_repo.create(message_data)
+
+        # Attribute the message to the agent (joins as collaborator if needed)
+        joined_participant = None
+        if sender_user_id:
+            from app.services.participant_service import ConversationParticipantService
+
+            participants = ConversationParticipantService(self.db)
+            joined_participant = await participants.record_outbound(conversation, sender_user_id)
+
         await self.db.commit()
 
+        if joined_participant:
+            await participants.broadcast("participant:joined", joined_participant)
+
         logger.info(f"Message {message.id} created with status 'pending'")
 
         # 6. Send via Meta Cloud API
-        meta_api = MetaCloudAPI(
-            phone_number_id=whatsapp_number.phone_number_id,
-            access_token=whatsapp_number.access_token
-        )
+        meta_api = whatsapp_clients.client(whatsapp_number)
 
         try:
             # Get contact WhatsApp ID (remove + if present)
@@ -4777,18 +7424,27 @@ __result__ = __script_func__()
             recipient = contact.whatsapp_id.replace("+", "")
 
             # Send based on message type
-            if message_type == "text":
+            if message_type == "text" and content.get("preview_url"):
+                response = await meta_api.send_text_with_preview(
+                    to=recipient,
+                    text=content.get("text", ""),
+                    url=content.get("preview_link"),
+                    reply_to_message_id=quoted_whatsapp_id
+                )
+
+            elif message_type == "text":
                 response = await meta_api.send_text_message(
                     to=recipient,
                     text=content.get("text", ""),
-                    preview_url=content.get("preview_url", False)
+                    reply_to_message_id=quoted_whatsapp_id
                 )
 
             elif message_type == "image":
                 response = await meta_api.send_image_message(
                     to=recipient,
                     image_url=content.get("url"),
-                    caption=content.get("caption")
+                    caption=content.get("caption"),
+                    reply_to_message_id=quoted_whatsapp_id
                 )
 
             elif message_type == "document":
@@ -4796,7 +7452,8 @@ __result__ = __script_func__()
                     to=recipient,
                     document_url=content.get("url"),
                     filename=content.get("filename"),
-                    caption=content.get("caption")
+                    caption=content.get("caption"),
+                    reply_to_message_id=quoted_whatsapp_id
                 )
 
             elif message_type == "template":
@@ -4804,7 +7461,30 @@ __result__ = __script_func__()
                     to=recipient,
                     template_name=content.get("name"),
                     language_code=content.get("language", "pt_BR"),
-                    components=content.get("components")
+                    components=content.get("components"),
+                    reply_to_message_id=quoted_whatsapp_id
+                )
+
+            elif message_type == "cta_url":
+                response = await meta_api.send_cta_url_message(
+                    to=recipient,
+                    body_text=content.get("body", ""),
+                    display_text=content.get("display_text", ""),
+                    url=content.get("url", ""),
+                    header_text=content.get("header"),
+                    footer_text=content.get("footer"),
+                    reply_to_message_id=quoted_whatsapp_id
+                )
+
+            elif message_type == "address":
+                response = await meta_api.send_address_message(
+                    to=recipient,
+                    body_text=content.get("text", ""),
+                    country=content.get("country", ""),
+                    values=content.get("values"),
+                    saved_addresses=content.get("saved_addresses"),
+                    validation_errors=content.get("validation_errors"),
+                    reply_to_message_id=quoted_whatsapp_id
                 )
 
             else:
@@ -4817,13 +7497,22 @@ __result__ = __script_func__()
                 await message_repo.update(message.id, {
                     "whatsapp_message_id": whatsapp_message_id,
                     "status": "sent",
-                    "sent_at": datetime.utcnow()
+                    "sent_at": datetime.utcnow(),
+                    "extra_data": _with_meta_response(message, getattr(response, "response", None)),
                 })
 
                 logger.info(f"✅ Message sent successfully. WhatsApp ID: {whatsapp_message_id}")
             else:
                 logger.warning("No message ID returned from Meta API")
 
+            # Business-initiated templates count against the number's messaging tier
+            if message_type == "template" and not is_within_window:
+                from app.core.whatsapp_rate_limit import get_tier_shaper
+
+                shaper = get_tier_shaper(whatsapp_number)
+                if shaper:
+                    await shaper.record(recipient)
+
             # 8. Update conversation metrics
             await conversation_repo.update(conversation_id, {
                 "last_message_at": datetime.utcnow(),
@@ -4835,6 +7524,7 @@ __result__ = __script_func__()
 
             await self.db.commit()
             await self.db.refresh(message)
+            await message_repo.decrypt_messages([message])
 
             # Emit WebSocket event for new message
             from app.websocket.manager import emit_to_conversation
@@ -4844,10 +7534,13 @@ __result__ = __script_func__()
                 "conversation_id": str(conversation_id),
                 "direction": message.direction,
                 "sender_type": message.sender_type,
+                "sender_user_id": str(message.sender_user_id) if message.sender_user_id else None,
                 "message_type": message.message_type,
                 "content": message.content,
                 "status": message.status,
                 "whatsapp_message_id": message.whatsapp_message_id,
+                "reply_to_message_id": str(message.reply_to_message_id) if message.reply_to_message_id else None,
+                "client_message_id": client_message_id,
                 "created_at": message.created_at.isoformat() if message.created_at else None,
                 "sent_at": message.sent_at.isoformat() if message.sent_at else None,
             }
@@ -4868,11 +7561,12 @@ __result__ = __script_func__()
                 "status": "failed",
                 "failed_at": datetime.utcnow(),
                 "error_code": e.error_code,
-                "error_message": e.message
+                "error_message": e.message,
+                "extra_data": _with_meta_response(message, e.response),
             })
             await self.db.commit()
 
-            logger.error(f"Failed to send message: {e.message}")
+            logger.error(f"Failed to send message: {e.message} (fbtrace_id: {e.fbtrace_id})")
             raise
 
         except Exception as e:
@@ -4886,3 +7580,72 @@ __result__ = __script_func__()
 
             logger.error(f"Unexpected error sending message: {e}")
             raise
+
+    async def send_read_receipts(self, conversation_id: UUID, organization_id: UUID) -> int:
+        """
+        Send read receipts (blue ticks) for inbound messages not yet read
+
+        Best-effort: failures are logged and the messages stay unread so the
+        next call retries them.
+
+        Args:
+            conversation_id: Conversation ID
+            organization_id: Organization ID
+
+        Returns:
+            Number of messages marked as read on WhatsApp
+        """
+        from app.repositories.conversation import ConversationRepository
+        from datetime import datetime
+
+        conversation = await ConversationRepository(self.db).get_with_contact(conversation_id, organization_id)
+        if not conversation:
+            raise NotFoundException("Conversation not found")
+
+        result = await self.db.execute(
+            select(Message).where(
+                Message.conversation_id == conversation_id,
+                Message.direction == "inbound",
+                Message.read_at.is_(None),
+                Message.whatsapp_message_id.isnot(None),
+                Message.deleted_at.is_(None),
+            )
+        )
+        messages = {m.whatsapp_message_id: m for m in result.scalars().all()}
+        if not messages:
+            return 0
+
+        whatsapp_number = await self.repo.get(conversation.whatsapp_number_id)
+        if not whatsapp_number:
+            return 0
+
+        read_ids: List[str] = []
+        try:
+            if whatsapp_number.connection_type == "official":
+                meta_api = whatsapp_clients.client(whatsapp_number)
+                response = await meta_api.mark_messages_as_read(list(messages))
+                read_ids = response["read"]
+
+            elif whatsapp_number.connection_type == "qrcode":
+                evolution = EvolutionAPIClient(
+                    api_url=whatsapp_number.evolution_api_url,
+                    api_key=whatsapp_number.evolution_api_key
+                )
+                await evolution.mark_messages_as_read(
+                    instance_name=whatsapp_number.evolution_instance_name,
+                    phone_number=conversation.contact.whatsapp_id.replace("+", ""),
+                    message_ids=list(messages),
+                )
+                read_ids = list(messages)
+
+        except Exception as e:
+            logger.warning(f"⚠️ Could not send read receipts for conversation {conversation_id}: {e}")
+            return 0
+
+        now = datetime.utcnow()
+        for whatsapp_message_id in read_ids:
+            messages[whatsapp_message_id].read_at = now
+        await self.db.commit()
+
+        logger.info(f"✅ Marked {len(read_ids)} message(s) as read (conversation {conversation_id})")
+        return len(read_ids)
@@ -0,0 +1,249 @@
+"""
+WhatsApp Token Service - Meta access token validity and rotation
+
+Tokens are checked with the Graph API debug_token endpoint (daily by the
+check_whatsapp_tokens task, or on demand) and the result is kept on the
+number (token_status, token_expires_at). Once per token, when it enters the
+last WHATSAPP_TOKEN_EXPIRY_WARNING_DAYS, expires or is revoked:
+- whatsapp:token_expiring is emitted to the organization room
+- listeners registered with on_token_expiring run (email the admins, open a
+  ticket...)
+
+rotate_token swaps a number's token without a restart: the new token is
+validated first and the client pool picks it up on the next call.
+"""
+
+import logging
+from dataclasses import asdict, dataclass
+from datetime import datetime, timedelta, timezone
+from typing import Any, Awaitable, Callable, Dict, List, Optional
+from uuid import UUID
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.config import settings
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.integrations.meta_api import MetaAPIError, MetaCloudAPI
+from app.integrations.whatsapp_pool import whatsapp_clients
+from app.models.whatsapp_number import WhatsAppNumber
+
+logger = logging.getLogger(__name__)
+
+TOKEN_VALID = "valid"
+TOKEN_EXPIRING = "expiring"
+TOKEN_EXPIRED = "expired"
+TOKEN_INVALID = "invalid"
+
+# Graph API error code of expired/revoked tokens
+INVALID_TOKEN_CODE = "190"
+
+
+@dataclass
+class TokenExpiring:
+    """A number's access token about to expire, expired or revoked"""
+
+    whatsapp_number_id: str
+    organization_id: str
+    phone_number: str
+    status: str
+    expires_at: Optional[datetime]
+    days_left: Optional[int]
+    reason: Optional[str] = None
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data["expires_at"] = self.expires_at.isoformat() if self.expires_at else None
+        return data
+
+
+TokenListener = Callable[[TokenExpiring], Awaitable[None]]
+
+_listeners: List[TokenListener] = []
+
+
+def on_token_expiring(listener: TokenListener) -> TokenListener:
+    """
+    Call ``listener(event)`` for every TokenExpiring event
+
+    Usable as a decorator. Listener failures are logged and never stop the
+    check of other numbers.
+    """
+    if listener not in _listeners:
+        _listeners.append(listener)
+    return listener
+
+
+def remove_token_listener(listener: TokenListener) -> None:
+    if listener in _listeners:
+        _listeners.remove(listener)
+
+
+def token_status(
+    is_valid: bool,
+    expires_at: Optional[datetime],
+    now: Optional[datetime] = None,
+    warning_days: Optional[int] = None,
+) -> str:
+    """Status of a token from its debug_token validity and expiry"""
+    now = now or datetime.now(timezone.utc)
+    warning_days = settings.WHATSAPP_TOKEN_EXPIRY_WARNING_DAYS if warning_days is None else warning_days
+
+    if expires_at and expires_at <= now:
+        return TOKEN_EXPIRED
+    if not is_valid:
+        return TOKEN_INVALID
+    if expires_at and expires_at - now <= timedelta(days=warning_days):
+        return TOKEN_EXPIRING
+    return TOKEN_VALID
+
+
+class WhatsAppTokenService:
+    """Service for checking and rotating Meta access tokens"""
+
+    def __init__(self, db: AsyncSession):
+        self.db = db
+
+    async def get_status(self, number_id: UUID, organization_id: UUID) -> Dict[str, Any]:
+        """Check the token of a number now and return its status"""
+        number = await self._get_official_number(number_id, organization_id)
+        return await self.check_number(number)
+
+    async def check_number(self, number: WhatsAppNumber) -> Dict[str, Any]:
+        """
+        Inspect the number's token, store the result and raise TokenExpiring once per token
+
+        Raises:
+            MetaAPIError: Network or Graph API failure other than an invalid token
+        """
+        info = await self._inspect(whatsapp_clients.client(number))
+        now = datetime.now(timezone.utc)
+        status = token_status(info["is_valid"], info["expires_at"], now)
+
+        number.token_status = status
+        number.token_expires_at = info["expires_at"]
+        number.token_checked_at = now
+
+        notify = status != TOKEN_VALID and number.token_expiry_notified_at is None
+        if status == TOKEN_VALID:
+            number.token_expiry_notified_at = None
+        elif notify:
+            number.token_expiry_notified_at = now
+        await self.db.commit()
+
+        if notify:
+            await self._notify(number, info.get("error"))
+        return self._result(number, info)
+
+    async def check_all(self) -> Dict[str, int]:
+        """Check the tokens of every active official number"""
+        numbers = (await self.db.execute(
+            select(WhatsAppNumber).where(
+                WhatsAppNumber.connection_type == "official",
+                WhatsAppNumber.is_active.is_(True),
+                WhatsAppNumber.access_token.isnot(None),
+            )
+        )).scalars().all()
+
+        counts = {"checked": 0, "failed": 0, TOKEN_VALID: 0, TOKEN_EXPIRING: 0, TOKEN_EXPIRED: 0, TOKEN_INVALID: 0}
+        for number in numbers:
+            try:
+                result = await self.check_number(number)
+            except MetaAPIError as e:
+                counts["failed"] += 1
+                logger.warning(f"⚠️ Could not check the token of {number.phone_number}: {e.message}")
+                continue
+            counts["checked"] += 1
+            counts[result["status"]] += 1
+        return counts
+
+    async def rotate_token(self, number_id: UUID, organization_id: UUID, access_token: str) -> Dict[str, Any]:
+        """
+        Replace a number's token after validating the new one
+
+        Raises:
+            BadRequestException: If the new token is invalid or already expired
+        """
+        number = await self._get_official_number(number_id, organization_id)
+        access_token = access_token.strip()
+
+        try:
+            info = await self._inspect(MetaCloudAPI(phone_number_id=number.phone_number_id, access_token=access_token))
+        except MetaAPIError as e:
+            raise BadRequestException(f"Could not validate the new token: {e.message}")
+
+        now = datetime.now(timezone.utc)
+        status = token_status(info["is_valid"], info["expires_at"], now)
+        if status in (TOKEN_INVALID, TOKEN_EXPIRED):
+            raise BadRequestException(f"New access token is {status}: {info.get('error') or 'rejected by Meta'}")
+
+        number.access_token = access_token
+        number.token_status = status
+        number.token_expires_at = info["expires_at"]
+        number.token_checked_at = now
+        number.token_expiry_notified_at = None
+        await self.db.commit()
+
+        logger.info(f"🔑 Access token of {number.phone_number} rotated ({status})")
+        return self._result(number, info)
+
+    async def _inspect(self, api: MetaCloudAPI) -> Dict[str, Any]:
+        """debug_token, with revoked/expired tokens reported as invalid instead of raising"""
+        try:
+            return await api.debug_token()
+        except MetaAPIError as e:
+            if str(e.error_code) != INVALID_TOKEN_CODE and e.status_code != 401:
+                raise
+            return {"is_valid": False, "expires_at": None, "scopes": [], "error": e.message}
+
+    async def _notify(self, number: WhatsAppNumber, reason: Optional[str]) -> None:
+        from app.websocket.manager import emit_to_organization
+
+        event = TokenExpiring(
+            whatsapp_number_id=str(number.id),
+            organization_id=str(number.organization_id),
+            phone_number=number.phone_number,
+            status=number.token_status,
+            expires_at=number.token_expires_at,
+            days_left=_days_left(number.token_expires_at),
+            reason=reason,
+        )
+        expiry = f" (expires {event.expires_at:%Y-%m-%d})" if event.expires_at else ""
+        logger.warning(f"🔑 Access token of {number.phone_number} is {event.status}{expiry}")
+
+        for listener in list(_listeners):
+            try:
+                await listener(event)
+            except Exception as e:
+                logger.error(f"❌ TokenExpiring listener {getattr(listener, '__name__', listener)} failed: {e}")
+
+        try:
+            await emit_to_organization(event.organization_id, "whatsapp:token_expiring", event.to_dict())
+        except Exception as e:
+            logger.warning(f"⚠️ Failed to emit whatsapp:token_expiring: {e}")
+
+    async def _get_official_number(self, number_id: UUID, organization_id: UUID) -> WhatsAppNumber:
+        number = await self.db.get(WhatsAppNumber, number_id)
+        if not number or number.organization_id != organization_id:
+            raise NotFoundException("WhatsApp number not found")
+        if number.connection_type != "official":
+            raise BadRequestException("Only available for Official API connections")
+        return number
+
+    @staticmethod
+    def _result(number: WhatsAppNumber, info: Dict[str, Any]) -> Dict[str, Any]:
+        return {
+            "whatsapp_number_id": str(number.id),
+            "status": number.token_status,
+            "expires_at": number.token_expires_at,
+            "days_left": _days_left(number.token_expires_at),
+            "checked_at": number.token_checked_at,
+            "scopes": info.get("scopes") or [],
+            "error": info.get("error"),
+        }
+
+
+def _days_left(expires_at: Optional[datetime]) -> Optional[int]:
+    if not expires_at:
+        return None
+    return max((expires_at - datetime.now(timezone.utc)).days, 0)
@@ -0,0 +1,85 @@
+"""
+Ad Audience Tasks - Celery tasks for Meta Custom Audiences sync
+
+sync_ad_audiences runs periodically and queues one sync_ad_audience per
+active audience of organizations with Meta Ads enabled. Opt-outs do not wait
+for the next run: remove_contact_from_ad_audiences is queued as soon as a
+contact opts out, is blocked or is deleted.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+from uuid import UUID
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.core.exceptions import BadRequestException
+from app.services.ad_audience_service import AdAudienceService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="sync_ad_audiences")
+def sync_ad_audiences() -> Dict[str, Any]:
+    """Periodic task that queues the sync of every active ad audience"""
+    logger.info("📣 Queuing ad audience syncs...")
+
+    try:
+        result = asyncio.run(_sync_ad_audiences_async())
+        logger.info(f"✅ Ad audience syncs queued: {result}")
+        return result
+
+    except Exception as e:
+        logger.error(f"❌ Failed to queue ad audience syncs: {str(e)}")
+        raise
+
+
+async def _sync_ad_audiences_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        audiences = await AdAudienceService(db).list_syncable_audiences()
+
+        for audience in audiences:
+            sync_ad_audience.apply_async(args=[str(audience.id), str(audience.organization_id)])
+
+        return {"audiences_queued": len(audiences)}
+
+
+@celery_app.task(
+    name="sync_ad_audience",
+    bind=True,
+    autoretry_for=(ConnectionError,),
+    retry_backoff=True,
+    max_retries=3,
+)
+def sync_ad_audience(self, audience_id: str, organization_id: str) -> Dict[str, Any]:
+    """Push the changes of one audience to Meta"""
+    return asyncio.run(_sync_ad_audience_async(audience_id, organization_id))
+
+
+async def _sync_ad_audience_async(audience_id: str, organization_id: str) -> Dict[str, Any]:
+    async with async_session() as db:
+        try:
+            result = await AdAudienceService(db).sync_audience(UUID(audience_id), UUID(organization_id))
+        except BadRequestException as e:
+            # Failure already recorded on the audience (last_sync_status / last_error)
+            return {"audience_id": audience_id, "status": "failed", "error": e.detail}
+
+        return {"audience_id": audience_id, "status": "success", **result.model_dump(mode="json")}
+
+
+@celery_app.task(
+    name="remove_contact_from_ad_audiences",
+    autoretry_for=(ConnectionError,),
+    retry_backoff=True,
+    max_retries=3,
+)
+def remove_contact_from_ad_audiences(contact_id: str) -> Dict[str, Any]:
+    """Remove a contact that opted out from every audience it was pushed to"""
+    removed = asyncio.run(_remove_contact_async(contact_id))
+    return {"contact_id": contact_id, "audiences": removed}
+
+
+async def _remove_contact_async(contact_id: str) -> int:
+    async with async_session() as db:
+        return await AdAudienceService(db).remove_contact(UUID(contact_id))
@@ -0,0 +1,195 @@
+"""
+Campaign Checkpoints - Crash-safe resume for campaign dispatching
+
+Persists the progress of every dispatch batch in ``Campaign.dispatch_checkpoint``
+so a crash or deploy mid-campaign resumes exactly where it stopped:
+- Contacts are dispatched in ascending id order
+- Each batch stores its exact contact ids and the last contact processed
+- Resumed batches dispatch their stored contacts after the cursor, so
+  contacts added to or removed from the audience meanwhile do not change them
+- A Redis claim per campaign+contact guards against double sends
+- Batches held back by the messaging tier record when they may resume
+"""
+
+import logging
+from datetime import datetime, timezone
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from sqlalchemy.orm.attributes import flag_modified
+
+from app.core.redis import redis_client
+from app.models.campaign import Campaign
+
+logger = logging.getLogger(__name__)
+
+# Statuses in message_statuses that mean the contact already got the message
+SENT_STATUSES = {"sent", "delivered", "read"}
+
+# How long a send claim is kept in Redis (7 days)
+SEND_CLAIM_TTL_SECONDS = 7 * 24 * 3600
+
+
+class CampaignCheckpointManager:
+    """
+    Reads and updates the dispatch checkpoint of a campaign
+
+    Checkpoint layout:
+        {
+            "batch_size": 100,
+            "batches": {
+                "0": {"contact_ids": ["<uuid>", ...], "cursor": "<uuid>|null", "done": false},
+                ...
+            },
+            "resume_count": 0,
+            "deferred_until": "2025-11-03T08:12:00" (only while held by the tier or throttle),
+            "updated_at": "2025-11-02T09:30:00"
+        }
+
+    The caller is responsible for committing the session.
+    """
+
+    def __init__(self, campaign: Campaign):
+        self.campaign = campaign
+
+        if self.campaign.dispatch_checkpoint is None:
+            self.campaign.dispatch_checkpoint = {}
+
+    @property
+    def checkpoint(self) -> Dict[str, Any]:
+        return self.campaign.dispatch_checkpoint
+
+    @property
+    def has_checkpoint(self) -> bool:
+        """True if batches were already planned for this campaign"""
+        return bool(self.checkpoint.get("batches"))
+
+    def _touch(self) -> None:
+        self.checkpoint["updated_at"] = datetime.utcnow().isoformat()
+        flag_modified(self.campaign, "dispatch_checkpoint")
+
+    def init_batches(self, batches: List[List[str]], batch_size: int) -> None:
+        """
+        Record the planned batches (contact ids must be sorted ascending)
+
+        Args:
+            batches: Contact id batches in dispatch order
+            batch_size: Size used to split the batches
+        """
+        self.checkpoint["batch_size"] = batch_size
+        self.checkpoint["batches"] = {
+            str(index): {
+                "contact_ids": list(contact_ids),
+                "cursor": None,
+                "done": False,
+            }
+            for index, contact_ids in enumerate(batches)
+            if contact_ids
+        }
+        self.checkpoint.setdefault("resume_count", 0)
+        self._touch()
+
+    def pending_batches(self) -> List[Dict[str, Any]]:
+        """
+        Get batches that still have contacts to dispatch
+
+        Returns:
+            List of dicts with index, cursor and the contact_ids after the cursor
+        """
+        pending = []
+        for index, batch in sorted(
+            self.checkpoint.get("batches", {}).items(), key=lambda item: int(item[0])
+        ):
+            if batch.get("done"):
+                continue
+            contact_ids = batch["contact_ids"]
+            cursor = batch.get("cursor")
+            if cursor in contact_ids:
+                contact_ids = contact_ids[contact_ids.index(cursor) + 1:]
+            pending.append({
+                "index": int(index),
+                "cursor": cursor,
+                "contact_ids": contact_ids,
+            })
+        return pending
+
+    def record_resume(self) -> None:
+        """Count a resume of the dispatcher"""
+        self.checkpoint["resume_count"] = self.checkpoint.get("resume_count", 0) + 1
+        self.checkpoint.pop("deferred_until", None)
+        self._touch()
+
+    def defer(self, until: datetime) -> None:
+        """Hold the dispatch until the messaging tier or campaign throttle has room (UTC)"""
+        if until.tzinfo is not None:
+            until = until.astimezone(timezone.utc).replace(tzinfo=None)
+        current = self.deferred_until
+        if current is None or until > current:
+            self.checkpoint["deferred_until"] = until.isoformat()
+        self._touch()
+
+    @property
+    def deferred_until(self) -> Optional[datetime]:
+        """When a deferred dispatch may resume (naive UTC), if deferred"""
+        value = self.checkpoint.get("deferred_until")
+        return datetime.fromisoformat(value) if value else None
+
+    def advance(self, batch_index: int, contact_id: UUID) -> None:
+        """Move the cursor of a batch past the given contact"""
+        batch = self.checkpoint.get("batches", {}).get(str(batch_index))
+        if batch is None:
+            return
+        batch["cursor"] = str(contact_id)
+        self._touch()
+
+    def mark_batch_done(self, batch_index: int) -> None:
+        """Mark a batch as fully dispatched"""
+        batch = self.checkpoint.get("batches", {}).get(str(batch_index))
+        if batch is None:
+            return
+        batch["done"] = True
+        batch["cursor"] = batch["contact_ids"][-1]
+        self._touch()
+
+    def is_complete(self) -> bool:
+        """True if every planned batch was fully dispatched"""
+        return self.has_checkpoint and not self.pending_batches()
+
+    def is_already_sent(self, contact_id: UUID) -> bool:
+        """Check message_statuses for a previous successful send"""
+        status = (self.campaign.message_statuses or {}).get(str(contact_id))
+        return bool(status) and status.get("status") in SENT_STATUSES
+
+    async def claim_send(self, contact_id: UUID) -> bool:
+        """
+        Claim the right to send to a contact (idempotency guard)
+
+        Uses Redis SET NX so two workers resuming the same batch never send
+        twice. Fails open when Redis is unavailable, relying on
+        message_statuses instead.
+
+        Returns:
+            True if this worker may send, False if already claimed
+        """
+        key = f"campaign:{self.campaign.id}:sent:{contact_id}"
+        try:
+            claimed = await redis_client.set(
+                key, "1", expire=SEND_CLAIM_TTL_SECONDS, nx=True
+            )
+            return bool(claimed)
+        except Exception as e:
+            logger.warning(f"⚠️ Send claim unavailable for {key}: {e}")
+            return True
+
+    async def release_send(self, contact_id: UUID) -> None:
+        """Release a claim after a failed send so a resume can retry it"""
+        key = f"campaign:{self.campaign.id}:sent:{contact_id}"
+        try:
+            await redis_client.delete(key)
+        except Exception as e:
+            logger.warning(f"⚠️ Could not release send claim {key}: {e}")
+
+    def reset(self) -> None:
+        """Clear the checkpoint (e.g. when a campaign is restarted from scratch)"""
+        self.campaign.dispatch_checkpoint = {}
+        flag_modified(self.campaign, "dispatch_checkpoint")
@@ -6,26 +6,118 @@ Provides sophisticated retry mechanisms for campaign message sending with:
 - Real-time status updates
 - Configurable exponential backoff
 - Retry history preservation
+- Error-code aware decisions (DeliveryError): permanent failures drop the
+  contact, re-engagement errors (131047) switch to the campaign template
+- Template sends in the contact's language variant (resolve_campaign_template)
+- Sent messages stored in the contact's open conversation (created on the
+  first campaign message), tagged with the campaign in extra_data
+- One campaign_messages row per contact (CampaignMessage) with the outcome,
+  attempts and stored message, so restarted dispatches and reports read it
 """
 
 import logging
 import asyncio
 from datetime import datetime
-from typing import Dict, Any, Optional, Tuple
+from typing import Dict, Any, List, Optional, Tuple
 from uuid import UUID
 
+from sqlalchemy import select
 from sqlalchemy.ext.asyncio import AsyncSession
 
-from app.models.campaign import Campaign
+from app.models.campaign import Campaign, CampaignMessage
 from app.models.contact import Contact
-from app.models.whatsapp_number import WhatsAppNumber
-from app.models.conversation import Message
+from app.models.whatsapp_number import WhatsAppNumber, WhatsAppTemplate
+from app.models.conversation import Conversation
 from app.integrations.meta_api import MetaCloudAPI, MetaAPIError
 from app.integrations.evolution_api import EvolutionAPIClient
+from app.repositories.conversation import ConversationRepository, MessageRepository
+from app.schemas.webhook import DeliveryError
+from app.services.campaign_template_validator import MEDIA_HEADER_TYPES, CampaignTemplateValidator
+from app.services.template_service import TemplateService
+from app.utils.campaign_personalization import personalize
+from app.utils.template_carousel import build_carousel_component
+from app.utils.template_components import CopyCodeButton, LimitedTimeOffer, copy_code_button_index
 
 logger = logging.getLogger(__name__)
 
 
+async def resolve_campaign_template(
+    db: AsyncSession,
+    campaign: Campaign,
+    contact: Contact,
+    whatsapp_number: WhatsAppNumber,
+    ab_variant: Optional[Dict[str, Any]] = None,
+) -> Tuple[Optional[str], str, Optional[List[Dict[str, Any]]]]:
+    """
+    Template name, language and components to send to a contact.
+    
+    The language variant follows the contact's language (Contact.language)
+    when an approved variant of the template exists. An A/B test variant
+    replaces the template and, when it sets them, the template variables.
+    Contact variables in the template variables are filled in (see
+    app.utils.campaign_personalization).
+    """
+    ab_variant = ab_variant or {}
+    content = campaign.message_content or {}
+    template_id = ab_variant.get("template_id") or campaign.template_id
+    variables = ab_variant.get("template_variables") or campaign.template_variables or {}
+    variables, _ = personalize(variables, contact)
+    stmt = select(WhatsAppTemplate).where(
+        WhatsAppTemplate.organization_id == campaign.organization_id,
+        WhatsAppTemplate.deleted_at.is_(None),
+    )
+    if template_id:
+        stmt = stmt.where(WhatsAppTemplate.id == UUID(str(template_id)))
+    elif content.get("name"):
+        stmt = stmt.where(WhatsAppTemplate.name == content["name"])
+        if content.get("language"):
+            stmt = stmt.where(WhatsAppTemplate.language == content["language"])
+    else:
+        return None, "", None
+    
+    template = (await db.execute(stmt.limit(1))).scalar_one_or_none()
+    name = template.name if template else content.get("name")
+    if not name:
+        return None, "", None
+    
+    language = await TemplateService(db).resolve_language_variant(
+        name,
+        content.get("language") or (template.language if template else None) or "pt_BR",
+        whatsapp_number.id,
+        campaign.organization_id,
+        contact.language,
+    )
+    
+    header, body, media_url = CampaignTemplateValidator._split_parameters(variables)
+    components = []
+    header_type = (template.header_type or "").upper() if template else ""
+    if header:
+        components.append({
+            "type": "header",
+            "parameters": [{"type": "text", "text": str(value)} for value in header],
+        })
+    elif media_url and header_type in MEDIA_HEADER_TYPES:
+        media_type = header_type.lower()
+        components.append({
+            "type": "header",
+            "parameters": [{"type": media_type, media_type: {"link": media_url}}],
+        })
+    if body:
+        components.append({
+            "type": "body",
+            "parameters": [{"type": "text", "text": str(value)} for value in body],
+        })
+    if template and (template.limited_time_offer or {}).get("has_expiration") and variables.get("offer_expires_at"):
+        components.append(LimitedTimeOffer.parse(variables["offer_expires_at"]).to_component())
+    if template and template.carousel_cards:
+        components.append(build_carousel_component(variables.get("cards") or [], template.carousel_cards))
+    coupon_index = copy_code_button_index(template.buttons) if template else None
+    if coupon_index is not None and variables.get("coupon_code"):
+        components.append(CopyCodeButton(code=str(variables["coupon_code"]), index=coupon_index).to_component())
+    
+    return name, language, components or None
+
+
 class CampaignRetryManager:
     """
     Manages retry logic and error tracking for campaign messages
@@ -46,6 +138,8 @@ class CampaignRetryManager:
             self.campaign.errors = []
         if self.campaign.message_statuses is None:
             self.campaign.message_statuses = {}
+        # campaign_messages rows loaded or created by this manager, by contact id
+        self._deliveries: Dict[UUID, CampaignMessage] = {}
     
     def calculate_retry_delay(self, attempt: int) -> float:
         """
@@ -101,6 +195,8 @@ class CampaignRetryManager:
         success: bool,
         error: Optional[str] = None,
         message_id: Optional[str] = None,
+        delivery_error: Optional[DeliveryError] = None,
+        ab_variant: Optional[Dict[str, Any]] = None,
     ) -> None:
         """
         Record a message sending attempt
@@ -111,6 +207,8 @@ class CampaignRetryManager:
             success: Whether the attempt succeeded
             error: Error message if failed
             message_id: WhatsApp message ID if successful
+            delivery_error: Parsed Meta error (permanent failures drop the contact)
+            ab_variant: A/B test variant sent to the contact
         """
         contact_id_str = str(contact.id)
         timestamp = datetime.utcnow().isoformat()
@@ -130,6 +228,8 @@ class CampaignRetryManager:
         
         # Update status
         status = self.campaign.message_statuses[contact_id_str]
+        if ab_variant:
+            status["variant"] = ab_variant["id"]
         
         # Add attempt to history
         attempt_record = {
@@ -139,6 +239,9 @@ class CampaignRetryManager:
             "error": error,
             "message_id": message_id,
         }
+        if delivery_error:
+            attempt_record["error_code"] = delivery_error.raw_code
+            attempt_record["error_kind"] = delivery_error.code.name.lower()
         status["attempts"].append(attempt_record)
         status["last_update"] = timestamp
         
@@ -146,7 +249,12 @@ class CampaignRetryManager:
             status["status"] = "sent"
             status["message_id"] = message_id
         else:
-            status["status"] = "failed" if attempt >= self.campaign.retry_max_attempts - 1 else "retrying"
+            dropped = bool(delivery_error and delivery_error.permanent_failure)
+            final = dropped or attempt >= self.campaign.retry_max_attempts - 1
+            status["status"] = "failed" if final else "retrying"
+            if dropped:
+                status["dropped"] = True
+                status["drop_reason"] = delivery_error.guidance
             
             # Also add to errors array for backward compatibility
             self.campaign.errors.append({
@@ -162,6 +270,20 @@ class CampaignRetryManager:
             self.campaign.error_count += 1
             self.campaign.last_error_message = error
         
+        delivery = await self._delivery(contact)
+        delivery.status = status["status"]
+        delivery.attempts = len(status["attempts"])
+        delivery.ab_variant = status.get("variant")
+        if success:
+            delivery.whatsapp_message_id = message_id
+            delivery.sent_at = delivery.sent_at or datetime.utcnow()
+            delivery.error_code = delivery.error_message = None
+        else:
+            delivery.error_code = delivery_error.raw_code if delivery_error else None
+            delivery.error_message = error
+            if status["status"] == "failed":
+                delivery.failed_at = datetime.utcnow()
+        
         # Mark as modified to trigger JSONB update
         from sqlalchemy.orm.attributes import flag_modified
         flag_modified(self.campaign, "message_statuses")
@@ -173,25 +295,36 @@ class CampaignRetryManager:
         self,
         contact: Contact,
         whatsapp_number: WhatsAppNumber,
+        ab_variant: Optional[Dict[str, Any]] = None,
     ) -> Tuple[bool, Optional[str]]:
         """
         Send message with automatic retry logic
         
+        The Meta error code decides the next step:
+        - permanent failure (131026 not on WhatsApp, 131050 opted out...): drop the contact
+        - needs template (131047 outside the 24h window): switch to the campaign
+          template right away, or stop when the campaign has none
+        - anything else: retry with exponential backoff
+        
         Args:
             contact: Contact to send message to
             whatsapp_number: WhatsApp number to send from
+            ab_variant: A/B test variant to send (see app.utils.campaign_ab_test)
             
         Returns:
             Tuple of (success, message_id)
         """
         attempts = self.get_contact_attempts(contact.id)
+        use_template = False
         
         while attempts < self.campaign.retry_max_attempts:
             try:
                 # Send message
-                success, message_id, error = await self._send_single_message(
+                success, message_id, error, delivery_error = await self._send_single_message(
                     contact=contact,
                     whatsapp_number=whatsapp_number,
+                    use_template=use_template,
+                    ab_variant=ab_variant,
                 )
                 
                 # Record attempt
@@ -201,6 +334,8 @@ class CampaignRetryManager:
                     success=success,
                     error=error,
                     message_id=message_id,
+                    delivery_error=delivery_error,
+                    ab_variant=ab_variant,
                 )
                 
                 if success:
@@ -213,6 +348,27 @@ class CampaignRetryManager:
                 # Failed - check if we should retry
                 attempts += 1
                 
+                if delivery_error and delivery_error.permanent_failure:
+                    logger.warning(
+                        f"🚫 Dropping {contact.whatsapp_id}: {delivery_error.guidance} "
+                        f"(code: {delivery_error.raw_code})"
+                    )
+                    return False, None
+                
+                if delivery_error and delivery_error.needs_template:
+                    if use_template or not self.has_template():
+                        logger.warning(
+                            f"⚠️ {contact.whatsapp_id} is outside the 24h window and campaign "
+                            f"{self.campaign.id} has no template to fall back to"
+                        )
+                        return False, None
+                    logger.info(
+                        f"🔁 {contact.whatsapp_id} is outside the 24h window, "
+                        f"retrying with the campaign template"
+                    )
+                    use_template = True
+                    continue
+                
                 if attempts < self.campaign.retry_max_attempts:
                     # Calculate delay and wait
                     delay = self.calculate_retry_delay(attempts)
@@ -240,6 +396,7 @@ class CampaignRetryManager:
                     attempt=attempts,
                     success=False,
                     error=error_msg,
+                    ab_variant=ab_variant,
                 )
                 
                 attempts += 1
@@ -252,25 +409,89 @@ class CampaignRetryManager:
         
         return False, None
     
+    async def _delivery(self, contact: Contact) -> CampaignMessage:
+        """campaign_messages row of a contact (kept from an earlier dispatch, or new)"""
+        delivery = self._deliveries.get(contact.id)
+        if delivery is None:
+            delivery = await self.db.scalar(
+                select(CampaignMessage).where(
+                    CampaignMessage.campaign_id == self.campaign.id,
+                    CampaignMessage.contact_id == contact.id,
+                )
+            )
+            if delivery is None:
+                delivery = CampaignMessage(
+                    organization_id=self.campaign.organization_id,
+                    campaign_id=self.campaign.id,
+                    contact_id=contact.id,
+                    status="pending",
+                    attempts=0,
+                )
+                self.db.add(delivery)
+            self._deliveries[contact.id] = delivery
+        return delivery
+    
+    def has_template(self) -> bool:
+        """Campaign has a template to send (template campaign or fallback template_id)"""
+        return bool(self.campaign.template_id) or (
+            self.campaign.message_type == "template"
+            and bool((self.campaign.message_content or {}).get("name"))
+        )
+    
     async def _send_single_message(
         self,
         contact: Contact,
         whatsapp_number: WhatsAppNumber,
-    ) -> Tuple[bool, Optional[str], Optional[str]]:
+        use_template: bool = False,
+        ab_variant: Optional[Dict[str, Any]] = None,
+    ) -> Tuple[bool, Optional[str], Optional[str], Optional[DeliveryError]]:
         """
         Send a single message without retry
         
+        Args:
+            use_template: Send the campaign template instead of the text
+                (re-engagement fallback)
+            ab_variant: A/B test variant (its template or text replaces the campaign's)
+        
         Returns:
-            Tuple of (success, message_id, error)
+            Tuple of (success, message_id, error, delivery_error)
         """
         try:
             # Prepare message content
-            if self.campaign.message_type == "text":
-                message_text = self.campaign.message_content.get("text", "")
+            if use_template or self.campaign.message_type == "template":
+                if whatsapp_number.connection_type != "official":
+                    return False, None, "Template messages require an official number", None
                 
-                # Replace variables (basic implementation)
-                message_text = message_text.replace("{{contact.name}}", contact.name or "")
-                message_text = message_text.replace("{{contact.phone}}", contact.phone or "")
+                template_name, language_code, components = await resolve_campaign_template(
+                    self.db, self.campaign, contact, whatsapp_number, ab_variant
+                )
+                if not template_name:
+                    return False, None, "Campaign has no template", None
+                
+                api = MetaCloudAPI(
+                    phone_number_id=whatsapp_number.phone_number_id,
+                    access_token=whatsapp_number.access_token,
+                )
+                
+                response = await api.send_template_message(
+                    to=contact.whatsapp_id,
+                    template_name=template_name,
+                    language_code=language_code,
+                    components=components,
+                )
+                
+                message_id = response.get("messages", [{}])[0].get("id")
+                message_type, content = "template", {
+                    "name": template_name,
+                    "language": language_code,
+                    "components": components,
+                }
+                
+            elif self.campaign.message_type == "text":
+                message_text = (ab_variant or {}).get("text") or self.campaign.message_content.get("text", "")
+                
+                # Contact variables ({{contact.name}}, {{contact.attributes.x}}...)
+                message_text, _ = personalize(message_text, contact)
                 
                 # Send via WhatsApp
                 if whatsapp_number.connection_type == "official":
@@ -290,49 +511,119 @@ class CampaignRetryManager:
                 else:
                     # Use Evolution API (QR Code)
                     api = EvolutionAPIClient(
-                        base_url=whatsapp_number.evolution_api_url,
+                        api_url=whatsapp_number.evolution_api_url,
                         api_key=whatsapp_number.evolution_api_key,
                     )
                     
-                    response = await api.send_text(
-                        instance_name=whatsapp_number.instance_name,
-                        number=contact.whatsapp_id,
-                        text=message_text,
+                    response = await api.send_text_message(
+                        instance_name=whatsapp_number.evolution_instance_name,
+                        phone_number=contact.whatsapp_id.replace("+", ""),
+                        message=message_text,
                     )
                     
-                    message_id = response.get("key", {}).get("id")
-                
-                # Save message to database
-                message = Message(
-                    organization_id=self.campaign.organization_id,
-                    contact_id=contact.id,
-                    whatsapp_number_id=whatsapp_number.id,
-                    direction="outbound",
-                    content_type="text",
-                    content={"text": message_text},
-                    status="sent",
-                    whatsapp_message_id=message_id,
-                    metadata={
-                        "campaign_id": str(self.campaign.id),
-                        "campaign_name": self.campaign.name,
-                    },
-                )
-                self.db.add(message)
-                await self.db.commit()
+                    message_id = (response.get("key") or {}).get("id")
                 
-                return True, message_id, None
+                message_type, content = "text", {"text": message_text}
                 
             else:
                 error = f"Unsupported message type: {self.campaign.message_type}"
-                return False, None, error
-                
+                return False, None, error, None
+            
         except MetaAPIError as e:
             error = f"Meta API error: {e.message} (code: {e.error_code})"
-            return False, None, error
+            return False, None, error, DeliveryError.from_code(e.error_code, detail=e.message)
             
         except Exception as e:
             error = str(e)
-            return False, None, error
+            return False, None, error, None
+        
+        # WhatsApp accepted the message: storing it can no longer fail the send
+        await self._store_message(contact, whatsapp_number, message_type, content, message_id, ab_variant)
+        
+        return True, message_id, None, None
+    
+    async def _store_message(
+        self,
+        contact: Contact,
+        whatsapp_number: WhatsAppNumber,
+        message_type: str,
+        content: Dict[str, Any],
+        message_id: Optional[str],
+        ab_variant: Optional[Dict[str, Any]] = None,
+    ) -> None:
+        """
+        Store a sent message in the contact's open conversation
+        
+        Goes through MessageRepository, so encrypted tenants store the content
+        encrypted. Errors are logged instead of raised: the message is already
+        out, and a failed send would be retried (sent again).
+        """
+        whatsapp_id = contact.whatsapp_id
+        try:
+            conversation = await self._get_or_create_conversation(contact, whatsapp_number)
+            now = datetime.utcnow()
+            
+            message = await MessageRepository(self.db).create({
+                "organization_id": self.campaign.organization_id,
+                "conversation_id": conversation.id,
+                "whatsapp_number_id": whatsapp_number.id,
+                "direction": "outbound",
+                "sender_type": "system",
+                "message_type": message_type,
+                "content": content,
+                "status": "sent",
+                "sent_at": now,
+                "whatsapp_message_id": message_id,
+                "extra_data": {
+                    "campaign_id": str(self.campaign.id),
+                    "campaign_name": self.campaign.name,
+                    **({"ab_variant": ab_variant["id"]} if ab_variant else {}),
+                },
+            })
+            
+            conversation.last_message_at = now
+            conversation.total_messages = (conversation.total_messages or 0) + 1
+            (await self._delivery(contact)).message_id = message.id
+            await self.db.commit()
+            
+        except Exception as e:
+            logger.error(
+                f"❌ Campaign message {message_id} sent to {whatsapp_id} could not be stored: {e}"
+            )
+            # The rollback expires every loaded row: reload the ones the batch keeps using
+            await self.db.rollback()
+            self._deliveries.clear()
+            for instance in (self.campaign, contact, whatsapp_number):
+                await self.db.refresh(instance)
+    
+    async def _get_or_create_conversation(
+        self, contact: Contact, whatsapp_number: WhatsAppNumber
+    ) -> Conversation:
+        """Open conversation of the contact, created for the first message otherwise"""
+        conversation_repo = ConversationRepository(self.db)
+        conversations = await conversation_repo.get_by_contact(
+            contact_id=contact.id,
+            organization_id=self.campaign.organization_id,
+            status="open",
+        )
+        if conversations:
+            return conversations[0]
+        
+        now = datetime.utcnow()
+        conversation = await conversation_repo.create({
+            "organization_id": self.campaign.organization_id,
+            "contact_id": contact.id,
+            "whatsapp_number_id": whatsapp_number.id,
+            "status": "open",
+            "channel": "whatsapp",
+            "first_message_at": now,
+            "last_message_at": now,
+            "total_messages": 0,
+            "is_bot_active": bool(whatsapp_number.default_chatbot_id),
+            "active_chatbot_id": whatsapp_number.default_chatbot_id,
+        })
+        logger.info(f"Created conversation {conversation.id} for campaign {self.campaign.id}")
+        return conversation
     
     async def update_message_status(
         self,
@@ -388,6 +679,7 @@ class CampaignRetryManager:
         total_attempts = 0
         successful_on_first = 0
         required_retries = 0
+        dropped = 0
         
         for contact_status in self.campaign.message_statuses.values():
             status = contact_status.get("status", "pending")
@@ -395,6 +687,8 @@ class CampaignRetryManager:
             
             attempts = len(contact_status.get("attempts", []))
             total_attempts += attempts
+            if contact_status.get("dropped"):
+                dropped += 1
             
             if status == "sent" and attempts == 1:
                 successful_on_first += 1
@@ -410,5 +704,6 @@ class CampaignRetryManager:
             "avg_attempts_per_contact": round(avg_attempts, 2),
             "successful_on_first_attempt": successful_on_first,
             "required_retries": required_retries,
+            "dropped": dropped,
             "retry_rate": round(required_retries / total_contacts * 100, 2) if total_contacts > 0 else 0,
         }
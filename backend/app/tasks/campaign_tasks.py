@@ -5,31 +5,47 @@ Handles batch processing, message sending, retry logic, and progress tracking
 for bulk messaging campaigns.
 """
 
+import copy
 import logging
 import asyncio
 from datetime import datetime, timedelta
-from typing import List, Dict, Any, Optional
+from typing import List, Dict, Any
 from uuid import UUID
 
-from celery import Task, group, chord
-from sqlalchemy import select, and_, or_
+from celery import group, chord
+from sqlalchemy import select, and_
 from sqlalchemy.ext.asyncio import AsyncSession
 
-from app.tasks.celery_app import celery_app
+from app.tasks.celery_app import FaultInjectingTask, celery_app
 from app.core.database import async_session
-from app.core.whatsapp_rate_limit import get_whatsapp_rate_limiter
+from app.core.campaign_throttle import get_campaign_throttle
+from app.core.whatsapp_rate_limit import get_tier_shaper, get_whatsapp_rate_limiter
+from app.core.region_router import region_router
 from app.tasks.campaign_retry import CampaignRetryManager
+from app.tasks.campaign_checkpoint import CampaignCheckpointManager
 from app.models.campaign import Campaign
 from app.models.contact import Contact
+from app.models.organization import Organization
 from app.models.whatsapp_number import WhatsAppNumber
-from app.models.conversation import Message
+from app.repositories.contact_segment import audience_conditions
+from app.services.annotation_service import AnnotationService
 from app.services.whatsapp_service import WhatsAppService
-from app.integrations.meta_api import MetaCloudAPI, MetaAPIError
+from app.utils.campaign_ab_test import (
+    contact_variant,
+    decide_winner,
+    phase_contacts,
+    variant_results,
+    winner_variant,
+)
+from app.utils.campaign_recurrence import is_finished, naive_utc, next_occurrence
 
 logger = logging.getLogger(__name__)
 
+# Campaign throttle holds up to this long are waited in the batch; longer ones defer it
+THROTTLE_MAX_WAIT_SECONDS = 60
+
 
-class CampaignTask(Task):
+class CampaignTask(FaultInjectingTask):
     """Base task class with campaign-specific error handling"""
     
     autoretry_for = (Exception,)
@@ -89,7 +105,7 @@ async def _execute_campaign_async(campaign_id: str, task_id: str) -> Dict[str, A
         if not campaign:
             raise ValueError(f"Campaign {campaign_id} not found")
         
-        if campaign.status not in ["draft", "scheduled"]:
+        if campaign.status not in ["draft", "scheduled", "running"]:
             raise ValueError(f"Campaign {campaign_id} has invalid status: {campaign.status}")
         
         # 2. Validate WhatsApp number
@@ -105,46 +121,117 @@ async def _execute_campaign_async(campaign_id: str, task_id: str) -> Dict[str, A
         if not whatsapp_number or not whatsapp_number.is_active:
             raise ValueError("WhatsApp number is not active")
         
+        checkpoint = CampaignCheckpointManager(campaign)
+        batch_size = checkpoint.checkpoint.get("batch_size", 100)
+        
+        # Keep batches pinned to the tenant's region
+        queue = await _campaign_queue(db, campaign.organization_id)
+        
+        if checkpoint.has_checkpoint:
+            # Resume: only dispatch the stored contacts left after each batch cursor
+            batch_tasks = []
+            remaining = 0
+            
+            for batch in checkpoint.pending_batches():
+                if not batch["contact_ids"]:
+                    checkpoint.mark_batch_done(batch["index"])
+                    continue
+                
+                remaining += len(batch["contact_ids"])
+                batch_tasks.append(process_batch.s(
+                    campaign_id=campaign_id,
+                    contact_ids=batch["contact_ids"],
+                    batch_index=batch["index"],
+                ).set(queue=queue))
+            
+            checkpoint.record_resume()
+            campaign.status = "running"
+            campaign.paused_at = None
+            await db.commit()
+            
+            logger.info(
+                f"🔁 Campaign {campaign_id} resumed from checkpoint: "
+                f"{len(batch_tasks)} batches, {remaining} contacts left"
+            )
+            
+            job = chord(batch_tasks)(finalize_campaign.s(campaign_id).set(queue=queue))
+            
+            return {
+                "campaign_id": campaign_id,
+                "task_id": task_id,
+                "total_contacts": campaign.total_recipients,
+                "remaining_contacts": remaining,
+                "total_batches": len(batch_tasks),
+                "batch_size": batch_size,
+                "status": "running",
+                "resumed": True,
+                "started_at": campaign.started_at.isoformat() if campaign.started_at else None,
+            }
+        
         # 3. Fetch target contacts
-        contacts = await _get_campaign_contacts(db, campaign)
+        audience = await _get_campaign_contacts(db, campaign)
         
-        if not contacts:
+        if not audience:
             raise ValueError("No contacts found for campaign")
         
-        logger.info(f"📊 Campaign {campaign_id}: {len(contacts)} contacts to process")
+        # A/B test: the test group first, the rest of the audience once the winner is declared
+        contacts = audience
+        rollout = False
+        if campaign.ab_test:
+            contacts = phase_contacts(campaign.ab_test, campaign.id, audience)
+            rollout = winner_variant(campaign.ab_test) is not None
+            if not contacts and rollout:
+                campaign.complete()
+                await db.commit()
+                logger.info(f"🏁 Campaign {campaign_id}: whole audience was in the A/B test")
+                return {"campaign_id": campaign_id, "task_id": task_id, "status": "completed"}
+            if not contacts:
+                raise ValueError("No contacts in the A/B test group")
         
-        # 4. Update campaign status and stats
-        campaign.status = "running"
-        campaign.started_at = datetime.utcnow()
-        campaign.total_recipients = len(contacts)
-        campaign.messages_pending = len(contacts)
-        await db.commit()
+        logger.info(f"📊 Campaign {campaign_id}: {len(contacts)} contacts to process")
         
-        # 5. Divide into batches (100 contacts per batch)
-        batch_size = 100
+        # 4. Divide into batches (100 contacts per batch)
         batches = [
-            contacts[i:i + batch_size]
+            [str(contact.id) for contact in contacts[i:i + batch_size]]
             for i in range(0, len(contacts), batch_size)
         ]
         
+        # 5. Update campaign status, stats and checkpoint
+        campaign.status = "running"
+        campaign.started_at = campaign.started_at or datetime.utcnow()
+        campaign.total_recipients = len(audience)
+        campaign.messages_pending = len(audience) - campaign.messages_sent - campaign.messages_failed
+        checkpoint.init_batches(batches, batch_size)
+        await db.commit()
+        
         logger.info(f"📦 Campaign {campaign_id}: {len(batches)} batches created")
         
+        # Dashboard annotation: metric changes can be traced back to the launch
+        if not rollout:
+            await AnnotationService(db).record(
+                "campaign_launch",
+                f"Campaign {campaign.name} launched",
+                organization_id=campaign.organization_id,
+                description=f"{len(audience)} recipients",
+                resource_type="campaign",
+                resource_id=campaign.id,
+                extra_data={"total_recipients": len(audience)},
+            )
+        
         # 6. Create batch processing tasks
         batch_tasks = []
-        for batch_index, contact_batch in enumerate(batches):
-            contact_ids = [str(contact.id) for contact in contact_batch]
-            
+        for batch_index, contact_ids in enumerate(batches):
             # Create task for each batch
             task = process_batch.s(
                 campaign_id=campaign_id,
                 contact_ids=contact_ids,
                 batch_index=batch_index,
-            )
+            ).set(queue=queue)
             batch_tasks.append(task)
         
         # 7. Execute batches in parallel with callback
         # Use chord to execute all batches and then call completion callback
-        callback = finalize_campaign.s(campaign_id)
+        callback = finalize_campaign.s(campaign_id).set(queue=queue)
         job = chord(batch_tasks)(callback)
         
         return {
@@ -158,52 +245,37 @@ async def _execute_campaign_async(campaign_id: str, task_id: str) -> Dict[str, A
         }
 
 
+async def _campaign_queue(db: AsyncSession, organization_id: UUID) -> str:
+    """Region-tagged campaigns queue serving the organization"""
+    home_region = await db.scalar(
+        select(Organization.home_region).where(Organization.id == organization_id)
+    )
+    return await region_router.queue_for("campaigns", organization_id, home_region)
+
+
 async def _get_campaign_contacts(db: AsyncSession, campaign: Campaign) -> List[Contact]:
     """
     Fetch contacts based on campaign targeting configuration.
     
-    Supports:
-    - All contacts in organization
-    - Specific contact IDs
-    - Contacts with specific tags
-    - Segment filters
+    The audience (all contacts, tags, a custom list or a segment) is
+    compiled to SQL by app.repositories.contact_segment.
+    
+    Contacts are ordered by id, the order batches are dispatched and
+    checkpointed in.
     """
     
-    # Base query
     query = select(Contact).where(
-        and_(
-            Contact.organization_id == campaign.organization_id,
-            Contact.deleted_at.is_(None),
+        *audience_conditions(
+            campaign.organization_id,
+            campaign.audience_type,
+            campaign.target_tag_ids,
+            campaign.target_contact_ids,
+            campaign.segment_filters,
+            respect_opt_out=campaign.respect_opt_out,
         )
     )
     
-    # Apply audience targeting
-    if campaign.audience_type == "all_contacts":
-        # All contacts
-        pass
-    
-    elif campaign.audience_type == "custom_list":
-        # Specific contact IDs
-        if campaign.target_contact_ids:
-            query = query.where(Contact.id.in_(campaign.target_contact_ids))
-        else:
-            return []
-    
-    elif campaign.audience_type == "tags":
-        # Contacts with specific tags
-        if campaign.target_tag_ids:
-            # Use PostgreSQL array overlap operator
-            query = query.where(Contact.tags.overlap(campaign.target_tag_ids))
-        else:
-            return []
-    
-    # Apply additional filters
-    if campaign.respect_opt_out:
-        # Skip opted-out contacts
-        query = query.where(or_(Contact.opted_out.is_(None), Contact.opted_out == False))
-    
-    # Only contacts with WhatsApp
-    query = query.where(Contact.whatsapp_id.isnot(None))
+    query = query.order_by(Contact.id)
     
     # Execute query
     result = await db.execute(query)
@@ -298,29 +370,96 @@ async def _process_batch_async(
             whatsapp_number.connection_type
         )
         
+        # Messaging limit tier, shared with every other sender of this number
+        tier_shaper = get_tier_shaper(whatsapp_number)
+        
+        # Campaign's own limits, shared by every batch of the campaign
+        throttle = get_campaign_throttle(campaign)
+        
         # Check current usage
         usage = await rate_limiter.get_current_usage()
         logger.info(
             f"📊 WhatsApp {whatsapp_number.id} rate limit usage: {usage}"
         )
         
-        # Load contacts
+        # Load contacts (ordered by id to keep the checkpoint cursor monotonic)
         stmt = select(Contact).where(
             Contact.id.in_([UUID(cid) for cid in contact_ids])
-        )
+        ).order_by(Contact.id)
         result = await db.execute(stmt)
         contacts = result.scalars().all()
         
-        # Initialize retry manager
+        # Initialize retry manager and checkpoint
         retry_manager = CampaignRetryManager(campaign, db)
+        checkpoint = CampaignCheckpointManager(campaign)
         
         # Process each contact
         sent_count = 0
         failed_count = 0
+        duplicate_count = 0
         rate_limit_paused = False
+        deferred_until = None
+        stopped_status = None
         
         for contact in contacts:
+            # Paused or cancelled while the batch runs: stop before the next send,
+            # leaving the cursor on this contact so the batch resumes from it
+            current_status = await db.scalar(
+                select(Campaign.status).where(Campaign.id == campaign.id)
+            )
+            if current_status in ("paused", "cancelled"):
+                logger.warning(
+                    f"⚠️ Campaign {campaign_id} was {current_status}, "
+                    f"stopping batch {batch_index}"
+                )
+                stopped_status = current_status
+                break
+            
+            # Idempotency guard: never send twice to the same contact
+            if checkpoint.is_already_sent(contact.id) or not await checkpoint.claim_send(contact.id):
+                logger.info(
+                    f"⏭️ Skipping {contact.whatsapp_id}: already sent in campaign {campaign_id}"
+                )
+                duplicate_count += 1
+                checkpoint.advance(batch_index, contact.id)
+                await db.commit()
+                continue
+            
             try:
+                # Outside the campaign's business hours (or over a limit window):
+                # hold the rest of the batch until sending may resume
+                hold = throttle.check_hours(contact)
+                if hold.allowed:
+                    hold = await throttle.reserve()
+                    if not hold.allowed and hold.wait_seconds <= THROTTLE_MAX_WAIT_SECONDS:
+                        logger.info(f"⏳ Waiting {hold.wait_seconds:.0f}s for campaign {campaign_id} throttle...")
+                        await asyncio.sleep(hold.wait_seconds)
+                        hold = await throttle.reserve()
+                if not hold.allowed:
+                    deferred_until = hold.send_at
+                    logger.warning(
+                        f"⏳ Campaign {campaign_id} throttled ({hold.reason}), batch {batch_index} "
+                        f"deferred until {deferred_until.isoformat()}"
+                    )
+                    checkpoint.defer(deferred_until)
+                    await checkpoint.release_send(contact.id)
+                    await db.commit()
+                    break
+                
+                # Tier full: hold the rest of the batch until a slot frees up
+                tier = await tier_shaper.reserve(contact.whatsapp_id) if tier_shaper else None
+                if tier and not tier.allowed:
+                    deferred_until = tier.send_at
+                    logger.warning(
+                        f"⏳ Messaging tier {tier.tier} full, batch {batch_index} of campaign "
+                        f"{campaign_id} deferred until {deferred_until.isoformat()}"
+                    )
+                    checkpoint.defer(deferred_until)
+                    await checkpoint.release_send(contact.id)
+                    await throttle.release()
+                    await db.commit()
+                    break
+                
                 # Check rate limit before sending
                 can_send, reason = await rate_limiter.can_send_message()
                 
@@ -342,6 +481,8 @@ async def _process_batch_async(
                             f"Rate limit exceeded: {reason}. "
                             f"Campaign paused. Wait {wait_time/60:.1f} minutes."
                         )
+                        await checkpoint.release_send(contact.id)
+                        await throttle.release()
                         await db.commit()
                         rate_limit_paused = True
                         break
@@ -350,10 +491,14 @@ async def _process_batch_async(
                         logger.info(f"⏳ Waiting {wait_time}s for rate limit...")
                         await asyncio.sleep(wait_time)
                 
-                # Send message with automatic retry
+                # Send message with automatic retry (A/B test: the contact's variant)
                 success, message_id = await retry_manager.send_message_with_retry(
                     contact=contact,
                     whatsapp_number=whatsapp_number,
+                    ab_variant=(
+                        contact_variant(campaign.ab_test, campaign.id, contact.id)
+                        if campaign.ab_test else None
+                    ),
                 )
                 
                 if success:
@@ -368,7 +513,11 @@ async def _process_batch_async(
                     failed_count += 1
                     campaign.messages_failed += 1
                     campaign.messages_pending -= 1
+                    await checkpoint.release_send(contact.id)
+                    if tier and tier.reserved:
+                        await tier_shaper.release(contact.whatsapp_id)
                 
+                checkpoint.advance(batch_index, contact.id)
                 await db.commit()
                 
                 # Rate limiting: delay between messages
@@ -384,10 +533,21 @@ async def _process_batch_async(
                 campaign.messages_pending -= 1
                 campaign.error_count += 1
                 campaign.last_error_message = str(e)
+                await checkpoint.release_send(contact.id)
+                checkpoint.advance(batch_index, contact.id)
                 await db.commit()
         
+        if not rate_limit_paused and not deferred_until and not stopped_status:
+            checkpoint.mark_batch_done(batch_index)
+            await db.commit()
+        
         # Return results
-        status = "paused" if rate_limit_paused else "completed"
+        if rate_limit_paused:
+            status = "paused"
+        elif stopped_status:
+            status = stopped_status
+        else:
+            status = "deferred" if deferred_until else "completed"
         
         return {
             "campaign_id": campaign_id,
@@ -395,110 +555,14 @@ async def _process_batch_async(
             "total": len(contact_ids),
             "sent": sent_count,
             "failed": failed_count,
+            "duplicates": duplicate_count,
             "skipped": len(contact_ids) - sent_count - failed_count,
             "status": status,
             "rate_limit_paused": rate_limit_paused,
+            "deferred_until": deferred_until.isoformat() if deferred_until else None,
         }
 
 
-async def _send_campaign_message(
-    db: AsyncSession,
-    campaign: Campaign,
-    contact: Contact,
-    whatsapp_number: WhatsAppNumber,
-) -> bool:
-    """
-    Send a single campaign message to a contact.
-    
-    Returns:
-        True if sent successfully, False otherwise
-    """
-    
-    try:
-        # Prepare message content
-        if campaign.message_type == "text":
-            message_text = campaign.message_content.get("text", "")
-            
-            # Replace variables (basic implementation)
-            message_text = message_text.replace("{{contact.name}}", contact.name or "")
-            message_text = message_text.replace("{{contact.phone}}", contact.phone or "")
-            
-            # Send via WhatsApp
-            if whatsapp_number.connection_type == "official":
-                # Use Meta Cloud API
-                api = MetaCloudAPI(
-                    phone_number_id=whatsapp_number.phone_number_id,
-                    access_token=whatsapp_number.access_token,
-                )
-                
-                response = await api.send_text_message(
-                    to=contact.whatsapp_id,
-                    text=message_text,
-                )
-                
-                message_id = response.get("messages", [{}])[0].get("id")
-                
-            else:
-                # Use Evolution API (QR Code)
-                from app.integrations.evolution_api import EvolutionAPI
-                
-                api = EvolutionAPI(
-                    base_url=whatsapp_number.evolution_api_url,
-                    api_key=whatsapp_number.evolution_api_key,
-                )
-                
-                response = await api.send_text(
-                    instance_name=whatsapp_number.instance_name,
-                    number=contact.whatsapp_id,
-                    text=message_text,
-                )
-                
-                message_id = response.get("key", {}).get("id")
-            
-            # Save message to database
-            message = Message(
-                organization_id=campaign.organization_id,
-                contact_id=contact.id,
-                whatsapp_number_id=whatsapp_number.id,
-                direction="outbound",
-                content_type="text",
-                content={"text": message_text},
-                status="sent",
-                whatsapp_message_id=message_id,
-                metadata={
-                    "campaign_id": str(campaign.id),
-                    "campaign_name": campaign.name,
-                },
-            )
-            db.add(message)
-            
-            logger.info(
-                f"✅ Sent campaign message to {contact.whatsapp_id} "
-                f"(message_id: {message_id})"
-            )
-            
-            return True
-            
-        else:
-            logger.warning(
-                f"⚠️ Unsupported message type: {campaign.message_type}"
-            )
-            return False
-            
-    except MetaAPIError as e:
-        logger.error(
-            f"❌ Meta API error sending to {contact.whatsapp_id}: "
-            f"{e.message} (code: {e.error_code})"
-        )
-        return False
-        
-    except Exception as e:
-        logger.error(
-            f"❌ Error sending to {contact.whatsapp_id}: {str(e)}"
-        )
-        return False
-
-
 @celery_app.task(name="finalize_campaign")
 def finalize_campaign(batch_results: List[Dict[str, Any]], campaign_id: str) -> Dict[str, Any]:
     """
@@ -540,6 +604,34 @@ async def _finalize_campaign_async(
         if not campaign:
             raise ValueError(f"Campaign {campaign_id} not found")
         
+        # Paused/cancelled campaigns or unfinished batches stay resumable
+        checkpoint = CampaignCheckpointManager(campaign)
+        if campaign.status != "running" or (
+            checkpoint.has_checkpoint and not checkpoint.is_complete()
+        ):
+            logger.info(
+                f"⏸️ Campaign {campaign_id} not finalized "
+                f"(status={campaign.status}, pending batches="
+                f"{len(checkpoint.pending_batches())})"
+            )
+            return {
+                "campaign_id": campaign_id,
+                "status": campaign.status,
+                "pending_batches": len(checkpoint.pending_batches()),
+            }
+        
+        # A/B test group done: the rest of the audience waits for the winner
+        if campaign.ab_test and not winner_variant(campaign.ab_test):
+            campaign.status = "testing"
+            campaign.ab_test = {**campaign.ab_test, "test_completed_at": datetime.utcnow().isoformat()}
+            await db.commit()
+            logger.info(f"🧪 Campaign {campaign_id} A/B test sent, waiting for a winner")
+            return {
+                "campaign_id": campaign_id,
+                "status": "testing",
+                "messages_sent": campaign.messages_sent,
+            }
+        
         # Mark as completed
         campaign.complete()
         
@@ -634,8 +726,9 @@ async def _process_scheduled_campaigns_async() -> Dict[str, Any]:
         started_count = 0
         for campaign in campaigns:
             try:
-                # Trigger campaign execution
-                execute_campaign.delay(str(campaign.id))
+                # Trigger campaign execution on the tenant's regional queue
+                queue = await _campaign_queue(db, campaign.organization_id)
+                execute_campaign.apply_async(args=[str(campaign.id)], queue=queue)
                 started_count += 1
                 logger.info(f"🚀 Started campaign {campaign.id}: {campaign.name}")
                 
@@ -648,3 +741,273 @@ async def _process_scheduled_campaigns_async() -> Dict[str, Any]:
             "campaigns_found": len(campaigns),
             "campaigns_started": started_count,
         }
+
+
+# Periodic task to resume campaigns interrupted by a crash or deploy
+STALLED_CAMPAIGN_MINUTES = 15
+
+
+@celery_app.task(name="resume_stalled_campaigns")
+def resume_stalled_campaigns() -> Dict[str, Any]:
+    """
+    Periodic task to resume running campaigns whose checkpoint stopped moving.
+    
+    A campaign is considered stalled when it is still "running", has
+    pending batches and its checkpoint was not updated for
+    STALLED_CAMPAIGN_MINUTES (worker crash, deploy, lost chord).
+    Campaigns deferred by the messaging tier or their throttle resume once
+    deferred_until passes.
+    """
+    logger.info("🔍 Checking for stalled campaigns...")
+    
+    try:
+        result = asyncio.run(_resume_stalled_campaigns_async())
+        logger.info(f"✅ Stalled campaigns processed: {result}")
+        return result
+        
+    except Exception as e:
+        logger.error(f"❌ Failed to resume stalled campaigns: {str(e)}")
+        raise
+
+
+async def _resume_stalled_campaigns_async() -> Dict[str, Any]:
+    """Async implementation of stalled campaigns recovery"""
+    
+    async with async_session() as db:
+        threshold = datetime.utcnow() - timedelta(minutes=STALLED_CAMPAIGN_MINUTES)
+        
+        stmt = select(Campaign).where(
+            and_(
+                Campaign.status == "running",
+                Campaign.deleted_at.is_(None),
+            )
+        )
+        result = await db.execute(stmt)
+        campaigns = result.scalars().all()
+        
+        resumed_count = 0
+        for campaign in campaigns:
+            checkpoint = CampaignCheckpointManager(campaign)
+            if not checkpoint.has_checkpoint or checkpoint.is_complete():
+                continue
+            
+            # Deferred campaigns resume as soon as their slot is due
+            deferred_until = checkpoint.deferred_until
+            if deferred_until:
+                if deferred_until > datetime.utcnow():
+                    continue
+            else:
+                updated_at = checkpoint.checkpoint.get("updated_at")
+                if updated_at and datetime.fromisoformat(updated_at) > threshold:
+                    continue
+            
+            queue = await _campaign_queue(db, campaign.organization_id)
+            execute_campaign.apply_async(args=[str(campaign.id)], queue=queue)
+            resumed_count += 1
+            logger.info(f"🔁 Resuming stalled campaign {campaign.id}: {campaign.name}")
+        
+        return {
+            "campaigns_running": len(campaigns),
+            "campaigns_resumed": resumed_count,
+        }
+
+
+# Occurrences of recurring campaigns are created this far ahead, so they can be
+# reviewed as scheduled campaigns before they are sent
+RECURRENCE_LOOKAHEAD_HOURS = 24
+# Occurrences missed by more than this (beat down) are skipped instead of sent late
+MISSED_OCCURRENCE_MINUTES = 60
+
+# Parent fields every occurrence is created with
+OCCURRENCE_FIELDS = (
+    "organization_id",
+    "created_by_user_id",
+    "whatsapp_number_id",
+    "template_id",
+    "description",
+    "campaign_type",
+    "message_type",
+    "message_content",
+    "template_variables",
+    "audience_type",
+    "target_tag_ids",
+    "target_contact_ids",
+    "segment_filters",
+    "messages_per_hour",
+    "delay_between_messages_seconds",
+    "throttle",
+    "ab_test",
+    "respect_opt_out",
+    "skip_active_conversations",
+    "retry_max_attempts",
+    "retry_base_delay",
+    "retry_max_delay",
+    "settings",
+)
+
+
+@celery_app.task(name="materialize_recurring_campaigns")
+def materialize_recurring_campaigns() -> Dict[str, Any]:
+    """
+    Periodic task to create the occurrences of recurring campaigns.
+    
+    Each occurrence due within RECURRENCE_LOOKAHEAD_HOURS becomes a child
+    campaign (status "scheduled", scheduled_at = occurrence time) that
+    process_scheduled_campaigns starts when due. Parents whose end condition
+    is reached are completed.
+    """
+    logger.info("🔍 Checking for recurring campaigns...")
+    
+    try:
+        result = asyncio.run(_materialize_recurring_campaigns_async())
+        logger.info(f"✅ Recurring campaigns processed: {result}")
+        return result
+        
+    except Exception as e:
+        logger.error(f"❌ Failed to materialize recurring campaigns: {str(e)}")
+        raise
+
+
+async def _materialize_recurring_campaigns_async() -> Dict[str, Any]:
+    """Async implementation of recurring campaigns materialization"""
+    
+    async with async_session() as db:
+        now = datetime.utcnow()
+        horizon = now + timedelta(hours=RECURRENCE_LOOKAHEAD_HOURS)
+        missed_before = now - timedelta(minutes=MISSED_OCCURRENCE_MINUTES)
+        
+        # Locked so overlapping runs never create the same occurrence twice
+        stmt = select(Campaign).where(
+            and_(
+                Campaign.status == "recurring",
+                Campaign.next_occurrence_at <= horizon,
+                Campaign.deleted_at.is_(None),
+            )
+        ).with_for_update(skip_locked=True)
+        result = await db.execute(stmt)
+        parents = result.scalars().all()
+        
+        created_count = 0
+        skipped_count = 0
+        finished_count = 0
+        for parent in parents:
+            recurrence = parent.recurrence or {}
+            next_at = naive_utc(parent.next_occurrence_at)
+            
+            while next_at and next_at <= horizon and not is_finished(recurrence, parent.occurrences_created):
+                if next_at < missed_before:
+                    logger.warning(
+                        f"⏭️ Skipping missed occurrence {next_at.isoformat()} of campaign {parent.id}"
+                    )
+                    skipped_count += 1
+                else:
+                    db.add(_build_occurrence(parent, next_at))
+                    parent.occurrences_created += 1
+                    created_count += 1
+                    logger.info(
+                        f"🔁 Occurrence #{parent.occurrences_created} of campaign {parent.id} "
+                        f"scheduled for {next_at.isoformat()}"
+                    )
+                next_at = next_occurrence(recurrence, parent.scheduled_at, next_at)
+            
+            if next_at is None or is_finished(recurrence, parent.occurrences_created):
+                parent.status = "completed"
+                parent.completed_at = now
+                parent.next_occurrence_at = None
+                finished_count += 1
+                logger.info(f"🏁 Recurring campaign {parent.id} finished")
+            else:
+                parent.next_occurrence_at = next_at
+        
+        await db.commit()
+        
+        return {
+            "campaigns_recurring": len(parents),
+            "occurrences_created": created_count,
+            "occurrences_skipped": skipped_count,
+            "campaigns_finished": finished_count,
+        }
+
+
+def _build_occurrence(parent: Campaign, scheduled_at: datetime) -> Campaign:
+    """Child campaign of the parent's next occurrence"""
+    number = parent.occurrences_created + 1
+    occurrence = Campaign(
+        **{field: copy.deepcopy(getattr(parent, field)) for field in OCCURRENCE_FIELDS}
+    )
+    occurrence.name = f"{parent.name} #{number}"[:255]
+    occurrence.parent_campaign_id = parent.id
+    occurrence.occurrence_number = number
+    occurrence.status = "scheduled"
+    occurrence.scheduled_at = scheduled_at
+    occurrence.total_recipients = parent.total_recipients
+    occurrence.messages_pending = parent.total_recipients
+    return occurrence
+
+
+@celery_app.task(name="evaluate_campaign_ab_tests")
+def evaluate_campaign_ab_tests() -> Dict[str, Any]:
+    """
+    Periodic task to declare the winners of campaign A/B tests.
+    
+    For every campaign waiting in status "testing", compares the variants
+    sent to the test group (see app.utils.campaign_ab_test.decide_winner).
+    Once a winner is declared, the rest of the audience is dispatched with it.
+    """
+    logger.info("🔍 Checking campaign A/B tests...")
+    
+    try:
+        result = asyncio.run(_evaluate_campaign_ab_tests_async())
+        logger.info(f"✅ Campaign A/B tests processed: {result}")
+        return result
+        
+    except Exception as e:
+        logger.error(f"❌ Failed to evaluate campaign A/B tests: {str(e)}")
+        raise
+
+
+async def _evaluate_campaign_ab_tests_async() -> Dict[str, Any]:
+    """Async implementation of A/B test evaluation"""
+    
+    async with async_session() as db:
+        stmt = select(Campaign).where(
+            and_(
+                Campaign.status == "testing",
+                Campaign.deleted_at.is_(None),
+            )
+        )
+        result = await db.execute(stmt)
+        campaigns = result.scalars().all()
+        
+        decided_count = 0
+        for campaign in campaigns:
+            config = campaign.ab_test or {}
+            test_completed_at = config.get("test_completed_at")
+            now = datetime.utcnow()
+            winner = decide_winner(
+                config,
+                variant_results(config, campaign.message_statuses or {}),
+                datetime.fromisoformat(test_completed_at) if test_completed_at else None,
+                now,
+            )
+            if not winner:
+                continue
+            
+            campaign.ab_test = {**config, "winner": {**winner, "decided_at": now.isoformat()}}
+            campaign.status = "running"
+            # Fresh batches for the rest of the audience
+            CampaignCheckpointManager(campaign).reset()
+            await db.commit()
+            
+            queue = await _campaign_queue(db, campaign.organization_id)
+            execute_campaign.apply_async(args=[str(campaign.id)], queue=queue)
+            decided_count += 1
+            logger.info(
+                f"🏆 Campaign {campaign.id} A/B test winner: variant {winner['variant']} "
+                f"({winner['reason']}), rolling out to the rest of the audience"
+            )
+        
+        return {
+            "campaigns_testing": len(campaigns),
+            "winners_declared": decided_count,
+        }
@@ -4,19 +4,88 @@ Celery Application Configuration
 Configures Celery for background tasks and periodic scheduling
 """
 
-from celery import Celery
+from urllib.parse import quote
+
+import asyncio
+import logging
+
+from celery import Celery, Task
 from celery.schedules import crontab
 from app.core.config import get_settings
+from app.core.fault_injection import InjectedFault, fault_injector
+from app.core.sla_queue import MAX_PRIORITY, PRIORITY_DEFAULT, SLA_HEADER, sla_scheduler
 
 settings = get_settings()
+logger = logging.getLogger(__name__)
+
+
+class FaultInjectingTask(Task):
+    """
+    Base task applying the "queue" fault injection rule (staging only)
+
+    An injected error follows the task's autoretry policy and then fails like
+    any other error; a drop acknowledges the task without running it.
+
+    Jobs sent by the SLA scheduler claim their ``sla_job_id`` first: when a
+    promoted copy of the job already started, this one is skipped.
+    """
+
+    def __call__(self, *args, **kwargs):
+        job_id = getattr(self.request, SLA_HEADER, None)
+        if job_id and not asyncio.run(sla_scheduler.claim(job_id, self.request.id)):
+            logger.info(f"⏭️ Task {self.name} skipped: SLA job {job_id} already started")
+            return None
+        if fault_injector.rule("queue"):
+            try:
+                if fault_injector.before_task("queue"):
+                    logger.warning(f"🧪 Task {self.name} dropped by fault injection")
+                    return None
+            except InjectedFault as e:
+                if isinstance(e, tuple(getattr(self, "autoretry_for", None) or ())):
+                    raise self.retry(exc=e)
+                raise
+        return super().__call__(*args, **kwargs)
+
+
+def _sentinel_url(db: int) -> str:
+    """kombu/celery Sentinel URL: sentinel://host:port/db;sentinel://..."""
+    password = f":{quote(settings.REDIS_PASSWORD, safe='')}@" if settings.REDIS_PASSWORD else ""
+    return ";".join(f"sentinel://{password}{node}/{db}" for node in settings.REDIS_SENTINELS)
+
+
+# Redis Sentinel: broker and results follow the current master after failover.
+# Redis Cluster is not supported by kombu, so the broker keeps CELERY_BROKER_URL.
+if settings.REDIS_MODE == "sentinel" and settings.REDIS_SENTINELS:
+    broker_url = _sentinel_url(1)
+    result_backend = _sentinel_url(2)
+    sentinel_options = {
+        "master_name": settings.REDIS_SENTINEL_MASTER,
+        "sentinel_kwargs": {"password": settings.REDIS_SENTINEL_PASSWORD},
+    }
+else:
+    broker_url = settings.CELERY_BROKER_URL
+    result_backend = settings.CELERY_RESULT_BACKEND
+    sentinel_options = None
 
 # Create Celery app
 celery_app = Celery(
     "pytake",
-    broker=settings.CELERY_BROKER_URL,
-    backend=settings.CELERY_RESULT_BACKEND,
+    broker=broker_url,
+    backend=result_backend,
+    task_cls=FaultInjectingTask,
 )
 
+# Priority sub-queues so SLA jobs can be promoted ahead of newer work (0 runs first)
+celery_app.conf.broker_transport_options = {
+    **(sentinel_options or {}),
+    "priority_steps": list(range(MAX_PRIORITY + 1)),
+    "sep": ":",
+    "queue_order_strategy": "priority",
+}
+
+if sentinel_options:
+    celery_app.conf.result_backend_transport_options = sentinel_options
+
 # Celery Configuration
 celery_app.conf.update(
     task_serializer="json",
@@ -33,6 +102,9 @@ celery_app.conf.update(
     # Result backend settings
     result_expires=3600,  # Results expire after 1 hour
 
+    # Priorities (see app.core.sla_queue)
+    task_default_priority=PRIORITY_DEFAULT,
+
     # Worker settings
     worker_prefetch_multiplier=4,
     worker_max_tasks_per_child=1000,
@@ -45,7 +117,19 @@ celery_app.conf.update(
         "process_batch": {"queue": "campaigns"},
         "finalize_campaign": {"queue": "campaigns"},
         "process_scheduled_campaigns": {"queue": "campaigns"},
+        "resume_stalled_campaigns": {"queue": "campaigns"},
         "process_webhook": {"queue": "webhooks"},
+        "replay_webhook_events": {"queue": "webhooks"},
+        "purge_webhook_events": {"queue": "webhooks"},
+        "plan_dunning_reminders": {"queue": "dunning"},
+        "send_dunning_reminder": {"queue": "dunning"},
+        "sync_ad_audiences": {"queue": "ad_audiences"},
+        "sync_ad_audience": {"queue": "ad_audiences"},
+        "remove_contact_from_ad_audiences": {"queue": "ad_audiences"},
+        "region_heartbeat": {"queue": "regional"},
+        "escalate_sla_jobs": {"queue": "sla"},
+        "check_whatsapp_tokens": {"queue": "templates"},
+        "import_contacts": {"queue": "imports"},
     },
 )
 
@@ -71,6 +155,131 @@ celery_app.conf.beat_schedule = {
         },
     },
 
+    # Resume campaigns interrupted mid-dispatch - Every 5 minutes
+    "resume-stalled-campaigns": {
+        "task": "resume_stalled_campaigns",
+        "schedule": crontab(minute="*/5"),
+        "options": {
+            "queue": "campaigns",
+            "expires": 300,
+        },
+    },
+
+    # Create the occurrences of recurring campaigns - Every 5 minutes
+    "materialize-recurring-campaigns": {
+        "task": "materialize_recurring_campaigns",
+        "schedule": crontab(minute="*/5"),
+        "options": {
+            "queue": "campaigns",
+            "expires": 300,
+        },
+    },
+
+    # Declare campaign A/B test winners and roll them out - Every 15 minutes
+    "evaluate-campaign-ab-tests": {
+        "task": "evaluate_campaign_ab_tests",
+        "schedule": crontab(minute="*/15"),
+        "options": {
+            "queue": "campaigns",
+            "expires": 900,
+        },
+    },
+
+    # Dunning reminders - Every hour (sends are delayed to each rule's send time)
+    "plan-dunning-reminders": {
+        "task": "plan_dunning_reminders",
+        "schedule": crontab(minute=5),
+        "options": {
+            "queue": "dunning",
+            "expires": 3600,
+        },
+    },
+
+    # Meta Custom Audiences - push segment changes
+    "sync-ad-audiences": {
+        "task": "sync_ad_audiences",
+        "schedule": float(settings.AD_AUDIENCE_SYNC_INTERVAL_MINUTES * 60),
+        "options": {
+            "queue": "ad_audiences",
+            "expires": settings.AD_AUDIENCE_SYNC_INTERVAL_MINUTES * 60,
+        },
+    },
+
+    # Stored webhook payloads retention - Every day at 3:30 AM
+    "purge-webhook-events": {
+        "task": "purge_webhook_events",
+        "schedule": crontab(hour=3, minute=30),
+        "options": {
+            "queue": "webhooks",
+            "expires": 3600,
+        },
+    },
+
+    # Meta access token validity/expiry - Every day at 8 AM
+    "check-whatsapp-tokens": {
+        "task": "check_whatsapp_tokens",
+        "schedule": crontab(hour=8, minute=0),
+        "options": {
+            "queue": "templates",
+            "expires": 3600,
+        },
+    },
+
+    # Expired flow console sessions - Every hour
+    "purge-flow-console-sessions": {
+        "task": "purge_flow_console_sessions",
+        "schedule": crontab(minute=15),
+        "options": {
+            "expires": 3600,
+        },
+    },
+
+    # Flows paused by wait nodes - Every minute
+    "resume-flow-waits": {
+        "task": "resume_flow_waits",
+        "schedule": crontab(),
+        "options": {
+            "expires": 60,
+        },
+    },
+
+    # Idle and expired flow sessions - Every minute
+    "expire-flow-sessions": {
+        "task": "expire_flow_sessions",
+        "schedule": crontab(),
+        "options": {
+            "expires": 60,
+        },
+    },
+
+    # Flow nodes interrupted mid-execution - Every minute
+    "resume-interrupted-flows": {
+        "task": "resume_interrupted_flows",
+        "schedule": crontab(),
+        "options": {
+            "expires": 60,
+        },
+    },
+
+    # invoice.overdue flow events - Every hour
+    "emit-overdue-invoice-events": {
+        "task": "emit_overdue_invoice_events",
+        "schedule": crontab(minute=35),
+        "options": {
+            "expires": 3600,
+        },
+    },
+
+    # Promote queued jobs about to miss their SLA - Every few seconds
+    "escalate-sla-jobs": {
+        "task": "escalate_sla_jobs",
+        "schedule": float(settings.SLA_ESCALATION_INTERVAL_SECONDS),
+        "options": {
+            "queue": "sla",
+            "expires": settings.SLA_ESCALATION_INTERVAL_SECONDS,
+        },
+    },
+
     # Example: Cleanup old data - Every day at 3 AM
     # "cleanup-old-data": {
     #     "task": "cleanup_old_messages",
@@ -79,14 +288,36 @@ celery_app.conf.beat_schedule = {
     # },
 }
 
+# Region heartbeats - one entry per region, consumed only by that region's workers
+if len(settings.REGIONS) > 1:
+    for _region in settings.REGIONS:
+        celery_app.conf.beat_schedule[f"region-heartbeat-{_region}"] = {
+            "task": "region_heartbeat",
+            "schedule": 30.0,
+            "options": {
+                "queue": f"regional.{_region}",
+                "expires": 30,
+            },
+        }
+
 # Auto-discover tasks
 celery_app.autodiscover_tasks(
     [
         "app.tasks.template_sync",
         "app.tasks.campaign_tasks",
         "app.tasks.flow_automation_tasks",
+        "app.tasks.webhook_tasks",
+        "app.tasks.dunning_tasks",
+        "app.tasks.ad_audience_tasks",
+        "app.tasks.sla_tasks",
+        "app.tasks.whatsapp_token_tasks",
+        "app.tasks.flow_console_tasks",
+        "app.tasks.flow_wait_tasks",
+        "app.tasks.flow_timeout_tasks",
+        "app.tasks.flow_checkpoint_tasks",
+        "app.tasks.flow_event_tasks",
+        "app.tasks.contact_import_tasks",
         # Add other task modules here as needed
-        # "app.tasks.webhook_tasks",
     ]
 )
 
@@ -0,0 +1,43 @@
+"""
+Contact Import Tasks - Celery task for CSV/XLSX contact imports
+
+POST /contacts/import stores the upload in the shared media storage and
+queues import_contacts, so any worker can import the file (see
+app.services.contact_import_service).
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from app.tasks.celery_app import celery_app
+from app.services.contact_import_service import run_contact_import
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(
+    name="import_contacts",
+    # Large files take longer than the default 30 minutes
+    time_limit=3 * 60 * 60,
+    soft_time_limit=3 * 60 * 60 - 300,
+)
+def import_contacts(
+    organization_id: str,
+    job: Dict[str, Any],
+    update_existing: bool = False,
+    tag_ids: Optional[List[str]] = None,
+    default_country_code: Optional[str] = None,
+) -> Dict[str, Any]:
+    """Import a stored contact file (failures are recorded in the import progress)"""
+    logger.info(f"📥 Importing contacts of import {job['import_id']}...")
+
+    progress = asyncio.run(run_contact_import(
+        UUID(organization_id),
+        job,
+        update_existing=update_existing,
+        tag_ids=[UUID(tag_id) for tag_id in tag_ids or []],
+        default_country_code=default_country_code,
+    ))
+    return progress.to_dict()
@@ -0,0 +1,93 @@
+"""
+Dunning Tasks - Celery tasks for ERP invoice reminders
+
+plan_dunning_reminders runs periodically, creates the day's reminders and
+puts each one on the delayed queue (eta = rule send time); send_dunning_reminder
+sends it, re-checking the invoice first. Reminders deferred by the number's
+messaging tier are re-enqueued at their reserved slot.
+
+Reminders are transactional sends: they carry an SLA_TRANSACTIONAL_SECONDS
+deadline counted from their send time (see app.core.sla_queue).
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+from uuid import UUID
+
+from app.tasks.celery_app import celery_app
+from app.core.config import settings
+from app.core.database import async_session
+from app.core.sla_queue import sla_scheduler
+from app.models.dunning import DunningReminder
+from app.services.dunning_service import DunningService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="plan_dunning_reminders")
+def plan_dunning_reminders() -> Dict[str, Any]:
+    """
+    Periodic task that plans today's dunning reminders.
+
+    Safe to run several times a day: each (rule, invoice) is planned once,
+    so invoices synced later in the day are still picked up.
+    """
+    logger.info("📅 Planning dunning reminders...")
+
+    try:
+        result = asyncio.run(_plan_dunning_reminders_async())
+        logger.info(f"✅ Dunning reminders planned: {result}")
+        return result
+
+    except Exception as e:
+        logger.error(f"❌ Failed to plan dunning reminders: {str(e)}")
+        raise
+
+
+async def _plan_dunning_reminders_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        reminders = await DunningService(db).plan_reminders()
+
+        for reminder in reminders:
+            await sla_scheduler.submit(
+                send_dunning_reminder,
+                args=[str(reminder.id)],
+                eta=reminder.scheduled_for,
+                queue="dunning",
+                sla_seconds=settings.SLA_TRANSACTIONAL_SECONDS,
+                organization_id=reminder.organization_id,
+            )
+
+        return {"reminders_planned": len(reminders)}
+
+
+@celery_app.task(
+    name="send_dunning_reminder",
+    bind=True,
+    autoretry_for=(ConnectionError,),
+    retry_backoff=True,
+    max_retries=3,
+)
+def send_dunning_reminder(self, reminder_id: str) -> Dict[str, Any]:
+    """Send one planned dunning reminder (runs at the rule's send time)"""
+    status = asyncio.run(_send_dunning_reminder_async(reminder_id))
+    return {"reminder_id": reminder_id, "status": status}
+
+
+async def _send_dunning_reminder_async(reminder_id: str) -> str:
+    async with async_session() as db:
+        status = await DunningService(db).send_reminder(UUID(reminder_id))
+
+        if status == "deferred":
+            reminder = await db.get(DunningReminder, UUID(reminder_id))
+            await sla_scheduler.submit(
+                send_dunning_reminder,
+                args=[reminder_id],
+                eta=reminder.scheduled_for,
+                queue="dunning",
+                sla_seconds=settings.SLA_TRANSACTIONAL_SECONDS,
+                organization_id=reminder.organization_id,
+            )
+
+        return status
@@ -11,11 +11,11 @@ from datetime import datetime, timedelta
 from typing import List, Dict, Any, Optional
 from uuid import UUID
 
-from celery import Task, group, chord
+from celery import group, chord
 from sqlalchemy import select, and_
 from sqlalchemy.ext.asyncio import AsyncSession
 
-from app.tasks.celery_app import celery_app
+from app.tasks.celery_app import FaultInjectingTask, celery_app
 from app.core.database import async_session
 from app.models.flow_automation import (
     FlowAutomation,
@@ -30,7 +30,7 @@ from app.services.whatsapp_service import WhatsAppService
 logger = logging.getLogger(__name__)
 
 
-class FlowAutomationTask(Task):
+class FlowAutomationTask(FaultInjectingTask):
     """Base task class with flow automation-specific error handling"""
     
     autoretry_for = (Exception,)
@@ -0,0 +1,37 @@
+"""
+Flow Checkpoint Tasks - resume nodes interrupted mid-execution
+
+Conversations record which node is running (see app.utils.flow_checkpoint);
+this task runs every minute, claims the conversations whose node has been
+"executing" for too long (the process died) and runs the node again
+without repeating the messages it already sent. See
+WhatsAppService.resume_interrupted_flows.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.services.whatsapp_service import WhatsAppService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="resume_interrupted_flows")
+def resume_interrupted_flows() -> Dict[str, Any]:
+    """Periodic task that resumes flow nodes interrupted by a crash or deploy"""
+    return asyncio.run(_resume_interrupted_flows_async())
+
+
+async def _resume_interrupted_flows_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        counts = await WhatsAppService(db).resume_interrupted_flows()
+
+    if any(counts.values()):
+        logger.info(
+            f"♻️ Flow checkpoints: {counts['resumed']} resumed, {counts['given_up']} given up, "
+            f"{counts['failed']} failed"
+        )
+    return counts
@@ -0,0 +1,31 @@
+"""
+Flow Console Tasks - cleanup of idle flow console sessions
+
+Console sessions are kept in PostgreSQL (Redis only caches them) and expire
+FLOW_CONSOLE_SESSION_TTL_SECONDS after their last turn; this task deletes the
+expired rows. See app.services.flow_console_service.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.services.flow_console_service import FlowConsoleService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="purge_flow_console_sessions")
+def purge_flow_console_sessions() -> Dict[str, Any]:
+    """Periodic task that deletes expired flow console sessions"""
+    return asyncio.run(_purge_flow_console_sessions_async())
+
+
+async def _purge_flow_console_sessions_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        deleted = await FlowConsoleService(db).purge_expired()
+
+    logger.info(f"🗑️ Purged {deleted} expired flow console sessions")
+    return {"deleted": deleted}
@@ -0,0 +1,50 @@
+"""
+Flow Event Tasks - start flows from internal and external events
+
+Emitting an event queues run_flow_event_trigger once per matching trigger
+(see FlowEventService.emit); emit_overdue_invoice_events runs every hour and
+emits invoice.overdue for open ERP invoices past their due date.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+from uuid import UUID
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.services.dunning_service import DunningService
+from app.services.flow_event_service import FlowEventService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="run_flow_event_trigger")
+def run_flow_event_trigger(trigger_id: str, contact_id: str, event_type: str, payload: Dict[str, Any]) -> str:
+    """Start the flow of an event trigger for a contact"""
+    return asyncio.run(_run_flow_event_trigger_async(trigger_id, contact_id, event_type, payload))
+
+
+async def _run_flow_event_trigger_async(
+    trigger_id: str, contact_id: str, event_type: str, payload: Dict[str, Any]
+) -> str:
+    async with async_session() as db:
+        outcome = await FlowEventService(db).run_trigger(UUID(trigger_id), UUID(contact_id), event_type, payload)
+
+    logger.info(f"⚡ Event {event_type}: trigger {trigger_id} {outcome} for contact {contact_id}")
+    return outcome
+
+
+@celery_app.task(name="emit_overdue_invoice_events")
+def emit_overdue_invoice_events() -> Dict[str, Any]:
+    """Periodic task that emits invoice.overdue for invoices that became overdue"""
+    return asyncio.run(_emit_overdue_invoice_events_async())
+
+
+async def _emit_overdue_invoice_events_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        emitted = await DunningService(db).emit_overdue_events()
+
+    if emitted:
+        logger.info(f"📅 invoice.overdue emitted for {emitted} invoice(s)")
+    return {"emitted": emitted}
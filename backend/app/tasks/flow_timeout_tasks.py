@@ -0,0 +1,36 @@
+"""
+Flow Timeout Tasks - enforce flow session timeouts
+
+Conversations store when their flow session times out without input and
+when it expires (see app.utils.flow_timeout); this task runs every minute,
+claims the overdue sessions and runs the chatbot's fallback or finishes the
+flow. See WhatsAppService.expire_flow_sessions.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.services.whatsapp_service import WhatsAppService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="expire_flow_sessions")
+def expire_flow_sessions() -> Dict[str, Any]:
+    """Periodic task that times out idle and expired flow sessions"""
+    return asyncio.run(_expire_flow_sessions_async())
+
+
+async def _expire_flow_sessions_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        counts = await WhatsAppService(db).expire_flow_sessions()
+
+    if any(counts.values()):
+        logger.info(
+            f"⏰ Flow timeouts: {counts['fallback']} fallbacks, {counts['finished']} finished, "
+            f"{counts['postponed']} postponed, {counts['failed']} failed"
+        )
+    return counts
@@ -0,0 +1,34 @@
+"""
+Flow Wait Tasks - resume flows paused by wait nodes
+
+Wait nodes store their resume moment in flow_waits; this task runs every
+minute, claims the due waits and follows each wait node's outgoing edge.
+See WhatsAppService._execute_wait.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.services.whatsapp_service import WhatsAppService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="resume_flow_waits")
+def resume_flow_waits() -> Dict[str, Any]:
+    """Periodic task that resumes flows whose wait nodes are due"""
+    return asyncio.run(_resume_flow_waits_async())
+
+
+async def _resume_flow_waits_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        counts = await WhatsAppService(db).resume_due_waits()
+
+    if any(counts.values()):
+        logger.info(
+            f"▶️ Flow waits: {counts['resumed']} resumed, {counts['cancelled']} cancelled, {counts['failed']} failed"
+        )
+    return counts
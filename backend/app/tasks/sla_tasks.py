@@ -0,0 +1,25 @@
+"""
+SLA Tasks - promotion of queued jobs about to miss their SLA
+
+escalate_sla_jobs runs every SLA_ESCALATION_INTERVAL_SECONDS on its own
+queue ("sla") so it is never stuck behind the backlog it is meant to cut
+through. See app.core.sla_queue.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+
+from app.tasks.celery_app import celery_app
+from app.core.sla_queue import sla_scheduler
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="escalate_sla_jobs")
+def escalate_sla_jobs() -> Dict[str, Any]:
+    """Re-publish at-risk jobs at the top priority and emit SlaAtRisk events"""
+    events = asyncio.run(sla_scheduler.escalate())
+    if events:
+        logger.warning(f"🚨 {len(events)} job(s) promoted to meet their SLA")
+    return {"promoted": len(events), "jobs": [event.job_id for event in events]}
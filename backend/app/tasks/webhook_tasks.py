@@ -0,0 +1,122 @@
+"""
+Webhook Tasks - Celery tasks for webhook processing
+
+Used in multi-region deployments: when a webhook lands on an instance that
+does not serve the tenant, the payload is forwarded to the region-tagged
+webhooks queue of the region the tenant is pinned to.
+
+Stored webhook events (see WebhookEventService) are replayed here on request
+and purged after WEBHOOK_EVENT_RETENTION_DAYS.
+"""
+
+import logging
+import asyncio
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.core.region_router import region_router
+from app.core.redis import redis_client
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="process_webhook", bind=True, max_retries=3)
+def process_webhook(
+    self,
+    payload: Dict[str, Any],
+    event_id: Optional[str] = None,
+    traceparent: Optional[str] = None,
+) -> Dict[str, Any]:
+    """
+    Process a Meta webhook payload in the worker's region.
+
+    Args:
+        payload: Raw webhook body (already signature-verified)
+        event_id: Stored webhook event to update with the outcome
+        traceparent: Trace of the instance that received the webhook
+    """
+    try:
+        status = asyncio.run(_process_webhook_async(payload, event_id, traceparent))
+        return {"status": status}
+
+    except Exception as e:
+        logger.error(f"❌ Webhook processing failed: {str(e)}")
+        raise self.retry(exc=e, countdown=5)
+
+
+async def _process_webhook_async(
+    payload: Dict[str, Any],
+    event_id: Optional[str] = None,
+    traceparent: Optional[str] = None,
+) -> str:
+    """Async implementation of webhook processing"""
+    from app.core.tracing import tracer
+    from app.services.webhook_event_service import WebhookEventService
+
+    with tracer.start_span("whatsapp.webhook.forwarded", parent=traceparent):
+        async with async_session() as db:
+            return await WebhookEventService(db).process(UUID(event_id) if event_id else None, payload)
+
+
+@celery_app.task(name="replay_webhook_events")
+def replay_webhook_events(event_ids: List[str]) -> Dict[str, Any]:
+    """
+    Replay stored webhook events (failed or skipped) requested through the API.
+
+    Args:
+        event_ids: Stored webhook event IDs, processed in arrival order
+    """
+    return asyncio.run(_replay_webhook_events_async(event_ids))
+
+
+async def _replay_webhook_events_async(event_ids: List[str]) -> Dict[str, Any]:
+    """Async implementation of webhook replay"""
+    from app.services.webhook_event_service import WebhookEventService
+
+    async with async_session() as db:
+        summary = await WebhookEventService(db).replay([UUID(event_id) for event_id in event_ids])
+
+    logger.info(f"🔁 Webhook replay finished: {summary}")
+    return summary
+
+
+@celery_app.task(name="purge_webhook_events")
+def purge_webhook_events() -> Dict[str, Any]:
+    """Delete stored webhook payloads older than WEBHOOK_EVENT_RETENTION_DAYS."""
+    return asyncio.run(_purge_webhook_events_async())
+
+
+async def _purge_webhook_events_async() -> Dict[str, Any]:
+    """Async implementation of webhook event retention"""
+    from app.core.config import settings
+    from app.services.webhook_event_service import WebhookEventService
+
+    async with async_session() as db:
+        deleted = await WebhookEventService(db).purge(settings.WEBHOOK_EVENT_RETENTION_DAYS)
+
+    logger.info(f"🗑️ Purged {deleted} stored webhook events")
+    return {"deleted": deleted}
+
+
+@celery_app.task(name="region_heartbeat")
+def region_heartbeat() -> Dict[str, Any]:
+    """
+    Publish the heartbeat of this worker's region.
+
+    Scheduled once per region on the "regional.<region>" queue, so the
+    heartbeat is only refreshed while that region has live workers.
+    """
+    return asyncio.run(_region_heartbeat_async())
+
+
+async def _region_heartbeat_async() -> Dict[str, Any]:
+    """Async implementation of region heartbeat"""
+    from app.core.config import settings
+
+    if not redis_client.client:
+        await redis_client.connect()
+
+    await region_router.record_heartbeat(settings.DEPLOYMENT_REGION)
+    return {"region": settings.DEPLOYMENT_REGION}
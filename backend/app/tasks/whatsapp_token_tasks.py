@@ -0,0 +1,37 @@
+"""
+WhatsApp Token Tasks - daily check of Meta access tokens
+
+check_whatsapp_tokens inspects the token of every active official number so
+operators are warned (whatsapp:token_expiring) days before Meta starts
+answering 401. See app.services.whatsapp_token_service.
+"""
+
+import asyncio
+import logging
+from typing import Any, Dict
+
+from app.tasks.celery_app import celery_app
+from app.core.database import async_session
+from app.services.whatsapp_token_service import WhatsAppTokenService
+
+logger = logging.getLogger(__name__)
+
+
+@celery_app.task(name="check_whatsapp_tokens")
+def check_whatsapp_tokens() -> Dict[str, Any]:
+    """Periodic task that checks the validity and expiry of WhatsApp access tokens"""
+    logger.info("🔑 Checking WhatsApp access tokens...")
+
+    try:
+        result = asyncio.run(_check_whatsapp_tokens_async())
+        logger.info(f"✅ WhatsApp access tokens checked: {result}")
+        return result
+
+    except Exception as e:
+        logger.error(f"❌ Failed to check WhatsApp access tokens: {str(e)}")
+        raise
+
+
+async def _check_whatsapp_tokens_async() -> Dict[str, Any]:
+    async with async_session() as db:
+        return await WhatsAppTokenService(db).check_all()
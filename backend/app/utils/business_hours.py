@@ -0,0 +1,221 @@
+"""
+Business hours and holiday calendar
+
+The tenant's working hours live in organization.settings["business_hours"],
+in the same format as queue business hours plus a holiday calendar:
+
+    {
+        "timezone": "America/Sao_Paulo",     # defaults to the organization timezone
+        "schedule": {
+            "monday": {"enabled": true, "start": "09:00", "end": "18:00"},
+            "saturday": {"enabled": true, "start": "09:00", "end": "12:00"},
+            "sunday": {"enabled": false}
+        },
+        "holidays": [
+            {"date": "2025-11-20", "name": "Consciência Negra"},
+            {"date": "12-25", "name": "Natal"},                     # every year
+            {"date": "12-24", "name": "Véspera de Natal", "start": "09:00", "end": "12:00"}
+        ]
+    }
+
+Days missing from the schedule are closed; a holiday closes the whole day
+unless it sets its own (reduced) hours. Without a schedule the organization
+is always open.
+
+The business_hours node branches on it:
+
+    {
+        "nodeType": "business_hours",
+        "saveToVariable": "expediente",        # optional: open, closed or holiday
+        "nextOpenVariable": "reabre_em"        # optional: "dd/mm/YYYY HH:MM" of the next opening
+    }
+
+Its outputs (edge sourceHandle) are "open", "closed" and "holiday"; holidays
+follow "closed" when the holiday output is not connected.
+"""
+
+from dataclasses import dataclass
+from datetime import date, datetime, time, timedelta
+from typing import Any, Dict, List, Optional, Tuple
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+BUSINESS_HOURS_NODE_TYPE = "business_hours"
+
+OPEN_HANDLE = "open"
+CLOSED_HANDLE = "closed"
+HOLIDAY_HANDLE = "holiday"
+
+DEFAULT_TIMEZONE = "America/Sao_Paulo"
+WEEKDAYS = ("monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday")
+
+# How far ahead the next opening is searched (long holiday breaks included)
+NEXT_OPEN_SEARCH_DAYS = 31
+
+
+@dataclass
+class BusinessHoursStatus:
+    """Whether the tenant is open at a given moment"""
+
+    is_open: bool
+    holiday: Optional[str] = None
+    next_open: Optional[datetime] = None
+
+    @property
+    def handle(self) -> str:
+        """Node output to follow"""
+        if self.is_open:
+            return OPEN_HANDLE
+        return HOLIDAY_HANDLE if self.holiday is not None else CLOSED_HANDLE
+
+
+def validate_business_hours(config: Optional[Dict[str, Any]]) -> List[str]:
+    """
+    Problems of a business hours configuration
+
+    Returns:
+        Error messages (empty when the configuration is valid)
+    """
+    if not config:
+        return []
+    if not isinstance(config, dict):
+        return ["business_hours must be an object"]
+
+    errors = []
+    if config.get("timezone"):
+        try:
+            ZoneInfo(config["timezone"])
+        except (ZoneInfoNotFoundError, ValueError):
+            errors.append(f"Unknown timezone '{config['timezone']}'")
+
+    schedule = config.get("schedule") or {}
+    if not isinstance(schedule, dict):
+        errors.append("schedule must be an object")
+        schedule = {}
+    for day, hours in schedule.items():
+        if day not in WEEKDAYS:
+            errors.append(f"Unknown weekday '{day}'")
+        elif not isinstance(hours, dict):
+            errors.append(f"{day} must be an object")
+        elif hours.get("enabled", True):
+            errors.extend(_check_period(day, hours))
+
+    holidays = config.get("holidays") or []
+    if not isinstance(holidays, list):
+        return errors + ["holidays must be a list"]
+    for holiday in holidays:
+        if not isinstance(holiday, dict) or _holiday_date(holiday.get("date")) is None:
+            errors.append(f"Invalid holiday date {holiday.get('date') if isinstance(holiday, dict) else holiday!r}")
+        elif holiday.get("start") or holiday.get("end"):
+            errors.extend(_check_period(f"Holiday {holiday['date']}", holiday))
+    return errors
+
+
+def business_hours_status(
+    config: Optional[Dict[str, Any]], now: datetime, default_timezone: Optional[str] = None
+) -> BusinessHoursStatus:
+    """Whether the tenant is open at now (aware datetime), the holiday and the next opening when closed"""
+    config = config or {}
+    if not config.get("schedule"):
+        return BusinessHoursStatus(is_open=True)
+
+    local_now = now.astimezone(_timezone(config, default_timezone))
+    holiday = holiday_name(config, local_now.date())
+    start, end = _hours(config, local_now.date())
+    if start is not None and start <= local_now.time().replace(tzinfo=None) < end:
+        return BusinessHoursStatus(is_open=True, holiday=holiday)
+    return BusinessHoursStatus(is_open=False, holiday=holiday, next_open=next_opening(config, local_now))
+
+
+def next_opening(config: Dict[str, Any], local_now: datetime) -> Optional[datetime]:
+    """Start of the next working period after local_now (in the tenant timezone), None when not found"""
+    for offset in range(NEXT_OPEN_SEARCH_DAYS + 1):
+        day = local_now.date() + timedelta(days=offset)
+        start, _ = _hours(config, day)
+        if start is None:
+            continue
+        opening = datetime.combine(day, start, tzinfo=local_now.tzinfo)
+        if opening > local_now:
+            return opening
+    return None
+
+
+def holiday_name(config: Dict[str, Any], day: date) -> Optional[str]:
+    """Name of the holiday on day ("" for unnamed holidays), None on regular days"""
+    holiday = _holiday(config, day)
+    return (holiday.get("name") or "") if holiday is not None else None
+
+
+def _hours(config: Dict[str, Any], day: date) -> Tuple[Optional[time], Optional[time]]:
+    """Opening and closing time of a day, (None, None) when closed"""
+    holiday = _holiday(config, day)
+    if holiday is not None:
+        hours = holiday if holiday.get("start") and holiday.get("end") else {"enabled": False}
+    else:
+        hours = (config.get("schedule") or {}).get(WEEKDAYS[day.weekday()]) or {"enabled": False}
+
+    if not hours.get("enabled", True):
+        return None, None
+    try:
+        start = _parse_time(hours.get("start") or "00:00")
+        end = _parse_time(hours.get("end") or "23:59")
+    except ValueError:
+        return None, None
+    if end == time(23, 59):
+        end = time.max
+    return (start, end) if start < end else (None, None)
+
+
+def _holiday(config: Dict[str, Any], day: date) -> Optional[Dict[str, Any]]:
+    for holiday in config.get("holidays") or []:
+        if not isinstance(holiday, dict):
+            continue
+        holiday_date = _holiday_date(holiday.get("date"))
+        if holiday_date is None:
+            continue
+        if isinstance(holiday_date, date) and holiday_date == day:
+            return holiday
+        if isinstance(holiday_date, tuple) and holiday_date == (day.month, day.day):
+            return holiday
+    return None
+
+
+def _holiday_date(value: Any):
+    """date for YYYY-MM-DD, (month, day) for yearly MM-DD, None when invalid"""
+    if not isinstance(value, str):
+        return None
+    try:
+        if len(value) == 10:
+            return date.fromisoformat(value)
+        if len(value) == 5:
+            month, day = (int(part) for part in value.split("-"))
+            date(2000, month, day)  # leap year, so 02-29 is accepted
+            return month, day
+    except ValueError:
+        return None
+    return None
+
+
+def _check_period(label: str, hours: Dict[str, Any]) -> List[str]:
+    try:
+        start = _parse_time(hours.get("start") or "00:00")
+        end = _parse_time(hours.get("end") or "23:59")
+    except ValueError:
+        return [f"{label}: times must be HH:MM"]
+    if start >= end:
+        return [f"{label}: start must be before end"]
+    return []
+
+
+def _parse_time(value: str) -> time:
+    return datetime.strptime(str(value), "%H:%M").time()
+
+
+def _timezone(config: Dict[str, Any], default_timezone: Optional[str]) -> ZoneInfo:
+    for name in (config.get("timezone"), default_timezone, DEFAULT_TIMEZONE):
+        if not name:
+            continue
+        try:
+            return ZoneInfo(name)
+        except (ZoneInfoNotFoundError, ValueError):
+            continue
+    return ZoneInfo(DEFAULT_TIMEZONE)
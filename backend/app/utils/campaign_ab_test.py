@@ -0,0 +1,205 @@
+"""
+A/B tests of campaigns
+
+A campaign with ab_test first sends its variants to a test share of the
+audience, then sends the winning variant to everyone else:
+
+    {
+        "variants": [
+            {"id": "a", "label": "Controle", "percentage": 50, "template_id": "<uuid>"},
+            {"id": "b", "label": "Cupom", "percentage": 50, "template_id": "<uuid>",
+             "template_variables": {"body": ["NOV10"]}},
+            # text campaigns: {"id": "c", "text": "Olá {{contact.name}}!"}
+        ],
+        "test_percentage": 20,        # share of the audience in the test
+        "metric": "read",             # read, reply or click (button / list reply)
+        "confidence_level": 0.95,
+        "min_sample_size": 100,       # sends per variant before a winner may be declared
+        "max_test_hours": 48,         # optional: roll out the best variant after this long
+        "winner": {"variant": "b", "reason": "significant", "p_value": 0.003, "decided_at": "..."}
+    }
+
+Contacts are split by hashing the campaign with the contact (see
+app.utils.flow_split), so a resumed dispatch puts everyone in the same group
+and variant. After the test sends, the campaign waits in status "testing"
+until evaluate_campaign_ab_tests declares a winner: every variant has
+min_sample_size sends and the best rate beats each other variant in a
+two-proportion z-test at the confidence level. Results come from the
+per-contact entries of campaign.message_statuses (variant, status, replied_at,
+clicked_at).
+"""
+
+import math
+from datetime import datetime, timedelta
+from statistics import NormalDist
+from typing import Any, Dict, Iterable, List, Optional, Tuple
+
+from app.utils.flow_split import assign_variant, split_variants
+
+METRIC_READ = "read"
+METRIC_REPLY = "reply"
+METRIC_CLICK = "click"
+
+WINNER_SIGNIFICANT = "significant"
+WINNER_TIMEOUT = "max_test_hours"
+
+# Statuses of a contact that received the message
+SENT_STATUSES = {"sent", "delivered", "read"}
+DELIVERED_STATUSES = {"delivered", "read"}
+# Inbound message types that answer a button or list of the campaign message
+CLICK_MESSAGE_TYPES = {"button", "interactive"}
+
+
+def ab_variants(config: Dict[str, Any]) -> List[Dict[str, Any]]:
+    """Variants of the test with their content (template_id, template_variables or text)"""
+    content = {str(variant.get("id")): variant for variant in config.get("variants") or [] if isinstance(variant, dict)}
+    return [{**content[variant["id"]], **variant} for variant in split_variants(config)]
+
+
+def in_test_group(config: Dict[str, Any], campaign_id: Any, contact_id: Any) -> bool:
+    """Whether a contact is in the test share of the audience"""
+    percentage = float(config.get("test_percentage") or 0)
+    groups = [{"id": "test", "percentage": percentage}, {"id": "rest", "percentage": 100 - percentage}]
+    group = assign_variant(groups, f"campaign:{campaign_id}:test", contact_id)
+    return bool(group) and group["id"] == "test"
+
+
+def winner_variant(config: Dict[str, Any]) -> Optional[Dict[str, Any]]:
+    """Variant declared the winner, if any"""
+    winner = (config.get("winner") or {}).get("variant")
+    return next((variant for variant in ab_variants(config) if variant["id"] == winner), None)
+
+
+def contact_variant(config: Dict[str, Any], campaign_id: Any, contact_id: Any) -> Optional[Dict[str, Any]]:
+    """
+    Variant to send to a contact
+
+    Returns:
+        The contact's variant in the test group, the winner for everyone else
+        once decided, None while the rest of the audience waits
+    """
+    if in_test_group(config, campaign_id, contact_id):
+        return assign_variant(ab_variants(config), f"campaign:{campaign_id}", contact_id)
+    return winner_variant(config)
+
+
+def phase_contacts(config: Dict[str, Any], campaign_id: Any, contacts: Iterable[Any]) -> List[Any]:
+    """Contacts to dispatch now: the test group, or the rest once a winner is declared"""
+    rollout = winner_variant(config) is not None
+    return [
+        contact for contact in contacts
+        if in_test_group(config, campaign_id, contact.id) != rollout
+    ]
+
+
+def record_engagement(entry: Dict[str, Any], message_type: Optional[str], at: datetime) -> bool:
+    """
+    Mark a contact's reply (and click, for button and list replies) on its status entry
+
+    Returns:
+        True if this was the contact's first reply
+    """
+    first = not entry.get("replied_at")
+    if first:
+        entry["replied_at"] = at.isoformat()
+    if message_type in CLICK_MESSAGE_TYPES and not entry.get("clicked_at"):
+        entry["clicked_at"] = at.isoformat()
+    return first
+
+
+def wilson_interval(successes: int, total: int, confidence: float) -> Tuple[float, float]:
+    """Confidence interval of a rate (0-1)"""
+    if total <= 0:
+        return 0.0, 0.0
+    z = NormalDist().inv_cdf(1 - (1 - confidence) / 2)
+    rate = successes / total
+    center = (rate + z * z / (2 * total)) / (1 + z * z / total)
+    margin = z * math.sqrt(rate * (1 - rate) / total + z * z / (4 * total * total)) / (1 + z * z / total)
+    return max(0.0, center - margin), min(1.0, center + margin)
+
+
+def two_proportion_p_value(successes_a: int, total_a: int, successes_b: int, total_b: int) -> float:
+    """One-sided p-value that rate a is higher than rate b (z-test)"""
+    if total_a <= 0 or total_b <= 0:
+        return 1.0
+    pooled = (successes_a + successes_b) / (total_a + total_b)
+    error = math.sqrt(pooled * (1 - pooled) * (1 / total_a + 1 / total_b))
+    if error == 0:
+        return 1.0
+    z = (successes_a / total_a - successes_b / total_b) / error
+    return 1 - NormalDist().cdf(z)
+
+
+def variant_results(config: Dict[str, Any], statuses: Dict[str, Dict[str, Any]]) -> List[Dict[str, Any]]:
+    """
+    Sends, engagement and metric rate of each variant
+
+    Returns:
+        One dict per variant: variant, label, sent, delivered, read, replied,
+        clicked, conversions (of the metric), rate and its confidence interval
+    """
+    metric = config.get("metric") or METRIC_READ
+    confidence = float(config.get("confidence_level") or 0.95)
+    counts = {
+        variant["id"]: {
+            "variant": variant["id"], "label": variant["label"],
+            "sent": 0, "delivered": 0, "read": 0, "replied": 0, "clicked": 0,
+        }
+        for variant in ab_variants(config)
+    }
+    for entry in statuses.values():
+        row = counts.get(entry.get("variant"))
+        if row is None or (entry.get("status") not in SENT_STATUSES and not entry.get("replied_at")):
+            continue
+        row["sent"] += 1
+        row["delivered"] += entry.get("status") in DELIVERED_STATUSES or bool(entry.get("replied_at"))
+        row["read"] += entry.get("status") == "read" or bool(entry.get("replied_at"))
+        row["replied"] += bool(entry.get("replied_at"))
+        row["clicked"] += bool(entry.get("clicked_at"))
+
+    results = []
+    for row in counts.values():
+        conversions = row[{METRIC_READ: "read", METRIC_REPLY: "replied", METRIC_CLICK: "clicked"}.get(metric, "read")]
+        low, high = wilson_interval(conversions, row["sent"], confidence)
+        results.append({
+            **row,
+            "conversions": conversions,
+            "rate": conversions / row["sent"] if row["sent"] else 0.0,
+            "ci_low": low,
+            "ci_high": high,
+        })
+    return results
+
+
+def decide_winner(
+    config: Dict[str, Any],
+    results: List[Dict[str, Any]],
+    test_completed_at: Optional[datetime],
+    now: datetime,
+) -> Optional[Dict[str, Any]]:
+    """
+    Winner of the test, if it can be declared
+
+    Returns:
+        {"variant", "reason", "p_value"}: significant when the best variant beats
+        every other one with enough sends, max_test_hours when the test ran out
+        of time (best rate wins); None to keep waiting
+    """
+    if len(results) < 2:
+        return {"variant": results[0]["variant"], "reason": WINNER_SIGNIFICANT, "p_value": None} if results else None
+
+    ranked = sorted(results, key=lambda row: (-row["rate"], -row["sent"], row["variant"]))
+    best = ranked[0]
+    p_value = max(
+        two_proportion_p_value(best["conversions"], best["sent"], row["conversions"], row["sent"])
+        for row in ranked[1:]
+    )
+    confidence = float(config.get("confidence_level") or 0.95)
+    min_sample = int(config.get("min_sample_size") or 0)
+    if all(row["sent"] >= min_sample for row in results) and best["sent"] and p_value < 1 - confidence:
+        return {"variant": best["variant"], "reason": WINNER_SIGNIFICANT, "p_value": p_value}
+
+    max_hours = config.get("max_test_hours")
+    if max_hours and test_completed_at and now >= test_completed_at + timedelta(hours=float(max_hours)):
+        return {"variant": best["variant"], "reason": WINNER_TIMEOUT, "p_value": p_value}
+    return None
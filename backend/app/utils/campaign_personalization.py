@@ -0,0 +1,119 @@
+"""
+Per-contact variables of campaign messages
+
+Text messages and template variables of a campaign may reference the
+contact they are sent to:
+
+    {{contact.name}}, {{contact.first_name}}, {{contact.phone}}, {{contact.email}},
+    {{contact.company}}, {{contact.job_title}}, {{contact.language}},
+    {{contact.address_city}}, {{contact.address_state}}, {{contact.address_country}}
+    {{contact.attributes.<key>}} (or {{contact.custom_fields.<key>}})
+
+A variable without a value renders empty and is reported as missing, so a
+dry run can flag the contacts that would get an incomplete message.
+"""
+
+import re
+from typing import Any, Dict, List, Optional, Set, Tuple
+
+PLACEHOLDER_PATTERN = re.compile(r"\{\{\s*contact\.([A-Za-z0-9_.\-]+)\s*\}\}")
+TEMPLATE_PARAMETER_PATTERN = re.compile(r"\{\{\s*(\d+)\s*\}\}")
+
+# Variable -> contact column
+CONTACT_VARIABLES = {
+    "name": "name",
+    "first_name": "name",
+    "phone": "whatsapp_id",
+    "whatsapp_id": "whatsapp_id",
+    "email": "email",
+    "company": "company",
+    "job_title": "job_title",
+    "language": "language",
+    "address_city": "address_city",
+    "address_state": "address_state",
+    "address_country": "address_country",
+}
+ATTRIBUTE_PREFIXES = ("attributes.", "custom_fields.")
+
+
+def variable_field(path: str) -> Optional[str]:
+    """Contact field of a variable (a segment field name), None for unknown variables"""
+    for prefix in ATTRIBUTE_PREFIXES:
+        if path.startswith(prefix) and len(path) > len(prefix):
+            return f"attributes.{path[len(prefix):]}"
+    return CONTACT_VARIABLES.get(path)
+
+
+def contact_value(contact: Any, path: str) -> Optional[str]:
+    """Value of a variable for a contact (None when missing)"""
+    field = variable_field(path)
+    if field is None:
+        return None
+    if field.startswith("attributes."):
+        value = (getattr(contact, "attributes", None) or {}).get(field.split(".", 1)[1])
+    else:
+        value = getattr(contact, field, None)
+        if path == "first_name" and value:
+            value = str(value).split()[0]
+    if value is None or value == "":
+        return None
+    return str(value)
+
+
+def find_variables(value: Any) -> Set[str]:
+    """Contact variables referenced in a text, list or dict"""
+    if isinstance(value, str):
+        return set(PLACEHOLDER_PATTERN.findall(value))
+    if isinstance(value, dict):
+        return set().union(*(find_variables(item) for item in value.values())) if value else set()
+    if isinstance(value, (list, tuple)):
+        return set().union(*(find_variables(item) for item in value)) if value else set()
+    return set()
+
+
+def personalize(value: Any, contact: Any) -> Tuple[Any, List[str]]:
+    """
+    Value with the contact's variables filled in
+
+    Returns:
+        (personalized value, variables without a value for the contact)
+    """
+    missing: List[str] = []
+
+    def fill(item: Any) -> Any:
+        if isinstance(item, str):
+            def replace(match: "re.Match") -> str:
+                resolved = contact_value(contact, match.group(1))
+                if resolved is None:
+                    if match.group(1) not in missing:
+                        missing.append(match.group(1))
+                    return ""
+                return resolved
+            return PLACEHOLDER_PATTERN.sub(replace, item)
+        if isinstance(item, dict):
+            return {key: fill(child) for key, child in item.items()}
+        if isinstance(item, list):
+            return [fill(child) for child in item]
+        return item
+
+    return fill(value), missing
+
+
+def render_template_text(text: Optional[str], parameters: List[Any]) -> Optional[str]:
+    """Template text ({{1}}, {{2}}...) with its parameters"""
+    if not text:
+        return text
+
+    def replace(match: "re.Match") -> str:
+        index = int(match.group(1)) - 1
+        return str(parameters[index]) if 0 <= index < len(parameters) else match.group(0)
+
+    return TEMPLATE_PARAMETER_PATTERN.sub(replace, text)
+
+
+def component_parameters(components: Optional[List[Dict[str, Any]]], section: str) -> List[Any]:
+    """Text parameters of a template component (header or body)"""
+    for component in components or []:
+        if component.get("type") == section:
+            return [parameter.get("text", "") for parameter in component.get("parameters") or []]
+    return []
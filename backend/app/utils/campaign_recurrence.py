@@ -0,0 +1,124 @@
+"""
+Occurrences of recurring campaigns
+
+A campaign with a recurrence is a parent that never sends itself: the
+materialize_recurring_campaigns task creates one child campaign per
+occurrence (status "scheduled", linked by parent_campaign_id), which the
+scheduled-campaigns task then runs like any other. Recurrence config:
+
+    frequency               daily, weekly or monthly
+    interval                every N days / weeks / months (counted from the parent's scheduled_at)
+    days_of_week            weekly: ["MON", "WED", "FRI"]
+    day_of_month            monthly: 1-31 (last day of shorter months)
+    hour, minute            local time of each occurrence
+    timezone                IANA name of that local time
+    end_after_occurrences   stop after N occurrences
+    end_date                stop after this local date
+
+Datetimes are naive UTC, like the rest of the campaign scheduling.
+"""
+
+import calendar
+from datetime import date, datetime, timedelta, timezone
+from typing import Any, Dict, Optional
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+FREQUENCY_DAILY = "daily"
+FREQUENCY_WEEKLY = "weekly"
+FREQUENCY_MONTHLY = "monthly"
+
+WEEKDAYS = ("MON", "TUE", "WED", "THU", "FRI", "SAT", "SUN")
+DEFAULT_TIMEZONE = "America/Sao_Paulo"
+
+
+def recurrence_zone(config: Dict[str, Any]) -> ZoneInfo:
+    """Timezone of the occurrences (the default one when unknown)"""
+    try:
+        return ZoneInfo(config.get("timezone") or DEFAULT_TIMEZONE)
+    except (ZoneInfoNotFoundError, ValueError):
+        return ZoneInfo(DEFAULT_TIMEZONE)
+
+
+def is_finished(config: Dict[str, Any], occurrences_created: int) -> bool:
+    """Whether the end-after-N condition has been reached"""
+    limit = config.get("end_after_occurrences")
+    return bool(limit) and occurrences_created >= limit
+
+
+def naive_utc(moment: datetime) -> datetime:
+    """Moment as naive UTC (datetimes read from the database are aware)"""
+    if moment.tzinfo is None:
+        return moment
+    return moment.astimezone(timezone.utc).replace(tzinfo=None)
+
+
+def next_occurrence(
+    config: Dict[str, Any], anchor: datetime, after: datetime
+) -> Optional[datetime]:
+    """
+    First occurrence strictly after a moment
+
+    Args:
+        config: Recurrence config of the parent campaign
+        anchor: Start of the recurrence; intervals count from its local date
+        after: Moment to search from
+
+    Returns:
+        Naive UTC datetime, or None when the end date has passed
+    """
+    anchor, after = naive_utc(anchor), naive_utc(after)
+    zone = recurrence_zone(config)
+    interval = max(1, int(config.get("interval") or 1))
+    anchor_day = _local(anchor, zone).date()
+    end_date = _end_date(config)
+
+    day = max(anchor_day, _local(after, zone).date())
+    # Any rule matches at least once in interval periods (a month is at most 31 days)
+    for _ in range(31 * interval + 8):
+        if end_date and day > end_date:
+            return None
+        if _matches(config, anchor_day, day, interval):
+            local = datetime(
+                day.year, day.month, day.day,
+                int(config.get("hour") or 0), int(config.get("minute") or 0),
+                tzinfo=zone,
+            )
+            moment = local.astimezone(timezone.utc).replace(tzinfo=None)
+            if moment > after and moment >= anchor:
+                return moment
+        day += timedelta(days=1)
+    return None
+
+
+def _local(moment: datetime, zone: ZoneInfo) -> datetime:
+    return moment.replace(tzinfo=timezone.utc).astimezone(zone)
+
+
+def _end_date(config: Dict[str, Any]) -> Optional[date]:
+    end = config.get("end_date")
+    if isinstance(end, str):
+        return date.fromisoformat(end[:10])
+    return end
+
+
+def _matches(config: Dict[str, Any], anchor_day: date, day: date, interval: int) -> bool:
+    frequency = config.get("frequency")
+    if frequency == FREQUENCY_DAILY:
+        return (day - anchor_day).days % interval == 0
+
+    if frequency == FREQUENCY_WEEKLY:
+        days = config.get("days_of_week") or [WEEKDAYS[anchor_day.weekday()]]
+        if WEEKDAYS[day.weekday()] not in {name.upper() for name in days}:
+            return False
+        week_start = day - timedelta(days=day.weekday())
+        anchor_week_start = anchor_day - timedelta(days=anchor_day.weekday())
+        return ((week_start - anchor_week_start).days // 7) % interval == 0
+
+    if frequency == FREQUENCY_MONTHLY:
+        months = (day.year - anchor_day.year) * 12 + day.month - anchor_day.month
+        if months % interval:
+            return False
+        wanted = int(config.get("day_of_month") or anchor_day.day)
+        return day.day == min(wanted, calendar.monthrange(day.year, day.month)[1])
+
+    return False
@@ -0,0 +1,37 @@
+"""Click-to-chat (wa.me) deep link helpers."""
+import re
+from typing import Optional
+from urllib.parse import quote
+
+WA_ME_BASE_URL = "https://wa.me"
+
+# E.164 allows at most 15 digits (country code included)
+_MIN_DIGITS = 8
+_MAX_DIGITS = 15
+
+
+def normalize_wa_phone(phone_number: str) -> str:
+    """
+    Return the phone number as wa.me expects it: digits only, with country code.
+
+    Raises:
+        ValueError: If the number has an invalid length
+    """
+    digits = re.sub(r"\D", "", phone_number or "")
+    if not _MIN_DIGITS <= len(digits) <= _MAX_DIGITS:
+        raise ValueError(f"Invalid phone number for click-to-chat: {phone_number!r}")
+    return digits
+
+
+def build_click_to_chat_link(phone_number: str, text: Optional[str] = None) -> str:
+    """
+    Build a wa.me link that opens a chat with the number, optionally with prefilled text.
+
+    Example:
+        build_click_to_chat_link("+55 11 99999-9999", "Quero a promoção")
+        -> "https://wa.me/5511999999999?text=Quero%20a%20promo%C3%A7%C3%A3o"
+    """
+    link = f"{WA_ME_BASE_URL}/{normalize_wa_phone(phone_number)}"
+    if text:
+        link += f"?text={quote(text, safe='')}"
+    return link
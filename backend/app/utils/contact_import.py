@@ -0,0 +1,247 @@
+"""
+Streaming parse of contact import files
+
+CSV and XLSX files are read one row at a time from disk, so the size of an
+import is bounded by the disk, not the memory. The first row is the header;
+columns are matched by name (case and accents are ignored):
+
+    phone       phone, telefone, celular, whatsapp, whatsapp_id, numero, mobile
+    name        name, nome
+    email       email, e-mail
+    company     company, empresa
+    job_title   job_title, cargo
+    notes       notes, observacoes, obs
+
+Any other column goes to the contact's attributes. CSV files may be
+separated by comma, semicolon or tab and are read as UTF-8 (with or without
+BOM).
+
+Phones are normalized to the WhatsApp ID format (digits with country code):
+a leading 00 or trunk 0 is dropped and national numbers (up to 11 digits,
+without + or 00) get the default country code.
+"""
+
+import csv
+import io
+import re
+import unicodedata
+from dataclasses import asdict, dataclass, field
+from datetime import datetime
+from typing import Any, Dict, Iterator, List, Optional, Tuple
+
+FORMAT_CSV = "csv"
+FORMAT_XLSX = "xlsx"
+FORMATS = {".csv": FORMAT_CSV, ".txt": FORMAT_CSV, ".xlsx": FORMAT_XLSX}
+
+FIELD_ALIASES = {
+    "phone": {"phone", "telefone", "celular", "whatsapp", "whatsapp_id", "numero", "mobile"},
+    "name": {"name", "nome"},
+    "email": {"email", "e-mail"},
+    "company": {"company", "empresa"},
+    "job_title": {"job_title", "cargo"},
+    "notes": {"notes", "observacoes", "obs"},
+}
+# Longest value each contact column holds
+FIELD_LENGTHS = {"name": 255, "email": 255, "company": 255, "job_title": 255}
+
+MIN_PHONE_DIGITS = 10
+MAX_PHONE_DIGITS = 15
+# National numbers (area code + number) have at most this many digits
+MAX_NATIONAL_DIGITS = 11
+
+# Errors kept in the progress of an import (the counters cover every row)
+MAX_REPORTED_ERRORS = 100
+
+EMAIL_PATTERN = re.compile(r"^[^@\s]+@[^@\s]+\.[^@\s]+$")
+
+
+@dataclass
+class ContactRow:
+    """One valid row of an import file"""
+
+    line: int
+    phone: str
+    fields: Dict[str, str] = field(default_factory=dict)
+    attributes: Dict[str, str] = field(default_factory=dict)
+
+
+@dataclass
+class ImportProgress:
+    """Progress and outcome of an import"""
+
+    import_id: str
+    filename: str
+    file_format: str
+    status: str = "queued"  # queued, running, completed, failed
+    progress_percentage: float = 0.0
+    processed_rows: int = 0
+    created: int = 0
+    updated: int = 0
+    skipped: int = 0  # repeated in the file, or already a contact without update_existing
+    invalid: int = 0
+    errors: List[Dict[str, Any]] = field(default_factory=list)
+    error_message: Optional[str] = None
+    started_at: Optional[str] = None
+    completed_at: Optional[str] = None
+
+    def add_error(self, line: int, error: str) -> None:
+        self.invalid += 1
+        if len(self.errors) < MAX_REPORTED_ERRORS:
+            self.errors.append({"line": line, "error": error})
+
+    def finish(self, status: str, error_message: Optional[str] = None) -> None:
+        self.status = status
+        self.error_message = error_message
+        self.completed_at = datetime.utcnow().isoformat()
+        if status == "completed":
+            self.progress_percentage = 100.0
+
+    def to_dict(self) -> Dict[str, Any]:
+        return asdict(self)
+
+
+def file_format(filename: Optional[str]) -> Optional[str]:
+    """Format of an import file from its extension"""
+    name = (filename or "").lower()
+    return next((fmt for extension, fmt in FORMATS.items() if name.endswith(extension)), None)
+
+
+def normalize_phone(value: Any, default_country_code: Optional[str] = None) -> str:
+    """
+    Phone as a WhatsApp ID (digits with country code)
+
+    Raises:
+        ValueError: If the value is not a phone number
+    """
+    text = _text(value)
+    digits = re.sub(r"\D", "", text)
+    international = text.startswith("+") or digits.startswith("00")
+    digits = digits.lstrip("0")
+    if default_country_code and not international and len(digits) <= MAX_NATIONAL_DIGITS:
+        digits = default_country_code + digits
+
+    if not MIN_PHONE_DIGITS <= len(digits) <= MAX_PHONE_DIGITS:
+        raise ValueError(f"invalid phone number: {text!r}")
+    return digits
+
+
+def column_mapping(header: List[Any]) -> Dict[int, str]:
+    """
+    Field of each header column: a contact field or "attributes.<name>"
+
+    Raises:
+        ValueError: If no column holds the phone
+    """
+    mapping: Dict[int, str] = {}
+    for index, name in enumerate(header):
+        key = _key(name)
+        if not key:
+            continue
+        target = next((field_name for field_name, aliases in FIELD_ALIASES.items() if key in aliases), None)
+        if target and target in mapping.values():
+            target = None
+        mapping[index] = target or f"attributes.{key}"
+
+    if "phone" not in mapping.values():
+        raise ValueError("File must have a phone column (phone, telefone, celular or whatsapp)")
+    return mapping
+
+
+def parse_row(
+    line: int, values: List[Any], mapping: Dict[int, str], default_country_code: Optional[str] = None
+) -> Optional[ContactRow]:
+    """
+    Contact of a data row (None for blank rows)
+
+    Raises:
+        ValueError: If the phone or email is invalid
+    """
+    values = [_text(value) for value in values]
+    if not any(values):
+        return None
+
+    row = ContactRow(line=line, phone="")
+    for index, target in mapping.items():
+        value = values[index] if index < len(values) else ""
+        if not value:
+            continue
+        if target == "phone":
+            row.phone = normalize_phone(value, default_country_code)
+        elif target.startswith("attributes."):
+            row.attributes[target.split(".", 1)[1]] = value
+        else:
+            row.fields[target] = value[:FIELD_LENGTHS[target]] if target in FIELD_LENGTHS else value
+
+    if not row.phone:
+        raise ValueError("missing phone number")
+    email = row.fields.get("email")
+    if email and not EMAIL_PATTERN.match(email):
+        raise ValueError(f"invalid email: {email!r}")
+    return row
+
+
+class RowReader:
+    """Rows of an import file, read from disk one at a time"""
+
+    def __init__(self, path: str, file_format: str):
+        self.path = path
+        self.file_format = file_format
+        self._progress = 0.0
+
+    @property
+    def progress(self) -> float:
+        """Share of the file read so far (0-1)"""
+        return self._progress
+
+    def __iter__(self) -> Iterator[Tuple[int, List[Any]]]:
+        """(line, values) of every row, header included"""
+        if self.file_format == FORMAT_XLSX:
+            return self._xlsx_rows()
+        return self._csv_rows()
+
+    def _csv_rows(self) -> Iterator[Tuple[int, List[Any]]]:
+        with open(self.path, "rb") as raw:
+            size = max(1, raw.seek(0, io.SEEK_END))
+            raw.seek(0)
+            text = io.TextIOWrapper(raw, encoding="utf-8-sig", errors="replace", newline="")
+            first = text.readline()
+            delimiter = max((",", ";", "\t"), key=first.count)
+            for line, values in enumerate(csv.reader(_chain(first, text), delimiter=delimiter), start=1):
+                self._progress = min(raw.tell() / size, 1.0)
+                yield line, values
+        self._progress = 1.0
+
+    def _xlsx_rows(self) -> Iterator[Tuple[int, List[Any]]]:
+        from openpyxl import load_workbook
+
+        workbook = load_workbook(self.path, read_only=True, data_only=True)
+        try:
+            sheet = workbook.active
+            total = sheet.max_row or 0
+            for line, values in enumerate(sheet.iter_rows(values_only=True), start=1):
+                if total:
+                    self._progress = min(line / total, 1.0)
+                yield line, list(values)
+        finally:
+            workbook.close()
+        self._progress = 1.0
+
+
+def _chain(first: str, rest: Iterator[str]) -> Iterator[str]:
+    yield first
+    yield from rest
+
+
+def _text(value: Any) -> str:
+    """Cell as text (spreadsheets store phones as numbers)"""
+    if value is None:
+        return ""
+    if isinstance(value, float) and value.is_integer():
+        value = int(value)
+    return str(value).strip()
+
+
+def _key(name: Any) -> str:
+    """Header name without accents, lowercase, spaces as underscores"""
+    text = unicodedata.normalize("NFKD", _text(name)).encode("ascii", "ignore").decode()
+    return re.sub(r"\s+", "_", text.lower())
@@ -0,0 +1,249 @@
+"""
+Contact segmentation
+
+The segment_filters of campaigns and ad audiences (audience_type "segment")
+is a group of conditions; groups nest:
+
+    {
+        "match": "all",                          # all (AND) or any (OR) of the conditions
+        "conditions": [
+            {"field": "tags", "operator": "has_any", "value": ["<tag uuid>", ...]},
+            {"field": "attributes.plano", "operator": "in", "value": ["gold", "platinum"]},
+            {"field": "last_interaction", "operator": "within_days", "value": 30},
+            {"match": "any", "conditions": [
+                {"field": "is_vip", "operator": "is_true"},
+                {"field": "lead_score", "operator": "gte", "value": 80}
+            ]}
+        ]
+    }
+
+Fields and their operators:
+
+    text        name, email, whatsapp_id, company, job_title, source, lifecycle_stage,
+                language, address_city, address_state, address_country
+                eq, neq, in, not_in, contains, not_contains, starts_with, is_empty, is_not_empty
+    number      lead_score, total_messages_sent, total_messages_received, total_conversations
+                eq, neq, gt, gte, lt, lte, between, is_empty, is_not_empty
+    boolean     is_vip, opt_in, is_blocked
+                is_true, is_false
+    date        created_at, last_message_at, last_message_received_at, last_interaction
+                within_days, older_than_days, before, after, between, is_empty, is_not_empty
+    tags        has_any, has_all, has_none (value: tag ids)
+    attributes.<key>  custom fields of the contact: the text operators plus gt, gte, lt,
+                lte, between (numeric values only), is_true, is_false, exists, not_exists
+
+last_interaction is the last message exchanged (is_empty: never talked).
+Text comparisons ignore case. An empty "all" group matches every contact,
+an empty "any" group none. The SQL is built by
+app.repositories.contact_segment.
+"""
+
+import re
+from dataclasses import dataclass, field
+from datetime import date, datetime, timezone
+from typing import Any, Dict, List, Optional, Union
+from uuid import UUID
+
+MATCH_ALL = "all"
+MATCH_ANY = "any"
+
+TEXT_FIELDS = {
+    "name", "email", "whatsapp_id", "company", "job_title", "source", "lifecycle_stage",
+    "language", "address_city", "address_state", "address_country",
+}
+NUMBER_FIELDS = {"lead_score", "total_messages_sent", "total_messages_received", "total_conversations"}
+BOOLEAN_FIELDS = {"is_vip", "opt_in", "is_blocked"}
+DATE_FIELDS = {"created_at", "last_message_at", "last_message_received_at", "last_interaction"}
+TAGS_FIELD = "tags"
+ATTRIBUTE_PREFIX = "attributes."
+
+TEXT_OPERATORS = {"eq", "neq", "in", "not_in", "contains", "not_contains", "starts_with", "is_empty", "is_not_empty"}
+NUMBER_OPERATORS = {"eq", "neq", "gt", "gte", "lt", "lte", "between", "is_empty", "is_not_empty"}
+BOOLEAN_OPERATORS = {"is_true", "is_false"}
+DATE_OPERATORS = {"within_days", "older_than_days", "before", "after", "between", "is_empty", "is_not_empty"}
+TAG_OPERATORS = {"has_any", "has_all", "has_none"}
+ATTRIBUTE_OPERATORS = (
+    TEXT_OPERATORS | {"gt", "gte", "lt", "lte", "between"} | BOOLEAN_OPERATORS | {"exists", "not_exists"}
+)
+
+# Operators without a value
+UNARY_OPERATORS = {"is_empty", "is_not_empty", "is_true", "is_false", "exists", "not_exists"}
+LIST_OPERATORS = {"in", "not_in", "has_any", "has_all", "has_none"}
+
+MAX_DEPTH = 4
+MAX_CONDITIONS = 50
+MAX_LIST_VALUES = 500
+ATTRIBUTE_KEY_PATTERN = re.compile(r"^[A-Za-z0-9_\-]{1,64}$")
+
+
+class SegmentError(ValueError):
+    """Invalid segmentation"""
+
+
+@dataclass
+class SegmentCondition:
+    """One field comparison (value already normalized for its field type)"""
+
+    field: str
+    operator: str
+    value: Any = None
+
+    @property
+    def attribute(self) -> Optional[str]:
+        """Key of a custom field condition"""
+        return self.field[len(ATTRIBUTE_PREFIX):] if self.field.startswith(ATTRIBUTE_PREFIX) else None
+
+
+@dataclass
+class SegmentGroup:
+    """Conditions joined by all (AND) or any (OR)"""
+
+    match: str = MATCH_ALL
+    conditions: List[Union[SegmentCondition, "SegmentGroup"]] = field(default_factory=list)
+
+
+def parse_segment(filters: Optional[Dict[str, Any]]) -> SegmentGroup:
+    """
+    Validated segmentation tree
+
+    Raises:
+        SegmentError: On the first invalid group or condition
+    """
+    counter = [0]
+    return _parse_group(filters or {}, 1, "segment", counter)
+
+
+def validate_segment(filters: Optional[Dict[str, Any]]) -> List[str]:
+    """
+    Problems of a segmentation
+
+    Returns:
+        Error messages (empty when the segmentation is valid)
+    """
+    try:
+        parse_segment(filters)
+    except SegmentError as e:
+        return [str(e)]
+    return []
+
+
+def _parse_group(data: Any, depth: int, path: str, counter: List[int]) -> SegmentGroup:
+    if not isinstance(data, dict):
+        raise SegmentError(f"{path} must be an object")
+    if depth > MAX_DEPTH:
+        raise SegmentError(f"{path}: groups nest at most {MAX_DEPTH} levels")
+
+    match = data.get("match") or MATCH_ALL
+    if match not in (MATCH_ALL, MATCH_ANY):
+        raise SegmentError(f"{path}: match must be 'all' or 'any'")
+    conditions = data.get("conditions") or []
+    if not isinstance(conditions, list):
+        raise SegmentError(f"{path}: conditions must be a list")
+
+    group = SegmentGroup(match=match)
+    for index, item in enumerate(conditions):
+        item_path = f"{path}.conditions[{index}]"
+        if isinstance(item, dict) and "conditions" in item:
+            group.conditions.append(_parse_group(item, depth + 1, item_path, counter))
+            continue
+        counter[0] += 1
+        if counter[0] > MAX_CONDITIONS:
+            raise SegmentError(f"Segment has more than {MAX_CONDITIONS} conditions")
+        group.conditions.append(_parse_condition(item, item_path))
+    return group
+
+
+def _parse_condition(data: Any, path: str) -> SegmentCondition:
+    if not isinstance(data, dict):
+        raise SegmentError(f"{path} must be an object")
+    name = data.get("field")
+    operator = data.get("operator")
+    if not isinstance(name, str) or not name:
+        raise SegmentError(f"{path}: field is required")
+
+    operators = _operators(name, path)
+    if operator not in operators:
+        raise SegmentError(f"{path}: operator '{operator}' is not valid for {name} ({', '.join(sorted(operators))})")
+
+    condition = SegmentCondition(field=name, operator=operator)
+    if operator in UNARY_OPERATORS:
+        return condition
+
+    value = data.get("value")
+    if operator in LIST_OPERATORS:
+        if not isinstance(value, list) or not value:
+            raise SegmentError(f"{path}: {operator} takes a non-empty list")
+        if len(value) > MAX_LIST_VALUES:
+            raise SegmentError(f"{path}: at most {MAX_LIST_VALUES} values")
+        condition.value = [_value(name, operator, item, path) for item in value]
+    elif operator == "between":
+        if not isinstance(value, list) or len(value) != 2:
+            raise SegmentError(f"{path}: between takes [from, to]")
+        condition.value = [_value(name, operator, item, path) for item in value]
+    else:
+        condition.value = _value(name, operator, value, path)
+    return condition
+
+
+def _operators(name: str, path: str) -> set:
+    if name in TEXT_FIELDS:
+        return TEXT_OPERATORS
+    if name in NUMBER_FIELDS:
+        return NUMBER_OPERATORS
+    if name in BOOLEAN_FIELDS:
+        return BOOLEAN_OPERATORS
+    if name in DATE_FIELDS:
+        return DATE_OPERATORS
+    if name == TAGS_FIELD:
+        return TAG_OPERATORS
+    if name.startswith(ATTRIBUTE_PREFIX):
+        if not ATTRIBUTE_KEY_PATTERN.match(name[len(ATTRIBUTE_PREFIX):]):
+            raise SegmentError(f"{path}: invalid custom field name '{name}'")
+        return ATTRIBUTE_OPERATORS
+    raise SegmentError(f"{path}: unknown field '{name}'")
+
+
+def _value(name: str, operator: str, value: Any, path: str) -> Any:
+    """Value of a condition in the type its field compares with"""
+    if name == TAGS_FIELD:
+        try:
+            return UUID(str(value))
+        except ValueError:
+            raise SegmentError(f"{path}: invalid tag id '{value}'")
+
+    if name in DATE_FIELDS:
+        if operator in ("within_days", "older_than_days"):
+            if isinstance(value, bool) or not isinstance(value, (int, float)) or value <= 0:
+                raise SegmentError(f"{path}: {operator} takes a positive number of days")
+            return value
+        return _datetime(value, path)
+
+    numeric = name in NUMBER_FIELDS or operator in ("gt", "gte", "lt", "lte", "between")
+    if numeric:
+        if isinstance(value, bool) or not isinstance(value, (int, float)):
+            raise SegmentError(f"{path}: {operator} takes a number")
+        return value
+
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if not isinstance(value, (str, int, float)) or value == "":
+        raise SegmentError(f"{path}: {operator} takes a text or number")
+    return str(value)
+
+
+def _datetime(value: Any, path: str) -> datetime:
+    """Aware UTC datetime of an ISO date or datetime"""
+    if isinstance(value, str):
+        try:
+            moment = datetime.fromisoformat(value.replace("Z", "+00:00"))
+        except ValueError:
+            raise SegmentError(f"{path}: invalid date '{value}'")
+    elif isinstance(value, datetime):
+        moment = value
+    elif isinstance(value, date):
+        moment = datetime(value.year, value.month, value.day)
+    else:
+        raise SegmentError(f"{path}: dates must be ISO strings")
+    if moment.tzinfo is None:
+        moment = moment.replace(tzinfo=timezone.utc)
+    return moment.astimezone(timezone.utc)
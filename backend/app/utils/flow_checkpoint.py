@@ -0,0 +1,162 @@
+"""
+Crash-safe execution checkpoints of flow nodes
+
+Before a node runs, the conversation records "about to execute node X"
+(node_checkpoint, with node_checkpoint_at set); when the flow moves on it
+records "completed node X" with the node's output. Messages sent while the
+node runs are counted:
+
+    {
+        "run_id": "3f0c...",             # one per execution of the node
+        "node_id": "node-3",             # React Flow node ID
+        "status": "executing",           # executing / completed
+        "message_id": "uuid",            # inbound message that triggered the run
+        "sent": 1,                       # messages confirmed sent by the node
+        "sending": false,                # a send was started and not confirmed
+        "replay": 0,                     # sends to skip in the current run (resume)
+        "resumes": 0,
+        "output": {"next_node_id": "node-4", "source_handle": null},  # once completed
+        "started_at": "...",
+        "completed_at": "..."
+    }
+
+node_checkpoint_at stays set while a node runs, and while the next node is
+about to run. If the process dies, the resume_interrupted_flows task picks
+conversations whose checkpoint is older than STALE_AFTER_SECONDS and runs
+the conversation's current node again with the same inbound message. Sends
+the interrupted run already made are skipped; a send started but not
+confirmed counts as made (at most once, never twice), so the contact never
+gets a message twice. Other side effects of the node (HTTP calls, variables)
+run again. A node interrupted MAX_RESUMES times is given up and the
+conversation is handed to an agent.
+"""
+
+import uuid
+from datetime import datetime
+from typing import Any, Awaitable, Callable, Dict, Optional
+
+STATUS_EXECUTING = "executing"
+STATUS_COMPLETED = "completed"
+
+# A node running for longer than this is considered interrupted
+STALE_AFTER_SECONDS = 300
+MAX_RESUMES = 3
+
+# Client calls that are not messages to the contact
+UNGUARDED_SENDS = {"send_typing_on"}
+
+
+def begin(
+    checkpoint: Optional[Dict[str, Any]],
+    node_id: str,
+    message_id: Optional[str],
+    now: datetime,
+    resume: bool = False,
+) -> Dict[str, Any]:
+    """
+    Checkpoint of a node about to execute
+
+    With resume, an interrupted run of the same node is continued: the
+    sends it made (or started) are replayed as skipped.
+    """
+    previous = checkpoint or {}
+    resuming = resume and previous.get("status") == STATUS_EXECUTING and previous.get("node_id") == node_id
+    sent = previous.get("sent", 0) + (1 if previous.get("sending") else 0) if resuming else 0
+    return {
+        "run_id": uuid.uuid4().hex,
+        "node_id": node_id,
+        "status": STATUS_EXECUTING,
+        "message_id": previous.get("message_id") if resuming else message_id,
+        "sent": sent,
+        "sending": False,
+        "replay": sent,
+        "resumes": previous.get("resumes", 0) + 1 if resuming else 0,
+        "started_at": now.isoformat(),
+    }
+
+
+def complete(
+    checkpoint: Optional[Dict[str, Any]],
+    node_id: str,
+    output: Dict[str, Any],
+    now: datetime,
+) -> Dict[str, Any]:
+    """Checkpoint of a node that finished with its output (next node, handle...)"""
+    previous = checkpoint if checkpoint and checkpoint.get("node_id") == node_id else {}
+    return {
+        "run_id": previous.get("run_id"),
+        "node_id": node_id,
+        "status": STATUS_COMPLETED,
+        "message_id": previous.get("message_id"),
+        "sent": previous.get("sent", 0),
+        "resumes": previous.get("resumes", 0),
+        "output": output,
+        "started_at": previous.get("started_at"),
+        "completed_at": now.isoformat(),
+    }
+
+
+def is_executing(checkpoint: Optional[Dict[str, Any]], run_id: Optional[str] = None) -> bool:
+    """Whether the checkpoint is a running node (this run when run_id is given)"""
+    if not checkpoint or checkpoint.get("status") != STATUS_EXECUTING:
+        return False
+    return run_id is None or checkpoint.get("run_id") == run_id
+
+
+def should_give_up(checkpoint: Optional[Dict[str, Any]]) -> bool:
+    """Whether the node was interrupted too many times to run it again"""
+    return bool(checkpoint) and checkpoint.get("resumes", 0) >= MAX_RESUMES
+
+
+class CheckpointedSender:
+    """
+    Client proxy whose send_* calls go through the node's checkpoint
+
+    Sends already made by an interrupted run are skipped (an empty response
+    is returned); others are recorded as started before the call and as
+    sent after it. persist saves the checkpoint after each change; on_sent,
+    if given, is called with the method name and arguments of each message
+    actually sent.
+    """
+
+    def __init__(
+        self,
+        client: Any,
+        checkpoint: Dict[str, Any],
+        persist: Callable[[Dict[str, Any]], Awaitable[None]],
+        on_sent: Optional[Callable[[str, tuple, Dict[str, Any]], Awaitable[None]]] = None,
+    ):
+        self._client = client
+        self._checkpoint = checkpoint
+        self._persist = persist
+        self._on_sent = on_sent
+
+    def __getattr__(self, name: str) -> Any:
+        attribute = getattr(self._client, name)
+        if not name.startswith("send_") or name in UNGUARDED_SENDS or not callable(attribute):
+            return attribute
+
+        async def send(*args, **kwargs):
+            checkpoint = self._checkpoint
+            if checkpoint.get("replay", 0) > 0:
+                checkpoint["replay"] -= 1
+                await self._persist(checkpoint)
+                return {}
+
+            checkpoint["sending"] = True
+            await self._persist(checkpoint)
+            try:
+                response = await attribute(*args, **kwargs)
+            except Exception:
+                # The API refused the message: callers retry it themselves
+                checkpoint["sending"] = False
+                await self._persist(checkpoint)
+                raise
+            checkpoint["sending"] = False
+            checkpoint["sent"] = checkpoint.get("sent", 0) + 1
+            await self._persist(checkpoint)
+            if self._on_sent is not None:
+                await self._on_sent(name, args, kwargs)
+            return response
+
+        return send
@@ -0,0 +1,244 @@
+"""
+Complexity and cycle analysis of flow canvases
+
+Run with the static validation (see ChatbotService.validate_flow), so the
+editor shows it on save and publishing is refused on errors. Metrics of the
+part of the flow reachable from the start node:
+
+    node_count           nodes in the canvas
+    max_depth            nodes on the longest path from the start node (each cycle counted once)
+    cycles               groups of nodes that can run again in the same session
+    unguarded_cycles     cycles without a node that waits for the contact or bounds the iterations
+    estimated_messages   messages sent on the longest path (cycles once, loop nodes maxIterations times)
+
+Diagnostics (limits from settings: FLOW_MAX_NODES, FLOW_MAX_DEPTH,
+FLOW_MAX_MESSAGES_PER_RUN; FLOW_COMPLEXITY_ENFORCEMENT "error" refuses
+publishing, "warning" only reports):
+
+    infinite_cycle       error    unguarded cycle with no way out: the bot would run forever
+    unguarded_cycle      warning  unguarded cycle that only stops when a condition holds
+    too_many_nodes       limit    node_count over FLOW_MAX_NODES
+    flow_too_deep        limit    max_depth over FLOW_MAX_DEPTH
+    too_many_messages    limit    estimated_messages over FLOW_MAX_MESSAGES_PER_RUN
+
+Messages of sub-flows are not counted (they are analysed when published).
+"""
+
+from dataclasses import asdict, dataclass, field
+from typing import Any, Dict, List, Optional, Set, Tuple
+
+from app.core.config import settings
+from app.utils.flow_loop import max_iterations
+from app.utils.flow_validation import SEVERITY_ERROR, SEVERITY_WARNING, FlowDiagnostic, flow_targets
+
+# Nodes that stop the run until the contact answers (or time passes): a cycle through
+# one of them cannot spin on its own
+INPUT_NODE_TYPES = {
+    "question",
+    "media_collect",
+    "location_collect",
+    "survey",
+    "intent",
+    "interactive_buttons",
+    "interactive_list",
+    "wait",
+}
+# Messages sent by each node type (survey: score question plus the comment prompt)
+MESSAGE_NODE_TYPES = {
+    "message": 1,
+    "question": 1,
+    "media_collect": 1,
+    "location_collect": 1,
+    "survey": 1,
+    "intent": 1,
+    "interactive_buttons": 1,
+    "interactive_list": 1,
+    "whatsapp_template": 1,
+    "handoff": 1,
+}
+
+
+@dataclass
+class ComplexityLimits:
+    """Complexity a flow may have when published"""
+
+    max_nodes: int
+    max_depth: int
+    max_messages_per_run: int
+    severity: str = SEVERITY_ERROR  # of the limit diagnostics
+
+    @classmethod
+    def from_settings(cls) -> "ComplexityLimits":
+        return cls(
+            max_nodes=settings.FLOW_MAX_NODES,
+            max_depth=settings.FLOW_MAX_DEPTH,
+            max_messages_per_run=settings.FLOW_MAX_MESSAGES_PER_RUN,
+            severity=SEVERITY_WARNING if settings.FLOW_COMPLEXITY_ENFORCEMENT == SEVERITY_WARNING else SEVERITY_ERROR,
+        )
+
+
+@dataclass
+class FlowComplexity:
+    """Graph metrics of a flow"""
+
+    node_count: int = 0
+    max_depth: int = 0
+    cycles: int = 0
+    unguarded_cycles: int = 0
+    estimated_messages: int = 0
+    cycle_nodes: List[List[str]] = field(default_factory=list)
+
+    def to_dict(self) -> Dict[str, Any]:
+        return asdict(self)
+
+
+def analyze_flow(
+    canvas_data: Optional[Dict[str, Any]], limits: Optional[ComplexityLimits] = None
+) -> Tuple[FlowComplexity, List[FlowDiagnostic]]:
+    """Metrics of a canvas and the diagnostics of its cycles and limits"""
+    limits = limits or ComplexityLimits.from_settings()
+    canvas_data = canvas_data or {}
+    nodes = {node["id"]: node for node in canvas_data.get("nodes", []) if node.get("id")}
+    edges = [
+        edge for edge in canvas_data.get("edges", [])
+        if edge.get("source") in nodes and edge.get("target") in nodes
+    ]
+    targets = {
+        source: [target for target in node_targets if target in nodes]
+        for source, node_targets in flow_targets(list(nodes.values()), edges).items()
+    }
+    complexity = FlowComplexity(node_count=len(nodes))
+    diagnostics: List[FlowDiagnostic] = []
+
+    start_id = next((node_id for node_id, node in nodes.items() if _node_type(node) == "start"), None)
+    if start_id:
+        components = _components(start_id, targets)
+        component_of = {node_id: index for index, members in enumerate(components) for node_id in members}
+
+        for members in components:
+            if len(members) == 1 and members[0] not in targets.get(members[0], []):
+                continue
+            complexity.cycles += 1
+            complexity.cycle_nodes.append(sorted(members))
+            if any(_node_type(nodes[node_id]) in INPUT_NODE_TYPES | {"loop"} for node_id in members):
+                continue
+
+            complexity.unguarded_cycles += 1
+            member_set = set(members)
+            has_exit = any(target not in member_set for node_id in members for target in targets.get(node_id, []))
+            path = " → ".join(sorted(members))
+            if has_exit:
+                diagnostics.append(FlowDiagnostic(
+                    "unguarded_cycle", SEVERITY_WARNING,
+                    f"Nodes {path} repeat without waiting for the contact until a condition lets the flow out",
+                    node_id=sorted(members)[0],
+                ))
+            else:
+                diagnostics.append(FlowDiagnostic(
+                    "infinite_cycle", SEVERITY_ERROR,
+                    f"Nodes {path} repeat forever without waiting for the contact",
+                    node_id=sorted(members)[0],
+                ))
+
+        complexity.max_depth, complexity.estimated_messages = _longest_paths(
+            start_id, components, component_of, targets, nodes
+        )
+
+    if complexity.node_count > limits.max_nodes:
+        diagnostics.append(FlowDiagnostic(
+            "too_many_nodes", limits.severity,
+            f"Flow has {complexity.node_count} nodes (limit {limits.max_nodes})",
+        ))
+    if complexity.max_depth > limits.max_depth:
+        diagnostics.append(FlowDiagnostic(
+            "flow_too_deep", limits.severity,
+            f"Longest path runs {complexity.max_depth} nodes (limit {limits.max_depth})",
+        ))
+    if complexity.estimated_messages > limits.max_messages_per_run:
+        diagnostics.append(FlowDiagnostic(
+            "too_many_messages", limits.severity,
+            f"A run may send about {complexity.estimated_messages} messages (limit {limits.max_messages_per_run})",
+        ))
+    return complexity, diagnostics
+
+
+def _node_type(node: Dict[str, Any]) -> Optional[str]:
+    return (node.get("data") or {}).get("nodeType")
+
+
+def _messages(node: Dict[str, Any]) -> int:
+    data = node.get("data") or {}
+    node_type = data.get("nodeType")
+    if node_type == "handoff" and data.get("sendTransferMessage") is False:
+        return 0
+    count = MESSAGE_NODE_TYPES.get(node_type, 0)
+    if node_type == "survey" and data.get("askComment"):
+        count += 1
+    return count
+
+
+def _components(start_id: str, targets: Dict[str, List[str]]) -> List[List[str]]:
+    """Strongly connected components reachable from the start node (Tarjan, iterative)"""
+    index: Dict[str, int] = {}
+    low: Dict[str, int] = {}
+    on_stack: Set[str] = set()
+    stack: List[str] = []
+    components: List[List[str]] = []
+
+    work = [(start_id, 0)]
+    while work:
+        node_id, position = work.pop()
+        if position == 0:
+            index[node_id] = low[node_id] = len(index)
+            stack.append(node_id)
+            on_stack.add(node_id)
+        node_targets = targets.get(node_id, [])
+        if position < len(node_targets):
+            work.append((node_id, position + 1))
+            target = node_targets[position]
+            if target not in index:
+                work.append((target, 0))
+            elif target in on_stack:
+                low[node_id] = min(low[node_id], index[target])
+            continue
+
+        if low[node_id] == index[node_id]:
+            members = []
+            while True:
+                member = stack.pop()
+                on_stack.discard(member)
+                members.append(member)
+                if member == node_id:
+                    break
+            components.append(members)
+        if work:
+            parent = work[-1][0]
+            low[parent] = min(low[parent], low[node_id])
+    return components
+
+
+def _longest_paths(
+    start_id: str,
+    components: List[List[str]],
+    component_of: Dict[str, int],
+    targets: Dict[str, List[str]],
+    nodes: Dict[str, Dict[str, Any]],
+) -> Tuple[int, int]:
+    """(nodes, messages) on the longest path from the start node over the components"""
+    depth: Dict[int, int] = {}
+    messages: Dict[int, int] = {}
+    # Tarjan emits components in reverse topological order: successors come first
+    for component, members in enumerate(components):
+        member_messages = sum(_messages(nodes[node_id]) for node_id in members)
+        loops = [node_id for node_id in members if _node_type(nodes[node_id]) == "loop"]
+        if loops:
+            member_messages *= max(max_iterations(nodes[node_id].get("data") or {}) for node_id in loops)
+        successors = {
+            component_of[target]
+            for node_id in members for target in targets.get(node_id, [])
+            if component_of.get(target, component) != component
+        }
+        depth[component] = len(members) + max((depth[successor] for successor in successors), default=0)
+        messages[component] = member_messages + max((messages[successor] for successor in successors), default=0)
+    start = component_of[start_id]
+    return depth[start], messages[start]
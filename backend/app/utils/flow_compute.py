@@ -0,0 +1,188 @@
+"""
+Set Variable node values
+
+Each entry of a set_variable node's "variables" list assigns one variable.
+Entries run in order, so later entries see the values set by earlier ones:
+
+    static      "value": literal value
+    variable    "variableSource": copy of another variable
+    expression  "expression": text with {{variables}} replaced ("{{nome}} {{sobrenome}}")
+    compute     "expression": expression language of Condition nodes
+                ("tentativas + 1", "preco * quantidade", "nome + ' ' + sobrenome")
+    increment   "variableSource" (defaults to the variable itself) plus "step"
+                (default 1); unset or non-numeric counters start at 0
+    date        "date" (ISO 8601, dd/mm/yyyy [HH:MM] or a {{variable}}; now if
+                empty) plus "addAmount" and "addUnit" (minutes, hours, days,
+                weeks, months, years); "timezone" of dates without an offset
+                and "format" (strftime, default dd/mm/yyyy HH:MM, or "iso")
+    random      one item of "options", or of the list in "variableSource"
+
+An entry that cannot be computed (invalid expression or date, empty list)
+sets its variable to null.
+"""
+
+import calendar
+import random
+import re
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, List, Optional, Tuple
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+from app.utils.flow_expression import ExpressionError, evaluate_expression
+from app.utils.flow_wait import DATE_FORMATS
+
+# Units added with timedelta; months and years keep the day of the month
+DATE_UNITS = ("minutes", "hours", "days", "weeks")
+
+DEFAULT_DATE_FORMAT = "%d/%m/%Y %H:%M"
+
+DEFAULT_TIMEZONE = "America/Sao_Paulo"
+
+_VARIABLE = re.compile(r"\{\{(\w+)\}\}")
+_WHOLE_VARIABLE = re.compile(r"^\{\{(\w+)\}\}$")
+
+
+def apply_set_variables(
+    config: List[Dict[str, Any]], variables: Dict[str, Any], now: Optional[datetime] = None
+) -> List[Tuple[str, str]]:
+    """
+    Assign the variables of a set_variable node (updates variables in place)
+
+    Returns:
+        (variable, error) of the entries set to null because they failed
+    """
+    errors = []
+    for entry in config or []:
+        name = entry.get("name")
+        if not name:
+            continue
+        try:
+            variables[name] = compute_value(entry, variables, now)
+        except ValueError as e:
+            variables[name] = None
+            errors.append((name, str(e)))
+    return errors
+
+
+def compute_value(entry: Dict[str, Any], variables: Dict[str, Any], now: Optional[datetime] = None) -> Any:
+    """
+    Value of one set_variable entry
+
+    Raises:
+        ValueError: If the value cannot be computed
+    """
+    value_type = entry.get("valueType", "static")
+
+    if value_type == "static":
+        return entry.get("value")
+
+    if value_type == "variable":
+        return variables.get(entry.get("variableSource"))
+
+    if value_type == "expression":
+        return _VARIABLE.sub(
+            lambda match: str(variables.get(match.group(1), "")), str(entry.get("expression") or "")
+        )
+
+    if value_type == "compute":
+        try:
+            return evaluate_expression(str(entry.get("expression") or ""), variables)
+        except ExpressionError as e:
+            raise ValueError(str(e)) from e
+
+    if value_type == "increment":
+        return _increment(variables.get(entry.get("variableSource") or entry.get("name")), entry.get("step", 1))
+
+    if value_type == "date":
+        return _compute_date(entry, variables, now or datetime.now(timezone.utc))
+
+    if value_type == "random":
+        options = entry.get("options")
+        if entry.get("variableSource"):
+            options = variables.get(entry["variableSource"])
+        if not isinstance(options, list) or not options:
+            raise ValueError("random needs a non-empty list of options")
+        return random.choice(options)
+
+    raise ValueError(f"Unknown value type: {value_type}")
+
+
+def _increment(current: Any, step: Any) -> Any:
+    try:
+        step = float(step)
+    except (TypeError, ValueError):
+        raise ValueError(f"Invalid increment step: {step!r}")
+    try:
+        current = float(current)
+    except (TypeError, ValueError):
+        current = 0.0
+    result = current + step
+    return int(result) if result.is_integer() else result
+
+
+def _compute_date(entry: Dict[str, Any], variables: Dict[str, Any], now: datetime) -> str:
+    zone = _zone(entry.get("timezone"))
+    moment = _parse_date(entry.get("date"), variables, zone) or now.astimezone(zone)
+
+    try:
+        amount = float(entry.get("addAmount") or 0)
+    except (TypeError, ValueError):
+        raise ValueError(f"Invalid date amount: {entry.get('addAmount')!r}")
+    unit = entry.get("addUnit", "days")
+    if unit in DATE_UNITS:
+        moment = moment + timedelta(**{unit: amount})
+    elif unit in ("months", "years"):
+        if not amount.is_integer():
+            raise ValueError(f"{unit} must be added in whole numbers")
+        moment = _add_months(moment, int(amount) * (12 if unit == "years" else 1))
+    else:
+        raise ValueError(f"Unknown date unit: {unit}")
+
+    output_format = entry.get("format") or DEFAULT_DATE_FORMAT
+    return moment.isoformat() if output_format == "iso" else moment.strftime(output_format)
+
+
+def _parse_date(value: Any, variables: Dict[str, Any], zone: ZoneInfo) -> Optional[datetime]:
+    """Date of an entry in the entry's zone, None for "now" """
+    match = _WHOLE_VARIABLE.match(str(value or "").strip())
+    if match:
+        value = variables.get(match.group(1))
+        if value in (None, ""):
+            raise ValueError(f"Variable {match.group(1)} has no date")
+
+    if isinstance(value, datetime):
+        moment = value
+    else:
+        text = str(value or "").strip()
+        if not text:
+            return None
+        moment = None
+        try:
+            moment = datetime.fromisoformat(text.replace("Z", "+00:00"))
+        except ValueError:
+            for date_format in DATE_FORMATS:
+                try:
+                    moment = datetime.strptime(text, date_format)
+                    break
+                except ValueError:
+                    continue
+        if moment is None:
+            raise ValueError(f"Invalid date: {value!r}")
+
+    if moment.tzinfo is None:
+        return moment.replace(tzinfo=zone)
+    return moment.astimezone(zone)
+
+
+def _add_months(moment: datetime, months: int) -> datetime:
+    """Same day months later, clamped to the month's last day (31/01 + 1 month = 28/02)"""
+    index = moment.month - 1 + months
+    year, month = moment.year + index // 12, index % 12 + 1
+    return moment.replace(year=year, month=month, day=min(moment.day, calendar.monthrange(year, month)[1]))
+
+
+def _zone(name: Optional[str]) -> ZoneInfo:
+    try:
+        return ZoneInfo(name or DEFAULT_TIMEZONE)
+    except (ZoneInfoNotFoundError, ValueError):
+        return ZoneInfo(DEFAULT_TIMEZONE)
@@ -0,0 +1,83 @@
+"""
+Event-triggered flow starts
+
+Flow event triggers start a flow for a contact when something happens
+outside the conversation. Built-in events and their payload:
+
+    campaign.reply_received  campaign_id, campaign_name, text
+    invoice.overdue          invoice (external_id, number, amount, currency,
+                             due_date, days_overdue, payment_url, pix_code, extra)
+    ticket.resolved          conversation_id, agent_id, reason
+
+External systems post any other event name ("order.shipped") with their own
+payload to /flow-events/emit.
+
+A trigger fires when every entry of its "conditions" matches the payload
+({"invoice.currency": "BRL", "status": ["late", "blocked"]}: dotted paths,
+a list accepts any of its values). "variable_mapping" copies payload values
+into flow variables ({"valor": "invoice.amount"}); the whole payload is also
+available as the "event" variable.
+"""
+
+import re
+from typing import Any, Dict, Optional
+
+EVENT_CAMPAIGN_REPLY = "campaign.reply_received"
+EVENT_INVOICE_OVERDUE = "invoice.overdue"
+EVENT_TICKET_RESOLVED = "ticket.resolved"
+
+BUILTIN_EVENTS = (EVENT_CAMPAIGN_REPLY, EVENT_INVOICE_OVERDUE, EVENT_TICKET_RESOLVED)
+
+# Flow variable holding the event type and payload
+EVENT_VARIABLE = "event"
+
+_EVENT_TYPE = re.compile(r"^[a-z0-9_]+(\.[a-z0-9_]+)*$")
+
+
+def validate_event_type(event_type: str) -> str:
+    """
+    Check an event name (lowercase words separated by dots)
+
+    Raises:
+        ValueError: If the name is invalid
+    """
+    if not _EVENT_TYPE.match(event_type or ""):
+        raise ValueError(f"Invalid event type '{event_type}': use lowercase words separated by dots")
+    return event_type
+
+
+def payload_value(payload: Any, path: str) -> Optional[Any]:
+    """Value at a dotted path of the payload ("invoice.amount", "items.0.sku"), None when missing"""
+    value = payload
+    for part in str(path).split("."):
+        if isinstance(value, dict):
+            value = value.get(part)
+        elif isinstance(value, list) and part.isdigit() and int(part) < len(value):
+            value = value[int(part)]
+        else:
+            return None
+        if value is None:
+            return None
+    return value
+
+
+def event_matches(conditions: Optional[Dict[str, Any]], payload: Dict[str, Any]) -> bool:
+    """Whether the payload satisfies every condition (compared as text)"""
+    for path, expected in (conditions or {}).items():
+        actual = payload_value(payload, path)
+        if actual is None:
+            return False
+        accepted = expected if isinstance(expected, list) else [expected]
+        if str(actual) not in {str(value) for value in accepted}:
+            return False
+    return True
+
+
+def event_variables(
+    event_type: str, payload: Dict[str, Any], variable_mapping: Optional[Dict[str, str]] = None
+) -> Dict[str, Any]:
+    """Flow variables of an event: the mapped payload values plus the "event" variable"""
+    variables: Dict[str, Any] = {EVENT_VARIABLE: {"type": event_type, **payload}}
+    for name, path in (variable_mapping or {}).items():
+        variables[name] = payload_value(payload, path)
+    return variables
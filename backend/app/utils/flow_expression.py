@@ -0,0 +1,290 @@
+"""
+Expression language of Condition nodes
+
+A small, side-effect free language evaluated against the conversation
+variables (never with eval):
+
+    invoice.amount > 100 && customer.status == 'active'
+    (tentativas + 1) * 2 <= 6 || not cliente_vip
+    email matches '^[^@]+@empresa\\.com$' and tags contains 'vip'
+    $.api_response.items[0].price >= 49.9
+    plano in ['gold', 'platinum']
+
+Operators, loosest first: ``||``/``or``, ``&&``/``and``, ``!``/``not``,
+comparisons (``== != > < >= <= contains matches in``), ``+ -``, ``* / %``
+and unary ``-``. Variables are dotted paths into the context (``a.b[0].c``,
+or JSONPath starting with ``$``); unknown variables are null. Numbers and
+numeric strings compare as numbers, everything else as text.
+"""
+
+import re
+from typing import Any, Dict, List, Optional, Tuple
+
+from app.utils.http_request import json_path
+
+MAX_EXPRESSION_LENGTH = 1000
+MAX_REGEX_LENGTH = 200
+
+
+class ExpressionError(ValueError):
+    """Expression that cannot be parsed or evaluated"""
+
+
+_TOKEN = re.compile(
+    r"""
+    \s*(?:
+        (?P<number>\d+(?:\.\d+)?)
+      | (?P<string>'(?:[^'\\]|\\.)*'|"(?:[^"\\]|\\.)*")
+      | (?P<path>\$(?:\.[A-Za-z_][\w-]*|\[[^\]]*\])*|[A-Za-z_]\w*(?:\.[A-Za-z_]\w*|\[\d+\])*)
+      | (?P<op>&&|\|\||==|!=|>=|<=|[-+*/%()<>!\[\],])
+    )""",
+    re.VERBOSE,
+)
+
+_KEYWORDS = {"and": "&&", "or": "||", "not": "!"}
+_WORD_OPERATORS = {"contains", "matches", "in"}
+_LITERALS = {"true": True, "false": False, "null": None, "none": None}
+_COMPARISONS = {"==", "!=", ">", "<", ">=", "<=", "contains", "matches", "in"}
+
+
+def _tokenize(expression: str) -> List[Tuple[str, Any]]:
+    tokens: List[Tuple[str, Any]] = []
+    position = 0
+    expression = expression.rstrip()
+    while position < len(expression):
+        match = _TOKEN.match(expression, position)
+        if not match:
+            raise ExpressionError(f"Unexpected character at position {position}: {expression[position:position + 10]!r}")
+        position = match.end()
+        kind = match.lastgroup
+        value = match.group(kind)
+        if kind == "number":
+            tokens.append(("value", float(value) if "." in value else int(value)))
+        elif kind == "string":
+            tokens.append(("value", re.sub(r"\\(.)", r"\1", value[1:-1])))
+        elif kind == "path":
+            word = value.lower()
+            if word in _LITERALS:
+                tokens.append(("value", _LITERALS[word]))
+            elif word in _KEYWORDS:
+                tokens.append(("op", _KEYWORDS[word]))
+            elif word in _WORD_OPERATORS:
+                tokens.append(("op", word))
+            else:
+                tokens.append(("path", value))
+        else:
+            tokens.append(("op", value))
+    return tokens
+
+
+class _Parser:
+    """Recursive descent parser producing a tuple tree"""
+
+    def __init__(self, tokens: List[Tuple[str, Any]]):
+        self.tokens = tokens
+        self.position = 0
+
+    def parse(self):
+        if not self.tokens:
+            raise ExpressionError("Empty expression")
+        node = self._or()
+        if self.position < len(self.tokens):
+            raise ExpressionError(f"Unexpected {self.tokens[self.position][1]!r}")
+        return node
+
+    def _peek(self) -> Optional[Tuple[str, Any]]:
+        return self.tokens[self.position] if self.position < len(self.tokens) else None
+
+    def _accept(self, *operators: str) -> Optional[str]:
+        token = self._peek()
+        if token and token[0] == "op" and token[1] in operators:
+            self.position += 1
+            return token[1]
+        return None
+
+    def _expect(self, operator: str) -> None:
+        if not self._accept(operator):
+            raise ExpressionError(f"Expected {operator!r}")
+
+    def _or(self):
+        node = self._and()
+        while self._accept("||"):
+            node = ("||", node, self._and())
+        return node
+
+    def _and(self):
+        node = self._not()
+        while self._accept("&&"):
+            node = ("&&", node, self._not())
+        return node
+
+    def _not(self):
+        if self._accept("!"):
+            return ("!", self._not())
+        return self._comparison()
+
+    def _comparison(self):
+        node = self._additive()
+        operator = self._accept(*_COMPARISONS)
+        if operator:
+            node = (operator, node, self._additive())
+        return node
+
+    def _additive(self):
+        node = self._multiplicative()
+        while True:
+            operator = self._accept("+", "-")
+            if not operator:
+                return node
+            node = (operator, node, self._multiplicative())
+
+    def _multiplicative(self):
+        node = self._unary()
+        while True:
+            operator = self._accept("*", "/", "%")
+            if not operator:
+                return node
+            node = (operator, node, self._unary())
+
+    def _unary(self):
+        if self._accept("-"):
+            return ("neg", self._unary())
+        return self._primary()
+
+    def _primary(self):
+        token = self._peek()
+        if token is None:
+            raise ExpressionError("Unexpected end of expression")
+        self.position += 1
+        kind, value = token
+        if kind == "value":
+            return ("value", value)
+        if kind == "path":
+            return ("path", value)
+        if value == "(":
+            node = self._or()
+            self._expect(")")
+            return node
+        if value == "[":
+            items = []
+            if not self._accept("]"):
+                items.append(self._or())
+                while self._accept(","):
+                    items.append(self._or())
+                self._expect("]")
+            return ("list", items)
+        raise ExpressionError(f"Unexpected {value!r}")
+
+
+def parse_expression(expression: str):
+    """
+    Parse an expression (used to validate it before saving a flow)
+
+    Raises:
+        ExpressionError: If the expression is invalid
+    """
+    if len(expression or "") > MAX_EXPRESSION_LENGTH:
+        raise ExpressionError(f"Expressions are limited to {MAX_EXPRESSION_LENGTH} characters")
+    return _Parser(_tokenize(expression or "")).parse()
+
+
+def evaluate_expression(expression: str, variables: Dict[str, Any]) -> Any:
+    """
+    Value of an expression against the flow variables
+
+    Raises:
+        ExpressionError: If the expression is invalid or cannot be evaluated
+    """
+    return _evaluate(parse_expression(expression), variables)
+
+
+def _number(value: Any) -> Optional[float]:
+    if isinstance(value, bool) or value is None:
+        return None
+    try:
+        return float(value)
+    except (TypeError, ValueError):
+        return None
+
+
+def _arithmetic(operator: str, left: Any, right: Any) -> Any:
+    if operator == "+" and not (_number(left) is not None and _number(right) is not None):
+        return f"{'' if left is None else left}{'' if right is None else right}"
+    a, b = _number(left), _number(right)
+    if a is None or b is None:
+        raise ExpressionError(f"{operator} needs numbers, got {left!r} and {right!r}")
+    if operator in ("/", "%") and b == 0:
+        raise ExpressionError("Division by zero")
+    if operator == "+":
+        result = a + b
+    elif operator == "-":
+        result = a - b
+    elif operator == "*":
+        result = a * b
+    elif operator == "/":
+        result = a / b
+    else:
+        result = a % b
+    return int(result) if result.is_integer() else result
+
+
+def _compare(operator: str, left: Any, right: Any) -> bool:
+    if operator == "contains":
+        if isinstance(left, (list, tuple, dict)):
+            return right in left
+        return left is not None and str(right) in str(left)
+    if operator == "in":
+        return _compare("contains", right, left)
+    if operator == "matches":
+        pattern = str(right or "")
+        if len(pattern) > MAX_REGEX_LENGTH:
+            raise ExpressionError(f"Regular expressions are limited to {MAX_REGEX_LENGTH} characters")
+        try:
+            return left is not None and re.search(pattern, str(left)) is not None
+        except re.error as e:
+            raise ExpressionError(f"Invalid regular expression {pattern!r}: {e}")
+
+    a, b = _number(left), _number(right)
+    if a is not None and b is not None:
+        left, right = a, b
+    elif operator in ("==", "!="):
+        same = left == right if left is None or right is None or isinstance(left, bool) or isinstance(right, bool) \
+            else str(left) == str(right)
+        return same if operator == "==" else not same
+    elif left is None or right is None:
+        return False
+    else:
+        left, right = str(left), str(right)
+
+    return {
+        "==": left == right,
+        "!=": left != right,
+        ">": left > right,
+        "<": left < right,
+        ">=": left >= right,
+        "<=": left <= right,
+    }[operator]
+
+
+def _evaluate(node, variables: Dict[str, Any]) -> Any:
+    kind = node[0]
+    if kind == "value":
+        return node[1]
+    if kind == "path":
+        return json_path(variables, node[1])
+    if kind == "list":
+        return [_evaluate(item, variables) for item in node[1]]
+    if kind == "neg":
+        value = _number(_evaluate(node[1], variables))
+        if value is None:
+            raise ExpressionError("- needs a number")
+        return -value
+    if kind == "!":
+        return not _evaluate(node[1], variables)
+    if kind == "&&":
+        return bool(_evaluate(node[1], variables)) and bool(_evaluate(node[2], variables))
+    if kind == "||":
+        return bool(_evaluate(node[1], variables)) or bool(_evaluate(node[2], variables))
+    if kind in _COMPARISONS:
+        return _compare(kind, _evaluate(node[1], variables), _evaluate(node[2], variables))
+    return _arithmetic(kind, _evaluate(node[1], variables), _evaluate(node[2], variables))
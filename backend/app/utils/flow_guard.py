@@ -0,0 +1,158 @@
+"""
+Per-contact flow guards
+
+Engine-level protection against flows that spam a contact. A chatbot can
+tighten or relax the defaults in its settings:
+
+    "flow_guard": {
+        "max_flow_starts_per_hour": 20,     # flows started (jumps included)
+        "max_flow_starts_per_day": 100,
+        "max_messages_per_hour": 60,        # messages sent by flow nodes
+        "max_messages_per_day": 300,
+        "max_node_visits": 10,              # runs of one node without a contact message
+        "max_cycle_repeats": 5,             # A -> B -> A -> B ... without a contact message
+        "fallback": "handoff",              # or "end"
+        "fallback_message": "Desculpe, ..."
+    }
+
+Rate limits are counted per contact in fixed hour and day windows (Redis).
+The loop guard checks the nodes run since the contact's last message: a
+node repeated too often, or a short cycle of nodes repeating, is a flow
+bouncing between nodes on its own.
+
+A tripped guard stops the flow. Loops send the fallback message once; rate
+limits send nothing else to the contact. The conversation is then handed
+to an agent ("handoff") or the flow just ends ("end").
+"""
+
+from dataclasses import dataclass
+from datetime import datetime
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+FLOW_GUARD_KEY = "flow_guard"
+
+# Context variable: canvas IDs of the nodes run since the contact's last message
+EXECUTION_PATH_KEY = "_execution_path"
+MAX_EXECUTION_PATH = 50
+
+GUARD_FLOW_STARTS = "flow_starts"
+GUARD_MESSAGES = "messages"
+
+# Nodes counted as messages (end nodes are bounded by the flow starts)
+FLOW_GUARD_MESSAGE_NODE_TYPES = (
+    "message",
+    "question",
+    "media_collect",
+    "location_collect",
+    "survey",
+    "interactive_buttons",
+    "interactive_list",
+    "whatsapp_template",
+)
+
+FALLBACK_HANDOFF = "handoff"
+FALLBACK_END = "end"
+
+# Longest cycle (in nodes) the loop guard looks for
+MAX_CYCLE_LENGTH = 4
+
+DEFAULT_FALLBACK_MESSAGE = (
+    "Desculpe, detectamos um problema no fluxo de atendimento. "
+    "Um agente humano irá atendê-lo em breve."
+)
+
+
+@dataclass
+class FlowGuardSettings:
+    """Flow guard settings of a chatbot"""
+
+    max_flow_starts_per_hour: int = 20
+    max_flow_starts_per_day: int = 100
+    max_messages_per_hour: int = 60
+    max_messages_per_day: int = 300
+    max_node_visits: int = 10
+    max_cycle_repeats: int = 5
+    fallback: str = FALLBACK_HANDOFF
+    fallback_message: str = DEFAULT_FALLBACK_MESSAGE
+
+    @classmethod
+    def from_settings(cls, chatbot_settings: Optional[Dict[str, Any]]) -> "FlowGuardSettings":
+        """Parse chatbot.settings; invalid or non-positive limits keep the default"""
+        config = (chatbot_settings or {}).get(FLOW_GUARD_KEY) or {}
+        if not isinstance(config, dict):
+            return cls()
+
+        defaults = cls()
+        limits = {
+            name: _positive(config.get(name), getattr(defaults, name))
+            for name in (
+                "max_flow_starts_per_hour",
+                "max_flow_starts_per_day",
+                "max_messages_per_hour",
+                "max_messages_per_day",
+                "max_node_visits",
+                "max_cycle_repeats",
+            )
+        }
+        return cls(
+            **limits,
+            fallback=config.get("fallback") if config.get("fallback") in (FALLBACK_HANDOFF, FALLBACK_END)
+            else FALLBACK_HANDOFF,
+            fallback_message=config.get("fallback_message") or DEFAULT_FALLBACK_MESSAGE,
+        )
+
+    def limits(self, guard: str) -> Tuple[int, int]:
+        """Hourly and daily limit of a rate guard"""
+        if guard == GUARD_FLOW_STARTS:
+            return self.max_flow_starts_per_hour, self.max_flow_starts_per_day
+        return self.max_messages_per_hour, self.max_messages_per_day
+
+
+def rate_windows(
+    guard: str, contact_id: Any, now: datetime, settings: FlowGuardSettings
+) -> List[Tuple[str, int, int]]:
+    """
+    Redis counters of a rate guard for the contact
+
+    Returns:
+        (key, ttl_seconds, limit) of the current hour and day windows
+    """
+    hourly, daily = settings.limits(guard)
+    prefix = f"flow_guard:{contact_id}:{guard}"
+    return [
+        (f"{prefix}:h:{now:%Y%m%d%H}", 3600, hourly),
+        (f"{prefix}:d:{now:%Y%m%d}", 86400, daily),
+    ]
+
+
+def detect_loop(path: Sequence[str], max_node_visits: int, max_cycle_repeats: int) -> Optional[str]:
+    """
+    Why the nodes run since the contact's last message look like a loop
+
+    Returns:
+        Description of the loop, or None
+    """
+    if not path:
+        return None
+
+    last = path[-1]
+    visits = path.count(last)
+    if visits > max_node_visits:
+        return f"node {last} ran {visits} times without a contact message"
+
+    for length in range(2, MAX_CYCLE_LENGTH + 1):
+        cycle = list(path[-length:])
+        if len(set(cycle)) < length or len(path) < length * max_cycle_repeats:
+            continue
+        window = list(path[-length * max_cycle_repeats:])
+        if window == cycle * max_cycle_repeats:
+            return f"nodes {' -> '.join(cycle)} repeated {max_cycle_repeats} times without a contact message"
+    return None
+
+
+def _positive(value: Any, default: int) -> int:
+    try:
+        value = int(value)
+    except (TypeError, ValueError):
+        return default
+    return value if value > 0 else default
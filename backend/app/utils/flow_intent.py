@@ -0,0 +1,107 @@
+"""
+Intent detection of the Intent node
+
+The contact's free text is classified by the AI provider against the
+node's intents; each intent is an output of the node (sourceHandle = intent
+name) and answers below the confidence threshold leave through "fallback":
+
+    {
+        "intents": [
+            {"name": "segunda_via", "label": "2ª via do boleto", "examples": ["perdi meu boleto"]},
+            {"name": "cancelamento", "label": "Cancelar plano", "description": "Quer encerrar o contrato"},
+            "suporte"
+        ],
+        "confidenceThreshold": 0.6
+    }
+
+When the fallback output is not connected, the node shows a numbered menu
+of the intent labels instead and routes the contact's choice.
+"""
+
+import json
+import re
+import unicodedata
+from typing import Any, Dict, List, Optional, Tuple
+
+INTENT_FALLBACK_HANDLE = "fallback"
+DEFAULT_CONFIDENCE_THRESHOLD = 0.6
+
+_JSON_OBJECT = re.compile(r"\{.*\}", re.DOTALL)
+
+
+def normalize_intents(intents: Any) -> List[Dict[str, Any]]:
+    """Intents as dicts with name and label; plain strings are accepted"""
+    normalized = []
+    for intent in intents or []:
+        if isinstance(intent, str):
+            intent = {"name": intent}
+        name = str(intent.get("name") or "").strip()
+        if not name or name == INTENT_FALLBACK_HANDLE:
+            continue
+        normalized.append({**intent, "name": name, "label": intent.get("label") or name})
+    return normalized
+
+
+def build_intent_prompt(intents: List[Dict[str, Any]], message: str) -> Tuple[str, str]:
+    """System prompt and prompt asking the model for {"intent": ..., "confidence": ...}"""
+    lines = []
+    for intent in intents:
+        line = f"- {intent['name']}: {intent['label']}"
+        if intent.get("description"):
+            line += f" ({intent['description']})"
+        if intent.get("examples"):
+            line += f". Exemplos: {'; '.join(str(example) for example in intent['examples'])}"
+        lines.append(line)
+
+    system_prompt = (
+        "Você classifica mensagens de clientes em uma das intenções abaixo.\n"
+        + "\n".join(lines)
+        + "\nResponda apenas com JSON no formato "
+        '{"intent": "<nome da intenção ou none>", "confidence": <número de 0 a 1>}.'
+    )
+    return system_prompt, f"Mensagem: {message}"
+
+
+def parse_intent_response(response: str, intents: List[Dict[str, Any]]) -> Tuple[Optional[str], float]:
+    """
+    Intent name and confidence from the model answer
+
+    Unknown intents and unparseable answers give (None, 0.0).
+    """
+    match = _JSON_OBJECT.search(response or "")
+    if not match:
+        return None, 0.0
+    try:
+        data = json.loads(match.group(0))
+        confidence = float(data.get("confidence", 0))
+    except (ValueError, TypeError, AttributeError):
+        return None, 0.0
+
+    name = str(data.get("intent") or "").strip()
+    for intent in intents:
+        if intent["name"].lower() == name.lower():
+            return intent["name"], max(0.0, min(confidence, 1.0))
+    return None, 0.0
+
+
+def intent_menu(intents: List[Dict[str, Any]], text: str) -> str:
+    """Numbered menu of the intent labels"""
+    options = "\n".join(f"{index}. {intent['label']}" for index, intent in enumerate(intents, start=1))
+    return f"{text}\n\n{options}"
+
+
+def match_menu_choice(reply: str, intents: List[Dict[str, Any]]) -> Optional[str]:
+    """Intent picked from the menu by number, label or name"""
+    choice = _normalize(reply)
+    if choice.isdigit():
+        index = int(choice) - 1
+        return intents[index]["name"] if 0 <= index < len(intents) else None
+    for intent in intents:
+        if choice in (_normalize(intent["label"]), _normalize(intent["name"])):
+            return intent["name"]
+    return None
+
+
+def _normalize(text: Any) -> str:
+    text = unicodedata.normalize("NFKD", str(text or "")).encode("ascii", "ignore").decode()
+    return text.strip().rstrip(".").lower()
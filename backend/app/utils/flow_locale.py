@@ -0,0 +1,103 @@
+"""
+Multi-language flow content
+
+Nodes can carry translations of their fields keyed by locale; a translation
+only needs the fields it changes (dict fields such as validation are merged):
+
+    {
+        "questionText": "Qual o seu CPF?",
+        "validation": {"errorMessage": "CPF inválido"},
+        "translations": {
+            "es": {"questionText": "¿Cuál es tu DNI?", "validation": {"errorMessage": "DNI inválido"}},
+            "en_US": {"questionText": "What is your tax ID?"}
+        }
+    }
+
+The contact's locale is the "locale" context variable (e.g. set from a
+language menu) or their profile language, detected from their first
+messages. The variant is picked along a fallback chain: the exact locale,
+any variant of its language, the flow's default_locale (same two steps),
+then the node's own fields.
+"""
+
+import re
+from typing import Any, Dict, List, Optional
+
+from app.utils.language_detection import base_language
+
+TRANSLATIONS_KEY = "translations"
+
+# Context variable that overrides the contact's profile language
+LOCALE_VARIABLE = "locale"
+
+
+def normalize_locale(code: Any) -> Optional[str]:
+    """Canonical locale: "pt-br" / "PT_BR" -> "pt_BR", "ES" -> "es"."""
+    if not isinstance(code, str) or not code.strip():
+        return None
+    parts = re.split(r"[_-]", code.strip(), maxsplit=1)
+    language = parts[0].lower()
+    if not language:
+        return None
+    if len(parts) > 1 and parts[1]:
+        return f"{language}_{parts[1].upper()}"
+    return language
+
+
+def locale_chain(contact_locale: Any, default_locale: Any = None) -> List[str]:
+    """
+    Locales to try, in order.
+
+    Example:
+        locale_chain("es-MX", "pt_BR") -> ["es_MX", "es", "pt_BR", "pt"]
+    """
+    chain: List[str] = []
+    for code in (contact_locale, default_locale):
+        locale = normalize_locale(code)
+        for candidate in (locale, base_language(locale)):
+            if candidate and candidate not in chain:
+                chain.append(candidate)
+    return chain
+
+
+def pick_translation(translations: Dict[str, Any], chain: List[str]) -> Optional[str]:
+    """
+    Key of the translation to render, None for the node's own fields.
+
+    A bare language in the chain ("es") also matches regional variants
+    ("es_MX", "es_AR"), first in alphabetical order.
+    """
+    available = {
+        normalize_locale(key): key
+        for key, value in translations.items()
+        if isinstance(value, dict) and normalize_locale(key)
+    }
+    for locale in chain:
+        if locale in available:
+            return available[locale]
+        if "_" not in locale:
+            for normalized in sorted(available):
+                if base_language(normalized) == locale:
+                    return available[normalized]
+    return None
+
+
+def localize_node_data(
+    node_data: Dict[str, Any], contact_locale: Any, default_locale: Any = None
+) -> Dict[str, Any]:
+    """Node data with the best translation merged in (and translations removed)"""
+    translations = node_data.get(TRANSLATIONS_KEY)
+    if not isinstance(translations, dict) or not translations:
+        return node_data
+
+    data = {key: value for key, value in node_data.items() if key != TRANSLATIONS_KEY}
+    key = pick_translation(translations, locale_chain(contact_locale, default_locale))
+    if key is None:
+        return data
+
+    for field, value in translations[key].items():
+        if isinstance(value, dict) and isinstance(data.get(field), dict):
+            data[field] = {**data[field], **value}
+        else:
+            data[field] = value
+    return data
@@ -0,0 +1,126 @@
+"""
+Loop node iteration
+
+A loop node runs its body (the edge with sourceHandle "loop") once per item
+of an array variable, or until its conditions are met, then follows the edge
+with sourceHandle "done". The last node of the body links back to the loop
+node:
+
+    {
+        "loopType": "array",             # "array" (default) or "condition"
+        "arrayVariable": "api_response.faturas",
+        "itemVariable": "fatura",        # default "item"
+        "indexVariable": "fatura_numero",  # position of the item: 1, 2, ... (default "loop_index")
+        "maxIterations": 5,              # default 10, capped at MAX_LOOP_ITERATIONS
+        # loopType "condition": repeat until the conditions are met
+        "conditions": [{"variable": "opcao", "operator": "==", "value": "sair"}],
+        "logicOperator": "AND"
+    }
+
+Every iteration binds the item and its position; dict items also bind their
+scalar fields as ``<itemVariable>_<field>`` so messages can use
+{{fatura_valor}}. The position is kept in variables["_loops"][node_id] and
+dropped when the loop is done.
+
+Shared by the WhatsApp engine and the flow console.
+"""
+
+from typing import Any, Dict, List, Optional, Tuple
+
+LOOP_STATE_KEY = "_loops"
+LOOP_BODY_HANDLE = "loop"
+LOOP_DONE_HANDLE = "done"
+
+DEFAULT_LOOP_ITERATIONS = 10
+MAX_LOOP_ITERATIONS = 100
+
+
+def max_iterations(node_data: Dict[str, Any]) -> int:
+    """Iterations allowed by the node, between 1 and MAX_LOOP_ITERATIONS"""
+    try:
+        value = int(node_data.get("maxIterations") or DEFAULT_LOOP_ITERATIONS)
+    except (TypeError, ValueError):
+        value = DEFAULT_LOOP_ITERATIONS
+    return min(max(value, 1), MAX_LOOP_ITERATIONS)
+
+
+def loop_items(node_data: Dict[str, Any], variables: Dict[str, Any]) -> List[Any]:
+    """Items of arrayVariable (dotted paths reach into dicts and lists); empty if it is not an array"""
+    value: Any = variables
+    for part in str(node_data.get("arrayVariable") or "").split("."):
+        if isinstance(value, dict):
+            value = value.get(part)
+        elif isinstance(value, list) and part.isdigit() and int(part) < len(value):
+            value = value[int(part)]
+        else:
+            return []
+    return value if isinstance(value, list) else []
+
+
+def next_iteration(
+    node_id: str,
+    node_data: Dict[str, Any],
+    variables: Dict[str, Any],
+    condition_met: Optional[bool] = None,
+) -> Tuple[bool, str]:
+    """
+    Move a loop node to its next iteration
+
+    Updates ``variables`` in place: binds the item and position of the new
+    iteration, or drops the loop position when the loop is done.
+
+    Args:
+        node_id: Canvas id of the loop node
+        node_data: Loop node data
+        variables: Flow variables (conversation context or console session)
+        condition_met: Result of the node's conditions ("condition" loops)
+
+    Returns:
+        (whether to run the body, description of the iteration)
+    """
+    loops = dict(variables.get(LOOP_STATE_KEY) or {})
+    state = dict(loops.get(node_id) or {"index": 0})
+    index = state["index"]
+    limit = max_iterations(node_data)
+
+    if node_data.get("loopType") == "condition":
+        items = None
+        if condition_met:
+            done = f"Conditions met after {index} iteration(s)"
+        elif index >= limit:
+            done = f"Max iterations ({limit}) reached before the conditions were met"
+        else:
+            done = None
+    else:
+        items = loop_items(node_data, variables)
+        if index >= len(items):
+            done = f"Done after {index} of {len(items)} item(s)"
+        elif index >= limit:
+            done = f"Max iterations ({limit}) reached, {len(items) - index} item(s) left"
+        else:
+            done = None
+
+    if done:
+        loops.pop(node_id, None)
+        if loops:
+            variables[LOOP_STATE_KEY] = loops
+        else:
+            variables.pop(LOOP_STATE_KEY, None)
+        return False, done
+
+    variables[node_data.get("indexVariable") or "loop_index"] = index + 1
+    if items is not None:
+        item_variable = node_data.get("itemVariable") or "item"
+        item = items[index]
+        variables[item_variable] = item
+        if isinstance(item, dict):
+            for key, value in item.items():
+                if not isinstance(value, (dict, list)):
+                    variables[f"{item_variable}_{key}"] = value
+
+    state["index"] = index + 1
+    loops[node_id] = state
+    variables[LOOP_STATE_KEY] = loops
+
+    total = f"/{min(len(items), limit)}" if items is not None else f" (max {limit})"
+    return True, f"Iteration {index + 1}{total}"
@@ -0,0 +1,81 @@
+"""
+Live session migration on publish
+
+Publishing a flow creates new node copies, so sessions already running keep
+pointing at the nodes of the version they started on. The publish request
+chooses what happens to them:
+
+    continue               sessions finish on the version they started on
+    map_node_ids           sessions move to the node with the same canvas ID
+                           in the new version ("node_map" renames old IDs:
+                           {"pergunta_cpf": "pergunta_documento"}); sessions
+                           on a node missing from the new version continue
+                           on their version
+    restart_at_checkpoint  sessions move to "checkpoint_node_id" (the start
+                           node when empty) of the new version, which runs
+                           again when the contact sends the next message
+
+Sessions of flows that were never published run the draft, whose nodes are
+replaced when the canvas is saved, so they are migrated the same way.
+"""
+
+from typing import Dict, List, Optional
+
+MIGRATION_CONTINUE = "continue"
+MIGRATION_MAP_NODE_IDS = "map_node_ids"
+MIGRATION_RESTART_AT_CHECKPOINT = "restart_at_checkpoint"
+
+MIGRATION_STRATEGIES = (MIGRATION_CONTINUE, MIGRATION_MAP_NODE_IDS, MIGRATION_RESTART_AT_CHECKPOINT)
+
+# Context variable: the current node runs on the next contact message instead of
+# receiving it as an answer (also set by flows opened with an event template)
+FLOW_RESTART_KEY = "_flow_restart"
+
+
+def validate_migration(
+    strategy: str,
+    node_map: Dict[str, str],
+    checkpoint_node_id: Optional[str],
+    new_node_ids: List[str],
+) -> List[str]:
+    """
+    Problems of a migration against the node IDs of the new version
+
+    Returns:
+        Error messages (empty when the migration can run)
+    """
+    if strategy not in MIGRATION_STRATEGIES:
+        return [f"Unknown migration strategy: {strategy}"]
+
+    available = set(new_node_ids)
+    errors = []
+    if strategy == MIGRATION_MAP_NODE_IDS:
+        for old_id, new_id in (node_map or {}).items():
+            if new_id not in available:
+                errors.append(f"node_map target '{new_id}' (from '{old_id}') is not in the new version")
+    if strategy == MIGRATION_RESTART_AT_CHECKPOINT and checkpoint_node_id and checkpoint_node_id not in available:
+        errors.append(f"Checkpoint node '{checkpoint_node_id}' is not in the new version")
+    return errors
+
+
+def migration_target(
+    strategy: str,
+    node_id: str,
+    new_node_ids: List[str],
+    start_node_id: str,
+    node_map: Optional[Dict[str, str]] = None,
+    checkpoint_node_id: Optional[str] = None,
+) -> Optional[str]:
+    """
+    Canvas ID of the node a session on node_id moves to in the new version
+
+    Returns:
+        The node ID, or None when the session continues on its version
+    """
+    if strategy == MIGRATION_MAP_NODE_IDS:
+        target = (node_map or {}).get(node_id, node_id)
+        return target if target in set(new_node_ids) else None
+    if strategy == MIGRATION_RESTART_AT_CHECKPOINT:
+        return checkpoint_node_id or start_node_id
+    return None
+
@@ -0,0 +1,134 @@
+"""
+Back navigation in flows
+
+Contacts can go back to the previous question instead of restarting the
+menu. A chatbot turns it on in its settings:
+
+    "navigation": {
+        "enabled": true,
+        "keywords": ["voltar", "anterior"],     # answers that go back (case and accents ignored)
+        "back_button": true,                    # Official API: questions carry a "Voltar" reply button
+        "back_button_title": "⬅️ Voltar",
+        "no_history_message": "Você já está no início."
+    }
+
+Every answered question pushes an entry on the session's navigation history
+(context variable "_navigation_history") with the contact's variables as they
+were when the question was asked. Going back pops the last entry, restores
+those variables (answers given after it are discarded) and asks that question
+again. The history belongs to one flow run: it is cleared when a flow starts
+and never crosses into another flow. A question with "allowBack": false
+(e.g. after a payment) does not go back.
+"""
+
+import unicodedata
+from dataclasses import dataclass, field
+from typing import Any, Dict, List, Optional, Tuple
+
+NAVIGATION_KEY = "navigation"
+
+# Context variable: answered questions of the current flow run, oldest first
+NAVIGATION_HISTORY_KEY = "_navigation_history"
+MAX_NAVIGATION_HISTORY = 20
+
+# Reply button id of the back button (inbound interactive.button_reply.id)
+BACK_BUTTON_ID = "__flow_back__"
+
+DEFAULT_BACK_KEYWORDS = ("voltar", "anterior")
+DEFAULT_BACK_BUTTON_TITLE = "⬅️ Voltar"
+DEFAULT_NO_HISTORY_MESSAGE = "Você já está no início, não há pergunta anterior."
+
+
+@dataclass
+class NavigationSettings:
+    """Back navigation settings of a chatbot"""
+
+    enabled: bool = False
+    keywords: List[str] = field(default_factory=lambda: list(DEFAULT_BACK_KEYWORDS))
+    back_button: bool = False
+    back_button_title: str = DEFAULT_BACK_BUTTON_TITLE
+    no_history_message: str = DEFAULT_NO_HISTORY_MESSAGE
+
+    @classmethod
+    def from_settings(cls, chatbot_settings: Optional[Dict[str, Any]]) -> "NavigationSettings":
+        """Parse chatbot.settings; navigation is off unless enabled"""
+        config = (chatbot_settings or {}).get(NAVIGATION_KEY) or {}
+        if not isinstance(config, dict):
+            return cls()
+
+        keywords = config.get("keywords")
+        if not isinstance(keywords, list) or not keywords:
+            keywords = list(DEFAULT_BACK_KEYWORDS)
+        return cls(
+            enabled=bool(config.get("enabled")),
+            keywords=[normalize_keyword(keyword) for keyword in keywords if normalize_keyword(keyword)],
+            back_button=bool(config.get("back_button")),
+            back_button_title=config.get("back_button_title") or DEFAULT_BACK_BUTTON_TITLE,
+            no_history_message=config.get("no_history_message") or DEFAULT_NO_HISTORY_MESSAGE,
+        )
+
+
+def normalize_keyword(text: Any) -> str:
+    """Lowercase text without accents, emoji or surrounding punctuation"""
+    decomposed = unicodedata.normalize("NFKD", str(text or "").casefold())
+    kept = "".join(char for char in decomposed if char.isalnum() or char.isspace())
+    return " ".join(kept.split())
+
+
+def is_back_request(content: Optional[Dict[str, Any]], settings: NavigationSettings) -> bool:
+    """Whether an inbound message (content) asks to go back: the back button or a keyword"""
+    if not settings.enabled or not content:
+        return False
+
+    reply = (content.get("interactive") or {}).get("button_reply") or {}
+    if reply.get("id") == BACK_BUTTON_ID:
+        return True
+
+    text = normalize_keyword(content.get("text"))
+    if not text:
+        return False
+    return text in settings.keywords or (settings.back_button and text == normalize_keyword(settings.back_button_title))
+
+
+def push_history(
+    context_vars: Dict[str, Any], node_id: str, canvas_node_id: str, flow_id: Any
+) -> List[Dict[str, Any]]:
+    """
+    Navigation history with the question the contact just answered
+
+    context_vars are the variables before the answer is saved; only the
+    contact's variables (no "_" internals) are kept in the entry.
+    """
+    entry = {
+        "node_id": str(node_id),
+        "canvas_node_id": canvas_node_id,
+        "flow_id": str(flow_id),
+        "variables": {name: value for name, value in context_vars.items() if not name.startswith("_")},
+    }
+    history = list(context_vars.get(NAVIGATION_HISTORY_KEY) or []) + [entry]
+    return history[-MAX_NAVIGATION_HISTORY:]
+
+
+def pop_history(context_vars: Dict[str, Any], flow_id: Any) -> Tuple[Optional[Dict[str, Any]], Dict[str, Any]]:
+    """
+    Go back one question in the flow run
+
+    Returns:
+        (entry to return to or None when there is no previous question in
+        this flow, context variables restored to that question)
+    """
+    history = list(context_vars.get(NAVIGATION_HISTORY_KEY) or [])
+    if not history or history[-1].get("flow_id") != str(flow_id):
+        return None, context_vars
+
+    entry = history.pop()
+    canvas_node_id = entry.get("canvas_node_id")
+    restored = {
+        name: value
+        for name, value in context_vars.items()
+        if name.startswith("_")
+        and name not in (f"_attempts_{canvas_node_id}", f"_question_timestamp_{canvas_node_id}")
+    }
+    restored.update(entry.get("variables") or {})
+    restored[NAVIGATION_HISTORY_KEY] = history
+    return entry, restored
@@ -0,0 +1,83 @@
+"""
+A/B split node
+
+Routes a share of the sessions down each of its outputs:
+
+    {
+        "nodeType": "split",
+        "experimentKey": "oferta-novembro",   # optional, defaults to flow and node ID
+        "variants": [
+            {"id": "a", "label": "Controle", "percentage": 50},
+            {"id": "b", "label": "Desconto", "percentage": 50}
+        ],
+        "saveToVariable": "oferta_variant"    # optional
+    }
+
+Each variant is an output of the node (edge sourceHandle = variant id). The
+variant is drawn by hashing the experiment key with the contact, so a
+contact always gets the same branch, in every session; percentages are
+relative when they do not add up to 100. The variant is recorded on the
+session's visit to the node, which the flow funnel uses to compare the
+conversion of the branches.
+"""
+
+import hashlib
+from typing import Any, Dict, List, Optional
+
+SPLIT_NODE_TYPE = "split"
+
+
+def split_variants(node_data: Dict[str, Any]) -> List[Dict[str, Any]]:
+    """
+    Variants of a split node as {id, label, percentage}
+
+    Entries without an ID are dropped; when no variant sets a percentage the
+    traffic is split evenly.
+    """
+    variants = []
+    for variant in node_data.get("variants") or []:
+        if not isinstance(variant, dict) or variant.get("id") in (None, ""):
+            continue
+        try:
+            percentage = max(float(variant.get("percentage")), 0.0)
+        except (TypeError, ValueError):
+            percentage = None
+        variants.append({
+            "id": str(variant["id"]),
+            "label": variant.get("label") or str(variant["id"]),
+            "percentage": percentage,
+        })
+
+    if variants and all(variant["percentage"] is None for variant in variants):
+        for variant in variants:
+            variant["percentage"] = 100 / len(variants)
+    for variant in variants:
+        variant["percentage"] = variant["percentage"] or 0.0
+    return variants
+
+
+def experiment_key(node_data: Dict[str, Any], flow_id: Any, node_id: str) -> str:
+    """Salt of the assignment; sharing a key across nodes keeps contacts in the same variant"""
+    return str(node_data.get("experimentKey") or f"{flow_id}:{node_id}")
+
+
+def assign_variant(variants: List[Dict[str, Any]], key: str, subject: Any) -> Optional[Dict[str, Any]]:
+    """
+    Variant of a subject (contact), stable for the same key and percentages
+
+    Returns:
+        The variant, or None when no variant gets traffic
+    """
+    total = sum(variant["percentage"] for variant in variants)
+    if total <= 0:
+        return None
+
+    digest = hashlib.sha256(f"{key}:{subject}".encode("utf-8")).hexdigest()
+    point = int(digest[:15], 16) / 16 ** 15 * total
+
+    cumulative = 0.0
+    for variant in variants:
+        cumulative += variant["percentage"]
+        if point < cumulative and variant["percentage"] > 0:
+            return variant
+    return [variant for variant in variants if variant["percentage"] > 0][-1]
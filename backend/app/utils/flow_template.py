@@ -0,0 +1,193 @@
+"""
+Variable interpolation of flow messages
+
+Renders ``{{ path | filter:arg }}`` placeholders with the conversation
+variables:
+
+    Olá {{ contato.nome | title }}!
+    Sua fatura de {{ fatura.valor | currency }} vence em {{ fatura.vencimento | date }}.
+    Plano: {{ plano | default:'Básico' | upper }}
+    {{ descricao | truncate:40 }}
+
+Paths are dotted and may index lists (``pedido.itens[0].nome``). Missing
+variables render as an empty string (use ``default`` to show something
+else) instead of leaking the placeholder to the contact. ``{{org.*}}``
+placeholders belong to the organization secrets/vars resolver and are kept.
+
+Filters: upper, lower, title, capitalize, trim, default:value,
+truncate:length, date:format (default dd/mm/yyyy), currency:code (default
+BRL, e.g. R$ 1.234,56), number:decimals (1.234,56) and join:separator.
+"""
+
+import logging
+import re
+from datetime import date, datetime, timezone
+from typing import Any, Callable, Dict, List
+
+from app.utils.http_request import json_path
+
+logger = logging.getLogger(__name__)
+
+_PLACEHOLDER = re.compile(r"\{\{\s*([^{}]+?)\s*\}\}")
+_ARGUMENT = re.compile(r"""'((?:[^'\\]|\\.)*)'|"((?:[^"\\]|\\.)*)"|([^:]+)""")
+
+CURRENCY_SYMBOLS = {"BRL": "R$", "USD": "US$", "EUR": "€"}
+
+
+def render_text(text: str, variables: Dict[str, Any]) -> str:
+    """Text with every {{ placeholder }} rendered"""
+    if not text or "{{" not in text:
+        return text or ""
+    return _PLACEHOLDER.sub(lambda match: _render_placeholder(match, variables), text)
+
+
+def _render_placeholder(match: "re.Match", variables: Dict[str, Any]) -> str:
+    parts = _split_filters(match.group(1))
+    path = parts[0].strip()
+    if path == "org" or path.startswith("org."):
+        return match.group(0)
+
+    try:
+        value = json_path(variables, path)
+    except ValueError:
+        logger.warning(f"⚠️ Invalid variable path in template: {path}")
+        value = None
+
+    for expression in parts[1:]:
+        name, arguments = _parse_filter(expression)
+        apply = FILTERS.get(name)
+        if apply is None:
+            logger.warning(f"⚠️ Unknown template filter: {name}")
+            continue
+        try:
+            value = apply(value, *arguments)
+        except (TypeError, ValueError) as e:
+            logger.warning(f"⚠️ Template filter {name} failed on {value!r}: {e}")
+
+    return "" if value is None else _to_text(value)
+
+
+def _split_filters(expression: str) -> List[str]:
+    """Split on | outside of quotes"""
+    parts, current, quote = [], "", None
+    for char in expression:
+        if quote:
+            if char == quote:
+                quote = None
+        elif char in ("'", '"'):
+            quote = char
+        elif char == "|":
+            parts.append(current)
+            current = ""
+            continue
+        current += char
+    parts.append(current)
+    return parts
+
+
+def _parse_filter(expression: str):
+    name, _, raw_arguments = expression.strip().partition(":")
+    arguments = []
+    position = 0
+    while raw_arguments and position < len(raw_arguments):
+        match = _ARGUMENT.match(raw_arguments, position)
+        if not match:
+            break
+        quoted = match.group(1) if match.group(1) is not None else match.group(2)
+        arguments.append(re.sub(r"\\(.)", r"\1", quoted) if quoted is not None else match.group(3).strip())
+        position = match.end() + 1  # skip the ':' separator
+    return name.strip().lower(), arguments
+
+
+def _to_text(value: Any) -> str:
+    if isinstance(value, float) and value.is_integer():
+        return str(int(value))
+    return str(value)
+
+
+def _to_number(value: Any) -> float:
+    if isinstance(value, (int, float)) and not isinstance(value, bool):
+        return float(value)
+    text = str(value).strip()
+    # 1.234,56 (pt-BR) -> 1234.56
+    if "," in text and re.fullmatch(r"-?[\d.]*,\d+", text):
+        text = text.replace(".", "").replace(",", ".")
+    return float(text)
+
+
+def _format_number(value: float, decimals: int) -> str:
+    """1234.5 -> 1.234,50"""
+    formatted = f"{value:,.{decimals}f}"
+    return formatted.replace(",", "_").replace(".", ",").replace("_", ".")
+
+
+def _to_datetime(value: Any) -> Any:
+    if isinstance(value, (datetime, date)):
+        return value
+    if isinstance(value, (int, float)) and not isinstance(value, bool):
+        # Unix timestamp (seconds or milliseconds)
+        return datetime.fromtimestamp(value / 1000 if value > 1e11 else value, tz=timezone.utc)
+    text = str(value).strip()
+    try:
+        return datetime.fromisoformat(text.replace("Z", "+00:00"))
+    except ValueError:
+        return datetime.strptime(text, "%d/%m/%Y")
+
+
+def _default(value: Any, fallback: str = "") -> Any:
+    return fallback if value is None or value == "" or value == [] else value
+
+
+def _truncate(value: Any, length: str = "50") -> Any:
+    if value is None:
+        return None
+    text, limit = _to_text(value), int(length)
+    return text if len(text) <= limit else text[: max(limit - 1, 0)].rstrip() + "…"
+
+
+def _date(value: Any, date_format: str = "%d/%m/%Y") -> Any:
+    if value is None or value == "":
+        return value
+    return _to_datetime(value).strftime(date_format)
+
+
+def _currency(value: Any, code: str = "BRL") -> Any:
+    if value is None or value == "":
+        return value
+    code = code.upper()
+    symbol = CURRENCY_SYMBOLS.get(code, code)
+    amount = _to_number(value)
+    if code == "BRL":
+        return f"{'-' if amount < 0 else ''}{symbol} {_format_number(abs(amount), 2)}"
+    return f"{'-' if amount < 0 else ''}{symbol} {abs(amount):,.2f}"
+
+
+def _number(value: Any, decimals: str = "2") -> Any:
+    if value is None or value == "":
+        return value
+    return _format_number(_to_number(value), int(decimals))
+
+
+def _join(value: Any, separator: str = ", ") -> Any:
+    if isinstance(value, (list, tuple)):
+        return separator.join(_to_text(item) for item in value)
+    return value
+
+
+def _text_filter(method: str) -> Callable[[Any], Any]:
+    return lambda value: value if value is None else getattr(_to_text(value), method)()
+
+
+FILTERS: Dict[str, Callable[..., Any]] = {
+    "upper": _text_filter("upper"),
+    "lower": _text_filter("lower"),
+    "title": _text_filter("title"),
+    "capitalize": _text_filter("capitalize"),
+    "trim": _text_filter("strip"),
+    "default": _default,
+    "truncate": _truncate,
+    "date": _date,
+    "currency": _currency,
+    "number": _number,
+    "join": _join,
+}
@@ -0,0 +1,198 @@
+"""
+Parameterized flow templates
+
+Gallery templates (app/templates/flows) declare the parameters a tenant fills
+in when instantiating them, and use them anywhere in flow_data as
+[[parameter]] placeholders:
+
+    "parameters": [
+        {"name": "company_name", "label": "Nome da empresa", "type": "text", "required": true},
+        {"name": "plans", "label": "Planos", "type": "list", "default": ["Básico", "Pro"]},
+        {"name": "erp_provider", "label": "ERP", "type": "choice", "options": ["bling", "tiny", "omie"]}
+    ]
+
+A placeholder inside a longer string is replaced by the value as text (lists
+joined with ", "); a string that is only a placeholder becomes the value
+itself, so "[[plans]]" is a real list. Runtime variables ({{var}}) are left
+untouched.
+
+metadata.version ("1.2") is the template version. Flows created from a
+template keep their origin (template, version, parameters and a fingerprint
+of the generated canvas) so they can be upgraded to a newer version with the
+same parameters, as long as the draft was not edited since.
+"""
+
+import hashlib
+import json
+import re
+from typing import Any, Dict, List, Optional, Tuple
+
+PARAMETER_TYPES = ("text", "number", "boolean", "choice", "list")
+
+PLACEHOLDER_PATTERN = re.compile(r"\[\[\s*([a-zA-Z_][a-zA-Z0-9_]*)\s*\]\]")
+
+# Node and edge keys that only change the layout of the canvas
+LAYOUT_KEYS = ("position", "positionAbsolute", "selected", "dragging", "width", "height")
+
+
+def validate_template(template_data: Dict[str, Any]) -> List[str]:
+    """
+    Problems of a template's parameter declarations
+
+    Returns:
+        Error messages (empty when the template can be instantiated)
+    """
+    errors = []
+    declared = set()
+    for parameter in template_data.get("parameters") or []:
+        name = parameter.get("name") if isinstance(parameter, dict) else None
+        if not name or not re.fullmatch(r"[a-zA-Z_][a-zA-Z0-9_]*", name):
+            errors.append(f"Invalid parameter name {name!r}")
+            continue
+        if name in declared:
+            errors.append(f"Parameter '{name}' is declared twice")
+        declared.add(name)
+        if parameter.get("type", "text") not in PARAMETER_TYPES:
+            errors.append(f"Parameter '{name}' has unknown type '{parameter.get('type')}'")
+        if parameter.get("type") == "choice" and not parameter.get("options"):
+            errors.append(f"Parameter '{name}' is a choice without options")
+
+    for name in sorted(template_placeholders(template_data.get("flow_data") or {}) - declared):
+        errors.append(f"Placeholder [[{name}]] is not a declared parameter")
+    return errors
+
+
+def template_placeholders(value: Any) -> set:
+    """Parameter names used as placeholders anywhere in value"""
+    if isinstance(value, str):
+        return set(PLACEHOLDER_PATTERN.findall(value))
+    if isinstance(value, dict):
+        return set().union(*(template_placeholders(item) for item in value.values()))
+    if isinstance(value, list):
+        return set().union(*(template_placeholders(item) for item in value))
+    return set()
+
+
+def resolve_parameters(
+    parameters: List[Dict[str, Any]], values: Optional[Dict[str, Any]]
+) -> Tuple[Dict[str, Any], List[str]]:
+    """
+    Parameter values for an instantiation: given values, then defaults
+
+    Returns:
+        (values by parameter name, error messages)
+    """
+    values = values or {}
+    resolved: Dict[str, Any] = {}
+    errors = []
+    declared = {parameter["name"] for parameter in parameters}
+
+    for name in sorted(set(values) - declared):
+        errors.append(f"Unknown parameter '{name}'")
+
+    for parameter in parameters:
+        name = parameter["name"]
+        value = values.get(name, parameter.get("default"))
+        if value is None or value == "" or value == []:
+            if parameter.get("required"):
+                errors.append(f"Parameter '{name}' is required")
+            resolved[name] = _empty(parameter.get("type", "text"))
+            continue
+        value, error = _coerce(parameter, value)
+        if error:
+            errors.append(error)
+        resolved[name] = value
+    return resolved, errors
+
+
+def instantiate(value: Any, parameters: Dict[str, Any]) -> Any:
+    """Copy of value (flow_data) with the placeholders replaced by the parameter values"""
+    if isinstance(value, str):
+        whole = PLACEHOLDER_PATTERN.fullmatch(value.strip())
+        if whole and whole.group(1) in parameters:
+            return parameters[whole.group(1)]
+        return PLACEHOLDER_PATTERN.sub(
+            lambda match: _as_text(parameters[match.group(1)]) if match.group(1) in parameters else match.group(0),
+            value,
+        )
+    if isinstance(value, dict):
+        return {key: instantiate(item, parameters) for key, item in value.items()}
+    if isinstance(value, list):
+        return [instantiate(item, parameters) for item in value]
+    return value
+
+
+def canvas_fingerprint(canvas_data: Dict[str, Any]) -> str:
+    """Hash of a canvas' nodes and edges, ignoring the layout"""
+    content = {
+        key: [
+            {field: item_value for field, item_value in item.items() if field not in LAYOUT_KEYS}
+            for item in canvas_data.get(key) or []
+            if isinstance(item, dict)
+        ]
+        for key in ("nodes", "edges")
+    }
+    return hashlib.sha256(json.dumps(content, sort_keys=True, default=str).encode()).hexdigest()
+
+
+def parse_version(version: Any) -> Tuple[int, ...]:
+    """Comparable form of a template version ("1.10" > "1.9"); invalid parts count as 0"""
+    parts = []
+    for part in str(version or "0").split("."):
+        try:
+            parts.append(int(part))
+        except ValueError:
+            parts.append(0)
+    return tuple(parts)
+
+
+def is_newer_version(candidate: Any, current: Any) -> bool:
+    """Whether candidate is a later template version than current"""
+    return parse_version(candidate) > parse_version(current)
+
+
+def _coerce(parameter: Dict[str, Any], value: Any) -> Tuple[Any, Optional[str]]:
+    name = parameter["name"]
+    parameter_type = parameter.get("type", "text")
+
+    if parameter_type == "number":
+        try:
+            number = float(value)
+        except (TypeError, ValueError):
+            return value, f"Parameter '{name}' must be a number"
+        return (int(number) if number.is_integer() else number), None
+    if parameter_type == "boolean":
+        if isinstance(value, bool):
+            return value, None
+        if str(value).lower() in ("true", "false"):
+            return str(value).lower() == "true", None
+        return value, f"Parameter '{name}' must be true or false"
+    if parameter_type == "list":
+        if isinstance(value, str):
+            value = [item.strip() for item in value.split(",") if item.strip()]
+        if not isinstance(value, list):
+            return value, f"Parameter '{name}' must be a list"
+        return [str(item) for item in value], None
+    if parameter_type == "choice" and value not in (parameter.get("options") or []):
+        return value, f"Parameter '{name}' must be one of: {', '.join(map(str, parameter.get('options') or []))}"
+
+    max_length = parameter.get("max_length")
+    if max_length and len(str(value)) > max_length:
+        return value, f"Parameter '{name}' is longer than {max_length} characters"
+    return str(value), None
+
+
+def _empty(parameter_type: str) -> Any:
+    if parameter_type == "list":
+        return []
+    if parameter_type == "boolean":
+        return False
+    return None if parameter_type == "number" else ""
+
+
+def _as_text(value: Any) -> str:
+    if isinstance(value, list):
+        return ", ".join(str(item) for item in value)
+    if isinstance(value, bool):
+        return "sim" if value else "não"
+    return "" if value is None else str(value)
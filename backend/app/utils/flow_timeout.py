@@ -0,0 +1,92 @@
+"""
+Flow session timeouts
+
+A chatbot can limit how long a flow session lasts and how long it waits for
+the contact, in its settings:
+
+    "session_timeout": {
+        "timeout_minutes": 30,           # no input from the contact for this long
+        "max_duration_minutes": 1440,    # session expires this long after it started
+        "fallback_node_id": "node-abc",  # node of the running flow to execute, or
+        "fallback_flow_id": "uuid",      # flow of the chatbot to start
+        "timeout_message": "Encerrando o atendimento por inatividade.",
+        "close_conversation": true
+    }
+
+The deadlines are stored on the conversation (flow_timeout_at and
+flow_expires_at) and enforced by the expire_flow_sessions task. The first
+timeout runs the fallback (e.g. a "still there?" question); if the session
+times out again before the contact answers, or there is no fallback, the
+timeout message is sent and the flow finishes.
+"""
+
+from dataclasses import dataclass
+from datetime import datetime, timedelta
+from typing import Any, Dict, Optional
+
+SESSION_TIMEOUT_KEY = "session_timeout"
+
+# Set in context_variables once the fallback ran; the next timeout finishes the flow
+FLOW_TIMEOUT_FIRED_KEY = "_flow_timeout_fired"
+
+# How long the fallback waits for an answer when timeout_minutes is not set
+FALLBACK_GRACE_MINUTES = 15
+
+
+@dataclass
+class FlowTimeoutSettings:
+    """Session timeout settings of a chatbot"""
+
+    timeout_minutes: Optional[int] = None
+    max_duration_minutes: Optional[int] = None
+    fallback_node_id: Optional[str] = None
+    fallback_flow_id: Optional[str] = None
+    timeout_message: Optional[str] = None
+    close_conversation: bool = False
+
+    @classmethod
+    def from_settings(cls, chatbot_settings: Optional[Dict[str, Any]]) -> "FlowTimeoutSettings":
+        """Parse chatbot.settings; invalid or non-positive limits are ignored"""
+        config = (chatbot_settings or {}).get(SESSION_TIMEOUT_KEY) or {}
+        if not isinstance(config, dict):
+            return cls()
+        return cls(
+            timeout_minutes=_minutes(config.get("timeout_minutes")),
+            max_duration_minutes=_minutes(config.get("max_duration_minutes")),
+            fallback_node_id=config.get("fallback_node_id") or None,
+            fallback_flow_id=config.get("fallback_flow_id") or None,
+            timeout_message=config.get("timeout_message") or None,
+            close_conversation=bool(config.get("close_conversation", False)),
+        )
+
+    @property
+    def enabled(self) -> bool:
+        return bool(self.timeout_minutes or self.max_duration_minutes)
+
+    @property
+    def has_fallback(self) -> bool:
+        return bool(self.fallback_node_id or self.fallback_flow_id)
+
+    def timeout_at(self, last_input_at: datetime) -> Optional[datetime]:
+        """When the session times out without further input"""
+        if not self.timeout_minutes:
+            return None
+        return last_input_at + timedelta(minutes=self.timeout_minutes)
+
+    def expires_at(self, started_at: datetime) -> Optional[datetime]:
+        """When a session started at started_at expires"""
+        if not self.max_duration_minutes:
+            return None
+        return started_at + timedelta(minutes=self.max_duration_minutes)
+
+    def fallback_timeout_at(self, now: datetime) -> datetime:
+        """How long the fallback waits for the contact before the flow finishes"""
+        return now + timedelta(minutes=self.timeout_minutes or FALLBACK_GRACE_MINUTES)
+
+
+def _minutes(value: Any) -> Optional[int]:
+    try:
+        minutes = int(value)
+    except (TypeError, ValueError):
+        return None
+    return minutes if minutes > 0 else None
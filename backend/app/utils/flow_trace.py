@@ -0,0 +1,103 @@
+"""
+Execution trace of flow sessions
+
+Every session (one run of a flow for a conversation, see FlowNodeVisit)
+gets an append-only log in flow_trace_events, returned by
+GET /chatbots/flows/sessions/{session_id}/trace to debug complaints about
+what the bot did:
+
+- node_executed: a node ran ({"resume": true} when resumed after a crash)
+- input_received: the contact's message the session processed
+- message_sent: a message the bot sent while running a node
+- variable_set: a context variable changed ({"name", "old", "new"}), on
+  the node that changed it
+
+Internal state kept in context variables ("_" prefix: execution path,
+sub-flow stack, loops...) is not logged. Values are truncated to
+MAX_VALUE_LENGTH characters.
+"""
+
+import json
+from typing import Any, Dict, List, Optional, Sequence
+
+EVENT_NODE_EXECUTED = "node_executed"
+EVENT_INPUT_RECEIVED = "input_received"
+EVENT_MESSAGE_SENT = "message_sent"
+EVENT_VARIABLE_SET = "variable_set"
+
+MAX_VALUE_LENGTH = 1000
+
+# Client arguments holding the text of a sent message
+_TEXT_ARGUMENTS = ("text", "body", "body_text", "caption", "question")
+
+
+def trace_event(event_type: str, node: Any = None, data: Optional[Dict[str, Any]] = None) -> Dict[str, Any]:
+    """Event to append to a session's trace (node is the Node involved, if any)"""
+    return {
+        "event_type": event_type,
+        "node_id": getattr(node, "node_id", None),
+        "node_type": getattr(node, "node_type", None),
+        "data": data or {},
+    }
+
+
+def trace_value(value: Any) -> Any:
+    """Value as stored in the trace (JSON-safe, long values truncated)"""
+    if value is None or isinstance(value, (bool, int, float)):
+        return value
+    if isinstance(value, str):
+        return value if len(value) <= MAX_VALUE_LENGTH else value[:MAX_VALUE_LENGTH] + "…"
+    serialized = json.dumps(value, ensure_ascii=False, default=str)
+    if len(serialized) > MAX_VALUE_LENGTH:
+        return serialized[:MAX_VALUE_LENGTH] + "…"
+    return json.loads(serialized)
+
+
+def variable_changes(
+    before: Optional[Dict[str, Any]], after: Optional[Dict[str, Any]]
+) -> List[Dict[str, Any]]:
+    """
+    Context variables set, changed or removed between two snapshots
+
+    Returns:
+        {"name", "old", "new"} per variable ("removed": True when dropped),
+        in name order
+    """
+    before = before or {}
+    after = after or {}
+    changes = []
+    for name in sorted(set(before) | set(after)):
+        if name.startswith("_"):
+            continue
+        if name in before and name in after and before[name] == after[name]:
+            continue
+        change = {"name": name, "old": trace_value(before.get(name)), "new": trace_value(after.get(name))}
+        if name not in after:
+            change["removed"] = True
+        changes.append(change)
+    return changes
+
+
+def input_data(message: Any) -> Dict[str, Any]:
+    """Trace data of an inbound message"""
+    content = getattr(message, "content", None) or {}
+    data = {
+        "message_id": str(message.id) if getattr(message, "id", None) else None,
+        "message_type": getattr(message, "message_type", None),
+    }
+    text = content.get("text") or content.get("caption") or content.get("title")
+    if text:
+        data["text"] = trace_value(text)
+    return data
+
+
+def sent_data(method: str, args: Sequence[Any], kwargs: Dict[str, Any]) -> Dict[str, Any]:
+    """Trace data of a message sent through a WhatsApp client method"""
+    data: Dict[str, Any] = {"method": method}
+    text = next((kwargs[name] for name in _TEXT_ARGUMENTS if isinstance(kwargs.get(name), str)), None)
+    if text is None:
+        # Positional calls: first string after the recipient (text, body, media URL, template name)
+        text = next((arg for arg in args[1:] if isinstance(arg, str)), None)
+    if text:
+        data["text"] = trace_value(text)
+    return data
@@ -0,0 +1,63 @@
+"""
+Tenant references of exported flows
+
+Node data points at records of the exporting organization by UUID; the
+export lists them with a portable key so the importing organization can
+resolve its own records:
+
+    flow        targetFlowId (Jump / Sub-flow)   -> flow name
+    department  departmentId (Handoff)           -> department slug
+    queue       queueId (Handoff)                -> queue slug
+    agent       agentId (Handoff)                -> user email
+
+Canvas node ids are local to the flow and are kept as they are.
+"""
+
+from copy import deepcopy
+from typing import Any, Dict, List, Optional, Tuple
+
+FLOW_EXPORT_FORMAT_VERSION = "2.0"
+SUPPORTED_FORMAT_VERSIONS = ("1.0", FLOW_EXPORT_FORMAT_VERSION)
+
+REFERENCE_KEYS = {
+    "targetFlowId": "flow",
+    "departmentId": "department",
+    "queueId": "queue",
+    "agentId": "agent",
+}
+
+
+def collect_references(canvas_data: Optional[Dict[str, Any]]) -> List[Tuple[str, str]]:
+    """Unique (type, id) pairs referenced by the canvas nodes, in canvas order"""
+    references = []
+    for node in (canvas_data or {}).get("nodes", []):
+        data = node.get("data") or {}
+        for key, reference_type in REFERENCE_KEYS.items():
+            value = data.get(key)
+            if value and (reference_type, str(value)) not in references:
+                references.append((reference_type, str(value)))
+    return references
+
+
+def remap_references(
+    canvas_data: Optional[Dict[str, Any]], id_map: Dict[str, Optional[str]]
+) -> Tuple[Dict[str, Any], List[Dict[str, str]]]:
+    """
+    Copy of the canvas with the referenced ids replaced through id_map
+
+    References missing from id_map (or mapped to None) are cleared and
+    returned as {"type", "id", "node_id"} so the caller can report them.
+    """
+    canvas = deepcopy(canvas_data or {"nodes": [], "edges": []})
+    unresolved = []
+    for node in canvas.get("nodes", []):
+        data = node.get("data") or {}
+        for key, reference_type in REFERENCE_KEYS.items():
+            value = data.get(key)
+            if not value:
+                continue
+            new_id = id_map.get(str(value))
+            if new_id is None:
+                unresolved.append({"type": reference_type, "id": str(value), "node_id": node.get("id")})
+            data[key] = new_id
+    return canvas, unresolved
@@ -0,0 +1,182 @@
+"""
+Keyword triggers for flows
+
+A trigger maps an inbound text to a flow of the organization:
+
+    exact    the whole message is the phrase ("menu", "2ª via")
+    keyword  the phrase appears as whole words ("quero falar com atendente")
+    regex    a Python regular expression, case-insensitive (r"boleto|2.? via")
+
+Exact and keyword matching ignore case, accents, punctuation and repeated
+spaces, so "2ª via" also matches "2a VIA!". Triggers are evaluated before
+the chatbot's main flow; those with interrupts_flow also while a flow is
+running (global commands such as "menu"). When several match, the highest
+priority wins, then the most specific match type (exact, keyword, regex),
+then the oldest trigger.
+"""
+
+import re
+import unicodedata
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from typing import Any, Iterable, List, Optional
+
+MATCH_EXACT = "exact"
+MATCH_KEYWORD = "keyword"
+MATCH_REGEX = "regex"
+MATCH_TYPES = (MATCH_EXACT, MATCH_KEYWORD, MATCH_REGEX)
+
+# Same priority: the most specific match type wins
+SPECIFICITY = {MATCH_EXACT: 0, MATCH_KEYWORD: 1, MATCH_REGEX: 2}
+
+# Regexes only see the start of long messages
+MAX_REGEX_INPUT_LENGTH = 1000
+
+_OLDEST = datetime.min.replace(tzinfo=timezone.utc)
+
+
+@dataclass
+class TriggerConflict:
+    """Two active triggers of different flows that compete for the same messages"""
+
+    kind: str  # duplicate or overlap
+    trigger_id: Any
+    other_trigger_id: Any
+    message: str
+
+
+def normalize_text(text: Any) -> str:
+    """Casefolded text without accents, punctuation or repeated spaces"""
+    if not isinstance(text, str):
+        return ""
+    decomposed = unicodedata.normalize("NFKD", text.casefold())
+    stripped = "".join(char for char in decomposed if not unicodedata.combining(char))
+    return " ".join(re.sub(r"[^\w\s]", " ", stripped).split())
+
+
+def validate_pattern(match_type: str, pattern: str) -> None:
+    """
+    Raise ValueError when the pattern can never match.
+
+    Exact and keyword patterns need at least one letter or digit; regexes
+    must compile and must not match the empty string (they would fire on
+    every message).
+    """
+    if match_type not in MATCH_TYPES:
+        raise ValueError(f"match_type must be one of {', '.join(MATCH_TYPES)}")
+    if match_type == MATCH_REGEX:
+        try:
+            compiled = re.compile(pattern, re.IGNORECASE)
+        except re.error as e:
+            raise ValueError(f"invalid regular expression: {e}") from e
+        if compiled.search(""):
+            raise ValueError("regular expression matches every message")
+    elif not normalize_text(pattern):
+        raise ValueError("pattern must contain letters or digits")
+
+
+def trigger_matches(match_type: str, pattern: str, text: Any) -> bool:
+    """Whether a message text fires a trigger"""
+    if not isinstance(text, str) or not text.strip():
+        return False
+    if match_type == MATCH_REGEX:
+        try:
+            return re.search(pattern, text[:MAX_REGEX_INPUT_LENGTH], re.IGNORECASE) is not None
+        except re.error:
+            return False
+
+    phrase = normalize_text(pattern)
+    if not phrase:
+        return False
+    normalized = normalize_text(text)
+    if match_type == MATCH_EXACT:
+        return normalized == phrase
+    if match_type == MATCH_KEYWORD:
+        return re.search(rf"(?<!\w){re.escape(phrase)}(?!\w)", normalized) is not None
+    return False
+
+
+def trigger_order(trigger: Any) -> tuple:
+    """Sort key: priority, then match type specificity, then age"""
+    return (
+        -(trigger.priority or 0),
+        SPECIFICITY.get(trigger.match_type, len(SPECIFICITY)),
+        trigger.created_at or _OLDEST,
+    )
+
+
+def pick_trigger(triggers: Iterable[Any], text: Any, in_flow: bool = False) -> Optional[Any]:
+    """
+    Trigger fired by a message, None if no active trigger matches.
+
+    Args:
+        triggers: Objects with match_type, pattern, priority, interrupts_flow,
+            is_active and created_at (FlowTrigger rows)
+        text: Message text
+        in_flow: A flow is running; only triggers with interrupts_flow apply
+    """
+    for trigger in sorted(triggers, key=trigger_order):
+        if not trigger.is_active or (in_flow and not trigger.interrupts_flow):
+            continue
+        if trigger_matches(trigger.match_type, trigger.pattern, text):
+            return trigger
+    return None
+
+
+def find_conflicts(triggers: Iterable[Any]) -> List[TriggerConflict]:
+    """
+    Conflicts between active triggers that lead to different flows.
+
+    duplicate: same match type and (normalized) pattern; only one of them
+        can ever fire.
+    overlap: the phrase of an exact or keyword trigger also fires the other
+        trigger; the message says which one wins.
+
+    Overlaps between two regexes are not detected.
+    """
+    active = sorted((trigger for trigger in triggers if trigger.is_active), key=trigger_order)
+    conflicts: List[TriggerConflict] = []
+
+    for index, winner in enumerate(active):
+        for other in active[index + 1:]:
+            if winner.flow_id == other.flow_id:
+                continue
+
+            if winner.match_type == other.match_type and _same_pattern(winner, other):
+                conflicts.append(TriggerConflict(
+                    kind="duplicate",
+                    trigger_id=winner.id,
+                    other_trigger_id=other.id,
+                    message=f"{winner.match_type} trigger '{winner.pattern}' is defined twice for different flows",
+                ))
+                continue
+
+            shared_phrase = _shared_phrase(winner, other)
+            if shared_phrase is not None:
+                conflicts.append(TriggerConflict(
+                    kind="overlap",
+                    trigger_id=winner.id,
+                    other_trigger_id=other.id,
+                    message=(
+                        f"'{shared_phrase}' fires both {winner.match_type} trigger '{winner.pattern}' "
+                        f"and {other.match_type} trigger '{other.pattern}'; '{winner.pattern}' wins"
+                    ),
+                ))
+
+    return conflicts
+
+
+def _same_pattern(first: Any, second: Any) -> bool:
+    if first.match_type == MATCH_REGEX:
+        return first.pattern == second.pattern
+    return normalize_text(first.pattern) == normalize_text(second.pattern)
+
+
+def _shared_phrase(first: Any, second: Any) -> Optional[str]:
+    """A phrase of one trigger that also fires the other, if any"""
+    for trigger, other in ((first, second), (second, first)):
+        if trigger.match_type == MATCH_REGEX:
+            continue
+        if trigger_matches(other.match_type, other.pattern, trigger.pattern):
+            return trigger.pattern
+    return None
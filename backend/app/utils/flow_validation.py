@@ -0,0 +1,294 @@
+"""
+Static validation of flow canvases
+
+Run when a flow is saved (diagnostics are returned with the flow) and when
+it is published (errors block publishing). Works on the React Flow
+canvas_data so drafts are checked exactly as the editor saved them:
+
+    missing_start            error    no start node
+    multiple_start           error    more than one start node
+    dangling_edge            error    edge source/target is not a node
+    whatsapp_limit           error    interactive node over WhatsApp limits
+    invalid_split            error    split node without two variants with traffic, or a variant not connected
+    invalid_script           error    sandbox script that does not compile or uses forbidden constructs
+    invalid_validation       error    question validation with an unknown type, a bad pattern or inverted bounds
+    invalid_media_collect    error    media collection node without output variable, or with unknown types / size
+    invalid_service_area     error    location collection node whose service area is not a valid polygon
+    invalid_survey           error    survey node with an unknown survey type or malformed skip keywords
+    missing_end              warning  no end node
+    unreachable_node         warning  node not reachable from the start node
+    missing_failure_branch   warning  node that can fail without the failure output connected
+                                      (or a business hours node without its open/closed outputs, or a
+                                      location node with a service area without its in/out of area outputs)
+    split_percentages        warning  split variant percentages do not add up to 100
+"""
+
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional, Set
+
+from app.utils.business_hours import BUSINESS_HOURS_NODE_TYPE, CLOSED_HANDLE, OPEN_HANDLE
+from app.utils.flow_loop import LOOP_DONE_HANDLE
+from app.utils.flow_split import SPLIT_NODE_TYPE, split_variants
+from app.utils.input_validation import check_validation_config
+from app.utils.location_collection import (
+    IN_AREA_HANDLE,
+    LOCATION_COLLECT_NODE_TYPE,
+    OUT_OF_AREA_HANDLE,
+    check_location_config,
+)
+from app.utils.media_collection import MEDIA_COLLECT_NODE_TYPE, check_media_config
+from app.utils.message_limits import validate_buttons, validate_list
+from app.utils.script_sandbox import ERROR_HANDLE as SCRIPT_ERROR_HANDLE, SANDBOX_SCRIPT_NODE_TYPE, validate_script
+from app.utils.survey import SURVEY_NODE_TYPE, check_survey_config
+
+SEVERITY_ERROR = "error"
+SEVERITY_WARNING = "warning"
+
+# Error output of API Call nodes with errorHandling.onError "branch" (see WhatsAppService)
+API_CALL_ERROR_HANDLE = "error"
+CONDITION_FALSE_LABELS = ("false", "no", "não")
+
+
+@dataclass
+class FlowDiagnostic:
+    """One problem found in a flow"""
+
+    code: str
+    severity: str
+    message: str
+    node_id: Optional[str] = None
+    edge_id: Optional[str] = None
+    field: Optional[str] = None
+
+
+def validate_flow(canvas_data: Optional[Dict[str, Any]]) -> List[FlowDiagnostic]:
+    """Diagnostics of a canvas, errors first"""
+    canvas_data = canvas_data or {}
+    nodes = [node for node in canvas_data.get("nodes", []) if node.get("id")]
+    edges = canvas_data.get("edges", [])
+    node_types = {node["id"]: (node.get("data") or {}).get("nodeType") for node in nodes}
+
+    diagnostics: List[FlowDiagnostic] = []
+
+    start_ids = [node_id for node_id, node_type in node_types.items() if node_type == "start"]
+    if not start_ids:
+        diagnostics.append(FlowDiagnostic("missing_start", SEVERITY_ERROR, "Flow has no start node"))
+    for node_id in start_ids[1:]:
+        diagnostics.append(FlowDiagnostic(
+            "multiple_start", SEVERITY_ERROR, "Flow has more than one start node", node_id=node_id
+        ))
+
+    if nodes and "end" not in node_types.values():
+        diagnostics.append(FlowDiagnostic("missing_end", SEVERITY_WARNING, "Flow has no end node"))
+
+    valid_edges = []
+    for edge in edges:
+        missing = [end for end in (edge.get("source"), edge.get("target")) if end not in node_types]
+        if missing:
+            diagnostics.append(FlowDiagnostic(
+                "dangling_edge", SEVERITY_ERROR,
+                f"Edge points to missing node {missing[0] or '(empty)'}",
+                node_id=edge.get("source") if edge.get("source") in node_types else None,
+                edge_id=edge.get("id"),
+            ))
+        else:
+            valid_edges.append(edge)
+
+    if start_ids:
+        reachable = _reachable(start_ids[0], nodes, valid_edges)
+        for node in nodes:
+            if node["id"] not in reachable:
+                diagnostics.append(FlowDiagnostic(
+                    "unreachable_node", SEVERITY_WARNING,
+                    "Node cannot be reached from the start node", node_id=node["id"],
+                ))
+
+    for node in nodes:
+        outgoing = [edge for edge in valid_edges if edge.get("source") == node["id"]]
+        diagnostics.extend(_check_failure_branch(node, outgoing))
+        diagnostics.extend(_check_whatsapp_limits(node))
+        diagnostics.extend(_check_split(node, outgoing))
+        diagnostics.extend(_check_sandbox_script(node))
+        diagnostics.extend(_check_question_validation(node))
+        diagnostics.extend(_check_media_collect(node))
+        diagnostics.extend(_check_location_collect(node))
+        diagnostics.extend(_check_survey(node))
+
+    return sorted(diagnostics, key=lambda diagnostic: diagnostic.severity != SEVERITY_ERROR)
+
+
+def has_errors(diagnostics: List[FlowDiagnostic]) -> bool:
+    """Whether any diagnostic blocks publishing"""
+    return any(diagnostic.severity == SEVERITY_ERROR for diagnostic in diagnostics)
+
+
+def flow_targets(nodes: List[Dict[str, Any]], edges: List[Dict[str, Any]]) -> Dict[str, List[str]]:
+    """Nodes each node can go to next: edges, jumps and random paths"""
+    targets: Dict[str, List[str]] = {}
+    for edge in edges:
+        targets.setdefault(edge["source"], []).append(edge["target"])
+    for node in nodes:
+        data = node.get("data") or {}
+        if data.get("nodeType") == "jump" and data.get("targetNodeId"):
+            targets.setdefault(node["id"], []).append(data["targetNodeId"])
+        for path in data.get("paths") or []:
+            if isinstance(path, dict) and path.get("targetNodeId"):
+                targets.setdefault(node["id"], []).append(path["targetNodeId"])
+    return targets
+
+
+def _reachable(start_id: str, nodes: List[Dict[str, Any]], edges: List[Dict[str, Any]]) -> Set[str]:
+    """Nodes reachable from the start node through edges, jumps and random paths"""
+    targets = flow_targets(nodes, edges)
+    reachable, pending = {start_id}, [start_id]
+    while pending:
+        for target in targets.get(pending.pop(), []):
+            if target not in reachable:
+                reachable.add(target)
+                pending.append(target)
+    return reachable
+
+
+def _check_failure_branch(node: Dict[str, Any], outgoing: List[Dict[str, Any]]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    node_type = data.get("nodeType")
+    handles = {edge.get("sourceHandle") for edge in outgoing}
+
+    if node_type == "api_call" and (data.get("errorHandling") or {}).get("onError") == "branch":
+        if API_CALL_ERROR_HANDLE not in handles:
+            return [_missing_branch(node, "API Call has onError \"branch\" but its error output is not connected")]
+
+    elif node_type == SANDBOX_SCRIPT_NODE_TYPE and (data.get("errorHandling") or {}).get("onError") == "branch":
+        if SCRIPT_ERROR_HANDLE not in handles:
+            return [_missing_branch(node, "Script has onError \"branch\" but its error output is not connected")]
+
+    elif node_type == "condition" and not data.get("hasDefaultRoute"):
+        labels = {str(edge.get("label") or "").lower() for edge in outgoing}
+        if outgoing and not labels & set(CONDITION_FALSE_LABELS):
+            return [_missing_branch(node, "Condition has no false edge; unmatched conversations stop here")]
+
+    elif node_type == "loop" and outgoing and LOOP_DONE_HANDLE not in handles:
+        return [_missing_branch(node, "Loop has no done output; conversations stop after the last iteration")]
+
+    elif node_type == BUSINESS_HOURS_NODE_TYPE and outgoing:
+        if OPEN_HANDLE not in handles:
+            return [_missing_branch(node, "Business hours has no open output; conversations in working hours stop here")]
+        if CLOSED_HANDLE not in handles:
+            return [_missing_branch(node, "Business hours has no closed output; conversations after hours stop here")]
+
+    elif node_type == LOCATION_COLLECT_NODE_TYPE and data.get("serviceArea") and outgoing:
+        if IN_AREA_HANDLE not in handles:
+            return [_missing_branch(node, "Location has a service area but no in_area output; covered contacts stop here")]
+        if OUT_OF_AREA_HANDLE not in handles:
+            return [_missing_branch(node, "Location has a service area but no out_of_area output; uncovered contacts stop here")]
+
+    return []
+
+
+def _missing_branch(node: Dict[str, Any], message: str) -> FlowDiagnostic:
+    return FlowDiagnostic("missing_failure_branch", SEVERITY_WARNING, message, node_id=node["id"])
+
+
+def _check_split(node: Dict[str, Any], outgoing: List[Dict[str, Any]]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    if data.get("nodeType") != SPLIT_NODE_TYPE:
+        return []
+
+    variants = split_variants(data)
+    ids = [variant["id"] for variant in variants]
+    if len([variant for variant in variants if variant["percentage"] > 0]) < 2:
+        return [_invalid_split(node, "Split needs at least two variants with traffic")]
+    if len(set(ids)) != len(ids):
+        return [_invalid_split(node, "Split variant IDs must be unique")]
+
+    handles = {edge.get("sourceHandle") for edge in outgoing}
+    diagnostics = [
+        _invalid_split(node, f"Split variant '{variant['label']}' is not connected; its sessions stop here")
+        for variant in variants
+        if variant["percentage"] > 0 and variant["id"] not in handles
+    ]
+    total = sum(variant["percentage"] for variant in variants)
+    if abs(total - 100) > 0.01:
+        diagnostics.append(FlowDiagnostic(
+            "split_percentages", SEVERITY_WARNING,
+            f"Split percentages add up to {total:g}, traffic is shared proportionally",
+            node_id=node["id"],
+        ))
+    return diagnostics
+
+
+def _invalid_split(node: Dict[str, Any], message: str) -> FlowDiagnostic:
+    return FlowDiagnostic("invalid_split", SEVERITY_ERROR, message, node_id=node["id"])
+
+
+def _check_sandbox_script(node: Dict[str, Any]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    if data.get("nodeType") != SANDBOX_SCRIPT_NODE_TYPE:
+        return []
+    return [
+        FlowDiagnostic("invalid_script", SEVERITY_ERROR, error, node_id=node["id"], field="code")
+        for error in validate_script(data.get("code", ""))
+    ]
+
+
+def _check_question_validation(node: Dict[str, Any]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    if data.get("nodeType") != "question":
+        return []
+    return [
+        FlowDiagnostic("invalid_validation", SEVERITY_ERROR, error, node_id=node["id"], field="validation")
+        for error in check_validation_config(data)
+    ]
+
+
+def _check_media_collect(node: Dict[str, Any]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    if data.get("nodeType") != MEDIA_COLLECT_NODE_TYPE:
+        return []
+    return [
+        FlowDiagnostic("invalid_media_collect", SEVERITY_ERROR, error, node_id=node["id"])
+        for error in check_media_config(data)
+    ]
+
+
+def _check_location_collect(node: Dict[str, Any]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    if data.get("nodeType") != LOCATION_COLLECT_NODE_TYPE:
+        return []
+    return [
+        FlowDiagnostic("invalid_service_area", SEVERITY_ERROR, error, node_id=node["id"], field="serviceArea")
+        for error in check_location_config(data)
+    ]
+
+
+def _check_survey(node: Dict[str, Any]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    if data.get("nodeType") != SURVEY_NODE_TYPE:
+        return []
+    return [
+        FlowDiagnostic("invalid_survey", SEVERITY_ERROR, error, node_id=node["id"])
+        for error in check_survey_config(data)
+    ]
+
+
+def _check_whatsapp_limits(node: Dict[str, Any]) -> List[FlowDiagnostic]:
+    data = node.get("data") or {}
+    node_type = data.get("nodeType")
+
+    if node_type == "interactive_buttons":
+        issues = validate_buttons(
+            data.get("bodyText"), data.get("buttons") or [],
+            header=data.get("headerText"), footer=data.get("footerText"),
+        )
+    elif node_type == "interactive_list":
+        issues = validate_list(
+            data.get("bodyText"), data.get("buttonText", "Ver opções"), data.get("sections") or [],
+            header=data.get("headerText"), footer=data.get("footerText"),
+        )
+    else:
+        return []
+
+    return [
+        FlowDiagnostic("whatsapp_limit", SEVERITY_ERROR, str(issue), node_id=node["id"], field=issue.field)
+        for issue in issues
+    ]
@@ -0,0 +1,92 @@
+"""
+Wait node scheduling
+
+A wait node pauses the conversation's flow for a while or until a moment,
+then follows its outgoing edge. The resume moment is stored in flow_waits
+(see FlowWait) and picked up by the resume_flow_waits task, so waits survive
+restarts:
+
+    {
+        "waitType": "duration",          # "duration" (default) or "until"
+        "duration": 2,
+        "durationUnit": "days",          # "minutes", "hours" or "days"
+        # waitType "until": ISO 8601, dd/mm/yyyy HH:MM, dd/mm/yyyy or a {{variable}}
+        "until": "{{data_retorno}}",
+        "timezone": "America/Sao_Paulo", # zone of dates without an offset
+        "resumeOnReply": false           # resume as soon as the contact writes
+    }
+"""
+
+import re
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, Optional
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+WAIT_UNITS = {"minutes": 60, "hours": 3600, "days": 86400}
+
+MAX_WAIT = timedelta(days=90)
+
+DATE_FORMATS = ("%d/%m/%Y %H:%M", "%d/%m/%Y")
+
+_VARIABLE = re.compile(r"^\{\{(\w+)\}\}$")
+
+
+def wait_resume_at(node_data: Dict[str, Any], variables: Dict[str, Any], now: Optional[datetime] = None) -> datetime:
+    """
+    Moment (UTC) a wait node resumes the flow
+
+    Raises:
+        ValueError: If the duration or date is invalid, in the past or more than MAX_WAIT away
+    """
+    now = now or datetime.now(timezone.utc)
+
+    if node_data.get("waitType") == "until":
+        resume_at = _parse_until(node_data.get("until"), variables, node_data.get("timezone"))
+        if resume_at <= now:
+            raise ValueError(f"Wait date {resume_at.isoformat()} is in the past")
+    else:
+        unit = node_data.get("durationUnit", "minutes")
+        if unit not in WAIT_UNITS:
+            raise ValueError(f"Unknown wait unit: {unit}")
+        try:
+            duration = float(node_data.get("duration"))
+        except (TypeError, ValueError):
+            raise ValueError(f"Invalid wait duration: {node_data.get('duration')!r}")
+        if duration <= 0:
+            raise ValueError(f"Invalid wait duration: {duration}")
+        resume_at = now + timedelta(seconds=duration * WAIT_UNITS[unit])
+
+    if resume_at - now > MAX_WAIT:
+        raise ValueError(f"Waits are limited to {MAX_WAIT.days} days")
+    return resume_at
+
+
+def _parse_until(value: Any, variables: Dict[str, Any], tz_name: Optional[str]) -> datetime:
+    match = _VARIABLE.match(str(value or "").strip())
+    if match:
+        value = variables.get(match.group(1))
+
+    if isinstance(value, datetime):
+        moment = value
+    else:
+        text = str(value or "").strip()
+        moment = None
+        try:
+            moment = datetime.fromisoformat(text.replace("Z", "+00:00"))
+        except ValueError:
+            for date_format in DATE_FORMATS:
+                try:
+                    moment = datetime.strptime(text, date_format)
+                    break
+                except ValueError:
+                    continue
+        if moment is None:
+            raise ValueError(f"Invalid wait date: {value!r}")
+
+    if moment.tzinfo is None:
+        try:
+            zone = ZoneInfo(tz_name or "America/Sao_Paulo")
+        except ZoneInfoNotFoundError:
+            zone = ZoneInfo("America/Sao_Paulo")
+        moment = moment.replace(tzinfo=zone)
+    return moment.astimezone(timezone.utc)
@@ -0,0 +1,90 @@
+"""
+Templating and response mapping of the API Call node
+
+render_template fills {{variable}} placeholders in the URL, headers, query
+params and body (nested dicts and lists included); json_path reads fields of
+the response back into variables:
+
+    json_path({"data": {"items": [{"id": 7}]}}, "$.data.items[0].id")   # 7
+    json_path({"data": {"items": [{"id": 7}, {"id": 9}]}}, "$.data.items[*].id")   # [7, 9]
+
+Supported JSONPath: ``$``, ``.field``, ``['field']``, ``[n]`` (negative
+indexes count from the end) and ``[*]``.
+"""
+
+import re
+from typing import Any, Dict, List
+
+_VARIABLE = re.compile(r"\{\{(\w+)\}\}")
+_PATH_TOKEN = re.compile(r"\.([^.\[\]]+)|\[(\*|-?\d+|'[^']*'|\"[^\"]*\")\]")
+
+# Status codes worth retrying; other 4xx answers will not change on a retry
+RETRYABLE_STATUS_CODES = {408, 425, 429}
+
+
+def render_template(value: Any, variables: Dict[str, Any]) -> Any:
+    """Replace {{variable}} in strings, recursively; unknown placeholders are kept"""
+    if isinstance(value, str):
+        return _VARIABLE.sub(
+            lambda match: str(variables[match.group(1)]) if match.group(1) in variables else match.group(0),
+            value,
+        )
+    if isinstance(value, dict):
+        return {key: render_template(item, variables) for key, item in value.items()}
+    if isinstance(value, list):
+        return [render_template(item, variables) for item in value]
+    return value
+
+
+def json_path(data: Any, expression: str) -> Any:
+    """
+    Value at a JSONPath expression, or None if the path does not exist
+
+    Raises:
+        ValueError: If the expression is not supported
+    """
+    expression = (expression or "").strip()
+    if not expression.startswith("$"):
+        expression = f"$.{expression}"
+
+    tokens: List[str] = []
+    position = 1
+    while position < len(expression):
+        match = _PATH_TOKEN.match(expression, position)
+        if not match:
+            raise ValueError(f"Unsupported JSONPath: {expression}")
+        tokens.append(match.group(1) if match.group(1) is not None else match.group(2))
+        position = match.end()
+
+    values, wildcard = [data], False
+    for token in tokens:
+        next_values = []
+        for value in values:
+            if token == "*":
+                wildcard = True
+                if isinstance(value, list):
+                    next_values.extend(value)
+                elif isinstance(value, dict):
+                    next_values.extend(value.values())
+            elif isinstance(value, list) and re.fullmatch(r"-?\d+", token):
+                index = int(token)
+                if -len(value) <= index < len(value):
+                    next_values.append(value[index])
+            elif isinstance(value, dict):
+                key = token[1:-1] if token[:1] in ("'", '"') else token
+                if key in value:
+                    next_values.append(value[key])
+        values = next_values
+
+    if wildcard:
+        return values
+    return values[0] if values else None
+
+
+def map_response(data: Any, mapping: Dict[str, str]) -> Dict[str, Any]:
+    """Variables from the response, e.g. {"pedido_status": "$.order.status"}"""
+    return {variable: json_path(data, expression) for variable, expression in (mapping or {}).items()}
+
+
+def is_retryable(status_code: int) -> bool:
+    return status_code >= 500 or status_code in RETRYABLE_STATUS_CODES
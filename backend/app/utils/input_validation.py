@@ -0,0 +1,326 @@
+"""
+Question node input validation
+
+Validates the contact's answer to a question node by its responseType:
+
+    text       any text (minLength / maxLength)
+    number     numbers in BR or international notation (min / max)
+    email      e-mail address
+    phone      BR phone with DDD (landline or mobile, "mobileOnly"); international
+               numbers starting with "+" are accepted by length
+    cpf        CPF with check digits
+    cnpj       CNPJ with check digits (numeric or the alphanumeric format)
+    document   CPF or CNPJ
+    date       date in "dateFormat" (default DD/MM/YYYY; several formats as a list),
+               optionally restricted with allowPast / allowFuture / minDate / maxDate
+    options    one of the node's options (value or label)
+    regex      text matching "pattern"
+
+    "validation": {
+        "required": true,
+        "pattern": "^[A-Z]{3}-?\\d{4}$",       # custom regex, checked for every type
+        "errorMessage": "CPF inválido, digite só os números.",
+        "maxAttempts": 3,
+        "maxAttemptsMessage": "Não consegui validar sua resposta.",
+        "normalizedVariable": "cpf_numeros"    # optional: cleaned value (digits, ISO date, number)
+    }
+
+Invalid answers are asked again until maxAttempts; then the question follows
+its "invalid" output when connected, otherwise the answer is discarded and
+the flow continues.
+"""
+
+import re
+from dataclasses import dataclass
+from datetime import date, datetime
+from typing import Any, Dict, List, Optional
+
+INVALID_HANDLE = "invalid"
+
+RESPONSE_TYPES = ("text", "number", "email", "phone", "cpf", "cnpj", "document", "date", "options", "regex")
+
+DEFAULT_MAX_ATTEMPTS = 3
+DEFAULT_DATE_FORMAT = "DD/MM/YYYY"
+DEFAULT_MAX_ATTEMPTS_MESSAGE = "Número máximo de tentativas excedido. Continuando com o atendimento..."
+
+DEFAULT_ERRORS = {
+    "required": "Por favor, digite uma resposta.",
+    "number": "Por favor, digite um número válido.",
+    "email": "Por favor, digite um e-mail válido.",
+    "phone": "Por favor, digite um telefone válido com DDD.",
+    "cpf": "Por favor, digite um CPF válido.",
+    "cnpj": "Por favor, digite um CNPJ válido.",
+    "document": "Por favor, digite um CPF ou CNPJ válido.",
+    "date": "Por favor, digite uma data válida no formato {format}.",
+    "regex": "Por favor, digite uma resposta no formato esperado.",
+}
+
+EMAIL_PATTERN = re.compile(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$")
+
+# Date format tokens used in the canvas, in strptime terms (longest first)
+DATE_TOKENS = (("YYYY", "%Y"), ("YY", "%y"), ("DD", "%d"), ("MM", "%m"))
+
+
+@dataclass
+class AnswerValidation:
+    """Outcome of validating an answer"""
+
+    valid: bool
+    error: Optional[str] = None
+    value: Any = None
+
+
+def validate_answer(text: str, node_data: Dict[str, Any], today: Optional[date] = None) -> AnswerValidation:
+    """
+    Validate the answer to a question node
+
+    Returns:
+        AnswerValidation with the error message to send back, or the
+        normalized value (digits, ISO date, number, option value)
+    """
+    response_type = node_data.get("responseType", "text")
+    validation = node_data.get("validation") or {}
+    custom_error = validation.get("errorMessage")
+    text = (text or "").strip()
+
+    if not text:
+        if validation.get("required", True):
+            return AnswerValidation(False, custom_error or DEFAULT_ERRORS["required"])
+        return AnswerValidation(True, value="")
+
+    value, error = _check_type(response_type, text, node_data, validation, today or date.today())
+    if error is None and validation.get("pattern") and response_type != "regex":
+        if not _matches(validation["pattern"], text):
+            error = DEFAULT_ERRORS["regex"]
+    if error is not None:
+        return AnswerValidation(False, custom_error or error)
+    return AnswerValidation(True, value=value)
+
+
+def max_attempts(node_data: Dict[str, Any]) -> int:
+    """Answers accepted before the node gives up (validation.maxAttempts, or maxAttempts of collection nodes)"""
+    validation = node_data.get("validation") or {}
+    try:
+        attempts = int(validation.get("maxAttempts", node_data.get("maxAttempts", DEFAULT_MAX_ATTEMPTS)))
+    except (TypeError, ValueError):
+        return DEFAULT_MAX_ATTEMPTS
+    return attempts if attempts > 0 else DEFAULT_MAX_ATTEMPTS
+
+
+def check_validation_config(node_data: Dict[str, Any]) -> List[str]:
+    """
+    Problems of a question node's validation settings
+
+    Returns:
+        Error messages (empty when the settings are usable)
+    """
+    response_type = node_data.get("responseType", "text")
+    validation = node_data.get("validation") or {}
+    errors = []
+
+    if response_type not in RESPONSE_TYPES:
+        errors.append(f"Unknown responseType '{response_type}'")
+    if validation.get("pattern"):
+        try:
+            re.compile(validation["pattern"])
+        except re.error as e:
+            errors.append(f"Invalid pattern: {e}")
+    elif response_type == "regex":
+        errors.append("responseType \"regex\" needs a pattern")
+
+    for low, high in (("min", "max"), ("minLength", "maxLength"), ("minDate", "maxDate")):
+        if validation.get(low) is not None and validation.get(high) is not None:
+            try:
+                if _bound(low, validation[low]) > _bound(high, validation[high]):
+                    errors.append(f"{low} is greater than {high}")
+            except ValueError:
+                errors.append(f"{low}/{high} must be {'dates (YYYY-MM-DD)' if low == 'minDate' else 'numbers'}")
+
+    if response_type == "date":
+        for date_format in _date_formats(validation):
+            if "%d" not in _strptime_format(date_format) or "%m" not in _strptime_format(date_format):
+                errors.append(f"Date format '{date_format}' needs DD and MM")
+    return errors
+
+
+def is_valid_cpf(value: str) -> bool:
+    """CPF check digits (11 digits, punctuation ignored)"""
+    digits = re.sub(r"\D", "", value or "")
+    if len(digits) != 11 or len(set(digits)) == 1:
+        return False
+    numbers = [int(digit) for digit in digits]
+    for position in (9, 10):
+        total = sum(number * weight for number, weight in zip(numbers[:position], range(position + 1, 1, -1)))
+        if (total * 10) % 11 % 10 != numbers[position]:
+            return False
+    return True
+
+
+def is_valid_cnpj(value: str) -> bool:
+    """CNPJ check digits, numeric or alphanumeric (letters in the first 12 characters)"""
+    characters = re.sub(r"[^0-9A-Z]", "", (value or "").upper())
+    if len(characters) != 14 or not characters[12:].isdigit() or len(set(characters)) == 1:
+        return False
+    # Alphanumeric CNPJ: each character is worth its ASCII code minus 48 (digits keep their value)
+    numbers = [ord(character) - 48 for character in characters]
+    for position in (12, 13):
+        weights = [(index % 8) + 2 for index in range(position)][::-1]
+        remainder = sum(number * weight for number, weight in zip(numbers[:position], weights)) % 11
+        if (0 if remainder < 2 else 11 - remainder) != numbers[position]:
+            return False
+    return True
+
+
+def normalize_br_phone(value: str, mobile_only: bool = False) -> Optional[str]:
+    """
+    Phone in E.164 digits (55 + DDD + number), None when invalid
+
+    International numbers ("+" and a country other than 55) are kept as
+    their digits when 8 to 15 digits long.
+    """
+    value = (value or "").strip()
+    digits = re.sub(r"\D", "", value)
+    if value.startswith("+") and not digits.startswith("55"):
+        return digits if 8 <= len(digits) <= 15 and not mobile_only else None
+
+    if len(digits) in (12, 13) and digits.startswith("55"):
+        digits = digits[2:]
+    if len(digits) in (11, 12) and digits.startswith("0"):
+        digits = digits[1:]  # trunk prefix: 0 11 98765-4321
+    if len(digits) not in (10, 11) or "0" in digits[:2]:
+        return None
+
+    number = digits[2:]
+    is_mobile = len(number) == 9 and number[0] == "9"
+    is_landline = len(number) == 8 and number[0] in "2345"
+    if not is_mobile and (mobile_only or not is_landline):
+        return None
+    return f"55{digits}"
+
+
+def parse_date(value: str, formats: List[str]) -> Optional[date]:
+    """First format (canvas tokens like DD/MM/YYYY) that parses value"""
+    for date_format in formats:
+        try:
+            return datetime.strptime(value.strip(), _strptime_format(date_format)).date()
+        except ValueError:
+            continue
+    return None
+
+
+def parse_number(value: str) -> Optional[float]:
+    """Number in BR (1.234,56) or international (1,234.56 / 1234.56) notation"""
+    text = re.sub(r"[^\d,.\-]", "", value or "")
+    if "," in text and "." in text:
+        if text.rfind(",") > text.rfind("."):
+            text = text.replace(".", "").replace(",", ".")
+        else:
+            text = text.replace(",", "")
+    else:
+        text = text.replace(",", ".")
+    try:
+        return float(text)
+    except ValueError:
+        return None
+
+
+def _check_type(response_type, text, node_data, validation, today):
+    """(normalized value, error or None) of an answer by type"""
+    if response_type == "number":
+        number = parse_number(text)
+        if number is None:
+            return None, DEFAULT_ERRORS["number"]
+        if validation.get("min") is not None and number < float(validation["min"]):
+            return None, f"Por favor, digite um número a partir de {validation['min']}."
+        if validation.get("max") is not None and number > float(validation["max"]):
+            return None, f"Por favor, digite um número até {validation['max']}."
+        return (int(number) if number.is_integer() else number), None
+
+    if response_type == "email":
+        if not EMAIL_PATTERN.match(text):
+            return None, DEFAULT_ERRORS["email"]
+        return text.lower(), None
+
+    if response_type == "phone":
+        phone = normalize_br_phone(text, mobile_only=bool(validation.get("mobileOnly")))
+        return (phone, None) if phone else (None, DEFAULT_ERRORS["phone"])
+
+    if response_type in ("cpf", "cnpj", "document"):
+        document = re.sub(r"[^0-9A-Z]", "", text.upper())
+        if response_type in ("cpf", "document") and is_valid_cpf(document):
+            return document, None
+        if response_type in ("cnpj", "document") and is_valid_cnpj(document):
+            return document, None
+        return None, DEFAULT_ERRORS[response_type]
+
+    if response_type == "date":
+        formats = _date_formats(validation)
+        parsed = parse_date(text, formats)
+        if parsed is None:
+            return None, DEFAULT_ERRORS["date"].format(format=formats[0])
+        if validation.get("allowPast") is False and parsed < today:
+            return None, "Por favor, digite uma data a partir de hoje."
+        if validation.get("allowFuture") is False and parsed > today:
+            return None, "Por favor, digite uma data até hoje."
+        if validation.get("minDate") and parsed < _bound("minDate", validation["minDate"]):
+            return None, f"Por favor, digite uma data a partir de {_bound('minDate', validation['minDate']):%d/%m/%Y}."
+        if validation.get("maxDate") and parsed > _bound("maxDate", validation["maxDate"]):
+            return None, f"Por favor, digite uma data até {_bound('maxDate', validation['maxDate']):%d/%m/%Y}."
+        return parsed.isoformat(), None
+
+    if response_type == "options":
+        return _match_option(text, node_data.get("options") or [])
+
+    if response_type == "regex":
+        if not validation.get("pattern") or _matches(validation["pattern"], text):
+            return text, None
+        return None, DEFAULT_ERRORS["regex"]
+
+    # text (and unknown types)
+    if validation.get("minLength") and len(text) < int(validation["minLength"]):
+        return None, f"Por favor, digite pelo menos {validation['minLength']} caracteres."
+    if validation.get("maxLength") and len(text) > int(validation["maxLength"]):
+        return None, f"Por favor, digite no máximo {validation['maxLength']} caracteres."
+    return text, None
+
+
+def _match_option(text: str, options: List[Any]):
+    if not options:
+        return text, None  # no options to check against
+
+    answer = text.lower()
+    labels = []
+    for option in options:
+        if isinstance(option, dict):
+            value, label = str(option.get("value", "")), str(option.get("label", ""))
+        else:
+            value = label = str(option)
+        if answer in (value.strip().lower(), label.strip().lower()):
+            return value or label, None
+        if label:
+            labels.append(f"'{label}'")
+    return None, f"Por favor, escolha uma das opções: {', '.join(labels)}"
+
+
+def _matches(pattern: str, text: str) -> bool:
+    try:
+        return re.fullmatch(pattern, text) is not None
+    except re.error:
+        return True  # broken patterns are reported by the flow validator, not to the contact
+
+
+def _date_formats(validation: Dict[str, Any]) -> List[str]:
+    formats = validation.get("dateFormat") or DEFAULT_DATE_FORMAT
+    return [formats] if isinstance(formats, str) else list(formats)
+
+
+def _strptime_format(date_format: str) -> str:
+    for token, directive in DATE_TOKENS:
+        date_format = date_format.replace(token, directive)
+    return date_format
+
+
+def _bound(name: str, value: Any):
+    if name in ("minDate", "maxDate"):
+        return date.fromisoformat(str(value))
+    return float(value)
@@ -0,0 +1,156 @@
+"""Contact language detection and language-aware template/agent matching."""
+import re
+import unicodedata
+from typing import Iterable, Optional, Sequence
+
+# Frequent function words per language (accents stripped, lowercase)
+STOPWORDS = {
+    "pt": {
+        "o", "os", "as", "um", "uma", "de", "do", "da", "dos", "das", "em", "no", "na", "nos",
+        "que", "nao", "sim", "com", "para", "pra", "por", "voce", "vc", "eu", "ele", "ela",
+        "meu", "minha", "seu", "sua", "isso", "esse", "essa", "tem", "tenho", "quero", "obrigado",
+        "obrigada", "ola", "oi", "bom", "boa", "dia", "tarde", "noite", "tudo", "bem", "mais",
+        "muito", "como", "quando", "onde", "qual", "pedido", "ainda", "entao", "agora", "aqui",
+    },
+    "es": {
+        "el", "los", "las", "un", "una", "de", "del", "en", "que", "no", "si", "con", "para",
+        "por", "usted", "yo", "mi", "tu", "su", "es", "esta", "estoy", "tengo", "quiero",
+        "gracias", "hola", "buenos", "buenas", "dias", "tardes", "noches", "todo", "bien", "mas",
+        "muy", "como", "cuando", "donde", "cual", "pedido", "todavia", "ahora", "aqui", "pero",
+        "y", "lo", "le", "me", "necesito", "puedo", "hay",
+    },
+    "en": {
+        "the", "a", "an", "of", "to", "in", "on", "is", "are", "was", "i", "you", "he", "she",
+        "it", "we", "they", "my", "your", "this", "that", "have", "has", "want", "need", "thanks",
+        "thank", "hello", "hi", "good", "morning", "afternoon", "evening", "all", "and", "but",
+        "not", "what", "when", "where", "how", "order", "still", "now", "here", "can", "please",
+        "with", "for", "do", "does",
+    },
+    "fr": {
+        "le", "la", "les", "un", "une", "des", "du", "de", "et", "est", "je", "tu", "il", "elle",
+        "nous", "vous", "mon", "ma", "mes", "ce", "cette", "pas", "oui", "non", "avec", "pour",
+        "merci", "bonjour", "bonsoir", "salut", "commande", "encore", "maintenant", "ici",
+        "ai", "veux", "besoin", "comment", "quand", "ou", "mais", "tres", "suis",
+    },
+    "it": {
+        "il", "lo", "la", "gli", "le", "un", "una", "di", "del", "della", "che", "non", "si",
+        "con", "per", "io", "tu", "lui", "lei", "mio", "mia", "questo", "questa", "ho", "voglio",
+        "grazie", "ciao", "buongiorno", "buonasera", "tutto", "bene", "molto", "come", "quando",
+        "dove", "ordine", "ancora", "adesso", "qui", "ma", "sono", "e",
+    },
+    "de": {
+        "der", "die", "das", "ein", "eine", "und", "ist", "ich", "du", "er", "sie", "wir", "mein",
+        "meine", "nicht", "ja", "nein", "mit", "fur", "danke", "hallo", "guten", "morgen", "tag",
+        "abend", "alles", "gut", "sehr", "wie", "wann", "wo", "bestellung", "noch", "jetzt",
+        "hier", "aber", "habe", "mochte", "brauche", "bitte",
+    },
+}
+
+# Characters that (almost) only appear in one of the candidate languages
+MARKERS = {
+    "pt": re.compile(r"[ãõ]|ção|ções"),
+    "es": re.compile(r"[ñ¿¡]|ción|ciones"),
+    "fr": re.compile(r"[èùœ]|\b[cdjlmnst]'"),
+    "de": re.compile(r"[äöüß]"),
+}
+
+LANGUAGE_NAMES = {
+    "pt": "portuguese",
+    "es": "spanish",
+    "en": "english",
+    "fr": "french",
+    "it": "italian",
+    "de": "german",
+}
+
+_WORD_PATTERN = re.compile(r"[a-z]+")
+_MIN_SCORE = 2
+
+
+def _strip_accents(text: str) -> str:
+    return "".join(
+        char for char in unicodedata.normalize("NFKD", text) if not unicodedata.combining(char)
+    )
+
+
+def detect_language(text: Optional[str]) -> Optional[str]:
+    """
+    Guess the language of a message (ISO 639-1 code).
+
+    Scores frequent words and language-specific characters. Returns None for
+    short or ambiguous texts ("ok", "👍", a tie between Portuguese and Spanish)
+    so a later message can decide.
+
+    Example:
+        detect_language("Hola, ¿dónde está mi pedido?") -> "es"
+    """
+    lowered = (text or "").lower()
+    words = _WORD_PATTERN.findall(_strip_accents(lowered))
+    if not words:
+        return None
+
+    scores = {
+        language: sum(1 for word in words if word in stopwords)
+        for language, stopwords in STOPWORDS.items()
+    }
+    for language, pattern in MARKERS.items():
+        scores[language] += 2 * len(pattern.findall(lowered))
+
+    ranked = sorted(scores.items(), key=lambda item: item[1], reverse=True)
+    (best, best_score), (_, runner_up) = ranked[0], ranked[1]
+    if best_score < _MIN_SCORE or best_score == runner_up:
+        return None
+    return best
+
+
+def base_language(code: Optional[str]) -> Optional[str]:
+    """Language part of a locale: "pt_BR" / "pt-BR" -> "pt"."""
+    if not code:
+        return None
+    return re.split(r"[_-]", code.strip().lower(), maxsplit=1)[0] or None
+
+
+def pick_template_language(
+    available: Sequence[str],
+    contact_language: Optional[str],
+    default: Optional[str] = None,
+) -> Optional[str]:
+    """
+    Template language variant to send to a contact.
+
+    Keeps the default when it already matches the contact's language, else
+    picks the first available variant of the contact's language ("es" matches
+    "es", "es_MX", "es_AR"...). Falls back to the default.
+
+    Example:
+        pick_template_language(["pt_BR", "es", "en_US"], "es", "pt_BR") -> "es"
+    """
+    wanted = base_language(contact_language)
+    if not wanted or base_language(default) == wanted:
+        return default
+    for language in available:
+        if language == contact_language:
+            return language
+    for language in sorted(available):
+        if base_language(language) == wanted:
+            return language
+    return default
+
+
+def skill_languages(skill_names: Iterable[str]) -> set:
+    """
+    Languages an agent speaks, from their skills.
+
+    A skill named after the language code or its English name counts:
+    "es", "lang:es", "spanish".
+    """
+    names_to_codes = {name: code for code, name in LANGUAGE_NAMES.items()}
+    languages = set()
+    for skill in skill_names:
+        name = (skill or "").strip().lower()
+        if name.startswith("lang:"):
+            name = name[len("lang:"):]
+        code = names_to_codes.get(name) or (name if name in LANGUAGE_NAMES else None)
+        if code:
+            languages.add(code)
+    return languages
@@ -0,0 +1,197 @@
+"""
+Location collection node
+
+Asks the contact to share a location and waits for it; with a service area
+the node branches on coverage (ISPs checking an address before selling):
+
+    {
+        "nodeType": "location_collect",
+        "promptText": "Compartilhe a localização do endereço de instalação.",
+        "requestButton": true,          # Official API: native "Enviar localização" button
+        "outputVariable": "localizacao",
+        "serviceArea": [[-23.55, -46.64], [-23.55, -46.60], [-23.52, -46.60], [-23.52, -46.64]],
+        "errorMessage": "Não recebi sua localização. Toque em 📎 > Localização.",
+        "maxAttempts": 3,
+        "maxAttemptsMessage": "Vou seguir sem a localização."
+    }
+
+serviceArea is one polygon ([lat, lng] pairs or {"lat", "lng"} objects),
+several named areas ([{"name": "Centro", "polygon": [...]}]) or a GeoJSON
+Polygon / MultiPolygon / Feature / FeatureCollection (GeoJSON positions are
+[lng, lat] and inner rings are holes).
+
+The variable gets the location plus the coverage result:
+
+    {"latitude": -23.53, "longitude": -46.62, "name": null, "address": null,
+     "in_area": true, "area": "Centro"}
+
+With a service area the node follows its "in_area" or "out_of_area" output,
+otherwise its default output. Messages without a location are asked again
+until maxAttempts; then the node follows its "invalid" output when
+connected, otherwise the default output without the variable.
+"""
+
+from dataclasses import dataclass, field
+from typing import Any, Dict, List, Optional, Tuple
+
+LOCATION_COLLECT_NODE_TYPE = "location_collect"
+
+IN_AREA_HANDLE = "in_area"
+OUT_OF_AREA_HANDLE = "out_of_area"
+
+DEFAULT_PROMPT = "Por favor, compartilhe sua localização."
+DEFAULT_ERROR_MESSAGE = "Não recebi sua localização. Toque em 📎 e escolha Localização para enviá-la."
+
+Point = Tuple[float, float]  # (lat, lng)
+
+
+@dataclass
+class ServiceArea:
+    """Polygon (outer ring and holes) of a coverage area"""
+
+    name: Optional[str]
+    outer: List[Point]
+    holes: List[List[Point]] = field(default_factory=list)
+
+    def contains(self, lat: float, lng: float) -> bool:
+        return point_in_polygon(lat, lng, self.outer) and not any(
+            point_in_polygon(lat, lng, hole) and not _on_boundary(lat, lng, hole) for hole in self.holes
+        )
+
+
+def parse_service_areas(value: Any) -> List[ServiceArea]:
+    """
+    Coverage areas of a node's serviceArea
+
+    Raises:
+        ValueError: If the area is not a polygon list, named areas or GeoJSON
+    """
+    if not value:
+        return []
+    if isinstance(value, dict):
+        return _geojson_areas(value)
+    if not isinstance(value, list):
+        raise ValueError("serviceArea must be a polygon, a list of areas or GeoJSON")
+    if all(isinstance(item, dict) and "polygon" in item for item in value):
+        return [ServiceArea(item.get("name"), _ring(item["polygon"])) for item in value]
+    return [ServiceArea(None, _ring(value))]
+
+
+def point_in_polygon(lat: float, lng: float, polygon: List[Point]) -> bool:
+    """Whether the point is inside the polygon or on its edge (ray casting)"""
+    if _on_boundary(lat, lng, polygon):
+        return True
+    inside = False
+    previous = polygon[-1]
+    for current in polygon:
+        (lat1, lng1), (lat2, lng2) = previous, current
+        if (lng1 > lng) != (lng2 > lng):
+            crossing = lat1 + (lng - lng1) * (lat2 - lat1) / (lng2 - lng1)
+            if lat < crossing:
+                inside = not inside
+        previous = current
+    return inside
+
+
+def find_area(lat: float, lng: float, areas: List[ServiceArea]) -> Optional[ServiceArea]:
+    """First area containing the point"""
+    return next((area for area in areas if area.contains(lat, lng)), None)
+
+
+def extract_location(message_type: Optional[str], content: Optional[Dict[str, Any]]) -> Optional[Dict[str, Any]]:
+    """Location shared in an inbound message (Message.message_type / content), None otherwise"""
+    if message_type != "location":
+        return None
+    location = (content or {}).get("location") or {}
+    try:
+        latitude, longitude = float(location["latitude"]), float(location["longitude"])
+    except (KeyError, TypeError, ValueError):
+        return None
+    if not (-90 <= latitude <= 90 and -180 <= longitude <= 180):
+        return None
+    return {
+        "latitude": latitude,
+        "longitude": longitude,
+        "name": location.get("name"),
+        "address": location.get("address"),
+    }
+
+
+def check_location_config(node_data: Dict[str, Any]) -> List[str]:
+    """
+    Problems of a location collection node's settings
+
+    Returns:
+        Error messages (empty when the settings are usable)
+    """
+    try:
+        parse_service_areas(node_data.get("serviceArea"))
+    except ValueError as e:
+        return [str(e)]
+    return []
+
+
+def _ring(points: Any) -> List[Point]:
+    """Polygon of [lat, lng] pairs or {"lat", "lng"} objects"""
+    if not isinstance(points, list):
+        raise ValueError("A polygon must be a list of points")
+    ring = []
+    for point in points:
+        try:
+            if isinstance(point, dict):
+                ring.append(_point(point.get("lat", point.get("latitude")), point.get("lng", point.get("longitude"))))
+            else:
+                ring.append(_point(point[0], point[1]))
+        except (IndexError, KeyError, TypeError, ValueError):
+            raise ValueError(f"Invalid polygon point {point!r}")
+    if len(ring) > 1 and ring[0] == ring[-1]:
+        ring.pop()  # closed rings repeat the first point
+    if len(ring) < 3:
+        raise ValueError("A polygon needs at least 3 points")
+    return ring
+
+
+def _geojson_areas(geojson: Dict[str, Any], name: Optional[str] = None) -> List[ServiceArea]:
+    kind = geojson.get("type")
+    if kind == "FeatureCollection":
+        return [area for feature in geojson.get("features") or [] for area in _geojson_areas(feature)]
+    if kind == "Feature":
+        return _geojson_areas(geojson.get("geometry") or {}, (geojson.get("properties") or {}).get("name"))
+    if kind == "Polygon":
+        return [_geojson_polygon(geojson.get("coordinates"), name)]
+    if kind == "MultiPolygon":
+        return [_geojson_polygon(polygon, name) for polygon in geojson.get("coordinates") or []]
+    raise ValueError(f"Unsupported GeoJSON type {kind!r} (use Polygon or MultiPolygon)")
+
+
+def _geojson_polygon(rings: Any, name: Optional[str]) -> ServiceArea:
+    if not isinstance(rings, list) or not rings:
+        raise ValueError("GeoJSON polygon without coordinates")
+    # GeoJSON positions are [lng, lat]
+    try:
+        converted = [_ring([[position[1], position[0]] for position in ring]) for ring in rings]
+    except (IndexError, KeyError, TypeError):
+        raise ValueError("Invalid GeoJSON polygon coordinates")
+    return ServiceArea(name, converted[0], converted[1:])
+
+
+def _point(lat: Any, lng: Any) -> Point:
+    lat, lng = float(lat), float(lng)
+    if not (-90 <= lat <= 90 and -180 <= lng <= 180):
+        raise ValueError("out of range")
+    return lat, lng
+
+
+def _on_boundary(lat: float, lng: float, polygon: List[Point], tolerance: float = 1e-9) -> bool:
+    previous = polygon[-1]
+    for current in polygon:
+        (lat1, lng1), (lat2, lng2) = previous, current
+        cross = (lat - lat1) * (lng2 - lng1) - (lng - lng1) * (lat2 - lat1)
+        if (
+            abs(cross) <= tolerance
+            and min(lat1, lat2) - tolerance <= lat <= max(lat1, lat2) + tolerance
+            and min(lng1, lng2) - tolerance <= lng <= max(lng1, lng2) + tolerance
+        ):
+            return True
+        previous = current
+    return False
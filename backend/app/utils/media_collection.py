@@ -0,0 +1,167 @@
+"""
+Media collection node
+
+Asks the contact for a file (proof of payment, ID, voice note...) and waits
+for it, like a question node waits for text:
+
+    {
+        "nodeType": "media_collect",
+        "promptText": "Envie a foto ou o PDF do comprovante de pagamento.",
+        "mediaTypes": ["image", "document"],           # image, document, audio, video, sticker
+        "mimeTypes": ["image/*", "application/pdf"],   # optional allow-list ("type/*" wildcards)
+        "maxSizeMb": 10,
+        "outputVariable": "comprovante",
+        "errorMessage": "Envie uma imagem ou PDF de até 10 MB.",
+        "maxAttempts": 3,
+        "maxAttemptsMessage": "Não recebi o arquivo. Vou seguir com o atendimento."
+    }
+
+The media is downloaded from the Graph API (official numbers only) into the
+configured storage backend (app.core.media_storage) and the variable gets a
+reference to it:
+
+    {"reference": "<org>/<conversation>/<media id>.pdf", "type": "document",
+     "mime_type": "application/pdf", "size": 48213, "sha256": "...",
+     "filename": "comprovante.pdf", "caption": null, "media_id": "..."}
+
+Anything else (text, a PDF when only images are accepted, a file over the
+size limit, a failed download) is asked again until maxAttempts; then the
+node follows its "invalid" output when connected, otherwise the flow
+continues without the variable.
+"""
+
+from dataclasses import dataclass, field
+from typing import Any, Dict, List, Optional
+
+from app.utils.input_validation import DEFAULT_MAX_ATTEMPTS
+
+MEDIA_COLLECT_NODE_TYPE = "media_collect"
+
+MEDIA_TYPES = ("image", "document", "audio", "video", "sticker")
+DEFAULT_MEDIA_TYPES = ("image", "document")
+
+# Largest media the Cloud API delivers (documents)
+MAX_MEDIA_SIZE_MB = 100
+DEFAULT_MAX_SIZE_MB = 16
+
+DEFAULT_PROMPT = "Por favor, envie o arquivo."
+MEDIA_TYPE_LABELS = {
+    "image": "imagem",
+    "document": "documento",
+    "audio": "áudio",
+    "video": "vídeo",
+    "sticker": "figurinha",
+}
+
+
+@dataclass
+class InboundMedia:
+    """Media of an inbound message"""
+
+    type: str
+    media_id: Optional[str] = None
+    mime_type: Optional[str] = None
+    filename: Optional[str] = None
+    caption: Optional[str] = None
+
+
+@dataclass
+class MediaRequirements:
+    """What a media collection node accepts"""
+
+    media_types: List[str] = field(default_factory=lambda: list(DEFAULT_MEDIA_TYPES))
+    mime_types: List[str] = field(default_factory=list)
+    max_size_mb: float = DEFAULT_MAX_SIZE_MB
+    max_attempts: int = DEFAULT_MAX_ATTEMPTS
+
+    @classmethod
+    def from_node(cls, node_data: Dict[str, Any]) -> "MediaRequirements":
+        """Requirements of a node; unknown types are ignored and the size is capped at the Cloud API limit"""
+        media_types = [kind for kind in node_data.get("mediaTypes") or [] if kind in MEDIA_TYPES]
+        try:
+            max_size_mb = float(node_data.get("maxSizeMb") or DEFAULT_MAX_SIZE_MB)
+        except (TypeError, ValueError):
+            max_size_mb = DEFAULT_MAX_SIZE_MB
+        try:
+            max_attempts = int(node_data.get("maxAttempts") or DEFAULT_MAX_ATTEMPTS)
+        except (TypeError, ValueError):
+            max_attempts = DEFAULT_MAX_ATTEMPTS
+        return cls(
+            media_types=media_types or list(DEFAULT_MEDIA_TYPES),
+            mime_types=[str(mime).lower() for mime in node_data.get("mimeTypes") or []],
+            max_size_mb=min(max_size_mb, MAX_MEDIA_SIZE_MB) if max_size_mb > 0 else DEFAULT_MAX_SIZE_MB,
+            max_attempts=max_attempts if max_attempts > 0 else DEFAULT_MAX_ATTEMPTS,
+        )
+
+    @property
+    def max_bytes(self) -> int:
+        return int(self.max_size_mb * 1024 * 1024)
+
+    def error_message(self) -> str:
+        """Default message asking again for an accepted file"""
+        kinds = " ou ".join(MEDIA_TYPE_LABELS[kind] for kind in self.media_types)
+        return f"Por favor, envie um arquivo do tipo {kinds} de até {self.max_size_mb:g} MB."
+
+
+def extract_media(message_type: Optional[str], content: Optional[Dict[str, Any]]) -> Optional[InboundMedia]:
+    """Media of an inbound message (Message.message_type / content), None for other messages"""
+    if message_type not in MEDIA_TYPES:
+        return None
+    media = (content or {}).get(message_type) or {}
+    return InboundMedia(
+        type=message_type,
+        media_id=media.get("id"),
+        mime_type=media.get("mime_type"),
+        filename=media.get("filename"),
+        caption=media.get("caption"),
+    )
+
+
+def check_media(media: Optional[InboundMedia], requirements: MediaRequirements) -> bool:
+    """Whether the media is of an accepted type (the size is checked while downloading)"""
+    if media is None or not media.media_id or media.type not in requirements.media_types:
+        return False
+    if not requirements.mime_types:
+        return True
+
+    # "audio/ogg; codecs=opus" -> audio/ogg
+    mime_type = (media.mime_type or "").split(";")[0].strip().lower()
+    return any(
+        mime_type == accepted or (accepted.endswith("/*") and mime_type.startswith(accepted[:-1]))
+        for accepted in requirements.mime_types
+    )
+
+
+def media_variable(media: InboundMedia, stored: Any) -> Dict[str, Any]:
+    """Session variable of a collected media (stored is a pytake_client StoredMedia)"""
+    return {
+        "reference": stored.reference,
+        "type": media.type,
+        "mime_type": stored.mime_type or media.mime_type,
+        "size": stored.size,
+        "sha256": stored.sha256,
+        "filename": media.filename,
+        "caption": media.caption,
+        "media_id": media.media_id,
+    }
+
+
+def check_media_config(node_data: Dict[str, Any]) -> List[str]:
+    """
+    Problems of a media collection node's settings
+
+    Returns:
+        Error messages (empty when the settings are usable)
+    """
+    errors = []
+    unknown = [kind for kind in node_data.get("mediaTypes") or [] if kind not in MEDIA_TYPES]
+    if unknown:
+        errors.append(f"Unknown media types: {', '.join(map(str, unknown))}")
+    if not node_data.get("outputVariable"):
+        errors.append("outputVariable is required")
+    try:
+        if float(node_data.get("maxSizeMb") or DEFAULT_MAX_SIZE_MB) > MAX_MEDIA_SIZE_MB:
+            errors.append(f"maxSizeMb is over the WhatsApp limit of {MAX_MEDIA_SIZE_MB} MB")
+    except (TypeError, ValueError):
+        errors.append("maxSizeMb must be a number")
+    return errors
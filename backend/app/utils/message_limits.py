@@ -0,0 +1,299 @@
+"""
+Outbound message limits (WhatsApp Cloud API)
+
+Shared by the flow engine, campaigns and the send endpoint so limit errors
+are reported precisely before calling the API instead of surfacing as
+generic Meta errors (or silently truncated text).
+
+Lengths are counted in grapheme clusters (what the user sees as one
+character), after NFC normalization: "ã" typed as a + combining tilde,
+"👍🏽", "🇧🇷" and "👨‍👩‍👧" each count as 1.
+"""
+
+import unicodedata
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional
+
+from app.utils.urls import is_valid_preview_url
+
+# Character limits
+TEXT_BODY_MAX = 4096
+CAPTION_MAX = 1024
+INTERACTIVE_BODY_MAX = 1024
+INTERACTIVE_HEADER_MAX = 60
+INTERACTIVE_FOOTER_MAX = 60
+BUTTON_TITLE_MAX = 20
+BUTTON_ID_MAX = 256
+LIST_BUTTON_MAX = 20
+LIST_SECTION_TITLE_MAX = 24
+LIST_ROW_TITLE_MAX = 24
+LIST_ROW_DESCRIPTION_MAX = 72
+LIST_ROW_ID_MAX = 200
+CTA_DISPLAY_TEXT_MAX = 20
+
+# Count limits
+MAX_BUTTONS = 3
+MAX_LIST_SECTIONS = 10
+MAX_LIST_ROWS = 10
+
+_ZWJ = "\u200d"
+_KEYCAP = "\u20e3"
+
+
+def _is_regional_indicator(char: str) -> bool:
+    return 0x1F1E6 <= ord(char) <= 0x1F1FF
+
+
+def _extends_cluster(char: str) -> bool:
+    """Code points that never start a new grapheme cluster"""
+    code = ord(char)
+    return (
+        unicodedata.category(char) in ("Mn", "Me", "Mc")
+        or char == _ZWJ
+        or char == _KEYCAP
+        or 0xFE00 <= code <= 0xFE0F  # variation selectors
+        or 0x1F3FB <= code <= 0x1F3FF  # skin tone modifiers
+        or 0xE0020 <= code <= 0xE007F  # tag sequences (subdivision flags)
+    )
+
+
+def split_graphemes(text: str) -> List[str]:
+    """Split text into grapheme clusters (simplified UAX #29 for emoji and accents)"""
+    clusters: List[str] = []
+    regional_run = 0
+
+    for char in unicodedata.normalize("NFC", text or ""):
+        if clusters:
+            previous = clusters[-1]
+            joins = (
+                _extends_cluster(char)
+                or previous.endswith(_ZWJ)
+                or (previous.endswith("\r") and char == "\n")
+                or (_is_regional_indicator(char) and regional_run % 2 == 1)
+            )
+            if joins:
+                clusters[-1] += char
+                regional_run = regional_run + 1 if _is_regional_indicator(char) else 0
+                continue
+
+        clusters.append(char)
+        regional_run = 1 if _is_regional_indicator(char) else 0
+
+    return clusters
+
+
+def grapheme_len(text: Optional[str]) -> int:
+    """Length as displayed to the user"""
+    return len(split_graphemes(text or ""))
+
+
+def truncate_graphemes(text: Optional[str], limit: int, ellipsis: str = "") -> str:
+    """Truncate without splitting emoji or accented characters"""
+    clusters = split_graphemes(text or "")
+    if len(clusters) <= limit:
+        return "".join(clusters)
+    keep = max(limit - grapheme_len(ellipsis), 0)
+    return "".join(clusters[:keep]) + ellipsis
+
+
+@dataclass
+class MessageLimitIssue:
+    """One limit violation"""
+
+    field: str
+    message: str
+    limit: int
+    actual: int
+
+    def __str__(self) -> str:
+        return f"{self.field}: {self.message}"
+
+
+class OutboundMessageError(ValueError):
+    """Raised when an outbound message violates WhatsApp limits"""
+
+    def __init__(self, issues: List[MessageLimitIssue]):
+        self.issues = issues
+        super().__init__("; ".join(str(issue) for issue in issues))
+
+
+def _check_length(
+    issues: List[MessageLimitIssue], field: str, value: Optional[str], limit: int, required: bool = False
+) -> None:
+    if value is None or value == "":
+        if required:
+            issues.append(MessageLimitIssue(field, "is required", limit, 0))
+        return
+
+    length = grapheme_len(value)
+    if length > limit:
+        issues.append(MessageLimitIssue(
+            field, f"has {length} characters (max {limit})", limit, length
+        ))
+
+
+def _check_count(issues: List[MessageLimitIssue], field: str, count: int, limit: int) -> None:
+    if count > limit:
+        issues.append(MessageLimitIssue(field, f"has {count} items (max {limit})", limit, count))
+
+
+# ============================================
+# VALIDATORS
+# ============================================
+
+
+def validate_text(text: Optional[str], field: str = "text") -> List[MessageLimitIssue]:
+    issues: List[MessageLimitIssue] = []
+    _check_length(issues, field, text, TEXT_BODY_MAX, required=True)
+    return issues
+
+
+def validate_caption(caption: Optional[str], field: str = "caption") -> List[MessageLimitIssue]:
+    issues: List[MessageLimitIssue] = []
+    _check_length(issues, field, caption, CAPTION_MAX)
+    return issues
+
+
+def validate_buttons(
+    body: Optional[str],
+    buttons: List[Dict[str, Any]],
+    header: Optional[str] = None,
+    footer: Optional[str] = None,
+) -> List[MessageLimitIssue]:
+    """Reply buttons message (max 3 buttons, 20-character titles)"""
+    issues: List[MessageLimitIssue] = []
+    _check_length(issues, "body", body, INTERACTIVE_BODY_MAX, required=True)
+    _check_length(issues, "header", header, INTERACTIVE_HEADER_MAX)
+    _check_length(issues, "footer", footer, INTERACTIVE_FOOTER_MAX)
+
+    if not buttons:
+        issues.append(MessageLimitIssue("buttons", "at least one button is required", MAX_BUTTONS, 0))
+    _check_count(issues, "buttons", len(buttons or []), MAX_BUTTONS)
+
+    seen_ids = set()
+    for index, button in enumerate(buttons or []):
+        _check_length(issues, f"buttons[{index}].title", button.get("title"), BUTTON_TITLE_MAX, required=True)
+        button_id = button.get("id")
+        if button_id:
+            _check_length(issues, f"buttons[{index}].id", str(button_id), BUTTON_ID_MAX)
+            if button_id in seen_ids:
+                issues.append(MessageLimitIssue(f"buttons[{index}].id", "is duplicated", 1, 2))
+            seen_ids.add(button_id)
+
+    return issues
+
+
+def validate_list(
+    body: Optional[str],
+    button_text: Optional[str],
+    sections: List[Dict[str, Any]],
+    header: Optional[str] = None,
+    footer: Optional[str] = None,
+) -> List[MessageLimitIssue]:
+    """List message (max 10 sections and 10 rows in total)"""
+    issues: List[MessageLimitIssue] = []
+    _check_length(issues, "body", body, INTERACTIVE_BODY_MAX, required=True)
+    _check_length(issues, "header", header, INTERACTIVE_HEADER_MAX)
+    _check_length(issues, "footer", footer, INTERACTIVE_FOOTER_MAX)
+    _check_length(issues, "button", button_text, LIST_BUTTON_MAX, required=True)
+
+    sections = sections or []
+    _check_count(issues, "sections", len(sections), MAX_LIST_SECTIONS)
+    total_rows = sum(len(section.get("rows", [])) for section in sections)
+    if total_rows == 0:
+        issues.append(MessageLimitIssue("sections", "at least one row is required", MAX_LIST_ROWS, 0))
+    _check_count(issues, "rows", total_rows, MAX_LIST_ROWS)
+
+    seen_ids = set()
+    for s_index, section in enumerate(sections):
+        # Section title is required when there is more than one section
+        _check_length(
+            issues, f"sections[{s_index}].title", section.get("title"),
+            LIST_SECTION_TITLE_MAX, required=len(sections) > 1,
+        )
+        for r_index, row in enumerate(section.get("rows", [])):
+            prefix = f"sections[{s_index}].rows[{r_index}]"
+            _check_length(issues, f"{prefix}.title", row.get("title"), LIST_ROW_TITLE_MAX, required=True)
+            _check_length(issues, f"{prefix}.description", row.get("description"), LIST_ROW_DESCRIPTION_MAX)
+            row_id = row.get("id")
+            if row_id:
+                _check_length(issues, f"{prefix}.id", str(row_id), LIST_ROW_ID_MAX)
+                if row_id in seen_ids:
+                    issues.append(MessageLimitIssue(f"{prefix}.id", "is duplicated", 1, 2))
+                seen_ids.add(row_id)
+
+    return issues
+
+
+def validate_cta_url(
+    body: Optional[str],
+    display_text: Optional[str],
+    url: Optional[str],
+    header: Optional[str] = None,
+    footer: Optional[str] = None,
+) -> List[MessageLimitIssue]:
+    """Call-to-action URL button message (one button opening an http(s) link)"""
+    issues: List[MessageLimitIssue] = []
+    _check_length(issues, "body", body, INTERACTIVE_BODY_MAX, required=True)
+    _check_length(issues, "header", header, INTERACTIVE_HEADER_MAX)
+    _check_length(issues, "footer", footer, INTERACTIVE_FOOTER_MAX)
+    _check_length(issues, "display_text", display_text, CTA_DISPLAY_TEXT_MAX, required=True)
+    if not is_valid_preview_url(url or ""):
+        issues.append(MessageLimitIssue("url", "must be an absolute http(s) URL", 0, 0))
+    return issues
+
+
+def validate_outbound_message(message_type: str, content: Dict[str, Any]) -> List[MessageLimitIssue]:
+    """
+    Validate the content of an outbound message (send endpoint format)
+
+    Args:
+        message_type: text, image, video, document, audio, template, interactive, cta_url, address
+        content: {"text"} | {"url", "caption"} | {"body", "buttons", ...} | {"body", "button", "sections", ...}
+            | {"body", "display_text", "url", ...} | {"text", "country", ...}
+
+    Returns:
+        List of violations (empty when valid)
+    """
+    content = content or {}
+
+    if message_type == "text":
+        return validate_text(content.get("text"))
+
+    if message_type in ("image", "video", "document"):
+        return validate_caption(content.get("caption"))
+
+    if message_type == "interactive":
+        if "sections" in content:
+            return validate_list(
+                content.get("body"), content.get("button"), content.get("sections", []),
+                header=content.get("header"), footer=content.get("footer"),
+            )
+        return validate_buttons(
+            content.get("body"), content.get("buttons", []),
+            header=content.get("header"), footer=content.get("footer"),
+        )
+
+    if message_type == "cta_url":
+        return validate_cta_url(
+            content.get("body"), content.get("display_text"), content.get("url"),
+            header=content.get("header"), footer=content.get("footer"),
+        )
+
+    if message_type == "address":
+        issues: List[MessageLimitIssue] = []
+        _check_length(issues, "text", content.get("text"), INTERACTIVE_BODY_MAX, required=True)
+        _check_length(issues, "country", content.get("country"), 2, required=True)
+        return issues
+
+    return []
+
+
+def ensure_valid_outbound_message(message_type: str, content: Dict[str, Any]) -> None:
+    """
+    Raises:
+        OutboundMessageError: With every violation found
+    """
+    issues = validate_outbound_message(message_type, content)
+    if issues:
+        raise OutboundMessageError(issues)
@@ -17,19 +17,29 @@ class NodeAvailability:
         "start",
         "message",
         "question",
+        "media_collect",
+        "location_collect",
+        "survey",
         "condition",
         "end",
         "handoff",
         "delay",
+        "wait",
         "jump",
+        "subflow",
+        "loop",
         "action",
         "api_call",
         "ai_prompt",
+        "intent",
         "database_query",
         "script",
+        "sandbox_script",
         "set_variable",
         "random",
+        "split",
         "datetime",
+        "business_hours",
         "analytics",
         "whatsapp_template",
         "interactive_buttons",
@@ -41,25 +51,35 @@ class NodeAvailability:
         "start",
         "message",
         "question",
+        "location_collect",
+        "survey",
         "condition",
         "end",
         "handoff",
         "delay",
+        "wait",
         "jump",
+        "subflow",
+        "loop",
         "action",
         "api_call",
         "ai_prompt",
+        "intent",
         "database_query",
         "script",
+        "sandbox_script",
         "set_variable",
         "random",
+        "split",
         "datetime",
+        "business_hours",
         "analytics",
     ]
 
     # Nodes EXCLUSIVE to Meta Cloud API (official)
     OFFICIAL_ONLY_NODES = [
         "whatsapp_template",  # Templates require Meta approval
+        "media_collect",  # Media is downloaded from the Graph API
     ]
 
     # Nodes that work but are EXPERIMENTAL on Evolution API
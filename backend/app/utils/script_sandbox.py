@@ -0,0 +1,243 @@
+"""
+Sandboxed script node
+
+Runs tenant snippets for transformations the expression language cannot
+express, isolated from the platform:
+
+    {
+        "nodeType": "sandbox_script",
+        "code": "total = sum(item['preco'] * item['qtd'] for item in itens)\\nreturn round(total * 0.9, 2)",
+        "outputVariable": "total_com_desconto",
+        "cpuSeconds": 1,        # optional, CPU time limit
+        "memoryMb": 64,         # optional, address space limit
+        "timeoutSeconds": 3,    # optional, wall clock limit
+        "errorHandling": {"onError": "continue", "fallbackValue": null}   # continue, stop or branch
+    }
+
+Snippets are a safe subset of Python (the body of a function that returns
+the result): no imports, no names or attributes starting with an
+underscore, no exec/eval/open, and only pure builtins. They see the session
+variables only, as names and as the "variables" dict, and the result must
+be JSON serializable.
+
+Each run is a separate interpreter process started without site packages,
+environment variables or inherited descriptors, under CPU, memory, file
+size and process limits; with no import statement and no __import__
+builtin, a snippet cannot reach the network or the filesystem.
+With onError "branch" the node has "success" and "error" outputs.
+"""
+
+import ast
+import asyncio
+import json
+import keyword
+import sys
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional
+
+SANDBOX_SCRIPT_NODE_TYPE = "sandbox_script"
+
+SUCCESS_HANDLE = "success"
+ERROR_HANDLE = "error"
+
+DEFAULT_CPU_SECONDS = 1
+DEFAULT_MEMORY_MB = 64
+DEFAULT_TIMEOUT_SECONDS = 3
+MAX_CPU_SECONDS = 5
+MAX_MEMORY_MB = 256
+MAX_TIMEOUT_SECONDS = 10
+
+MAX_CODE_LENGTH = 10_000
+MAX_OUTPUT_BYTES = 64 * 1024
+
+SAFE_BUILTINS = (
+    "abs", "all", "any", "bool", "dict", "divmod", "enumerate", "filter", "float", "int",
+    "isinstance", "len", "list", "map", "max", "min", "range", "reversed", "round", "set",
+    "sorted", "str", "sum", "tuple", "zip", "ValueError", "KeyError", "IndexError", "TypeError",
+    "ZeroDivisionError", "Exception",
+)
+
+FORBIDDEN_NAMES = frozenset({
+    "exec", "eval", "compile", "open", "input", "breakpoint", "globals", "locals", "vars",
+    "getattr", "setattr", "delattr", "type", "object", "memoryview", "help", "exit", "quit",
+})
+
+# Attributes that reach object internals through strings
+FORBIDDEN_ATTRIBUTES = frozenset({"format", "format_map", "mro", "gi_frame", "f_globals"})
+
+# Runs in the child process: reads {code, variables} from stdin, writes {ok, result|error}
+_RUNNER = """
+import json, sys
+builtins = __builtins__ if isinstance(__builtins__, dict) else __builtins__.__dict__
+request = json.loads(sys.stdin.read())
+safe = {name: builtins[name] for name in request["builtins"]}
+namespace = {"__builtins__": safe, "variables": request["variables"]}
+namespace.update(request["names"])
+try:
+    exec(request["source"], namespace)
+    result = namespace["__script__"]()
+    output = json.dumps({"ok": True, "result": result})
+except BaseException as error:
+    output = json.dumps({"ok": False, "error": f"{type(error).__name__}: {error}"})
+sys.stdout.write(output)
+"""
+
+
+@dataclass
+class SandboxLimits:
+    """Resource limits of one script run"""
+
+    cpu_seconds: int = DEFAULT_CPU_SECONDS
+    memory_mb: int = DEFAULT_MEMORY_MB
+    timeout_seconds: float = DEFAULT_TIMEOUT_SECONDS
+
+    @classmethod
+    def from_node(cls, node_data: Dict[str, Any]) -> "SandboxLimits":
+        """Limits of a node, clamped to the platform maximums"""
+        return cls(
+            cpu_seconds=_clamp(node_data.get("cpuSeconds"), DEFAULT_CPU_SECONDS, MAX_CPU_SECONDS),
+            memory_mb=_clamp(node_data.get("memoryMb"), DEFAULT_MEMORY_MB, MAX_MEMORY_MB),
+            timeout_seconds=_clamp(node_data.get("timeoutSeconds"), DEFAULT_TIMEOUT_SECONDS, MAX_TIMEOUT_SECONDS),
+        )
+
+
+@dataclass
+class ScriptResult:
+    """Outcome of a script run"""
+
+    ok: bool
+    result: Any = None
+    error: Optional[str] = None
+
+
+class ScriptError(Exception):
+    """Script rejected before running"""
+
+
+def validate_script(code: str) -> List[str]:
+    """
+    Problems of a snippet (syntax and forbidden constructs)
+
+    Returns:
+        Error messages (empty when the snippet can run)
+    """
+    if not code or not code.strip():
+        return ["Script is empty"]
+    if len(code) > MAX_CODE_LENGTH:
+        return [f"Script is longer than {MAX_CODE_LENGTH} characters"]
+    try:
+        tree = ast.parse(_function_source(code))
+    except SyntaxError as e:
+        return [f"Syntax error on line {max((e.lineno or 2) - 1, 1)}: {e.msg}"]
+
+    errors = []
+    for node in ast.walk(tree):
+        if isinstance(node, (ast.Import, ast.ImportFrom)):
+            errors.append("Imports are not allowed")
+        elif isinstance(node, (ast.Global, ast.Nonlocal)):
+            errors.append("global/nonlocal are not allowed")
+        elif isinstance(node, (ast.AsyncFunctionDef, ast.Await, ast.Yield, ast.YieldFrom)):
+            errors.append("async code and generators are not allowed")
+        elif isinstance(node, ast.Name) and (
+            node.id.startswith("_") or node.id in FORBIDDEN_NAMES
+        ):
+            errors.append(f"Name '{node.id}' is not allowed")
+        elif isinstance(node, ast.Attribute) and (
+            node.attr.startswith("_") or node.attr in FORBIDDEN_ATTRIBUTES
+        ):
+            errors.append(f"Attribute '{node.attr}' is not allowed")
+    return list(dict.fromkeys(errors))
+
+
+async def run_script(code: str, variables: Dict[str, Any], limits: Optional[SandboxLimits] = None) -> ScriptResult:
+    """
+    Run a snippet in a sandboxed interpreter
+
+    Raises:
+        ScriptError: If the snippet is invalid or the variables are not JSON serializable
+    """
+    errors = validate_script(code)
+    if errors:
+        raise ScriptError("; ".join(errors))
+
+    limits = limits or SandboxLimits()
+    try:
+        request = json.dumps({
+            "source": _function_source(code),
+            "builtins": list(SAFE_BUILTINS),
+            "variables": variables,
+            "names": {
+                name: value
+                for name, value in variables.items()
+                if name.isidentifier() and not name.startswith("_") and not keyword.iskeyword(name)
+            },
+        }, default=str)
+    except (TypeError, ValueError) as e:
+        raise ScriptError(f"Variables are not serializable: {e}")
+
+    process = await asyncio.create_subprocess_exec(
+        sys.executable, "-I", "-S", "-c", _RUNNER,
+        stdin=asyncio.subprocess.PIPE,
+        stdout=asyncio.subprocess.PIPE,
+        stderr=asyncio.subprocess.DEVNULL,
+        env={},
+        close_fds=True,
+        preexec_fn=lambda: _apply_limits(limits),
+    )
+    try:
+        stdout, _ = await asyncio.wait_for(process.communicate(request.encode()), timeout=limits.timeout_seconds)
+    except asyncio.TimeoutError:
+        process.kill()
+        await process.wait()
+        return ScriptResult(ok=False, error=f"Script exceeded {limits.timeout_seconds:g}s")
+
+    return parse_output(stdout, process.returncode, limits)
+
+
+def parse_output(stdout: bytes, returncode: Optional[int], limits: SandboxLimits) -> ScriptResult:
+    """Result of a run from the child's output and exit status"""
+    if len(stdout) > MAX_OUTPUT_BYTES:
+        return ScriptResult(ok=False, error=f"Script result is larger than {MAX_OUTPUT_BYTES // 1024} KB")
+    if not stdout:
+        if returncode is not None and returncode < 0:
+            return ScriptResult(ok=False, error=f"Script exceeded {limits.cpu_seconds}s of CPU")
+        return ScriptResult(ok=False, error=f"Script exceeded {limits.memory_mb} MB or crashed")
+    try:
+        data = json.loads(stdout)
+    except ValueError:
+        return ScriptResult(ok=False, error="Script output is not valid")
+    if not data.get("ok"):
+        return ScriptResult(ok=False, error=data.get("error") or "Script failed")
+    return ScriptResult(ok=True, result=data.get("result"))
+
+
+def _function_source(code: str) -> str:
+    """The snippet as the body of __script__(); a single expression is returned"""
+    lines = code.strip("\n").splitlines()
+    if len(lines) == 1 and not lines[0].lstrip().startswith("return"):
+        try:
+            ast.parse(lines[0].strip(), mode="eval")
+            lines = [f"return {lines[0].strip()}"]
+        except SyntaxError:
+            pass
+    return "def __script__():\n" + "\n".join(f"    {line}" for line in lines) + "\n"
+
+
+def _apply_limits(limits: SandboxLimits) -> None:
+    """Resource limits of the child process (runs between fork and exec)"""
+    import resource
+
+    memory = limits.memory_mb * 1024 * 1024
+    resource.setrlimit(resource.RLIMIT_CPU, (limits.cpu_seconds, limits.cpu_seconds))
+    resource.setrlimit(resource.RLIMIT_AS, (memory, memory))
+    resource.setrlimit(resource.RLIMIT_FSIZE, (0, 0))
+    resource.setrlimit(resource.RLIMIT_CORE, (0, 0))
+    resource.setrlimit(resource.RLIMIT_NPROC, (0, 0))
+
+
+def _clamp(value: Any, default: float, maximum: float):
+    try:
+        value = type(default)(value)
+    except (TypeError, ValueError):
+        return default
+    return min(value, maximum) if value > 0 else default
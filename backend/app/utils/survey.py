@@ -0,0 +1,165 @@
+"""
+Survey node (NPS / CSAT)
+
+Asks for a score, optionally a comment, and records the answer in
+survey_responses for the dashboard:
+
+    {
+        "nodeType": "survey",
+        "surveyType": "nps",            # nps (0-10) or csat (1-5)
+        "questionText": "De 0 a 10, quanto você recomendaria a gente para um amigo?",
+        "askComment": true,
+        "commentPrompt": "Quer contar o motivo da sua nota? (ou responda \"pular\")",
+        "outputVariable": "nps",
+        "errorMessage": "Responda com um número de 0 a 10.",
+        "maxAttempts": 3,
+        "maxAttemptsMessage": "Tudo bem, seguimos sem a avaliação."
+    }
+
+Scores are read from the answer text ("9", "nota 9", "9/10", "9️⃣"). The
+variable gets {"score": 9, "category": "promoter", "comment": "..."}.
+
+After the answer the node follows the output named after the category when
+connected ("promoter" / "passive" / "detractor" for NPS, "satisfied" /
+"neutral" / "dissatisfied" for CSAT), otherwise its default output. Invalid
+scores are asked again until maxAttempts; then the node follows its
+"invalid" output when connected, otherwise the default output.
+
+NPS is the percentage of promoters (9-10) minus the percentage of
+detractors (0-6); CSAT is the percentage of satisfied answers (4-5).
+"""
+
+import re
+from dataclasses import dataclass
+from typing import Any, Dict, List, Mapping, Optional
+
+SURVEY_NODE_TYPE = "survey"
+
+NPS = "nps"
+CSAT = "csat"
+
+# Accepted score range per survey type
+SCORE_RANGES = {NPS: (0, 10), CSAT: (1, 5)}
+
+PROMOTER, PASSIVE, DETRACTOR = "promoter", "passive", "detractor"
+SATISFIED, NEUTRAL, DISSATISFIED = "satisfied", "neutral", "dissatisfied"
+# Node outputs followed only for their category
+CATEGORY_HANDLES = (PROMOTER, PASSIVE, DETRACTOR, SATISFIED, NEUTRAL, DISSATISFIED)
+
+DEFAULT_QUESTIONS = {
+    NPS: "De 0 a 10, quanto você recomendaria a gente para um amigo?",
+    CSAT: "De 1 a 5, como você avalia o atendimento?",
+}
+DEFAULT_COMMENT_PROMPT = "Quer deixar um comentário sobre a sua nota? Se não, responda \"pular\"."
+DEFAULT_SKIP_KEYWORDS = ("pular", "não", "nao", "n", "skip", "-")
+MAX_COMMENT_LENGTH = 2000
+
+_KEYCAPS = {"🔟": "10", **{f"{digit}️⃣": str(digit) for digit in range(10)}}
+_NUMBER = re.compile(r"(?<![\d,.])(\d{1,2})(?!\d|[,.]\d)")
+
+
+@dataclass
+class SurveySummary:
+    """Scores of a set of survey answers"""
+
+    responses: int = 0
+    average_score: Optional[float] = None
+    promoters: int = 0  # satisfied answers for CSAT
+    passives: int = 0  # neutral answers for CSAT
+    detractors: int = 0  # dissatisfied answers for CSAT
+    score: Optional[float] = None  # NPS (-100 to 100) or CSAT (0 to 100)
+
+
+def survey_type(node_data: Dict[str, Any]) -> str:
+    """Survey type of a node (NPS unless set to CSAT)"""
+    return CSAT if str(node_data.get("surveyType") or NPS).lower() == CSAT else NPS
+
+
+def parse_score(text: Optional[str], kind: str) -> Optional[int]:
+    """
+    Score in an answer, None when there is no single score in range
+
+    Accepts a bare number, a number inside a sentence ("nota 9"), "9/10"
+    and keycap emojis.
+    """
+    if not text:
+        return None
+    for keycap, digits in _KEYCAPS.items():
+        text = text.replace(keycap, f" {digits} ")
+    text = re.sub(r"/\s*(10|5)\b", "", text)  # "9/10", "4 / 5"
+
+    numbers = {int(match) for match in _NUMBER.findall(text)}
+    if len(numbers) != 1:
+        return None
+    score = numbers.pop()
+    low, high = SCORE_RANGES[kind]
+    return score if low <= score <= high else None
+
+
+def classify(score: int, kind: str) -> str:
+    """Category of a score (also the node output followed)"""
+    if kind == CSAT:
+        return SATISFIED if score >= 4 else NEUTRAL if score == 3 else DISSATISFIED
+    return PROMOTER if score >= 9 else PASSIVE if score >= 7 else DETRACTOR
+
+
+def is_skip(text: Optional[str], node_data: Dict[str, Any]) -> bool:
+    """Whether the contact declined to leave a comment"""
+    keywords = node_data.get("skipKeywords") or DEFAULT_SKIP_KEYWORDS
+    return (text or "").strip().lower() in {str(keyword).strip().lower() for keyword in keywords}
+
+
+def clean_comment(text: Optional[str]) -> Optional[str]:
+    """Comment as stored (trimmed, None when empty)"""
+    comment = (text or "").strip()[:MAX_COMMENT_LENGTH]
+    return comment or None
+
+
+def summarize(kind: str, score_counts: Mapping[int, int]) -> SurveySummary:
+    """
+    NPS or CSAT of answers grouped by score
+
+    Args:
+        kind: nps or csat
+        score_counts: Number of answers per score
+    """
+    summary = SurveySummary()
+    total = 0
+    for score, count in score_counts.items():
+        if not count:
+            continue
+        summary.responses += count
+        total += score * count
+        category = classify(score, kind)
+        if category in (PROMOTER, SATISFIED):
+            summary.promoters += count
+        elif category in (PASSIVE, NEUTRAL):
+            summary.passives += count
+        else:
+            summary.detractors += count
+
+    if summary.responses:
+        summary.average_score = round(total / summary.responses, 2)
+        promoters = summary.promoters / summary.responses * 100
+        if kind == CSAT:
+            summary.score = round(promoters, 2)
+        else:
+            summary.score = round(promoters - summary.detractors / summary.responses * 100, 2)
+    return summary
+
+
+def check_survey_config(node_data: Dict[str, Any]) -> List[str]:
+    """
+    Problems of a survey node's settings
+
+    Returns:
+        Error messages (empty when the settings are usable)
+    """
+    errors = []
+    kind = node_data.get("surveyType")
+    if kind is not None and str(kind).lower() not in SCORE_RANGES:
+        errors.append(f"Unknown survey type '{kind}' (use nps or csat)")
+    skip_keywords = node_data.get("skipKeywords")
+    if skip_keywords is not None and not isinstance(skip_keywords, list):
+        errors.append("skipKeywords must be a list")
+    return errors
@@ -0,0 +1,300 @@
+"""
+Typed template message builder
+
+Builds the components of a template message and checks every parameter
+against the placeholders the template declares before anything is sent:
+
+    template = (
+        TemplateBuilder.for_template(whatsapp_template)   # or TemplateBuilder("order_update", "pt_BR", body_placeholders=2)
+        .header_image("https://cdn.example.com/pedido.jpg")
+        .body_text("Maria")
+        .body_currency("R$ 99,90", "BRL", 99.90)
+        .quick_reply(0, "track-123")
+    )
+    await api.send_template(contact.whatsapp_id, template)
+
+build() raises TemplateBuildError listing every problem (missing or extra
+parameters, wrong parameter type for the header format or button type,
+invalid text) so callers get one clear error instead of a Meta rejection.
+"""
+
+import re
+from dataclasses import dataclass
+from datetime import datetime
+from typing import TYPE_CHECKING, Any, Dict, List, Optional, Union
+
+from app.utils.template_carousel import build_carousel_component
+from app.utils.template_components import CopyCodeButton, LimitedTimeOffer
+
+if TYPE_CHECKING:
+    from app.models.whatsapp_number import WhatsAppTemplate
+
+HEADER_FORMATS = {"TEXT", "IMAGE", "VIDEO", "DOCUMENT"}
+
+# Meta rejects parameters with new lines, tabs or more than 4 consecutive spaces
+INVALID_PARAMETER_PATTERN = re.compile(r"[\n\t]| {5,}")
+
+# Parameter type expected by each button type
+BUTTON_PARAMETER_TYPES = {"QUICK_REPLY": "payload", "URL": "text", "COPY_CODE": "coupon_code"}
+
+
+@dataclass(frozen=True)
+class TemplateParameterIssue:
+    """A parameter that does not match the template"""
+
+    field: str
+    message: str
+
+    def __str__(self) -> str:
+        return f"{self.field}: {self.message}"
+
+
+class TemplateBuildError(ValueError):
+    """Template parameters do not match the declared placeholders"""
+
+    def __init__(self, template_name: str, issues: List[TemplateParameterIssue]):
+        self.template_name = template_name
+        self.issues = issues
+        super().__init__(f"Template '{template_name}': " + "; ".join(str(issue) for issue in issues))
+
+
+class TemplateBuilder:
+    """Fluent builder of template message components"""
+
+    def __init__(
+        self,
+        name: str,
+        language: str = "pt_BR",
+        header_format: Optional[str] = None,
+        header_placeholders: int = 0,
+        body_placeholders: int = 0,
+        buttons: Optional[List[str]] = None,
+    ):
+        """
+        Args:
+            name: Template name
+            language: Language code
+            header_format: TEXT, IMAGE, VIDEO or DOCUMENT (None if the template has no header)
+            header_placeholders: {{n}} placeholders of a TEXT header
+            body_placeholders: {{n}} placeholders of the body
+            buttons: Button types in order (QUICK_REPLY, URL, COPY_CODE, PHONE_NUMBER...)
+        """
+        self.name = name
+        self.language = language
+        self.header_format = (header_format or "").upper() or None
+        self.header_placeholders = header_placeholders
+        self.body_placeholders = body_placeholders
+        self.buttons = [str(button).upper() for button in buttons or []]
+
+        self._header: List[Dict[str, Any]] = []
+        self._body: List[Dict[str, Any]] = []
+        self._buttons: Dict[int, Dict[str, Any]] = {}
+        self._extra: List[Dict[str, Any]] = []
+
+    @classmethod
+    def for_template(cls, template: "WhatsAppTemplate", language: Optional[str] = None) -> "TemplateBuilder":
+        """Builder with the placeholders of a synced template"""
+        return cls(
+            template.name,
+            language or template.language,
+            header_format=template.header_type,
+            header_placeholders=template.header_variables_count or 0,
+            body_placeholders=template.body_variables_count or 0,
+            buttons=[button.get("type", "") for button in template.buttons or []],
+        )
+
+    # ============================================
+    # HEADER
+    # ============================================
+
+    def header_text(self, text: Any) -> "TemplateBuilder":
+        self._header.append({"type": "text", "text": _text(text)})
+        return self
+
+    def header_image(self, link: Optional[str] = None, media_id: Optional[str] = None) -> "TemplateBuilder":
+        return self._header_media("image", link, media_id)
+
+    def header_video(self, link: Optional[str] = None, media_id: Optional[str] = None) -> "TemplateBuilder":
+        return self._header_media("video", link, media_id)
+
+    def header_document(
+        self, link: Optional[str] = None, media_id: Optional[str] = None, filename: Optional[str] = None
+    ) -> "TemplateBuilder":
+        self._header_media("document", link, media_id)
+        if filename:
+            self._header[-1]["document"]["filename"] = filename
+        return self
+
+    def _header_media(self, media_type: str, link: Optional[str], media_id: Optional[str]) -> "TemplateBuilder":
+        media = {"id": media_id} if media_id else {"link": link}
+        self._header.append({"type": media_type, media_type: media})
+        return self
+
+    # ============================================
+    # BODY
+    # ============================================
+
+    def body_text(self, *values: Any) -> "TemplateBuilder":
+        """Text parameters, in placeholder order"""
+        self._body.extend({"type": "text", "text": _text(value)} for value in values)
+        return self
+
+    def body_currency(self, fallback_value: str, code: str, amount: float) -> "TemplateBuilder":
+        """Currency parameter (amount in units, e.g. 99.90; sent as amount_1000)"""
+        self._body.append({
+            "type": "currency",
+            "currency": {"fallback_value": fallback_value, "code": code.upper(), "amount_1000": round(amount * 1000)},
+        })
+        return self
+
+    def body_date_time(self, fallback_value: Union[str, datetime]) -> "TemplateBuilder":
+        """Date/time parameter (datetimes are shown as dd/mm/yyyy HH:MM)"""
+        if isinstance(fallback_value, datetime):
+            fallback_value = fallback_value.strftime("%d/%m/%Y %H:%M")
+        self._body.append({"type": "date_time", "date_time": {"fallback_value": fallback_value}})
+        return self
+
+    # ============================================
+    # BUTTONS AND EXTRA COMPONENTS
+    # ============================================
+
+    def quick_reply(self, index: int, payload: str) -> "TemplateBuilder":
+        self._buttons[index] = {"type": "payload", "payload": payload}
+        return self
+
+    def url_button(self, index: int, text: Any) -> "TemplateBuilder":
+        """Value of the {{1}} suffix of a dynamic URL button"""
+        self._buttons[index] = {"type": "text", "text": _text(text)}
+        return self
+
+    def coupon_code(self, code: str, index: Optional[int] = None) -> "TemplateBuilder":
+        """Code of the COPY_CODE button (index defaults to the template's COPY_CODE button)"""
+        if index is None:
+            index = self.buttons.index("COPY_CODE") if "COPY_CODE" in self.buttons else 0
+        self._buttons[index] = CopyCodeButton(code=code, index=index).to_component()["parameters"][0]
+        return self
+
+    def limited_time_offer(self, expires_at: Union[datetime, str, int]) -> "TemplateBuilder":
+        self._extra.append(LimitedTimeOffer.parse(expires_at).to_component())
+        return self
+
+    def carousel(self, cards: List[Dict[str, Any]], definition: Optional[List[Dict[str, Any]]] = None) -> "TemplateBuilder":
+        """Carousel cards (see app.utils.template_carousel)"""
+        self._extra.append(build_carousel_component(cards, definition))
+        return self
+
+    # ============================================
+    # BUILD
+    # ============================================
+
+    def validate(self) -> List[TemplateParameterIssue]:
+        """Every mismatch between the parameters and the declared placeholders"""
+        issues: List[TemplateParameterIssue] = []
+
+        # Header
+        if self.header_format == "TEXT":
+            _check_count(issues, "header", self.header_placeholders, self._header)
+            _check_types(issues, "header", self._header, {"text"})
+        elif self.header_format in HEADER_FORMATS:
+            media_type = self.header_format.lower()
+            if not self._header:
+                issues.append(TemplateParameterIssue("header", f"{self.header_format} header needs a media link or id"))
+            elif len(self._header) > 1:
+                issues.append(TemplateParameterIssue("header", "media headers take a single parameter"))
+            elif self._header[0]["type"] != media_type:
+                issues.append(TemplateParameterIssue(
+                    "header", f"expected a {media_type} but got a {self._header[0]['type']}"
+                ))
+            else:
+                media = self._header[0][media_type]
+                if not media.get("id") and not str(media.get("link") or "").startswith("https://"):
+                    issues.append(TemplateParameterIssue("header", "media link must be a public HTTPS URL"))
+        elif self._header:
+            issues.append(TemplateParameterIssue("header", "template has no header parameters"))
+
+        # Body
+        _check_count(issues, "body", self.body_placeholders, self._body)
+        _check_types(issues, "body", self._body, {"text", "currency", "date_time"})
+
+        # Buttons
+        for index, parameter in sorted(self._buttons.items()):
+            field = f"buttons[{index}]"
+            if index >= len(self.buttons):
+                issues.append(TemplateParameterIssue(field, f"template has {len(self.buttons)} button(s)"))
+                continue
+            button_type = self.buttons[index]
+            expected = BUTTON_PARAMETER_TYPES.get(button_type)
+            if expected is None:
+                issues.append(TemplateParameterIssue(field, f"{button_type} buttons take no parameters"))
+            elif parameter["type"] != expected:
+                issues.append(TemplateParameterIssue(
+                    field, f"{button_type} button expects a {expected} but got a {parameter['type']}"
+                ))
+            elif not str(parameter.get(expected) or "").strip():
+                issues.append(TemplateParameterIssue(field, f"{expected} is empty"))
+        for index, button_type in enumerate(self.buttons):
+            if button_type in ("QUICK_REPLY", "COPY_CODE") and index not in self._buttons:
+                issues.append(TemplateParameterIssue(
+                    f"buttons[{index}]", f"{button_type} button needs a {BUTTON_PARAMETER_TYPES[button_type]}"
+                ))
+
+        coupon = next((p for p in self._buttons.values() if p["type"] == "coupon_code"), None)
+        if coupon:
+            problem = CopyCodeButton(code=coupon["coupon_code"]).validate()
+            if problem:
+                issues.append(TemplateParameterIssue("buttons", problem))
+
+        return issues
+
+    def build(self) -> List[Dict[str, Any]]:
+        """
+        Components of the message
+
+        Raises:
+            TemplateBuildError: If a parameter does not match the template
+        """
+        issues = self.validate()
+        if issues:
+            raise TemplateBuildError(self.name, issues)
+
+        components: List[Dict[str, Any]] = []
+        if self._header:
+            components.append({"type": "header", "parameters": self._header})
+        if self._body:
+            components.append({"type": "body", "parameters": self._body})
+        components.extend(self._extra)
+        for index, parameter in sorted(self._buttons.items()):
+            sub_type = {"payload": "quick_reply", "text": "url", "coupon_code": "copy_code"}[parameter["type"]]
+            components.append({"type": "button", "sub_type": sub_type, "index": str(index), "parameters": [parameter]})
+        return components
+
+
+def _text(value: Any) -> str:
+    return value if isinstance(value, str) else str(value)
+
+
+def _check_count(
+    issues: List[TemplateParameterIssue], section: str, expected: int, parameters: List[Dict[str, Any]]
+) -> None:
+    if len(parameters) != expected:
+        issues.append(TemplateParameterIssue(
+            section, f"template declares {expected} placeholder(s) but {len(parameters)} parameter(s) were given"
+        ))
+
+
+def _check_types(
+    issues: List[TemplateParameterIssue], section: str, parameters: List[Dict[str, Any]], allowed: set
+) -> None:
+    for index, parameter in enumerate(parameters, start=1):
+        field = f"{section}[{index}]"
+        if parameter["type"] not in allowed:
+            issues.append(TemplateParameterIssue(field, f"{parameter['type']} parameters are not allowed in the {section}"))
+        elif parameter["type"] == "text":
+            if not parameter["text"].strip():
+                issues.append(TemplateParameterIssue(field, f"{{{{{index}}}}} is empty"))
+            elif INVALID_PARAMETER_PATTERN.search(parameter["text"]):
+                issues.append(TemplateParameterIssue(
+                    field, f"{{{{{index}}}}} cannot contain new lines, tabs or more than 4 consecutive spaces"
+                ))
+        elif not str(parameter[parameter["type"]].get("fallback_value") or "").strip():
+            issues.append(TemplateParameterIssue(field, f"{{{{{index}}}}} needs a fallback_value"))
@@ -0,0 +1,125 @@
+"""
+Media card carousel templates
+
+A carousel template is a message bubble followed by 2-10 cards. Every card
+has an IMAGE or VIDEO header, a body with its own {{n}} placeholders and up
+to two buttons (QUICK_REPLY, or URL with an optional {{1}} suffix), and all
+cards of a template share the same layout.
+
+The layout is read from the CAROUSEL component synced from Meta
+(carousel_definition) and the values of each card are serialized into the
+``carousel`` component sent with the message (build_carousel_component):
+
+    components = [
+        {"type": "body", "parameters": [{"type": "text", "text": "Maria"}]},
+        build_carousel_component([
+            {"header_url": "https://cdn.example.com/1.jpg", "body": ["R$ 99"], "buttons": [{"payload": "buy-1"}]},
+            {"header_url": "https://cdn.example.com/2.jpg", "body": ["R$ 79"], "buttons": [{"payload": "buy-2"}]},
+        ], carousel_definition(template_components)),
+    ]
+    await api.send_template_message(to, "summer_sale", "pt_BR", components=components)
+
+Card values use the form of ``Campaign.template_variables["cards"]``:
+    {"header_url": "https://..." | "header_id": "<media id>", "body": [...],
+     "buttons": [{"payload": "..."} | {"text": "<url suffix>"}, ...]}
+"""
+
+import re
+from typing import Any, Dict, List, Optional
+
+CAROUSEL_MIN_CARDS = 2
+CAROUSEL_MAX_CARDS = 10
+CAROUSEL_MAX_BUTTONS = 2
+
+CAROUSEL_HEADER_FORMATS = {"IMAGE", "VIDEO"}
+
+_VARIABLE = re.compile(r"\{\{(\d+)\}\}")
+
+
+def carousel_definition(components: Optional[List[Dict[str, Any]]]) -> List[Dict[str, Any]]:
+    """
+    Card layout of a template, from its components as returned by Meta
+
+    Returns:
+        One entry per card (empty if the template has no CAROUSEL component):
+        {"header_format": "IMAGE", "body_variables_count": 1,
+         "buttons": [{"type": "QUICK_REPLY", "has_variable": False}, {"type": "URL", "has_variable": True}]}
+    """
+    carousel = next(
+        (c for c in components or [] if str(c.get("type", "")).upper() == "CAROUSEL"), None
+    )
+    if not carousel:
+        return []
+
+    cards = []
+    for card in carousel.get("cards") or []:
+        definition = {"header_format": None, "body_variables_count": 0, "buttons": []}
+        for component in card.get("components") or []:
+            component_type = str(component.get("type", "")).upper()
+            if component_type == "HEADER":
+                definition["header_format"] = str(component.get("format") or "").upper() or None
+            elif component_type == "BODY":
+                definition["body_variables_count"] = len(set(_VARIABLE.findall(component.get("text") or "")))
+            elif component_type == "BUTTONS":
+                definition["buttons"] = [
+                    {
+                        "type": str(button.get("type", "")).upper(),
+                        "has_variable": bool(_VARIABLE.search(button.get("url") or "")),
+                    }
+                    for button in component.get("buttons") or []
+                ]
+        cards.append(definition)
+    return cards
+
+
+def build_carousel_component(
+    cards: List[Dict[str, Any]],
+    definition: Optional[List[Dict[str, Any]]] = None,
+) -> Dict[str, Any]:
+    """
+    ``carousel`` component of a template message
+
+    Args:
+        cards: Values of each card, in order
+        definition: Card layout (carousel_definition). Gives the header media
+            type and button types; without it headers are images and buttons
+            are quick replies unless they only carry "text".
+
+    Returns:
+        {"type": "carousel", "cards": [{"card_index": 0, "components": [...]}, ...]}
+    """
+    serialized = []
+    for index, card in enumerate(cards):
+        layout = definition[index] if definition and index < len(definition) else {}
+        components: List[Dict[str, Any]] = []
+
+        media_type = str(layout.get("header_format") or card.get("header_type") or "IMAGE").lower()
+        media = {"id": card["header_id"]} if card.get("header_id") else {"link": card.get("header_url")}
+        components.append({"type": "header", "parameters": [{"type": media_type, media_type: media}]})
+
+        if card.get("body"):
+            components.append({
+                "type": "body",
+                "parameters": [{"type": "text", "text": str(value)} for value in card["body"]],
+            })
+
+        button_layouts = layout.get("buttons") or []
+        for button_index, button in enumerate(card.get("buttons") or []):
+            button_layout = button_layouts[button_index] if button_index < len(button_layouts) else {}
+            button_type = button_layout.get("type") or ("URL" if "payload" not in button else "QUICK_REPLY")
+            if button_type == "URL":
+                if not button_layout.get("has_variable", True) or not button.get("text"):
+                    continue
+                parameter = {"type": "text", "text": str(button["text"])}
+            else:
+                parameter = {"type": "payload", "payload": str(button.get("payload") or "")}
+            components.append({
+                "type": "button",
+                "sub_type": "url" if button_type == "URL" else "quick_reply",
+                "index": str(button_index),
+                "parameters": [parameter],
+            })
+
+        serialized.append({"card_index": index, "components": components})
+
+    return {"type": "carousel", "cards": serialized}
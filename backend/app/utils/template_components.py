@@ -0,0 +1,111 @@
+"""
+Promotional template components (coupon codes, limited-time offers)
+
+Typed builders for the message side of promotional templates, so campaigns
+and callers don't hand-build component JSON:
+
+    components = [
+        {"type": "body", "parameters": [{"type": "text", "text": "Maria"}]},
+        LimitedTimeOffer(expires_at=datetime(2025, 11, 30, 23, 59, tzinfo=timezone.utc)).to_component(),
+        CopyCodeButton(code="BLACK20", index=copy_code_button_index(template.buttons)).to_component(),
+    ]
+    await api.send_template_message(to, "black_friday", "pt_BR", components=components)
+
+The template side is synced from Meta: COPY_CODE buttons are kept in
+WhatsAppTemplate.buttons and the LIMITED_TIME_OFFER component in
+WhatsAppTemplate.limited_time_offer.
+"""
+
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from typing import Any, Dict, List, Optional, Union
+
+COUPON_CODE_MAX = 15
+
+
+@dataclass(frozen=True)
+class CopyCodeButton:
+    """Coupon code copied to the clipboard by a COPY_CODE button"""
+
+    code: str
+    index: int = 0
+
+    def validate(self) -> Optional[str]:
+        """Problem with the code, or None when Meta will accept it"""
+        code = (self.code or "").strip()
+        if not code:
+            return "coupon code is empty"
+        if len(code) > COUPON_CODE_MAX:
+            return f"coupon code has {len(code)} characters (max {COUPON_CODE_MAX})"
+        if any(char.isspace() for char in code):
+            return "coupon code cannot contain spaces"
+        return None
+
+    def to_component(self) -> Dict[str, Any]:
+        return {
+            "type": "button",
+            "sub_type": "copy_code",
+            "index": str(self.index),
+            "parameters": [{"type": "coupon_code", "coupon_code": self.code.strip()}],
+        }
+
+
+@dataclass(frozen=True)
+class LimitedTimeOffer:
+    """Expiration of a limited-time-offer template (countdown shown by WhatsApp)"""
+
+    expires_at: datetime
+
+    @classmethod
+    def parse(cls, value: Union[datetime, str, int, float]) -> "LimitedTimeOffer":
+        """
+        From a datetime, an ISO 8601 string or a Unix timestamp in milliseconds
+
+        Naive datetimes are taken as UTC.
+
+        Raises:
+            ValueError: If the value is not a date
+        """
+        if isinstance(value, bool):
+            raise ValueError(f"Invalid offer expiration: {value!r}")
+        if isinstance(value, (int, float)):
+            expires_at = datetime.fromtimestamp(value / 1000, tz=timezone.utc)
+        elif isinstance(value, datetime):
+            expires_at = value
+        elif isinstance(value, str) and value.strip():
+            expires_at = datetime.fromisoformat(value.strip().replace("Z", "+00:00"))
+        else:
+            raise ValueError(f"Invalid offer expiration: {value!r}")
+
+        if expires_at.tzinfo is None:
+            expires_at = expires_at.replace(tzinfo=timezone.utc)
+        return cls(expires_at=expires_at)
+
+    @property
+    def expiration_time_ms(self) -> int:
+        return int(self.expires_at.timestamp() * 1000)
+
+    def to_component(self) -> Dict[str, Any]:
+        return {
+            "type": "limited_time_offer",
+            "parameters": [{
+                "type": "limited_time_offer",
+                "limited_time_offer": {"expiration_time_ms": self.expiration_time_ms},
+            }],
+        }
+
+
+def copy_code_button_index(buttons: Optional[List[Dict[str, Any]]]) -> Optional[int]:
+    """Position of the COPY_CODE button among a template's buttons (None if it has none)"""
+    for index, button in enumerate(buttons or []):
+        if str(button.get("type", "")).upper() == "COPY_CODE":
+            return index
+    return None
+
+
+def limited_time_offer_definition(components: Optional[List[Dict[str, Any]]]) -> Optional[Dict[str, Any]]:
+    """LIMITED_TIME_OFFER settings of a template definition, e.g. {"text": "...", "has_expiration": True}"""
+    for component in components or []:
+        if str(component.get("type", "")).upper() == "LIMITED_TIME_OFFER":
+            return dict(component.get("limited_time_offer") or {})
+    return None
@@ -0,0 +1,26 @@
+"""URL helpers for outbound messages (link previews)."""
+import re
+from typing import List
+from urllib.parse import urlparse
+
+# http(s) URLs and bare www. hosts, as WhatsApp detects them in message bodies
+URL_PATTERN = re.compile(r"(?:https?://|www\.)[^\s<>\"']+", re.IGNORECASE)
+
+# Punctuation that usually closes a sentence rather than belonging to the URL
+_TRAILING_PUNCTUATION = ".,;:!?)]}"
+
+
+def extract_urls(text: str) -> List[str]:
+    """Return the URLs found in a message body, in order of appearance."""
+    if not text:
+        return []
+    return [match.rstrip(_TRAILING_PUNCTUATION) for match in URL_PATTERN.findall(text)]
+
+
+def is_valid_preview_url(url: str) -> bool:
+    """Check that a URL can produce a link preview (absolute http/https with a host)."""
+    if not url or any(char.isspace() for char in url):
+        return False
+
+    parsed = urlparse(url)
+    return parsed.scheme in ("http", "https") and "." in parsed.netloc
@@ -0,0 +1,315 @@
+"""
+WebSocket Command Channel
+
+Lets agents act on conversations over the Socket.IO connection instead of REST.
+
+Client -> Server (with acknowledgement callback):
+    socket.emit("command", {
+        "id": "tmp-1700000000-1",          # optimistic id generated by the UI
+        "command": "send_message",         # send_message | mark_read | typing | assign
+        "payload": {"conversation_id": "uuid", "message_type": "text", "content": {"text": "Oi"}}
+    }, (ack) => ...)
+
+Acknowledgement:
+    {"id": "tmp-...", "command": "send_message", "ok": true, "data": {...message...}}
+    {"id": "tmp-...", "command": "send_message", "ok": false, "error": {"code": 400, "message": "..."}}
+
+Reconciliation:
+- send_message stores the optimistic id as client_message_id and echoes it in
+  the ack and in the "message:new" event, so every tab of the sender can
+  replace the pending bubble with the server message
+- A retried command with the same id (e.g. ack lost on reconnect) returns the
+  cached ack instead of executing twice
+
+Authorization:
+- Every command resolves the caller from the connection's access token like
+  REST requests (AuthService.get_current_user): deactivated users, removed
+  workspace memberships and role changes apply to the next command
+- A connection whose token no longer resolves (401) is closed, leaving the
+  organization room; reconnecting with that token is rejected
+- Only roles that can access conversations (agents and admins) run commands
+"""
+
+import json
+import logging
+from dataclasses import dataclass
+from typing import Any, Awaitable, Callable, Dict, Optional
+from uuid import UUID
+
+from fastapi import HTTPException, status
+from pydantic import ValidationError
+
+from app.core.database import async_session
+from app.core.redis import redis_client
+from app.schemas.websocket import (
+    AssignCommand,
+    MarkReadCommand,
+    SendMessageCommand,
+    TypingCommand,
+    WSCommand,
+    WSCommandAck,
+    WSCommandError,
+    WSCommandType,
+)
+from .manager import sio
+
+logger = logging.getLogger(__name__)
+
+# How long acks are kept for retried commands
+ACK_TTL_SECONDS = 300
+ACK_KEY = "ws:ack:{user_id}:{command_id}"
+PENDING = "pending"
+
+# Commands that change state and must not run twice for the same id
+IDEMPOTENT_COMMANDS = {WSCommandType.SEND_MESSAGE, WSCommandType.ASSIGN}
+
+
+@dataclass
+class CommandContext:
+    """Authenticated caller of a command"""
+
+    sid: str
+    user_id: UUID
+    organization_id: UUID
+    role: Optional[str] = None
+
+
+async def resolve_context(sid: str, token: str) -> CommandContext:
+    """
+    Caller of a command, resolved from the access token on every command
+
+    Raises:
+        HTTPException: If the token is no longer valid for an active user and
+            workspace (401), or the role cannot act on conversations (403)
+    """
+    from app.services.auth_service import AuthService
+
+    async with async_session() as db:
+        user = await AuthService(db).get_current_user(token)
+
+    if not user.can_access_conversations:
+        raise HTTPException(
+            status_code=status.HTTP_403_FORBIDDEN,
+            detail="Insufficient permissions. Required roles: super_admin, org_admin, agent",
+        )
+
+    return CommandContext(
+        sid=sid,
+        user_id=user.id,
+        organization_id=user.organization_id,
+        role=user.role,
+    )
+
+
+# ============================================
+# HANDLERS
+# ============================================
+
+
+async def _send_message(ctx: CommandContext, payload: Dict[str, Any], command_id: str) -> Dict[str, Any]:
+    from app.schemas.message import MessageResponse
+    from app.services.whatsapp_service import WhatsAppService
+
+    data = SendMessageCommand.model_validate(payload)
+
+    async with async_session() as db:
+        message = await WhatsAppService(db).send_message(
+            conversation_id=data.conversation_id,
+            organization_id=ctx.organization_id,
+            message_type=data.message_type,
+            content=data.content,
+            sender_user_id=ctx.user_id,
+            reply_to_message_id=data.reply_to_message_id,
+            client_message_id=command_id,
+        )
+        result = MessageResponse.model_validate(message).model_dump(mode="json")
+
+    result["client_message_id"] = command_id
+    return result
+
+
+async def _mark_read(ctx: CommandContext, payload: Dict[str, Any], command_id: str) -> Dict[str, Any]:
+    from app.services.conversation_service import ConversationService
+
+    data = MarkReadCommand.model_validate(payload)
+
+    async with async_session() as db:
+        conversation = await ConversationService(db).mark_as_read(
+            conversation_id=data.conversation_id,
+            organization_id=ctx.organization_id,
+        )
+        await db.commit()
+
+    return {"conversation_id": str(conversation.id), "unread_count": 0}
+
+
+async def _typing(ctx: CommandContext, payload: Dict[str, Any], command_id: str) -> Dict[str, Any]:
+    from app.services.conversation_service import ConversationService
+
+    data = TypingCommand.model_validate(payload)
+
+    # Only conversations of the caller's organization (404 otherwise)
+    async with async_session() as db:
+        await ConversationService(db).get_by_id(
+            conversation_id=data.conversation_id,
+            organization_id=ctx.organization_id,
+        )
+
+    await sio.emit("user_typing", {
+        "conversation_id": str(data.conversation_id),
+        "user_id": str(ctx.user_id),
+        "typing": data.typing,
+    }, room=f"conversation:{data.conversation_id}", skip_sid=ctx.sid)
+
+    return {"conversation_id": str(data.conversation_id), "typing": data.typing}
+
+
+async def _assign(ctx: CommandContext, payload: Dict[str, Any], command_id: str) -> Dict[str, Any]:
+    from app.schemas.conversation import Conversation as ConversationSchema
+    from app.services.conversation_service import ConversationService
+
+    data = AssignCommand.model_validate(payload)
+
+    async with async_session() as db:
+        conversation = await ConversationService(db).assign_to_agent(
+            conversation_id=data.conversation_id,
+            organization_id=ctx.organization_id,
+            agent_id=data.agent_id,
+        )
+        await db.commit()
+        result = ConversationSchema.model_validate(conversation).model_dump(mode="json")
+
+    await sio.emit("conversation:assigned", {
+        "conversation_id": str(data.conversation_id),
+        "agent_id": str(data.agent_id),
+        "assigned_by": str(ctx.user_id),
+    }, room=f"organization:{ctx.organization_id}")
+
+    return result
+
+
+HANDLERS: Dict[WSCommandType, Callable[..., Awaitable[Dict[str, Any]]]] = {
+    WSCommandType.SEND_MESSAGE: _send_message,
+    WSCommandType.MARK_READ: _mark_read,
+    WSCommandType.TYPING: _typing,
+    WSCommandType.ASSIGN: _assign,
+}
+
+
+# ============================================
+# DISPATCH
+# ============================================
+
+
+def _error_ack(command_id: Optional[str], command: Optional[str], code: int, message: str) -> Dict[str, Any]:
+    return WSCommandAck(
+        id=command_id,
+        command=command,
+        ok=False,
+        error=WSCommandError(code=code, message=message),
+    ).model_dump(mode="json")
+
+
+async def _claim(key: str) -> Optional[Dict[str, Any]]:
+    """
+    Claim a command id
+
+    Returns:
+        None if the command may run, otherwise the ack to return (cached or in progress)
+    """
+    try:
+        if await redis_client.set(key, PENDING, expire=ACK_TTL_SECONDS, nx=True):
+            return None
+        cached = await redis_client.get(key)
+    except Exception as e:
+        logger.warning(f"⚠️ Command dedup unavailable for {key}: {e}")
+        return None
+
+    if cached and cached != PENDING:
+        return json.loads(cached)
+    return {"pending": True}
+
+
+async def _store_ack(key: str, ack: Dict[str, Any]) -> None:
+    try:
+        if ack["ok"]:
+            await redis_client.set(key, json.dumps(ack), expire=ACK_TTL_SECONDS)
+        else:
+            # Failed commands can be retried with the same id
+            await redis_client.delete(key)
+    except Exception as e:
+        logger.warning(f"⚠️ Could not store command ack {key}: {e}")
+
+
+async def dispatch_command(ctx: CommandContext, data: Any) -> Dict[str, Any]:
+    """
+    Validate and execute a command
+
+    Args:
+        ctx: Authenticated caller
+        data: Raw command envelope
+
+    Returns:
+        Acknowledgement dict (WSCommandAck)
+    """
+    try:
+        command = WSCommand.model_validate(data or {})
+    except ValidationError as e:
+        raw = data if isinstance(data, dict) else {}
+        return _error_ack(raw.get("id"), raw.get("command"), 422, str(e.errors()[0]["msg"]))
+
+    command_name = command.command.value
+    key = ACK_KEY.format(user_id=ctx.user_id, command_id=command.id)
+
+    if command.command in IDEMPOTENT_COMMANDS:
+        previous = await _claim(key)
+        if previous is not None:
+            if previous.get("pending"):
+                return _error_ack(command.id, command_name, 409, "Command already in progress")
+            logger.info(f"Returning cached ack for command {command.id}")
+            return previous
+
+    try:
+        result = await HANDLERS[command.command](ctx, command.payload, command.id)
+        ack = WSCommandAck(id=command.id, command=command_name, ok=True, data=result).model_dump(mode="json")
+
+    except ValidationError as e:
+        ack = _error_ack(command.id, command_name, 422, str(e.errors()[0]["msg"]))
+    except HTTPException as e:
+        ack = _error_ack(command.id, command_name, e.status_code, str(e.detail))
+    except ValueError as e:
+        ack = _error_ack(command.id, command_name, 400, str(e))
+    except Exception as e:
+        logger.error(f"❌ Command {command_name} ({command.id}) failed: {e}")
+        ack = _error_ack(command.id, command_name, 500, getattr(e, "message", None) or str(e))
+
+    if command.command in IDEMPOTENT_COMMANDS:
+        await _store_ack(key, ack)
+
+    return ack
+
+
+@sio.event
+async def command(sid, data):
+    """
+    Execute an agent command; the return value is the Socket.IO acknowledgement
+
+    Args:
+        data: {"id": "optimistic-id", "command": "send_message", "payload": {...}}
+    """
+    async with sio.session(sid) as session:
+        token = session.get("token")
+
+    raw = data if isinstance(data, dict) else {}
+    if not token:
+        return _error_ack(raw.get("id"), raw.get("command"), 401, "Not authenticated")
+
+    try:
+        ctx = await resolve_context(sid, token)
+    except HTTPException as e:
+        if e.status_code == status.HTTP_401_UNAUTHORIZED:
+            logger.warning(f"Closing socket {sid}: access revoked")
+            await sio.disconnect(sid)
+        return _error_ack(raw.get("id"), raw.get("command"), e.status_code, str(e.detail))
+
+    return await dispatch_command(ctx, data)
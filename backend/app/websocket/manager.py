@@ -1,12 +1,17 @@
 """
 Socket.IO Manager - Real-time WebSocket communication
+
+Connections authenticate like REST requests (AuthService.get_current_user):
+inactive users and tokens of a workspace the user was removed from are
+rejected. Commands re-check the token and close connections whose access was
+revoked since (see app.websocket.commands).
 """
 
 import socketio
 from typing import Optional
 import logging
-from jose import jwt, JWTError
-from app.core.config import settings
+from fastapi import HTTPException
+from app.models.user import User
 
 logger = logging.getLogger(__name__)
 
@@ -30,25 +35,25 @@ def get_sio_app():
     return sio_app
 
 
-async def verify_token(token: str) -> Optional[dict]:
+async def authenticate(token: str) -> Optional[User]:
     """
-    Verify JWT token and return payload
+    Resolve the user of an access token
 
     Args:
         token: JWT access token
 
     Returns:
-        Token payload if valid, None otherwise
+        User scoped to the token's workspace, None if the token is invalid,
+        the user inactive or the workspace membership revoked
     """
+    from app.core.database import async_session
+    from app.services.auth_service import AuthService
+
     try:
-        payload = jwt.decode(
-            token,
-            settings.JWT_SECRET_KEY,
-            algorithms=[settings.JWT_ALGORITHM]
-        )
-        return payload
-    except JWTError as e:
-        logger.error(f"JWT verification failed: {e}")
+        async with async_session() as db:
+            return await AuthService(db).get_current_user(token)
+    except HTTPException as e:
+        logger.error(f"Socket authentication failed: {e.detail}")
         return None
 
 
@@ -68,21 +73,23 @@ async def connect(sid, environ, auth):
 
     token = auth['token']
 
-    # Verify token
-    payload = await verify_token(token)
-    if not payload:
+    # Resolve user and workspace
+    user = await authenticate(token)
+    if not user:
         logger.warning(f"Client {sid} connection rejected: Invalid token")
         return False
 
     # Store user info in session
-    user_id = payload.get('sub')
-    organization_id = payload.get('organization_id')
-    role = payload.get('role')
+    user_id = str(user.id)
+    organization_id = str(user.organization_id)
+    role = user.role
 
     async with sio.session(sid) as session:
         session['user_id'] = user_id
         session['organization_id'] = organization_id
         session['role'] = role
+        # Commands re-resolve the caller from the token (see app.websocket.commands)
+        session['token'] = token
 
     logger.info(f"Client {sid} connected successfully. User: {user_id}, Org: {organization_id}")
 
@@ -0,0 +1,70 @@
+"""
+pytake-client - Typed async SDK for the PyTake API
+
+Shared by tenant integrations and PyTake's own workers so request/response
+models are declared once (see pytake_client.models and
+scripts/generate_client_models.py).
+"""
+
+from pytake_client.client import PyTakeClient
+from pytake_client.errors import MediaFetchError, PyTakeAPIError, PyTakeAuthError, PyTakeError
+from pytake_client.media import LocalMediaStorage, MediaFetcher, MediaStorage
+from pytake_client.processor import (
+    DedupStore,
+    EventResult,
+    InMemoryDedupStore,
+    NumberRoute,
+    PhoneNumberRouter,
+    RedisDedupStore,
+    RoutingMetrics,
+    WebhookHandler,
+    WebhookProcessor,
+)
+from pytake_client.webhooks import (
+    DeliveryError,
+    DeliveryErrorCode,
+    IdentityChange,
+    InboundMessage,
+    InboundMessageType,
+    MessageEcho,
+    StoredMedia,
+    WebhookChallenge,
+    WebhookEvent,
+    WebhookEventType,
+    iter_events,
+    verify_signature,
+)
+
+__all__ = [
+    "PyTakeClient",
+    "PyTakeAPIError",
+    "PyTakeAuthError",
+    "PyTakeError",
+    "MediaFetchError",
+    "LocalMediaStorage",
+    "MediaFetcher",
+    "MediaStorage",
+    "DedupStore",
+    "EventResult",
+    "InMemoryDedupStore",
+    "NumberRoute",
+    "PhoneNumberRouter",
+    "RedisDedupStore",
+    "RoutingMetrics",
+    "WebhookHandler",
+    "WebhookProcessor",
+    "DeliveryError",
+    "DeliveryErrorCode",
+    "IdentityChange",
+    "InboundMessage",
+    "InboundMessageType",
+    "MessageEcho",
+    "StoredMedia",
+    "WebhookChallenge",
+    "WebhookEvent",
+    "WebhookEventType",
+    "iter_events",
+    "verify_signature",
+]
+
+__version__ = "0.1.0"
@@ -0,0 +1,388 @@
+"""
+PyTake async API client
+
+Typed wrapper around the REST API (/api/v1). Resources are grouped in
+namespaces mirroring the API routers:
+
+    async with PyTakeClient("https://api.pytake.net") as client:
+        await client.auth.login("admin@acme.com", "Secret123")
+        conversations = await client.conversations.list(status="open")
+        await client.messages.send_text(conversations[0].id, "Olá!")
+"""
+
+from typing import Any, Dict, List, Optional, Type, TypeVar
+from uuid import UUID
+
+import httpx
+from pydantic import BaseModel
+
+from pytake_client.errors import PyTakeAPIError, PyTakeAuthError
+from pytake_client.models import (
+    CampaignCreate,
+    CampaignInDB,
+    CampaignListResponse,
+    CampaignProgress,
+    CampaignStartResponse,
+    CampaignUpdate,
+    Conversation,
+    ConversationAssign,
+    FlowCreate,
+    FlowInDB,
+    FlowListResponse,
+    FlowUpdate,
+    MessageResponse,
+    MessageSendRequest,
+    RefreshTokenRequest,
+    Token,
+    UserLogin,
+)
+from pytake_client.webhooks import WebhookEvent, iter_events, verify_signature
+
+ModelT = TypeVar("ModelT", bound=BaseModel)
+
+DEFAULT_API_PREFIX = "/api/v1"
+DEFAULT_TIMEOUT_SECONDS = 30.0
+
+
+class _Resource:
+    """Base for API namespaces"""
+
+    def __init__(self, client: "PyTakeClient"):
+        self._client = client
+
+
+class AuthResource(_Resource):
+    """/auth"""
+
+    async def login(self, email: str, password: str) -> Token:
+        """Authenticate and keep the tokens on the client"""
+        body = UserLogin(email=email, password=password)
+        data = await self._client.request("POST", "/auth/login", json=_dump(body))
+        token = Token.model_validate(data["token"])
+        self._client.set_token(token)
+        return token
+
+    async def refresh(self) -> Token:
+        """Exchange the refresh token for a new access token"""
+        if not self._client.token:
+            raise PyTakeAuthError(401, "No refresh token available - login first")
+
+        body = RefreshTokenRequest(refresh_token=self._client.token.refresh_token)
+        data = await self._client.request("POST", "/auth/refresh", json=_dump(body))
+        token = Token.model_validate(data)
+        self._client.set_token(token)
+        return token
+
+    async def me(self) -> Dict[str, Any]:
+        """Current user"""
+        return await self._client.request("GET", "/auth/me")
+
+
+class ConversationsResource(_Resource):
+    """/conversations"""
+
+    async def list(
+        self,
+        status: Optional[str] = None,
+        assigned_to_me: bool = False,
+        skip: int = 0,
+        limit: int = 100,
+    ) -> List[Conversation]:
+        params: Dict[str, Any] = {"skip": skip, "limit": limit}
+        if status:
+            params["status"] = status
+        if assigned_to_me:
+            params["assigned_to_me"] = True
+        data = await self._client.request("GET", "/conversations/", params=params)
+        return [Conversation.model_validate(item) for item in data]
+
+    async def get(self, conversation_id: UUID) -> Conversation:
+        data = await self._client.request("GET", f"/conversations/{conversation_id}")
+        return Conversation.model_validate(data)
+
+    async def mark_read(self, conversation_id: UUID) -> Conversation:
+        data = await self._client.request("POST", f"/conversations/{conversation_id}/read")
+        return Conversation.model_validate(data)
+
+    async def assign(self, conversation_id: UUID, agent_id: UUID) -> Conversation:
+        body = ConversationAssign(agent_id=agent_id)
+        data = await self._client.request(
+            "POST", f"/conversations/{conversation_id}/assign", json=_dump(body)
+        )
+        return Conversation.model_validate(data)
+
+
+class MessagesResource(_Resource):
+    """/conversations/{id}/messages"""
+
+    async def list(
+        self, conversation_id: UUID, skip: int = 0, limit: int = 100
+    ) -> List[MessageResponse]:
+        data = await self._client.request(
+            "GET",
+            f"/conversations/{conversation_id}/messages",
+            params={"skip": skip, "limit": limit},
+        )
+        return [MessageResponse.model_validate(item) for item in data]
+
+    async def send(self, conversation_id: UUID, request: MessageSendRequest) -> MessageResponse:
+        data = await self._client.request(
+            "POST", f"/conversations/{conversation_id}/messages", json=_dump(request)
+        )
+        return MessageResponse.model_validate(data)
+
+    async def send_text(
+        self,
+        conversation_id: UUID,
+        text: str,
+        reply_to_message_id: Optional[UUID] = None,
+    ) -> MessageResponse:
+        return await self.send(
+            conversation_id,
+            MessageSendRequest(
+                message_type="text",
+                content={"text": text},
+                reply_to_message_id=reply_to_message_id,
+            ),
+        )
+
+    async def send_link(
+        self,
+        conversation_id: UUID,
+        text: str,
+        url: Optional[str] = None,
+        reply_to_message_id: Optional[UUID] = None,
+    ) -> MessageResponse:
+        """Text with link preview (url is appended to the text if missing)"""
+        content: Dict[str, Any] = {"text": text, "preview_url": True}
+        if url:
+            content["preview_link"] = url
+        return await self.send(
+            conversation_id,
+            MessageSendRequest(
+                message_type="text",
+                content=content,
+                reply_to_message_id=reply_to_message_id,
+            ),
+        )
+
+    async def send_template(
+        self,
+        conversation_id: UUID,
+        name: str,
+        language: str = "pt_BR",
+        components: Optional[List[Dict[str, Any]]] = None,
+        reply_to_message_id: Optional[UUID] = None,
+    ) -> MessageResponse:
+        return await self.send(
+            conversation_id,
+            MessageSendRequest(
+                message_type="template",
+                content={"name": name, "language": language, "components": components or []},
+                reply_to_message_id=reply_to_message_id,
+            ),
+        )
+
+
+class CampaignsResource(_Resource):
+    """/campaigns"""
+
+    async def list(
+        self, status: Optional[str] = None, skip: int = 0, limit: int = 100
+    ) -> CampaignListResponse:
+        params: Dict[str, Any] = {"skip": skip, "limit": limit}
+        if status:
+            params["status"] = status
+        data = await self._client.request("GET", "/campaigns/", params=params)
+        return CampaignListResponse.model_validate(data)
+
+    async def get(self, campaign_id: UUID) -> CampaignInDB:
+        return await self._client.request_model(
+            CampaignInDB, "GET", f"/campaigns/{campaign_id}"
+        )
+
+    async def create(self, campaign: CampaignCreate) -> CampaignInDB:
+        return await self._client.request_model(
+            CampaignInDB, "POST", "/campaigns/", json=_dump(campaign)
+        )
+
+    async def update(self, campaign_id: UUID, changes: CampaignUpdate) -> CampaignInDB:
+        return await self._client.request_model(
+            CampaignInDB, "PATCH", f"/campaigns/{campaign_id}", json=_dump(changes)
+        )
+
+    async def start(self, campaign_id: UUID) -> CampaignStartResponse:
+        return await self._client.request_model(
+            CampaignStartResponse, "POST", f"/campaigns/{campaign_id}/start"
+        )
+
+    async def pause(self, campaign_id: UUID) -> CampaignInDB:
+        return await self._client.request_model(
+            CampaignInDB, "POST", f"/campaigns/{campaign_id}/pause"
+        )
+
+    async def resume(self, campaign_id: UUID) -> CampaignInDB:
+        return await self._client.request_model(
+            CampaignInDB, "POST", f"/campaigns/{campaign_id}/resume"
+        )
+
+    async def cancel(self, campaign_id: UUID) -> CampaignInDB:
+        return await self._client.request_model(
+            CampaignInDB, "POST", f"/campaigns/{campaign_id}/cancel"
+        )
+
+    async def progress(self, campaign_id: UUID) -> CampaignProgress:
+        return await self._client.request_model(
+            CampaignProgress, "GET", f"/campaigns/{campaign_id}/progress"
+        )
+
+
+class FlowsResource(_Resource):
+    """/chatbots/{id}/flows and /chatbots/flows/{id}"""
+
+    async def list(self, chatbot_id: UUID) -> FlowListResponse:
+        return await self._client.request_model(
+            FlowListResponse, "GET", f"/chatbots/{chatbot_id}/flows"
+        )
+
+    async def get(self, flow_id: UUID) -> FlowInDB:
+        return await self._client.request_model(FlowInDB, "GET", f"/chatbots/flows/{flow_id}")
+
+    async def create(self, flow: FlowCreate) -> FlowInDB:
+        return await self._client.request_model(
+            FlowInDB, "POST", f"/chatbots/{flow.chatbot_id}/flows", json=_dump(flow)
+        )
+
+    async def update(self, flow_id: UUID, changes: FlowUpdate) -> FlowInDB:
+        return await self._client.request_model(
+            FlowInDB, "PATCH", f"/chatbots/flows/{flow_id}", json=_dump(changes)
+        )
+
+    async def export(self, flow_id: UUID) -> Dict[str, Any]:
+        return await self._client.request("GET", f"/chatbots/flows/{flow_id}/export")
+
+
+class WebhooksResource(_Resource):
+    """Helpers for webhook payloads forwarded to tenant integrations"""
+
+    def __init__(self, client: "PyTakeClient", secret: Optional[str] = None):
+        super().__init__(client)
+        self.secret = secret
+
+    def verify(self, payload: bytes, signature: Optional[str]) -> bool:
+        """Verify X-Hub-Signature-256 with the configured secret"""
+        if not self.secret:
+            raise ValueError("webhook_secret was not configured on the client")
+        return verify_signature(payload, signature, self.secret)
+
+    def parse(self, payload: Dict[str, Any]) -> List[WebhookEvent]:
+        """Typed events of a webhook payload"""
+        return list(iter_events(payload))
+
+
+class PyTakeClient:
+    """
+    Async client for the PyTake REST API
+
+    Args:
+        base_url: Server URL (e.g. https://api.pytake.net)
+        access_token: Existing access token (optional, see auth.login)
+        api_prefix: API prefix (default /api/v1)
+        timeout: Request timeout in seconds
+        webhook_secret: Secret used by webhooks.verify
+        http_client: Custom httpx.AsyncClient (tests, proxies)
+    """
+
+    def __init__(
+        self,
+        base_url: str,
+        access_token: Optional[str] = None,
+        api_prefix: str = DEFAULT_API_PREFIX,
+        timeout: float = DEFAULT_TIMEOUT_SECONDS,
+        webhook_secret: Optional[str] = None,
+        http_client: Optional[httpx.AsyncClient] = None,
+    ):
+        self.base_url = base_url.rstrip("/") + api_prefix
+        self.token: Optional[Token] = None
+        self._access_token = access_token
+        self._http = http_client or httpx.AsyncClient(timeout=timeout)
+        self._owns_http = http_client is None
+
+        self.auth = AuthResource(self)
+        self.conversations = ConversationsResource(self)
+        self.messages = MessagesResource(self)
+        self.campaigns = CampaignsResource(self)
+        self.flows = FlowsResource(self)
+        self.webhooks = WebhooksResource(self, webhook_secret)
+
+    async def __aenter__(self) -> "PyTakeClient":
+        return self
+
+    async def __aexit__(self, *exc_info: Any) -> None:
+        await self.close()
+
+    async def close(self) -> None:
+        if self._owns_http:
+            await self._http.aclose()
+
+    def set_token(self, token: Token) -> None:
+        self.token = token
+        self._access_token = token.access_token
+
+    def _headers(self) -> Dict[str, str]:
+        headers = {"Accept": "application/json"}
+        if self._access_token:
+            headers["Authorization"] = f"Bearer {self._access_token}"
+        return headers
+
+    async def request(
+        self,
+        method: str,
+        path: str,
+        params: Optional[Dict[str, Any]] = None,
+        json: Optional[Any] = None,
+    ) -> Any:
+        """
+        Send a request and return the decoded JSON body
+
+        Raises:
+            PyTakeAuthError: On 401/403
+            PyTakeAPIError: On any other non-2xx response
+        """
+        response = await self._http.request(
+            method,
+            f"{self.base_url}{path}",
+            params=params,
+            json=json,
+            headers=self._headers(),
+        )
+
+        if response.status_code >= 400:
+            try:
+                body = response.json()
+            except ValueError:
+                body = response.text
+            detail = body.get("detail", body) if isinstance(body, dict) else body
+            error_cls = PyTakeAuthError if response.status_code in (401, 403) else PyTakeAPIError
+            raise error_cls(response.status_code, detail, body)
+
+        if response.status_code == 204 or not response.content:
+            return None
+        return response.json()
+
+    async def request_model(
+        self,
+        model: Type[ModelT],
+        method: str,
+        path: str,
+        params: Optional[Dict[str, Any]] = None,
+        json: Optional[Any] = None,
+    ) -> ModelT:
+        """Send a request and validate the body into a model"""
+        data = await self.request(method, path, params=params, json=json)
+        return model.model_validate(data)
+
+
+def _dump(model: BaseModel) -> Dict[str, Any]:
+    """Serialize a request model (unset optionals are omitted)"""
+    return model.model_dump(mode="json", exclude_none=True)
@@ -0,0 +1,32 @@
+"""
+PyTake client errors
+"""
+
+from typing import Any, Optional
+
+
+class PyTakeError(Exception):
+    """Base error raised by the client"""
+
+
+class PyTakeAPIError(PyTakeError):
+    """Non-2xx response from the API"""
+
+    def __init__(self, status_code: int, detail: Any = None, body: Optional[Any] = None):
+        self.status_code = status_code
+        self.detail = detail
+        self.body = body
+        super().__init__(f"PyTake API error {status_code}: {detail}")
+
+
+class PyTakeAuthError(PyTakeAPIError):
+    """401/403 response (missing, expired or insufficient credentials)"""
+
+
+class MediaFetchError(PyTakeError):
+    """Inbound media could not be resolved, downloaded or stored"""
+
+    def __init__(self, media_id: str, reason: str):
+        self.media_id = media_id
+        self.reason = reason
+        super().__init__(f"Media {media_id}: {reason}")
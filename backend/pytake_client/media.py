@@ -0,0 +1,269 @@
+"""
+Inbound media fetching
+
+Meta webhooks carry only a media id for images, audio, video, documents and
+stickers; the bytes must be fetched with the business access token before
+the temporary URL expires. MediaFetcher resolves the URL, streams the
+download to a storage backend and attaches a StoredMedia to the event:
+
+    from pytake_client.media import LocalMediaStorage, MediaFetcher
+
+    fetcher = MediaFetcher(access_token, LocalMediaStorage("/var/pytake/media"))
+    processor = WebhookProcessor(handlers=[Support()], media_fetcher=fetcher)
+
+    # in a handler
+    async def on_message(self, event):
+        if event.media:
+            path = event.media.reference
+
+Other backends (S3, GCS...) implement MediaStorage.save() (and read() to
+stream stored objects back).
+"""
+
+import base64
+import hashlib
+import logging
+import mimetypes
+import os
+from typing import AsyncIterator, Optional
+
+import httpx
+
+from pytake_client.errors import MediaFetchError
+from pytake_client.webhooks import StoredMedia, WebhookEvent
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_GRAPH_URL = "https://graph.facebook.com"
+DEFAULT_GRAPH_VERSION = "v21.0"
+# Largest media accepted by the Cloud API (documents)
+DEFAULT_MAX_MEDIA_BYTES = 100 * 1024 * 1024
+READ_CHUNK_BYTES = 1024 * 1024
+
+
+# ============================================
+# STORAGE
+# ============================================
+
+
+class MediaStorage:
+    """Interface of the backends receiving downloaded media"""
+
+    async def save(self, key: str, chunks: AsyncIterator[bytes], mime_type: Optional[str]) -> str:
+        """
+        Store the bytes under a key
+
+        Returns:
+            Reference to the stored object (path, URL, object key...)
+        """
+        raise NotImplementedError
+
+    def read(self, key: str) -> AsyncIterator[bytes]:
+        """Stream back the bytes stored under a key"""
+        raise NotImplementedError
+
+    async def delete(self, key: str) -> None:
+        """Remove a partially or wrongly stored object"""
+
+
+class LocalMediaStorage(MediaStorage):
+    """Files under a local directory (``<base_dir>/<key>``)"""
+
+    def __init__(self, base_dir: str):
+        self.base_dir = base_dir
+
+    async def save(self, key: str, chunks: AsyncIterator[bytes], mime_type: Optional[str]) -> str:
+        path = os.path.join(self.base_dir, key)
+        os.makedirs(os.path.dirname(path), exist_ok=True)
+        with open(path, "wb") as file:
+            async for chunk in chunks:
+                file.write(chunk)
+        return path
+
+    async def read(self, key: str) -> AsyncIterator[bytes]:
+        with open(os.path.join(self.base_dir, key), "rb") as file:
+            while chunk := file.read(READ_CHUNK_BYTES):
+                yield chunk
+
+    async def delete(self, key: str) -> None:
+        try:
+            os.remove(os.path.join(self.base_dir, key))
+        except FileNotFoundError:
+            pass
+
+
+class InMemoryMediaStorage(MediaStorage):
+    """Keeps media in a dict (tests, small scripts)"""
+
+    def __init__(self):
+        self.objects = {}
+
+    async def save(self, key: str, chunks: AsyncIterator[bytes], mime_type: Optional[str]) -> str:
+        self.objects[key] = b"".join([chunk async for chunk in chunks])
+        return key
+
+    async def read(self, key: str) -> AsyncIterator[bytes]:
+        yield self.objects[key]
+
+    async def delete(self, key: str) -> None:
+        self.objects.pop(key, None)
+
+
+# ============================================
+# FETCHER
+# ============================================
+
+
+class MediaFetcher:
+    """
+    Download inbound media into a storage backend
+
+    Args:
+        access_token: Business access token of the receiving number
+        storage: Backend receiving the bytes
+        graph_url: Graph API base URL
+        api_version: Graph API version
+        max_bytes: Downloads larger than this are aborted
+        http_client: Custom httpx.AsyncClient (tests, proxies)
+    """
+
+    def __init__(
+        self,
+        access_token: str,
+        storage: MediaStorage,
+        graph_url: str = DEFAULT_GRAPH_URL,
+        api_version: str = DEFAULT_GRAPH_VERSION,
+        max_bytes: int = DEFAULT_MAX_MEDIA_BYTES,
+        http_client: Optional[httpx.AsyncClient] = None,
+    ):
+        self.access_token = access_token
+        self.storage = storage
+        self.graph_url = graph_url.rstrip("/")
+        self.api_version = api_version
+        self.max_bytes = max_bytes
+        self._http = http_client or httpx.AsyncClient(timeout=60.0)
+
+    @property
+    def _auth(self) -> dict:
+        return {"Authorization": f"Bearer {self.access_token}"}
+
+    async def resolve_url(self, media_id: str) -> dict:
+        """
+        Media metadata from the Graph API: url (valid for a few minutes),
+        mime_type, sha256 and file_size
+
+        Raises:
+            MediaFetchError: If Meta does not return a URL
+        """
+        try:
+            response = await self._http.get(
+                f"{self.graph_url}/{self.api_version}/{media_id}", headers=self._auth
+            )
+        except httpx.HTTPError as e:
+            raise MediaFetchError(media_id, f"lookup failed: {e}") from e
+        if response.status_code >= 400:
+            raise MediaFetchError(media_id, f"lookup failed with status {response.status_code}")
+
+        info = response.json()
+        if not info.get("url"):
+            raise MediaFetchError(media_id, "no download URL returned")
+        return info
+
+    async def fetch(
+        self,
+        media_id: str,
+        mime_type: Optional[str] = None,
+        filename: Optional[str] = None,
+        key_prefix: str = "",
+    ) -> StoredMedia:
+        """
+        Resolve, download and store one media
+
+        The download is streamed to the storage; it is aborted (and the stored
+        object deleted) when larger than max_bytes or when its SHA-256 does not
+        match the one announced by Meta.
+
+        Raises:
+            MediaFetchError: On lookup, download, size or checksum failures
+        """
+        info = await self.resolve_url(media_id)
+        mime_type = info.get("mime_type") or mime_type
+        key = f"{key_prefix}{media_id}{_extension(mime_type, filename)}"
+
+        digest = hashlib.sha256()
+        size = 0
+
+        async def chunks() -> AsyncIterator[bytes]:
+            nonlocal size
+            async with self._http.stream("GET", info["url"], headers=self._auth) as response:
+                if response.status_code >= 400:
+                    raise MediaFetchError(media_id, f"download failed with status {response.status_code}")
+                async for chunk in response.aiter_bytes():
+                    size += len(chunk)
+                    if size > self.max_bytes:
+                        raise MediaFetchError(media_id, f"larger than {self.max_bytes} bytes")
+                    digest.update(chunk)
+                    yield chunk
+
+        try:
+            reference = await self.storage.save(key, chunks(), mime_type)
+        except MediaFetchError:
+            await self.storage.delete(key)
+            raise
+        except httpx.HTTPError as e:
+            await self.storage.delete(key)
+            raise MediaFetchError(media_id, f"download failed: {e}") from e
+
+        sha256 = digest.hexdigest()
+        expected = info.get("sha256")
+        # Meta has sent the checksum both hex and base64 encoded
+        if expected and expected not in (sha256, base64.b64encode(digest.digest()).decode()):
+            await self.storage.delete(key)
+            raise MediaFetchError(media_id, "checksum mismatch")
+
+        return StoredMedia(
+            media_id=media_id,
+            reference=reference,
+            mime_type=mime_type,
+            size=size,
+            sha256=sha256,
+            filename=filename,
+        )
+
+    async def attach(self, event: WebhookEvent) -> Optional[StoredMedia]:
+        """
+        Fetch the media of an inbound event and set ``event.media``
+
+        Events without media are left untouched. Failures are logged and
+        recorded in ``event.media_error`` instead of raised, so handlers still
+        receive the message.
+        """
+        inbound = event.inbound
+        if inbound is None or inbound.media is None or not inbound.media.id:
+            return None
+
+        try:
+            event.media = await self.fetch(
+                inbound.media.id,
+                mime_type=inbound.media.mime_type,
+                filename=inbound.media.filename,
+                key_prefix=f"{event.phone_number_id}/" if event.phone_number_id else "",
+            )
+        except MediaFetchError as e:
+            logger.warning(f"⚠️ {e}")
+            event.media_error = e.reason
+        return event.media
+
+    async def close(self) -> None:
+        await self._http.aclose()
+
+
+def _extension(mime_type: Optional[str], filename: Optional[str]) -> str:
+    """File extension from the sent filename, else from the MIME type"""
+    extension = os.path.splitext(filename or "")[1]
+    if extension[1:].isalnum():
+        return extension.lower()
+    if mime_type:
+        # "audio/ogg; codecs=opus" -> audio/ogg
+        return mimetypes.guess_extension(mime_type.split(";")[0].strip()) or ""
+    return ""
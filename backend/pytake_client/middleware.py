@@ -0,0 +1,165 @@
+"""
+ASGI middleware for Meta webhook signature verification
+
+Opt-in module (not imported by ``pytake_client``): it has no dependencies
+beyond the standard library and works with any ASGI framework (FastAPI,
+Starlette, Quart...).
+
+    from pytake_client.middleware import WebhookSignatureMiddleware, get_raw_body
+
+    app.add_middleware(
+        WebhookSignatureMiddleware,
+        secret=os.environ["META_APP_SECRET"],
+        paths=["/webhooks/meta"],
+    )
+
+    @app.post("/webhooks/meta")
+    async def receive(request: Request):
+        raw = get_raw_body(request.scope)  # exact bytes that were signed
+        payload = await request.json()     # body is replayed to the handler
+
+The body is read and verified (constant-time HMAC comparison) before the
+request reaches the handler; invalid requests never hit application code.
+"""
+
+import json
+from typing import Any, Awaitable, Callable, Dict, Iterable, Optional, Tuple
+
+from pytake_client.webhooks import verify_signature
+
+Scope = Dict[str, Any]
+Message = Dict[str, Any]
+Receive = Callable[[], Awaitable[Message]]
+Send = Callable[[Message], Awaitable[None]]
+ASGIApp = Callable[[Scope, Receive, Send], Awaitable[None]]
+
+SIGNATURE_HEADER = b"x-hub-signature-256"
+RAW_BODY_KEY = "pytake_raw_body"
+DEFAULT_MAX_BODY_SIZE = 5 * 1024 * 1024  # Meta payloads are far below this
+
+
+def get_raw_body(scope: Scope) -> Optional[bytes]:
+    """Raw body captured by WebhookSignatureMiddleware (None if not verified)"""
+    return (scope.get("state") or {}).get(RAW_BODY_KEY)
+
+
+def _path_matches(path: str, pattern: str) -> bool:
+    if pattern.endswith("*"):
+        return path.startswith(pattern[:-1])
+    return path.rstrip("/") == pattern.rstrip("/")
+
+
+class WebhookSignatureMiddleware:
+    """
+    Reject webhook requests whose X-Hub-Signature-256 does not match the app secret
+
+    Args:
+        app: ASGI application
+        secret: Meta app secret used to sign payloads
+        paths: Paths to protect, a trailing ``*`` matches a prefix (other paths
+            pass through untouched)
+        methods: HTTP methods to verify (GET verification challenges are unsigned)
+        max_body_size: Bodies above this size are rejected with 413
+
+    Responses:
+        401: Missing signature header
+        403: Signature does not match
+        413: Body too large
+    """
+
+    def __init__(
+        self,
+        app: ASGIApp,
+        secret: str,
+        paths: Iterable[str] = ("*",),
+        methods: Iterable[str] = ("POST",),
+        max_body_size: int = DEFAULT_MAX_BODY_SIZE,
+    ):
+        if not secret:
+            raise ValueError("WebhookSignatureMiddleware requires an app secret")
+
+        self.app = app
+        self.secret = secret
+        self.paths = tuple(paths)
+        self.methods = {method.upper() for method in methods}
+        self.max_body_size = max_body_size
+
+    def protects(self, scope: Scope) -> bool:
+        """Whether the request must carry a valid signature"""
+        return (
+            scope.get("type") == "http"
+            and scope.get("method", "").upper() in self.methods
+            and any(_path_matches(scope.get("path", ""), pattern) for pattern in self.paths)
+        )
+
+    async def __call__(self, scope: Scope, receive: Receive, send: Send) -> None:
+        if not self.protects(scope):
+            await self.app(scope, receive, send)
+            return
+
+        body, too_large = await self._read_body(receive)
+        if too_large:
+            await self._reject(send, 413, "Payload too large")
+            return
+
+        signature = self._header(scope, SIGNATURE_HEADER)
+        if not signature:
+            await self._reject(send, 401, "Missing signature")
+            return
+
+        if not verify_signature(body, signature, self.secret):
+            await self._reject(send, 403, "Invalid signature")
+            return
+
+        scope.setdefault("state", {})[RAW_BODY_KEY] = body
+        await self.app(scope, self._replay(body, receive), send)
+
+    async def _read_body(self, receive: Receive) -> Tuple[bytes, bool]:
+        chunks = []
+        size = 0
+        while True:
+            message = await receive()
+            if message["type"] == "http.disconnect":
+                break
+            chunk = message.get("body", b"")
+            size += len(chunk)
+            if size > self.max_body_size:
+                return b"", True
+            chunks.append(chunk)
+            if not message.get("more_body", False):
+                break
+        return b"".join(chunks), False
+
+    @staticmethod
+    def _replay(body: bytes, receive: Receive) -> Receive:
+        """Receive that hands the already-read body to the application"""
+        sent = False
+
+        async def replay() -> Message:
+            nonlocal sent
+            if not sent:
+                sent = True
+                return {"type": "http.request", "body": body, "more_body": False}
+            return await receive()
+
+        return replay
+
+    @staticmethod
+    def _header(scope: Scope, name: bytes) -> Optional[str]:
+        for key, value in scope.get("headers") or []:
+            if key.lower() == name:
+                return value.decode("latin-1")
+        return None
+
+    @staticmethod
+    async def _reject(send: Send, status: int, detail: str) -> None:
+        body = json.dumps({"detail": detail}).encode("utf-8")
+        await send({
+            "type": "http.response.start",
+            "status": status,
+            "headers": [
+                (b"content-type", b"application/json"),
+                (b"content-length", str(len(body)).encode("ascii")),
+            ],
+        })
+        await send({"type": "http.response.body", "body": body})
@@ -0,0 +1,291 @@
+"""
+PyTake API models
+
+Generated from the API OpenAPI spec by scripts/generate_client_models.py.
+Do not edit by hand - change the backend schema and regenerate.
+"""
+
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, ConfigDict, Field
+
+
+class APIModel(BaseModel):
+    """Base for API models (unknown fields are kept for forward compatibility)"""
+
+    model_config = ConfigDict(extra="allow", populate_by_name=True)
+
+
+class UserLogin(APIModel):
+    """Schema for user login"""
+
+    email: str
+    password: str
+
+
+class Token(APIModel):
+    """JWT token response"""
+
+    access_token: str
+    refresh_token: str
+    token_type: str = 'bearer'
+    expires_in: int
+
+
+class RefreshTokenRequest(APIModel):
+    """Request to refresh access token"""
+
+    refresh_token: str
+
+
+class MessageSendRequest(APIModel):
+    """Schema for sending a message"""
+
+    message_type: str
+    content: Dict[str, Any]
+    reply_to_message_id: Optional[UUID] = None
+
+
+class MessageResponse(APIModel):
+    """Schema for message response"""
+
+    id: UUID
+    conversation_id: UUID
+    direction: str
+    sender_type: str
+    message_type: str
+    content: Dict[str, Any]
+    status: str
+    whatsapp_message_id: Optional[str] = None
+    reply_to_message_id: Optional[UUID] = None
+    created_at: datetime
+    sent_at: Optional[datetime] = None
+    delivered_at: Optional[datetime] = None
+    read_at: Optional[datetime] = None
+    failed_at: Optional[datetime] = None
+    error_code: Optional[str] = None
+    error_message: Optional[str] = None
+    meta_response: Optional[Dict[str, Any]] = None
+
+
+class Conversation(APIModel):
+    """Public conversation schema"""
+
+    id: UUID
+    organization_id: UUID
+    contact_id: UUID
+    whatsapp_number_id: UUID
+    status: str
+    assigned_agent_id: Optional[UUID] = None
+    assigned_department_id: Optional[UUID] = None
+    priority: Optional[str] = None
+    last_message_at: Optional[datetime] = None
+    last_inbound_at: Optional[datetime] = None
+    last_outbound_at: Optional[datetime] = None
+    first_response_at: Optional[datetime] = None
+    resolved_at: Optional[datetime] = None
+    closed_at: Optional[datetime] = None
+    total_messages: int = 0
+    unread_count: int = 0
+    response_time_seconds: Optional[int] = None
+    resolution_time_seconds: Optional[int] = None
+    channel: str = 'whatsapp'
+    extra_data: Optional[Dict[str, Any]] = None
+    tags: Optional[List[str]] = None
+    created_at: datetime
+    updated_at: datetime
+
+
+class ConversationAssign(APIModel):
+    """Schema for assigning conversation to agent"""
+
+    agent_id: UUID
+
+
+class CampaignCreate(APIModel):
+    """Schema for creating a campaign"""
+
+    name: str
+    description: Optional[str] = None
+    campaign_type: str = 'broadcast'
+    whatsapp_number_id: Optional[UUID] = None
+    template_id: Optional[UUID] = None
+    message_type: str = 'text'
+    message_content: Optional[Dict[str, Any]] = None
+    template_variables: Optional[Dict[str, Any]] = None
+    audience_type: str = 'all_contacts'
+    target_tag_ids: Optional[List[UUID]] = None
+    target_contact_ids: Optional[List[UUID]] = None
+    segment_filters: Optional[Dict[str, Any]] = None
+    messages_per_hour: int = 100
+    delay_between_messages_seconds: int = 2
+    respect_opt_out: bool = True
+    skip_active_conversations: bool = False
+    scheduled_at: Optional[datetime] = None
+    settings: Optional[Dict[str, Any]] = None
+    retry_max_attempts: int = 3
+    retry_base_delay: int = 60
+    retry_max_delay: int = 3600
+
+
+class CampaignUpdate(APIModel):
+    """Schema for updating a campaign"""
+
+    name: Optional[str] = None
+    description: Optional[str] = None
+    campaign_type: Optional[str] = None
+    whatsapp_number_id: Optional[UUID] = None
+    template_id: Optional[UUID] = None
+    message_type: Optional[str] = None
+    message_content: Optional[Dict[str, Any]] = None
+    template_variables: Optional[Dict[str, Any]] = None
+    audience_type: Optional[str] = None
+    target_tag_ids: Optional[List[UUID]] = None
+    target_contact_ids: Optional[List[UUID]] = None
+    segment_filters: Optional[Dict[str, Any]] = None
+    messages_per_hour: Optional[int] = None
+    delay_between_messages_seconds: Optional[int] = None
+    respect_opt_out: Optional[bool] = None
+    skip_active_conversations: Optional[bool] = None
+    scheduled_at: Optional[datetime] = None
+    settings: Optional[Dict[str, Any]] = None
+    retry_max_attempts: Optional[int] = None
+    retry_base_delay: Optional[int] = None
+    retry_max_delay: Optional[int] = None
+
+
+class CampaignInDB(APIModel):
+    """Schema for campaign in database"""
+
+    name: str
+    description: Optional[str] = None
+    campaign_type: str = 'broadcast'
+    whatsapp_number_id: Optional[UUID] = None
+    template_id: Optional[UUID] = None
+    message_type: str = 'text'
+    message_content: Optional[Dict[str, Any]] = None
+    template_variables: Optional[Dict[str, Any]] = None
+    audience_type: str = 'all_contacts'
+    target_tag_ids: Optional[List[UUID]] = None
+    target_contact_ids: Optional[List[UUID]] = None
+    segment_filters: Optional[Dict[str, Any]] = None
+    messages_per_hour: int = 100
+    delay_between_messages_seconds: int = 2
+    respect_opt_out: bool = True
+    skip_active_conversations: bool = False
+    scheduled_at: Optional[datetime] = None
+    settings: Optional[Dict[str, Any]] = None
+    retry_max_attempts: int = 3
+    retry_base_delay: int = 60
+    retry_max_delay: int = 3600
+    id: UUID
+    organization_id: UUID
+    created_by_user_id: Optional[UUID] = None
+    status: str = 'draft'
+    started_at: Optional[datetime] = None
+    completed_at: Optional[datetime] = None
+    paused_at: Optional[datetime] = None
+    cancelled_at: Optional[datetime] = None
+    total_recipients: int = 0
+    messages_sent: int = 0
+    messages_delivered: int = 0
+    messages_read: int = 0
+    messages_failed: int = 0
+    messages_pending: int = 0
+    replies_count: int = 0
+    unique_replies_count: int = 0
+    opt_outs_count: int = 0
+    delivery_rate: Optional[float] = None
+    read_rate: Optional[float] = None
+    reply_rate: Optional[float] = None
+    estimated_cost: Optional[float] = None
+    actual_cost: Optional[float] = None
+    error_count: int = 0
+    last_error_message: Optional[str] = None
+    errors: Optional[List[Dict[str, Any]]] = None
+    message_statuses: Optional[Dict[str, Any]] = None
+    created_at: datetime
+    updated_at: datetime
+    deleted_at: Optional[datetime] = None
+
+
+class CampaignListResponse(APIModel):
+    """Response for campaign list"""
+
+    total: int
+    items: List[CampaignInDB]
+
+
+class CampaignProgress(APIModel):
+    """Campaign progress details"""
+
+    status: str
+    total_recipients: int
+    messages_sent: int
+    messages_pending: int
+    messages_failed: int
+    progress_percentage: float
+    estimated_completion_time: Optional[datetime] = None
+
+
+class CampaignStartResponse(APIModel):
+    """Response for start action"""
+
+    campaign_id: UUID
+    status: str
+    started_at: datetime
+    total_recipients: int
+    message: str
+
+
+class FlowCreate(APIModel):
+    """Schema for creating a flow"""
+
+    name: str
+    description: Optional[str] = None
+    is_main: bool = False
+    is_fallback: bool = False
+    canvas_data: Optional[Dict[str, Any]] = None
+    variables: Optional[Dict[str, Any]] = None
+    is_active: bool = True
+    chatbot_id: UUID
+
+
+class FlowUpdate(APIModel):
+    """Schema for updating a flow"""
+
+    name: Optional[str] = None
+    description: Optional[str] = None
+    is_main: Optional[bool] = None
+    is_fallback: Optional[bool] = None
+    canvas_data: Optional[Dict[str, Any]] = None
+    variables: Optional[Dict[str, Any]] = None
+    is_active: Optional[bool] = None
+
+
+class FlowInDB(APIModel):
+    """Schema for flow in database"""
+
+    name: str
+    description: Optional[str] = None
+    is_main: bool = False
+    is_fallback: bool = False
+    canvas_data: Optional[Dict[str, Any]] = None
+    variables: Optional[Dict[str, Any]] = None
+    is_active: bool = True
+    id: UUID
+    organization_id: UUID
+    chatbot_id: UUID
+    version: int
+    created_at: datetime
+    updated_at: datetime
+    deleted_at: Optional[datetime] = None
+
+
+class FlowListResponse(APIModel):
+    """Response for flow list"""
+
+    total: int
+    items: List[FlowInDB]
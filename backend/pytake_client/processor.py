@@ -0,0 +1,642 @@
+"""
+Webhook processing: deduplication and handler dispatch
+
+Meta redelivers a webhook when the endpoint is slow to answer, so the same
+message can arrive several times. WebhookProcessor yields each message and
+status update once, keyed by its id, using a pluggable dedup store:
+
+    from pytake_client.processor import RedisDedupStore, WebhookProcessor
+
+    processor = WebhookProcessor(RedisDedupStore(redis.asyncio.from_url(url)), ttl_seconds=86400)
+
+    for event in await processor.new_events(payload):
+        ...
+
+InMemoryDedupStore (the default) is enough for a single process; use
+RedisDedupStore when several workers receive the same webhooks.
+
+Instead of branching on event.type, register WebhookHandler subclasses and
+call ``processor.dispatch(payload)``: each event goes to the handler method
+of its kind (on_message, on_status, on_template_update...).
+
+Pass a MediaFetcher (pytake_client.media) to download inbound media and
+attach it to events (``event.media``) before they reach handlers.
+
+Meta batches several entries/changes per POST. ``processor.iter_events``
+streams them one by one and ``processor.process(payload, callback)`` runs a
+callback on each concurrently, isolating failures per event.
+
+One webhook URL receives the events of every number of the app. Pass a
+PhoneNumberRouter to send each event to the tenant and handlers of the number
+that received it (``event.route``), with metrics on unroutable events.
+"""
+
+import asyncio
+import logging
+import time
+from collections import OrderedDict
+from dataclasses import dataclass, field
+from typing import Any, AsyncIterator, Awaitable, Callable, Dict, List, Optional
+
+from pytake_client.webhooks import WebhookEvent, WebhookEventType, iter_events
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_DEDUP_TTL_SECONDS = 24 * 60 * 60
+DEFAULT_DEDUP_MAX_SIZE = 10_000
+
+
+def message_dedup_key(message: Dict[str, Any]) -> Optional[str]:
+    """Dedup key of an entry of ``messages[]`` (None without an id)"""
+    message_id = message.get("id")
+    return f"message:{message_id}" if message_id else None
+
+
+def status_dedup_key(status: Dict[str, Any]) -> Optional[str]:
+    """
+    Dedup key of an entry of ``statuses[]`` (None without an id)
+
+    A message goes through several statuses (sent, delivered, read) under the
+    same id, so the status is part of the key.
+    """
+    message_id = status.get("id")
+    if not message_id:
+        return None
+    return f"status:{message_id}:{status.get('status', '')}"
+
+
+def echo_dedup_key(echo: Dict[str, Any]) -> Optional[str]:
+    """Dedup key of an entry of ``message_echoes[]`` (None without an id)"""
+    message_id = echo.get("id")
+    return f"echo:{message_id}" if message_id else None
+
+
+def event_dedup_key(event: WebhookEvent) -> Optional[str]:
+    """
+    Dedup key of a webhook event
+
+    Template status and quality updates carry no id and are never deduplicated
+    (applying them twice is harmless).
+    """
+    if event.type in (WebhookEventType.TEMPLATE_STATUS, WebhookEventType.PHONE_NUMBER_QUALITY):
+        return None
+    if event.type in (WebhookEventType.STATUS, WebhookEventType.PAYMENT):
+        return status_dedup_key(event.data)
+    if event.type == WebhookEventType.MESSAGE_ECHO:
+        return echo_dedup_key(event.data)
+    return message_dedup_key(event.data)
+
+
+# ============================================
+# DEDUP STORES
+# ============================================
+
+
+class DedupStore:
+    """Interface of the stores used by WebhookProcessor"""
+
+    async def claim(self, key: str, ttl_seconds: int) -> bool:
+        """Mark a key as seen; True only the first time within the TTL"""
+        raise NotImplementedError
+
+    async def release(self, key: str) -> None:
+        """Forget a key so a redelivery is processed again"""
+        raise NotImplementedError
+
+
+class InMemoryDedupStore(DedupStore):
+    """
+    Per-process LRU of seen keys
+
+    Keys expire after their TTL; the least recently claimed keys are evicted
+    once ``max_size`` is reached.
+    """
+
+    def __init__(self, max_size: int = DEFAULT_DEDUP_MAX_SIZE, clock=time.monotonic):
+        self.max_size = max_size
+        self._clock = clock
+        self._entries: "OrderedDict[str, float]" = OrderedDict()
+        self._lock = asyncio.Lock()
+
+    def __len__(self) -> int:
+        return len(self._entries)
+
+    async def claim(self, key: str, ttl_seconds: int) -> bool:
+        async with self._lock:
+            now = self._clock()
+            expires_at = self._entries.get(key)
+            if expires_at is not None and expires_at > now:
+                return False
+
+            self._entries[key] = now + ttl_seconds
+            self._entries.move_to_end(key)
+            while len(self._entries) > self.max_size:
+                self._entries.popitem(last=False)
+            return True
+
+    async def release(self, key: str) -> None:
+        async with self._lock:
+            self._entries.pop(key, None)
+
+
+class RedisDedupStore(DedupStore):
+    """
+    Seen keys shared by every worker through Redis (``SET key NX EX ttl``)
+
+    Works with ``redis.asyncio`` clients. Fails open: when Redis is unavailable
+    events are processed rather than dropped.
+    """
+
+    def __init__(self, client: Any, prefix: str = "pytake:webhook:seen:"):
+        self.client = client
+        self.prefix = prefix
+
+    async def _set_nx(self, key: str, ttl_seconds: int) -> bool:
+        return bool(await self.client.set(key, "1", ex=ttl_seconds, nx=True))
+
+    async def claim(self, key: str, ttl_seconds: int) -> bool:
+        try:
+            return await self._set_nx(self.prefix + key, ttl_seconds)
+        except Exception as e:
+            logger.warning(f"⚠️ Webhook dedup store unavailable, processing {key}: {e}")
+            return True
+
+    async def release(self, key: str) -> None:
+        try:
+            await self.client.delete(self.prefix + key)
+        except Exception as e:
+            logger.warning(f"⚠️ Could not release webhook dedup key {key}: {e}")
+
+
+# ============================================
+# HANDLERS
+# ============================================
+
+
+class WebhookHandler:
+    """
+    Base class for webhook event handlers
+
+    Override the methods for the events you care about; the others do
+    nothing. Specific message events (order, reaction, edit, identity change)
+    fall back to on_message and payments to on_status unless overridden.
+
+        class Support(WebhookHandler):
+            async def on_message(self, event):
+                message = event.inbound
+                ...
+
+            async def on_status(self, event):
+                ...
+
+        processor = WebhookProcessor(handlers=[Support()])
+        await processor.dispatch(payload)
+    """
+
+    async def on_message(self, event: WebhookEvent) -> None:
+        """Any entry of ``messages[]`` (typed content in ``event.inbound``)"""
+
+    async def on_order(self, event: WebhookEvent) -> None:
+        await self.on_message(event)
+
+    async def on_reaction(self, event: WebhookEvent) -> None:
+        await self.on_message(event)
+
+    async def on_edit(self, event: WebhookEvent) -> None:
+        await self.on_message(event)
+
+    async def on_identity_change(self, event: WebhookEvent) -> None:
+        """Contact changed number or identity (``event.identity_change``)"""
+        await self.on_message(event)
+
+    async def on_echo(self, event: WebhookEvent) -> None:
+        """Message sent from the WhatsApp Business app (``event.echo``)"""
+
+    async def on_status(self, event: WebhookEvent) -> None:
+        """Delivery status of a message sent by the business"""
+
+    async def on_payment(self, event: WebhookEvent) -> None:
+        await self.on_status(event)
+
+    async def on_template_update(self, event: WebhookEvent) -> None:
+        """Template review result (``event.template_status``)"""
+
+    async def on_quality_update(self, event: WebhookEvent) -> None:
+        """Quality rating / messaging limit tier change (``event.quality_update``)"""
+
+    async def on_error(self, event: WebhookEvent, error: Exception) -> None:
+        """
+        Called when another method of this handler raised
+
+        The default re-raises so the failure reaches the caller of dispatch();
+        override to log and continue with the next events.
+        """
+        raise error
+
+
+# Handler method for each event type
+HANDLER_METHODS: Dict[WebhookEventType, str] = {
+    WebhookEventType.MESSAGE: "on_message",
+    WebhookEventType.REQUEST_WELCOME: "on_message",
+    WebhookEventType.UNKNOWN: "on_message",
+    WebhookEventType.ORDER: "on_order",
+    WebhookEventType.REACTION: "on_reaction",
+    WebhookEventType.EDIT: "on_edit",
+    WebhookEventType.IDENTITY_CHANGE: "on_identity_change",
+    WebhookEventType.MESSAGE_ECHO: "on_echo",
+    WebhookEventType.STATUS: "on_status",
+    WebhookEventType.PAYMENT: "on_payment",
+    WebhookEventType.TEMPLATE_STATUS: "on_template_update",
+    WebhookEventType.PHONE_NUMBER_QUALITY: "on_quality_update",
+}
+
+
+def ordering_key(event: WebhookEvent) -> str:
+    """
+    Events sharing this key (same number and contact) are processed in order
+
+    Messages are keyed by sender, statuses and echoes by recipient; template
+    and quality updates by number only.
+    """
+    if event.type == WebhookEventType.MESSAGE_ECHO:
+        contact = event.data.get("to") or ""
+    else:
+        contact = event.data.get("from") or event.data.get("recipient_id") or ""
+    return f"{event.phone_number_id or ''}:{contact}"
+
+
+async def call_handlers(handlers: List[WebhookHandler], event: WebhookEvent) -> None:
+    """Call the method of the event's kind on each handler, in order"""
+    method = HANDLER_METHODS.get(event.type, "on_message")
+    for handler in handlers:
+        try:
+            await getattr(handler, method)(event)
+        except Exception as e:
+            await handler.on_error(event, e)
+
+
+@dataclass
+class EventResult:
+    """Outcome of a callback run on one event by WebhookProcessor.process()"""
+
+    event: WebhookEvent
+    result: Any = None
+    error: Optional[BaseException] = None
+
+    @property
+    def ok(self) -> bool:
+        return self.error is None
+
+
+# ============================================
+# ROUTING
+# ============================================
+
+# Reported per WhatsApp Business Account, without a phone_number_id
+ACCOUNT_EVENT_TYPES = (WebhookEventType.TEMPLATE_STATUS, WebhookEventType.PHONE_NUMBER_QUALITY)
+
+UNROUTABLE_MISSING = "missing_phone_number_id"
+UNROUTABLE_UNKNOWN = "unknown_phone_number_id"
+
+# Distinct unknown numbers kept in RoutingMetrics (all of them are counted)
+MAX_TRACKED_UNKNOWN_NUMBERS = 100
+
+
+@dataclass
+class NumberRoute:
+    """Tenant, settings and handlers of one phone number"""
+
+    phone_number_id: str
+    tenant: Any = None
+    config: Dict[str, Any] = field(default_factory=dict)
+    handlers: List[WebhookHandler] = field(default_factory=list)
+
+
+@dataclass
+class RoutingMetrics:
+    """Counters of a PhoneNumberRouter (per process)"""
+
+    routed: Dict[str, int] = field(default_factory=dict)
+    unroutable: Dict[str, int] = field(default_factory=dict)
+    unknown_numbers: Dict[str, int] = field(default_factory=dict)
+    account_events: int = 0
+    last_unroutable_at: Optional[float] = None
+
+    @property
+    def unroutable_total(self) -> int:
+        return sum(self.unroutable.values())
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "routed": dict(self.routed),
+            "unroutable": dict(self.unroutable),
+            "unroutable_total": self.unroutable_total,
+            "unknown_numbers": dict(self.unknown_numbers),
+            "account_events": self.account_events,
+            "last_unroutable_at": self.last_unroutable_at,
+        }
+
+
+def split_by_number(payload: Dict[str, Any]) -> "OrderedDict[Optional[str], Dict[str, Any]]":
+    """
+    One payload per phone_number_id, in order of first appearance
+
+    Meta may batch the changes of several numbers in one POST. Each part keeps
+    the entries' ids with only the changes of its number; changes without one
+    (template and quality updates) are grouped under None.
+    """
+    parts: "OrderedDict[Optional[str], Dict[str, Any]]" = OrderedDict()
+    envelope = {key: value for key, value in payload.items() if key != "entry"}
+    for entry in payload.get("entry", []):
+        entry_parts: Dict[Optional[str], Dict[str, Any]] = {}
+        for change in entry.get("changes", []):
+            phone_number_id = (change.get("value") or {}).get("metadata", {}).get("phone_number_id")
+            if phone_number_id not in entry_parts:
+                part = parts.setdefault(phone_number_id, {**envelope, "entry": []})
+                entry_parts[phone_number_id] = {
+                    **{key: value for key, value in entry.items() if key != "changes"},
+                    "changes": [],
+                }
+                part["entry"].append(entry_parts[phone_number_id])
+            entry_parts[phone_number_id]["changes"].append(change)
+    return parts
+
+
+RouteResolver = Callable[[str], Awaitable[Optional[NumberRoute]]]
+UnroutableCallback = Callable[[str, Optional[str], Optional[WebhookEvent]], Awaitable[None]]
+
+
+class PhoneNumberRouter:
+    """
+    Route webhook events by the number that received them
+
+    Maps ``metadata.phone_number_id`` to a NumberRoute: routes added with
+    add() first, then the resolver (e.g. a database lookup). Resolver answers
+    are cached for ``cache_ttl_seconds``, unknown numbers included, so events
+    of an unregistered number don't hit the database one by one.
+
+        router = PhoneNumberRouter()
+        router.add("1234", handlers=[StoreA()], tenant="store-a", config={"token": ...})
+        router.add("5678", handlers=[StoreB()], tenant="store-b")
+
+        processor = WebhookProcessor(store, router=router)
+        await processor.dispatch(payload)   # event.route.tenant in the handlers
+
+    Events without a number or with an unknown one are unroutable: they are
+    counted in ``metrics``, logged and passed to ``on_unroutable(reason,
+    phone_number_id, event)``. Template and quality updates belong to the
+    account, not to a number, and are left to the processor's handlers.
+    """
+
+    def __init__(
+        self,
+        resolver: Optional[RouteResolver] = None,
+        cache_ttl_seconds: float = 300,
+        on_unroutable: Optional[UnroutableCallback] = None,
+        clock=time.monotonic,
+    ):
+        self.resolver = resolver
+        self.cache_ttl_seconds = cache_ttl_seconds
+        self.on_unroutable = on_unroutable
+        self.metrics = RoutingMetrics()
+        self._clock = clock
+        self._routes: Dict[str, NumberRoute] = {}
+        self._cache: Dict[str, tuple] = {}
+
+    def add(
+        self,
+        phone_number_id: str,
+        handlers: Optional[List[WebhookHandler]] = None,
+        tenant: Any = None,
+        config: Optional[Dict[str, Any]] = None,
+    ) -> NumberRoute:
+        """Route a number to its own handlers (overrides the resolver)"""
+        route = NumberRoute(phone_number_id, tenant, dict(config or {}), list(handlers or []))
+        self._routes[phone_number_id] = route
+        return route
+
+    def remove(self, phone_number_id: str) -> None:
+        self._routes.pop(phone_number_id, None)
+        self.invalidate(phone_number_id)
+
+    def invalidate(self, phone_number_id: Optional[str] = None) -> None:
+        """Drop cached resolver answers (all of them without an id)"""
+        if phone_number_id is None:
+            self._cache.clear()
+        else:
+            self._cache.pop(phone_number_id, None)
+
+    async def resolve(self, phone_number_id: str) -> Optional[NumberRoute]:
+        """Route of a number, or None if unknown (resolver errors propagate)"""
+        route = self._routes.get(phone_number_id)
+        if route is not None or self.resolver is None:
+            return route
+
+        cached = self._cache.get(phone_number_id)
+        now = self._clock()
+        if cached and cached[0] > now:
+            return cached[1]
+
+        route = await self.resolver(phone_number_id)
+        self._cache[phone_number_id] = (now + self.cache_ttl_seconds, route)
+        return route
+
+    async def route_number(
+        self, phone_number_id: Optional[str], event: Optional[WebhookEvent] = None
+    ) -> Optional[NumberRoute]:
+        """Resolve a number and count the outcome"""
+        if not phone_number_id:
+            await self.record_unroutable(UNROUTABLE_MISSING, None, event)
+            return None
+
+        route = await self.resolve(phone_number_id)
+        if route is None:
+            await self.record_unroutable(UNROUTABLE_UNKNOWN, phone_number_id, event)
+            return None
+
+        self.metrics.routed[phone_number_id] = self.metrics.routed.get(phone_number_id, 0) + 1
+        return route
+
+    async def route(self, event: WebhookEvent) -> Optional[NumberRoute]:
+        """Set ``event.route`` (None for account events and unroutable ones)"""
+        if event.type in ACCOUNT_EVENT_TYPES and not event.phone_number_id:
+            self.metrics.account_events += 1
+            return None
+        event.route = await self.route_number(event.phone_number_id, event)
+        return event.route
+
+    async def dispatch(self, event: WebhookEvent) -> Optional[NumberRoute]:
+        """Route one event and call the handlers of its number"""
+        route = await self.route(event)
+        if route is not None:
+            await call_handlers(route.handlers, event)
+        return route
+
+    async def record_unroutable(
+        self, reason: str, phone_number_id: Optional[str] = None, event: Optional[WebhookEvent] = None
+    ) -> None:
+        """Count an event that no number claims (callback failures are only logged)"""
+        metrics = self.metrics
+        metrics.unroutable[reason] = metrics.unroutable.get(reason, 0) + 1
+        metrics.last_unroutable_at = time.time()
+        if phone_number_id and (
+            phone_number_id in metrics.unknown_numbers
+            or len(metrics.unknown_numbers) < MAX_TRACKED_UNKNOWN_NUMBERS
+        ):
+            metrics.unknown_numbers[phone_number_id] = metrics.unknown_numbers.get(phone_number_id, 0) + 1
+
+        logger.warning(f"🧭 Unroutable webhook event ({reason}): phone_number_id={phone_number_id}")
+
+        if self.on_unroutable is not None:
+            try:
+                await self.on_unroutable(reason, phone_number_id, event)
+            except Exception as e:
+                logger.error(f"❌ Unroutable callback failed: {e}")
+
+
+# ============================================
+# PROCESSOR
+# ============================================
+
+
+class WebhookProcessor:
+    """
+    Skip messages and status updates already received and route events to handlers
+
+    Args:
+        store: Dedup store (defaults to a per-process InMemoryDedupStore)
+        ttl_seconds: How long an id is remembered
+        handlers: WebhookHandler instances called by dispatch(), in order
+        media_fetcher: Opt-in MediaFetcher attaching inbound media to events
+            before dispatch()/process() hand them over
+        router: Opt-in PhoneNumberRouter setting ``event.route`` and calling
+            the handlers of the receiving number after the shared ones
+    """
+
+    def __init__(
+        self,
+        store: Optional[DedupStore] = None,
+        ttl_seconds: int = DEFAULT_DEDUP_TTL_SECONDS,
+        handlers: Optional[List[WebhookHandler]] = None,
+        media_fetcher: Optional[Any] = None,
+        router: Optional[PhoneNumberRouter] = None,
+    ):
+        self.store = store or InMemoryDedupStore()
+        self.ttl_seconds = ttl_seconds
+        self.handlers: List[WebhookHandler] = list(handlers or [])
+        self.media_fetcher = media_fetcher
+        self.router = router
+
+    def register(self, handler: WebhookHandler) -> WebhookHandler:
+        """Add a handler (returned, so it can be used inline)"""
+        self.handlers.append(handler)
+        return handler
+
+    async def claim(self, key: Optional[str]) -> bool:
+        """True if the key was not seen yet (events without a key always pass)"""
+        if not key:
+            return True
+        return await self.store.claim(key, self.ttl_seconds)
+
+    async def release(self, key: Optional[str]) -> None:
+        """Forget a key, e.g. when processing failed and should be retried"""
+        if key:
+            await self.store.release(key)
+
+    async def is_new(self, event: WebhookEvent) -> bool:
+        return await self.claim(event_dedup_key(event))
+
+    async def iter_events(self, payload: Dict[str, Any], dedup: bool = True) -> AsyncIterator[WebhookEvent]:
+        """
+        Stream the events of a payload, flattening its entries and changes
+
+        Each event is claimed when it is yielded, so a consumer that stops
+        early leaves the remaining ones unclaimed.
+
+            async for event in processor.iter_events(payload):
+                ...
+        """
+        for event in iter_events(payload):
+            if not dedup or await self.is_new(event):
+                yield event
+            else:
+                logger.info(f"⏭️ Duplicate webhook event skipped: {event_dedup_key(event)}")
+
+    async def new_events(self, payload: Dict[str, Any]) -> List[WebhookEvent]:
+        """Events of a webhook payload that were not received before"""
+        return [event async for event in self.iter_events(payload)]
+
+    async def process(
+        self,
+        payload: Dict[str, Any],
+        callback: Callable[[WebhookEvent], Awaitable[Any]],
+        concurrency: int = 10,
+    ) -> List[EventResult]:
+        """
+        Run a callback on every new event of a payload concurrently
+
+        Events of the same contact (see ordering_key) run one after the other,
+        in payload order; up to ``concurrency`` contacts run at once. A failing
+        event does not stop the others: its error is returned in its result
+        and its dedup key released so Meta's redelivery retries only that one.
+
+        Returns:
+            One EventResult per new event, in payload order
+        """
+        results: List[EventResult] = []
+        lanes: "OrderedDict[str, List[EventResult]]" = OrderedDict()
+        async for event in self.iter_events(payload):
+            result = EventResult(event)
+            results.append(result)
+            lanes.setdefault(ordering_key(event), []).append(result)
+
+        semaphore = asyncio.Semaphore(max(1, concurrency))
+
+        async def run_lane(lane: List[EventResult]) -> None:
+            async with semaphore:
+                for result in lane:
+                    try:
+                        if self.router is not None:
+                            await self.router.route(result.event)
+                        await self.fetch_media(result.event)
+                        result.result = await callback(result.event)
+                    except Exception as e:
+                        result.error = e
+                        await self.release(event_dedup_key(result.event))
+                        logger.error(f"❌ Webhook event {event_dedup_key(result.event)} failed: {e}")
+
+        await asyncio.gather(*(run_lane(lane) for lane in lanes.values()))
+        return results
+
+    async def dispatch(self, payload: Dict[str, Any]) -> List[WebhookEvent]:
+        """
+        Route the new events of a payload to every registered handler
+
+        When a handler fails (its on_error re-raised), the dedup keys of that
+        event and of the ones not dispatched yet are released so Meta's
+        redelivery processes them again, and the error propagates.
+
+        Returns:
+            The events that were dispatched
+        """
+        events = await self.new_events(payload)
+        for index, event in enumerate(events):
+            try:
+                await self.dispatch_event(event)
+            except Exception:
+                for pending in events[index:]:
+                    await self.release(event_dedup_key(pending))
+                raise
+        return events
+
+    async def fetch_media(self, event: WebhookEvent) -> None:
+        """Attach the event's inbound media when a media fetcher is configured"""
+        if self.media_fetcher is not None and event.media is None:
+            await self.media_fetcher.attach(event)
+
+    async def dispatch_event(self, event: WebhookEvent) -> None:
+        """Route one event (no deduplication) to every registered handler"""
+        await self.fetch_media(event)
+        await call_handlers(self.handlers, event)
+        if self.router is not None:
+            await self.router.dispatch(event)
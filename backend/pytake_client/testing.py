@@ -0,0 +1,435 @@
+"""
+Test helpers: fake Graph API and webhook payload builders
+
+Opt-in (not imported by the package) and with no extra dependencies. The fake
+serves the WhatsApp Cloud API endpoints through an httpx.MockTransport, so
+integrations can be tested without credentials or network:
+
+    from pytake_client.testing import FakeGraphAPI, text_message, webhook_payload
+
+    graph = FakeGraphAPI(access_token="test-token")
+    graph.add_media("media-1", b"...", mime_type="image/jpeg")
+
+    fetcher = MediaFetcher("test-token", storage, http_client=graph.client())
+    ...
+    assert graph.sent_messages[0]["text"]["body"] == "Olá!"
+
+    payload = webhook_payload(text_message("Olá", sender="5511999999999"))
+
+Served endpoints (any Graph API version): POST /{phone_number_id}/messages,
+POST /{phone_number_id}/media, GET/DELETE /{media_id} and the media download
+URL, GET/POST/DELETE /{waba_id}/message_templates. Anything else answers 404
+with a Graph error body; fail_next() queues errors for the next calls.
+"""
+
+import hashlib
+import hmac
+import json
+import re
+import time
+from dataclasses import dataclass, field
+from email import policy
+from email.parser import BytesParser
+from itertools import count
+from typing import Any, Dict, List, Optional, Tuple
+
+import httpx
+
+FAKE_MEDIA_HOST = "https://lookaside.fbsbx.test"
+
+_VERSIONED_PATH = re.compile(r"^/v\d+\.\d+/(.+)$")
+
+
+@dataclass
+class FakeMedia:
+    """Media stored by the fake (uploaded or added with add_media)"""
+
+    media_id: str
+    content: bytes
+    mime_type: str
+    phone_number_id: Optional[str] = None
+
+    @property
+    def sha256(self) -> str:
+        return hashlib.sha256(self.content).hexdigest()
+
+
+@dataclass
+class RecordedRequest:
+    """Request received by the fake"""
+
+    method: str
+    path: str
+    params: Dict[str, str]
+    headers: Dict[str, str]
+    body: Any = None
+
+
+@dataclass
+class _QueuedError:
+    status_code: int
+    code: int
+    message: str
+    path: Optional[str] = None
+
+
+@dataclass
+class FakeGraphAPI:
+    """
+    In-memory WhatsApp Cloud API
+
+    Any host is accepted, so clients keep their configured Graph API URL.
+
+    Args:
+        access_token: When set, requests with another Bearer token get a 401 (code 190)
+    """
+
+    access_token: Optional[str] = None
+    requests: List[RecordedRequest] = field(default_factory=list)
+    sent_messages: List[Dict[str, Any]] = field(default_factory=list)
+    media: Dict[str, FakeMedia] = field(default_factory=dict)
+    templates: Dict[str, List[Dict[str, Any]]] = field(default_factory=dict)
+
+    def __post_init__(self):
+        self._ids = count(1)
+        self._errors: List[_QueuedError] = []
+
+    # ------------------------------------------------------------------
+    # Setup
+    # ------------------------------------------------------------------
+
+    @property
+    def transport(self) -> httpx.MockTransport:
+        return httpx.MockTransport(self.handle)
+
+    def client(self, **kwargs: Any) -> httpx.AsyncClient:
+        """httpx.AsyncClient served by the fake (kwargs go to the client)"""
+        return httpx.AsyncClient(transport=self.transport, **kwargs)
+
+    def add_media(self, media_id: str, content: bytes, mime_type: str = "image/jpeg") -> FakeMedia:
+        """Make a media id resolvable, e.g. the one of an inbound webhook"""
+        self.media[media_id] = FakeMedia(media_id, content, mime_type)
+        return self.media[media_id]
+
+    def add_template(
+        self,
+        waba_id: str,
+        name: str,
+        language: str = "pt_BR",
+        status: str = "APPROVED",
+        category: str = "UTILITY",
+        components: Optional[List[Dict[str, Any]]] = None,
+    ) -> Dict[str, Any]:
+        """Add a template to the account's message_templates listing"""
+        template = {
+            "id": self._next_id("tpl"),
+            "name": name,
+            "language": language,
+            "status": status,
+            "category": category,
+            "components": components or [],
+        }
+        self.templates.setdefault(waba_id, []).append(template)
+        return template
+
+    def fail_next(self, status_code: int = 500, code: int = 1, message: str = "Fake error",
+                  path: Optional[str] = None) -> None:
+        """
+        Answer the next matching request with a Graph error
+
+        Args:
+            status_code: HTTP status
+            code: Graph error code (e.g. 131047, 130429, 190)
+            message: Error message
+            path: Only fail requests to this path (without version), e.g. "123/messages"
+        """
+        self._errors.append(_QueuedError(status_code, code, message, path))
+
+    # ------------------------------------------------------------------
+    # Dispatch
+    # ------------------------------------------------------------------
+
+    def handle(self, request: httpx.Request) -> httpx.Response:
+        url = str(request.url)
+        if url.startswith(FAKE_MEDIA_HOST):
+            return self._download(request)
+
+        match = _VERSIONED_PATH.match(request.url.path)
+        path = match.group(1).strip("/") if match else request.url.path.strip("/")
+        self.requests.append(RecordedRequest(
+            method=request.method,
+            path=path,
+            params=dict(request.url.params),
+            headers=dict(request.headers),
+            body=self._body(request),
+        ))
+
+        if self.access_token and request.headers.get("Authorization") != f"Bearer {self.access_token}":
+            return graph_error(401, 190, "Invalid OAuth access token.")
+
+        for queued in self._errors:
+            if queued.path is None or queued.path == path:
+                self._errors.remove(queued)
+                return graph_error(queued.status_code, queued.code, queued.message)
+
+        if not match:
+            return graph_error(404, 100, "Unsupported request - missing API version")
+
+        node, _, edge = path.partition("/")
+        body = self.requests[-1].body
+        if edge == "messages" and request.method == "POST":
+            return self._send(node, body)
+        if edge == "media" and request.method == "POST":
+            return self._upload(node, request)
+        if edge == "message_templates":
+            return self._templates(request.method, node, body, dict(request.url.params))
+        if not edge and node in self.media:
+            return self._media(request.method, self.media[node])
+
+        return graph_error(404, 100, f"Unsupported {request.method} request on {path}")
+
+    # ------------------------------------------------------------------
+    # Endpoints
+    # ------------------------------------------------------------------
+
+    def _send(self, phone_number_id: str, body: Any) -> httpx.Response:
+        if not isinstance(body, dict) or not body.get("to"):
+            return graph_error(400, 100, "The parameter to is required.")
+
+        message_id = self._next_id("wamid.FAKE")
+        self.sent_messages.append({**body, "id": message_id, "phone_number_id": phone_number_id})
+        return httpx.Response(200, json={
+            "messaging_product": "whatsapp",
+            "contacts": [{"input": body["to"], "wa_id": re.sub(r"\D", "", body["to"])}],
+            "messages": [{"id": message_id}],
+        })
+
+    def _upload(self, phone_number_id: str, request: httpx.Request) -> httpx.Response:
+        content, mime_type = _multipart_file(request)
+        if content is None:
+            return graph_error(400, 100, "The parameter file is required.")
+
+        media = self.add_media(self._next_id("media"), content, mime_type or "application/octet-stream")
+        media.phone_number_id = phone_number_id
+        return httpx.Response(200, json={"id": media.media_id})
+
+    def _media(self, method: str, media: FakeMedia) -> httpx.Response:
+        if method == "DELETE":
+            del self.media[media.media_id]
+            return httpx.Response(200, json={"success": True})
+
+        return httpx.Response(200, json={
+            "messaging_product": "whatsapp",
+            "id": media.media_id,
+            "url": f"{FAKE_MEDIA_HOST}/{media.media_id}",
+            "mime_type": media.mime_type,
+            "sha256": media.sha256,
+            "file_size": len(media.content),
+        })
+
+    def _download(self, request: httpx.Request) -> httpx.Response:
+        media = self.media.get(request.url.path.strip("/"))
+        if media is None:
+            return httpx.Response(404)
+        return httpx.Response(200, content=media.content, headers={"Content-Type": media.mime_type})
+
+    def _templates(self, method: str, waba_id: str, body: Any, params: Dict[str, str]) -> httpx.Response:
+        templates = self.templates.setdefault(waba_id, [])
+
+        if method == "GET":
+            listed = [t for t in templates if "name" not in params or t["name"] == params["name"]]
+            return httpx.Response(200, json={"data": listed, "paging": {"cursors": {}}})
+
+        if method == "POST":
+            if not isinstance(body, dict) or not body.get("name"):
+                return graph_error(400, 100, "The parameter name is required.")
+            template = self.add_template(
+                waba_id,
+                body["name"],
+                language=body.get("language", "pt_BR"),
+                status="PENDING",
+                category=body.get("category", "UTILITY"),
+                components=body.get("components"),
+            )
+            return httpx.Response(200, json={
+                "id": template["id"], "status": "PENDING", "category": template["category"],
+            })
+
+        if method == "DELETE":
+            name = params.get("name")
+            remaining = [t for t in templates if t["name"] != name]
+            if len(remaining) == len(templates):
+                return graph_error(404, 100, f"Template {name} not found")
+            self.templates[waba_id] = remaining
+            return httpx.Response(200, json={"success": True})
+
+        return graph_error(405, 100, f"Unsupported {method} on message_templates")
+
+    # ------------------------------------------------------------------
+    # Helpers
+    # ------------------------------------------------------------------
+
+    def _next_id(self, prefix: str) -> str:
+        return f"{prefix}.{next(self._ids)}"
+
+    @staticmethod
+    def _body(request: httpx.Request) -> Any:
+        if not request.content or "json" not in request.headers.get("Content-Type", ""):
+            return None
+        return json.loads(request.content)
+
+
+def graph_error(status_code: int, code: int, message: str) -> httpx.Response:
+    """Response with Meta's error envelope"""
+    return httpx.Response(status_code, json={
+        "error": {"message": message, "type": "OAuthException", "code": code, "fbtrace_id": "FAKE"},
+    })
+
+
+def _multipart_file(request: httpx.Request) -> Tuple[Optional[bytes], Optional[str]]:
+    content_type = request.headers.get("Content-Type", "")
+    if not content_type.startswith("multipart/"):
+        return None, None
+
+    message = BytesParser(policy=policy.HTTP).parsebytes(
+        f"Content-Type: {content_type}\r\n\r\n".encode() + request.read()
+    )
+    for part in message.iter_parts():
+        if part.get_param("name", header="content-disposition") == "file":
+            return part.get_payload(decode=True), part.get_content_type()
+    return None, None
+
+
+# ============================================
+# WEBHOOK PAYLOADS
+# ============================================
+
+
+def webhook_payload(
+    *messages: Dict[str, Any],
+    statuses: Optional[List[Dict[str, Any]]] = None,
+    phone_number_id: str = "123456789",
+    display_phone_number: str = "5511900000000",
+    waba_id: str = "987654321",
+    contact_name: str = "Cliente Teste",
+) -> Dict[str, Any]:
+    """Messages webhook (field == "messages") with the given messages and statuses"""
+    value: Dict[str, Any] = {
+        "messaging_product": "whatsapp",
+        "metadata": {"display_phone_number": display_phone_number, "phone_number_id": phone_number_id},
+    }
+    if messages:
+        value["contacts"] = [
+            {"profile": {"name": contact_name}, "wa_id": wa_id}
+            for wa_id in dict.fromkeys(message["from"] for message in messages)
+        ]
+        value["messages"] = list(messages)
+    if statuses:
+        value["statuses"] = statuses
+    return _envelope(waba_id, "messages", value)
+
+
+def text_message(body: str, sender: str = "5511999999999", message_id: Optional[str] = None,
+                 timestamp: Optional[int] = None) -> Dict[str, Any]:
+    """Inbound text message"""
+    return {**_message(sender, message_id, timestamp, "text"), "text": {"body": body}}
+
+
+def media_message(media_type: str, media_id: str, mime_type: str = "image/jpeg", caption: Optional[str] = None,
+                  filename: Optional[str] = None, sender: str = "5511999999999",
+                  message_id: Optional[str] = None, content: Optional[bytes] = None) -> Dict[str, Any]:
+    """
+    Inbound image/audio/video/document/sticker message
+
+    Pass the same media_id to FakeGraphAPI.add_media() so MediaFetcher can
+    download it; ``content`` fills in the announced sha256.
+    """
+    media: Dict[str, Any] = {"id": media_id, "mime_type": mime_type}
+    if caption:
+        media["caption"] = caption
+    if filename:
+        media["filename"] = filename
+    if content is not None:
+        media["sha256"] = hashlib.sha256(content).hexdigest()
+    return {**_message(sender, message_id, None, media_type), media_type: media}
+
+
+def button_reply(button_id: str, title: str, sender: str = "5511999999999",
+                 message_id: Optional[str] = None) -> Dict[str, Any]:
+    """Inbound reply to an interactive reply button"""
+    return {
+        **_message(sender, message_id, None, "interactive"),
+        "interactive": {"type": "button_reply", "button_reply": {"id": button_id, "title": title}},
+    }
+
+
+def address_reply(values: Dict[str, Any], saved_address_id: Optional[str] = None,
+                  sender: str = "5511999999999", message_id: Optional[str] = None) -> Dict[str, Any]:
+    """Inbound answer to an address message (native address form)"""
+    response: Dict[str, Any] = {"values": values}
+    if saved_address_id:
+        response["saved_address_id"] = saved_address_id
+    return {
+        **_message(sender, message_id, None, "interactive"),
+        "interactive": {
+            "type": "nfm_reply",
+            "nfm_reply": {
+                "name": "address_message",
+                "body": ", ".join(str(value) for value in values.values()),
+                "response_json": json.dumps(response),
+            },
+        },
+    }
+
+
+def status_update(message_id: str, status: str = "delivered", recipient: str = "5511999999999",
+                  error_code: Optional[int] = None, error_title: str = "Fake error",
+                  timestamp: Optional[int] = None) -> Dict[str, Any]:
+    """statuses[] entry (sent, delivered, read, failed); error_code fills errors[] of a failure"""
+    entry: Dict[str, Any] = {
+        "id": message_id,
+        "status": status,
+        "timestamp": str(timestamp or int(time.time())),
+        "recipient_id": recipient,
+    }
+    if error_code is not None:
+        entry["errors"] = [{"code": error_code, "title": error_title, "message": error_title}]
+    return entry
+
+
+def template_status_update(name: str, event: str = "APPROVED", template_id: str = "1",
+                           language: str = "pt_BR", reason: str = "NONE",
+                           waba_id: str = "987654321") -> Dict[str, Any]:
+    """Template review webhook (field == "message_template_status_update")"""
+    return _envelope(waba_id, "message_template_status_update", {
+        "event": event,
+        "message_template_id": template_id,
+        "message_template_name": name,
+        "message_template_language": language,
+        "reason": reason,
+    })
+
+
+def sign_payload(payload: Any, secret: str) -> Tuple[bytes, str]:
+    """Raw body and X-Hub-Signature-256 header of a payload, as Meta sends them"""
+    body = payload if isinstance(payload, bytes) else json.dumps(payload).encode("utf-8")
+    return body, "sha256=" + hmac.new(secret.encode("utf-8"), body, hashlib.sha256).hexdigest()
+
+
+_message_ids = count(1)
+
+
+def _message(sender: str, message_id: Optional[str], timestamp: Optional[int], message_type: str) -> Dict[str, Any]:
+    return {
+        "from": sender,
+        "id": message_id or f"wamid.IN.{next(_message_ids)}",
+        "timestamp": str(timestamp or int(time.time())),
+        "type": message_type,
+    }
+
+
+def _envelope(waba_id: str, field_name: str, value: Dict[str, Any]) -> Dict[str, Any]:
+    return {
+        "object": "whatsapp_business_account",
+        "entry": [{"id": waba_id, "changes": [{"field": field_name, "value": value}]}],
+    }
@@ -0,0 +1,1305 @@
+"""
+Webhook payloads
+
+Typed representations of Meta Cloud API webhook payloads: every inbound
+message type (InboundMessage), business events (orders, payments) and
+changes to earlier messages (reactions, edits), security notifications
+(IdentityChange) and messages sent from the WhatsApp Business app
+(MessageEcho), plus helpers for tenant integrations that receive webhooks
+forwarded by PyTake:
+
+    from pytake_client.webhooks import iter_events, verify_signature
+
+    if verify_signature(raw_body, request.headers["X-Hub-Signature-256"], secret):
+        for event in iter_events(json.loads(raw_body)):
+            ...
+
+The backend re-exports these models from app.schemas.webhook.
+"""
+
+import hashlib
+import hmac
+import json
+from enum import Enum
+from typing import Any, Dict, Iterator, List, Mapping, Optional, TypeVar
+
+from pydantic import BaseModel, Field
+
+K = TypeVar("K")
+
+
+class WebhookEventType(str, Enum):
+    """Kind of event carried by a webhook message or status"""
+
+    MESSAGE = "message"
+    STATUS = "status"
+    ORDER = "order"
+    PAYMENT = "payment"
+    REACTION = "reaction"
+    EDIT = "edit"
+    REQUEST_WELCOME = "request_welcome"
+    TEMPLATE_STATUS = "template_status"
+    PHONE_NUMBER_QUALITY = "phone_number_quality"
+    IDENTITY_CHANGE = "identity_change"
+    MESSAGE_ECHO = "message_echo"
+    UNKNOWN = "unknown"
+
+
+# ============================================
+# CONTEXT (REPLIES / FORWARDS)
+# ============================================
+
+
+class MessageContext(BaseModel):
+    """
+    Context of an inbound message: quoted message and forward flags
+
+    Meta payload (message.context):
+    {
+      "context": {
+        "from": "5511888888888",
+        "id": "wamid.quoted",
+        "forwarded": true,
+        "frequently_forwarded": false
+      }
+    }
+    """
+
+    quoted_message_id: Optional[str] = None
+    quoted_from: Optional[str] = None
+    forwarded: bool = False
+    frequently_forwarded: bool = False
+    referred_product: Optional[Dict[str, Any]] = None
+
+    @property
+    def is_reply(self) -> bool:
+        return bool(self.quoted_message_id)
+
+    @classmethod
+    def from_webhook(cls, message: Dict[str, Any]) -> Optional["MessageContext"]:
+        """Build from a raw webhook message (None when there is no context)"""
+        context = message.get("context")
+        if not context:
+            return None
+        return cls(
+            quoted_message_id=context.get("id"),
+            quoted_from=context.get("from"),
+            forwarded=bool(context.get("forwarded", False)),
+            frequently_forwarded=bool(context.get("frequently_forwarded", False)),
+            referred_product=context.get("referred_product"),
+        )
+
+
+# ============================================
+# REACTIONS & EDITS
+# ============================================
+
+
+class MessageReaction(BaseModel):
+    """
+    Reaction of a contact to an earlier message (an empty emoji removes it)
+
+    Meta payload (message.type == "reaction"):
+    {
+      "from": "5511999999999",
+      "id": "wamid.reaction",
+      "timestamp": "1700000000",
+      "type": "reaction",
+      "reaction": {"message_id": "wamid.target", "emoji": "👍"}
+    }
+    """
+
+    message_id: Optional[str] = None
+    emoji: Optional[str] = None
+    sender: Optional[str] = None
+    timestamp: Optional[int] = None
+
+    @property
+    def is_removal(self) -> bool:
+        return not self.emoji
+
+    @classmethod
+    def from_webhook(cls, message: Dict[str, Any]) -> "MessageReaction":
+        """Build from a raw webhook message"""
+        reaction = message.get("reaction") or {}
+        return cls(
+            message_id=reaction.get("message_id"),
+            emoji=reaction.get("emoji") or None,
+            sender=message.get("from"),
+            timestamp=message.get("timestamp"),
+        )
+
+
+class MessageEdit(BaseModel):
+    """
+    New content of a message edited by the contact
+
+    Meta payload (message.type == "edit"):
+    {
+      "from": "5511999999999",
+      "id": "wamid.edit",
+      "timestamp": "1700000000",
+      "type": "edit",
+      "edit": {
+        "original_message_id": "wamid.original",
+        "message": {"type": "text", "text": {"body": "new text"}}
+      }
+    }
+    """
+
+    original_message_id: Optional[str] = None
+    message_type: str = "text"
+    content: Dict[str, Any] = Field(default_factory=dict)
+    sender: Optional[str] = None
+    timestamp: Optional[int] = None
+
+    @classmethod
+    def from_webhook(cls, message: Dict[str, Any]) -> "MessageEdit":
+        """Build from a raw webhook message"""
+        edit = message.get("edit") or {}
+        edited = edit.get("message") or {}
+        message_type = edited.get("type", "text")
+
+        if message_type == "text":
+            content = {"text": (edited.get("text") or {}).get("body", "")}
+        else:
+            # Media captions: keep the media object as stored for new messages
+            content = {message_type: edited.get(message_type) or {}}
+
+        return cls(
+            original_message_id=edit.get("original_message_id"),
+            message_type=message_type,
+            content=content,
+            sender=message.get("from"),
+            timestamp=message.get("timestamp"),
+        )
+
+
+# ============================================
+# ORDERS
+# ============================================
+
+
+class OrderProductItem(BaseModel):
+    """Product line of an order message"""
+
+    product_retailer_id: str
+    quantity: int = Field(default=1, ge=0)
+    item_price: float = Field(default=0.0, ge=0)
+    currency: str = "BRL"
+
+    @property
+    def subtotal(self) -> float:
+        return round(self.quantity * self.item_price, 2)
+
+
+class OrderMessage(BaseModel):
+    """
+    Order sent by a contact from a catalog / cart
+
+    Meta payload (message.type == "order"):
+    {
+      "order": {
+        "catalog_id": "123",
+        "text": "optional note",
+        "product_items": [
+          {"product_retailer_id": "SKU1", "quantity": "2", "item_price": "10.5", "currency": "BRL"}
+        ]
+      }
+    }
+    """
+
+    catalog_id: Optional[str] = None
+    text: Optional[str] = None
+    product_items: List[OrderProductItem] = Field(default_factory=list)
+
+    @property
+    def currency(self) -> Optional[str]:
+        return self.product_items[0].currency if self.product_items else None
+
+    @property
+    def total_amount(self) -> float:
+        return round(sum(item.subtotal for item in self.product_items), 2)
+
+    @property
+    def total_items(self) -> int:
+        return sum(item.quantity for item in self.product_items)
+
+    @classmethod
+    def from_webhook(cls, message: Dict[str, Any]) -> "OrderMessage":
+        """Build from a raw webhook message"""
+        return cls.model_validate(message.get("order") or {})
+
+    def to_content(self) -> Dict[str, Any]:
+        """Content stored in Message.content / flow variables"""
+        data = self.model_dump()
+        data["total_amount"] = self.total_amount
+        data["total_items"] = self.total_items
+        data["currency"] = self.currency
+        return data
+
+
+# ============================================
+# PAYMENTS
+# ============================================
+
+
+class PaymentAmount(BaseModel):
+    """Amount in minor units (value / offset)"""
+
+    value: int = 0
+    offset: int = 100
+
+    @property
+    def decimal(self) -> float:
+        return round(self.value / (self.offset or 1), 2)
+
+
+class PaymentTransaction(BaseModel):
+    """Transaction attached to a payment status"""
+
+    id: Optional[str] = None
+    type: Optional[str] = None
+    status: Optional[str] = None
+    created_timestamp: Optional[int] = None
+    updated_timestamp: Optional[int] = None
+    method: Dict[str, Any] = Field(default_factory=dict)
+    error: Optional[Dict[str, Any]] = None
+
+
+class PaymentStatus(BaseModel):
+    """
+    Payment status notification
+
+    Meta payload (status.type == "payment"):
+    {
+      "id": "wamid.xxx",
+      "status": "captured",
+      "type": "payment",
+      "timestamp": "1700000000",
+      "recipient_id": "5511999999999",
+      "payment": {
+        "reference_id": "order-123",
+        "amount": {"value": 21000, "offset": 100},
+        "currency": "BRL",
+        "transaction": {"id": "tx-1", "type": "pix", "status": "success"}
+      }
+    }
+    """
+
+    id: Optional[str] = None
+    status: str
+    timestamp: Optional[int] = None
+    recipient_id: Optional[str] = None
+    reference_id: Optional[str] = None
+    amount: PaymentAmount = Field(default_factory=PaymentAmount)
+    currency: Optional[str] = None
+    transaction: Optional[PaymentTransaction] = None
+
+    @classmethod
+    def from_webhook(cls, status: Dict[str, Any]) -> "PaymentStatus":
+        """Build from a raw webhook status entry"""
+        payment = status.get("payment") or {}
+        return cls(
+            id=status.get("id"),
+            status=status.get("status", "unknown"),
+            timestamp=status.get("timestamp"),
+            recipient_id=status.get("recipient_id"),
+            reference_id=payment.get("reference_id"),
+            amount=payment.get("amount") or {},
+            currency=payment.get("currency"),
+            transaction=payment.get("transaction"),
+        )
+
+    def to_variables(self) -> Dict[str, Any]:
+        """Flat dict exposed to flows"""
+        return {
+            "reference_id": self.reference_id,
+            "status": self.status,
+            "amount": self.amount.decimal,
+            "currency": self.currency,
+            "transaction_id": self.transaction.id if self.transaction else None,
+            "transaction_status": self.transaction.status if self.transaction else None,
+        }
+
+
+# ============================================
+# TEMPLATES
+# ============================================
+
+
+class TemplateStatusUpdate(BaseModel):
+    """
+    Template review result (field == "message_template_status_update")
+
+    Meta payload (change.value):
+    {
+      "event": "APPROVED",
+      "message_template_id": 123456789,
+      "message_template_name": "order_confirmation",
+      "message_template_language": "pt_BR",
+      "reason": "NONE"
+    }
+    """
+
+    event: str
+    template_id: Optional[str] = None
+    name: Optional[str] = None
+    language: Optional[str] = None
+    reason: Optional[str] = None
+
+    @property
+    def rejected_reason(self) -> Optional[str]:
+        """Reason only when it carries information ("NONE" means no reason)"""
+        if not self.reason or self.reason == "NONE":
+            return None
+        return self.reason
+
+    @classmethod
+    def from_webhook(cls, value: Dict[str, Any]) -> "TemplateStatusUpdate":
+        """Build from the ``value`` of a template status change"""
+        template_id = value.get("message_template_id")
+        return cls(
+            event=str(value.get("event", "UNKNOWN")).upper(),
+            template_id=str(template_id) if template_id is not None else None,
+            name=value.get("message_template_name"),
+            language=value.get("message_template_language"),
+            reason=value.get("reason"),
+        )
+
+
+# ============================================
+# PHONE NUMBERS
+# ============================================
+
+
+class PhoneNumberQualityUpdate(BaseModel):
+    """
+    Quality/messaging limit change of a number (field == "phone_number_quality_update")
+
+    Meta payload (change.value):
+    {
+      "display_phone_number": "5511999999999",
+      "event": "UPGRADE",
+      "current_limit": "TIER_10K",
+      "old_limit": "TIER_1K"
+    }
+
+    event: FLAGGED, UNFLAGGED, UPGRADE, DOWNGRADE, ONBOARDING
+    """
+
+    display_phone_number: str
+    event: str
+    current_limit: Optional[str] = None
+    old_limit: Optional[str] = None
+
+    @property
+    def digits(self) -> str:
+        """Phone number without formatting (matches WhatsAppNumber.phone_number)"""
+        return "".join(ch for ch in self.display_phone_number if ch.isdigit())
+
+    @classmethod
+    def from_webhook(cls, value: Dict[str, Any]) -> "PhoneNumberQualityUpdate":
+        """Build from the ``value`` of a phone number quality change"""
+        return cls(
+            display_phone_number=str(value.get("display_phone_number", "")),
+            event=str(value.get("event", "UNKNOWN")).upper(),
+            current_limit=value.get("current_limit"),
+            old_limit=value.get("old_limit"),
+        )
+
+
+# ============================================
+# DELIVERY ERRORS
+# ============================================
+
+
+class DeliveryErrorCode(str, Enum):
+    """Meta error codes of failed sends and failed status webhooks"""
+
+    REENGAGEMENT_WINDOW = "131047"  # More than 24h since the customer last replied
+    UNDELIVERABLE = "131026"  # Not on WhatsApp, old app version, not accepted terms...
+    MARKETING_OPT_OUT = "131050"  # Customer stopped marketing messages
+    EXPERIMENT = "130472"  # Number is part of a Meta experiment
+    ECOSYSTEM_ENGAGEMENT = "131049"  # Meta held the message to keep engagement healthy
+    SPAM_RATE_LIMIT = "131048"
+    PAIR_RATE_LIMIT = "131056"  # Too many messages to the same recipient
+    RATE_LIMIT = "130429"  # Cloud API throughput reached
+    GENERIC_ERROR = "131000"
+    SERVICE_UNAVAILABLE = "131016"
+    RECIPIENT_IS_SENDER = "131021"
+    RECIPIENT_NOT_ALLOWED = "131030"  # Test number sending to a number off its allowed list
+    ACCOUNT_LOCKED = "131031"
+    PAYMENT_ISSUE = "131042"
+    INVALID_PARAMETER = "131009"
+    UNSUPPORTED_MESSAGE_TYPE = "131051"
+    TEMPLATE_PARAMETER_MISMATCH = "132000"
+    TEMPLATE_NOT_FOUND = "132001"
+    TEMPLATE_PAUSED = "132015"
+    TEMPLATE_DISABLED = "132016"
+    NUMBER_NOT_REGISTERED = "133010"
+    UNKNOWN = "unknown"
+
+
+# code -> (needs_template, permanent_failure, guidance)
+DELIVERY_ERROR_GUIDANCE = {
+    DeliveryErrorCode.REENGAGEMENT_WINDOW: (
+        True, False, "Outside the 24h customer service window - send an approved template"
+    ),
+    DeliveryErrorCode.UNDELIVERABLE: (
+        False, True, "Recipient cannot receive WhatsApp messages - drop the contact"
+    ),
+    DeliveryErrorCode.MARKETING_OPT_OUT: (
+        False, True, "Customer stopped marketing messages - do not resend marketing templates"
+    ),
+    DeliveryErrorCode.EXPERIMENT: (
+        False, True, "Number is part of a Meta experiment and cannot receive marketing templates"
+    ),
+    DeliveryErrorCode.ECOSYSTEM_ENGAGEMENT: (
+        False, False, "Held by Meta for ecosystem health - retry later (not immediately)"
+    ),
+    DeliveryErrorCode.SPAM_RATE_LIMIT: (False, False, "Spam rate limit - slow down and retry later"),
+    DeliveryErrorCode.PAIR_RATE_LIMIT: (False, False, "Too many messages to this recipient - retry later"),
+    DeliveryErrorCode.RATE_LIMIT: (False, False, "Throughput limit reached - retry with backoff"),
+    DeliveryErrorCode.GENERIC_ERROR: (False, False, "Temporary Meta error - retry"),
+    DeliveryErrorCode.SERVICE_UNAVAILABLE: (False, False, "Meta service unavailable - retry"),
+    DeliveryErrorCode.RECIPIENT_IS_SENDER: (False, True, "Recipient is the sending number itself"),
+    DeliveryErrorCode.RECIPIENT_NOT_ALLOWED: (
+        False, True, "Test number can only send to its allowed recipients"
+    ),
+    DeliveryErrorCode.ACCOUNT_LOCKED: (False, True, "Business account locked - contact Meta support"),
+    DeliveryErrorCode.PAYMENT_ISSUE: (False, True, "Business payment method issue - fix billing"),
+    DeliveryErrorCode.INVALID_PARAMETER: (False, True, "Invalid request parameter - fix the message"),
+    DeliveryErrorCode.UNSUPPORTED_MESSAGE_TYPE: (False, True, "Message type not supported"),
+    DeliveryErrorCode.TEMPLATE_PARAMETER_MISMATCH: (
+        False, True, "Template parameters do not match the template - fix the variables"
+    ),
+    DeliveryErrorCode.TEMPLATE_NOT_FOUND: (
+        False, True, "Template name/language not found - sync templates or pick another"
+    ),
+    DeliveryErrorCode.TEMPLATE_PAUSED: (False, True, "Template paused for low quality"),
+    DeliveryErrorCode.TEMPLATE_DISABLED: (False, True, "Template disabled for low quality"),
+    DeliveryErrorCode.NUMBER_NOT_REGISTERED: (False, True, "Sending number is not registered"),
+    DeliveryErrorCode.UNKNOWN: (False, False, "Unknown error - retry with backoff"),
+}
+
+
+class DeliveryError(BaseModel):
+    """
+    Error of an undeliverable message with guidance for retries
+
+    Meta payload (status.errors[0] or error of a failed send):
+    {
+      "code": 131047,
+      "title": "Re-engagement message",
+      "message": "Re-engagement message",
+      "error_data": {"details": "Message failed to send because more than 24 hours have passed..."}
+    }
+
+    needs_template: resend only as an approved template
+    permanent_failure: resending will not help (drop the recipient or fix the template/account)
+    """
+
+    code: DeliveryErrorCode = DeliveryErrorCode.UNKNOWN
+    raw_code: Optional[str] = None
+    title: Optional[str] = None
+    detail: Optional[str] = None
+    needs_template: bool = False
+    permanent_failure: bool = False
+    guidance: Optional[str] = None
+
+    @property
+    def retryable(self) -> bool:
+        """Same message can be retried (with backoff)"""
+        return not self.needs_template and not self.permanent_failure
+
+    @classmethod
+    def from_code(
+        cls, code: Any, title: Optional[str] = None, detail: Optional[str] = None
+    ) -> "DeliveryError":
+        """Build from a raw error code (int or str)"""
+        raw_code = str(code) if code is not None else None
+        try:
+            kind = DeliveryErrorCode(raw_code)
+        except ValueError:
+            kind = DeliveryErrorCode.UNKNOWN
+        needs_template, permanent_failure, guidance = DELIVERY_ERROR_GUIDANCE[kind]
+        return cls(
+            code=kind,
+            raw_code=raw_code,
+            title=title,
+            detail=detail or title,
+            needs_template=needs_template,
+            permanent_failure=permanent_failure,
+            guidance=guidance,
+        )
+
+    @classmethod
+    def from_webhook(cls, error: Dict[str, Any]) -> "DeliveryError":
+        """Build from an ``errors[]`` entry of a failed status"""
+        return cls.from_code(
+            error.get("code"),
+            title=error.get("title"),
+            detail=(error.get("error_data") or {}).get("details") or error.get("message"),
+        )
+
+    @classmethod
+    def from_status(cls, status: Dict[str, Any]) -> "DeliveryError":
+        """First error of a failed status webhook"""
+        return cls.from_webhook((status.get("errors") or [{}])[0])
+
+
+# ============================================
+# CLASSIFICATION
+# ============================================
+
+
+# System notices about the contact's number or identity key
+IDENTITY_NOTICE_TYPES = ("customer_identity_changed", "user_changed_number")
+
+# Changes carrying messages sent from the WhatsApp Business app (coexistence)
+ECHO_FIELDS = ("smb_message_echoes", "message_echoes")
+
+
+def classify_message(message: Dict[str, Any]) -> WebhookEventType:
+    """Event type of a webhook ``messages[]`` entry"""
+    message_type = message.get("type")
+    if message_type == "order":
+        return WebhookEventType.ORDER
+    if message_type == "reaction":
+        return WebhookEventType.REACTION
+    if message_type == "edit":
+        return WebhookEventType.EDIT
+    if message_type == "request_welcome":
+        # First time the user opens the chat (welcome message enabled)
+        return WebhookEventType.REQUEST_WELCOME
+    if message_type == "system" and (message.get("system") or {}).get("type") in IDENTITY_NOTICE_TYPES:
+        return WebhookEventType.IDENTITY_CHANGE
+    return WebhookEventType.MESSAGE
+
+
+def classify_status(status: Dict[str, Any]) -> WebhookEventType:
+    """Event type of a webhook ``statuses[]`` entry"""
+    if status.get("type") == "payment" or "payment" in status:
+        return WebhookEventType.PAYMENT
+    return WebhookEventType.STATUS
+
+
+# ============================================
+# INBOUND MESSAGES
+# ============================================
+
+
+class InboundMessageType(str, Enum):
+    """
+    Kind of an inbound ``messages[]`` entry
+
+    Interactive replies are split by reply type (button, list, flow, address) and
+    types PyTake does not model yet are kept as UNKNOWN with the raw JSON.
+    """
+
+    TEXT = "text"
+    IMAGE = "image"
+    VIDEO = "video"
+    AUDIO = "audio"
+    DOCUMENT = "document"
+    STICKER = "sticker"
+    LOCATION = "location"
+    CONTACTS = "contacts"
+    BUTTON_REPLY = "button_reply"  # interactive reply button
+    LIST_REPLY = "list_reply"  # interactive list row
+    FLOW_REPLY = "flow_reply"  # WhatsApp Flow response (nfm_reply)
+    ADDRESS_REPLY = "address_reply"  # native address form (nfm_reply named address_message)
+    BUTTON = "button"  # template quick reply button
+    ORDER = "order"
+    REACTION = "reaction"
+    EDIT = "edit"
+    SYSTEM = "system"  # user changed number / identity changed
+    REQUEST_WELCOME = "request_welcome"
+    UNSUPPORTED = "unsupported"  # type Meta itself does not support
+    UNKNOWN = "unknown"
+
+
+MEDIA_TYPES = {
+    InboundMessageType.IMAGE,
+    InboundMessageType.VIDEO,
+    InboundMessageType.AUDIO,
+    InboundMessageType.DOCUMENT,
+    InboundMessageType.STICKER,
+}
+
+
+class MediaAttachment(BaseModel):
+    """Media object of image/video/audio/document/sticker messages"""
+
+    id: Optional[str] = None
+    mime_type: Optional[str] = None
+    sha256: Optional[str] = None
+    caption: Optional[str] = None
+    filename: Optional[str] = None
+    voice: Optional[bool] = None  # audio recorded in the chat (PTT)
+    animated: Optional[bool] = None  # sticker
+
+
+class StoredMedia(BaseModel):
+    """Inbound media downloaded by MediaFetcher (pytake_client.media)"""
+
+    media_id: str
+    reference: str  # Where the storage backend put the bytes (path, URL, key...)
+    mime_type: Optional[str] = None
+    size: int = 0
+    sha256: Optional[str] = None  # Hex digest of the downloaded bytes
+    filename: Optional[str] = None
+
+
+class LocationShare(BaseModel):
+    """Location pin shared by the contact"""
+
+    latitude: Optional[float] = None
+    longitude: Optional[float] = None
+    name: Optional[str] = None
+    address: Optional[str] = None
+    url: Optional[str] = None
+
+
+class SharedContact(BaseModel):
+    """
+    Contact card shared by the contact
+
+    Meta payload (message.contacts[]):
+    {
+      "name": {"formatted_name": "Maria Silva", "first_name": "Maria"},
+      "phones": [{"phone": "+55 11 99999-9999", "wa_id": "5511999999999", "type": "CELL"}],
+      "emails": [{"email": "maria@example.com", "type": "WORK"}]
+    }
+    """
+
+    name: Optional[str] = None
+    phones: List[str] = Field(default_factory=list)
+    wa_ids: List[str] = Field(default_factory=list)
+    emails: List[str] = Field(default_factory=list)
+    organization: Optional[str] = None
+
+    @classmethod
+    def from_webhook(cls, card: Dict[str, Any]) -> "SharedContact":
+        """Build from one entry of message.contacts"""
+        name = card.get("name") or {}
+        phones = card.get("phones") or []
+        return cls(
+            name=name.get("formatted_name") or name.get("first_name"),
+            phones=[p["phone"] for p in phones if p.get("phone")],
+            wa_ids=[p["wa_id"] for p in phones if p.get("wa_id")],
+            emails=[e["email"] for e in card.get("emails") or [] if e.get("email")],
+            organization=(card.get("org") or {}).get("company"),
+        )
+
+
+class InteractiveReply(BaseModel):
+    """Reply to an interactive button or list row"""
+
+    id: Optional[str] = None
+    title: Optional[str] = None
+    description: Optional[str] = None
+
+
+def _nfm_response(nfm_reply: Dict[str, Any]) -> Dict[str, Any]:
+    """response_json of an nfm_reply (a JSON string); undecodable values are kept as raw"""
+    raw_response = nfm_reply.get("response_json")
+    try:
+        return json.loads(raw_response) if isinstance(raw_response, str) else (raw_response or {})
+    except ValueError:
+        return {"raw": raw_response}
+
+
+class FlowReply(BaseModel):
+    """Response of a WhatsApp Flow (interactive.type == "nfm_reply")"""
+
+    name: Optional[str] = None
+    body: Optional[str] = None
+    response: Dict[str, Any] = Field(default_factory=dict)
+
+    @classmethod
+    def from_webhook(cls, nfm_reply: Dict[str, Any]) -> "FlowReply":
+        """Build from interactive.nfm_reply (response_json is a JSON string)"""
+        return cls(name=nfm_reply.get("name"), body=nfm_reply.get("body"), response=_nfm_response(nfm_reply))
+
+
+ADDRESS_MESSAGE = "address_message"
+
+
+class ShippingAddress(BaseModel):
+    """
+    Address filled in the native WhatsApp address form (interactive address_message)
+
+    Meta payload (interactive.nfm_reply):
+    {
+      "name": "address_message",
+      "body": "Priya, 2nd floor, Lodha Bellissimo, Mumbai",
+      "response_json": "{\"saved_address_id\": \"address1\", \"values\": {\"name\": \"Priya\", \"in_pin_code\": \"400011\", ...}}"
+    }
+    """
+
+    saved_address_id: Optional[str] = None  # Set when the contact picked one of the saved_addresses sent
+    name: Optional[str] = None
+    phone_number: Optional[str] = None
+    postal_code: Optional[str] = None  # in_pin_code / sg_post_code
+    house_number: Optional[str] = None
+    floor_number: Optional[str] = None
+    tower_number: Optional[str] = None
+    building_name: Optional[str] = None
+    unit_number: Optional[str] = None
+    address: Optional[str] = None
+    landmark_area: Optional[str] = None
+    city: Optional[str] = None
+    state: Optional[str] = None
+    body: Optional[str] = None  # Summary WhatsApp shows in the chat
+    values: Dict[str, Any] = Field(default_factory=dict)  # Form fields as sent by Meta
+
+    @property
+    def one_line(self) -> str:
+        """Address on one line, for agents and chatbot variables"""
+        parts = [
+            self.house_number, self.floor_number and f"floor {self.floor_number}", self.tower_number,
+            self.building_name, self.unit_number, self.address, self.landmark_area, self.city, self.state,
+            self.postal_code,
+        ]
+        return ", ".join(str(part) for part in parts if part) or (self.body or "")
+
+    @classmethod
+    def from_webhook(cls, nfm_reply: Dict[str, Any]) -> "ShippingAddress":
+        """Build from interactive.nfm_reply of an address_message"""
+        response = _nfm_response(nfm_reply)
+        values = response.get("values") or {}
+        fields = {
+            key: str(values[key]) for key in cls.model_fields
+            if key not in ("saved_address_id", "postal_code", "body", "values") and values.get(key) not in (None, "")
+        }
+        postal_code = values.get("in_pin_code") or values.get("sg_post_code")
+        return cls(
+            saved_address_id=response.get("saved_address_id"),
+            postal_code=str(postal_code) if postal_code else None,
+            body=nfm_reply.get("body"),
+            values=values,
+            **fields,
+        )
+
+
+class QuickReplyButton(BaseModel):
+    """Template quick reply button pressed by the contact (type == "button")"""
+
+    text: Optional[str] = None
+    payload: Optional[str] = None
+
+
+class SystemNotice(BaseModel):
+    """
+    System message about the contact
+
+    Meta payload (message.type == "system"):
+    {
+      "system": {
+        "body": "User A changed from 5511999999999 to 5511888888888",
+        "wa_id": "5511999999999",
+        "new_wa_id": "5511888888888",
+        "type": "user_changed_number"
+      }
+    }
+    """
+
+    type: Optional[str] = None
+    body: Optional[str] = None
+    wa_id: Optional[str] = None
+    new_wa_id: Optional[str] = None
+    identity: Optional[str] = None
+
+    @property
+    def is_number_change(self) -> bool:
+        return self.type == "user_changed_number" and bool(self.new_wa_id)
+
+    @property
+    def is_identity_change(self) -> bool:
+        return self.type == "customer_identity_changed"
+
+    @classmethod
+    def from_webhook(cls, message: Dict[str, Any]) -> "SystemNotice":
+        """Build from a raw webhook message"""
+        system = message.get("system") or {}
+        return cls(
+            type=system.get("type"),
+            body=system.get("body"),
+            wa_id=system.get("wa_id") or system.get("customer") or message.get("from"),
+            new_wa_id=system.get("new_wa_id"),
+            identity=system.get("identity"),
+        )
+
+
+_INTERACTIVE_TYPES = {
+    "button_reply": InboundMessageType.BUTTON_REPLY,
+    "list_reply": InboundMessageType.LIST_REPLY,
+    "nfm_reply": InboundMessageType.FLOW_REPLY,
+}
+
+
+def inbound_message_type(message: Dict[str, Any]) -> InboundMessageType:
+    """Exhaustive type of a webhook ``messages[]`` entry"""
+    raw_type = message.get("type")
+    if raw_type == "interactive":
+        interactive = message.get("interactive") or {}
+        interactive_type = interactive.get("type")
+        if interactive_type == "nfm_reply" and (interactive.get("nfm_reply") or {}).get("name") == ADDRESS_MESSAGE:
+            return InboundMessageType.ADDRESS_REPLY
+        return _INTERACTIVE_TYPES.get(interactive_type, InboundMessageType.UNKNOWN)
+    try:
+        return InboundMessageType(raw_type)
+    except ValueError:
+        return InboundMessageType.UNKNOWN
+
+
+class InboundMessage(BaseModel):
+    """
+    Typed inbound message covering every ``messages[]`` type
+
+    Only the field of the message type is set (text, media, location...).
+    ``raw`` always keeps the original JSON, so UNKNOWN / UNSUPPORTED
+    messages are never lost:
+
+        inbound = InboundMessage.from_webhook(message)
+        if inbound.type == InboundMessageType.LIST_REPLY:
+            row_id = inbound.reply.id
+    """
+
+    id: Optional[str] = None
+    sender: Optional[str] = None
+    timestamp: Optional[int] = None
+    type: InboundMessageType
+    raw_type: Optional[str] = None
+    context: Optional[MessageContext] = None
+
+    text: Optional[str] = None
+    media: Optional[MediaAttachment] = None
+    location: Optional[LocationShare] = None
+    contacts: List[SharedContact] = Field(default_factory=list)
+    reply: Optional[InteractiveReply] = None
+    flow_reply: Optional[FlowReply] = None
+    address: Optional[ShippingAddress] = None
+    button: Optional[QuickReplyButton] = None
+    order: Optional[OrderMessage] = None
+    reaction: Optional[MessageReaction] = None
+    edit: Optional[MessageEdit] = None
+    system: Optional[SystemNotice] = None
+    errors: List[Dict[str, Any]] = Field(default_factory=list)
+
+    raw: Dict[str, Any] = Field(default_factory=dict)
+
+    @property
+    def is_media(self) -> bool:
+        return self.type in MEDIA_TYPES
+
+    @property
+    def text_value(self) -> Optional[str]:
+        """What the contact "said": text, reply title, button text or caption"""
+        if self.text is not None:
+            return self.text
+        if self.reply:
+            return self.reply.title
+        if self.address:
+            return self.address.one_line
+        if self.button:
+            return self.button.text
+        if self.media:
+            return self.media.caption
+        return None
+
+    @classmethod
+    def from_webhook(cls, message: Dict[str, Any]) -> "InboundMessage":
+        """Build from a raw webhook ``messages[]`` entry"""
+        message_type = inbound_message_type(message)
+        raw_type = message.get("type")
+        data: Dict[str, Any] = {
+            "id": message.get("id"),
+            "sender": message.get("from"),
+            "timestamp": message.get("timestamp"),
+            "type": message_type,
+            "raw_type": raw_type,
+            "context": MessageContext.from_webhook(message),
+            "errors": message.get("errors") or [],
+            "raw": message,
+        }
+
+        if message_type == InboundMessageType.TEXT:
+            data["text"] = (message.get("text") or {}).get("body", "")
+        elif message_type in MEDIA_TYPES:
+            data["media"] = MediaAttachment.model_validate(message.get(raw_type) or {})
+        elif message_type == InboundMessageType.LOCATION:
+            data["location"] = LocationShare.model_validate(message.get("location") or {})
+        elif message_type == InboundMessageType.CONTACTS:
+            data["contacts"] = [SharedContact.from_webhook(card) for card in message.get("contacts") or []]
+        elif message_type in (InboundMessageType.BUTTON_REPLY, InboundMessageType.LIST_REPLY):
+            interactive = message.get("interactive") or {}
+            data["reply"] = InteractiveReply.model_validate(interactive.get(interactive.get("type")) or {})
+        elif message_type == InboundMessageType.FLOW_REPLY:
+            data["flow_reply"] = FlowReply.from_webhook((message.get("interactive") or {}).get("nfm_reply") or {})
+        elif message_type == InboundMessageType.ADDRESS_REPLY:
+            data["address"] = ShippingAddress.from_webhook((message.get("interactive") or {}).get("nfm_reply") or {})
+        elif message_type == InboundMessageType.BUTTON:
+            data["button"] = QuickReplyButton.model_validate(message.get("button") or {})
+        elif message_type == InboundMessageType.ORDER:
+            data["order"] = OrderMessage.from_webhook(message)
+        elif message_type == InboundMessageType.REACTION:
+            data["reaction"] = MessageReaction.from_webhook(message)
+        elif message_type == InboundMessageType.EDIT:
+            data["edit"] = MessageEdit.from_webhook(message)
+        elif message_type == InboundMessageType.SYSTEM:
+            data["system"] = SystemNotice.from_webhook(message)
+
+        return cls(**data)
+
+    def to_content(self) -> Dict[str, Any]:
+        """Content stored in Message.content (keyed by the raw Meta type)"""
+        if self.type == InboundMessageType.TEXT:
+            return {"text": self.text}
+        if self.media is not None:
+            return {self.raw_type: self.media.model_dump(exclude_none=True)}
+        if self.location is not None:
+            return {"location": self.location.model_dump(exclude_none=True)}
+        if self.type == InboundMessageType.CONTACTS:
+            return {
+                "contacts": self.raw.get("contacts") or [],
+                "shared_contacts": [card.model_dump() for card in self.contacts],
+            }
+        if self.reply is not None or self.flow_reply is not None or self.address is not None:
+            interactive = self.raw.get("interactive") or {}
+            reply_type = interactive.get("type")
+            content = {"type": reply_type, reply_type: interactive.get(reply_type)}
+            if self.flow_reply is not None:
+                content["nfm_reply"] = self.flow_reply.model_dump()
+            if self.address is not None:
+                content["address"] = {**self.address.model_dump(exclude_none=True), "one_line": self.address.one_line}
+            return {"interactive": content}
+        if self.button is not None:
+            return {"button": self.button.model_dump()}
+        if self.order is not None:
+            return {"order": self.order.to_content()}
+        if self.system is not None:
+            return {"system": self.system.model_dump(exclude_none=True)}
+        if self.type == InboundMessageType.REQUEST_WELCOME:
+            return {}
+        if self.type == InboundMessageType.UNSUPPORTED:
+            return {"errors": self.errors, "raw": self.raw}
+        # Reactions/edits are applied to earlier messages; unknown types keep the payload
+        return {"raw": self.raw}
+
+
+# ============================================
+# SECURITY NOTIFICATIONS & ECHOES
+# ============================================
+
+
+class IdentityChangeKind(str, Enum):
+    """What changed about the contact"""
+
+    NUMBER_CHANGED = "number_changed"  # moved to a new phone number
+    IDENTITY_CHANGED = "identity_changed"  # new identity key (reinstall / new device)
+
+
+class IdentityChange(BaseModel):
+    """
+    The contact changed phone number or identity
+
+    WhatsApp recommends confirming who is on the other side before sharing
+    sensitive data again: a new identity means WhatsApp was reinstalled or
+    moved to another device, not necessarily by the same person.
+
+    Meta payload (message.type == "system"):
+    {
+      "from": "5511999999999",
+      "id": "wamid.xxx",
+      "timestamp": "1700000000",
+      "type": "system",
+      "system": {
+        "body": "User's security code changed",
+        "customer": "5511999999999",
+        "identity": "ESfRs6L1wA0=",
+        "type": "customer_identity_changed"
+      }
+    }
+    """
+
+    kind: IdentityChangeKind
+    message_id: Optional[str] = None
+    wa_id: Optional[str] = None
+    new_wa_id: Optional[str] = None
+    identity_hash: Optional[str] = None
+    body: Optional[str] = None
+    timestamp: Optional[int] = None
+
+    @property
+    def current_wa_id(self) -> Optional[str]:
+        """WhatsApp ID the contact uses from now on"""
+        return self.new_wa_id or self.wa_id
+
+    @classmethod
+    def from_webhook(cls, message: Dict[str, Any]) -> "IdentityChange":
+        """Build from a raw webhook ``system`` message"""
+        notice = SystemNotice.from_webhook(message)
+        kind = (
+            IdentityChangeKind.NUMBER_CHANGED
+            if notice.type == "user_changed_number"
+            else IdentityChangeKind.IDENTITY_CHANGED
+        )
+        return cls(
+            kind=kind,
+            message_id=message.get("id"),
+            wa_id=notice.wa_id,
+            new_wa_id=notice.new_wa_id,
+            identity_hash=notice.identity,
+            body=notice.body,
+            timestamp=message.get("timestamp"),
+        )
+
+
+class MessageEcho(BaseModel):
+    """
+    Message the business sent from the WhatsApp Business app
+
+    Numbers shared between the app and the Cloud API (coexistence) echo the
+    app's outgoing messages so the conversation history stays complete.
+
+    Meta payload (field == "smb_message_echoes"):
+    {
+      "message_echoes": [{
+        "from": "15550000000",
+        "to": "5511999999999",
+        "id": "wamid.xxx",
+        "timestamp": "1700000000",
+        "type": "text",
+        "text": {"body": "Seu pedido saiu para entrega"}
+      }]
+    }
+    """
+
+    id: Optional[str] = None
+    from_number: Optional[str] = None
+    to: Optional[str] = None
+    timestamp: Optional[int] = None
+    message: InboundMessage
+
+    @classmethod
+    def from_webhook(cls, echo: Dict[str, Any]) -> "MessageEcho":
+        """Build from a raw ``message_echoes[]`` entry"""
+        return cls(
+            id=echo.get("id"),
+            from_number=echo.get("from"),
+            to=echo.get("to"),
+            timestamp=echo.get("timestamp"),
+            message=InboundMessage.from_webhook(echo),
+        )
+
+
+# ============================================
+# SIGNATURES & ITERATION
+# ============================================
+
+
+def verify_signature(payload: bytes, signature: Optional[str], secret: str) -> bool:
+    """
+    Verify an X-Hub-Signature-256 header (format: "sha256=<hex>")
+
+    Args:
+        payload: Raw request body
+        signature: Header value
+        secret: App secret used to sign the payload
+
+    Returns:
+        True if the signature matches
+    """
+    if not signature or not signature.startswith("sha256="):
+        return False
+
+    expected = hmac.new(secret.encode("utf-8"), payload, hashlib.sha256).hexdigest()
+    return hmac.compare_digest(expected, signature.split("sha256=", 1)[1])
+
+
+class WebhookChallenge(BaseModel):
+    """
+    Meta webhook verification request (GET ?hub.mode=subscribe&hub.verify_token=...&hub.challenge=...)
+
+    Supports several verify tokens (one per tenant/number):
+
+        challenge = WebhookChallenge.from_query(request.query_params)
+        tenant = challenge.match({"acme": "token-a", "globex": "token-b"})
+        if tenant is not None:
+            return challenge.challenge
+    """
+
+    mode: Optional[str] = None
+    verify_token: Optional[str] = None
+    challenge: Optional[str] = None
+
+    @classmethod
+    def from_query(cls, params: Mapping[str, str]) -> "WebhookChallenge":
+        return cls(
+            mode=params.get("hub.mode"),
+            verify_token=params.get("hub.verify_token"),
+            challenge=params.get("hub.challenge"),
+        )
+
+    @property
+    def is_complete(self) -> bool:
+        return bool(self.mode and self.verify_token and self.challenge)
+
+    @property
+    def is_subscribe(self) -> bool:
+        return self.mode == "subscribe"
+
+    def matches(self, token: Optional[str]) -> bool:
+        """Constant-time comparison with one expected token"""
+        if not token or not self.verify_token:
+            return False
+        return hmac.compare_digest(token.encode("utf-8"), self.verify_token.encode("utf-8"))
+
+    def match(self, tokens: Mapping[K, Optional[str]]) -> Optional[K]:
+        """
+        Find which tenant the verify token belongs to
+
+        Args:
+            tokens: Tenant key -> expected verify token
+
+        Returns:
+            Key of the matching tenant, or None
+        """
+        found = None
+        for key, token in tokens.items():
+            # Compare with every token so timing does not reveal the position
+            if self.matches(token) and found is None:
+                found = key
+        return found
+
+
+class WebhookEvent(BaseModel):
+    """One message or status entry of a webhook payload"""
+
+    type: WebhookEventType
+    phone_number_id: Optional[str] = None
+    data: Dict[str, Any] = Field(default_factory=dict)
+
+    # Set by MediaFetcher when the processor fetches inbound media
+    media: Optional[StoredMedia] = None
+    media_error: Optional[str] = None
+
+    # Set by PhoneNumberRouter: tenant and settings of the receiving number
+    route: Optional[Any] = None
+
+    @property
+    def context(self) -> Optional[MessageContext]:
+        if self.type not in (WebhookEventType.MESSAGE, WebhookEventType.ORDER):
+            return None
+        return MessageContext.from_webhook(self.data)
+
+    @property
+    def order(self) -> Optional[OrderMessage]:
+        if self.type != WebhookEventType.ORDER:
+            return None
+        return OrderMessage.from_webhook(self.data)
+
+    @property
+    def reaction(self) -> Optional[MessageReaction]:
+        if self.type != WebhookEventType.REACTION:
+            return None
+        return MessageReaction.from_webhook(self.data)
+
+    @property
+    def edit(self) -> Optional[MessageEdit]:
+        if self.type != WebhookEventType.EDIT:
+            return None
+        return MessageEdit.from_webhook(self.data)
+
+    @property
+    def inbound(self) -> Optional[InboundMessage]:
+        """Typed message for any entry of ``messages[]``"""
+        if self.type in (
+            WebhookEventType.STATUS,
+            WebhookEventType.PAYMENT,
+            WebhookEventType.TEMPLATE_STATUS,
+            WebhookEventType.PHONE_NUMBER_QUALITY,
+            WebhookEventType.MESSAGE_ECHO,
+        ):
+            return None
+        return InboundMessage.from_webhook(self.data)
+
+    @property
+    def identity_change(self) -> Optional[IdentityChange]:
+        if self.type != WebhookEventType.IDENTITY_CHANGE:
+            return None
+        return IdentityChange.from_webhook(self.data)
+
+    @property
+    def echo(self) -> Optional[MessageEcho]:
+        if self.type != WebhookEventType.MESSAGE_ECHO:
+            return None
+        return MessageEcho.from_webhook(self.data)
+
+    @property
+    def payment(self) -> Optional[PaymentStatus]:
+        if self.type != WebhookEventType.PAYMENT:
+            return None
+        return PaymentStatus.from_webhook(self.data)
+
+    @property
+    def template_status(self) -> Optional[TemplateStatusUpdate]:
+        if self.type != WebhookEventType.TEMPLATE_STATUS:
+            return None
+        return TemplateStatusUpdate.from_webhook(self.data)
+
+    @property
+    def quality_update(self) -> Optional[PhoneNumberQualityUpdate]:
+        if self.type != WebhookEventType.PHONE_NUMBER_QUALITY:
+            return None
+        return PhoneNumberQualityUpdate.from_webhook(self.data)
+
+
+def iter_events(payload: Dict[str, Any]) -> Iterator[WebhookEvent]:
+    """Iterate over the messages, statuses and echoes of a Meta webhook payload"""
+    for entry in payload.get("entry", []):
+        for change in entry.get("changes", []):
+            value = change.get("value", {})
+            phone_number_id = value.get("metadata", {}).get("phone_number_id")
+
+            if change.get("field") == "message_template_status_update":
+                yield WebhookEvent(
+                    type=WebhookEventType.TEMPLATE_STATUS,
+                    phone_number_id=phone_number_id,
+                    data=value,
+                )
+                continue
+
+            if change.get("field") == "phone_number_quality_update":
+                yield WebhookEvent(
+                    type=WebhookEventType.PHONE_NUMBER_QUALITY,
+                    phone_number_id=phone_number_id,
+                    data=value,
+                )
+                continue
+
+            if change.get("field") in ECHO_FIELDS:
+                for echo in value.get("message_echoes", []):
+                    yield WebhookEvent(
+                        type=WebhookEventType.MESSAGE_ECHO,
+                        phone_number_id=phone_number_id,
+                        data=echo,
+                    )
+                continue
+
+            for message in value.get("messages", []):
+                yield WebhookEvent(
+                    type=classify_message(message),
+                    phone_number_id=phone_number_id,
+                    data=message,
+                )
+
+            for status in value.get("statuses", []):
+                yield WebhookEvent(
+                    type=classify_status(status),
+                    phone_number_id=phone_number_id,
+                    data=status,
+                )
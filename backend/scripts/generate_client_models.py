@@ -0,0 +1,186 @@
+"""
+Generate pytake_client/models.py from the API OpenAPI spec
+
+Keeps the DTOs shipped in the pytake-client SDK in sync with the schemas
+served by the backend. Run after changing any request/response schema
+listed in COMPONENTS:
+
+    python scripts/generate_client_models.py --spec http://localhost:8000/api/v1/openapi.json
+    python scripts/generate_client_models.py --spec openapi.json --check
+
+The spec is only served with DEBUG=true (see app/main.py).
+"""
+
+import argparse
+import json
+import sys
+import urllib.request
+from pathlib import Path
+from typing import Any, Dict, List, Set
+
+OUTPUT = Path(__file__).resolve().parent.parent / "pytake_client" / "models.py"
+
+# Components exported by the SDK (order matters: dependencies first)
+COMPONENTS = [
+    "UserLogin",
+    "Token",
+    "RefreshTokenRequest",
+    "MessageSendRequest",
+    "MessageResponse",
+    "Conversation",
+    "ConversationAssign",
+    "CampaignCreate",
+    "CampaignUpdate",
+    "CampaignInDB",
+    "CampaignListResponse",
+    "CampaignProgress",
+    "CampaignStartResponse",
+    "FlowCreate",
+    "FlowUpdate",
+    "FlowInDB",
+    "FlowListResponse",
+]
+
+HEADER = '''"""
+PyTake API models
+
+Generated from the API OpenAPI spec by scripts/generate_client_models.py.
+Do not edit by hand - change the backend schema and regenerate.
+"""
+
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from uuid import UUID
+
+from pydantic import BaseModel, ConfigDict, Field
+
+
+class APIModel(BaseModel):
+    """Base for API models (unknown fields are kept for forward compatibility)"""
+
+    model_config = ConfigDict(extra="allow", populate_by_name=True)
+'''
+
+
+def load_spec(source: str) -> Dict[str, Any]:
+    """Load the OpenAPI spec from a file path or URL"""
+    if source.startswith(("http://", "https://")):
+        with urllib.request.urlopen(source) as response:
+            return json.loads(response.read().decode("utf-8"))
+    return json.loads(Path(source).read_text(encoding="utf-8"))
+
+
+def python_type(schema: Dict[str, Any]) -> str:
+    """Map an OpenAPI property schema to a Python type annotation"""
+    if "$ref" in schema:
+        return schema["$ref"].rsplit("/", 1)[-1]
+
+    if "anyOf" in schema:
+        options = [s for s in schema["anyOf"] if s.get("type") != "null"]
+        inner = python_type(options[0]) if len(options) == 1 else "Any"
+        return f"Optional[{inner}]"
+
+    schema_type = schema.get("type")
+    schema_format = schema.get("format")
+
+    if schema_type == "string":
+        if schema_format == "uuid":
+            return "UUID"
+        if schema_format == "date-time":
+            return "datetime"
+        return "str"
+    if schema_type == "integer":
+        return "int"
+    if schema_type == "number":
+        return "float"
+    if schema_type == "boolean":
+        return "bool"
+    if schema_type == "array":
+        return f"List[{python_type(schema.get('items', {}))}]"
+    if schema_type == "object":
+        return "Dict[str, Any]"
+    return "Any"
+
+
+def field_default(name: str, schema: Dict[str, Any], required: Set[str]) -> str:
+    """Render the default value of a field"""
+    if name in required:
+        return ""
+
+    if "default" in schema:
+        default = schema["default"]
+        if isinstance(default, list):
+            return " = Field(default_factory=list)"
+        if isinstance(default, dict):
+            return " = Field(default_factory=dict)"
+        return f" = {default!r}"
+
+    return " = None"
+
+
+def render_model(name: str, schema: Dict[str, Any]) -> str:
+    """Render one component as a pydantic model"""
+    required = set(schema.get("required", []))
+    lines = [f"class {name}(APIModel):"]
+
+    description = (schema.get("description") or name).strip().splitlines()[0]
+    lines.append(f'    """{description}"""')
+    lines.append("")
+
+    properties = schema.get("properties", {})
+    if not properties:
+        lines.append("    pass")
+
+    for field_name, field_schema in properties.items():
+        annotation = python_type(field_schema)
+        if field_name not in required and not annotation.startswith("Optional["):
+            if "default" not in field_schema or field_schema["default"] is None:
+                annotation = f"Optional[{annotation}]"
+        lines.append(
+            f"    {field_name}: {annotation}"
+            f"{field_default(field_name, field_schema, required)}"
+        )
+
+    return "\n".join(lines)
+
+
+def generate(spec: Dict[str, Any]) -> str:
+    """Render the models module"""
+    schemas = spec.get("components", {}).get("schemas", {})
+    missing: List[str] = [name for name in COMPONENTS if name not in schemas]
+    if missing:
+        raise SystemExit(f"❌ Components missing from spec: {', '.join(missing)}")
+
+    blocks = [HEADER.rstrip("\n")]
+    for name in COMPONENTS:
+        blocks.append(render_model(name, schemas[name]))
+
+    return "\n\n\n".join(blocks) + "\n"
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("--spec", required=True, help="Path or URL of openapi.json")
+    parser.add_argument(
+        "--check",
+        action="store_true",
+        help="Exit with status 1 if models.py is out of date instead of writing it",
+    )
+    args = parser.parse_args()
+
+    rendered = generate(load_spec(args.spec))
+
+    if args.check:
+        if OUTPUT.read_text(encoding="utf-8") != rendered:
+            print("❌ pytake_client/models.py is out of date - regenerate it")
+            return 1
+        print("✅ pytake_client/models.py is up to date")
+        return 0
+
+    OUTPUT.write_text(rendered, encoding="utf-8")
+    print(f"✅ Wrote {OUTPUT}")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
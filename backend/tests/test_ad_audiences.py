@@ -0,0 +1,113 @@
+"""
+Ad Audiences Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import hashlib
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+
+from app.integrations import meta_ads_api
+from app.integrations.meta_ads_api import (
+    MetaAdsAPI,
+    hash_contact,
+    normalize_email,
+    normalize_phone,
+)
+from app.services.ad_audience_service import diff_members
+
+
+def _sha(value):
+    return hashlib.sha256(value.encode()).hexdigest()
+
+
+def _member(contact_id, phone_hash, email_hash=None):
+    return SimpleNamespace(contact_id=contact_id, phone_hash=phone_hash, email_hash=email_hash)
+
+
+class TestHashing:
+    """Tests for Meta identifier normalization"""
+
+    def test_normalize_phone(self):
+        """Test formatting and leading zeros are removed"""
+        assert normalize_phone("+55 (11) 99999-9999") == "5511999999999"
+        assert normalize_phone("005511999999999") == "5511999999999"
+        assert normalize_phone("") is None
+
+    def test_normalize_email(self):
+        """Test emails are trimmed and lowercase"""
+        assert normalize_email("  Maria@Example.COM ") == "maria@example.com"
+        assert normalize_email(None) is None
+
+    def test_hash_contact(self):
+        """Test hashes follow the PHONE, EMAIL schema"""
+        assert hash_contact("+5511999999999", "Maria@Example.com") == (
+            _sha("5511999999999"),
+            _sha("maria@example.com"),
+        )
+        assert hash_contact("5511999999999", None) == (_sha("5511999999999"), None)
+
+
+class TestDiffMembers:
+    """Tests for incremental audience updates"""
+
+    def test_new_and_removed_contacts(self):
+        """Test contacts entering and leaving the segment"""
+        kept, left, new = uuid4(), uuid4(), uuid4()
+        members = [_member(kept, "a"), _member(left, "b")]
+
+        to_add, to_remove = diff_members({kept: ("a", None), new: ("c", None)}, members)
+
+        assert to_add == {new: ("c", None)}
+        assert [member.contact_id for member in to_remove] == [left]
+
+    def test_changed_identifiers_are_replaced(self):
+        """Test a member with an outdated hash is removed and added again"""
+        contact_id = uuid4()
+        member = _member(contact_id, "old-phone", "email")
+
+        to_add, to_remove = diff_members({contact_id: ("new-phone", "email")}, [member])
+
+        assert to_remove == [member]
+        assert to_add == {contact_id: ("new-phone", "email")}
+
+    def test_unchanged_audience(self):
+        """Test nothing is sent when the segment did not change"""
+        contact_id = uuid4()
+        assert diff_members({contact_id: ("a", "b")}, [_member(contact_id, "a", "b")]) == ({}, [])
+
+
+class TestMetaAdsAPI:
+    """Tests for Custom Audience requests"""
+
+    def test_users_payload(self):
+        """Test missing identifiers are sent as empty strings"""
+        payload = MetaAdsAPI.users_payload([("p1", None), (None, "e2")])
+        assert payload == {"payload": {"schema": ["PHONE", "EMAIL"], "data": [["p1", ""], ["", "e2"]]}}
+
+    def test_ad_account_prefix(self):
+        """Test act_ prefix is optional"""
+        assert MetaAdsAPI("act_123", "token").ad_account_id == "123"
+        assert MetaAdsAPI("123", "token").ad_account_id == "123"
+
+    @pytest.mark.asyncio
+    async def test_users_are_sent_in_batches(self, monkeypatch):
+        """Test large audiences are split per request limit"""
+        monkeypatch.setattr(meta_ads_api, "MAX_USERS_PER_REQUEST", 2)
+        calls = []
+
+        async def fake_request(method, path, payload):
+            calls.append((method, path, len(payload["payload"]["data"])))
+            return {"num_received": len(payload["payload"]["data"])}
+
+        client = MetaAdsAPI("123", "token")
+        monkeypatch.setattr(client, "_request", fake_request)
+
+        received = await client.remove_users("999", [("a", None), ("b", None), ("c", None)])
+
+        assert received == 3
+        assert calls == [("DELETE", "999/users", 2), ("DELETE", "999/users", 1)]
+        assert await client.add_users("999", []) == 0
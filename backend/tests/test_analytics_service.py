@@ -6,11 +6,15 @@ Autor: Kayo Carvalho Fernandes
 import pytest
 import pytest_asyncio
 from uuid import uuid4
-from datetime import datetime, timedelta
+from datetime import datetime, timedelta, timezone
 
 from sqlalchemy.ext.asyncio import AsyncSession
 
+from app.core.exceptions import NotFoundException
+from app.models.chatbot import FlowNodeVisit
+from app.schemas.chatbot import ChatbotCreate, FlowCreate, FlowUpdate
 from app.services.analytics_service import AnalyticsService
+from app.services.chatbot_service import ChatbotService
 from tests.conftest import OrganizationFactory, UserFactory
 
 
@@ -113,3 +117,126 @@ class TestAnalyticsMultiTenancy:
         # Both should be independent
         assert result1.total_contacts == 0
         assert result2.total_contacts == 0
+
+
+class TestAnalyticsFlowFunnel:
+    """Tests for the per-node flow funnel"""
+
+    @pytest_asyncio.fixture
+    async def service(self, db_session: AsyncSession) -> AnalyticsService:
+        return AnalyticsService(db_session)
+
+    async def _flow(self, db_session):
+        org = await OrganizationFactory.create_in_db(db_session)
+        chatbot_service = ChatbotService(db_session)
+        bot = await chatbot_service.create_chatbot(ChatbotCreate(name="Funnel Bot"), org.id)
+        flow = await chatbot_service.create_flow(FlowCreate(name="Main", chatbot_id=bot.id), org.id)
+        flow = await chatbot_service.update_flow(flow.id, org.id, FlowUpdate(canvas_data={
+            "nodes": [
+                {"id": "start-1", "data": {"nodeType": "start"}},
+                {"id": "ask-1", "data": {"nodeType": "question", "label": "Pergunta CPF"}},
+                {"id": "end-1", "data": {"nodeType": "end"}},
+            ],
+            "edges": [],
+        }))
+        return org, flow
+
+    async def _session(self, db_session, org, flow, nodes, started_at, variant=None):
+        """Add a session that visited the nodes one minute apart (variant tags split nodes)"""
+        session_id = uuid4()
+        for index, (node_id, node_type) in enumerate(nodes):
+            entered_at = started_at + timedelta(minutes=index)
+            db_session.add(FlowNodeVisit(
+                id=uuid4(),
+                organization_id=org.id,
+                flow_id=flow.id,
+                conversation_id=uuid4(),
+                session_id=session_id,
+                node_id=node_id,
+                node_type=node_type,
+                entered_at=entered_at,
+                exited_at=entered_at + timedelta(minutes=1) if index < len(nodes) - 1 else None,
+                duration_ms=60000 if index < len(nodes) - 1 else None,
+                variant=variant if node_type == "split" else None,
+            ))
+        await db_session.commit()
+
+    @pytest.mark.asyncio
+    async def test_completion_drop_offs_and_exit_points(self, service, db_session):
+        """Test sessions are split into completed, in progress and abandoned per last node"""
+        org, flow = await self._flow(db_session)
+        now = datetime.now(timezone.utc)
+        full = [("start-1", "start"), ("ask-1", "question"), ("end-1", "end")]
+        stuck = [("start-1", "start"), ("ask-1", "question")]
+        await self._session(db_session, org, flow, full, now - timedelta(hours=3))
+        await self._session(db_session, org, flow, stuck, now - timedelta(hours=2))
+        await self._session(db_session, org, flow, stuck, now - timedelta(hours=2))
+        await self._session(db_session, org, flow, stuck, now - timedelta(minutes=5))
+
+        result = await service.get_flow_funnel(
+            flow.id, org.id, now - timedelta(days=1), now + timedelta(minutes=1)
+        )
+
+        assert result.total_sessions == 4
+        assert result.completed_sessions == 1
+        assert result.in_progress_sessions == 1
+        assert result.abandoned_sessions == 2
+        assert result.completion_rate == 25.0
+
+        ask = next(step for step in result.nodes if step.node_id == "ask-1")
+        assert ask.label == "Pergunta CPF"
+        assert ask.sessions == 4
+        assert ask.drop_offs == 2
+        assert ask.drop_off_rate == 50.0
+        assert ask.avg_time_spent_seconds == 60.0
+        assert [point.node_id for point in result.top_exit_points] == ["ask-1"]
+        assert result.top_exit_points[0].share == 100.0
+
+    @pytest.mark.asyncio
+    async def test_flow_of_other_org_not_found(self, service, db_session):
+        """Test the funnel of another organization's flow is not returned"""
+        _, flow = await self._flow(db_session)
+        other_org = await OrganizationFactory.create_in_db(db_session)
+
+        with pytest.raises(NotFoundException):
+            await service.get_flow_funnel(
+                flow.id, other_org.id, datetime.now(timezone.utc) - timedelta(days=1), datetime.now(timezone.utc)
+            )
+
+    @pytest.mark.asyncio
+    async def test_split_variants_are_compared(self, service, db_session):
+        """Test sessions and conversions per split variant, by completion or goal node"""
+        org, flow = await self._flow(db_session)
+        flow.canvas_data = {
+            "nodes": [
+                *flow.canvas_data["nodes"],
+                {"id": "split-1", "data": {"nodeType": "split", "label": "Oferta", "variants": [
+                    {"id": "a", "label": "Controle", "percentage": 50},
+                    {"id": "b", "label": "Desconto", "percentage": 50},
+                ]}},
+            ],
+            "edges": [],
+        }
+        await db_session.commit()
+
+        now = datetime.now(timezone.utc)
+        converted = [("start-1", "start"), ("split-1", "split"), ("ask-1", "question"), ("end-1", "end")]
+        stuck = [("start-1", "start"), ("split-1", "split")]
+        await self._session(db_session, org, flow, converted, now - timedelta(hours=3), variant="a")
+        await self._session(db_session, org, flow, stuck, now - timedelta(hours=3), variant="a")
+        await self._session(db_session, org, flow, stuck, now - timedelta(hours=3), variant="b")
+
+        result = await service.get_flow_funnel(flow.id, org.id, now - timedelta(days=1), now)
+        [split] = result.splits
+        assert split.node_id == "split-1"
+        assert split.label == "Oferta"
+        assert [(v.variant, v.label, v.sessions, v.conversions, v.conversion_rate) for v in split.variants] == [
+            ("a", "Controle", 2, 1, 50.0),
+            ("b", "Desconto", 1, 0, 0.0),
+        ]
+
+        by_goal = await service.get_flow_funnel(
+            flow.id, org.id, now - timedelta(days=1), now, goal_node_id="ask-1"
+        )
+        assert by_goal.goal_node_id == "ask-1"
+        assert [v.conversions for v in by_goal.splits[0].variants] == [1, 0]
@@ -0,0 +1,118 @@
+"""
+Annotation Service Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import ForbiddenException, NotFoundException
+from app.schemas.annotation import AnnotationCreate
+from app.services.annotation_service import AnnotationService
+from tests.conftest import OrganizationFactory
+
+NOW = datetime(2025, 11, 7, 12, 0, tzinfo=timezone.utc)
+
+
+class TestAnnotationService:
+    """Tests for annotation listing, manual annotations and deploys"""
+
+    @pytest_asyncio.fixture
+    async def service(self, db_session: AsyncSession, monkeypatch) -> AnnotationService:
+        broadcasts = []
+
+        async def fake_broadcast(self, annotation):
+            broadcasts.append(annotation)
+
+        monkeypatch.setattr(AnnotationService, "_broadcast", fake_broadcast)
+        service = AnnotationService(db_session)
+        service.broadcasts = broadcasts
+        return service
+
+    @pytest.mark.asyncio
+    async def test_list_period_with_platform_annotations(
+        self, service: AnnotationService, db_session: AsyncSession
+    ):
+        """Test org and platform annotations of the period, ranged ones included"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        other = await OrganizationFactory.create_in_db(db_session)
+
+        await service.record("campaign_launch", "Black Friday", organization_id=org.id, occurred_at=NOW)
+        await service.record("deploy", "Deploy 1.2.0", occurred_at=NOW - timedelta(hours=1))
+        await service.record("custom", "Other tenant", organization_id=other.id, occurred_at=NOW)
+        await service.record("custom", "Last month", organization_id=org.id, occurred_at=NOW - timedelta(days=40))
+        await service.create_annotation(org.id, AnnotationCreate(
+            title="TV ad week",
+            occurred_at=NOW - timedelta(days=3),
+            ends_at=NOW + timedelta(days=4),
+        ))
+
+        items = await service.list_annotations(org.id, NOW - timedelta(days=1), NOW + timedelta(days=1))
+
+        assert [a.title for a in items] == ["TV ad week", "Deploy 1.2.0", "Black Friday"]
+
+        only_deploys = await service.list_annotations(
+            org.id, NOW - timedelta(days=1), NOW + timedelta(days=1), kinds=["deploy"]
+        )
+        assert [a.kind for a in only_deploys] == ["deploy"]
+
+    @pytest.mark.asyncio
+    async def test_create_manual_annotation_is_broadcast(
+        self, service: AnnotationService, db_session: AsyncSession
+    ):
+        """Test manual annotations default to now and reach open dashboards"""
+        org = await OrganizationFactory.create_in_db(db_session)
+
+        annotation = await service.create_annotation(org.id, AnnotationCreate(title="Price change"))
+
+        assert annotation.source == "manual"
+        assert annotation.kind == "custom"
+        assert annotation.occurred_at is not None
+        assert service.broadcasts == [annotation]
+
+    @pytest.mark.asyncio
+    async def test_delete_rules(self, service: AnnotationService, db_session: AsyncSession):
+        """Test platform and foreign annotations cannot be deleted"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        other = await OrganizationFactory.create_in_db(db_session)
+
+        deploy = await service.record("deploy", "Deploy 1.2.0")
+        foreign = await service.record("custom", "Other tenant", organization_id=other.id)
+        own = await service.record("custom", "Ours", organization_id=org.id)
+
+        with pytest.raises(ForbiddenException):
+            await service.delete_annotation(deploy.id, org.id)
+        with pytest.raises(NotFoundException):
+            await service.delete_annotation(foreign.id, org.id)
+
+        await service.delete_annotation(own.id, org.id)
+        with pytest.raises(NotFoundException):
+            await service.delete_annotation(own.id, org.id)
+
+    @pytest.mark.asyncio
+    async def test_record_deploy_once_per_release(self, service: AnnotationService):
+        """Test every replica starting the same release records a single deploy"""
+        first = await service.record_deploy("1.2.0", "a1b2c3d")
+        second = await service.record_deploy("1.2.0", "a1b2c3d")
+        next_release = await service.record_deploy("1.2.1")
+
+        assert first.title == "Deploy 1.2.0+a1b2c3d"
+        assert first.organization_id is None
+        assert second is None
+        assert next_release.extra_data["release"] == "1.2.1"
+
+
+class TestAnnotationCreate:
+    """Tests for AnnotationCreate validation"""
+
+    def test_range_must_be_ordered(self):
+        with pytest.raises(ValueError):
+            AnnotationCreate(title="Backwards", occurred_at=NOW, ends_at=NOW - timedelta(hours=1))
+
+    def test_unknown_kind(self):
+        with pytest.raises(ValueError):
+            AnnotationCreate(title="Oops", kind="incident")
@@ -14,7 +14,8 @@ from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.services.auth_service import AuthService
 from app.schemas.auth import UserLogin, UserRegister
-from app.core.security import verify_password, hash_password
+from app.core.security import decode_token, verify_password, hash_password
+from app.models.user import UserWorkspace
 from tests.conftest import OrganizationFactory, UserFactory
 
 
@@ -302,6 +303,101 @@ class TestAuthServiceLogout:
             pass
 
 
+class TestAuthServiceWorkspaces:
+    """Tests for multi-workspace accounts and tenant switching"""
+
+    @pytest_asyncio.fixture
+    async def auth_service(self, db_session: AsyncSession) -> AuthService:
+        return AuthService(db_session)
+
+    @pytest_asyncio.fixture
+    async def agency_user(self, db_session: AsyncSession):
+        """Agency admin who is also an agent of a client organization"""
+        agency = await OrganizationFactory.create_in_db(db_session)
+        client = await OrganizationFactory.create_in_db(db_session)
+        user = await UserFactory.create_in_db(
+            db_session, organization_id=agency.id, role="org_admin"
+        )
+        db_session.add(UserWorkspace(
+            user_id=user.id, organization_id=client.id, role="agent", department_ids=[]
+        ))
+        await db_session.commit()
+        return user, agency, client
+
+    @pytest.mark.asyncio
+    async def test_switch_issues_scoped_tokens(self, auth_service: AuthService, agency_user):
+        """Test switched tokens carry the client organization and role"""
+        user, agency, client = agency_user
+
+        token = await auth_service.switch_workspace(user, client.id)
+
+        access = decode_token(token.access_token)
+        assert access["organization_id"] == str(client.id)
+        assert access["role"] == "agent"
+        assert decode_token(token.refresh_token)["organization_id"] == str(client.id)
+
+        scoped = await auth_service.get_current_user(token.access_token)
+        assert scoped.organization_id == client.id
+        assert scoped.role == "agent"
+        assert scoped.home_organization_id == agency.id
+
+    @pytest.mark.asyncio
+    async def test_switch_back_home(self, auth_service: AuthService, agency_user):
+        """Test switching back restores the home organization and role"""
+        user, agency, client = agency_user
+        client_token = await auth_service.switch_workspace(user, client.id)
+        scoped = await auth_service.get_current_user(client_token.access_token)
+
+        token = await auth_service.switch_workspace(scoped, agency.id)
+
+        access = decode_token(token.access_token)
+        assert access["organization_id"] == str(agency.id)
+        assert access["role"] == "org_admin"
+
+    @pytest.mark.asyncio
+    async def test_switch_requires_membership(
+        self, auth_service: AuthService, agency_user, db_session: AsyncSession
+    ):
+        """Test organizations without membership are rejected"""
+        user, _, _ = agency_user
+        other = await OrganizationFactory.create_in_db(db_session)
+
+        with pytest.raises(HTTPException) as exc_info:
+            await auth_service.switch_workspace(user, other.id)
+
+        assert exc_info.value.status_code == 403
+
+    @pytest.mark.asyncio
+    async def test_revoked_membership_invalidates_token(
+        self, auth_service: AuthService, agency_user, db_session: AsyncSession
+    ):
+        """Test tokens of a removed workspace stop working"""
+        user, _, client = agency_user
+        token = await auth_service.switch_workspace(user, client.id)
+
+        workspace = await auth_service._get_workspace(user.id, client.id)
+        workspace.is_active = False
+        await db_session.commit()
+
+        with pytest.raises(HTTPException):
+            await auth_service.get_current_user(token.access_token)
+        with pytest.raises(HTTPException):
+            await auth_service.refresh_access_token(token.refresh_token)
+
+    @pytest.mark.asyncio
+    async def test_list_workspaces(self, auth_service: AuthService, agency_user):
+        """Test home organization first, then memberships with their roles"""
+        user, agency, client = agency_user
+
+        workspaces = await auth_service.list_workspaces(user)
+
+        assert [w.organization_id for w in workspaces] == [agency.id, client.id]
+        assert workspaces[0].is_home and workspaces[0].is_current
+        assert workspaces[0].role == "org_admin"
+        assert workspaces[1].role == "agent"
+        assert not workspaces[1].is_current
+
+
 class TestPasswordSecurity:
     """Tests for password hashing and verification"""
 
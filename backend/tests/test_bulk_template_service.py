@@ -0,0 +1,165 @@
+"""
+Bulk Template Send Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+from types import SimpleNamespace
+
+import pytest
+
+from app.core.config import settings
+from app.integrations.meta_api import MetaAPIError
+from app.services.bulk_template_service import (
+    BulkTemplateSendService,
+    build_components,
+    parse_recipients_csv,
+    results_csv,
+)
+
+
+def _template(**overrides):
+    data = dict(
+        name="order_ready",
+        language="pt_BR",
+        header_type="TEXT",
+        header_variables_count=0,
+        body_variables_count=2,
+    )
+    data.update(overrides)
+    return SimpleNamespace(**data)
+
+
+class FakeMetaAPI:
+    def __init__(self, fail_for=()):
+        self.fail_for = set(fail_for)
+        self.sent = []
+
+    async def send_template_message(self, to, template_name, language_code, components=None):
+        if to in self.fail_for:
+            raise MetaAPIError("Recipient not on WhatsApp", error_code="131026")
+        self.sent.append((to, components))
+        return {"messages": [{"id": f"wamid.{to}"}]}
+
+
+class FakeRateLimiter:
+    def __init__(self, waits=()):
+        self.waits = list(waits)
+        self.recorded = 0
+
+    async def wait_if_needed(self):
+        return self.waits.pop(0) if self.waits else 0.0
+
+    async def record_message_sent(self):
+        self.recorded += 1
+
+
+class FakeTierShaper:
+    def __init__(self, full_after=None):
+        self.full_after = full_after
+        self.reserved = []
+        self.released = []
+
+    async def reserve(self, recipient):
+        allowed = self.full_after is None or len(self.reserved) < self.full_after
+        self.reserved.append(recipient)
+        return SimpleNamespace(
+            allowed=allowed, reserved=True, tier="TIER_1K", send_at=datetime(2025, 11, 10, tzinfo=timezone.utc)
+        )
+
+    async def release(self, recipient):
+        self.released.append(recipient)
+
+
+class TestParseRecipientsCsv:
+    """Tests for reading the recipients CSV"""
+
+    def test_variables_in_column_order(self):
+        rows = parse_recipients_csv(
+            "phone,header1,var1,var2\n+55 (11) 99999-9999,Pedido 42,Maria,20%\n\n5511988888888,Pedido 43,João\n"
+        )
+
+        assert [(r.line, r.phone, r.header, r.body) for r in rows] == [
+            (2, "5511999999999", ["Pedido 42"], ["Maria", "20%"]),
+            # Missing trailing columns are empty (reported as invalid parameters later)
+            (4, "5511988888888", ["Pedido 43"], ["João", ""]),
+        ]
+        assert build_components(rows[0]) == [
+            {"type": "header", "parameters": [{"type": "text", "text": "Pedido 42"}]},
+            {"type": "body", "parameters": [{"type": "text", "text": "Maria"}, {"type": "text", "text": "20%"}]},
+        ]
+
+    def test_invalid_and_duplicate_phones(self):
+        rows = parse_recipients_csv("phone\n123\n5511999999999\n55 11 99999 9999\n")
+
+        assert [(r.status, r.error) for r in rows] == [
+            ("invalid", "invalid phone number"),
+            ("pending", None),
+            ("skipped", "duplicate phone"),
+        ]
+
+    @pytest.mark.parametrize("content", ["", "phone\n", "phone\n5511999999999\n5511988888888\n"])
+    def test_rejected_files(self, content):
+        with pytest.raises(ValueError):
+            parse_recipients_csv(content, max_rows=1)
+
+
+class TestBulkDispatch:
+    """Tests for sending rows with the campaign guards"""
+
+    @pytest.mark.asyncio
+    async def test_row_outcomes(self):
+        """Test sent, opted-out, invalid and failed rows in one blast"""
+        rows = parse_recipients_csv(
+            "phone,var1,var2\n"
+            "5511900000001,Maria,20%\n"
+            "5511900000002,João,15%\n"
+            "5511900000003,Ana\n"
+            "5511900000004,Rui,10%\n"
+        )
+        api, limiter, shaper = FakeMetaAPI(fail_for={"5511900000004"}), FakeRateLimiter(), FakeTierShaper()
+
+        await BulkTemplateSendService(db=None)._dispatch(
+            rows, _template(), {"5511900000002"}, api, limiter, shaper
+        )
+
+        assert [(r.status, r.message_id) for r in rows] == [
+            ("sent", "wamid.5511900000001"),
+            ("skipped", None),
+            ("invalid", None),
+            ("failed", None),
+        ]
+        assert rows[1].error == "opted out"
+        assert rows[2].error == "body parameter {{2}} is empty"
+        assert limiter.recorded == 1
+        # The failed send gives its tier slot back
+        assert shaper.released == ["5511900000004"]
+
+    @pytest.mark.asyncio
+    async def test_exhausted_limits_skip_remaining_rows(self, monkeypatch):
+        monkeypatch.setattr(settings, "BULK_SEND_MAX_WAIT_SECONDS", 60)
+        rows = parse_recipients_csv("phone\n5511900000001\n5511900000002\n5511900000003\n")
+        api = FakeMetaAPI()
+
+        await BulkTemplateSendService(db=None)._dispatch(
+            rows, _template(body_variables_count=0), set(), api, FakeRateLimiter(waits=[0, 3600]), FakeTierShaper()
+        )
+
+        assert [r.status for r in rows] == ["sent", "skipped", "skipped"]
+        assert rows[2].error == rows[1].error == "rate limit reached, retry in 60 min"
+        assert "line,phone,status,message_id,error" in results_csv(rows)
+
+    @pytest.mark.asyncio
+    async def test_full_tier_is_not_deferred(self):
+        """Test rows over the messaging tier are skipped and their queued slot released"""
+        rows = parse_recipients_csv("phone\n5511900000001\n5511900000002\n")
+        shaper = FakeTierShaper(full_after=1)
+
+        await BulkTemplateSendService(db=None)._dispatch(
+            rows, _template(body_variables_count=0), set(), FakeMetaAPI(), FakeRateLimiter(), shaper
+        )
+
+        assert [r.status for r in rows] == ["sent", "skipped"]
+        assert rows[1].error.startswith("messaging tier TIER_1K full until")
+        assert shaper.released == ["5511900000002"]
@@ -0,0 +1,107 @@
+"""
+Business Hours Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+
+import pytest
+
+from app.utils.business_hours import business_hours_status, validate_business_hours
+
+WEEKDAY = {"enabled": True, "start": "09:00", "end": "18:00"}
+
+CONFIG = {
+    "timezone": "America/Sao_Paulo",
+    "schedule": {
+        "monday": WEEKDAY,
+        "tuesday": WEEKDAY,
+        "wednesday": WEEKDAY,
+        "thursday": WEEKDAY,
+        "friday": WEEKDAY,
+        "saturday": {"enabled": True, "start": "09:00", "end": "12:00"},
+        "sunday": {"enabled": False},
+    },
+    "holidays": [
+        {"date": "2025-11-20", "name": "Consciência Negra"},
+        {"date": "12-25", "name": "Natal"},
+        {"date": "12-24", "name": "Véspera de Natal", "start": "09:00", "end": "12:00"},
+    ],
+}
+
+
+def _utc(*args):
+    return datetime(*args, tzinfo=timezone.utc)
+
+
+class TestBusinessHoursStatus:
+    """Tests for business_hours_status()"""
+
+    @pytest.mark.parametrize("now,handle", [
+        (_utc(2025, 11, 18, 12, 0), "open"),     # Tuesday 09:00 in São Paulo
+        (_utc(2025, 11, 18, 11, 59), "closed"),  # Tuesday 08:59
+        (_utc(2025, 11, 18, 21, 0), "closed"),   # Tuesday 18:00, end is exclusive
+        (_utc(2025, 11, 22, 14, 0), "open"),     # Saturday 11:00
+        (_utc(2025, 11, 22, 18, 0), "closed"),   # Saturday 15:00
+        (_utc(2025, 11, 23, 14, 0), "closed"),   # Sunday
+    ])
+    def test_weekly_schedule_in_tenant_timezone(self, now, handle):
+        """Test the schedule is read in the tenant timezone"""
+        assert business_hours_status(CONFIG, now).handle == handle
+
+    def test_holidays_close_the_day_or_reduce_hours(self):
+        """Test dated and yearly holidays, with and without reduced hours"""
+        black_consciousness = business_hours_status(CONFIG, _utc(2025, 11, 20, 15, 0))
+        assert black_consciousness.handle == "holiday"
+        assert black_consciousness.holiday == "Consciência Negra"
+        assert business_hours_status(CONFIG, _utc(2026, 12, 25, 15, 0)).handle == "holiday"
+
+        christmas_eve_morning = business_hours_status(CONFIG, _utc(2025, 12, 24, 13, 0))
+        assert christmas_eve_morning.is_open and christmas_eve_morning.holiday == "Véspera de Natal"
+        assert business_hours_status(CONFIG, _utc(2025, 12, 24, 18, 0)).handle == "holiday"
+
+    def test_next_opening_skips_closed_days_and_holidays(self):
+        """Test the next opening is the start of the next working period"""
+        friday_night = business_hours_status(CONFIG, _utc(2025, 11, 21, 23, 0))
+        assert friday_night.next_open.isoformat() == "2025-11-22T09:00:00-03:00"
+
+        wednesday_night = business_hours_status(CONFIG, _utc(2025, 11, 19, 23, 0))
+        assert wednesday_night.next_open.isoformat() == "2025-11-21T09:00:00-03:00"
+
+    def test_without_schedule_is_always_open(self):
+        """Test tenants without business hours are never closed"""
+        assert business_hours_status({}, _utc(2025, 11, 23, 3, 0)).is_open
+        assert business_hours_status(None, _utc(2025, 11, 23, 3, 0)).is_open
+
+    def test_organization_timezone_is_the_fallback(self):
+        """Test the organization timezone applies when the config has none"""
+        config = {**CONFIG, "timezone": None}
+        assert business_hours_status(config, _utc(2025, 11, 18, 9, 30), "UTC").is_open
+        assert not business_hours_status(config, _utc(2025, 11, 18, 9, 30), "America/Sao_Paulo").is_open
+
+
+class TestValidateBusinessHours:
+    """Tests for validate_business_hours()"""
+
+    def test_valid_config(self):
+        """Test the documented format is accepted"""
+        assert validate_business_hours(CONFIG) == []
+        assert validate_business_hours(None) == []
+
+    def test_invalid_config(self):
+        """Test timezones, weekdays, times and holiday dates are checked"""
+        errors = validate_business_hours({
+            "timezone": "America/Atlantida",
+            "schedule": {"segunda": WEEKDAY, "monday": {"start": "18:00", "end": "09:00"}, "friday": {"start": "9h"}},
+            "holidays": [{"date": "2025-02-30"}, {"date": "12-25", "start": "13:00", "end": "12:00"}],
+        })
+
+        assert errors == [
+            "Unknown timezone 'America/Atlantida'",
+            "Unknown weekday 'segunda'",
+            "monday: start must be before end",
+            "friday: times must be HH:MM",
+            "Invalid holiday date '2025-02-30'",
+            "Holiday 12-25: start must be before end",
+        ]
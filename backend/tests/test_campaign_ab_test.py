@@ -0,0 +1,164 @@
+"""
+Campaign A/B Test Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+from pydantic import ValidationError
+
+from app.schemas.campaign import CampaignABTest
+from app.utils.campaign_ab_test import (
+    WINNER_SIGNIFICANT,
+    WINNER_TIMEOUT,
+    contact_variant,
+    decide_winner,
+    in_test_group,
+    phase_contacts,
+    record_engagement,
+    two_proportion_p_value,
+    variant_results,
+    wilson_interval,
+)
+
+CAMPAIGN_ID = uuid4()
+NOW = datetime(2025, 11, 19, 12, 0)
+
+
+def _config(**overrides):
+    config = {
+        "variants": [{"id": "a", "label": "Controle"}, {"id": "b", "text": "Olá {{contact.name}}!"}],
+        "test_percentage": 20,
+        "metric": "read",
+        "confidence_level": 0.95,
+        "min_sample_size": 100,
+    }
+    return {**config, **overrides}
+
+
+def _statuses(variant, sent, read):
+    return {
+        f"{variant}-{index}": {"variant": variant, "status": "read" if index < read else "delivered"}
+        for index in range(sent)
+    }
+
+
+class TestAssignment:
+    """Tests for the split of the audience"""
+
+    def test_test_group_share_and_stable_variant(self):
+        """Test about test_percentage of the audience is tested and variants are stable"""
+        config = _config()
+        contacts = [uuid4() for _ in range(2000)]
+        tested = [contact for contact in contacts if in_test_group(config, CAMPAIGN_ID, contact)]
+
+        assert 300 < len(tested) < 500
+        for contact in tested[:50]:
+            variant = contact_variant(config, CAMPAIGN_ID, contact)
+            assert variant["id"] in {"a", "b"}
+            assert contact_variant(config, CAMPAIGN_ID, contact) == variant
+
+    def test_rest_of_audience_waits_for_the_winner(self):
+        """Test the rest of the audience gets nothing until a winner, then the winner"""
+        contacts = [SimpleNamespace(id=uuid4()) for _ in range(200)]
+        config = _config()
+        test_group = phase_contacts(config, CAMPAIGN_ID, contacts)
+        rest = [contact for contact in contacts if contact not in test_group]
+
+        assert rest and all(contact_variant(config, CAMPAIGN_ID, contact.id) is None for contact in rest)
+
+        decided = _config(winner={"variant": "b", "reason": WINNER_SIGNIFICANT})
+        assert phase_contacts(decided, CAMPAIGN_ID, contacts) == rest
+        winner = contact_variant(decided, CAMPAIGN_ID, rest[0].id)
+        assert winner["id"] == "b" and winner["text"] == "Olá {{contact.name}}!"
+
+
+class TestStatistics:
+    """Tests for the confidence intervals and significance"""
+
+    def test_wilson_interval(self):
+        """Test the interval contains the rate and is empty without sends"""
+        low, high = wilson_interval(50, 100, 0.95)
+        assert low == pytest.approx(0.404, abs=0.001)
+        assert high == pytest.approx(0.596, abs=0.001)
+        assert wilson_interval(0, 0, 0.95) == (0.0, 0.0)
+
+    def test_two_proportion_p_value(self):
+        """Test a clear difference is significant and equal rates are not"""
+        assert two_proportion_p_value(60, 100, 40, 100) < 0.01
+        assert two_proportion_p_value(50, 100, 50, 100) == pytest.approx(0.5)
+        assert two_proportion_p_value(1, 0, 1, 10) == 1.0
+
+    def test_variant_results(self):
+        """Test counts per variant from the message statuses"""
+        statuses = {
+            **_statuses("a", 3, 1),
+            "failed": {"variant": "a", "status": "failed"},
+            "replied": {"variant": "b", "status": "delivered", "replied_at": "x", "clicked_at": "x"},
+            "other": {"status": "read"},
+        }
+        results = {row["variant"]: row for row in variant_results(_config(metric="reply"), statuses)}
+
+        assert results["a"]["sent"] == 3 and results["a"]["read"] == 1
+        assert results["a"]["conversions"] == 0
+        assert results["b"]["sent"] == 1 and results["b"]["read"] == 1
+        assert results["b"]["replied"] == 1 and results["b"]["clicked"] == 1
+        assert results["b"]["rate"] == 1.0
+        assert results["a"]["label"] == "Controle"
+
+
+class TestWinner:
+    """Tests for declaring the winner"""
+
+    def test_significant_winner(self):
+        """Test the best variant wins once it beats the others with enough sends"""
+        config = _config()
+        statuses = {**_statuses("a", 150, 60), **_statuses("b", 150, 90)}
+        winner = decide_winner(config, variant_results(config, statuses), NOW, NOW)
+
+        assert winner["variant"] == "b"
+        assert winner["reason"] == WINNER_SIGNIFICANT
+        assert winner["p_value"] < 0.05
+
+    def test_waits_for_sample_size(self):
+        """Test no winner is declared below min_sample_size"""
+        config = _config()
+        statuses = {**_statuses("a", 50, 10), **_statuses("b", 50, 40)}
+        assert decide_winner(config, variant_results(config, statuses), NOW, NOW) is None
+
+    def test_best_rate_wins_after_max_test_hours(self):
+        """Test the best variant is rolled out when the test runs out of time"""
+        config = _config(max_test_hours=24)
+        statuses = {**_statuses("a", 150, 60), **_statuses("b", 150, 62)}
+        results = variant_results(config, statuses)
+
+        assert decide_winner(config, results, NOW, NOW + timedelta(hours=23)) is None
+        winner = decide_winner(config, results, NOW, NOW + timedelta(hours=24))
+        assert winner["variant"] == "b" and winner["reason"] == WINNER_TIMEOUT
+
+
+class TestEngagement:
+    """Tests for recording replies and clicks"""
+
+    def test_record_engagement(self):
+        """Test only the first reply counts and button replies are clicks"""
+        entry = {"variant": "a", "status": "read"}
+
+        assert record_engagement(entry, "text", NOW) is True
+        assert "clicked_at" not in entry
+        assert record_engagement(entry, "button", NOW) is False
+        assert entry["replied_at"] == entry["clicked_at"] == NOW.isoformat()
+
+
+class TestSchema:
+    """Tests for the A/B test schema"""
+
+    def test_variant_ids_must_be_unique(self):
+        """Test duplicated variant ids are refused"""
+        with pytest.raises(ValidationError):
+            CampaignABTest(variants=[{"id": "a"}, {"id": "a"}])
+        assert CampaignABTest(variants=[{"id": "a"}, {"id": "b"}]).test_percentage == 20
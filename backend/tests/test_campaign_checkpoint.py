@@ -0,0 +1,270 @@
+"""
+Campaign Checkpoint Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from contextlib import asynccontextmanager
+from datetime import datetime, timezone
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+from sqlalchemy import update
+from sqlalchemy.ext.asyncio import AsyncSession
+
+import app.tasks.campaign_tasks as campaign_tasks
+from app.models.campaign import Campaign
+from app.models.contact import Contact
+from app.models.whatsapp_number import WhatsAppNumber
+from app.tasks.campaign_checkpoint import CampaignCheckpointManager
+from tests.conftest import OrganizationFactory
+
+
+def _make_campaign() -> Campaign:
+    return Campaign(id=uuid4(), dispatch_checkpoint={}, message_statuses={})
+
+
+class TestCampaignCheckpointManager:
+    """Tests for CampaignCheckpointManager"""
+
+    def test_init_batches_records_contacts(self):
+        """Test batches are stored with their exact contact ids"""
+        campaign = _make_campaign()
+        checkpoint = CampaignCheckpointManager(campaign)
+
+        checkpoint.init_batches([["a", "b"], ["c"]], batch_size=2)
+
+        assert checkpoint.has_checkpoint
+        assert campaign.dispatch_checkpoint["batches"]["0"]["contact_ids"] == ["a", "b"]
+        assert [batch["contact_ids"] for batch in checkpoint.pending_batches()] == [["a", "b"], ["c"]]
+
+    def test_advance_and_complete(self):
+        """Test cursor advances and batches complete"""
+        campaign = _make_campaign()
+        checkpoint = CampaignCheckpointManager(campaign)
+        checkpoint.init_batches([["a", "b"], ["c"]], batch_size=2)
+
+        checkpoint.advance(0, "a")
+        assert checkpoint.pending_batches()[0]["cursor"] == "a"
+        assert checkpoint.pending_batches()[0]["contact_ids"] == ["b"]
+
+        checkpoint.mark_batch_done(0)
+        checkpoint.mark_batch_done(1)
+
+        assert checkpoint.is_complete()
+        assert checkpoint.pending_batches() == []
+
+    def test_defer_until_resume(self):
+        """Test tier deferrals keep the latest ETA and are cleared on resume"""
+        checkpoint = CampaignCheckpointManager(_make_campaign())
+        checkpoint.init_batches([["a"]], batch_size=1)
+
+        checkpoint.defer(datetime(2025, 11, 3, 8, 0, tzinfo=timezone.utc))
+        checkpoint.defer(datetime(2025, 11, 3, 7, 0, tzinfo=timezone.utc))
+
+        assert checkpoint.deferred_until == datetime(2025, 11, 3, 8, 0)
+
+        checkpoint.record_resume()
+
+        assert checkpoint.deferred_until is None
+
+    def test_is_already_sent(self):
+        """Test sent contacts are detected from message_statuses"""
+        campaign = _make_campaign()
+        contact_id = uuid4()
+        campaign.message_statuses = {str(contact_id): {"status": "delivered"}}
+        checkpoint = CampaignCheckpointManager(campaign)
+
+        assert checkpoint.is_already_sent(contact_id)
+        assert not checkpoint.is_already_sent(uuid4())
+
+    @pytest.mark.asyncio
+    async def test_claim_send_fails_open_without_redis(self):
+        """Test claim is granted when Redis is not connected"""
+        checkpoint = CampaignCheckpointManager(_make_campaign())
+
+        assert await checkpoint.claim_send(uuid4()) is True
+
+
+class FakeRateLimiter:
+    async def get_current_usage(self):
+        return {}
+
+    async def can_send_message(self):
+        return True, None
+
+    async def record_message_sent(self):
+        pass
+
+
+class FakeThrottle:
+    allowed = SimpleNamespace(allowed=True)
+
+    def check_hours(self, contact):
+        return self.allowed
+
+    async def reserve(self):
+        return self.allowed
+
+
+@pytest.fixture
+def sent(monkeypatch):
+    """Contacts sent to by the batch; pause_with is the status an agent sets after the first send"""
+    sent = SimpleNamespace(contact_ids=[], pause_with=None)
+
+    class FakeRetryManager:
+        def __init__(self, campaign, db):
+            self.campaign, self.db = campaign, db
+
+        async def send_message_with_retry(self, contact, whatsapp_number, ab_variant=None):
+            sent.contact_ids.append(contact.id)
+            if sent.pause_with and len(sent.contact_ids) == 1:
+                await self.db.execute(
+                    update(Campaign).where(Campaign.id == self.campaign.id).values(status=sent.pause_with)
+                )
+            return True, "wamid"
+
+    async def rate_limiter(number_id, connection_type):
+        return FakeRateLimiter()
+
+    monkeypatch.setattr(campaign_tasks, "CampaignRetryManager", FakeRetryManager)
+    monkeypatch.setattr(campaign_tasks, "get_whatsapp_rate_limiter", rate_limiter)
+    monkeypatch.setattr(campaign_tasks, "get_tier_shaper", lambda number: None)
+    monkeypatch.setattr(campaign_tasks, "get_campaign_throttle", lambda campaign: FakeThrottle())
+    return sent
+
+
+def _use_session(monkeypatch, db):
+    """Run the campaign tasks on the test database session"""
+
+    @asynccontextmanager
+    async def session():
+        yield db
+
+    monkeypatch.setattr(campaign_tasks, "async_session", session)
+
+
+class TestBatchPause:
+    """Tests for pausing a campaign while a batch is sending"""
+
+    async def _run_batch(self, db, monkeypatch):
+        org = await OrganizationFactory.create_in_db(db)
+        number = WhatsAppNumber(id=uuid4(), organization_id=org.id, phone_number="+5511900000000", is_active=True)
+        contacts = sorted(
+            (Contact(id=uuid4(), organization_id=org.id, whatsapp_id=f"+55119999900{n}") for n in range(3)),
+            key=lambda contact: contact.id,
+        )
+        campaign = Campaign(
+            id=uuid4(),
+            organization_id=org.id,
+            name="Black Friday",
+            status="running",
+            audience_type="all",
+            whatsapp_number_id=number.id,
+            messages_sent=0,
+            messages_failed=0,
+            messages_pending=3,
+            delay_between_messages_seconds=0,
+            dispatch_checkpoint={},
+            message_statuses={},
+        )
+        CampaignCheckpointManager(campaign).init_batches([[str(contact.id) for contact in contacts]], batch_size=3)
+        db.add_all([number, *contacts, campaign])
+        await db.commit()
+        _use_session(monkeypatch, db)
+
+        result = await campaign_tasks._process_batch_async(
+            str(campaign.id), [str(contact.id) for contact in contacts], 0
+        )
+        await db.refresh(campaign)
+        return result, contacts, campaign
+
+    @pytest.mark.asyncio
+    @pytest.mark.parametrize("status", ["paused", "cancelled"])
+    async def test_stops_mid_batch_and_keeps_cursor(self, db_session: AsyncSession, monkeypatch, sent, status):
+        """Test a pause or cancel during the batch stops before the next send"""
+        sent.pause_with = status
+        result, contacts, campaign = await self._run_batch(db_session, monkeypatch)
+
+        assert sent.contact_ids == [contacts[0].id]
+        assert result["status"] == status
+        assert result["sent"] == 1
+        assert campaign.status == status
+        assert campaign.messages_sent == 1
+        [batch] = CampaignCheckpointManager(campaign).pending_batches()
+        assert batch["cursor"] == str(contacts[0].id)
+
+    @pytest.mark.asyncio
+    async def test_running_campaign_completes_batch(self, db_session: AsyncSession, monkeypatch, sent):
+        """Test a campaign still running sends the whole batch"""
+        result, contacts, campaign = await self._run_batch(db_session, monkeypatch)
+
+        assert sent.contact_ids == [contact.id for contact in contacts]
+        assert result["status"] == "completed"
+        assert campaign.messages_sent == 3
+        assert CampaignCheckpointManager(campaign).pending_batches() == []
+
+
+@pytest.fixture
+def dispatched(monkeypatch):
+    """Batch tasks created by the dispatcher (kwargs of process_batch)"""
+    dispatched = []
+
+    class FakeSignature:
+        def __init__(self, **kwargs):
+            dispatched.append(kwargs)
+
+        def set(self, **options):
+            return self
+
+    async def campaign_queue(db, organization_id):
+        return "campaigns"
+
+    monkeypatch.setattr(campaign_tasks.process_batch, "s", FakeSignature)
+    monkeypatch.setattr(campaign_tasks.finalize_campaign, "s", lambda campaign_id: SimpleNamespace(set=lambda **options: None))
+    monkeypatch.setattr(campaign_tasks, "_campaign_queue", campaign_queue)
+    monkeypatch.setattr(campaign_tasks, "chord", lambda tasks: lambda callback: None)
+    return dispatched
+
+
+class TestResumeFromCheckpoint:
+    """Tests for resuming a campaign dispatch from its checkpoint"""
+
+    @pytest.mark.asyncio
+    async def test_resume_sends_the_stored_batch(self, db_session: AsyncSession, monkeypatch, dispatched):
+        """Test a resumed batch keeps its planned contacts when the audience changed"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        number = WhatsAppNumber(id=uuid4(), organization_id=org.id, phone_number="+5511900000000", is_active=True)
+        first, second, joined, third = (
+            Contact(id=contact_id, organization_id=org.id, whatsapp_id=f"+55119999900{n}")
+            for n, contact_id in enumerate(sorted(uuid4() for _ in range(4)))
+        )
+        campaign = Campaign(
+            id=uuid4(),
+            organization_id=org.id,
+            name="Black Friday",
+            status="running",
+            audience_type="all",
+            whatsapp_number_id=number.id,
+            dispatch_checkpoint={},
+            message_statuses={},
+        )
+        checkpoint = CampaignCheckpointManager(campaign)
+        checkpoint.init_batches([[str(first.id), str(second.id), str(third.id)]], batch_size=100)
+        checkpoint.advance(0, first.id)
+        # joined entered the audience after the batch was planned, between its contacts
+        db_session.add_all([number, first, second, joined, third, campaign])
+        await db_session.commit()
+
+        _use_session(monkeypatch, db_session)
+
+        result = await campaign_tasks._execute_campaign_async(str(campaign.id), "task-1")
+
+        assert result["resumed"] is True
+        assert result["remaining_contacts"] == 2
+        assert dispatched[0] == {
+            "campaign_id": str(campaign.id),
+            "contact_ids": [str(second.id), str(third.id)],
+            "batch_index": 0,
+        }
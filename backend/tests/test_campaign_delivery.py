@@ -0,0 +1,323 @@
+"""
+Campaign Delivery Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from contextlib import asynccontextmanager
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+import app.tasks.campaign_retry as campaign_retry
+import app.tasks.campaign_tasks as campaign_tasks
+from app.integrations.meta_api import MetaAPIError
+from app.models.campaign import Campaign, CampaignMessage
+from app.models.contact import Contact
+from app.models.conversation import Conversation, Message
+from app.models.whatsapp_number import WhatsAppNumber
+from app.tasks.campaign_checkpoint import CampaignCheckpointManager
+from app.tasks.campaign_retry import CampaignRetryManager
+from tests.conftest import OrganizationFactory
+
+
+class FakeMetaAPI:
+    """MetaCloudAPI recording the messages sent (error codes queued in `errors` fail the next sends)"""
+
+    sent = []
+    errors = []
+
+    def __init__(self, phone_number_id, access_token):
+        pass
+
+    async def send_text_message(self, to, text):
+        if FakeMetaAPI.errors:
+            raise MetaAPIError("Message undeliverable", error_code=FakeMetaAPI.errors.pop(0))
+        FakeMetaAPI.sent.append((to, text))
+        return {"messages": [{"id": f"wamid.{len(FakeMetaAPI.sent)}"}]}
+
+
+class FakeEvolutionAPI:
+    """EvolutionAPIClient recording the messages sent"""
+
+    sent = []
+
+    def __init__(self, api_url, api_key):
+        self.api_url = api_url
+
+    async def send_text_message(self, instance_name, phone_number, message):
+        FakeEvolutionAPI.sent.append((instance_name, phone_number, message))
+        return {"key": {"id": "3EB0C767D26A"}}
+
+
+@pytest.fixture
+def apis(monkeypatch):
+    """WhatsApp APIs of the campaign sender"""
+    FakeMetaAPI.sent, FakeMetaAPI.errors, FakeEvolutionAPI.sent = [], [], []
+    monkeypatch.setattr(campaign_retry, "MetaCloudAPI", FakeMetaAPI)
+    monkeypatch.setattr(campaign_retry, "EvolutionAPIClient", FakeEvolutionAPI)
+    return SimpleNamespace(meta=FakeMetaAPI.sent, meta_errors=FakeMetaAPI.errors, evolution=FakeEvolutionAPI.sent)
+
+
+@pytest_asyncio.fixture
+async def org(db_session: AsyncSession):
+    """Organization running the campaign"""
+    return await OrganizationFactory.create_in_db(db_session)
+
+
+def _campaign(org):
+    return Campaign(
+        id=uuid4(),
+        organization_id=org.id,
+        name="Black Friday",
+        status="running",
+        audience_type="all",
+        message_type="text",
+        message_content={"text": "Olá {{contact.name}}, a Black Friday começou!"},
+        template_id=None,
+        ab_test=None,
+        retry_max_attempts=3,
+        retry_base_delay=0,
+        retry_max_delay=0,
+        errors=[],
+        message_statuses={},
+        dispatch_checkpoint={},
+        error_count=0,
+        messages_sent=0,
+        messages_failed=0,
+        messages_pending=0,
+        delay_between_messages_seconds=0,
+    )
+
+
+def _contact(org, name="Maria Silva", whatsapp_id="+5511999999999"):
+    return Contact(id=uuid4(), organization_id=org.id, name=name, whatsapp_id=whatsapp_id)
+
+
+def _number(org, connection_type="official"):
+    return WhatsAppNumber(
+        id=uuid4(),
+        organization_id=org.id,
+        phone_number="+5511900000000",
+        connection_type=connection_type,
+        phone_number_id="1234",
+        access_token="token",
+        evolution_api_url="https://evolution.example.com",
+        evolution_api_key="key",
+        evolution_instance_name="loja",
+        default_chatbot_id=None,
+    )
+
+
+async def _save(db, *rows):
+    db.add_all(rows)
+    await db.commit()
+    return rows
+
+
+async def _stored(db, model):
+    return (await db.execute(select(model))).scalars().all()
+
+
+class TestCampaignSend:
+    """Tests for sending a campaign message and storing it"""
+
+    @pytest.mark.asyncio
+    async def test_successful_send_is_stored_once(self, db_session: AsyncSession, org, apis):
+        """Test one send makes one API call and stores one message in a new conversation"""
+        campaign, contact, number = await _save(db_session, _campaign(org), _contact(org), _number(org))
+
+        success, message_id = await CampaignRetryManager(campaign, db_session).send_message_with_retry(contact, number)
+
+        assert (success, message_id) == (True, "wamid.1")
+        assert apis.meta == [("+5511999999999", "Olá Maria Silva, a Black Friday começou!")]
+
+        [conversation] = await _stored(db_session, Conversation)
+        assert conversation.contact_id == contact.id and conversation.status == "open"
+        assert conversation.total_messages == 1
+
+        [message] = await _stored(db_session, Message)
+        assert message.conversation_id == conversation.id
+        assert message.direction == "outbound"
+        assert message.message_type == "text"
+        assert message.content == {"text": "Olá Maria Silva, a Black Friday começou!"}
+        assert message.whatsapp_message_id == "wamid.1"
+        assert message.extra_data["campaign_id"] == str(campaign.id)
+        await db_session.refresh(campaign)
+        assert campaign.message_statuses[str(contact.id)]["status"] == "sent"
+
+    @pytest.mark.asyncio
+    async def test_open_conversation_is_reused(self, db_session: AsyncSession, org, apis):
+        """Test the message goes to the contact's open conversation"""
+        campaign, contact, number = await _save(db_session, _campaign(org), _contact(org), _number(org))
+        [open_conversation] = await _save(db_session, Conversation(
+            id=uuid4(),
+            organization_id=org.id,
+            contact_id=contact.id,
+            whatsapp_number_id=number.id,
+            status="open",
+            total_messages=4,
+        ))
+
+        await CampaignRetryManager(campaign, db_session).send_message_with_retry(contact, number)
+
+        assert await _stored(db_session, Conversation) == [open_conversation]
+        assert (await _stored(db_session, Message))[0].conversation_id == open_conversation.id
+        await db_session.refresh(open_conversation)
+        assert open_conversation.total_messages == 5
+
+    @pytest.mark.asyncio
+    async def test_qrcode_number_sends_through_evolution(self, db_session: AsyncSession, org, apis):
+        """Test QR code numbers send through the Evolution API instance"""
+        campaign, contact, number = await _save(db_session, _campaign(org), _contact(org), _number(org, "qrcode"))
+
+        success, message_id = await CampaignRetryManager(campaign, db_session).send_message_with_retry(contact, number)
+
+        assert (success, message_id) == (True, "3EB0C767D26A")
+        assert apis.evolution == [("loja", "5511999999999", "Olá Maria Silva, a Black Friday começou!")]
+        assert apis.meta == []
+        assert len(await _stored(db_session, Message)) == 1
+
+    @pytest.mark.asyncio
+    async def test_storage_failure_does_not_resend(self, db_session: AsyncSession, org, apis, monkeypatch):
+        """Test a message WhatsApp accepted is not sent again when storing it fails"""
+        campaign, contact, number = await _save(db_session, _campaign(org), _contact(org), _number(org))
+
+        async def create(self, data):
+            raise RuntimeError("database unavailable")
+
+        monkeypatch.setattr(campaign_retry.MessageRepository, "create", create)
+
+        success, _ = await CampaignRetryManager(campaign, db_session).send_message_with_retry(contact, number)
+
+        assert success is True
+        assert len(apis.meta) == 1
+        assert await _stored(db_session, Message) == []
+        await db_session.refresh(campaign)
+        assert len(campaign.message_statuses[str(contact.id)]["attempts"]) == 1
+        # The outcome is still recorded for the contact
+        [delivery] = await _stored(db_session, CampaignMessage)
+        assert (delivery.status, delivery.whatsapp_message_id, delivery.message_id) == ("sent", "wamid.1", None)
+
+
+class TestCampaignMessages:
+    """Tests for the campaign_messages row of each contact"""
+
+    @pytest.mark.asyncio
+    async def test_sent_message_is_linked(self, db_session: AsyncSession, org, apis):
+        """Test a sent message gets one row linked to the stored message"""
+        campaign, contact, number = await _save(db_session, _campaign(org), _contact(org), _number(org))
+
+        await CampaignRetryManager(campaign, db_session).send_message_with_retry(contact, number)
+
+        [message] = await _stored(db_session, Message)
+        [delivery] = await _stored(db_session, CampaignMessage)
+        assert delivery.campaign_id == campaign.id and delivery.contact_id == contact.id
+        assert delivery.organization_id == org.id
+        assert delivery.message_id == message.id
+        assert delivery.whatsapp_message_id == "wamid.1"
+        assert (delivery.status, delivery.attempts) == ("sent", 1)
+        assert delivery.sent_at is not None
+
+    @pytest.mark.asyncio
+    async def test_dropped_contact_is_recorded_as_failed(self, db_session: AsyncSession, org, apis):
+        """Test a permanent Meta error leaves a failed row with the error and no message"""
+        apis.meta_errors.append("131026")
+        campaign, contact, number = await _save(db_session, _campaign(org), _contact(org), _number(org))
+
+        success, _ = await CampaignRetryManager(campaign, db_session).send_message_with_retry(contact, number)
+
+        assert success is False
+        assert await _stored(db_session, Message) == []
+        [delivery] = await _stored(db_session, CampaignMessage)
+        assert (delivery.status, delivery.attempts, delivery.error_code) == ("failed", 1, "131026")
+        assert delivery.failed_at is not None
+
+    @pytest.mark.asyncio
+    async def test_restarted_dispatch_updates_its_row(self, db_session: AsyncSession, org, apis):
+        """Test a send resumed after a restart updates the row of the earlier attempts"""
+        campaign, contact, number = _campaign(org), _contact(org), _number(org)
+        campaign.message_statuses = {str(contact.id): {"status": "retrying", "attempts": [{"success": False}]}}
+        earlier = CampaignMessage(
+            id=uuid4(), organization_id=org.id, campaign_id=campaign.id, contact_id=contact.id, status="retrying",
+            attempts=1, error_code="131000", error_message="Something went wrong", sent_at=None,
+        )
+        await _save(db_session, campaign, contact, number, earlier)
+
+        await CampaignRetryManager(campaign, db_session).send_message_with_retry(contact, number)
+
+        assert await _stored(db_session, CampaignMessage) == [earlier]
+        await db_session.refresh(earlier)
+        assert (earlier.status, earlier.attempts, earlier.error_code) == ("sent", 2, None)
+        assert earlier.message_id == (await _stored(db_session, Message))[0].id
+
+
+class FakeRateLimiter:
+    async def get_current_usage(self):
+        return {}
+
+    async def can_send_message(self):
+        return True, None
+
+    async def record_message_sent(self):
+        pass
+
+
+class FakeThrottle:
+    allowed = SimpleNamespace(allowed=True)
+
+    def check_hours(self, contact):
+        return self.allowed
+
+    async def reserve(self):
+        return self.allowed
+
+
+class TestCampaignDispatch:
+    """Tests for dispatching a batch end to end"""
+
+    @pytest.mark.asyncio
+    async def test_batch_sends_and_persists_every_contact(self, db_session: AsyncSession, org, apis, monkeypatch):
+        """Test a batch sends once per contact, stores the messages and updates the campaign"""
+        async def rate_limiter(number_id, connection_type):
+            return FakeRateLimiter()
+
+        @asynccontextmanager
+        async def session():
+            yield db_session
+
+        monkeypatch.setattr(campaign_tasks, "get_whatsapp_rate_limiter", rate_limiter)
+        monkeypatch.setattr(campaign_tasks, "get_tier_shaper", lambda number: None)
+        monkeypatch.setattr(campaign_tasks, "get_campaign_throttle", lambda campaign: FakeThrottle())
+        monkeypatch.setattr(campaign_tasks, "async_session", session)
+
+        campaign, number = _campaign(org), _number(org)
+        contacts = sorted(
+            [_contact(org, "Maria Silva", "+5511999999999"), _contact(org, "João Souza", "+5511988888888")],
+            key=lambda contact: contact.id,
+        )
+        campaign.whatsapp_number_id, campaign.messages_pending = number.id, 2
+        CampaignCheckpointManager(campaign).init_batches([[str(contact.id) for contact in contacts]], batch_size=2)
+        await _save(db_session, campaign, number, *contacts)
+
+        result = await campaign_tasks._process_batch_async(
+            str(campaign.id), [str(contact.id) for contact in contacts], 0
+        )
+
+        assert (result["status"], result["sent"], result["failed"]) == ("completed", 2, 0)
+        assert sorted(apis.meta) == [
+            ("+5511988888888", "Olá João Souza, a Black Friday começou!"),
+            ("+5511999999999", "Olá Maria Silva, a Black Friday começou!"),
+        ]
+        messages = await _stored(db_session, Message)
+        deliveries = {delivery.contact_id: delivery for delivery in await _stored(db_session, CampaignMessage)}
+        assert len(messages) == 2 and set(deliveries) == {contact.id for contact in contacts}
+        assert {delivery.message_id for delivery in deliveries.values()} == {message.id for message in messages}
+        assert all(delivery.status == "sent" for delivery in deliveries.values())
+        await db_session.refresh(campaign)
+        assert (campaign.messages_sent, campaign.messages_pending) == (2, 0)
+        assert CampaignCheckpointManager(campaign).is_complete()
@@ -0,0 +1,103 @@
+"""
+Campaign Personalization Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+from types import SimpleNamespace
+
+from app.core.campaign_throttle import estimate_send_duration
+from app.utils.campaign_personalization import (
+    component_parameters,
+    find_variables,
+    personalize,
+    render_template_text,
+    variable_field,
+)
+
+
+def _contact(**fields):
+    data = {"name": None, "whatsapp_id": "5511999999999", "email": None, "attributes": {}}
+    data.update(fields)
+    return SimpleNamespace(**data)
+
+
+def _campaign(messages_per_hour=None, **throttle):
+    return SimpleNamespace(messages_per_hour=messages_per_hour, throttle=throttle or None)
+
+
+class TestPersonalize:
+    """Tests for contact variables"""
+
+    def test_fills_contact_fields(self):
+        """Test fields, first name and custom fields resolve"""
+        contact = _contact(name="Maria da Silva", attributes={"plano": "gold"})
+
+        text, missing = personalize(
+            "Olá {{contact.first_name}} ({{ contact.phone }}), plano {{contact.custom_fields.plano}}",
+            contact,
+        )
+
+        assert text == "Olá Maria (5511999999999), plano gold"
+        assert missing == []
+
+    def test_missing_variables_render_empty(self):
+        """Test missing and unknown variables are reported once"""
+        text, missing = personalize(
+            {"body": ["{{contact.name}}", "{{contact.email}}", "{{contact.name}}"], "other": 3},
+            _contact(),
+        )
+
+        assert text == {"body": ["", "", ""], "other": 3}
+        assert missing == ["name", "email"]
+        assert personalize("{{contact.password}}", _contact())[1] == ["password"]
+
+    def test_find_variables(self):
+        """Test variables are collected from nested values"""
+        assert find_variables([
+            "Oi {{contact.name}}",
+            {"body": ["{{contact.attributes.plano}}", 10]},
+            None,
+        ]) == {"name", "attributes.plano"}
+        assert variable_field("custom_fields.plano") == "attributes.plano"
+        assert variable_field("phone") == "whatsapp_id"
+        assert variable_field("password") is None
+
+    def test_render_template_text(self):
+        """Test template parameters fill {{n}} placeholders"""
+        components = [{"type": "body", "parameters": [{"type": "text", "text": "Maria"}]}]
+
+        assert render_template_text(
+            "Olá {{1}}, pedido {{2}}", component_parameters(components, "body")
+        ) == "Olá Maria, pedido {{2}}"
+        assert component_parameters(components, "header") == []
+
+
+class TestSendDuration:
+    """Tests for the sending time estimate"""
+
+    start = datetime(2025, 11, 17, 9, 0, tzinfo=timezone.utc)  # monday
+
+    def test_hour_limit(self):
+        """Test the audience is spread over the hour limit"""
+        assert estimate_send_duration(_campaign(messages_per_hour=100), 250, self.start) == timedelta(hours=2.5)
+        assert estimate_send_duration(_campaign(messages_per_hour=100), 0, self.start) == timedelta(0)
+
+    def test_day_limit(self):
+        """Test the day limit moves the rest to the next day"""
+        campaign = _campaign(messages_per_hour=100, messages_per_day=150)
+
+        assert estimate_send_duration(campaign, 200, self.start) == timedelta(hours=15.5)
+
+    def test_business_hours(self):
+        """Test closed hours do not send"""
+        campaign = _campaign(
+            messages_per_hour=100,
+            business_hours={"timezone": "UTC", "schedule": {
+                "monday": {"enabled": True, "start": "09:00", "end": "10:00"},
+                "tuesday": {"enabled": True, "start": "09:00", "end": "10:00"},
+            }},
+        )
+
+        assert estimate_send_duration(campaign, 150, self.start) == timedelta(hours=24.5)
@@ -0,0 +1,101 @@
+"""
+Campaign Recurrence Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+
+import pytest
+from pydantic import ValidationError
+
+from app.schemas.campaign import CampaignRecurrence
+from app.utils.campaign_recurrence import is_finished, next_occurrence
+
+# Tuesday 2025-11-18 12:00 UTC (09:00 in São Paulo)
+ANCHOR = datetime(2025, 11, 18, 12, 0)
+
+
+def _occurrences(config, count, anchor=ANCHOR):
+    moments = []
+    after = anchor
+    for _ in range(count):
+        after = next_occurrence(config, anchor, after)
+        if after is None:
+            break
+        moments.append(after)
+    return moments
+
+
+class TestNextOccurrence:
+    """Tests for next_occurrence()"""
+
+    def test_daily_at_local_time(self):
+        """Test daily occurrences every N days at the configured local time"""
+        config = {"frequency": "daily", "interval": 2, "hour": 8, "minute": 30}
+
+        assert _occurrences(config, 3) == [
+            datetime(2025, 11, 20, 11, 30),
+            datetime(2025, 11, 22, 11, 30),
+            datetime(2025, 11, 24, 11, 30),
+        ]
+
+    def test_weekly_on_days_of_week(self):
+        """Test weekly occurrences on the chosen days, skipping weeks by interval"""
+        config = {"frequency": "weekly", "interval": 2, "days_of_week": ["MON", "FRI"], "hour": 10}
+
+        assert [moment.strftime("%a %d") for moment in _occurrences(config, 4)] == [
+            "Fri 21", "Mon 01", "Fri 05", "Mon 15",
+        ]
+
+    def test_monthly_clamps_to_the_last_day(self):
+        """Test a day of month missing from shorter months falls on their last day"""
+        config = {"frequency": "monthly", "day_of_month": 31, "hour": 9, "timezone": "UTC"}
+
+        assert [moment.date().isoformat() for moment in _occurrences(config, 3)] == [
+            "2025-11-30", "2025-12-31", "2026-01-31",
+        ]
+
+    def test_aware_datetimes_and_timezone(self):
+        """Test aware datetimes from the database give the same naive UTC result"""
+        config = {"frequency": "daily", "hour": 9, "timezone": "Europe/Lisbon"}
+        aware = ANCHOR.replace(tzinfo=timezone.utc)
+
+        assert next_occurrence(config, aware, aware) == datetime(2025, 11, 19, 9, 0)
+
+
+class TestEndConditions:
+    """Tests for the end of a recurrence"""
+
+    def test_end_date(self):
+        """Test no occurrence is returned after the end date"""
+        config = {"frequency": "daily", "hour": 9, "end_date": "2025-11-20"}
+
+        assert len(_occurrences(config, 10)) == 2
+
+    def test_end_after_occurrences(self):
+        """Test the recurrence finishes after N occurrences"""
+        config = {"frequency": "daily", "end_after_occurrences": 3}
+
+        assert not is_finished(config, 2)
+        assert is_finished(config, 3)
+        assert not is_finished({"frequency": "daily"}, 100)
+
+
+class TestRecurrenceSchema:
+    """Tests for CampaignRecurrence validation"""
+
+    def test_rules_need_their_fields(self):
+        """Test weekly needs days, monthly a day of month and the timezone must exist"""
+        with pytest.raises(ValidationError):
+            CampaignRecurrence(frequency="weekly")
+        with pytest.raises(ValidationError):
+            CampaignRecurrence(frequency="weekly", days_of_week=["MON", "FUNDAY"])
+        with pytest.raises(ValidationError):
+            CampaignRecurrence(frequency="monthly")
+        with pytest.raises(ValidationError):
+            CampaignRecurrence(frequency="daily", timezone="Mars/Olympus")
+
+        weekly = CampaignRecurrence(frequency="weekly", days_of_week=["mon", "fri"])
+
+        assert weekly.days_of_week == ["MON", "FRI"]
@@ -0,0 +1,254 @@
+"""
+Campaign Template Validator Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+
+from app.services.campaign_template_validator import CampaignTemplateValidator
+
+
+NUMBER_ID = uuid4()
+
+
+def _template(**overrides):
+    data = dict(
+        id=uuid4(),
+        name="black_friday",
+        language="pt_BR",
+        category="UTILITY",
+        status="APPROVED",
+        rejected_reason=None,
+        meta_template_id="123",
+        is_enabled=True,
+        whatsapp_number_id=NUMBER_ID,
+        header_type=None,
+        header_variables_count=0,
+        body_variables_count=2,
+        carousel_cards=None,
+        buttons=[],
+        limited_time_offer=None,
+    )
+    data.update(overrides)
+    return SimpleNamespace(**data)
+
+
+def _campaign(**overrides):
+    data = dict(
+        id=uuid4(),
+        organization_id=uuid4(),
+        whatsapp_number_id=NUMBER_ID,
+        template_id=uuid4(),
+        message_type="template",
+        message_content={},
+        template_variables={"body": ["Maria", "20%"]},
+        respect_opt_out=True,
+        campaign_type="broadcast",
+    )
+    data.update(overrides)
+    return SimpleNamespace(**data)
+
+
+def _validator(monkeypatch, template, languages=("pt_BR",)) -> CampaignTemplateValidator:
+    validator = CampaignTemplateValidator(db=None)
+
+    async def fake_find(organization_id, template_id, name, language):
+        return template
+
+    async def fake_languages(organization_id, name):
+        return list(languages)
+
+    monkeypatch.setattr(validator, "_find_template", fake_find)
+    monkeypatch.setattr(validator, "_approved_languages", fake_languages)
+    return validator
+
+
+def _codes(issues):
+    return [issue.code for issue in issues]
+
+
+class TestSplitParameters:
+    """Tests for template_variables parsing"""
+
+    def test_structured_layout(self):
+        header, body, media = CampaignTemplateValidator._split_parameters({
+            "header": ["Oi"], "body": ["a", "b"], "header_media_url": "https://x.com/a.png",
+        })
+
+        assert header == ["Oi"]
+        assert body == ["a", "b"]
+        assert media == "https://x.com/a.png"
+
+    def test_flat_layout_is_body_in_order(self):
+        header, body, media = CampaignTemplateValidator._split_parameters({"2": "b", "10": "j", "1": "a"})
+
+        assert header == []
+        assert body == ["a", "b", "j"]
+        assert media is None
+
+
+class TestCampaignTemplateValidator:
+    """Tests for CampaignTemplateValidator.validate()"""
+
+    @pytest.mark.asyncio
+    async def test_valid_template(self, monkeypatch):
+        """Test an approved template with matching parameters passes"""
+        report = await _validator(monkeypatch, _template()).validate(
+            _campaign(campaign_type="drip")
+        )
+
+        assert report.is_valid is True
+        assert report.templates[0].errors == []
+
+    @pytest.mark.asyncio
+    async def test_parameter_count_and_format(self, monkeypatch):
+        """Test wrong count and invalid parameter text are reported"""
+        report = await _validator(monkeypatch, _template()).validate(
+            _campaign(template_variables={"body": ["linha\nquebrada"]})
+        )
+
+        errors = _codes(report.templates[0].errors)
+        assert report.is_valid is False
+        assert "parameter_count_mismatch" in errors
+        assert "parameter_format_invalid" in errors
+
+    @pytest.mark.asyncio
+    async def test_media_header_requires_https_url(self, monkeypatch):
+        """Test media headers need an HTTPS media URL"""
+        template = _template(header_type="IMAGE")
+
+        missing = await _validator(monkeypatch, template).validate(_campaign())
+        insecure = await _validator(monkeypatch, template).validate(_campaign(
+            template_variables={"body": ["a", "b"], "header_media_url": "http://x.com/a.png"}
+        ))
+
+        assert "media_header_missing" in _codes(missing.templates[0].errors)
+        assert "media_header_invalid" in _codes(insecure.templates[0].errors)
+
+    @pytest.mark.asyncio
+    async def test_carousel_cards_are_validated_per_card(self, monkeypatch):
+        """Test card count, card header media, body parameters and button values"""
+        layout = {
+            "header_format": "IMAGE",
+            "body_variables_count": 1,
+            "buttons": [{"type": "QUICK_REPLY", "has_variable": False}, {"type": "URL", "has_variable": True}],
+        }
+        template = _template(carousel_cards=[layout, layout])
+        valid_card = {"header_url": "https://x.com/1.png", "body": ["R$ 99"], "buttons": [{"payload": "buy"}, {"text": "1"}]}
+
+        valid = await _validator(monkeypatch, template).validate(_campaign(
+            template_variables={"body": ["a", "b"], "cards": [valid_card, valid_card]}
+        ))
+        missing_card = await _validator(monkeypatch, template).validate(_campaign(
+            template_variables={"body": ["a", "b"], "cards": [valid_card]}
+        ))
+        invalid = await _validator(monkeypatch, template).validate(_campaign(
+            template_variables={"body": ["a", "b"], "cards": [
+                valid_card,
+                {"header_url": "http://x.com/2.png", "body": [], "buttons": [{"payload": "buy"}]},
+            ]}
+        ))
+
+        errors = invalid.templates[0].errors
+        assert valid.is_valid is True
+        assert _codes(missing_card.templates[0].errors) == ["carousel_card_count_mismatch"]
+        assert _codes(errors) == ["media_header_invalid", "parameter_count_mismatch", "carousel_button_missing"]
+        assert [issue.field for issue in errors] == [
+            "template_variables.cards[1].header_url",
+            "template_variables.cards[1].body",
+            "template_variables.cards[1].buttons[1].text",
+        ]
+
+    @pytest.mark.asyncio
+    async def test_coupon_code_and_offer_expiration(self, monkeypatch):
+        """Test promotional templates need a valid coupon code and a future offer expiration"""
+        template = _template(
+            category="MARKETING",
+            buttons=[{"type": "COPY_CODE", "example": "SAVE10"}],
+            limited_time_offer={"text": "Só hoje!", "has_expiration": True},
+        )
+        future = (datetime.now(timezone.utc) + timedelta(days=2)).isoformat()
+
+        valid = await _validator(monkeypatch, template).validate(_campaign(
+            template_variables={"body": ["a", "b"], "coupon_code": "BLACK20", "offer_expires_at": future}
+        ))
+        missing = await _validator(monkeypatch, template).validate(_campaign(template_variables={"body": ["a", "b"]}))
+        invalid = await _validator(monkeypatch, template).validate(_campaign(
+            template_variables={"body": ["a", "b"], "coupon_code": "BLACK FRIDAY 2025", "offer_expires_at": "2020-01-01"}
+        ))
+
+        assert valid.is_valid is True
+        assert _codes(missing.templates[0].errors) == ["coupon_code_invalid", "offer_expiration_missing"]
+        assert _codes(invalid.templates[0].errors) == ["coupon_code_invalid", "offer_expired"]
+
+    @pytest.mark.asyncio
+    async def test_language_unavailable(self, monkeypatch):
+        """Test a requested language without an approved translation is rejected"""
+        report = await _validator(monkeypatch, _template(), languages=["pt_BR"]).validate(
+            _campaign(message_content={"language": "en_US"})
+        )
+
+        issue = report.templates[0].errors[0]
+        assert issue.code == "language_unavailable"
+        assert "pt_BR" in issue.message
+
+    @pytest.mark.asyncio
+    async def test_category_vs_consent(self, monkeypatch):
+        """Test marketing needs opt-in, authentication is blocked and utility broadcasts warn"""
+        marketing = await _validator(monkeypatch, _template(category="MARKETING")).validate(
+            _campaign(respect_opt_out=False)
+        )
+        authentication = await _validator(monkeypatch, _template(category="AUTHENTICATION")).validate(
+            _campaign()
+        )
+        utility = await _validator(monkeypatch, _template(category="UTILITY")).validate(_campaign())
+
+        assert _codes(marketing.templates[0].errors) == ["marketing_requires_opt_in"]
+        assert _codes(authentication.templates[0].errors) == ["category_not_allowed"]
+        assert utility.is_valid is True
+        assert _codes(utility.templates[0].warnings) == ["utility_broadcast"]
+
+    @pytest.mark.asyncio
+    async def test_unapproved_template_from_other_number(self, monkeypatch):
+        """Test status and number ownership errors"""
+        template = _template(status="REJECTED", rejected_reason="INVALID_FORMAT", whatsapp_number_id=uuid4())
+
+        report = await _validator(monkeypatch, template, languages=[]).validate(_campaign())
+
+        errors = _codes(report.templates[0].errors)
+        assert "template_not_approved" in errors
+        assert "template_number_mismatch" in errors
+
+    @pytest.mark.asyncio
+    async def test_template_not_found(self, monkeypatch):
+        """Test a missing template yields an actionable error"""
+        report = await _validator(monkeypatch, None).validate(_campaign())
+
+        assert report.is_valid is False
+        assert _codes(report.templates[0].errors) == ["template_not_found"]
+
+    @pytest.mark.asyncio
+    async def test_text_campaign_has_no_templates(self, monkeypatch):
+        """Test campaigns without templates are valid"""
+        report = await _validator(monkeypatch, None).validate(
+            _campaign(template_id=None, message_type="text", message_content={"text": "Olá 👋"})
+        )
+
+        assert report.is_valid is True
+        assert report.templates == []
+
+    @pytest.mark.asyncio
+    async def test_text_campaign_over_limit(self, monkeypatch):
+        """Test text content over 4096 characters blocks the campaign"""
+        report = await _validator(monkeypatch, None).validate(
+            _campaign(template_id=None, message_type="text", message_content={"text": "🎉" * 4097})
+        )
+
+        assert report.is_valid is False
+        assert _codes(report.message_errors) == ["message_limit_exceeded"]
+        assert report.message_errors[0].field == "message_content.text"
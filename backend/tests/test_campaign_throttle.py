@@ -0,0 +1,97 @@
+"""
+Campaign Throttle Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+
+from app.core.campaign_throttle import (
+    REASON_BUSINESS_HOURS,
+    CampaignThrottle,
+    InMemoryThrottleStore,
+    throttle_limits,
+)
+
+# 2025-11-18 12:00:30 UTC, a Tuesday
+NOW = datetime(2025, 11, 18, 12, 0, 30, tzinfo=timezone.utc).timestamp()
+
+BUSINESS_HOURS = {
+    "timezone": "America/Sao_Paulo",
+    "schedule": {day: {"enabled": True, "start": "09:00", "end": "18:00"} for day in ("monday", "tuesday")},
+}
+
+
+def _campaign(messages_per_hour=None, **throttle):
+    return SimpleNamespace(id=uuid4(), messages_per_hour=messages_per_hour, throttle=throttle)
+
+
+class TestWindows:
+    """Tests for the per-window limits"""
+
+    def test_limits_of_a_campaign(self):
+        """Test the hour limit comes from the column and unset windows are left out"""
+        assert throttle_limits(_campaign(100, messages_per_minute=10)) == {"minute": 10, "hour": 100}
+
+    @pytest.mark.asyncio
+    async def test_full_window_holds_until_the_next_one(self):
+        """Test sends over the minute limit wait for the next minute"""
+        throttle = CampaignThrottle(_campaign(100, messages_per_minute=2), InMemoryThrottleStore())
+
+        assert (await throttle.reserve(NOW)).allowed
+        assert (await throttle.reserve(NOW)).allowed
+        held = await throttle.reserve(NOW)
+
+        assert not held.allowed and held.reason == "minute"
+        assert held.send_at == datetime(2025, 11, 18, 12, 1, tzinfo=timezone.utc)
+        assert (await throttle.reserve(NOW + 60)).allowed
+
+    @pytest.mark.asyncio
+    async def test_workers_share_the_budget(self):
+        """Test throttles of the same campaign on the same store share counters"""
+        campaign = _campaign(messages_per_day=3)
+        store = InMemoryThrottleStore()
+        first, second = CampaignThrottle(campaign, store), CampaignThrottle(campaign, store)
+
+        results = [(await worker.reserve(NOW)).allowed for worker in (first, second, first, second)]
+
+        assert results == [True, True, True, False]
+
+    @pytest.mark.asyncio
+    async def test_release_gives_the_send_back(self):
+        """Test a reserved send not made frees its slot in every window"""
+        throttle = CampaignThrottle(_campaign(1, messages_per_minute=1), InMemoryThrottleStore())
+
+        await throttle.reserve(NOW)
+        await throttle.release(NOW)
+
+        assert (await throttle.reserve(NOW)).allowed
+
+
+class TestBusinessHours:
+    """Tests for CampaignThrottle.check_hours()"""
+
+    def test_closed_holds_until_opening(self):
+        """Test contacts are only messaged while open"""
+        throttle = CampaignThrottle(_campaign(business_hours=BUSINESS_HOURS), InMemoryThrottleStore())
+        contact = SimpleNamespace(attributes={})
+
+        assert throttle.check_hours(contact, datetime(2025, 11, 18, 15, 0, tzinfo=timezone.utc)).allowed
+        held = throttle.check_hours(contact, datetime(2025, 11, 18, 22, 0, tzinfo=timezone.utc))
+
+        assert not held.allowed and held.reason == REASON_BUSINESS_HOURS
+        assert held.send_at == datetime(2025, 11, 24, 12, 0, tzinfo=timezone.utc)
+
+    def test_contact_timezone(self):
+        """Test business hours are read in the contact's timezone when enabled"""
+        campaign = _campaign(business_hours=BUSINESS_HOURS, use_contact_timezone=True)
+        throttle = CampaignThrottle(campaign, InMemoryThrottleStore())
+        now = datetime(2025, 11, 18, 12, 30, tzinfo=timezone.utc)  # 09:30 in São Paulo
+
+        assert throttle.check_hours(SimpleNamespace(attributes={"timezone": "America/Sao_Paulo"}), now).allowed
+        assert not throttle.check_hours(SimpleNamespace(attributes={"timezone": "America/Los_Angeles"}), now).allowed
+        assert throttle.check_hours(SimpleNamespace(attributes={"timezone": "Not/AZone"}), now).allowed
@@ -12,15 +12,18 @@ from uuid import uuid4
 from fastapi import HTTPException
 from sqlalchemy.ext.asyncio import AsyncSession
 
+from app.models.conversation import Conversation
 from app.services.chatbot_service import ChatbotService
 from app.schemas.chatbot import (
     ChatbotCreate,
     ChatbotUpdate,
     FlowCreate,
+    FlowImport,
     FlowUpdate,
     NodeCreate,
     NodeUpdate,
 )
+from app.utils.flow_migration import FLOW_RESTART_KEY
 from tests.conftest import OrganizationFactory, UserFactory
 
 
@@ -357,3 +360,255 @@ class TestChatbotServiceMultiTenancy:
         assert len(org2_bots) == 1
         assert org1_bots[0].id == bot1.id
         assert org2_bots[0].id == bot2.id
+
+
+class TestChatbotServiceFlowVersions:
+    """Tests for publishing and rolling back flow versions"""
+
+    @pytest_asyncio.fixture
+    async def chatbot_service(self, db_session: AsyncSession) -> ChatbotService:
+        return ChatbotService(db_session)
+
+    async def _draft_flow(self, chatbot_service: ChatbotService, db_session: AsyncSession):
+        org = await OrganizationFactory.create_in_db(db_session)
+        bot = await chatbot_service.create_chatbot(ChatbotCreate(name="Versioned Bot"), org.id)
+        flow = await chatbot_service.create_flow(FlowCreate(name="Main", chatbot_id=bot.id), org.id)
+        flow = await chatbot_service.update_flow(flow.id, org.id, FlowUpdate(canvas_data={
+            "nodes": [
+                {"id": "start-1", "data": {"nodeType": "start"}},
+                {"id": "msg-1", "data": {"nodeType": "message", "text": "Olá"}},
+                {"id": "end-1", "data": {"nodeType": "end"}},
+            ],
+            "edges": [
+                {"id": "e1", "source": "start-1", "target": "msg-1"},
+                {"id": "e2", "source": "msg-1", "target": "end-1"},
+            ],
+        }))
+        message = await chatbot_service.node_repo.get_by_node_id("msg-1", flow.id, org.id)
+        return org, flow, message
+
+    @pytest.mark.asyncio
+    async def test_publish_snapshots_draft(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test publishing copies the draft nodes and bumps the draft version"""
+        org, flow, _ = await self._draft_flow(chatbot_service, db_session)
+
+        version = await chatbot_service.publish_flow(flow.id, org.id, notes="Primeira versão")
+
+        assert version.version == 1
+        assert version.status == "published"
+        assert flow.published_version_id == version.id
+        assert flow.version == 2
+
+        published_nodes = await chatbot_service.node_repo.get_by_flow(flow.id, org.id, version.id)
+        draft_nodes = await chatbot_service.list_nodes(flow.id, org.id)
+        assert {node.node_id for node in published_nodes} == {"start-1", "msg-1", "end-1"}
+        assert len(draft_nodes) == 3
+        assert all(node.flow_version_id is None for node in draft_nodes)
+
+    @pytest.mark.asyncio
+    async def test_published_nodes_are_immutable(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test draft edits do not leak into the published version"""
+        org, flow, message = await self._draft_flow(chatbot_service, db_session)
+        version = await chatbot_service.publish_flow(flow.id, org.id)
+
+        await chatbot_service.update_node(message.id, org.id, NodeUpdate(data={"nodeType": "message", "text": "Oi"}))
+
+        published = await chatbot_service.node_repo.get_by_node_id("msg-1", flow.id, org.id, version.id)
+        assert published.data == {"nodeType": "message", "text": "Olá"}
+
+        with pytest.raises(HTTPException):
+            await chatbot_service.update_node(published.id, org.id, NodeUpdate(label="x"))
+        with pytest.raises(HTTPException):
+            await chatbot_service.delete_node(published.id, org.id)
+
+    @pytest.mark.asyncio
+    async def test_publish_requires_start_node(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test a draft without a start node cannot be published"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        bot = await chatbot_service.create_chatbot(ChatbotCreate(name="Empty Bot"), org.id)
+        flow = await chatbot_service.create_flow(FlowCreate(name="Empty", chatbot_id=bot.id), org.id)
+
+        with pytest.raises(HTTPException):
+            await chatbot_service.publish_flow(flow.id, org.id)
+
+    @pytest.mark.asyncio
+    async def test_rollback_switches_live_version(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test rollback archives the live version and republishes the older one"""
+        org, flow, _ = await self._draft_flow(chatbot_service, db_session)
+        first = await chatbot_service.publish_flow(flow.id, org.id)
+        second = await chatbot_service.publish_flow(flow.id, org.id)
+        assert first.status == "archived"
+
+        restored = await chatbot_service.rollback_flow(flow.id, org.id, 1)
+
+        assert restored.id == first.id
+        assert restored.status == "published"
+        assert second.status == "archived"
+        assert flow.published_version_id == first.id
+        assert flow.version == 3
+
+        versions = await chatbot_service.list_flow_versions(flow.id, org.id)
+        assert [version.version for version in versions] == [2, 1]
+
+        with pytest.raises(HTTPException):
+            await chatbot_service.rollback_flow(flow.id, org.id, 1)
+        with pytest.raises(HTTPException):
+            await chatbot_service.rollback_flow(flow.id, org.id, 9)
+
+    async def _session_on(self, db_session: AsyncSession, org, flow, node) -> Conversation:
+        conversation = Conversation(
+            id=uuid4(),
+            organization_id=org.id,
+            contact_id=uuid4(),
+            whatsapp_number_id=uuid4(),
+            status="open",
+            is_bot_active=True,
+            active_flow_id=flow.id,
+            current_node_id=node.id,
+        )
+        db_session.add(conversation)
+        await db_session.commit()
+        return conversation
+
+    @pytest.mark.asyncio
+    async def test_publish_migrates_running_sessions(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test map_node_ids moves sessions to the same node of the new version"""
+        org, flow, _ = await self._draft_flow(chatbot_service, db_session)
+        first = await chatbot_service.publish_flow(flow.id, org.id)
+        old_node = await chatbot_service.node_repo.get_by_node_id("msg-1", flow.id, org.id, first.id)
+        conversation = await self._session_on(db_session, org, flow, old_node)
+
+        second = await chatbot_service.publish_flow(flow.id, org.id, migration_strategy="map_node_ids")
+
+        new_node = await chatbot_service.node_repo.get_by_node_id("msg-1", flow.id, org.id, second.id)
+        assert conversation.current_node_id == new_node.id
+        assert second.session_migration == {"strategy": "map_node_ids", "migrated": 1, "restarted": 0, "kept": 0}
+
+    @pytest.mark.asyncio
+    async def test_publish_restarts_sessions_at_checkpoint(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test restart_at_checkpoint flags sessions to run the checkpoint again"""
+        org, flow, _ = await self._draft_flow(chatbot_service, db_session)
+        first = await chatbot_service.publish_flow(flow.id, org.id)
+        end_node = await chatbot_service.node_repo.get_by_node_id("end-1", flow.id, org.id, first.id)
+        conversation = await self._session_on(db_session, org, flow, end_node)
+
+        with pytest.raises(HTTPException):
+            await chatbot_service.publish_flow(
+                flow.id, org.id, migration_strategy="restart_at_checkpoint", checkpoint_node_id="missing"
+            )
+
+        second = await chatbot_service.publish_flow(
+            flow.id, org.id, migration_strategy="restart_at_checkpoint", checkpoint_node_id="msg-1"
+        )
+
+        checkpoint = await chatbot_service.node_repo.get_by_node_id("msg-1", flow.id, org.id, second.id)
+        assert conversation.current_node_id == checkpoint.id
+        assert conversation.context_variables[FLOW_RESTART_KEY] is True
+        assert second.session_migration["restarted"] == 1
+
+    @pytest.mark.asyncio
+    async def test_publish_keeps_sessions_by_default(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test sessions keep their version without a migration strategy"""
+        org, flow, _ = await self._draft_flow(chatbot_service, db_session)
+        first = await chatbot_service.publish_flow(flow.id, org.id)
+        old_node = await chatbot_service.node_repo.get_by_node_id("msg-1", flow.id, org.id, first.id)
+        conversation = await self._session_on(db_session, org, flow, old_node)
+
+        second = await chatbot_service.publish_flow(flow.id, org.id)
+
+        assert conversation.current_node_id == old_node.id
+        assert second.session_migration["kept"] == 1
+
+
+class TestChatbotServiceExportImport:
+    """Tests for moving flows between organizations"""
+
+    @pytest_asyncio.fixture
+    async def chatbot_service(self, db_session: AsyncSession) -> ChatbotService:
+        return ChatbotService(db_session)
+
+    async def _flow(self, chatbot_service: ChatbotService, org_id, name: str, canvas_data=None):
+        bot = await chatbot_service.create_chatbot(ChatbotCreate(name=f"{name} Bot"), org_id)
+        return await chatbot_service.create_flow(
+            FlowCreate(name=name, chatbot_id=bot.id, canvas_data=canvas_data or {"nodes": [], "edges": []}),
+            org_id,
+        )
+
+    @pytest.mark.asyncio
+    async def test_import_remaps_flow_references(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test referenced flows are remapped by name and self-references point to the new flow"""
+        staging = await OrganizationFactory.create_in_db(db_session)
+        production = await OrganizationFactory.create_in_db(db_session)
+
+        survey = await self._flow(chatbot_service, staging.id, "Pesquisa")
+        prod_survey = await self._flow(chatbot_service, production.id, "Pesquisa")
+        main = await self._flow(chatbot_service, staging.id, "Principal")
+        main = await chatbot_service.update_flow(main.id, staging.id, FlowUpdate(canvas_data={
+            "nodes": [
+                {"id": "start-1", "data": {"nodeType": "start"}},
+                {"id": "sub-1", "data": {"nodeType": "subflow", "targetFlowId": str(survey.id)}},
+                {"id": "jump-1", "data": {"nodeType": "jump", "jumpType": "flow", "targetFlowId": str(main.id)}},
+            ],
+            "edges": [
+                {"id": "e1", "source": "start-1", "target": "sub-1"},
+                {"id": "e2", "source": "sub-1", "target": "jump-1"},
+            ],
+        }))
+
+        document = await chatbot_service.export_flow(main.id, staging.id)
+        assert document["format_version"] == "2.0"
+        assert {"type": "flow", "id": str(survey.id), "key": "Pesquisa"} in document["references"]
+
+        bot = await chatbot_service.create_chatbot(ChatbotCreate(name="Prod Bot"), production.id)
+        imported = await chatbot_service.import_flow(
+            FlowImport.model_validate(document), bot.id, production.id
+        )
+
+        nodes = {node["id"]: node["data"] for node in imported.canvas_data["nodes"]}
+        assert imported.organization_id == production.id
+        assert nodes["sub-1"]["targetFlowId"] == str(prod_survey.id)
+        assert nodes["jump-1"]["targetFlowId"] == str(imported.id)
+        assert len(await chatbot_service.list_nodes(imported.id, production.id)) == 3
+
+    @pytest.mark.asyncio
+    async def test_unresolved_references_fail_unless_dropped(
+        self, chatbot_service: ChatbotService, db_session: AsyncSession
+    ):
+        """Test references missing in the target organization need id_map or drop_unresolved"""
+        staging = await OrganizationFactory.create_in_db(db_session)
+        production = await OrganizationFactory.create_in_db(db_session)
+        queue_id = str(uuid4())
+        flow = await self._flow(chatbot_service, staging.id, "Suporte", {
+            "nodes": [{"id": "handoff-1", "data": {"nodeType": "handoff", "queueId": queue_id}}],
+            "edges": [],
+        })
+
+        document = FlowImport.model_validate(await chatbot_service.export_flow(flow.id, staging.id))
+        bot = await chatbot_service.create_chatbot(ChatbotCreate(name="Prod Bot"), production.id)
+
+        with pytest.raises(HTTPException):
+            await chatbot_service.import_flow(document, bot.id, production.id)
+
+        dropped = await chatbot_service.import_flow(document, bot.id, production.id, drop_unresolved=True)
+        assert dropped.canvas_data["nodes"][0]["data"]["queueId"] is None
+
+        mapped_id = str(uuid4())
+        document.id_map = {queue_id: mapped_id}
+        mapped = await chatbot_service.import_flow(document, bot.id, production.id)
+        assert mapped.canvas_data["nodes"][0]["data"]["queueId"] == mapped_id
@@ -0,0 +1,30 @@
+"""
+Click-to-Chat Utils Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.click_to_chat import build_click_to_chat_link, normalize_wa_phone
+
+
+class TestClickToChat:
+    """Tests for wa.me link generation"""
+
+    def test_normalizes_formatted_number(self):
+        """Test punctuation and + are stripped"""
+        assert normalize_wa_phone("+55 (11) 99999-9999") == "5511999999999"
+
+    def test_link_without_text(self):
+        assert build_click_to_chat_link("+5511999999999") == "https://wa.me/5511999999999"
+
+    def test_prefilled_text_is_encoded(self):
+        """Test spaces, accents and reserved characters are percent-encoded"""
+        link = build_click_to_chat_link("5511999999999", "Quero a promoção & frete?")
+
+        assert link == "https://wa.me/5511999999999?text=Quero%20a%20promo%C3%A7%C3%A3o%20%26%20frete%3F"
+
+    def test_invalid_number(self):
+        with pytest.raises(ValueError):
+            build_click_to_chat_link("123")
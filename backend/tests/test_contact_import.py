@@ -0,0 +1,232 @@
+"""
+Contact Import Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import asyncio
+import os
+from contextlib import asynccontextmanager
+from types import SimpleNamespace
+
+import pytest
+import pytest_asyncio
+from pytake_client.media import InMemoryMediaStorage
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+import app.core.database as database
+import app.services.contact_import_service as contact_import_service
+from app.core.config import settings
+from app.core.exceptions import BadRequestException
+from app.models.contact import Contact
+from app.services.contact_import_service import ContactImportService, run_contact_import
+from app.tasks.contact_import_tasks import import_contacts
+from app.utils.contact_import import (
+    MAX_REPORTED_ERRORS,
+    ImportProgress,
+    RowReader,
+    column_mapping,
+    file_format,
+    normalize_phone,
+    parse_row,
+)
+from tests.conftest import OrganizationFactory
+
+
+class TestPhones:
+    """Tests for phone normalization"""
+
+    def test_national_numbers_get_the_country_code(self):
+        """Test national numbers are prefixed and trunk zeros dropped"""
+        assert normalize_phone("(11) 99999-9999", "55") == "5511999999999"
+        assert normalize_phone("011 99999-9999", "55") == "5511999999999"
+        assert normalize_phone(11999999999.0, "55") == "5511999999999"
+
+    def test_international_numbers_are_kept(self):
+        """Test numbers with + or 00 or a country code are not prefixed"""
+        assert normalize_phone("+1 202 555 0123", "55") == "12025550123"
+        assert normalize_phone("00 351 912 345 678", "55") == "351912345678"
+        assert normalize_phone("55 11 99999-9999", "55") == "5511999999999"
+
+    def test_invalid_numbers(self):
+        """Test short or long numbers are refused"""
+        with pytest.raises(ValueError):
+            normalize_phone("12345", "55")
+        with pytest.raises(ValueError):
+            normalize_phone("1234567890123456", "55")
+
+
+class TestRows:
+    """Tests for header mapping and row parsing"""
+
+    def test_column_mapping(self):
+        """Test aliases, accents and unknown columns"""
+        mapping = column_mapping(["Telefone", "Nome", "E-mail", "Observações", "Cidade Natal"])
+
+        assert mapping == {0: "phone", 1: "name", 2: "email", 3: "notes", 4: "attributes.cidade_natal"}
+
+    def test_header_requires_a_phone(self):
+        """Test files without a phone column are refused"""
+        with pytest.raises(ValueError):
+            column_mapping(["name", "email"])
+
+    def test_parse_row(self):
+        """Test fields, attributes, blank rows and invalid emails"""
+        mapping = column_mapping(["phone", "name", "email", "plano"])
+
+        row = parse_row(2, ["11 99999-9999", "Maria", "maria@example.com", "gold"], mapping, "55")
+        assert row.phone == "5511999999999"
+        assert row.fields == {"name": "Maria", "email": "maria@example.com"}
+        assert row.attributes == {"plano": "gold"}
+
+        assert parse_row(3, ["", " ", None], mapping, "55") is None
+        with pytest.raises(ValueError):
+            parse_row(4, ["11 99999-9999", "João", "not-an-email"], mapping, "55")
+        with pytest.raises(ValueError):
+            parse_row(5, ["", "Sem telefone"], mapping, "55")
+
+
+class TestReader:
+    """Tests for streaming files from disk"""
+
+    def test_csv_with_bom_and_semicolons(self, tmp_path):
+        """Test the delimiter is detected and the progress reaches the end"""
+        path = tmp_path / "contacts.csv"
+        path.write_bytes("\ufefftelefone;nome\n11999999999;Maria\n11988888888;\"Silva; João\"\n".encode("utf-8"))
+        reader = RowReader(str(path), file_format(path.name))
+
+        rows = list(reader)
+
+        assert rows == [
+            (1, ["telefone", "nome"]),
+            (2, ["11999999999", "Maria"]),
+            (3, ["11988888888", "Silva; João"]),
+        ]
+        assert reader.progress == 1.0
+
+    def test_file_format(self):
+        """Test formats come from the extension"""
+        assert file_format("Contatos.XLSX") == "xlsx"
+        assert file_format("contatos.csv") == "csv"
+        assert file_format("contatos.pdf") is None
+
+
+class TestProgress:
+    """Tests for the import progress"""
+
+    def test_errors_are_capped(self):
+        """Test every invalid row is counted but only the first ones are kept"""
+        progress = ImportProgress(import_id="x", filename="a.csv", file_format="csv")
+        for line in range(MAX_REPORTED_ERRORS + 10):
+            progress.add_error(line, "invalid phone number")
+        progress.finish("completed")
+
+        assert progress.invalid == MAX_REPORTED_ERRORS + 10
+        assert len(progress.errors) == MAX_REPORTED_ERRORS
+        assert progress.to_dict()["progress_percentage"] == 100.0
+
+
+class FakeUpload:
+    def __init__(self, body: bytes, filename="contatos.csv"):
+        self.body = body
+        self.filename = filename
+        self.content_type = "text/csv"
+
+    async def read(self, size: int) -> bytes:
+        chunk, self.body = self.body[:size], self.body[size:]
+        return chunk
+
+
+@pytest_asyncio.fixture
+async def worker(db_session: AsyncSession, monkeypatch, tmp_path):
+    """Shared storage, queued tasks and the organization importing on the test database"""
+    storage = InMemoryMediaStorage()
+    queued = []
+
+    async def fake_save(self, organization_id, progress):
+        pass
+
+    @asynccontextmanager
+    async def session():
+        yield db_session
+
+    monkeypatch.setattr(contact_import_service, "get_media_storage", lambda: storage)
+    monkeypatch.setattr(ContactImportService, "_save", fake_save)
+    monkeypatch.setattr(import_contacts, "apply_async", lambda args, kwargs: queued.append((args, kwargs)))
+    monkeypatch.setattr(database, "async_session", session)
+    monkeypatch.setattr(settings, "CONTACT_IMPORT_DIR", str(tmp_path))
+    org = await OrganizationFactory.create_in_db(db_session)
+    return SimpleNamespace(db=db_session, org=org, storage=storage, queued=queued)
+
+
+async def _imported_phones(worker):
+    result = await worker.db.execute(select(Contact.whatsapp_id).where(Contact.organization_id == worker.org.id))
+    return result.scalars().all()
+
+
+class TestImportJob:
+    """Tests for queuing an upload and importing it on a worker"""
+
+    @pytest.mark.asyncio
+    async def test_upload_is_stored_and_imported_by_the_worker(self, worker):
+        """Test the upload goes to the shared storage and the queued job imports it"""
+        job = await ContactImportService(worker.db).start(
+            worker.org.id, FakeUpload(b"telefone;nome\n11999999999;Maria\n"), default_country_code="55"
+        )
+
+        assert list(worker.storage.objects) == [job["storage_key"]]
+        [(args, kwargs)] = worker.queued
+        assert args == [str(worker.org.id), job]
+        assert kwargs == {"update_existing": False, "tag_ids": [], "default_country_code": "55"}
+
+        progress = await run_contact_import(worker.org.id, job, default_country_code="55")
+
+        assert progress.status == "completed"
+        assert progress.created == 1
+        assert await _imported_phones(worker) == ["5511999999999"]
+        assert worker.storage.objects == {}
+        assert os.listdir(settings.CONTACT_IMPORT_DIR) == []
+
+    @pytest.mark.asyncio
+    async def test_refused_upload_is_not_kept(self, worker, monkeypatch):
+        """Test too large uploads are removed from the storage and not queued"""
+        monkeypatch.setattr(settings, "CONTACT_IMPORT_MAX_FILE_MB", 0)
+
+        with pytest.raises(BadRequestException):
+            await ContactImportService(worker.db).start(worker.org.id, FakeUpload(b"telefone\n11999999999\n"))
+
+        assert worker.storage.objects == {}
+        assert worker.queued == []
+
+    @pytest.mark.asyncio
+    async def test_task_reads_the_stored_upload(self, worker, monkeypatch, tmp_path):
+        """Test the Celery task imports the upload from the shared storage on another worker"""
+        await ContactImportService(worker.db).start(
+            worker.org.id, FakeUpload(b"telefone;nome\n11999999999;Maria\n"), default_country_code="55"
+        )
+        [(args, kwargs)] = worker.queued
+
+        # A worker with its own import directory: nothing of the request is on its disk
+        worker_dir = tmp_path / "worker"
+        worker_dir.mkdir()
+        monkeypatch.setattr(settings, "CONTACT_IMPORT_DIR", str(worker_dir))
+        # The task runs its own event loop, as on a Celery worker
+        result = await asyncio.to_thread(import_contacts, *args, **kwargs)
+
+        assert result["status"] == "completed"
+        assert await _imported_phones(worker) == ["5511999999999"]
+        assert worker.storage.objects == {}
+        assert os.listdir(worker_dir) == []
+
+    @pytest.mark.asyncio
+    async def test_task_fails_without_the_stored_upload(self, worker):
+        """Test the import fails when the upload is gone from the shared storage"""
+        await ContactImportService(worker.db).start(worker.org.id, FakeUpload(b"telefone\n11999999999\n"))
+        [(args, kwargs)] = worker.queued
+        worker.storage.objects.clear()
+
+        result = await asyncio.to_thread(import_contacts, *args, **kwargs)
+
+        assert result["status"] == "failed"
+        assert await _imported_phones(worker) == []
@@ -0,0 +1,94 @@
+"""
+Contact Segment Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+from uuid import uuid4
+
+import pytest
+
+from app.utils.contact_segment import (
+    MATCH_ANY,
+    MAX_CONDITIONS,
+    SegmentCondition,
+    SegmentError,
+    SegmentGroup,
+    parse_segment,
+    validate_segment,
+)
+
+
+def _condition(field, operator, value=None):
+    return {"field": field, "operator": operator, "value": value}
+
+
+class TestParse:
+    """Tests for parsing segmentations"""
+
+    def test_nested_groups(self):
+        """Test groups nest and values are normalized for their field"""
+        tag_id = uuid4()
+        segment = parse_segment({
+            "conditions": [
+                _condition("tags", "has_all", [str(tag_id)]),
+                _condition("last_interaction", "before", "2025-11-01"),
+                {"match": "any", "conditions": [
+                    _condition("is_vip", "is_true"),
+                    _condition("attributes.plano", "in", ["gold", 10, True]),
+                ]},
+            ],
+        })
+
+        assert segment.match == "all"
+        assert segment.conditions[0] == SegmentCondition("tags", "has_all", [tag_id])
+        assert segment.conditions[1].value == datetime(2025, 11, 1, tzinfo=timezone.utc)
+        group = segment.conditions[2]
+        assert isinstance(group, SegmentGroup) and group.match == MATCH_ANY
+        assert group.conditions[0].value is None
+        assert group.conditions[1].attribute == "plano"
+        assert group.conditions[1].value == ["gold", "10", "true"]
+
+    def test_empty_segment(self):
+        """Test an empty segmentation is an empty all group"""
+        assert parse_segment({}) == SegmentGroup()
+        assert parse_segment(None) == SegmentGroup()
+
+    @pytest.mark.parametrize("condition", [
+        _condition("password", "eq", "x"),
+        _condition("name", "gt", 1),
+        _condition("lead_score", "gte", "high"),
+        _condition("tags", "has_any", ["not-a-uuid"]),
+        _condition("tags", "has_any", []),
+        _condition("last_interaction", "within_days", 0),
+        _condition("created_at", "after", "yesterday"),
+        _condition("attributes.bad key", "eq", "x"),
+        _condition("attributes.score", "between", [1]),
+        _condition("email", "eq", ""),
+    ])
+    def test_invalid_conditions(self, condition):
+        """Test unknown fields, wrong operators and bad values are refused"""
+        with pytest.raises(SegmentError):
+            parse_segment({"conditions": [condition]})
+
+    def test_limits(self):
+        """Test the number of conditions and nesting are bounded"""
+        too_many = {"conditions": [_condition("is_vip", "is_true")] * (MAX_CONDITIONS + 1)}
+        deep = {"conditions": []}
+        for _ in range(5):
+            deep = {"conditions": [deep]}
+
+        assert validate_segment(too_many)
+        assert validate_segment(deep)
+
+    def test_validate_segment(self):
+        """Test errors point at the invalid condition"""
+        errors = validate_segment({"match": "any", "conditions": [
+            _condition("is_vip", "is_true"),
+            _condition("lead_score", "like", 10),
+        ]})
+
+        assert len(errors) == 1
+        assert errors[0].startswith("segment.conditions[1]")
+        assert validate_segment({"match": "any", "conditions": [_condition("lead_score", "between", [10, 50])]}) == []
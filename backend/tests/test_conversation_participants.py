@@ -0,0 +1,157 @@
+"""
+Conversation Participant Service Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.models.conversation import Conversation
+from app.schemas.conversation_participant import ParticipantAdd, ParticipantUpdate
+from app.services.participant_service import ConversationParticipantService
+from tests.conftest import OrganizationFactory, UserFactory
+
+
+async def _conversation(db: AsyncSession, organization_id, **kwargs) -> Conversation:
+    conversation = Conversation(
+        id=uuid4(),
+        organization_id=organization_id,
+        contact_id=uuid4(),
+        whatsapp_number_id=uuid4(),
+        status="open",
+        **kwargs,
+    )
+    db.add(conversation)
+    await db.commit()
+    return conversation
+
+
+class TestConversationParticipants:
+    """Tests for primary/collaborator management and attribution"""
+
+    @pytest_asyncio.fixture
+    async def service(self, db_session: AsyncSession, monkeypatch) -> ConversationParticipantService:
+        events = []
+
+        async def fake_broadcast(self, event, participant):
+            events.append((event, participant.user_id))
+
+        monkeypatch.setattr(ConversationParticipantService, "broadcast", fake_broadcast)
+        service = ConversationParticipantService(db_session)
+        service.events = events
+        return service
+
+    @pytest.mark.asyncio
+    async def test_add_collaborator_and_primary(
+        self, service: ConversationParticipantService, db_session: AsyncSession
+    ):
+        """Test collaborators join and a primary participant reassigns the conversation"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        alice = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        bob = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        conversation = await _conversation(db_session, org.id, current_agent_id=alice.id)
+        await service.set_primary(conversation, alice.id)
+
+        await service.add_participant(conversation.id, org.id, ParticipantAdd(user_id=bob.id))
+        items = await service.list_participants(conversation.id, org.id)
+        assert [(p.user_id, p.role) for p in items] == [(alice.id, "primary"), (bob.id, "collaborator")]
+
+        await service.update_participant(conversation.id, org.id, bob.id, ParticipantUpdate(role="primary"))
+        items = await service.list_participants(conversation.id, org.id)
+        assert [(p.user_id, p.role) for p in items] == [(bob.id, "primary"), (alice.id, "collaborator")]
+        assert conversation.current_agent_id == bob.id
+        assert service.events == [("participant:joined", bob.id), ("participant:updated", bob.id)]
+
+    @pytest.mark.asyncio
+    async def test_agent_of_other_organization(
+        self, service: ConversationParticipantService, db_session: AsyncSession
+    ):
+        org = await OrganizationFactory.create_in_db(db_session)
+        other = await OrganizationFactory.create_in_db(db_session)
+        stranger = await UserFactory.create_in_db(db_session, organization_id=other.id)
+        conversation = await _conversation(db_session, org.id)
+
+        with pytest.raises(NotFoundException):
+            await service.add_participant(conversation.id, org.id, ParticipantAdd(user_id=stranger.id))
+
+    @pytest.mark.asyncio
+    async def test_primary_cannot_leave(
+        self, service: ConversationParticipantService, db_session: AsyncSession
+    ):
+        """Test collaborators leave (history kept) but the primary must be reassigned"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        alice = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        bob = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        conversation = await _conversation(db_session, org.id)
+        await service.add_participant(conversation.id, org.id, ParticipantAdd(user_id=alice.id, role="primary"))
+        await service.add_participant(conversation.id, org.id, ParticipantAdd(user_id=bob.id))
+
+        with pytest.raises(BadRequestException):
+            await service.remove_participant(conversation.id, org.id, alice.id)
+
+        await service.remove_participant(conversation.id, org.id, bob.id)
+        assert [p.user_id for p in await service.list_participants(conversation.id, org.id)] == [alice.id]
+        history = await service.list_participants(conversation.id, org.id, include_left=True)
+        assert len(history) == 2
+
+    @pytest.mark.asyncio
+    async def test_transfer_removes_primary(
+        self, service: ConversationParticipantService, db_session: AsyncSession
+    ):
+        """Test a transfer to a queue makes the primary leave and keeps collaborators"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        alice = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        bob = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        conversation = await _conversation(db_session, org.id)
+        await service.set_primary(conversation, alice.id)
+        await service.add_participant(conversation.id, org.id, ParticipantAdd(user_id=bob.id))
+
+        await service.set_primary(conversation, None)
+        await db_session.commit()
+
+        assert await service.get_primary(conversation.id) is None
+        assert [p.user_id for p in await service.list_participants(conversation.id, org.id)] == [bob.id]
+
+    @pytest.mark.asyncio
+    async def test_outbound_attribution(
+        self, service: ConversationParticipantService, db_session: AsyncSession
+    ):
+        """Test a replying agent joins once and their messages are counted"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        carol = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        conversation = await _conversation(db_session, org.id)
+
+        joined = await service.record_outbound(conversation, carol.id)
+        again = await service.record_outbound(conversation, carol.id)
+
+        assert joined is not None and joined.role == "collaborator"
+        assert again is None
+        assert joined.messages_sent == 2
+        assert joined.last_message_at is not None
+
+    @pytest.mark.asyncio
+    async def test_notification_preferences(
+        self, service: ConversationParticipantService, db_session: AsyncSession
+    ):
+        """Test all, mentions and none notification levels"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        everything = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        mentions = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        muted = await UserFactory.create_in_db(db_session, organization_id=org.id)
+        conversation = await _conversation(db_session, org.id)
+        for user, level in ((everything, "all"), (mentions, "mentions"), (muted, "none")):
+            await service.add_participant(
+                conversation.id, org.id, ParticipantAdd(user_id=user.id, notification_level=level)
+            )
+
+        assert await service.notification_recipients(conversation.id) == [everything.id]
+        assert set(await service.notification_recipients(conversation.id, [mentions.id, muted.id])) == {
+            everything.id,
+            mentions.id,
+        }
+        assert await service.notification_recipients(conversation.id, exclude_user_id=everything.id) == []
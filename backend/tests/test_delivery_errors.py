@@ -0,0 +1,155 @@
+"""
+Delivery Error Handling Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.models.campaign import Campaign
+from app.models.contact import Contact
+from app.schemas.webhook import DeliveryError, DeliveryErrorCode
+from app.tasks.campaign_retry import CampaignRetryManager
+from tests.conftest import OrganizationFactory
+
+
+@pytest_asyncio.fixture
+async def org(db_session: AsyncSession):
+    """Organization running the campaign"""
+    return await OrganizationFactory.create_in_db(db_session)
+
+
+async def _campaign(db, org, **overrides) -> Campaign:
+    data = dict(
+        id=uuid4(),
+        organization_id=org.id,
+        name="Black Friday",
+        status="running",
+        audience_type="all",
+        message_type="text",
+        message_content={"text": "Oi!"},
+        template_id=None,
+        retry_max_attempts=3,
+        retry_base_delay=0,
+        retry_max_delay=0,
+        errors=[],
+        message_statuses={},
+        error_count=0,
+        last_error_message=None,
+    )
+    data.update(overrides)
+    campaign = Campaign(**data)
+    db.add(campaign)
+    await db.commit()
+    return campaign
+
+
+async def _contact(db, org) -> Contact:
+    contact = Contact(id=uuid4(), organization_id=org.id, name="Maria", whatsapp_id="5511999999999")
+    db.add(contact)
+    await db.commit()
+    return contact
+
+
+def _manager(db, campaign, results):
+    """Retry manager whose sends return the given (success, error_code) results in order"""
+    manager = CampaignRetryManager(campaign, db)
+    manager.sent_as_template = []
+
+    async def send(contact, whatsapp_number, use_template=False, ab_variant=None):
+        manager.sent_as_template.append(use_template)
+        success, code = results.pop(0)
+        if success:
+            return True, "wamid.1", None, None
+        return False, None, f"code {code}", DeliveryError.from_code(code, detail="failed")
+
+    manager._send_single_message = send
+    return manager
+
+
+class TestDeliveryError:
+    """Tests for parsing Meta error objects"""
+
+    def test_from_status(self):
+        error = DeliveryError.from_status({"errors": [{
+            "code": 131047,
+            "title": "Re-engagement message",
+            "error_data": {"details": "More than 24 hours have passed"},
+        }]})
+
+        assert error.code == DeliveryErrorCode.REENGAGEMENT_WINDOW
+        assert (error.needs_template, error.permanent_failure, error.retryable) == (True, False, False)
+        assert error.detail == "More than 24 hours have passed"
+
+    @pytest.mark.parametrize("code,permanent,retryable", [
+        (131026, True, False),
+        ("131050", True, False),
+        (132001, True, False),
+        (130429, False, True),
+        (131049, False, True),
+        (999999, False, True),
+        (None, False, True),
+    ])
+    def test_guidance_flags(self, code, permanent, retryable):
+        error = DeliveryError.from_code(code)
+
+        assert error.permanent_failure is permanent
+        assert error.retryable is retryable
+        assert error.guidance
+
+    def test_unknown_code_keeps_raw_value(self):
+        error = DeliveryError.from_code(999999, title="Weird")
+
+        assert error.code == DeliveryErrorCode.UNKNOWN
+        assert error.raw_code == "999999"
+
+
+class TestCampaignRetryDecisions:
+    """Tests for retry decisions driven by error codes"""
+
+    @pytest.mark.asyncio
+    async def test_permanent_failure_drops_contact_without_retrying(self, db_session: AsyncSession, org):
+        campaign = await _campaign(db_session, org)
+        contact = await _contact(db_session, org)
+        manager = _manager(db_session, campaign, [(False, 131026)])
+
+        assert await manager.send_message_with_retry(contact, SimpleNamespace()) == (False, None)
+
+        await db_session.refresh(campaign)
+        status = campaign.message_statuses[str(contact.id)]
+        assert status["status"] == "failed" and status["dropped"] is True
+        assert len(status["attempts"]) == 1
+        assert status["attempts"][0]["error_kind"] == "undeliverable"
+        assert manager.get_retry_statistics()["dropped"] == 1
+
+    @pytest.mark.asyncio
+    async def test_reengagement_switches_to_template(self, db_session: AsyncSession, org):
+        campaign = await _campaign(db_session, org, template_id=uuid4())
+        manager = _manager(db_session, campaign, [(False, 131047), (True, None)])
+
+        success, message_id = await manager.send_message_with_retry(await _contact(db_session, org), SimpleNamespace())
+
+        assert (success, message_id) == (True, "wamid.1")
+        assert manager.sent_as_template == [False, True]
+
+    @pytest.mark.asyncio
+    async def test_reengagement_without_template_stops(self, db_session: AsyncSession, org):
+        manager = _manager(db_session, await _campaign(db_session, org), [(False, 131047)])
+
+        assert await manager.send_message_with_retry(await _contact(db_session, org), SimpleNamespace()) == (False, None)
+        assert manager.sent_as_template == [False]
+
+    @pytest.mark.asyncio
+    async def test_transient_errors_are_retried(self, db_session: AsyncSession, org):
+        campaign = await _campaign(db_session, org)
+        contact = await _contact(db_session, org)
+        manager = _manager(db_session, campaign, [(False, 130429), (False, 131000), (True, None)])
+
+        assert await manager.send_message_with_retry(contact, SimpleNamespace()) == (True, "wamid.1")
+        await db_session.refresh(campaign)
+        assert len(campaign.message_statuses[str(contact.id)]["attempts"]) == 3
@@ -0,0 +1,148 @@
+"""
+Dunning Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import date, datetime, time, timezone
+from decimal import Decimal
+from types import SimpleNamespace
+
+import pytest
+
+from app.services.dunning_service import (
+    format_amount,
+    invoice_event_payload,
+    local_today,
+    resolve_variable,
+    scheduled_datetime,
+    skip_reason,
+    target_due_date,
+)
+
+
+def _invoice(**overrides):
+    data = {
+        "external_id": "ERP-1",
+        "number": "NF-100",
+        "amount": Decimal("1234.56"),
+        "currency": "BRL",
+        "due_date": date(2025, 11, 10),
+        "status": "open",
+        "pix_code": "00020126pix",
+        "payment_url": "https://pay.example.com/1",
+        "extra_data": {"plan": "Fibra 500"},
+    }
+    data.update(overrides)
+    invoice = SimpleNamespace(**data)
+    invoice.is_open = invoice.status == "open"
+    return invoice
+
+
+def _contact(**overrides):
+    data = {
+        "display_name": "Maria",
+        "whatsapp_id": "5511999999999",
+        "is_blocked": False,
+        "deleted_at": None,
+    }
+    data.update(overrides)
+    return SimpleNamespace(**data)
+
+
+def _rule(**overrides):
+    data = {"offer_pix": False, "min_amount": None}
+    data.update(overrides)
+    return SimpleNamespace(**data)
+
+
+class TestRuleSchedule:
+    """Tests for due date offsets and send time"""
+
+    def test_target_due_date(self):
+        """Test before, on and after the due date"""
+        today = date(2025, 11, 7)
+        assert target_due_date(today, -3) == date(2025, 11, 10)
+        assert target_due_date(today, 0) == today
+        assert target_due_date(today, 5) == date(2025, 11, 2)
+
+    def test_scheduled_datetime_uses_rule_timezone(self):
+        """Test 09:00 in Sao Paulo is 12:00 UTC"""
+        moment = scheduled_datetime(date(2025, 11, 7), time(9, 0), "America/Sao_Paulo")
+        assert moment == datetime(2025, 11, 7, 12, 0, tzinfo=timezone.utc)
+
+    def test_invalid_timezone_falls_back(self):
+        """Test unknown timezones default to Sao Paulo"""
+        moment = scheduled_datetime(date(2025, 11, 7), time(9, 0), "Mars/Olympus")
+        assert moment.hour == 12
+
+    def test_local_today(self):
+        """Test the local date differs from UTC near midnight"""
+        now = datetime(2025, 11, 8, 1, 30, tzinfo=timezone.utc)
+        assert local_today("America/Sao_Paulo", now) == date(2025, 11, 7)
+
+
+class TestVariables:
+    """Tests for template parameter resolution"""
+
+    def test_format_amount(self):
+        """Test BRL and other currencies"""
+        assert format_amount(Decimal("1234.56"), "BRL") == "R$ 1.234,56"
+        assert format_amount(Decimal("10"), "USD") == "10.00 USD"
+
+    def test_resolve_variables(self):
+        """Test contact and invoice fields"""
+        invoice, contact = _invoice(), _contact()
+        today = date(2025, 11, 15)
+
+        assert resolve_variable("contact.name", invoice, contact, today) == "Maria"
+        assert resolve_variable("invoice.amount", invoice, contact, today) == "R$ 1.234,56"
+        assert resolve_variable("invoice.due_date", invoice, contact, today) == "10/11/2025"
+        assert resolve_variable("invoice.days_overdue", invoice, contact, today) == "5"
+        assert resolve_variable("invoice.pix_code", invoice, contact, today) == "00020126pix"
+        assert resolve_variable("invoice.extra.plan", invoice, contact, today) == "Fibra 500"
+
+    def test_days_overdue_not_negative(self):
+        """Test reminders before the due date report zero days overdue"""
+        value = resolve_variable("invoice.days_overdue", _invoice(), _contact(), date(2025, 11, 7))
+        assert value == "0"
+
+    def test_unknown_variable(self):
+        """Test unknown paths are rejected"""
+        with pytest.raises(ValueError):
+            resolve_variable("invoice.secret", _invoice(), _contact())
+
+    def test_invoice_event_payload(self):
+        """Test the invoice.overdue flow event carries the formatted invoice"""
+        payload = invoice_event_payload(_invoice(), date(2025, 11, 15))
+        assert payload["invoice"]["amount"] == "R$ 1.234,56"
+        assert payload["invoice"]["due_date"] == "10/11/2025"
+        assert payload["invoice"]["days_overdue"] == 5
+        assert payload["invoice"]["extra"] == {"plan": "Fibra 500"}
+
+
+class TestSkipReason:
+    """Tests for reminder eligibility"""
+
+    def test_open_invoice_is_sent(self):
+        """Test no skip reason for an eligible invoice"""
+        assert skip_reason(_rule(), _invoice(), _contact()) is None
+
+    def test_paid_invoice_is_skipped(self):
+        """Test invoices paid meanwhile are not reminded"""
+        assert skip_reason(_rule(), _invoice(status="paid"), _contact()) == "invoice_paid"
+
+    def test_blocked_contact_is_skipped(self):
+        """Test blocked or missing contacts"""
+        assert skip_reason(_rule(), _invoice(), _contact(is_blocked=True)) == "contact_unavailable"
+        assert skip_reason(_rule(), _invoice(), None) == "contact_unavailable"
+
+    def test_pix_rule_requires_pix_code(self):
+        """Test offer_pix rules skip invoices without Pix"""
+        rule = _rule(offer_pix=True)
+        assert skip_reason(rule, _invoice(pix_code=None), _contact()) == "missing_pix_code"
+
+    def test_min_amount(self):
+        """Test small invoices below the rule minimum"""
+        rule = _rule(min_amount=Decimal("50"))
+        assert skip_reason(rule, _invoice(amount=Decimal("49.90")), _contact()) == "below_min_amount"
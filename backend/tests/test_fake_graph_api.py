@@ -0,0 +1,134 @@
+"""
+Fake Graph API Test Harness Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from pytake_client.media import InMemoryMediaStorage, MediaFetcher
+from pytake_client.testing import (
+    FakeGraphAPI,
+    media_message,
+    sign_payload,
+    status_update,
+    template_status_update,
+    text_message,
+    webhook_payload,
+)
+from pytake_client.webhooks import WebhookEventType, iter_events, verify_signature
+
+GRAPH = "https://graph.facebook.com/v21.0"
+AUTH = {"Authorization": "Bearer test-token"}
+
+
+class TestFakeGraphAPI:
+    """Tests for the fake Cloud API endpoints"""
+
+    @pytest.mark.asyncio
+    async def test_send_message(self):
+        """Test sends are answered like Meta and recorded"""
+        graph = FakeGraphAPI(access_token="test-token")
+
+        async with graph.client() as client:
+            response = await client.post(f"{GRAPH}/123/messages", headers=AUTH, json={
+                "messaging_product": "whatsapp", "to": "+55 11 99999-9999",
+                "type": "text", "text": {"body": "Olá!"},
+            })
+
+        message_id = response.json()["messages"][0]["id"]
+        assert response.json()["contacts"] == [{"input": "+55 11 99999-9999", "wa_id": "5511999999999"}]
+        assert graph.sent_messages[0]["id"] == message_id
+        assert graph.sent_messages[0]["phone_number_id"] == "123"
+        assert graph.sent_messages[0]["text"]["body"] == "Olá!"
+
+    @pytest.mark.asyncio
+    async def test_uploaded_media_can_be_fetched(self):
+        """Test an upload is resolvable and downloadable by MediaFetcher"""
+        graph = FakeGraphAPI(access_token="test-token")
+
+        async with graph.client() as client:
+            response = await client.post(
+                f"{GRAPH}/123/media", headers=AUTH,
+                data={"messaging_product": "whatsapp"},
+                files={"file": ("nota.pdf", b"%PDF-1.4 fake", "application/pdf")},
+            )
+        media_id = response.json()["id"]
+
+        storage = InMemoryMediaStorage()
+        fetcher = MediaFetcher("test-token", storage, http_client=graph.client())
+        stored = await fetcher.fetch(media_id)
+        await fetcher.close()
+
+        assert stored.mime_type == "application/pdf"
+        assert storage.objects[stored.reference] == b"%PDF-1.4 fake"
+
+    @pytest.mark.asyncio
+    async def test_templates(self):
+        """Test templates are created pending, listed by name and deleted"""
+        graph = FakeGraphAPI()
+        graph.add_template("waba", "boas_vindas")
+
+        async with graph.client() as client:
+            created = await client.post(f"{GRAPH}/waba/message_templates", json={
+                "name": "promo", "language": "pt_BR", "category": "MARKETING", "components": [],
+            })
+            listed = await client.get(f"{GRAPH}/waba/message_templates", params={"name": "promo"})
+            deleted = await client.delete(f"{GRAPH}/waba/message_templates", params={"name": "boas_vindas"})
+
+        assert created.json()["status"] == "PENDING"
+        assert [t["name"] for t in listed.json()["data"]] == ["promo"]
+        assert deleted.json() == {"success": True}
+        assert [t["name"] for t in graph.templates["waba"]] == ["promo"]
+
+    @pytest.mark.asyncio
+    async def test_errors(self):
+        """Test bad tokens, queued failures and unknown endpoints use Meta's error body"""
+        graph = FakeGraphAPI(access_token="test-token")
+        graph.fail_next(400, 131047, "Re-engagement message", path="123/messages")
+
+        async with graph.client() as client:
+            bad_token = await client.post(f"{GRAPH}/123/messages", json={"to": "1"})
+            failed = await client.post(f"{GRAPH}/123/messages", headers=AUTH, json={"to": "1"})
+            sent = await client.post(f"{GRAPH}/123/messages", headers=AUTH, json={"to": "1"})
+            unknown = await client.get(f"{GRAPH}/123/whatsapp_commerce_settings", headers=AUTH)
+
+        assert (bad_token.status_code, bad_token.json()["error"]["code"]) == (401, 190)
+        assert (failed.status_code, failed.json()["error"]["code"]) == (400, 131047)
+        assert sent.status_code == 200
+        assert unknown.status_code == 404
+        assert len(graph.requests) == 4
+
+
+class TestWebhookBuilders:
+    """Tests for canned webhook payloads"""
+
+    def test_messages_and_statuses(self):
+        """Test built payloads parse into the expected events"""
+        payload = webhook_payload(
+            text_message("Olá", message_id="wamid.1"),
+            media_message("image", "media-1", caption="Comprovante"),
+            statuses=[status_update("wamid.out", "failed", error_code=131026)],
+            phone_number_id="555",
+        )
+
+        events = list(iter_events(payload))
+
+        assert [event.type for event in events] == [
+            WebhookEventType.MESSAGE, WebhookEventType.MESSAGE, WebhookEventType.STATUS,
+        ]
+        assert {event.phone_number_id for event in events} == {"555"}
+        assert events[0].inbound.text == "Olá"
+        assert events[1].inbound.media.id == "media-1"
+        assert len(payload["entry"][0]["changes"][0]["value"]["contacts"]) == 1
+
+    def test_template_status_and_signature(self):
+        """Test template updates and signed bodies"""
+        payload = template_status_update("promo", "REJECTED", reason="INVALID_FORMAT")
+        body, signature = sign_payload(payload, "app-secret")
+
+        event = next(iter_events(payload))
+
+        assert event.type == WebhookEventType.TEMPLATE_STATUS
+        assert verify_signature(body, signature, "app-secret")
+        assert not verify_signature(body, signature, "other-secret")
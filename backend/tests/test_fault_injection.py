@@ -0,0 +1,116 @@
+"""
+Fault Injection Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import httpx
+import pytest
+
+from app.core.fault_injection import (
+    FaultInjectionTransport,
+    FaultInjector,
+    FaultRule,
+    InjectedFault,
+    parse_rules,
+)
+
+
+class OkTransport(httpx.AsyncBaseTransport):
+    """Upstream answering 200"""
+
+    def __init__(self):
+        self.calls = 0
+
+    async def handle_async_request(self, request):
+        self.calls += 1
+        return httpx.Response(200, json={"ok": True}, request=request)
+
+
+class TestParseRules:
+    """Tests for FAULT_INJECTION_RULES parsing"""
+
+    def test_rules_per_target(self):
+        rules = parse_rules("meta_api.latency=0.5:800, meta_api.error=0.1:502,webhook.drop=0.2")
+
+        assert rules["meta_api"] == FaultRule(latency_rate=0.5, latency_ms=800, error_rate=0.1, error_status=502)
+        assert rules["webhook"] == FaultRule(drop_rate=0.2)
+        assert parse_rules("") == {}
+
+    @pytest.mark.parametrize("value", ["erp.error=0.1", "queue.explode=0.1", "queue.error=2"])
+    def test_invalid_rules(self, value):
+        with pytest.raises(ValueError):
+            parse_rules(value)
+
+
+class TestFaultInjector:
+    """Tests for fault decisions"""
+
+    def test_disabled_injector_never_faults(self):
+        injector = FaultInjector({"queue": FaultRule(error_rate=1.0, drop_rate=1.0)}, enabled=False)
+
+        assert injector.rule("queue") is None
+        assert injector.before_task() is False
+
+    def test_seed_makes_faults_reproducible(self):
+        """Test the same seed yields the same fault sequence"""
+        rules = {"meta_api": FaultRule(error_rate=0.5)}
+        first = FaultInjector(rules, enabled=True, seed=42)
+        second = FaultInjector(rules, enabled=True, seed=42)
+
+        sequence = [first.error("meta_api") for _ in range(20)]
+
+        assert sequence == [second.error("meta_api") for _ in range(20)]
+        assert {None, 503} == set(sequence)
+
+    @pytest.mark.asyncio
+    async def test_inbound_error_and_drop(self):
+        injector = FaultInjector(enabled=False)
+
+        with injector.injected("webhook", FaultRule(error_rate=1.0, error_status=500)):
+            with pytest.raises(InjectedFault) as exc:
+                await injector.inbound("webhook")
+            assert exc.value.status_code == 500
+
+        with injector.injected("webhook", FaultRule(drop_rate=1.0)):
+            assert await injector.inbound("webhook") is True
+
+        # Rules and state are restored after the block
+        assert injector.rule("webhook") is None
+        assert await injector.inbound("webhook") is False
+
+
+class TestFaultInjectionTransport:
+    """Tests for faults on outbound HTTP integrations"""
+
+    @pytest.mark.asyncio
+    async def test_injected_error_response(self):
+        """Test a 5xx is answered without reaching the upstream"""
+        upstream = OkTransport()
+        injector = FaultInjector({"meta_api": FaultRule(error_rate=1.0, error_status=503)}, enabled=True)
+
+        async with httpx.AsyncClient(transport=FaultInjectionTransport("meta_api", injector, upstream)) as client:
+            response = await client.post("https://graph.facebook.com/v21.0/123/messages", json={})
+
+        assert response.status_code == 503
+        assert upstream.calls == 0
+
+    @pytest.mark.asyncio
+    async def test_injected_drop_is_a_connection_error(self):
+        injector = FaultInjector({"http_connector": FaultRule(drop_rate=1.0)}, enabled=True)
+        transport = FaultInjectionTransport("http_connector", injector, OkTransport())
+
+        async with httpx.AsyncClient(transport=transport) as client:
+            with pytest.raises(httpx.RequestError):
+                await client.get("https://erp.example.com/orders")
+
+    @pytest.mark.asyncio
+    async def test_other_targets_pass_through(self):
+        upstream = OkTransport()
+        injector = FaultInjector({"meta_api": FaultRule(error_rate=1.0)}, enabled=True)
+
+        async with httpx.AsyncClient(transport=FaultInjectionTransport("evolution_api", injector, upstream)) as client:
+            response = await client.get("https://evolution.example.com/instance")
+
+        assert response.status_code == 200
+        assert upstream.calls == 1
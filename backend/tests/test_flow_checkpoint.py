@@ -0,0 +1,156 @@
+"""
+Flow Checkpoint Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+
+import pytest
+
+from app.utils.flow_checkpoint import (
+    MAX_RESUMES,
+    STATUS_COMPLETED,
+    CheckpointedSender,
+    begin,
+    complete,
+    is_executing,
+    should_give_up,
+)
+
+NOW = datetime(2025, 11, 18, 18, 0, tzinfo=timezone.utc)
+
+
+class FakeClient:
+    """WhatsApp client that records the messages sent"""
+
+    def __init__(self, fail=False):
+        self.sent = []
+        self.fail = fail
+
+    async def send_text_message(self, to, text):
+        if self.fail:
+            raise RuntimeError("(#131026) Message undeliverable")
+        self.sent.append(text)
+        return {"messages": [{"id": f"wamid.{len(self.sent)}"}]}
+
+    async def send_typing_on(self, message_id):
+        self.sent.append("typing")
+
+
+def _sender(client, checkpoint, on_sent=None):
+    saved = []
+
+    async def persist(updated):
+        saved.append(dict(updated))
+
+    return CheckpointedSender(client, checkpoint, persist, on_sent), saved
+
+
+class TestCheckpointLifecycle:
+    """Tests for begin(), complete() and should_give_up()"""
+
+    def test_begin_and_complete(self):
+        """Test a node is executing until completed with its output"""
+        checkpoint = begin(None, "node-3", "msg-1", NOW)
+
+        assert is_executing(checkpoint, checkpoint["run_id"])
+        assert checkpoint["sent"] == checkpoint["replay"] == 0
+
+        done = complete(checkpoint, "node-3", {"next_node_id": "node-4"}, NOW)
+
+        assert done["status"] == STATUS_COMPLETED
+        assert done["output"] == {"next_node_id": "node-4"}
+        assert done["message_id"] == "msg-1"
+        assert not is_executing(done)
+
+    def test_resume_replays_sends_of_the_interrupted_run(self):
+        """Test confirmed and unconfirmed sends are skipped when the same node resumes"""
+        interrupted = begin(None, "node-3", "msg-1", NOW)
+        interrupted.update(sent=1, sending=True)
+
+        resumed = begin(interrupted, "node-3", None, NOW, resume=True)
+
+        assert resumed["replay"] == 2
+        assert resumed["message_id"] == "msg-1"
+        assert resumed["resumes"] == 1
+        assert resumed["run_id"] != interrupted["run_id"]
+
+    def test_new_runs_do_not_replay(self):
+        """Test a later execution of the node, or another node, sends everything again"""
+        interrupted = begin(None, "node-3", "msg-1", NOW)
+        interrupted["sent"] = 1
+
+        assert begin(interrupted, "node-3", "msg-2", NOW)["replay"] == 0
+        assert begin(interrupted, "node-4", None, NOW, resume=True)["replay"] == 0
+
+    def test_gives_up_after_max_resumes(self):
+        """Test a node that keeps crashing is not run forever"""
+        checkpoint = begin(None, "node-3", None, NOW)
+        for _ in range(MAX_RESUMES):
+            assert not should_give_up(checkpoint)
+            checkpoint = begin(checkpoint, "node-3", None, NOW, resume=True)
+
+        assert should_give_up(checkpoint)
+
+
+class TestCheckpointedSender:
+    """Tests for CheckpointedSender"""
+
+    @pytest.mark.asyncio
+    async def test_counts_sends(self):
+        """Test sends are recorded as started and then confirmed"""
+        client = FakeClient()
+        checkpoint = begin(None, "node-3", None, NOW)
+        sender, saved = _sender(client, checkpoint)
+
+        response = await sender.send_text_message("5511999990000", "Olá!")
+        await sender.send_typing_on("wamid.in")
+
+        assert response["messages"][0]["id"] == "wamid.1"
+        assert client.sent == ["Olá!", "typing"]
+        assert [state["sending"] for state in saved] == [True, False]
+        assert checkpoint["sent"] == 1
+
+    @pytest.mark.asyncio
+    async def test_resume_does_not_send_twice(self):
+        """Test a resumed node only sends the messages the crashed run did not"""
+        client = FakeClient()
+        interrupted = begin(None, "node-3", None, NOW)
+        interrupted["sent"] = 1
+        checkpoint = begin(interrupted, "node-3", None, NOW, resume=True)
+        sender, _ = _sender(client, checkpoint)
+
+        assert await sender.send_text_message("5511999990000", "Olá!") == {}
+        await sender.send_text_message("5511999990000", "Qual o seu CPF?")
+
+        assert client.sent == ["Qual o seu CPF?"]
+        assert checkpoint["sent"] == 2
+
+    @pytest.mark.asyncio
+    async def test_on_sent_only_for_messages_sent(self):
+        """Test on_sent is called for sends made, not for replayed ones"""
+        interrupted = begin(None, "node-3", None, NOW)
+        interrupted["sent"] = 1
+        checkpoint = begin(interrupted, "node-3", None, NOW, resume=True)
+        calls = []
+
+        async def on_sent(method, args, kwargs):
+            calls.append((method, args))
+
+        sender, _ = _sender(FakeClient(), checkpoint, on_sent)
+        await sender.send_text_message("5511999990000", "Olá!")
+        await sender.send_text_message("5511999990000", "Qual o seu CPF?")
+
+        assert calls == [("send_text_message", ("5511999990000", "Qual o seu CPF?"))]
+
+    @pytest.mark.asyncio
+    async def test_refused_send_is_not_counted(self):
+        """Test a send refused by the API is not skipped on resume"""
+        checkpoint = begin(None, "node-3", None, NOW)
+        sender, _ = _sender(FakeClient(fail=True), checkpoint)
+
+        with pytest.raises(RuntimeError):
+            await sender.send_text_message("5511999990000", "Olá!")
+
+        assert checkpoint["sent"] == 0 and not checkpoint["sending"]
@@ -0,0 +1,117 @@
+"""
+Flow Complexity Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.flow_complexity import ComplexityLimits, analyze_flow
+
+LIMITS = ComplexityLimits(max_nodes=100, max_depth=100, max_messages_per_run=100)
+
+
+def _canvas(nodes, edges):
+    return {
+        "nodes": [{"id": node_id, "data": {"nodeType": node_type, **data}} for node_id, node_type, data in nodes],
+        "edges": [{"id": f"e{index}", "source": source, "target": target} for index, (source, target) in enumerate(edges)],
+    }
+
+
+def _codes(diagnostics):
+    return [(diagnostic.code, diagnostic.severity) for diagnostic in diagnostics]
+
+
+class TestMetrics:
+    """Tests for the metrics of analyze_flow()"""
+
+    def test_depth_and_messages_follow_the_longest_path(self):
+        """Test depth and messages are those of the longest branch"""
+        canvas = _canvas(
+            [
+                ("start", "start", {}),
+                ("hi", "message", {}),
+                ("menu", "condition", {}),
+                ("short", "end", {}),
+                ("m1", "message", {}),
+                ("nps", "survey", {"askComment": True}),
+                ("end", "end", {}),
+            ],
+            [("start", "hi"), ("hi", "menu"), ("menu", "short"), ("menu", "m1"), ("m1", "nps"), ("nps", "end")],
+        )
+
+        complexity, diagnostics = analyze_flow(canvas, LIMITS)
+
+        assert complexity.node_count == 7
+        assert complexity.max_depth == 6
+        assert complexity.estimated_messages == 4
+        assert complexity.cycles == 0
+        assert diagnostics == []
+
+    def test_loop_body_counts_per_iteration(self):
+        """Test messages inside a loop node's body count maxIterations times"""
+        canvas = _canvas(
+            [
+                ("start", "start", {}),
+                ("loop", "loop", {"maxIterations": 5}),
+                ("item", "message", {}),
+                ("end", "end", {}),
+            ],
+            [("start", "loop"), ("loop", "item"), ("item", "loop"), ("loop", "end")],
+        )
+
+        complexity, diagnostics = analyze_flow(canvas, LIMITS)
+
+        assert complexity.cycles == 1
+        assert complexity.cycle_nodes == [["item", "loop"]]
+        assert complexity.unguarded_cycles == 0
+        assert complexity.estimated_messages == 5
+        assert diagnostics == []
+
+
+class TestCycles:
+    """Tests for the cycle diagnostics"""
+
+    def test_cycle_waiting_for_the_contact_is_fine(self):
+        """Test a menu that asks again is not reported"""
+        canvas = _canvas(
+            [("start", "start", {}), ("ask", "question", {}), ("check", "condition", {}), ("end", "end", {})],
+            [("start", "ask"), ("ask", "check"), ("check", "ask"), ("check", "end")],
+        )
+
+        complexity, diagnostics = analyze_flow(canvas, LIMITS)
+
+        assert complexity.cycles == 1 and complexity.unguarded_cycles == 0
+        assert diagnostics == []
+
+    def test_unguarded_cycles(self):
+        """Test cycles that never wait are warnings, or errors without a way out"""
+        with_exit = _canvas(
+            [("start", "start", {}), ("call", "api_call", {}), ("check", "condition", {}), ("end", "end", {})],
+            [("start", "call"), ("call", "check"), ("check", "call"), ("check", "end")],
+        )
+        forever = _canvas(
+            [("start", "start", {}), ("a", "message", {}), ("b", "jump", {"targetNodeId": "a"})],
+            [("start", "a"), ("a", "b")],
+        )
+
+        assert _codes(analyze_flow(with_exit, LIMITS)[1]) == [("unguarded_cycle", "warning")]
+        assert _codes(analyze_flow(forever, LIMITS)[1]) == [("infinite_cycle", "error")]
+
+
+class TestLimits:
+    """Tests for the complexity limits"""
+
+    def test_limits_refuse_or_warn(self):
+        """Test limits are errors by default and warnings when enforcement is warning"""
+        canvas = _canvas(
+            [("start", "start", {})] + [(f"m{index}", "message", {}) for index in range(4)],
+            [("start", "m0"), ("m0", "m1"), ("m1", "m2"), ("m2", "m3")],
+        )
+        strict = ComplexityLimits(max_nodes=4, max_depth=4, max_messages_per_run=3)
+        lenient = ComplexityLimits(max_nodes=4, max_depth=4, max_messages_per_run=3, severity="warning")
+
+        assert _codes(analyze_flow(canvas, strict)[1]) == [
+            ("too_many_nodes", "error"),
+            ("flow_too_deep", "error"),
+            ("too_many_messages", "error"),
+        ]
+        assert {severity for _, severity in _codes(analyze_flow(canvas, lenient)[1])} == {"warning"}
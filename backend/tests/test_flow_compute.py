@@ -0,0 +1,75 @@
+"""
+Flow Compute Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+
+import pytest
+
+from app.utils.flow_compute import apply_set_variables, compute_value
+
+NOW = datetime(2025, 1, 31, 15, 30, tzinfo=timezone.utc)  # 12:30 in São Paulo
+
+
+class TestComputeValue:
+    """Tests for the value types of set_variable entries"""
+
+    def test_static_variable_and_template(self):
+        """Test the original value types keep working"""
+        variables = {"nome": "Ana", "sobrenome": "Lima"}
+        assert compute_value({"valueType": "static", "value": 3}, variables) == 3
+        assert compute_value({"valueType": "variable", "variableSource": "nome"}, variables) == "Ana"
+        assert compute_value({"valueType": "expression", "expression": "{{nome}} {{sobrenome}}"}, variables) == "Ana Lima"
+
+    @pytest.mark.parametrize("expression,expected", [
+        ("preco * quantidade", 60),
+        ("(tentativas + 1) % 3", 0),
+        ("nome + ' #' + pedido.id", "Ana #42"),
+    ])
+    def test_compute_uses_expression_language(self, expression, expected):
+        """Test arithmetic and concatenation over nested variables"""
+        variables = {"preco": 20, "quantidade": "3", "tentativas": 2, "nome": "Ana", "pedido": {"id": 42}}
+        assert compute_value({"valueType": "compute", "expression": expression}, variables) == expected
+
+    def test_increment_starts_at_zero(self):
+        """Test counters without a value start at 0 and honor the step"""
+        assert compute_value({"name": "tentativas", "valueType": "increment"}, {}) == 1
+        assert compute_value({"name": "tentativas", "valueType": "increment"}, {"tentativas": "2"}) == 3
+        assert compute_value({"name": "saldo", "valueType": "increment", "step": -0.5}, {"saldo": 1}) == 0.5
+
+    @pytest.mark.parametrize("entry,expected", [
+        ({"addAmount": 7, "addUnit": "days", "format": "%d/%m/%Y"}, "07/02/2025"),
+        ({"addAmount": 1, "addUnit": "months"}, "28/02/2025 12:30"),
+        ({"date": "{{pedido_em}}", "addAmount": 2, "addUnit": "hours", "format": "iso"}, "2025-01-10T11:00:00-03:00"),
+        ({"date": "15/03/2025", "addAmount": -1, "addUnit": "years", "format": "%d/%m/%Y"}, "15/03/2024"),
+    ])
+    def test_date_math(self, entry, expected):
+        """Test dates from now or a variable, plus an amount, in the entry's format"""
+        variables = {"pedido_em": "2025-01-10T12:00:00Z"}
+        assert compute_value({"valueType": "date", **entry}, variables, NOW) == expected
+
+    def test_random_pick(self):
+        """Test one of the options (or of a list variable) is picked"""
+        assert compute_value({"valueType": "random", "options": ["a", "b"]}, {}) in ("a", "b")
+        assert compute_value({"valueType": "random", "variableSource": "brindes"}, {"brindes": ["caneca"]}) == "caneca"
+
+
+class TestApplySetVariables:
+    """Tests for apply_set_variables()"""
+
+    def test_entries_run_in_order_and_failures_are_null(self):
+        """Test later entries see earlier results and failed entries become null"""
+        variables = {"preco": 10}
+        errors = apply_set_variables([
+            {"name": "quantidade", "valueType": "increment"},
+            {"name": "total", "valueType": "compute", "expression": "preco * quantidade"},
+            {"name": "quebrado", "valueType": "compute", "expression": "preco *"},
+            {"name": "vencimento", "valueType": "date", "date": "{{sem_data}}"},
+            {"name": "brinde", "valueType": "random", "options": []},
+        ], variables, NOW)
+
+        assert variables["total"] == 10
+        assert variables["quebrado"] is None and variables["vencimento"] is None and variables["brinde"] is None
+        assert [name for name, _ in errors] == ["quebrado", "vencimento", "brinde"]
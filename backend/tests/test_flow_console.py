@@ -0,0 +1,534 @@
+"""
+Flow Test Console Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy import func, select, update
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.models.chatbot import Chatbot, Flow, FlowConsoleSession, FlowVersion
+from app.models.organization import Organization
+from app.schemas.flow_console import FlowConsoleInput, FlowConsoleStart, FlowSimulationRequest
+from app.services.flow_console_service import FlowConsoleService
+from tests.conftest import OrganizationFactory, UserFactory
+
+ORG_ID = uuid4()
+USER_ID = uuid4()
+FLOW_ID = uuid4()
+
+
+def _node(node_id, node_type, **data):
+    return {"id": node_id, "data": {"nodeType": node_type, **data}}
+
+
+def _edge(source, target, **extra):
+    return {"id": f"{source}-{target}", "source": source, "target": target, **extra}
+
+
+AGE_FLOW = {
+    "nodes": [
+        _node("node-1", "start"),
+        _node("node-2", "message", messageText="Olá {{contact_name}}!"),
+        _node(
+            "node-3",
+            "question",
+            questionText="Qual a sua idade?",
+            responseType="number",
+            outputVariable="age",
+            validation={"maxAttempts": 3, "errorMessage": "Idade inválida"},
+        ),
+        _node("node-4", "condition", conditions=[{"variable": "age", "operator": ">=", "value": "18"}]),
+        _node("node-5", "api_call", url="https://crm.example.com"),
+        _node("node-6", "end", farewellMessage="Plano {{plan}} liberado"),
+        _node("node-7", "handoff", transferMessage="Vou chamar um atendente"),
+    ],
+    "edges": [
+        _edge("node-1", "node-2"),
+        _edge("node-2", "node-3"),
+        _edge("node-3", "node-4"),
+        _edge("node-4", "node-5", label="true"),
+        _edge("node-4", "node-7", label="false"),
+        _edge("node-5", "node-6"),
+    ],
+}
+
+
+class FakeRedis:
+    def __init__(self):
+        self.keys = {}
+
+    async def set(self, key, value, expire=None, nx=False):
+        self.keys[key] = value
+        return True
+
+    async def get(self, key):
+        return self.keys.get(key)
+
+    async def delete(self, *keys):
+        return sum(1 for key in keys if self.keys.pop(key, None) is not None)
+
+
+@pytest_asyncio.fixture
+async def db(db_session: AsyncSession):
+    """Test database with the organization and the user running the console"""
+    await OrganizationFactory.create_in_db(db_session, id=ORG_ID)
+    await UserFactory.create_in_db(db_session, id=USER_ID, organization_id=ORG_ID)
+    return db_session
+
+
+async def _console(db, canvas_data, redis=None, versions=None, default_locale=None):
+    chatbot = Chatbot(id=uuid4(), organization_id=ORG_ID, name="Bot Console")
+    flow = Flow(
+        id=FLOW_ID,
+        organization_id=ORG_ID,
+        chatbot_id=chatbot.id,
+        name="Console",
+        canvas_data=canvas_data,
+        default_locale=default_locale,
+    )
+    db.add_all([chatbot, flow])
+    for version, version_canvas in (versions or {}).items():
+        db.add(FlowVersion(
+            id=uuid4(),
+            organization_id=ORG_ID,
+            flow_id=FLOW_ID,
+            version=version,
+            canvas_data=version_canvas,
+            published_at=datetime.now(timezone.utc),
+        ))
+    await db.commit()
+    return FlowConsoleService(db, redis=redis or FakeRedis())
+
+
+class TestFlowConsole:
+    """Tests for chatting with a flow over the console"""
+
+    @pytest.mark.asyncio
+    async def test_start_runs_until_question(self, db):
+        console = await _console(db, AGE_FLOW)
+
+        session = await console.start_session(
+            FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart(variables={"contact_name": "Ana"})
+        )
+
+        assert [output["text"] for output in session["outputs"]] == ["Olá Ana!", "Qual a sua idade?"]
+        assert session["current_node_id"] == "node-3"
+        assert session["waiting_for_input"] is True
+        assert session["steps"][-1]["status"] == "waiting"
+
+    @pytest.mark.asyncio
+    async def test_invalid_answer_is_retried_then_saved(self, db):
+        console = await _console(db, AGE_FLOW)
+        session = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+        session_id = session["session_id"]
+
+        retry = await console.send_message(FLOW_ID, session_id, ORG_ID, FlowConsoleInput(text="abc"))
+        assert retry["outputs"] == [{"type": "error", "node_id": "node-3", "text": "Idade inválida", "content": {}}]
+        assert retry["waiting_for_input"] is True
+
+        done = await console.send_message(
+            FLOW_ID, session_id, ORG_ID, FlowConsoleInput(text="30", variables={"plan": "Gold"})
+        )
+
+        assert done["variables"] == {"plan": "Gold", "age": "30"}
+        assert [step["status"] for step in done["steps"]] == ["executed", "executed", "skipped", "executed"]
+        assert done["outputs"][-1]["text"] == "Plano Gold liberado"
+        assert done["finished"] is True
+        assert (await console.get_session(FLOW_ID, session_id, ORG_ID))["turns"] == 2
+
+        with pytest.raises(BadRequestException):
+            await console.send_message(FLOW_ID, session_id, ORG_ID, FlowConsoleInput(text="oi"))
+
+    @pytest.mark.asyncio
+    async def test_invalid_answers_follow_invalid_output(self, db):
+        """Test a question follows its invalid output after maxAttempts and saves the normalized value"""
+        document = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node(
+                    "node-2",
+                    "question",
+                    questionText="Qual o seu CPF?",
+                    responseType="cpf",
+                    outputVariable="cpf",
+                    validation={"maxAttempts": 2, "normalizedVariable": "cpf_numeros", "maxAttemptsMessage": "Vamos seguir"},
+                ),
+                _node("node-3", "end", farewellMessage="CPF {{cpf_numeros}} recebido"),
+                _node("node-4", "handoff", transferMessage="Vou chamar um atendente"),
+            ],
+            "edges": [
+                _edge("node-1", "node-2"),
+                _edge("node-2", "node-4", sourceHandle="invalid"),
+                _edge("node-2", "node-3"),
+            ],
+        }
+        console = await _console(db, document)
+
+        valid = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+        valid = await console.send_message(FLOW_ID, valid["session_id"], ORG_ID, FlowConsoleInput(text="529.982.247-25"))
+        assert valid["variables"] == {"cpf": "529.982.247-25", "cpf_numeros": "52998224725"}
+        assert valid["outputs"][-1]["text"] == "CPF 52998224725 recebido"
+
+        session = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+        retry = await console.send_message(FLOW_ID, session["session_id"], ORG_ID, FlowConsoleInput(text="111.111.111-11"))
+        invalid = await console.send_message(FLOW_ID, session["session_id"], ORG_ID, FlowConsoleInput(text="123"))
+        assert retry["outputs"][0]["text"] == "Por favor, digite um CPF válido."
+        assert [output["text"] for output in invalid["outputs"]] == ["Vamos seguir", "Vou chamar um atendente"]
+        assert "cpf" not in invalid["variables"]
+        assert invalid["current_node_id"] == "node-4"
+
+    @pytest.mark.asyncio
+    async def test_condition_false_branch_hands_off(self, db):
+        console = await _console(db, AGE_FLOW)
+        session = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+
+        session = await console.send_message(FLOW_ID, session["session_id"], ORG_ID, FlowConsoleInput(text="15"))
+
+        assert session["outputs"][-1]["type"] == "handoff"
+        assert session["outputs"][-1]["text"] == "Vou chamar um atendente"
+        assert session["current_node_id"] == "node-7"
+        assert session["finished"] is True
+
+    @pytest.mark.asyncio
+    async def test_loop_without_input_is_stopped(self, db):
+        loop = {
+            "nodes": [_node("node-1", "start"), _node("node-2", "set_variable"), _node("node-3", "delay")],
+            "edges": [_edge("node-1", "node-2"), _edge("node-2", "node-3"), _edge("node-3", "node-2")],
+        }
+        console = await _console(db, loop)
+
+        session = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+
+        assert session["finished"] is True
+        assert session["steps"][-1]["status"] == "error"
+
+    @pytest.mark.asyncio
+    async def test_loop_node_iterates_items(self, db):
+        """Test the loop body runs once per item up to maxIterations, then the done edge is followed"""
+        invoices = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node("node-2", "loop", arrayVariable="faturas", itemVariable="fatura", maxIterations=2),
+                _node("node-3", "message", messageText="Fatura {{fatura_numero}}: {{fatura_valor}}"),
+                _node("node-4", "end", farewellMessage="Fim"),
+            ],
+            "edges": [
+                _edge("node-1", "node-2"),
+                _edge("node-2", "node-3", sourceHandle="loop"),
+                _edge("node-2", "node-4", sourceHandle="done"),
+                _edge("node-3", "node-2"),
+            ],
+        }
+        console = await _console(db, invoices)
+        faturas = [{"numero": n, "valor": f"R$ {n}"} for n in ("1001", "1002", "1003")]
+
+        session = await console.start_session(
+            FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart(variables={"faturas": faturas})
+        )
+
+        assert [output["text"] for output in session["outputs"]] == [
+            "Fatura 1001: R$ 1001", "Fatura 1002: R$ 1002", "Fim"
+        ]
+        assert session["steps"][-2]["detail"] == "Max iterations (2) reached, 1 item(s) left"
+        assert session["finished"] is True
+
+    @pytest.mark.asyncio
+    async def test_intent_node_follows_mocked_intent(self, db):
+        """Test the intent node follows the mocked intent variable, or its fallback output"""
+        support = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node("node-2", "intent", intents=["segunda_via", "cancelamento"]),
+                _node("node-3", "end", farewellMessage="Segue seu boleto"),
+                _node("node-4", "end", farewellMessage="Que pena!"),
+                _node("node-5", "end", farewellMessage="Escolha uma opção"),
+            ],
+            "edges": [
+                _edge("node-1", "node-2"),
+                _edge("node-2", "node-3", sourceHandle="segunda_via"),
+                _edge("node-2", "node-4", sourceHandle="cancelamento"),
+                _edge("node-2", "node-5", sourceHandle="fallback"),
+            ],
+        }
+        console = await _console(db, support)
+
+        mocked = await console.start_session(
+            FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart(variables={"intent": "cancelamento"})
+        )
+        unmocked = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+
+        assert [output["text"] for output in mocked["outputs"]] == ["Que pena!"]
+        assert [output["text"] for output in unmocked["outputs"]] == ["Escolha uma opção"]
+        assert unmocked["steps"][1]["status"] == "skipped"
+
+    @pytest.mark.asyncio
+    async def test_session_is_scoped_to_flow_and_organization(self, db):
+        console = await _console(db, AGE_FLOW)
+        session = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+
+        with pytest.raises(NotFoundException):
+            await console.get_session(FLOW_ID, session["session_id"], uuid4())
+        with pytest.raises(NotFoundException):
+            await console.get_session(uuid4(), session["session_id"], ORG_ID)
+
+        await console.end_session(FLOW_ID, session["session_id"], ORG_ID)
+        with pytest.raises(NotFoundException):
+            await console.get_session(FLOW_ID, session["session_id"], ORG_ID)
+
+    @pytest.mark.asyncio
+    async def test_session_survives_redis_flush(self, db):
+        """Test a session missing from Redis is loaded from PostgreSQL and cached again"""
+        redis = FakeRedis()
+        console = await _console(db, AGE_FLOW, redis)
+        session = await console.start_session(FLOW_ID, ORG_ID, USER_ID, FlowConsoleStart())
+        session_id = session["session_id"]
+
+        redis.keys.clear()
+        done = await console.send_message(FLOW_ID, session_id, ORG_ID, FlowConsoleInput(text="30"))
+
+        assert done["variables"]["age"] == "30"
+        assert done["turns"] == 1
+        assert f"flow_console:{session_id}" in redis.keys
+
+        await db.execute(
+            update(FlowConsoleSession)
+            .where(FlowConsoleSession.session_id == session_id)
+            .values(expires_at=datetime.now(timezone.utc))
+        )
+        await db.commit()
+        redis.keys.clear()
+        with pytest.raises(NotFoundException):
+            await console.get_session(FLOW_ID, session_id, ORG_ID)
+
+
+class TestFlowSimulation:
+    """Tests for running a flow against scripted inputs"""
+
+    @pytest.mark.asyncio
+    async def test_transcript_has_variables_per_turn(self, db):
+        """Test every turn records the input, outputs and variables, and nothing is stored"""
+        redis = FakeRedis()
+        console = await _console(db, AGE_FLOW, redis=redis)
+
+        result = await console.simulate(FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({
+            "variables": {"contact_name": "Ana", "plan": "Gold"},
+            "inputs": [{"text": "abc"}, {"text": "30"}, {"text": "obrigado"}],
+            "expect": {"finished": True, "node_id": "node-6", "variables": {"age": "30"}},
+        }))
+
+        assert [turn["input"] for turn in result["turns"]] == [None, "abc", "30"]
+        assert [output["text"] for output in result["turns"][0]["outputs"]] == ["Olá Ana!", "Qual a sua idade?"]
+        assert result["turns"][1]["outputs"][0]["text"] == "Idade inválida"
+        assert "age" not in result["turns"][1]["variables"]
+        assert result["turns"][2]["variables"]["age"] == "30"
+        assert result["turns"][2]["outputs"][-1]["text"] == "Plano Gold liberado"
+        assert result["finished"] is True
+        assert result["unused_inputs"] == 1
+        assert result["passed"] is True
+        assert redis.keys == {}
+        assert (await db.execute(select(func.count()).select_from(FlowConsoleSession))).scalar() == 0
+
+    @pytest.mark.asyncio
+    async def test_failed_expectations_are_reported(self, db):
+        """Test unmet expectations make the simulation fail with readable messages"""
+        console = await _console(db, AGE_FLOW)
+
+        result = await console.simulate(FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({
+            "inputs": [{"text": "15"}],
+            "expect": {"node_id": "node-6", "variables": {"age": "18", "plan": "Gold"}},
+        }))
+
+        assert result["current_node_id"] == "node-7"
+        assert result["passed"] is False
+        assert result["failures"] == [
+            "Expected to stop on node node-6, stopped on node-7",
+            "Expected variable age='18', got '15'",
+            "Expected variable plan='Gold', it is not set",
+        ]
+
+    @pytest.mark.asyncio
+    async def test_runs_published_version(self, db):
+        """Test a published version is simulated instead of the draft when requested"""
+        published = {
+            "nodes": [_node("node-1", "start"), _node("node-2", "end", farewellMessage="Versão 1")],
+            "edges": [_edge("node-1", "node-2")],
+        }
+        console = await _console(db, AGE_FLOW, versions={1: published})
+
+        result = await console.simulate(FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest(version=1))
+
+        assert result["version"] == 1
+        assert result["turns"][0]["outputs"][0]["text"] == "Versão 1"
+        assert result["finished"] is True
+
+        with pytest.raises(NotFoundException):
+            await console.simulate(FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest(version=2))
+
+    @pytest.mark.asyncio
+    async def test_locale_variable_renders_translations(self, db):
+        """Test the locale variable picks node translations, falling back to the flow's default locale"""
+        translated = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node(
+                    "node-2",
+                    "question",
+                    questionText="Qual a sua idade?",
+                    responseType="number",
+                    validation={"maxAttempts": 3, "errorMessage": "Idade inválida"},
+                    translations={
+                        "es": {"questionText": "¿Cuántos años tienes?", "validation": {"errorMessage": "Edad inválida"}},
+                        "en": {"questionText": "How old are you?"},
+                    },
+                ),
+            ],
+            "edges": [_edge("node-1", "node-2")],
+        }
+        console = await _console(db, translated, default_locale="en")
+
+        spanish = await console.simulate(FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({
+            "variables": {"locale": "es-MX"},
+            "inputs": [{"text": "abc"}],
+        }))
+        german = await console.simulate(FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({
+            "variables": {"locale": "de"},
+        }))
+
+        assert [turn["outputs"][0]["text"] for turn in spanish["turns"]] == ["¿Cuántos años tienes?", "Edad inválida"]
+        assert german["turns"][0]["outputs"][0]["text"] == "How old are you?"
+
+    @pytest.mark.asyncio
+    async def test_split_is_stable_per_contact_or_mocked(self, db):
+        """Test split nodes draw the contact's variant, or follow the variant mocked in saveToVariable"""
+        offer = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node(
+                    "node-2",
+                    "split",
+                    saveToVariable="offer",
+                    variants=[{"id": "a", "percentage": 50}, {"id": "b", "percentage": 50}],
+                ),
+                _node("node-3", "end", farewellMessage="Oferta A"),
+                _node("node-4", "end", farewellMessage="Oferta B"),
+            ],
+            "edges": [
+                _edge("node-1", "node-2"),
+                _edge("node-2", "node-3", sourceHandle="a"),
+                _edge("node-2", "node-4", sourceHandle="b"),
+            ],
+        }
+        console = await _console(db, offer)
+
+        async def run(variables):
+            result = await console.simulate(
+                FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({"variables": variables})
+            )
+            return result["turns"][0]["outputs"][0]["text"], result["turns"][0]["variables"]["offer"]
+
+        drawn = {await run({"contact_id": "contact-1"}) for _ in range(3)}
+        assert len(drawn) == 1
+        text, variant = drawn.pop()
+        assert text == f"Oferta {variant.upper()}"
+        assert await run({"offer": "b"}) == ("Oferta B", "b")
+
+    @pytest.mark.asyncio
+    async def test_business_hours_follows_organization_hours_or_mock(self, db):
+        """Test business hours nodes branch on the organization's hours, holidays fall back to closed"""
+        support = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node("node-2", "business_hours", saveToVariable="expediente", nextOpenVariable="reabre_em"),
+                _node("node-3", "end", farewellMessage="Atendente"),
+                _node("node-4", "end", farewellMessage="Retornamos {{reabre_em}}"),
+            ],
+            "edges": [
+                _edge("node-1", "node-2"),
+                _edge("node-2", "node-3", sourceHandle="open"),
+                _edge("node-2", "node-4", sourceHandle="closed"),
+            ],
+        }
+        console = await _console(db, support)
+        today = datetime.now(timezone.utc).date()
+        days = ("monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday")
+        organization = await db.get(Organization, ORG_ID)
+        organization.settings = {
+            "business_hours": {
+                "timezone": "UTC",
+                "schedule": {day: {"enabled": True, "start": "00:00", "end": "23:59"} for day in days},
+                "holidays": [{"date": today.isoformat(), "name": "Feriado"}],
+            },
+        }
+        await db.commit()
+
+        async def run(variables):
+            result = await console.simulate(
+                FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({"variables": variables})
+            )
+            turn = result["turns"][0]
+            return turn["outputs"][0]["text"], turn["variables"]["expediente"]
+
+        tomorrow = (today + timedelta(days=1)).strftime("%d/%m/%Y")
+        assert await run({}) == (f"Retornamos {tomorrow} 00:00", "holiday")
+        assert await run({"expediente": "open"}) == ("Atendente", "open")
+
+    @pytest.mark.asyncio
+    async def test_sandbox_script_runs_and_branches_on_error(self, db):
+        """Test sandbox scripts run in the console and follow the error output when they fail"""
+        canvas = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node("node-2", "sandbox_script", code="return 100 / quantidade", outputVariable="unitario",
+                      errorHandling={"onError": "branch"}),
+                _node("node-3", "end", farewellMessage="Unitário {{unitario}}"),
+                _node("node-4", "end", farewellMessage="Erro no cálculo"),
+            ],
+            "edges": [
+                _edge("node-1", "node-2"),
+                _edge("node-2", "node-3", sourceHandle="success"),
+                _edge("node-2", "node-4", sourceHandle="error"),
+            ],
+        }
+        console = await _console(db, canvas)
+
+        async def run(variables):
+            result = await console.simulate(
+                FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({"variables": variables})
+            )
+            return result["turns"][0]["outputs"][0]["text"]
+
+        assert await run({"quantidade": 4}) == "Unitário 25"
+        assert await run({"quantidade": 0}) == "Erro no cálculo"
+
+    @pytest.mark.asyncio
+    async def test_set_variable_computes_values(self, db):
+        """Test set_variable nodes compute values with the engine's value types"""
+        counter = {
+            "nodes": [
+                _node("node-1", "start"),
+                _node("node-2", "set_variable", variables=[
+                    {"name": "tentativas", "valueType": "increment"},
+                    {"name": "total", "valueType": "compute", "expression": "preco * tentativas"},
+                    {"name": "quebrado", "valueType": "compute", "expression": "preco +"},
+                ]),
+                _node("node-3", "end", farewellMessage="Total {{total}}"),
+            ],
+            "edges": [_edge("node-1", "node-2"), _edge("node-2", "node-3")],
+        }
+        console = await _console(db, counter)
+
+        result = await console.simulate(FLOW_ID, ORG_ID, USER_ID, FlowSimulationRequest.model_validate({
+            "variables": {"preco": 15, "tentativas": 1},
+        }))
+
+        turn = result["turns"][0]
+        assert turn["outputs"][0]["text"] == "Total 30"
+        assert turn["variables"]["tentativas"] == 2
+        assert turn["variables"]["quebrado"] is None
@@ -0,0 +1,63 @@
+"""
+Flow Events Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.flow_events import (
+    EVENT_INVOICE_OVERDUE,
+    event_matches,
+    event_variables,
+    payload_value,
+    validate_event_type,
+)
+
+PAYLOAD = {
+    "invoice": {"number": "NF-100", "amount": "R$ 89,90", "currency": "BRL", "days_overdue": 3},
+    "items": [{"sku": "FIBRA-500"}],
+}
+
+
+class TestEventType:
+    """Tests for validate_event_type()"""
+
+    @pytest.mark.parametrize("event_type", ["invoice.overdue", "order.shipped", "erp_sync"])
+    def test_valid_names(self, event_type):
+        """Test lowercase words separated by dots are accepted"""
+        assert validate_event_type(event_type) == event_type
+
+    @pytest.mark.parametrize("event_type", ["", "Invoice.Overdue", "order..shipped", "order.", "pedido enviado"])
+    def test_invalid_names(self, event_type):
+        """Test other names are rejected"""
+        with pytest.raises(ValueError):
+            validate_event_type(event_type)
+
+
+class TestPayload:
+    """Tests for payload paths, conditions and flow variables"""
+
+    def test_payload_value(self):
+        """Test dotted paths reach nested values and list items"""
+        assert payload_value(PAYLOAD, "invoice.amount") == "R$ 89,90"
+        assert payload_value(PAYLOAD, "items.0.sku") == "FIBRA-500"
+        assert payload_value(PAYLOAD, "items.3.sku") is None
+        assert payload_value(PAYLOAD, "invoice.amount.cents") is None
+
+    def test_conditions_compare_as_text(self):
+        """Test every condition must match and lists accept any of their values"""
+        assert event_matches({}, PAYLOAD)
+        assert event_matches({"invoice.currency": "BRL", "invoice.days_overdue": "3"}, PAYLOAD)
+        assert event_matches({"invoice.days_overdue": [1, 3, 7]}, PAYLOAD)
+        assert not event_matches({"invoice.currency": "USD"}, PAYLOAD)
+        assert not event_matches({"invoice.customer": "ACME"}, PAYLOAD)
+
+    def test_event_variables(self):
+        """Test mapped values become variables next to the whole event"""
+        variables = event_variables(EVENT_INVOICE_OVERDUE, PAYLOAD, {"valor": "invoice.amount", "plano": "plan"})
+
+        assert variables["valor"] == "R$ 89,90"
+        assert variables["plano"] is None
+        assert variables["event"]["type"] == "invoice.overdue"
+        assert variables["event"]["invoice"]["number"] == "NF-100"
@@ -0,0 +1,65 @@
+"""
+Flow Expression Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.flow_expression import ExpressionError, evaluate_expression, parse_expression
+
+VARIABLES = {
+    "invoice": {"amount": "150.50", "items": [{"price": 49.9}, {"price": 100.6}]},
+    "customer": {"status": "active", "tags": ["vip", "b2b"]},
+    "email": "maria@empresa.com",
+    "tentativas": 2,
+    "cliente_vip": False,
+}
+
+
+class TestEvaluateExpression:
+    """Tests for evaluate_expression()"""
+
+    @pytest.mark.parametrize("expression, expected", [
+        ("invoice.amount > 100 && customer.status == 'active'", True),
+        ("invoice.amount > 200 || customer.status != 'active'", False),
+        ("(tentativas + 1) * 2 <= 6 and not cliente_vip", True),
+        ("!(tentativas == 2)", False),
+        ("email matches '^[^@]+@empresa\\\\.com$'", True),
+        ("customer.tags contains 'vip' and 'b2b' in customer.tags", True),
+        ("customer.status in ['gold', 'platinum']", False),
+        ("$.invoice.items[1].price >= 100", True),
+        ("invoice.items[0].price + invoice.items[1].price == 150.5", True),
+        ("desconhecida == null && !(desconhecida > 0)", True),
+        ("email contains 'EMPRESA'", False),
+        ("tentativas % 2 == 0 && -tentativas < 0", True),
+    ])
+    def test_expressions(self, expression, expected):
+        """Test comparisons, boolean logic, regex, contains, arithmetic and paths"""
+        assert evaluate_expression(expression, VARIABLES) is expected
+
+    def test_values(self):
+        """Test numbers keep their type and + joins text"""
+        assert evaluate_expression("tentativas * 3", VARIABLES) == 6
+        assert evaluate_expression("7 / 2", VARIABLES) == 3.5
+        assert evaluate_expression("'Olá, ' + customer.status", VARIABLES) == "Olá, active"
+
+    @pytest.mark.parametrize("expression", [
+        "",
+        "invoice.amount >",
+        "(tentativas > 1",
+        "tentativas = 2",
+        "import os",
+        "customer.status * 2",
+        "tentativas / 0",
+        "email matches '('",
+    ])
+    def test_invalid_expressions(self, expression):
+        """Test syntax and evaluation errors raise ExpressionError"""
+        with pytest.raises(ExpressionError):
+            evaluate_expression(expression, VARIABLES)
+
+    def test_length_is_limited(self):
+        """Test very long expressions are rejected before parsing"""
+        with pytest.raises(ExpressionError):
+            parse_expression("1 + " * 500 + "1")
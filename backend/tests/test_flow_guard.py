@@ -0,0 +1,79 @@
+"""
+Flow Guard Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+
+from app.utils.flow_guard import (
+    FALLBACK_END,
+    FALLBACK_HANDOFF,
+    GUARD_FLOW_STARTS,
+    GUARD_MESSAGES,
+    FlowGuardSettings,
+    detect_loop,
+    rate_windows,
+)
+
+
+class TestFlowGuardSettings:
+    """Tests for FlowGuardSettings.from_settings()"""
+
+    def test_defaults(self):
+        """Test chatbots without flow_guard get the engine defaults"""
+        guard = FlowGuardSettings.from_settings({})
+        assert guard.limits(GUARD_FLOW_STARTS) == (20, 100)
+        assert guard.limits(GUARD_MESSAGES) == (60, 300)
+        assert guard.fallback == FALLBACK_HANDOFF
+
+    def test_overrides_and_invalid_values(self):
+        """Test valid limits override the defaults and invalid ones are ignored"""
+        guard = FlowGuardSettings.from_settings({"flow_guard": {
+            "max_messages_per_hour": "10",
+            "max_messages_per_day": 0,
+            "max_cycle_repeats": "muitos",
+            "fallback": FALLBACK_END,
+            "fallback_message": "Vamos continuar depois.",
+        }})
+
+        assert guard.limits(GUARD_MESSAGES) == (10, 300)
+        assert guard.max_cycle_repeats == 5
+        assert guard.fallback == FALLBACK_END
+        assert guard.fallback_message == "Vamos continuar depois."
+        assert FlowGuardSettings.from_settings({"flow_guard": {"fallback": "ignore"}}).fallback == FALLBACK_HANDOFF
+
+
+class TestRateWindows:
+    """Tests for rate_windows()"""
+
+    def test_hour_and_day_keys_per_contact(self):
+        """Test each guard counts per contact in the current hour and day"""
+        now = datetime(2025, 11, 18, 14, 30, tzinfo=timezone.utc)
+        windows = rate_windows(GUARD_MESSAGES, "contact-1", now, FlowGuardSettings(max_messages_per_hour=5))
+
+        assert windows == [
+            ("flow_guard:contact-1:messages:h:2025111814", 3600, 5),
+            ("flow_guard:contact-1:messages:d:20251118", 86400, 300),
+        ]
+
+
+class TestDetectLoop:
+    """Tests for detect_loop()"""
+
+    def test_linear_paths_are_not_loops(self):
+        """Test paths without repetition pass"""
+        assert detect_loop([], 10, 5) is None
+        assert detect_loop(["start", "menu", "api", "msg"], 10, 5) is None
+
+    def test_node_repeated_too_often(self):
+        """Test one node running more than max_node_visits times"""
+        path = ["menu", "a", "menu", "b", "menu", "c", "menu"]
+        assert detect_loop(path, 3, 5) == "node menu ran 4 times without a contact message"
+        assert detect_loop(path, 4, 5) is None
+
+    def test_ping_pong_between_nodes(self):
+        """Test short cycles repeating without input"""
+        assert detect_loop(["start"] + ["a", "b"] * 3, 10, 3) == "nodes a -> b repeated 3 times without a contact message"
+        assert detect_loop(["a", "b", "c"] * 3, 10, 3) == "nodes a -> b -> c repeated 3 times without a contact message"
+        assert detect_loop(["a", "b"] * 2 + ["c"], 10, 3) is None
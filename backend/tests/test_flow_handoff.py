@@ -0,0 +1,126 @@
+"""
+Handoff Node Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.models.chatbot import Chatbot, Flow, Node
+from app.models.conversation import Conversation
+from app.services.whatsapp_service import HANDOFF_RETURN_KEY, WhatsAppService
+from tests.conftest import OrganizationFactory
+
+
+@pytest_asyncio.fixture
+async def engine(db_session: AsyncSession, monkeypatch):
+    """WhatsAppService on the test database with _advance_to_next_node faked"""
+    org = await OrganizationFactory.create_in_db(db_session)
+    service = WhatsAppService(db_session)
+    calls = []
+
+    async def advance_to_next_node(conversation, node, flow, incoming_message):
+        calls.append((node.node_id, flow.id))
+
+    monkeypatch.setattr(service, "_advance_to_next_node", advance_to_next_node)
+    return SimpleNamespace(service=service, db=db_session, org=org, calls=calls)
+
+
+async def _handoff_node(engine):
+    chatbot = Chatbot(id=uuid4(), organization_id=engine.org.id, name="Bot Atendimento")
+    flow = Flow(id=uuid4(), organization_id=engine.org.id, chatbot_id=chatbot.id, name="Atendimento")
+    node = Node(
+        id=uuid4(),
+        organization_id=engine.org.id,
+        flow_id=flow.id,
+        node_id="node-handoff",
+        node_type="handoff",
+    )
+    engine.db.add_all([chatbot, flow, node])
+    await engine.db.commit()
+    return flow, node
+
+
+async def _handed_off_conversation(engine, target=None) -> Conversation:
+    extra_data = {"handoff_context": "Cliente quer cancelar", "close_reason": "resolvido"}
+    if target is not None:
+        extra_data[HANDOFF_RETURN_KEY] = target
+    conversation = Conversation(
+        id=uuid4(),
+        organization_id=engine.org.id,
+        contact_id=uuid4(),
+        whatsapp_number_id=uuid4(),
+        status="closed",
+        is_bot_active=False,
+        extra_data=extra_data,
+    )
+    engine.db.add(conversation)
+    await engine.db.commit()
+    return conversation
+
+
+class TestReturnFromHandoff:
+    """Tests for returning the contact to the flow after the agent closes the conversation"""
+
+    @pytest.mark.asyncio
+    async def test_resumes_flow_after_handoff_node(self, engine):
+        """Test the bot is reactivated and follows the handoff node's outgoing edge"""
+        flow, node = await _handoff_node(engine)
+        conversation = await _handed_off_conversation(engine, {"flow_id": str(flow.id), "node_id": str(node.id)})
+
+        assert await engine.service.return_from_handoff(conversation.id, engine.org.id) is True
+
+        assert engine.calls == [("node-handoff", flow.id)]
+        await engine.db.refresh(conversation)
+        assert conversation.status == "open"
+        assert conversation.is_bot_active is True
+        assert conversation.active_flow_id == flow.id
+        assert conversation.current_node_id == node.id
+        assert HANDOFF_RETURN_KEY not in conversation.extra_data
+        assert conversation.extra_data["close_reason"] == "resolvido"
+
+    @pytest.mark.asyncio
+    async def test_without_return_target_nothing_happens(self, engine):
+        """Test conversations handed off without returnToFlow stay closed"""
+        conversation = await _handed_off_conversation(engine)
+
+        assert await engine.service.return_from_handoff(conversation.id, engine.org.id) is False
+        assert await engine.service.return_from_handoff(conversation.id, uuid4()) is False
+
+        assert engine.calls == []
+        await engine.db.refresh(conversation)
+        assert conversation.status == "closed"
+        assert conversation.is_bot_active is False
+
+    @pytest.mark.asyncio
+    async def test_deleted_flow_clears_return_target(self, engine):
+        """Test a return target whose flow no longer exists is dropped instead of retried"""
+        conversation = await _handed_off_conversation(engine, {"flow_id": str(uuid4()), "node_id": str(uuid4())})
+
+        assert await engine.service.return_from_handoff(conversation.id, engine.org.id) is False
+
+        assert engine.calls == []
+        await engine.db.refresh(conversation)
+        assert HANDOFF_RETURN_KEY not in conversation.extra_data
+        assert conversation.status == "closed"
+
+    @pytest.mark.asyncio
+    async def test_flow_of_other_org_is_not_resumed(self, engine):
+        """Test a return target pointing at another organization's flow is dropped"""
+        flow, node = await _handoff_node(engine)
+        conversation = await _handed_off_conversation(engine, {"flow_id": str(flow.id), "node_id": str(node.id)})
+        other_org = await OrganizationFactory.create_in_db(engine.db)
+        conversation.organization_id = other_org.id
+        await engine.db.commit()
+
+        assert await engine.service.return_from_handoff(conversation.id, other_org.id) is False
+
+        assert engine.calls == []
+        await engine.db.refresh(conversation)
+        assert HANDOFF_RETURN_KEY not in conversation.extra_data
+        assert conversation.status == "closed"
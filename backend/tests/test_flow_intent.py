@@ -0,0 +1,74 @@
+"""
+Intent Node Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.flow_intent import (
+    build_intent_prompt,
+    intent_menu,
+    match_menu_choice,
+    normalize_intents,
+    parse_intent_response,
+)
+
+INTENTS = normalize_intents([
+    {"name": "segunda_via", "label": "2ª via do boleto", "examples": ["perdi meu boleto"]},
+    {"name": "cancelamento", "label": "Cancelar plano", "description": "Quer encerrar o contrato"},
+    "suporte",
+    {"name": "fallback"},
+    {"label": "sem nome"},
+])
+
+
+class TestIntentPrompt:
+    """Tests for normalize_intents() and build_intent_prompt()"""
+
+    def test_normalize_intents(self):
+        """Test plain strings get a label and nameless or reserved intents are dropped"""
+        assert [intent["name"] for intent in INTENTS] == ["segunda_via", "cancelamento", "suporte"]
+        assert INTENTS[2]["label"] == "suporte"
+
+    def test_prompt_lists_intents(self):
+        """Test the system prompt carries names, descriptions and examples"""
+        system_prompt, prompt = build_intent_prompt(INTENTS, "quero cancelar")
+
+        assert "- segunda_via: 2ª via do boleto. Exemplos: perdi meu boleto" in system_prompt
+        assert "- cancelamento: Cancelar plano (Quer encerrar o contrato)" in system_prompt
+        assert '"confidence"' in system_prompt
+        assert prompt == "Mensagem: quero cancelar"
+
+
+class TestParseIntentResponse:
+    """Tests for parse_intent_response()"""
+
+    def test_json_answers(self):
+        """Test plain and fenced JSON, case-insensitive names and clamped confidence"""
+        assert parse_intent_response('{"intent": "cancelamento", "confidence": 0.92}', INTENTS) == ("cancelamento", 0.92)
+        assert parse_intent_response('```json\n{"intent": "SUPORTE", "confidence": 1.4}\n```', INTENTS) == ("suporte", 1.0)
+
+    def test_unknown_or_invalid_answers(self):
+        """Test unknown intents and unparseable answers give no intent"""
+        assert parse_intent_response('{"intent": "none", "confidence": 0.9}', INTENTS) == (None, 0.0)
+        assert parse_intent_response('{"intent": "suporte", "confidence": "alta"}', INTENTS) == (None, 0.0)
+        assert parse_intent_response("Acho que é cancelamento", INTENTS) == (None, 0.0)
+        assert parse_intent_response(None, INTENTS) == (None, 0.0)
+
+
+class TestIntentMenu:
+    """Tests for the low confidence menu"""
+
+    def test_menu_text(self):
+        """Test the menu numbers the intent labels"""
+        assert intent_menu(INTENTS, "Não entendi.") == (
+            "Não entendi.\n\n1. 2ª via do boleto\n2. Cancelar plano\n3. suporte"
+        )
+
+    def test_menu_choice(self):
+        """Test choices by number, label (accents and case ignored) or name"""
+        assert match_menu_choice("2", INTENTS) == "cancelamento"
+        assert match_menu_choice(" cancelar PLANO. ", INTENTS) == "cancelamento"
+        assert match_menu_choice("2a via do boleto", INTENTS) == "segunda_via"
+        assert match_menu_choice("suporte", INTENTS) == "suporte"
+        assert match_menu_choice("9", INTENTS) is None
+        assert match_menu_choice("outra coisa", INTENTS) is None
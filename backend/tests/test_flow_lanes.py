@@ -0,0 +1,91 @@
+"""
+Flow Lanes Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import asyncio
+
+import pytest
+
+from app.core.flow_lanes import LANE_BULK, LANE_PRIORITY, FlowLaneScheduler, flow_lane
+
+
+class TestFlowLane:
+    """Tests for flow_lane()"""
+
+    def test_vip_contacts_and_paid_plans(self):
+        """Test VIP contacts, priority tags and paid plans use the priority lane"""
+        options = {"priority_tags": ["vip"], "priority_plans": ["professional"]}
+
+        assert flow_lane(True, [], "free", **options) == LANE_PRIORITY
+        assert flow_lane(False, ["cliente", " VIP "], "free", **options) == LANE_PRIORITY
+        assert flow_lane(False, [], "professional", **options) == LANE_PRIORITY
+        assert flow_lane(False, [], "professional", plan_active=False, **options) == LANE_BULK
+        assert flow_lane(False, ["cliente"], "free", **options) == LANE_BULK
+
+
+async def _run_saturated(scheduler, lanes):
+    """Start one step per lane while the engine is full; returns the order they ran"""
+    order = []
+    await scheduler.acquire(LANE_BULK)  # engine saturated
+
+    async def step(index, lane):
+        async with scheduler.slot(lane):
+            order.append(index)
+
+    tasks = [asyncio.create_task(step(index, lane)) for index, lane in enumerate(lanes)]
+    await asyncio.sleep(0)
+    scheduler.release()
+    await asyncio.gather(*tasks)
+    return order
+
+
+class TestFlowLaneScheduler:
+    """Tests for FlowLaneScheduler"""
+
+    @pytest.mark.asyncio
+    async def test_runs_right_away_below_capacity(self):
+        """Test steps do not wait while slots are free"""
+        scheduler = FlowLaneScheduler(max_concurrency=2)
+
+        await scheduler.acquire(LANE_BULK)
+        await scheduler.acquire(LANE_PRIORITY)
+
+        assert scheduler.active == 2
+        scheduler.release()
+        assert scheduler.active == 1
+
+    @pytest.mark.asyncio
+    async def test_priority_steps_go_first_when_saturated(self):
+        """Test waiting priority steps start before bulk steps queued earlier"""
+        scheduler = FlowLaneScheduler(max_concurrency=1, starvation_limit=5)
+
+        order = await _run_saturated(scheduler, [LANE_BULK, LANE_BULK, LANE_PRIORITY, LANE_PRIORITY])
+
+        assert order == [2, 3, 0, 1]
+
+    @pytest.mark.asyncio
+    async def test_bulk_is_not_starved(self):
+        """Test a bulk step runs after starvation_limit priority steps in a row"""
+        scheduler = FlowLaneScheduler(max_concurrency=1, starvation_limit=2)
+
+        order = await _run_saturated(scheduler, [LANE_BULK] + [LANE_PRIORITY] * 4)
+
+        assert order == [1, 2, 0, 3, 4]
+
+    @pytest.mark.asyncio
+    async def test_cancelled_waiter_does_not_hold_a_slot(self):
+        """Test a step cancelled while waiting leaves the queue"""
+        scheduler = FlowLaneScheduler(max_concurrency=1)
+        await scheduler.acquire(LANE_BULK)
+
+        waiter = asyncio.create_task(scheduler.acquire(LANE_PRIORITY))
+        await asyncio.sleep(0)
+        waiter.cancel()
+        with pytest.raises(asyncio.CancelledError):
+            await waiter
+
+        scheduler.release()
+        assert scheduler.active == 0
+        assert scheduler.waiting(LANE_PRIORITY) == 0
@@ -0,0 +1,64 @@
+"""
+Flow Locale Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.flow_locale import locale_chain, localize_node_data, normalize_locale
+
+NODE = {
+    "nodeType": "question",
+    "questionText": "Qual o seu CPF?",
+    "validation": {"maxAttempts": 3, "errorMessage": "CPF inválido"},
+    "translations": {
+        "es_AR": {"questionText": "¿Cuál es tu DNI?", "validation": {"errorMessage": "DNI inválido"}},
+        "en-us": {"questionText": "What is your tax ID?"},
+    },
+}
+
+
+class TestLocaleChain:
+    """Tests for normalize_locale() and locale_chain()"""
+
+    @pytest.mark.parametrize("code,expected", [
+        ("pt-br", "pt_BR"),
+        ("PT_BR", "pt_BR"),
+        ("ES", "es"),
+        ("", None),
+        (None, None),
+    ])
+    def test_normalize_locale(self, code, expected):
+        """Test locales are canonicalized as language_REGION"""
+        assert normalize_locale(code) == expected
+
+    def test_chain_tries_contact_then_flow_default(self):
+        """Test the exact locale comes before its language, then the flow default"""
+        assert locale_chain("es-MX", "pt_BR") == ["es_MX", "es", "pt_BR", "pt"]
+        assert locale_chain(None, "pt") == ["pt"]
+        assert locale_chain("pt", "pt_BR") == ["pt", "pt_BR"]
+
+
+class TestLocalizeNodeData:
+    """Tests for localize_node_data()"""
+
+    def test_regional_variant_matches_language(self):
+        """Test a contact in es_MX gets the es_AR translation, with dict fields merged"""
+        data = localize_node_data(NODE, "es-MX", "pt_BR")
+
+        assert data["questionText"] == "¿Cuál es tu DNI?"
+        assert data["validation"] == {"maxAttempts": 3, "errorMessage": "DNI inválido"}
+        assert "translations" not in data
+
+    def test_falls_back_to_default_locale_then_own_content(self):
+        """Test unknown locales use the flow default, then the node's own fields"""
+        assert localize_node_data(NODE, "de", "en_US")["questionText"] == "What is your tax ID?"
+        assert localize_node_data(NODE, "de", "pt_BR")["questionText"] == "Qual o seu CPF?"
+        assert localize_node_data(NODE, None)["validation"]["errorMessage"] == "CPF inválido"
+
+    def test_nodes_without_translations_are_unchanged(self):
+        """Test the node data is returned as is when it has no translations"""
+        data = {"nodeType": "message", "messageText": "Olá"}
+
+        assert localize_node_data(data, "es") is data
@@ -0,0 +1,77 @@
+"""
+Flow Loop Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.flow_loop import LOOP_STATE_KEY, MAX_LOOP_ITERATIONS, loop_items, max_iterations, next_iteration
+
+INVOICES = [
+    {"numero": "1001", "valor": "R$ 99,90", "itens": [1, 2]},
+    {"numero": "1002", "valor": "R$ 49,90", "itens": []},
+    {"numero": "1003", "valor": "R$ 19,90", "itens": []},
+]
+
+
+class TestLoopItems:
+    """Tests for loop_items() and max_iterations()"""
+
+    def test_dotted_paths(self):
+        """Test arrays are found inside dicts and lists, anything else is empty"""
+        variables = {"api_response": {"faturas": INVOICES}, "nome": "Maria"}
+
+        assert loop_items({"arrayVariable": "api_response.faturas"}, variables) == INVOICES
+        assert loop_items({"arrayVariable": "api_response.faturas.0.itens"}, variables) == [1, 2]
+        assert loop_items({"arrayVariable": "nome"}, variables) == []
+        assert loop_items({"arrayVariable": "api_response.boletos"}, variables) == []
+
+    def test_max_iterations_is_capped(self):
+        """Test the node limit defaults to 10 and never exceeds MAX_LOOP_ITERATIONS"""
+        assert max_iterations({}) == 10
+        assert max_iterations({"maxIterations": "5"}) == 5
+        assert max_iterations({"maxIterations": 0}) == 10
+        assert max_iterations({"maxIterations": 10_000}) == MAX_LOOP_ITERATIONS
+
+
+class TestNextIteration:
+    """Tests for next_iteration()"""
+
+    def test_binds_each_item_until_the_limit(self):
+        """Test item, position and scalar fields are bound and the limit ends the loop"""
+        data = {"arrayVariable": "faturas", "itemVariable": "fatura", "indexVariable": "posicao", "maxIterations": 2}
+        variables = {"faturas": INVOICES}
+
+        assert next_iteration("node-4", data, variables) == (True, "Iteration 1/2")
+        assert variables["fatura"] == INVOICES[0]
+        assert variables["fatura_valor"] == "R$ 99,90"
+        assert "fatura_itens" not in variables
+        assert variables["posicao"] == 1
+
+        assert next_iteration("node-4", data, variables) == (True, "Iteration 2/2")
+        assert variables["fatura_numero"] == "1002"
+
+        run, detail = next_iteration("node-4", data, variables)
+        assert not run
+        assert detail == "Max iterations (2) reached, 1 item(s) left"
+        assert LOOP_STATE_KEY not in variables
+
+        # Entering the loop again starts from the first item
+        assert next_iteration("node-4", data, variables)[0]
+        assert variables["fatura_numero"] == "1001"
+
+    def test_condition_loop_stops_when_met_or_at_the_limit(self):
+        """Test condition loops run until the conditions are met, never past maxIterations"""
+        data = {"loopType": "condition", "maxIterations": 2}
+        variables = {}
+
+        assert next_iteration("node-2", data, variables, condition_met=False) == (True, "Iteration 1 (max 2)")
+        assert next_iteration("node-2", data, variables, condition_met=True) == (
+            False, "Conditions met after 1 iteration(s)"
+        )
+
+        next_iteration("node-2", data, variables, condition_met=False)
+        next_iteration("node-2", data, variables, condition_met=False)
+        run, detail = next_iteration("node-2", data, variables, condition_met=False)
+        assert not run
+        assert detail == "Max iterations (2) reached before the conditions were met"
+        assert variables["loop_index"] == 2
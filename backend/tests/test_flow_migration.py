@@ -0,0 +1,52 @@
+"""
+Flow Migration Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.flow_migration import migration_target, validate_migration
+
+NEW_NODES = ["start-1", "pergunta_documento", "confirmacao", "end-1"]
+
+
+class TestMigrationTarget:
+    """Tests for migration_target()"""
+
+    def test_continue_keeps_sessions(self):
+        """Test sessions stay on their version"""
+        assert migration_target("continue", "confirmacao", NEW_NODES, "start-1") is None
+
+    def test_map_node_ids_uses_same_id_or_map(self):
+        """Test same canvas ID, renamed IDs and removed nodes"""
+        node_map = {"pergunta_cpf": "pergunta_documento"}
+        assert migration_target("map_node_ids", "confirmacao", NEW_NODES, "start-1", node_map) == "confirmacao"
+        assert migration_target("map_node_ids", "pergunta_cpf", NEW_NODES, "start-1", node_map) == "pergunta_documento"
+        assert migration_target("map_node_ids", "pergunta_email", NEW_NODES, "start-1", node_map) is None
+
+    def test_restart_at_checkpoint_defaults_to_start(self):
+        """Test every session moves to the checkpoint, or the start node"""
+        assert migration_target(
+            "restart_at_checkpoint", "pergunta_email", NEW_NODES, "start-1", checkpoint_node_id="confirmacao"
+        ) == "confirmacao"
+        assert migration_target("restart_at_checkpoint", "pergunta_email", NEW_NODES, "start-1") == "start-1"
+
+
+class TestValidateMigration:
+    """Tests for validate_migration()"""
+
+    def test_valid_migrations(self):
+        """Test migrations pointing at nodes of the new version"""
+        assert validate_migration("continue", {}, None, NEW_NODES) == []
+        assert validate_migration("map_node_ids", {"pergunta_cpf": "pergunta_documento"}, None, NEW_NODES) == []
+        assert validate_migration("restart_at_checkpoint", {}, None, NEW_NODES) == []
+
+    @pytest.mark.parametrize("strategy,node_map,checkpoint", [
+        ("map_node_ids", {"pergunta_cpf": "pergunta_rg"}, None),
+        ("restart_at_checkpoint", {}, "pergunta_cpf"),
+        ("rewind", {}, None),
+    ])
+    def test_invalid_migrations(self, strategy, node_map, checkpoint):
+        """Test unknown targets, checkpoints and strategies"""
+        assert len(validate_migration(strategy, node_map, checkpoint, NEW_NODES)) == 1
@@ -0,0 +1,90 @@
+"""
+Flow Navigation Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.flow_navigation import (
+    BACK_BUTTON_ID,
+    MAX_NAVIGATION_HISTORY,
+    NAVIGATION_HISTORY_KEY,
+    NavigationSettings,
+    is_back_request,
+    pop_history,
+    push_history,
+)
+
+
+class TestNavigationSettings:
+    """Tests for NavigationSettings.from_settings()"""
+
+    def test_disabled_by_default(self):
+        """Test chatbots without navigation settings do not treat keywords as back"""
+        navigation = NavigationSettings.from_settings({})
+
+        assert not navigation.enabled
+        assert not is_back_request({"text": "voltar"}, navigation)
+
+    def test_keywords_are_normalized(self):
+        """Test configured keywords ignore case and accents"""
+        navigation = NavigationSettings.from_settings({"navigation": {"enabled": True, "keywords": ["Início", "Back"]}})
+
+        assert navigation.keywords == ["inicio", "back"]
+        assert is_back_request({"text": "  INICIO!"}, navigation)
+        assert not is_back_request({"text": "voltar"}, navigation)
+
+
+class TestIsBackRequest:
+    """Tests for is_back_request()"""
+
+    def test_back_button_and_keywords(self):
+        """Test the back button reply, its title and the keywords go back"""
+        navigation = NavigationSettings(enabled=True, keywords=["anterior"], back_button=True)
+
+        assert is_back_request({"interactive": {"type": "button_reply", "button_reply": {"id": BACK_BUTTON_ID}}}, navigation)
+        assert is_back_request({"text": "⬅️ Voltar"}, navigation)
+        assert is_back_request({"text": "Anterior"}, navigation)
+        assert not is_back_request({"text": "quero voltar amanhã"}, navigation)
+        assert not is_back_request({"interactive": {"type": "button_reply", "button_reply": {"id": "sim"}}}, navigation)
+
+
+class TestHistory:
+    """Tests for push_history() and pop_history()"""
+
+    def test_back_restores_variables_of_the_question(self):
+        """Test going back restores the variables from when the question was asked"""
+        context = {"nome": "Ana", "_flow_session": "s1"}
+        context[NAVIGATION_HISTORY_KEY] = push_history(context, "uuid-1", "node-cpf", "flow-1")
+        context["cpf"] = "123"
+        context[NAVIGATION_HISTORY_KEY] = push_history(context, "uuid-2", "node-plano", "flow-1")
+        context.update({"plano": "Pro", "_attempts_node-plano": 2})
+
+        entry, restored = pop_history(context, "flow-1")
+
+        assert entry["canvas_node_id"] == "node-plano"
+        assert restored["cpf"] == "123"
+        assert "plano" not in restored
+        assert "_attempts_node-plano" not in restored
+        assert restored["_flow_session"] == "s1"
+        assert [item["node_id"] for item in restored[NAVIGATION_HISTORY_KEY]] == ["uuid-1"]
+
+    def test_history_does_not_cross_flows(self):
+        """Test the previous question of another flow is not a back target"""
+        context = {"nome": "Ana"}
+        context[NAVIGATION_HISTORY_KEY] = push_history(context, "uuid-1", "node-1", "flow-1")
+
+        entry, restored = pop_history(context, "flow-2")
+
+        assert entry is None
+        assert restored is context
+        assert pop_history({}, "flow-1")[0] is None
+
+    def test_history_is_capped(self):
+        """Test only the latest questions are kept"""
+        context = {}
+        for index in range(MAX_NAVIGATION_HISTORY + 5):
+            context[NAVIGATION_HISTORY_KEY] = push_history(context, f"uuid-{index}", f"node-{index}", "flow-1")
+
+        history = context[NAVIGATION_HISTORY_KEY]
+        assert len(history) == MAX_NAVIGATION_HISTORY
+        assert history[-1]["node_id"] == f"uuid-{MAX_NAVIGATION_HISTORY + 4}"
@@ -0,0 +1,174 @@
+"""
+Flow Session Lock Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import asyncio
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.flow_session import (
+    LOCK_KEY_PREFIX,
+    FlowSessionLock,
+    FlowSessionLockError,
+    FlowSessionManager,
+    InMemoryLockStore,
+    build_lock_store,
+)
+from app.models.conversation import Conversation
+from app.services.whatsapp_service import WhatsAppService
+from tests.conftest import OrganizationFactory
+
+
+def _manager(**kwargs):
+    options = {"ttl_seconds": 60, "wait_seconds": 0.2, "poll_seconds": 0.01}
+    options.update(kwargs)
+    return FlowSessionManager(InMemoryLockStore(), **options)
+
+
+class TestFlowSessionManager:
+    """Tests for FlowSessionManager with the in-memory store"""
+
+    @pytest.mark.asyncio
+    async def test_sessions_of_a_conversation_run_one_at_a_time(self):
+        """Test a second holder waits for the first to release"""
+        manager = _manager(wait_seconds=1)
+        events = []
+
+        async def run(name):
+            async with manager.lock("conv-1"):
+                events.append(f"{name}:start")
+                await asyncio.sleep(0.05)
+                events.append(f"{name}:end")
+
+        await asyncio.gather(run("a"), run("b"))
+
+        assert events in (
+            ["a:start", "a:end", "b:start", "b:end"],
+            ["b:start", "b:end", "a:start", "a:end"],
+        )
+
+    @pytest.mark.asyncio
+    async def test_other_conversations_are_not_blocked(self):
+        """Test locks are per conversation"""
+        manager = _manager()
+
+        async with manager.lock("conv-1"):
+            async with manager.lock("conv-2") as lock:
+                assert lock.conversation_id == "conv-2"
+
+    @pytest.mark.asyncio
+    async def test_gives_up_after_waiting(self):
+        """Test a session held for longer than the wait raises FlowSessionLockError"""
+        manager = _manager(wait_seconds=0.05)
+
+        async with manager.lock("conv-1"):
+            with pytest.raises(FlowSessionLockError):
+                await manager.acquire("conv-1")
+
+    @pytest.mark.asyncio
+    async def test_fencing_tokens_increase(self):
+        """Test every acquisition gets a higher fencing token"""
+        manager = _manager()
+
+        async with manager.lock("conv-1") as first:
+            pass
+        async with manager.lock("conv-1") as second:
+            pass
+
+        assert second.token > first.token
+
+    @pytest.mark.asyncio
+    async def test_timed_out_lock_is_taken_over(self):
+        """Test a lock past its timeout goes to the next holder and the old one cannot release it"""
+        manager = _manager(ttl_seconds=0.05)
+
+        stale = await manager.acquire("conv-1")
+        await asyncio.sleep(0.06)
+        current = await manager.acquire("conv-1")
+        await manager.release(stale)
+
+        assert current.token > stale.token
+        assert not await manager.store.acquire(f"{LOCK_KEY_PREFIX}conv-1", "other", 60)
+
+    @pytest.mark.asyncio
+    async def test_held_lock_is_renewed(self):
+        """Test a session running longer than the timeout keeps its lock"""
+        manager = _manager(ttl_seconds=0.06)
+
+        async with manager.lock("conv-1") as lock:
+            await asyncio.sleep(0.15)
+            with pytest.raises(FlowSessionLockError):
+                await manager.acquire("conv-1")
+
+        assert not lock.lost
+
+    @pytest.mark.asyncio
+    async def test_renewal_of_a_taken_over_lock_fails(self):
+        """Test renewing a lock that timed out and went to another holder marks it lost"""
+        manager = _manager(ttl_seconds=0.05)
+
+        stale = await manager.acquire("conv-1")
+        await asyncio.sleep(0.06)
+        await manager.acquire("conv-1")
+
+        assert not await manager.renew(stale)
+        assert stale.lost
+
+
+class TestBuildLockStore:
+    """Tests for build_lock_store()"""
+
+    def test_unknown_backend(self):
+        """Test the memory backend is built and unknown backends are rejected"""
+        assert isinstance(build_lock_store("memory"), InMemoryLockStore)
+        with pytest.raises(ValueError):
+            build_lock_store("etcd")
+
+
+class TestFencedSessionWrites:
+    """Tests for flow session writes of WhatsAppService under a lost lock"""
+
+    @pytest.mark.asyncio
+    async def test_stale_token_write_is_rejected(self, db_session: AsyncSession, monkeypatch):
+        """Test a node run by a holder whose lock was taken over writes nothing and stops the flow"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        conversation = Conversation(
+            id=uuid4(),
+            organization_id=org.id,
+            contact_id=uuid4(),
+            whatsapp_number_id=uuid4(),
+            status="open",
+            is_bot_active=True,
+            context_variables={"plano": "basic"},
+            flow_session_fence=2,  # stamped by the process that took the session over
+        )
+        db_session.add(conversation)
+        await db_session.commit()
+
+        service = WhatsAppService(db_session)
+        service._session_lock = FlowSessionLock(str(conversation.id), "stale-owner", token=1)
+        advanced = []
+
+        async def advance(*args):
+            advanced.append(args)
+
+        monkeypatch.setattr(service, "_advance_to_next_node", advance)
+
+        await service._execute_set_variable(
+            conversation,
+            SimpleNamespace(node_id="set-1"),
+            SimpleNamespace(id=uuid4()),
+            None,
+            {"variables": [{"name": "plano", "valueType": "static", "value": "premium"}]},
+        )
+
+        await db_session.refresh(conversation)
+        assert conversation.context_variables == {"plano": "basic"}
+        assert service._session_lock.lost
+        with pytest.raises(FlowSessionLockError):
+            service._check_session(conversation)
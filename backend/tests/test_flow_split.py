@@ -0,0 +1,55 @@
+"""
+Flow Split Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.flow_split import assign_variant, experiment_key, split_variants
+
+
+class TestSplitVariants:
+    """Tests for split_variants()"""
+
+    def test_missing_percentages_split_evenly(self):
+        """Test variants without percentages share the traffic"""
+        variants = split_variants({"variants": [{"id": "a"}, {"id": "b", "label": "Desconto"}, {"label": "sem id"}]})
+        assert variants == [
+            {"id": "a", "label": "a", "percentage": 50.0},
+            {"id": "b", "label": "Desconto", "percentage": 50.0},
+        ]
+
+    def test_invalid_percentages_get_no_traffic(self):
+        """Test negative or non-numeric percentages count as zero"""
+        variants = split_variants({"variants": [
+            {"id": "a", "percentage": 80},
+            {"id": "b", "percentage": "x"},
+            {"id": "c", "percentage": -5},
+        ]})
+        assert [variant["percentage"] for variant in variants] == [80.0, 0.0, 0.0]
+
+    def test_experiment_key_defaults_to_flow_and_node(self):
+        """Test the assignment salt"""
+        assert experiment_key({}, "flow-1", "node-2") == "flow-1:node-2"
+        assert experiment_key({"experimentKey": "oferta"}, "flow-1", "node-2") == "oferta"
+
+
+class TestAssignVariant:
+    """Tests for assign_variant()"""
+
+    VARIANTS = split_variants({"variants": [{"id": "a", "percentage": 70}, {"id": "b", "percentage": 30}]})
+
+    def test_assignment_is_stable_per_contact(self):
+        """Test the same contact always gets the same variant"""
+        first = assign_variant(self.VARIANTS, "oferta", "contact-1")
+        assert all(assign_variant(self.VARIANTS, "oferta", "contact-1") == first for _ in range(5))
+
+    def test_traffic_follows_percentages(self):
+        """Test the share of contacts per variant is close to the percentages"""
+        drawn = [assign_variant(self.VARIANTS, "oferta", f"contact-{index}")["id"] for index in range(2000)]
+        assert 0.65 < drawn.count("a") / len(drawn) < 0.75
+
+    def test_variants_without_traffic_are_never_drawn(self):
+        """Test a 0% variant is skipped and no traffic at all returns None"""
+        variants = split_variants({"variants": [{"id": "a", "percentage": 0}, {"id": "b", "percentage": 100}]})
+        assert {assign_variant(variants, "k", index)["id"] for index in range(50)} == {"b"}
+        assert assign_variant(split_variants({"variants": [{"id": "a", "percentage": 0}]}), "k", 1) is None
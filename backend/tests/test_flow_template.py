@@ -0,0 +1,79 @@
+"""
+Flow Template Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import date
+
+from app.utils.flow_template import render_text
+
+VARIABLES = {
+    "nome": "maria silva",
+    "contato": {"nome": "joão", "email": "joao@example.com"},
+    "pedido": {"itens": [{"nome": "Camiseta"}, {"nome": "Boné"}], "total": 1234.5},
+    "tags": ["vip", "gold"],
+    "vencimento": "2025-11-20",
+    "vazio": "",
+}
+
+
+class TestRenderText:
+    """Tests for render_text() placeholders"""
+
+    def test_paths(self):
+        """Test plain variables, nested paths and list indexes"""
+        assert render_text("Olá {{nome}}", VARIABLES) == "Olá maria silva"
+        assert render_text("{{ contato.email }}", VARIABLES) == "joao@example.com"
+        assert render_text("{{pedido.itens[1].nome}}", VARIABLES) == "Boné"
+        assert render_text("Sem variáveis", VARIABLES) == "Sem variáveis"
+        assert render_text(None, VARIABLES) == ""
+
+    def test_missing_variables_render_empty(self):
+        """Test missing variables do not leak the placeholder"""
+        assert render_text("Olá {{apelido}}!", VARIABLES) == "Olá !"
+        assert render_text("{{contato.telefone}}", VARIABLES) == ""
+
+    def test_org_placeholders_are_kept(self):
+        """Test {{org.*}} is left for the organization resolver"""
+        assert render_text("Token {{org.secrets.API_KEY}}", VARIABLES) == "Token {{org.secrets.API_KEY}}"
+
+
+class TestFilters:
+    """Tests for the template filters"""
+
+    def test_text_filters(self):
+        """Test case filters, trim, truncate and chaining"""
+        assert render_text("{{ nome | upper }}", VARIABLES) == "MARIA SILVA"
+        assert render_text("{{ nome | title }}", VARIABLES) == "Maria Silva"
+        assert render_text("{{ contato.nome | capitalize }}", VARIABLES) == "João"
+        assert render_text("{{ nome | truncate:8 }}", VARIABLES) == "maria s…"
+        assert render_text("{{ nome | truncate:50 }}", VARIABLES) == "maria silva"
+        assert render_text("{{ nome | title | truncate:5 }}", VARIABLES) == "Mari…"
+
+    def test_default(self):
+        """Test default fills missing and empty values only"""
+        assert render_text("{{ plano | default:'Básico' | upper }}", VARIABLES) == "BÁSICO"
+        assert render_text("{{ vazio | default:\"n/a\" }}", VARIABLES) == "n/a"
+        assert render_text("{{ nome | default:'x' }}", VARIABLES) == "maria silva"
+
+    def test_currency_and_number(self):
+        """Test BRL formatting, pt-BR numbers and other currencies"""
+        assert render_text("{{ pedido.total | currency }}", VARIABLES) == "R$ 1.234,50"
+        assert render_text("{{ valor | currency }}", {"valor": "1.234,5"}) == "R$ 1.234,50"
+        assert render_text("{{ valor | currency }}", {"valor": -10}) == "-R$ 10,00"
+        assert render_text("{{ pedido.total | currency:USD }}", VARIABLES) == "US$ 1,234.50"
+        assert render_text("{{ pedido.total | number:1 }}", VARIABLES) == "1.234,5"
+
+    def test_date(self):
+        """Test ISO strings, date objects and custom formats"""
+        assert render_text("{{ vencimento | date }}", VARIABLES) == "20/11/2025"
+        assert render_text("{{ dia | date:'%d/%m' }}", {"dia": date(2025, 1, 5)}) == "05/01"
+        assert render_text("{{ vencimento | date:'%Y' }}", VARIABLES) == "2025"
+
+    def test_join_and_invalid_filters(self):
+        """Test join, unknown filters and failing filters keep the value"""
+        assert render_text("{{ tags | join }}", VARIABLES) == "vip, gold"
+        assert render_text("{{ tags | join:' / ' }}", VARIABLES) == "vip / gold"
+        assert render_text("{{ nome | shout }}", VARIABLES) == "maria silva"
+        assert render_text("{{ nome | currency }}", VARIABLES) == "maria silva"
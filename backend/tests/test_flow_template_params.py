@@ -0,0 +1,124 @@
+"""
+Flow Template Parameters Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import json
+from pathlib import Path
+
+from app.utils.flow_template_params import (
+    canvas_fingerprint,
+    instantiate,
+    is_newer_version,
+    resolve_parameters,
+    validate_template,
+)
+
+TEMPLATES_DIR = Path(__file__).parent.parent / "app" / "templates" / "flows"
+
+PARAMETERS = [
+    {"name": "company_name", "label": "Empresa", "type": "text", "required": True},
+    {"name": "plans", "label": "Planos", "type": "list", "default": ["Básico", "Pro"]},
+    {"name": "erp_provider", "label": "ERP", "type": "choice", "options": ["bling", "tiny"]},
+    {"name": "max_installments", "label": "Parcelas", "type": "number", "default": 12},
+]
+
+
+class TestValidateTemplate:
+    """Tests for validate_template()"""
+
+    def test_gallery_templates_are_valid(self):
+        """Test every shipped template declares the parameters its placeholders use"""
+        for template_file in TEMPLATES_DIR.glob("*.json"):
+            template_data = json.loads(template_file.read_text(encoding="utf-8"))
+            assert validate_template(template_data) == [], template_file.name
+
+    def test_undeclared_placeholders_and_bad_parameters(self):
+        """Test placeholders need a declaration and choices need options"""
+        errors = validate_template({
+            "parameters": [{"name": "erp", "type": "choice"}, {"name": "erp"}],
+            "flow_data": {"canvas_data": {"nodes": [{"data": {"text": "Olá da [[empresa]]"}}]}},
+        })
+
+        assert errors == [
+            "Parameter 'erp' is a choice without options",
+            "Parameter 'erp' is declared twice",
+            "Placeholder [[empresa]] is not a declared parameter",
+        ]
+
+
+class TestResolveParameters:
+    """Tests for resolve_parameters()"""
+
+    def test_values_defaults_and_coercion(self):
+        """Test given values are coerced and missing ones take the default"""
+        values, errors = resolve_parameters(PARAMETERS, {
+            "company_name": "Acme",
+            "plans": "Start, Growth",
+            "erp_provider": "bling",
+            "max_installments": "6",
+        })
+
+        assert errors == []
+        assert values == {
+            "company_name": "Acme",
+            "plans": ["Start", "Growth"],
+            "erp_provider": "bling",
+            "max_installments": 6,
+        }
+        assert resolve_parameters(PARAMETERS, {"company_name": "Acme"})[0]["plans"] == ["Básico", "Pro"]
+
+    def test_invalid_values(self):
+        """Test required, unknown and out-of-options values are reported"""
+        _, errors = resolve_parameters(PARAMETERS, {"erp_provider": "sap", "cor": "azul"})
+
+        assert errors == [
+            "Unknown parameter 'cor'",
+            "Parameter 'company_name' is required",
+            "Parameter 'erp_provider' must be one of: bling, tiny",
+        ]
+
+
+class TestInstantiate:
+    """Tests for instantiate()"""
+
+    def test_placeholders_are_replaced(self):
+        """Test inline placeholders become text and whole-string ones keep the value type"""
+        flow_data = {
+            "nodes": [{"data": {
+                "text": "Bem-vindo à [[company_name]]! Planos: [[plans]]. Olá {{contact_name}}",
+                "options": "[[plans]]",
+                "provider": "[[ erp_provider ]]",
+                "other": "[[not_a_parameter]]",
+            }}],
+        }
+
+        result = instantiate(flow_data, {"company_name": "Acme", "plans": ["Start", "Growth"], "erp_provider": "bling"})
+
+        assert result["nodes"][0]["data"] == {
+            "text": "Bem-vindo à Acme! Planos: Start, Growth. Olá {{contact_name}}",
+            "options": ["Start", "Growth"],
+            "provider": "bling",
+            "other": "[[not_a_parameter]]",
+        }
+        assert flow_data["nodes"][0]["data"]["options"] == "[[plans]]"
+
+
+class TestUpgradeHelpers:
+    """Tests for canvas_fingerprint() and is_newer_version()"""
+
+    def test_fingerprint_ignores_layout(self):
+        """Test moving nodes is not an edit, changing their data is"""
+        canvas = {"nodes": [{"id": "n1", "position": {"x": 0, "y": 0}, "data": {"text": "Oi"}}], "edges": []}
+        moved = {"nodes": [{"id": "n1", "position": {"x": 90, "y": 40}, "data": {"text": "Oi"}}], "edges": []}
+        edited = {"nodes": [{"id": "n1", "position": {"x": 0, "y": 0}, "data": {"text": "Olá"}}], "edges": []}
+
+        assert canvas_fingerprint(canvas) == canvas_fingerprint(moved)
+        assert canvas_fingerprint(canvas) != canvas_fingerprint(edited)
+
+    def test_versions_compare_numerically(self):
+        """Test template versions compare part by part"""
+        assert is_newer_version("1.10", "1.9")
+        assert is_newer_version("2", "1.9.9")
+        assert not is_newer_version("1.1", "1.1")
@@ -0,0 +1,211 @@
+"""
+Flow Timeout Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.models.chatbot import Chatbot, Flow, FlowWait, Node
+from app.models.conversation import Conversation
+from app.services.whatsapp_service import SUBFLOW_STACK_KEY, WhatsAppService
+from app.utils.flow_timeout import FALLBACK_GRACE_MINUTES, FLOW_TIMEOUT_FIRED_KEY, FlowTimeoutSettings
+from tests.conftest import OrganizationFactory
+
+NOW = datetime(2025, 11, 18, 12, 0, tzinfo=timezone.utc)
+
+
+class TestFlowTimeoutSettings:
+    """Tests for FlowTimeoutSettings"""
+
+    def test_parses_chatbot_settings(self):
+        """Test limits and fallbacks are read from settings["session_timeout"]"""
+        timeouts = FlowTimeoutSettings.from_settings({"session_timeout": {
+            "timeout_minutes": "30",
+            "max_duration_minutes": 1440,
+            "fallback_node_id": "node-still-there",
+            "timeout_message": "Encerrando por inatividade.",
+            "close_conversation": True,
+        }})
+
+        assert timeouts.enabled and timeouts.has_fallback
+        assert timeouts.timeout_at(NOW) == NOW + timedelta(minutes=30)
+        assert timeouts.expires_at(NOW) == NOW + timedelta(days=1)
+        assert timeouts.fallback_timeout_at(NOW) == NOW + timedelta(minutes=30)
+        assert timeouts.close_conversation is True
+
+    @pytest.mark.parametrize("chatbot_settings", [
+        None,
+        {},
+        {"session_timeout": "30"},
+        {"session_timeout": {"timeout_minutes": 0, "max_duration_minutes": "abc"}},
+    ])
+    def test_missing_or_invalid_limits_disable_timeouts(self, chatbot_settings):
+        """Test sessions without valid limits never time out"""
+        timeouts = FlowTimeoutSettings.from_settings(chatbot_settings)
+
+        assert not timeouts.enabled and not timeouts.has_fallback
+        assert timeouts.timeout_at(NOW) is None
+        assert timeouts.expires_at(NOW) is None
+        assert timeouts.fallback_timeout_at(NOW) == NOW + timedelta(minutes=FALLBACK_GRACE_MINUTES)
+
+
+@pytest_asyncio.fixture
+async def engine(db_session: AsyncSession, monkeypatch):
+    """WhatsAppService on the test database with node execution and sending faked"""
+    org = await OrganizationFactory.create_in_db(db_session)
+    service = WhatsAppService(db_session)
+    calls = []
+
+    async def execute_node(conversation, node, flow, incoming_message):
+        calls.append(("execute", node.node_id))
+
+    async def start_flow(conversation, flow, incoming_message):
+        calls.append(("start", flow.id))
+
+    async def send_error_message(conversation, error_text):
+        calls.append(("send", error_text))
+
+    async def finalize_flow(conversation):
+        calls.append(("finalize", conversation.id))
+
+    monkeypatch.setattr(service, "_execute_node", execute_node)
+    monkeypatch.setattr(service, "_start_flow", start_flow)
+    monkeypatch.setattr(service, "_send_error_message", send_error_message)
+    monkeypatch.setattr(service, "_finalize_flow", finalize_flow)
+    return SimpleNamespace(service=service, db=db_session, org=org, calls=calls)
+
+
+async def _flow(engine, name, organization_id=None, chatbot_settings=None) -> Flow:
+    organization_id = organization_id or engine.org.id
+    chatbot = Chatbot(id=uuid4(), organization_id=organization_id, name=f"Bot {name}", settings=chatbot_settings or {})
+    flow = Flow(id=uuid4(), organization_id=organization_id, chatbot_id=chatbot.id, name=name)
+    engine.db.add_all([chatbot, flow])
+    await engine.db.commit()
+    return flow
+
+
+async def _node(engine, flow, node_id, node_type="question") -> Node:
+    node = Node(id=uuid4(), organization_id=flow.organization_id, flow_id=flow.id, node_id=node_id, node_type=node_type)
+    engine.db.add(node)
+    await engine.db.commit()
+    return node
+
+
+async def _conversation(engine, session_timeout, node_type="question", context=None):
+    flow = await _flow(engine, "Atendimento", chatbot_settings={"session_timeout": session_timeout})
+    node = await _node(engine, flow, "node-cpf", node_type)
+    conversation = Conversation(
+        id=uuid4(),
+        organization_id=engine.org.id,
+        contact_id=uuid4(),
+        whatsapp_number_id=uuid4(),
+        status="open",
+        is_bot_active=True,
+        active_chatbot_id=flow.chatbot_id,
+        active_flow_id=flow.id,
+        current_node_id=node.id,
+        context_variables=dict(context or {}),
+    )
+    engine.db.add(conversation)
+    await engine.db.commit()
+    return conversation, flow
+
+
+class TestRunFlowTimeout:
+    """Tests for WhatsAppService._run_flow_timeout()"""
+
+    @pytest.mark.asyncio
+    async def test_first_timeout_runs_fallback_node(self, engine):
+        """Test the fallback node runs once and gets its own timeout"""
+        conversation, flow = await _conversation(engine, {"timeout_minutes": 30, "fallback_node_id": "node-still-there"})
+        fallback = await _node(engine, flow, "node-still-there")
+        wait = FlowWait(
+            id=uuid4(),
+            organization_id=engine.org.id,
+            conversation_id=conversation.id,
+            flow_id=flow.id,
+            node_id=conversation.current_node_id,
+            resume_at=NOW,
+        )
+        engine.db.add(wait)
+        await engine.db.commit()
+
+        assert await engine.service._run_flow_timeout(conversation, "timeout") == "fallback"
+
+        assert engine.calls == [("execute", "node-still-there")]
+        assert conversation.current_node_id == fallback.id
+        assert conversation.context_variables[FLOW_TIMEOUT_FIRED_KEY] is True
+        assert conversation.flow_timeout_at > datetime.now(timezone.utc) + timedelta(minutes=29)
+        await engine.db.refresh(wait)
+        assert wait.status == "cancelled"
+
+    @pytest.mark.asyncio
+    async def test_second_timeout_sends_message_and_closes(self, engine):
+        """Test a session that times out again after its fallback is finished"""
+        conversation, _ = await _conversation(
+            engine,
+            {
+                "timeout_minutes": 30,
+                "fallback_node_id": "node-still-there",
+                "timeout_message": "Encerrando o atendimento por inatividade.",
+                "close_conversation": True,
+            },
+            context={FLOW_TIMEOUT_FIRED_KEY: True},
+        )
+
+        assert await engine.service._run_flow_timeout(conversation, "timeout") == "finished"
+
+        assert engine.calls == [
+            ("send", "Encerrando o atendimento por inatividade."),
+            ("finalize", conversation.id),
+        ]
+        assert conversation.status == "closed"
+        assert conversation.closed_at is not None
+
+    @pytest.mark.asyncio
+    async def test_expired_session_starts_fallback_flow(self, engine):
+        """Test the fallback flow replaces the session, dropping pending sub-flow returns"""
+        fallback_flow = await _flow(engine, "Ainda está aí?")
+        conversation, _ = await _conversation(
+            engine,
+            {"max_duration_minutes": 60, "fallback_flow_id": str(fallback_flow.id)},
+            context={SUBFLOW_STACK_KEY: [{"flow_id": str(uuid4()), "node_id": "node-1"}]},
+        )
+
+        assert await engine.service._run_flow_timeout(conversation, "expired") == "fallback"
+
+        assert engine.calls == [("start", fallback_flow.id)]
+        assert SUBFLOW_STACK_KEY not in conversation.context_variables
+        assert conversation.flow_expires_at is None
+        assert conversation.flow_timeout_at is not None
+
+    @pytest.mark.asyncio
+    async def test_fallback_of_other_org_is_ignored(self, engine):
+        """Test a fallback flow of another organization is never started"""
+        other_org = await OrganizationFactory.create_in_db(engine.db)
+        other_flow = await _flow(engine, "Outra empresa", organization_id=other_org.id)
+        conversation, _ = await _conversation(engine, {"timeout_minutes": 10, "fallback_flow_id": str(other_flow.id)})
+
+        assert await engine.service._run_flow_timeout(conversation, "timeout") == "finished"
+
+        assert engine.calls == [("finalize", conversation.id)]
+        assert conversation.status == "open"
+
+    @pytest.mark.asyncio
+    async def test_wait_nodes_only_expire(self, engine):
+        """Test idle timeouts are postponed while a wait node is pausing the flow"""
+        conversation, _ = await _conversation(
+            engine, {"timeout_minutes": 10, "fallback_node_id": "node-still-there"}, node_type="wait"
+        )
+
+        assert await engine.service._run_flow_timeout(conversation, "timeout") == "postponed"
+
+        assert engine.calls == []
+        assert conversation.flow_timeout_at > datetime.now(timezone.utc)
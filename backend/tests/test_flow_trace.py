@@ -0,0 +1,67 @@
+"""
+Flow Trace Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from types import SimpleNamespace
+
+from app.utils.flow_trace import (
+    EVENT_NODE_EXECUTED,
+    MAX_VALUE_LENGTH,
+    input_data,
+    sent_data,
+    trace_event,
+    trace_value,
+    variable_changes,
+)
+
+
+class TestVariableChanges:
+    """Tests for variable_changes()"""
+
+    def test_set_changed_and_removed(self):
+        """Test new, changed and removed variables are reported in name order"""
+        before = {"cpf": "123", "plano": "basic", "cupom": "X10"}
+        after = {"cpf": "123", "plano": "pro", "email": "ana@example.com"}
+
+        assert variable_changes(before, after) == [
+            {"name": "cupom", "old": "X10", "new": None, "removed": True},
+            {"name": "email", "old": None, "new": "ana@example.com"},
+            {"name": "plano", "old": "basic", "new": "pro"},
+        ]
+
+    def test_internal_state_is_ignored(self):
+        """Test "_" variables (execution path, loops, session) are not logged"""
+        before = {"_execution_path": ["a"], "_flow_session": "s1"}
+        after = {"_execution_path": ["a", "b"], "_loops": {}, "nome": None}
+
+        assert variable_changes(before, after) == [{"name": "nome", "old": None, "new": None}]
+        assert variable_changes(None, None) == []
+
+
+class TestTraceValues:
+    """Tests for trace_value(), trace_event(), input_data() and sent_data()"""
+
+    def test_long_values_are_truncated(self):
+        """Test long strings and structures are cut to MAX_VALUE_LENGTH"""
+        assert trace_value("x" * (MAX_VALUE_LENGTH + 5)) == "x" * MAX_VALUE_LENGTH + "…"
+        assert isinstance(trace_value({"items": ["y" * MAX_VALUE_LENGTH]}), str)
+        assert trace_value({"total": 3}) == {"total": 3}
+
+    def test_event_payloads(self):
+        """Test node, inbound message and sent message data"""
+        node = SimpleNamespace(node_id="node-3", node_type="question")
+        assert trace_event(EVENT_NODE_EXECUTED, node) == {
+            "event_type": "node_executed", "node_id": "node-3", "node_type": "question", "data": {},
+        }
+
+        message = SimpleNamespace(id="m-1", message_type="text", content={"text": "quero o plano pro"})
+        assert input_data(message) == {"message_id": "m-1", "message_type": "text", "text": "quero o plano pro"}
+
+        assert sent_data("send_text_message", ("5511999990000", "Qual o seu CPF?"), {}) == {
+            "method": "send_text_message", "text": "Qual o seu CPF?",
+        }
+        assert sent_data("send_interactive_buttons", (), {"to": "5511999990000", "body_text": "Escolha"}) == {
+            "method": "send_interactive_buttons", "text": "Escolha",
+        }
@@ -0,0 +1,113 @@
+"""
+Flow Export/Import Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+from pydantic import ValidationError
+
+from app.schemas.chatbot import FlowImport
+from app.utils.flow_transfer import collect_references, remap_references
+
+CANVAS = {
+    "nodes": [
+        {"id": "start-1", "data": {"nodeType": "start"}},
+        {"id": "jump-1", "data": {"nodeType": "jump", "jumpType": "flow", "targetFlowId": "flow-a"}},
+        {"id": "handoff-1", "data": {"nodeType": "handoff", "queueId": "queue-a", "agentId": "agent-a"}},
+        {"id": "sub-1", "data": {"nodeType": "subflow", "targetFlowId": "flow-a"}},
+    ],
+    "edges": [
+        {"id": "e1", "source": "start-1", "target": "jump-1"},
+        {"id": "e2", "source": "start-1", "target": "handoff-1", "sourceHandle": "else"},
+    ],
+    "viewport": {"x": 0, "y": 0, "zoom": 1},
+}
+
+
+class TestReferences:
+    """Tests for collecting and remapping tenant references"""
+
+    def test_collect_references_is_unique_and_ordered(self):
+        """Test each referenced record is listed once, in canvas order"""
+        assert collect_references(CANVAS) == [
+            ("flow", "flow-a"),
+            ("queue", "queue-a"),
+            ("agent", "agent-a"),
+        ]
+        assert collect_references(None) == []
+
+    def test_remap_replaces_ids_without_touching_the_source(self):
+        """Test mapped ids are replaced in a copy of the canvas"""
+        canvas, unresolved = remap_references(
+            CANVAS, {"flow-a": "flow-b", "queue-a": "queue-b", "agent-a": "agent-b"}
+        )
+
+        assert unresolved == []
+        assert canvas["nodes"][1]["data"]["targetFlowId"] == "flow-b"
+        assert canvas["nodes"][2]["data"] == {"nodeType": "handoff", "queueId": "queue-b", "agentId": "agent-b"}
+        assert canvas["nodes"][3]["data"]["targetFlowId"] == "flow-b"
+        assert canvas["viewport"] == CANVAS["viewport"]
+        assert CANVAS["nodes"][1]["data"]["targetFlowId"] == "flow-a"
+
+    def test_unresolved_references_are_cleared_and_reported(self):
+        """Test references without a mapping are cleared and listed per node"""
+        canvas, unresolved = remap_references(CANVAS, {"flow-a": "flow-b", "agent-a": None})
+
+        assert canvas["nodes"][2]["data"]["queueId"] is None
+        assert canvas["nodes"][2]["data"]["agentId"] is None
+        assert unresolved == [
+            {"type": "queue", "id": "queue-a", "node_id": "handoff-1"},
+            {"type": "agent", "id": "agent-a", "node_id": "handoff-1"},
+        ]
+
+
+class TestExportSchema:
+    """Tests for the export document schema"""
+
+    def test_valid_document_keeps_canvas_extras(self):
+        """Test React Flow fields outside the schema survive validation"""
+        document = FlowImport.model_validate({
+            "format_version": "2.0",
+            "flow": {"name": "Atendimento", "canvas_data": CANVAS},
+            "references": [{"type": "flow", "id": "flow-a", "key": "Identificação"}],
+            "id_map": {"queue-a": "queue-b"},
+        })
+
+        canvas = document.flow.canvas_data.model_dump(exclude_unset=True)
+        assert canvas["viewport"] == CANVAS["viewport"]
+        assert canvas["edges"][1]["sourceHandle"] == "else"
+        assert "sourceHandle" not in canvas["edges"][0]
+
+    def test_legacy_format_is_accepted(self):
+        """Test 1.0 documents (with the old settings key) still validate"""
+        document = FlowImport.model_validate({
+            "format_version": "1.0",
+            "flow": {"name": "Antigo", "canvas_data": {"nodes": [], "edges": []}, "settings": None},
+        })
+
+        assert document.references == []
+        assert document.id_map == {}
+
+    @pytest.mark.parametrize("document", [
+        {"format_version": "3.0", "flow": {"name": "x"}},
+        {"format_version": "2.0"},
+        {"format_version": "2.0", "flow": {"name": ""}},
+        {"format_version": "2.0", "flow": {"name": "x", "canvas_data": {"nodes": [{"id": "a"}, {"id": "a"}]}}},
+        {"format_version": "2.0", "flow": {"name": "x", "canvas_data": {
+            "nodes": [{"id": "a"}], "edges": [{"source": "a", "target": "b"}]
+        }}},
+        {"format_version": "2.0", "flow": {"name": "x"}, "references": [{"type": "template", "id": "t"}]},
+    ])
+    def test_invalid_documents_are_rejected(self, document):
+        """Test unsupported versions, missing fields and broken graphs fail validation"""
+        with pytest.raises(ValidationError):
+            FlowImport.model_validate(document)
+
+    def test_json_schema_is_published(self):
+        """Test the JSON Schema describes the document"""
+        schema = FlowImport.model_json_schema()
+
+        assert set(schema["required"]) == {"flow"}
+        assert "id_map" in schema["properties"]
+        assert "FlowExportCanvas" in schema["$defs"]
@@ -0,0 +1,150 @@
+"""
+Flow Triggers Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+from types import SimpleNamespace
+
+import pytest
+
+from app.utils.flow_triggers import (
+    find_conflicts,
+    normalize_text,
+    pick_trigger,
+    trigger_matches,
+    validate_pattern,
+)
+
+NOW = datetime(2025, 11, 18, 12, 0, tzinfo=timezone.utc)
+
+
+def _trigger(pattern, match_type="keyword", flow_id="menu-flow", priority=0, age_minutes=0, **overrides):
+    fields = {
+        "id": f"{match_type}:{pattern}",
+        "flow_id": flow_id,
+        "match_type": match_type,
+        "pattern": pattern,
+        "priority": priority,
+        "interrupts_flow": False,
+        "is_active": True,
+        "created_at": NOW - timedelta(minutes=age_minutes),
+    }
+    fields.update(overrides)
+    return SimpleNamespace(**fields)
+
+
+class TestMatching:
+    """Tests for normalize_text() and trigger_matches()"""
+
+    def test_normalize_ignores_case_accents_and_punctuation(self):
+        """Test "2ª Via!" and "2a via" normalize the same way"""
+        assert normalize_text("  2ª   Via! ") == "2a via"
+        assert normalize_text("Falar com ATENDENTE?") == "falar com atendente"
+
+    @pytest.mark.parametrize("match_type,pattern,text,expected", [
+        ("exact", "menu", "Menu!", True),
+        ("exact", "menu", "ver o menu", False),
+        ("exact", "2ª via", "2a via", True),
+        ("keyword", "atendente", "quero falar com um Atendente", True),
+        ("keyword", "via", "quero a 2ª via do boleto", True),
+        ("keyword", "via", "enviar comprovante", False),
+        ("keyword", "falar com atendente", "pode me deixar falar com atendente?", True),
+        ("regex", r"boleto|2.? via", "Preciso do BOLETO", True),
+        ("regex", r"^\d{11}$", "cpf 12345678901", False),
+    ])
+    def test_trigger_matches(self, match_type, pattern, text, expected):
+        """Test exact, keyword (whole words) and regex matching"""
+        assert trigger_matches(match_type, pattern, text) is expected
+
+    def test_empty_text_never_matches(self):
+        """Test media without caption does not fire triggers"""
+        assert trigger_matches("regex", r"\w*x", "") is False
+        assert trigger_matches("keyword", "menu", None) is False
+
+    @pytest.mark.parametrize("match_type,pattern", [
+        ("regex", "(unclosed"),
+        ("regex", r"\w*"),
+        ("keyword", "!!!"),
+        ("contains", "menu"),
+    ])
+    def test_invalid_patterns_are_rejected(self, match_type, pattern):
+        """Test invalid regexes, regexes matching everything and empty phrases"""
+        with pytest.raises(ValueError):
+            validate_pattern(match_type, pattern)
+
+
+class TestPickTrigger:
+    """Tests for pick_trigger()"""
+
+    def test_highest_priority_wins(self):
+        """Test priority is evaluated before specificity"""
+        keyword = _trigger("boleto", flow_id="billing", priority=10)
+        exact = _trigger("2ª via do boleto", "exact", flow_id="duplicate")
+        assert pick_trigger([exact, keyword], "2a via do boleto") is keyword
+
+    def test_same_priority_most_specific_wins(self):
+        """Test exact beats keyword beats regex"""
+        regex = _trigger(r"via", "regex", flow_id="regex")
+        keyword = _trigger("2ª via", flow_id="keyword")
+        exact = _trigger("2ª via", "exact", flow_id="exact")
+        assert pick_trigger([regex, keyword, exact], "2ª via") is exact
+        assert pick_trigger([regex, keyword], "2ª via") is keyword
+
+    def test_oldest_wins_ties(self):
+        """Test the oldest trigger wins when priority and type are equal"""
+        newer = _trigger("menu", flow_id="new")
+        older = _trigger("menu", flow_id="old", age_minutes=5)
+        assert pick_trigger([newer, older], "menu") is older
+
+    def test_running_flow_only_interrupting_triggers(self):
+        """Test only interrupts_flow triggers fire while a flow is running"""
+        menu = _trigger("menu", "exact", interrupts_flow=True)
+        billing = _trigger("boleto", flow_id="billing")
+        assert pick_trigger([menu, billing], "boleto", in_flow=True) is None
+        assert pick_trigger([menu, billing], "menu", in_flow=True) is menu
+        assert pick_trigger([menu, billing], "boleto") is billing
+
+    def test_inactive_triggers_are_ignored(self):
+        """Test disabled triggers never fire"""
+        assert pick_trigger([_trigger("menu", is_active=False)], "menu") is None
+
+
+class TestFindConflicts:
+    """Tests for find_conflicts()"""
+
+    def test_duplicate_patterns_of_different_flows(self):
+        """Test the same normalized phrase pointing at two flows"""
+        conflicts = find_conflicts([
+            _trigger("2ª via", "exact", flow_id="billing"),
+            _trigger("2a VIA", "exact", flow_id="support", age_minutes=1),
+        ])
+        assert [conflict.kind for conflict in conflicts] == ["duplicate"]
+        assert conflicts[0].trigger_id == "exact:2a VIA"
+
+    def test_overlap_reports_winner(self):
+        """Test a keyword that shadows an exact phrase of another flow"""
+        keyword = _trigger("via", flow_id="billing", priority=5)
+        exact = _trigger("2ª via", "exact", flow_id="invoice")
+        conflicts = find_conflicts([exact, keyword])
+        assert len(conflicts) == 1
+        assert conflicts[0].kind == "overlap"
+        assert conflicts[0].trigger_id == keyword.id
+        assert "'via' wins" in conflicts[0].message
+
+    def test_regex_against_phrase(self):
+        """Test a regex that fires on another trigger's phrase"""
+        conflicts = find_conflicts([
+            _trigger("boleto", "exact", flow_id="billing"),
+            _trigger(r"bolet", "regex", flow_id="support"),
+        ])
+        assert [conflict.kind for conflict in conflicts] == ["overlap"]
+
+    def test_same_flow_and_inactive_are_not_conflicts(self):
+        """Test triggers of the same flow or disabled ones never conflict"""
+        assert find_conflicts([
+            _trigger("menu", "exact"),
+            _trigger("menu"),
+            _trigger("menu", "exact", flow_id="other", is_active=False),
+        ]) == []
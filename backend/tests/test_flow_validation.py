@@ -0,0 +1,205 @@
+"""
+Flow Validation Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.flow_validation import has_errors, validate_flow
+
+
+def _node(node_id, node_type, **data):
+    return {"id": node_id, "data": {"nodeType": node_type, **data}}
+
+
+def _edge(source, target, **extra):
+    return {"id": f"{source}-{target}", "source": source, "target": target, **extra}
+
+
+def _codes(diagnostics):
+    return [(diagnostic.code, diagnostic.node_id) for diagnostic in diagnostics]
+
+
+class TestGraphChecks:
+    """Tests for start/end, edges and reachability"""
+
+    def test_valid_flow_has_no_diagnostics(self):
+        """Test a linear flow from start to end is clean"""
+        canvas = {
+            "nodes": [_node("start", "start"), _node("msg", "message"), _node("end", "end")],
+            "edges": [_edge("start", "msg"), _edge("msg", "end")],
+        }
+
+        assert validate_flow(canvas) == []
+
+    def test_missing_and_duplicated_start(self):
+        """Test flows need exactly one start node and should have an end"""
+        assert _codes(validate_flow({"nodes": [_node("msg", "message")], "edges": []})) == [
+            ("missing_start", None),
+            ("missing_end", None),
+        ]
+
+        diagnostics = validate_flow({
+            "nodes": [_node("a", "start"), _node("b", "start"), _node("end", "end")],
+            "edges": [_edge("a", "end"), _edge("b", "end")],
+        })
+        assert _codes(diagnostics) == [("multiple_start", "b"), ("unreachable_node", "b")]
+        assert has_errors(diagnostics)
+
+    def test_dangling_edges_and_unreachable_nodes(self):
+        """Test edges to missing nodes are errors and orphan nodes warnings, errors first"""
+        diagnostics = validate_flow({
+            "nodes": [_node("start", "start"), _node("end", "end"), _node("orphan", "message")],
+            "edges": [_edge("start", "end"), _edge("start", "ghost"), _edge("orphan", "end")],
+        })
+
+        assert _codes(diagnostics) == [("dangling_edge", "start"), ("unreachable_node", "orphan")]
+        assert diagnostics[0].edge_id == "start-ghost"
+        assert diagnostics[0].severity == "error"
+        assert diagnostics[1].severity == "warning"
+
+    def test_jumps_and_random_paths_count_as_reachable(self):
+        """Test nodes reached only through a jump or a random path are not reported"""
+        canvas = {
+            "nodes": [
+                _node("start", "start"),
+                _node("jump", "jump", jumpType="node", targetNodeId="via-jump"),
+                _node("via-jump", "random", paths=[{"targetNodeId": "end"}]),
+                _node("end", "end"),
+            ],
+            "edges": [_edge("start", "jump")],
+        }
+
+        assert validate_flow(canvas) == []
+
+
+class TestNodeChecks:
+    """Tests for failure branches and WhatsApp limits"""
+
+    def test_missing_failure_branches(self):
+        """Test branching API calls, conditions and loops need their failure output"""
+        canvas = {
+            "nodes": [
+                _node("start", "start"),
+                _node("api", "api_call", errorHandling={"onError": "branch"}),
+                _node("cond", "condition"),
+                _node("loop", "loop"),
+                _node("end", "end"),
+            ],
+            "edges": [
+                _edge("start", "api"),
+                _edge("api", "cond", sourceHandle="success"),
+                _edge("cond", "loop", label="true"),
+                _edge("loop", "end", sourceHandle="loop"),
+            ],
+        }
+
+        assert _codes(validate_flow(canvas)) == [
+            ("missing_failure_branch", "api"),
+            ("missing_failure_branch", "cond"),
+            ("missing_failure_branch", "loop"),
+        ]
+
+        canvas["edges"] += [
+            _edge("api", "end", sourceHandle="error"),
+            _edge("cond", "end", label="Não"),
+            _edge("loop", "end", sourceHandle="done"),
+        ]
+        assert validate_flow(canvas) == []
+
+    def test_interactive_nodes_over_whatsapp_limits(self):
+        """Test button and list nodes are checked against WhatsApp limits"""
+        buttons = [{"id": f"b{index}", "title": f"Opção {index}"} for index in range(4)]
+        rows = [{"id": f"r{index}", "title": f"Item {index}"} for index in range(11)]
+        canvas = {
+            "nodes": [
+                _node("start", "start"),
+                _node("buttons", "interactive_buttons", bodyText="Escolha", buttons=buttons),
+                _node("list", "interactive_list", bodyText="Escolha", sections=[{"rows": rows}]),
+                _node("end", "end"),
+            ],
+            "edges": [_edge("start", "buttons"), _edge("buttons", "list"), _edge("list", "end")],
+        }
+
+        diagnostics = validate_flow(canvas)
+
+        assert _codes(diagnostics) == [("whatsapp_limit", "buttons"), ("whatsapp_limit", "list")]
+        assert [diagnostic.field for diagnostic in diagnostics] == ["buttons", "rows"]
+        assert has_errors(diagnostics)
+
+    def test_split_variants_need_traffic_and_outputs(self):
+        """Test split nodes need two variants with traffic, each connected"""
+        variants = [{"id": "a", "percentage": 60}, {"id": "b", "percentage": 30}]
+        canvas = {
+            "nodes": [
+                _node("start", "start"),
+                _node("split", "split", variants=variants),
+                _node("end", "end"),
+            ],
+            "edges": [_edge("start", "split"), _edge("split", "end", sourceHandle="a")],
+        }
+
+        assert _codes(validate_flow(canvas)) == [("invalid_split", "split"), ("split_percentages", "split")]
+
+        variants[1]["percentage"] = 40
+        canvas["edges"].append(_edge("split", "end", sourceHandle="b", id="split-end-b"))
+        assert validate_flow(canvas) == []
+
+        variants[1]["percentage"] = 0
+        assert _codes(validate_flow(canvas)) == [("invalid_split", "split")]
+
+    def test_business_hours_needs_open_and_closed_outputs(self):
+        """Test business hours nodes need both outputs, the holiday output is optional"""
+        canvas = {
+            "nodes": [
+                _node("start", "start"),
+                _node("hours", "business_hours"),
+                _node("handoff", "handoff"),
+                _node("end", "end"),
+            ],
+            "edges": [
+                _edge("start", "hours"),
+                _edge("hours", "handoff", sourceHandle="open"),
+                _edge("handoff", "end"),
+            ],
+        }
+
+        assert _codes(validate_flow(canvas)) == [("missing_failure_branch", "hours")]
+
+        canvas["edges"].append(_edge("hours", "end", sourceHandle="closed"))
+        assert validate_flow(canvas) == []
+
+    def test_sandbox_script_must_compile(self):
+        """Test sandbox scripts with forbidden constructs block publishing"""
+        canvas = {
+            "nodes": [
+                _node("start", "start"),
+                _node("script", "sandbox_script", code="import os\nreturn os.getcwd()"),
+                _node("end", "end"),
+            ],
+            "edges": [_edge("start", "script"), _edge("script", "end")],
+        }
+
+        diagnostics = validate_flow(canvas)
+        assert _codes(diagnostics) == [("invalid_script", "script")]
+        assert has_errors(diagnostics)
+
+        canvas["nodes"][1]["data"]["code"] = "return round(preco * 0.9, 2)"
+        assert validate_flow(canvas) == []
+
+    def test_question_validation_must_be_usable(self):
+        """Test question validation with a broken pattern or inverted bounds blocks publishing"""
+        canvas = {
+            "nodes": [
+                _node("start", "start"),
+                _node("ask", "question", responseType="number", validation={"min": 10, "max": 1, "pattern": "[0-9"}),
+                _node("end", "end"),
+            ],
+            "edges": [_edge("start", "ask"), _edge("ask", "end")],
+        }
+
+        diagnostics = validate_flow(canvas)
+        assert _codes(diagnostics) == [("invalid_validation", "ask"), ("invalid_validation", "ask")]
+        assert has_errors(diagnostics)
+
+        canvas["nodes"][1]["data"]["validation"] = {"min": 1, "max": 10}
+        assert validate_flow(canvas) == []
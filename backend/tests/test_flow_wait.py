@@ -0,0 +1,50 @@
+"""
+Flow Wait Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+
+import pytest
+
+from app.utils.flow_wait import MAX_WAIT, wait_resume_at
+
+NOW = datetime(2025, 11, 16, 12, 0, tzinfo=timezone.utc)
+
+
+class TestWaitResumeAt:
+    """Tests for wait_resume_at()"""
+
+    def test_durations(self):
+        """Test minutes, hours and days are added to now"""
+        assert wait_resume_at({"duration": 30}, {}, NOW) == NOW + timedelta(minutes=30)
+        assert wait_resume_at({"duration": "2", "durationUnit": "hours"}, {}, NOW) == NOW + timedelta(hours=2)
+        assert wait_resume_at({"duration": 1.5, "durationUnit": "days"}, {}, NOW) == NOW + timedelta(hours=36)
+
+    def test_until_dates_and_variables(self):
+        """Test ISO dates keep their offset and local dates use the node timezone"""
+        iso = {"waitType": "until", "until": "2025-11-20T09:00:00-03:00"}
+        assert wait_resume_at(iso, {}, NOW) == datetime(2025, 11, 20, 12, 0, tzinfo=timezone.utc)
+
+        from_variable = {"waitType": "until", "until": "{{data_retorno}}"}
+        assert wait_resume_at(from_variable, {"data_retorno": "20/11/2025 09:00"}, NOW) == datetime(
+            2025, 11, 20, 12, 0, tzinfo=timezone.utc
+        )
+
+        utc = {"waitType": "until", "until": "20/11/2025", "timezone": "UTC"}
+        assert wait_resume_at(utc, {}, NOW) == datetime(2025, 11, 20, tzinfo=timezone.utc)
+
+    @pytest.mark.parametrize("node_data", [
+        {"duration": 0},
+        {"duration": "abc"},
+        {"duration": 5, "durationUnit": "weeks"},
+        {"duration": MAX_WAIT.days + 1, "durationUnit": "days"},
+        {"waitType": "until", "until": "2025-11-15T09:00:00Z"},
+        {"waitType": "until", "until": "{{missing}}"},
+        {"waitType": "until", "until": "amanhã"},
+    ])
+    def test_invalid_waits(self, node_data):
+        """Test invalid, past or too long waits are rejected"""
+        with pytest.raises(ValueError):
+            wait_resume_at(node_data, {}, NOW)
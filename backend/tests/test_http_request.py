@@ -0,0 +1,66 @@
+"""
+HTTP Request Node Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.http_request import is_retryable, json_path, map_response, render_template
+
+ORDER = {
+    "order": {
+        "status": "shipped",
+        "items": [{"name": "Camiseta", "qty": 2}, {"name": "Boné", "qty": 1}],
+        "tracking-code": "BR123",
+    }
+}
+
+
+class TestRenderTemplate:
+    """Tests for render_template()"""
+
+    def test_nested_values(self):
+        """Test placeholders are filled inside dicts and lists, unknown ones are kept"""
+        body = {"cliente": {"nome": "{{nome}}", "tags": ["{{plano}}", "vip"]}, "total": 10, "x": "{{missing}}"}
+
+        rendered = render_template(body, {"nome": "Maria", "plano": "Gold"})
+
+        assert rendered == {"cliente": {"nome": "Maria", "tags": ["Gold", "vip"]}, "total": 10, "x": "{{missing}}"}
+        assert render_template("https://api.example.com/users/{{id}}", {"id": 42}) == "https://api.example.com/users/42"
+        assert render_template("{{org.secrets.API_KEY}}", {}) == "{{org.secrets.API_KEY}}"
+
+
+class TestJsonPath:
+    """Tests for json_path(), map_response() and is_retryable()"""
+
+    def test_paths(self):
+        """Test fields, indexes, quoted keys and wildcards"""
+        assert json_path(ORDER, "$.order.status") == "shipped"
+        assert json_path(ORDER, "order.items[0].name") == "Camiseta"
+        assert json_path(ORDER, "$.order.items[-1].qty") == 1
+        assert json_path(ORDER, "$.order['tracking-code']") == "BR123"
+        assert json_path(ORDER, "$.order.items[*].name") == ["Camiseta", "Boné"]
+        assert json_path(ORDER, "$") == ORDER
+
+    def test_missing_paths_are_none(self):
+        """Test paths that do not exist give None instead of failing"""
+        assert json_path(ORDER, "$.order.items[5].name") is None
+        assert json_path(ORDER, "$.customer.name") is None
+        assert json_path("texto", "$.order") is None
+
+    def test_map_response(self):
+        """Test every mapped variable is read and unsupported expressions are rejected"""
+        assert map_response(ORDER, {"pedido_status": "$.order.status", "itens": "$.order.items[*].qty"}) == {
+            "pedido_status": "shipped",
+            "itens": [2, 1],
+        }
+        with pytest.raises(ValueError):
+            json_path(ORDER, "$..name")
+
+    def test_retryable_status_codes(self):
+        """Test only server errors, timeouts and rate limits are retried"""
+        assert is_retryable(503)
+        assert is_retryable(429)
+        assert not is_retryable(404)
+        assert not is_retryable(400)
@@ -0,0 +1,216 @@
+"""
+Identity Change & Message Echo Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from uuid import uuid4
+
+import pytest
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import NotFoundException
+from app.models.conversation import Conversation
+from app.services.identity_service import (
+    IdentityVerificationService,
+    register_identity_change_hook,
+    requires_reverification,
+    unregister_identity_change_hook,
+)
+from pytake_client.processor import WebhookHandler, WebhookProcessor, event_dedup_key, ordering_key
+from pytake_client.webhooks import (
+    IdentityChange,
+    IdentityChangeKind,
+    WebhookEventType,
+    classify_message,
+    iter_events,
+)
+from tests.conftest import OrganizationFactory
+
+IDENTITY_MESSAGE = {
+    "from": "5511999999999",
+    "id": "wamid.identity",
+    "timestamp": "1700000000",
+    "type": "system",
+    "system": {
+        "body": "User's security code changed",
+        "customer": "5511999999999",
+        "identity": "ESfRs6L1wA0=",
+        "type": "customer_identity_changed",
+    },
+}
+
+NUMBER_MESSAGE = {
+    "from": "5511999999999",
+    "id": "wamid.number",
+    "type": "system",
+    "system": {
+        "body": "User A changed from 5511999999999 to 5511888888888",
+        "wa_id": "5511999999999",
+        "new_wa_id": "5511888888888",
+        "type": "user_changed_number",
+    },
+}
+
+ECHO = {
+    "from": "15550000000",
+    "to": "5511999999999",
+    "id": "wamid.echo",
+    "timestamp": "1700000100",
+    "type": "text",
+    "text": {"body": "Seu pedido saiu para entrega"},
+}
+
+
+def _payload(field, value):
+    return {"entry": [{"changes": [{
+        "field": field,
+        "value": {"metadata": {"phone_number_id": "123"}, **value},
+    }]}]}
+
+
+class TestIdentityEvents:
+    """Tests for parsing security notifications and echoes"""
+
+    def test_identity_change(self):
+        assert classify_message(IDENTITY_MESSAGE) == WebhookEventType.IDENTITY_CHANGE
+        change = IdentityChange.from_webhook(IDENTITY_MESSAGE)
+
+        assert change.kind == IdentityChangeKind.IDENTITY_CHANGED
+        assert change.identity_hash == "ESfRs6L1wA0="
+        assert change.wa_id == change.current_wa_id == "5511999999999"
+        assert change.timestamp == 1700000000
+
+    def test_number_change(self):
+        event = next(iter_events(_payload("messages", {"messages": [NUMBER_MESSAGE]})))
+        change = event.identity_change
+
+        assert change.kind == IdentityChangeKind.NUMBER_CHANGED
+        assert change.current_wa_id == "5511888888888"
+
+    def test_other_system_messages_stay_messages(self):
+        message = {**IDENTITY_MESSAGE, "system": {"type": "other", "body": "x"}}
+
+        assert classify_message(message) == WebhookEventType.MESSAGE
+
+    def test_message_echo(self):
+        event = next(iter_events(_payload("smb_message_echoes", {"message_echoes": [ECHO]})))
+
+        assert event.type == WebhookEventType.MESSAGE_ECHO
+        assert event.inbound is None
+        assert event.echo.to == "5511999999999"
+        assert event.echo.message.text == "Seu pedido saiu para entrega"
+        assert event_dedup_key(event) == "echo:wamid.echo"
+        assert ordering_key(event) == "123:5511999999999"
+
+
+class TestIdentityDispatch:
+    """Tests for routing the new events to handlers"""
+
+    @pytest.mark.asyncio
+    async def test_routes_to_handler_methods(self):
+        calls = []
+
+        class Security(WebhookHandler):
+            async def on_identity_change(self, event):
+                calls.append(("identity", event.identity_change.kind))
+
+            async def on_echo(self, event):
+                calls.append(("echo", event.echo.id))
+
+        processor = WebhookProcessor(handlers=[Security()])
+        await processor.dispatch(_payload("messages", {"messages": [IDENTITY_MESSAGE]}))
+        await processor.dispatch(_payload("smb_message_echoes", {"message_echoes": [ECHO]}))
+
+        assert calls == [("identity", IdentityChangeKind.IDENTITY_CHANGED), ("echo", "wamid.echo")]
+
+    @pytest.mark.asyncio
+    async def test_identity_change_falls_back_to_on_message(self):
+        seen = []
+
+        class Inbox(WebhookHandler):
+            async def on_message(self, event):
+                seen.append(event.type)
+
+        await WebhookProcessor(handlers=[Inbox()]).dispatch(
+            _payload("messages", {"messages": [NUMBER_MESSAGE]})
+        )
+
+        assert seen == [WebhookEventType.IDENTITY_CHANGE]
+
+
+class RecordingService(IdentityVerificationService):
+    """Keeps the emitted WebSocket events"""
+
+    def __init__(self, db):
+        super().__init__(db)
+        self.emitted = []
+
+    async def _emit(self, conversation, event, state):
+        self.emitted.append(event)
+
+
+@pytest.fixture
+def service(db_session: AsyncSession):
+    return RecordingService(db_session)
+
+
+async def _conversation(db, extra_data=None) -> Conversation:
+    org = await OrganizationFactory.create_in_db(db)
+    conversation = Conversation(
+        id=uuid4(),
+        organization_id=org.id,
+        contact_id=uuid4(),
+        whatsapp_number_id=uuid4(),
+        status="open",
+        extra_data=extra_data or {},
+    )
+    db.add(conversation)
+    await db.commit()
+    return conversation
+
+
+class TestIdentityVerification:
+    """Tests for flagging conversations"""
+
+    @pytest.mark.asyncio
+    async def test_flag_marks_conversation_and_runs_hooks(self, service, db_session: AsyncSession):
+        conversation = await _conversation(db_session, {"context": {"a": 1}})
+        hooked = []
+
+        async def hook(db, flagged, change):
+            hooked.append((flagged.id, change.kind))
+
+        async def broken(db, flagged, change):
+            raise RuntimeError("hook down")
+
+        register_identity_change_hook(broken)
+        register_identity_change_hook(hook)
+        try:
+            state = await service.flag(conversation, IdentityChange.from_webhook(IDENTITY_MESSAGE))
+        finally:
+            unregister_identity_change_hook(broken)
+            unregister_identity_change_hook(hook)
+
+        assert state["required"] is True
+        assert state["identity_hash"] == "ESfRs6L1wA0="
+        await db_session.refresh(conversation)
+        assert requires_reverification(conversation)
+        assert conversation.extra_data["context"] == {"a": 1}
+        assert hooked == [(conversation.id, IdentityChangeKind.IDENTITY_CHANGED)]
+        assert service.emitted == ["conversation:identity_changed"]
+
+    @pytest.mark.asyncio
+    async def test_mark_verified_clears_flag(self, service, db_session: AsyncSession):
+        user_id = uuid4()
+        conversation = await _conversation(db_session)
+        await service.flag(conversation, IdentityChange.from_webhook(NUMBER_MESSAGE))
+
+        with pytest.raises(NotFoundException):
+            await service.mark_verified(conversation.id, uuid4(), user_id)
+        verified = await service.mark_verified(conversation.id, conversation.organization_id, user_id)
+
+        await db_session.refresh(verified)
+        assert not requires_reverification(verified)
+        assert verified.extra_data["identity_verification"]["verified_by"] == str(user_id)
+        assert service.emitted[-1] == "conversation:identity_verified"
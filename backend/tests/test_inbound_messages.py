@@ -0,0 +1,207 @@
+"""
+Inbound Message Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import json
+from pathlib import Path
+
+import pytest
+
+from app.schemas.webhook import (
+    InboundMessage,
+    InboundMessageType,
+    WebhookEventType,
+    iter_events,
+)
+
+FIXTURES = json.loads((Path(__file__).parent / "fixtures" / "webhook_messages.json").read_text())
+
+
+def _parse(name: str) -> InboundMessage:
+    return InboundMessage.from_webhook(FIXTURES[name])
+
+
+class TestInboundMessageTypes:
+    """Tests for the exhaustive inbound message type"""
+
+    @pytest.mark.parametrize(
+        "name, expected",
+        [
+            ("text", InboundMessageType.TEXT),
+            ("image", InboundMessageType.IMAGE),
+            ("voice_note", InboundMessageType.AUDIO),
+            ("document", InboundMessageType.DOCUMENT),
+            ("sticker", InboundMessageType.STICKER),
+            ("location", InboundMessageType.LOCATION),
+            ("contacts", InboundMessageType.CONTACTS),
+            ("button_reply", InboundMessageType.BUTTON_REPLY),
+            ("list_reply", InboundMessageType.LIST_REPLY),
+            ("flow_reply", InboundMessageType.FLOW_REPLY),
+            ("address_reply", InboundMessageType.ADDRESS_REPLY),
+            ("quick_reply_button", InboundMessageType.BUTTON),
+            ("order", InboundMessageType.ORDER),
+            ("reaction", InboundMessageType.REACTION),
+            ("system_changed_number", InboundMessageType.SYSTEM),
+            ("unsupported", InboundMessageType.UNSUPPORTED),
+            ("unknown", InboundMessageType.UNKNOWN),
+        ],
+    )
+    def test_fixture_types(self, name, expected):
+        """Test every fixture maps to its type and keeps the raw JSON"""
+        inbound = _parse(name)
+
+        assert inbound.type == expected
+        assert inbound.raw == FIXTURES[name]
+        assert inbound.sender == "5511999999999"
+        assert isinstance(inbound.timestamp, int)
+
+
+class TestInboundMessageContent:
+    """Tests for typed fields and stored content"""
+
+    def test_text(self):
+        inbound = _parse("text")
+
+        assert inbound.text == "Olá, quero saber do meu pedido 📦"
+        assert inbound.to_content() == {"text": inbound.text}
+
+    def test_media(self):
+        """Test media ids, captions, voice notes and stickers"""
+        image, audio, document, sticker = (
+            _parse("image"), _parse("voice_note"), _parse("document"), _parse("sticker")
+        )
+
+        assert image.is_media
+        assert image.text_value == "Comprovante"
+        assert image.to_content()["image"]["id"] == "1043278950582342"
+        assert audio.media.voice is True
+        assert document.to_content()["document"]["filename"] == "boleto.pdf"
+        assert sticker.media.animated is False
+
+    def test_location(self):
+        location = _parse("location").location
+
+        assert location.latitude == -23.561414
+        assert location.name == "MASP"
+
+    def test_contacts(self):
+        """Test shared contact cards keep phones, WhatsApp ids and e-mails"""
+        inbound = _parse("contacts")
+        card = inbound.contacts[0]
+
+        assert card.name == "Maria Silva"
+        assert card.phones == ["+55 11 98888-8888", "+55 11 3333-3333"]
+        assert card.wa_ids == ["5511988888888"]
+        assert card.emails == ["maria@acme.com.br"]
+        assert card.organization == "Acme Ltda"
+        assert inbound.to_content()["contacts"] == FIXTURES["contacts"]["contacts"]
+
+    def test_button_and_list_replies(self):
+        """Test interactive replies carry the id and the context"""
+        button, row = _parse("button_reply"), _parse("list_reply")
+
+        assert button.reply.id == "confirm"
+        assert button.text_value == "Confirmar"
+        assert button.context.quoted_message_id == "wamid.question"
+        assert button.to_content() == {
+            "interactive": {"type": "button_reply", "button_reply": {"id": "confirm", "title": "Confirmar"}}
+        }
+        assert row.reply.id == "plan_500"
+        assert row.reply.description == "500 Mega por R$ 99,90"
+
+    def test_flow_reply(self):
+        """Test response_json of a WhatsApp Flow is decoded"""
+        flow = _parse("flow_reply").flow_reply
+
+        assert flow.response == {"flow_token": "abc", "date": "2025-11-10", "people": "2"}
+
+    def test_address_reply(self):
+        """Test the address form answer is parsed into a shipping address"""
+        inbound = _parse("address_reply")
+        address = inbound.address
+
+        assert inbound.flow_reply is None
+        assert address.saved_address_id == "address1"
+        assert address.postal_code == "400011"
+        assert address.building_name == "Lodha Bellissimo"
+        assert address.city == "Mumbai"
+        assert inbound.text_value == (
+            "floor 2, Lodha Bellissimo, Apollo Mills Compound, Mahalakshmi, Mumbai, Maharashtra, 400011"
+        )
+        content = inbound.to_content()["interactive"]
+        assert content["type"] == "nfm_reply"
+        assert content["address"]["phone_number"] == "+91 9999999999"
+        assert content["address"]["one_line"] == inbound.text_value
+
+    def test_invalid_flow_response_json(self):
+        """Test an undecodable response_json is preserved as raw"""
+        message = json.loads(json.dumps(FIXTURES["flow_reply"]))
+        message["interactive"]["nfm_reply"]["response_json"] = "{not json"
+
+        assert InboundMessage.from_webhook(message).flow_reply.response == {"raw": "{not json"}
+
+    def test_quick_reply_button(self):
+        inbound = _parse("quick_reply_button")
+
+        assert inbound.button.payload == "STOP_PROMOTIONS"
+        assert inbound.text_value == "Parar promoções"
+
+    def test_order_and_reaction(self):
+        assert _parse("order").order.total_amount == 21.0
+        assert _parse("reaction").reaction.message_id == "wamid.target"
+
+    def test_system_changed_number(self):
+        """Test the old and new WhatsApp ids of a number change"""
+        system = _parse("system_changed_number").system
+
+        assert system.is_number_change
+        assert system.wa_id == "5511999999999"
+        assert system.new_wa_id == "5511977777777"
+
+    def test_unsupported_and_unknown_keep_raw(self):
+        """Test unmodelled types are stored with the original payload"""
+        unsupported, unknown = _parse("unsupported"), _parse("unknown")
+
+        assert unsupported.errors[0]["code"] == 131051
+        assert unsupported.to_content()["raw"] == FIXTURES["unsupported"]
+        assert unknown.raw_type == "poll"
+        assert unknown.to_content() == {"raw": FIXTURES["unknown"]}
+
+    def test_unknown_interactive_type(self):
+        """Test new interactive reply kinds are not misclassified"""
+        inbound = InboundMessage.from_webhook({
+            "from": "5511999999999",
+            "type": "interactive",
+            "interactive": {"type": "call_permission_reply", "call_permission_reply": {"response": "accept"}},
+        })
+
+        assert inbound.type == InboundMessageType.UNKNOWN
+        assert inbound.reply is None
+
+
+class TestWebhookEventInbound:
+    """Tests for WebhookEvent.inbound"""
+
+    def test_inbound_from_payload(self):
+        """Test messages expose the inbound message and statuses do not"""
+        payload = {
+            "entry": [{
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "metadata": {"phone_number_id": "123"},
+                        "messages": [FIXTURES["list_reply"], FIXTURES["order"]],
+                        "statuses": [{"id": "wamid.x", "status": "read"}],
+                    },
+                }],
+            }],
+        }
+
+        events = list(iter_events(payload))
+
+        assert events[0].inbound.type == InboundMessageType.LIST_REPLY
+        assert events[1].type == WebhookEventType.ORDER
+        assert events[1].inbound.order.catalog_id == "1234567890"
+        assert events[2].inbound is None
@@ -0,0 +1,133 @@
+"""
+Input Validation Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import date
+
+from app.utils.input_validation import (
+    DEFAULT_MAX_ATTEMPTS,
+    check_validation_config,
+    is_valid_cnpj,
+    is_valid_cpf,
+    max_attempts,
+    normalize_br_phone,
+    validate_answer,
+)
+
+TODAY = date(2026, 10, 16)
+
+
+def _question(response_type, **validation):
+    return {"responseType": response_type, "validation": validation}
+
+
+class TestDocuments:
+    """Tests for CPF and CNPJ check digits"""
+
+    def test_cpf_check_digits(self):
+        """Test CPFs are accepted with or without punctuation and rejected with wrong digits"""
+        assert is_valid_cpf("529.982.247-25")
+        assert is_valid_cpf("52998224725")
+        assert not is_valid_cpf("529.982.247-24")
+        assert not is_valid_cpf("111.111.111-11")
+        assert not is_valid_cpf("5299822472")
+
+    def test_cnpj_numeric_and_alphanumeric(self):
+        """Test numeric and alphanumeric CNPJs use the same check digits"""
+        assert is_valid_cnpj("11.222.333/0001-81")
+        assert is_valid_cnpj("12.abc.345/01de-35")
+        assert not is_valid_cnpj("11.222.333/0001-82")
+        assert not is_valid_cnpj("00.000.000/0000-00")
+
+    def test_document_accepts_either(self):
+        """Test the document type accepts a CPF or a CNPJ and returns only its characters"""
+        assert validate_answer("529.982.247-25", _question("document")).value == "52998224725"
+        assert validate_answer("11.222.333/0001-81", _question("document")).value == "11222333000181"
+        assert validate_answer("11.222.333/0001-81", _question("cpf")).error == "Por favor, digite um CPF válido."
+
+
+class TestPhone:
+    """Tests for normalize_br_phone()"""
+
+    def test_br_numbers_are_normalized(self):
+        """Test mobile and landline numbers become 55 + DDD + number"""
+        assert normalize_br_phone("(11) 98765-4321") == "5511987654321"
+        assert normalize_br_phone("+55 11 98765-4321") == "5511987654321"
+        assert normalize_br_phone("011 3456-7890") == "551134567890"
+        assert normalize_br_phone("98765-4321") is None
+
+    def test_mobile_only_and_international(self):
+        """Test mobileOnly rejects landlines and international numbers keep their digits"""
+        assert normalize_br_phone("11 3456-7890", mobile_only=True) is None
+        assert normalize_br_phone("+1 415 555 2671") == "14155552671"
+        assert not validate_answer("11 3456-7890", _question("phone", mobileOnly=True)).valid
+
+
+class TestValidateAnswer:
+    """Tests for validate_answer()"""
+
+    def test_number_range_and_br_notation(self):
+        """Test numbers in BR notation are parsed and checked against min/max"""
+        question = _question("number", min=1, max=2000)
+
+        assert validate_answer("1.234,50", question).value == 1234.5
+        assert validate_answer("30", question).value == 30
+        assert validate_answer("2500", question).error == "Por favor, digite um número até 2000."
+        assert not validate_answer("abc", question).valid
+
+    def test_date_formats_and_bounds(self):
+        """Test dates are parsed in the configured formats, returned as ISO and checked against today"""
+        question = _question("date", dateFormat=["DD/MM/YYYY", "DD/MM/YY"], allowPast=False)
+
+        assert validate_answer("20/10/2026", question, today=TODAY).value == "2026-10-20"
+        assert validate_answer("20/10/26", question, today=TODAY).value == "2026-10-20"
+        assert validate_answer("31/02/2027", question, today=TODAY).error == (
+            "Por favor, digite uma data válida no formato DD/MM/YYYY."
+        )
+        assert validate_answer("01/01/2020", question, today=TODAY).error == "Por favor, digite uma data a partir de hoje."
+
+    def test_custom_pattern_and_error_message(self):
+        """Test the custom pattern applies to any type and errorMessage replaces the default"""
+        question = _question("text", pattern=r"[A-Z]{3}-?\d{4}", errorMessage="Placa inválida")
+
+        assert validate_answer("ABC-1234", question).valid
+        assert validate_answer("ABC1234", _question("regex", pattern=r"[A-Z]{3}-?\d{4}")).valid
+        assert validate_answer("placa", question).error == "Placa inválida"
+
+    def test_required_and_options(self):
+        """Test empty answers only pass when not required and options match value or label"""
+        question = {"responseType": "options", "options": [{"value": "1", "label": "Vendas"}, "Suporte"]}
+
+        assert validate_answer("", question).error == "Por favor, digite uma resposta."
+        assert validate_answer("", {"validation": {"required": False}}).valid
+        assert validate_answer("vendas", question).value == "1"
+        assert validate_answer("Suporte", question).value == "Suporte"
+        assert validate_answer("RH", question).error == "Por favor, escolha uma das opções: 'Vendas', 'Suporte'"
+
+    def test_max_attempts_default(self):
+        """Test invalid maxAttempts values fall back to the default"""
+        assert max_attempts(_question("text", maxAttempts=5)) == 5
+        assert max_attempts(_question("text", maxAttempts=0)) == DEFAULT_MAX_ATTEMPTS
+        assert max_attempts({}) == DEFAULT_MAX_ATTEMPTS
+
+
+class TestCheckValidationConfig:
+    """Tests for check_validation_config()"""
+
+    def test_reports_unusable_settings(self):
+        """Test unknown types, broken patterns, inverted bounds and incomplete date formats are reported"""
+        assert check_validation_config(_question("cpf")) == []
+        assert check_validation_config({"responseType": "rg"}) == ["Unknown responseType 'rg'"]
+        assert check_validation_config(_question("regex")) == ['responseType "regex" needs a pattern']
+        assert check_validation_config(_question("text", minLength=10, maxLength=5)) == [
+            "minLength is greater than maxLength"
+        ]
+        assert check_validation_config(_question("date", minDate="2026-12-01", maxDate="2026-01-01")) == [
+            "minDate is greater than maxDate"
+        ]
+        assert check_validation_config(_question("date", dateFormat="MM/YYYY")) == [
+            "Date format 'MM/YYYY' needs DD and MM"
+        ]
+        assert check_validation_config(_question("text", pattern="[0-9"))[0].startswith("Invalid pattern")
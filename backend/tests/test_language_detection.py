@@ -0,0 +1,88 @@
+"""
+Contact Language Detection Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from types import SimpleNamespace
+
+import pytest
+
+from app.core.config import settings
+from app.services.whatsapp_service import WhatsAppService
+from app.utils.language_detection import (
+    base_language,
+    detect_language,
+    pick_template_language,
+    skill_languages,
+)
+from pytake_client.webhooks import InboundMessage
+
+
+class TestDetectLanguage:
+    """Tests for guessing the language of a message"""
+
+    @pytest.mark.parametrize("text,expected", [
+        ("Olá, tudo bem? Quero saber do meu pedido", "pt"),
+        ("Não recebi a encomenda", "pt"),
+        ("Hola, ¿dónde está mi pedido?", "es"),
+        ("Hi, where is my order?", "en"),
+        ("Bonjour, je voudrais savoir où est ma commande", "fr"),
+        ("Ciao, dove è il mio ordine?", "it"),
+        ("Guten Tag, wo ist meine Bestellung?", "de"),
+    ])
+    def test_detects_language(self, text, expected):
+        assert detect_language(text) == expected
+
+    @pytest.mark.parametrize("text", ["ok", "👍", "", None, "123456"])
+    def test_short_or_ambiguous_text_is_unknown(self, text):
+        assert detect_language(text) is None
+
+
+class TestTemplateLanguage:
+    """Tests for picking the template language variant"""
+
+    def test_picks_variant_in_contact_language(self):
+        assert pick_template_language(["pt_BR", "es_MX", "en_US"], "es", "pt_BR") == "es_MX"
+
+    def test_keeps_default_when_it_matches_or_no_variant(self):
+        assert pick_template_language(["pt_BR", "es"], "pt", "pt_BR") == "pt_BR"
+        assert pick_template_language(["pt_BR"], "de", "pt_BR") == "pt_BR"
+        assert pick_template_language(["pt_BR", "es"], None, "pt_BR") == "pt_BR"
+
+    def test_base_language(self):
+        assert base_language("pt_BR") == base_language("PT-br") == "pt"
+        assert base_language(None) is None
+
+
+class TestAgentLanguages:
+    """Tests for reading languages from agent skills"""
+
+    def test_skill_languages(self):
+        assert skill_languages(["Spanish", "lang:en", "PT", "vendas", "billing"]) == {"es", "en", "pt"}
+
+
+class TestContactLanguage:
+    """Tests for storing the detected language on the contact"""
+
+    @staticmethod
+    def _inbound(text):
+        return InboundMessage.from_webhook({"from": "5511999999999", "id": "wamid.1", "type": "text", "text": {"body": text}})
+
+    def test_stores_language_from_early_message(self):
+        contact = SimpleNamespace(id="c1", language=None, total_messages_received=1)
+
+        WhatsAppService._detect_contact_language(None, contact, self._inbound("Hola, necesito ayuda con mi pedido"))
+
+        assert contact.language == "es"
+
+    def test_keeps_known_language_and_stops_after_early_messages(self, monkeypatch):
+        monkeypatch.setattr(settings, "LANGUAGE_DETECTION_MESSAGES", 3)
+        known = SimpleNamespace(id="c1", language="pt", total_messages_received=0)
+        late = SimpleNamespace(id="c2", language=None, total_messages_received=10)
+
+        WhatsAppService._detect_contact_language(None, known, self._inbound("Hi, where is my order?"))
+        WhatsAppService._detect_contact_language(None, late, self._inbound("Hi, where is my order?"))
+
+        assert known.language == "pt"
+        assert late.language is None
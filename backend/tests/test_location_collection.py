@@ -0,0 +1,98 @@
+"""
+Location Collection Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.location_collection import (
+    check_location_config,
+    extract_location,
+    find_area,
+    parse_service_areas,
+    point_in_polygon,
+)
+
+# Square around Praça da Sé, São Paulo ([lat, lng])
+SQUARE = [[-23.56, -46.64], [-23.56, -46.62], [-23.54, -46.62], [-23.54, -46.64]]
+INSIDE = (-23.55, -46.63)
+OUTSIDE = (-23.50, -46.63)
+
+
+class TestPointInPolygon:
+    """Tests for point_in_polygon()"""
+
+    def test_inside_outside_and_edge(self):
+        """Test points inside or on the edge are covered and points outside are not"""
+        polygon = parse_service_areas(SQUARE)[0].outer
+
+        assert point_in_polygon(*INSIDE, polygon)
+        assert not point_in_polygon(*OUTSIDE, polygon)
+        assert point_in_polygon(-23.56, -46.63, polygon)
+
+    def test_concave_polygon(self):
+        """Test the notch of an L-shaped area is not covered"""
+        l_shape = parse_service_areas([[0, 0], [0, 2], [1, 2], [1, 1], [2, 1], [2, 0]])[0].outer
+
+        assert point_in_polygon(1.5, 0.5, l_shape)
+        assert not point_in_polygon(1.5, 1.5, l_shape)
+
+
+class TestServiceAreas:
+    """Tests for parse_service_areas() and find_area()"""
+
+    def test_named_areas(self):
+        """Test the first area containing the point is returned"""
+        areas = parse_service_areas([
+            {"name": "Centro", "polygon": SQUARE},
+            {"name": "Norte", "polygon": [{"lat": -23.51, "lng": -46.64}, {"lat": -23.51, "lng": -46.62}, {"lat": -23.49, "lng": -46.63}]},
+        ])
+
+        assert find_area(*INSIDE, areas).name == "Centro"
+        assert find_area(*OUTSIDE, areas).name == "Norte"
+        assert find_area(-23.0, -46.0, areas) is None
+
+    def test_geojson_with_hole(self):
+        """Test GeoJSON positions are [lng, lat] and inner rings are not covered"""
+        feature = {
+            "type": "Feature",
+            "properties": {"name": "Sé"},
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [
+                    [[-46.64, -23.56], [-46.62, -23.56], [-46.62, -23.54], [-46.64, -23.54], [-46.64, -23.56]],
+                    [[-46.631, -23.551], [-46.629, -23.551], [-46.629, -23.549], [-46.631, -23.549]],
+                ],
+            },
+        }
+        areas = parse_service_areas({"type": "FeatureCollection", "features": [feature]})
+
+        assert areas[0].name == "Sé"
+        assert find_area(-23.555, -46.635, areas) is not None
+        assert find_area(*INSIDE, areas) is None
+
+    def test_invalid_areas_are_reported(self):
+        """Test malformed polygons are reported by the flow validator"""
+        assert check_location_config({"serviceArea": SQUARE}) == []
+        assert check_location_config({}) == []
+        assert check_location_config({"serviceArea": [[0, 0], [1, 1]]}) == ["A polygon needs at least 3 points"]
+        assert check_location_config({"serviceArea": {"type": "Point", "coordinates": [0, 0]}}) == [
+            "Unsupported GeoJSON type 'Point' (use Polygon or MultiPolygon)"
+        ]
+        with pytest.raises(ValueError):
+            parse_service_areas([[0, 0], [0, 200], [1, 1]])
+
+
+class TestExtractLocation:
+    """Tests for extract_location()"""
+
+    def test_location_message(self):
+        """Test shared locations are parsed and other messages are ignored"""
+        content = {"location": {"latitude": -23.55, "longitude": -46.63, "name": "Sé", "address": "Praça da Sé"}}
+
+        assert extract_location("location", content) == {
+            "latitude": -23.55, "longitude": -46.63, "name": "Sé", "address": "Praça da Sé"
+        }
+        assert extract_location("text", {"text": "Rua Direita, 10"}) is None
+        assert extract_location("location", {"location": {"latitude": 123, "longitude": 0}}) is None
@@ -0,0 +1,100 @@
+"""
+Media Collection Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+from pytake_client.media import InMemoryMediaStorage, LocalMediaStorage
+from pytake_client.webhooks import StoredMedia
+
+from app.core.media_storage import build_media_storage
+from app.utils.media_collection import (
+    DEFAULT_MAX_SIZE_MB,
+    MAX_MEDIA_SIZE_MB,
+    MediaRequirements,
+    check_media,
+    check_media_config,
+    extract_media,
+    media_variable,
+)
+
+PDF_CONTENT = {"document": {"id": "wamid-media-1", "mime_type": "application/pdf", "filename": "comprovante.pdf"}}
+VOICE_CONTENT = {"audio": {"id": "wamid-media-2", "mime_type": "audio/ogg; codecs=opus", "voice": True}}
+
+
+class TestMediaRequirements:
+    """Tests for MediaRequirements.from_node()"""
+
+    def test_defaults_and_limits(self):
+        """Test unknown types are ignored and the size is capped at the Cloud API limit"""
+        requirements = MediaRequirements.from_node({"mediaTypes": ["pdf"], "maxSizeMb": 500, "maxAttempts": 0})
+
+        assert requirements.media_types == ["image", "document"]
+        assert requirements.max_size_mb == MAX_MEDIA_SIZE_MB
+        assert requirements.max_attempts == 3
+        assert MediaRequirements.from_node({}).max_bytes == DEFAULT_MAX_SIZE_MB * 1024 * 1024
+
+    def test_error_message_lists_accepted_types(self):
+        """Test the default retry message names the accepted types and size"""
+        requirements = MediaRequirements.from_node({"mediaTypes": ["image", "document"], "maxSizeMb": 10})
+
+        assert requirements.error_message() == "Por favor, envie um arquivo do tipo imagem ou documento de até 10 MB."
+
+
+class TestCheckMedia:
+    """Tests for extract_media() and check_media()"""
+
+    def test_text_is_not_media(self):
+        """Test text messages are rejected"""
+        assert extract_media("text", {"text": "segue o comprovante"}) is None
+        assert not check_media(None, MediaRequirements())
+
+    def test_media_type_and_mime_allow_list(self):
+        """Test the media type and MIME wildcards must match, ignoring MIME parameters"""
+        pdf = extract_media("document", PDF_CONTENT)
+        voice = extract_media("audio", VOICE_CONTENT)
+
+        assert pdf.filename == "comprovante.pdf"
+        assert check_media(pdf, MediaRequirements.from_node({"mimeTypes": ["image/*", "application/pdf"]}))
+        assert not check_media(pdf, MediaRequirements.from_node({"mimeTypes": ["image/*"]}))
+        assert not check_media(voice, MediaRequirements())
+        assert check_media(voice, MediaRequirements.from_node({"mediaTypes": ["audio"], "mimeTypes": ["audio/ogg"]}))
+
+    def test_variable_references_stored_media(self):
+        """Test the session variable points to the stored object"""
+        pdf = extract_media("document", PDF_CONTENT)
+        stored = StoredMedia(media_id="wamid-media-1", reference="org/conv/wamid-media-1.pdf", size=48213, sha256="ab")
+
+        variable = media_variable(pdf, stored)
+
+        assert variable["reference"] == "org/conv/wamid-media-1.pdf"
+        assert variable["mime_type"] == "application/pdf"
+        assert variable["size"] == 48213
+        assert variable["type"] == "document"
+
+
+class TestMediaConfig:
+    """Tests for check_media_config() and the storage backends"""
+
+    def test_reports_unusable_settings(self):
+        """Test nodes need an output variable, known types and a size within the limit"""
+        assert check_media_config({"outputVariable": "comprovante"}) == []
+        assert check_media_config({"mediaTypes": ["pdf"], "maxSizeMb": 200}) == [
+            "Unknown media types: pdf",
+            "outputVariable is required",
+            f"maxSizeMb is over the WhatsApp limit of {MAX_MEDIA_SIZE_MB} MB",
+        ]
+
+    def test_storage_backends(self, tmp_path):
+        """Test built-in backends, import paths and invalid settings"""
+        assert isinstance(build_media_storage("local", str(tmp_path)), LocalMediaStorage)
+        assert isinstance(build_media_storage("memory", ""), InMemoryMediaStorage)
+        assert isinstance(
+            build_media_storage("pytake_client.media:InMemoryMediaStorage", ""), InMemoryMediaStorage
+        )
+
+        with pytest.raises(ValueError):
+            build_media_storage("s3", "")
+        with pytest.raises(ValueError):
+            build_media_storage("pytake_client.webhooks:StoredMedia", "")
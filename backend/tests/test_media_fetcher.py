@@ -0,0 +1,148 @@
+"""
+Media Fetcher Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import hashlib
+from contextlib import asynccontextmanager
+
+import pytest
+
+from pytake_client.errors import MediaFetchError
+from pytake_client.media import InMemoryMediaStorage, MediaFetcher
+from pytake_client.processor import WebhookHandler, WebhookProcessor
+
+IMAGE = b"\xff\xd8\xff" + b"x" * 1000
+
+
+class FakeResponse:
+    def __init__(self, status_code=200, json=None, body=b""):
+        self.status_code = status_code
+        self._json = json
+        self.body = body
+
+    def json(self):
+        return self._json
+
+    async def aiter_bytes(self):
+        for start in range(0, len(self.body), 256):
+            yield self.body[start:start + 256]
+
+
+class FakeGraph:
+    """httpx.AsyncClient-like client serving the media lookup and download"""
+
+    def __init__(self, body=IMAGE, sha256=None, lookup_status=200):
+        self.body = body
+        self.sha256 = sha256 if sha256 is not None else hashlib.sha256(body).hexdigest()
+        self.lookup_status = lookup_status
+        self.requests = []
+
+    async def get(self, url, headers=None):
+        self.requests.append(("GET", url, headers))
+        return FakeResponse(self.lookup_status, json={
+            "url": "https://lookaside.fbsbx.com/media/abc",
+            "mime_type": "image/jpeg",
+            "sha256": self.sha256,
+            "file_size": len(self.body),
+        })
+
+    @asynccontextmanager
+    async def stream(self, method, url, headers=None):
+        self.requests.append((method, url, headers))
+        yield FakeResponse(body=self.body)
+
+    async def aclose(self):
+        pass
+
+
+def _image_payload(media_id="media-1"):
+    return {"entry": [{"changes": [{
+        "field": "messages",
+        "value": {
+            "metadata": {"phone_number_id": "123"},
+            "messages": [{
+                "id": "wamid.1",
+                "from": "5511999999999",
+                "type": "image",
+                "image": {"id": media_id, "mime_type": "image/jpeg"},
+            }],
+        },
+    }]}]}
+
+
+class TestMediaFetcher:
+    """Tests for resolving, downloading and verifying media"""
+
+    @pytest.mark.asyncio
+    async def test_fetch_stores_media(self):
+        graph = FakeGraph()
+        storage = InMemoryMediaStorage()
+        fetcher = MediaFetcher("token", storage, http_client=graph)
+
+        media = await fetcher.fetch("media-1", key_prefix="123/")
+
+        assert media.reference == "123/media-1.jpg"
+        assert media.size == len(IMAGE)
+        assert media.sha256 == hashlib.sha256(IMAGE).hexdigest()
+        assert storage.objects["123/media-1.jpg"] == IMAGE
+        assert graph.requests[0][1] == "https://graph.facebook.com/v21.0/media-1"
+        # The download also needs the access token
+        assert all(headers == {"Authorization": "Bearer token"} for _, _, headers in graph.requests)
+
+    @pytest.mark.asyncio
+    async def test_checksum_mismatch_deletes_object(self):
+        storage = InMemoryMediaStorage()
+        fetcher = MediaFetcher("token", storage, http_client=FakeGraph(sha256="0" * 64))
+
+        with pytest.raises(MediaFetchError) as exc:
+            await fetcher.fetch("media-1")
+
+        assert exc.value.reason == "checksum mismatch"
+        assert storage.objects == {}
+
+    @pytest.mark.asyncio
+    async def test_size_limit_aborts_download(self):
+        storage = InMemoryMediaStorage()
+        fetcher = MediaFetcher("token", storage, max_bytes=512, http_client=FakeGraph())
+
+        with pytest.raises(MediaFetchError, match="larger than 512 bytes"):
+            await fetcher.fetch("media-1")
+
+        assert storage.objects == {}
+
+
+class TestMediaPipeline:
+    """Tests for attaching media before events reach handlers"""
+
+    @pytest.mark.asyncio
+    async def test_handlers_receive_stored_media(self):
+        seen = []
+
+        class Recorder(WebhookHandler):
+            async def on_message(self, event):
+                seen.append((event.media, event.media_error))
+
+        fetcher = MediaFetcher("token", InMemoryMediaStorage(), http_client=FakeGraph())
+        processor = WebhookProcessor(handlers=[Recorder()], media_fetcher=fetcher)
+
+        await processor.dispatch(_image_payload())
+
+        media, error = seen[0]
+        assert media.reference == "123/media-1.jpg"
+        assert error is None
+
+    @pytest.mark.asyncio
+    async def test_failure_is_recorded_on_event(self):
+        """Test a failed lookup still delivers the message, with media_error set"""
+        fetcher = MediaFetcher("token", InMemoryMediaStorage(), http_client=FakeGraph(lookup_status=404))
+        processor = WebhookProcessor(media_fetcher=fetcher)
+
+        async def handle(event):
+            return event.media, event.media_error
+
+        results = await processor.process(_image_payload(), handle)
+
+        assert results[0].ok
+        assert results[0].result == (None, "lookup failed with status 404")
@@ -0,0 +1,176 @@
+"""
+Message Content Cipher Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+from cryptography.fernet import Fernet
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+import app.websocket.manager as ws_manager
+from app.core.encryption import factory
+from app.core.encryption.base import DecryptionError
+from app.core.encryption.fernet_provider import FernetEncryptionProvider
+from app.core.encryption.message_cipher import MessageContentCipher
+from app.models.conversation import Message
+from app.repositories.conversation import MessageRepository
+from app.services.whatsapp_service import WhatsAppService
+from tests.conftest import OrganizationFactory
+
+
+@pytest.fixture
+def cipher(monkeypatch) -> MessageContentCipher:
+    provider = FernetEncryptionProvider(encryption_key=Fernet.generate_key().decode())
+    monkeypatch.setattr(factory, "encryption_service", provider)
+    return MessageContentCipher()
+
+
+class TestMessageContentCipher:
+    """Tests for per-tenant message encryption"""
+
+    def test_round_trip(self, cipher: MessageContentCipher):
+        """Test content is encrypted and decrypted with the tenant key"""
+        data_key = cipher.generate_data_key()
+        content = {"text": "Resultado do exame: normal"}
+
+        envelope = cipher.encrypt_content(content, data_key)
+
+        assert cipher.is_encrypted(envelope)
+        assert "exame" not in envelope["ciphertext"]
+        assert cipher.decrypt_content(envelope, data_key) == content
+
+    def test_plain_content_passes_through(self, cipher: MessageContentCipher):
+        """Test plain content is returned unchanged on decrypt"""
+        data_key = cipher.generate_data_key()
+
+        assert cipher.decrypt_content({"text": "hi"}, data_key) == {"text": "hi"}
+
+    def test_wrong_tenant_key_fails(self, cipher: MessageContentCipher):
+        """Test another tenant's key cannot decrypt the envelope"""
+        envelope = cipher.encrypt_content({"text": "hi"}, cipher.generate_data_key())
+
+        with pytest.raises(DecryptionError):
+            cipher.decrypt_content(envelope, cipher.generate_data_key())
+
+
+async def _organization(db, data_key=None):
+    return await OrganizationFactory.create_in_db(
+        db, message_encryption_enabled=data_key is not None, message_data_key=data_key
+    )
+
+
+async def _message(db, organization, content, extra_data=None) -> Message:
+    """Message stored as given (already encrypted content stays encrypted)"""
+    message = Message(
+        id=uuid4(),
+        organization_id=organization.id,
+        conversation_id=uuid4(),
+        direction="inbound",
+        sender_type="contact",
+        message_type="text",
+        whatsapp_message_id=f"wamid.{uuid4().hex}",
+        content=content,
+        extra_data=extra_data or {},
+    )
+    db.add(message)
+    await db.commit()
+    return message
+
+
+async def _stored(db, message):
+    """Content and extra_data as stored in the database"""
+    result = await db.execute(select(Message.content, Message.extra_data).where(Message.id == message.id))
+    return result.one()
+
+
+class TestMessageRepositoryEncryption:
+    """Tests for encryption on the message repository write and read paths"""
+
+    @pytest.mark.asyncio
+    async def test_update_encrypts_content(self, cipher: MessageContentCipher, db_session: AsyncSession):
+        """Test updated content is stored encrypted for encrypted tenants"""
+        data_key = cipher.generate_data_key()
+        message = await _message(db_session, await _organization(db_session, data_key), {"text": "oi"})
+
+        await MessageRepository(db_session).update(
+            message.id, {"content": {"text": "Resultado do exame"}, "status": "read"}
+        )
+
+        content, _ = await _stored(db_session, message)
+        assert await db_session.scalar(select(Message.status).where(Message.id == message.id)) == "read"
+        assert cipher.is_encrypted(content)
+        assert cipher.decrypt_content(content, data_key) == {"text": "Resultado do exame"}
+
+    @pytest.mark.asyncio
+    async def test_update_without_encryption(self, cipher: MessageContentCipher, db_session: AsyncSession):
+        """Test content stays plain for tenants without encryption"""
+        message = await _message(db_session, await _organization(db_session), {"text": "olá"})
+
+        await MessageRepository(db_session).update(message.id, {"content": {"text": "oi"}})
+
+        content, _ = await _stored(db_session, message)
+        assert content == {"text": "oi"}
+
+    @pytest.mark.asyncio
+    async def test_get_by_whatsapp_id_decrypts(self, cipher: MessageContentCipher, db_session: AsyncSession):
+        """Test messages looked up by WhatsApp ID come back decrypted"""
+        data_key = cipher.generate_data_key()
+        organization = await _organization(db_session, data_key)
+        message = await _message(db_session, organization, cipher.encrypt_content({"text": "oi"}, data_key))
+
+        found = await MessageRepository(db_session).get_by_whatsapp_id(message.whatsapp_message_id, organization.id)
+
+        assert found.content == {"text": "oi"}
+
+    @pytest.mark.asyncio
+    async def test_edit_history_round_trip(self, cipher: MessageContentCipher, db_session: AsyncSession):
+        """Test edit history entries are encrypted on write and decrypted on read"""
+        data_key = cipher.generate_data_key()
+        message = await _message(db_session, await _organization(db_session, data_key), {"text": "novo"})
+        repo = MessageRepository(db_session)
+        history = [{"content": {"text": "texto antigo"}, "replaced_at": "2025-11-20T10:00:00+00:00"}]
+
+        await repo.update(message.id, {"extra_data": {"edit_history": history, "edited_at": "x"}})
+
+        _, stored = await _stored(db_session, message)
+        assert stored["edited_at"] == "x"
+        assert cipher.is_encrypted(stored["edit_history"][0]["content"])
+
+        found = await repo.get(message.id)
+        assert found.extra_data["edit_history"] == history
+
+
+class TestMessageEditEncryption:
+    """Tests for contact edits of messages of encrypted tenants"""
+
+    @pytest.mark.asyncio
+    async def test_edit_keeps_content_and_history_encrypted(
+        self, cipher: MessageContentCipher, db_session: AsyncSession, monkeypatch
+    ):
+        """Test the old text goes to the history decrypted and everything is stored encrypted"""
+        data_key = cipher.generate_data_key()
+        organization = await _organization(db_session, data_key)
+        message = await _message(db_session, organization, cipher.encrypt_content({"text": "texto antigo"}, data_key))
+        emitted = []
+
+        async def emit_to_conversation(conversation_id, event, data, exclude_sid=None):
+            emitted.append((event, data))
+
+        monkeypatch.setattr(ws_manager, "emit_to_conversation", emit_to_conversation)
+        service = WhatsAppService(db_session)
+        edit = SimpleNamespace(
+            original_message_id=message.whatsapp_message_id, content={"text": "texto novo"}, timestamp=1763632800
+        )
+
+        await service._process_message_edit(edit, SimpleNamespace(organization_id=organization.id))
+
+        content, extra_data = await _stored(db_session, message)
+        assert cipher.decrypt_content(content, data_key) == {"text": "texto novo"}
+        entry = extra_data["edit_history"][0]
+        assert cipher.decrypt_content(entry["content"], data_key) == {"text": "texto antigo"}
+        assert emitted[0][1]["content"] == {"text": "texto novo"}
@@ -0,0 +1,111 @@
+"""
+Message Limits Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.message_limits import (
+    OutboundMessageError,
+    ensure_valid_outbound_message,
+    grapheme_len,
+    truncate_graphemes,
+    validate_buttons,
+    validate_cta_url,
+    validate_list,
+    validate_outbound_message,
+)
+
+
+def _fields(issues):
+    return [issue.field for issue in issues]
+
+
+class TestGraphemes:
+    """Tests for grapheme counting and truncation"""
+
+    def test_emoji_and_accents_count_as_one(self):
+        """Test skin tones, flags, ZWJ families and combining accents"""
+        assert grapheme_len("👍🏽") == 1
+        assert grapheme_len("🇧🇷🇵🇹") == 2
+        assert grapheme_len("👨‍👩‍👧") == 1
+        assert grapheme_len("ã") == 1
+        assert grapheme_len("1️⃣") == 1
+        assert grapheme_len("Promoção 🎉") == 10
+
+    def test_truncate_does_not_split_emoji(self):
+        """Test truncation keeps whole clusters"""
+        assert truncate_graphemes("Oi 👍🏽👍🏽", 4) == "Oi 👍🏽"
+        assert truncate_graphemes("🇧🇷🇧🇷🇧🇷", 2) == "🇧🇷🇧🇷"
+        assert truncate_graphemes("Olá mundo", 5, ellipsis="…") == "Olá …"
+        assert truncate_graphemes("curto", 20) == "curto"
+
+
+class TestValidators:
+    """Tests for the message validators"""
+
+    def test_button_limits(self):
+        """Test titles over 20 characters, too many buttons and duplicated ids"""
+        buttons = [
+            {"id": "a", "title": "Sim 👍"},
+            {"id": "a", "title": "Quero falar com atendente"},
+            {"id": "c", "title": "Não"},
+            {"id": "d", "title": "Depois"},
+        ]
+
+        fields = _fields(validate_buttons("Escolha uma opção", buttons))
+
+        assert "buttons" in fields
+        assert "buttons[1].title" in fields
+        assert "buttons[1].id" in fields
+        assert "buttons[0].title" not in fields
+
+    def test_emoji_title_within_limit(self):
+        """Test a 20-grapheme title with emoji is accepted"""
+        title = "👍🏽" * 20
+
+        assert validate_buttons("Body", [{"id": "1", "title": title}]) == []
+
+    def test_list_row_limits(self):
+        """Test total rows and row field lengths"""
+        rows = [{"id": str(i), "title": f"Opção {i}"} for i in range(11)]
+        rows[0]["description"] = "x" * 73
+
+        fields = _fields(validate_list("Body", "Ver opções", [{"title": "Menu", "rows": rows}]))
+
+        assert "rows" in fields
+        assert "sections[0].rows[0].description" in fields
+
+    def test_list_requires_section_titles_when_multiple(self):
+        """Test section titles are required with more than one section"""
+        sections = [
+            {"rows": [{"id": "1", "title": "A"}]},
+            {"title": "B", "rows": [{"id": "2", "title": "B"}]},
+        ]
+
+        assert _fields(validate_list("Body", "Menu", sections)) == ["sections[0].title"]
+
+    def test_cta_url_limits(self):
+        """Test the button label length and that only absolute http(s) URLs are accepted"""
+        assert validate_cta_url("Acompanhe seu pedido", "Rastrear 📦", "https://loja.com/p/1") == []
+
+        fields = _fields(validate_cta_url("", "Clique aqui para rastrear", "javascript:alert(1)"))
+        assert fields == ["body", "display_text", "url"]
+        assert _fields(validate_outbound_message("cta_url", {"body": "Oi", "display_text": "Ver"})) == ["url"]
+
+    def test_text_over_limit(self):
+        """Test text bodies are limited to 4096 characters"""
+        issues = validate_outbound_message("text", {"text": "á" * 4097})
+
+        assert len(issues) == 1
+        assert issues[0].limit == 4096
+        assert issues[0].actual == 4097
+
+    def test_ensure_raises_with_all_issues(self):
+        """Test ensure_valid_outbound_message reports every violation"""
+        with pytest.raises(OutboundMessageError) as exc_info:
+            ensure_valid_outbound_message("image", {"url": "https://x.com/a.png", "caption": "x" * 1025})
+
+        assert _fields(exc_info.value.issues) == ["caption"]
+        ensure_valid_outbound_message("text", {"text": "Olá 👋"})
@@ -0,0 +1,200 @@
+"""
+Message Status Tracker Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+import app.websocket.manager as ws_manager
+from app.models.campaign import Campaign
+from app.models.conversation import Conversation, Message
+from app.schemas.message import DeliveryStats
+from app.services.message_status_tracker import MessageStatusTracker, next_status, receipt_error
+from tests.conftest import OrganizationFactory
+
+
+@pytest_asyncio.fixture
+async def conversation(db_session: AsyncSession) -> Conversation:
+    """Open conversation of a contact"""
+    org = await OrganizationFactory.create_in_db(db_session)
+    conversation = Conversation(
+        id=uuid4(),
+        organization_id=org.id,
+        contact_id=uuid4(),
+        whatsapp_number_id=uuid4(),
+        status="open",
+    )
+    db_session.add(conversation)
+    await db_session.commit()
+    return conversation
+
+
+async def _message(db, conversation, status="sent", **extra_data) -> Message:
+    message = Message(
+        id=uuid4(),
+        organization_id=conversation.organization_id,
+        conversation_id=conversation.id,
+        direction="outbound",
+        sender_type="system",
+        message_type="text",
+        content={"text": "Olá!"},
+        whatsapp_message_id="wamid.1",
+        status=status,
+        extra_data=dict(extra_data),
+    )
+    db.add(message)
+    await db.commit()
+    return message
+
+
+async def _campaign(db, conversation, **overrides) -> Campaign:
+    data = dict(
+        id=uuid4(),
+        organization_id=conversation.organization_id,
+        name="Black Friday",
+        status="running",
+        audience_type="all",
+        messages_sent=10,
+        messages_delivered=0,
+        messages_read=0,
+        messages_failed=0,
+        messages_pending=0,
+        total_recipients=10,
+        error_count=0,
+        delivery_rate=0.0,
+        read_rate=0.0,
+        progress_percentage=100.0,
+        message_statuses={},
+    )
+    data.update(overrides)
+    campaign = Campaign(**data)
+    db.add(campaign)
+    await db.commit()
+    return campaign
+
+
+@pytest.fixture
+def events(monkeypatch):
+    """WebSocket events emitted by the tracker"""
+    emitted = []
+
+    async def emit_to_conversation(conversation_id, event, data, exclude_sid=None):
+        emitted.append(("conversation", event, data))
+
+    async def emit_to_organization(organization_id, event, data):
+        emitted.append(("organization", event, data))
+
+    monkeypatch.setattr(ws_manager, "emit_to_conversation", emit_to_conversation)
+    monkeypatch.setattr(ws_manager, "emit_to_organization", emit_to_organization)
+    return emitted
+
+
+class TestStatusTransitions:
+    """Tests for forward-only status progression"""
+
+    @pytest.mark.parametrize("current,receipt,expected", [
+        ("pending", "sent", "sent"),
+        ("sent", "read", "read"),
+        ("read", "delivered", None),
+        ("delivered", "delivered", None),
+        ("sent", "failed", "failed"),
+        ("delivered", "failed", None),
+        ("failed", "read", None),
+        ("sent", "deleted", None),
+    ])
+    def test_next_status(self, current, receipt, expected):
+        assert next_status(current, receipt) == expected
+
+    def test_receipt_error_prefers_details(self):
+        status = {"errors": [{
+            "code": 131047,
+            "title": "Re-engagement message",
+            "error_data": {"details": "Message failed to send because more than 24 hours have passed"},
+        }]}
+
+        assert receipt_error(status) == {
+            "code": "131047",
+            "title": "Re-engagement message",
+            "detail": "Message failed to send because more than 24 hours have passed",
+            "kind": "reengagement_window",
+            "needs_template": True,
+            "permanent_failure": False,
+            "guidance": "Outside the 24h customer service window - send an approved template",
+        }
+
+    def test_delivery_stats_rates(self):
+        stats = DeliveryStats.from_counts({"pending": 2, "sent": 1, "delivered": 4, "read": 4, "failed": 1})
+
+        assert stats.total == 12
+        assert stats.delivery_rate == 80.0
+        assert stats.read_rate == 50.0
+        assert stats.failure_rate == 10.0
+
+
+class TestMessageStatusTracker:
+    """Tests for applying receipts"""
+
+    @pytest.mark.asyncio
+    async def test_out_of_order_read_fills_earlier_steps(self, db_session: AsyncSession, conversation, events):
+        message = await _message(db_session, conversation, "sent")
+        tracker = MessageStatusTracker(db_session)
+
+        change = await tracker.handle({"id": "wamid.1", "status": "read", "timestamp": "1762700000"})
+        stale = await tracker.handle({"id": "wamid.1", "status": "delivered", "timestamp": "1762699990"})
+
+        await db_session.refresh(message)
+        assert change.previous == "sent" and message.status == "read"
+        assert message.delivered_at == message.read_at == message.sent_at
+        assert stale is None
+        assert [e[1] for e in events] == ["message:status"]
+
+    @pytest.mark.asyncio
+    async def test_failure_emits_error_detail(self, db_session: AsyncSession, conversation, events):
+        """Test a failed receipt stores the error and emits message:failed"""
+        message = await _message(db_session, conversation, "sent")
+        tracker = MessageStatusTracker(db_session)
+
+        await tracker.handle({
+            "id": "wamid.1",
+            "status": "failed",
+            "timestamp": "1762700000",
+            "errors": [{"code": 131026, "title": "Message undeliverable"}],
+        })
+
+        await db_session.refresh(message)
+        assert (message.status, message.error_code, message.error_message) == (
+            "failed", "131026", "Message undeliverable"
+        )
+        failures = [(target, data) for target, event, data in events if event == "message:failed"]
+        assert [target for target, _ in failures] == ["conversation", "organization"]
+        assert failures[0][1]["error_code"] == "131026"
+        assert failures[0][1]["previous_status"] == "sent"
+        assert failures[0][1]["permanent_failure"] is True
+        assert message.extra_data["delivery_error"]["kind"] == "undeliverable"
+
+    @pytest.mark.asyncio
+    async def test_campaign_counts_each_step_once(self, db_session: AsyncSession, conversation, events, monkeypatch):
+        """Test redelivered or out-of-order receipts do not double count"""
+        campaign = await _campaign(
+            db_session, conversation, message_statuses={str(conversation.contact_id): {"status": "sent"}}
+        )
+        await _message(db_session, conversation, "sent", campaign_id=str(campaign.id))
+        tracker = MessageStatusTracker(db_session)
+        monkeypatch.setattr(tracker, "_broadcast_campaign_progress", _noop)
+
+        for receipt in ("delivered", "delivered", "read", "delivered"):
+            await tracker.handle({"id": "wamid.1", "status": receipt})
+
+        await db_session.refresh(campaign)
+        assert (campaign.messages_delivered, campaign.messages_read) == (1, 1)
+        assert (campaign.delivery_rate, campaign.read_rate) == (10.0, 10.0)
+        assert campaign.message_statuses[str(conversation.contact_id)]["status"] == "read"
+
+
+async def _noop(*args, **kwargs):
+    return None
@@ -0,0 +1,203 @@
+"""
+Messaging Tier Shaper Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+from types import SimpleNamespace
+
+import pytest
+
+from app.core import whatsapp_rate_limit
+from app.core.whatsapp_rate_limit import (
+    DEFAULT_MESSAGING_TIER,
+    TIER_WINDOW_SECONDS,
+    MessagingTierShaper,
+    get_tier_shaper,
+    next_slot_at,
+    tier_limit,
+)
+
+NOW = 1_700_000_000.0
+
+
+class FakeRedis:
+    """In-memory sorted sets and keys used by the shaper"""
+
+    def __init__(self):
+        self.zsets = {}
+        self.keys = {}
+
+    async def set(self, key, value, expire=None, nx=False):
+        if nx and key in self.keys:
+            return False
+        self.keys[key] = value
+        return True
+
+    async def get(self, key):
+        return self.keys.get(key)
+
+    async def delete(self, *keys):
+        return sum(1 for key in keys if self.keys.pop(key, None) is not None)
+
+    async def expire(self, key, seconds):
+        return True
+
+    async def zadd(self, name, mapping):
+        self.zsets.setdefault(name, {}).update(mapping)
+        return len(mapping)
+
+    async def zrem(self, name, *values):
+        return sum(1 for value in values if self.zsets.get(name, {}).pop(value, None) is not None)
+
+    async def zscore(self, name, value):
+        return self.zsets.get(name, {}).get(value)
+
+    async def zrange(self, name, start, end, withscores=False):
+        items = sorted(self.zsets.get(name, {}).items(), key=lambda item: item[1])
+        return items if withscores else [member for member, _ in items]
+
+    async def zremrangebyscore(self, name, min_score, max_score):
+        zset = self.zsets.get(name, {})
+        expired = [member for member, score in zset.items() if score <= max_score]
+        for member in expired:
+            del zset[member]
+        return len(expired)
+
+
+@pytest.fixture
+def redis(monkeypatch) -> FakeRedis:
+    fake = FakeRedis()
+    monkeypatch.setattr(whatsapp_rate_limit, "rate_limit_redis", fake)
+    return fake
+
+
+def _at(timestamp: float) -> datetime:
+    return datetime.fromtimestamp(timestamp, tz=timezone.utc)
+
+
+class TestTierLimits:
+    """Tests for tier limits and slot computation"""
+
+    def test_tier_limit(self):
+        """Test known, unlimited and unknown tiers"""
+        assert tier_limit("TIER_1K") == 1000
+        assert tier_limit("TIER_2K") == 2000
+        assert tier_limit("TIER_UNLIMITED") is None
+        assert tier_limit(None) == tier_limit(DEFAULT_MESSAGING_TIER)
+        assert tier_limit("TIER_SOMETHING_NEW") == tier_limit(DEFAULT_MESSAGING_TIER)
+
+    def test_next_slot_at(self):
+        """Test room now, the oldest slot expiring and queued sends"""
+        assert next_slot_at([NOW - 10], 2, NOW) == NOW
+        assert next_slot_at([NOW - 100, NOW - 10], 2, NOW) == NOW - 100 + TIER_WINDOW_SECONDS
+        # Third recipient over the limit waits for the second slot
+        scores = [NOW - 100, NOW - 10, NOW - 100 + TIER_WINDOW_SECONDS]
+        assert next_slot_at(scores, 2, NOW) == NOW - 10 + TIER_WINDOW_SECONDS
+
+    def test_qr_code_numbers_are_not_shaped(self):
+        number = SimpleNamespace(id="n1", connection_type="qr_code", messaging_limit_tier=None)
+        assert get_tier_shaper(number) is None
+
+
+class TestMessagingTierShaper:
+    """Tests for slot reservation across senders of a number"""
+
+    @pytest.mark.asyncio
+    async def test_overflow_is_queued_with_eta(self, redis):
+        """Test recipients over the tier get the ETA of the slot they take over"""
+        shaper = MessagingTierShaper("n1", "TIER_50")
+        shaper.limit = 2
+
+        first = await shaper.reserve("5511900000001", now=NOW)
+        second = await shaper.reserve("5511900000002", now=NOW + 60)
+        third = await shaper.reserve("5511900000003", now=NOW + 120)
+        fourth = await shaper.reserve("5511900000004", now=NOW + 180)
+
+        assert first.allowed and second.allowed
+        assert not third.allowed
+        assert third.send_at == _at(NOW + TIER_WINDOW_SECONDS)
+        assert fourth.send_at == _at(NOW + 60 + TIER_WINDOW_SECONDS)
+
+    @pytest.mark.asyncio
+    async def test_recipient_in_window_is_free(self, redis):
+        """Test a customer already reached today does not take another slot"""
+        shaper = MessagingTierShaper("n1", "TIER_50")
+        shaper.limit = 1
+
+        await shaper.reserve("5511900000001", now=NOW)
+        again = await shaper.reserve("5511900000001", now=NOW + 3600)
+
+        assert again.allowed
+        assert not again.reserved
+
+    @pytest.mark.asyncio
+    async def test_queued_recipient_is_sent_at_its_slot(self, redis):
+        """Test a deferred send goes out once its reserved slot is due"""
+        shaper = MessagingTierShaper("n1", "TIER_50")
+        shaper.limit = 1
+
+        await shaper.reserve("5511900000001", now=NOW)
+        queued = await shaper.reserve("5511900000002", now=NOW + 10)
+        retry = await shaper.reserve("5511900000002", now=queued.send_at.timestamp())
+
+        assert not queued.allowed
+        assert retry.allowed
+        # The first recipient left the window
+        assert await redis.zscore(shaper.recipients_key, "5511900000001") is None
+
+    @pytest.mark.asyncio
+    async def test_shared_across_senders(self, redis):
+        """Test campaigns and transactional sends of a number share the tier"""
+        campaign = MessagingTierShaper("n1", "TIER_50")
+        dunning = MessagingTierShaper("n1", "TIER_50")
+        other_number = MessagingTierShaper("n2", "TIER_50")
+        campaign.limit = dunning.limit = other_number.limit = 1
+
+        await campaign.reserve("5511900000001", now=NOW)
+
+        assert not (await dunning.reserve("5511900000002", now=NOW)).allowed
+        assert (await other_number.reserve("5511900000002", now=NOW)).allowed
+
+    @pytest.mark.asyncio
+    async def test_release_frees_the_slot(self, redis):
+        shaper = MessagingTierShaper("n1", "TIER_50")
+        shaper.limit = 1
+
+        await shaper.reserve("5511900000001", now=NOW)
+        await shaper.release("5511900000001")
+
+        assert (await shaper.reserve("5511900000002", now=NOW)).allowed
+
+    @pytest.mark.asyncio
+    async def test_utilization(self, redis):
+        """Test used, queued and next slot metrics"""
+        shaper = MessagingTierShaper("n1", "TIER_50")
+
+        for index in range(50):
+            await shaper.reserve(f"55119{index:08d}", now=NOW + index)
+        await shaper.reserve("5511999999999", now=NOW + 100)
+        await shaper.record("5511988888888", now=NOW + 100)
+
+        usage = await shaper.get_utilization(now=NOW + 200)
+
+        assert usage["tier"] == "TIER_50"
+        assert usage["used"] == 51
+        assert usage["queued"] == 1
+        assert usage["remaining"] == 0
+        assert usage["percentage"] == 102.0
+        assert usage["next_slot_at"] is not None
+
+    @pytest.mark.asyncio
+    async def test_unlimited_tier(self, redis):
+        """Test unlimited numbers are tracked but never queued"""
+        shaper = MessagingTierShaper("n1", "TIER_UNLIMITED")
+
+        for index in range(300):
+            assert (await shaper.reserve(f"55119{index:08d}", now=NOW)).allowed
+
+        usage = await shaper.get_utilization(now=NOW)
+        assert usage["limit"] is None
+        assert usage["remaining"] is None
+        assert usage["used"] == 300
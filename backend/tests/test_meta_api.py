@@ -0,0 +1,620 @@
+"""
+Meta Cloud API Client Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+from pydantic import BaseModel
+
+from app.integrations import meta_api
+from app.integrations.meta_api import MetaAPIError, MetaCloudAPI, ResponseInfo
+from app.utils.template_builder import TemplateBuildError, TemplateBuilder
+
+
+def _client() -> MetaCloudAPI:
+    return MetaCloudAPI(phone_number_id="123", access_token="token")
+
+
+class _FakeResponse:
+    def __init__(self, status_code, data, headers=None):
+        self.status_code = status_code
+        self._data = data
+        self.headers = headers or {}
+
+    def json(self):
+        return self._data
+
+
+def _fake_http_client(response, requests):
+    responses = response if isinstance(response, list) else None
+
+    class FakeAsyncClient:
+        def __init__(self, *args, **kwargs):
+            pass
+
+        async def __aenter__(self):
+            return self
+
+        async def __aexit__(self, *args):
+            return False
+
+        async def get(self, url, params=None, headers=None):
+            requests.append((url, params))
+            return responses.pop(0) if responses is not None else response
+
+        async def request(self, method, url, params=None, json=None, headers=None):
+            requests.append((method, url, params, json))
+            return responses.pop(0) if responses is not None else response
+
+        async def post(self, url, json=None, headers=None):
+            requests.append((url, json))
+            return responses.pop(0) if responses is not None else response
+
+    return FakeAsyncClient
+
+
+class TestMarkMessagesAsRead:
+    """Tests for MetaCloudAPI.mark_messages_as_read()"""
+
+    @pytest.mark.asyncio
+    async def test_marks_each_message_once(self, monkeypatch):
+        """Test duplicates are sent only once"""
+        client = _client()
+        calls = []
+
+        async def fake_mark(message_id, typing=False):
+            calls.append(message_id)
+            return {"success": True}
+
+        monkeypatch.setattr(client, "mark_message_as_read", fake_mark)
+
+        result = await client.mark_messages_as_read(["wamid.1", "wamid.2", "wamid.1"])
+
+        assert sorted(calls) == ["wamid.1", "wamid.2"]
+        assert sorted(result["read"]) == ["wamid.1", "wamid.2"]
+        assert result["failed"] == {}
+
+    @pytest.mark.asyncio
+    async def test_failures_do_not_stop_batch(self, monkeypatch):
+        """Test a failing message is reported while the others are marked"""
+        client = _client()
+
+        async def fake_mark(message_id, typing=False):
+            if message_id == "wamid.bad":
+                raise MetaAPIError("Invalid parameter")
+            return {"success": True}
+
+        monkeypatch.setattr(client, "mark_message_as_read", fake_mark)
+
+        result = await client.mark_messages_as_read(["wamid.ok", "wamid.bad"])
+
+        assert result["read"] == ["wamid.ok"]
+        assert result["failed"] == {"wamid.bad": "Invalid parameter"}
+
+
+class TestQRCodes:
+    """Tests for the message_qrdls helpers"""
+
+    @pytest.mark.asyncio
+    async def test_create_qr_code_payload(self, monkeypatch):
+        """Test the prefilled message and image format are sent"""
+        client = _client()
+        requests = []
+
+        async def fake_request(method, path="", params=None, payload=None):
+            requests.append((method, path, payload))
+            return {"code": "ABC", "prefilled_message": payload["prefilled_message"]}
+
+        monkeypatch.setattr(client, "_qr_request", fake_request)
+
+        result = await client.create_qr_code("Quero a promoção", image_format="svg")
+
+        assert requests == [("POST", "", {"prefilled_message": "Quero a promoção", "generate_qr_image": "SVG"})]
+        assert result["code"] == "ABC"
+
+    @pytest.mark.asyncio
+    async def test_get_missing_qr_code(self, monkeypatch):
+        """Test an unknown code returns None"""
+        client = _client()
+
+        async def fake_request(method, path="", params=None, payload=None):
+            return {"data": []}
+
+        monkeypatch.setattr(client, "_qr_request", fake_request)
+
+        assert await client.get_qr_code("NOPE") is None
+
+
+class TestTwoStepVerification:
+    """Tests for PIN and registration helpers"""
+
+    @pytest.mark.asyncio
+    async def test_invalid_pin_is_rejected_locally(self):
+        """Test PINs that are not 6 digits never reach Meta"""
+        client = _client()
+
+        for pin in ("12345", "1234567", "12a456"):
+            with pytest.raises(MetaAPIError):
+                await client.set_two_step_pin(pin)
+
+    @pytest.mark.asyncio
+    async def test_register_payload(self, monkeypatch):
+        """Test register sends messaging_product and pin"""
+        client = _client()
+        requests = []
+
+        async def fake_request(path, payload):
+            requests.append((path, payload))
+            return {"success": True}
+
+        monkeypatch.setattr(client, "_phone_number_request", fake_request)
+
+        assert await client.register_phone_number("123456") is True
+        assert await client.set_two_step_pin("654321") is True
+        assert requests == [
+            ("/register", {"messaging_product": "whatsapp", "pin": "123456"}),
+            ("", {"pin": "654321"}),
+        ]
+
+
+class TestPhoneNumberInfo:
+    """Tests for MetaCloudAPI.get_phone_number_info()"""
+
+    @pytest.mark.asyncio
+    async def test_quality_and_messaging_limit(self, monkeypatch):
+        """Test the requested fields and the normalized response"""
+        requests = []
+        response = _FakeResponse(200, {
+            "display_phone_number": "+55 11 99999-9999",
+            "verified_name": "PyTake",
+            "quality_rating": "YELLOW",
+            "messaging_limit_tier": "TIER_1K",
+            "name_status": "APPROVED",
+            "throughput": {"level": "STANDARD"},
+            "id": "123",
+        })
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+
+        info = await _client().get_phone_number_info()
+
+        url, params = requests[0]
+        assert url.endswith("/123")
+        assert "quality_rating" in params["fields"].split(",")
+        assert info["quality_rating"] == "YELLOW"
+        assert info["messaging_limit_tier"] == "TIER_1K"
+        assert info["daily_conversation_limit"] == 1000
+        assert info["name_status"] == "APPROVED"
+        assert info["throughput_level"] == "STANDARD"
+
+    @pytest.mark.asyncio
+    async def test_error_raises_meta_api_error(self, monkeypatch):
+        """Test Graph errors keep the HTTP status code"""
+        response = _FakeResponse(401, {"error": {"message": "Invalid OAuth access token", "code": 190}})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, []))
+
+        with pytest.raises(MetaAPIError) as exc_info:
+            await _client().get_phone_number_info()
+
+        assert exc_info.value.status_code == 401
+        assert exc_info.value.error_code == "190"
+
+
+class TestTemplatePagination:
+    """Tests for MetaCloudAPI.list_all_templates()"""
+
+    @pytest.mark.asyncio
+    async def test_follows_cursors_until_last_page(self, monkeypatch):
+        """Test every page is fetched with the previous "after" cursor"""
+        requests = []
+        pages = [
+            _FakeResponse(200, {
+                "data": [{"name": "a"}, {"name": "b"}],
+                "paging": {"cursors": {"after": "CUR1"}, "next": "https://graph.facebook.com/next"},
+            }),
+            _FakeResponse(200, {
+                "data": [{"name": "c"}],
+                "paging": {"cursors": {"after": "CUR2"}},
+            }),
+        ]
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(pages, requests))
+
+        templates = await _client().list_all_templates("waba-1", page_size=2)
+
+        assert [t["name"] for t in templates] == ["a", "b", "c"]
+        assert "after" not in requests[0][1]
+        assert requests[1][1]["after"] == "CUR1"
+        assert len(requests) == 2
+
+
+class TestConversationalAutomation:
+    """Tests for ice breakers, commands and welcome message"""
+
+    @pytest.mark.asyncio
+    async def test_update_payload(self, monkeypatch):
+        """Test only given settings are sent and command slashes are stripped"""
+        client = _client()
+        requests = []
+
+        async def fake_request(path, payload):
+            requests.append((path, payload))
+            return {"success": True}
+
+        monkeypatch.setattr(client, "_phone_number_request", fake_request)
+
+        assert await client.update_conversational_automation(
+            enable_welcome_message=True,
+            commands=[{"command_name": "/pedidos", "command_description": "Ver meus pedidos 📦"}],
+        ) is True
+        assert requests == [(
+            "/conversational_automation",
+            {
+                "enable_welcome_message": True,
+                "commands": [{"command_name": "pedidos", "command_description": "Ver meus pedidos 📦"}],
+            },
+        )]
+
+    @pytest.mark.asyncio
+    async def test_invalid_components_are_rejected_locally(self):
+        """Test limits on ice breakers and command names"""
+        client = _client()
+
+        with pytest.raises(MetaAPIError):
+            await client.update_conversational_automation(prompts=["a", "b", "c", "d", "e"])
+        with pytest.raises(MetaAPIError):
+            await client.update_conversational_automation(prompts=["x" * 81])
+        with pytest.raises(MetaAPIError):
+            await client.update_conversational_automation(
+                commands=[{"command_name": "meus pedidos", "command_description": "Pedidos"}]
+            )
+
+    @pytest.mark.asyncio
+    async def test_get_defaults(self, monkeypatch):
+        """Test a number without components returns empty settings"""
+        requests = []
+        response = _FakeResponse(200, {"id": "123"})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+
+        automation = await _client().get_conversational_automation()
+
+        assert requests[0][1] == {"fields": "conversational_automation"}
+        assert automation == {"enable_welcome_message": False, "prompts": [], "commands": []}
+
+
+class TestGraphAPIVersion:
+    """Tests for the configured Graph API version and capability gating"""
+
+    def test_urls_use_configured_version(self, monkeypatch):
+        """Test every URL is built from WHATSAPP_API_URL and WHATSAPP_API_VERSION"""
+        monkeypatch.setattr(meta_api.settings, "WHATSAPP_API_URL", "https://graph.facebook.com")
+        monkeypatch.setattr(meta_api.settings, "WHATSAPP_API_VERSION", "v21.0")
+
+        assert meta_api.graph_url("123", "messages") == "https://graph.facebook.com/v21.0/123/messages"
+        assert _client().base_url == "https://graph.facebook.com/v21.0"
+        assert MetaCloudAPI("123", "token", api_version="v19.0").base_url == "https://graph.facebook.com/v19.0"
+
+    def test_version_comparison(self):
+        """Test versions compare numerically, not as strings"""
+        assert meta_api.parse_graph_version("v9.0") < meta_api.parse_graph_version("v18.0")
+        assert meta_api.supports_capability("carousel_templates", "v20.0")
+        assert not meta_api.supports_capability("carousel_templates", "v18.0")
+        with pytest.raises(ValueError):
+            meta_api.parse_graph_version("18")
+
+    def test_template_capabilities(self):
+        """Test carousel and Flow components are detected on creation and on send"""
+        definition = [
+            {"type": "BODY", "text": "Oferta"},
+            {"type": "CAROUSEL", "cards": []},
+            {"type": "BUTTONS", "buttons": [{"type": "FLOW", "text": "Agendar", "flow_id": "1"}]},
+        ]
+        send = [{"type": "button", "sub_type": "flow", "index": "0", "parameters": []}]
+
+        assert meta_api.template_capabilities(definition) == {"carousel_templates", "flows"}
+        assert meta_api.template_capabilities(send) == {"flows"}
+        assert meta_api.template_capabilities([{"type": "BODY", "text": "Oi"}]) == set()
+
+    @pytest.mark.asyncio
+    async def test_carousel_template_rejected_on_old_version(self, monkeypatch):
+        """Test a clear error is raised before calling Meta"""
+        requests = []
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(_FakeResponse(200, {}), requests))
+        client = MetaCloudAPI("123", "token", api_version="v18.0")
+
+        with pytest.raises(meta_api.GraphAPIVersionError) as error:
+            await client.create_template("waba", "promo", "pt_BR", "MARKETING", [{"type": "CAROUSEL", "cards": []}])
+
+        assert error.value.required_version == "v19.0"
+        assert error.value.status_code == 400
+        assert "WHATSAPP_API_VERSION" in error.value.message
+        assert requests == []
+
+    @pytest.mark.asyncio
+    async def test_send_flow_message_payload(self, monkeypatch):
+        """Test the navigate action starts the Flow on the given screen"""
+        sent = []
+
+        class FakeAsyncClient:
+            def __init__(self, *args, **kwargs):
+                pass
+
+            async def __aenter__(self):
+                return self
+
+            async def __aexit__(self, *args):
+                return False
+
+            async def post(self, url, json=None, headers=None):
+                sent.append((url, json))
+                return _FakeResponse(200, {"messages": [{"id": "wamid.1"}]})
+
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", FakeAsyncClient)
+        client = MetaCloudAPI("123", "token", api_version="v21.0")
+
+        await client.send_flow_message(
+            "5511999999999", "987", "Agendar", "Escolha um horário",
+            flow_token="conv-1", screen="DATE", data={"unit": "centro"},
+        )
+
+        url, payload = sent[0]
+        assert url.endswith("/v21.0/123/messages")
+        assert payload["interactive"]["type"] == "flow"
+        assert payload["interactive"]["action"]["parameters"] == {
+            "flow_message_version": "3",
+            "flow_id": "987",
+            "flow_cta": "Agendar",
+            "flow_action": "navigate",
+            "flow_token": "conv-1",
+            "flow_action_payload": {"screen": "DATE", "data": {"unit": "centro"}},
+        }
+
+
+class _PhoneNumber(BaseModel):
+    id: str
+    display_phone_number: str
+
+
+class TestGraphRequest:
+    """Tests for raw Graph API calls"""
+
+    @pytest.mark.asyncio
+    async def test_typed_response(self, monkeypatch):
+        """Test the path is versioned and the body validated into the model"""
+        requests = []
+        response = _FakeResponse(200, {"id": "123", "display_phone_number": "+55 11 99999-9999"})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+        client = MetaCloudAPI("123", "token", api_version="v21.0")
+
+        result = await client.graph_request("get", "/123", params={"fields": "id"}, response_model=_PhoneNumber)
+
+        assert requests == [("GET", "https://graph.facebook.com/v21.0/123", {"fields": "id"}, None)]
+        assert result.status_code == 200
+        assert result.data == _PhoneNumber(id="123", display_phone_number="+55 11 99999-9999")
+
+    @pytest.mark.asyncio
+    async def test_raw_response_and_errors(self, monkeypatch):
+        """Test dict bodies are returned as is and Graph errors are parsed"""
+        responses = [
+            _FakeResponse(200, {"success": True}),
+            _FakeResponse(403, {"error": {"message": "Missing permission", "code": 200}}),
+            _FakeResponse(200, {"id": "123"}),
+        ]
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(responses, []))
+        client = _client()
+
+        assert (await client.graph_request("POST", "waba/subscribed_apps", body={})).data == {"success": True}
+
+        with pytest.raises(MetaAPIError) as error:
+            await client.graph_request("DELETE", "waba/subscribed_apps")
+        assert (error.value.error_code, error.value.status_code) == ("200", 403)
+
+        with pytest.raises(MetaAPIError) as error:
+            await client.graph_request("GET", "123", response_model=_PhoneNumber)
+        assert "validation error" in error.value.message
+
+    @pytest.mark.asyncio
+    async def test_absolute_urls_are_rejected(self, monkeypatch):
+        """Test the access token is never sent to another host"""
+        requests = []
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(_FakeResponse(200, {}), requests))
+
+        with pytest.raises(MetaAPIError):
+            await _client().graph_request("GET", "https://example.com/collect")
+
+        assert requests == []
+
+    @pytest.mark.asyncio
+    async def test_debug_token(self, monkeypatch):
+        """Test token inspection converts timestamps (0 means never expires)"""
+        requests = []
+        response = _FakeResponse(200, {"data": {
+            "app_id": "42", "type": "SYSTEM_USER", "is_valid": True, "expires_at": 0,
+            "data_access_expires_at": 1767225600, "scopes": ["whatsapp_business_messaging"],
+        }})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+
+        info = await _client().debug_token()
+
+        assert requests[0][2] == {"input_token": "token"}
+        assert info["is_valid"] is True
+        assert info["expires_at"] is None
+        assert info["data_access_expires_at"].year == 2026
+        assert info["scopes"] == ["whatsapp_business_messaging"]
+
+
+class TestResponseInfo:
+    """Tests for request ids and rate-limit headers of responses"""
+
+    @pytest.mark.asyncio
+    async def test_sent_message_carries_headers(self, monkeypatch):
+        """Test a send keeps Meta's body and exposes trace id and quota usage"""
+        response = _FakeResponse(200, {"messages": [{"id": "wamid.1"}]}, headers={
+            "x-fb-trace-id": "AbC123",
+            "x-fb-request-id": "req-1",
+            "x-app-usage": '{"call_count": 12, "total_time": 3, "total_cputime": 2}',
+            "x-business-use-case-usage": '{"waba": [{"type": "whatsapp_business_management", '
+                                         '"call_count": 85, "total_time": 10, "total_cputime": 5, '
+                                         '"estimated_time_to_regain_access": 0}]}',
+        })
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, []))
+
+        result = await _client().send_text_message("5511999999999", "Olá")
+
+        assert result["messages"][0]["id"] == "wamid.1"
+        assert result.response.fbtrace_id == "AbC123"
+        assert result.response.request_id == "req-1"
+        assert result.response.usage_percent == 85
+        assert result.response.retry_after is None
+
+    @pytest.mark.asyncio
+    async def test_errors_carry_retry_after_and_trace_id(self, monkeypatch):
+        """Test throttled calls expose when to retry and the body's fbtrace_id"""
+        response = _FakeResponse(
+            429,
+            {"error": {"message": "Rate limit hit", "code": 80008, "fbtrace_id": "XyZ"}},
+            headers={"x-business-use-case-usage": '{"waba": [{"call_count": 100, "estimated_time_to_regain_access": 5}]}'},
+        )
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, []))
+
+        with pytest.raises(MetaAPIError) as error:
+            await _client().send_text_message("5511999999999", "Olá")
+
+        assert error.value.fbtrace_id == "XyZ"
+        assert error.value.retry_after == 300
+        assert error.value.response.to_dict()["usage_percent"] == 100
+
+    def test_retry_after_header_wins(self):
+        """Test Retry-After seconds take precedence over usage estimates"""
+        info = ResponseInfo.from_response(_FakeResponse(503, {}, headers={"retry-after": "30"}))
+
+        assert info.retry_after == 30
+        assert info.usage_percent is None
+        assert MetaAPIError("Network error").fbtrace_id is None
+
+
+class TestCtaUrlMessage:
+    """Tests for MetaCloudAPI.send_cta_url_message()"""
+
+    @pytest.mark.asyncio
+    async def test_payload(self, monkeypatch):
+        """Test the URL goes behind a labelled button"""
+        requests = []
+        response = _FakeResponse(200, {"messages": [{"id": "wamid.1"}]})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+
+        await _client().send_cta_url_message(
+            "5511999999999", "Seu pedido saiu para entrega", "Rastrear pedido", "https://loja.com/p/1",
+            footer_text="Loja",
+        )
+
+        _, payload = requests[0]
+        assert payload["interactive"] == {
+            "type": "cta_url",
+            "body": {"text": "Seu pedido saiu para entrega"},
+            "action": {"name": "cta_url", "parameters": {"display_text": "Rastrear pedido", "url": "https://loja.com/p/1"}},
+            "footer": {"text": "Loja"},
+        }
+
+    @pytest.mark.asyncio
+    async def test_invalid_url(self, monkeypatch):
+        """Test relative or non-http URLs fail before calling Meta"""
+        requests = []
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(_FakeResponse(200, {}), requests))
+
+        with pytest.raises(MetaAPIError):
+            await _client().send_cta_url_message("5511999999999", "Pedido", "Ver", "/pedido/1")
+
+        assert requests == []
+
+
+class TestSendTemplate:
+    """Tests for MetaCloudAPI.send_template()"""
+
+    @pytest.mark.asyncio
+    async def test_builder_components(self, monkeypatch):
+        """Test name, language and built components go in the template payload"""
+        requests = []
+        response = _FakeResponse(200, {"messages": [{"id": "wamid.1"}]})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+
+        await _client().send_template(
+            "5511999999999",
+            TemplateBuilder("order_update", "en_US", body_placeholders=1).body_text("Maria"),
+        )
+
+        _, payload = requests[0]
+        assert payload["template"] == {
+            "name": "order_update",
+            "language": {"code": "en_US"},
+            "components": [{"type": "body", "parameters": [{"type": "text", "text": "Maria"}]}],
+        }
+
+    @pytest.mark.asyncio
+    async def test_invalid_parameters_are_not_sent(self, monkeypatch):
+        """Test a TemplateBuildError is raised before calling Meta"""
+        requests = []
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(_FakeResponse(200, {}), requests))
+
+        with pytest.raises(TemplateBuildError):
+            await _client().send_template("5511999999999", TemplateBuilder("order_update", body_placeholders=2))
+
+        assert requests == []
+
+
+class TestAddressMessage:
+    """Tests for MetaCloudAPI.send_address_message()"""
+
+    @pytest.mark.asyncio
+    async def test_payload(self, monkeypatch):
+        """Test prefilled values and saved addresses go in the action parameters"""
+        requests = []
+        response = _FakeResponse(200, {"messages": [{"id": "wamid.1"}]})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+        saved = [{"id": "home", "value": {"name": "Priya", "in_pin_code": "400011", "city": "Mumbai"}}]
+
+        await _client().send_address_message(
+            "919999999999", "Where should we deliver?", "in", values={"name": "Priya"}, saved_addresses=saved,
+        )
+
+        url, payload = requests[0]
+        assert url.endswith("/123/messages")
+        assert payload["interactive"] == {
+            "type": "address_message",
+            "body": {"text": "Where should we deliver?"},
+            "action": {
+                "name": "address_message",
+                "parameters": {"country": "IN", "values": {"name": "Priya"}, "saved_addresses": saved},
+            },
+        }
+
+    @pytest.mark.asyncio
+    async def test_unsupported_country(self, monkeypatch):
+        """Test countries without the address form fail before calling Meta"""
+        requests = []
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(_FakeResponse(200, {}), requests))
+
+        with pytest.raises(MetaAPIError):
+            await _client().send_address_message("5511999999999", "Endereço?", "BR")
+
+        assert requests == []
+
+
+class TestLocationRequest:
+    """Tests for MetaCloudAPI.send_location_request()"""
+
+    @pytest.mark.asyncio
+    async def test_payload(self, monkeypatch):
+        """Test the message carries the native send_location action"""
+        requests = []
+        response = _FakeResponse(200, {"messages": [{"id": "wamid.1"}]})
+        monkeypatch.setattr(meta_api.httpx, "AsyncClient", _fake_http_client(response, requests))
+
+        await _client().send_location_request("5511999999999", "Compartilhe o endereço de instalação")
+
+        url, payload = requests[0]
+        assert url.endswith("/123/messages")
+        assert payload["interactive"] == {
+            "type": "location_request_message",
+            "body": {"text": "Compartilhe o endereço de instalação"},
+            "action": {"name": "send_location"},
+        }
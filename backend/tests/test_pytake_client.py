@@ -0,0 +1,131 @@
+"""
+PyTake Client SDK Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import hashlib
+import hmac
+import json
+from uuid import uuid4
+
+import httpx
+import pytest
+
+from pytake_client import PyTakeAPIError, PyTakeAuthError, PyTakeClient, WebhookEventType
+from pytake_client.models import CampaignCreate
+
+
+def _client(handler) -> PyTakeClient:
+    return PyTakeClient(
+        "https://api.test",
+        webhook_secret="secret",
+        http_client=httpx.AsyncClient(transport=httpx.MockTransport(handler)),
+    )
+
+
+TOKEN = {
+    "access_token": "access",
+    "refresh_token": "refresh",
+    "token_type": "bearer",
+    "expires_in": 3600,
+}
+
+
+class TestPyTakeClient:
+    """Tests for PyTakeClient"""
+
+    @pytest.mark.asyncio
+    async def test_login_sets_bearer_token(self):
+        """Test login stores the token and authenticates later requests"""
+        seen = []
+
+        def handler(request: httpx.Request) -> httpx.Response:
+            seen.append(request)
+            if request.url.path == "/api/v1/auth/login":
+                return httpx.Response(200, json={"user": {}, "token": TOKEN})
+            return httpx.Response(200, json=[])
+
+        async with _client(handler) as client:
+            await client.auth.login("user@example.com", "Secret123")
+            await client.conversations.list(status="open")
+
+        assert seen[1].headers["Authorization"] == "Bearer access"
+        assert seen[1].url.params["status"] == "open"
+
+    @pytest.mark.asyncio
+    async def test_create_campaign_omits_unset_fields(self):
+        """Test request models are serialized without None values"""
+        campaign_id = uuid4()
+        body = {}
+
+        def handler(request: httpx.Request) -> httpx.Response:
+            body.update(json.loads(request.content))
+            return httpx.Response(200, json={
+                **body,
+                "id": str(campaign_id),
+                "organization_id": str(uuid4()),
+                "created_at": "2025-11-05T10:00:00",
+                "updated_at": "2025-11-05T10:00:00",
+            })
+
+        async with _client(handler) as client:
+            campaign = await client.campaigns.create(CampaignCreate(name="Black Friday"))
+
+        assert campaign.id == campaign_id
+        assert "description" not in body
+
+    @pytest.mark.asyncio
+    async def test_errors_are_typed(self):
+        """Test 401 raises PyTakeAuthError and 404 raises PyTakeAPIError"""
+        def handler(request: httpx.Request) -> httpx.Response:
+            if request.url.path.endswith("/me"):
+                return httpx.Response(401, json={"detail": "Not authenticated"})
+            return httpx.Response(404, json={"detail": "Campaign not found"})
+
+        async with _client(handler) as client:
+            with pytest.raises(PyTakeAuthError):
+                await client.auth.me()
+            with pytest.raises(PyTakeAPIError) as exc_info:
+                await client.campaigns.get(uuid4())
+
+        assert exc_info.value.status_code == 404
+        assert exc_info.value.detail == "Campaign not found"
+
+
+class TestWebhookHelpers:
+    """Tests for the webhooks namespace"""
+
+    def test_verify_signature(self):
+        """Test X-Hub-Signature-256 verification"""
+        client = _client(lambda request: httpx.Response(200))
+        payload = b'{"entry": []}'
+        signature = "sha256=" + hmac.new(b"secret", payload, hashlib.sha256).hexdigest()
+
+        assert client.webhooks.verify(payload, signature)
+        assert not client.webhooks.verify(payload, "sha256=bad")
+
+    def test_parse_events(self):
+        """Test payload entries are classified"""
+        client = _client(lambda request: httpx.Response(200))
+        payload = {
+            "entry": [{
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "metadata": {"phone_number_id": "123"},
+                        "messages": [{"type": "order", "order": {"product_items": []}}],
+                        "statuses": [{"id": "wamid.1", "status": "read"}],
+                    },
+                }],
+            }],
+        }
+
+        events = client.webhooks.parse(payload)
+
+        assert [event.type for event in events] == [
+            WebhookEventType.ORDER,
+            WebhookEventType.STATUS,
+        ]
+        assert events[0].phone_number_id == "123"
+        assert events[0].order is not None
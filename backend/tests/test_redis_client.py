@@ -0,0 +1,77 @@
+"""
+Redis Client Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import asyncio
+
+import pytest
+from redis.exceptions import ConnectionError as RedisConnectionError
+
+from app.core.config import Settings
+from app.core.redis import RedisClient, parse_node
+
+
+class FakeRedis:
+    """Minimal async client that fails on demand"""
+
+    def __init__(self, fail: bool = False):
+        self.fail = fail
+
+    async def get(self, key):
+        if self.fail:
+            raise RedisConnectionError("Connection refused")
+        return "value"
+
+
+class TestRedisConfig:
+    """Tests for HA settings parsing"""
+
+    def test_parse_node(self):
+        assert parse_node("sentinel-1:26380", 26379) == ("sentinel-1", 26380)
+        assert parse_node("sentinel-1", 26379) == ("sentinel-1", 26379)
+
+    def test_pool_sizes_and_nodes(self):
+        """Test comma-separated env values become dict/list"""
+        assert Settings.parse_redis_pool_sizes_after("cache=20, queue=5") == {"cache": 20, "queue": 5}
+        assert Settings.parse_redis_nodes_after("a:26379, b:26379,") == ["a:26379", "b:26379"]
+
+
+class TestRedisClient:
+    """Tests for RedisClient command execution and metrics"""
+
+    @pytest.mark.asyncio
+    async def test_not_initialized(self):
+        """Test the default client still requires connect()"""
+        with pytest.raises(RuntimeError):
+            await RedisClient().get("key")
+
+    @pytest.mark.asyncio
+    async def test_errors_are_counted(self):
+        """Test connection errors are recorded and re-raised"""
+        client = RedisClient("cache")
+        client.client = FakeRedis(fail=True)
+
+        with pytest.raises(RedisConnectionError):
+            await client.get("key")
+
+        assert client.stats["commands"] == 1
+        assert client.stats["errors"] == 1
+        assert client.stats["last_error"] == "Connection refused"
+
+    def test_lazy_client_reconnects_in_new_event_loop(self, monkeypatch):
+        """Test component clients reconnect when used from another loop (Celery tasks)"""
+        client = RedisClient("rate_limit", lazy=True)
+        connects = []
+
+        async def fake_connect():
+            connects.append(1)
+            client.client = FakeRedis()
+            client._loop = asyncio.get_running_loop()
+
+        monkeypatch.setattr(client, "connect", fake_connect)
+
+        assert asyncio.run(client.get("key")) == "value"
+        assert asyncio.run(client.get("key")) == "value"
+        assert len(connects) == 2
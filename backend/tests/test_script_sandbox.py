@@ -0,0 +1,99 @@
+"""
+Script Sandbox Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import pytest
+
+from app.utils.script_sandbox import (
+    MAX_CPU_SECONDS,
+    SandboxLimits,
+    ScriptError,
+    parse_output,
+    run_script,
+    validate_script,
+)
+
+
+class TestValidateScript:
+    """Tests for validate_script()"""
+
+    def test_valid_snippets(self):
+        """Test expressions and function bodies using pure builtins are accepted"""
+        assert validate_script("round(preco * 0.9, 2)") == []
+        assert validate_script("total = sum(item['preco'] for item in itens)\nreturn total") == []
+
+    def test_forbidden_constructs(self):
+        """Test imports, dunder access and unsafe builtins are rejected"""
+        assert validate_script("import os\nreturn 1") == ["Imports are not allowed"]
+        assert validate_script("().__class__.__bases__") == [
+            "Attribute '__bases__' is not allowed",
+            "Attribute '__class__' is not allowed",
+        ]
+        assert validate_script("open('/etc/passwd').read()") == ["Name 'open' is not allowed"]
+        assert validate_script("'{0.__class__}'.format(1)") == ["Attribute 'format' is not allowed"]
+
+    def test_empty_and_syntax_errors(self):
+        """Test empty snippets and syntax errors report the snippet's own line"""
+        assert validate_script("  ") == ["Script is empty"]
+        assert validate_script("x = 1\nreturn (x") == ["Syntax error on line 2: '(' was never closed"]
+
+
+class TestSandboxLimits:
+    """Tests for SandboxLimits.from_node()"""
+
+    def test_limits_are_clamped(self):
+        """Test node limits cannot exceed the platform maximums"""
+        limits = SandboxLimits.from_node({"cpuSeconds": 60, "memoryMb": "128", "timeoutSeconds": -1})
+
+        assert limits.cpu_seconds == MAX_CPU_SECONDS
+        assert limits.memory_mb == 128
+        assert limits.timeout_seconds == 3
+
+
+class TestRunScript:
+    """Tests for run_script()"""
+
+    @pytest.mark.asyncio
+    async def test_returns_result_from_variables(self):
+        """Test snippets see the session variables as names and as the variables dict"""
+        result = await run_script(
+            "desconto = variables.get('cupom', 0)\nreturn [item['qtd'] - desconto for item in itens]",
+            {"itens": [{"qtd": 3}, {"qtd": 5}], "cupom": 1},
+        )
+
+        assert result.ok
+        assert result.result == [2, 4]
+
+    @pytest.mark.asyncio
+    async def test_runtime_errors_are_reported(self):
+        """Test exceptions raised by the snippet become the result error"""
+        result = await run_script("1 / zero", {"zero": 0})
+
+        assert not result.ok
+        assert result.error.startswith("ZeroDivisionError")
+
+    @pytest.mark.asyncio
+    async def test_runaway_script_is_stopped(self):
+        """Test infinite loops are killed by the CPU limit"""
+        result = await run_script("while True:\n    pass", {}, SandboxLimits(cpu_seconds=1, timeout_seconds=3))
+
+        assert not result.ok
+        assert "exceeded" in result.error
+
+    @pytest.mark.asyncio
+    async def test_invalid_script_is_not_run(self):
+        """Test invalid snippets raise before a process is started"""
+        with pytest.raises(ScriptError):
+            await run_script("__import__('os')", {})
+
+
+class TestParseOutput:
+    """Tests for parse_output()"""
+
+    def test_killed_process_reports_cpu_limit(self):
+        """Test a process killed by a signal without output hit the CPU limit"""
+        result = parse_output(b"", -9, SandboxLimits(cpu_seconds=2))
+
+        assert result.error == "Script exceeded 2s of CPU"
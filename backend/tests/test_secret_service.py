@@ -10,8 +10,8 @@ from uuid import uuid4
 
 from sqlalchemy.ext.asyncio import AsyncSession
 
-from app.services.secret_service import SecretService
-from app.models.secret import SecretScope, EncryptionProvider
+from app.services.secret_service import OrgSecretResolver, SecretService
+from app.models.secret import Secret, SecretKind, SecretScope, SecretUsage, EncryptionProvider
 from tests.conftest import OrganizationFactory
 
 
@@ -499,3 +499,126 @@ class TestSecretServiceMultiTenancy:
         assert len(org2_secrets) == 1
         assert org1_secrets[0].id == secret1.id
         assert org2_secrets[0].id == secret2.id
+
+
+class TestOrgSecretResolver:
+    """Tests for {{org.secrets.X}} / {{org.vars.X}} interpolation and its audit"""
+
+    @pytest_asyncio.fixture
+    async def secret_service(self, db_session: AsyncSession) -> SecretService:
+        return SecretService(db_session)
+
+    @pytest.mark.asyncio
+    async def test_resolves_secrets_and_variables(
+        self, secret_service: SecretService, db_session: AsyncSession
+    ):
+        """Test references in nested headers/body are replaced and audited once"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        secret = await secret_service.create_secret(
+            organization_id=org.id, name="api_key", display_name="API Key",
+            value="sk-123", scope=SecretScope.ORGANIZATION
+        )
+        await secret_service.create_secret(
+            organization_id=org.id, name="base_url", display_name="Base URL",
+            value="https://erp.example.com", scope=SecretScope.ORGANIZATION,
+            kind=SecretKind.VARIABLE
+        )
+        resolver = OrgSecretResolver(db_session, org.id)
+
+        headers = await resolver.resolve(
+            {"Authorization": "Bearer {{org.secrets.API_KEY}}", "X-Retries": 3},
+            SecretUsage.FLOW_HTTP.value, "node", "node-1"
+        )
+        body = await resolver.resolve(
+            {"items": ["{{ org.vars.base_url }}/orders", "{{org.secrets.api_key}}"]},
+            SecretUsage.FLOW_HTTP.value, "node", "node-1"
+        )
+
+        assert headers == {"Authorization": "Bearer sk-123", "X-Retries": 3}
+        assert body == {"items": ["https://erp.example.com/orders", "sk-123"]}
+
+        logs, total = await secret_service.list_access_logs(secret.id, org.id)
+        assert [log.action for log in logs] == ["resolve", "create"]
+        assert (logs[0].usage, logs[0].resource_id) == ("flow_http", "node-1")
+
+    @pytest.mark.asyncio
+    async def test_unresolvable_references_are_left_untouched(
+        self, secret_service: SecretService, db_session: AsyncSession
+    ):
+        """Test unknown names, wrong namespace and disallowed usages are not replaced"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        restricted = await secret_service.create_secret(
+            organization_id=org.id, name="erp_token", display_name="ERP Token",
+            value="tok", scope=SecretScope.ORGANIZATION,
+            allowed_usages=[SecretUsage.FLOW_HTTP.value]
+        )
+        resolver = OrgSecretResolver(db_session, org.id)
+
+        prompt = "{{org.secrets.erp_token}} {{org.vars.erp_token}} {{org.secrets.missing}}"
+        resolved = await resolver.resolve(prompt, SecretUsage.AI_PROMPT.value, "node", "ai-1")
+
+        assert resolved == prompt
+        logs, _ = await secret_service.list_access_logs(restricted.id, org.id)
+        denied = [log for log in logs if not log.allowed]
+        assert len(denied) == 2
+        assert all(log.action == "resolve" for log in denied)
+
+    @pytest.mark.asyncio
+    async def test_chatbot_entry_overrides_organization_entry(
+        self, secret_service: SecretService, db_session: AsyncSession
+    ):
+        org = await OrganizationFactory.create_in_db(db_session)
+        chatbot_id = uuid4()
+        await secret_service.create_secret(
+            organization_id=org.id, name="token", display_name="Org token",
+            value="org", scope=SecretScope.ORGANIZATION
+        )
+        await secret_service.create_secret(
+            organization_id=org.id, name="token", display_name="Bot token",
+            value="bot", scope=SecretScope.CHATBOT, chatbot_id=chatbot_id
+        )
+
+        for_bot = OrgSecretResolver(db_session, org.id, chatbot_id=chatbot_id)
+        for_org = OrgSecretResolver(db_session, org.id)
+
+        assert await for_bot.resolve("{{org.secrets.token}}", "webhook") == "bot"
+        assert await for_org.resolve("{{org.secrets.token}}", "webhook") == "org"
+
+
+class TestSecretPermissions:
+    """Tests for per-key read permissions"""
+
+    def test_default_readers(self):
+        secret = Secret(kind=SecretKind.SECRET, allowed_roles=None)
+        variable = Secret(kind=SecretKind.VARIABLE, allowed_roles=None)
+
+        assert secret.can_be_read_by("org_admin")
+        assert not secret.can_be_read_by("agent")
+        assert variable.can_be_read_by("viewer")
+
+    def test_allowed_roles_override_defaults(self):
+        secret = Secret(kind=SecretKind.SECRET, allowed_roles=["agent"])
+        variable = Secret(kind=SecretKind.VARIABLE, allowed_roles=[])
+
+        assert secret.can_be_read_by("agent")
+        assert not secret.can_be_read_by("viewer")
+        assert not variable.can_be_read_by("agent")
+        assert variable.can_be_read_by("super_admin")
+
+    @pytest.mark.asyncio
+    async def test_denied_reveal_is_audited(self, db_session: AsyncSession):
+        secret_service = SecretService(db_session)
+        org = await OrganizationFactory.create_in_db(db_session)
+        secret = await secret_service.create_secret(
+            organization_id=org.id, name="db_password", display_name="DB",
+            value="pw", scope=SecretScope.ORGANIZATION
+        )
+
+        with pytest.raises(PermissionError):
+            await secret_service.get_secret_with_value(secret.id, org.id, role="agent")
+        revealed = await secret_service.get_secret_with_value(secret.id, org.id, role="org_admin")
+
+        assert revealed["value"] == "pw"
+        assert revealed["reference"] == "{{org.secrets.db_password}}"
+        logs, _ = await secret_service.list_access_logs(secret.id, org.id)
+        assert [(log.action, log.allowed) for log in logs][:2] == [("reveal", True), ("reveal", False)]
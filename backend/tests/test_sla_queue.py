@@ -0,0 +1,166 @@
+"""
+SLA Queue Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+
+import pytest
+
+from app.core.sla_queue import (
+    PRIORITY_URGENT,
+    SLA_HEADER,
+    SlaScheduler,
+    on_sla_at_risk,
+    remove_sla_listener,
+    sla_priority,
+)
+
+NOW = 1_700_000_000.0
+
+
+class FakeRedis:
+    """In-memory keys and sorted sets used by the scheduler"""
+
+    def __init__(self):
+        self.keys = {}
+        self.zsets = {}
+
+    async def set(self, key, value, expire=None, nx=False):
+        if nx and key in self.keys:
+            return None
+        self.keys[key] = value
+        return True
+
+    async def get(self, key):
+        return self.keys.get(key)
+
+    async def delete(self, *keys):
+        return sum(1 for key in keys if self.keys.pop(key, None) is not None)
+
+    async def zadd(self, name, mapping):
+        self.zsets.setdefault(name, {}).update(mapping)
+        return len(mapping)
+
+    async def zrem(self, name, *values):
+        zset = self.zsets.get(name, {})
+        return sum(1 for value in values if zset.pop(value, None) is not None)
+
+    async def zrangebyscore(self, name, min_score, max_score):
+        items = sorted(self.zsets.get(name, {}).items(), key=lambda item: item[1])
+        return [member for member, score in items if score <= max_score]
+
+
+class BrokenRedis(FakeRedis):
+    async def set(self, key, value, expire=None, nx=False):
+        raise ConnectionError("redis down")
+
+
+class Broker:
+    """Records what would be published to Celery"""
+
+    def __init__(self):
+        self.sent = []
+
+    def __call__(self, name, **options):
+        self.sent.append((name, options))
+
+
+def _scheduler(redis=None):
+    broker = Broker()
+    return SlaScheduler(redis or FakeRedis(), at_risk_ratio=0.25, send=broker), broker
+
+
+class TestSlaPriority:
+    """Tests for the priority of a job from its remaining SLA"""
+
+    def test_priority_rises_as_deadline_approaches(self):
+        assert sla_priority(60, 60, 0.25) == 4
+        assert sla_priority(30, 60, 0.25) == 2
+        assert sla_priority(15, 60, 0.25) == PRIORITY_URGENT
+        assert sla_priority(-5, 60, 0.25) == PRIORITY_URGENT
+
+
+class TestSlaScheduler:
+    """Tests for submitting, promoting and claiming SLA jobs"""
+
+    @pytest.mark.asyncio
+    async def test_submit_sends_ahead_of_default_priority(self):
+        scheduler, broker = _scheduler()
+
+        job_id = await scheduler.submit(
+            "process_webhook", args=[{"entry": []}], sla_seconds=60, queue="webhooks", now=NOW
+        )
+
+        name, options = broker.sent[0]
+        assert name == "process_webhook"
+        assert options["priority"] == 4
+        assert options["queue"] == "webhooks"
+        assert options["headers"] == {SLA_HEADER: job_id}
+
+    @pytest.mark.asyncio
+    async def test_escalate_promotes_at_risk_jobs_once(self):
+        scheduler, broker = _scheduler()
+        events = []
+
+        async def listener(event):
+            events.append(event)
+
+        job_id = await scheduler.submit("send_dunning_reminder", args=["r1"], sla_seconds=60, organization_id="org-1", now=NOW)
+        await scheduler.submit("send_dunning_reminder", args=["r2"], sla_seconds=600, now=NOW)
+
+        on_sla_at_risk(listener)
+        try:
+            assert await scheduler.escalate(now=NOW + 30) == []
+            promoted = await scheduler.escalate(now=NOW + 50)
+            assert await scheduler.escalate(now=NOW + 55) == []
+        finally:
+            remove_sla_listener(listener)
+
+        assert [event.job_id for event in promoted] == [job_id]
+        assert events[0].remaining_seconds == 10
+        assert events[0].organization_id == "org-1"
+        name, options = broker.sent[-1]
+        assert (name, options["args"], options["priority"]) == ("send_dunning_reminder", ["r1"], PRIORITY_URGENT)
+        assert options["headers"] == {SLA_HEADER: job_id}
+
+    @pytest.mark.asyncio
+    async def test_delayed_job_deadline_counts_from_eta(self):
+        scheduler, broker = _scheduler()
+        eta = datetime.fromtimestamp(NOW + 3600, tz=timezone.utc)
+
+        await scheduler.submit("send_dunning_reminder", args=["r1"], sla_seconds=60, eta=eta, now=NOW)
+
+        assert await scheduler.escalate(now=NOW + 3600) == []
+        assert len(await scheduler.escalate(now=NOW + 3650)) == 1
+        assert broker.sent[0][1]["eta"] == eta
+
+    @pytest.mark.asyncio
+    async def test_first_copy_claims_the_job(self):
+        scheduler, _ = _scheduler()
+        job_id = await scheduler.submit("process_webhook", sla_seconds=60, now=NOW)
+        await scheduler.escalate(now=NOW + 50)
+
+        assert await scheduler.claim(job_id, "promoted-task", now=NOW + 51)
+        assert not await scheduler.claim(job_id, "original-task", now=NOW + 52)
+        assert await scheduler.claim(job_id, "promoted-task", now=NOW + 53)  # retry of the winner
+
+    @pytest.mark.asyncio
+    async def test_claimed_job_is_not_promoted(self):
+        scheduler, broker = _scheduler()
+        job_id = await scheduler.submit("process_webhook", sla_seconds=60, now=NOW)
+
+        await scheduler.claim(job_id, "task-1", now=NOW + 1)
+
+        assert await scheduler.escalate(now=NOW + 59) == []
+        assert len(broker.sent) == 1
+
+    @pytest.mark.asyncio
+    async def test_fails_open_without_redis(self):
+        scheduler, broker = _scheduler(BrokenRedis())
+
+        job_id = await scheduler.submit("process_webhook", sla_seconds=60, now=NOW)
+
+        assert len(broker.sent) == 1
+        assert await scheduler.claim(job_id, "task-1")
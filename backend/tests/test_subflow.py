@@ -0,0 +1,124 @@
+"""
+Sub-flow Node Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from types import SimpleNamespace
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.models.chatbot import Chatbot, Flow
+from app.models.conversation import Conversation
+from app.services.whatsapp_service import MAX_SUBFLOW_DEPTH, SUBFLOW_STACK_KEY, WhatsAppService
+from tests.conftest import OrganizationFactory
+
+
+@pytest_asyncio.fixture
+async def engine(db_session: AsyncSession, monkeypatch):
+    """WhatsAppService on the test database with _start_flow and _advance_to_next_node faked"""
+    org = await OrganizationFactory.create_in_db(db_session)
+    service = WhatsAppService(db_session)
+    calls = []
+
+    async def start_flow(conversation, flow, incoming_message):
+        calls.append(("start", flow.id))
+
+    async def advance_to_next_node(conversation, node, flow, incoming_message):
+        calls.append(("advance", node.node_id))
+
+    monkeypatch.setattr(service, "_start_flow", start_flow)
+    monkeypatch.setattr(service, "_advance_to_next_node", advance_to_next_node)
+    return SimpleNamespace(service=service, db=db_session, org=org, calls=calls)
+
+
+async def _flow(engine, name) -> Flow:
+    chatbot = Chatbot(id=uuid4(), organization_id=engine.org.id, name=f"Bot {name}")
+    flow = Flow(id=uuid4(), organization_id=engine.org.id, chatbot_id=chatbot.id, name=name)
+    engine.db.add_all([chatbot, flow])
+    await engine.db.commit()
+    return flow
+
+
+async def _conversation(engine, stack=None) -> Conversation:
+    context = {"nome": "Maria"}
+    if stack is not None:
+        context[SUBFLOW_STACK_KEY] = stack
+    conversation = Conversation(
+        id=uuid4(),
+        organization_id=engine.org.id,
+        contact_id=uuid4(),
+        whatsapp_number_id=uuid4(),
+        status="open",
+        is_bot_active=True,
+        context_variables=context,
+    )
+    engine.db.add(conversation)
+    await engine.db.commit()
+    return conversation
+
+
+async def _stored_context(engine, conversation):
+    await engine.db.refresh(conversation)
+    return conversation.context_variables
+
+
+class TestExecuteSubflow:
+    """Tests for the Sub-flow Node call"""
+
+    @pytest.mark.asyncio
+    async def test_pushes_return_position_and_starts_target(self, engine):
+        """Test the parent flow and node are pushed before the target flow starts"""
+        parent = await _flow(engine, "Atendimento")
+        survey = await _flow(engine, "Pesquisa de satisfação")
+        conversation = await _conversation(engine)
+        node = SimpleNamespace(node_id="node-7", flow_version_id=None)
+
+        await engine.service._execute_subflow(conversation, node, parent, None, {"targetFlowId": str(survey.id)})
+
+        context = await _stored_context(engine, conversation)
+        assert context[SUBFLOW_STACK_KEY] == [{"flow_id": str(parent.id), "node_id": "node-7", "version_id": None}]
+        assert context["nome"] == "Maria"
+        assert engine.calls == [("start", survey.id)]
+
+    @pytest.mark.asyncio
+    async def test_recursive_and_too_deep_calls_are_skipped(self, engine):
+        """Test a flow already on the stack or past the max depth is not called again"""
+        parent = await _flow(engine, "Atendimento")
+        identification = await _flow(engine, "Identificação")
+        node = SimpleNamespace(node_id="node-3", flow_version_id=None)
+        data = {"targetFlowId": str(identification.id)}
+
+        recursive = await _conversation(engine, [{"flow_id": str(identification.id), "node_id": "node-1"}])
+        await engine.service._execute_subflow(recursive, node, parent, None, data)
+
+        deep = await _conversation(engine, [{"flow_id": str(uuid4()), "node_id": "node-1"}] * MAX_SUBFLOW_DEPTH)
+        await engine.service._execute_subflow(deep, node, parent, None, data)
+
+        missing = await _conversation(engine)
+        await engine.service._execute_subflow(missing, node, parent, None, {"targetFlowId": "not-a-uuid"})
+
+        assert engine.calls == [("advance", "node-3")] * 3
+        assert len((await _stored_context(engine, recursive))[SUBFLOW_STACK_KEY]) == 1
+        assert len((await _stored_context(engine, deep))[SUBFLOW_STACK_KEY]) == MAX_SUBFLOW_DEPTH
+        assert SUBFLOW_STACK_KEY not in await _stored_context(engine, missing)
+
+    @pytest.mark.asyncio
+    async def test_flow_of_other_org_is_skipped(self, engine):
+        """Test a target flow of another organization is never called"""
+        parent = await _flow(engine, "Atendimento")
+        other_org = await OrganizationFactory.create_in_db(engine.db)
+        chatbot = Chatbot(id=uuid4(), organization_id=other_org.id, name="Bot Outra empresa")
+        other_flow = Flow(id=uuid4(), organization_id=other_org.id, chatbot_id=chatbot.id, name="Outra empresa")
+        engine.db.add_all([chatbot, other_flow])
+        await engine.db.commit()
+        conversation = await _conversation(engine)
+        node = SimpleNamespace(node_id="node-3", flow_version_id=None)
+
+        await engine.service._execute_subflow(conversation, node, parent, None, {"targetFlowId": str(other_flow.id)})
+
+        assert engine.calls == [("advance", "node-3")]
+        assert SUBFLOW_STACK_KEY not in await _stored_context(engine, conversation)
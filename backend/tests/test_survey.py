@@ -0,0 +1,115 @@
+"""
+Survey Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.flow_validation import validate_flow
+from app.utils.survey import (
+    CSAT,
+    DETRACTOR,
+    NEUTRAL,
+    NPS,
+    PASSIVE,
+    PROMOTER,
+    check_survey_config,
+    classify,
+    clean_comment,
+    is_skip,
+    parse_score,
+    summarize,
+    survey_type,
+)
+
+
+class TestParseScore:
+    """Tests for parse_score()"""
+
+    def test_accepted_formats(self):
+        """Test bare numbers, sentences, "x/10" and keycap emojis"""
+        assert parse_score("9", NPS) == 9
+        assert parse_score("nota 10!", NPS) == 10
+        assert parse_score("8/10", NPS) == 8
+        assert parse_score("4 / 5", CSAT) == 4
+        assert parse_score("🔟", NPS) == 10
+        assert parse_score("7️⃣", NPS) == 7
+        assert parse_score("0", NPS) == 0
+
+    def test_rejects_ambiguous_or_out_of_range(self):
+        """Test answers without exactly one score in range are rejected"""
+        assert parse_score("", NPS) is None
+        assert parse_score("ótimo atendimento", NPS) is None
+        assert parse_score("entre 7 e 8", NPS) is None
+        assert parse_score("100", NPS) is None
+        assert parse_score("9.5", NPS) is None
+        assert parse_score("0", CSAT) is None
+        assert parse_score("6", CSAT) is None
+
+
+class TestClassify:
+    """Tests for classify() and survey_type()"""
+
+    def test_nps_and_csat_categories(self):
+        """Test NPS and CSAT bands"""
+        assert [classify(score, NPS) for score in (6, 7, 8, 9)] == [DETRACTOR, PASSIVE, PASSIVE, PROMOTER]
+        assert classify(3, CSAT) == NEUTRAL
+        assert survey_type({"surveyType": "CSAT"}) == CSAT
+        assert survey_type({}) == NPS
+
+
+class TestComments:
+    """Tests for is_skip() and clean_comment()"""
+
+    def test_skip_keywords(self):
+        """Test default and custom skip keywords"""
+        assert is_skip(" Pular ", {})
+        assert not is_skip("demorou muito", {})
+        assert is_skip("sem comentários", {"skipKeywords": ["sem comentários"]})
+        assert clean_comment("   ") is None
+        assert clean_comment(" demorou ") == "demorou"
+
+
+class TestSummarize:
+    """Tests for summarize()"""
+
+    def test_nps(self):
+        """Test NPS is % promoters minus % detractors"""
+        summary = summarize(NPS, {10: 5, 9: 1, 8: 2, 3: 2})
+
+        assert summary.responses == 10
+        assert (summary.promoters, summary.passives, summary.detractors) == (6, 2, 2)
+        assert summary.score == 40.0
+        assert summary.average_score == 8.1
+
+    def test_csat_and_empty(self):
+        """Test CSAT is the % of 4-5 answers and no answers give no score"""
+        summary = summarize(CSAT, {5: 2, 4: 1, 2: 1})
+
+        assert summary.score == 75.0
+        assert summarize(NPS, {}).score is None
+
+
+class TestSurveyConfig:
+    """Tests for check_survey_config() and the flow validator"""
+
+    def test_reports_unusable_settings(self):
+        """Test unknown survey types are blocking errors"""
+        assert check_survey_config({"surveyType": "nps", "skipKeywords": ["pular"]}) == []
+        assert check_survey_config({"surveyType": "ces", "skipKeywords": "pular"}) == [
+            "Unknown survey type 'ces' (use nps or csat)",
+            "skipKeywords must be a list",
+        ]
+
+        canvas = {
+            "nodes": [
+                {"id": "start", "data": {"nodeType": "start"}},
+                {"id": "nps", "data": {"nodeType": "survey", "surveyType": "ces"}},
+                {"id": "end", "data": {"nodeType": "end"}},
+            ],
+            "edges": [
+                {"id": "e1", "source": "start", "target": "nps"},
+                {"id": "e2", "source": "nps", "target": "end"},
+            ],
+        }
+        codes = [diagnostic.code for diagnostic in validate_flow(canvas)]
+        assert codes == ["invalid_survey"]
@@ -0,0 +1,104 @@
+"""
+Template Builder Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime
+from types import SimpleNamespace
+
+import pytest
+
+from app.utils.template_builder import TemplateBuildError, TemplateBuilder
+
+
+def _template(**overrides):
+    data = dict(
+        name="order_update",
+        language="pt_BR",
+        header_type="IMAGE",
+        header_variables_count=0,
+        body_variables_count=3,
+        buttons=[{"type": "QUICK_REPLY", "text": "Rastrear"}, {"type": "URL", "text": "Ver", "url": "https://loja.com/{{1}}"}],
+    )
+    data.update(overrides)
+    return SimpleNamespace(**data)
+
+
+def _fields(error):
+    return [issue.field for issue in error.value.issues]
+
+
+class TestTemplateBuilder:
+    """Tests for TemplateBuilder.build()"""
+
+    def test_typed_parameters(self):
+        """Test header media, text/currency/date_time body parameters and buttons"""
+        components = (
+            TemplateBuilder.for_template(_template())
+            .header_image("https://cdn.com/pedido.jpg")
+            .body_text("Maria")
+            .body_currency("R$ 99,90", "brl", 99.9)
+            .body_date_time(datetime(2025, 11, 30, 18, 0))
+            .quick_reply(0, "track-123")
+            .url_button(1, "p/123")
+            .build()
+        )
+
+        assert components == [
+            {"type": "header", "parameters": [{"type": "image", "image": {"link": "https://cdn.com/pedido.jpg"}}]},
+            {"type": "body", "parameters": [
+                {"type": "text", "text": "Maria"},
+                {"type": "currency", "currency": {"fallback_value": "R$ 99,90", "code": "BRL", "amount_1000": 99900}},
+                {"type": "date_time", "date_time": {"fallback_value": "30/11/2025 18:00"}},
+            ]},
+            {"type": "button", "sub_type": "quick_reply", "index": "0",
+             "parameters": [{"type": "payload", "payload": "track-123"}]},
+            {"type": "button", "sub_type": "url", "index": "1", "parameters": [{"type": "text", "text": "p/123"}]},
+        ]
+
+    def test_placeholder_count_and_types(self):
+        """Test every mismatch is reported in one error"""
+        builder = (
+            TemplateBuilder.for_template(_template())
+            .header_video("https://cdn.com/pedido.mp4")
+            .body_text("Maria", "linha\nquebrada")
+            .url_button(0, "p/123")
+            .quick_reply(5, "x")
+        )
+
+        with pytest.raises(TemplateBuildError) as error:
+            builder.build()
+
+        assert _fields(error) == ["header", "body", "body[2]", "buttons[0]", "buttons[5]"]
+        assert "expected a image but got a video" in str(error.value)
+        assert "QUICK_REPLY button expects a payload but got a text" in str(error.value)
+
+    def test_text_header_and_coupon_code(self):
+        """Test text header parameters and the COPY_CODE button index from the template"""
+        template = _template(
+            header_type="TEXT",
+            header_variables_count=1,
+            body_variables_count=0,
+            buttons=[{"type": "URL", "text": "Loja", "url": "https://loja.com"}, {"type": "COPY_CODE", "example": "X"}],
+        )
+
+        components = TemplateBuilder.for_template(template).header_text("Maria").coupon_code("BLACK20").build()
+
+        assert components == [
+            {"type": "header", "parameters": [{"type": "text", "text": "Maria"}]},
+            {"type": "button", "sub_type": "copy_code", "index": "1",
+             "parameters": [{"type": "coupon_code", "coupon_code": "BLACK20"}]},
+        ]
+        with pytest.raises(TemplateBuildError, match="max 15"):
+            TemplateBuilder.for_template(template).header_text("Maria").coupon_code("X" * 16).build()
+
+    def test_media_header_needs_https_or_media_id(self):
+        """Test plain HTTP links are rejected and uploaded media ids accepted"""
+        builder = TemplateBuilder("promo", header_format="DOCUMENT")
+
+        with pytest.raises(TemplateBuildError, match="HTTPS"):
+            TemplateBuilder("promo", header_format="DOCUMENT").header_document("http://cdn.com/a.pdf").build()
+        assert builder.header_document(media_id="123", filename="boleto.pdf").build() == [
+            {"type": "header", "parameters": [{"type": "document", "document": {"id": "123", "filename": "boleto.pdf"}}]},
+        ]
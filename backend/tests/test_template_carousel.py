@@ -0,0 +1,85 @@
+"""
+Template Carousel Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.integrations.meta_api import template_capabilities
+from app.utils.template_carousel import build_carousel_component, carousel_definition
+
+
+def _card(header_format="IMAGE"):
+    return {
+        "components": [
+            {"type": "HEADER", "format": header_format},
+            {"type": "BODY", "text": "{{1}} por apenas {{2}}"},
+            {"type": "BUTTONS", "buttons": [
+                {"type": "QUICK_REPLY", "text": "Comprar"},
+                {"type": "URL", "text": "Ver", "url": "https://loja.com/p/{{1}}"},
+            ]},
+        ]
+    }
+
+
+TEMPLATE_COMPONENTS = [
+    {"type": "BODY", "text": "Olá {{1}}, confira:"},
+    {"type": "CAROUSEL", "cards": [_card(), _card("VIDEO")]},
+]
+
+
+class TestCarouselDefinition:
+    """Tests for carousel_definition()"""
+
+    def test_card_layout(self):
+        """Test header format, body variables and buttons of every card"""
+        cards = carousel_definition(TEMPLATE_COMPONENTS)
+
+        assert [card["header_format"] for card in cards] == ["IMAGE", "VIDEO"]
+        assert cards[0]["body_variables_count"] == 2
+        assert cards[0]["buttons"] == [
+            {"type": "QUICK_REPLY", "has_variable": False},
+            {"type": "URL", "has_variable": True},
+        ]
+
+    def test_template_without_carousel(self):
+        assert carousel_definition([{"type": "BODY", "text": "Oi"}]) == []
+        assert carousel_definition(None) == []
+
+
+class TestBuildCarouselComponent:
+    """Tests for build_carousel_component()"""
+
+    def test_serialization(self):
+        """Test media headers, body parameters and button parameters per card"""
+        component = build_carousel_component(
+            [
+                {"header_url": "https://cdn.com/1.jpg", "body": ["Tênis", 99],
+                 "buttons": [{"payload": "buy-1"}, {"text": "tenis"}]},
+                {"header_id": "media-2", "body": ["Bolsa", 79], "buttons": [{"payload": "buy-2"}, {"text": "bolsa"}]},
+            ],
+            carousel_definition(TEMPLATE_COMPONENTS),
+        )
+
+        first, second = component["cards"]
+        assert component["type"] == "carousel"
+        assert first["card_index"] == 0
+        assert first["components"] == [
+            {"type": "header", "parameters": [{"type": "image", "image": {"link": "https://cdn.com/1.jpg"}}]},
+            {"type": "body", "parameters": [{"type": "text", "text": "Tênis"}, {"type": "text", "text": "99"}]},
+            {"type": "button", "sub_type": "quick_reply", "index": "0",
+             "parameters": [{"type": "payload", "payload": "buy-1"}]},
+            {"type": "button", "sub_type": "url", "index": "1", "parameters": [{"type": "text", "text": "tenis"}]},
+        ]
+        assert second["components"][0]["parameters"] == [{"type": "video", "video": {"id": "media-2"}}]
+        assert template_capabilities([component]) == {"carousel_templates"}
+
+    def test_static_url_buttons_have_no_parameter(self):
+        """Test URL buttons without a {{1}} suffix are left out of the message"""
+        layout = [{"header_format": "IMAGE", "body_variables_count": 0,
+                   "buttons": [{"type": "URL", "has_variable": False}]}]
+
+        component = build_carousel_component([{"header_url": "https://cdn.com/1.jpg", "buttons": [{"text": "x"}]}], layout)
+
+        assert component["cards"][0]["components"] == [
+            {"type": "header", "parameters": [{"type": "image", "image": {"link": "https://cdn.com/1.jpg"}}]},
+        ]
@@ -0,0 +1,86 @@
+"""
+Template Components Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timezone
+
+import pytest
+
+from app.utils.template_components import (
+    CopyCodeButton,
+    LimitedTimeOffer,
+    copy_code_button_index,
+    limited_time_offer_definition,
+)
+
+EXPIRES_AT = datetime(2025, 11, 30, 23, 59, tzinfo=timezone.utc)
+
+
+class TestCopyCodeButton:
+    """Tests for COPY_CODE button parameters"""
+
+    def test_component(self):
+        """Test the coupon_code parameter and button index"""
+        assert CopyCodeButton(code=" BLACK20 ", index=1).to_component() == {
+            "type": "button",
+            "sub_type": "copy_code",
+            "index": "1",
+            "parameters": [{"type": "coupon_code", "coupon_code": "BLACK20"}],
+        }
+
+    def test_validation(self):
+        """Test empty, too long and spaced codes are rejected"""
+        assert CopyCodeButton(code="BLACK20").validate() is None
+        assert CopyCodeButton(code=" ").validate() == "coupon code is empty"
+        assert "max 15" in CopyCodeButton(code="X" * 16).validate()
+        assert CopyCodeButton(code="BLACK 20").validate() == "coupon code cannot contain spaces"
+
+    def test_button_index(self):
+        """Test the COPY_CODE button is found among the template buttons"""
+        buttons = [{"type": "URL", "text": "Loja"}, {"type": "COPY_CODE", "example": "SAVE10"}]
+
+        assert copy_code_button_index(buttons) == 1
+        assert copy_code_button_index([{"type": "QUICK_REPLY", "text": "Sim"}]) is None
+        assert copy_code_button_index(None) is None
+
+
+class TestLimitedTimeOffer:
+    """Tests for limited-time offer expiration"""
+
+    def test_component(self):
+        """Test expiration_time_ms of the limited_time_offer parameter"""
+        assert LimitedTimeOffer(expires_at=EXPIRES_AT).to_component() == {
+            "type": "limited_time_offer",
+            "parameters": [{
+                "type": "limited_time_offer",
+                "limited_time_offer": {"expiration_time_ms": 1764547140000},
+            }],
+        }
+
+    @pytest.mark.parametrize("value", [
+        EXPIRES_AT,
+        "2025-11-30T23:59:00Z",
+        "2025-11-30T20:59:00-03:00",
+        "2025-11-30T23:59:00",
+        1764547140000,
+    ])
+    def test_parse(self, value):
+        """Test datetimes, ISO strings (naive as UTC) and millisecond timestamps"""
+        assert LimitedTimeOffer.parse(value).expires_at == EXPIRES_AT
+
+    def test_parse_rejects_non_dates(self):
+        for value in (None, "", "amanhã", True):
+            with pytest.raises(ValueError):
+                LimitedTimeOffer.parse(value)
+
+    def test_definition(self):
+        """Test LIMITED_TIME_OFFER settings are read from the template components"""
+        components = [
+            {"type": "LIMITED_TIME_OFFER", "limited_time_offer": {"text": "Só hoje!", "has_expiration": True}},
+            {"type": "BODY", "text": "Use o cupom {{1}}"},
+        ]
+
+        assert limited_time_offer_definition(components) == {"text": "Só hoje!", "has_expiration": True}
+        assert limited_time_offer_definition(components[1:]) is None
@@ -0,0 +1,133 @@
+"""
+Tracing Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import httpx
+import pytest
+
+from app.core import tracing
+from app.core.tracing import (
+    SpanExporter,
+    TraceContext,
+    TracingTransport,
+    current_traceparent,
+    hash_recipient,
+    tracer,
+)
+
+TRACEPARENT = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+
+
+class CollectingExporter(SpanExporter):
+    """Keeps finished spans"""
+
+    def __init__(self):
+        self.spans = []
+
+    def export(self, span):
+        self.spans.append(span)
+
+
+@pytest.fixture
+def exporter(monkeypatch):
+    collected = CollectingExporter()
+    monkeypatch.setattr(tracing.tracer, "exporter", collected)
+    return collected
+
+
+def _graph(request):
+    if request.url.path.endswith("/messages"):
+        return httpx.Response(200, json={"messages": [{"id": "wamid.1"}]}, headers={"x-fb-trace-id": "AbC123"})
+    return httpx.Response(400, json={"error": {"message": "Invalid parameter", "code": 100, "fbtrace_id": "XyZ"}})
+
+
+def _client(parent=None):
+    return httpx.AsyncClient(transport=TracingTransport("meta_api", "123", parent, wrapped=httpx.MockTransport(_graph)))
+
+
+class TestTraceContext:
+    """Tests for W3C traceparent headers"""
+
+    def test_round_trip(self):
+        """Test a valid header is parsed and formatted back"""
+        context = TraceContext.from_traceparent(TRACEPARENT)
+
+        assert context.trace_id == "4bf92f3577b34da6a3ce929d0e0e4736"
+        assert context.span_id == "00f067aa0ba902b7"
+        assert context.traceparent == TRACEPARENT
+
+    def test_invalid_headers_are_ignored(self):
+        """Test malformed and all-zero ids start a new trace"""
+        assert TraceContext.from_traceparent("garbage") is None
+        assert TraceContext.from_traceparent(f"00-{'0' * 32}-00f067aa0ba902b7-01") is None
+        assert TraceContext.from_traceparent(None) is None
+
+
+class TestTracer:
+    """Tests for span nesting"""
+
+    def test_nested_spans(self, exporter):
+        """Test children share the trace and errors are recorded"""
+        with tracer.start_span("webhook", parent=TRACEPARENT) as webhook:
+            assert current_traceparent() == webhook.context.traceparent
+            with pytest.raises(ValueError):
+                with tracer.start_span("reply", **{"whatsapp.message_type": "text", "ignored": None}):
+                    raise ValueError("boom")
+
+        reply, root = exporter.spans
+        assert root.context.trace_id == reply.context.trace_id == "4bf92f3577b34da6a3ce929d0e0e4736"
+        assert root.parent_span_id == "00f067aa0ba902b7"
+        assert reply.parent_span_id == root.context.span_id
+        assert reply.error == "ValueError: boom"
+        assert reply.attributes == {"whatsapp.message_type": "text"}
+        assert current_traceparent() is None
+
+    def test_exporter_failures_are_swallowed(self, monkeypatch):
+        """Test a broken exporter never fails the traced operation"""
+        class BrokenExporter(SpanExporter):
+            def export(self, span):
+                raise RuntimeError("collector down")
+
+        monkeypatch.setattr(tracing.tracer, "exporter", BrokenExporter())
+
+        with tracer.start_span("send"):
+            pass
+
+
+class TestTracingTransport:
+    """Tests for spans of Meta API requests"""
+
+    @pytest.mark.asyncio
+    async def test_reply_is_child_of_webhook(self, exporter):
+        """Test an outbound send joins the webhook's trace with Meta's request id"""
+        with tracer.start_span("whatsapp.webhook", parent=TRACEPARENT):
+            async with _client() as client:
+                response = await client.post(
+                    "https://graph.facebook.com/v21.0/123/messages",
+                    json={"to": "+55 11 99999-9999", "type": "text", "text": {"body": "Olá"}},
+                )
+
+        send, webhook = exporter.spans
+        assert response.json()["messages"][0]["id"] == "wamid.1"
+        assert send.name == "meta_api POST messages"
+        assert send.parent_span_id == webhook.context.span_id
+        assert send.attributes["whatsapp.phone_number_id"] == "123"
+        assert send.attributes["whatsapp.message_type"] == "text"
+        assert send.attributes["whatsapp.recipient_hash"] == hash_recipient("5511999999999")
+        assert "5511999999999" not in str(send.attributes)
+        assert send.attributes["meta.fbtrace_id"] == "AbC123"
+
+    @pytest.mark.asyncio
+    async def test_error_body_and_explicit_parent(self, exporter):
+        """Test the parent context is used outside a span and error fbtrace_id is captured"""
+        async with _client(parent=TRACEPARENT) as client:
+            response = await client.get("https://graph.facebook.com/v21.0/123/whatsapp_business_profile")
+
+        span = exporter.spans[0]
+        assert response.json()["error"]["code"] == 100
+        assert span.parent_span_id == "00f067aa0ba902b7"
+        assert span.attributes["http.status_code"] == 400
+        assert span.attributes["meta.fbtrace_id"] == "XyZ"
+        assert span.error == "HTTP 400"
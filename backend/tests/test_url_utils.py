@@ -0,0 +1,36 @@
+"""
+URL Utils Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.utils.urls import extract_urls, is_valid_preview_url
+
+
+class TestExtractUrls:
+    """Tests for URL detection in message bodies"""
+
+    def test_detects_urls_in_order(self):
+        """Test http(s) and www URLs are found without trailing punctuation"""
+        text = "Veja https://pytake.net/planos, ou www.exemplo.com.br!"
+
+        assert extract_urls(text) == ["https://pytake.net/planos", "www.exemplo.com.br"]
+
+    def test_text_without_url(self):
+        """Test plain text has no URLs"""
+        assert extract_urls("Olá, tudo bem?") == []
+        assert extract_urls("") == []
+
+
+class TestIsValidPreviewUrl:
+    """Tests for preview URL validation"""
+
+    def test_valid_urls(self):
+        assert is_valid_preview_url("https://pytake.net")
+        assert is_valid_preview_url("http://exemplo.com.br/a?b=1")
+
+    def test_invalid_urls(self):
+        assert not is_valid_preview_url("ftp://pytake.net")
+        assert not is_valid_preview_url("pytake.net")
+        assert not is_valid_preview_url("https://localhost")
+        assert not is_valid_preview_url("https://pytake.net/a b")
@@ -0,0 +1,155 @@
+"""
+Webhook Event Service Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.models.webhook_event import WebhookEvent
+from app.models.whatsapp_number import WhatsAppNumber
+from app.schemas.webhook_event import WebhookReplayRequest
+from app.services.webhook_event_service import WebhookEventService, first_phone_number_id
+from app.services.whatsapp_service import WhatsAppService
+from tests.conftest import OrganizationFactory
+
+
+def _payload(phone_number_id: str = "123", message_id: str = "wamid.1") -> dict:
+    return {
+        "object": "whatsapp_business_account",
+        "entry": [{
+            "id": "waba-1",
+            "changes": [{
+                "field": "messages",
+                "value": {
+                    "metadata": {"phone_number_id": phone_number_id},
+                    "messages": [{"id": message_id, "from": "5511999999999", "type": "text"}],
+                },
+            }],
+        }],
+    }
+
+
+async def _reload(db: AsyncSession, event: WebhookEvent) -> WebhookEvent:
+    await db.refresh(event)
+    return event
+
+
+class TestWebhookEventService:
+    """Tests for webhook persistence, outcomes and replay"""
+
+    @pytest_asyncio.fixture
+    async def service(self, db_session: AsyncSession, monkeypatch) -> WebhookEventService:
+        calls = []
+
+        async def fake_process_webhook(self, payload, dedup=True):
+            calls.append((payload["entry"][0]["changes"][0]["value"]["messages"][0]["id"], dedup))
+            if service.fail_with:
+                raise RuntimeError(service.fail_with)
+            return service.handled
+
+        monkeypatch.setattr(WhatsAppService, "process_webhook", fake_process_webhook)
+        service = WebhookEventService(db_session)
+        service.calls = calls
+        service.fail_with = None
+        service.handled = 1
+        service.enqueued = []
+        monkeypatch.setattr(service, "_enqueue_replay", lambda ids: service.enqueued.append(list(ids)))
+        return service
+
+    @pytest.mark.asyncio
+    async def test_outcomes_are_recorded(self, service: WebhookEventService, db_session: AsyncSession):
+        """Test processed, skipped and failed payloads keep their status and error"""
+        org = await OrganizationFactory.create_in_db(db_session)
+
+        ok = await service.record(_payload(message_id="wamid.ok"), organization_id=org.id)
+        assert await service.process(ok.id, ok.payload) == "processed"
+
+        service.handled = 0
+        skipped = await service.record(_payload(message_id="wamid.skip"), organization_id=org.id)
+        assert await service.process(skipped.id, skipped.payload) == "skipped"
+
+        service.fail_with = "database is down"
+        failed = await service.record(_payload(message_id="wamid.fail"), organization_id=org.id)
+        with pytest.raises(RuntimeError):
+            await service.process(failed.id, failed.payload)
+
+        ok, skipped, failed = [await _reload(db_session, e) for e in (ok, skipped, failed)]
+        assert (ok.status, ok.attempts, ok.processed_at is not None) == ("processed", 1, True)
+        assert skipped.status == "skipped"
+        assert (failed.status, failed.error) == ("failed", "database is down")
+        assert ok.phone_number_id == "123"
+
+    @pytest.mark.asyncio
+    async def test_replay_failed_events(self, service: WebhookEventService, db_session: AsyncSession):
+        """Test failed events are queued, then replayed in order without dedup"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        first = await service.record(_payload(message_id="wamid.1"), organization_id=org.id)
+        second = await service.record(_payload(message_id="wamid.2"), organization_id=org.id)
+        done = await service.record(_payload(message_id="wamid.3"), organization_id=org.id)
+        await service.mark(first.id, "failed", "boom")
+        await service.mark(second.id, "skipped")
+        await service.mark(done.id, "processed")
+
+        queued = await service.request_replay(org.id, WebhookReplayRequest(since=first.received_at))
+
+        assert queued == [first.id, second.id]
+        assert service.enqueued == [[first.id, second.id]]
+        assert (await _reload(db_session, first)).status == "queued"
+
+        summary = await service.replay(queued)
+
+        assert summary == {"processed": 2, "failed": 0, "skipped": 0}
+        assert service.calls == [("wamid.1", False), ("wamid.2", False)]
+        assert (await _reload(db_session, first)).attempts == 2
+
+    @pytest.mark.asyncio
+    async def test_nothing_to_replay(self, service: WebhookEventService, db_session: AsyncSession):
+        org = await OrganizationFactory.create_in_db(db_session)
+        done = await service.record(_payload(), organization_id=org.id)
+        await service.mark(done.id, "processed")
+
+        with pytest.raises(BadRequestException):
+            await service.request_replay(org.id, WebhookReplayRequest(event_ids=[done.id]))
+
+    @pytest.mark.asyncio
+    async def test_unclaimed_events_of_own_numbers(
+        self, service: WebhookEventService, db_session: AsyncSession
+    ):
+        """Test events received before a number was registered become visible to its owner"""
+        org = await OrganizationFactory.create_in_db(db_session)
+        other = await OrganizationFactory.create_in_db(db_session)
+        db_session.add(WhatsAppNumber(
+            id=uuid4(), organization_id=org.id, phone_number="+5511999990000", phone_number_id="555"
+        ))
+        await db_session.commit()
+
+        early = await service.record(_payload(phone_number_id="555"), source="meta")
+        stranger = await service.record(_payload(phone_number_id="999"), source="meta")
+
+        items, total = await service.list_events(org.id)
+        assert [e.id for e in items] == [early.id] and total == 1
+        with pytest.raises(NotFoundException):
+            await service.get_event(stranger.id, org.id)
+        assert (await service.list_events(other.id))[1] == 0
+
+        await service.mark(early.id, "skipped")
+        await service.request_replay(org.id, WebhookReplayRequest(event_ids=[early.id]))
+        assert (await _reload(db_session, early)).organization_id == org.id
+
+
+class TestWebhookReplayRequest:
+    """Tests for replay selection validation"""
+
+    def test_requires_ids_or_period(self):
+        with pytest.raises(ValueError):
+            WebhookReplayRequest()
+
+    def test_first_phone_number_id(self):
+        assert first_phone_number_id(_payload(phone_number_id="42")) == "42"
+        assert first_phone_number_id({"entry": []}) is None
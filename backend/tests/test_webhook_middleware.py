@@ -0,0 +1,140 @@
+"""
+Webhook Signature Middleware Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import hashlib
+import hmac
+import json
+
+import pytest
+
+from pytake_client.middleware import WebhookSignatureMiddleware, get_raw_body
+
+SECRET = "app-secret"
+BODY = json.dumps({"object": "whatsapp_business_account", "entry": []}).encode()
+
+
+def _sign(body: bytes, secret: str = SECRET) -> bytes:
+    return ("sha256=" + hmac.new(secret.encode(), body, hashlib.sha256).hexdigest()).encode()
+
+
+class _Handler:
+    """ASGI app that records what reached it"""
+
+    def __init__(self):
+        self.called = False
+        self.body = None
+        self.raw_body = None
+
+    async def __call__(self, scope, receive, send):
+        self.called = True
+        message = await receive()
+        self.body = message["body"]
+        self.raw_body = get_raw_body(scope)
+        await send({"type": "http.response.start", "status": 200, "headers": []})
+        await send({"type": "http.response.body", "body": b"ok"})
+
+
+async def _request(middleware, path="/webhooks/meta", method="POST", body=BODY, signature=None, chunks=1):
+    headers = [(b"content-type", b"application/json")]
+    if signature is not None:
+        headers.append((b"X-Hub-Signature-256", signature))
+
+    size = max(len(body) // chunks, 1)
+    parts = [body[i:i + size] for i in range(0, len(body), size)] or [b""]
+    messages = [
+        {"type": "http.request", "body": part, "more_body": i < len(parts) - 1}
+        for i, part in enumerate(parts)
+    ]
+    sent = []
+
+    async def receive():
+        return messages.pop(0) if messages else {"type": "http.disconnect"}
+
+    async def send(message):
+        sent.append(message)
+
+    scope = {"type": "http", "method": method, "path": path, "headers": headers}
+    await middleware(scope, receive, send)
+    return sent[0]["status"], sent[1].get("body", b"")
+
+
+class TestWebhookSignatureMiddleware:
+    """Tests for signature verification before the handler"""
+
+    @pytest.mark.asyncio
+    async def test_valid_signature_reaches_handler(self):
+        """Test the handler gets the body and the captured raw body"""
+        handler = _Handler()
+        middleware = WebhookSignatureMiddleware(handler, secret=SECRET, paths=["/webhooks/meta"])
+
+        status, _ = await _request(middleware, signature=_sign(BODY), chunks=3)
+
+        assert status == 200
+        assert handler.body == BODY
+        assert handler.raw_body == BODY
+
+    @pytest.mark.asyncio
+    async def test_missing_signature(self):
+        handler = _Handler()
+        middleware = WebhookSignatureMiddleware(handler, secret=SECRET)
+
+        status, body = await _request(middleware)
+
+        assert status == 401
+        assert json.loads(body) == {"detail": "Missing signature"}
+        assert not handler.called
+
+    @pytest.mark.asyncio
+    async def test_invalid_signature(self):
+        """Test a payload signed with another secret never reaches the handler"""
+        handler = _Handler()
+        middleware = WebhookSignatureMiddleware(handler, secret=SECRET)
+
+        status, _ = await _request(middleware, signature=_sign(BODY, "other-secret"))
+
+        assert status == 403
+        assert not handler.called
+
+    @pytest.mark.asyncio
+    async def test_tampered_body(self):
+        handler = _Handler()
+        middleware = WebhookSignatureMiddleware(handler, secret=SECRET)
+
+        status, _ = await _request(middleware, body=BODY + b" ", signature=_sign(BODY))
+
+        assert status == 403
+
+    @pytest.mark.asyncio
+    async def test_unprotected_requests_pass_through(self):
+        """Test other paths and GET verification challenges are not checked"""
+        handler = _Handler()
+        middleware = WebhookSignatureMiddleware(handler, secret=SECRET, paths=["/webhooks/meta"])
+
+        assert (await _request(middleware, path="/webhooks/meta/test"))[0] == 200
+        assert (await _request(middleware, method="GET"))[0] == 200
+        assert handler.raw_body is None
+
+    @pytest.mark.asyncio
+    async def test_prefix_and_trailing_slash(self):
+        handler = _Handler()
+        middleware = WebhookSignatureMiddleware(handler, secret=SECRET, paths=["/hooks/*", "/webhooks/meta"])
+
+        assert (await _request(middleware, path="/hooks/tenant-a"))[0] == 401
+        assert (await _request(middleware, path="/webhooks/meta/"))[0] == 401
+
+    @pytest.mark.asyncio
+    async def test_body_too_large(self):
+        handler = _Handler()
+        middleware = WebhookSignatureMiddleware(handler, secret=SECRET, max_body_size=10)
+
+        status, _ = await _request(middleware, signature=_sign(BODY))
+
+        assert status == 413
+        assert not handler.called
+
+    def test_requires_secret(self):
+        with pytest.raises(ValueError):
+            WebhookSignatureMiddleware(_Handler(), secret="")
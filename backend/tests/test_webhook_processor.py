@@ -0,0 +1,422 @@
+"""
+Webhook Processor Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import asyncio
+
+import pytest
+
+from pytake_client.processor import (
+    UNROUTABLE_MISSING,
+    UNROUTABLE_UNKNOWN,
+    InMemoryDedupStore,
+    NumberRoute,
+    PhoneNumberRouter,
+    RedisDedupStore,
+    WebhookHandler,
+    WebhookProcessor,
+    split_by_number,
+    status_dedup_key,
+)
+
+
+def _payload(messages=(), statuses=()):
+    return {
+        "object": "whatsapp_business_account",
+        "entry": [{
+            "id": "waba-1",
+            "changes": [{
+                "field": "messages",
+                "value": {
+                    "metadata": {"phone_number_id": "123"},
+                    "messages": list(messages),
+                    "statuses": list(statuses),
+                },
+            }],
+        }],
+    }
+
+
+def _text(message_id: str) -> dict:
+    return {"id": message_id, "from": "5511999999999", "type": "text", "text": {"body": "Oi"}}
+
+
+class FakeClock:
+    def __init__(self):
+        self.now = 0.0
+
+    def __call__(self) -> float:
+        return self.now
+
+
+class FakeRedis:
+    """redis.asyncio-like client (SET NX EX)"""
+
+    def __init__(self, fail: bool = False):
+        self.keys = {}
+        self.fail = fail
+
+    async def set(self, key, value, ex=None, nx=False):
+        if self.fail:
+            raise ConnectionError("redis down")
+        if nx and key in self.keys:
+            return None
+        self.keys[key] = (value, ex)
+        return True
+
+    async def delete(self, *keys):
+        return sum(1 for key in keys if self.keys.pop(key, None) is not None)
+
+
+class TestWebhookProcessor:
+    """Tests for skipping redelivered messages and statuses"""
+
+    @pytest.mark.asyncio
+    async def test_redelivery_is_skipped(self):
+        """Test the same payload delivered twice yields its events once"""
+        processor = WebhookProcessor()
+        payload = _payload(messages=[_text("wamid.1")], statuses=[{"id": "wamid.0", "status": "read"}])
+
+        first = await processor.new_events(payload)
+        second = await processor.new_events(payload)
+
+        assert [event.data["id"] for event in first] == ["wamid.1", "wamid.0"]
+        assert second == []
+
+    @pytest.mark.asyncio
+    async def test_status_progression_is_not_deduplicated(self):
+        """Test delivered and read of the same message are distinct events"""
+        processor = WebhookProcessor()
+        delivered = {"id": "wamid.1", "status": "delivered"}
+        read = {"id": "wamid.1", "status": "read"}
+
+        events = await processor.new_events(_payload(statuses=[delivered, read, delivered]))
+
+        assert [event.data["status"] for event in events] == ["delivered", "read"]
+
+    @pytest.mark.asyncio
+    async def test_events_without_id_always_pass(self):
+        processor = WebhookProcessor()
+        payload = {"entry": [{"changes": [{
+            "field": "message_template_status_update",
+            "value": {"event": "APPROVED", "message_template_id": 1},
+        }]}]}
+
+        assert len(await processor.new_events(payload)) == 1
+        assert len(await processor.new_events(payload)) == 1
+
+    @pytest.mark.asyncio
+    async def test_release_allows_reprocessing(self):
+        """Test a released key (failed processing) is accepted again"""
+        processor = WebhookProcessor()
+        key = status_dedup_key({"id": "wamid.1", "status": "sent"})
+
+        assert await processor.claim(key)
+        await processor.release(key)
+        assert await processor.claim(key)
+
+
+class TestInMemoryDedupStore:
+    """Tests for the per-process LRU store"""
+
+    @pytest.mark.asyncio
+    async def test_ttl_expiry(self):
+        clock = FakeClock()
+        store = InMemoryDedupStore(clock=clock)
+
+        assert await store.claim("message:1", 60)
+        clock.now = 59
+        assert not await store.claim("message:1", 60)
+        clock.now = 61
+        assert await store.claim("message:1", 60)
+
+    @pytest.mark.asyncio
+    async def test_lru_eviction(self):
+        """Test the least recently claimed keys are evicted past max_size"""
+        store = InMemoryDedupStore(max_size=2)
+
+        await store.claim("message:1", 60)
+        await store.claim("message:2", 60)
+        await store.claim("message:3", 60)
+
+        assert len(store) == 2
+        assert await store.claim("message:1", 60)
+        assert not await store.claim("message:3", 60)
+
+
+class TestRedisDedupStore:
+    """Tests for the shared Redis store"""
+
+    @pytest.mark.asyncio
+    async def test_shared_between_processors(self):
+        """Test two workers sharing Redis process a redelivery once"""
+        redis = FakeRedis()
+        worker_a = WebhookProcessor(RedisDedupStore(redis), ttl_seconds=300)
+        worker_b = WebhookProcessor(RedisDedupStore(redis), ttl_seconds=300)
+        payload = _payload(messages=[_text("wamid.1")])
+
+        assert len(await worker_a.new_events(payload)) == 1
+        assert await worker_b.new_events(payload) == []
+        assert redis.keys["pytake:webhook:seen:message:wamid.1"] == ("1", 300)
+
+    @pytest.mark.asyncio
+    async def test_fails_open(self):
+        """Test events are processed when Redis is unavailable"""
+        processor = WebhookProcessor(RedisDedupStore(FakeRedis(fail=True)))
+        payload = _payload(messages=[_text("wamid.1")])
+
+        assert len(await processor.new_events(payload)) == 1
+        assert len(await processor.new_events(payload)) == 1
+
+
+class Recorder(WebhookHandler):
+    """Handler recording the method called for each event"""
+
+    def __init__(self):
+        self.calls = []
+
+    async def on_message(self, event):
+        self.calls.append(("message", event.data.get("id")))
+
+    async def on_status(self, event):
+        self.calls.append(("status", event.data.get("status")))
+
+    async def on_template_update(self, event):
+        self.calls.append(("template", event.template_status.event))
+
+
+class TestWebhookDispatch:
+    """Tests for routing events to WebhookHandler methods"""
+
+    @pytest.mark.asyncio
+    async def test_routes_by_event_kind(self):
+        """Test messages, statuses and template updates reach their methods"""
+        recorder = Recorder()
+        processor = WebhookProcessor(handlers=[recorder])
+        payload = _payload(
+            messages=[_text("wamid.1"), {"id": "wamid.2", "type": "reaction", "reaction": {"emoji": "👍"}}],
+            statuses=[{"id": "wamid.0", "status": "read"}],
+        )
+        payload["entry"][0]["changes"].append({
+            "field": "message_template_status_update",
+            "value": {"event": "APPROVED", "message_template_id": 1},
+        })
+
+        await processor.dispatch(payload)
+
+        # Reactions fall back to on_message
+        assert recorder.calls == [
+            ("message", "wamid.1"),
+            ("message", "wamid.2"),
+            ("status", "read"),
+            ("template", "APPROVED"),
+        ]
+
+    @pytest.mark.asyncio
+    async def test_every_handler_is_called_once_per_event(self):
+        first, second = Recorder(), Recorder()
+        processor = WebhookProcessor()
+        processor.register(first)
+        processor.register(second)
+        payload = _payload(messages=[_text("wamid.1")])
+
+        await processor.dispatch(payload)
+        await processor.dispatch(payload)
+
+        assert first.calls == second.calls == [("message", "wamid.1")]
+
+    @pytest.mark.asyncio
+    async def test_on_error_can_swallow(self):
+        """Test a handler's on_error decides whether dispatch continues"""
+        errors = []
+
+        class Tolerant(WebhookHandler):
+            async def on_message(self, event):
+                raise ValueError(event.data["id"])
+
+            async def on_error(self, event, error):
+                errors.append(str(error))
+
+        processor = WebhookProcessor(handlers=[Tolerant()])
+        await processor.dispatch(_payload(messages=[_text("wamid.1"), _text("wamid.2")]))
+
+        assert errors == ["wamid.1", "wamid.2"]
+
+    @pytest.mark.asyncio
+    async def test_failure_releases_pending_events(self):
+        """Test events not handled because of an error are processed on redelivery"""
+        recorder = Recorder()
+
+        class Flaky(WebhookHandler):
+            failures = 1
+
+            async def on_message(self, event):
+                if event.data["id"] == "wamid.2" and self.failures:
+                    self.failures -= 1
+                    raise RuntimeError("database is down")
+
+        processor = WebhookProcessor(handlers=[Flaky(), recorder])
+        payload = _payload(messages=[_text("wamid.1"), _text("wamid.2"), _text("wamid.3")])
+
+        with pytest.raises(RuntimeError):
+            await processor.dispatch(payload)
+        await processor.dispatch(payload)
+
+        assert recorder.calls == [("message", "wamid.1"), ("message", "wamid.2"), ("message", "wamid.3")]
+
+
+class TestWebhookStream:
+    """Tests for streaming and concurrent processing of batched payloads"""
+
+    @pytest.mark.asyncio
+    async def test_flattens_entries_and_changes(self):
+        """Test events of every entry and change are streamed in payload order"""
+        processor = WebhookProcessor()
+        payload = _payload(messages=[_text("wamid.1")])
+        payload["entry"].append(_payload(messages=[_text("wamid.2")], statuses=[{"id": "wamid.0", "status": "sent"}])["entry"][0])
+
+        ids = [event.data["id"] async for event in processor.iter_events(payload)]
+
+        assert ids == ["wamid.1", "wamid.2", "wamid.0"]
+        assert [e async for e in processor.iter_events(payload)] == []
+        assert len([e async for e in processor.iter_events(payload, dedup=False)]) == 3
+
+    @pytest.mark.asyncio
+    async def test_failures_are_isolated(self):
+        """Test a failing event is reported and released while the others succeed"""
+        processor = WebhookProcessor()
+        payload = _payload(messages=[_text("wamid.1"), _text("wamid.2")])
+
+        async def handle(event):
+            if event.data["id"] == "wamid.1":
+                raise ValueError("boom")
+            return event.data["id"]
+
+        results = await processor.process(payload, handle)
+
+        assert [(r.ok, r.result) for r in results] == [(False, None), (True, "wamid.2")]
+        assert str(results[0].error) == "boom"
+        # Only the failed event is processed again on redelivery
+        assert [e.data["id"] for e in await processor.new_events(payload)] == ["wamid.1"]
+
+    @pytest.mark.asyncio
+    async def test_contacts_run_concurrently_in_order(self):
+        """Test different contacts overlap while each contact's events stay ordered"""
+        processor = WebhookProcessor()
+        alice = [dict(_text(f"wamid.a{i}"), **{"from": "551100000001"}) for i in range(3)]
+        bob = [dict(_text(f"wamid.b{i}"), **{"from": "551100000002"}) for i in range(3)]
+        payload = _payload(messages=[m for pair in zip(alice, bob) for m in pair])
+        running, peak, order = 0, 0, []
+
+        async def handle(event):
+            nonlocal running, peak
+            running += 1
+            peak = max(peak, running)
+            await asyncio.sleep(0)
+            order.append(event.data["id"])
+            running -= 1
+
+        await processor.process(payload, handle, concurrency=2)
+
+        assert peak == 2
+        assert [i for i in order if i.startswith("wamid.a")] == ["wamid.a0", "wamid.a1", "wamid.a2"]
+        assert [i for i in order if i.startswith("wamid.b")] == ["wamid.b0", "wamid.b1", "wamid.b2"]
+
+
+def _number_change(phone_number_id, *messages):
+    return {
+        "field": "messages",
+        "value": {"metadata": {"phone_number_id": phone_number_id}, "messages": list(messages)},
+    }
+
+
+class TestPhoneNumberRouter:
+    """Tests for routing events to the handlers of the receiving number"""
+
+    @pytest.mark.asyncio
+    async def test_dispatches_to_the_number_handlers(self):
+        """Test each number's events reach only its handlers, after the shared ones"""
+        shared, store_a, store_b = Recorder(), Recorder(), Recorder()
+        router = PhoneNumberRouter()
+        router.add("111", handlers=[store_a], tenant="store-a")
+        router.add("222", handlers=[store_b], tenant="store-b")
+        processor = WebhookProcessor(handlers=[shared], router=router)
+        payload = {"entry": [{"id": "waba-1", "changes": [
+            _number_change("111", _text("wamid.a")),
+            _number_change("222", _text("wamid.b")),
+        ]}]}
+
+        events = await processor.dispatch(payload)
+
+        assert shared.calls == [("message", "wamid.a"), ("message", "wamid.b")]
+        assert store_a.calls == [("message", "wamid.a")]
+        assert store_b.calls == [("message", "wamid.b")]
+        assert [event.route.tenant for event in events] == ["store-a", "store-b"]
+        assert router.metrics.routed == {"111": 1, "222": 1}
+
+    @pytest.mark.asyncio
+    async def test_unroutable_events_are_counted(self):
+        """Test unknown and missing numbers are reported, account events are not"""
+        reported = []
+
+        async def on_unroutable(reason, phone_number_id, event):
+            reported.append((reason, phone_number_id))
+
+        router = PhoneNumberRouter(on_unroutable=on_unroutable)
+        processor = WebhookProcessor(router=router)
+        payload = {"entry": [{"id": "waba-1", "changes": [
+            _number_change("999", _text("wamid.1"), _text("wamid.2")),
+            {"field": "messages", "value": {"messages": [_text("wamid.3")]}},
+            {"field": "message_template_status_update", "value": {"event": "APPROVED"}},
+        ]}]}
+
+        events = await processor.dispatch(payload)
+
+        assert all(event.route is None for event in events)
+        assert reported == [
+            (UNROUTABLE_UNKNOWN, "999"), (UNROUTABLE_UNKNOWN, "999"), (UNROUTABLE_MISSING, None),
+        ]
+        assert router.metrics.unroutable == {UNROUTABLE_UNKNOWN: 2, UNROUTABLE_MISSING: 1}
+        assert router.metrics.unknown_numbers == {"999": 2}
+        assert router.metrics.account_events == 1
+
+    @pytest.mark.asyncio
+    async def test_resolver_answers_are_cached(self):
+        """Test the resolver runs once per number per TTL, unknown numbers included"""
+        clock = FakeClock()
+        lookups = []
+
+        async def resolver(phone_number_id):
+            lookups.append(phone_number_id)
+            return NumberRoute(phone_number_id, tenant="org-1") if phone_number_id == "111" else None
+
+        router = PhoneNumberRouter(resolver=resolver, cache_ttl_seconds=60, clock=clock)
+
+        assert (await router.route_number("111")).tenant == "org-1"
+        assert await router.route_number("111")
+        assert await router.route_number("999") is None
+        assert await router.route_number("999") is None
+        assert lookups == ["111", "999"]
+
+        clock.now = 61
+        await router.route_number("111")
+        router.invalidate("999")
+        await router.route_number("999")
+        assert lookups == ["111", "999", "111", "999"]
+
+    def test_split_by_number(self):
+        """Test a batch is split per number, keeping entry ids and account changes apart"""
+        template = {"field": "message_template_status_update", "value": {"event": "APPROVED"}}
+        payload = {"object": "whatsapp_business_account", "entry": [
+            {"id": "waba-1", "changes": [_number_change("111", _text("wamid.1")), template]},
+            {"id": "waba-2", "changes": [_number_change("222", _text("wamid.2")), _number_change("111", _text("wamid.3"))]},
+        ]}
+
+        parts = split_by_number(payload)
+
+        assert list(parts) == ["111", None, "222"]
+        assert [entry["id"] for entry in parts["111"]["entry"]] == ["waba-1", "waba-2"]
+        assert parts["111"]["object"] == "whatsapp_business_account"
+        assert parts[None]["entry"] == [{"id": "waba-1", "changes": [template]}]
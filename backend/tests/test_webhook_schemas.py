@@ -0,0 +1,239 @@
+"""
+Webhook Schemas Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from app.schemas.webhook import (
+    MessageContext,
+    MessageEdit,
+    MessageReaction,
+    OrderMessage,
+    PaymentStatus,
+    TemplateStatusUpdate,
+    WebhookChallenge,
+    WebhookEventType,
+    classify_message,
+    classify_status,
+    iter_events,
+)
+
+
+class TestOrderMessage:
+    """Tests for order message parsing"""
+
+    def test_parse_order(self):
+        """Test order items, quantities and prices are parsed"""
+        message = {
+            "from": "5511999999999",
+            "id": "wamid.order",
+            "type": "order",
+            "order": {
+                "catalog_id": "cat-1",
+                "text": "Sem cebola",
+                "product_items": [
+                    {"product_retailer_id": "SKU1", "quantity": "2", "item_price": "10.50", "currency": "BRL"},
+                    {"product_retailer_id": "SKU2", "quantity": 1, "item_price": 5, "currency": "BRL"},
+                ],
+            },
+        }
+
+        order = OrderMessage.from_webhook(message)
+
+        assert classify_message(message) == WebhookEventType.ORDER
+        assert order.catalog_id == "cat-1"
+        assert order.total_items == 3
+        assert order.total_amount == 26.0
+        assert order.to_content()["currency"] == "BRL"
+
+
+class TestPaymentStatus:
+    """Tests for payment status parsing"""
+
+    def test_parse_payment(self):
+        """Test payment amount and transaction are parsed"""
+        status = {
+            "id": "wamid.pay",
+            "status": "captured",
+            "type": "payment",
+            "recipient_id": "5511999999999",
+            "payment": {
+                "reference_id": "order-123",
+                "amount": {"value": 21000, "offset": 100},
+                "currency": "BRL",
+                "transaction": {"id": "tx-1", "type": "pix", "status": "success"},
+            },
+        }
+
+        payment = PaymentStatus.from_webhook(status)
+
+        assert classify_status(status) == WebhookEventType.PAYMENT
+        assert payment.amount.decimal == 210.0
+        assert payment.to_variables()["transaction_id"] == "tx-1"
+
+    def test_regular_status_is_not_payment(self):
+        """Test delivery statuses keep the status event type"""
+        assert classify_status({"id": "wamid.x", "status": "delivered"}) == WebhookEventType.STATUS
+
+
+class TestMessageContext:
+    """Tests for reply/forward context parsing"""
+
+    def test_parse_reply_context(self):
+        """Test quoted message id and forward flags are parsed"""
+        message = {
+            "id": "wamid.reply",
+            "type": "text",
+            "text": {"body": "Sim"},
+            "context": {"from": "5511888888888", "id": "wamid.quoted", "forwarded": True},
+        }
+
+        context = MessageContext.from_webhook(message)
+
+        assert context.is_reply
+        assert context.quoted_message_id == "wamid.quoted"
+        assert context.forwarded is True
+        assert context.frequently_forwarded is False
+
+    def test_message_without_context(self):
+        """Test messages without context return None"""
+        assert MessageContext.from_webhook({"type": "text", "text": {"body": "Oi"}}) is None
+
+
+class TestReactionsAndEdits:
+    """Tests for reaction and edit parsing"""
+
+    def test_parse_reaction(self):
+        """Test the reacted message and emoji are parsed"""
+        message = {
+            "from": "5511999999999",
+            "id": "wamid.reaction",
+            "timestamp": "1700000000",
+            "type": "reaction",
+            "reaction": {"message_id": "wamid.target", "emoji": "❤️"},
+        }
+
+        reaction = MessageReaction.from_webhook(message)
+
+        assert classify_message(message) == WebhookEventType.REACTION
+        assert reaction.message_id == "wamid.target"
+        assert reaction.emoji == "❤️"
+        assert reaction.sender == "5511999999999"
+        assert reaction.is_removal is False
+
+    def test_empty_emoji_removes_reaction(self):
+        """Test an empty emoji is a removal"""
+        reaction = MessageReaction.from_webhook({
+            "from": "5511999999999",
+            "type": "reaction",
+            "reaction": {"message_id": "wamid.target", "emoji": ""},
+        })
+
+        assert reaction.is_removal is True
+        assert reaction.emoji is None
+
+    def test_parse_text_edit(self):
+        """Test edited text is stored like a new text message"""
+        message = {
+            "from": "5511999999999",
+            "id": "wamid.edit",
+            "type": "edit",
+            "edit": {
+                "original_message_id": "wamid.original",
+                "message": {"type": "text", "text": {"body": "Quero 2 pizzas"}},
+            },
+        }
+
+        edit = MessageEdit.from_webhook(message)
+
+        assert classify_message(message) == WebhookEventType.EDIT
+        assert edit.original_message_id == "wamid.original"
+        assert edit.content == {"text": "Quero 2 pizzas"}
+
+    def test_request_welcome(self):
+        """Test first-open welcome requests are classified"""
+        message = {"from": "5511999999999", "id": "wamid.w", "type": "request_welcome"}
+
+        assert classify_message(message) == WebhookEventType.REQUEST_WELCOME
+
+
+class TestTemplateStatusUpdate:
+    """Tests for template status webhook parsing"""
+
+    def test_parse_rejection(self):
+        """Test id, name and rejection reason are parsed"""
+        update = TemplateStatusUpdate.from_webhook({
+            "event": "REJECTED",
+            "message_template_id": 123456789,
+            "message_template_name": "order_confirmation",
+            "message_template_language": "pt_BR",
+            "reason": "INVALID_FORMAT",
+        })
+
+        assert update.event == "REJECTED"
+        assert update.template_id == "123456789"
+        assert update.language == "pt_BR"
+        assert update.rejected_reason == "INVALID_FORMAT"
+
+    def test_none_reason(self):
+        """Test the literal NONE reason is treated as no reason"""
+        update = TemplateStatusUpdate.from_webhook({"event": "approved", "reason": "NONE"})
+
+        assert update.event == "APPROVED"
+        assert update.rejected_reason is None
+
+
+class TestPhoneNumberQualityUpdate:
+    """Tests for messaging limit tier webhooks"""
+
+    def test_parse_upgrade(self):
+        """Test the new tier is parsed and the number normalized"""
+        payload = {
+            "entry": [{
+                "id": "waba-1",
+                "changes": [{
+                    "field": "phone_number_quality_update",
+                    "value": {
+                        "display_phone_number": "+55 11 99999-9999",
+                        "event": "upgrade",
+                        "current_limit": "TIER_10K",
+                        "old_limit": "TIER_2K",
+                    },
+                }],
+            }],
+        }
+
+        event = next(iter_events(payload))
+
+        assert event.type == WebhookEventType.PHONE_NUMBER_QUALITY
+        assert event.inbound is None
+        assert event.quality_update.event == "UPGRADE"
+        assert event.quality_update.current_limit == "TIER_10K"
+        assert event.quality_update.digits == "5511999999999"
+
+
+class TestWebhookChallenge:
+    """Tests for WebhookChallenge verification with multiple tokens"""
+
+    def test_from_query(self):
+        challenge = WebhookChallenge.from_query({
+            "hub.mode": "subscribe", "hub.verify_token": "token-b", "hub.challenge": "123",
+        })
+
+        assert challenge.is_complete
+        assert challenge.is_subscribe
+        assert challenge.challenge == "123"
+
+    def test_match_resolves_tenant(self):
+        """Test the tenant owning the token is returned"""
+        challenge = WebhookChallenge(mode="subscribe", verify_token="token-b", challenge="123")
+
+        assert challenge.match({"acme": "token-a", "globex": "token-b", "empty": None}) == "globex"
+        assert challenge.match({"acme": "token-a"}) is None
+
+    def test_missing_token_never_matches(self):
+        """Test numbers without a token do not accept empty tokens"""
+        challenge = WebhookChallenge(mode="subscribe", verify_token=None, challenge="123")
+
+        assert not challenge.is_complete
+        assert challenge.match({"acme": None, "globex": ""}) is None
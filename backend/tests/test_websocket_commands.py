@@ -0,0 +1,301 @@
+"""
+WebSocket Command Channel Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import json
+from contextlib import asynccontextmanager
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core import database
+from app.core.exceptions import NotFoundException
+from app.core.security import create_access_token
+from app.models.conversation import Conversation
+from app.models.user import UserWorkspace
+from app.schemas.websocket import WSCommandType
+from app.services.auth_service import AuthService
+from app.websocket import commands, manager
+from app.websocket.commands import CommandContext, dispatch_command, resolve_context
+from tests.conftest import OrganizationFactory, UserFactory
+
+
+def _ctx(organization_id=None) -> CommandContext:
+    return CommandContext(sid="sid-1", user_id=uuid4(), organization_id=organization_id or uuid4())
+
+
+class TestDispatchCommand:
+    """Tests for dispatch_command"""
+
+    @pytest.mark.asyncio
+    async def test_invalid_envelope(self):
+        """Test unknown commands are rejected with the client id echoed"""
+        ack = await dispatch_command(_ctx(), {"id": "tmp-1", "command": "delete_everything"})
+
+        assert ack["ok"] is False
+        assert ack["id"] == "tmp-1"
+        assert ack["error"]["code"] == 422
+
+    @pytest.mark.asyncio
+    async def test_success_ack(self, monkeypatch):
+        """Test handler result is returned in the ack"""
+        async def fake_typing(ctx, payload, command_id):
+            return {"typing": True}
+
+        monkeypatch.setitem(commands.HANDLERS, WSCommandType.TYPING, fake_typing)
+
+        ack = await dispatch_command(_ctx(), {
+            "id": "tmp-2",
+            "command": "typing",
+            "payload": {"conversation_id": str(uuid4())},
+        })
+
+        assert ack == {
+            "id": "tmp-2",
+            "command": "typing",
+            "ok": True,
+            "data": {"typing": True},
+            "error": None,
+        }
+
+    @pytest.mark.asyncio
+    async def test_http_errors_are_mapped(self, monkeypatch):
+        """Test service exceptions become error acks"""
+        async def fake_mark_read(ctx, payload, command_id):
+            raise NotFoundException("Conversation not found")
+
+        monkeypatch.setitem(commands.HANDLERS, WSCommandType.MARK_READ, fake_mark_read)
+
+        ack = await dispatch_command(_ctx(), {"id": "tmp-3", "command": "mark_read"})
+
+        assert ack["ok"] is False
+        assert ack["error"] == {"code": 404, "message": "Conversation not found"}
+
+    @pytest.mark.asyncio
+    async def test_retried_send_returns_cached_ack(self, monkeypatch):
+        """Test a retried send_message does not send twice"""
+        cached = {"id": "tmp-4", "command": "send_message", "ok": True, "data": {"id": "m1"}, "error": None}
+        calls = []
+
+        async def fake_set(key, value, expire=None, nx=False):
+            return False
+
+        async def fake_get(key):
+            return json.dumps(cached)
+
+        async def fake_send(ctx, payload, command_id):
+            calls.append(command_id)
+            return {}
+
+        monkeypatch.setattr(commands.redis_client, "set", fake_set)
+        monkeypatch.setattr(commands.redis_client, "get", fake_get)
+        monkeypatch.setitem(commands.HANDLERS, WSCommandType.SEND_MESSAGE, fake_send)
+
+        ack = await dispatch_command(_ctx(), {"id": "tmp-4", "command": "send_message", "payload": {}})
+
+        assert ack == cached
+        assert calls == []
+
+
+@pytest_asyncio.fixture
+async def db(db_session: AsyncSession, monkeypatch):
+    """Test database used by the command handlers and the connection authentication"""
+
+    @asynccontextmanager
+    async def session():
+        yield db_session
+
+    monkeypatch.setattr(commands, "async_session", session)
+    monkeypatch.setattr(database, "async_session", session)
+    return db_session
+
+
+@pytest_asyncio.fixture
+async def conversation(db) -> Conversation:
+    """Conversation of the caller's organization"""
+    org = await OrganizationFactory.create_in_db(db)
+    conversation = Conversation(
+        id=uuid4(),
+        organization_id=org.id,
+        contact_id=uuid4(),
+        whatsapp_number_id=uuid4(),
+        status="open",
+    )
+    db.add(conversation)
+    await db.commit()
+    return conversation
+
+
+@pytest.fixture
+def emitted(monkeypatch):
+    """Typing events emitted"""
+    emitted = []
+
+    async def fake_emit(event, data, room=None, skip_sid=None):
+        emitted.append((event, room))
+
+    monkeypatch.setattr(commands.sio, "emit", fake_emit)
+    return emitted
+
+
+class TestTypingCommand:
+    """Tests for the typing command"""
+
+    @pytest.mark.asyncio
+    async def test_typing_is_broadcast(self, conversation, emitted):
+        """Test typing in an organization conversation reaches its room"""
+        ack = await dispatch_command(_ctx(conversation.organization_id), {
+            "id": "tmp-5",
+            "command": "typing",
+            "payload": {"conversation_id": str(conversation.id)},
+        })
+
+        assert ack["ok"] is True
+        assert emitted == [("user_typing", f"conversation:{conversation.id}")]
+
+    @pytest.mark.asyncio
+    async def test_other_conversation_is_rejected(self, conversation, emitted):
+        """Test typing in a conversation outside the organization is not broadcast"""
+        ack = await dispatch_command(_ctx(), {
+            "id": "tmp-6",
+            "command": "typing",
+            "payload": {"conversation_id": str(conversation.id)},
+        })
+
+        assert ack["ok"] is False
+        assert ack["error"]["code"] == 404
+        assert emitted == []
+
+
+class FakeSocketSession:
+    """sio.session() of a connection authenticated with token"""
+
+    def __init__(self, token):
+        self.token = token
+
+    def __call__(self, sid):
+        return self
+
+    async def __aenter__(self):
+        return {"token": self.token} if self.token else {}
+
+    async def __aexit__(self, *exc):
+        return False
+
+
+async def _user_token(db, role="agent"):
+    """User of a new organization and an access token of its home workspace"""
+    org = await OrganizationFactory.create_in_db(db)
+    user = await UserFactory.create_in_db(db, organization_id=org.id, role=role)
+    return user, create_access_token(subject=str(user.id))
+
+
+async def _guest_token(db):
+    """Agency user with a token scoped to a client workspace, and the membership"""
+    user, _ = await _user_token(db, role="org_admin")
+    client = await OrganizationFactory.create_in_db(db)
+    workspace = UserWorkspace(
+        id=uuid4(), user_id=user.id, organization_id=client.id, role="agent", department_ids=[]
+    )
+    db.add(workspace)
+    await db.commit()
+    token = await AuthService(db).switch_workspace(user, client.id)
+    return client, workspace, token.access_token
+
+
+class TestCommandAuthorization:
+    """Tests for resolving the caller of every command"""
+
+    @pytest.mark.asyncio
+    async def test_context_comes_from_the_resolved_user(self, db):
+        """Test the context carries the organization and role of the resolved workspace"""
+        user, _ = await _user_token(db)
+        client, _, token = await _guest_token(db)
+
+        ctx = await resolve_context("sid-1", token)
+
+        assert (ctx.organization_id, ctx.role) == (client.id, "agent")
+        home = await resolve_context("sid-1", create_access_token(subject=str(user.id)))
+        assert (home.user_id, home.organization_id, home.role) == (user.id, user.organization_id, "agent")
+
+    @pytest.mark.asyncio
+    async def test_viewer_cannot_send(self, db, monkeypatch):
+        """Test a role without conversation access gets a 403 ack and nothing runs"""
+        _, token = await _user_token(db, role="viewer")
+        calls = []
+
+        async def fake_send(ctx, payload, command_id):
+            calls.append(command_id)
+            return {}
+
+        monkeypatch.setattr(commands.sio, "session", FakeSocketSession(token))
+        monkeypatch.setitem(commands.HANDLERS, WSCommandType.SEND_MESSAGE, fake_send)
+
+        ack = await commands.command("sid-1", {"id": "tmp-7", "command": "send_message", "payload": {}})
+
+        assert ack["ok"] is False
+        assert ack["error"]["code"] == 403
+        assert calls == []
+
+    @pytest.mark.asyncio
+    async def test_revoked_token_is_rejected(self, db, monkeypatch):
+        """Test a token no longer valid for its user or workspace gets a 401 ack and the connection is closed"""
+        _, workspace, token = await _guest_token(db)
+        workspace.is_active = False
+        await db.commit()
+        closed = []
+
+        async def fake_disconnect(sid):
+            closed.append(sid)
+
+        monkeypatch.setattr(commands.sio, "session", FakeSocketSession(token))
+        monkeypatch.setattr(commands.sio, "disconnect", fake_disconnect)
+
+        ack = await commands.command("sid-1", {"id": "tmp-8", "command": "mark_read", "payload": {}})
+
+        assert ack["ok"] is False
+        assert ack["error"] == {"code": 401, "message": "Could not validate credentials"}
+        assert closed == ["sid-1"]
+
+
+@pytest.fixture
+def rooms(monkeypatch):
+    """Rooms joined by connections"""
+    rooms = []
+
+    async def fake_enter_room(sid, room):
+        rooms.append(room)
+
+    async def fake_emit(event, data, room=None, skip_sid=None):
+        pass
+
+    monkeypatch.setattr(manager.sio, "session", FakeSocketSession(None))
+    monkeypatch.setattr(manager.sio, "enter_room", fake_enter_room)
+    monkeypatch.setattr(manager.sio, "emit", fake_emit)
+    return rooms
+
+
+class TestSocketConnect:
+    """Tests for authenticating Socket.IO connections"""
+
+    @pytest.mark.asyncio
+    async def test_guest_joins_the_resolved_workspace(self, db, rooms):
+        """Test a guest token joins the room of the workspace resolved for the user"""
+        client, _, token = await _guest_token(db)
+
+        assert await manager.connect("sid-1", {}, {"token": token}) is True
+        assert rooms == [f"organization:{client.id}"]
+
+    @pytest.mark.asyncio
+    async def test_removed_guest_is_rejected(self, db, rooms):
+        """Test a token of a workspace the user was removed from cannot connect"""
+        _, workspace, token = await _guest_token(db)
+        workspace.is_active = False
+        await db.commit()
+
+        assert await manager.connect("sid-1", {}, {"token": token}) is False
+        assert rooms == []
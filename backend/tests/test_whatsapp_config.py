@@ -0,0 +1,85 @@
+"""
+WhatsApp Egress Config Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import httpx
+import pytest
+
+from app.core import fault_injection
+from app.core.fault_injection import FaultInjectionTransport, FaultInjector, FaultRule, http_client
+from app.core.whatsapp_config import WhatsAppConfig
+
+
+class OkTransport(httpx.AsyncBaseTransport):
+    """Upstream answering 200"""
+
+    def __init__(self):
+        self.calls = 0
+
+    async def handle_async_request(self, request):
+        self.calls += 1
+        return httpx.Response(200, json={"ok": True}, request=request)
+
+
+class ProxiedConfig(WhatsAppConfig):
+    """Config whose transport stands in for the proxy"""
+
+    upstream = OkTransport()
+
+    def transport(self):
+        return self.upstream
+
+
+class TestWhatsAppConfig:
+    """Tests for proxy, TLS and timeout settings"""
+
+    def test_timeout_defaults_and_connect_cap(self):
+        """Test the call timeout defaults to timeout_seconds and connecting is capped"""
+        config = WhatsAppConfig(timeout_seconds=30.0, connect_timeout_seconds=10.0)
+
+        assert config.timeout() == httpx.Timeout(30.0, connect=10.0)
+        assert config.timeout(5.0) == httpx.Timeout(5.0, connect=5.0)
+
+    def test_system_cas_without_bundle(self):
+        """Test TLS uses the system CAs unless a bundle is set"""
+        assert WhatsAppConfig().verify is True
+
+    @pytest.mark.asyncio
+    async def test_whatsapp_targets_use_config(self, monkeypatch):
+        """Test Meta and Evolution clients go through the configured transport"""
+        config = ProxiedConfig(proxy_url="http://proxy.corp:3128", timeout_seconds=12.0)
+        monkeypatch.setattr(fault_injection, "whatsapp_config", config)
+        monkeypatch.setattr(fault_injection, "fault_injector", FaultInjector({}, enabled=False))
+
+        async with http_client("meta_api") as client:
+            response = await client.get("https://graph.facebook.com/v18.0/123")
+
+        assert response.status_code == 200
+        assert config.upstream.calls == 1
+        assert client.timeout == httpx.Timeout(12.0, connect=10.0)
+
+    def test_faults_wrap_the_proxied_transport(self, monkeypatch):
+        """Test injected faults still reach Meta through the proxy"""
+        config = ProxiedConfig(proxy_url="socks5://proxy.corp:1080")
+        monkeypatch.setattr(fault_injection, "whatsapp_config", config)
+        monkeypatch.setattr(
+            fault_injection, "fault_injector", FaultInjector({"evolution_api": FaultRule(error_rate=0.5)}, enabled=True)
+        )
+
+        client = http_client("evolution_api", timeout=10.0)
+
+        faults = client._transport.wrapped  # inside the tracing span
+        assert isinstance(faults, FaultInjectionTransport)
+        assert faults.wrapped is config.upstream
+
+    def test_other_targets_connect_directly(self, monkeypatch):
+        """Test flow API calls ignore the WhatsApp proxy and timeouts"""
+        monkeypatch.setattr(fault_injection, "whatsapp_config", ProxiedConfig(timeout_seconds=99.0))
+        monkeypatch.setattr(fault_injection, "fault_injector", FaultInjector({}, enabled=False))
+
+        client = http_client("http_connector", timeout=5.0)
+
+        assert client.timeout == httpx.Timeout(5.0)
+        assert not isinstance(client._transport, OkTransport)
@@ -0,0 +1,90 @@
+"""
+WhatsApp Client Metrics Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+import httpx
+import pytest
+
+from app.core.whatsapp_metrics import (
+    InMemoryMetricsSink,
+    MetricsSink,
+    MetricsTransport,
+    endpoint_label,
+)
+
+GRAPH = "https://graph.facebook.com/v21.0"
+
+
+def _graph(request):
+    if request.url.path.endswith("/fail"):
+        raise httpx.ConnectError("connection refused", request=request)
+    status_code = 400 if request.method == "DELETE" else 200
+    return httpx.Response(status_code, json={}, headers={
+        "X-App-Usage": '{"call_count": 4, "total_time": 2, "total_cputime": 1}',
+        "X-Business-Use-Case-Usage": "not-json",
+    })
+
+
+def _client(sink, phone_number_id="123"):
+    transport = MetricsTransport("meta_api", sink, phone_number_id, wrapped=httpx.MockTransport(_graph))
+    return httpx.AsyncClient(transport=transport)
+
+
+class TestMetricsTransport:
+    """Tests for per-number request metrics"""
+
+    @pytest.mark.asyncio
+    async def test_requests_are_aggregated_per_number(self):
+        """Test counts, status codes, endpoints and usage headers"""
+        sink = InMemoryMetricsSink()
+
+        async with _client(sink) as client:
+            await client.post(f"{GRAPH}/123/messages", json={})
+            await client.post(f"{GRAPH}/123/messages", json={})
+            await client.delete(f"{GRAPH}/waba/message_templates")
+        async with _client(sink, "456") as client:
+            await client.get(f"{GRAPH}/456")
+
+        stats = sink.snapshot()
+        assert stats["123"]["requests"] == 3
+        assert stats["123"]["errors"] == 1
+        assert stats["123"]["status_codes"] == {"200": 2, "400": 1}
+        assert stats["123"]["endpoints"] == {"POST messages": 2, "DELETE message_templates": 1}
+        assert stats["123"]["rate_limit"] == {
+            "x-business-use-case-usage": "not-json",
+            "x-app-usage": {"call_count": 4, "total_time": 2, "total_cputime": 1},
+        }
+        assert stats["123"]["latency_ms_max"] >= stats["123"]["latency_ms_avg"] >= 0
+        assert stats["456"]["endpoints"] == {"GET node": 1}
+
+    @pytest.mark.asyncio
+    async def test_network_errors_are_recorded(self):
+        """Test failed connections count as errors and still raise"""
+        sink = InMemoryMetricsSink()
+
+        async with _client(sink) as client:
+            with pytest.raises(httpx.ConnectError):
+                await client.get(f"{GRAPH}/123/fail")
+
+        assert sink.snapshot()["123"]["status_codes"] == {"network_error": 1}
+        assert sink.snapshot()["123"]["errors"] == 1
+
+    @pytest.mark.asyncio
+    async def test_sink_failures_do_not_fail_calls(self):
+        """Test a broken sink never breaks sending"""
+        class BrokenSink(MetricsSink):
+            def record(self, metric):
+                raise RuntimeError("statsd down")
+
+        async with _client(BrokenSink()) as client:
+            response = await client.post(f"{GRAPH}/123/messages", json={})
+
+        assert response.status_code == 200
+
+    def test_endpoint_label(self):
+        """Test node ids and versions are dropped from labels"""
+        assert endpoint_label("/v21.0/123/messages") == "messages"
+        assert endpoint_label("/v21.0/123/whatsapp_business_profile") == "whatsapp_business_profile"
+        assert endpoint_label("/v21.0/987654") == "node"
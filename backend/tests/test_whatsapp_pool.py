@@ -0,0 +1,111 @@
+"""
+WhatsApp Client Pool Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from types import SimpleNamespace
+
+import pytest
+
+from app.core.whatsapp_config import SharedTransport, WhatsAppConfig
+from app.integrations.whatsapp_pool import SenderPolicy, WhatsAppClientPool
+
+
+def _number(phone_number_id, token="token", connection_type="official", is_active=True):
+    return SimpleNamespace(
+        phone_number_id=phone_number_id,
+        access_token=token,
+        connection_type=connection_type,
+        is_active=is_active,
+    )
+
+
+class TestClientPool:
+    """Tests for client reuse"""
+
+    def test_clients_are_reused_per_number(self):
+        """Test one client per number, recreated when the token rotates"""
+        pool = WhatsAppClientPool()
+        number = _number("111")
+
+        first = pool.client(number)
+        assert pool.client(number) is first
+
+        number.access_token = "rotated"
+        rotated = pool.client(number)
+        assert rotated is not first
+        assert rotated.access_token == "rotated"
+
+        pool.forget("111")
+        assert pool.client(number) is not rotated
+
+    @pytest.mark.asyncio
+    async def test_connection_pool_is_shared_per_loop(self):
+        """Test WhatsApp clients reuse one transport that survives client close"""
+        config = WhatsAppConfig()
+
+        transport = config.transport()
+
+        assert isinstance(transport, SharedTransport)
+        assert config.transport() is transport
+        await transport.aclose()
+        assert config.transport() is transport
+
+
+class TestSenderPolicies:
+    """Tests for sender selection"""
+
+    def test_only_active_official_numbers(self):
+        """Test QR code, inactive and tokenless numbers never send"""
+        pool = WhatsAppClientPool()
+        numbers = [
+            _number("111", connection_type="qrcode"),
+            _number("222", is_active=False),
+            _number("333", token=None),
+        ]
+
+        assert pool.pick(numbers) is None
+        assert pool.pick(numbers + [_number("444")]).phone_number_id == "444"
+
+    def test_sticky_per_contact(self):
+        """Test a contact keeps its number and removing another number doesn't move it"""
+        pool = WhatsAppClientPool()
+        numbers = [_number("111"), _number("222"), _number("333")]
+        contacts = [f"55119999900{i:02d}" for i in range(30)]
+
+        chosen = {c: pool.pick(numbers, SenderPolicy.STICKY, c).phone_number_id for c in contacts}
+        assert chosen == {c: pool.pick(list(reversed(numbers)), SenderPolicy.STICKY, c).phone_number_id for c in contacts}
+        assert len(set(chosen.values())) > 1
+
+        remaining = [n for n in numbers if n.phone_number_id != "333"]
+        for contact, phone_number_id in chosen.items():
+            if phone_number_id != "333":
+                assert pool.pick(remaining, SenderPolicy.STICKY, contact).phone_number_id == phone_number_id
+
+    def test_round_robin(self):
+        """Test numbers are used in turn"""
+        pool = WhatsAppClientPool()
+        numbers = [_number("222"), _number("111")]
+
+        picks = [pool.pick(numbers, SenderPolicy.ROUND_ROBIN).phone_number_id for _ in range(4)]
+
+        assert picks == ["111", "222", "111", "222"]
+
+    @pytest.mark.asyncio
+    async def test_least_loaded(self):
+        """Test the number with fewer sends in flight is chosen"""
+        pool = WhatsAppClientPool()
+        numbers = [_number("111"), _number("222")]
+
+        async with pool.sender(numbers, SenderPolicy.LEAST_LOADED) as (busy, api):
+            assert api.phone_number_id == busy.phone_number_id
+            assert pool.in_flight(busy.phone_number_id) == 1
+            other = pool.pick(numbers, SenderPolicy.LEAST_LOADED)
+            assert other.phone_number_id != busy.phone_number_id
+
+        assert pool.in_flight(busy.phone_number_id) == 0
+
+        with pytest.raises(ValueError):
+            async with pool.sender([], SenderPolicy.LEAST_LOADED):
+                pass
@@ -0,0 +1,164 @@
+"""
+WhatsApp Token Unit Tests
+
+Autor: Kayo Carvalho Fernandes
+"""
+
+from datetime import datetime, timedelta, timezone
+from uuid import uuid4
+
+import pytest
+import pytest_asyncio
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+import app.websocket.manager as ws_manager
+from app.core.exceptions import BadRequestException, NotFoundException
+from app.integrations.meta_api import MetaAPIError, MetaCloudAPI
+from app.models.whatsapp_number import WhatsAppNumber
+from app.services import whatsapp_token_service
+from app.services.whatsapp_token_service import (
+    TOKEN_EXPIRED,
+    TOKEN_EXPIRING,
+    TOKEN_INVALID,
+    TOKEN_VALID,
+    WhatsAppTokenService,
+    on_token_expiring,
+    remove_token_listener,
+    token_status,
+)
+from tests.conftest import OrganizationFactory
+
+NOW = datetime(2025, 11, 12, 9, 0, tzinfo=timezone.utc)
+
+
+@pytest_asyncio.fixture
+async def number(db_session: AsyncSession) -> WhatsAppNumber:
+    """Official number of an organization, using the "current" access token"""
+    org = await OrganizationFactory.create_in_db(db_session)
+    number = WhatsAppNumber(
+        id=uuid4(),
+        organization_id=org.id,
+        phone_number="+5511999999999",
+        phone_number_id="123",
+        connection_type="official",
+        is_active=True,
+        access_token="current",
+    )
+    db_session.add(number)
+    await db_session.commit()
+    return number
+
+
+async def _stored_token(db, number):
+    return await db.scalar(select(WhatsAppNumber.access_token).where(WhatsAppNumber.id == number.id))
+
+
+@pytest.fixture
+def tokens(monkeypatch):
+    """debug_token answers by access token; revoked tokens raise code 190"""
+    answers = {}
+
+    async def debug_token(self):
+        answer = answers[self.access_token]
+        if isinstance(answer, MetaAPIError):
+            raise answer
+        return {"scopes": ["whatsapp_business_messaging"], "error": None, **answer}
+
+    monkeypatch.setattr(MetaCloudAPI, "debug_token", debug_token)
+    return answers
+
+
+@pytest.fixture
+def events(monkeypatch):
+    emitted = []
+
+    async def emit_to_organization(organization_id, event, data):
+        emitted.append((event, data))
+
+    monkeypatch.setattr(ws_manager, "emit_to_organization", emit_to_organization)
+    return emitted
+
+
+class TestTokenStatus:
+    """Tests for token_status()"""
+
+    def test_statuses(self):
+        """Test expiry window, expired and revoked tokens"""
+        assert token_status(True, None, NOW, 7) == TOKEN_VALID
+        assert token_status(True, NOW + timedelta(days=30), NOW, 7) == TOKEN_VALID
+        assert token_status(True, NOW + timedelta(days=7), NOW, 7) == TOKEN_EXPIRING
+        assert token_status(False, NOW - timedelta(seconds=1), NOW, 7) == TOKEN_EXPIRED
+        assert token_status(False, None, NOW, 7) == TOKEN_INVALID
+
+
+class TestCheckNumber:
+    """Tests for expiry checks and notifications"""
+
+    @pytest.mark.asyncio
+    async def test_expiring_token_is_notified_once(self, db_session: AsyncSession, number, tokens, events):
+        """Test TokenExpiring reaches listeners and the organization only once per token"""
+        received = []
+
+        @on_token_expiring
+        async def listener(event):
+            received.append(event)
+
+        expires_at = datetime.now(timezone.utc) + timedelta(days=3, hours=1)
+        tokens["current"] = {"is_valid": True, "expires_at": expires_at}
+        service = WhatsAppTokenService(db_session)
+
+        try:
+            first = await service.check_number(number)
+            await service.check_number(number)
+        finally:
+            remove_token_listener(listener)
+
+        assert first["status"] == TOKEN_EXPIRING
+        assert first["days_left"] == 3
+        assert number.token_expires_at == expires_at
+        assert [event.status for event in received] == [TOKEN_EXPIRING]
+        assert events == [("whatsapp:token_expiring", received[0].to_dict())]
+
+    @pytest.mark.asyncio
+    async def test_revoked_token_is_reported_invalid(self, db_session: AsyncSession, number, tokens, events):
+        """Test Graph error 190 marks the token invalid instead of failing the check"""
+        tokens["current"] = MetaAPIError("Session has expired", error_code="190", status_code=401)
+
+        result = await WhatsAppTokenService(db_session).check_number(number)
+
+        assert result["status"] == TOKEN_INVALID
+        assert result["error"] == "Session has expired"
+        assert await db_session.scalar(
+            select(WhatsAppNumber.token_status).where(WhatsAppNumber.id == number.id)
+        ) == TOKEN_INVALID
+        assert events[0][1]["reason"] == "Session has expired"
+
+
+class TestRotateToken:
+    """Tests for token rotation"""
+
+    @pytest.mark.asyncio
+    async def test_rotation_validates_the_new_token(self, db_session: AsyncSession, number, tokens, events):
+        """Test an invalid token is rejected and a valid one replaces the current token"""
+        number.token_expiry_notified_at = NOW
+        await db_session.commit()
+        tokens["bad"] = {"is_valid": False, "expires_at": None, "error": "Malformed access token"}
+        tokens["system-user"] = {"is_valid": True, "expires_at": None}
+        service = WhatsAppTokenService(db_session)
+
+        with pytest.raises(BadRequestException):
+            await service.rotate_token(number.id, number.organization_id, "bad")
+        assert await _stored_token(db_session, number) == "current"
+
+        with pytest.raises(NotFoundException):
+            await service.rotate_token(number.id, uuid4(), "system-user")
+
+        result = await service.rotate_token(number.id, number.organization_id, " system-user ")
+
+        assert result["status"] == TOKEN_VALID
+        assert result["expires_at"] is None
+        assert await _stored_token(db_session, number) == "system-user"
+        assert number.token_expiry_notified_at is None
+        assert whatsapp_token_service.whatsapp_clients.client(number).access_token == "system-user"
+        assert events == []